@@ -0,0 +1,128 @@
+// Admin dashboard: polls the status report with the admin token, which is
+// kept for this browser tab only.
+
+const STATUS_URL = "/api/v2/admin/status";
+const REFRESH_MS = 15000;
+const TOKEN_KEY = "valtix-admin-token";
+
+let timer = null;
+
+function $(id) {
+  return document.getElementById(id);
+}
+
+function cell(text, className) {
+  const td = document.createElement("td");
+  td.textContent = text ?? "";
+  if (className) td.className = className;
+  return td;
+}
+
+function fillList(dl, entries) {
+  dl.replaceChildren();
+  for (const [label, value, className] of entries) {
+    const dt = document.createElement("dt");
+    dt.textContent = label;
+    const dd = document.createElement("dd");
+    dd.textContent = String(value);
+    if (className) dd.className = className;
+    dl.append(dt, dd);
+  }
+}
+
+function fillTable(tbody, rows) {
+  tbody.replaceChildren();
+  for (const cells of rows) {
+    const tr = document.createElement("tr");
+    tr.append(...cells);
+    tbody.append(tr);
+  }
+}
+
+function render(report) {
+  const health = report.health;
+  fillList($("health"), [
+    ["Status", health.status, health.status],
+    ["Version", health.version],
+    ["Schema version", health.schema_version],
+    ["Safe mode", health.safe_mode ? "on" : "off"],
+    ["Maintenance", health.maintenance ? "on" : "off"],
+  ]);
+
+  const queues = report.queues;
+  fillList($("queues"), [
+    ["Pending confirmations", queues.pending_confirmations],
+    ["Stuck transactions", queues.stuck_transactions, queues.stuck_transactions ? "warning" : ""],
+    ["Scheduled transactions", queues.scheduled_transactions],
+    ["SIEM outbox", queues.siem_outbox],
+    ["Open bridge transfers", queues.open_bridge_transfers],
+    ["Open cross-chain swaps", queues.open_cross_chain_swaps],
+  ]);
+
+  fillTable($("rpc"), report.rpc.providers.map((p) => [
+    cell(p.chain),
+    cell(p.provider),
+    cell(p.calls),
+    cell(p.failures),
+    cell(`${(p.failure_rate * 100).toFixed(1)}%`, p.failure_rate > 0.05 ? "warning" : ""),
+  ]));
+
+  fillTable($("audit"), report.recent_audit_events.map((e) => [
+    cell(new Date(e.created_at).toLocaleString()),
+    cell(e.tenant_id),
+    cell(e.event),
+    cell(e.severity, e.severity),
+    cell(e.user_id),
+  ]));
+
+  $("updated").textContent = `Updated ${new Date(report.generated_at).toLocaleTimeString()}`;
+}
+
+function showSignIn(message) {
+  clearTimeout(timer);
+  $("dashboard").hidden = true;
+  $("sign-out").hidden = true;
+  $("sign-in").hidden = false;
+  $("error").hidden = !message;
+  $("error").textContent = message || "";
+}
+
+async function refresh() {
+  const token = sessionStorage.getItem(TOKEN_KEY);
+  if (!token) return showSignIn();
+
+  try {
+    const response = await fetch(STATUS_URL, {
+      headers: { Authorization: `Bearer ${token}` },
+      cache: "no-store",
+    });
+    if (response.status === 401) {
+      sessionStorage.removeItem(TOKEN_KEY);
+      return showSignIn("The admin token was rejected.");
+    }
+    if (!response.ok) throw new Error(`${response.status} ${await response.text()}`);
+
+    render(await response.json());
+    $("sign-in").hidden = true;
+    $("sign-out").hidden = false;
+    $("dashboard").hidden = false;
+  } catch (e) {
+    $("updated").textContent = `Refresh failed: ${e.message}`;
+  }
+  clearTimeout(timer);
+  timer = setTimeout(refresh, REFRESH_MS);
+}
+
+document.addEventListener("DOMContentLoaded", () => {
+  $("sign-in").addEventListener("submit", (event) => {
+    event.preventDefault();
+    sessionStorage.setItem(TOKEN_KEY, $("token").value);
+    $("token").value = "";
+    refresh();
+  });
+  $("sign-out").addEventListener("click", () => {
+    sessionStorage.removeItem(TOKEN_KEY);
+    showSignIn();
+  });
+  refresh();
+});
@@ -0,0 +1,18 @@
+//! Build script: compiles the gRPC proto definitions when the `grpc` feature is enabled
+
+fn main() {
+    println!("cargo:rerun-if-changed=proto/wallet.proto");
+
+    #[cfg(feature = "grpc")]
+    {
+        // Use a bundled protoc so builds don't depend on a system install
+        std::env::set_var(
+            "PROTOC",
+            protoc_bin_vendored::protoc_bin_path().expect("bundled protoc unavailable"),
+        );
+        tonic_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/wallet.proto"], &["proto"])
+            .expect("failed to compile gRPC protos");
+    }
+}
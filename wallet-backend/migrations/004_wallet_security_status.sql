@@ -0,0 +1,21 @@
+-- Wallet health / backup status tracking
+
+-- Mnemonic entropy encrypted with the wallet password, used to verify backup quizzes
+ALTER TABLE wallets ADD COLUMN encrypted_entropy BLOB;
+ALTER TABLE wallets ADD COLUMN mnemonic_word_count INTEGER;
+ALTER TABLE wallets ADD COLUMN backup_confirmed_at TEXT;
+
+-- Login security settings
+ALTER TABLE users ADD COLUMN two_factor_enabled INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN password_changed_at TEXT;
+
+-- Outstanding backup quizzes (word positions the user must answer)
+CREATE TABLE IF NOT EXISTS backup_challenges (
+    id TEXT PRIMARY KEY,
+    wallet_id TEXT NOT NULL REFERENCES wallets(id) ON DELETE CASCADE,
+    positions TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_backup_challenges_wallet ON backup_challenges(wallet_id);
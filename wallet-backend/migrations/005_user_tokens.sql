@@ -0,0 +1,17 @@
+-- Custom tokens tracked per user
+
+-- Tokens a user added by address, or hid from balance results
+CREATE TABLE IF NOT EXISTS user_tokens (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL CHECK (chain IN ('solana', 'ethereum')),
+    token_address TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    name TEXT,
+    decimals INTEGER NOT NULL,
+    hidden INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(user_id, chain, token_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_tokens_user_chain ON user_tokens(user_id, chain);
@@ -0,0 +1,6 @@
+-- Fiat-denominated sends
+
+-- Conversion applied when a send amount was entered in fiat
+ALTER TABLE transaction_history ADD COLUMN fiat_amount TEXT;
+ALTER TABLE transaction_history ADD COLUMN fiat_currency TEXT;
+ALTER TABLE transaction_history ADD COLUMN fiat_rate TEXT;
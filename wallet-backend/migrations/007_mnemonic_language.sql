@@ -0,0 +1,5 @@
+-- Multi-language mnemonics
+
+-- Wordlist the wallet's mnemonic was written in, so backup quizzes can
+-- rebuild the phrase from the stored entropy
+ALTER TABLE wallets ADD COLUMN mnemonic_language TEXT NOT NULL DEFAULT 'english';
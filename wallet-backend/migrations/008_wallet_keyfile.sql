@@ -0,0 +1,5 @@
+-- Keyfile second factor
+
+-- Set when the seed key is derived from the password plus a keyfile hash;
+-- the keyfile itself is never stored
+ALTER TABLE wallets ADD COLUMN keyfile_required INTEGER NOT NULL DEFAULT 0;
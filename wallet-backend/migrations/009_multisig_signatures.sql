@@ -0,0 +1,17 @@
+-- Offline multi-sig signing
+
+-- Payload exported for owners to sign; fixed on first export so every owner
+-- signs the same bytes
+ALTER TABLE multisig_transactions ADD COLUMN signing_payload TEXT;
+
+-- Verified owner signatures over the signing payload
+CREATE TABLE IF NOT EXISTS multisig_signatures (
+    id TEXT PRIMARY KEY,
+    transaction_id TEXT NOT NULL REFERENCES multisig_transactions(id) ON DELETE CASCADE,
+    owner_address TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(transaction_id, owner_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_multisig_signatures_tx ON multisig_signatures(transaction_id);
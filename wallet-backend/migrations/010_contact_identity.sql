@@ -0,0 +1,11 @@
+-- Resolved name-service identity of contact addresses (ENS, SNS)
+
+-- Cached lookup result; identity_name is NULL when the address has no name.
+-- identity_records holds a JSON object of profile text records.
+ALTER TABLE contacts ADD COLUMN identity_name TEXT;
+ALTER TABLE contacts ADD COLUMN identity_avatar TEXT;
+ALTER TABLE contacts ADD COLUMN identity_records TEXT;
+-- NULL until the first lookup succeeds
+ALTER TABLE contacts ADD COLUMN identity_refreshed_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_contacts_identity_refreshed ON contacts(identity_refreshed_at);
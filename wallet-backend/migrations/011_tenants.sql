@@ -0,0 +1,28 @@
+-- Multi-tenant deployments
+
+-- One row per isolated product served by this backend. Requests are mapped
+-- to a tenant by API key (stored as a SHA-256 hash) or hostname. NULL
+-- overrides fall back to the server-wide configuration.
+CREATE TABLE IF NOT EXISTS tenants (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    hostname TEXT UNIQUE,
+    api_key_hash TEXT UNIQUE,
+    solana_rpc_url TEXT,
+    eth_rpc_url TEXT,
+    rate_limit_max_requests INTEGER,
+    rate_limit_window_secs INTEGER,
+    -- Free-form JSON object handed to the tenant's clients
+    settings TEXT NOT NULL DEFAULT '{}',
+    is_active INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Everything created before tenancy belongs to the default tenant
+INSERT OR IGNORE INTO tenants (id, name) VALUES ('default', 'Default');
+
+ALTER TABLE users ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE wallets ADD COLUMN tenant_id TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_users_tenant_email ON users(tenant_id, email);
+CREATE INDEX IF NOT EXISTS idx_wallets_tenant ON wallets(tenant_id);
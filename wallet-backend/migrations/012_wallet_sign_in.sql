@@ -0,0 +1,24 @@
+-- Sign-in with an external wallet (EIP-4361 SIWE and its Solana equivalent)
+
+-- Messages issued for signing; each nonce can be redeemed once
+CREATE TABLE IF NOT EXISTS sign_in_challenges (
+    nonce TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    chain TEXT NOT NULL,
+    address TEXT NOT NULL,
+    message TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Addresses a user has proven control of and may sign in with
+CREATE TABLE IF NOT EXISTS user_addresses (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL,
+    address TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(chain, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_addresses_user ON user_addresses(user_id);
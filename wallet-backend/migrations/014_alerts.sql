@@ -0,0 +1,48 @@
+-- Balance change alerts
+
+-- User-defined rules on an account. balance_below / balance_above fire when
+-- the balance crosses the threshold and re-arm once it crosses back;
+-- outflow_over fires for every outgoing native transfer larger than the
+-- threshold. Thresholds are in the native coin, or in `currency` (e.g. USD)
+-- when set.
+CREATE TABLE IF NOT EXISTS alerts (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('balance_below', 'balance_above', 'outflow_over')),
+    threshold TEXT NOT NULL,
+    currency TEXT,
+    webhook_url TEXT,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    -- Balance rules: whether the condition held at the last evaluation
+    triggered INTEGER NOT NULL DEFAULT 0,
+    -- Outflow rules: rowid of the last transaction_history row checked
+    history_cursor INTEGER NOT NULL DEFAULT 0,
+    last_triggered_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Latest native balance the alert watcher saw for each account
+CREATE TABLE IF NOT EXISTS balance_snapshots (
+    account_id TEXT PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    balance TEXT NOT NULL,
+    taken_at TEXT NOT NULL
+);
+
+-- Fired alerts, kept as the user's notification feed. webhook_status is
+-- NULL when the alert has no webhook.
+CREATE TABLE IF NOT EXISTS alert_notifications (
+    id TEXT PRIMARY KEY,
+    alert_id TEXT NOT NULL REFERENCES alerts(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    -- Balance or transaction that fired the alert
+    balance TEXT,
+    tx_signature TEXT,
+    webhook_status TEXT CHECK (webhook_status IN ('delivered', 'failed')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_user ON alerts(user_id);
+CREATE INDEX IF NOT EXISTS idx_alerts_account ON alerts(account_id);
+CREATE INDEX IF NOT EXISTS idx_alert_notifications_user ON alert_notifications(user_id, created_at DESC);
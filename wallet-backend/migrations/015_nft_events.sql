@@ -0,0 +1,42 @@
+-- NFT ownership changes detected against the cache
+
+-- Marketplace escrow holding a cached NFT that is listed for sale; NULL
+-- while the account holds it
+ALTER TABLE nft_cache ADD COLUMN escrow_address TEXT;
+
+-- SQLite can't alter a CHECK constraint, so transaction_history is rebuilt
+-- to accept the nft_burn and nft_listing types and to record which token of
+-- a collection an NFT event concerns. Row IDs are kept, as alert cursors
+-- refer to them.
+CREATE TABLE transaction_history_new (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL CHECK (chain IN ('solana', 'ethereum')),
+    signature TEXT NOT NULL,
+    tx_type TEXT NOT NULL CHECK (tx_type IN ('send', 'receive', 'swap', 'nft_transfer', 'nft_burn', 'nft_listing', 'contract_interaction', 'unknown')),
+    from_address TEXT,
+    to_address TEXT,
+    amount TEXT,
+    token_address TEXT,
+    status TEXT NOT NULL CHECK (status IN ('pending', 'confirmed', 'failed')),
+    block_number INTEGER,
+    timestamp TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    fiat_amount TEXT,
+    fiat_currency TEXT,
+    fiat_rate TEXT,
+    token_id TEXT,
+    UNIQUE(chain, signature)
+);
+
+INSERT INTO transaction_history_new
+    (rowid, id, account_id, chain, signature, tx_type, from_address, to_address, amount, token_address, status, block_number, timestamp, created_at, fiat_amount, fiat_currency, fiat_rate)
+SELECT rowid, id, account_id, chain, signature, tx_type, from_address, to_address, amount, token_address, status, block_number, timestamp, created_at, fiat_amount, fiat_currency, fiat_rate
+FROM transaction_history;
+
+DROP TABLE transaction_history;
+ALTER TABLE transaction_history_new RENAME TO transaction_history;
+
+CREATE INDEX IF NOT EXISTS idx_tx_history_account ON transaction_history(account_id);
+CREATE INDEX IF NOT EXISTS idx_tx_history_timestamp ON transaction_history(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_tx_history_account_timestamp ON transaction_history(account_id, timestamp DESC);
@@ -0,0 +1,12 @@
+-- Anticipated and observed balance changes of sent transactions
+
+-- JSON-encoded effects: expected_changes is recorded from a simulation
+-- before broadcast, actual_changes once the transaction lands. A row is
+-- flagged when the two disagree beyond the network fee.
+ALTER TABLE transaction_history ADD COLUMN expected_changes TEXT;
+ALTER TABLE transaction_history ADD COLUMN actual_changes TEXT;
+ALTER TABLE transaction_history ADD COLUMN effects_mismatch INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_tx_history_unreconciled
+    ON transaction_history(created_at)
+    WHERE expected_changes IS NOT NULL AND actual_changes IS NULL;
@@ -0,0 +1,11 @@
+-- End-to-end encrypted client sync data
+
+-- One opaque blob per user (transaction labels, UI preferences), encrypted
+-- by the client; the server only stores it. version is bumped on every
+-- write and served as the ETag for optimistic concurrency.
+CREATE TABLE IF NOT EXISTS sync_blobs (
+    user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    data BLOB NOT NULL,
+    updated_at TEXT NOT NULL
+);
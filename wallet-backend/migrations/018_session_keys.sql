@@ -0,0 +1,25 @@
+-- Scoped session keys for dApp auto-signing
+
+-- A session key lets a dApp send from one account without the wallet being
+-- unlocked, within limits set by the user: one asset (token_address, NULL
+-- for the native coin), at most max_amount per transaction, only to
+-- allowed_targets (JSON array of contract / program addresses), and only
+-- until expires_at. The seed is stored encrypted under the key itself,
+-- which is handed to the dApp once and never stored; revoking the session
+-- discards the ciphertext.
+CREATE TABLE IF NOT EXISTS session_keys (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    token_address TEXT,
+    max_amount TEXT NOT NULL,
+    allowed_targets TEXT NOT NULL,
+    encrypted_seed BLOB,
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT,
+    last_used_at TEXT,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_keys_user ON session_keys(user_id);
@@ -0,0 +1,13 @@
+-- Stuck transaction detection and replacement
+
+-- JSON-encoded `Broadcast`: the height a send was made at, plus its nonce
+-- and gas price on Ethereum or its blockhash's last valid height on Solana.
+-- stuck_at is set when a pending send has sat too long to expect it to land
+-- on its own; replaced_by holds the hash of the speed-up sent in its place.
+ALTER TABLE transaction_history ADD COLUMN broadcast TEXT;
+ALTER TABLE transaction_history ADD COLUMN stuck_at TEXT;
+ALTER TABLE transaction_history ADD COLUMN replaced_by TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tx_history_pending_broadcast
+    ON transaction_history(created_at)
+    WHERE status = 'pending' AND broadcast IS NOT NULL AND replaced_by IS NULL;
@@ -0,0 +1,24 @@
+-- Fiat prices at transaction time, for tax reporting
+
+-- Daily prices per chain, token ('' for the native coin) and currency.
+-- A NULL price records that the feed has no data for that day, so the
+-- token isn't looked up again.
+CREATE TABLE IF NOT EXISTS historical_prices (
+    chain TEXT NOT NULL,
+    token_address TEXT NOT NULL DEFAULT '',
+    currency TEXT NOT NULL,
+    date TEXT NOT NULL,
+    price REAL,
+    fetched_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (chain, token_address, currency, date)
+);
+
+-- Fiat per token on the day of the transaction. priced_at is set once a
+-- price has been looked up, whether or not one was found.
+ALTER TABLE transaction_history ADD COLUMN price_at_tx TEXT;
+ALTER TABLE transaction_history ADD COLUMN price_currency TEXT;
+ALTER TABLE transaction_history ADD COLUMN priced_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tx_history_unpriced
+    ON transaction_history(created_at)
+    WHERE priced_at IS NULL;
@@ -0,0 +1,11 @@
+-- Solana Pay memos and reference keys on sends
+
+ALTER TABLE transaction_history ADD COLUMN memo TEXT;
+
+-- Reference keys attached to a send, for looking it up the way the
+-- recipient will
+CREATE TABLE IF NOT EXISTS transaction_references (
+    reference TEXT NOT NULL,
+    transaction_id TEXT NOT NULL REFERENCES transaction_history(id) ON DELETE CASCADE,
+    PRIMARY KEY (reference, transaction_id)
+);
@@ -0,0 +1,3 @@
+-- Metadata URI a cached NFT was last read from, so a refresh can tell when
+-- a collection has revealed by pointing its tokens somewhere new
+ALTER TABLE nft_cache ADD COLUMN metadata_uri TEXT;
@@ -0,0 +1,4 @@
+-- Confirmation depth recorded by the tracker while a send awaits its
+-- chain's threshold
+
+ALTER TABLE transaction_history ADD COLUMN confirmations INTEGER;
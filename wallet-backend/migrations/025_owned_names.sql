@@ -0,0 +1,26 @@
+-- ENS / SNS names registered from the wallet
+
+-- ENS registers in two steps: the commitment is sent first (status
+-- 'committed', keeping the secret it hides until ready_at), and the name is
+-- registered once the commitment is old enough. SNS names are registered in
+-- one go. target_address is where the name points: the ENS address record,
+-- or the owner of an SNS domain. expires_at is NULL for names that don't
+-- expire (SNS).
+CREATE TABLE IF NOT EXISTS owned_names (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('committed', 'registered')),
+    target_address TEXT,
+    years INTEGER,
+    commitment_secret TEXT,
+    ready_at TEXT,
+    expires_at TEXT,
+    last_tx_hash TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (chain, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_owned_names_account ON owned_names(account_id);
@@ -0,0 +1,32 @@
+-- Sends scheduled for broadcast at a later time
+
+-- Only the intent is stored: the send is built and signed when it falls due,
+-- so it gets a fresh blockhash / nonce. status moves from 'scheduled' to
+-- 'executing' while the scheduler has claimed it, then to 'sent' or
+-- 'failed'; 'cancelled' and 'expired' (still unsent at expires_at) are
+-- final. reference_keys is a JSON array of Solana Pay reference keys.
+CREATE TABLE IF NOT EXISTS scheduled_transactions (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    token_address TEXT,
+    memo TEXT,
+    reference_keys TEXT NOT NULL DEFAULT '[]',
+    execute_after TEXT NOT NULL,
+    expires_at TEXT,
+    status TEXT NOT NULL CHECK (
+        status IN ('scheduled', 'executing', 'sent', 'failed', 'cancelled', 'expired')
+    ),
+    tx_hash TEXT,
+    last_error TEXT,
+    executed_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_transactions_due
+    ON scheduled_transactions(status, execute_after);
+CREATE INDEX IF NOT EXISTS idx_scheduled_transactions_account
+    ON scheduled_transactions(account_id);
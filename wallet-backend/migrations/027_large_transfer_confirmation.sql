@@ -0,0 +1,24 @@
+-- Extra confirmation for large sends
+
+-- A user's large transfer threshold, a decimal amount in `currency`. Sends
+-- worth more (or that can't be priced) are held as a send challenge until
+-- the user re-enters their password. The held send request is stored as
+-- JSON and discarded once confirmed, after too many wrong passwords, or
+-- when the challenge expires.
+CREATE TABLE IF NOT EXISTS large_transfer_thresholds (
+    user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    threshold TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS send_challenges (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    request TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_send_challenges_user ON send_challenges(user_id);
@@ -0,0 +1,5 @@
+-- On-chain royalty terms of a cached NFT as JSON, and the collection it
+-- records. An unverified collection is only the minter's claim.
+ALTER TABLE nft_cache ADD COLUMN royalties_json TEXT;
+ALTER TABLE nft_cache ADD COLUMN collection_address TEXT;
+ALTER TABLE nft_cache ADD COLUMN collection_verified INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+-- Receipt of a settled Ethereum send, and the exact fee a settled send paid
+-- in base units (wei or lamports)
+
+ALTER TABLE transaction_history ADD COLUMN gas_used INTEGER;
+ALTER TABLE transaction_history ADD COLUMN effective_gas_price TEXT;
+ALTER TABLE transaction_history ADD COLUMN receipt_status INTEGER;
+ALTER TABLE transaction_history ADD COLUMN logs_count INTEGER;
+ALTER TABLE transaction_history ADD COLUMN contract_address TEXT;
+ALTER TABLE transaction_history ADD COLUMN fee_paid TEXT;
@@ -0,0 +1,6 @@
+-- Per-account RPC endpoint
+
+-- A node or private relay the account's balance reads, sends and
+-- confirmation checks go to instead of the tenant's or server's endpoint.
+-- It is health-checked when saved; NULL uses the default.
+ALTER TABLE accounts ADD COLUMN rpc_url TEXT;
@@ -0,0 +1,5 @@
+-- MEV protection default per account
+
+-- Ethereum sends from an account with mev_protect set go through the private
+-- relay unless the send says otherwise.
+ALTER TABLE accounts ADD COLUMN mev_protect INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+-- Token list cache
+
+-- The Jupiter token list, kept so token pickers, swap mint checks and
+-- balance labels work straight after a restart. Replaced wholesale per
+-- chain on each refresh.
+CREATE TABLE IF NOT EXISTS token_list (
+    chain TEXT NOT NULL CHECK (chain IN ('solana', 'ethereum')),
+    address TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    name TEXT NOT NULL,
+    decimals INTEGER NOT NULL,
+    logo_uri TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (chain, address)
+);
@@ -0,0 +1,5 @@
+-- Scopes granted to a session
+
+-- Comma-separated scopes (read, trade, admin) carried by the session's access
+-- tokens. Sessions from before scopes keep full access.
+ALTER TABLE user_sessions ADD COLUMN scopes TEXT NOT NULL DEFAULT 'read,trade,admin';
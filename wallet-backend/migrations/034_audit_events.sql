@@ -0,0 +1,16 @@
+-- Audit log of security-sensitive account and wallet changes
+
+CREATE TABLE IF NOT EXISTS audit_events (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    user_id TEXT,
+    -- e.g. `wallet.encryption_password_changed`
+    event TEXT NOT NULL,
+    -- `info`, `warning` or `critical`
+    severity TEXT NOT NULL,
+    -- JSON object with event-specific fields
+    details TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_tenant_created ON audit_events(tenant_id, created_at);
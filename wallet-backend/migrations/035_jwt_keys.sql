@@ -0,0 +1,11 @@
+-- JWT signing keys added at runtime, identified by the `kid` token header
+
+-- New tokens are signed with the newest active key; tokens signed with any
+-- active key (or with JWT_SECRET) are accepted. Retired keys are kept so
+-- their IDs are never reused.
+CREATE TABLE IF NOT EXISTS jwt_keys (
+    kid TEXT PRIMARY KEY,
+    secret TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    retired_at TEXT
+);
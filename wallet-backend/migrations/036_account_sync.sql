@@ -0,0 +1,9 @@
+-- When each account's balance was last read from its chain
+
+-- Set on every successful balance read; `last_known_balance` is the native
+-- balance in display units. `sync_error` holds the last failed read's error
+-- until a read succeeds again.
+ALTER TABLE accounts ADD COLUMN last_synced_at TEXT;
+ALTER TABLE accounts ADD COLUMN last_known_balance TEXT;
+ALTER TABLE accounts ADD COLUMN sync_error TEXT;
+ALTER TABLE accounts ADD COLUMN sync_error_at TEXT;
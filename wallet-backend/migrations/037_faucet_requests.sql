@@ -0,0 +1,17 @@
+-- Test-network funding requested through the built-in faucet
+
+-- Rows are only used to rate-limit requests per address per chain over a
+-- rolling day.
+CREATE TABLE IF NOT EXISTS faucet_requests (
+    id TEXT PRIMARY KEY,
+    user_id TEXT,
+    chain TEXT NOT NULL,
+    address TEXT NOT NULL,
+    -- Base units (lamports / wei) requested
+    amount TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_faucet_requests_address
+    ON faucet_requests(chain, address, created_at);
@@ -0,0 +1,24 @@
+-- Saved send drafts, reused from the send form or as quick actions
+
+-- amount is kept as entered, so it may be a fiat amount such as `25 USD`,
+-- or empty for a draft that asks for it each time. memo is sealed like
+-- transaction memos when field encryption is on.
+CREATE TABLE IF NOT EXISTS send_templates (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    chain TEXT NOT NULL CHECK (chain IN ('solana', 'ethereum')),
+    from_address TEXT,
+    to_address TEXT NOT NULL,
+    amount TEXT NOT NULL DEFAULT '',
+    token_address TEXT,
+    memo TEXT,
+    quick_action INTEGER NOT NULL DEFAULT 0,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    last_used_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(user_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_send_templates_user ON send_templates(user_id);
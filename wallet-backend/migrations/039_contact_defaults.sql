@@ -0,0 +1,5 @@
+-- Send defaults per contact: the token usually sent to them and a typical
+-- amount, kept as entered (coin, token or fiat such as `25 USD`)
+
+ALTER TABLE contacts ADD COLUMN default_token_address TEXT;
+ALTER TABLE contacts ADD COLUMN default_amount TEXT;
@@ -0,0 +1,32 @@
+-- Nightly comparison of cached balances with the chain
+
+-- Each run checks every account's live native balance against its last
+-- known balance plus the history recorded since. Accounts that don't add up
+-- get a discrepancy: `missed_incoming`, `unexplained_outgoing` or
+-- `sync_failed`. Balances are in display units.
+CREATE TABLE IF NOT EXISTS reconciliation_runs (
+    id TEXT PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL,
+    accounts_checked INTEGER NOT NULL,
+    discrepancy_count INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reconciliation_runs_started ON reconciliation_runs(started_at);
+
+CREATE TABLE IF NOT EXISTS balance_discrepancies (
+    id TEXT PRIMARY KEY,
+    run_id TEXT NOT NULL REFERENCES reconciliation_runs(id) ON DELETE CASCADE,
+    tenant_id TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    address TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('missed_incoming', 'unexplained_outgoing', 'sync_failed')),
+    cached_balance TEXT,
+    expected_balance TEXT,
+    live_balance TEXT,
+    detail TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_balance_discrepancies_run ON balance_discrepancies(run_id);
@@ -0,0 +1,18 @@
+-- Private notes on accounts, contacts and transactions
+
+-- body is sealed under a key derived from the unlocked wallet's seed, so
+-- notes can't be read while the wallet is locked, nor with the operator's
+-- field encryption key alone. Notes go with their wallet.
+CREATE TABLE IF NOT EXISTS notes (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    wallet_id TEXT NOT NULL REFERENCES wallets(id) ON DELETE CASCADE,
+    target_type TEXT NOT NULL CHECK (target_type IN ('account', 'contact', 'transaction')),
+    target_id TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notes_user_wallet ON notes(user_id, wallet_id);
+CREATE INDEX IF NOT EXISTS idx_notes_target ON notes(target_type, target_id);
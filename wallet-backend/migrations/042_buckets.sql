@@ -0,0 +1,17 @@
+-- Savings buckets: named, off-chain partitions of an account's native balance
+
+-- allocated is in the chain's native base units, as a decimal string since
+-- wei amounts overflow SQLite integers. Nothing on chain moves when funds
+-- are allocated or moved between buckets; the unallocated remainder is the
+-- live balance less every allocation.
+CREATE TABLE IF NOT EXISTS buckets (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    allocated TEXT NOT NULL DEFAULT '0',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (account_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_buckets_account ON buckets(account_id);
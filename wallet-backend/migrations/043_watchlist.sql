@@ -0,0 +1,44 @@
+-- Watchlist: external addresses a user follows without owning them
+
+-- The alert watcher polls every entry: last_balance is the native balance
+-- (display units) it saw last, and last_signature the newest transaction
+-- involving the address (Solana only). Both start out NULL and the first
+-- poll only records them, so nothing that happened before the address was
+-- added shows up as activity. threshold is in the native coin; balance
+-- movements at least that large notify, through webhook_url when set.
+CREATE TABLE IF NOT EXISTS watchlist (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    address TEXT NOT NULL,
+    label TEXT,
+    threshold TEXT,
+    webhook_url TEXT,
+    last_balance TEXT,
+    last_signature TEXT,
+    last_checked_at TEXT,
+    created_at TEXT NOT NULL,
+    UNIQUE (user_id, chain, address)
+);
+
+-- What the watcher saw happen to a watched address. balance_change rows
+-- carry the signed movement in the native coin and the new balance;
+-- transaction rows the signature. notified is set on movements that met
+-- the entry's threshold; webhook_status is NULL when nothing was POSTed.
+CREATE TABLE IF NOT EXISTS watchlist_activity (
+    id TEXT PRIMARY KEY,
+    watch_id TEXT NOT NULL REFERENCES watchlist(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('balance_change', 'transaction')),
+    amount TEXT,
+    balance TEXT,
+    signature TEXT,
+    status TEXT,
+    block_time INTEGER,
+    notified INTEGER NOT NULL DEFAULT 0,
+    webhook_status TEXT CHECK (webhook_status IN ('delivered', 'failed')),
+    detected_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_watchlist_user ON watchlist(user_id);
+CREATE INDEX IF NOT EXISTS idx_watchlist_activity_watch ON watchlist_activity(watch_id, detected_at DESC);
@@ -0,0 +1,12 @@
+-- Runtime feature flags set by operators
+
+-- One row per flag that has been set; flags without a row keep their
+-- built-in default. Names are fixed by the server: maintenance (read-only
+-- API, off by default), sends.<chain> and swaps (on by default). reason is
+-- shown to clients refused because of the flag.
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name TEXT PRIMARY KEY,
+    enabled INTEGER NOT NULL,
+    reason TEXT,
+    updated_at TEXT NOT NULL
+);
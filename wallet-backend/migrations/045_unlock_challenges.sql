@@ -0,0 +1,12 @@
+-- Nonces an unlock request redeems, so a captured request can't be replayed.
+-- Redeemed rows are kept until they expire, as they count towards the
+-- per-tenant issuance limit.
+CREATE TABLE IF NOT EXISTS unlock_challenges (
+    nonce TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    expires_at TEXT NOT NULL,
+    redeemed_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_unlock_challenges_tenant ON unlock_challenges(tenant_id, created_at);
@@ -0,0 +1,19 @@
+-- Outbox of audit and transaction events awaiting export to a SIEM. Rows are
+-- written in the same transaction as the change they describe and deleted
+-- once delivered.
+CREATE TABLE IF NOT EXISTS siem_outbox (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    -- `audit` or `transaction`
+    source TEXT NOT NULL,
+    event TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    -- Structured JSON event as exported
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_siem_outbox_due ON siem_outbox(next_attempt_at, created_at);
@@ -0,0 +1,72 @@
+-- Stake pool deposits and withdrawals in history
+
+-- SQLite can't alter a CHECK constraint, so transaction_history is rebuilt
+-- to accept the stake and unstake types. Row IDs are kept, as alert cursors
+-- refer to them. Dropping the old table cascades to its tags and payment
+-- references, so those are set aside and restored.
+CREATE TEMP TABLE saved_transaction_tags AS SELECT * FROM transaction_tags;
+CREATE TEMP TABLE saved_transaction_references AS SELECT * FROM transaction_references;
+
+CREATE TABLE transaction_history_new (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL CHECK (chain IN ('solana', 'ethereum')),
+    signature TEXT NOT NULL,
+    tx_type TEXT NOT NULL CHECK (tx_type IN ('send', 'receive', 'swap', 'stake', 'unstake', 'nft_transfer', 'nft_burn', 'nft_listing', 'contract_interaction', 'unknown')),
+    from_address TEXT,
+    to_address TEXT,
+    amount TEXT,
+    token_address TEXT,
+    status TEXT NOT NULL CHECK (status IN ('pending', 'confirmed', 'failed')),
+    block_number INTEGER,
+    timestamp TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    fiat_amount TEXT,
+    fiat_currency TEXT,
+    fiat_rate TEXT,
+    token_id TEXT,
+    expected_changes TEXT,
+    actual_changes TEXT,
+    effects_mismatch INTEGER,
+    broadcast TEXT,
+    stuck_at TEXT,
+    replaced_by TEXT,
+    price_at_tx TEXT,
+    price_currency TEXT,
+    priced_at TEXT,
+    memo TEXT,
+    confirmations INTEGER,
+    gas_used INTEGER,
+    effective_gas_price TEXT,
+    receipt_status INTEGER,
+    logs_count INTEGER,
+    contract_address TEXT,
+    fee_paid TEXT,
+    UNIQUE(chain, signature)
+);
+
+INSERT INTO transaction_history_new
+    (rowid, id, account_id, chain, signature, tx_type, from_address, to_address, amount, token_address, status, block_number, timestamp, created_at, fiat_amount, fiat_currency, fiat_rate, token_id, expected_changes, actual_changes, effects_mismatch, broadcast, stuck_at, replaced_by, price_at_tx, price_currency, priced_at, memo, confirmations, gas_used, effective_gas_price, receipt_status, logs_count, contract_address, fee_paid)
+SELECT rowid, id, account_id, chain, signature, tx_type, from_address, to_address, amount, token_address, status, block_number, timestamp, created_at, fiat_amount, fiat_currency, fiat_rate, token_id, expected_changes, actual_changes, effects_mismatch, broadcast, stuck_at, replaced_by, price_at_tx, price_currency, priced_at, memo, confirmations, gas_used, effective_gas_price, receipt_status, logs_count, contract_address, fee_paid
+FROM transaction_history;
+
+DROP TABLE transaction_history;
+ALTER TABLE transaction_history_new RENAME TO transaction_history;
+
+INSERT OR IGNORE INTO transaction_tags SELECT * FROM saved_transaction_tags;
+INSERT OR IGNORE INTO transaction_references SELECT * FROM saved_transaction_references;
+DROP TABLE saved_transaction_tags;
+DROP TABLE saved_transaction_references;
+
+CREATE INDEX IF NOT EXISTS idx_tx_history_account ON transaction_history(account_id);
+CREATE INDEX IF NOT EXISTS idx_tx_history_timestamp ON transaction_history(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_tx_history_account_timestamp ON transaction_history(account_id, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_tx_history_unreconciled
+    ON transaction_history(created_at)
+    WHERE expected_changes IS NOT NULL AND actual_changes IS NULL;
+CREATE INDEX IF NOT EXISTS idx_tx_history_pending_broadcast
+    ON transaction_history(created_at)
+    WHERE status = 'pending' AND broadcast IS NOT NULL AND replaced_by IS NULL;
+CREATE INDEX IF NOT EXISTS idx_tx_history_unpriced
+    ON transaction_history(created_at)
+    WHERE priced_at IS NULL;
@@ -0,0 +1,30 @@
+-- ETH deposited from an L1 account into the official bridge of an L2
+
+-- A deposit is 'submitted' once broadcast on L1 and 'confirmed' once it has
+-- landed there, at which point finalizes_at is set from the bridge's
+-- deposit window: the time the L2's nodes take to derive the deposit from
+-- L1 and credit it. It is 'finalized' when that window has passed, or
+-- 'failed' if it reverted on L1. Either outcome is notified once, through
+-- webhook_url when set; webhook_status is NULL when nothing was POSTed.
+CREATE TABLE IF NOT EXISTS bridge_transfers (
+    id TEXT PRIMARY KEY,
+    account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    network TEXT NOT NULL,
+    l1_chain_id INTEGER NOT NULL,
+    l1_tx_hash TEXT NOT NULL UNIQUE,
+    bridge_contract TEXT NOT NULL,
+    address TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('submitted', 'confirmed', 'finalized', 'failed')),
+    l1_block_number INTEGER,
+    confirmed_at TEXT,
+    finalizes_at TEXT,
+    finalized_at TEXT,
+    webhook_url TEXT,
+    webhook_status TEXT CHECK (webhook_status IN ('delivered', 'failed')),
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_bridge_transfers_account ON bridge_transfers(account_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_bridge_transfers_open ON bridge_transfers(status) WHERE status IN ('submitted', 'confirmed');
@@ -0,0 +1,41 @@
+-- Swaps moving value between Solana and Ethereum along a route quoted by an
+-- aggregator
+
+-- A swap is 'submitted' once its source transaction is broadcast and
+-- 'bridging' once that has landed, while the bridge carries the value
+-- across. It ends 'completed' when the destination transaction has
+-- delivered, 'refunded' when the bridge gave the value back on the source
+-- chain, or 'failed'. Amounts are in display units of their token; a NULL
+-- token is the chain's native coin. The id is the route ID carried by both
+-- history entries of the swap.
+CREATE TABLE IF NOT EXISTS cross_chain_swaps (
+    id TEXT PRIMARY KEY,
+    from_account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    to_account_id TEXT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    from_chain TEXT NOT NULL CHECK (from_chain IN ('solana', 'ethereum')),
+    to_chain TEXT NOT NULL CHECK (to_chain IN ('solana', 'ethereum')),
+    from_token TEXT,
+    to_token TEXT,
+    from_amount TEXT NOT NULL,
+    quoted_amount TEXT NOT NULL,
+    min_amount TEXT NOT NULL,
+    received_amount TEXT,
+    tool TEXT NOT NULL,
+    estimated_duration_secs INTEGER NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('submitted', 'bridging', 'completed', 'refunded', 'failed')),
+    status_message TEXT,
+    approval_tx_hash TEXT,
+    source_tx_hash TEXT NOT NULL,
+    destination_tx_hash TEXT,
+    completed_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (from_chain, source_tx_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_cross_chain_swaps_from ON cross_chain_swaps(from_account_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_cross_chain_swaps_to ON cross_chain_swaps(to_account_id);
+CREATE INDEX IF NOT EXISTS idx_cross_chain_swaps_open ON cross_chain_swaps(status) WHERE status IN ('submitted', 'bridging');
+
+ALTER TABLE transaction_history ADD COLUMN route_id TEXT;
+CREATE INDEX IF NOT EXISTS idx_tx_history_route ON transaction_history(route_id) WHERE route_id IS NOT NULL;
@@ -0,0 +1,26 @@
+-- Transfers built for a signer away from the server, such as an air-gapped
+-- device holding the sending address's key
+
+-- payload is the unsigned transaction as exported: base64 bincode (Solana)
+-- or RLP (Ethereum), with signing_hash the hash an Ethereum signer signs.
+-- A transfer is 'exported' until a signature over it by from_address comes
+-- back, then 'broadcast' with tx_hash set. amount and fee are in display
+-- units of the chain's native coin.
+CREATE TABLE IF NOT EXISTS cold_transactions (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id TEXT NOT NULL,
+    chain TEXT NOT NULL CHECK (chain IN ('solana', 'ethereum')),
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    fee TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    signing_hash TEXT,
+    status TEXT NOT NULL CHECK (status IN ('exported', 'broadcast')),
+    tx_hash TEXT,
+    created_at TEXT NOT NULL,
+    broadcast_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_cold_transactions_user ON cold_transactions(user_id, created_at DESC);
@@ -0,0 +1,8 @@
+-- CORS origins added by operators at runtime
+
+-- Allowed alongside the CORS_ORIGIN list, which stays fixed at startup.
+-- origin is stored normalized (scheme://host[:port], no trailing slash).
+CREATE TABLE IF NOT EXISTS cors_origins (
+    origin TEXT PRIMARY KEY,
+    created_at TEXT NOT NULL
+);
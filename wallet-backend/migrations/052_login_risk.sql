@@ -0,0 +1,29 @@
+-- Suspicious login detection
+
+-- What each login looked like, compared against later logins. The device
+-- fingerprint is stored hashed; country is an ISO code from a trusted proxy.
+ALTER TABLE user_sessions ADD COLUMN device_fingerprint TEXT;
+ALTER TABLE user_sessions ADD COLUMN country TEXT;
+ALTER TABLE user_sessions ADD COLUMN risk_score INTEGER NOT NULL DEFAULT 0;
+
+-- Password logins held back for step-up verification. The session is only
+-- opened once the emailed link's token is presented; token_hash is its
+-- SHA-256. signals is a comma-separated list of what raised the score.
+CREATE TABLE IF NOT EXISTS login_verifications (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    token_hash TEXT NOT NULL,
+    scopes TEXT NOT NULL,
+    device_info TEXT,
+    ip_address TEXT,
+    device_fingerprint TEXT,
+    country TEXT,
+    risk_score INTEGER NOT NULL,
+    signals TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    verified_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_verifications_user ON login_verifications(user_id);
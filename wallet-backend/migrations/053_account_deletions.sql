@@ -0,0 +1,25 @@
+-- Account deletion
+
+-- Set when a user's data has been purged. The row stays behind as a
+-- tombstone so audit events keep pointing at a user; its email and password
+-- hash are scrubbed.
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
+
+-- Deletion requests. A request is confirmed by presenting the token issued
+-- for it (token_hash is its SHA-256) before token_expires_at; the account is
+-- then purged once purge_after passes, unless a login cancels it first.
+CREATE TABLE IF NOT EXISTS account_deletions (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id),
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    token_hash TEXT NOT NULL,
+    token_expires_at TEXT NOT NULL,
+    confirmed_at TEXT,
+    purge_after TEXT,
+    cancelled_at TEXT,
+    purged_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_deletions_user ON account_deletions(user_id);
+CREATE INDEX IF NOT EXISTS idx_account_deletions_purge ON account_deletions(purge_after);
@@ -0,0 +1,5 @@
+-- Token display overrides
+
+-- Icon shown for the token in place of the token list's logo. Only https
+-- URLs are stored, normalized when set.
+ALTER TABLE user_tokens ADD COLUMN icon_url TEXT;
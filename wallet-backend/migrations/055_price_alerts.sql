@@ -0,0 +1,67 @@
+-- Price alerts
+
+-- User-defined rules on the price of a native coin (token_address '') or a
+-- token. price_above / price_below compare the price in `currency` with the
+-- threshold; rises_by / drops_by compare its percentage change over the
+-- last window_hours. A rule fires when its condition starts to hold and
+-- re-arms once it stops.
+CREATE TABLE IF NOT EXISTS price_alerts (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL CHECK (chain IN ('solana', 'ethereum')),
+    token_address TEXT NOT NULL DEFAULT '',
+    symbol TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('price_above', 'price_below', 'rises_by', 'drops_by')),
+    threshold TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    window_hours INTEGER,
+    webhook_url TEXT,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    -- Whether the condition held at the last evaluation
+    triggered INTEGER NOT NULL DEFAULT 0,
+    last_price TEXT,
+    last_triggered_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Prices seen by the refresh loop, kept as long as the longest window so
+-- change rules have something to compare against
+CREATE TABLE IF NOT EXISTS price_samples (
+    chain TEXT NOT NULL,
+    token_address TEXT NOT NULL DEFAULT '',
+    currency TEXT NOT NULL,
+    price REAL NOT NULL,
+    sampled_at TEXT NOT NULL,
+    PRIMARY KEY (chain, token_address, currency, sampled_at)
+);
+
+-- Fired price alerts share the notification feed with balance alerts.
+-- SQLite can't relax a NOT NULL constraint, so alert_notifications is
+-- rebuilt with exactly one of alert_id and price_alert_id set, and the
+-- price that fired a price rule.
+CREATE TABLE alert_notifications_new (
+    id TEXT PRIMARY KEY,
+    alert_id TEXT REFERENCES alerts(id) ON DELETE CASCADE,
+    price_alert_id TEXT REFERENCES price_alerts(id) ON DELETE CASCADE,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    -- Balance, price or transaction that fired the alert
+    balance TEXT,
+    price TEXT,
+    tx_signature TEXT,
+    webhook_status TEXT CHECK (webhook_status IN ('delivered', 'failed')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    CHECK ((alert_id IS NULL) != (price_alert_id IS NULL))
+);
+
+INSERT INTO alert_notifications_new
+    (id, alert_id, user_id, message, balance, tx_signature, webhook_status, created_at)
+SELECT id, alert_id, user_id, message, balance, tx_signature, webhook_status, created_at
+FROM alert_notifications;
+
+DROP TABLE alert_notifications;
+ALTER TABLE alert_notifications_new RENAME TO alert_notifications;
+
+CREATE INDEX IF NOT EXISTS idx_alert_notifications_user ON alert_notifications(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_price_alerts_user ON price_alerts(user_id);
+CREATE INDEX IF NOT EXISTS idx_price_alerts_active ON price_alerts(chain, token_address, currency) WHERE is_active = 1;
@@ -0,0 +1,30 @@
+-- Signed webhook deliveries
+
+-- Secret each tenant's webhook deliveries are signed with, created on first
+-- use and replaced when rotated
+CREATE TABLE IF NOT EXISTS webhook_secrets (
+    tenant_id TEXT PRIMARY KEY,
+    secret TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+-- Every POST to a webhook, kept for debugging. webhook_id is the alert,
+-- price alert, watchlist entry or bridge transfer the URL belongs to; a
+-- delivery outlives its source so recent failures stay visible. status_code
+-- is NULL when no response arrived, with the reason in error.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    webhook_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    url TEXT NOT NULL,
+    -- Sent by the test endpoint rather than by a real event
+    test INTEGER NOT NULL DEFAULT 0,
+    status_code INTEGER,
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON webhook_deliveries(webhook_id, created_at DESC);
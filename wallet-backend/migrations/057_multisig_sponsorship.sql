@@ -0,0 +1,7 @@
+-- Multi-sig deployment sponsorship
+
+-- Primary account that paid for deploying the multi-sig, and what the
+-- deployment was estimated to cost it in base units. Both are NULL when
+-- the owners deploy it themselves.
+ALTER TABLE multisig_wallets ADD COLUMN sponsor_address TEXT;
+ALTER TABLE multisig_wallets ADD COLUMN deployment_cost TEXT;
@@ -0,0 +1,17 @@
+-- Signed share links
+
+-- Read-only links to one account's balance and history or one multi-sig's
+-- proposals. The link's token is signed rather than stored; a row exists so
+-- its owner can list and revoke it.
+CREATE TABLE IF NOT EXISTS share_links (
+    id TEXT PRIMARY KEY,
+    tenant_id TEXT NOT NULL DEFAULT 'default',
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    scope TEXT NOT NULL CHECK (scope IN ('account', 'multisig')),
+    resource_id TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_share_links_user ON share_links(user_id, created_at DESC);
@@ -0,0 +1,13 @@
+-- Token list preferences
+
+-- How a user's token balances are listed: the order (NULL keeps the chain's
+-- order) and a value in `currency` below which tokens are folded into an
+-- "others" bucket (NULL never folds). Query parameters on the balance
+-- endpoints override both per request.
+CREATE TABLE IF NOT EXISTS token_view_settings (
+    user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    sort TEXT CHECK (sort IN ('value', 'name', 'activity')),
+    small_balance_threshold TEXT,
+    currency TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
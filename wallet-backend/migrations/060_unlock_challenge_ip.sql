@@ -0,0 +1,8 @@
+-- Unlock challenges per client
+
+-- Issuance is limited per client IP, so one client can't use up the tenant's
+-- nonces and keep the owner from unlocking; the per-tenant count remains as
+-- a higher backstop.
+ALTER TABLE unlock_challenges ADD COLUMN ip_address TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_unlock_challenges_ip ON unlock_challenges(tenant_id, ip_address, created_at);
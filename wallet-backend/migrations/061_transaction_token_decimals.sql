@@ -0,0 +1,6 @@
+-- Token decimals on history entries
+
+-- Decimals of the token a token entry's `amount` is given in, so its base
+-- units can be derived. NULL for native entries and tokens recorded
+-- without them.
+ALTER TABLE transaction_history ADD COLUMN token_decimals INTEGER;
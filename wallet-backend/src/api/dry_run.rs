@@ -0,0 +1,14 @@
+//! Dry runs of mutating endpoints
+//!
+//! `?dry_run=true` on a send, swap execution or multisig execution runs the
+//! same validation, fee estimation and policy checks and answers with what
+//! would happen, but nothing is broadcast and nothing is stored.
+
+use serde::Deserialize;
+
+/// `dry_run` query param
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct DryRunQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
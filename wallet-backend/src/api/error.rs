@@ -0,0 +1,188 @@
+//! Structured API error envelope (v2+)
+//!
+//! Errors are returned as `{"error": {"code": "...", "message": "...",
+//! "request_id": "..."}}` where `code` is a stable machine-readable identifier.
+//! When the caller asked for another language and the catalog has the code,
+//! `message` is translated and the original English text moves to `detail`.
+//! Diagnosed transaction failures also carry `remediation` and `retryable`.
+
+use axum::{
+    body::to_bytes,
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use super::i18n;
+use crate::chains::diagnostics::FailureDiagnosis;
+use super::middleware::locale::current_locale;
+use super::middleware::request_id::current_request_id;
+
+/// Largest plain-text error body that is rewrapped into an envelope
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// Structured API error
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub remediation: Option<&'static str>,
+    pub retryable: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorEnvelope<'a> {
+    error: ErrorBody<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody<'a> {
+    code: &'a str,
+    message: &'a str,
+    /// Untranslated message, when `message` was localized
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remediation: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retryable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            remediation: None,
+            retryable: None,
+        }
+    }
+
+    /// Use a failed transaction's diagnosis for the code and remediation
+    pub fn with_diagnosis(self, diagnosis: &FailureDiagnosis) -> Self {
+        Self {
+            code: diagnosis.code,
+            remediation: Some(diagnosis.remediation),
+            retryable: Some(diagnosis.retryable),
+            ..self
+        }
+    }
+
+    /// Build an error using the default code for the status
+    pub fn from_status(status: StatusCode, message: impl Into<String>) -> Self {
+        Self::new(status, default_code(status), message)
+    }
+}
+
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self::from_status(status, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let locale = current_locale().unwrap_or_default();
+        let localized = i18n::message(self.code, locale);
+        let body = ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code,
+                message: localized.unwrap_or(&self.message),
+                detail: localized.map(|_| self.message.as_str()),
+                remediation: self.remediation,
+                retryable: self.retryable,
+                request_id: current_request_id(),
+            },
+        };
+
+        let mut response = (self.status, Json(body)).into_response();
+        let language = if localized.is_some() { locale } else { i18n::Locale::En };
+        response.headers_mut().insert(
+            header::CONTENT_LANGUAGE,
+            HeaderValue::from_static(language.tag()),
+        );
+        response
+    }
+}
+
+/// Default stable error code for an HTTP status
+pub fn default_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::GONE => "gone",
+        StatusCode::PRECONDITION_FAILED => "precondition_failed",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        StatusCode::PRECONDITION_REQUIRED => "precondition_required",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+        StatusCode::BAD_GATEWAY => "upstream_error",
+        StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+        s if s.is_client_error() => "client_error",
+        _ => "internal_error",
+    }
+}
+
+/// Plain-text error for a failed transaction; the v2 envelope takes its code
+/// and remediation from `diagnosis`
+pub fn diagnosed_error(
+    status: StatusCode,
+    message: String,
+    diagnosis: Option<FailureDiagnosis>,
+) -> Response {
+    let mut response = (status, message).into_response();
+    if let Some(diagnosis) = diagnosis {
+        response.extensions_mut().insert(diagnosis);
+    }
+    response
+}
+
+/// Middleware that rewraps non-JSON error responses into the structured envelope
+pub async fn envelope_errors(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if is_json {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = to_bytes(body, MAX_ERROR_BODY).await.unwrap_or_default();
+    let message = match String::from_utf8_lossy(&bytes).trim() {
+        "" => status.canonical_reason().unwrap_or("Error").to_string(),
+        text => text.to_string(),
+    };
+
+    let mut error = ApiError::from_status(status, message);
+    if let Some(diagnosis) = parts.extensions.get::<FailureDiagnosis>() {
+        error = error.with_diagnosis(diagnosis);
+    }
+    let mut wrapped = error.into_response();
+    for (name, value) in parts.headers.iter() {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            wrapped.headers_mut().append(name, value.clone());
+        }
+    }
+    wrapped
+}
+
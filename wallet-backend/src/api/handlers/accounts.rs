@@ -3,14 +3,21 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::core::Chain;
-use crate::services::wallet_service;
+use crate::core::{Chain, SolanaScheme};
+use crate::services::ownership_service::{
+    self, OwnershipProof, OwnershipProofRequest, OwnershipServiceError, OwnershipVerification,
+    VerifyOwnershipRequest,
+};
+use crate::services::statement_service::{self, StatementError, StatementFormat};
+use crate::services::user_service::Claims;
+use crate::services::wallet_service::{self, AccountDiscovery, WalletServiceError};
 use crate::storage::models::AccountResponse;
 use crate::AppState;
 
@@ -47,6 +54,13 @@ pub async fn create_account(
         .parse()
         .map_err(|e: String| (StatusCode::BAD_REQUEST, e))?;
 
+    if !state.config.chain_enabled(chain) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Chain {} is not enabled", chain),
+        ));
+    }
+
     let account = wallet_service::derive_new_account(&state, chain, request.name)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -54,6 +68,56 @@ pub async fn create_account(
     Ok(Json(account))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DiscoverQuery {
+    /// Indexes to scan per scheme
+    pub count: Option<u32>,
+}
+
+/// Solana addresses of the wallet under every derivation path scheme, with
+/// which ones have been used
+pub async fn discover_accounts(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DiscoverQuery>,
+) -> Result<Json<AccountDiscovery>, (StatusCode, String)> {
+    let discovery = wallet_service::discover_solana_accounts(&state, query.count)
+        .await
+        .map_err(discovery_error_status)?;
+
+    Ok(Json(discovery))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportDiscoveredRequest {
+    pub scheme: SolanaScheme,
+    pub indexes: Vec<u32>,
+}
+
+/// Add discovered Solana accounts under the chosen scheme
+pub async fn import_discovered(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ImportDiscoveredRequest>,
+) -> Result<Json<Vec<AccountResponse>>, (StatusCode, String)> {
+    let accounts =
+        wallet_service::import_solana_accounts(&state, request.scheme, &request.indexes)
+            .await
+            .map_err(discovery_error_status)?;
+
+    Ok(Json(accounts))
+}
+
+fn discovery_error_status(e: WalletServiceError) -> (StatusCode, String) {
+    let status = match e {
+        WalletServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        WalletServiceError::IndexInUse(_) => StatusCode::CONFLICT,
+        WalletServiceError::WalletLocked => StatusCode::UNAUTHORIZED,
+        WalletServiceError::NoWalletFound => StatusCode::NOT_FOUND,
+        WalletServiceError::ChainError(_) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
 /// Delete account
 pub async fn delete_account(
     State(state): State<Arc<AppState>>,
@@ -76,3 +140,154 @@ pub async fn delete_account(
     tracing::info!("Account deleted successfully: {}", id);
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Account RPC endpoint request; an empty or missing URL restores the default
+#[derive(Debug, Deserialize)]
+pub struct SetRpcUrlRequest {
+    pub rpc_url: Option<String>,
+}
+
+/// Set or clear the RPC endpoint an account uses
+pub async fn set_rpc_url(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<SetRpcUrlRequest>,
+) -> Result<Json<AccountResponse>, (StatusCode, String)> {
+    let account = wallet_service::set_account_rpc_url(&state, &id, request.rpc_url)
+        .await
+        .map_err(account_error_status)?;
+
+    Ok(Json(account))
+}
+
+/// MEV protection request
+#[derive(Debug, Deserialize)]
+pub struct SetMevProtectRequest {
+    pub enabled: bool,
+}
+
+/// Set whether an Ethereum account sends through the private relay by default
+pub async fn set_mev_protect(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<SetMevProtectRequest>,
+) -> Result<Json<AccountResponse>, (StatusCode, String)> {
+    let account = wallet_service::set_account_mev_protect(&state, &id, request.enabled)
+        .await
+        .map_err(account_error_status)?;
+
+    Ok(Json(account))
+}
+
+/// Read an account's balance from its chain now; the account comes back
+/// with its updated sync state
+pub async fn sync_account(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<AccountResponse>, (StatusCode, String)> {
+    let account = wallet_service::sync_account(&state, &id)
+        .await
+        .map_err(account_error_status)?;
+
+    Ok(Json(account))
+}
+
+fn account_error_status(e: WalletServiceError) -> (StatusCode, String) {
+    let status = match e {
+        WalletServiceError::AccountNotFound => StatusCode::NOT_FOUND,
+        WalletServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        WalletServiceError::ChainError(_) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Sign a caller-supplied challenge with the account's key
+pub async fn prove_ownership(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<OwnershipProofRequest>,
+) -> Result<Json<OwnershipProof>, (StatusCode, String)> {
+    let proof = ownership_service::prove_ownership(&state, &claims.sub, &id, request)
+        .await
+        .map_err(ownership_error_status)?;
+
+    Ok(Json(proof))
+}
+
+/// Check an ownership proof from any wallet
+pub async fn verify_ownership(
+    Json(request): Json<VerifyOwnershipRequest>,
+) -> Result<Json<OwnershipVerification>, (StatusCode, String)> {
+    let verification =
+        ownership_service::verify_ownership(request).map_err(ownership_error_status)?;
+
+    Ok(Json(verification))
+}
+
+fn ownership_error_status(e: OwnershipServiceError) -> (StatusCode, String) {
+    let status = match e {
+        OwnershipServiceError::NotFound => StatusCode::NOT_FOUND,
+        OwnershipServiceError::InvalidChallenge(_)
+        | OwnershipServiceError::InvalidChain(_)
+        | OwnershipServiceError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+        OwnershipServiceError::WalletError(WalletServiceError::WalletLocked) => {
+            StatusCode::UNAUTHORIZED
+        }
+        OwnershipServiceError::Signing(_)
+        | OwnershipServiceError::WalletError(_)
+        | OwnershipServiceError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    /// `YYYY-MM`
+    pub month: String,
+    #[serde(default)]
+    pub format: StatementFormat,
+}
+
+/// Monthly statement for an account, as a PDF or JSON
+pub async fn get_statement(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<StatementQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let statement = statement_service::statement(&state, &id, &query.month)
+        .await
+        .map_err(statement_error_status)?;
+
+    match query.format {
+        StatementFormat::Json => Ok(Json(statement).into_response()),
+        StatementFormat::Pdf => {
+            let pdf = statement_service::render_pdf(&statement).map_err(statement_error_status)?;
+            let disposition = format!(
+                "attachment; filename=\"statement-{}-{}.pdf\"",
+                statement.address, statement.month
+            );
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "application/pdf".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                pdf,
+            )
+                .into_response())
+        }
+    }
+}
+
+fn statement_error_status(e: StatementError) -> (StatusCode, String) {
+    let status = match e {
+        StatementError::NotFound => StatusCode::NOT_FOUND,
+        StatementError::InvalidMonth(_) => StatusCode::BAD_REQUEST,
+        StatementError::Chain(_) => StatusCode::BAD_GATEWAY,
+        StatementError::InvalidChain(_)
+        | StatementError::Render(_)
+        | StatementError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,151 @@
+//! Balance and price alert handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::alert_service::{
+    self, AlertServiceError, CreateAlertRequest, UpdateAlertRequest,
+};
+use crate::services::price_alert_service::{
+    self, CreatePriceAlertRequest, UpdatePriceAlertRequest,
+};
+use crate::services::user_service::Claims;
+use crate::storage::models::{AlertNotificationResponse, AlertResponse, PriceAlertResponse};
+use crate::AppState;
+
+/// List the user's alert rules
+pub async fn list_alerts(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AlertResponse>>, (StatusCode, String)> {
+    let alerts = alert_service::list_alerts(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(alerts))
+}
+
+/// Create an alert rule on an account
+pub async fn create_alert(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateAlertRequest>,
+) -> Result<Json<AlertResponse>, (StatusCode, String)> {
+    let alert = alert_service::create_alert(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(alert))
+}
+
+/// Change an alert's threshold, currency, webhook or enabled state
+pub async fn update_alert(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateAlertRequest>,
+) -> Result<Json<AlertResponse>, (StatusCode, String)> {
+    let alert = alert_service::update_alert(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(alert))
+}
+
+/// Delete an alert rule and its notifications
+pub async fn delete_alert(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    alert_service::delete_alert(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// List the user's price alert rules
+pub async fn list_price_alerts(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<PriceAlertResponse>>, (StatusCode, String)> {
+    let alerts = price_alert_service::list_price_alerts(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(alerts))
+}
+
+/// Create a price alert rule on a coin or token
+pub async fn create_price_alert(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreatePriceAlertRequest>,
+) -> Result<Json<PriceAlertResponse>, (StatusCode, String)> {
+    let alert = price_alert_service::create_price_alert(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(alert))
+}
+
+/// Change a price alert's threshold, window, webhook or enabled state
+pub async fn update_price_alert(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdatePriceAlertRequest>,
+) -> Result<Json<PriceAlertResponse>, (StatusCode, String)> {
+    let alert = price_alert_service::update_price_alert(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(alert))
+}
+
+/// Delete a price alert rule and its notifications
+pub async fn delete_price_alert(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    price_alert_service::delete_price_alert(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Most recent fired balance and price alerts, newest first
+pub async fn list_notifications(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AlertNotificationResponse>>, (StatusCode, String)> {
+    let notifications = alert_service::list_notifications(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(notifications))
+}
+
+fn error_status(e: AlertServiceError) -> (StatusCode, String) {
+    let status = match e {
+        AlertServiceError::InvalidKind(_)
+        | AlertServiceError::InvalidThreshold(_)
+        | AlertServiceError::InvalidCurrency(_)
+        | AlertServiceError::InvalidWebhook(_)
+        | AlertServiceError::InvalidChain(_)
+        | AlertServiceError::InvalidToken(_)
+        | AlertServiceError::InvalidWindow(_) => StatusCode::BAD_REQUEST,
+        AlertServiceError::AccountNotFound | AlertServiceError::NotFound => StatusCode::NOT_FOUND,
+        AlertServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        AlertServiceError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,62 @@
+//! Spending analytics and transaction tag handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::services::analytics_service::{
+    self, AnalyticsError, SetTagsRequest, SpendingQuery, SpendingReport, TagsResponse,
+};
+use crate::AppState;
+
+/// Outflows by month, tag and counterparty, with token and fiat totals
+pub async fn spending(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SpendingQuery>,
+) -> Result<Json<SpendingReport>, (StatusCode, String)> {
+    let report = analytics_service::spending(&state, query)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(report))
+}
+
+/// Get a transaction's tags
+pub async fn get_tags(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<TagsResponse>, (StatusCode, String)> {
+    let tags = analytics_service::get_tags(&state, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(tags))
+}
+
+/// Replace a transaction's tags
+pub async fn set_tags(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<SetTagsRequest>,
+) -> Result<Json<TagsResponse>, (StatusCode, String)> {
+    let tags = analytics_service::set_tags(&state, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(tags))
+}
+
+fn error_status(e: AnalyticsError) -> (StatusCode, String) {
+    let status = match e {
+        AnalyticsError::NotFound => StatusCode::NOT_FOUND,
+        AnalyticsError::InvalidTag(_)
+        | AnalyticsError::TooManyTags
+        | AnalyticsError::InvalidRange(_) => StatusCode::BAD_REQUEST,
+        AnalyticsError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
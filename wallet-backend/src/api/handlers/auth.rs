@@ -1,14 +1,20 @@
 //! Authentication handlers
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::{
-    extract::State,
+    extract::{ConnectInfo, Path, State},
+    Extension,
     http::StatusCode,
     Json,
 };
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
 
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::services::user_service::Claims;
+use crate::core::{check_mnemonic, get_wordlist, language_name, parse_language, MnemonicCheck};
 use crate::services::wallet_service;
 use crate::AppState;
 
@@ -17,16 +23,19 @@ use crate::AppState;
 pub struct StatusResponse {
     pub has_wallet: bool,
     pub is_unlocked: bool,
+    pub keyfile_required: bool,
 }
 
 /// Get wallet status
 pub async fn status(State(state): State<Arc<AppState>>) -> Json<StatusResponse> {
-    let has_wallet = state.db.wallet_exists().await.unwrap_or(false);
+    let has_wallet = state.db.wallet_exists(&current_tenant_id()).await.unwrap_or(false);
     let is_unlocked = wallet_service::is_unlocked(&state).await;
+    let keyfile_required = wallet_service::keyfile_required(&state).await;
 
     Json(StatusResponse {
         has_wallet,
         is_unlocked,
+        keyfile_required,
     })
 }
 
@@ -34,6 +43,28 @@ pub async fn status(State(state): State<Arc<AppState>>) -> Json<StatusResponse>
 #[derive(Debug, Deserialize)]
 pub struct UnlockRequest {
     pub password: String,
+    /// Base64 keyfile contents, for wallets created with one
+    pub keyfile: Option<String>,
+    /// Nonce from `/auth/unlock/challenge`; required unless
+    /// `UNLOCK_CHALLENGE_REQUIRED=false`
+    pub nonce: Option<String>,
+}
+
+/// Issue a single-use nonce for the next unlock request
+pub async fn unlock_challenge(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> Result<Json<wallet_service::UnlockChallengeResponse>, (StatusCode, String)> {
+    let challenge = wallet_service::create_unlock_challenge(&state, &addr.ip().to_string())
+        .await
+        .map_err(|e| match e {
+            wallet_service::WalletServiceError::TooManyChallenges => {
+                (StatusCode::TOO_MANY_REQUESTS, e.to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    Ok(Json(challenge))
 }
 
 /// Unlock wallet
@@ -41,13 +72,25 @@ pub async fn unlock(
     State(state): State<Arc<AppState>>,
     Json(request): Json<UnlockRequest>,
 ) -> Result<Json<StatusResponse>, (StatusCode, String)> {
-    wallet_service::unlock_wallet(&state, &request.password)
-        .await
-        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+    let keyfile = decode_keyfile(request.keyfile.as_deref())?;
+    wallet_service::unlock_with_challenge(
+        &state,
+        request.nonce.as_deref(),
+        &request.password,
+        keyfile.as_deref().map(Vec::as_slice),
+    )
+    .await
+    .map_err(|e| match e {
+        wallet_service::WalletServiceError::DatabaseError(_) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+        _ => (StatusCode::UNAUTHORIZED, e.to_string()),
+    })?;
 
     Ok(Json(StatusResponse {
         has_wallet: true,
         is_unlocked: true,
+        keyfile_required: keyfile.is_some(),
     }))
 }
 
@@ -58,13 +101,63 @@ pub async fn lock(State(state): State<Arc<AppState>>) -> Json<StatusResponse> {
     Json(StatusResponse {
         has_wallet: true,
         is_unlocked: false,
+        keyfile_required: wallet_service::keyfile_required(&state).await,
     })
 }
 
+/// Encryption password change request
+#[derive(Debug, Deserialize)]
+pub struct ChangeEncryptionPasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+    /// Base64 keyfile contents, for wallets created with one
+    pub keyfile: Option<String>,
+}
+
+/// Re-encrypt the wallet seed under a new password; the wallet is locked afterwards
+pub async fn change_encryption_password(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ChangeEncryptionPasswordRequest>,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    if request.new_password.len() < 8 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "New password must be at least 8 characters".to_string(),
+        ));
+    }
+
+    let keyfile = decode_keyfile(request.keyfile.as_deref())?;
+    wallet_service::change_encryption_password(
+        &state,
+        &claims.sub,
+        &request.current_password,
+        &request.new_password,
+        keyfile.as_deref().map(Vec::as_slice),
+    )
+    .await
+    .map_err(|e| match e {
+        wallet_service::WalletServiceError::NoWalletFound => (StatusCode::NOT_FOUND, e.to_string()),
+        wallet_service::WalletServiceError::DatabaseError(_) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        }
+        _ => (StatusCode::UNAUTHORIZED, e.to_string()),
+    })?;
+
+    Ok(Json(StatusResponse {
+        has_wallet: true,
+        is_unlocked: false,
+        keyfile_required: keyfile.is_some(),
+    }))
+}
+
 /// Create wallet request
 #[derive(Debug, Deserialize)]
 pub struct CreateWalletRequest {
     pub password: String,
+    /// Base64 keyfile contents. Its hash is mixed into the encryption key and
+    /// the same file must be uploaded at every unlock; it is never stored.
+    pub keyfile: Option<String>,
 }
 
 /// Create wallet response
@@ -79,8 +172,10 @@ pub async fn create_wallet(
     State(state): State<Arc<AppState>>,
     Json(request): Json<CreateWalletRequest>,
 ) -> Result<Json<CreateWalletResponse>, (StatusCode, String)> {
-    let (wallet_id, mnemonic) = wallet_service::create_wallet(&state, &request.password)
-        .await
+    let keyfile = decode_keyfile(request.keyfile.as_deref())?;
+    let (wallet_id, mnemonic) =
+        wallet_service::create_wallet(&state, &request.password, keyfile.as_deref().map(Vec::as_slice))
+            .await
         .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
 
     Ok(Json(CreateWalletResponse { wallet_id, mnemonic }))
@@ -91,6 +186,8 @@ pub async fn create_wallet(
 pub struct ImportWalletRequest {
     pub mnemonic: String,
     pub password: String,
+    /// Optional keyfile second factor, as for wallet creation
+    pub keyfile: Option<String>,
 }
 
 /// Import wallet response
@@ -104,20 +201,59 @@ pub async fn import_wallet(
     State(state): State<Arc<AppState>>,
     Json(request): Json<ImportWalletRequest>,
 ) -> Result<Json<ImportWalletResponse>, (StatusCode, String)> {
-    let wallet_id = wallet_service::import_wallet(&state, &request.mnemonic, &request.password)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let keyfile = decode_keyfile(request.keyfile.as_deref())?;
+    let wallet_id = wallet_service::import_wallet(
+        &state,
+        &request.mnemonic,
+        &request.password,
+        keyfile.as_deref().map(Vec::as_slice),
+    )
+    .await
+    .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
 
     Ok(Json(ImportWalletResponse { wallet_id }))
 }
 
+/// Validate mnemonic request
+#[derive(Debug, Deserialize)]
+pub struct ValidateMnemonicRequest {
+    pub mnemonic: String,
+}
+
+/// Check a phrase before import: unknown words with suggestions, length and checksum
+pub async fn validate_mnemonic(
+    Json(request): Json<ValidateMnemonicRequest>,
+) -> Json<MnemonicCheck> {
+    Json(check_mnemonic(&request.mnemonic))
+}
+
+/// Wordlist response
+#[derive(Debug, Serialize)]
+pub struct WordlistResponse {
+    pub language: String,
+    pub words: Vec<&'static str>,
+}
+
+/// BIP39 wordlist for a language, e.g. `spanish` or `chinese-simplified`
+pub async fn get_wordlist_for_language(
+    Path(lang): Path<String>,
+) -> Result<Json<WordlistResponse>, (StatusCode, String)> {
+    let language = parse_language(&lang)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unsupported language: {}", lang)))?;
+
+    Ok(Json(WordlistResponse {
+        language: language_name(language).to_string(),
+        words: get_wordlist(language),
+    }))
+}
+
 /// Reset wallet (Debug/Dev only - wipes whole DB)
 pub async fn reset(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<StatusResponse>, (StatusCode, String)> {
     tracing::info!("Resetting wallet database...");
 
-    state.db.reset_database().await.map_err(|e| {
+    state.db.reset_database(&current_tenant_id()).await.map_err(|e| {
         tracing::error!("Failed to reset database: {}", e);
         (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
     })?;
@@ -130,9 +266,19 @@ pub async fn reset(
     Ok(Json(StatusResponse {
         has_wallet: false,
         is_unlocked: false,
+        keyfile_required: false,
     }))
 }
 
+fn decode_keyfile(
+    encoded: Option<&str>,
+) -> Result<Option<Zeroizing<Vec<u8>>>, (StatusCode, String)> {
+    encoded
+        .map(wallet_service::decode_keyfile)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
 /// CSRF Token response
 #[derive(Debug, Serialize)]
 pub struct CsrfResponse {
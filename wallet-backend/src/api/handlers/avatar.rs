@@ -0,0 +1,49 @@
+//! Avatar handlers
+
+use axum::{
+    extract::{Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::services::avatar_service::{self, AvatarError};
+
+#[derive(Debug, Deserialize)]
+pub struct AvatarQuery {
+    /// Pixels per side
+    pub size: Option<u32>,
+}
+
+/// Identicon SVG for an address. The image never changes for a given
+/// address, so clients and proxies may cache it indefinitely.
+pub async fn get_avatar(
+    Path((chain, address)): Path<(String, String)>,
+    Query(query): Query<AvatarQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let avatar = avatar_service::generate(&chain, &address, query.size).map_err(|e| match e {
+        AvatarError::UnsupportedChain(_)
+        | AvatarError::InvalidAddress(_)
+        | AvatarError::InvalidSize => (StatusCode::BAD_REQUEST, e.to_string()),
+    })?;
+
+    let cache = [
+        (header::CACHE_CONTROL, "public, max-age=31536000, immutable".to_string()),
+        (header::ETAG, avatar.etag.clone()),
+    ];
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim() == avatar.etag));
+    if unchanged {
+        return Ok((StatusCode::NOT_MODIFIED, cache).into_response());
+    }
+
+    Ok((
+        cache,
+        [(header::CONTENT_TYPE, "image/svg+xml")],
+        avatar.svg,
+    )
+        .into_response())
+}
@@ -3,34 +3,88 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    Json,
+    Extension, Json,
 };
 
+use crate::services::balance_service::{self, AccountBalancesResponse, BalanceServiceError};
+use super::user_tokens;
+use crate::services::token_service::{self, TokenViewQuery};
 use crate::services::transaction_service::{self, BalanceResponse, TokenBalanceResponse};
+use crate::services::user_service::Claims;
 use crate::AppState;
 
 /// Get balance for address
+///
+/// Signed-in users get their custom tokens merged in and hidden ones removed.
+/// Tokens are then sorted and small balances folded as `sort`,
+/// `small_balance_threshold` and `currency` ask, or else as the user's token
+/// view settings do.
 pub async fn get_balance(
+    claims: Option<Extension<Claims>>,
     State(state): State<Arc<AppState>>,
     Path((chain, address)): Path<(String, String)>,
+    Query(view): Query<TokenViewQuery>,
 ) -> Result<Json<BalanceResponse>, (StatusCode, String)> {
-    let balance = transaction_service::get_balance(&state, &chain, &address)
+    let mut balance = transaction_service::get_balance(&state, &chain, &address)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    let user_id = claims.as_ref().map(|Extension(claims)| claims.sub.as_str());
+    if let Some(user_id) = user_id {
+        balance.tokens =
+            token_service::merge_user_tokens(&state, user_id, &chain, &address, balance.tokens)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    token_service::apply_token_view(&state, user_id, &mut balance, &view)
+        .await
+        .map_err(user_tokens::error_status)?;
+
     Ok(Json(balance))
 }
 
 /// Get token balances for address
+///
+/// Sorted like the balance, but small balances stay listed: a bare list has
+/// nowhere to put the others bucket.
 pub async fn get_tokens(
-    State(state): State<Arc<AppState>>,
-    Path((chain, address)): Path<(String, String)>,
+    claims: Option<Extension<Claims>>,
+    state: State<Arc<AppState>>,
+    path: Path<(String, String)>,
+    Query(view): Query<TokenViewQuery>,
 ) -> Result<Json<Vec<TokenBalanceResponse>>, (StatusCode, String)> {
-    let balance = transaction_service::get_balance(&state, &chain, &address)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let view = TokenViewQuery {
+        small_balance_threshold: Some("0".to_string()),
+        ..view
+    };
+    let Json(balance) = get_balance(claims, state, path, Query(view)).await?;
 
     Ok(Json(balance.tokens))
 }
+
+/// Balances of every account, read concurrently
+///
+/// Accounts that fail or miss the deadline carry an `error` instead of a
+/// `balance`; the others are still returned. Token lists are sorted and
+/// folded as for a single balance.
+pub async fn get_account_balances(
+    claims: Option<Extension<Claims>>,
+    State(state): State<Arc<AppState>>,
+    Query(view): Query<TokenViewQuery>,
+) -> Result<Json<AccountBalancesResponse>, (StatusCode, String)> {
+    view.check().map_err(user_tokens::error_status)?;
+    let user_id = claims.as_ref().map(|Extension(claims)| claims.sub.as_str());
+    let balances = balance_service::get_account_balances(&state, user_id, view)
+        .await
+        .map_err(|e| {
+            let status = match e {
+                BalanceServiceError::NoWalletFound => StatusCode::NOT_FOUND,
+                BalanceServiceError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, e.to_string())
+        })?;
+
+    Ok(Json(balances))
+}
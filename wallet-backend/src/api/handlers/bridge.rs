@@ -0,0 +1,82 @@
+//! L2 bridge handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::chains::ChainClientError;
+use crate::services::bridge_service::{
+    self, BridgeDepositRequest, BridgeServiceError, BridgeTransferResponse, BridgeTransfersQuery,
+    L2NetworkResponse,
+};
+use crate::services::wallet_service::WalletServiceError;
+use crate::AppState;
+
+/// L2s with an official bridge, their deposit windows and challenge periods
+pub async fn list_networks(State(state): State<Arc<AppState>>) -> Json<Vec<L2NetworkResponse>> {
+    Json(bridge_service::list_networks(&state))
+}
+
+/// Deposit ETH from an L1 account into an L2's bridge
+pub async fn deposit(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BridgeDepositRequest>,
+) -> Result<Json<BridgeTransferResponse>, Response> {
+    let transfer = bridge_service::deposit(&state, request)
+        .await
+        .map_err(bridge_error)?;
+
+    Ok(Json(transfer))
+}
+
+/// Bridge deposits, newest first
+pub async fn list_transfers(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BridgeTransfersQuery>,
+) -> Result<Json<Vec<BridgeTransferResponse>>, Response> {
+    let transfers = bridge_service::list_transfers(&state, query)
+        .await
+        .map_err(bridge_error)?;
+
+    Ok(Json(transfers))
+}
+
+/// One bridge deposit and its status
+pub async fn get_transfer(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<BridgeTransferResponse>, Response> {
+    let transfer = bridge_service::get_transfer(&state, &id)
+        .await
+        .map_err(bridge_error)?;
+
+    Ok(Json(transfer))
+}
+
+/// Sends switched off by a flag get its structured 503
+fn bridge_error(e: BridgeServiceError) -> Response {
+    let status = match e {
+        BridgeServiceError::FeatureDisabled(disabled) => return disabled.into_response(),
+        BridgeServiceError::InvalidChain(_)
+        | BridgeServiceError::UnknownNetwork(_)
+        | BridgeServiceError::InvalidAmount(_)
+        | BridgeServiceError::InvalidWebhook(_)
+        | BridgeServiceError::Chain(ChainClientError::InvalidAddress(_))
+        | BridgeServiceError::Chain(ChainClientError::InvalidAmount(_)) => StatusCode::BAD_REQUEST,
+        BridgeServiceError::NotFound => StatusCode::NOT_FOUND,
+        BridgeServiceError::WalletError(WalletServiceError::WalletLocked) => StatusCode::UNAUTHORIZED,
+        BridgeServiceError::Chain(ChainClientError::InsufficientBalance { .. }) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        BridgeServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        BridgeServiceError::WalletError(_) | BridgeServiceError::Database(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string()).into_response()
+}
@@ -0,0 +1,76 @@
+//! Savings bucket handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::services::bucket_service::{
+    self, BucketError, BucketSummary, BucketTransferRequest, CreateBucketRequest,
+};
+use crate::AppState;
+
+/// An account's buckets against its live balance
+pub async fn list_buckets(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<String>,
+) -> Result<Json<BucketSummary>, (StatusCode, String)> {
+    let summary = bucket_service::list_buckets(&state, &account_id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(summary))
+}
+
+/// Add a bucket, optionally funded from the unallocated balance
+pub async fn create_bucket(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<String>,
+    Json(request): Json<CreateBucketRequest>,
+) -> Result<Json<BucketSummary>, (StatusCode, String)> {
+    let summary = bucket_service::create_bucket(&state, &account_id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(summary))
+}
+
+/// Move funds between buckets or the unallocated balance
+pub async fn transfer(
+    State(state): State<Arc<AppState>>,
+    Path(account_id): Path<String>,
+    Json(request): Json<BucketTransferRequest>,
+) -> Result<Json<BucketSummary>, (StatusCode, String)> {
+    let summary = bucket_service::transfer(&state, &account_id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(summary))
+}
+
+/// Delete a bucket, releasing what it held
+pub async fn delete_bucket(
+    State(state): State<Arc<AppState>>,
+    Path((account_id, bucket_id)): Path<(String, String)>,
+) -> Result<Json<BucketSummary>, (StatusCode, String)> {
+    let summary = bucket_service::delete_bucket(&state, &account_id, &bucket_id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(summary))
+}
+
+fn error_status(e: BucketError) -> (StatusCode, String) {
+    let status = match e {
+        BucketError::InvalidBucket(_) | BucketError::InvalidAmount(_) => StatusCode::BAD_REQUEST,
+        BucketError::AccountNotFound | BucketError::NotFound => StatusCode::NOT_FOUND,
+        BucketError::Duplicate(_) | BucketError::Conflict => StatusCode::CONFLICT,
+        BucketError::InsufficientFunds(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        BucketError::Chain(_) => StatusCode::BAD_GATEWAY,
+        BucketError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,126 @@
+//! Cold signing handlers (unsigned export, signature import)
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use crate::api::handlers::contacts::QrRender;
+use crate::chains::ChainClientError;
+use crate::services::cold_signing_service::{
+    self, BuildColdTransferRequest, ColdSignatureRequest, ColdSigningServiceError,
+    ColdTransactionResponse,
+};
+use crate::services::qr_service::{self, QrError, QrFormat};
+use crate::services::user_service::Claims;
+use crate::AppState;
+
+/// Build an unsigned transfer for an offline signer
+pub async fn build_transfer(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BuildColdTransferRequest>,
+) -> Result<(StatusCode, Json<ColdTransactionResponse>), Response> {
+    let transfer = cold_signing_service::build_transfer(&state, &claims.sub, request)
+        .await
+        .map_err(cold_signing_error)?;
+
+    Ok((StatusCode::CREATED, Json(transfer)))
+}
+
+/// The user's cold transfers, newest first
+pub async fn list_transfers(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ColdTransactionResponse>>, Response> {
+    let transfers = cold_signing_service::list_transfers(&state, &claims.sub)
+        .await
+        .map_err(cold_signing_error)?;
+
+    Ok(Json(transfers))
+}
+
+pub async fn get_transfer(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ColdTransactionResponse>, Response> {
+    let transfer = cold_signing_service::get_transfer(&state, &claims.sub, &id)
+        .await
+        .map_err(cold_signing_error)?;
+
+    Ok(Json(transfer))
+}
+
+/// One part of the export as a QR code (SVG by default, or `format=png`)
+pub async fn qr_part(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path((id, part)): Path<(String, usize)>,
+    Query(render): Query<QrRender>,
+) -> Result<Response, Response> {
+    let data = cold_signing_service::qr_part(&state, &claims.sub, &id, part)
+        .await
+        .map_err(cold_signing_error)?;
+    let size = render.size.unwrap_or(qr_service::DEFAULT_SIZE);
+
+    Ok(match render.format {
+        QrFormat::Png => {
+            let png = qr_service::render_png(&data, size).map_err(qr_error)?;
+            ([(header::CONTENT_TYPE, "image/png")], png).into_response()
+        }
+        QrFormat::Svg | QrFormat::Json => {
+            let svg = qr_service::render_svg(&data, size).map_err(qr_error)?;
+            ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
+        }
+    })
+}
+
+/// Import the offline signature and broadcast the signed transfer
+pub async fn submit_signature(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<ColdSignatureRequest>,
+) -> Result<Json<ColdTransactionResponse>, Response> {
+    let transfer = cold_signing_service::submit_signature(&state, &claims.sub, &id, request)
+        .await
+        .map_err(cold_signing_error)?;
+
+    Ok(Json(transfer))
+}
+
+/// Sends switched off by a flag get its structured 503
+fn cold_signing_error(e: ColdSigningServiceError) -> Response {
+    let status = match e {
+        ColdSigningServiceError::FeatureDisabled(disabled) => return disabled.into_response(),
+        ColdSigningServiceError::InvalidChain(_)
+        | ColdSigningServiceError::InvalidAddress(_)
+        | ColdSigningServiceError::InvalidAmount(_)
+        | ColdSigningServiceError::InvalidSignature(_)
+        | ColdSigningServiceError::Chain(ChainClientError::InvalidAddress(_))
+        | ColdSigningServiceError::Chain(ChainClientError::InvalidAmount(_)) => {
+            StatusCode::BAD_REQUEST
+        }
+        ColdSigningServiceError::NotFound => StatusCode::NOT_FOUND,
+        ColdSigningServiceError::AlreadyBroadcast => StatusCode::CONFLICT,
+        ColdSigningServiceError::Chain(ChainClientError::InsufficientBalance { .. }) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        ColdSigningServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        ColdSigningServiceError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string()).into_response()
+}
+
+fn qr_error(e: QrError) -> Response {
+    let status = match e {
+        QrError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string()).into_response()
+}
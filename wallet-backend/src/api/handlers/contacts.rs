@@ -3,24 +3,35 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use base64::{Engine, engine::general_purpose::STANDARD};
-use qrcode::{QrCode, render::svg};
 use serde::{Deserialize, Serialize};
 
+use crate::api::dry_run::DryRunQuery;
+use crate::api::fields::FieldsQuery;
+use crate::api::handlers::transaction;
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::services::contact_service::{
+    self, ContactSendRequest, ContactServiceError, DuplicateContacts,
+};
+use crate::services::identity_service::{self, IdentityServiceError};
+use crate::services::qr_service::{self, PaymentRequest, QrError, QrFormat, ScannedPayload};
+use crate::services::user_service::Claims;
 use crate::storage::models::{ContactResponse, ContactRow};
 use crate::AppState;
 
 /// List all contacts
 pub async fn list_contacts(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Vec<ContactResponse>>, (StatusCode, String)> {
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let wallet = state
         .db
-        .get_primary_wallet()
+        .get_primary_wallet(&current_tenant_id())
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "No wallet found".to_string()))?;
@@ -31,7 +42,8 @@ pub async fn list_contacts(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok(Json(contacts.into_iter().map(ContactResponse::from).collect()))
+    let contacts: Vec<ContactResponse> = contacts.into_iter().map(ContactResponse::from).collect();
+    Ok(Json(fields.select(&contacts)))
 }
 
 /// Create contact request
@@ -41,6 +53,10 @@ pub struct CreateContactRequest {
     pub chain: String,
     pub address: String,
     pub notes: Option<String>,
+    /// Token sends to the contact default to
+    pub default_token_address: Option<String>,
+    /// Amount sends to the contact default to (coin, token or fiat)
+    pub default_amount: Option<String>,
 }
 
 /// Create new contact
@@ -50,18 +66,22 @@ pub async fn create_contact(
 ) -> Result<Json<ContactResponse>, (StatusCode, String)> {
     let wallet = state
         .db
-        .get_primary_wallet()
+        .get_primary_wallet(&current_tenant_id())
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "No wallet found".to_string()))?;
 
-    let contact = ContactRow::new(
-        wallet.id,
-        request.name,
-        request.chain.to_lowercase(),
-        request.address,
-        request.notes,
-    );
+    let chain = request.chain.to_lowercase();
+    let address = contact_service::check_address(&state, &wallet.id, &chain, &request.address, None)
+        .await
+        .map_err(contact_error)?;
+    let mut contact = ContactRow::new(wallet.id, request.name, chain, address, request.notes);
+    (contact.default_token_address, contact.default_amount) = contact_service::check_defaults(
+        &contact,
+        request.default_token_address,
+        request.default_amount,
+    )
+    .map_err(contact_error)?;
 
     state
         .db
@@ -69,6 +89,7 @@ pub async fn create_contact(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    let contact = identity_service::resolve_new_contact(&state, contact).await;
     Ok(Json(ContactResponse::from(contact)))
 }
 
@@ -86,11 +107,16 @@ pub async fn get_contact(
     Ok(Json(ContactResponse::from(contact)))
 }
 
-/// Update contact request
+/// Update contact request; the address and send defaults are left
+/// unchanged when omitted, and defaults are cleared with an empty string
 #[derive(Debug, Deserialize)]
 pub struct UpdateContactRequest {
     pub name: String,
     pub notes: Option<String>,
+    /// New address on the contact's chain
+    pub address: Option<String>,
+    pub default_token_address: Option<String>,
+    pub default_amount: Option<String>,
 }
 
 /// Update contact
@@ -99,21 +125,49 @@ pub async fn update_contact(
     Path(id): Path<String>,
     Json(request): Json<UpdateContactRequest>,
 ) -> Result<Json<ContactResponse>, (StatusCode, String)> {
+    let readdressed = match &request.address {
+        Some(address) => contact_service::set_address(&state, &id, address)
+            .await
+            .map_err(contact_error)?,
+        None => None,
+    };
+
     state
         .db
         .update_contact(&id, &request.name, request.notes.as_deref())
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let contact = state
-        .db
-        .get_contact(&id)
-        .await
-        .map_err(|_| (StatusCode::NOT_FOUND, "Contact not found".to_string()))?;
+    let contact = contact_service::set_defaults(
+        &state,
+        &id,
+        request.default_token_address,
+        request.default_amount,
+    )
+    .await
+    .map_err(contact_error)?;
 
+    // Look up the identity of a new address now rather than on the next
+    // background refresh
+    let contact = match readdressed {
+        Some(_) => identity_service::resolve_new_contact(&state, contact).await,
+        None => contact,
+    };
     Ok(Json(ContactResponse::from(contact)))
 }
 
+/// Contacts saved more than once under the same address, with a suggested
+/// merge for each
+pub async fn list_duplicates(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DuplicateContacts>>, (StatusCode, String)> {
+    let duplicates = contact_service::find_duplicates(&state)
+        .await
+        .map_err(contact_error)?;
+
+    Ok(Json(duplicates))
+}
+
 /// Delete contact
 pub async fn delete_contact(
     State(state): State<Arc<AppState>>,
@@ -128,44 +182,179 @@ pub async fn delete_contact(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+/// Send to a contact's stored address, filling the amount and token from
+/// its defaults where the request leaves them out. Otherwise handled like
+/// `/transactions/send`, including large transfer confirmation and
+/// `dry_run`.
+pub async fn send_to_contact(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<DryRunQuery>,
+    Json(request): Json<ContactSendRequest>,
+) -> Result<Response, Response> {
+    let request = contact_service::send_request(&state, &id, request)
+        .await
+        .map_err(|e| contact_error(e).into_response())?;
+
+    transaction::send(Extension(claims), State(state), Query(query), Json(request)).await
+}
+
+fn contact_error(e: ContactServiceError) -> (StatusCode, String) {
+    let status = match e {
+        ContactServiceError::NotFound => StatusCode::NOT_FOUND,
+        ContactServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        ContactServiceError::AddressMismatch { .. } | ContactServiceError::Duplicate { .. } => {
+            StatusCode::CONFLICT
+        }
+        ContactServiceError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Re-resolve a contact's ENS / SNS identity
+pub async fn refresh_identity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ContactResponse>, (StatusCode, String)> {
+    let contact = identity_service::refresh_contact_identity(&state, &id)
+        .await
+        .map_err(identity_error)?;
+
+    Ok(Json(ContactResponse::from(contact)))
+}
+
+pub(crate) fn identity_error(e: IdentityServiceError) -> (StatusCode, String) {
+    let status = match e {
+        IdentityServiceError::NotFound => StatusCode::NOT_FOUND,
+        IdentityServiceError::InvalidChain(_) => StatusCode::BAD_REQUEST,
+        IdentityServiceError::Resolver(_) => StatusCode::BAD_GATEWAY,
+        IdentityServiceError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
 /// QR code response
 #[derive(Debug, Serialize)]
 pub struct QrCodeResponse {
     pub chain: String,
     pub address: String,
+    /// Payment URI the code encodes
+    pub uri: String,
     pub qr_svg: String,
     pub qr_data_url: String,
 }
 
-/// Generate QR code for an address
+/// Payment details and rendering options for an address QR code
+#[derive(Debug, Deserialize)]
+pub struct QrQuery {
+    pub amount: Option<String>,
+    pub token: Option<String>,
+    pub memo: Option<String>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub format: QrFormat,
+    pub size: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QrRender {
+    #[serde(default)]
+    pub format: QrFormat,
+    /// Minimum pixels per side
+    pub size: Option<u32>,
+}
+
+/// Generate QR code for an address, optionally requesting a payment
 pub async fn generate_qr(
+    State(state): State<Arc<AppState>>,
     Path((chain, address)): Path<(String, String)>,
-) -> Result<Json<QrCodeResponse>, (StatusCode, String)> {
-    // Create payment URI based on chain
-    let uri = match chain.to_lowercase().as_str() {
-        "solana" => format!("solana:{}", address),
-        "ethereum" => format!("ethereum:{}", address),
-        _ => address.clone(),
+    Query(query): Query<QrQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let request = PaymentRequest {
+        chain,
+        recipient: address,
+        amount: query.amount,
+        token: query.token,
+        memo: query.memo,
+        label: query.label,
+        message: query.message,
+        references: query.reference.into_iter().collect(),
+    };
+    let render = QrRender {
+        format: query.format,
+        size: query.size,
     };
+    qr_response(&state, request, render).await
+}
 
-    // Generate QR code
-    let code = QrCode::new(uri.as_bytes())
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+/// Generate QR code for a payment request
+pub async fn payment_request_qr(
+    State(state): State<Arc<AppState>>,
+    Query(render): Query<QrRender>,
+    Json(request): Json<PaymentRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    qr_response(&state, request, render).await
+}
 
-    // Render as SVG
-    let svg_string = code.render::<svg::Color>()
-        .min_dimensions(200, 200)
-        .max_dimensions(300, 300)
-        .build();
+/// Scanned QR code contents
+#[derive(Debug, Deserialize)]
+pub struct ParseQrRequest {
+    pub payload: String,
+}
 
-    // Also create a data URL for embedding
-    let svg_base64 = STANDARD.encode(svg_string.as_bytes());
-    let data_url = format!("data:image/svg+xml;base64,{}", svg_base64);
+/// Parse a scanned QR code into send form fields
+pub async fn parse_qr(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ParseQrRequest>,
+) -> Result<Json<ScannedPayload>, (StatusCode, String)> {
+    let scanned = qr_service::parse_payload(&state, &request.payload)
+        .await
+        .map_err(qr_error)?;
 
-    Ok(Json(QrCodeResponse {
-        chain,
-        address,
-        qr_svg: svg_string,
-        qr_data_url: data_url,
-    }))
+    Ok(Json(scanned))
+}
+
+async fn qr_response(
+    state: &Arc<AppState>,
+    request: PaymentRequest,
+    render: QrRender,
+) -> Result<Response, (StatusCode, String)> {
+    let uri = qr_service::payment_uri(state, &request).await.map_err(qr_error)?;
+    let size = render.size.unwrap_or(qr_service::DEFAULT_SIZE);
+
+    Ok(match render.format {
+        QrFormat::Png => {
+            let png = qr_service::render_png(&uri, size).map_err(qr_error)?;
+            ([(header::CONTENT_TYPE, "image/png")], png).into_response()
+        }
+        QrFormat::Svg => {
+            let svg = qr_service::render_svg(&uri, size).map_err(qr_error)?;
+            ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
+        }
+        QrFormat::Json => {
+            let svg = qr_service::render_svg(&uri, size).map_err(qr_error)?;
+            // Also create a data URL for embedding
+            let data_url = format!("data:image/svg+xml;base64,{}", STANDARD.encode(svg.as_bytes()));
+            Json(QrCodeResponse {
+                chain: request.chain,
+                address: request.recipient,
+                uri,
+                qr_svg: svg,
+                qr_data_url: data_url,
+            })
+            .into_response()
+        }
+    })
+}
+
+fn qr_error(e: QrError) -> (StatusCode, String) {
+    let status = match e {
+        QrError::InvalidChain(_) | QrError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        QrError::Chain(_) => StatusCode::BAD_GATEWAY,
+        QrError::Encoding(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
 }
@@ -0,0 +1,55 @@
+//! CORS origin administration handlers
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::services::cors_service::{
+    self, CorsOriginRequest, CorsOriginResponse, CorsServiceError,
+};
+use crate::AppState;
+
+/// Every allowed origin, configured and added
+pub async fn list_origins(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<CorsOriginResponse>>, (StatusCode, String)> {
+    let origins = cors_service::list_origins(&state)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(origins))
+}
+
+/// Allow an origin without a restart
+pub async fn add_origin(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CorsOriginRequest>,
+) -> Result<(StatusCode, Json<CorsOriginResponse>), (StatusCode, String)> {
+    let origin = cors_service::add_origin(&state, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok((StatusCode::CREATED, Json(origin)))
+}
+
+/// Remove an origin added through the API
+pub async fn remove_origin(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CorsOriginRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    cors_service::remove_origin(&state, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn error_status(e: CorsServiceError) -> (StatusCode, String) {
+    let status = match e {
+        CorsServiceError::InvalidOrigin(_) => StatusCode::BAD_REQUEST,
+        CorsServiceError::AlreadyAllowed(_) => StatusCode::CONFLICT,
+        CorsServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+        CorsServiceError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
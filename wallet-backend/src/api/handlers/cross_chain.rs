@@ -0,0 +1,95 @@
+//! Cross-chain swap handlers (LI.FI routing)
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::chains::ChainClientError;
+use crate::services::cross_chain_service::{
+    self, CrossChainQuoteResponse, CrossChainServiceError, CrossChainSwapRequest,
+    CrossChainSwapResponse, CrossChainSwapsQuery,
+};
+use crate::services::wallet_service::WalletServiceError;
+use crate::AppState;
+
+/// Quote the best route between two accounts on different chains
+pub async fn get_quote(
+    State(state): State<Arc<AppState>>,
+    Query(request): Query<CrossChainSwapRequest>,
+) -> Result<Json<CrossChainQuoteResponse>, Response> {
+    let quote = cross_chain_service::quote(&state, request)
+        .await
+        .map_err(cross_chain_error)?;
+
+    Ok(Json(quote))
+}
+
+/// Send the source transaction of a freshly quoted route
+pub async fn execute(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CrossChainSwapRequest>,
+) -> Result<Json<CrossChainSwapResponse>, Response> {
+    let swap = cross_chain_service::execute(&state, request)
+        .await
+        .map_err(cross_chain_error)?;
+
+    Ok(Json(swap))
+}
+
+/// Cross-chain swaps, newest first
+pub async fn list_swaps(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CrossChainSwapsQuery>,
+) -> Result<Json<Vec<CrossChainSwapResponse>>, Response> {
+    let swaps = cross_chain_service::list_swaps(&state, query)
+        .await
+        .map_err(cross_chain_error)?;
+
+    Ok(Json(swaps))
+}
+
+/// One cross-chain swap by its route ID
+pub async fn get_swap(
+    State(state): State<Arc<AppState>>,
+    Path(route_id): Path<String>,
+) -> Result<Json<CrossChainSwapResponse>, Response> {
+    let swap = cross_chain_service::get_swap(&state, &route_id)
+        .await
+        .map_err(cross_chain_error)?;
+
+    Ok(Json(swap))
+}
+
+/// Swaps or sends switched off by a flag get its structured 503
+fn cross_chain_error(e: CrossChainServiceError) -> Response {
+    let status = match e {
+        CrossChainServiceError::FeatureDisabled(disabled) => return disabled.into_response(),
+        CrossChainServiceError::InvalidRoute(_)
+        | CrossChainServiceError::InvalidAmount(_)
+        | CrossChainServiceError::InvalidToken(_)
+        | CrossChainServiceError::Chain(ChainClientError::InvalidAddress(_))
+        | CrossChainServiceError::Chain(ChainClientError::InvalidAmount(_)) => {
+            StatusCode::BAD_REQUEST
+        }
+        CrossChainServiceError::NotFound => StatusCode::NOT_FOUND,
+        CrossChainServiceError::WalletError(WalletServiceError::WalletLocked) => {
+            StatusCode::UNAUTHORIZED
+        }
+        CrossChainServiceError::NoRoute(_)
+        | CrossChainServiceError::Chain(ChainClientError::InsufficientBalance { .. }) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        CrossChainServiceError::Aggregator(_) | CrossChainServiceError::Chain(_) => {
+            StatusCode::BAD_GATEWAY
+        }
+        CrossChainServiceError::WalletError(_) | CrossChainServiceError::Database(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string()).into_response()
+}
@@ -0,0 +1,76 @@
+//! Admin dashboard: the bundled status page and the report it polls
+//!
+//! The page under `/admin` is static and holds no data; it asks for the
+//! admin token and reads `/api/v2/admin/status` with it, so only the status
+//! route sits behind the admin guard. Like the rest of the admin API, the
+//! page isn't served when no `TENANT_ADMIN_TOKEN` is configured.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use include_dir::{include_dir, Dir};
+
+use crate::services::status_service::{self, StatusError, StatusReport};
+use crate::AppState;
+
+static ASSETS: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/admin");
+
+/// The page only loads its own script and stylesheet and talks to this
+/// origin
+const DASHBOARD_CSP: &str = "default-src 'none'; script-src 'self'; style-src 'self'; \
+                             connect-src 'self'; frame-ancestors 'none'; base-uri 'none'";
+
+/// Health, RPC usage, queue depths and recent audit events
+pub async fn status(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<StatusReport>, (StatusCode, String)> {
+    let report = status_service::report(&state).await.map_err(|e| match e {
+        StatusError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    })?;
+
+    Ok(Json(report))
+}
+
+/// The dashboard page
+pub async fn index(State(state): State<Arc<AppState>>) -> Response {
+    asset(&state, "index.html")
+}
+
+/// A script, stylesheet or other file bundled with the dashboard
+pub async fn file(State(state): State<Arc<AppState>>, Path(path): Path<String>) -> Response {
+    asset(&state, &path)
+}
+
+fn asset(state: &AppState, path: &str) -> Response {
+    if state.config.tenancy.admin_token.is_none() {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    }
+    let Some(file) = ASSETS.get_file(path) else {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type(path)),
+            (header::CACHE_CONTROL, "no-cache"),
+            (header::CONTENT_SECURITY_POLICY, DASHBOARD_CSP),
+        ],
+        file.contents(),
+    )
+        .into_response()
+}
+
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
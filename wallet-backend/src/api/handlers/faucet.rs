@@ -0,0 +1,38 @@
+//! Test-network faucet handler
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::faucet_service::{self, FaucetError, FaucetFunding};
+use crate::services::user_service::Claims;
+use crate::AppState;
+
+/// Fund an address from its chain's test-network faucet
+pub async fn request_funding(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path((chain, address)): Path<(String, String)>,
+) -> Result<Json<FaucetFunding>, (StatusCode, String)> {
+    let funding = faucet_service::request_funding(&state, Some(&claims.sub), &chain, &address)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(funding))
+}
+
+fn error_status(e: FaucetError) -> (StatusCode, String) {
+    let status = match e {
+        FaucetError::Disabled => StatusCode::NOT_FOUND,
+        FaucetError::InvalidChain(_) | FaucetError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+        FaucetError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        FaucetError::LimitReached { .. } => StatusCode::TOO_MANY_REQUESTS,
+        FaucetError::Faucet(_) => StatusCode::BAD_GATEWAY,
+        FaucetError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,47 @@
+//! Feature flag administration handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::services::feature_flag_service::{
+    self, FeatureFlagError, FeatureFlagResponse, SetFlagRequest,
+};
+use crate::AppState;
+
+/// Every flag with its current value
+pub async fn list_flags(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<FeatureFlagResponse>>, (StatusCode, String)> {
+    let flags = feature_flag_service::list_flags(&state)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(flags))
+}
+
+/// Turn a flag on or off, with an optional reason shown to refused clients
+pub async fn set_flag(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(request): Json<SetFlagRequest>,
+) -> Result<Json<FeatureFlagResponse>, (StatusCode, String)> {
+    let flag = feature_flag_service::set_flag(&state, &name, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(flag))
+}
+
+fn error_status(e: FeatureFlagError) -> (StatusCode, String) {
+    let status = match e {
+        FeatureFlagError::UnknownFlag(_) => StatusCode::NOT_FOUND,
+        FeatureFlagError::InvalidReason(_) => StatusCode::BAD_REQUEST,
+        FeatureFlagError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,62 @@
+//! JWT signing key handlers for the admin API
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::services::user_service::UserServiceError;
+use crate::storage::models::JwtKeyInfo;
+use crate::AppState;
+
+/// Keys added so far, newest first
+pub async fn list_keys(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<JwtKeyInfo>>, (StatusCode, String)> {
+    let keys = state
+        .user_service
+        .list_signing_keys()
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(keys))
+}
+
+/// Add a key and sign new tokens with it
+pub async fn add_key(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<JwtKeyInfo>, (StatusCode, String)> {
+    let key = state
+        .user_service
+        .add_signing_key()
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(key))
+}
+
+/// Stop accepting tokens signed with a key
+pub async fn retire_key(
+    State(state): State<Arc<AppState>>,
+    Path(kid): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    state
+        .user_service
+        .retire_signing_key(&kid)
+        .await
+        .map_err(error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn error_status(e: UserServiceError) -> (StatusCode, String) {
+    let status = match e {
+        UserServiceError::SigningKeyNotFound(_) => StatusCode::NOT_FOUND,
+        UserServiceError::LastSigningKey(_) => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
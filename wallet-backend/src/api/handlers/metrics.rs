@@ -0,0 +1,59 @@
+//! Operational metrics: the Prometheus scrape endpoint, RPC usage summary
+//! and database schema status
+
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+
+use serde::Serialize;
+
+use crate::chains::metered::RpcUsageReport;
+use crate::storage::schema::{SchemaStatus, MIGRATOR};
+use crate::AppState;
+
+/// All metrics in the Prometheus text format
+pub async fn metrics(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let encode_error = |e: prometheus::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    let mut body = state.rpc_metrics.encode().map_err(encode_error)?;
+    body.push_str(&state.maintenance_metrics.encode().map_err(encode_error)?);
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
+
+/// Calls, failure rates and latency per RPC provider and method since startup
+pub async fn rpc_usage(State(state): State<Arc<AppState>>) -> Json<RpcUsageReport> {
+    Json(state.rpc_metrics.usage())
+}
+
+/// Schema status response
+#[derive(Debug, Serialize)]
+pub struct SchemaResponse {
+    #[serde(flatten)]
+    pub status: SchemaStatus,
+    /// Writes are refused because the schema is ahead of this binary
+    pub safe_mode: bool,
+}
+
+/// Applied schema version against this binary's, with pending and unknown
+/// migrations
+pub async fn schema(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SchemaResponse>, (StatusCode, String)> {
+    let status = state
+        .db
+        .schema_status(&MIGRATOR)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(SchemaResponse {
+        status,
+        safe_mode: state.safe_mode,
+    }))
+}
@@ -1,11 +1,40 @@
 //! API handlers
 
 pub mod accounts;
+pub mod alerts;
+pub mod analytics;
 pub mod auth;
+pub mod avatar;
 pub mod balance;
+pub mod bridge;
+pub mod buckets;
+pub mod cold_signing;
 pub mod contacts;
+pub mod cors_origins;
+pub mod cross_chain;
+pub mod dashboard;
+pub mod faucet;
+pub mod feature_flags;
+pub mod jwt_keys;
+pub mod metrics;
 pub mod multisig;
+pub mod names;
+pub mod notes;
 pub mod nft;
+pub mod reconciliation;
+pub mod rent;
+pub mod security;
+pub mod session_keys;
+pub mod shares;
+pub mod staking;
 pub mod swap;
+pub mod sync;
+pub mod templates;
+pub mod tenants;
+pub mod token_list;
 pub mod transaction;
 pub mod user_auth;
+pub mod user_tokens;
+pub mod v2;
+pub mod watchlist;
+pub mod webhooks;
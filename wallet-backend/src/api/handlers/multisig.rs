@@ -3,28 +3,36 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::Deserialize;
+use serde_json::Value;
+
+use crate::api::dry_run::DryRunQuery;
+use crate::api::fields::FieldsQuery;
 
 use crate::services::multisig_service::{
-    self, CreateMultisigRequest, ProposeTransactionRequest,
+    self, CreateMultisigRequest, DeploymentEstimate, EstimateMultisigRequest, MultisigServiceError,
+    ProposeTransactionRequest, SigningPayload,
 };
 use crate::services::wallet_service;
 use crate::storage::models::{MultisigTransactionResponse, MultisigWalletResponse};
 use crate::AppState;
 
-/// List all multi-sig wallets
+/// List all multi-sig wallets; with a sparse fieldset, owners are an
+/// expansion (`?include=owners`)
 pub async fn list_multisigs(
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Vec<MultisigWalletResponse>>, (StatusCode, String)> {
-    let multisigs = multisig_service::list_multisigs(&state)
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let multisigs = multisig_service::list_multisigs(&state, fields.wants("owners"))
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok(Json(multisigs))
+    Ok(Json(fields.select(&multisigs)))
 }
 
 /// Create multi-sig wallet
@@ -39,17 +47,42 @@ pub async fn create_multisig(
 
     let multisig = multisig_service::create_multisig(&state, request)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(deployment_error)?;
 
     Ok(Json(multisig))
 }
 
+/// Estimate what deploying a multi-sig costs and whether the primary
+/// account can sponsor it
+pub async fn estimate_deployment(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<EstimateMultisigRequest>,
+) -> Result<Json<DeploymentEstimate>, (StatusCode, String)> {
+    let estimate = multisig_service::estimate_deployment(&state, request)
+        .await
+        .map_err(deployment_error)?;
+
+    Ok(Json(estimate))
+}
+
+fn deployment_error(e: MultisigServiceError) -> (StatusCode, String) {
+    let status = match e {
+        MultisigServiceError::InvalidChain(_)
+        | MultisigServiceError::InvalidConfig(_)
+        | MultisigServiceError::NoSponsor(_) => StatusCode::BAD_REQUEST,
+        MultisigServiceError::SponsorUnderfunded { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        MultisigServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
 /// Get single multi-sig
 pub async fn get_multisig(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<Json<MultisigWalletResponse>, (StatusCode, String)> {
-    let multisigs = multisig_service::list_multisigs(&state)
+    let multisigs = multisig_service::list_multisigs(&state, true)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -110,32 +143,45 @@ pub struct ExecuteResponse {
     pub signature: String,
 }
 
-/// Execute transaction
+/// Execute transaction. With `dry_run`, only the checks run.
 pub async fn execute_transaction(
     State(state): State<Arc<AppState>>,
     Path((id, tx_id)): Path<(String, String)>,
-) -> Result<Json<ExecuteResponse>, (StatusCode, String)> {
+    Query(query): Query<DryRunQuery>,
+) -> Result<Response, (StatusCode, String)> {
     // Check if unlocked
     if !wallet_service::is_unlocked(&state).await {
         return Err((StatusCode::UNAUTHORIZED, "Wallet is locked".to_string()));
     }
 
+    if query.dry_run {
+        let preview = multisig_service::preview_execute(&state, &id, &tx_id)
+            .await
+            .map_err(execute_error)?;
+        return Ok(Json(preview).into_response());
+    }
+
     let signature = multisig_service::execute_transaction(&state, &id, &tx_id)
         .await
-        .map_err(|e| match e {
-            multisig_service::MultisigServiceError::InsufficientApprovals => {
-                (StatusCode::BAD_REQUEST, "Insufficient approvals".to_string())
-            }
-            multisig_service::MultisigServiceError::NotFound => {
-                (StatusCode::NOT_FOUND, "Multi-sig not found".to_string())
-            }
-            multisig_service::MultisigServiceError::TransactionNotFound => {
-                (StatusCode::NOT_FOUND, "Transaction not found".to_string())
-            }
-            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
-        })?;
+        .map_err(execute_error)?;
+
+    Ok(Json(ExecuteResponse { signature }).into_response())
+}
 
-    Ok(Json(ExecuteResponse { signature }))
+fn execute_error(e: MultisigServiceError) -> (StatusCode, String) {
+    match e {
+        MultisigServiceError::InsufficientApprovals => (
+            StatusCode::BAD_REQUEST,
+            "Insufficient approvals".to_string(),
+        ),
+        MultisigServiceError::NotFound => {
+            (StatusCode::NOT_FOUND, "Multi-sig not found".to_string())
+        }
+        MultisigServiceError::TransactionNotFound => {
+            (StatusCode::NOT_FOUND, "Transaction not found".to_string())
+        }
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
 }
 
 /// Get pending transactions
@@ -149,3 +195,52 @@ pub async fn get_transactions(
 
     Ok(Json(transactions))
 }
+
+/// Export a proposed transaction for owners to sign offline
+pub async fn export_signing_payload(
+    State(state): State<Arc<AppState>>,
+    Path((id, tx_id)): Path<(String, String)>,
+) -> Result<Json<SigningPayload>, (StatusCode, String)> {
+    let payload = multisig_service::export_signing_payload(&state, &id, &tx_id)
+        .await
+        .map_err(signature_error)?;
+
+    Ok(Json(payload))
+}
+
+/// Submit signature request
+#[derive(Debug, Deserialize)]
+pub struct SubmitSignatureRequest {
+    /// Base58 ed25519 signature over the message (Solana) or hex ECDSA
+    /// signature over the Safe transaction hash (Ethereum)
+    pub signature: String,
+}
+
+/// Upload an owner's offline signature; counts as that owner's approval
+pub async fn submit_signature(
+    State(state): State<Arc<AppState>>,
+    Path((id, tx_id)): Path<(String, String)>,
+    Json(request): Json<SubmitSignatureRequest>,
+) -> Result<Json<MultisigTransactionResponse>, (StatusCode, String)> {
+    let tx = multisig_service::submit_signature(&state, &id, &tx_id, &request.signature)
+        .await
+        .map_err(signature_error)?;
+
+    Ok(Json(tx))
+}
+
+fn signature_error(e: MultisigServiceError) -> (StatusCode, String) {
+    let status = match e {
+        MultisigServiceError::NotFound | MultisigServiceError::TransactionNotFound => {
+            StatusCode::NOT_FOUND
+        }
+        MultisigServiceError::InvalidSignature(_)
+        | MultisigServiceError::InvalidTransaction(_)
+        | MultisigServiceError::NotAnOwner(_) => StatusCode::BAD_REQUEST,
+        MultisigServiceError::AlreadyApproved | MultisigServiceError::TransactionClosed(_) => {
+            StatusCode::CONFLICT
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
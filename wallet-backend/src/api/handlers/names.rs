@@ -0,0 +1,97 @@
+//! ENS / SNS name registration handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::chains::{ChainClientError, NameQuote};
+use crate::services::name_service::{self, NameServiceError, RegisterNameRequest};
+use crate::services::wallet_service::WalletServiceError;
+use crate::storage::models::OwnedNameResponse;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct QuoteQuery {
+    pub years: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTargetRequest {
+    pub address: String,
+}
+
+/// Availability, price and renewal cost of a name
+pub async fn quote(
+    State(state): State<Arc<AppState>>,
+    Path((chain, name)): Path<(String, String)>,
+    Query(query): Query<QuoteQuery>,
+) -> Result<Json<NameQuote>, (StatusCode, String)> {
+    let quote = name_service::quote(&state, &chain, &name, query.years)
+        .await
+        .map_err(name_error_status)?;
+
+    Ok(Json(quote))
+}
+
+/// Names registered or committed to by the current tenant's accounts
+pub async fn list_names(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<OwnedNameResponse>>, (StatusCode, String)> {
+    let names = name_service::list(&state).await.map_err(name_error_status)?;
+
+    Ok(Json(names))
+}
+
+/// Register a name to an account. ENS names take two calls: the first
+/// commits, the second registers once the commitment is old enough.
+pub async fn register(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RegisterNameRequest>,
+) -> Result<Json<OwnedNameResponse>, (StatusCode, String)> {
+    let name = name_service::register(&state, request)
+        .await
+        .map_err(name_error_status)?;
+
+    Ok(Json(name))
+}
+
+/// Point a registered name at another address
+pub async fn set_target(
+    State(state): State<Arc<AppState>>,
+    Path((chain, name)): Path<(String, String)>,
+    Json(request): Json<SetTargetRequest>,
+) -> Result<Json<OwnedNameResponse>, (StatusCode, String)> {
+    let name = name_service::set_target(&state, &chain, &name, &request.address)
+        .await
+        .map_err(name_error_status)?;
+
+    Ok(Json(name))
+}
+
+fn name_error_status(e: NameServiceError) -> (StatusCode, String) {
+    let status = match e {
+        NameServiceError::InvalidChain(_)
+        | NameServiceError::InvalidName(_)
+        | NameServiceError::InvalidYears
+        | NameServiceError::Chain(ChainClientError::InvalidAddress(_)) => StatusCode::BAD_REQUEST,
+        NameServiceError::NotFound => StatusCode::NOT_FOUND,
+        NameServiceError::NameTaken(_)
+        | NameServiceError::AlreadyRegistered(_)
+        | NameServiceError::NotReady(_)
+        | NameServiceError::NotRegistered(_) => StatusCode::CONFLICT,
+        NameServiceError::WalletError(WalletServiceError::WalletLocked) => StatusCode::UNAUTHORIZED,
+        NameServiceError::Chain(ChainClientError::InsufficientBalance { .. }) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        NameServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        NameServiceError::WalletError(_) | NameServiceError::Database(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
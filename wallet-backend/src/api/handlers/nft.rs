@@ -3,12 +3,15 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
+use serde_json::Value;
 
-use crate::services::nft_service;
+use crate::api::fields::FieldsQuery;
+
+use crate::services::nft_service::{self, NftRefreshSummary, NftServiceError};
 use crate::storage::models::NftResponse;
 use crate::AppState;
 
@@ -16,12 +19,13 @@ use crate::AppState;
 pub async fn list_nfts(
     State(state): State<Arc<AppState>>,
     Path((chain, address)): Path<(String, String)>,
-) -> Result<Json<Vec<NftResponse>>, (StatusCode, String)> {
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>, (StatusCode, String)> {
     let nfts = nft_service::get_nfts(&state, &chain, &address)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok(Json(nfts))
+    Ok(Json(fields.select(&nfts)))
 }
 
 /// Get single NFT details
@@ -35,3 +39,19 @@ pub async fn get_nft(
 
     Ok(Json(nft))
 }
+
+/// Re-fetch metadata for all cached NFTs of an account
+pub async fn refresh_metadata(
+    State(state): State<Arc<AppState>>,
+    Path((chain, address)): Path<(String, String)>,
+) -> Result<Json<NftRefreshSummary>, (StatusCode, String)> {
+    let summary = nft_service::refresh_metadata(&state, &chain, &address)
+        .await
+        .map_err(|e| match e {
+            NftServiceError::InvalidChain(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+            NftServiceError::NotFound => (StatusCode::NOT_FOUND, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    Ok(Json(summary))
+}
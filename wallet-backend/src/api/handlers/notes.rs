@@ -0,0 +1,97 @@
+//! Encrypted note handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::note_service::{
+    self, CreateNoteRequest, NoteError, NoteQuery, UpdateNoteRequest,
+};
+use crate::services::user_service::Claims;
+use crate::services::wallet_service::WalletServiceError;
+use crate::storage::models::NoteResponse;
+use crate::AppState;
+
+/// List the user's notes, optionally on one target or matching a search
+pub async fn list_notes(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<NoteQuery>,
+) -> Result<Json<Vec<NoteResponse>>, (StatusCode, String)> {
+    let notes = note_service::list_notes(&state, &claims.sub, query)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(notes))
+}
+
+/// Attach a note to an account, contact or transaction
+pub async fn create_note(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateNoteRequest>,
+) -> Result<Json<NoteResponse>, (StatusCode, String)> {
+    let note = note_service::create_note(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(note))
+}
+
+/// Get a note
+pub async fn get_note(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<NoteResponse>, (StatusCode, String)> {
+    let note = note_service::get_note(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(note))
+}
+
+/// Replace a note's body
+pub async fn update_note(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateNoteRequest>,
+) -> Result<Json<NoteResponse>, (StatusCode, String)> {
+    let note = note_service::update_note(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(note))
+}
+
+/// Delete a note
+pub async fn delete_note(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    note_service::delete_note(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+fn error_status(e: NoteError) -> (StatusCode, String) {
+    let status = match e {
+        NoteError::InvalidTarget(_) | NoteError::InvalidNote(_) => StatusCode::BAD_REQUEST,
+        NoteError::NotFound | NoteError::WalletError(WalletServiceError::NoWalletFound) => {
+            StatusCode::NOT_FOUND
+        }
+        NoteError::WalletError(WalletServiceError::WalletLocked) => StatusCode::UNAUTHORIZED,
+        NoteError::Undecryptable | NoteError::WalletError(_) | NoteError::DatabaseError(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,27 @@
+//! Balance reconciliation report handler
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+
+use crate::services::reconciliation_service::{self, ReconciliationError, ReconciliationReport};
+use crate::AppState;
+
+/// The latest reconciliation run with the discrepancies it found
+pub async fn get_report(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ReconciliationReport>, (StatusCode, String)> {
+    let report = reconciliation_service::latest_report(&state)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(report))
+}
+
+fn error_status(e: ReconciliationError) -> (StatusCode, String) {
+    let status = match e {
+        ReconciliationError::NotFound => StatusCode::NOT_FOUND,
+        ReconciliationError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
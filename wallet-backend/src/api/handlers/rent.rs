@@ -0,0 +1,64 @@
+//! Solana rent handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::api::handlers::swap::account_keypair;
+use crate::chains::solana::{
+    reclaim_rent_async, rent_report_async, ReclaimResult, RentReport, TransactionError,
+};
+use crate::AppState;
+
+/// Reclaim rent request
+#[derive(Debug, Deserialize)]
+pub struct ReclaimRentRequest {
+    pub address: String,
+}
+
+/// Rent locked in an address's token, stake and nonce accounts, and what
+/// closing its empty token accounts would return
+pub async fn get_rent_report(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+) -> Result<Json<RentReport>, (StatusCode, String)> {
+    let report = rent_report_async(&state.solana_rpc_url, &address)
+        .await
+        .map_err(|e| (error_status(&e), e.to_string()))?;
+
+    Ok(Json(report))
+}
+
+/// Close every empty token account of one of the wallet's Solana accounts
+pub async fn reclaim_rent(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ReclaimRentRequest>,
+) -> Result<Json<ReclaimResult>, (StatusCode, String)> {
+    let keypair = account_keypair(&state, &request.address).await?;
+
+    let result = reclaim_rent_async(&state.solana_rpc_url, keypair)
+        .await
+        .map_err(|e| (error_status(&e), e.to_string()))?
+        .ok_or((StatusCode::BAD_REQUEST, "No empty token accounts to close".to_string()))?;
+
+    tracing::info!(
+        address = %request.address,
+        closed = result.closed.len(),
+        lamports = result.lamports,
+        "Rent reclaimed"
+    );
+    Ok(Json(result))
+}
+
+fn error_status(e: &TransactionError) -> StatusCode {
+    match e {
+        TransactionError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+        TransactionError::RpcError(_) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
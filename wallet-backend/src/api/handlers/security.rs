@@ -0,0 +1,61 @@
+//! Wallet security status handlers
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Extension, Json};
+
+use crate::services::security_service::{
+    self, BackupChallengeResponse, SecurityServiceError, SecurityStatusResponse,
+    VerifyBackupRequest,
+};
+use crate::services::user_service::Claims;
+use crate::AppState;
+
+/// Get composite wallet security status
+pub async fn get_security_status(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SecurityStatusResponse>, (StatusCode, String)> {
+    let status = security_service::get_security_status(&state, &claims.sub)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(status))
+}
+
+/// Start a backup verification quiz
+pub async fn create_backup_challenge(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<BackupChallengeResponse>, (StatusCode, String)> {
+    let challenge = security_service::create_backup_challenge(&state)
+        .await
+        .map_err(|e| match e {
+            SecurityServiceError::NoWalletFound => (StatusCode::NOT_FOUND, e.to_string()),
+            SecurityServiceError::BackupUnavailable => (StatusCode::CONFLICT, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    Ok(Json(challenge))
+}
+
+/// Answer a backup verification quiz
+pub async fn verify_backup(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<VerifyBackupRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    security_service::verify_backup_challenge(&state, request)
+        .await
+        .map_err(|e| match e {
+            SecurityServiceError::ChallengeNotFound => (StatusCode::NOT_FOUND, e.to_string()),
+            SecurityServiceError::ChallengeExpired => (StatusCode::GONE, e.to_string()),
+            SecurityServiceError::InvalidPassword | SecurityServiceError::Keyfile(_) => {
+                (StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            SecurityServiceError::IncorrectWords | SecurityServiceError::BackupUnavailable => {
+                (StatusCode::BAD_REQUEST, e.to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    Ok(Json(serde_json::json!({ "backup_confirmed": true })))
+}
@@ -0,0 +1,94 @@
+//! dApp session key handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::session_key_service::{
+    self, CreateSessionKeyRequest, SessionKeyError, SessionKeyWithSecret, SessionSendRequest,
+};
+use crate::services::transaction_service::{SendResponse, TransactionServiceError};
+use crate::services::user_service::Claims;
+use crate::services::wallet_service::WalletServiceError;
+use crate::storage::models::SessionKeyResponse;
+use crate::AppState;
+
+/// List the user's session keys, including expired and revoked ones
+pub async fn list_session_keys(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SessionKeyResponse>>, (StatusCode, String)> {
+    let sessions = session_key_service::list_session_keys(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(sessions))
+}
+
+/// Authorize a session key; the key is only returned in this response
+pub async fn create_session_key(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateSessionKeyRequest>,
+) -> Result<Json<SessionKeyWithSecret>, (StatusCode, String)> {
+    let session = session_key_service::create_session_key(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(session))
+}
+
+/// Revoke a session key
+pub async fn revoke_session_key(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    session_key_service::revoke_session_key(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Send within a session's policy; the wallet may be locked
+pub async fn send(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<SessionSendRequest>,
+) -> Result<Json<SendResponse>, (StatusCode, String)> {
+    let result = session_key_service::send_with_session_key(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(result))
+}
+
+fn error_status(e: SessionKeyError) -> (StatusCode, String) {
+    let status = match e {
+        SessionKeyError::InvalidAmount(_)
+        | SessionKeyError::InvalidExpiry(_)
+        | SessionKeyError::InvalidTargets => StatusCode::BAD_REQUEST,
+        SessionKeyError::AccountNotFound | SessionKeyError::NotFound => StatusCode::NOT_FOUND,
+        SessionKeyError::Expired | SessionKeyError::Revoked => StatusCode::GONE,
+        SessionKeyError::InvalidKey | SessionKeyError::PolicyViolation(_) => StatusCode::FORBIDDEN,
+        SessionKeyError::WalletError(WalletServiceError::WalletLocked) => StatusCode::UNAUTHORIZED,
+        SessionKeyError::SendFailed(ref e) => match e {
+            TransactionServiceError::InvalidChain(_)
+            | TransactionServiceError::InvalidAddress(_)
+            | TransactionServiceError::InvalidAmount(_) => StatusCode::BAD_REQUEST,
+            TransactionServiceError::InsufficientBalance { .. }
+            | TransactionServiceError::RentExemption(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        },
+        SessionKeyError::WalletError(_) | SessionKeyError::DatabaseError(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
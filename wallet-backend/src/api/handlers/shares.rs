@@ -0,0 +1,95 @@
+//! Share link handlers
+//!
+//! Links are created, listed and revoked by their owner; the shared views
+//! behind them are reached with the link's token instead of a JWT.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::share_service::{
+    self, CreateShareRequest, ShareError, ShareGrant, SharedAccountView, SharedMultisigView,
+};
+use crate::services::user_service::Claims;
+use crate::storage::models::ShareLinkResponse;
+use crate::AppState;
+
+/// List the user's share links
+pub async fn list_links(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ShareLinkResponse>>, (StatusCode, String)> {
+    let links = share_service::list_links(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(links))
+}
+
+/// Create an expiring read-only link to an account or multi-sig
+pub async fn create_link(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateShareRequest>,
+) -> Result<(StatusCode, Json<ShareLinkResponse>), (StatusCode, String)> {
+    let link = share_service::create_link(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+/// Revoke a share link
+pub async fn revoke_link(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ShareLinkResponse>, (StatusCode, String)> {
+    let link = share_service::revoke_link(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(link))
+}
+
+/// Shared account: balance and latest history
+pub async fn shared_account(
+    Extension(grant): Extension<ShareGrant>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SharedAccountView>, (StatusCode, String)> {
+    let view = share_service::account_view(&state, &grant)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(view))
+}
+
+/// Shared multi-sig: owners and pending proposals
+pub async fn shared_multisig(
+    Extension(grant): Extension<ShareGrant>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SharedMultisigView>, (StatusCode, String)> {
+    let view = share_service::multisig_view(&state, &grant)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(view))
+}
+
+fn error_status(e: ShareError) -> (StatusCode, String) {
+    let status = match e {
+        ShareError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        ShareError::NotFound => StatusCode::NOT_FOUND,
+        ShareError::InvalidToken => StatusCode::UNAUTHORIZED,
+        ShareError::WrongScope => StatusCode::FORBIDDEN,
+        ShareError::Expired | ShareError::Revoked => StatusCode::GONE,
+        ShareError::Disabled => StatusCode::SERVICE_UNAVAILABLE,
+        ShareError::Balance(_) => StatusCode::BAD_GATEWAY,
+        ShareError::Multisig(_) | ShareError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,70 @@
+//! Liquid staking handlers (SPL stake pools)
+
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::chains::ChainClientError;
+use crate::services::staking_service::{
+    self, StakePoolResponse, StakeRequest, StakeResponse, StakingServiceError,
+};
+use crate::services::wallet_service::WalletServiceError;
+use crate::AppState;
+
+/// Known liquid staking tokens with their pools' exchange rates and APYs
+pub async fn list_pools(State(state): State<Arc<AppState>>) -> Json<Vec<StakePoolResponse>> {
+    Json(staking_service::list_pools(&state).await)
+}
+
+/// Deposit SOL into a stake pool for its liquid staking token
+pub async fn stake(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<StakeRequest>,
+) -> Result<Json<StakeResponse>, Response> {
+    let staked = staking_service::stake(&state, request)
+        .await
+        .map_err(staking_error)?;
+
+    Ok(Json(staked))
+}
+
+/// Redeem liquid staking tokens for SOL
+pub async fn unstake(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<StakeRequest>,
+) -> Result<Json<StakeResponse>, Response> {
+    let unstaked = staking_service::unstake(&state, request)
+        .await
+        .map_err(staking_error)?;
+
+    Ok(Json(unstaked))
+}
+
+/// Sends switched off by a flag get its structured 503
+fn staking_error(e: StakingServiceError) -> Response {
+    let status = match e {
+        StakingServiceError::FeatureDisabled(disabled) => return disabled.into_response(),
+        StakingServiceError::InvalidChain(_)
+        | StakingServiceError::UnknownToken(_)
+        | StakingServiceError::Unsupported(_)
+        | StakingServiceError::InvalidAmount(_)
+        | StakingServiceError::Chain(ChainClientError::InvalidAddress(_))
+        | StakingServiceError::Chain(ChainClientError::InvalidAmount(_)) => StatusCode::BAD_REQUEST,
+        StakingServiceError::NotFound => StatusCode::NOT_FOUND,
+        StakingServiceError::WalletError(WalletServiceError::WalletLocked) => StatusCode::UNAUTHORIZED,
+        StakingServiceError::Chain(ChainClientError::InsufficientBalance { .. })
+        | StakingServiceError::Chain(ChainClientError::RentExemption(_)) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        StakingServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        StakingServiceError::WalletError(_) | StakingServiceError::Database(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string()).into_response()
+}
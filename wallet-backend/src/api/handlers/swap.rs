@@ -5,14 +5,23 @@ use std::sync::Arc;
 use axum::{
     extract::{Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::api::dry_run::DryRunQuery;
+use crate::api::error::diagnosed_error;
+use crate::chains::diagnostics;
 use crate::chains::solana::{
-    get_quote as jupiter_get_quote, execute_swap as jupiter_execute_swap,
-    QuoteRequest, QuoteResponse, SolanaKeypair,
+    get_quote as jupiter_get_quote, execute_swap as jupiter_execute_swap, mints, simulate_swap,
+    unwrap_sol_after_async, unwrap_sol_async, wrap_sol_async, JitoBundle, QuoteRequest,
+    QuoteResponse, SolanaKeypair, SubmissionMode, SwapError, SwapSimulation, TransactionError,
+    WrapResult,
 };
+use crate::services::feature_flag_service::{self, FeatureFlag};
+use crate::services::token_list_service;
 use crate::services::wallet_service::{self, get_seed};
 use crate::AppState;
 
@@ -27,8 +36,10 @@ pub struct QuoteQuery {
 
 /// Get swap quote
 pub async fn get_quote(
+    State(state): State<Arc<AppState>>,
     Query(query): Query<QuoteQuery>,
 ) -> Result<Json<QuoteResponse>, (StatusCode, String)> {
+    check_mints(&state, &query.input_mint, &query.output_mint).await?;
     let request = QuoteRequest {
         input_mint: query.input_mint,
         output_mint: query.output_mint,
@@ -48,6 +59,14 @@ pub async fn get_quote(
 pub struct ExecuteSwapRequest {
     pub from_address: String,
     pub quote: QuoteResponse,
+    /// Once the swap confirms, close the wSOL account so SOL output ends up
+    /// as native SOL
+    #[serde(default)]
+    pub unwrap_sol: bool,
+    /// How the swap reaches the network; straight to the RPC node when
+    /// omitted
+    #[serde(default)]
+    pub submission_mode: SubmissionMode,
 }
 
 /// Execute swap response
@@ -56,39 +75,191 @@ pub struct ExecuteSwapResponse {
     pub signature: String,
     pub input_amount: String,
     pub output_amount: String,
+    /// Set when `unwrap_sol` closed a wSOL account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unwrap: Option<WrapResult>,
 }
 
-/// Execute swap
+/// What a swap would do, from a dry run
+#[derive(Debug, Serialize)]
+pub struct SwapPreview {
+    /// Always `true`; nothing was sent
+    pub dry_run: bool,
+    pub input_mint: String,
+    pub output_mint: String,
+    #[serde(flatten)]
+    pub simulation: SwapSimulation,
+    /// Whether SOL output would be unwrapped afterwards
+    pub unwrap_sol: bool,
+    pub submission_mode: SubmissionMode,
+}
+
+/// Execute swap. With `dry_run`, the swap is built, signed and simulated,
+/// but not sent.
 pub async fn execute_swap(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<DryRunQuery>,
     Json(request): Json<ExecuteSwapRequest>,
-) -> Result<Json<ExecuteSwapResponse>, (StatusCode, String)> {
+) -> Result<Response, Response> {
+    feature_flag_service::check(&state, FeatureFlag::Swaps)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    check_mints(&state, &request.quote.input_mint, &request.quote.output_mint)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let keypair = account_keypair(&state, &request.from_address)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    let unwrap_output = request.unwrap_sol && request.quote.output_mint == mints::SOL;
+
+    if query.dry_run {
+        let input_mint = request.quote.input_mint.clone();
+        let output_mint = request.quote.output_mint.clone();
+        let simulation = simulate_swap(&state.solana_rpc_url, &keypair, request.quote)
+            .await
+            .map_err(|e| swap_failure(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+        return Ok(Json(SwapPreview {
+            dry_run: true,
+            input_mint,
+            output_mint,
+            simulation,
+            unwrap_sol: unwrap_output,
+            submission_mode: request.submission_mode,
+        })
+        .into_response());
+    }
+
+    let jito = (request.submission_mode == SubmissionMode::Jito).then(|| JitoBundle {
+        block_engine_url: state.config.jito_block_engine_url.clone(),
+        tip_lamports: state.config.jito_tip_lamports,
+    });
+
+    let result = jupiter_execute_swap(&state.solana_rpc_url, &keypair, request.quote, jito.as_ref())
+        .await
+        .map_err(|e| swap_failure(StatusCode::BAD_GATEWAY, e))?;
+
+    // Jupiter may already have unwrapped into native SOL, leaving nothing to do
+    let unwrap = if unwrap_output {
+        unwrap_sol_after_async(&state.solana_rpc_url, keypair, &result.signature)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Swap {} succeeded but unwrap failed: {}", result.signature, e),
+                )
+                    .into_response()
+            })?
+    } else {
+        None
+    };
+
+    Ok(Json(ExecuteSwapResponse {
+        signature: result.signature,
+        input_amount: result.input_amount,
+        output_amount: result.output_amount,
+        unwrap,
+    })
+    .into_response())
+}
+
+/// Refuse mints missing from the token list
+async fn check_mints(
+    state: &Arc<AppState>,
+    input_mint: &str,
+    output_mint: &str,
+) -> Result<(), (StatusCode, String)> {
+    for mint in [input_mint, output_mint] {
+        token_list_service::check_swap_mint(state, mint)
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Wrap SOL request
+#[derive(Debug, Deserialize)]
+pub struct WrapSolRequest {
+    pub address: String,
+    /// Lamports to wrap
+    pub amount: u64,
+}
+
+/// Unwrap SOL request
+#[derive(Debug, Deserialize)]
+pub struct UnwrapSolRequest {
+    pub address: String,
+}
+
+/// Move SOL into the account's wSOL token account
+pub async fn wrap_sol(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<WrapSolRequest>,
+) -> Result<Json<WrapResult>, (StatusCode, String)> {
+    let keypair = account_keypair(&state, &request.address).await?;
+
+    let result = wrap_sol_async(&state.solana_rpc_url, keypair, request.amount)
+        .await
+        .map_err(|e| (wrap_error_status(&e), e.to_string()))?;
+
+    Ok(Json(result))
+}
+
+/// Close the account's wSOL token account back into SOL
+pub async fn unwrap_sol(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<UnwrapSolRequest>,
+) -> Result<Json<WrapResult>, (StatusCode, String)> {
+    let keypair = account_keypair(&state, &request.address).await?;
+
+    let result = unwrap_sol_async(&state.solana_rpc_url, keypair)
+        .await
+        .map_err(|e| (wrap_error_status(&e), e.to_string()))?
+        .ok_or((StatusCode::BAD_REQUEST, "No wrapped SOL to unwrap".to_string()))?;
+
+    Ok(Json(result))
+}
+
+/// Signing keypair of one of the wallet's Solana accounts
+pub(crate) async fn account_keypair(
+    state: &Arc<AppState>,
+    address: &str,
+) -> Result<SolanaKeypair, (StatusCode, String)> {
     // Check if unlocked
-    if !wallet_service::is_unlocked(&state).await {
+    if !wallet_service::is_unlocked(state).await {
         return Err((StatusCode::UNAUTHORIZED, "Wallet is locked".to_string()));
     }
 
-    let seed = get_seed(&state)
+    let seed = get_seed(state)
         .await
         .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
 
-    // Get account derivation index
+    // Get account derivation path
     let account = state
         .db
-        .get_account_by_address("solana", &request.from_address)
+        .get_account_by_address(&current_tenant_id(), "solana", address)
         .await
         .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
 
-    let keypair = SolanaKeypair::derive(&seed, account.derivation_index as u32)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    SolanaKeypair::derive_path(&seed, &account.derivation_path)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
 
-    let result = jupiter_execute_swap(&state.solana_rpc_url, &keypair, request.quote)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+/// Failed swaps are diagnosed like sends: 422 when the reason is recognized
+fn swap_failure(status: StatusCode, e: SwapError) -> Response {
+    let diagnosis = match e {
+        SwapError::SlippageExceeded => Some(diagnostics::SLIPPAGE_EXCEEDED),
+        SwapError::InsufficientBalance => Some(diagnostics::INSUFFICIENT_FUNDS),
+        _ => diagnostics::diagnose(&e.to_string()),
+    };
+    let status = if diagnosis.is_some() { StatusCode::UNPROCESSABLE_ENTITY } else { status };
+    diagnosed_error(status, e.to_string(), diagnosis)
+}
 
-    Ok(Json(ExecuteSwapResponse {
-        signature: result.signature,
-        input_amount: result.input_amount,
-        output_amount: result.output_amount,
-    }))
+fn wrap_error_status(e: &TransactionError) -> StatusCode {
+    match e {
+        TransactionError::RpcError(_) | TransactionError::TransactionFailed(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        _ => StatusCode::BAD_REQUEST,
+    }
 }
@@ -0,0 +1,131 @@
+//! Encrypted sync blob handlers
+//!
+//! The blob's version is its ETag. Writes must carry `If-Match` with the
+//! ETag last read (or `If-None-Match: *` to create the first blob); a stale
+//! one is refused with 412 so the client can merge and retry.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use crate::services::sync_service::{self, Precondition, SyncServiceError};
+use crate::services::user_service::Claims;
+use crate::AppState;
+
+/// Download the blob as stored
+pub async fn get_sync(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let blob = sync_service::get_blob(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+    let etag = etag(blob.version);
+
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| parse_etag(tag) == Some(blob.version)));
+    if unchanged {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("application/octet-stream")),
+            (header::ETAG, etag),
+        ],
+        blob.data,
+    )
+        .into_response())
+}
+
+/// Upload a new blob, replacing the version named in `If-Match`
+pub async fn put_sync(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, (StatusCode, String)> {
+    let precondition = precondition(&headers)?;
+    let blob = sync_service::put_blob(&state, &claims.sub, precondition, &body)
+        .await
+        .map_err(error_status)?;
+
+    Ok(([(header::ETAG, etag(blob.version))], Json(blob)).into_response())
+}
+
+/// Delete the blob at the version named in `If-Match`
+pub async fn delete_sync(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let version = match precondition(&headers)? {
+        Precondition::Version(version) => version,
+        _ => {
+            return Err((
+                StatusCode::PRECONDITION_REQUIRED,
+                "If-Match with the current ETag is required".to_string(),
+            ))
+        }
+    };
+    sync_service::delete_blob(&state, &claims.sub, version)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+fn etag(version: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{}\"", version)).expect("digits are a valid header value")
+}
+
+/// Version in an entity tag; weak tags compare the same
+fn parse_etag(tag: &str) -> Option<i64> {
+    let tag = tag.trim();
+    tag.strip_prefix("W/")
+        .unwrap_or(tag)
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .parse()
+        .ok()
+}
+
+fn precondition(headers: &HeaderMap) -> Result<Precondition, (StatusCode, String)> {
+    let header_str = |name| headers.get(name).and_then(|v: &HeaderValue| v.to_str().ok());
+
+    if let Some(value) = header_str(header::IF_MATCH) {
+        if value.trim() == "*" {
+            return Ok(Precondition::Exists);
+        }
+        return parse_etag(value).map(Precondition::Version).ok_or((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid If-Match: {}", value),
+        ));
+    }
+    if header_str(header::IF_NONE_MATCH).is_some_and(|v| v.trim() == "*") {
+        return Ok(Precondition::Absent);
+    }
+    Err((
+        StatusCode::PRECONDITION_REQUIRED,
+        "If-Match, or If-None-Match: * for a first upload, is required".to_string(),
+    ))
+}
+
+fn error_status(e: SyncServiceError) -> (StatusCode, String) {
+    let status = match e {
+        SyncServiceError::NotFound => StatusCode::NOT_FOUND,
+        SyncServiceError::Empty => StatusCode::BAD_REQUEST,
+        SyncServiceError::VersionMismatch => StatusCode::PRECONDITION_FAILED,
+        SyncServiceError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
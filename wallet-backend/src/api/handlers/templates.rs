@@ -0,0 +1,96 @@
+//! Send template handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::template_service::{
+    self, AppliedTemplate, ApplyTemplateRequest, CreateTemplateRequest, TemplateError,
+    UpdateTemplateRequest,
+};
+use crate::services::user_service::Claims;
+use crate::storage::models::SendTemplateResponse;
+use crate::AppState;
+
+/// List the user's templates, quick actions first
+pub async fn list_templates(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SendTemplateResponse>>, (StatusCode, String)> {
+    let templates = template_service::list_templates(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(templates))
+}
+
+/// Save a send draft as a named template
+pub async fn create_template(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateTemplateRequest>,
+) -> Result<Json<SendTemplateResponse>, (StatusCode, String)> {
+    let template = template_service::create_template(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(template))
+}
+
+/// Edit a template or mark it as a quick action
+pub async fn update_template(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateTemplateRequest>,
+) -> Result<Json<SendTemplateResponse>, (StatusCode, String)> {
+    let template = template_service::update_template(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(template))
+}
+
+/// Delete a template
+pub async fn delete_template(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    template_service::delete_template(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// Fill a send form from a template, counting the use
+pub async fn apply_template(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    request: Option<Json<ApplyTemplateRequest>>,
+) -> Result<Json<AppliedTemplate>, (StatusCode, String)> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let applied = template_service::apply_template(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(applied))
+}
+
+fn error_status(e: TemplateError) -> (StatusCode, String) {
+    let status = match e {
+        TemplateError::InvalidChain(_) | TemplateError::InvalidTemplate(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        TemplateError::NotFound => StatusCode::NOT_FOUND,
+        TemplateError::NameTaken => StatusCode::CONFLICT,
+        TemplateError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,96 @@
+//! Tenant handlers: the caller's own tenant, and the admin API
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::services::tenant_service::{
+    self, CreateTenantRequest, TenantServiceError, TenantWithKey, UpdateTenantRequest,
+};
+use crate::storage::models::{TenantPublic, TenantResponse};
+use crate::AppState;
+
+/// Name and public settings of the tenant serving this request
+pub async fn current_tenant(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TenantPublic>, (StatusCode, String)> {
+    let tenant = tenant_service::get_tenant(&state, &current_tenant_id())
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(TenantPublic::from(tenant)))
+}
+
+pub async fn list_tenants(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TenantResponse>>, (StatusCode, String)> {
+    let tenants = tenant_service::list_tenants(&state)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(tenants))
+}
+
+/// Create a tenant; the response holds its API key, which is not shown again
+pub async fn create_tenant(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateTenantRequest>,
+) -> Result<Json<TenantWithKey>, (StatusCode, String)> {
+    let tenant = tenant_service::create_tenant(&state, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(tenant))
+}
+
+pub async fn get_tenant(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<TenantResponse>, (StatusCode, String)> {
+    let tenant = tenant_service::get_tenant(&state, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(TenantResponse::from(tenant)))
+}
+
+/// Rename, re-host, disable or reconfigure a tenant
+pub async fn update_tenant(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateTenantRequest>,
+) -> Result<Json<TenantResponse>, (StatusCode, String)> {
+    let tenant = tenant_service::update_tenant(&state, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(tenant))
+}
+
+/// Issue a new API key, revoking the old one
+pub async fn rotate_api_key(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<TenantWithKey>, (StatusCode, String)> {
+    let tenant = tenant_service::rotate_api_key(&state, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(tenant))
+}
+
+fn error_status(e: TenantServiceError) -> (StatusCode, String) {
+    let status = match e {
+        TenantServiceError::NotFound => StatusCode::NOT_FOUND,
+        TenantServiceError::AlreadyExists => StatusCode::CONFLICT,
+        TenantServiceError::InvalidTenant(_) => StatusCode::BAD_REQUEST,
+        TenantServiceError::Disabled => StatusCode::FORBIDDEN,
+        TenantServiceError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,46 @@
+//! Token list search handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::core::Chain;
+use crate::services::token_list_service;
+use crate::storage::models::TokenListRow;
+use crate::AppState;
+
+/// Token search query params
+#[derive(Debug, Deserialize)]
+pub struct SearchTokensQuery {
+    /// Symbol, name or address to look for
+    pub q: String,
+    pub chain: Option<String>,
+    /// Defaults to 20, at most 50
+    pub limit: Option<usize>,
+}
+
+/// Search the token list by symbol, name or address
+pub async fn search_tokens(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchTokensQuery>,
+) -> Result<Json<Vec<TokenListRow>>, (StatusCode, String)> {
+    let chain = query
+        .chain
+        .as_deref()
+        .map(|chain| {
+            chain
+                .parse::<Chain>()
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid chain: {}", chain)))
+        })
+        .transpose()?;
+
+    let tokens =
+        token_list_service::search(&state, &query.q, chain, query.limit.unwrap_or(20)).await;
+
+    Ok(Json(tokens))
+}
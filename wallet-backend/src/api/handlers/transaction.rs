@@ -4,29 +4,192 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use serde::Deserialize;
 
-use crate::services::transaction_service::{self, SendRequest, SendResponse};
-use crate::services::wallet_service;
-use crate::storage::models::TransactionResponse;
+use crate::api::dry_run::DryRunQuery;
+use crate::api::error::diagnosed_error;
+use crate::api::fields::FieldsQuery;
+use crate::chains::diagnostics;
+use crate::chains::solana::FeeEstimate;
+use crate::services::transaction_service::{
+    self, ClearThresholdRequest, ConfirmSendRequest, FeeEstimateRequest, MaxSendResponse,
+    ReferenceLookup, SendOutcome, SendRequest, SendResponse, SetThresholdRequest,
+    TransactionServiceError,
+};
+use crate::services::user_service::Claims;
+use crate::services::scheduled_service::{self, ScheduleSendRequest, ScheduledServiceError};
+use crate::services::stuck_service::{self, StuckServiceError, StuckTransaction};
+use crate::services::wallet_service::{self, WalletServiceError};
+use crate::storage::models::{LargeTransferThresholdRow, ScheduledTransactionResponse};
 use crate::AppState;
 
-/// Send transaction
+/// Send transaction. Sends above the user's large transfer threshold are
+/// answered with 202 and a challenge to confirm instead. With `dry_run`,
+/// answers with a preview of the send and broadcasts nothing.
 pub async fn send(
+    Extension(claims): Extension<Claims>,
     State(state): State<Arc<AppState>>,
+    Query(query): Query<DryRunQuery>,
     Json(request): Json<SendRequest>,
-) -> Result<Json<SendResponse>, (StatusCode, String)> {
+) -> Result<Response, Response> {
     // Check if unlocked
     if !wallet_service::is_unlocked(&state).await {
-        return Err((StatusCode::UNAUTHORIZED, "Wallet is locked".to_string()));
+        return Err((StatusCode::UNAUTHORIZED, "Wallet is locked").into_response());
+    }
+
+    if query.dry_run {
+        let preview = transaction_service::preview_send(&state, &claims.sub, request)
+            .await
+            .map_err(broadcast_error)?;
+        return Ok(Json(preview).into_response());
     }
 
-    let result = transaction_service::send_transaction(&state, request)
+    let outcome = transaction_service::request_send(&state, &claims.sub, request)
+        .await
+        .map_err(broadcast_error)?;
+
+    Ok(match outcome {
+        SendOutcome::Sent(result) => Json(result).into_response(),
+        SendOutcome::ConfirmationRequired(challenge) => {
+            (StatusCode::ACCEPTED, Json(challenge)).into_response()
+        }
+    })
+}
+
+/// Confirm a large send by re-entering the password
+pub async fn confirm_send(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ConfirmSendRequest>,
+) -> Result<Json<SendResponse>, Response> {
+    let result = transaction_service::confirm_send(&state, &claims.sub, request)
+        .await
+        .map_err(broadcast_error)?;
+
+    Ok(Json(result))
+}
+
+/// The user's large transfer threshold; `null` when every send goes
+/// through without confirmation
+pub async fn get_large_transfer_threshold(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Option<LargeTransferThresholdRow>>, (StatusCode, String)> {
+    let threshold = transaction_service::get_large_transfer_threshold(&state, &claims.sub)
+        .await
+        .map_err(send_error_status)?;
+
+    Ok(Json(threshold))
+}
+
+/// Set the large transfer threshold (requires the password)
+pub async fn set_large_transfer_threshold(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SetThresholdRequest>,
+) -> Result<Json<LargeTransferThresholdRow>, (StatusCode, String)> {
+    let threshold = transaction_service::set_large_transfer_threshold(&state, &claims.sub, request)
+        .await
+        .map_err(send_error_status)?;
+
+    Ok(Json(threshold))
+}
+
+/// Remove the large transfer threshold (requires the password)
+pub async fn clear_large_transfer_threshold(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ClearThresholdRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    transaction_service::clear_large_transfer_threshold(&state, &claims.sub, request)
+        .await
+        .map_err(send_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn send_error_status(e: TransactionServiceError) -> (StatusCode, String) {
+    let status = match e {
+        TransactionServiceError::InvalidChain(_)
+        | TransactionServiceError::InvalidAddress(_)
+        | TransactionServiceError::InvalidAmount(_)
+        | TransactionServiceError::InvalidMemo(_)
+        | TransactionServiceError::InvalidThreshold(_)
+        | TransactionServiceError::InvalidBucket(_) => StatusCode::BAD_REQUEST,
+        TransactionServiceError::InsufficientBalance { .. }
+        | TransactionServiceError::RentExemption(_)
+        | TransactionServiceError::BucketExceeded(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        TransactionServiceError::PriceUnavailable(_)
+        | TransactionServiceError::FeatureDisabled(_) => StatusCode::SERVICE_UNAVAILABLE,
+        TransactionServiceError::InvalidPassword => StatusCode::UNAUTHORIZED,
+        TransactionServiceError::ChallengeNotFound | TransactionServiceError::AccountNotFound => {
+            StatusCode::NOT_FOUND
+        }
+        TransactionServiceError::ConfirmationRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Chains refusing a send get 422 with a diagnosis when the reason is
+/// recognized, 502 otherwise; sends switched off by a flag get its 503
+fn broadcast_error(e: TransactionServiceError) -> Response {
+    match e {
+        TransactionServiceError::TransactionFailed(ref message) => {
+            let diagnosis = diagnostics::diagnose(message);
+            let status = match diagnosis {
+                Some(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                None => StatusCode::BAD_GATEWAY,
+            };
+            diagnosed_error(status, e.to_string(), diagnosis)
+        }
+        TransactionServiceError::FeatureDisabled(disabled) => disabled.into_response(),
+        e => send_error_status(e).into_response(),
+    }
+}
+
+/// Estimate the cost of a send
+pub async fn estimate_fee(
+    State(state): State<Arc<AppState>>,
+    Query(request): Query<FeeEstimateRequest>,
+) -> Result<Json<FeeEstimate>, (StatusCode, String)> {
+    let estimate = transaction_service::estimate_fee(&state, request)
+        .await
+        .map_err(|e| match e {
+            TransactionServiceError::InvalidChain(_)
+            | TransactionServiceError::InvalidAddress(_)
+            | TransactionServiceError::InvalidAmount(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+            _ => (StatusCode::BAD_GATEWAY, e.to_string()),
+        })?;
+
+    Ok(Json(estimate))
+}
+
+/// Max-send query params
+#[derive(Debug, Deserialize)]
+pub struct MaxSendQuery {
+    pub chain: String,
+    pub address: String,
+    pub token: Option<String>,
+}
+
+/// Maximum amount an address can send after fees
+pub async fn max_send(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<MaxSendQuery>,
+) -> Result<Json<MaxSendResponse>, (StatusCode, String)> {
+    let result = transaction_service::max_send(&state, &query.chain, &query.address, query.token)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| match e {
+            TransactionServiceError::InvalidChain(_) | TransactionServiceError::InvalidAddress(_) => {
+                (StatusCode::BAD_REQUEST, e.to_string())
+            }
+            _ => (StatusCode::BAD_GATEWAY, e.to_string()),
+        })?;
 
     Ok(Json(result))
 }
@@ -40,16 +203,151 @@ pub struct HistoryQuery {
 
 /// Get transaction history
 pub async fn get_history(
+    Extension(claims): Extension<Claims>,
     State(state): State<Arc<AppState>>,
     Path((chain, address)): Path<(String, String)>,
     Query(query): Query<HistoryQuery>,
-) -> Result<Json<Vec<TransactionResponse>>, (StatusCode, String)> {
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let limit = query.limit.unwrap_or(50);
     let offset = query.offset.unwrap_or(0);
 
-    let history = transaction_service::get_transaction_history(&state, &chain, &address, limit, offset)
+    let history = transaction_service::get_transaction_history(&state, &claims.sub, &chain, &address, limit, offset)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(send_error_status)?;
+
+    Ok(Json(fields.select(&history)))
+}
+
+/// Download an account's history as CSV, with fiat values at the
+/// transaction date
+pub async fn export_history(
+    State(state): State<Arc<AppState>>,
+    Path((chain, address)): Path<(String, String)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let csv = transaction_service::export_history_csv(&state, &chain, &address)
+        .await
+        .map_err(send_error_status)?;
+
+    let disposition = format!("attachment; filename=\"{}-{}.csv\"", chain, address);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        csv,
+    ))
+}
+
+/// Look up a payment by a Solana Pay reference key
+pub async fn find_by_reference(
+    State(state): State<Arc<AppState>>,
+    Path(reference): Path<String>,
+) -> Result<Json<ReferenceLookup>, (StatusCode, String)> {
+    let lookup = transaction_service::find_by_reference(&state, &reference)
+        .await
+        .map_err(|e| match e {
+            TransactionServiceError::InvalidAddress(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+            TransactionServiceError::TransactionFailed(_) => (StatusCode::BAD_GATEWAY, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    Ok(Json(lookup))
+}
+
+/// Sends flagged as stuck that haven't been sped up
+pub async fn list_stuck(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<StuckTransaction>>, (StatusCode, String)> {
+    let stuck = stuck_service::list_stuck(&state)
+        .await
+        .map_err(stuck_error_status)?;
+
+    Ok(Json(stuck))
+}
+
+/// Replace a stuck send: same nonce and a higher gas price on Ethereum, a
+/// fresh blockhash on Solana
+pub async fn speed_up(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<SendResponse>, (StatusCode, String)> {
+    let result = stuck_service::speed_up(&state, &id)
+        .await
+        .map_err(stuck_error_status)?;
+
+    Ok(Json(result))
+}
+
+fn stuck_error_status(e: StuckServiceError) -> (StatusCode, String) {
+    let status = match e {
+        StuckServiceError::NotFound => StatusCode::NOT_FOUND,
+        StuckServiceError::NotStuck
+        | StuckServiceError::AlreadyLanded
+        | StuckServiceError::NotReplaceable(_) => StatusCode::CONFLICT,
+        StuckServiceError::WalletError(WalletServiceError::WalletLocked) => StatusCode::UNAUTHORIZED,
+        StuckServiceError::SendFailed(ref e) => match e {
+            TransactionServiceError::InvalidAddress(_) | TransactionServiceError::InvalidAmount(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            TransactionServiceError::InsufficientBalance { .. }
+            | TransactionServiceError::RentExemption(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            TransactionServiceError::FeatureDisabled(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_GATEWAY,
+        },
+        StuckServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        StuckServiceError::InvalidChain(_)
+        | StuckServiceError::WalletError(_)
+        | StuckServiceError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Schedule a send to be built, signed and broadcast once `execute_at` passes
+pub async fn schedule_send(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ScheduleSendRequest>,
+) -> Result<Json<ScheduledTransactionResponse>, (StatusCode, String)> {
+    let scheduled = scheduled_service::schedule(&state, &claims.sub, request)
+        .await
+        .map_err(scheduled_error_status)?;
+
+    Ok(Json(scheduled))
+}
+
+/// Scheduled sends, soonest first, including executed and cancelled ones
+pub async fn list_scheduled(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ScheduledTransactionResponse>>, (StatusCode, String)> {
+    let scheduled = scheduled_service::list(&state)
+        .await
+        .map_err(scheduled_error_status)?;
+
+    Ok(Json(scheduled))
+}
+
+/// Cancel a scheduled send before it executes
+pub async fn cancel_scheduled(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ScheduledTransactionResponse>, (StatusCode, String)> {
+    let scheduled = scheduled_service::cancel(&state, &id)
+        .await
+        .map_err(scheduled_error_status)?;
+
+    Ok(Json(scheduled))
+}
 
-    Ok(Json(history))
+fn scheduled_error_status(e: ScheduledServiceError) -> (StatusCode, String) {
+    let status = match e {
+        ScheduledServiceError::InvalidSend(e) => return send_error_status(e),
+        ScheduledServiceError::InvalidChain(_)
+        | ScheduledServiceError::InvalidAmount(_)
+        | ScheduledServiceError::InvalidSchedule(_) => StatusCode::BAD_REQUEST,
+        ScheduledServiceError::NotFound => StatusCode::NOT_FOUND,
+        ScheduledServiceError::NotCancellable(_) => StatusCode::CONFLICT,
+        ScheduledServiceError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
 }
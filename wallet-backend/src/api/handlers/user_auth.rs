@@ -3,18 +3,26 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{ConnectInfo, State},
+    extract::{ConnectInfo, Path, State},
     http::{header, HeaderMap, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
-use crate::services::user_service::{Claims, UserServiceError};
+use crate::services::account_deletion_service::{self, AccountDeletionError};
+use crate::services::login_risk_service::{self, LoginClient};
+use crate::services::sign_in_service::{
+    self, SignInChallengeRequest, SignInChallengeResponse, SignInRequest, SignInServiceError,
+};
+use crate::services::user_service::{
+    Claims, LoginOutcome, UserServiceError, LOGIN_VERIFICATION_TTL_SECS, SIGNING_TOKEN_TTL_SECS,
+};
 use crate::storage::models::{
-    ChangePasswordRequest, CreateUserRequest, LoginRequest, LoginResponse, RefreshTokenResponse,
-    UserPublic,
+    ChangePasswordRequest, CreateUserRequest, DeleteAccountRequest, LoginRequest,
+    LoginStepUpResponse, OAuthAuthorizeResponse, OAuthCallbackRequest, RefreshTokenResponse, Scope,
+    SigningTokenRequest, SigningTokenResponse, UserAddressResponse, UserPublic, VerifyLoginRequest,
 };
 use crate::AppState;
 
@@ -71,18 +79,24 @@ pub async fn register(
     }))
 }
 
-/// Login response with cookie header for refresh token
+/// Login response with cookie header for refresh token, or 202 with a
+/// challenge when the login looks risky enough to need step-up verification
 pub async fn login(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(request): Json<LoginRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let (device_info, ip_address) = extract_request_info(&headers, Some(addr));
+) -> Result<Response, (StatusCode, String)> {
+    let (_, ip_address) = extract_request_info(&headers, Some(addr));
+    let client = LoginClient::from_headers(
+        &headers,
+        ip_address,
+        state.config.login_risk.country_header.as_deref(),
+    );
 
-    let (response, refresh_token) = state
+    let outcome = state
         .user_service
-        .login(request, device_info, ip_address)
+        .login(request, client.clone())
         .await
         .map_err(|e| match e {
             UserServiceError::InvalidCredentials => {
@@ -91,16 +105,153 @@ pub async fn login(
             _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
         })?;
 
-    // Set refresh token as HttpOnly cookie
+    match outcome {
+        LoginOutcome::LoggedIn {
+            response,
+            refresh_token,
+            assessment,
+        } => {
+            login_risk_service::record_decision(&state, &response.user.id, "allowed", &client, &assessment)
+                .await;
+            Ok((refresh_cookie(&refresh_token), Json(response)).into_response())
+        }
+        LoginOutcome::StepUp {
+            user_id,
+            verification,
+            assessment,
+        } => {
+            login_risk_service::send_verification_link(&state, &verification, &client, &assessment)
+                .await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+            login_risk_service::record_decision(&state, &user_id, "step_up_required", &client, &assessment)
+                .await;
+
+            let response = LoginStepUpResponse {
+                step_up_required: true,
+                challenge_id: verification.id,
+                risk_score: assessment.score,
+                signals: assessment.signals.iter().map(|s| s.as_str().to_string()).collect(),
+                expires_in: LOGIN_VERIFICATION_TTL_SECS,
+            };
+            Ok((StatusCode::ACCEPTED, Json(response)).into_response())
+        }
+    }
+}
+
+/// Complete a login held back for step-up verification
+pub async fn verify_login(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<VerifyLoginRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (response, refresh_token, client, assessment) = state
+        .user_service
+        .verify_login(&request.challenge_id, &request.token)
+        .await
+        .map_err(|e| match e {
+            UserServiceError::InvalidToken | UserServiceError::TokenExpired => (
+                StatusCode::UNAUTHORIZED,
+                "Verification link is invalid or expired".to_string(),
+            ),
+            UserServiceError::InvalidCredentials => (StatusCode::UNAUTHORIZED, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+    login_risk_service::record_decision(&state, &response.user.id, "verified", &client, &assessment)
+        .await;
+
+    Ok((refresh_cookie(&refresh_token), Json(response)))
+}
+
+/// Set refresh token as HttpOnly cookie
+fn refresh_cookie(refresh_token: &str) -> HeaderMap {
     let cookie = format!(
-        "refresh_token={}; HttpOnly; Secure; SameSite=Strict; Path=/api/v1/users; Max-Age=604800",
+        "refresh_token={}; HttpOnly; Secure; SameSite=Strict; Path=/api; Max-Age=604800",
         refresh_token
     );
 
-    let mut response_headers = HeaderMap::new();
-    response_headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
+    let mut headers = HeaderMap::new();
+    headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
+    headers
+}
 
-    Ok((response_headers, Json(response)))
+/// Social login providers the server is configured for
+pub async fn oauth_providers(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "providers": state.user_service.oauth_providers() }))
+}
+
+/// Start a social login: returns the provider URL to redirect the user to
+pub async fn oauth_authorize(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+) -> Result<Json<OAuthAuthorizeResponse>, (StatusCode, String)> {
+    let response = state
+        .user_service
+        .oauth_authorize(&provider)
+        .await
+        .map_err(oauth_error)?;
+
+    Ok(Json(response))
+}
+
+/// Complete a social login with the code the provider redirected back with
+pub async fn oauth_callback(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<OAuthCallbackRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (device_info, ip_address) = extract_request_info(&headers, Some(addr));
+
+    let (response, refresh_token) = state
+        .user_service
+        .oauth_login(&provider, request, device_info, ip_address)
+        .await
+        .map_err(oauth_error)?;
+
+    Ok((refresh_cookie(&refresh_token), Json(response)))
+}
+
+fn oauth_error(e: UserServiceError) -> (StatusCode, String) {
+    let status = match e {
+        UserServiceError::OAuthProviderUnavailable(_) => StatusCode::NOT_FOUND,
+        UserServiceError::OAuthInvalidState | UserServiceError::InvalidCredentials => {
+            StatusCode::UNAUTHORIZED
+        }
+        UserServiceError::OAuthEmailUnverified => StatusCode::FORBIDDEN,
+        UserServiceError::UserAlreadyExists => StatusCode::CONFLICT,
+        UserServiceError::OAuthProvider(_) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Issue a SIWE / Sign-In With Solana message for a wallet to sign
+pub async fn wallet_challenge(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SignInChallengeRequest>,
+) -> Result<Json<SignInChallengeResponse>, (StatusCode, String)> {
+    let challenge = sign_in_service::create_challenge(&state, request)
+        .await
+        .map_err(sign_in_error)?;
+
+    Ok(Json(challenge))
+}
+
+/// Log in with a signed message from a linked wallet address
+pub async fn wallet_login(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<SignInRequest>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (device_info, ip_address) = extract_request_info(&headers, Some(addr));
+
+    let (response, refresh_token) =
+        sign_in_service::login(&state, request, device_info, ip_address)
+            .await
+            .map_err(sign_in_error)?;
+
+    Ok((refresh_cookie(&refresh_token), Json(response)))
 }
 
 /// Refresh access token using refresh token from cookie
@@ -152,7 +303,7 @@ pub async fn logout(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     // Clear the refresh token cookie
-    let cookie = "refresh_token=; HttpOnly; Secure; SameSite=Strict; Path=/api/v1/users; Max-Age=0";
+    let cookie = "refresh_token=; HttpOnly; Secure; SameSite=Strict; Path=/api; Max-Age=0";
 
     let mut headers = HeaderMap::new();
     headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
@@ -217,3 +368,124 @@ pub async fn change_password(
         "message": "Password changed successfully. Please login again."
     })))
 }
+
+/// Delete the account: 202 with a confirmation token, then, once the token
+/// is sent back, the exported data and when it will be purged
+pub async fn delete_account(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<DeleteAccountRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let map_err = |e: AccountDeletionError| match e {
+        AccountDeletionError::InvalidPassword | AccountDeletionError::InvalidToken => {
+            (StatusCode::UNAUTHORIZED, e.to_string())
+        }
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let Some(token) = request.confirmation_token else {
+        let pending = account_deletion_service::request_deletion(&state, &claims.sub, &request.password)
+            .await
+            .map_err(map_err)?;
+        return Ok((StatusCode::ACCEPTED, Json(pending)).into_response());
+    };
+
+    let confirmed =
+        account_deletion_service::confirm_deletion(&state, &claims.sub, &request.password, &token)
+            .await
+            .map_err(map_err)?;
+
+    // Sessions are revoked, so clear the refresh token cookie too
+    let cookie = "refresh_token=; HttpOnly; Secure; SameSite=Strict; Path=/api; Max-Age=0";
+    let mut headers = HeaderMap::new();
+    headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
+
+    Ok((headers, Json(confirmed)).into_response())
+}
+
+/// Mint a signing token for sends, swaps and multisig executions, after
+/// checking the password again
+pub async fn signing_token(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SigningTokenRequest>,
+) -> Result<Json<SigningTokenResponse>, (StatusCode, String)> {
+    if !claims.has_scope(Scope::Trade) {
+        return Err((StatusCode::FORBIDDEN, "Token lacks the trade scope".to_string()));
+    }
+
+    state
+        .user_service
+        .verify_password(&claims.sub, &request.password)
+        .await
+        .map_err(|e| match e {
+            UserServiceError::InvalidCredentials => {
+                (StatusCode::UNAUTHORIZED, "Password is incorrect".to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    let signing_token = state
+        .user_service
+        .generate_signing_token(&claims)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(SigningTokenResponse {
+        signing_token,
+        expires_in: SIGNING_TOKEN_TTL_SECS,
+    }))
+}
+
+/// Wallet addresses the user can sign in with
+pub async fn list_addresses(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<UserAddressResponse>>, (StatusCode, String)> {
+    let addresses = sign_in_service::list_addresses(&state, &claims.sub)
+        .await
+        .map_err(sign_in_error)?;
+
+    Ok(Json(addresses))
+}
+
+/// Link the wallet address that signed a sign-in message
+pub async fn link_address(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SignInRequest>,
+) -> Result<Json<UserAddressResponse>, (StatusCode, String)> {
+    let address = sign_in_service::link_address(&state, &claims.sub, request)
+        .await
+        .map_err(sign_in_error)?;
+
+    Ok(Json(address))
+}
+
+pub async fn unlink_address(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    sign_in_service::unlink_address(&state, &claims.sub, &id)
+        .await
+        .map_err(sign_in_error)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+fn sign_in_error(e: SignInServiceError) -> (StatusCode, String) {
+    let status = match e {
+        SignInServiceError::InvalidChain(_) | SignInServiceError::InvalidAddress(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        SignInServiceError::InvalidChallenge
+        | SignInServiceError::InvalidSignature(_)
+        | SignInServiceError::NotLinked => StatusCode::UNAUTHORIZED,
+        SignInServiceError::AlreadyLinked => StatusCode::CONFLICT,
+        SignInServiceError::NotFound => StatusCode::NOT_FOUND,
+        SignInServiceError::UserError(_) | SignInServiceError::DatabaseError(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
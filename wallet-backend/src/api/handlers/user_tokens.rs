@@ -0,0 +1,131 @@
+//! Custom token tracking handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Deserialize;
+
+use crate::services::token_service::{
+    self, AddTokenRequest, SetTokenViewRequest, TokenServiceError, UpdateTokenRequest,
+};
+use crate::services::user_service::Claims;
+use crate::storage::models::{TokenViewSettingsRow, UserTokenResponse};
+use crate::AppState;
+
+/// List tokens query
+#[derive(Debug, Deserialize)]
+pub struct ListTokensQuery {
+    pub chain: Option<String>,
+}
+
+/// List the user's tracked and hidden tokens
+pub async fn list_tokens(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListTokensQuery>,
+) -> Result<Json<Vec<UserTokenResponse>>, (StatusCode, String)> {
+    let tokens = token_service::list_tokens(&state, &claims.sub, query.chain.as_deref())
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(tokens))
+}
+
+/// Track a custom token
+pub async fn add_token(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AddTokenRequest>,
+) -> Result<Json<UserTokenResponse>, (StatusCode, String)> {
+    let token = token_service::add_token(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(token))
+}
+
+/// Rename, re-icon, hide or unhide a token
+pub async fn update_token(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateTokenRequest>,
+) -> Result<Json<UserTokenResponse>, (StatusCode, String)> {
+    let token = token_service::update_token(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(token))
+}
+
+/// Stop tracking a token
+pub async fn delete_token(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    token_service::delete_token(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// How the user's token balances are listed; `null` when in the chain's
+/// order with nothing folded
+pub async fn get_token_view(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Option<TokenViewSettingsRow>>, (StatusCode, String)> {
+    let settings = token_service::get_token_view(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(settings))
+}
+
+/// Set the token order and small balance threshold
+pub async fn set_token_view(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SetTokenViewRequest>,
+) -> Result<Json<TokenViewSettingsRow>, (StatusCode, String)> {
+    let settings = token_service::set_token_view(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(settings))
+}
+
+/// Go back to the chain's order with nothing folded
+pub async fn clear_token_view(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    token_service::clear_token_view(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) fn error_status(e: TokenServiceError) -> (StatusCode, String) {
+    let status = match e {
+        TokenServiceError::InvalidChain(_)
+        | TokenServiceError::InvalidToken(_)
+        | TokenServiceError::DecimalsMismatch { .. }
+        | TokenServiceError::SymbolRequired
+        | TokenServiceError::InvalidIconUrl(_)
+        | TokenServiceError::InvalidThreshold(_)
+        | TokenServiceError::InvalidCurrency(_) => StatusCode::BAD_REQUEST,
+        TokenServiceError::NotFound => StatusCode::NOT_FOUND,
+        TokenServiceError::AlreadyTracked => StatusCode::CONFLICT,
+        TokenServiceError::Chain(_) => StatusCode::BAD_GATEWAY,
+        TokenServiceError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
@@ -0,0 +1,72 @@
+//! v2 account handlers
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::parse_timestamp;
+use crate::api::error::ApiError;
+use crate::services::wallet_service;
+use crate::storage::models::AccountResponse;
+use crate::AppState;
+
+/// Account with typed timestamps
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountV2 {
+    pub id: String,
+    pub name: String,
+    pub chain: String,
+    pub derivation_path: String,
+    pub derivation_index: u32,
+    pub public_key: String,
+    pub address: String,
+    pub created_at: Option<DateTime<Utc>>,
+    /// RPC endpoint used instead of the default one
+    pub rpc_url: Option<String>,
+    /// Sends go through the private relay by default
+    pub mev_protect: bool,
+    /// Last successful balance read
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Native balance as of `last_synced_at`
+    pub last_known_balance: Option<String>,
+    /// `last_known_balance` in base units (lamports, wei)
+    pub last_known_balance_raw: Option<String>,
+    /// Error from the last balance read, when it failed
+    pub sync_error: Option<String>,
+    pub sync_error_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccountResponse> for AccountV2 {
+    fn from(account: AccountResponse) -> Self {
+        Self {
+            created_at: parse_timestamp(&account.created_at),
+            last_synced_at: account.last_synced_at.as_deref().and_then(parse_timestamp),
+            sync_error_at: account.sync_error_at.as_deref().and_then(parse_timestamp),
+            id: account.id,
+            name: account.name,
+            chain: account.chain,
+            derivation_path: account.derivation_path,
+            derivation_index: account.derivation_index,
+            public_key: account.public_key,
+            address: account.address,
+            rpc_url: account.rpc_url,
+            mev_protect: account.mev_protect,
+            last_known_balance: account.last_known_balance,
+            last_known_balance_raw: account.last_known_balance_raw,
+            sync_error: account.sync_error,
+        }
+    }
+}
+
+/// List all accounts
+pub async fn list_accounts(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AccountV2>>, ApiError> {
+    let accounts = wallet_service::list_accounts(&state)
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(accounts.into_iter().map(AccountV2::from).collect()))
+}
@@ -0,0 +1,55 @@
+//! v2 alert handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde_json::Value;
+
+use crate::api::error::ApiError;
+use crate::api::fields::FieldsQuery;
+use crate::api::pagination::{CountStrategy, Cursor, PageQuery, Paginated};
+use crate::services::user_service::Claims;
+use crate::storage::models::AlertNotificationResponse;
+use crate::AppState;
+
+/// List fired balance and price alerts, newest first
+pub async fn list_notifications(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PageQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let before = query.cursor()?;
+    let limit = query.limit();
+    let rows = state
+        .db
+        .get_alert_notifications_page(
+            &claims.sub,
+            before.as_ref().map(|c| (c.sort_key.as_str(), c.id.as_str())),
+            limit + 1,
+        )
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut page = Paginated::from_rows(
+        rows,
+        limit,
+        |row| Cursor::new(row.created_at.clone(), row.id.clone()),
+        AlertNotificationResponse::from,
+    );
+    if query.include_total {
+        // The feed keeps growing while alerts fire, so it is counted up to a cap
+        let strategy = CountStrategy::Estimate;
+        let count = state
+            .db
+            .count_alert_notifications(&claims.sub, strategy.cap())
+            .await
+            .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        page = page.with_total(count, strategy);
+    }
+    Ok(Json(fields.select(&page)))
+}
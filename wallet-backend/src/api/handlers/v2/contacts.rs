@@ -0,0 +1,126 @@
+//! v2 contact (address book) handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::parse_timestamp;
+use crate::api::error::ApiError;
+use crate::api::fields::FieldsQuery;
+use crate::api::handlers::contacts::identity_error;
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::api::pagination::{CountStrategy, Cursor, PageQuery, Paginated};
+use crate::services::identity_service;
+use crate::storage::models::{ContactIdentity, ContactRow};
+use crate::AppState;
+
+/// Contact with typed timestamps
+#[derive(Debug, Clone, Serialize)]
+pub struct ContactV2 {
+    pub id: String,
+    pub name: String,
+    pub chain: String,
+    pub address: String,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub identity: Option<ContactIdentity>,
+    pub identity_refreshed_at: Option<DateTime<Utc>>,
+    pub default_token_address: Option<String>,
+    pub default_amount: Option<String>,
+}
+
+impl From<ContactRow> for ContactV2 {
+    fn from(row: ContactRow) -> Self {
+        Self {
+            created_at: parse_timestamp(&row.created_at),
+            identity: row.identity(),
+            identity_refreshed_at: row.identity_refreshed_at.as_deref().and_then(parse_timestamp),
+            id: row.id,
+            name: row.name,
+            chain: row.chain,
+            address: row.address,
+            notes: row.notes,
+            default_token_address: row.default_token_address,
+            default_amount: row.default_amount,
+        }
+    }
+}
+
+/// List contacts, ordered by name
+pub async fn list_contacts(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PageQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let after = query.cursor()?;
+
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| ApiError::from_status(StatusCode::NOT_FOUND, "No wallet found"))?;
+
+    let limit = query.limit();
+    let rows = state
+        .db
+        .get_contacts_page(
+            &wallet.id,
+            after.as_ref().map(|c| (c.sort_key.as_str(), c.id.as_str())),
+            limit + 1,
+        )
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut page = Paginated::from_rows(
+        rows,
+        limit,
+        |row| Cursor::new(row.name.clone(), row.id.clone()),
+        ContactV2::from,
+    );
+    if query.include_total {
+        let count = state
+            .db
+            .count_contacts(&wallet.id)
+            .await
+            .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        page = page.with_total(count, CountStrategy::Exact);
+    }
+    Ok(Json(fields.select(&page)))
+}
+
+/// Get single contact
+pub async fn get_contact(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ContactV2>, ApiError> {
+    let contact = state
+        .db
+        .get_contact(&id)
+        .await
+        .map_err(|_| ApiError::from_status(StatusCode::NOT_FOUND, "Contact not found"))?;
+
+    Ok(Json(ContactV2::from(contact)))
+}
+
+/// Re-resolve a contact's ENS / SNS identity
+pub async fn refresh_identity(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ContactV2>, ApiError> {
+    let contact = identity_service::refresh_contact_identity(&state, &id)
+        .await
+        .map_err(|e| {
+            let (status, message) = identity_error(e);
+            ApiError::from_status(status, message)
+        })?;
+
+    Ok(Json(ContactV2::from(contact)))
+}
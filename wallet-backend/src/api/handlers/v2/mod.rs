@@ -0,0 +1,40 @@
+//! v2 API handlers
+//!
+//! v2 responses use typed ISO-8601 timestamps, the `Paginated` cursor
+//! envelope for lists and the structured error envelope. Endpoints whose v1
+//! shape is unchanged reuse the v1 handlers directly.
+
+pub mod accounts;
+pub mod alerts;
+pub mod contacts;
+pub mod multisig;
+pub mod nft;
+pub mod transaction;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Parse a stored timestamp (RFC 3339, or SQLite `datetime('now')` format)
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").map(|dt| dt.and_utc())
+        })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        let expected = DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(parse_timestamp("2026-01-02T03:04:05+00:00"), Some(expected));
+        assert_eq!(parse_timestamp("2026-01-02 03:04:05"), Some(expected));
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+}
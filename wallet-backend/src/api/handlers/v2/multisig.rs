@@ -0,0 +1,44 @@
+//! v2 multi-sig handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::Value;
+
+use crate::api::error::ApiError;
+use crate::api::fields::FieldsQuery;
+use crate::api::pagination::{CountStrategy, Cursor, PageQuery, Paginated};
+use crate::services::multisig_service;
+use crate::AppState;
+
+/// List multi-sig wallets, newest first; owners are listed unless a sparse
+/// fieldset leaves them out
+pub async fn list_multisigs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PageQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let before = query.cursor()?;
+    let multisigs = multisig_service::list_multisigs(&state, fields.wants("owners"))
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // A wallet has few multi-sigs, so they are paged in memory and counted exactly
+    let count = multisigs.len() as u64;
+    let mut page = Paginated::from_sorted(
+        multisigs,
+        before.as_ref(),
+        query.limit(),
+        true,
+        |ms| Cursor::new(ms.created_at.clone(), ms.id.clone()),
+        |ms| ms,
+    );
+    if query.include_total {
+        page = page.with_total(count, CountStrategy::Exact);
+    }
+    Ok(Json(fields.select(&page)))
+}
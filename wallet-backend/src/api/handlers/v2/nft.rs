@@ -0,0 +1,46 @@
+//! v2 NFT handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::Value;
+
+use crate::api::error::ApiError;
+use crate::api::fields::FieldsQuery;
+use crate::api::pagination::{CountStrategy, Cursor, PageQuery, Paginated};
+use crate::services::nft_service;
+use crate::AppState;
+
+/// List the NFTs an address holds, by collection (mint or contract) and
+/// token ID
+pub async fn list_nfts(
+    State(state): State<Arc<AppState>>,
+    Path((chain, address)): Path<(String, String)>,
+    Query(query): Query<PageQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let after = query.cursor()?;
+    let mut nfts = nft_service::get_nfts(&state, &chain, &address)
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    nfts.sort_by(|a, b| (&a.token_address, &a.token_id).cmp(&(&b.token_address, &b.token_id)));
+
+    // The whole gallery is loaded to merge chain and cache, so the count is exact
+    let count = nfts.len() as u64;
+    let mut page = Paginated::from_sorted(
+        nfts,
+        after.as_ref(),
+        query.limit(),
+        false,
+        |nft| Cursor::new(nft.token_address.clone(), nft.token_id.clone()),
+        |nft| nft,
+    );
+    if query.include_total {
+        page = page.with_total(count, CountStrategy::Exact);
+    }
+    Ok(Json(fields.select(&page)))
+}
@@ -0,0 +1,194 @@
+//! v2 transaction handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::parse_timestamp;
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::api::error::ApiError;
+use crate::api::fields::FieldsQuery;
+use crate::api::pagination::{CountStrategy, Cursor, PageQuery, Paginated};
+use crate::chains::{TxEffects, TxReceipt};
+use crate::services::token_service;
+use crate::services::transaction_service::{self, Counterparties};
+use crate::services::user_service::Claims;
+use crate::storage::models::TransactionRow;
+use crate::AppState;
+
+/// Transaction with typed timestamps
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionV2 {
+    pub id: String,
+    pub chain: String,
+    pub signature: String,
+    pub tx_type: String,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub amount: Option<String>,
+    /// Native amounts in base units (lamports, wei)
+    pub amount_raw: Option<String>,
+    pub token_address: Option<String>,
+    pub status: String,
+    pub block_number: Option<i64>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub fiat_amount: Option<String>,
+    pub fiat_currency: Option<String>,
+    pub fiat_rate: Option<String>,
+    pub token_id: Option<String>,
+    pub expected_changes: Option<TxEffects>,
+    pub actual_changes: Option<TxEffects>,
+    pub effects_mismatch: Option<bool>,
+    pub stuck_at: Option<DateTime<Utc>>,
+    pub replaced_by: Option<String>,
+    pub price_at_tx: Option<String>,
+    pub price_currency: Option<String>,
+    pub realized_value: Option<String>,
+    pub memo: Option<String>,
+    /// Confirmations seen by the tracker
+    pub confirmations: Option<i64>,
+    /// Receipt of a settled Ethereum send
+    pub receipt: Option<TxReceipt>,
+    /// Fee actually paid once settled, in base units
+    pub fee_paid: Option<String>,
+    /// Cross-chain swap this is one side of
+    pub route_id: Option<String>,
+    /// Contact or account name of the other side
+    pub counterparty_label: Option<String>,
+    /// The other side is another of the wallet's accounts
+    pub is_own_account: Option<bool>,
+    /// Symbol of the token moved as the user sees it
+    pub token_symbol: Option<String>,
+    /// Icon of the token moved
+    pub token_logo_uri: Option<String>,
+}
+
+impl From<TransactionRow> for TransactionV2 {
+    fn from(row: TransactionRow) -> Self {
+        Self {
+            timestamp: row.timestamp.as_deref().and_then(parse_timestamp),
+            created_at: parse_timestamp(&row.created_at),
+            stuck_at: row.stuck_at.as_deref().and_then(parse_timestamp),
+            expected_changes: row.expected_effects(),
+            actual_changes: row.actual_effects(),
+            realized_value: row.realized_value(),
+            amount_raw: row.amount_raw(),
+            receipt: row.receipt(),
+            id: row.id,
+            chain: row.chain,
+            signature: row.signature,
+            tx_type: row.tx_type,
+            from_address: row.from_address,
+            to_address: row.to_address,
+            amount: row.amount,
+            token_address: row.token_address,
+            status: row.status,
+            block_number: row.block_number,
+            fiat_amount: row.fiat_amount,
+            fiat_currency: row.fiat_currency,
+            fiat_rate: row.fiat_rate,
+            token_id: row.token_id,
+            effects_mismatch: row.effects_mismatch,
+            replaced_by: row.replaced_by,
+            price_at_tx: row.price_at_tx,
+            price_currency: row.price_currency,
+            memo: row.memo,
+            confirmations: row.confirmations,
+            fee_paid: row.fee_paid,
+            route_id: row.route_id,
+            counterparty_label: None,
+            is_own_account: None,
+            token_symbol: None,
+            token_logo_uri: None,
+        }
+    }
+}
+
+/// Get recorded transaction history for an account, newest first, with
+/// tokens shown the way the user sees them and transfers of tokens they hid
+/// left out
+pub async fn get_history(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path((chain, address)): Path<(String, String)>,
+    Query(query): Query<PageQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let before = query.cursor()?;
+
+    let account = state
+        .db
+        .get_account_by_address(&current_tenant_id(), &chain.to_lowercase(), &address)
+        .await
+        .map_err(|_| ApiError::from_status(StatusCode::NOT_FOUND, "Account not found"))?;
+
+    let counterparties = Counterparties::load(&state, &account)
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let limit = query.limit();
+    let rows = state
+        .db
+        .get_transactions_page(
+            &account.id,
+            before.as_ref().map(|c| (c.sort_key.as_str(), c.id.as_str())),
+            limit + 1,
+        )
+        .await
+        .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let displays = token_service::token_displays(
+        &state,
+        &claims.sub,
+        &account.chain,
+        rows.iter().filter_map(|row| row.token_address.as_deref()),
+    )
+    .await
+    .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let display = |token: Option<&str>| token.and_then(|t| displays.get(&t.to_lowercase()));
+
+    let mut page = Paginated::from_rows(
+        rows,
+        limit,
+        |row| {
+            let sort_key = row.timestamp.clone().unwrap_or_else(|| row.created_at.clone());
+            Cursor::new(sort_key, row.id.clone())
+        },
+        |row| {
+            let (label, own) =
+                counterparties.annotate(row.from_address.as_deref(), row.to_address.as_deref());
+            let token = display(row.token_address.as_deref());
+            TransactionV2 {
+                tx_type: transaction_service::canonical_type(&row.tx_type, own),
+                counterparty_label: label,
+                is_own_account: Some(own),
+                token_symbol: token.and_then(|t| t.symbol.clone()),
+                token_logo_uri: token.and_then(|t| t.logo_uri.clone()),
+                ..TransactionV2::from(row)
+            }
+        },
+    );
+    // Dropped after paging so the cursor still follows the stored rows
+    page.items
+        .retain(|tx| !display(tx.token_address.as_deref()).is_some_and(|t| t.hidden));
+    if query.include_total {
+        // History grows without bound, so it is counted up to a cap; the
+        // count includes transfers of hidden tokens
+        let strategy = CountStrategy::Estimate;
+        let count = state
+            .db
+            .count_transactions(&account.id, strategy.cap())
+            .await
+            .map_err(|e| ApiError::from_status(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        page = page.with_total(count, strategy);
+    }
+    Ok(Json(fields.select(&page)))
+}
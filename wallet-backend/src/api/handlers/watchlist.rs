@@ -0,0 +1,83 @@
+//! Watchlist handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::user_service::Claims;
+use crate::services::watchlist_service::{
+    self, AddWatchRequest, UpdateWatchRequest, WatchResponse, WatchlistError,
+};
+use crate::AppState;
+
+/// List watched addresses with their recent activity
+pub async fn list_watches(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<WatchResponse>>, (StatusCode, String)> {
+    let watches = watchlist_service::list_watches(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(watches))
+}
+
+/// Start watching an address
+pub async fn add_watch(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AddWatchRequest>,
+) -> Result<Json<WatchResponse>, (StatusCode, String)> {
+    let watch = watchlist_service::add_watch(&state, &claims.sub, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(watch))
+}
+
+/// Change a watched address's label, threshold or webhook
+pub async fn update_watch(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateWatchRequest>,
+) -> Result<Json<WatchResponse>, (StatusCode, String)> {
+    let watch = watchlist_service::update_watch(&state, &claims.sub, &id, request)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(watch))
+}
+
+/// Stop watching an address
+pub async fn remove_watch(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    watchlist_service::remove_watch(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+fn error_status(e: WatchlistError) -> (StatusCode, String) {
+    let status = match e {
+        WatchlistError::InvalidChain(_)
+        | WatchlistError::InvalidAddress(_)
+        | WatchlistError::InvalidLabel(_)
+        | WatchlistError::InvalidThreshold(_)
+        | WatchlistError::InvalidWebhook(_) => StatusCode::BAD_REQUEST,
+        WatchlistError::NotFound => StatusCode::NOT_FOUND,
+        WatchlistError::Duplicate(_) => StatusCode::CONFLICT,
+        WatchlistError::LimitReached => StatusCode::UNPROCESSABLE_ENTITY,
+        WatchlistError::Chain(_) => StatusCode::BAD_GATEWAY,
+        WatchlistError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
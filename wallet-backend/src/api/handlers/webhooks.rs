@@ -0,0 +1,89 @@
+//! Webhook catalog, signing secret, test delivery and delivery log handlers
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+
+use crate::services::user_service::Claims;
+use crate::services::webhook_service::{
+    self, WebhookCatalog, WebhookError, WebhookResponse, WebhookSecretResponse,
+};
+use crate::storage::models::WebhookDeliveryResponse;
+use crate::AppState;
+
+/// Event types with their JSON schemas, and how deliveries are signed
+pub async fn catalog() -> Json<WebhookCatalog> {
+    Json(webhook_service::catalog())
+}
+
+/// Every URL the user's events are POSTed to
+pub async fn list_webhooks(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<WebhookResponse>>, (StatusCode, String)> {
+    let webhooks = webhook_service::list_webhooks(&state, &claims.sub)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(webhooks))
+}
+
+/// The secret deliveries are signed with
+pub async fn get_secret(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<WebhookSecretResponse>, (StatusCode, String)> {
+    let secret = webhook_service::get_secret(&state)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(secret))
+}
+
+/// Replace the signing secret
+pub async fn rotate_secret(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<WebhookSecretResponse>, (StatusCode, String)> {
+    let secret = webhook_service::rotate_secret(&state)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(secret))
+}
+
+/// Send a signed sample event to a webhook and report the receiver's answer
+pub async fn send_test(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<WebhookDeliveryResponse>, (StatusCode, String)> {
+    let delivery = webhook_service::send_test(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(delivery))
+}
+
+/// A webhook's latest deliveries with the receiver's response codes
+pub async fn list_deliveries(
+    Extension(claims): Extension<Claims>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<WebhookDeliveryResponse>>, (StatusCode, String)> {
+    let deliveries = webhook_service::list_deliveries(&state, &claims.sub, &id)
+        .await
+        .map_err(error_status)?;
+
+    Ok(Json(deliveries))
+}
+
+fn error_status(e: WebhookError) -> (StatusCode, String) {
+    let status = match e {
+        WebhookError::NotFound => StatusCode::NOT_FOUND,
+        WebhookError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
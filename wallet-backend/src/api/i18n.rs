@@ -0,0 +1,199 @@
+//! Localized error messages
+//!
+//! Error envelopes keep their stable `code`; only the human-readable
+//! `message` is translated. The catalog below is keyed by code, so a code
+//! without an entry, or a request in English, keeps the handler's original
+//! message.
+
+/// Languages error messages can be returned in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Fr,
+    De,
+    Pt,
+}
+
+impl Locale {
+    /// Primary language subtag, as sent in `Content-Language`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::Fr => "fr",
+            Locale::De => "de",
+            Locale::Pt => "pt",
+        }
+    }
+
+    /// Match a language tag such as `pt-BR` on its primary subtag
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            "fr" => Some(Locale::Fr),
+            "de" => Some(Locale::De),
+            "pt" => Some(Locale::Pt),
+            _ => None,
+        }
+    }
+}
+
+/// Most preferred supported language in an `Accept-Language` header, by
+/// quality value then order; English when nothing listed is supported
+pub fn negotiate(accept_language: &str) -> Locale {
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let tag = parts.next().filter(|t| !t.is_empty())?;
+            let quality = parts
+                .find_map(|p| p.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+            Some((tag, quality))
+        })
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect();
+    // Stable, so equal weights keep the caller's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranges
+        .into_iter()
+        .find_map(|(tag, _)| if tag == "*" { Some(Locale::En) } else { Locale::from_tag(tag) })
+        .unwrap_or_default()
+}
+
+/// Catalog message for an error code; `None` for English and for codes
+/// without a translation
+pub fn message(code: &str, locale: Locale) -> Option<&'static str> {
+    use Locale::*;
+
+    let text = match (code, locale) {
+        (_, En) => return None,
+
+        ("bad_request", Es) => "La solicitud no es válida",
+        ("bad_request", Fr) => "La requête est invalide",
+        ("bad_request", De) => "Die Anfrage ist ungültig",
+        ("bad_request", Pt) => "A solicitação é inválida",
+
+        ("unauthorized", Es) => "Se requiere autenticación",
+        ("unauthorized", Fr) => "Authentification requise",
+        ("unauthorized", De) => "Authentifizierung erforderlich",
+        ("unauthorized", Pt) => "Autenticação necessária",
+
+        ("forbidden", Es) => "No tiene permiso para realizar esta acción",
+        ("forbidden", Fr) => "Vous n'êtes pas autorisé à effectuer cette action",
+        ("forbidden", De) => "Sie sind nicht berechtigt, diese Aktion auszuführen",
+        ("forbidden", Pt) => "Você não tem permissão para realizar esta ação",
+
+        ("not_found", Es) => "No se encontró el recurso solicitado",
+        ("not_found", Fr) => "La ressource demandée est introuvable",
+        ("not_found", De) => "Die angeforderte Ressource wurde nicht gefunden",
+        ("not_found", Pt) => "O recurso solicitado não foi encontrado",
+
+        ("conflict", Es) => "La solicitud entra en conflicto con el estado actual",
+        ("conflict", Fr) => "La requête est en conflit avec l'état actuel",
+        ("conflict", De) => "Die Anfrage steht im Konflikt mit dem aktuellen Zustand",
+        ("conflict", Pt) => "A solicitação conflita com o estado atual",
+
+        ("gone", Es) => "Este recurso ya no está disponible",
+        ("gone", Fr) => "Cette ressource n'est plus disponible",
+        ("gone", De) => "Diese Ressource ist nicht mehr verfügbar",
+        ("gone", Pt) => "Este recurso não está mais disponível",
+
+        ("precondition_failed", Es) => "Los datos cambiaron desde la última lectura",
+        ("precondition_failed", Fr) => "Les données ont changé depuis la dernière lecture",
+        ("precondition_failed", De) => "Die Daten haben sich seit dem letzten Abruf geändert",
+        ("precondition_failed", Pt) => "Os dados mudaram desde a última leitura",
+
+        ("payload_too_large", Es) => "El cuerpo de la solicitud es demasiado grande",
+        ("payload_too_large", Fr) => "Le corps de la requête est trop volumineux",
+        ("payload_too_large", De) => "Der Anfragetext ist zu groß",
+        ("payload_too_large", Pt) => "O corpo da solicitação é muito grande",
+
+        ("unsupported_media_type", Es) => "Tipo de contenido no admitido",
+        ("unsupported_media_type", Fr) => "Type de contenu non pris en charge",
+        ("unsupported_media_type", De) => "Nicht unterstützter Inhaltstyp",
+        ("unsupported_media_type", Pt) => "Tipo de conteúdo não suportado",
+
+        ("unprocessable_entity", Es) => "No se pudo procesar la solicitud",
+        ("unprocessable_entity", Fr) => "La requête n'a pas pu être traitée",
+        ("unprocessable_entity", De) => "Die Anfrage konnte nicht verarbeitet werden",
+        ("unprocessable_entity", Pt) => "Não foi possível processar a solicitação",
+
+        ("precondition_required", Es) => "La solicitud debe ser condicional",
+        ("precondition_required", Fr) => "La requête doit être conditionnelle",
+        ("precondition_required", De) => "Die Anfrage muss bedingt sein",
+        ("precondition_required", Pt) => "A solicitação deve ser condicional",
+
+        ("rate_limited", Es) => "Demasiadas solicitudes; inténtelo de nuevo más tarde",
+        ("rate_limited", Fr) => "Trop de requêtes ; réessayez plus tard",
+        ("rate_limited", De) => "Zu viele Anfragen; versuchen Sie es später erneut",
+        ("rate_limited", Pt) => "Muitas solicitações; tente novamente mais tarde",
+
+        ("upstream_error", Es) => "Un servicio externo devolvió un error",
+        ("upstream_error", Fr) => "Un service externe a renvoyé une erreur",
+        ("upstream_error", De) => "Ein externer Dienst hat einen Fehler gemeldet",
+        ("upstream_error", Pt) => "Um serviço externo retornou um erro",
+
+        ("service_unavailable", Es) => "El servicio no está disponible temporalmente",
+        ("service_unavailable", Fr) => "Le service est temporairement indisponible",
+        ("service_unavailable", De) => "Der Dienst ist vorübergehend nicht verfügbar",
+        ("service_unavailable", Pt) => "O serviço está temporariamente indisponível",
+
+        ("client_error", Es) => "No se pudo completar la solicitud",
+        ("client_error", Fr) => "La requête n'a pas pu aboutir",
+        ("client_error", De) => "Die Anfrage konnte nicht abgeschlossen werden",
+        ("client_error", Pt) => "Não foi possível concluir a solicitação",
+
+        ("internal_error", Es) => "Error interno del servidor",
+        ("internal_error", Fr) => "Erreur interne du serveur",
+        ("internal_error", De) => "Interner Serverfehler",
+        ("internal_error", Pt) => "Erro interno do servidor",
+
+        ("invalid_cursor", Es) => "El cursor de paginación no es válido",
+        ("invalid_cursor", Fr) => "Le curseur de pagination est invalide",
+        ("invalid_cursor", De) => "Der Paginierungs-Cursor ist ungültig",
+        ("invalid_cursor", Pt) => "O cursor de paginação é inválido",
+
+        ("maintenance", Es) => "El servicio está en mantenimiento y solo admite lecturas",
+        ("maintenance", Fr) => "Le service est en maintenance et n'accepte que les lectures",
+        ("maintenance", De) => "Der Dienst wird gewartet und erlaubt nur Lesezugriffe",
+        ("maintenance", Pt) => "O serviço está em manutenção e aceita apenas leituras",
+
+        ("feature_disabled", Es) => "Esta función está desactivada temporalmente",
+        ("feature_disabled", Fr) => "Cette fonctionnalité est temporairement désactivée",
+        ("feature_disabled", De) => "Diese Funktion ist vorübergehend deaktiviert",
+        ("feature_disabled", Pt) => "Este recurso está temporariamente desativado",
+
+        _ => return None,
+    };
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("es-MX,es;q=0.9,en;q=0.8"), Locale::Es);
+        assert_eq!(negotiate("en;q=0.5, fr-CA;q=0.9"), Locale::Fr);
+        // Unsupported languages are skipped, and q=0 means "not this one"
+        assert_eq!(negotiate("ja, pt-BR;q=0.7"), Locale::Pt);
+        assert_eq!(negotiate("de;q=0, *"), Locale::En);
+        assert_eq!(negotiate("ja"), Locale::En);
+        assert_eq!(negotiate(""), Locale::En);
+        assert_eq!(negotiate("de;q=abc, fr"), Locale::Fr);
+    }
+
+    #[test]
+    fn test_catalog() {
+        assert_eq!(message("unauthorized", Locale::Es), Some("Se requiere autenticación"));
+        assert_eq!(message("unauthorized", Locale::En), None);
+        assert_eq!(message("no_such_code", Locale::De), None);
+    }
+}
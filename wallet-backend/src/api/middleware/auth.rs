@@ -5,15 +5,19 @@ use std::sync::Arc;
 use axum::{
     body::Body,
     extract::State,
-    http::{header, Request, StatusCode},
+    http::{header, HeaderName, Method, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 
 use crate::services::user_service::Claims;
 use crate::services::wallet_service::is_unlocked;
+use crate::storage::models::Scope;
 use crate::AppState;
 
+/// Header carrying the signing token from `POST /auth/signing-token`
+pub static SIGNING_TOKEN_HEADER: HeaderName = HeaderName::from_static("x-signing-token");
+
 /// Require valid JWT authentication
 pub async fn require_auth(
     State(state): State<Arc<AppState>>,
@@ -45,6 +49,8 @@ pub async fn require_auth(
     };
 
     // Add claims to request extensions for handlers to use
+    tracing::Span::current().record("user_id", claims.sub.as_str());
+    crate::reporting::set_user(&claims.sub);
     request.extensions_mut().insert(claims);
 
     Ok(next.run(request).await)
@@ -65,6 +71,8 @@ pub async fn optional_auth(
         if auth_header.starts_with("Bearer ") {
             let token = &auth_header[7..];
             if let Ok(claims) = state.user_service.validate_token(token) {
+                tracing::Span::current().record("user_id", claims.sub.as_str());
+                crate::reporting::set_user(&claims.sub);
                 request.extensions_mut().insert(claims);
             }
         }
@@ -120,11 +128,67 @@ pub async fn require_auth_and_unlocked(
         return Err((StatusCode::UNAUTHORIZED, "Wallet is locked"));
     }
 
+    tracing::Span::current().record("user_id", claims.sub.as_str());
+    crate::reporting::set_user(&claims.sub);
     request.extensions_mut().insert(claims);
 
     Ok(next.run(request).await)
 }
 
+/// Refuse a request whose token lacks `scope`. Runs after the auth layer.
+fn check_scope(request: &Request<Body>, scope: Scope) -> Result<(), (StatusCode, String)> {
+    match get_user_claims(request) {
+        Some(claims) if claims.has_scope(scope) => Ok(()),
+        Some(_) => Err((
+            StatusCode::FORBIDDEN,
+            format!("Token lacks the {} scope", scope.as_str()),
+        )),
+        None => Err((StatusCode::UNAUTHORIZED, "Not authenticated".to_string())),
+    }
+}
+
+/// Require the `trade` scope for anything but reads
+pub async fn require_trade_scope(
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, String)> {
+    if request.method() != Method::GET {
+        check_scope(&request, Scope::Trade)?;
+    }
+    Ok(next.run(request).await)
+}
+
+/// Require the `admin` scope
+pub async fn require_admin_scope(
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, String)> {
+    check_scope(&request, Scope::Admin)?;
+    Ok(next.run(request).await)
+}
+
+/// Require a signing token from the same session in `X-Signing-Token`.
+/// Runs after the auth layer.
+pub async fn require_signing_token(
+    State(state): State<Arc<AppState>>,
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let claims = get_user_claims(&request).ok_or((StatusCode::UNAUTHORIZED, "Not authenticated"))?;
+    let token = request
+        .headers()
+        .get(&SIGNING_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or((StatusCode::UNAUTHORIZED, "Missing signing token"))?;
+
+    state
+        .user_service
+        .validate_signing_token(token, claims)
+        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid or expired signing token"))?;
+
+    Ok(next.run(request).await)
+}
+
 /// Extract authenticated user claims from request
 pub fn get_user_claims(request: &Request<Body>) -> Option<&Claims> {
     request.extensions().get::<Claims>()
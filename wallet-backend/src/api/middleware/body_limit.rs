@@ -0,0 +1,256 @@
+//! Request body size limits and JSON shape guards
+//!
+//! Bodies are capped per endpoint class: credentials-only auth endpoints get a
+//! small limit, endpoints taking uploads (imports, batches, keyfiles, sync
+//! blobs) a large one, everything else the default. No limit exceeds `BODY_LIMIT_MAX_BYTES`.
+//! JSON bodies are additionally rejected when nested deeper than
+//! `JSON_MAX_DEPTH` or when an object repeats a key, before any handler parses
+//! them.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+
+use crate::config::app::BodyLimitConfig;
+use crate::AppState;
+
+/// Auth endpoints that only carry credentials
+const AUTH_PATHS: &[&str] = &[
+    "/users/register",
+    "/users/login",
+    "/users/refresh",
+    "/users/change-password",
+    "/users/wallet-login",
+    "/users/oauth/",
+];
+
+/// Endpoints that accept keyfiles alongside a password
+const KEYFILE_PATHS: &[&str] = &["/wallet/create", "/auth/unlock", "/wallet/backup/verify"];
+
+/// Whether axum's `Json` extractor would accept a body of this type:
+/// `application/json` or any `application/*+json`, with parameters
+fn is_json_content_type(content_type: &str) -> bool {
+    content_type.parse::<mime::Mime>().is_ok_and(|mime| {
+        mime.type_() == mime::APPLICATION
+            && (mime.subtype() == mime::JSON || mime.suffix() == Some(mime::JSON))
+    })
+}
+
+/// Size class of an endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EndpointClass {
+    Auth,
+    Upload,
+    Default,
+}
+
+impl EndpointClass {
+    /// Class of a path within an API version
+    fn of(path: &str) -> Self {
+        if AUTH_PATHS.iter().any(|p| path.starts_with(p)) {
+            EndpointClass::Auth
+        } else if path.ends_with("/import")
+            || path.contains("/batch")
+            || KEYFILE_PATHS.contains(&path)
+            || path == "/sync"
+        {
+            EndpointClass::Upload
+        } else {
+            EndpointClass::Default
+        }
+    }
+
+    fn limit(&self, config: &BodyLimitConfig) -> usize {
+        let limit = match self {
+            EndpointClass::Auth => config.auth_bytes,
+            EndpointClass::Upload => config.upload_bytes,
+            EndpointClass::Default => config.default_bytes,
+        };
+        limit.min(config.max_bytes)
+    }
+}
+
+/// Enforce the body limit for the endpoint and vet JSON bodies. Runs inside
+/// each versioned router, so paths arrive without the `/api/vN` prefix.
+pub async fn limit_body(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let config = &state.config.body_limit;
+    let limit = EndpointClass::of(request.uri().path()).limit(config);
+
+    // Refuse declared oversize bodies without reading them
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if declared.is_some_and(|len| len > limit) {
+        return Err(too_large(limit));
+    }
+
+    let is_json = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(is_json_content_type);
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, limit).await.map_err(|_| too_large(limit))?;
+
+    if is_json {
+        if let Err(e) = check_json(&bytes, config.max_json_depth) {
+            return Err((StatusCode::BAD_REQUEST, e).into_response());
+        }
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(bytes))).await)
+}
+
+fn too_large(limit: usize) -> Response {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!("Request body exceeds {} bytes", limit),
+    )
+        .into_response()
+}
+
+/// Reject nesting beyond `max_depth` and duplicate object keys. Malformed JSON
+/// passes, so handlers report syntax errors as they always have.
+fn check_json(bytes: &[u8], max_depth: usize) -> Result<(), String> {
+    if bytes.is_empty() {
+        return Ok(());
+    }
+
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    match (JsonGuard { depth: 0, max_depth }).deserialize(&mut deserializer) {
+        Err(e) if e.is_data() => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Walks a JSON document without building it
+#[derive(Clone, Copy)]
+struct JsonGuard {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl JsonGuard {
+    fn nested<E: de::Error>(&self) -> Result<Self, E> {
+        if self.depth >= self.max_depth {
+            return Err(E::custom(format!(
+                "JSON nested deeper than {} levels",
+                self.max_depth
+            )));
+        }
+        Ok(JsonGuard {
+            depth: self.depth + 1,
+            max_depth: self.max_depth,
+        })
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for JsonGuard {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonGuard {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let inner = self.nested()?;
+        while seq.next_element_seed(inner)?.is_some() {}
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let inner = self.nested()?;
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if !keys.insert(key.clone()) {
+                return Err(de::Error::custom(format!("duplicate JSON key '{}'", key)));
+            }
+            map.next_value_seed(inner)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_classes() {
+        assert_eq!(EndpointClass::of("/users/login"), EndpointClass::Auth);
+        assert_eq!(EndpointClass::of("/users/oauth/google/callback"), EndpointClass::Auth);
+        assert_eq!(EndpointClass::of("/wallet/import"), EndpointClass::Upload);
+        assert_eq!(EndpointClass::of("/auth/unlock"), EndpointClass::Upload);
+        assert_eq!(EndpointClass::of("/sync"), EndpointClass::Upload);
+        assert_eq!(EndpointClass::of("/contacts"), EndpointClass::Default);
+    }
+
+    #[test]
+    fn test_json_content_types() {
+        assert!(is_json_content_type("application/json"));
+        assert!(is_json_content_type("application/json;charset=utf-8"));
+        assert!(is_json_content_type("Application/JSON"));
+        assert!(is_json_content_type("application/x+json"));
+        assert!(is_json_content_type("application/cloudevents+json; charset=utf-8"));
+        assert!(!is_json_content_type("text/json"));
+        assert!(!is_json_content_type("application/octet-stream"));
+    }
+
+    #[test]
+    fn test_check_json() {
+        assert!(check_json(br#"{"a": [1, {"b": null}], "c": "x"}"#, 3).is_ok());
+        assert!(check_json(br#"[[[[1]]]]"#, 3).is_err());
+        assert!(check_json(br#"{"a": 1, "a": 2}"#, 3).is_err());
+        assert!(check_json(br#"{"a": {"b": 1}, "c": {"b": 2}}"#, 3).is_ok());
+        // Syntax errors are left to the handler
+        assert!(check_json(br#"{"a": "#, 3).is_ok());
+    }
+}
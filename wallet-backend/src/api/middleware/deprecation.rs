@@ -0,0 +1,39 @@
+//! API deprecation headers
+//!
+//! Marks responses from superseded API versions with `Deprecation` (RFC 9745),
+//! `Sunset` (RFC 8594) and a `Link` to the successor version.
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+/// v1 deprecation date (2026-10-17T00:00:00Z) as an RFC 9745 structured date
+const V1_DEPRECATION: &str = "@1792195200";
+/// v1 removal date
+const V1_SUNSET: &str = "Sat, 17 Apr 2027 00:00:00 GMT";
+/// Successor version link
+const V1_SUCCESSOR_LINK: &str = "</api/v2>; rel=\"successor-version\"";
+
+/// Add deprecation headers to every v1 response
+pub async fn deprecate_v1(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static(V1_DEPRECATION),
+    );
+    headers.insert(
+        HeaderName::from_static("sunset"),
+        HeaderValue::from_static(V1_SUNSET),
+    );
+    headers.append(
+        axum::http::header::LINK,
+        HeaderValue::from_static(V1_SUCCESSOR_LINK),
+    );
+
+    response
+}
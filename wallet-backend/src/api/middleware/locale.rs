@@ -0,0 +1,35 @@
+//! Error message language
+//!
+//! The language negotiated from `Accept-Language` is kept for the duration
+//! of the request, so error envelopes can be written in it wherever they are
+//! built.
+
+use axum::{
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::api::i18n::{self, Locale};
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+/// Language negotiated for the request being handled, if any
+pub fn current_locale() -> Option<Locale> {
+    LOCALE.try_with(|locale| *locale).ok()
+}
+
+/// Negotiate the request's language
+pub async fn negotiate_locale(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(i18n::negotiate)
+        .unwrap_or_default();
+
+    LOCALE.scope(locale, next.run(request)).await
+}
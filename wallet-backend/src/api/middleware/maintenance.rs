@@ -0,0 +1,31 @@
+//! Maintenance mode: the read-only API operators switch on at runtime
+//!
+//! Unlike safe mode this is set through the `maintenance` feature flag, and
+//! the admin routes stay writable so the flag can be lifted again.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::services::feature_flag_service::{self, FeatureFlag};
+use crate::AppState;
+
+/// Refuse every request but `GET`, `HEAD` and `OPTIONS` with a structured
+/// 503 while the `maintenance` flag is on
+pub async fn refuse_writes_in_maintenance(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        if let Err(disabled) = feature_flag_service::check(&state, FeatureFlag::Maintenance).await {
+            return disabled.into_response();
+        }
+    }
+    next.run(request).await
+}
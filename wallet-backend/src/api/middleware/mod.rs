@@ -1,5 +1,13 @@
 //! API middleware
 
 pub mod auth;
+pub mod body_limit;
 pub mod rate_limit;
 pub mod csrf;
+pub mod deprecation;
+pub mod locale;
+pub mod maintenance;
+pub mod request_id;
+pub mod safe_mode;
+pub mod share;
+pub mod tenant;
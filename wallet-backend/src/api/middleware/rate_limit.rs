@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 use axum::{
     extract::{ConnectInfo, Request, State},
@@ -11,39 +11,51 @@ use axum::{
 };
 use once_cell::sync::Lazy;
 
-// Simple in-memory rate limiter: IP -> (count, reset_time)
-// Limit: 100 requests per minute
-const MAX_REQUESTS: u32 = 100;
-const WINDOW_DURATION: Duration = Duration::from_secs(60);
+use crate::api::middleware::tenant::{current_tenant, DEFAULT_TENANT};
+use crate::AppState;
 
-type RateLimitStore = Arc<Mutex<HashMap<IpAddr, (u32, Instant)>>>;
+// Simple in-memory rate limiter: (tenant, IP) -> (count, reset_time)
+// Limits come from the tenant's override, else `Config::rate_limit`
+// (default: 100 requests per minute)
+
+type RateLimitStore = Arc<Mutex<HashMap<(String, IpAddr), (u32, Instant)>>>;
 
 static RATE_LIMITER: Lazy<RateLimitStore> = Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
 
 pub async fn rate_limit_middleware(
+    State(state): State<Arc<AppState>>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let ip = addr.ip();
-    let mut store = RATE_LIMITER.lock().unwrap();
-
-    let now = Instant::now();
-    let (count, reset_time) = store.entry(ip).or_insert((0, now + WINDOW_DURATION));
-
-    if now > *reset_time {
-        *count = 0;
-        *reset_time = now + WINDOW_DURATION;
+    let tenant = current_tenant();
+    let limits = match tenant.as_ref().and_then(|t| t.rate_limit.as_ref()) {
+        Some(limits) => limits,
+        None if state.config.rate_limit.enabled => &state.config.rate_limit,
+        None => return Ok(next.run(request).await),
+    };
+    let key = (
+        tenant.as_ref().map_or(DEFAULT_TENANT, |t| t.id.as_str()).to_string(),
+        addr.ip(),
+    );
+    // Scope the lock so it is released before awaiting
+    {
+        let mut store = RATE_LIMITER.lock().unwrap();
+
+        let now = Instant::now();
+        let (count, reset_time) = store.entry(key).or_insert((0, now + limits.window));
+
+        if now > *reset_time {
+            *count = 0;
+            *reset_time = now + limits.window;
+        }
+
+        if *count >= limits.max_requests {
+            return Err(StatusCode::TOO_MANY_REQUESTS);
+        }
+
+        *count += 1;
     }
 
-    if *count >= MAX_REQUESTS {
-        return Err(StatusCode::TOO_MANY_REQUESTS);
-    }
-
-    *count += 1;
-
-    // Release lock before awaiting
-    drop(store);
-
     Ok(next.run(request).await)
 }
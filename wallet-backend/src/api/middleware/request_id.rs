@@ -0,0 +1,56 @@
+//! Request correlation IDs
+//!
+//! Every request gets an `X-Request-Id`: the caller's, if it sent a usable one,
+//! otherwise a fresh UUID. The ID is echoed on the response, recorded on the
+//! request's tracing span and included in v2 error envelopes.
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller-supplied ID that is propagated rather than replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// ID of the request being handled, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Assign or propagate the request ID
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| is_valid(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    // Validated above or generated, so always a legal header value
+    let value = HeaderValue::from_str(&id).expect("request id is a valid header value");
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), value.clone());
+
+    let mut response = REQUEST_ID.scope(id, next.run(request)).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    response
+}
+
+/// Caller IDs end up in logs, so only short printable tokens are kept
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
@@ -0,0 +1,30 @@
+//! Safe mode: read-only service over a schema this binary doesn't know
+//!
+//! When the database has been migrated by a newer release the server still
+//! answers reads, but anything that could write is refused.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::AppState;
+
+/// Refuse every request but `GET`, `HEAD` and `OPTIONS` in safe mode
+pub async fn refuse_writes_in_safe_mode(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, impl IntoResponse> {
+    if state.safe_mode && !matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database schema is newer than this server; writes are disabled",
+        ));
+    }
+    Ok(next.run(request).await)
+}
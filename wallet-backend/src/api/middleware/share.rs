@@ -0,0 +1,54 @@
+//! Share link middleware: read-only access without a JWT
+//!
+//! The shared views take the token from `?token=` so links work when pasted
+//! into a browser, or from `X-Share-Token` for clients that keep URLs free of
+//! secrets.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, Request, State},
+    http::{HeaderName, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::share_service::{self, ShareError, ShareScope};
+use crate::AppState;
+
+/// Header carrying a share token, as an alternative to `?token=`
+pub static SHARE_TOKEN_HEADER: HeaderName = HeaderName::from_static("x-share-token");
+
+/// Require a live share token for a view of `scope` and hand its
+/// `ShareGrant` to the handler
+pub async fn require_share_token(
+    State((state, scope)): State<(Arc<AppState>, ShareScope)>,
+    Query(query): Query<HashMap<String, String>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, String)> {
+    let token = query
+        .get("token")
+        .map(String::as_str)
+        .or_else(|| {
+            request
+                .headers()
+                .get(&SHARE_TOKEN_HEADER)
+                .and_then(|value| value.to_str().ok())
+        })
+        .ok_or((StatusCode::UNAUTHORIZED, "Missing share token".to_string()))?;
+
+    let grant = share_service::validate(&state, token, scope).await.map_err(|e| {
+        let status = match e {
+            ShareError::WrongScope => StatusCode::FORBIDDEN,
+            ShareError::Expired | ShareError::Revoked => StatusCode::GONE,
+            ShareError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        (status, e.to_string())
+    })?;
+
+    request.extensions_mut().insert(grant);
+    Ok(next.run(request).await)
+}
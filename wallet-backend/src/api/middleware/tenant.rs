@@ -0,0 +1,130 @@
+//! Tenant resolution
+//!
+//! With `MULTI_TENANT` on, every request is mapped to a tenant by its
+//! `X-Api-Key` header, falling back to the `Host` name. The tenant is held in a
+//! task-local for the rest of the request so storage, auth and chain access
+//! are scoped without threading it through every call. With tenancy off (and
+//! outside requests, e.g. background tasks) the default tenant applies.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::chains::ChainClients;
+use crate::config::app::RateLimitConfig;
+use crate::services::tenant_service::{self, TenantServiceError};
+use crate::AppState;
+
+/// Tenant owning everything created in single-tenant mode
+pub const DEFAULT_TENANT: &str = "default";
+
+pub static API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Tenant a request is served for
+#[derive(Clone)]
+pub struct TenantContext {
+    pub id: String,
+    /// Replaces `Config::rate_limit` for this tenant
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Clients for the tenant's own RPC endpoints, if it configured any
+    pub chains: Option<ChainClients>,
+}
+
+tokio::task_local! {
+    static TENANT: Arc<TenantContext>;
+}
+
+/// Tenant of the request being handled, if one was resolved
+pub fn current_tenant() -> Option<Arc<TenantContext>> {
+    TENANT.try_with(Arc::clone).ok()
+}
+
+/// ID of the current tenant, the default tenant outside a resolved request
+pub fn current_tenant_id() -> String {
+    TENANT
+        .try_with(|tenant| tenant.id.clone())
+        .unwrap_or_else(|_| DEFAULT_TENANT.to_string())
+}
+
+/// Run `future` as `tenant`
+pub async fn with_tenant<F: std::future::Future>(tenant: TenantContext, future: F) -> F::Output {
+    TENANT.scope(Arc::new(tenant), future).await
+}
+
+/// Carry the current tenant into `future`, for work spawned off the request's
+/// task
+pub fn in_current_tenant<F: std::future::Future>(future: F) -> impl std::future::Future<Output = F::Output> {
+    let tenant = current_tenant();
+    async move {
+        match tenant {
+            Some(tenant) => TENANT.scope(tenant, future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// Identify the tenant and run the rest of the request as it
+pub async fn resolve_tenant(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, impl IntoResponse> {
+    if !state.config.tenancy.enabled {
+        return Ok(next.run(request).await);
+    }
+
+    let api_key = request
+        .headers()
+        .get(&API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok());
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok());
+
+    match tenant_service::identify(&state, api_key, host).await {
+        Ok(tenant) => {
+            tracing::Span::current().record("tenant_id", tenant.id.as_str());
+            Ok(with_tenant(tenant, next.run(request)).await)
+        }
+        Err(TenantServiceError::NotFound) => Err((StatusCode::UNAUTHORIZED, "Unknown tenant")),
+        Err(TenantServiceError::Disabled) => Err((StatusCode::FORBIDDEN, "Tenant is disabled")),
+        Err(e) => {
+            tracing::error!(error = %e, "Tenant lookup failed");
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Tenant lookup failed"))
+        }
+    }
+}
+
+/// Require the `TENANT_ADMIN_TOKEN` bearer token; the admin API does not
+/// exist without one configured
+pub async fn require_tenant_admin(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, impl IntoResponse> {
+    let Some(admin_token) = state.config.tenancy.admin_token.as_deref() else {
+        return Err((StatusCode::NOT_FOUND, "Not found"));
+    };
+
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    // Compare digests so the check doesn't leak the token byte by byte
+    match token {
+        Some(token)
+            if tenant_service::hash_api_key(token) == tenant_service::hash_api_key(admin_token) =>
+        {
+            Ok(next.run(request).await)
+        }
+        _ => Err((StatusCode::UNAUTHORIZED, "Invalid admin token")),
+    }
+}
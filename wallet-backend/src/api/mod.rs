@@ -1,5 +1,10 @@
 //! API layer
 
+pub mod dry_run;
+pub mod error;
+pub mod fields;
 pub mod handlers;
+pub mod i18n;
 pub mod middleware;
+pub mod pagination;
 pub mod routes;
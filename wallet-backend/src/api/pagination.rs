@@ -0,0 +1,220 @@
+//! Cursor pagination (v2+)
+//!
+//! Cursors are opaque to clients: a URL-safe base64 encoding of the sort key
+//! and row id of the last item on the previous page (keyset pagination).
+//! Every v2 list answers with the same `Paginated` envelope; its `total` is
+//! only counted when asked for with `include_total=true`.
+
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::api::error::ApiError;
+
+/// Default page size
+pub const DEFAULT_PAGE_LIMIT: u32 = 50;
+/// Largest page size a client may request
+pub const MAX_PAGE_LIMIT: u32 = 200;
+/// Rows an estimated total is counted up to
+pub const COUNT_ESTIMATE_CAP: u32 = 10_000;
+
+/// Pagination query params
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+    /// Count the matching items into `total`
+    #[serde(default)]
+    pub include_total: bool,
+}
+
+impl PageQuery {
+    /// Requested page size, clamped to `1..=MAX_PAGE_LIMIT`
+    pub fn limit(&self) -> u32 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT)
+    }
+
+    /// Decoded cursor; `None` on the first page
+    pub fn cursor(&self) -> Result<Option<Cursor>, ApiError> {
+        match self.cursor.as_deref() {
+            Some(raw) => Cursor::decode(raw).map(Some).ok_or_else(|| {
+                ApiError::new(StatusCode::BAD_REQUEST, "invalid_cursor", "Invalid cursor")
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+/// How a list counts its `total`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountStrategy {
+    /// Every matching item; for lists that stay small
+    Exact,
+    /// Up to `COUNT_ESTIMATE_CAP` items; for lists that grow without bound
+    Estimate,
+}
+
+impl CountStrategy {
+    /// Rows a count query may stop at
+    pub fn cap(self) -> Option<u32> {
+        match self {
+            CountStrategy::Exact => None,
+            CountStrategy::Estimate => Some(COUNT_ESTIMATE_CAP),
+        }
+    }
+}
+
+/// One page of results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page, absent on the last page
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+    /// Matching items across all pages, with `include_total=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// `total` stopped at the estimate cap, so there may be more
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_is_estimate: Option<bool>,
+}
+
+impl<T> Paginated<T> {
+    /// Build a page from `limit + 1` fetched rows; the extra row only signals
+    /// that another page exists
+    pub fn from_rows<R>(
+        mut rows: Vec<R>,
+        limit: u32,
+        key: impl Fn(&R) -> Cursor,
+        map: impl FnMut(R) -> T,
+    ) -> Self {
+        let has_more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+
+        let next_cursor = if has_more {
+            rows.last().map(|row| key(row).encode())
+        } else {
+            None
+        };
+
+        Self {
+            items: rows.into_iter().map(map).collect(),
+            next_cursor,
+            has_more,
+            total: None,
+            total_is_estimate: None,
+        }
+    }
+
+    /// Page over a list already held in memory, sorted by `key` - ascending,
+    /// or newest first when `descending` - for lists assembled outside SQL
+    pub fn from_sorted<R>(
+        rows: Vec<R>,
+        after: Option<&Cursor>,
+        limit: u32,
+        descending: bool,
+        key: impl Fn(&R) -> Cursor,
+        map: impl FnMut(R) -> T,
+    ) -> Self {
+        let rows: Vec<R> = rows
+            .into_iter()
+            .filter(|row| {
+                after.is_none_or(|after| {
+                    let position = key(row).position().cmp(&after.position());
+                    if descending {
+                        position.is_lt()
+                    } else {
+                        position.is_gt()
+                    }
+                })
+            })
+            .take(limit as usize + 1)
+            .collect();
+        Self::from_rows(rows, limit, key, map)
+    }
+
+    /// Set `total` from a count made with `strategy`
+    pub fn with_total(mut self, counted: u64, strategy: CountStrategy) -> Self {
+        self.total = Some(counted);
+        self.total_is_estimate =
+            Some(strategy.cap().is_some_and(|cap| counted >= u64::from(cap)));
+        self
+    }
+}
+
+/// Decoded keyset position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub sort_key: String,
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn new(sort_key: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            sort_key: sort_key.into(),
+            id: id.into(),
+        }
+    }
+
+    /// Sort key and id, in the order pages follow
+    fn position(&self) -> (&str, &str) {
+        (&self.sort_key, &self.id)
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}\n{}", self.sort_key, self.id))
+    }
+
+    /// Decode a client-supplied cursor; returns `None` if it is malformed
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let (sort_key, id) = text.split_once('\n')?;
+        Some(Self::new(sort_key, id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = Cursor::new("2026-01-01T00:00:00+00:00", "abc-123");
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(Cursor::decode("not a cursor!"), None);
+    }
+
+    #[test]
+    fn test_page_from_rows() {
+        let page = Paginated::from_rows(vec![1, 2, 3], 2, |n| Cursor::new("", n.to_string()), |n| n);
+        assert_eq!(page.items, vec![1, 2]);
+        assert!(page.has_more);
+        assert_eq!(Cursor::decode(&page.next_cursor.unwrap()).unwrap().id, "2");
+
+        let page = Paginated::from_rows(vec![1, 2], 2, |n| Cursor::new("", n.to_string()), |n| n);
+        assert!(page.next_cursor.is_none());
+        assert!(!page.has_more);
+    }
+
+    #[test]
+    fn test_page_from_sorted() {
+        let key = |n: &u32| Cursor::new("", n.to_string());
+        let newest_first = vec![5, 4, 3, 2, 1];
+        let page = Paginated::from_sorted(newest_first.clone(), None, 2, true, key, |n| n);
+        assert_eq!(page.items, vec![5, 4]);
+
+        let after = Cursor::decode(&page.next_cursor.unwrap()).unwrap();
+        let page = Paginated::from_sorted(newest_first, Some(&after), 2, true, key, |n| n)
+            .with_total(5, CountStrategy::Exact);
+        assert_eq!(page.items, vec![3, 2]);
+        assert!(page.has_more);
+        assert_eq!((page.total, page.total_is_estimate), (Some(5), Some(false)));
+
+        let page = page.with_total(u64::from(COUNT_ESTIMATE_CAP), CountStrategy::Estimate);
+        assert_eq!(page.total_is_estimate, Some(true));
+    }
+}
@@ -0,0 +1,398 @@
+//! Route tree shared by the API versions
+//!
+//! Every route and the auth it needs is declared once here. A version passes
+//! the handlers it answers with its own response shapes, and any routes only
+//! it has.
+
+use std::sync::Arc;
+
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put, MethodRouter},
+    Router,
+};
+
+use crate::AppState;
+use crate::api;
+
+use crate::api::handlers::{
+    accounts, alerts, analytics, auth, avatar, balance, bridge, buckets, cold_signing, contacts,
+    cross_chain, faucet, multisig, names, nft, notes, rent, security, session_keys, shares, staking, swap, sync,
+    templates, tenants, token_list, transaction, user_auth, user_tokens, watchlist, webhooks,
+};
+use crate::api::middleware::auth::{
+    optional_auth, require_admin_scope, require_auth, require_auth_and_unlocked,
+    require_signing_token, require_trade_scope,
+};
+use crate::api::middleware::share::require_share_token;
+use crate::services::share_service::ShareScope;
+
+/// Handlers that differ between versions
+pub(super) struct Handlers {
+    /// `GET /nfts/:chain/:address`
+    pub list_nfts: MethodRouter<Arc<AppState>>,
+    /// `GET /accounts`
+    pub list_accounts: MethodRouter<Arc<AppState>>,
+    /// `GET /contacts`
+    pub list_contacts: MethodRouter<Arc<AppState>>,
+    /// `GET /contacts/:id`
+    pub get_contact: MethodRouter<Arc<AppState>>,
+    /// `POST /contacts/:id/identity/refresh`
+    pub refresh_identity: MethodRouter<Arc<AppState>>,
+    /// `GET /multisig`
+    pub list_multisigs: MethodRouter<Arc<AppState>>,
+    /// `GET /alerts/notifications`
+    pub list_notifications: MethodRouter<Arc<AppState>>,
+    /// `GET /transactions/:chain/:address`
+    pub get_history: MethodRouter<Arc<AppState>>,
+    /// Routes only this version has, behind `require_auth`
+    pub auth_routes: Router<Arc<AppState>>,
+}
+
+/// Create the routes of a version answering with `handlers`
+pub(super) fn create_routes(state: Arc<AppState>, handlers: Handlers) -> Router<Arc<AppState>> {
+    // Public balance queries, personalised with custom tokens when signed in
+    let balance_routes = Router::new()
+        .route("/balances/:chain/:address", get(balance::get_balance))
+        .route("/tokens/:chain/:address", get(balance::get_tokens))
+        .route("/accounts/balances", get(balance::get_account_balances))
+        .layer(from_fn_with_state(state.clone(), optional_auth));
+
+    // Token list search for token pickers
+    let token_list_routes = Router::new().route("/tokens/search", get(token_list::search_tokens));
+
+    // Public routes - no authentication required
+    let public_routes = Router::new()
+        // User authentication
+        .route("/users/register", post(user_auth::register))
+        .route("/users/login", post(user_auth::login))
+        .route("/users/login/verify", post(user_auth::verify_login))
+        .route("/users/refresh", post(user_auth::refresh_token))
+        .route("/users/wallet-login/challenge", post(user_auth::wallet_challenge))
+        .route("/users/wallet-login", post(user_auth::wallet_login))
+        .route("/users/oauth/providers", get(user_auth::oauth_providers))
+        .route("/users/oauth/:provider/authorize", post(user_auth::oauth_authorize))
+        .route("/users/oauth/:provider/callback", post(user_auth::oauth_callback))
+        // Legacy wallet auth (for backwards compatibility)
+        .route("/auth/status", get(auth::status))
+        .route("/auth/csrf", get(auth::get_csrf_token))
+        // Tenant branding
+        .route("/tenant", get(tenants::current_tenant))
+        // Public fee queries (read-only, no auth needed)
+        .route("/transactions/estimate-fee", get(transaction::estimate_fee))
+        .route("/transactions/max-send", get(transaction::max_send))
+        // Public NFT queries
+        .route("/nfts/:chain/:address", handlers.list_nfts)
+        .route("/nfts/:chain/:address/:id", get(nft::get_nft))
+        // ENS / SNS name availability and price
+        .route("/names/:chain/:name", get(names::quote))
+        // Swap quotes (read-only)
+        .route("/swap/quote", get(swap::get_quote))
+        // Liquid staking pools with exchange rates and APYs (read-only)
+        .route("/staking/pools", get(staking::list_pools))
+        // L2s with an official bridge, with deposit windows (read-only)
+        .route("/bridge/networks", get(bridge::list_networks))
+        // Webhook event types, schemas and signature scheme
+        .route("/webhooks/events", get(webhooks::catalog))
+        // Check an address ownership proof (anyone may verify)
+        .route("/ownership/verify", post(accounts::verify_ownership))
+        // Wallet management - PUBLIC (init/auth)
+        .route("/auth/unlock", post(auth::unlock))
+        .route("/auth/unlock/challenge", post(auth::unlock_challenge))
+        .route("/auth/lock", post(auth::lock))
+        .route("/auth/reset", post(auth::reset))
+        .route("/wallet/create", post(auth::create_wallet))
+        .route("/wallet/import", post(auth::import_wallet))
+        .route("/wallet/validate-mnemonic", post(auth::validate_mnemonic))
+        .route("/wallet/wordlist/:lang", get(auth::get_wordlist_for_language))
+        // Accounts
+        .route("/accounts", handlers.list_accounts)
+        .route("/accounts", post(accounts::create_account))
+        .route("/accounts/:id", delete(accounts::delete_account))
+        // Address Book
+        .route("/contacts", handlers.list_contacts)
+        .route("/contacts", post(contacts::create_contact))
+        .route("/contacts/:id", handlers.get_contact)
+        .route("/contacts/:id", post(contacts::update_contact))
+        .route("/contacts/:id/delete", post(contacts::delete_contact))
+        .route("/qr/:chain/:address", get(contacts::generate_qr))
+        .route("/qr/payment-request", post(contacts::payment_request_qr))
+        .route("/qr/parse", post(contacts::parse_qr))
+        .route("/avatar/:chain/:address", get(avatar::get_avatar))
+        // Multi-sig
+        .route("/multisig", handlers.list_multisigs)
+        .route("/multisig/create", post(multisig::create_multisig))
+        .route("/multisig/:id", get(multisig::get_multisig))
+        .route(
+            "/multisig/:id/transactions",
+            get(multisig::get_transactions),
+        );
+
+    // Protected routes - require JWT authentication
+    let auth_routes = Router::new()
+        // User profile
+        .route("/users/me", get(user_auth::me))
+        .route("/users/logout", post(user_auth::logout))
+        // Short-lived token for sends, swaps and multisig executions
+        .route("/auth/signing-token", post(user_auth::signing_token))
+        .route("/users/addresses", get(user_auth::list_addresses))
+        // Sends above this need the password re-entered
+        .route(
+            "/users/me/large-transfer-threshold",
+            get(transaction::get_large_transfer_threshold),
+        )
+        // Wallet health / backup status
+        .route("/wallet/security-status", get(security::get_security_status))
+        .route("/wallet/backup/challenge", post(security::create_backup_challenge))
+        .route("/wallet/backup/verify", post(security::verify_backup))
+        // Custom token tracking
+        .route("/user-tokens", get(user_tokens::list_tokens))
+        .route("/user-tokens", post(user_tokens::add_token))
+        .route("/user-tokens/:id", post(user_tokens::update_token))
+        .route("/user-tokens/:id", delete(user_tokens::delete_token))
+        // Token order and small balance folding on the balance endpoints
+        .route("/users/me/token-view", get(user_tokens::get_token_view))
+        .route("/users/me/token-view", put(user_tokens::set_token_view))
+        .route("/users/me/token-view", delete(user_tokens::clear_token_view))
+        // Saved send drafts and quick actions
+        .route("/templates", get(templates::list_templates))
+        .route("/templates", post(templates::create_template))
+        .route("/templates/:id", post(templates::update_template))
+        .route("/templates/:id", delete(templates::delete_template))
+        .route("/templates/:id/apply", post(templates::apply_template))
+        // Private notes, sealed under the unlocked wallet's key
+        .route("/notes", get(notes::list_notes))
+        .route("/notes", post(notes::create_note))
+        .route("/notes/:id", get(notes::get_note))
+        .route("/notes/:id", post(notes::update_note))
+        .route("/notes/:id", delete(notes::delete_note))
+        // Expiring read-only share links
+        .route("/shares", get(shares::list_links))
+        .route("/shares", post(shares::create_link))
+        .route("/shares/:id/revoke", post(shares::revoke_link))
+        // Rent held by a Solana address's accounts
+        .route("/solana/rent/:address", get(rent::get_rent_report))
+        // Balance and price alerts
+        .route("/alerts", get(alerts::list_alerts))
+        .route("/alerts", post(alerts::create_alert))
+        .route("/alerts/notifications", handlers.list_notifications)
+        .route("/alerts/prices", get(alerts::list_price_alerts))
+        .route("/alerts/prices", post(alerts::create_price_alert))
+        .route("/alerts/prices/:id", post(alerts::update_price_alert))
+        .route("/alerts/prices/:id", delete(alerts::delete_price_alert))
+        .route("/alerts/:id", post(alerts::update_alert))
+        .route("/alerts/:id", delete(alerts::delete_alert))
+        // Watched external addresses
+        .route("/watchlist", get(watchlist::list_watches))
+        .route("/watchlist", post(watchlist::add_watch))
+        .route("/watchlist/:id", post(watchlist::update_watch))
+        .route("/watchlist/:id", delete(watchlist::remove_watch))
+        // Signed webhook deliveries: secret, test sends and delivery log
+        .route("/webhooks", get(webhooks::list_webhooks))
+        .route("/webhooks/secret", get(webhooks::get_secret))
+        .route("/webhooks/secret/rotate", post(webhooks::rotate_secret))
+        .route("/webhooks/:id/test", post(webhooks::send_test))
+        .route("/webhooks/:id/deliveries", get(webhooks::list_deliveries))
+        // Encrypted client sync data
+        .route("/sync", get(sync::get_sync))
+        .route("/sync", put(sync::put_sync))
+        .route("/sync", delete(sync::delete_sync))
+        // Sends pending too long to land on their own
+        .route("/transactions/stuck", get(transaction::list_stuck))
+        // Time-locked sends
+        .route("/transactions/scheduled", get(transaction::list_scheduled))
+        .route(
+            "/transactions/scheduled/:id/cancel",
+            post(transaction::cancel_scheduled),
+        )
+        // Solana Pay payments by reference key
+        .route(
+            "/transactions/references/:reference",
+            get(transaction::find_by_reference),
+        )
+        // Transaction tags and spending analytics grouped by them
+        .route("/transactions/tags/:id", get(analytics::get_tags))
+        .route("/transactions/tags/:id", put(analytics::set_tags))
+        .route("/analytics/spending", get(analytics::spending))
+        // Monthly account statements
+        .route("/accounts/:id/statement", get(accounts::get_statement))
+        // Re-read the balance now, updating the account's sync state
+        .route("/accounts/:id/sync", post(accounts::sync_account))
+        // Multisig deployment cost
+        .route("/multisig/estimate", post(multisig::estimate_deployment))
+        // Contacts saved twice, with a suggested merge
+        .route("/contacts/duplicates", get(contacts::list_duplicates))
+        // Re-resolve a contact's ENS / SNS identity
+        .route("/contacts/:id/identity/refresh", handlers.refresh_identity)
+        // Savings buckets, off-chain partitions of an account's balance
+        .route("/accounts/:id/buckets", get(buckets::list_buckets))
+        .route("/accounts/:id/buckets", post(buckets::create_bucket))
+        .route("/accounts/:id/buckets/transfer", post(buckets::transfer))
+        .route("/accounts/:id/buckets/:bucket_id", delete(buckets::delete_bucket))
+        // Names registered from the wallet
+        .route("/names", get(names::list_names))
+        // L2 bridge deposits and their status
+        .route("/bridge/transfers", get(bridge::list_transfers))
+        .route("/bridge/transfers/:id", get(bridge::get_transfer))
+        // Cross-chain swaps: quotes, and their progress by route ID
+        .route("/cross-chain/quote", get(cross_chain::get_quote))
+        .route("/cross-chain/swaps", get(cross_chain::list_swaps))
+        .route("/cross-chain/swaps/:route_id", get(cross_chain::get_swap))
+        // Devnet / Sepolia funding for test accounts
+        .route("/faucet/:chain/:address", post(faucet::request_funding))
+        // Re-fetch cached NFT metadata, picking up reveals
+        .route("/nfts/:chain/:address/refresh", post(nft::refresh_metadata))
+        // dApp session keys (signing checks the session's policy instead)
+        .route("/session-keys", get(session_keys::list_session_keys))
+        .route("/session-keys/:id", delete(session_keys::revoke_session_key))
+        // Multi-sig offline signing
+        .route(
+            "/multisig/:id/transactions/:tx_id/payload",
+            get(multisig::export_signing_payload),
+        )
+        .route(
+            "/multisig/:id/transactions/:tx_id/signatures",
+            post(multisig::submit_signature),
+        )
+        // Cold signing: unsigned export, then the offline signature back
+        .route(
+            "/cold-signing/transactions",
+            get(cold_signing::list_transfers).post(cold_signing::build_transfer),
+        )
+        .route("/cold-signing/transactions/:id", get(cold_signing::get_transfer))
+        .route("/cold-signing/transactions/:id/qr/:part", get(cold_signing::qr_part))
+        .route(
+            "/cold-signing/transactions/:id/signature",
+            post(cold_signing::submit_signature),
+        )
+        .merge(handlers.auth_routes)
+        .layer(from_fn_with_state(state.clone(), require_auth));
+
+    // Protected routes that also require wallet to be unlocked
+    let wallet_routes = Router::new()
+        // Transactions (requires signing)
+        .route("/transactions/send/confirm", post(transaction::confirm_send))
+        .route("/transactions/:chain/:address", handlers.get_history)
+        .route(
+            "/transactions/:chain/:address/export",
+            get(transaction::export_history),
+        )
+        .route(
+            "/transactions/stuck/:id/speed-up",
+            post(transaction::speed_up),
+        )
+        .route("/transactions/scheduled", post(transaction::schedule_send))
+        // Swap helpers (requires signing)
+        .route("/swap/wrap", post(swap::wrap_sol))
+        .route("/swap/unwrap", post(swap::unwrap_sol))
+        // Close empty token accounts to get their rent back (requires signing)
+        .route("/solana/rent/reclaim", post(rent::reclaim_rent))
+        // ENS / SNS registration and address updates (requires signing)
+        .route("/names/register", post(names::register))
+        .route("/names/:chain/:name/target", post(names::set_target))
+        // Session key authorization (encrypts the seed under the new key)
+        .route("/session-keys", post(session_keys::create_session_key))
+        // Sign a challenge with an account's key to prove it holds the address
+        .route("/accounts/:id/prove-ownership", post(accounts::prove_ownership))
+        // Multi-sig operations
+        .route("/multisig/:id/propose", post(multisig::propose_transaction))
+        .route(
+            "/multisig/:id/approve/:tx_id",
+            post(multisig::approve_transaction),
+        )
+        .layer(axum::middleware::from_fn(require_trade_scope))
+        .layer(from_fn_with_state(state.clone(), require_auth_and_unlocked));
+
+    // Derive from the seed without signing - unlocked wallet, any scope
+    let seed_routes = Router::new()
+        // Solana accounts under legacy derivation paths, for imported phrases
+        .route("/accounts/discover", get(accounts::discover_accounts))
+        .route("/accounts/discover", post(accounts::import_discovered))
+        .layer(from_fn_with_state(state.clone(), require_auth_and_unlocked));
+
+    // Session-key sends sign with the wallet but decrypt the seed with the
+    // session key, so they need the trade scope without an unlocked wallet
+    let session_routes = Router::new()
+        .route("/session-keys/:id/send", post(session_keys::send))
+        .layer(axum::middleware::from_fn(require_trade_scope))
+        .layer(from_fn_with_state(state.clone(), require_auth));
+
+    // Account security - require the admin scope
+    let account_routes = Router::new()
+        .route("/users/logout-all", post(user_auth::logout_all))
+        .route("/users/change-password", post(user_auth::change_password))
+        .route("/users/delete-account", post(user_auth::delete_account))
+        .route(
+            "/wallet/change-encryption-password",
+            post(auth::change_encryption_password),
+        )
+        .route("/users/addresses", post(user_auth::link_address))
+        .route("/users/addresses/:id", delete(user_auth::unlink_address))
+        .route(
+            "/users/me/large-transfer-threshold",
+            put(transaction::set_large_transfer_threshold),
+        )
+        .route(
+            "/users/me/large-transfer-threshold",
+            delete(transaction::clear_large_transfer_threshold),
+        )
+        .layer(axum::middleware::from_fn(require_admin_scope))
+        .layer(from_fn_with_state(state.clone(), require_auth));
+
+    // Account settings that change where it signs and sends - the admin
+    // scope on an unlocked wallet
+    let account_settings_routes = Router::new()
+        // Custom RPC endpoint (own node or private relay), health-checked on save
+        .route("/accounts/:id/rpc", put(accounts::set_rpc_url))
+        // Send through the private relay by default (Ethereum)
+        .route("/accounts/:id/mev-protection", put(accounts::set_mev_protect))
+        .layer(axum::middleware::from_fn(require_admin_scope))
+        .layer(from_fn_with_state(state.clone(), require_auth_and_unlocked));
+
+    // Sends, swaps and multisig executions - also require a signing token
+    let signing_routes = Router::new()
+        .route("/transactions/send", post(transaction::send))
+        // Send to a contact's stored address with its defaults
+        .route("/contacts/:id/send", post(contacts::send_to_contact))
+        .route("/swap/execute", post(swap::execute_swap))
+        // Stake pool deposits and withdrawals
+        .route("/staking/stake", post(staking::stake))
+        .route("/staking/unstake", post(staking::unstake))
+        // ETH deposits into L2 bridges
+        .route("/bridge/deposit", post(bridge::deposit))
+        // Swaps between Solana and Ethereum accounts along an aggregator's route
+        .route("/cross-chain/execute", post(cross_chain::execute))
+        .route(
+            "/multisig/:id/execute/:tx_id",
+            post(multisig::execute_transaction),
+        )
+        .layer(from_fn_with_state(state.clone(), require_signing_token))
+        .layer(axum::middleware::from_fn(require_trade_scope))
+        .layer(from_fn_with_state(state.clone(), require_auth_and_unlocked));
+
+    // Shared read-only views, opened with a share token instead of a JWT
+    let share_routes = Router::new()
+        .route("/shared/account", get(shares::shared_account))
+        .layer(from_fn_with_state((state.clone(), ShareScope::Account), require_share_token))
+        .merge(
+            Router::new()
+                .route("/shared/multisig", get(shares::shared_multisig))
+                .layer(from_fn_with_state((state.clone(), ShareScope::Multisig), require_share_token)),
+        );
+
+    // Combine all routes
+    Router::new()
+        .merge(public_routes)
+        .merge(balance_routes)
+        .merge(token_list_routes)
+        .merge(auth_routes)
+        .merge(wallet_routes)
+        .merge(seed_routes)
+        .merge(session_routes)
+        .merge(account_routes)
+        .merge(account_settings_routes)
+        .merge(signing_routes)
+        .merge(share_routes)
+        .layer(axum::middleware::from_fn(api::middleware::csrf::validate_csrf))
+        .layer(from_fn_with_state(state, api::middleware::body_limit::limit_body))
+}
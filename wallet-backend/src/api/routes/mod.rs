@@ -0,0 +1,101 @@
+//! API route definitions
+//!
+//! Both API versions are built from the route tree in `common`, with v2
+//! swapping in its own handlers where its responses differ. v1 is kept
+//! intact for existing clients but marked deprecated via response headers.
+
+mod common;
+mod v1;
+mod v2;
+
+use std::sync::Arc;
+
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware::{from_fn, from_fn_with_state},
+    routing::{get, post},
+    Router,
+};
+
+use crate::api::handlers::{
+    cors_origins, dashboard, feature_flags, jwt_keys, metrics, reconciliation, tenants,
+};
+use crate::api::middleware::deprecation::deprecate_v1;
+use crate::api::middleware::maintenance::refuse_writes_in_maintenance;
+use crate::api::middleware::rate_limit::rate_limit_middleware;
+use crate::api::middleware::safe_mode::refuse_writes_in_safe_mode;
+use crate::api::middleware::tenant::{require_tenant_admin, resolve_tenant};
+use crate::AppState;
+
+/// Create all versioned API routes
+pub fn create_routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    let router = Router::new()
+        .nest(
+            "/api/v1",
+            v1::create_routes(state.clone()).layer(from_fn(deprecate_v1)),
+        )
+        .nest("/api/v2", v2::create_routes(state.clone()));
+
+    // Tenants may set their own limits even when the global one is off
+    let router = if state.config.rate_limit.enabled || state.config.tenancy.enabled {
+        router.layer(from_fn_with_state(state.clone(), rate_limit_middleware))
+    } else {
+        router
+    };
+
+    // Resolved outermost so the rate limiter sees the tenant. Per-endpoint
+    // body limits are applied within each version; this caps the rest.
+    // Maintenance mode leaves the admin routes writable so it can be lifted.
+    router
+        .layer(from_fn_with_state(state.clone(), refuse_writes_in_maintenance))
+        .layer(from_fn_with_state(state.clone(), resolve_tenant))
+        .nest("/api/admin", admin_routes(state.clone()))
+        .merge(operator_routes(state.clone()))
+        .layer(from_fn_with_state(state.clone(), refuse_writes_in_safe_mode))
+        .layer(DefaultBodyLimit::max(state.config.body_limit.max_bytes))
+}
+
+/// Tenant, signing key, feature flag and CORS origin administration,
+/// guarded by `TENANT_ADMIN_TOKEN` rather than tenant resolution
+fn admin_routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/tenants", get(tenants::list_tenants))
+        .route("/tenants", post(tenants::create_tenant))
+        .route("/tenants/:id", get(tenants::get_tenant))
+        .route("/tenants/:id", post(tenants::update_tenant))
+        .route("/tenants/:id/api-key", post(tenants::rotate_api_key))
+        .route("/jwt-keys", get(jwt_keys::list_keys))
+        .route("/jwt-keys", post(jwt_keys::add_key))
+        .route("/jwt-keys/:kid/retire", post(jwt_keys::retire_key))
+        .route("/flags", get(feature_flags::list_flags))
+        .route("/flags/:name", post(feature_flags::set_flag))
+        .route(
+            "/cors-origins",
+            get(cors_origins::list_origins)
+                .post(cors_origins::add_origin)
+                .delete(cors_origins::remove_origin),
+        )
+        .layer(from_fn_with_state(state, require_tenant_admin))
+}
+
+/// Metrics and the admin dashboard for operators, outside tenant resolution
+/// and rate limiting so they work in any mode. The usage summary, schema
+/// status, reconciliation report and dashboard status need the admin token.
+fn operator_routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    let usage = Router::new()
+        .route("/api/v1/admin/rpc-usage", get(metrics::rpc_usage))
+        .route("/api/v2/admin/rpc-usage", get(metrics::rpc_usage))
+        .route("/api/v1/admin/schema", get(metrics::schema))
+        .route("/api/v2/admin/schema", get(metrics::schema))
+        .route("/api/v1/admin/reconciliation", get(reconciliation::get_report))
+        .route("/api/v2/admin/reconciliation", get(reconciliation::get_report))
+        .route("/api/v1/admin/status", get(dashboard::status))
+        .route("/api/v2/admin/status", get(dashboard::status))
+        .layer(from_fn_with_state(state, require_tenant_admin));
+
+    Router::new()
+        .route("/metrics", get(metrics::metrics))
+        .route("/admin", get(dashboard::index))
+        .route("/admin/*path", get(dashboard::file))
+        .merge(usage)
+}
@@ -0,0 +1,31 @@
+//! v1 API routes (deprecated, superseded by v2)
+
+use std::sync::Arc;
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::api::handlers::{accounts, alerts, contacts, multisig, nft, transaction};
+use crate::AppState;
+
+use super::common::{self, Handlers};
+
+/// Create v1 API routes
+pub fn create_routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    common::create_routes(
+        state,
+        Handlers {
+            list_nfts: get(nft::list_nfts),
+            list_accounts: get(accounts::list_accounts),
+            list_contacts: get(contacts::list_contacts),
+            get_contact: get(contacts::get_contact),
+            refresh_identity: post(contacts::refresh_identity),
+            list_multisigs: get(multisig::list_multisigs),
+            list_notifications: get(alerts::list_notifications),
+            get_history: get(transaction::get_history),
+            auth_routes: Router::new(),
+        },
+    )
+}
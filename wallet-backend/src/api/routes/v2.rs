@@ -0,0 +1,41 @@
+//! v2 API routes
+//!
+//! Same surface as v1, with typed timestamps, cursor-paginated lists and the
+//! structured error envelope.
+
+use std::sync::Arc;
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::api;
+use crate::api::handlers::v2;
+use crate::AppState;
+
+use super::common::{self, Handlers};
+
+/// Create v2 API routes
+pub fn create_routes(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    let auth_routes = Router::new();
+    // Read-only GraphQL over the same services
+    #[cfg(feature = "graphql")]
+    let auth_routes = auth_routes.route("/graphql", post(crate::graphql::execute));
+
+    common::create_routes(
+        state,
+        Handlers {
+            list_nfts: get(v2::nft::list_nfts),
+            list_accounts: get(v2::accounts::list_accounts),
+            list_contacts: get(v2::contacts::list_contacts),
+            get_contact: get(v2::contacts::get_contact),
+            refresh_identity: post(v2::contacts::refresh_identity),
+            list_multisigs: get(v2::multisig::list_multisigs),
+            list_notifications: get(v2::alerts::list_notifications),
+            get_history: get(v2::transaction::get_history),
+            auth_routes,
+        },
+    )
+    .layer(axum::middleware::from_fn(api::error::envelope_errors))
+}
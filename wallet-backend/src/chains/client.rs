@@ -0,0 +1,833 @@
+//! Chain client abstraction
+//!
+//! Services reach the networks only through `ChainClient`, so the live RPC
+//! implementations can be swapped for mocks in tests.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::{Chain, SecureSeed};
+
+use super::ethereum::EthereumClient;
+use super::metered::{MeteredClient, RpcMetrics};
+use super::solana::{JitoBundle, SolanaClient};
+
+#[derive(Debug, Error)]
+pub enum ChainClientError {
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    /// Amounts are in the base unit of the asset being checked
+    #[error("Insufficient balance: {required} required, {available} available")]
+    InsufficientBalance { required: u128, available: u128 },
+    #[error("{0}")]
+    RentExemption(String),
+    #[error("RPC error: {0}")]
+    Rpc(String),
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+}
+
+/// Native and token balances of an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainBalance {
+    /// Native balance in display units (SOL, ETH)
+    pub native_balance: String,
+    /// Native balance in base units (lamports, wei)
+    pub native_balance_raw: String,
+    pub native_symbol: String,
+    pub tokens: Vec<ChainTokenBalance>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainTokenBalance {
+    pub address: String,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub balance: String,
+    pub decimals: u8,
+    pub ui_amount: f64,
+    /// Set for Solana Token-2022 mints
+    pub extensions: Option<TokenExtensions>,
+}
+
+/// Token-2022 extensions that change what a transfer delivers or how a
+/// balance reads
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenExtensions {
+    /// Fee withheld from each transfer in the current epoch
+    pub transfer_fee: Option<TransferFee>,
+    /// Annual rate; interest accrues in the UI amount only
+    pub interest_rate_bps: Option<i16>,
+}
+
+/// Token-2022 transfer fee, taken out of the amount sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferFee {
+    pub basis_points: u16,
+    /// Cap per transfer, in base units
+    pub maximum_fee: u64,
+}
+
+impl TransferFee {
+    /// Fee withheld when sending `amount` base units, rounded up as the
+    /// token program does
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(10_000);
+        fee.min(self.maximum_fee as u128) as u64
+    }
+}
+
+/// On-chain token metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub decimals: u8,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Largest amount an address can send after network fees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaxSend {
+    /// Base units (lamports, wei, or raw token amount)
+    pub max_amount: String,
+    pub max_ui_amount: String,
+    /// Network fee in the chain's native base unit
+    pub fee: String,
+    /// Whether the native balance covers the fee
+    pub fee_covered: bool,
+}
+
+/// Name-service identity an address has published (ENS, SNS)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Identity {
+    /// Primary name, e.g. `vitalik.eth` or `bonfida.sol`
+    pub name: String,
+    /// Avatar URI as published; may be `ipfs://` or an NFT reference
+    pub avatar: Option<String>,
+    /// Profile text records that are set, keyed by record name
+    pub records: BTreeMap<String, String>,
+}
+
+/// Who currently holds an NFT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "address", rename_all = "snake_case")]
+pub enum NftHolder {
+    /// A wallet address
+    Address(String),
+    /// A program or contract, usually a marketplace holding a listing
+    Escrow(String),
+    /// The token no longer exists
+    Burned,
+}
+
+/// An NFT's metadata as currently published
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NftMetadata {
+    /// Metadata URI recorded on chain; collections change it to reveal
+    pub uri: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub collection_name: Option<String>,
+    /// The off-chain document the URI points to
+    pub document: Option<serde_json::Value>,
+    /// On-chain royalty terms and collection (Metaplex); `None` where the
+    /// chain doesn't record them
+    pub royalties: Option<NftRoyalties>,
+    pub collection: Option<NftCollectionKey>,
+    pub uses: Option<NftUses>,
+}
+
+/// Royalty terms of an NFT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftRoyalties {
+    /// Share of each sale paid out, in basis points
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<NftCreator>,
+}
+
+/// A creator sharing in an NFT's royalties
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftCreator {
+    pub address: String,
+    /// Signed by the creator, not just claimed by the minter
+    pub verified: bool,
+    /// Percentage of the royalties paid to this creator
+    pub share: u8,
+}
+
+/// Collection an NFT claims to belong to. Only a verified claim has been
+/// signed by the collection's authority.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftCollectionKey {
+    pub address: String,
+    pub verified: bool,
+}
+
+/// Limited uses of an NFT, such as a ticket good for ten entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftUses {
+    /// `burn`, `multiple` or `single`
+    pub method: String,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+impl NftMetadata {
+    /// Take the standard fields from an off-chain metadata document
+    pub fn from_document(uri: String, document: Option<serde_json::Value>) -> Self {
+        let get = |key: &str| document.as_ref()?.get(key);
+        let text = |value: Option<&serde_json::Value>| value?.as_str().map(str::to_string);
+        let name = text(get("name"));
+        let description = text(get("description"));
+        let image_url = text(get("image"));
+        let collection_name = text(get("collection").and_then(|c| c.get("name")));
+        Self {
+            uri,
+            name,
+            description,
+            image_url,
+            collection_name,
+            document,
+            royalties: None,
+            collection: None,
+            uses: None,
+        }
+    }
+}
+
+/// Outgoing transfer, with the amount as the user entered it
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub to: String,
+    /// Token contract or mint; `None` for the native coin
+    pub token: Option<String>,
+    pub amount: String,
+    /// Send the whole balance minus fees
+    pub drain_all: bool,
+    /// Pending transaction this one supersedes (Ethereum)
+    pub replaces: Option<Replacement>,
+    /// Memo recorded with the transfer (Solana)
+    pub memo: Option<String>,
+    /// Solana Pay reference keys, added as read-only accounts so the
+    /// recipient can find the transaction
+    pub references: Vec<String>,
+    /// Private relay (Flashbots Protect) to broadcast through instead of the
+    /// public mempool (Ethereum)
+    pub private_rpc: Option<String>,
+    /// Submit as a tipped Jito bundle instead of straight to the RPC node
+    /// (Solana)
+    pub jito: Option<JitoBundle>,
+    /// Check, build and simulate the transfer without broadcasting it or
+    /// reserving a nonce; the result has status `simulated`
+    pub dry_run: bool,
+}
+
+/// Nonce and gas price of a pending Ethereum transaction being replaced; the
+/// replacement reuses the nonce and outbids the gas price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Replacement {
+    pub nonce: u64,
+    pub gas_price: u128,
+}
+
+/// Where a transfer was broadcast, for spotting it if it never lands
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Broadcast {
+    /// Block number (Ethereum) or block height (Solana) when sent
+    pub height: u64,
+    /// Account nonce used (Ethereum)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// Gas price offered in wei, as a decimal string (Ethereum)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    /// Last block height at which the transaction's blockhash is accepted
+    /// (Solana)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_valid_height: Option<u64>,
+    /// Sent to a private relay and not yet to the public mempool (Ethereum)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub private: bool,
+    /// Signed transaction, hex-encoded, for broadcasting publicly if the
+    /// private relay doesn't get it included (Ethereum)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_tx: Option<String>,
+}
+
+impl Broadcast {
+    /// What a speed-up needs to replace this transaction, if it can be
+    pub fn replacement(&self) -> Option<Replacement> {
+        Some(Replacement {
+            nonce: self.nonce?,
+            gas_price: self.gas_price.as_deref()?.parse().ok()?,
+        })
+    }
+}
+
+/// Change to one address's balance of one asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceChange {
+    /// Wallet address (the owner, for Solana token accounts)
+    pub address: String,
+    /// Token contract or mint; `None` for the native coin
+    pub token: Option<String>,
+    /// Signed amount in base units
+    pub delta: String,
+}
+
+/// Balance changes a transaction makes, anticipated or observed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxEffects {
+    pub changes: Vec<BalanceChange>,
+    /// Network fee in the chain's native base unit
+    pub fee: String,
+}
+
+impl TxEffects {
+    /// Whether `actual` did what these effects anticipated. The fee payer's
+    /// native change may differ by the fee, which is only known once the
+    /// transaction lands; every other change must match exactly.
+    pub fn matches(&self, actual: &TxEffects, fee_payer: &str) -> bool {
+        let fee_payer = fee_payer.to_lowercase();
+        let tolerance = parse_delta(&self.fee)
+            .unsigned_abs()
+            .max(parse_delta(&actual.fee).unsigned_abs());
+
+        let expected = self.by_asset();
+        let actual = actual.by_asset();
+        let keys: std::collections::HashSet<_> = expected.keys().chain(actual.keys()).collect();
+
+        keys.into_iter().all(|key| {
+            let want = expected.get(key).copied().unwrap_or(0);
+            let got = actual.get(key).copied().unwrap_or(0);
+            if key.0 == fee_payer && key.1.is_none() {
+                want.abs_diff(got) <= tolerance
+            } else {
+                want == got
+            }
+        })
+    }
+
+    /// Net non-zero delta per (address, token); addresses compare
+    /// case-insensitively for Ethereum
+    fn by_asset(&self) -> HashMap<(String, Option<String>), i128> {
+        let mut totals = HashMap::new();
+        for change in &self.changes {
+            *totals
+                .entry((
+                    change.address.to_lowercase(),
+                    change.token.as_ref().map(|t| t.to_lowercase()),
+                ))
+                .or_insert(0) += parse_delta(&change.delta);
+        }
+        totals.retain(|_, delta| *delta != 0);
+        totals
+    }
+}
+
+fn parse_delta(delta: &str) -> i128 {
+    delta.parse().unwrap_or(0)
+}
+
+/// Outcome of a transaction once it has landed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfirmedEffects {
+    /// `confirmed` or `failed`
+    pub status: String,
+    pub block_number: Option<i64>,
+    /// Blocks built on the transaction's so far, counting its own
+    #[serde(default)]
+    pub confirmations: u64,
+    pub effects: TxEffects,
+    /// Ethereum receipt; `None` on Solana
+    #[serde(default)]
+    pub receipt: Option<TxReceipt>,
+}
+
+/// Receipt of a mined Ethereum transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxReceipt {
+    pub gas_used: u64,
+    /// Wei per gas actually paid, base fee plus tip
+    pub effective_gas_price: String,
+    /// `false` when the transaction reverted
+    pub succeeded: bool,
+    pub logs_count: u64,
+    /// Address of the contract a deployment created
+    pub contract_address: Option<String>,
+}
+
+/// Broadcast transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentTransfer {
+    pub tx_hash: String,
+    pub status: String,
+    /// Amount actually sent: in the token's base units for token sends
+    pub amount: String,
+    /// Decimals of the token sent; `None` for native sends
+    pub token_decimals: Option<u32>,
+    /// Balance changes anticipated before broadcast
+    pub expected: Option<TxEffects>,
+    pub broadcast: Option<Broadcast>,
+}
+
+/// A transaction that includes a reference key among its accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferencedTransaction {
+    pub signature: String,
+    pub status: String,
+    /// Unix timestamp of the block it landed in
+    pub block_time: Option<i64>,
+    pub memo: Option<String>,
+}
+
+/// A transaction from an address's on-chain history, with the transfer it
+/// made where one could be read from it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub hash: String,
+    pub status: String,
+    pub block_number: Option<u64>,
+    /// Unix timestamp of the block it landed in
+    pub block_time: Option<i64>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Exact decimal in the asset's units
+    pub amount: Option<String>,
+    /// Base units
+    pub amount_raw: Option<String>,
+    /// Mint or contract of a token transfer; `None` for the native coin
+    pub token_address: Option<String>,
+    pub memo: Option<String>,
+}
+
+/// Availability and price of a name under the chain's name service (ENS
+/// `.eth`, SNS `.sol`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NameQuote {
+    pub name: String,
+    pub available: bool,
+    /// Address the name currently points to
+    pub address: Option<String>,
+    /// Price of registering for the quoted period, in `price_unit`
+    pub price: String,
+    /// `ETH` for ENS; `USD` for SNS, paid in the proxy's payment token
+    pub price_unit: String,
+    /// Period the price covers; `None` for names bought outright (SNS)
+    pub years: Option<u32>,
+    /// Price of renewing for one more year; `None` when names don't expire
+    pub renewal_price: Option<String>,
+    /// Unix timestamp the current registration lapses at
+    pub expires_at: Option<i64>,
+}
+
+/// Outcome of a name registration step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum NameRegistration {
+    /// ENS commitment sent; register with `secret` once `ready_at` (unix
+    /// timestamp) has passed
+    Committed {
+        tx_hash: String,
+        secret: String,
+        ready_at: i64,
+    },
+    Registered {
+        tx_hash: String,
+        /// Unix timestamp; `None` for names that don't expire
+        expires_at: Option<i64>,
+    },
+}
+
+/// Supply and fees of a stake pool that mints a liquid staking token
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StakePoolState {
+    pub address: String,
+    /// Liquid staking token the pool mints
+    pub pool_mint: String,
+    /// Lamports under management and pool tokens in circulation
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    /// The same totals as of the previous epoch, for the yield
+    pub last_epoch_total_lamports: u64,
+    pub last_epoch_pool_token_supply: u64,
+    pub last_update_epoch: u64,
+    /// Fees taken from SOL deposits and withdrawals, in basis points
+    pub sol_deposit_fee_bps: u32,
+    pub sol_withdrawal_fee_bps: u32,
+}
+
+/// What deploying a multi-sig costs its payer, in the chain's native base
+/// unit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeploymentCost {
+    /// Rent-exempt minimum of the accounts the deployment creates (Solana)
+    pub rent: u128,
+    /// Signature fees, or the gas at the current gas price
+    pub network_fee: u128,
+    /// Gas the deployment is expected to use (Ethereum)
+    pub gas: Option<u64>,
+}
+
+impl DeploymentCost {
+    pub fn total(&self) -> u128 {
+        self.rent + self.network_fee
+    }
+}
+
+/// ETH deposited into an L2's bridge on L1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeDeposit {
+    pub tx_hash: String,
+    /// Bridge contract the deposit was sent to
+    pub contract: String,
+    /// Chain id of the L1 network it was sent on
+    pub l1_chain_id: u64,
+}
+
+/// ERC-20 allowance the source call of a route spends from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenApproval {
+    pub token: String,
+    pub spender: String,
+    /// Base units
+    pub amount: u128,
+}
+
+/// Source transaction of a cross-chain route, as built by the aggregator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "chain", rename_all = "snake_case")]
+pub enum RouteTransaction {
+    /// Contract call, sent after approving the token it spends if any
+    Ethereum {
+        to: String,
+        /// Hex-encoded calldata
+        data: String,
+        /// Wei sent with the call
+        value: u128,
+        gas_limit: Option<u64>,
+        approval: Option<TokenApproval>,
+    },
+    /// Base64 serialized transaction for the sender to sign as fee payer
+    Solana { transaction: String },
+}
+
+/// Transactions sent for the source step of a route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteSubmission {
+    pub tx_hash: String,
+    /// Token approval sent ahead of the route's call (Ethereum)
+    pub approval_tx_hash: Option<String>,
+}
+
+/// A transfer built for a signer away from the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedTransfer {
+    /// Base64 unsigned transaction: bincode (Solana) or RLP (Ethereum)
+    pub payload: String,
+    /// Hash the signer signs (Ethereum only); Solana signers sign the
+    /// transaction's message
+    pub signing_hash: Option<String>,
+    /// Network fee in base units
+    pub fee: u128,
+}
+
+/// Network operations for a single chain
+#[async_trait]
+pub trait ChainClient: Send + Sync {
+    /// Native and token balances of an address
+    async fn balance(&self, address: &str) -> Result<ChainBalance, ChainClientError>;
+
+    /// Balance of a single token, zero if the address holds none
+    async fn token_balance(
+        &self,
+        address: &str,
+        token: &str,
+    ) -> Result<ChainTokenBalance, ChainClientError>;
+
+    /// Look up a token contract or mint; `InvalidAddress` if it isn't one
+    async fn token_metadata(&self, token: &str) -> Result<TokenMetadata, ChainClientError>;
+
+    /// Maximum sendable amount of the native coin or `token`
+    async fn max_send(
+        &self,
+        address: &str,
+        token: Option<&str>,
+    ) -> Result<MaxSend, ChainClientError>;
+
+    /// Reverse-resolve the primary name of an address and its profile
+    /// records; `None` if it has no name
+    async fn resolve_identity(&self, address: &str) -> Result<Option<Identity>, ChainClientError>;
+
+    /// Current holder of the NFT `token_id` of `token_address` (the mint on
+    /// Solana, where `token_id` is ignored)
+    async fn nft_holder(
+        &self,
+        token_address: &str,
+        token_id: &str,
+    ) -> Result<NftHolder, ChainClientError>;
+
+    /// Re-read the metadata URI of an NFT and fetch the document it points to
+    async fn nft_metadata(
+        &self,
+        token_address: &str,
+        token_id: &str,
+    ) -> Result<NftMetadata, ChainClientError>;
+
+    /// Sign and broadcast a transfer from the account at `derivation_path`,
+    /// or only simulate it for a `dry_run`. Solana always signs with a fresh blockhash, so a transfer whose
+    /// blockhash expired can simply be sent again.
+    async fn send(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transfer: Transfer,
+    ) -> Result<SentTransfer, ChainClientError>;
+
+    /// Broadcast an already signed transaction, hex-encoded (Ethereum) or
+    /// base64 (Solana), to the public mempool and return its hash
+    async fn broadcast_raw(&self, raw_tx: &str) -> Result<String, ChainClientError>;
+
+    /// Build an unsigned native transfer of `amount` base units for a signer
+    /// away from the server, checking `from` can pay for it
+    async fn build_unsigned_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<UnsignedTransfer, ChainClientError>;
+
+    /// Current block number (Ethereum) or block height (Solana)
+    async fn block_height(&self) -> Result<u64, ChainClientError>;
+
+    /// Whether the address has been used on chain, for discovering accounts
+    /// of an imported wallet
+    async fn has_activity(&self, address: &str) -> Result<bool, ChainClientError>;
+
+    /// Fund `address` with `amount` base units from the network's faucet,
+    /// where it serves one (Solana devnet / testnet). Returns the
+    /// transaction hash.
+    async fn request_airdrop(&self, address: &str, amount: u64) -> Result<String, ChainClientError>;
+
+    /// What a sent transaction did to the balances of `addresses`; `None`
+    /// while it is still pending
+    async fn transaction_effects(
+        &self,
+        tx_hash: &str,
+        addresses: &[String],
+    ) -> Result<Option<ConfirmedEffects>, ChainClientError>;
+
+    /// Recent transactions that include `reference` among their accounts,
+    /// newest first (Solana Pay reference keys)
+    async fn find_by_reference(
+        &self,
+        reference: &str,
+        limit: usize,
+    ) -> Result<Vec<ReferencedTransaction>, ChainClientError>;
+
+    /// Recent transactions of `address`, newest first
+    async fn history(&self, address: &str, limit: usize) -> Result<Vec<HistoryEntry>, ChainClientError>;
+
+    /// Availability and price of `name` (with its `.eth` / `.sol` suffix)
+    /// for `years` of registration
+    async fn name_quote(&self, name: &str, years: u32) -> Result<NameQuote, ChainClientError>;
+
+    /// Register `name` to the account at `derivation_path`, paying for
+    /// `years` where names expire. ENS takes two calls: without a
+    /// `commitment` secret the commitment is sent, and registering with it
+    /// completes once the commitment is old enough.
+    async fn register_name(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        years: u32,
+        commitment: Option<&str>,
+    ) -> Result<NameRegistration, ChainClientError>;
+
+    /// Point a name held by the account at `target`: the ENS address
+    /// record, or the owner of an SNS domain (which transfers it). Returns
+    /// the transaction hash.
+    async fn set_name_target(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        target: &str,
+    ) -> Result<String, ChainClientError>;
+
+    /// Supply, totals and fees of the stake pool at `pool` (Solana)
+    async fn stake_pool(&self, pool: &str) -> Result<StakePoolState, ChainClientError>;
+
+    /// Deposit `lamports` of the account's SOL into a stake pool for its
+    /// liquid staking token. Returns the transaction hash.
+    async fn stake_pool_deposit(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        pool: &str,
+        lamports: u64,
+    ) -> Result<String, ChainClientError>;
+
+    /// Redeem `pool_tokens` of the account's liquid staking token for SOL
+    /// from the pool's reserve. Returns the transaction hash.
+    async fn stake_pool_withdraw(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        pool: &str,
+        pool_tokens: u64,
+    ) -> Result<String, ChainClientError>;
+
+    /// Deposit `amount` wei of the account's ETH into the official bridge
+    /// of an L2 (`arbitrum`, `optimism`, `base`), to be credited to the
+    /// same address there
+    async fn bridge_deposit(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        network: &str,
+        amount: u128,
+    ) -> Result<BridgeDeposit, ChainClientError>;
+
+    /// Sign and broadcast the source step of a cross-chain route from the
+    /// account at `derivation_path`, approving the token it spends first
+    /// where the route needs that
+    async fn send_route_transaction(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transaction: &RouteTransaction,
+    ) -> Result<RouteSubmission, ChainClientError>;
+
+    /// Estimated cost of deploying a multi-sig with `owners` (Squads on
+    /// Solana, a Safe proxy on Ethereum)
+    async fn multisig_deployment_cost(
+        &self,
+        owners: &[String],
+        threshold: u8,
+    ) -> Result<DeploymentCost, ChainClientError>;
+
+    /// Create a multi-sig wallet and return its address
+    async fn create_multisig(
+        &self,
+        seed: &SecureSeed,
+        name: &str,
+        threshold: u8,
+        owners: &[String],
+    ) -> Result<String, ChainClientError>;
+}
+
+/// One client per supported chain
+#[derive(Clone)]
+pub struct ChainClients {
+    pub solana: Arc<dyn ChainClient>,
+    pub ethereum: Arc<dyn ChainClient>,
+}
+
+impl ChainClients {
+    /// Clients talking to the configured RPC endpoints
+    pub fn live(solana_rpc_url: &str, eth_rpc_url: &str, sns_api_url: &str) -> Self {
+        Self {
+            solana: Arc::new(SolanaClient::new(solana_rpc_url, sns_api_url)),
+            ethereum: Arc::new(EthereumClient::new(eth_rpc_url)),
+        }
+    }
+
+    /// Record every call of both clients in `metrics`, under the host of
+    /// the RPC endpoint each one talks to
+    pub fn metered(
+        self,
+        metrics: &Arc<RpcMetrics>,
+        solana_rpc_url: &str,
+        eth_rpc_url: &str,
+    ) -> Self {
+        Self {
+            solana: MeteredClient::wrap(self.solana, Chain::Solana, solana_rpc_url, metrics.clone()),
+            ethereum: MeteredClient::wrap(
+                self.ethereum,
+                Chain::Ethereum,
+                eth_rpc_url,
+                metrics.clone(),
+            ),
+        }
+    }
+
+    /// Metered live client for one chain talking to `url`, for RPC
+    /// endpoints other than the configured ones
+    pub fn connect(
+        chain: Chain,
+        url: &str,
+        sns_api_url: &str,
+        metrics: &Arc<RpcMetrics>,
+    ) -> Arc<dyn ChainClient> {
+        let client: Arc<dyn ChainClient> = match chain {
+            Chain::Solana => Arc::new(SolanaClient::new(url, sns_api_url)),
+            Chain::Ethereum => Arc::new(EthereumClient::new(url)),
+        };
+        MeteredClient::wrap(client, chain, url, metrics.clone())
+    }
+
+    /// The same clients with `client` in place of the one for `chain`
+    pub fn with(mut self, chain: Chain, client: Arc<dyn ChainClient>) -> Self {
+        match chain {
+            Chain::Solana => self.solana = client,
+            Chain::Ethereum => self.ethereum = client,
+        }
+        self
+    }
+
+    pub fn get(&self, chain: Chain) -> &dyn ChainClient {
+        match chain {
+            Chain::Solana => self.solana.as_ref(),
+            Chain::Ethereum => self.ethereum.as_ref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(address: &str, token: Option<&str>, delta: i64) -> BalanceChange {
+        BalanceChange {
+            address: address.to_string(),
+            token: token.map(str::to_string),
+            delta: delta.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_effects_match_within_fee() {
+        let expected = TxEffects {
+            changes: vec![change("Payer", None, -1_005_000), change("dest", None, 1_000_000)],
+            fee: "5000".to_string(),
+        };
+
+        // The payer's change may differ by the fee; addresses ignore case
+        let actual = TxEffects {
+            changes: vec![change("payer", None, -1_000_000), change("DEST", None, 1_000_000)],
+            fee: "0".to_string(),
+        };
+        assert!(expected.matches(&actual, "payer"));
+
+        // Anything else must be exact, including changes nobody expected
+        let short = TxEffects {
+            changes: vec![change("payer", None, -1_005_000), change("dest", None, 900_000)],
+            fee: "5000".to_string(),
+        };
+        assert!(!expected.matches(&short, "payer"));
+        let extra = TxEffects {
+            changes: [actual.changes.clone(), vec![change("dest", Some("mint"), 1)]].concat(),
+            fee: "5000".to_string(),
+        };
+        assert!(!expected.matches(&extra, "payer"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Diagnoses for failed broadcasts
+//!
+//! RPC nodes and the Jupiter API report refused transactions as free text.
+//! The common failures are recognized here and given a stable code and
+//! something the user can do about them.
+
+/// Why a transaction was refused, with what to do about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailureDiagnosis {
+    /// Stable machine-readable code
+    pub code: &'static str,
+    pub remediation: &'static str,
+    /// Sending again, unchanged, may succeed
+    pub retryable: bool,
+}
+
+pub const BLOCKHASH_EXPIRED: FailureDiagnosis = FailureDiagnosis {
+    code: "blockhash_expired",
+    remediation: "The transaction's blockhash expired before it landed. Send it again to sign it with a fresh one.",
+    retryable: true,
+};
+
+pub const INSUFFICIENT_FUNDS_FOR_RENT: FailureDiagnosis = FailureDiagnosis {
+    code: "insufficient_funds_for_rent",
+    remediation: "The transfer would leave an account below its rent-exempt minimum. Send the whole balance to close it, or leave the minimum behind.",
+    retryable: false,
+};
+
+pub const INSUFFICIENT_FUNDS: FailureDiagnosis = FailureDiagnosis {
+    code: "insufficient_funds",
+    remediation: "The account can't cover the amount plus fees. Lower the amount or top up the account.",
+    retryable: false,
+};
+
+pub const NONCE_TOO_LOW: FailureDiagnosis = FailureDiagnosis {
+    code: "nonce_too_low",
+    remediation: "A transaction with this nonce was already mined, possibly one sent from another wallet. Send again to use the next nonce.",
+    retryable: true,
+};
+
+pub const GAS_UNDERPRICED: FailureDiagnosis = FailureDiagnosis {
+    code: "gas_underpriced",
+    remediation: "The fee is below what the network, or the pending transaction it replaces, requires. Send again with a higher fee, or speed up the pending transaction.",
+    retryable: true,
+};
+
+pub const SLIPPAGE_EXCEEDED: FailureDiagnosis = FailureDiagnosis {
+    code: "slippage_exceeded",
+    remediation: "The price moved past the allowed slippage before the swap landed. Get a new quote, or allow more slippage.",
+    retryable: true,
+};
+
+/// Fragments of lower-cased error text, checked in order: rent before the
+/// general insufficient-funds messages it would also match
+const PATTERNS: &[(&[&str], FailureDiagnosis)] = &[
+    (
+        &["blockhash not found", "blockhashnotfound", "block height exceeded"],
+        BLOCKHASH_EXPIRED,
+    ),
+    (
+        &["insufficient funds for rent", "insufficientfundsforrent"],
+        INSUFFICIENT_FUNDS_FOR_RENT,
+    ),
+    (
+        &[
+            "insufficient funds",
+            "insufficient lamports",
+            "no record of a prior credit",
+        ],
+        INSUFFICIENT_FUNDS,
+    ),
+    (&["nonce too low"], NONCE_TOO_LOW),
+    (
+        &[
+            "transaction underpriced",
+            "max fee per gas less than block base fee",
+            "fee too low",
+        ],
+        GAS_UNDERPRICED,
+    ),
+    (
+        // Jupiter's SlippageToleranceExceeded is custom program error 6001
+        &["slippage", "custom program error: 0x1771"],
+        SLIPPAGE_EXCEEDED,
+    ),
+];
+
+/// Diagnose a failure from its error text; `None` when it isn't recognized
+pub fn diagnose(message: &str) -> Option<FailureDiagnosis> {
+    let message = message.to_lowercase();
+    PATTERNS
+        .iter()
+        .find(|(fragments, _)| fragments.iter().any(|f| message.contains(f)))
+        .map(|(_, diagnosis)| *diagnosis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose() {
+        let cases = [
+            (
+                "RPC response error -32002: Transaction simulation failed: Blockhash not found",
+                Some(BLOCKHASH_EXPIRED),
+            ),
+            (
+                "Transaction results in an account (1) with insufficient funds for rent",
+                Some(INSUFFICIENT_FUNDS_FOR_RENT),
+            ),
+            (
+                "Attempt to debit an account but found no record of a prior credit.",
+                Some(INSUFFICIENT_FUNDS),
+            ),
+            (
+                "(code: -32000, message: insufficient funds for gas * price + value, data: None)",
+                Some(INSUFFICIENT_FUNDS),
+            ),
+            ("(code: -32000, message: nonce too low, data: None)", Some(NONCE_TOO_LOW)),
+            ("replacement transaction underpriced", Some(GAS_UNDERPRICED)),
+            (
+                "simulation failed: Error processing Instruction 3: custom program error: 0x1771",
+                Some(SLIPPAGE_EXCEEDED),
+            ),
+            ("connection reset by peer", None),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(diagnose(message), expected, "{}", message);
+        }
+    }
+}
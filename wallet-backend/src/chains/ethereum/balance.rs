@@ -181,6 +181,82 @@ async fn get_erc20_decimals(rpc_url: &str, token_address: &str) -> Result<u8, Et
     Ok(decimals)
 }
 
+/// On-chain ERC-20 metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Erc20Metadata {
+    pub decimals: u8,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Read decimals, symbol and name from an ERC-20 contract.
+/// Fails with `InvalidAddress` if the address doesn't answer `decimals()`.
+pub async fn get_erc20_metadata(
+    rpc_url: &str,
+    token_address: &str,
+) -> Result<Erc20Metadata, EthBalanceError> {
+    // decimals() selector: 0x313ce567
+    let decimals = eth_call(rpc_url, token_address, "0x313ce567")
+        .await?
+        .and_then(|data| data.last().copied())
+        .ok_or_else(|| EthBalanceError::InvalidAddress(token_address.to_string()))?;
+
+    // symbol() and name() are optional in ERC-20
+    let symbol = eth_call(rpc_url, token_address, "0x95d89b41")
+        .await?
+        .and_then(|data| decode_abi_string(&data));
+    let name = eth_call(rpc_url, token_address, "0x06fdde03")
+        .await?
+        .and_then(|data| decode_abi_string(&data));
+
+    Ok(Erc20Metadata {
+        decimals,
+        symbol,
+        name,
+    })
+}
+
+/// Raw `eth_call` result; `None` when the call returns no data
+async fn eth_call(rpc_url: &str, to: &str, data: &str) -> Result<Option<Vec<u8>>, EthBalanceError> {
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0",
+        method: "eth_call",
+        params: vec![
+            serde_json::json!({ "to": to, "data": data }),
+            serde_json::Value::String("latest".to_string()),
+        ],
+        id: 1,
+    };
+
+    let response: JsonRpcResponse = reqwest::Client::new()
+        .post(rpc_url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| EthBalanceError::RpcError(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| EthBalanceError::RpcError(e.to_string()))?;
+
+    // Reverts come back as RPC errors; treat them as "no data"
+    if response.error.is_some() {
+        return Ok(None);
+    }
+
+    Ok(response
+        .result
+        .and_then(|hex_data| hex::decode(hex_data.trim_start_matches("0x")).ok())
+        .filter(|bytes| !bytes.is_empty()))
+}
+
+fn decode_abi_string(data: &[u8]) -> Option<String> {
+    ethers::abi::decode(&[ethers::abi::ParamType::String], data)
+        .ok()?
+        .into_iter()
+        .next()?
+        .into_string()
+}
+
 /// Known ERC-20 tokens on mainnet/testnets
 pub fn get_known_token_info(token_address: &str) -> Option<(&'static str, &'static str, u8)> {
     match token_address.to_lowercase().as_str() {
@@ -0,0 +1,188 @@
+//! Deposits into the official bridges of Ethereum L2s
+//!
+//! Each rollup escrows ETH in a contract on L1 and credits it to the same
+//! address on L2 once its nodes have derived the deposit from L1 blocks,
+//! which takes minutes. The way back is slower: a withdrawal to L1 only
+//! finalizes after the rollup's challenge period, about a week on all of
+//! them, which is why only deposits are built here.
+
+use ethers::abi::{self, Token};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, TransactionRequest, U256};
+use ethers::utils::id;
+
+use super::transaction::EthTxError;
+use super::wallet::EthereumWallet;
+use crate::chains::client::BridgeDeposit;
+
+const MAINNET: u64 = 1;
+const SEPOLIA: u64 = 11_155_111;
+
+/// Gas an OP Stack bridge is asked to forward to the L2 credit
+const OP_MIN_GAS_LIMIT: u32 = 200_000;
+
+/// Worst-case gas of a deposit, for the balance check
+pub const BRIDGE_DEPOSIT_GAS: u64 = 150_000;
+
+/// Challenge period of every supported rollup
+const SEVEN_DAYS_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// How a bridge takes ETH
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositCall {
+    /// `Inbox.depositEth()` (Arbitrum)
+    ArbitrumInbox,
+    /// `L1StandardBridge.depositETH(uint32,bytes)` (OP Stack)
+    OpStandardBridge,
+}
+
+/// The official bridge of an L2
+#[derive(Debug, Clone, Copy)]
+pub struct L2Bridge {
+    /// Identifier used in requests
+    pub network: &'static str,
+    pub name: &'static str,
+    pub call: DepositCall,
+    /// Bridge contract on each L1 it is deployed from, by chain id
+    pub contracts: &'static [(u64, &'static str)],
+    /// Seconds after L1 inclusion until a deposit is credited on L2
+    pub deposit_secs: i64,
+    /// Challenge period a withdrawal back to L1 has to wait out
+    pub withdrawal_secs: i64,
+}
+
+impl L2Bridge {
+    /// Bridge contract on the L1 with `chain_id`
+    pub fn contract(&self, chain_id: u64) -> Option<&'static str> {
+        self.contracts
+            .iter()
+            .find(|(id, _)| *id == chain_id)
+            .map(|(_, contract)| *contract)
+    }
+}
+
+pub const L2_BRIDGES: &[L2Bridge] = &[
+    L2Bridge {
+        network: "arbitrum",
+        name: "Arbitrum One",
+        call: DepositCall::ArbitrumInbox,
+        contracts: &[
+            (MAINNET, "0x4Dbd4fc535Ac27206064B68FfCf827b0A60BAB3f"),
+            (SEPOLIA, "0xaAe29B0366299461418F5324a79Afc425BE5ae21"),
+        ],
+        deposit_secs: 15 * 60,
+        withdrawal_secs: SEVEN_DAYS_SECS,
+    },
+    L2Bridge {
+        network: "optimism",
+        name: "OP Mainnet",
+        call: DepositCall::OpStandardBridge,
+        contracts: &[
+            (MAINNET, "0x99C9fc46f92E8a1c0deC1b1747d010903E884bE1"),
+            (SEPOLIA, "0xFBb0621E0B23b5478B630BD55a5f21f67730B0F1"),
+        ],
+        deposit_secs: 5 * 60,
+        withdrawal_secs: SEVEN_DAYS_SECS,
+    },
+    L2Bridge {
+        network: "base",
+        name: "Base",
+        call: DepositCall::OpStandardBridge,
+        contracts: &[
+            (MAINNET, "0x3154Cf16ccdb4C6d922629664174b904d80F2C35"),
+            (SEPOLIA, "0xfd0Bf71F60660E2f608ed56e1659C450eB113120"),
+        ],
+        deposit_secs: 5 * 60,
+        withdrawal_secs: SEVEN_DAYS_SECS,
+    },
+];
+
+/// The bridge of an L2 by its identifier, case-insensitively
+pub fn l2_bridge(network: &str) -> Option<&'static L2Bridge> {
+    L2_BRIDGES
+        .iter()
+        .find(|bridge| bridge.network.eq_ignore_ascii_case(network.trim()))
+}
+
+/// Calldata of a deposit crediting the sender on L2
+pub fn deposit_data(call: DepositCall) -> Bytes {
+    let (signature, args) = match call {
+        DepositCall::ArbitrumInbox => ("depositEth()", vec![]),
+        DepositCall::OpStandardBridge => (
+            "depositETH(uint32,bytes)",
+            vec![Token::Uint(U256::from(OP_MIN_GAS_LIMIT)), Token::Bytes(vec![])],
+        ),
+    };
+    let mut data = id(signature).to_vec();
+    data.extend(abi::encode(&args));
+    data.into()
+}
+
+/// Sign and broadcast a deposit of `value` wei into `bridge` on whichever
+/// L1 `rpc_url` serves
+pub async fn send_bridge_deposit(
+    rpc_url: &str,
+    wallet: &EthereumWallet,
+    bridge: &L2Bridge,
+    value: u128,
+    nonce: u64,
+    gas_price: u128,
+) -> Result<BridgeDeposit, EthTxError> {
+    let provider =
+        Provider::<Http>::try_from(rpc_url).map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?
+        .as_u64();
+    let contract = bridge.contract(chain_id).ok_or_else(|| {
+        EthTxError::InvalidAddress(format!("{} has no bridge on chain {}", bridge.name, chain_id))
+    })?;
+    let to: Address = contract
+        .parse()
+        .map_err(|_| EthTxError::InvalidAddress(contract.to_string()))?;
+
+    let signer = LocalWallet::from(wallet.signing_key()).with_chain_id(chain_id);
+    let client = SignerMiddleware::new(provider, signer);
+    let tx = TransactionRequest::new()
+        .to(to)
+        .data(deposit_data(bridge.call))
+        .value(value)
+        .nonce(nonce)
+        .gas_price(gas_price);
+    let pending = client
+        .send_transaction(tx, None)
+        .await
+        .map_err(|e| EthTxError::TransactionFailed(e.to_string()))?;
+
+    Ok(BridgeDeposit {
+        tx_hash: format!("0x{:x}", pending.tx_hash()),
+        contract: contract.to_string(),
+        l1_chain_id: chain_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposit_calls() {
+        let arbitrum = l2_bridge(" Arbitrum ").unwrap();
+        assert_eq!(arbitrum.contract(SEPOLIA), Some("0xaAe29B0366299461418F5324a79Afc425BE5ae21"));
+        assert_eq!(arbitrum.contract(5), None);
+        assert!(l2_bridge("zksync").is_none());
+
+        // depositEth() takes no arguments
+        let data = deposit_data(DepositCall::ArbitrumInbox);
+        assert_eq!(hex::encode(&data), "439370b1");
+
+        // depositETH(uint32,bytes): gas limit, offset, empty bytes
+        let data = deposit_data(DepositCall::OpStandardBridge);
+        assert_eq!(hex::encode(&data[..4]), "b1a1a882");
+        assert_eq!(data.len(), 4 + 3 * 32);
+        assert_eq!(U256::from_big_endian(&data[4..36]), U256::from(OP_MIN_GAS_LIMIT));
+    }
+}
@@ -0,0 +1,641 @@
+//! `ChainClient` backed by an Ethereum JSON-RPC endpoint
+
+use async_trait::async_trait;
+
+use crate::chains::client::{
+    BridgeDeposit, Broadcast, ChainBalance, ChainClient, ChainClientError, ChainTokenBalance,
+    ConfirmedEffects, DeploymentCost, HistoryEntry, Identity, MaxSend, NameQuote, NameRegistration, NftHolder, NftMetadata,
+    ReferencedTransaction, RouteSubmission, RouteTransaction, SentTransfer, StakePoolState,
+    TokenMetadata, Transfer, TxEffects, UnsignedTransfer,
+};
+use crate::core::{format_units, Chain, SecureSeed};
+
+use super::balance::{get_erc20_balance, get_erc20_metadata, get_eth_balance, EthBalanceError};
+use super::bridge::{l2_bridge, send_bridge_deposit, BRIDGE_DEPOSIT_GAS};
+use super::cold::build_unsigned_transfer;
+use super::ens::{
+    ens_commit, ens_quote, ens_register, ens_set_address, resolve_ens_identity, EnsError,
+    SECONDS_PER_YEAR,
+};
+use super::multisig::{compute_safe_address, safe_deployment_gas};
+use super::nft::{get_erc721_holder, get_erc721_metadata, EthNftError};
+use super::nonce::NonceManager;
+use super::route::{send_call, ContractCall, APPROVE_GAS, ROUTE_CALL_GAS};
+use super::transaction::{
+    check_eth_transfer, get_block_number, get_gas_price, get_transaction_effects,
+    get_transaction_history, has_activity,
+    max_sendable_eth, replacement_gas_price, send_erc20, send_eth, send_raw_transaction,
+    transfer_effects, EthTxError, ERC20_TRANSFER_GAS, NATIVE_TRANSFER_GAS,
+};
+use super::wallet::EthereumWallet;
+
+/// Default Safe deployment addresses (Sepolia)
+const SAFE_PROXY_FACTORY: &str = "0xa6B71E26C5e0845f74c812102Ca7114b6a896AB2";
+const SAFE_SINGLETON: &str = "0xd9Db270c1B5E3Bd161E8c8503c55cEABeE709552";
+
+/// Live Ethereum client
+pub struct EthereumClient {
+    rpc_url: String,
+    nonces: NonceManager,
+}
+
+impl EthereumClient {
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            nonces: NonceManager::new(),
+        }
+    }
+
+    async fn wei_balance(&self, address: &str) -> Result<u128, ChainClientError> {
+        let balance = get_eth_balance(&self.rpc_url, address).await?;
+        Ok(balance.wei.parse().unwrap_or(0))
+    }
+}
+
+/// Outcome of a dry run; nothing is signed, so there is no hash
+fn simulated(amount: String, token_decimals: Option<u32>, expected: TxEffects) -> SentTransfer {
+    SentTransfer {
+        tx_hash: String::new(),
+        status: "simulated".to_string(),
+        amount,
+        token_decimals,
+        expected: Some(expected),
+        broadcast: None,
+    }
+}
+
+#[async_trait]
+impl ChainClient for EthereumClient {
+    async fn balance(&self, address: &str) -> Result<ChainBalance, ChainClientError> {
+        let eth_balance = get_eth_balance(&self.rpc_url, address).await?;
+
+        // Note: For Ethereum, token balances require knowing which tokens to check
+        // In production, use an indexer like Alchemy or Etherscan API
+
+        let wei: u128 = eth_balance
+            .wei
+            .parse()
+            .map_err(|_| ChainClientError::Rpc(format!("unreadable balance {}", eth_balance.wei)))?;
+
+        Ok(ChainBalance {
+            native_balance: format_units(wei, Chain::Ethereum.native_decimals()),
+            native_balance_raw: eth_balance.wei,
+            native_symbol: "ETH".to_string(),
+            tokens: vec![],
+        })
+    }
+
+    async fn token_balance(
+        &self,
+        address: &str,
+        token: &str,
+    ) -> Result<ChainTokenBalance, ChainClientError> {
+        let balance = get_erc20_balance(&self.rpc_url, token, address).await?;
+
+        Ok(ChainTokenBalance {
+            address: balance.token_address,
+            symbol: balance.symbol,
+            name: balance.name,
+            balance: balance.balance,
+            decimals: balance.decimals,
+            ui_amount: balance.ui_amount,
+            extensions: None,
+        })
+    }
+
+    async fn token_metadata(&self, token: &str) -> Result<TokenMetadata, ChainClientError> {
+        if !super::wallet::validate_address(token) {
+            return Err(ChainClientError::InvalidAddress(token.to_string()));
+        }
+
+        let metadata = get_erc20_metadata(&self.rpc_url, token).await?;
+        Ok(TokenMetadata {
+            decimals: metadata.decimals,
+            symbol: metadata.symbol,
+            name: metadata.name,
+        })
+    }
+
+    async fn max_send(
+        &self,
+        address: &str,
+        token: Option<&str>,
+    ) -> Result<MaxSend, ChainClientError> {
+        let balance_wei = self.wei_balance(address).await?;
+        let gas_price = get_gas_price(&self.rpc_url).await?;
+
+        let (max_amount, max_ui_amount, fee) = match token {
+            Some(token_address) => {
+                let balance = get_erc20_balance(&self.rpc_url, token_address, address).await?;
+                let units = balance.balance.parse().unwrap_or(0);
+                (
+                    balance.balance,
+                    format_units(units, balance.decimals as u32),
+                    gas_price * ERC20_TRANSFER_GAS as u128,
+                )
+            }
+            None => {
+                let max = max_sendable_eth(balance_wei, gas_price);
+                (
+                    max.to_string(),
+                    format_units(max, Chain::Ethereum.native_decimals()),
+                    gas_price * NATIVE_TRANSFER_GAS as u128,
+                )
+            }
+        };
+
+        Ok(MaxSend {
+            max_amount,
+            max_ui_amount,
+            fee: fee.to_string(),
+            fee_covered: balance_wei >= fee,
+        })
+    }
+
+    async fn resolve_identity(&self, address: &str) -> Result<Option<Identity>, ChainClientError> {
+        Ok(resolve_ens_identity(&self.rpc_url, address).await?)
+    }
+
+    async fn nft_holder(
+        &self,
+        token_address: &str,
+        token_id: &str,
+    ) -> Result<NftHolder, ChainClientError> {
+        Ok(get_erc721_holder(&self.rpc_url, token_address, token_id).await?)
+    }
+
+    async fn nft_metadata(
+        &self,
+        token_address: &str,
+        token_id: &str,
+    ) -> Result<NftMetadata, ChainClientError> {
+        Ok(get_erc721_metadata(&self.rpc_url, token_address, token_id).await?)
+    }
+
+    async fn send(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transfer: Transfer,
+    ) -> Result<SentTransfer, ChainClientError> {
+        if transfer.drain_all {
+            return Err(ChainClientError::InvalidAmount(
+                "drain_all is only supported on solana".to_string(),
+            ));
+        }
+        if transfer.memo.is_some() || !transfer.references.is_empty() {
+            return Err(ChainClientError::TransactionFailed(
+                "memos and reference keys are only supported on solana".to_string(),
+            ));
+        }
+        if transfer.jito.is_some() {
+            return Err(ChainClientError::TransactionFailed(
+                "jito bundles are only supported on solana".to_string(),
+            ));
+        }
+
+        let wallet = EthereumWallet::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let from = wallet.address_string();
+
+        // Pre-flight: balances are checked before anything is signed
+        let eth_balance = self.wei_balance(&from).await?;
+        let gas_price = get_gas_price(&self.rpc_url).await?;
+
+        let (result, expected, broadcast, token_decimals) = match transfer.token {
+            Some(ref token_address) => {
+                let amount: u128 = transfer
+                    .amount
+                    .parse()
+                    .map_err(|_| ChainClientError::InvalidAmount(transfer.amount.clone()))?;
+
+                let token_balance: u128 = get_erc20_balance(&self.rpc_url, token_address, &from)
+                    .await?
+                    .balance
+                    .parse()
+                    .unwrap_or(0);
+                if amount > token_balance {
+                    return Err(ChainClientError::InsufficientBalance {
+                        required: amount,
+                        available: token_balance,
+                    });
+                }
+                check_eth_transfer(eth_balance, 0, gas_price, ERC20_TRANSFER_GAS)?;
+                // Only for recording; a token without `decimals()` still sends
+                let decimals = get_erc20_metadata(&self.rpc_url, token_address)
+                    .await
+                    .ok()
+                    .map(|metadata| u32::from(metadata.decimals));
+
+                let expected = transfer_effects(
+                    &from,
+                    &transfer.to,
+                    Some(token_address),
+                    amount,
+                    gas_price,
+                    ERC20_TRANSFER_GAS,
+                );
+                if transfer.dry_run {
+                    return Ok(simulated(amount.to_string(), decimals, expected));
+                }
+                let result =
+                    send_erc20(&self.rpc_url, &wallet, token_address, &transfer.to, amount).await?;
+                (result, expected, None, decimals)
+            }
+            None => {
+                let amount: f64 = transfer
+                    .amount
+                    .parse()
+                    .map_err(|_| ChainClientError::InvalidAmount(transfer.amount.clone()))?;
+                let value_wei = ethers::utils::parse_ether(amount)
+                    .map_err(|_| ChainClientError::InvalidAmount(transfer.amount.clone()))?
+                    .as_u128();
+
+                // A replacement reuses the pending transaction's nonce and
+                // has to outbid it
+                let gas_price = match transfer.replaces {
+                    Some(replaced) => replacement_gas_price(replaced.gas_price, gas_price),
+                    None => gas_price,
+                };
+                check_eth_transfer(eth_balance, value_wei, gas_price, NATIVE_TRANSFER_GAS)?;
+
+                let expected = transfer_effects(
+                    &from,
+                    &transfer.to,
+                    None,
+                    value_wei,
+                    gas_price,
+                    NATIVE_TRANSFER_GAS,
+                );
+                // Nothing is signed, so no nonce is taken
+                if transfer.dry_run {
+                    return Ok(simulated(transfer.amount, None, expected));
+                }
+                let height = get_block_number(&self.rpc_url).await?;
+                let nonce = match transfer.replaces {
+                    Some(replaced) => replaced.nonce,
+                    None => self.nonces.next(&self.rpc_url, &from).await?,
+                };
+                let broadcast_url = transfer.private_rpc.as_deref().unwrap_or(&self.rpc_url);
+                let result = send_eth(
+                    &self.rpc_url,
+                    broadcast_url,
+                    &wallet,
+                    &transfer.to,
+                    amount,
+                    nonce,
+                    gas_price,
+                )
+                .await
+                .inspect_err(|_| {
+                    if transfer.replaces.is_none() {
+                        self.nonces.release(&from, nonce);
+                    }
+                })?;
+                let broadcast = Broadcast {
+                    height,
+                    nonce: Some(nonce),
+                    gas_price: Some(gas_price.to_string()),
+                    last_valid_height: None,
+                    private: transfer.private_rpc.is_some(),
+                    raw_tx: result.raw_tx.clone(),
+                };
+                (result, expected, Some(broadcast), None)
+            }
+        };
+
+        Ok(SentTransfer {
+            tx_hash: result.tx_hash,
+            status: result.status,
+            amount: transfer.amount,
+            token_decimals,
+            expected: Some(expected),
+            broadcast,
+        })
+    }
+
+    async fn broadcast_raw(&self, raw_tx: &str) -> Result<String, ChainClientError> {
+        Ok(send_raw_transaction(&self.rpc_url, raw_tx).await?)
+    }
+
+    async fn build_unsigned_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<UnsignedTransfer, ChainClientError> {
+        Ok(build_unsigned_transfer(&self.rpc_url, from, to, amount).await?)
+    }
+
+    async fn block_height(&self) -> Result<u64, ChainClientError> {
+        Ok(get_block_number(&self.rpc_url).await?)
+    }
+
+    async fn has_activity(&self, address: &str) -> Result<bool, ChainClientError> {
+        Ok(has_activity(&self.rpc_url, address).await?)
+    }
+
+    async fn request_airdrop(&self, _address: &str, _amount: u64) -> Result<String, ChainClientError> {
+        Err(ChainClientError::Rpc(
+            "ethereum nodes don't serve airdrops; use a faucet API".to_string(),
+        ))
+    }
+
+    async fn transaction_effects(
+        &self,
+        tx_hash: &str,
+        addresses: &[String],
+    ) -> Result<Option<ConfirmedEffects>, ChainClientError> {
+        Ok(get_transaction_effects(&self.rpc_url, tx_hash, addresses).await?)
+    }
+
+    async fn find_by_reference(
+        &self,
+        _reference: &str,
+        _limit: usize,
+    ) -> Result<Vec<ReferencedTransaction>, ChainClientError> {
+        Err(ChainClientError::InvalidAddress(
+            "reference keys are only supported on solana".to_string(),
+        ))
+    }
+
+    /// Asset transfers through Alchemy's `alchemy_getAssetTransfers`
+    async fn history(&self, address: &str, limit: usize) -> Result<Vec<HistoryEntry>, ChainClientError> {
+        let transfers = get_transaction_history(&self.rpc_url, address, limit).await?;
+        Ok(transfers
+            .into_iter()
+            .map(|tx| HistoryEntry {
+                hash: tx.hash,
+                status: tx.status,
+                block_number: tx.block_number,
+                block_time: tx.timestamp.map(|ts| ts as i64),
+                from: Some(tx.from),
+                to: tx.to,
+                amount: Some(tx.value),
+                amount_raw: tx.value_raw,
+                token_address: None,
+                memo: None,
+            })
+            .collect())
+    }
+
+    async fn stake_pool(&self, _pool: &str) -> Result<StakePoolState, ChainClientError> {
+        Err(ChainClientError::InvalidAddress(
+            "stake pools are only supported on solana".to_string(),
+        ))
+    }
+
+    async fn stake_pool_deposit(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        _pool: &str,
+        _lamports: u64,
+    ) -> Result<String, ChainClientError> {
+        Err(ChainClientError::InvalidAddress(
+            "stake pools are only supported on solana".to_string(),
+        ))
+    }
+
+    async fn stake_pool_withdraw(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        _pool: &str,
+        _pool_tokens: u64,
+    ) -> Result<String, ChainClientError> {
+        Err(ChainClientError::InvalidAddress(
+            "stake pools are only supported on solana".to_string(),
+        ))
+    }
+
+    async fn bridge_deposit(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        network: &str,
+        amount: u128,
+    ) -> Result<BridgeDeposit, ChainClientError> {
+        let bridge = l2_bridge(network)
+            .ok_or_else(|| ChainClientError::InvalidAddress(format!("unknown L2 {}", network)))?;
+        let wallet = EthereumWallet::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let from = wallet.address_string();
+
+        let eth_balance = self.wei_balance(&from).await?;
+        let gas_price = get_gas_price(&self.rpc_url).await?;
+        check_eth_transfer(eth_balance, amount, gas_price, BRIDGE_DEPOSIT_GAS)?;
+
+        let nonce = self.nonces.next(&self.rpc_url, &from).await?;
+        send_bridge_deposit(&self.rpc_url, &wallet, bridge, amount, nonce, gas_price)
+            .await
+            .inspect_err(|_| self.nonces.release(&from, nonce))
+            .map_err(Into::into)
+    }
+
+    async fn send_route_transaction(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transaction: &RouteTransaction,
+    ) -> Result<RouteSubmission, ChainClientError> {
+        let RouteTransaction::Ethereum {
+            to,
+            data,
+            value,
+            gas_limit,
+            approval,
+        } = transaction
+        else {
+            return Err(ChainClientError::InvalidAddress(
+                "route transaction is not for ethereum".to_string(),
+            ));
+        };
+        let call = ContractCall::new(to, data, *value, *gas_limit)?;
+        let approve = approval.as_ref().map(ContractCall::approve).transpose()?;
+        let wallet = EthereumWallet::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let from = wallet.address_string();
+
+        // Both transactions' gas comes out of the ETH balance
+        let gas = gas_limit.unwrap_or(ROUTE_CALL_GAS) + approve.as_ref().map_or(0, |_| APPROVE_GAS);
+        let eth_balance = self.wei_balance(&from).await?;
+        let gas_price = get_gas_price(&self.rpc_url).await?;
+        check_eth_transfer(eth_balance, *value, gas_price, gas)?;
+
+        // The approval takes the lower nonce, so it is mined first
+        let approval_tx_hash = match approve {
+            Some(approve) => {
+                let nonce = self.nonces.next(&self.rpc_url, &from).await?;
+                let hash = send_call(&self.rpc_url, &wallet, &approve, nonce, gas_price)
+                    .await
+                    .inspect_err(|_| self.nonces.release(&from, nonce))?;
+                Some(hash)
+            }
+            None => None,
+        };
+        let nonce = self.nonces.next(&self.rpc_url, &from).await?;
+        let tx_hash = send_call(&self.rpc_url, &wallet, &call, nonce, gas_price)
+            .await
+            .inspect_err(|_| self.nonces.release(&from, nonce))?;
+
+        Ok(RouteSubmission {
+            tx_hash,
+            approval_tx_hash,
+        })
+    }
+
+    async fn multisig_deployment_cost(
+        &self,
+        owners: &[String],
+        _threshold: u8,
+    ) -> Result<DeploymentCost, ChainClientError> {
+        let gas = safe_deployment_gas(owners.len());
+        let gas_price = get_gas_price(&self.rpc_url).await?;
+        Ok(DeploymentCost {
+            rent: 0,
+            network_fee: gas as u128 * gas_price,
+            gas: Some(gas),
+        })
+    }
+
+    async fn create_multisig(
+        &self,
+        _seed: &SecureSeed,
+        _name: &str,
+        threshold: u8,
+        owners: &[String],
+    ) -> Result<String, ChainClientError> {
+        compute_safe_address(
+            SAFE_PROXY_FACTORY,
+            SAFE_SINGLETON,
+            owners,
+            threshold,
+            chrono::Utc::now().timestamp() as u64,
+        )
+        .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))
+    }
+
+    async fn name_quote(&self, name: &str, years: u32) -> Result<NameQuote, ChainClientError> {
+        let quote = ens_quote(&self.rpc_url, name, years).await?;
+        Ok(NameQuote {
+            name: name.to_string(),
+            available: quote.available,
+            address: quote.address,
+            price: ethers::utils::format_ether(quote.price_wei),
+            price_unit: "ETH".to_string(),
+            years: Some(years),
+            renewal_price: Some(ethers::utils::format_ether(quote.renewal_wei)),
+            expires_at: quote.expires_at,
+        })
+    }
+
+    async fn register_name(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        years: u32,
+        commitment: Option<&str>,
+    ) -> Result<NameRegistration, ChainClientError> {
+        let wallet = EthereumWallet::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let from = wallet.address_string();
+        let gas_price = get_gas_price(&self.rpc_url).await?;
+        let nonce = self.nonces.next(&self.rpc_url, &from).await?;
+
+        let registration = match commitment {
+            None => ens_commit(&self.rpc_url, &wallet, name, years, nonce, gas_price)
+                .await
+                .map(|commitment| NameRegistration::Committed {
+                    tx_hash: commitment.tx_hash,
+                    secret: commitment.secret,
+                    ready_at: commitment.ready_at,
+                }),
+            Some(secret) => {
+                ens_register(&self.rpc_url, &wallet, name, years, secret, nonce, gas_price)
+                    .await
+                    .map(|tx_hash| NameRegistration::Registered {
+                        tx_hash,
+                        expires_at: Some(
+                            chrono::Utc::now().timestamp()
+                                + (SECONDS_PER_YEAR * years as u64) as i64,
+                        ),
+                    })
+            }
+        };
+        if registration.is_err() {
+            self.nonces.release(&from, nonce);
+        }
+        Ok(registration?)
+    }
+
+    async fn set_name_target(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        target: &str,
+    ) -> Result<String, ChainClientError> {
+        let wallet = EthereumWallet::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let from = wallet.address_string();
+        let gas_price = get_gas_price(&self.rpc_url).await?;
+        let nonce = self.nonces.next(&self.rpc_url, &from).await?;
+
+        ens_set_address(&self.rpc_url, &wallet, name, target, nonce, gas_price)
+            .await
+            .inspect_err(|_| self.nonces.release(&from, nonce))
+            .map_err(Into::into)
+    }
+}
+
+impl From<EthBalanceError> for ChainClientError {
+    fn from(e: EthBalanceError) -> Self {
+        match e {
+            EthBalanceError::InvalidAddress(addr) => ChainClientError::InvalidAddress(addr),
+            EthBalanceError::RpcError(_) => ChainClientError::Rpc(e.to_string()),
+        }
+    }
+}
+
+impl From<EthNftError> for ChainClientError {
+    fn from(e: EthNftError) -> Self {
+        match e {
+            EthNftError::InvalidAddress(addr) => ChainClientError::InvalidAddress(addr),
+            _ => ChainClientError::Rpc(e.to_string()),
+        }
+    }
+}
+
+impl From<EnsError> for ChainClientError {
+    fn from(e: EnsError) -> Self {
+        match e {
+            EnsError::InvalidAddress(addr) | EnsError::InvalidName(addr) => {
+                ChainClientError::InvalidAddress(addr)
+            }
+            EnsError::RpcError(_) => ChainClientError::Rpc(e.to_string()),
+            EnsError::TransactionFailed(_) => ChainClientError::TransactionFailed(e.to_string()),
+        }
+    }
+}
+
+/// Keep balance shortfalls structured
+impl From<EthTxError> for ChainClientError {
+    fn from(e: EthTxError) -> Self {
+        match e {
+            EthTxError::InsufficientBalance {
+                required,
+                available,
+            } => ChainClientError::InsufficientBalance {
+                required,
+                available,
+            },
+            EthTxError::InvalidAddress(addr) => ChainClientError::InvalidAddress(addr),
+            EthTxError::InvalidAmount => ChainClientError::InvalidAmount(e.to_string()),
+            EthTxError::RpcError(_) => ChainClientError::Rpc(e.to_string()),
+            _ => ChainClientError::TransactionFailed(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Transfers signed away from the server
+//!
+//! The unsigned legacy (EIP-155) transaction travels to an offline signer as
+//! base64 RLP alongside the hash it signs; the 65-byte signature that comes
+//! back is checked against the sender before the signed transaction is
+//! assembled and broadcast.
+
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, BlockNumber, Signature, TransactionRequest};
+use ethers::utils::rlp::Rlp;
+
+use super::transaction::{check_eth_transfer, get_gas_price, EthTxError, NATIVE_TRANSFER_GAS};
+use crate::chains::client::UnsignedTransfer;
+
+/// Unsigned ETH transfer from `from`
+pub fn unsigned_transfer(
+    from: &str,
+    to: &str,
+    value_wei: u128,
+    nonce: u64,
+    gas_price_wei: u128,
+    chain_id: u64,
+) -> Result<TransactionRequest, EthTxError> {
+    let from = Address::from_str(from).map_err(|_| EthTxError::InvalidAddress(from.to_string()))?;
+    let to = Address::from_str(to).map_err(|_| EthTxError::InvalidAddress(to.to_string()))?;
+    if value_wei == 0 {
+        return Err(EthTxError::InvalidAmount);
+    }
+
+    Ok(TransactionRequest::new()
+        .from(from)
+        .to(to)
+        .value(value_wei)
+        .nonce(nonce)
+        .gas(NATIVE_TRANSFER_GAS)
+        .gas_price(gas_price_wei)
+        .chain_id(chain_id))
+}
+
+/// Encode an unsigned transaction for export with the hash its signer signs
+pub fn export_transfer(transaction: &TransactionRequest) -> UnsignedTransfer {
+    let gas = transaction.gas.unwrap_or_default();
+    let gas_price = transaction.gas_price.unwrap_or_default();
+    UnsignedTransfer {
+        payload: STANDARD.encode(transaction.rlp()),
+        signing_hash: Some(format!("0x{:x}", transaction.sighash())),
+        fee: (gas * gas_price).as_u128(),
+    }
+}
+
+/// Build an unsigned transfer at the sender's next nonce and current gas
+/// price, checking the sender can pay for it
+pub async fn build_unsigned_transfer(
+    rpc_url: &str,
+    from: &str,
+    to: &str,
+    value_wei: u128,
+) -> Result<UnsignedTransfer, EthTxError> {
+    let provider =
+        Provider::<Http>::try_from(rpc_url).map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    let sender =
+        Address::from_str(from).map_err(|_| EthTxError::InvalidAddress(from.to_string()))?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?
+        .as_u64();
+    let nonce = provider
+        .get_transaction_count(sender, Some(BlockNumber::Pending.into()))
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?
+        .as_u64();
+    let balance = provider
+        .get_balance(sender, None)
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?
+        .as_u128();
+    let gas_price = get_gas_price(rpc_url).await?;
+    check_eth_transfer(balance, value_wei, gas_price, NATIVE_TRANSFER_GAS)?;
+
+    let transaction = unsigned_transfer(from, to, value_wei, nonce, gas_price, chain_id)?;
+    Ok(export_transfer(&transaction))
+}
+
+/// Combine an exported transaction with the sender's hex signature over its
+/// signing hash, after checking who signed it. Returns the signed transaction
+/// as 0x-prefixed hex.
+pub fn attach_signature(payload: &str, from: &str, signature: &str) -> Result<String, EthTxError> {
+    let bytes = STANDARD
+        .decode(payload.trim())
+        .map_err(|e| EthTxError::TransactionFailed(format!("bad transaction: {}", e)))?;
+    let transaction = TransactionRequest::decode_unsigned_rlp(&Rlp::new(&bytes))
+        .map_err(|e| EthTxError::TransactionFailed(format!("bad transaction: {}", e)))?;
+    let chain_id = transaction
+        .chain_id
+        .ok_or_else(|| EthTxError::TransactionFailed("transaction has no chain ID".to_string()))?
+        .as_u64();
+    let sender =
+        Address::from_str(from).map_err(|_| EthTxError::InvalidAddress(from.to_string()))?;
+
+    let mut signature = Signature::from_str(signature.trim())
+        .map_err(|e| EthTxError::TransactionFailed(format!("bad signature: {}", e)))?;
+    let signer = signature
+        .recover(transaction.sighash())
+        .map_err(|e| EthTxError::TransactionFailed(format!("bad signature: {}", e)))?;
+    if signer != sender {
+        return Err(EthTxError::TransactionFailed(format!(
+            "signature is not by {} over this transaction",
+            from
+        )));
+    }
+
+    // Signers may give v as 0/1, 27/28 or already in EIP-155 form
+    let recovery_id = signature
+        .recovery_id()
+        .map_err(|e| EthTxError::TransactionFailed(format!("bad signature: {}", e)))?;
+    signature.v = chain_id * 2 + 35 + recovery_id.to_byte() as u64;
+
+    Ok(format!("0x{}", hex::encode(transaction.rlp_signed(&signature))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+
+    #[test]
+    fn test_attach_signature() {
+        let signer = LocalWallet::new(&mut rand::thread_rng());
+        let from = format!("{:?}", signer.address());
+        let to = "0x742d35cc6634c0532925a3b844bc9e7595f3fe70";
+        let transaction = unsigned_transfer(&from, to, 10u128.pow(16), 7, 2_000_000_000, 11155111)
+            .unwrap();
+        let exported = export_transfer(&transaction);
+        assert_eq!(exported.fee, 21_000 * 2_000_000_000);
+
+        let signature = signer.sign_hash(transaction.sighash()).unwrap();
+        let raw = attach_signature(&exported.payload, &from, &signature.to_string()).unwrap();
+        let raw = hex::decode(raw.trim_start_matches("0x")).unwrap();
+        let (decoded, _) = TransactionRequest::decode_signed_rlp(&Rlp::new(&raw)).unwrap();
+        assert_eq!(decoded.from, Some(signer.address()));
+        assert_eq!(decoded.nonce, Some(7.into()));
+        assert_eq!(decoded.chain_id, Some(11155111u64.into()));
+
+        // Another key's signature is refused
+        let other = LocalWallet::new(&mut rand::thread_rng());
+        let wrong = other.sign_hash(transaction.sighash()).unwrap();
+        assert!(attach_signature(&exported.payload, &from, &wrong.to_string()).is_err());
+    }
+}
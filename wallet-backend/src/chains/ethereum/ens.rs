@@ -0,0 +1,425 @@
+//! ENS reverse resolution, profile records and `.eth` registration
+//!
+//! Registration goes through the registrar controller's commit / reveal
+//! scheme: a commitment hiding the name is sent first, and the name can be
+//! registered with the secret once the commitment is old enough, so the
+//! name can't be front-run from the mempool.
+
+use std::collections::BTreeMap;
+
+use ethers::abi::{self, ParamType, Token};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{ens::namehash, Http, Middleware, Provider, ProviderError};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, TransactionRequest, H256, U256};
+use ethers::utils::{id, keccak256};
+use rand::RngCore;
+use thiserror::Error;
+
+use super::wallet::EthereumWallet;
+use crate::chains::client::Identity;
+
+#[derive(Debug, Error)]
+pub enum EnsError {
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+    #[error("RPC error: {0}")]
+    RpcError(String),
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+}
+
+/// ENS deployment (Sepolia)
+const REGISTRAR_CONTROLLER: &str = "0xFED6a969AaA60E4961FCD3EBF1A2e8913ac65B72";
+const BASE_REGISTRAR: &str = "0x57f1887a8BF19b14fC0dF6Fd9B2acc9Af147eA85";
+const PUBLIC_RESOLVER: &str = "0x8FADE66B79cC9f707aB26799354482EB93a5B7dD";
+const REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+/// Allowance for block time on top of the minimum commitment age
+const COMMITMENT_MARGIN_SECS: i64 = 15;
+/// Headroom on the quoted rent sent with a registration, in percent; the
+/// controller refunds what it doesn't charge
+const RENT_BUFFER_PERCENT: u64 = 5;
+
+/// ENS text records returned with an identity, and the key each is exposed as
+const TEXT_RECORDS: &[(&str, &str)] = &[
+    ("url", "url"),
+    ("description", "description"),
+    ("email", "email"),
+    ("com.twitter", "twitter"),
+    ("com.github", "github"),
+];
+
+/// Primary ENS name of an address with its avatar and profile records.
+/// Only names that resolve back to the address count.
+pub async fn resolve_ens_identity(
+    rpc_url: &str,
+    address: &str,
+) -> Result<Option<Identity>, EnsError> {
+    let address: Address = address
+        .parse()
+        .map_err(|_| EnsError::InvalidAddress(address.to_string()))?;
+    let provider =
+        Provider::<Http>::try_from(rpc_url).map_err(|e| EnsError::RpcError(e.to_string()))?;
+
+    let name = match provider.lookup_address(address).await {
+        Ok(name) => name,
+        Err(ProviderError::EnsError(_)) | Err(ProviderError::EnsNotOwned(_)) => return Ok(None),
+        Err(e) => return Err(EnsError::RpcError(e.to_string())),
+    };
+
+    let avatar = text_record(&provider, &name, "avatar").await?;
+    let mut records = BTreeMap::new();
+    for (field, key) in TEXT_RECORDS {
+        if let Some(value) = text_record(&provider, &name, field).await? {
+            records.insert(key.to_string(), value);
+        }
+    }
+
+    Ok(Some(Identity {
+        name,
+        avatar,
+        records,
+    }))
+}
+
+/// A text record, `None` when unset
+async fn text_record(
+    provider: &Provider<Http>,
+    name: &str,
+    field: &str,
+) -> Result<Option<String>, EnsError> {
+    match provider.resolve_field(name, field).await {
+        Ok(value) if !value.trim().is_empty() => Ok(Some(value.trim().to_string())),
+        Ok(_) | Err(ProviderError::EnsError(_)) => Ok(None),
+        Err(e) => Err(EnsError::RpcError(e.to_string())),
+    }
+}
+
+/// Availability, rent and expiry of a `.eth` name
+#[derive(Debug, Clone)]
+pub struct EnsQuote {
+    pub available: bool,
+    /// Address record of the name, if it resolves
+    pub address: Option<String>,
+    /// Rent (base plus any premium) for the requested period
+    pub price_wei: U256,
+    /// Rent for one more year
+    pub renewal_wei: U256,
+    /// Unix timestamp; `None` if the name was never registered
+    pub expires_at: Option<i64>,
+}
+
+/// A commitment sent for registering a name
+#[derive(Debug, Clone)]
+pub struct EnsCommitment {
+    pub tx_hash: String,
+    /// Hex secret the commitment hides, needed to register
+    pub secret: String,
+    /// Unix timestamp from which the name can be registered
+    pub ready_at: i64,
+}
+
+/// Quote registering `name` for `years`
+pub async fn ens_quote(rpc_url: &str, name: &str, years: u32) -> Result<EnsQuote, EnsError> {
+    let label = label_of(name)?;
+    let provider = provider(rpc_url)?;
+
+    let available = call(
+        &provider,
+        REGISTRAR_CONTROLLER,
+        "available(string)",
+        &[Token::String(label.to_string())],
+        &[ParamType::Bool],
+    )
+    .await?
+    .remove(0)
+    .into_bool()
+    .unwrap_or(false);
+    let price_wei = rent(&provider, label, years).await?;
+    let renewal_wei = rent(&provider, label, 1).await?;
+
+    let label_id = U256::from(keccak256(label.as_bytes()));
+    let expires = call(
+        &provider,
+        BASE_REGISTRAR,
+        "nameExpires(uint256)",
+        &[Token::Uint(label_id)],
+        &[ParamType::Uint(256)],
+    )
+    .await?
+    .remove(0)
+    .into_uint()
+    .unwrap_or_default();
+
+    let address = match provider.resolve_name(name).await {
+        Ok(address) if !address.is_zero() => Some(format!("{:?}", address)),
+        _ => None,
+    };
+
+    Ok(EnsQuote {
+        available,
+        address,
+        price_wei,
+        renewal_wei,
+        expires_at: (!expires.is_zero()).then(|| expires.low_u64() as i64),
+    })
+}
+
+/// Send the commitment for registering `name` to the wallet for `years`
+pub async fn ens_commit(
+    rpc_url: &str,
+    wallet: &EthereumWallet,
+    name: &str,
+    years: u32,
+    nonce: u64,
+    gas_price: u128,
+) -> Result<EnsCommitment, EnsError> {
+    let label = label_of(name)?;
+    let provider = provider(rpc_url)?;
+
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let args = registration_args(name, label, wallet, years, secret)?;
+    let commitment = call(
+        &provider,
+        REGISTRAR_CONTROLLER,
+        "makeCommitment(string,address,uint256,bytes32,address,bytes[],bool,uint16)",
+        &args,
+        &[ParamType::FixedBytes(32)],
+    )
+    .await?
+    .remove(0);
+
+    let min_age = call(
+        &provider,
+        REGISTRAR_CONTROLLER,
+        "minCommitmentAge()",
+        &[],
+        &[ParamType::Uint(256)],
+    )
+    .await?
+    .remove(0)
+    .into_uint()
+    .unwrap_or_default()
+    .low_u64() as i64;
+
+    let data = encode_call("commit(bytes32)", &[commitment]);
+    let tx_hash = send(provider, wallet, REGISTRAR_CONTROLLER, data, U256::zero(), nonce, gas_price)
+        .await?;
+
+    Ok(EnsCommitment {
+        tx_hash,
+        secret: hex::encode(secret),
+        ready_at: chrono::Utc::now().timestamp() + min_age + COMMITMENT_MARGIN_SECS,
+    })
+}
+
+/// Register a name whose commitment has aged, paying the rent for `years`
+/// from the wallet. The address record is set to the wallet.
+pub async fn ens_register(
+    rpc_url: &str,
+    wallet: &EthereumWallet,
+    name: &str,
+    years: u32,
+    secret: &str,
+    nonce: u64,
+    gas_price: u128,
+) -> Result<String, EnsError> {
+    let label = label_of(name)?;
+    let provider = provider(rpc_url)?;
+    let secret: [u8; 32] = hex::decode(secret)
+        .ok()
+        .and_then(|secret| secret.try_into().ok())
+        .ok_or_else(|| EnsError::InvalidName("commitment secret is malformed".to_string()))?;
+
+    let rent = rent(&provider, label, years).await?;
+    let value = rent + rent * RENT_BUFFER_PERCENT / 100;
+    let args = registration_args(name, label, wallet, years, secret)?;
+    let data = encode_call(
+        "register(string,address,uint256,bytes32,address,bytes[],bool,uint16)",
+        &args,
+    );
+    send(provider, wallet, REGISTRAR_CONTROLLER, data, value, nonce, gas_price).await
+}
+
+/// Point the address record of `name` at `target` through its resolver
+pub async fn ens_set_address(
+    rpc_url: &str,
+    wallet: &EthereumWallet,
+    name: &str,
+    target: &str,
+    nonce: u64,
+    gas_price: u128,
+) -> Result<String, EnsError> {
+    let target: Address = target
+        .parse()
+        .map_err(|_| EnsError::InvalidAddress(target.to_string()))?;
+    let provider = provider(rpc_url)?;
+    let node = namehash(name);
+
+    let resolver = call(
+        &provider,
+        REGISTRY,
+        "resolver(bytes32)",
+        &[Token::FixedBytes(node.as_bytes().to_vec())],
+        &[ParamType::Address],
+    )
+    .await?
+    .remove(0)
+    .into_address()
+    .unwrap_or_default();
+    if resolver.is_zero() {
+        return Err(EnsError::InvalidName(format!("{} has no resolver", name)));
+    }
+
+    let data = set_addr_call(node, target);
+    send(provider, wallet, &format!("{:?}", resolver), data, U256::zero(), nonce, gas_price)
+        .await
+}
+
+/// The label of a second-level `.eth` name
+fn label_of(name: &str) -> Result<&str, EnsError> {
+    name.strip_suffix(".eth")
+        .filter(|label| !label.is_empty() && !label.contains('.'))
+        .ok_or_else(|| EnsError::InvalidName(name.to_string()))
+}
+
+fn provider(rpc_url: &str) -> Result<Provider<Http>, EnsError> {
+    Provider::<Http>::try_from(rpc_url).map_err(|e| EnsError::RpcError(e.to_string()))
+}
+
+/// Rent (base plus premium) of a label for `years`
+async fn rent(provider: &Provider<Http>, label: &str, years: u32) -> Result<U256, EnsError> {
+    let price = call(
+        provider,
+        REGISTRAR_CONTROLLER,
+        "rentPrice(string,uint256)",
+        &[Token::String(label.to_string()), Token::Uint(duration(years))],
+        &[ParamType::Uint(256), ParamType::Uint(256)],
+    )
+    .await?;
+    Ok(price
+        .into_iter()
+        .filter_map(Token::into_uint)
+        .fold(U256::zero(), |total, part| total + part))
+}
+
+fn duration(years: u32) -> U256 {
+    U256::from(SECONDS_PER_YEAR * years as u64)
+}
+
+/// Arguments shared by `makeCommitment` and `register`: the public
+/// resolver, with the address record set to the wallet in the same call
+fn registration_args(
+    name: &str,
+    label: &str,
+    wallet: &EthereumWallet,
+    years: u32,
+    secret: [u8; 32],
+) -> Result<Vec<Token>, EnsError> {
+    let owner: Address = wallet
+        .address_string()
+        .parse()
+        .map_err(|_| EnsError::InvalidAddress(wallet.address_string()))?;
+    let resolver: Address = PUBLIC_RESOLVER.parse().expect("valid resolver address");
+
+    Ok(vec![
+        Token::String(label.to_string()),
+        Token::Address(owner),
+        Token::Uint(duration(years)),
+        Token::FixedBytes(secret.to_vec()),
+        Token::Address(resolver),
+        Token::Array(vec![Token::Bytes(set_addr_call(namehash(name), owner).to_vec())]),
+        Token::Bool(false),
+        Token::Uint(U256::zero()),
+    ])
+}
+
+fn set_addr_call(node: H256, address: Address) -> Bytes {
+    encode_call(
+        "setAddr(bytes32,address)",
+        &[Token::FixedBytes(node.as_bytes().to_vec()), Token::Address(address)],
+    )
+}
+
+fn encode_call(signature: &str, args: &[Token]) -> Bytes {
+    let mut data = id(signature).to_vec();
+    data.extend(abi::encode(args));
+    data.into()
+}
+
+/// `eth_call` a contract function and decode its outputs
+async fn call(
+    provider: &Provider<Http>,
+    contract: &str,
+    signature: &str,
+    args: &[Token],
+    outputs: &[ParamType],
+) -> Result<Vec<Token>, EnsError> {
+    let to: Address = contract
+        .parse()
+        .map_err(|_| EnsError::InvalidAddress(contract.to_string()))?;
+    let tx = TransactionRequest::new().to(to).data(encode_call(signature, args));
+    let output = provider
+        .call(&tx.into(), None)
+        .await
+        .map_err(|e| EnsError::RpcError(e.to_string()))?;
+    abi::decode(outputs, &output).map_err(|e| EnsError::RpcError(e.to_string()))
+}
+
+/// Sign and broadcast a contract call, returning the transaction hash
+async fn send(
+    provider: Provider<Http>,
+    wallet: &EthereumWallet,
+    contract: &str,
+    data: Bytes,
+    value: U256,
+    nonce: u64,
+    gas_price: u128,
+) -> Result<String, EnsError> {
+    let to: Address = contract
+        .parse()
+        .map_err(|_| EnsError::InvalidAddress(contract.to_string()))?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| EnsError::RpcError(e.to_string()))?
+        .as_u64();
+    let signer = LocalWallet::from(wallet.signing_key()).with_chain_id(chain_id);
+    let client = SignerMiddleware::new(provider, signer);
+
+    let tx = TransactionRequest::new()
+        .to(to)
+        .data(data)
+        .value(value)
+        .nonce(nonce)
+        .gas_price(gas_price);
+    let pending = client
+        .send_transaction(tx, None)
+        .await
+        .map_err(|e| EnsError::TransactionFailed(e.to_string()))?;
+    Ok(format!("0x{:x}", pending.tx_hash()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_calls() {
+        assert_eq!(label_of("alice.eth").unwrap(), "alice");
+        for name in ["alice", "pay.alice.eth", ".eth", "alice.sol"] {
+            assert!(label_of(name).is_err(), "{}", name);
+        }
+
+        // setAddr(bytes32,address) selector, then the two words
+        let data = set_addr_call(namehash("alice.eth"), Address::zero());
+        assert_eq!(hex::encode(&data[..4]), "d5fa2b00");
+        assert_eq!(data.len(), 4 + 2 * 32);
+        assert_eq!(duration(2), U256::from(63_072_000u64));
+    }
+}
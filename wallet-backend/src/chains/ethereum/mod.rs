@@ -1,13 +1,29 @@
 //! Ethereum blockchain operations using Alloy
 
 pub mod balance;
+pub mod bridge;
+pub mod client;
+pub mod cold;
+pub mod ens;
 pub mod multisig;
 pub mod nft;
+pub mod nonce;
+pub mod protect;
+pub mod route;
+pub mod siwe;
 pub mod transaction;
 pub mod wallet;
 
 pub use balance::*;
+pub use bridge::*;
+pub use client::*;
+pub use cold::*;
+pub use ens::*;
 pub use multisig::*;
 pub use nft::*;
+pub use nonce::*;
+pub use protect::*;
+pub use route::*;
+pub use siwe::*;
 pub use transaction::*;
 pub use wallet::*;
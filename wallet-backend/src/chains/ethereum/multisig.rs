@@ -1,7 +1,11 @@
 //! Ethereum multi-signature wallet operations (Gnosis Safe style) - Simplified
 
+use std::str::FromStr;
+
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Signature, H256};
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 use super::wallet::EthereumWallet;
@@ -20,6 +24,10 @@ pub enum EthMultisigError {
     TransactionNotFound,
     #[error("Safe creation failed: {0}")]
     CreationFailed(String),
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
 }
 
 /// Multi-sig (Safe) configuration
@@ -64,6 +72,17 @@ pub struct SafeCreationResult {
     pub tx_hash: Option<String>,
 }
 
+/// Gas of deploying a Safe proxy through the factory and running its setup
+/// with a single owner
+const SAFE_DEPLOY_GAS: u64 = 260_000;
+/// Setup gas each further owner adds, mostly for its storage slot
+const SAFE_DEPLOY_GAS_PER_OWNER: u64 = 25_000;
+
+/// Gas a Safe deployment with `owners` owners is expected to use
+pub fn safe_deployment_gas(owners: usize) -> u64 {
+    SAFE_DEPLOY_GAS + owners.saturating_sub(1) as u64 * SAFE_DEPLOY_GAS_PER_OWNER
+}
+
 /// Compute Safe address deterministically (CREATE2)
 pub fn compute_safe_address(
     _factory: &str,
@@ -175,7 +194,10 @@ pub fn add_safe_confirmation(
 }
 
 /// Get Safe info (placeholder - would query blockchain)
-pub async fn get_safe_info(_rpc_url: &str, safe_address: &str) -> Result<SafeWallet, EthMultisigError> {
+pub async fn get_safe_info(
+    _rpc_url: &str,
+    safe_address: &str,
+) -> Result<SafeWallet, EthMultisigError> {
     // In production, this would query the Safe contract
     Ok(SafeWallet {
         address: safe_address.to_string(),
@@ -184,3 +206,112 @@ pub async fn get_safe_info(_rpc_url: &str, safe_address: &str) -> Result<SafeWal
         nonce: 0,
     })
 }
+
+/// EIP-712 typed data for a Safe (v1.3+) transaction, in the form wallets take
+/// for `eth_signTypedData_v4`. Gas refund fields are zero: the executor pays.
+pub fn safe_typed_data(
+    safe_address: &str,
+    to: &str,
+    value_wei: &str,
+    data: &[u8],
+    nonce: u64,
+    chain_id: u64,
+) -> Result<TypedData, EthMultisigError> {
+    const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+    let typed_data = serde_json::json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "SafeTx": [
+                { "name": "to", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "data", "type": "bytes" },
+                { "name": "operation", "type": "uint8" },
+                { "name": "safeTxGas", "type": "uint256" },
+                { "name": "baseGas", "type": "uint256" },
+                { "name": "gasPrice", "type": "uint256" },
+                { "name": "gasToken", "type": "address" },
+                { "name": "refundReceiver", "type": "address" },
+                { "name": "nonce", "type": "uint256" }
+            ]
+        },
+        "primaryType": "SafeTx",
+        "domain": {
+            "chainId": chain_id,
+            "verifyingContract": safe_address
+        },
+        "message": {
+            "to": to,
+            "value": value_wei,
+            "data": format!("0x{}", hex::encode(data)),
+            "operation": 0,
+            "safeTxGas": "0",
+            "baseGas": "0",
+            "gasPrice": "0",
+            "gasToken": ZERO_ADDRESS,
+            "refundReceiver": ZERO_ADDRESS,
+            "nonce": nonce
+        }
+    });
+
+    let typed_data: TypedData = serde_json::from_value(typed_data)
+        .map_err(|e| EthMultisigError::InvalidTransaction(e.to_string()))?;
+    // Fail now rather than when an owner tries to sign
+    safe_tx_hash(&typed_data)?;
+    Ok(typed_data)
+}
+
+/// Hash the owners sign (the Safe transaction hash)
+pub fn safe_tx_hash(typed_data: &TypedData) -> Result<[u8; 32], EthMultisigError> {
+    typed_data
+        .encode_eip712()
+        .map_err(|e| EthMultisigError::InvalidTransaction(e.to_string()))
+}
+
+/// Address that produced a 65-byte ECDSA signature over the Safe transaction hash
+pub fn recover_safe_signer(
+    safe_tx_hash: [u8; 32],
+    signature: &str,
+) -> Result<String, EthMultisigError> {
+    let signature = Signature::from_str(signature)
+        .map_err(|e| EthMultisigError::InvalidSignature(e.to_string()))?;
+    let signer = signature
+        .recover(H256::from(safe_tx_hash))
+        .map_err(|e| EthMultisigError::InvalidSignature(e.to_string()))?;
+
+    Ok(format!("{:?}", signer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+
+    #[test]
+    fn test_recover_safe_signer() {
+        let owner: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap();
+        let typed_data = safe_typed_data(
+            "0x5afe5afe5afe5afe5afe5afe5afe5afe5afe5afe",
+            "0x000000000000000000000000000000000000dead",
+            "1000000000000000",
+            &[],
+            0,
+            11_155_111,
+        )
+        .unwrap();
+        let hash = safe_tx_hash(&typed_data).unwrap();
+
+        let signature = owner.sign_hash(H256::from(hash)).unwrap();
+        let signer = recover_safe_signer(hash, &signature.to_string()).unwrap();
+        assert_eq!(signer, format!("{:?}", owner.address()));
+
+        let other_hash = [0u8; 32];
+        let other = recover_safe_signer(other_hash, &signature.to_string()).unwrap();
+        assert_ne!(other, signer);
+    }
+}
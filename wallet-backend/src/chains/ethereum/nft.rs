@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::chains::client::{NftHolder, NftMetadata};
+use crate::chains::reader::ByteReader;
+
 #[derive(Debug, Error)]
 pub enum EthNftError {
     #[error("RPC error: {0}")]
@@ -140,13 +143,102 @@ pub async fn get_erc721_token_uri(
 
     let hex_result = response.result.unwrap_or_default();
 
-    // Decode ABI-encoded string (simplified)
-    // In production, use proper ABI decoding
     let decoded = decode_string_from_hex(&hex_result)?;
 
     Ok(decoded)
 }
 
+/// Address ERC-721 tokens are commonly burned to instead of the zero address
+const DEAD_ADDRESS: &str = "000000000000000000000000000000000000dead";
+
+/// Delegation designator prefix of EIP-7702 accounts, which are still wallets
+const DELEGATION_PREFIX: &str = "0xef0100";
+
+/// Current holder of an ERC-721 token. A reverted `ownerOf` or a zero/dead
+/// owner is a burn; an owner with contract code is a marketplace escrow.
+pub async fn get_erc721_holder(
+    rpc_url: &str,
+    contract: &str,
+    token_id: &str,
+) -> Result<NftHolder, EthNftError> {
+    let client = reqwest::Client::new();
+
+    // ownerOf(uint256) selector: 0x6352211e
+    let token_id = ethers::types::U256::from_dec_str(token_id)
+        .map_err(|_| EthNftError::MetadataError(format!("Invalid token ID {}", token_id)))?;
+    let data = format!("0x6352211e{:064x}", token_id);
+
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0",
+        method: "eth_call",
+        params: vec![
+            serde_json::json!({
+                "to": contract,
+                "data": data
+            }),
+            serde_json::Value::String("latest".to_string()),
+        ],
+        id: 1,
+    };
+
+    let response: JsonRpcResponse = client
+        .post(rpc_url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| EthNftError::RpcError(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| EthNftError::RpcError(e.to_string()))?;
+
+    if let Some(error) = response.error {
+        // ownerOf reverts for tokens that don't exist (any more)
+        if error.message.contains("revert") {
+            return Ok(NftHolder::Burned);
+        }
+        return Err(EthNftError::RpcError(error.message));
+    }
+
+    let result = response.result.unwrap_or_default();
+    let owner = decode_address_from_hex(&result)
+        .map_err(|_| EthNftError::InvalidAddress(contract.to_string()))?;
+    if owner.chars().all(|c| c == '0') || owner.eq_ignore_ascii_case(DEAD_ADDRESS) {
+        return Ok(NftHolder::Burned);
+    }
+    let owner = format!("0x{}", owner);
+
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0",
+        method: "eth_getCode",
+        params: vec![
+            serde_json::Value::String(owner.clone()),
+            serde_json::Value::String("latest".to_string()),
+        ],
+        id: 2,
+    };
+
+    let response: JsonRpcResponse = client
+        .post(rpc_url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| EthNftError::RpcError(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| EthNftError::RpcError(e.to_string()))?;
+
+    if let Some(error) = response.error {
+        return Err(EthNftError::RpcError(error.message));
+    }
+
+    let code = response.result.unwrap_or_default();
+    if code.len() > 2 && !code.starts_with(DELEGATION_PREFIX) {
+        Ok(NftHolder::Escrow(owner))
+    } else {
+        Ok(NftHolder::Address(owner))
+    }
+}
+
 /// Fetch off-chain NFT metadata from URI
 pub async fn fetch_nft_metadata(uri: &str) -> Result<serde_json::Value, EthNftError> {
     // Handle IPFS URIs
@@ -170,6 +262,24 @@ pub async fn fetch_nft_metadata(uri: &str) -> Result<serde_json::Value, EthNftEr
     Ok(metadata)
 }
 
+/// Current `tokenURI` of an ERC-721 token and the document it points to
+pub async fn get_erc721_metadata(
+    rpc_url: &str,
+    contract: &str,
+    token_id: &str,
+) -> Result<NftMetadata, EthNftError> {
+    let token_id_u64: u64 = token_id
+        .parse()
+        .map_err(|_| EthNftError::MetadataError(format!("Invalid token ID {}", token_id)))?;
+
+    let uri = get_erc721_token_uri(rpc_url, contract, token_id_u64).await?;
+    let document = match uri.as_str() {
+        "" => None,
+        uri => Some(fetch_nft_metadata(uri).await?),
+    };
+    Ok(NftMetadata::from_document(uri, document))
+}
+
 /// Get NFT details including metadata
 pub async fn get_nft_details(
     rpc_url: &str,
@@ -222,23 +332,95 @@ pub async fn get_nft_details(
     })
 }
 
-/// Decode ABI-encoded string from hex (simplified)
+/// Decode an ABI-encoded `string` return value from hex
 fn decode_string_from_hex(hex: &str) -> Result<String, EthNftError> {
     let bytes = hex::decode(hex.trim_start_matches("0x"))
         .map_err(|e| EthNftError::MetadataError(e.to_string()))?;
 
-    if bytes.len() < 64 {
-        return Err(EthNftError::MetadataError("Invalid ABI encoding".to_string()));
+    ByteReader::new(&bytes)
+        .abi_string()
+        .map_err(|e| EthNftError::MetadataError(format!("Invalid ABI encoding: {}", e)))
+}
+
+/// Decode an ABI-encoded `address` return value from hex, as lowercase hex
+/// without the `0x` prefix
+fn decode_address_from_hex(hex: &str) -> Result<String, EthNftError> {
+    let bytes = hex::decode(hex.trim_start_matches("0x"))
+        .map_err(|e| EthNftError::MetadataError(e.to_string()))?;
+
+    ByteReader::new(&bytes)
+        .abi_address()
+        .map(hex::encode)
+        .map_err(|e| EthNftError::MetadataError(format!("Invalid ABI encoding: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::fuzz::no_panic;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn word(value: usize) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn encoded_string(value: &str) -> String {
+        let mut data = hex::encode(value);
+        let padding = (64 - data.len() % 64) % 64;
+        data.push_str(&"0".repeat(padding));
+        format!("0x{}{}{}", word(32), word(value.len()), data)
     }
 
-    // Skip offset (32 bytes) and read length (32 bytes)
-    let len_bytes: [u8; 8] = bytes[56..64].try_into().unwrap_or([0u8; 8]);
-    let len = u64::from_be_bytes(len_bytes) as usize;
+    #[test]
+    fn test_decode_string() {
+        let uri = "ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/1";
+        assert_eq!(decode_string_from_hex(&encoded_string(uri)).unwrap(), uri);
+        assert_eq!(decode_string_from_hex(&encoded_string("")).unwrap(), "");
 
-    if bytes.len() < 64 + len {
-        return Err(EthNftError::MetadataError("String too short".to_string()));
+        let full = encoded_string(uri);
+        for len in (0..full.len() - 64).step_by(2) {
+            assert!(decode_string_from_hex(&full[..len]).is_err(), "{}", len);
+        }
+        // A length near u64::MAX used to overflow the bounds check
+        let huge = format!("0x{}{}", word(32), "f".repeat(64));
+        assert!(decode_string_from_hex(&huge).is_err());
+        assert!(decode_string_from_hex("0xzz").is_err());
+    }
+
+    #[test]
+    fn test_decode_address() {
+        let owner = format!("0x{}{}", "0".repeat(24), "ab".repeat(20));
+        assert_eq!(decode_address_from_hex(&owner).unwrap(), "ab".repeat(20));
+        assert!(decode_address_from_hex(&owner[..60]).is_err());
+        assert!(decode_address_from_hex(&format!("0x{}", "f".repeat(64))).is_err());
+        // Multi-byte characters used to panic when the word was sliced
+        assert!(decode_address_from_hex(&"é".repeat(40)).is_err());
     }
 
-    String::from_utf8(bytes[64..64 + len].to_vec())
-        .map_err(|e| EthNftError::MetadataError(e.to_string()))
+    #[test]
+    fn test_fuzz_decoders() {
+        // Fixed so a failure reproduces
+        let mut rng = StdRng::seed_from_u64(0);
+        let valid = hex::decode(&encoded_string("https://example.com/1.json")[2..]).unwrap();
+
+        for _ in 0..10_000 {
+            let len = rng.gen_range(0..256);
+            let data: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            no_panic(&data, |data| {
+                let _ = decode_string_from_hex(&hex::encode(data));
+                let _ = decode_address_from_hex(&hex::encode(data));
+                let _ = decode_address_from_hex(&String::from_utf8_lossy(data));
+            });
+
+            let mut mutated = valid.clone();
+            for _ in 0..rng.gen_range(1..8) {
+                let i = rng.gen_range(0..mutated.len());
+                mutated[i] = rng.gen();
+            }
+            mutated.truncate(rng.gen_range(0..=mutated.len()));
+            no_panic(&mutated, |mutated| {
+                let _ = decode_string_from_hex(&hex::encode(mutated));
+            });
+        }
+    }
 }
@@ -0,0 +1,84 @@
+//! Nonce allocation for outgoing Ethereum transactions
+//!
+//! A node's pending transaction count lags behind sends that are still
+//! propagating, so two sends in quick succession could be given the same
+//! nonce and the second would silently replace the first. The manager
+//! remembers the next nonce it handed out for each address and never goes
+//! below it.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use ethers::core::types::{Address, BlockNumber};
+use ethers::providers::{Http, Middleware, Provider};
+
+use super::transaction::EthTxError;
+
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next: Mutex<HashMap<String, u64>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve the next nonce for `address`: the node's pending count, or one
+    /// past the last nonce handed out, whichever is higher
+    pub async fn next(&self, rpc_url: &str, address: &str) -> Result<u64, EthTxError> {
+        let provider = Provider::<Http>::try_from(rpc_url)
+            .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+        let account = Address::from_str(address)
+            .map_err(|_| EthTxError::InvalidAddress(address.to_string()))?;
+
+        let pending = provider
+            .get_transaction_count(account, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+
+        Ok(self.reserve(address, pending.as_u64()))
+    }
+
+    /// Hand back a nonce whose transaction was never broadcast, if no later
+    /// one has been reserved since
+    pub fn release(&self, address: &str, nonce: u64) {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = next.get_mut(&address.to_lowercase()) {
+            if *entry == nonce + 1 {
+                *entry = nonce;
+            }
+        }
+    }
+
+    fn reserve(&self, address: &str, pending: u64) -> u64 {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = next.entry(address.to_lowercase()).or_insert(0);
+        let nonce = pending.max(*entry);
+        *entry = nonce + 1;
+        nonce
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_and_release() {
+        let nonces = NonceManager::new();
+        let address = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
+
+        assert_eq!(nonces.reserve(address, 4), 4);
+        // The node hasn't seen nonce 4 yet
+        assert_eq!(nonces.reserve(&address.to_lowercase(), 4), 5);
+        nonces.release(address, 5);
+        assert_eq!(nonces.reserve(address, 4), 5);
+        // Sends made elsewhere move the node ahead
+        assert_eq!(nonces.reserve(address, 9), 9);
+        // Only the latest reservation can be handed back
+        nonces.release(address, 5);
+        assert_eq!(nonces.reserve(address, 4), 10);
+    }
+}
@@ -0,0 +1,65 @@
+//! Flashbots Protect status lookups
+//!
+//! Transactions sent through the Protect RPC stay out of the public mempool,
+//! so a public node knows nothing of them until they are mined. The status
+//! API reports whether the relay is still trying to get one included or has
+//! given up on it.
+
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::transaction::EthTxError;
+
+/// Where the relay stands on a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PrivateTxStatus {
+    /// Still being offered to block builders
+    Pending,
+    Included,
+    /// Not included before the relay stopped trying
+    Failed,
+    /// Cancelled by the sender through the relay
+    Cancelled,
+    /// The relay hasn't seen it
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    status: PrivateTxStatus,
+}
+
+/// Status of `tx_hash` from the API at `status_url` (e.g.
+/// `https://protect.flashbots.net/tx`)
+pub async fn private_tx_status(
+    status_url: &str,
+    tx_hash: &str,
+) -> Result<PrivateTxStatus, EthTxError> {
+    let url = format!("{}/{}", status_url.trim_end_matches('/'), tx_hash);
+    let response = Client::new()
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    let body: StatusResponse = response
+        .json()
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    Ok(body.status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_parsing() {
+        let parse = |json: &str| serde_json::from_str::<StatusResponse>(json).unwrap().status;
+        assert_eq!(parse(r#"{"status":"PENDING","hash":"0x1"}"#), PrivateTxStatus::Pending);
+        assert_eq!(parse(r#"{"status":"FAILED"}"#), PrivateTxStatus::Failed);
+        assert_eq!(parse(r#"{"status":"SOMETHING_NEW"}"#), PrivateTxStatus::Unknown);
+    }
+}
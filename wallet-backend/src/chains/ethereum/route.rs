@@ -0,0 +1,137 @@
+//! Source transactions of cross-chain routes
+//!
+//! An aggregator builds the call that hands value to a bridge or swap
+//! contract; it is signed and sent here, after an ERC-20 approval when the
+//! route spends a token rather than ETH.
+
+use ethers::abi::{self, Token};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, TransactionRequest, U256};
+use ethers::utils::id;
+
+use super::transaction::EthTxError;
+use super::wallet::EthereumWallet;
+use crate::chains::client::TokenApproval;
+
+/// Gas of an ERC-20 approval
+pub const APPROVE_GAS: u64 = 60_000;
+
+/// Gas assumed for a route call the aggregator gave no limit for
+pub const ROUTE_CALL_GAS: u64 = 500_000;
+
+/// A contract call ready to sign
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractCall {
+    pub to: Address,
+    pub data: Bytes,
+    /// Wei sent with the call
+    pub value: u128,
+    /// Left for the node to estimate when `None`
+    pub gas_limit: Option<u64>,
+}
+
+impl ContractCall {
+    /// Parse the target and hex calldata of a call
+    pub fn new(
+        to: &str,
+        data: &str,
+        value: u128,
+        gas_limit: Option<u64>,
+    ) -> Result<Self, EthTxError> {
+        let to = to
+            .parse()
+            .map_err(|_| EthTxError::InvalidAddress(to.to_string()))?;
+        let data = hex::decode(data.trim_start_matches("0x"))
+            .map_err(|e| EthTxError::TransactionFailed(format!("bad calldata: {}", e)))?;
+        Ok(Self {
+            to,
+            data: data.into(),
+            value,
+            gas_limit,
+        })
+    }
+
+    /// `approve(spender, amount)` on the token contract
+    pub fn approve(approval: &TokenApproval) -> Result<Self, EthTxError> {
+        let token: Address = approval
+            .token
+            .parse()
+            .map_err(|_| EthTxError::InvalidAddress(approval.token.clone()))?;
+        let spender: Address = approval
+            .spender
+            .parse()
+            .map_err(|_| EthTxError::InvalidAddress(approval.spender.clone()))?;
+        let mut data = id("approve(address,uint256)").to_vec();
+        data.extend(abi::encode(&[
+            Token::Address(spender),
+            Token::Uint(U256::from(approval.amount)),
+        ]));
+        Ok(Self {
+            to: token,
+            data: data.into(),
+            value: 0,
+            gas_limit: Some(APPROVE_GAS),
+        })
+    }
+}
+
+/// Sign and broadcast `call` from the wallet. Returns the transaction hash.
+pub async fn send_call(
+    rpc_url: &str,
+    wallet: &EthereumWallet,
+    call: &ContractCall,
+    nonce: u64,
+    gas_price: u128,
+) -> Result<String, EthTxError> {
+    let provider =
+        Provider::<Http>::try_from(rpc_url).map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?
+        .as_u64();
+
+    let signer = LocalWallet::from(wallet.signing_key()).with_chain_id(chain_id);
+    let client = SignerMiddleware::new(provider, signer);
+    let mut tx = TransactionRequest::new()
+        .to(call.to)
+        .data(call.data.clone())
+        .value(call.value)
+        .nonce(nonce)
+        .gas_price(gas_price);
+    if let Some(gas_limit) = call.gas_limit {
+        tx = tx.gas(gas_limit);
+    }
+    let pending = client
+        .send_transaction(tx, None)
+        .await
+        .map_err(|e| EthTxError::TransactionFailed(e.to_string()))?;
+    Ok(format!("0x{:x}", pending.tx_hash()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approve_call() {
+        let approval = TokenApproval {
+            token: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+            spender: "0x1231DEB6f5749EF6cE6943a275A1D3E7486F4EaE".to_string(),
+            amount: 1_000_000,
+        };
+        let call = ContractCall::approve(&approval).unwrap();
+        assert_eq!(call.to, approval.token.parse::<Address>().unwrap());
+        assert_eq!(call.value, 0);
+        assert_eq!(hex::encode(&call.data[..4]), "095ea7b3");
+        assert_eq!(call.data.len(), 4 + 2 * 32);
+        assert_eq!(U256::from_big_endian(&call.data[36..]), U256::from(1_000_000u64));
+
+        let call = ContractCall::new(&approval.spender, "0xdeadbeef", 5, None).unwrap();
+        assert_eq!(call.data.to_vec(), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert!(ContractCall::new("nope", "0x", 0, None).is_err());
+        assert!(ContractCall::new(&approval.spender, "0xzz", 0, None).is_err());
+    }
+}
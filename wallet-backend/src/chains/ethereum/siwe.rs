@@ -0,0 +1,44 @@
+//! Sign-In With Ethereum (EIP-4361) signature checks
+
+use std::str::FromStr;
+
+use ethers::types::Signature;
+use ethers::utils::to_checksum;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SiweError {
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+}
+
+/// Checksummed address that produced an EIP-191 `personal_sign` signature
+/// over `message`
+pub fn recover_message_signer(message: &str, signature: &str) -> Result<String, SiweError> {
+    let signature = Signature::from_str(signature.trim())
+        .map_err(|e| SiweError::InvalidSignature(e.to_string()))?;
+    let signer = signature
+        .recover(message)
+        .map_err(|e| SiweError::InvalidSignature(e.to_string()))?;
+
+    Ok(to_checksum(&signer, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+
+    #[tokio::test]
+    async fn test_recover_message_signer() {
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+        let signature = wallet.sign_message("hello").await.unwrap();
+
+        let signer = recover_message_signer("hello", &signature.to_string()).unwrap();
+        assert_eq!(signer, to_checksum(&wallet.address(), None));
+        assert_eq!(crate::chains::ethereum::checksum_address(&signer), signer);
+
+        let other = recover_message_signer("goodbye", &signature.to_string()).unwrap();
+        assert_ne!(other, signer);
+    }
+}
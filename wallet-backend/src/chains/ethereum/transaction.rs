@@ -1,7 +1,7 @@
 //! Ethereum transaction operations using ethers-rs
 
-use ethers::core::types::{Address, TransactionRequest};
-use ethers::middleware::SignerMiddleware;
+use ethers::core::types::transaction::eip2718::TypedTransaction;
+use ethers::core::types::{Address, Bytes, TransactionRequest, H256, U256};
 use ethers::providers::{Http, Middleware, Provider};
 use ethers::signers::{LocalWallet, Signer};
 use serde::{Deserialize, Serialize};
@@ -9,6 +9,10 @@ use std::str::FromStr;
 use thiserror::Error;
 use reqwest::Client;
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use crate::chains::client::{BalanceChange, ConfirmedEffects, TxEffects, TxReceipt};
+use crate::core::{format_units, Chain};
 
 use super::wallet::EthereumWallet;
 
@@ -18,8 +22,8 @@ pub enum EthTxError {
     RpcError(String),
     #[error("Invalid address: {0}")]
     InvalidAddress(String),
-    #[error("Insufficient balance")]
-    InsufficientBalance,
+    #[error("Insufficient balance: {required} wei required, {available} available")]
+    InsufficientBalance { required: u128, available: u128 },
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
     #[error("Invalid amount")]
@@ -37,6 +41,9 @@ pub enum EthTxError {
 pub struct EthTxResult {
     pub tx_hash: String,
     pub status: String,
+    /// Signed transaction, hex-encoded, when it was signed here
+    #[serde(default)]
+    pub raw_tx: Option<String>,
 }
 
 /// Transaction info from history
@@ -46,6 +53,8 @@ pub struct EthTransactionInfo {
     pub from: String,
     pub to: Option<String>,
     pub value: String,
+    /// ETH transfers: `value` in wei
+    pub value_raw: Option<String>,
     pub gas_price: String,
     pub gas_used: Option<String>,
     pub block_number: Option<u64>,
@@ -74,6 +83,11 @@ struct AlchemyTransfer {
     to: Option<String>,
     value: Option<f64>,
     asset: Option<String>,
+    /// `external` for plain ETH transfers
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    raw_contract: AlchemyRawContract,
     // There can be more metadata, but we'll focus on what we need for EthTransactionInfo
     #[serde(default)]
     metadata: AlchemyMetadata,
@@ -85,18 +99,113 @@ struct AlchemyTransfer {
     gas_price: String,
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct AlchemyRawContract {
+    /// Hex amount in base units
+    value: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 struct AlchemyMetadata {
     // Add fields if needed
 }
 
-/// Send native ETH
+/// Gas limit of a plain ETH transfer
+pub const NATIVE_TRANSFER_GAS: u64 = 21_000;
+/// Typical gas limit of an ERC-20 `transfer` (first-time recipients cost more)
+pub const ERC20_TRANSFER_GAS: u64 = 65_000;
+
+/// Current gas price in wei, as used for legacy transactions by `send_eth`
+pub async fn get_gas_price(rpc_url: &str) -> Result<u128, EthTxError> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+
+    let gas_price = provider
+        .get_gas_price()
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+
+    Ok(gas_price.as_u128())
+}
+
+/// Gas price for a replacement of a pending transaction: nodes only accept
+/// one that outbids the original by at least 10%, so this offers 12.5% more,
+/// or the current price if that is higher
+pub fn replacement_gas_price(original_wei: u128, current_wei: u128) -> u128 {
+    current_wei.max(original_wei + original_wei / 8 + 1)
+}
+
+/// Latest block number
+pub async fn get_block_number(rpc_url: &str) -> Result<u64, EthTxError> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+
+    let block = provider
+        .get_block_number()
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+
+    Ok(block.as_u64())
+}
+
+/// Whether the address has sent a transaction or holds ether. Incoming
+/// transfers leave no trace without an indexer, so an emptied address that
+/// never sent anything reads as unused.
+pub async fn has_activity(rpc_url: &str, address: &str) -> Result<bool, EthTxError> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    let account = Address::from_str(address)
+        .map_err(|_| EthTxError::InvalidAddress(address.to_string()))?;
+
+    let nonce = provider
+        .get_transaction_count(account, None)
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    if !nonce.is_zero() {
+        return Ok(true);
+    }
+    let balance = provider
+        .get_balance(account, None)
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+
+    Ok(!balance.is_zero())
+}
+
+/// Largest ETH transfer: balance minus gas price x gas limit
+pub fn max_sendable_eth(balance_wei: u128, gas_price_wei: u128) -> u128 {
+    balance_wei.saturating_sub(gas_price_wei * NATIVE_TRANSFER_GAS as u128)
+}
+
+/// Balance rule for a transaction: value plus worst-case gas must be covered
+pub fn check_eth_transfer(
+    balance_wei: u128,
+    value_wei: u128,
+    gas_price_wei: u128,
+    gas_limit: u64,
+) -> Result<(), EthTxError> {
+    let required = value_wei.saturating_add(gas_price_wei.saturating_mul(gas_limit as u128));
+    if balance_wei < required {
+        return Err(EthTxError::InsufficientBalance {
+            required,
+            available: balance_wei,
+        });
+    }
+    Ok(())
+}
+
+/// Send native ETH with the given nonce and legacy gas price. The
+/// transaction is signed locally and broadcast to `broadcast_url`, which is
+/// `rpc_url` itself unless it goes through a private relay.
 pub async fn send_eth(
     rpc_url: &str,
+    broadcast_url: &str,
     wallet: &EthereumWallet,
     to: &str,
     amount_eth: f64,
+    nonce: u64,
+    gas_price_wei: u128,
 ) -> Result<EthTxResult, EthTxError> {
     // 1. Connect to the Ethereum node
     let provider = Provider::<Http>::try_from(rpc_url)
@@ -118,28 +227,59 @@ pub async fn send_eth(
     let signer_wallet = LocalWallet::from(wallet.signing_key())
         .with_chain_id(chain_id);
 
-    // 5. Build the transaction
-    let tx = TransactionRequest::new()
+    // 5. Build the transaction, with the gas limit estimated by the node
+    let mut tx: TypedTransaction = TransactionRequest::new()
+        .from(signer_wallet.address())
         .to(to_address)
-        .value(value);
-
-    // 6. Create client with signer middleware
-    let client = SignerMiddleware::new(provider, signer_wallet);
-
-    // 7. Send the transaction and get the pending transaction
-    let pending_tx = client
-        .send_transaction(tx, None)
+        .value(value)
+        .nonce(nonce)
+        .gas_price(gas_price_wei)
+        .chain_id(chain_id)
+        .into();
+    provider
+        .fill_transaction(&mut tx, None)
         .await
-        .map_err(|e| EthTxError::TransactionFailed(e.to_string()))?;
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
 
-    let tx_hash = format!("0x{:x}", pending_tx.tx_hash());
+    // 6. Sign it, keeping the signed bytes for a later public broadcast
+    let signature = signer_wallet
+        .sign_transaction(&tx)
+        .await
+        .map_err(|e| EthTxError::SigningError(e.to_string()))?;
+    let raw = tx.rlp_signed(&signature);
+
+    // 7. Broadcast and get the transaction hash
+    let tx_hash = if broadcast_url == rpc_url {
+        broadcast(&provider, raw.clone()).await?
+    } else {
+        let relay = Provider::<Http>::try_from(broadcast_url)
+            .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+        broadcast(&relay, raw.clone()).await?
+    };
 
     Ok(EthTxResult {
         tx_hash,
         status: "pending".to_string(),
+        raw_tx: Some(format!("0x{}", hex::encode(&raw))),
     })
 }
 
+/// Broadcast a signed, hex-encoded transaction and return its hash
+pub async fn send_raw_transaction(rpc_url: &str, raw_tx: &str) -> Result<String, EthTxError> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    let raw = hex::decode(raw_tx.trim_start_matches("0x"))
+        .map_err(|e| EthTxError::TransactionFailed(format!("bad signed transaction: {}", e)))?;
+    broadcast(&provider, raw.into()).await
+}
+
+async fn broadcast(provider: &Provider<Http>, raw: Bytes) -> Result<String, EthTxError> {
+    let pending = provider
+        .send_raw_transaction(raw)
+        .await
+        .map_err(|e| EthTxError::TransactionFailed(e.to_string()))?;
+    Ok(format!("0x{:x}", pending.tx_hash()))
+}
 
 /// Send ERC-20 tokens (simplified - returns placeholder for demo)
 pub async fn send_erc20(
@@ -184,6 +324,7 @@ pub async fn send_erc20(
     Ok(EthTxResult {
         tx_hash,
         status: "pending_placeholder".to_string(),
+        raw_tx: None,
     })
 }
 
@@ -236,13 +377,23 @@ pub async fn get_transaction_history(
         // Alchemy returns timestamp as DateTime<Utc>, convert to u64 seconds
         let timestamp = transfer.block_confirm_time.timestamp() as u64;
 
+        // The float `value` loses digits on large transfers; ETH amounts
+        // come from the exact hex one where Alchemy sends it
+        let wei = (transfer.category == "external")
+            .then_some(transfer.raw_contract.value.as_deref())
+            .flatten()
+            .and_then(|v| u128::from_str_radix(v.trim_start_matches("0x"), 16).ok());
+
         // Map Alchemy transfer to EthTransactionInfo
         transactions.push(EthTransactionInfo {
             hash: transfer.hash,
             from: transfer.from,
             to: transfer.to,
-            // Value from Alchemy is already a float, convert to string
-            value: transfer.value.map_or("0.0".to_string(), |v| v.to_string()),
+            value: match wei {
+                Some(wei) => format_units(wei, Chain::Ethereum.native_decimals()),
+                None => transfer.value.map_or("0.0".to_string(), |v| v.to_string()),
+            },
+            value_raw: wei.map(|wei| wei.to_string()),
             gas_price: u128::from_str_radix(transfer.gas_price.trim_start_matches("0x"), 16)
                 .map(|p| p.to_string())
                 .unwrap_or_else(|_| "0".to_string()),
@@ -258,6 +409,143 @@ pub async fn get_transaction_history(
     Ok(transactions)
 }
 
+/// ERC-20 `Transfer(address,address,uint256)` event topic
+const TRANSFER_TOPIC: &str = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// Balance changes a transfer will make: `amount` of ETH or `token` moves
+/// from sender to recipient, and the sender pays at most the gas limit at
+/// `gas_price`
+pub fn transfer_effects(
+    from: &str,
+    to: &str,
+    token: Option<&str>,
+    amount: u128,
+    gas_price: u128,
+    gas_limit: u64,
+) -> TxEffects {
+    let fee = gas_price.saturating_mul(gas_limit as u128);
+    let mut changes = EffectTotals::default();
+    changes.add(from, None, -(fee as i128));
+    changes.add(from, token, -(amount as i128));
+    changes.add(to, token, amount as i128);
+
+    TxEffects {
+        changes: changes.into_changes(|_| true),
+        fee: fee.to_string(),
+    }
+}
+
+/// Balance changes a mined transaction made for `addresses`, from its value
+/// and ERC-20 `Transfer` logs; `None` while it has no receipt
+pub async fn get_transaction_effects(
+    rpc_url: &str,
+    tx_hash: &str,
+    addresses: &[String],
+) -> Result<Option<ConfirmedEffects>, EthTxError> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+    let hash = H256::from_str(tx_hash)
+        .map_err(|_| EthTxError::TransactionFailed(format!("bad transaction hash {}", tx_hash)))?;
+
+    let Some(receipt) = provider
+        .get_transaction_receipt(hash)
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let tx = provider
+        .get_transaction(hash)
+        .await
+        .map_err(|e| EthTxError::RpcError(e.to_string()))?
+        .ok_or_else(|| EthTxError::RpcError(format!("transaction {} not found", tx_hash)))?;
+
+    let gas_used = receipt.gas_used.unwrap_or_default();
+    let gas_price = receipt.effective_gas_price.or(tx.gas_price).unwrap_or_default();
+    let fee = gas_used * gas_price;
+    let succeeded = receipt.status == Some(1u64.into());
+
+    let mut changes = EffectTotals::default();
+    let from = format!("{:?}", tx.from);
+    changes.add(&from, None, -(fee.low_u128() as i128));
+    // A reverted transaction still pays for its gas, but moves nothing
+    if succeeded {
+        if let Some(to) = tx.to {
+            changes.add(&from, None, -(tx.value.low_u128() as i128));
+            changes.add(&format!("{:?}", to), None, tx.value.low_u128() as i128);
+        }
+
+        let transfer_topic = H256::from_str(TRANSFER_TOPIC).expect("valid topic");
+        // NFT transfers share the signature but index the token id as well
+        for log in receipt
+            .logs
+            .iter()
+            .filter(|l| l.topics.len() == 3 && l.topics[0] == transfer_topic)
+        {
+            let token = format!("{:?}", log.address);
+            let amount = U256::from_big_endian(&log.data).low_u128() as i128;
+            let sender = format!("{:?}", Address::from(log.topics[1]));
+            let recipient = format!("{:?}", Address::from(log.topics[2]));
+            changes.add(&sender, Some(&token), -amount);
+            changes.add(&recipient, Some(&token), amount);
+        }
+    }
+
+    let confirmations = match receipt.block_number {
+        Some(block) => {
+            let head = provider
+                .get_block_number()
+                .await
+                .map_err(|e| EthTxError::RpcError(e.to_string()))?;
+            head.as_u64().saturating_sub(block.as_u64()) + 1
+        }
+        None => 0,
+    };
+
+    let addresses: Vec<String> = addresses.iter().map(|a| a.to_lowercase()).collect();
+    Ok(Some(ConfirmedEffects {
+        status: if succeeded { "confirmed" } else { "failed" }.to_string(),
+        block_number: receipt.block_number.map(|b| b.as_u64() as i64),
+        confirmations,
+        effects: TxEffects {
+            changes: changes.into_changes(|address| addresses.iter().any(|a| a == address)),
+            fee: fee.to_string(),
+        },
+        receipt: Some(TxReceipt {
+            gas_used: gas_used.low_u64(),
+            effective_gas_price: gas_price.to_string(),
+            succeeded,
+            logs_count: receipt.logs.len() as u64,
+            contract_address: receipt.contract_address.map(|a| format!("{:?}", a)),
+        }),
+    }))
+}
+
+/// Net change per lowercased address and token
+#[derive(Default)]
+struct EffectTotals(BTreeMap<(String, Option<String>), i128>);
+
+impl EffectTotals {
+    fn add(&mut self, address: &str, token: Option<&str>, delta: i128) {
+        *self
+            .0
+            .entry((address.to_lowercase(), token.map(str::to_lowercase)))
+            .or_insert(0) += delta;
+    }
+
+    fn into_changes(self, keep: impl Fn(&str) -> bool) -> Vec<BalanceChange> {
+        self.0
+            .into_iter()
+            .filter(|((address, _), delta)| *delta != 0 && keep(address))
+            .map(|((address, token), delta)| BalanceChange {
+                address,
+                token,
+                delta: delta.to_string(),
+            })
+            .collect()
+    }
+}
+
 /// Convert Wei to Gwei
 pub fn wei_to_gwei(wei: u128) -> f64 {
     wei as f64 / 1e9
@@ -269,3 +557,58 @@ pub fn gwei_to_wei(gwei: f64) -> u128 {
 }
 
 use sha2::Digest;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    #[test]
+    fn test_check_eth_transfer() {
+        let gas = 10 * GWEI * NATIVE_TRANSFER_GAS as u128;
+        assert!(check_eth_transfer(GWEI * GWEI, GWEI, 10 * GWEI, NATIVE_TRANSFER_GAS).is_ok());
+        assert!(matches!(
+            check_eth_transfer(GWEI, GWEI, 10 * GWEI, NATIVE_TRANSFER_GAS),
+            Err(EthTxError::InsufficientBalance { required, available })
+                if required == GWEI + gas && available == GWEI
+        ));
+        // The max-send amount always passes the pre-flight check
+        let balance = GWEI * GWEI;
+        let max = max_sendable_eth(balance, 10 * GWEI);
+        assert!(check_eth_transfer(balance, max, 10 * GWEI, NATIVE_TRANSFER_GAS).is_ok());
+    }
+
+    #[test]
+    fn test_replacement_gas_price() {
+        // At least 10% over the original, so nodes accept the replacement
+        let bumped = replacement_gas_price(10 * GWEI, 8 * GWEI);
+        assert!(bumped * 10 >= 10 * GWEI * 11);
+        assert_eq!(replacement_gas_price(10 * GWEI, 30 * GWEI), 30 * GWEI);
+    }
+
+    #[test]
+    fn test_transfer_effects() {
+        let from = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
+        let to = "0x71C7656EC7ab88b098defB751B7401B5f6d8976F";
+
+        let native = transfer_effects(from, to, None, GWEI, 10 * GWEI, NATIVE_TRANSFER_GAS);
+        let fee = 10 * GWEI * NATIVE_TRANSFER_GAS as u128;
+        assert_eq!(native.fee, fee.to_string());
+        let sender = native
+            .changes
+            .iter()
+            .find(|c| c.address == from.to_lowercase())
+            .unwrap();
+        assert_eq!(sender.delta, (-((GWEI + fee) as i128)).to_string());
+
+        // Token sends move the token and only cost the sender gas in ETH
+        let token = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+        let erc20 = transfer_effects(from, to, Some(token), 500, 10 * GWEI, ERC20_TRANSFER_GAS);
+        assert_eq!(erc20.changes.len(), 3);
+        assert!(erc20
+            .changes
+            .iter()
+            .any(|c| c.address == to.to_lowercase() && c.token.is_some() && c.delta == "500"));
+    }
+}
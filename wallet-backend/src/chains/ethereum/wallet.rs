@@ -5,7 +5,7 @@ use k256::elliptic_curve::sec1::ToEncodedPoint;
 use thiserror::Error;
 use tiny_keccak::{Hasher, Keccak};
 
-use crate::core::{derive_ethereum_keypair, SecureSeed};
+use crate::core::{derive_ethereum_from_path, derive_ethereum_keypair, SecureSeed};
 
 #[derive(Debug, Error)]
 pub enum EthWalletError {
@@ -53,6 +53,14 @@ impl EthereumWallet {
         Self::from_signing_key(signing_key)
     }
 
+    /// Derive wallet from seed at an account's recorded path
+    pub fn derive_path(seed: &SecureSeed, path: &str) -> Result<Self, EthWalletError> {
+        let (signing_key, _) = derive_ethereum_from_path(seed, path)
+            .map_err(|e| EthWalletError::DerivationFailed(e.to_string()))?;
+
+        Self::from_signing_key(signing_key)
+    }
+
     /// Get address as hex string (with 0x prefix)
     pub fn address_string(&self) -> String {
         self.address.clone()
@@ -80,6 +88,15 @@ impl EthereumWallet {
 
         Ok((sig_bytes.to_vec(), recovery_id))
     }
+
+    /// EIP-191 `personal_sign` signature over `message`, as 0x-prefixed hex
+    pub fn sign_message(&self, message: &str) -> Result<String, EthWalletError> {
+        let signature = ethers::signers::LocalWallet::from(self.signing_key())
+            .sign_hash(ethers::utils::hash_message(message))
+            .map_err(|e| EthWalletError::SigningError(e.to_string()))?;
+
+        Ok(format!("0x{}", signature))
+    }
 }
 
 /// Validate an Ethereum address
@@ -165,7 +182,7 @@ mod tests {
 
     #[test]
     fn test_validate_address() {
-        assert!(validate_address("0x742d35Cc6634C0532925a3b844Bc9e7595f3fE70")); // Valid checksum
+        assert!(validate_address("0x742d35cc6634c0532925a3B844Bc9E7595F3fe70")); // Valid checksum
         assert!(validate_address("0x742d35cc6634c0532925a3b844bc9e7595f3fe70")); // Valid lowercase
         assert!(validate_address("0x742D35CC6634C0532925A3B844BC9E7595F3FE70")); // Valid uppercase (treated as valid)
         
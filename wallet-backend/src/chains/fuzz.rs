@@ -0,0 +1,8 @@
+//! Helpers for fuzzing the decoders of RPC data
+
+/// Fail with the input that made `decode` panic, so it can be replayed
+pub fn no_panic(input: &[u8], decode: impl Fn(&[u8]) + std::panic::RefUnwindSafe) {
+    if std::panic::catch_unwind(|| decode(input)).is_err() {
+        panic!("panicked on input {}", hex::encode(input));
+    }
+}
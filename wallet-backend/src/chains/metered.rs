@@ -0,0 +1,538 @@
+//! RPC usage accounting
+//!
+//! `MeteredClient` wraps a chain client and records every call in a
+//! Prometheus registry, labelled by chain, provider (the RPC host) and
+//! method. Calls are counted per client operation; one operation may issue
+//! several JSON-RPC requests, so provider-side counts run higher.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Chain, SecureSeed};
+
+use super::client::{
+    BridgeDeposit, ChainBalance, ChainClient, ChainClientError, ChainTokenBalance, DeploymentCost,
+    ConfirmedEffects, Identity, MaxSend, NameQuote, NameRegistration, NftHolder, NftMetadata,
+    HistoryEntry, ReferencedTransaction, RouteSubmission, RouteTransaction, SentTransfer, StakePoolState,
+    TokenMetadata, Transfer, UnsignedTransfer,
+};
+
+const CALLS_METRIC: &str = "rpc_calls_total";
+const LATENCY_METRIC: &str = "rpc_call_duration_seconds";
+
+/// Latency buckets in seconds, from a cached node to a slow broadcast
+const LATENCY_BUCKETS: &[f64] = &[0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Call counters and latency histograms for every chain client
+pub struct RpcMetrics {
+    registry: Registry,
+    calls: IntCounterVec,
+    latency: HistogramVec,
+    started_at: DateTime<Utc>,
+}
+
+impl RpcMetrics {
+    pub fn new() -> Self {
+        let calls = IntCounterVec::new(
+            Opts::new(CALLS_METRIC, "Chain RPC calls by outcome"),
+            &["chain", "provider", "method", "outcome"],
+        )
+        .expect("valid counter");
+        let latency = HistogramVec::new(
+            HistogramOpts::new(LATENCY_METRIC, "Chain RPC call latency")
+                .buckets(LATENCY_BUCKETS.to_vec()),
+            &["chain", "provider", "method"],
+        )
+        .expect("valid histogram");
+
+        let registry = Registry::new();
+        registry
+            .register(Box::new(calls.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(latency.clone()))
+            .expect("metric registered once");
+
+        Self {
+            registry,
+            calls,
+            latency,
+            started_at: Utc::now(),
+        }
+    }
+
+    fn record(&self, chain: Chain, provider: &str, method: &str, elapsed: Duration, outcome: &str) {
+        let chain = chain.to_string();
+        self.calls
+            .with_label_values(&[&chain, provider, method, outcome])
+            .inc();
+        self.latency
+            .with_label_values(&[&chain, provider, method])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Every metric in the Prometheus text exposition format
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+
+    /// Totals per provider and method since startup
+    pub fn usage(&self) -> RpcUsageReport {
+        let mut providers: BTreeMap<(String, String), BTreeMap<String, MethodUsage>> =
+            BTreeMap::new();
+
+        for family in self.registry.gather() {
+            for metric in family.get_metric() {
+                let labels: BTreeMap<&str, &str> = metric
+                    .get_label()
+                    .iter()
+                    .map(|l| (l.get_name(), l.get_value()))
+                    .collect();
+                let key = (
+                    labels.get("chain").unwrap_or(&"").to_string(),
+                    labels.get("provider").unwrap_or(&"").to_string(),
+                );
+                let method = labels.get("method").unwrap_or(&"").to_string();
+                let usage = providers
+                    .entry(key)
+                    .or_default()
+                    .entry(method.clone())
+                    .or_insert_with(|| MethodUsage::new(method));
+
+                match family.get_name() {
+                    CALLS_METRIC => {
+                        let count = metric.get_counter().get_value() as u64;
+                        usage.calls += count;
+                        if labels.get("outcome") == Some(&"error") {
+                            usage.failures += count;
+                        }
+                    }
+                    LATENCY_METRIC => {
+                        let histogram = metric.get_histogram();
+                        if histogram.get_sample_count() > 0 {
+                            usage.avg_latency_ms = histogram.get_sample_sum() * 1000.0
+                                / histogram.get_sample_count() as f64;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let providers = providers
+            .into_iter()
+            .map(|((chain, provider), methods)| {
+                let methods: Vec<MethodUsage> = methods
+                    .into_values()
+                    .map(|mut m| {
+                        m.failure_rate = failure_rate(m.failures, m.calls);
+                        m
+                    })
+                    .collect();
+                let calls = methods.iter().map(|m| m.calls).sum();
+                let failures = methods.iter().map(|m| m.failures).sum();
+                ProviderUsage {
+                    chain,
+                    provider,
+                    calls,
+                    failures,
+                    failure_rate: failure_rate(failures, calls),
+                    methods,
+                }
+            })
+            .collect();
+
+        RpcUsageReport {
+            since: self.started_at.to_rfc3339(),
+            providers,
+        }
+    }
+}
+
+impl Default for RpcMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn failure_rate(failures: u64, calls: u64) -> f64 {
+    if calls == 0 {
+        0.0
+    } else {
+        failures as f64 / calls as f64
+    }
+}
+
+/// RPC usage since the server started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcUsageReport {
+    pub since: String,
+    pub providers: Vec<ProviderUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderUsage {
+    pub chain: String,
+    /// RPC host; paths and query strings, which often hold API keys, are
+    /// left out
+    pub provider: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub failure_rate: f64,
+    pub methods: Vec<MethodUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodUsage {
+    pub method: String,
+    pub calls: u64,
+    /// Calls the provider failed, as opposed to ones rejected for bad input
+    pub failures: u64,
+    pub failure_rate: f64,
+    pub avg_latency_ms: f64,
+}
+
+impl MethodUsage {
+    fn new(method: String) -> Self {
+        Self {
+            method,
+            calls: 0,
+            failures: 0,
+            failure_rate: 0.0,
+            avg_latency_ms: 0.0,
+        }
+    }
+}
+
+/// Host of an RPC URL, the label calls are accounted under
+pub fn provider_name(rpc_url: &str) -> String {
+    reqwest::Url::parse(rpc_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Chain client that records its calls in `RpcMetrics`
+pub struct MeteredClient {
+    inner: Arc<dyn ChainClient>,
+    chain: Chain,
+    provider: String,
+    metrics: Arc<RpcMetrics>,
+}
+
+impl MeteredClient {
+    pub fn wrap(
+        inner: Arc<dyn ChainClient>,
+        chain: Chain,
+        rpc_url: &str,
+        metrics: Arc<RpcMetrics>,
+    ) -> Arc<dyn ChainClient> {
+        Arc::new(Self {
+            inner,
+            chain,
+            provider: provider_name(rpc_url),
+            metrics,
+        })
+    }
+
+    async fn observe<T>(
+        &self,
+        method: &str,
+        call: impl Future<Output = Result<T, ChainClientError>>,
+    ) -> Result<T, ChainClientError> {
+        let start = Instant::now();
+        let result = call.await;
+        let outcome = match &result {
+            Ok(_) => "ok",
+            Err(ChainClientError::Rpc(_)) => "error",
+            Err(_) => "rejected",
+        };
+        self.metrics
+            .record(self.chain, &self.provider, method, start.elapsed(), outcome);
+        result
+    }
+}
+
+#[async_trait]
+impl ChainClient for MeteredClient {
+    async fn balance(&self, address: &str) -> Result<ChainBalance, ChainClientError> {
+        self.observe("balance", self.inner.balance(address)).await
+    }
+
+    async fn token_balance(
+        &self,
+        address: &str,
+        token: &str,
+    ) -> Result<ChainTokenBalance, ChainClientError> {
+        self.observe("token_balance", self.inner.token_balance(address, token))
+            .await
+    }
+
+    async fn token_metadata(&self, token: &str) -> Result<TokenMetadata, ChainClientError> {
+        self.observe("token_metadata", self.inner.token_metadata(token))
+            .await
+    }
+
+    async fn max_send(
+        &self,
+        address: &str,
+        token: Option<&str>,
+    ) -> Result<MaxSend, ChainClientError> {
+        self.observe("max_send", self.inner.max_send(address, token))
+            .await
+    }
+
+    async fn resolve_identity(&self, address: &str) -> Result<Option<Identity>, ChainClientError> {
+        self.observe("resolve_identity", self.inner.resolve_identity(address))
+            .await
+    }
+
+    async fn nft_holder(
+        &self,
+        token_address: &str,
+        token_id: &str,
+    ) -> Result<NftHolder, ChainClientError> {
+        self.observe("nft_holder", self.inner.nft_holder(token_address, token_id))
+            .await
+    }
+
+    async fn nft_metadata(
+        &self,
+        token_address: &str,
+        token_id: &str,
+    ) -> Result<NftMetadata, ChainClientError> {
+        self.observe("nft_metadata", self.inner.nft_metadata(token_address, token_id))
+            .await
+    }
+
+    async fn send(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transfer: Transfer,
+    ) -> Result<SentTransfer, ChainClientError> {
+        self.observe("send", self.inner.send(seed, derivation_path, transfer))
+            .await
+    }
+
+    async fn broadcast_raw(&self, raw_tx: &str) -> Result<String, ChainClientError> {
+        self.observe("broadcast_raw", self.inner.broadcast_raw(raw_tx))
+            .await
+    }
+
+    async fn build_unsigned_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<UnsignedTransfer, ChainClientError> {
+        self.observe(
+            "build_unsigned_transfer",
+            self.inner.build_unsigned_transfer(from, to, amount),
+        )
+        .await
+    }
+
+    async fn block_height(&self) -> Result<u64, ChainClientError> {
+        self.observe("block_height", self.inner.block_height()).await
+    }
+
+    async fn has_activity(&self, address: &str) -> Result<bool, ChainClientError> {
+        self.observe("has_activity", self.inner.has_activity(address))
+            .await
+    }
+
+    async fn request_airdrop(&self, address: &str, amount: u64) -> Result<String, ChainClientError> {
+        self.observe("request_airdrop", self.inner.request_airdrop(address, amount))
+            .await
+    }
+
+    async fn transaction_effects(
+        &self,
+        tx_hash: &str,
+        addresses: &[String],
+    ) -> Result<Option<ConfirmedEffects>, ChainClientError> {
+        self.observe(
+            "transaction_effects",
+            self.inner.transaction_effects(tx_hash, addresses),
+        )
+        .await
+    }
+
+    async fn find_by_reference(
+        &self,
+        reference: &str,
+        limit: usize,
+    ) -> Result<Vec<ReferencedTransaction>, ChainClientError> {
+        self.observe(
+            "find_by_reference",
+            self.inner.find_by_reference(reference, limit),
+        )
+        .await
+    }
+
+    async fn history(&self, address: &str, limit: usize) -> Result<Vec<HistoryEntry>, ChainClientError> {
+        self.observe("history", self.inner.history(address, limit)).await
+    }
+
+    async fn multisig_deployment_cost(
+        &self,
+        owners: &[String],
+        threshold: u8,
+    ) -> Result<DeploymentCost, ChainClientError> {
+        self.observe(
+            "multisig_deployment_cost",
+            self.inner.multisig_deployment_cost(owners, threshold),
+        )
+        .await
+    }
+
+    async fn create_multisig(
+        &self,
+        seed: &SecureSeed,
+        name: &str,
+        threshold: u8,
+        owners: &[String],
+    ) -> Result<String, ChainClientError> {
+        self.observe(
+            "create_multisig",
+            self.inner.create_multisig(seed, name, threshold, owners),
+        )
+        .await
+    }
+
+    async fn stake_pool(&self, pool: &str) -> Result<StakePoolState, ChainClientError> {
+        self.observe("stake_pool", self.inner.stake_pool(pool)).await
+    }
+
+    async fn stake_pool_deposit(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        pool: &str,
+        lamports: u64,
+    ) -> Result<String, ChainClientError> {
+        self.observe(
+            "stake_pool_deposit",
+            self.inner.stake_pool_deposit(seed, derivation_path, pool, lamports),
+        )
+        .await
+    }
+
+    async fn stake_pool_withdraw(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        pool: &str,
+        pool_tokens: u64,
+    ) -> Result<String, ChainClientError> {
+        self.observe(
+            "stake_pool_withdraw",
+            self.inner.stake_pool_withdraw(seed, derivation_path, pool, pool_tokens),
+        )
+        .await
+    }
+
+    async fn bridge_deposit(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        network: &str,
+        amount: u128,
+    ) -> Result<BridgeDeposit, ChainClientError> {
+        self.observe(
+            "bridge_deposit",
+            self.inner.bridge_deposit(seed, derivation_path, network, amount),
+        )
+        .await
+    }
+
+    async fn send_route_transaction(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transaction: &RouteTransaction,
+    ) -> Result<RouteSubmission, ChainClientError> {
+        self.observe(
+            "send_route_transaction",
+            self.inner.send_route_transaction(seed, derivation_path, transaction),
+        )
+        .await
+    }
+
+    async fn name_quote(&self, name: &str, years: u32) -> Result<NameQuote, ChainClientError> {
+        self.observe("name_quote", self.inner.name_quote(name, years)).await
+    }
+
+    async fn register_name(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        years: u32,
+        commitment: Option<&str>,
+    ) -> Result<NameRegistration, ChainClientError> {
+        self.observe(
+            "register_name",
+            self.inner.register_name(seed, derivation_path, name, years, commitment),
+        )
+        .await
+    }
+
+    async fn set_name_target(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        target: &str,
+    ) -> Result<String, ChainClientError> {
+        self.observe(
+            "set_name_target",
+            self.inner.set_name_target(seed, derivation_path, name, target),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_name_drops_path() {
+        assert_eq!(
+            provider_name("https://eth-mainnet.g.alchemy.com/v2/secret-key"),
+            "eth-mainnet.g.alchemy.com"
+        );
+        assert_eq!(provider_name("not a url"), "unknown");
+    }
+
+    #[test]
+    fn test_usage_summary() {
+        let metrics = RpcMetrics::new();
+        let provider = "rpc.example";
+        metrics.record(Chain::Solana, provider, "balance", Duration::from_millis(100), "ok");
+        metrics.record(Chain::Solana, provider, "balance", Duration::from_millis(300), "error");
+        metrics.record(Chain::Solana, provider, "send", Duration::from_millis(50), "rejected");
+
+        let report = metrics.usage();
+        assert_eq!(report.providers.len(), 1);
+        let usage = &report.providers[0];
+        assert_eq!((usage.calls, usage.failures), (3, 1));
+
+        let balance = usage.methods.iter().find(|m| m.method == "balance").unwrap();
+        assert_eq!(balance.failure_rate, 0.5);
+        assert!((balance.avg_latency_ms - 200.0).abs() < 1.0);
+
+        assert!(metrics.encode().unwrap().contains("rpc_calls_total"));
+    }
+}
@@ -1,4 +1,12 @@
 //! Blockchain-specific implementations
 
+pub mod client;
+pub mod diagnostics;
 pub mod ethereum;
+#[cfg(test)]
+pub mod fuzz;
+pub mod metered;
+pub mod reader;
 pub mod solana;
+
+pub use client::*;
@@ -0,0 +1,198 @@
+//! Bounds-checked reads over bytes returned by RPC nodes
+//!
+//! `eth_call` results come from outside and can be any length, so decoders
+//! read them through `ByteReader` instead of slicing.
+//! Every read checks the remaining length first and returns `ReadError`
+//! rather than panicking.
+
+use thiserror::Error;
+
+/// ABI word size
+pub const WORD: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReadError {
+    #[error("Unexpected end of data: {wanted} bytes at offset {offset}, {available} available")]
+    UnexpectedEnd {
+        offset: usize,
+        wanted: usize,
+        available: usize,
+    },
+    #[error("Value at offset {0} is out of range")]
+    OutOfRange(usize),
+    #[error("Invalid UTF-8 at offset {0}")]
+    InvalidUtf8(usize),
+}
+
+/// Cursor over a byte slice
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    /// Move to an absolute position, at most the end of the data
+    pub fn seek(&mut self, offset: usize) -> Result<(), ReadError> {
+        if offset > self.data.len() {
+            return Err(ReadError::UnexpectedEnd {
+                offset: self.offset,
+                wanted: offset - self.offset,
+                available: self.remaining(),
+            });
+        }
+        self.offset = offset;
+        Ok(())
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<(), ReadError> {
+        self.bytes(len).map(|_| ())
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], ReadError> {
+        if len > self.remaining() {
+            return Err(ReadError::UnexpectedEnd {
+                offset: self.offset,
+                wanted: len,
+                available: self.remaining(),
+            });
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], ReadError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, ReadError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub fn u32_le(&mut self) -> Result<u32, ReadError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64_le(&mut self) -> Result<u64, ReadError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// One 32-byte ABI word
+    pub fn abi_word(&mut self) -> Result<[u8; WORD], ReadError> {
+        self.array()
+    }
+
+    /// ABI `uint256` used as a length or offset; values that don't fit in
+    /// a `usize` are out of range
+    pub fn abi_usize(&mut self) -> Result<usize, ReadError> {
+        let start = self.offset;
+        let word = self.abi_word()?;
+        let (high, low) = word.split_at(WORD - 8);
+        if high.iter().any(|&b| b != 0) {
+            return Err(ReadError::OutOfRange(start));
+        }
+        let mut value = [0u8; 8];
+        value.copy_from_slice(low);
+        usize::try_from(u64::from_be_bytes(value)).map_err(|_| ReadError::OutOfRange(start))
+    }
+
+    /// ABI address: the low 20 bytes of a word whose high 12 are zero
+    pub fn abi_address(&mut self) -> Result<[u8; 20], ReadError> {
+        let start = self.offset;
+        let word = self.abi_word()?;
+        if word[..12].iter().any(|&b| b != 0) {
+            return Err(ReadError::OutOfRange(start));
+        }
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&word[12..]);
+        Ok(address)
+    }
+
+    /// ABI-encoded dynamic `string` return value: an offset word pointing
+    /// at a length word followed by the bytes
+    pub fn abi_string(&mut self) -> Result<String, ReadError> {
+        let offset = self.abi_usize()?;
+        self.seek(offset)?;
+        let len = self.abi_usize()?;
+        let start = self.offset;
+        let bytes = self.bytes(len)?;
+        std::str::from_utf8(bytes)
+            .map(str::to_string)
+            .map_err(|_| ReadError::InvalidUtf8(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: u64) -> Vec<u8> {
+        let mut word = vec![0u8; WORD];
+        word[WORD - 8..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    #[test]
+    fn test_reads_are_bounds_checked() {
+        let mut reader = ByteReader::new(&[1, 2, 0, 0, 0, 9]);
+        assert_eq!(reader.u8().unwrap(), 1);
+        assert_eq!(reader.u32_le().unwrap(), 2);
+        assert_eq!(
+            reader.u64_le(),
+            Err(ReadError::UnexpectedEnd {
+                offset: 5,
+                wanted: 8,
+                available: 1
+            })
+        );
+        // A failed read doesn't move the cursor
+        assert_eq!(reader.u8().unwrap(), 9);
+        assert!(reader.skip(1).is_err());
+        assert!(reader.bytes(usize::MAX).is_err());
+        assert!(reader.seek(7).is_err());
+    }
+
+    #[test]
+    fn test_abi_string() {
+        let mut data = word(32);
+        data.extend(word(3));
+        data.extend_from_slice(b"abc");
+        data.resize(96, 0);
+        assert_eq!(ByteReader::new(&data).abi_string().unwrap(), "abc");
+
+        // Length and offset words larger than the data, or than a usize
+        let mut long = word(32);
+        long.extend(word(u64::MAX));
+        assert!(ByteReader::new(&long).abi_string().is_err());
+        let mut far = word(u64::MAX);
+        far.extend(word(0));
+        assert!(ByteReader::new(&far).abi_string().is_err());
+        let mut wide = vec![0xff; WORD];
+        wide.extend(word(0));
+        assert_eq!(ByteReader::new(&wide).abi_string(), Err(ReadError::OutOfRange(0)));
+    }
+
+    #[test]
+    fn test_abi_address() {
+        let mut data = vec![0u8; 12];
+        data.extend_from_slice(&[0xab; 20]);
+        assert_eq!(ByteReader::new(&data).abi_address().unwrap(), [0xab; 20]);
+        data[0] = 1;
+        assert_eq!(ByteReader::new(&data).abi_address(), Err(ReadError::OutOfRange(0)));
+    }
+}
@@ -2,9 +2,16 @@
 
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig, transfer_fee::TransferFeeConfig,
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
 use thiserror::Error;
 
+use crate::chains::client::{TokenExtensions, TransferFee};
+
 #[derive(Debug, Error)]
 pub enum BalanceError {
     #[error("RPC error: {0}")]
@@ -34,6 +41,114 @@ pub struct TokenBalance {
     pub ui_amount: f64,
     pub symbol: Option<String>,
     pub name: Option<String>,
+    /// Set for Token-2022 mints
+    pub extensions: Option<TokenExtensions>,
+}
+
+/// Token programs a mint can belong to
+pub fn token_programs() -> [Pubkey; 2] {
+    [spl_token::id(), spl_token_2022::id()]
+}
+
+/// What a transfer of a mint's tokens needs to know about the mint
+#[derive(Debug, Clone)]
+pub struct MintInfo {
+    /// SPL Token or Token-2022
+    pub program_id: Pubkey,
+    pub decimals: u8,
+    /// Size of a new associated token account for the mint
+    pub account_len: usize,
+    /// `None` for SPL Token mints
+    pub extensions: Option<TokenExtensions>,
+}
+
+impl MintInfo {
+    pub fn is_token_2022(&self) -> bool {
+        self.program_id == spl_token_2022::id()
+    }
+
+    /// Token-2022 fee withheld from a transfer of `amount`
+    pub fn transfer_fee(&self, amount: u64) -> u64 {
+        self.extensions
+            .as_ref()
+            .and_then(|e| e.transfer_fee)
+            .map_or(0, |fee| fee.fee_for(amount))
+    }
+}
+
+/// Decode a mint account owned by `program_id`, resolving epoch-dependent
+/// extension settings at `epoch`
+pub fn parse_mint(program_id: &Pubkey, data: &[u8], epoch: u64) -> Option<MintInfo> {
+    if *program_id == spl_token::id() {
+        let mint = spl_token::state::Mint::unpack(data).ok()?;
+        return Some(MintInfo {
+            program_id: *program_id,
+            decimals: mint.decimals,
+            account_len: spl_token::state::Account::LEN,
+            extensions: None,
+        });
+    }
+    if *program_id != spl_token_2022::id() {
+        return None;
+    }
+
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(data).ok()?;
+    let transfer_fee = mint.get_extension::<TransferFeeConfig>().ok().map(|config| {
+        let fee = config.get_epoch_fee(epoch);
+        TransferFee {
+            basis_points: fee.transfer_fee_basis_points.into(),
+            maximum_fee: fee.maximum_fee.into(),
+        }
+    });
+    let interest_rate_bps = mint
+        .get_extension::<InterestBearingConfig>()
+        .ok()
+        .map(|config| i16::from(config.current_rate));
+
+    // Associated token accounts always carry ImmutableOwner on top of what the
+    // mint's extensions require
+    let mut account_extensions = ExtensionType::get_required_init_account_extensions(
+        &mint.get_extension_types().ok()?,
+    );
+    account_extensions.push(ExtensionType::ImmutableOwner);
+    let account_len = ExtensionType::try_calculate_account_len::<
+        spl_token_2022::state::Account,
+    >(&account_extensions)
+    .ok()?;
+
+    Some(MintInfo {
+        program_id: *program_id,
+        decimals: mint.base.decimals,
+        account_len,
+        extensions: Some(TokenExtensions {
+            transfer_fee,
+            interest_rate_bps,
+        }),
+    })
+}
+
+/// Look up a mint under either token program; `InvalidAddress` if `mint` is
+/// not one
+pub fn get_mint_info(client: &RpcClient, mint: &Pubkey) -> Result<MintInfo, BalanceError> {
+    let account = client
+        .get_account_with_commitment(mint, client.commitment())
+        .map_err(|e| BalanceError::RpcError(e.to_string()))?
+        .value
+        .filter(|account| token_programs().contains(&account.owner))
+        .ok_or_else(|| BalanceError::InvalidAddress(mint.to_string()))?;
+
+    // Transfer fees are scheduled by epoch
+    let epoch = if account.owner == spl_token_2022::id() {
+        client
+            .get_epoch_info()
+            .map_err(|e| BalanceError::RpcError(e.to_string()))?
+            .epoch
+    } else {
+        0
+    };
+
+    parse_mint(&account.owner, &account.data, epoch)
+        .ok_or_else(|| BalanceError::InvalidAddress(mint.to_string()))
 }
 
 /// Get native SOL balance
@@ -65,57 +180,142 @@ pub async fn get_sol_balance_async(rpc_url: &str, address: &str) -> Result<SolBa
         .map_err(|e| BalanceError::RpcError(e.to_string()))?
 }
 
-/// Get all SPL token balances for an address
-pub fn get_token_balances(rpc_url: &str, owner: &str) -> Result<Vec<TokenBalance>, BalanceError> {
+/// Decimals of an SPL or Token-2022 mint; fails with `InvalidAddress` if `mint` is not one
+pub fn get_mint_decimals(rpc_url: &str, mint: &str) -> Result<u8, BalanceError> {
+    let client = RpcClient::new(rpc_url.to_string());
+    let mint_pubkey: Pubkey = mint
+        .parse()
+        .map_err(|_| BalanceError::InvalidAddress(mint.to_string()))?;
+
+    Ok(get_mint_info(&client, &mint_pubkey)?.decimals)
+}
+
+/// Get mint decimals (async version)
+pub async fn get_mint_decimals_async(rpc_url: &str, mint: &str) -> Result<u8, BalanceError> {
+    let rpc_url = rpc_url.to_string();
+    let mint = mint.to_string();
+
+    tokio::task::spawn_blocking(move || get_mint_decimals(&rpc_url, &mint))
+        .await
+        .map_err(|e| BalanceError::RpcError(e.to_string()))?
+}
+
+/// Balance of one SPL token; zero if the owner has no token account for it
+pub fn get_token_balance(rpc_url: &str, owner: &str, mint: &str) -> Result<TokenBalance, BalanceError> {
     let client = RpcClient::new(rpc_url.to_string());
     let owner_pubkey: Pubkey = owner
         .parse()
         .map_err(|_| BalanceError::InvalidAddress(owner.to_string()))?;
+    let mint_pubkey: Pubkey = mint
+        .parse()
+        .map_err(|_| BalanceError::InvalidAddress(mint.to_string()))?;
 
-    // Get token accounts by owner
-    let token_accounts = client
-        .get_token_accounts_by_owner(
-            &owner_pubkey,
-            solana_client::rpc_request::TokenAccountsFilter::ProgramId(spl_token::id()),
-        )
-        .map_err(|e| BalanceError::RpcError(e.to_string()))?;
+    let mint_info = get_mint_info(&client, &mint_pubkey)?;
+    let token_account = get_associated_token_address_with_program_id(
+        &owner_pubkey,
+        &mint_pubkey,
+        &mint_info.program_id,
+    );
+    let (amount, decimals, ui_amount) = match client.get_token_account_balance(&token_account) {
+        Ok(balance) => (
+            balance.amount,
+            balance.decimals,
+            balance.ui_amount.unwrap_or(0.0),
+        ),
+        Err(_) => ("0".to_string(), mint_info.decimals, 0.0),
+    };
+
+    Ok(TokenBalance {
+        mint: mint.to_string(),
+        owner: owner.to_string(),
+        token_account: token_account.to_string(),
+        amount,
+        decimals,
+        ui_amount,
+        symbol: None,
+        name: None,
+        extensions: mint_info.extensions,
+    })
+}
+
+/// Get one SPL token balance (async version)
+pub async fn get_token_balance_async(
+    rpc_url: &str,
+    owner: &str,
+    mint: &str,
+) -> Result<TokenBalance, BalanceError> {
+    let rpc_url = rpc_url.to_string();
+    let owner = owner.to_string();
+    let mint = mint.to_string();
+
+    tokio::task::spawn_blocking(move || get_token_balance(&rpc_url, &owner, &mint))
+        .await
+        .map_err(|e| BalanceError::RpcError(e.to_string()))?
+}
+
+/// Get all SPL Token and Token-2022 balances for an address
+pub fn get_token_balances(rpc_url: &str, owner: &str) -> Result<Vec<TokenBalance>, BalanceError> {
+    let client = RpcClient::new(rpc_url.to_string());
+    let owner_pubkey: Pubkey = owner
+        .parse()
+        .map_err(|_| BalanceError::InvalidAddress(owner.to_string()))?;
 
     let mut balances = Vec::new();
 
-    for account in token_accounts {
-        if let solana_account_decoder::UiAccountData::Json(parsed) = account.account.data {
-            if let Some(info) = parsed.parsed.get("info") {
-                let mint = info
-                    .get("mint")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or_default()
-                    .to_string();
-
-                let token_amount = info.get("tokenAmount").cloned().unwrap_or_default();
-                let amount = token_amount
-                    .get("amount")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("0")
-                    .to_string();
-                let decimals = token_amount
-                    .get("decimals")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u8;
-                let ui_amount = token_amount
-                    .get("uiAmount")
-                    .and_then(|v| v.as_f64())
-                    .unwrap_or(0.0);
-
-                balances.push(TokenBalance {
-                    mint,
-                    owner: owner.to_string(),
-                    token_account: account.pubkey,
-                    amount,
-                    decimals,
-                    ui_amount,
-                    symbol: None,
-                    name: None,
-                });
+    for program_id in token_programs() {
+        let token_accounts = client
+            .get_token_accounts_by_owner(
+                &owner_pubkey,
+                solana_client::rpc_request::TokenAccountsFilter::ProgramId(program_id),
+            )
+            .map_err(|e| BalanceError::RpcError(e.to_string()))?;
+
+        for account in token_accounts {
+            if let solana_account_decoder::UiAccountData::Json(parsed) = account.account.data {
+                if let Some(info) = parsed.parsed.get("info") {
+                    let mint = info
+                        .get("mint")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string();
+
+                    let token_amount = info.get("tokenAmount").cloned().unwrap_or_default();
+                    let amount = token_amount
+                        .get("amount")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("0")
+                        .to_string();
+                    let decimals = token_amount
+                        .get("decimals")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(0) as u8;
+                    let ui_amount = token_amount
+                        .get("uiAmount")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0);
+
+                    // Extension settings live on the mint, not the token account
+                    let extensions = if program_id == spl_token_2022::id() {
+                        mint.parse::<Pubkey>()
+                            .ok()
+                            .and_then(|m| get_mint_info(&client, &m).ok())
+                            .and_then(|info| info.extensions)
+                    } else {
+                        None
+                    };
+
+                    balances.push(TokenBalance {
+                        mint,
+                        owner: owner.to_string(),
+                        token_account: account.pubkey,
+                        amount,
+                        decimals,
+                        ui_amount,
+                        symbol: None,
+                        name: None,
+                        extensions,
+                    });
+                }
             }
         }
     }
@@ -0,0 +1,565 @@
+//! `ChainClient` backed by a Solana JSON-RPC endpoint
+
+use async_trait::async_trait;
+
+use crate::chains::client::{
+    BridgeDeposit, Broadcast, ChainBalance, ChainClient, ChainClientError, ChainTokenBalance,
+    ConfirmedEffects, DeploymentCost, HistoryEntry, Identity, MaxSend, NameQuote, NameRegistration, NftHolder, NftMetadata,
+    ReferencedTransaction, RouteSubmission, RouteTransaction, SentTransfer, StakePoolState,
+    TokenMetadata, Transfer, UnsignedTransfer,
+};
+use crate::core::{format_units, Chain, SecureSeed};
+
+use super::balance::{
+    get_mint_decimals_async, get_sol_balance_async, get_token_balance_async,
+    get_token_balances_async, BalanceError,
+};
+use super::cold::{build_unsigned_transfer_async, send_serialized_async};
+use super::fee::max_sendable_async;
+use super::history::get_transfer_history_async;
+use super::multisig::{create_multisig, deployment_cost, MultisigConfig};
+use super::nft::{get_nft_holder_async, get_nft_metadata_async, NftError};
+use super::simulate::get_transaction_effects_async;
+use super::stake_pool::{deposit_sol, get_stake_pool_async, withdraw_sol};
+use super::sns::{
+    domain_price_usd, get_domain_owner_async, registration_transaction, resolve_sns_identity,
+    sign_and_send, transfer_domain, SnsError,
+};
+use super::transaction::{
+    get_block_height_async, get_transaction_history_async, has_activity_async,
+    request_airdrop_async, send_sol, send_token, sign_and_send_serialized, PaymentMarkers,
+    SendAmount, Submission, TransactionError,
+};
+use super::wallet::SolanaKeypair;
+
+/// Blocks a blockhash stays valid for
+const MAX_BLOCKHASH_AGE: u64 = 150;
+
+/// Live Solana client
+pub struct SolanaClient {
+    rpc_url: String,
+    sns_api_url: String,
+    http: reqwest::Client,
+}
+
+impl SolanaClient {
+    pub fn new(rpc_url: &str, sns_api_url: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            sns_api_url: sns_api_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl ChainClient for SolanaClient {
+    async fn balance(&self, address: &str) -> Result<ChainBalance, ChainClientError> {
+        let sol_balance = get_sol_balance_async(&self.rpc_url, address).await?;
+
+        let token_balances = get_token_balances_async(&self.rpc_url, address)
+            .await
+            .unwrap_or_default();
+
+        Ok(ChainBalance {
+            native_balance: lamports_to_sol(sol_balance.lamports),
+            native_balance_raw: sol_balance.lamports.to_string(),
+            native_symbol: "SOL".to_string(),
+            tokens: token_balances
+                .into_iter()
+                .map(|t| ChainTokenBalance {
+                    address: t.mint,
+                    symbol: t.symbol,
+                    name: t.name,
+                    balance: t.amount,
+                    decimals: t.decimals,
+                    ui_amount: t.ui_amount,
+                    extensions: t.extensions,
+                })
+                .collect(),
+        })
+    }
+
+    async fn token_balance(
+        &self,
+        address: &str,
+        token: &str,
+    ) -> Result<ChainTokenBalance, ChainClientError> {
+        let balance = get_token_balance_async(&self.rpc_url, address, token).await?;
+
+        Ok(ChainTokenBalance {
+            address: balance.mint,
+            symbol: None,
+            name: None,
+            balance: balance.amount,
+            decimals: balance.decimals,
+            ui_amount: balance.ui_amount,
+            extensions: balance.extensions,
+        })
+    }
+
+    async fn token_metadata(&self, token: &str) -> Result<TokenMetadata, ChainClientError> {
+        // Symbol and name live in Metaplex metadata, which not every mint has
+        Ok(TokenMetadata {
+            decimals: get_mint_decimals_async(&self.rpc_url, token).await?,
+            symbol: None,
+            name: None,
+        })
+    }
+
+    async fn max_send(
+        &self,
+        address: &str,
+        token: Option<&str>,
+    ) -> Result<MaxSend, ChainClientError> {
+        let max = max_sendable_async(&self.rpc_url, address, token).await?;
+
+        Ok(MaxSend {
+            max_amount: max.max_amount.to_string(),
+            max_ui_amount: max.max_ui_amount,
+            fee: max.fee.to_string(),
+            fee_covered: max.fee_covered,
+        })
+    }
+
+    async fn resolve_identity(&self, address: &str) -> Result<Option<Identity>, ChainClientError> {
+        Ok(resolve_sns_identity(&self.http, &self.sns_api_url, address).await?)
+    }
+
+    async fn nft_holder(
+        &self,
+        token_address: &str,
+        _token_id: &str,
+    ) -> Result<NftHolder, ChainClientError> {
+        Ok(get_nft_holder_async(&self.rpc_url, token_address).await?)
+    }
+
+    async fn nft_metadata(
+        &self,
+        token_address: &str,
+        _token_id: &str,
+    ) -> Result<NftMetadata, ChainClientError> {
+        Ok(get_nft_metadata_async(&self.rpc_url, token_address).await?)
+    }
+
+    async fn send(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transfer: Transfer,
+    ) -> Result<SentTransfer, ChainClientError> {
+        if transfer.private_rpc.is_some() {
+            return Err(ChainClientError::TransactionFailed(
+                "private relays are only supported on ethereum".to_string(),
+            ));
+        }
+        let keypair = SolanaKeypair::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let rpc_url = self.rpc_url.clone();
+        let jito = transfer.jito.clone();
+        let dry_run = transfer.dry_run;
+        let markers = PaymentMarkers {
+            memo: transfer.memo.clone(),
+            references: transfer
+                .references
+                .iter()
+                .map(|r| r.parse().map_err(|_| ChainClientError::InvalidAddress(r.clone())))
+                .collect::<Result<_, _>>()?,
+        };
+
+        tokio::task::spawn_blocking(move || {
+            let submission = match jito {
+                _ if dry_run => Submission::DryRun,
+                Some(ref jito) => Submission::Jito(jito),
+                None => Submission::Rpc,
+            };
+            match transfer.token {
+                Some(ref mint) => {
+                    let amount = if transfer.drain_all {
+                        SendAmount::All
+                    } else {
+                        SendAmount::Exact(parse_amount(&transfer.amount)?)
+                    };
+
+                    let result = send_token(
+                        &rpc_url,
+                        &keypair,
+                        &transfer.to,
+                        mint,
+                        amount,
+                        &markers,
+                        submission,
+                    )?;
+                    Ok(SentTransfer {
+                        tx_hash: result.signature,
+                        status: result.status,
+                        amount: result.amount.to_string(),
+                        token_decimals: result.decimals.map(u32::from),
+                        expected: result.expected,
+                        broadcast: Some(broadcast(result.last_valid_height)),
+                    })
+                }
+                None => {
+                    let amount = if transfer.drain_all {
+                        SendAmount::All
+                    } else {
+                        SendAmount::Exact(parse_amount(&transfer.amount)?)
+                    };
+
+                    let result =
+                        send_sol(&rpc_url, &keypair, &transfer.to, amount, &markers, submission)?;
+                    Ok(SentTransfer {
+                        tx_hash: result.signature,
+                        status: result.status,
+                        amount: lamports_to_sol(result.amount),
+                        token_decimals: None,
+                        expected: result.expected,
+                        broadcast: Some(broadcast(result.last_valid_height)),
+                    })
+                }
+            }
+        })
+        .await
+        .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?
+    }
+
+    async fn broadcast_raw(&self, raw_tx: &str) -> Result<String, ChainClientError> {
+        Ok(send_serialized_async(&self.rpc_url, raw_tx).await?)
+    }
+
+    async fn build_unsigned_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<UnsignedTransfer, ChainClientError> {
+        let lamports = u64::try_from(amount)
+            .map_err(|_| ChainClientError::InvalidAmount(amount.to_string()))?;
+        Ok(build_unsigned_transfer_async(&self.rpc_url, from, to, lamports).await?)
+    }
+
+    async fn block_height(&self) -> Result<u64, ChainClientError> {
+        Ok(get_block_height_async(&self.rpc_url).await?)
+    }
+
+    async fn has_activity(&self, address: &str) -> Result<bool, ChainClientError> {
+        Ok(has_activity_async(&self.rpc_url, address).await?)
+    }
+
+    async fn request_airdrop(&self, address: &str, amount: u64) -> Result<String, ChainClientError> {
+        Ok(request_airdrop_async(&self.rpc_url, address, amount).await?)
+    }
+
+    async fn transaction_effects(
+        &self,
+        tx_hash: &str,
+        addresses: &[String],
+    ) -> Result<Option<ConfirmedEffects>, ChainClientError> {
+        Ok(get_transaction_effects_async(&self.rpc_url, tx_hash, addresses).await?)
+    }
+
+    async fn find_by_reference(
+        &self,
+        reference: &str,
+        limit: usize,
+    ) -> Result<Vec<ReferencedTransaction>, ChainClientError> {
+        let history = get_transaction_history_async(&self.rpc_url, reference, limit).await?;
+        Ok(history
+            .into_iter()
+            .map(|tx| ReferencedTransaction {
+                signature: tx.signature,
+                status: tx.status,
+                block_time: tx.block_time,
+                memo: tx.memo,
+            })
+            .collect())
+    }
+
+    async fn history(&self, address: &str, limit: usize) -> Result<Vec<HistoryEntry>, ChainClientError> {
+        Ok(get_transfer_history_async(&self.rpc_url, address, limit).await?)
+    }
+
+    async fn multisig_deployment_cost(
+        &self,
+        owners: &[String],
+        _threshold: u8,
+    ) -> Result<DeploymentCost, ChainClientError> {
+        let rpc_url = self.rpc_url.clone();
+        let members = owners.len();
+        let (rent, fee) = tokio::task::spawn_blocking(move || deployment_cost(&rpc_url, members))
+            .await
+            .map_err(|e| ChainClientError::Rpc(e.to_string()))?
+            .map_err(|e| ChainClientError::Rpc(e.to_string()))?;
+
+        Ok(DeploymentCost {
+            rent: rent as u128,
+            network_fee: fee as u128,
+            gas: None,
+        })
+    }
+
+    async fn create_multisig(
+        &self,
+        seed: &SecureSeed,
+        name: &str,
+        threshold: u8,
+        owners: &[String],
+    ) -> Result<String, ChainClientError> {
+        let keypair = SolanaKeypair::derive(seed, 0)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let rpc_url = self.rpc_url.clone();
+        let config = MultisigConfig {
+            threshold,
+            owners: owners.to_vec(),
+            name: name.to_string(),
+        };
+
+        let result = tokio::task::spawn_blocking(move || create_multisig(&rpc_url, &keypair, &config))
+            .await
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+
+        Ok(result.address)
+    }
+
+    async fn stake_pool(&self, pool: &str) -> Result<StakePoolState, ChainClientError> {
+        Ok(get_stake_pool_async(&self.rpc_url, pool).await?.state)
+    }
+
+    async fn stake_pool_deposit(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        pool: &str,
+        lamports: u64,
+    ) -> Result<String, ChainClientError> {
+        let keypair = SolanaKeypair::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let rpc_url = self.rpc_url.clone();
+        let pool = pool.to_string();
+
+        Ok(tokio::task::spawn_blocking(move || deposit_sol(&rpc_url, &keypair, &pool, lamports))
+            .await
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))??)
+    }
+
+    async fn stake_pool_withdraw(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        pool: &str,
+        pool_tokens: u64,
+    ) -> Result<String, ChainClientError> {
+        let keypair = SolanaKeypair::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let rpc_url = self.rpc_url.clone();
+        let pool = pool.to_string();
+
+        Ok(tokio::task::spawn_blocking(move || withdraw_sol(&rpc_url, &keypair, &pool, pool_tokens))
+            .await
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))??)
+    }
+
+    async fn bridge_deposit(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        _network: &str,
+        _amount: u128,
+    ) -> Result<BridgeDeposit, ChainClientError> {
+        Err(ChainClientError::InvalidAddress(
+            "L2 bridges are only supported on ethereum".to_string(),
+        ))
+    }
+
+    async fn send_route_transaction(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transaction: &RouteTransaction,
+    ) -> Result<RouteSubmission, ChainClientError> {
+        let RouteTransaction::Solana { transaction } = transaction else {
+            return Err(ChainClientError::InvalidAddress(
+                "route transaction is not for solana".to_string(),
+            ));
+        };
+        let keypair = SolanaKeypair::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let rpc_url = self.rpc_url.clone();
+        let transaction = transaction.clone();
+
+        let tx_hash = tokio::task::spawn_blocking(move || {
+            sign_and_send_serialized(&rpc_url, &keypair, &transaction)
+        })
+        .await
+        .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))??;
+        Ok(RouteSubmission {
+            tx_hash,
+            approval_tx_hash: None,
+        })
+    }
+
+    async fn name_quote(&self, name: &str, _years: u32) -> Result<NameQuote, ChainClientError> {
+        let domain = sns_domain(name)?;
+        let owner = get_domain_owner_async(&self.rpc_url, domain).await?;
+
+        Ok(NameQuote {
+            name: name.to_string(),
+            available: owner.is_none(),
+            address: owner.map(|owner| owner.to_string()),
+            price: domain_price_usd(domain).to_string(),
+            price_unit: "USD".to_string(),
+            years: None,
+            renewal_price: None,
+            expires_at: None,
+        })
+    }
+
+    async fn register_name(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        _years: u32,
+        _commitment: Option<&str>,
+    ) -> Result<NameRegistration, ChainClientError> {
+        let domain = sns_domain(name)?;
+        let keypair = SolanaKeypair::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let transaction =
+            registration_transaction(&self.http, &self.sns_api_url, &keypair.address(), domain)
+                .await?;
+        let rpc_url = self.rpc_url.clone();
+
+        let tx_hash = tokio::task::spawn_blocking(move || {
+            sign_and_send(&rpc_url, &keypair, transaction)
+        })
+        .await
+        .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))??;
+        Ok(NameRegistration::Registered {
+            tx_hash,
+            expires_at: None,
+        })
+    }
+
+    async fn set_name_target(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        target: &str,
+    ) -> Result<String, ChainClientError> {
+        let domain = sns_domain(name)?.to_string();
+        let keypair = SolanaKeypair::derive_path(seed, derivation_path)
+            .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))?;
+        let rpc_url = self.rpc_url.clone();
+        let target = target.to_string();
+
+        Ok(tokio::task::spawn_blocking(move || {
+            transfer_domain(&rpc_url, &keypair, &domain, &target)
+        })
+        .await
+        .map_err(|e| ChainClientError::TransactionFailed(e.to_string()))??)
+    }
+}
+
+/// A `.sol` name without its suffix
+fn sns_domain(name: &str) -> Result<&str, ChainClientError> {
+    name.strip_suffix(".sol")
+        .filter(|domain| !domain.is_empty() && !domain.contains('.'))
+        .ok_or_else(|| ChainClientError::InvalidAddress(name.to_string()))
+}
+
+fn parse_amount<T: std::str::FromStr>(amount: &str) -> Result<T, ChainClientError> {
+    amount
+        .parse()
+        .map_err(|_| ChainClientError::InvalidAmount(amount.to_string()))
+}
+
+/// Lamports in SOL, exact to the last lamport
+fn lamports_to_sol(lamports: u64) -> String {
+    format_units(lamports as u128, Chain::Solana.native_decimals())
+}
+
+/// Blockhashes are accepted for 150 blocks, so a transaction was signed that
+/// far below the last height its blockhash is valid for
+fn broadcast(last_valid_height: u64) -> Broadcast {
+    Broadcast {
+        height: last_valid_height.saturating_sub(MAX_BLOCKHASH_AGE),
+        last_valid_height: Some(last_valid_height),
+        ..Default::default()
+    }
+}
+
+impl From<SnsError> for ChainClientError {
+    fn from(e: SnsError) -> Self {
+        match e {
+            SnsError::InvalidAddress(addr) | SnsError::InvalidName(addr) => {
+                ChainClientError::InvalidAddress(addr)
+            }
+            SnsError::ApiError(_) | SnsError::RpcError(_) => ChainClientError::Rpc(e.to_string()),
+            SnsError::TransactionFailed(_) => ChainClientError::TransactionFailed(e.to_string()),
+        }
+    }
+}
+
+impl From<NftError> for ChainClientError {
+    fn from(e: NftError) -> Self {
+        match e {
+            NftError::InvalidAddress(addr) => ChainClientError::InvalidAddress(addr),
+            _ => ChainClientError::Rpc(e.to_string()),
+        }
+    }
+}
+
+impl From<BalanceError> for ChainClientError {
+    fn from(e: BalanceError) -> Self {
+        match e {
+            BalanceError::InvalidAddress(addr) => ChainClientError::InvalidAddress(addr),
+            _ => ChainClientError::Rpc(e.to_string()),
+        }
+    }
+}
+
+/// Keep user-correctable errors distinct from network failures
+impl From<TransactionError> for ChainClientError {
+    fn from(e: TransactionError) -> Self {
+        match e {
+            TransactionError::InvalidAddress(addr) => ChainClientError::InvalidAddress(addr),
+            TransactionError::InvalidAmount => {
+                ChainClientError::InvalidAmount("amount must be greater than zero".to_string())
+            }
+            TransactionError::InsufficientBalance {
+                required,
+                available,
+            }
+            | TransactionError::InsufficientFeeBalance {
+                required,
+                available,
+            } => ChainClientError::InsufficientBalance {
+                required: required as u128,
+                available: available as u128,
+            },
+            TransactionError::BelowRentExemption { .. }
+            | TransactionError::RecipientBelowRentExemption { .. } => {
+                ChainClientError::RentExemption(e.to_string())
+            }
+            TransactionError::RpcError(_) => ChainClientError::Rpc(e.to_string()),
+            TransactionError::TransactionFailed(_) => {
+                ChainClientError::TransactionFailed(e.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lamports_to_sol_keeps_every_digit() {
+        assert_eq!(lamports_to_sol(1_234_567_890_123_456_789), "1234567890.123456789");
+        assert_eq!(lamports_to_sol(1_500_000_000), "1.5");
+        assert_eq!(lamports_to_sol(0), "0");
+    }
+}
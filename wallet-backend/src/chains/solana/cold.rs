@@ -0,0 +1,162 @@
+//! Transfers signed away from the server
+//!
+//! The unsigned transaction travels to an offline signer as base64 bincode;
+//! its base58 signature over the message comes back, is checked against the
+//! fee payer and set in place before the transaction is sent.
+
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::Transaction,
+};
+
+use super::transaction::{unsigned_transfer, TransactionError};
+use crate::chains::client::UnsignedTransfer;
+
+/// Encode an unsigned transaction for export; the signer signs its message
+pub fn export_transfer(transaction: &Transaction, fee: u64) -> UnsignedTransfer {
+    let bytes = bincode::serialize(transaction).expect("transactions serialize");
+    UnsignedTransfer {
+        payload: STANDARD.encode(bytes),
+        signing_hash: None,
+        fee: fee as u128,
+    }
+}
+
+/// Build an unsigned transfer on a fresh blockhash, checking `from` can pay
+/// for it
+pub fn build_unsigned_transfer(
+    rpc_url: &str,
+    from: &str,
+    to: &str,
+    lamports: u64,
+) -> Result<UnsignedTransfer, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let blockhash = client
+        .get_latest_blockhash()
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let transaction = unsigned_transfer(from, to, lamports, blockhash)?;
+    let fee = client
+        .get_fee_for_message(&transaction.message)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    let available = client
+        .get_balance(&transaction.message.account_keys[0])
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let required = lamports.saturating_add(fee);
+    if available < required {
+        return Err(TransactionError::InsufficientBalance {
+            required,
+            available,
+        });
+    }
+
+    Ok(export_transfer(&transaction, fee))
+}
+
+/// Async version of build_unsigned_transfer
+pub async fn build_unsigned_transfer_async(
+    rpc_url: &str,
+    from: &str,
+    to: &str,
+    lamports: u64,
+) -> Result<UnsignedTransfer, TransactionError> {
+    let (rpc_url, from, to) = (rpc_url.to_string(), from.to_string(), to.to_string());
+
+    tokio::task::spawn_blocking(move || build_unsigned_transfer(&rpc_url, &from, &to, lamports))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Set the fee payer's base58 signature in an exported transaction, after
+/// checking it signs the message. Returns the signed transaction as base64.
+pub fn attach_signature(
+    payload: &str,
+    from: &str,
+    signature: &str,
+) -> Result<String, TransactionError> {
+    let bytes = STANDARD
+        .decode(payload.trim())
+        .map_err(|e| TransactionError::TransactionFailed(format!("bad transaction: {}", e)))?;
+    let mut transaction: Transaction = bincode::deserialize(&bytes)
+        .map_err(|e| TransactionError::TransactionFailed(format!("bad transaction: {}", e)))?;
+    let payer = Pubkey::from_str(from)
+        .map_err(|_| TransactionError::InvalidAddress(from.to_string()))?;
+    if transaction.message.account_keys.first() != Some(&payer) {
+        return Err(TransactionError::InvalidAddress(format!(
+            "transaction is not paid by {}",
+            from
+        )));
+    }
+
+    let signature = Signature::from_str(signature.trim())
+        .map_err(|e| TransactionError::TransactionFailed(format!("bad signature: {}", e)))?;
+    if !signature.verify(payer.as_ref(), &transaction.message_data()) {
+        return Err(TransactionError::TransactionFailed(format!(
+            "signature is not by {} over this transaction",
+            from
+        )));
+    }
+    transaction.signatures = vec![signature];
+
+    let bytes = bincode::serialize(&transaction).expect("transactions serialize");
+    Ok(STANDARD.encode(bytes))
+}
+
+/// Send a signed base64 transaction. Returns its signature.
+pub fn send_serialized(rpc_url: &str, encoded: &str) -> Result<String, TransactionError> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| TransactionError::TransactionFailed(format!("bad transaction: {}", e)))?;
+    let transaction: Transaction = bincode::deserialize(&bytes)
+        .map_err(|e| TransactionError::TransactionFailed(format!("bad transaction: {}", e)))?;
+
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    client
+        .send_transaction(&transaction)
+        .map(|signature| signature.to_string())
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))
+}
+
+/// Async version of send_serialized
+pub async fn send_serialized_async(
+    rpc_url: &str,
+    encoded: &str,
+) -> Result<String, TransactionError> {
+    let (rpc_url, encoded) = (rpc_url.to_string(), encoded.to_string());
+
+    tokio::task::spawn_blocking(move || send_serialized(&rpc_url, &encoded))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_attach_signature() {
+        let signer = Keypair::new();
+        let from = signer.pubkey().to_string();
+        let to = Pubkey::new_unique().to_string();
+        let transaction = unsigned_transfer(&from, &to, 1_000, Hash::new_unique()).unwrap();
+        let exported = export_transfer(&transaction, 5_000);
+
+        let signature = signer.sign_message(&transaction.message_data()).to_string();
+        let signed = attach_signature(&exported.payload, &from, &signature).unwrap();
+        let signed: Transaction = bincode::deserialize(&STANDARD.decode(signed).unwrap()).unwrap();
+        assert!(signed.verify().is_ok());
+
+        // Someone else's signature, or a transaction paid by another address
+        let other = Keypair::new();
+        let wrong = other.sign_message(&transaction.message_data()).to_string();
+        assert!(attach_signature(&exported.payload, &from, &wrong).is_err());
+        assert!(attach_signature(&exported.payload, &other.pubkey().to_string(), &wrong).is_err());
+        assert!(attach_signature("not base64!", &from, &signature).is_err());
+    }
+}
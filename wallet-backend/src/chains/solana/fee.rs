@@ -0,0 +1,347 @@
+//! Solana fee estimation
+//!
+//! Builds the same message a send would produce (including recipient ATA
+//! creation) and asks the cluster for its signature fee, then adds the optional
+//! priority fee (compute unit limit x price) and any rent the sender must
+//! deposit for new accounts.
+
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{message::Message, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use solana_system_interface::instruction as system_instruction;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::core::{format_units, Chain};
+
+use super::balance::get_mint_info;
+use super::transaction::{token_transfer_instructions, TransactionError};
+
+/// Compute units budgeted per instruction kind when a priority fee is set
+const CU_COMPUTE_BUDGET: u32 = 150;
+const CU_SYSTEM_TRANSFER: u32 = 150;
+const CU_CREATE_ATA: u32 = 30_000;
+const CU_TOKEN_TRANSFER: u32 = 6_500;
+
+/// Priority fee level, as a percentile of recent prioritization fees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriorityLevel {
+    /// No compute budget instructions (matches the default send path)
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl PriorityLevel {
+    fn percentile(self) -> Option<usize> {
+        match self {
+            PriorityLevel::None => None,
+            PriorityLevel::Low => Some(25),
+            PriorityLevel::Medium => Some(50),
+            PriorityLevel::High => Some(75),
+        }
+    }
+}
+
+impl std::str::FromStr for PriorityLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(PriorityLevel::None),
+            "low" => Ok(PriorityLevel::Low),
+            "medium" => Ok(PriorityLevel::Medium),
+            "high" => Ok(PriorityLevel::High),
+            _ => Err(format!("Unknown priority level: {}", s)),
+        }
+    }
+}
+
+/// Estimated cost of a transfer, in lamports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Signature fee reported by the cluster for the transfer message
+    pub base_fee: u64,
+    /// Compute unit price (micro-lamports per CU), 0 without priority
+    pub compute_unit_price: u64,
+    pub compute_unit_limit: u32,
+    pub priority_fee: u64,
+    /// Rent deposited for accounts the transfer creates
+    pub rent: u64,
+    pub creates_token_account: bool,
+    /// Lamports leaving the sender as the transferred amount (SOL transfers only)
+    pub amount: u64,
+    /// Token-2022 transfer fee withheld from the amount sent, in token base
+    /// units; not part of `total`
+    pub token_transfer_fee: u64,
+    /// Everything deducted from the sender's SOL balance
+    pub total: u64,
+}
+
+/// What is being transferred
+#[derive(Debug, Clone)]
+pub enum TransferKind {
+    Sol { amount_sol: f64 },
+    /// Amount in base units; decimals and token program come from the mint
+    Token { mint: String, amount: u64 },
+}
+
+/// Estimate the full cost of a transfer from `from` to `to`
+pub fn estimate_transfer_fee(
+    rpc_url: &str,
+    from: &str,
+    to: &str,
+    transfer: TransferKind,
+    priority: PriorityLevel,
+) -> Result<FeeEstimate, TransactionError> {
+    let client = RpcClient::new(rpc_url.to_string());
+
+    let from_pubkey = parse_pubkey(from)?;
+    let to_pubkey = parse_pubkey(to)?;
+
+    let mut token_transfer_fee = 0;
+    let (instructions, compute_unit_limit, rent, creates_token_account, amount) = match transfer
+    {
+        TransferKind::Sol { amount_sol } => {
+            let lamports = (amount_sol * LAMPORTS_PER_SOL as f64) as u64;
+            if lamports == 0 {
+                return Err(TransactionError::InvalidAmount);
+            }
+            let ix = system_instruction::transfer(&from_pubkey, &to_pubkey, lamports);
+            (vec![ix], CU_SYSTEM_TRANSFER, 0, false, lamports)
+        }
+        TransferKind::Token { mint, amount } => {
+            let mint_pubkey = parse_pubkey(&mint)?;
+            let mint_info = get_mint_info(&client, &mint_pubkey)?;
+            let to_ata = get_associated_token_address_with_program_id(
+                &to_pubkey,
+                &mint_pubkey,
+                &mint_info.program_id,
+            );
+            let create_ata = client.get_account(&to_ata).is_err();
+
+            let rent = if create_ata {
+                client
+                    .get_minimum_balance_for_rent_exemption(mint_info.account_len)
+                    .map_err(|e| TransactionError::RpcError(e.to_string()))?
+            } else {
+                0
+            };
+
+            let instructions = token_transfer_instructions(
+                &from_pubkey,
+                &to_pubkey,
+                &mint_pubkey,
+                &mint_info,
+                amount,
+                create_ata,
+            )?;
+            token_transfer_fee = mint_info.transfer_fee(amount);
+            let cu = CU_TOKEN_TRANSFER + if create_ata { CU_CREATE_ATA } else { 0 };
+            (instructions, cu, rent, create_ata, 0)
+        }
+    };
+
+    // Signature fee depends only on the transfer itself
+    let blockhash = client
+        .get_latest_blockhash()
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let message = Message::new_with_blockhash(&instructions, Some(&from_pubkey), &blockhash);
+    let base_fee = client
+        .get_fee_for_message(&message)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    let (compute_unit_price, compute_unit_limit) = match priority.percentile() {
+        Some(percentile) => {
+            let writable: Vec<Pubkey> = message
+                .account_keys
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| message.is_maybe_writable(*i, None))
+                .map(|(_, key)| *key)
+                .collect();
+            let fees: Vec<u64> = client
+                .get_recent_prioritization_fees(&writable)
+                .map_err(|e| TransactionError::RpcError(e.to_string()))?
+                .into_iter()
+                .map(|f| f.prioritization_fee)
+                .collect();
+            (
+                percentile_of(fees, percentile),
+                compute_unit_limit + 2 * CU_COMPUTE_BUDGET,
+            )
+        }
+        None => (0, compute_unit_limit),
+    };
+
+    let priority_fee = priority_fee_lamports(compute_unit_limit, compute_unit_price);
+
+    Ok(FeeEstimate {
+        base_fee,
+        compute_unit_price,
+        compute_unit_limit,
+        priority_fee,
+        rent,
+        creates_token_account,
+        amount,
+        token_transfer_fee,
+        total: base_fee + priority_fee + rent + amount,
+    })
+}
+
+/// Estimate transfer cost (async version)
+pub async fn estimate_transfer_fee_async(
+    rpc_url: &str,
+    from: &str,
+    to: &str,
+    transfer: TransferKind,
+    priority: PriorityLevel,
+) -> Result<FeeEstimate, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let from = from.to_string();
+    let to = to.to_string();
+
+    tokio::task::spawn_blocking(move || estimate_transfer_fee(&rpc_url, &from, &to, transfer, priority))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Largest amount an address can send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolMaxSend {
+    /// Base units: lamports, or raw token amount when a mint is given
+    pub max_amount: u64,
+    pub max_ui_amount: String,
+    /// Network fee in lamports
+    pub fee: u64,
+    /// Whether the SOL balance covers the fee (always true for SOL transfers)
+    pub fee_covered: bool,
+}
+
+/// Compute the "send all" amount for SOL or an SPL token.
+/// A system account may be emptied completely, so only the fee is held back.
+pub fn max_sendable(
+    rpc_url: &str,
+    address: &str,
+    mint: Option<&str>,
+) -> Result<SolMaxSend, TransactionError> {
+    let client = RpcClient::new(rpc_url.to_string());
+    let owner = parse_pubkey(address)?;
+
+    let blockhash = client
+        .get_latest_blockhash()
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let sol_balance = client
+        .get_balance(&owner)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    match mint {
+        None => {
+            // Recipient doesn't change the fee; a self-transfer has the same shape
+            let ix = system_instruction::transfer(&owner, &owner, 1);
+            let message = Message::new_with_blockhash(&[ix], Some(&owner), &blockhash);
+            let fee = client
+                .get_fee_for_message(&message)
+                .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+            let max_amount = super::transaction::max_sendable_sol(sol_balance, fee);
+            Ok(SolMaxSend {
+                max_amount,
+                max_ui_amount: format_units(max_amount as u128, Chain::Solana.native_decimals()),
+                fee,
+                fee_covered: sol_balance >= fee,
+            })
+        }
+        Some(mint) => {
+            let mint_pubkey = parse_pubkey(mint)?;
+            let mint_info = get_mint_info(&client, &mint_pubkey)?;
+            let from_ata = get_associated_token_address_with_program_id(
+                &owner,
+                &mint_pubkey,
+                &mint_info.program_id,
+            );
+            let (max_amount, max_ui_amount) = match client.get_token_account_balance(&from_ata) {
+                Ok(balance) => (balance.amount.parse().unwrap_or(0), balance.ui_amount_string),
+                // No token account means nothing to send
+                Err(_) => (0, "0".to_string()),
+            };
+
+            let instructions = token_transfer_instructions(
+                &owner,
+                &owner,
+                &mint_pubkey,
+                &mint_info,
+                max_amount,
+                false,
+            )?;
+            let message = Message::new_with_blockhash(&instructions, Some(&owner), &blockhash);
+            let fee = client
+                .get_fee_for_message(&message)
+                .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+            Ok(SolMaxSend {
+                max_amount,
+                max_ui_amount,
+                fee,
+                fee_covered: sol_balance >= fee,
+            })
+        }
+    }
+}
+
+/// Compute the "send all" amount (async version)
+pub async fn max_sendable_async(
+    rpc_url: &str,
+    address: &str,
+    mint: Option<&str>,
+) -> Result<SolMaxSend, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let address = address.to_string();
+    let mint = mint.map(str::to_string);
+
+    tokio::task::spawn_blocking(move || max_sendable(&rpc_url, &address, mint.as_deref()))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Priority fee in lamports: CU limit x CU price (micro-lamports), rounded up
+pub fn priority_fee_lamports(compute_unit_limit: u32, compute_unit_price: u64) -> u64 {
+    (compute_unit_limit as u128 * compute_unit_price as u128).div_ceil(1_000_000) as u64
+}
+
+fn percentile_of(mut values: Vec<u64>, percentile: usize) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let idx = (values.len() - 1) * percentile / 100;
+    values[idx]
+}
+
+fn parse_pubkey(address: &str) -> Result<Pubkey, TransactionError> {
+    address
+        .parse()
+        .map_err(|_| TransactionError::InvalidAddress(address.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_fee_lamports() {
+        assert_eq!(priority_fee_lamports(200_000, 0), 0);
+        assert_eq!(priority_fee_lamports(200_000, 1_000), 200);
+        // Rounds up partial lamports
+        assert_eq!(priority_fee_lamports(450, 1), 1);
+    }
+
+    #[test]
+    fn test_percentile_of() {
+        assert_eq!(percentile_of(vec![], 50), 0);
+        assert_eq!(percentile_of(vec![40, 10, 30, 20, 50], 50), 30);
+        assert_eq!(percentile_of(vec![40, 10, 30, 20, 50], 75), 40);
+    }
+}
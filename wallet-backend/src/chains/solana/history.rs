@@ -0,0 +1,426 @@
+//! Transaction history with the transfers read from each transaction
+//!
+//! Signatures come from `getSignaturesForAddress`; each transaction is then
+//! fetched with `jsonParsed` encoding, and the first SOL, SPL Token or
+//! Token-2022 transfer in or out of the address, outer or inner, gives the
+//! entry's amount, counterparty and mint. Token transfers name token accounts, which are
+//! resolved to their owners through the transaction's token balances.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction,
+    UiParsedInstruction, UiMessage, UiTransactionEncoding, UiTransactionTokenBalance,
+};
+use tokio::task::JoinSet;
+
+use crate::chains::client::HistoryEntry;
+use crate::core::format_units;
+
+use super::transaction::TransactionError;
+
+/// Transactions fetched at once while enriching a page of history
+const FETCH_CONCURRENCY: usize = 8;
+/// Lamports
+const SOL_DECIMALS: u8 = 9;
+
+/// A SOL or token transfer, between wallet addresses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTransfer {
+    pub from: String,
+    pub to: String,
+    /// Base units
+    pub amount: u128,
+    /// `None` for SOL
+    pub mint: Option<String>,
+    pub decimals: u8,
+}
+
+/// Mint, owner and decimals of a token account a transaction touched
+struct TokenAccount {
+    mint: String,
+    owner: Option<String>,
+    decimals: u8,
+}
+
+/// The first transfer in or out of `address`, or `None` when the
+/// transaction moved nothing it can be read from
+pub fn parse_transfer(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    address: &str,
+) -> Option<ParsedTransfer> {
+    let EncodedTransaction::Json(transaction) = &tx.transaction.transaction else {
+        return None;
+    };
+    let UiMessage::Parsed(message) = &transaction.message else {
+        return None;
+    };
+    let meta = tx.transaction.meta.as_ref();
+
+    let mut token_accounts = HashMap::new();
+    if let Some(meta) = meta {
+        let pre: Option<&Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.as_ref().into();
+        let post: Option<&Vec<UiTransactionTokenBalance>> = meta.post_token_balances.as_ref().into();
+        for balance in pre.into_iter().chain(post).flatten() {
+            let Some(key) = message.account_keys.get(balance.account_index as usize) else {
+                continue;
+            };
+            token_accounts.insert(
+                key.pubkey.clone(),
+                TokenAccount {
+                    mint: balance.mint.clone(),
+                    owner: Option::<&String>::from(balance.owner.as_ref()).cloned(),
+                    decimals: balance.ui_token_amount.decimals,
+                },
+            );
+        }
+    }
+
+    let inner = meta
+        .and_then(|meta| Option::<&Vec<_>>::from(meta.inner_instructions.as_ref()))
+        .into_iter()
+        .flatten()
+        .flat_map(|inner| &inner.instructions);
+    message
+        .instructions
+        .iter()
+        .chain(inner)
+        .filter_map(|instruction| match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
+                instruction_transfer(&parsed.program, &parsed.parsed, &token_accounts)
+            }
+            _ => None,
+        })
+        .find(|transfer| transfer.from == address || transfer.to == address)
+}
+
+/// The transfer a parsed System, SPL Token or Token-2022 instruction makes,
+/// if any. Token-2022 transfers with a fee report the amount sent, before
+/// the fee is withheld from the recipient.
+fn instruction_transfer(
+    program: &str,
+    parsed: &Value,
+    token_accounts: &HashMap<String, TokenAccount>,
+) -> Option<ParsedTransfer> {
+    let kind = parsed.get("type")?.as_str()?;
+    let info = parsed.get("info")?;
+    let field = |name: &str| info.get(name).and_then(Value::as_str).map(str::to_string);
+
+    match (program, kind) {
+        ("system", "transfer" | "transferWithSeed") => Some(ParsedTransfer {
+            from: field("source")?,
+            to: field("destination")?,
+            amount: info.get("lamports")?.as_u64()? as u128,
+            mint: None,
+            decimals: SOL_DECIMALS,
+        }),
+        ("spl-token", "transfer" | "transferChecked")
+        | ("spl-token-2022", "transfer" | "transferChecked" | "transferCheckedWithFee") => {
+            let source = field("source")?;
+            let destination = field("destination")?;
+            let source_account = token_accounts.get(&source);
+            let destination_account = token_accounts.get(&destination);
+            let (amount, mint, decimals) = match kind {
+                "transferChecked" | "transferCheckedWithFee" => {
+                    let token_amount = info.get("tokenAmount")?;
+                    (
+                        token_amount.get("amount")?.as_str()?.parse().ok()?,
+                        field("mint")?,
+                        token_amount.get("decimals")?.as_u64()? as u8,
+                    )
+                }
+                _ => {
+                    let account = source_account.or(destination_account)?;
+                    (field("amount")?.parse().ok()?, account.mint.clone(), account.decimals)
+                }
+            };
+            // The signing authority owns the source when its balance isn't listed
+            let from = source_account
+                .and_then(|a| a.owner.clone())
+                .or_else(|| field("authority"))
+                .or_else(|| field("multisigAuthority"))
+                .unwrap_or(source);
+            let to = destination_account
+                .and_then(|a| a.owner.clone())
+                .unwrap_or(destination);
+            Some(ParsedTransfer {
+                from,
+                to,
+                amount,
+                mint: Some(mint),
+                decimals,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn fetch_transaction(
+    rpc_url: &str,
+    signature: &str,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let signature: Signature = signature
+        .parse()
+        .map_err(|_| TransactionError::TransactionFailed(format!("bad signature {}", signature)))?;
+    client
+        .get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::JsonParsed),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .map_err(|e| TransactionError::RpcError(e.to_string()))
+}
+
+/// Recent transactions of `address`, newest first, with their transfers.
+/// An entry whose transaction can't be fetched keeps its signature, status
+/// and memo.
+pub async fn get_transfer_history_async(
+    rpc_url: &str,
+    address: &str,
+    limit: usize,
+) -> Result<Vec<HistoryEntry>, TransactionError> {
+    let pubkey: Pubkey = address
+        .parse()
+        .map_err(|_| TransactionError::InvalidAddress(address.to_string()))?;
+    let url = rpc_url.to_string();
+    let signatures = tokio::task::spawn_blocking(move || {
+        RpcClient::new(url)
+            .get_signatures_for_address_with_config(
+                &pubkey,
+                GetConfirmedSignaturesForAddress2Config {
+                    limit: Some(limit),
+                    ..Default::default()
+                },
+            )
+            .map_err(|e| TransactionError::RpcError(e.to_string()))
+    })
+    .await
+    .map_err(|e| TransactionError::RpcError(e.to_string()))??;
+
+    let mut entries: Vec<HistoryEntry> = signatures
+        .into_iter()
+        .take(limit)
+        .map(|sig| HistoryEntry {
+            status: if sig.err.is_some() { "failed" } else { "confirmed" }.to_string(),
+            hash: sig.signature,
+            block_number: Some(sig.slot),
+            block_time: sig.block_time,
+            memo: sig.memo,
+            ..Default::default()
+        })
+        .collect();
+
+    let mut fetches = JoinSet::new();
+    let mut fetched = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if fetches.len() >= FETCH_CONCURRENCY {
+            fetched.extend(fetches.join_next().await);
+        }
+        let (rpc_url, signature) = (rpc_url.to_string(), entry.hash.clone());
+        fetches.spawn_blocking(move || (index, fetch_transaction(&rpc_url, &signature)));
+    }
+    while let Some(result) = fetches.join_next().await {
+        fetched.push(result);
+    }
+
+    for result in fetched {
+        let (index, tx) = result.map_err(|e| TransactionError::RpcError(e.to_string()))?;
+        let tx = match tx {
+            Ok(tx) => tx,
+            Err(e) => {
+                tracing::warn!(signature = %entries[index].hash, "History transaction fetch failed: {}", e);
+                continue;
+            }
+        };
+        let Some(transfer) = parse_transfer(&tx, address) else {
+            continue;
+        };
+        let entry = &mut entries[index];
+        entry.amount = Some(format_units(transfer.amount, transfer.decimals as u32));
+        entry.amount_raw = Some(transfer.amount.to_string());
+        entry.from = Some(transfer.from);
+        entry.to = Some(transfer.to);
+        entry.token_address = transfer.mint;
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    const OTHER: &str = "4PBP7KDao3Coe9vbJb15SKuvZ6A3uuUQX9tPB5XNPaRf";
+    const MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn transaction(instructions: Value, token_balances: Value) -> EncodedConfirmedTransactionWithStatusMeta {
+        serde_json::from_value(serde_json::json!({
+            "slot": 100,
+            "blockTime": 1_700_000_000,
+            "transaction": {
+                "signatures": ["sig"],
+                "message": {
+                    "accountKeys": [
+                        { "pubkey": WALLET, "writable": true, "signer": true, "source": "transaction" },
+                        { "pubkey": OTHER, "writable": true, "signer": false, "source": "transaction" },
+                        { "pubkey": "SrcTokenAccount", "writable": true, "signer": false, "source": "transaction" },
+                        { "pubkey": "DstTokenAccount", "writable": true, "signer": false, "source": "transaction" },
+                    ],
+                    "recentBlockhash": "hash",
+                    "instructions": instructions,
+                },
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [0, 0, 0, 0],
+                "postBalances": [0, 0, 0, 0],
+                "innerInstructions": [],
+                "preTokenBalances": token_balances,
+                "postTokenBalances": token_balances,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn token_balance(index: u8, owner: &str) -> Value {
+        token_balance_of(index, owner, "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+    }
+
+    fn token_balance_of(index: u8, owner: &str, program_id: &str) -> Value {
+        serde_json::json!({
+            "accountIndex": index,
+            "mint": MINT,
+            "owner": owner,
+            "programId": program_id,
+            "uiTokenAmount": { "amount": "0", "decimals": 6, "uiAmount": 0.0, "uiAmountString": "0" },
+        })
+    }
+
+    #[test]
+    fn test_parse_transfers() {
+        let sol = transaction(
+            serde_json::json!([{
+                "program": "system",
+                "programId": "11111111111111111111111111111111",
+                "parsed": {
+                    "type": "transfer",
+                    "info": { "source": WALLET, "destination": OTHER, "lamports": 1_500_000_000u64 },
+                },
+                "stackHeight": null,
+            }]),
+            serde_json::json!([]),
+        );
+        let transfer = parse_transfer(&sol, WALLET).unwrap();
+        assert_eq!(transfer.to, OTHER);
+        assert_eq!(transfer.amount, 1_500_000_000);
+        assert_eq!(transfer.mint, None);
+        assert_eq!(parse_transfer(&sol, OTHER).unwrap().from, WALLET);
+        assert!(parse_transfer(&sol, MINT).is_none());
+
+        // Token accounts resolve to their owners; a plain transfer takes its
+        // mint and decimals from the token balances
+        let token = transaction(
+            serde_json::json!([{
+                "program": "spl-token",
+                "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "parsed": {
+                    "type": "transfer",
+                    "info": {
+                        "source": "SrcTokenAccount",
+                        "destination": "DstTokenAccount",
+                        "amount": "2500000",
+                        "authority": OTHER,
+                    },
+                },
+                "stackHeight": null,
+            }]),
+            serde_json::json!([token_balance(2, OTHER), token_balance(3, WALLET)]),
+        );
+        let transfer = parse_transfer(&token, WALLET).unwrap();
+        assert_eq!(transfer.from, OTHER);
+        assert_eq!(transfer.to, WALLET);
+        assert_eq!(transfer.amount, 2_500_000);
+        assert_eq!(transfer.mint.as_deref(), Some(MINT));
+        assert_eq!(transfer.decimals, 6);
+        assert_eq!(format_units(transfer.amount, transfer.decimals as u32), "2.5");
+    }
+
+    #[test]
+    fn test_parse_token_2022_transfers() {
+        const TOKEN_2022: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+        let balances = serde_json::json!([
+            token_balance_of(2, WALLET, TOKEN_2022),
+            token_balance_of(3, OTHER, TOKEN_2022),
+        ]);
+
+        // The amount sent, with the transfer fee withheld from the recipient
+        let with_fee = transaction(
+            serde_json::json!([{
+                "program": "spl-token-2022",
+                "programId": TOKEN_2022,
+                "parsed": {
+                    "type": "transferCheckedWithFee",
+                    "info": {
+                        "source": "SrcTokenAccount",
+                        "mint": MINT,
+                        "destination": "DstTokenAccount",
+                        "authority": WALLET,
+                        "tokenAmount": {
+                            "amount": "1000000",
+                            "decimals": 6,
+                            "uiAmount": 1.0,
+                            "uiAmountString": "1",
+                        },
+                        "feeAmount": {
+                            "amount": "5000",
+                            "decimals": 6,
+                            "uiAmount": 0.005,
+                            "uiAmountString": "0.005",
+                        },
+                    },
+                },
+                "stackHeight": null,
+            }]),
+            balances.clone(),
+        );
+        let transfer = parse_transfer(&with_fee, WALLET).unwrap();
+        assert_eq!(transfer.from, WALLET);
+        assert_eq!(transfer.to, OTHER);
+        assert_eq!(transfer.amount, 1_000_000);
+        assert_eq!(transfer.mint.as_deref(), Some(MINT));
+        assert_eq!(transfer.decimals, 6);
+
+        let plain = transaction(
+            serde_json::json!([{
+                "program": "spl-token-2022",
+                "programId": TOKEN_2022,
+                "parsed": {
+                    "type": "transfer",
+                    "info": {
+                        "source": "SrcTokenAccount",
+                        "destination": "DstTokenAccount",
+                        "amount": "750000",
+                        "authority": WALLET,
+                    },
+                },
+                "stackHeight": null,
+            }]),
+            balances,
+        );
+        let transfer = parse_transfer(&plain, OTHER).unwrap();
+        assert_eq!(transfer.from, WALLET);
+        assert_eq!(transfer.amount, 750_000);
+        assert_eq!(transfer.mint.as_deref(), Some(MINT));
+    }
+}
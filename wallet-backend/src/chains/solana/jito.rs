@@ -0,0 +1,131 @@
+//! Jito block-engine bundle submission
+//!
+//! A bundle is a list of transactions the block engine lands together, in
+//! order, or not at all. Sends submitted this way are followed by a separate
+//! tip transfer to one of Jito's tip accounts, so the send itself is signed
+//! and simulated exactly as it would be for a regular RPC send. If the engine
+//! rejects the bundle or it hasn't landed in time, the caller falls back to
+//! sending the same signed transaction through its RPC node; being the same
+//! transaction it can land at most once.
+
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_system_interface::instruction as system_instruction;
+use thiserror::Error;
+
+/// How long a bundle may take to land before the RPC fallback
+const BUNDLE_LAND_TIMEOUT: Duration = Duration::from_secs(20);
+const BUNDLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Error)]
+pub enum JitoError {
+    #[error("Block engine error: {0}")]
+    BlockEngine(String),
+    #[error("Bundle {0} did not land")]
+    NotLanded(String),
+}
+
+/// How a Solana send or swap reaches the network
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionMode {
+    /// Straight to the RPC node
+    #[default]
+    Rpc,
+    /// As a tipped bundle through the Jito block engine
+    Jito,
+}
+
+/// Where and with what tip to submit a bundle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JitoBundle {
+    /// Block engine bundle endpoint (`.../api/v1/bundles`)
+    pub block_engine_url: String,
+    pub tip_lamports: u64,
+}
+
+/// Submit `transaction` followed by a tip from `payer` as one bundle, and
+/// wait for it to land. `rpc` is only used to watch for the signature.
+pub fn send_bundle(
+    rpc: &RpcClient,
+    jito: &JitoBundle,
+    payer: &Keypair,
+    transaction: &VersionedTransaction,
+) -> Result<String, JitoError> {
+    let engine = RpcClient::new(jito.block_engine_url.clone());
+
+    let tip_accounts: Vec<String> = engine
+        .send(RpcRequest::Custom { method: "getTipAccounts" }, json!([]))
+        .map_err(|e| JitoError::BlockEngine(e.to_string()))?;
+    let tip_account: Pubkey = tip_accounts
+        .choose(&mut rand::thread_rng())
+        .and_then(|a| a.parse().ok())
+        .ok_or_else(|| JitoError::BlockEngine("no tip accounts".to_string()))?;
+
+    // Same blockhash as the send, so the tip can't land without it
+    let tip = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&payer.pubkey(), &tip_account, jito.tip_lamports)],
+        Some(&payer.pubkey()),
+        &[payer],
+        *transaction.message.recent_blockhash(),
+    );
+    let encoded = [
+        bincode::serialize(transaction),
+        bincode::serialize(&VersionedTransaction::from(tip)),
+    ]
+    .into_iter()
+    .map(|tx| {
+        tx.map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+            .map_err(|e| JitoError::BlockEngine(e.to_string()))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let bundle_id: String = engine
+        .send(
+            RpcRequest::Custom { method: "sendBundle" },
+            json!([encoded, { "encoding": "base64" }]),
+        )
+        .map_err(|e| JitoError::BlockEngine(e.to_string()))?;
+    tracing::debug!(bundle_id = %bundle_id, tip_account = %tip_account, "Bundle submitted");
+
+    let signature = transaction.signatures[0];
+    let started = Instant::now();
+    while started.elapsed() < BUNDLE_LAND_TIMEOUT {
+        let status = rpc
+            .get_signature_status_with_commitment(&signature, CommitmentConfig::confirmed())
+            .map_err(|e| JitoError::BlockEngine(e.to_string()))?;
+        match status {
+            Some(Ok(())) => return Ok(signature.to_string()),
+            // Bundles are dropped rather than landed with a failing transaction
+            Some(Err(e)) => return Err(JitoError::BlockEngine(e.to_string())),
+            None => std::thread::sleep(BUNDLE_POLL_INTERVAL),
+        }
+    }
+    Err(JitoError::NotLanded(bundle_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submission_mode_parsing() {
+        let parse = |json: &str| serde_json::from_str::<SubmissionMode>(json).unwrap();
+        assert_eq!(parse(r#""rpc""#), SubmissionMode::Rpc);
+        assert_eq!(parse(r#""jito""#), SubmissionMode::Jito);
+        assert!(serde_json::from_str::<SubmissionMode>(r#""bundle""#).is_err());
+        assert_eq!(SubmissionMode::default(), SubmissionMode::Rpc);
+    }
+}
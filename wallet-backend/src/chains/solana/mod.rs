@@ -1,15 +1,37 @@
 //! Solana blockchain operations
 
 pub mod balance;
+pub mod client;
+pub mod cold;
+pub mod fee;
+pub mod history;
+pub mod jito;
 pub mod multisig;
 pub mod nft;
+pub mod rent;
+pub mod sns;
+pub mod simulate;
+pub mod stake_pool;
+pub mod siws;
 pub mod swap;
 pub mod transaction;
 pub mod wallet;
+pub mod wsol;
 
 pub use balance::*;
+pub use client::*;
+pub use cold::*;
+pub use fee::*;
+pub use history::*;
+pub use jito::*;
 pub use multisig::*;
 pub use nft::*;
+pub use rent::*;
+pub use sns::*;
+pub use simulate::*;
+pub use stake_pool::*;
+pub use siws::*;
 pub use swap::*;
 pub use transaction::*;
 pub use wallet::*;
+pub use wsol::*;
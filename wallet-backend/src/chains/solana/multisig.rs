@@ -2,16 +2,15 @@
 //!
 //! Implements a PDA-based multi-sig pattern similar to Squads Protocol
 
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
-    pubkey::Pubkey,
-    system_instruction,
-    system_program,
-    transaction::Transaction,
+    commitment_config::CommitmentConfig, hash::hash, instruction::AccountMeta, message::Message,
+    pubkey::Pubkey, signature::Signature, transaction::Transaction,
 };
+use solana_system_interface::{instruction as system_instruction, program as system_program};
 use thiserror::Error;
 
 use super::wallet::SolanaKeypair;
@@ -30,6 +29,8 @@ pub enum MultisigError {
     TransactionNotFound,
     #[error("Multisig creation failed: {0}")]
     CreationFailed(String),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
 }
 
 /// Multi-sig wallet configuration
@@ -66,6 +67,31 @@ pub struct MultisigResult {
     pub transaction_id: Option<String>,
 }
 
+/// Bytes of a Squads v4 multisig account before its member list: the
+/// discriminator, create key, config authority, threshold, time lock,
+/// transaction indexes, rent collector, bump and the list's length
+const SQUADS_MULTISIG_BASE_LEN: usize = 132;
+/// Bytes per member: its key and permission mask
+const SQUADS_MEMBER_LEN: usize = 33;
+/// A Squads create is signed by the creator and the one-off create key
+const CREATE_SIGNATURES: u64 = 2;
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Size of the Squads multisig account for `members` owners
+pub fn squads_multisig_len(members: usize) -> usize {
+    SQUADS_MULTISIG_BASE_LEN + members * SQUADS_MEMBER_LEN
+}
+
+/// Rent-exempt minimum of the multisig account and the signature fees of
+/// creating it, in lamports
+pub fn deployment_cost(rpc_url: &str, members: usize) -> Result<(u64, u64), MultisigError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let rent = client
+        .get_minimum_balance_for_rent_exemption(squads_multisig_len(members))
+        .map_err(|e| MultisigError::RpcError(e.to_string()))?;
+    Ok((rent, CREATE_SIGNATURES * LAMPORTS_PER_SIGNATURE))
+}
+
 /// Derive multisig PDA address
 pub fn derive_multisig_address(owners: &[Pubkey], nonce: u8) -> Pubkey {
     // Sort owners for deterministic derivation
@@ -96,12 +122,9 @@ pub fn create_multisig(
     let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
 
     // Parse owner addresses
-    let owners: Result<Vec<Pubkey>, _> = config
-        .owners
-        .iter()
-        .map(|addr| addr.parse())
-        .collect();
-    let owners = owners.map_err(|_| MultisigError::InvalidAddress("Invalid owner address".to_string()))?;
+    let owners: Result<Vec<Pubkey>, _> = config.owners.iter().map(|addr| addr.parse()).collect();
+    let owners =
+        owners.map_err(|_| MultisigError::InvalidAddress("Invalid owner address".to_string()))?;
 
     // Derive PDA address
     let multisig_address = derive_multisig_address(&owners, 0);
@@ -192,7 +215,8 @@ pub fn execute_transaction(
     // In a real implementation, this would invoke the multi-sig program.
     // For this demo, we just execute a direct transfer (assuming the executor has authority).
 
-    let instruction = system_instruction::transfer(&executor.pubkey(), &to_pubkey, pending.amount_lamports);
+    let instruction =
+        system_instruction::transfer(&executor.pubkey(), &to_pubkey, pending.amount_lamports);
 
     let blockhash = client
         .get_latest_blockhash()
@@ -212,6 +236,63 @@ pub fn execute_transaction(
     Ok(signature.to_string())
 }
 
+/// Unsigned transfer out of the multi-sig with every owner as a required
+/// signer, so owners can sign it offline in their own wallets.
+///
+/// Approvals are tracked off-chain (see above), so the blockhash is a
+/// placeholder derived from the proposal ID rather than a live one.
+pub fn build_transfer_for_signing(
+    multisig_address: &str,
+    owners: &[String],
+    to: &str,
+    amount_lamports: u64,
+    proposal_id: &str,
+) -> Result<Transaction, MultisigError> {
+    let multisig_pubkey: Pubkey = multisig_address
+        .parse()
+        .map_err(|_| MultisigError::InvalidAddress(multisig_address.to_string()))?;
+    let to_pubkey: Pubkey = to
+        .parse()
+        .map_err(|_| MultisigError::InvalidAddress(to.to_string()))?;
+
+    let mut instruction =
+        system_instruction::transfer(&multisig_pubkey, &to_pubkey, amount_lamports);
+    for owner in owners {
+        let owner: Pubkey = owner
+            .parse()
+            .map_err(|_| MultisigError::InvalidAddress(owner.clone()))?;
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(owner, true));
+    }
+
+    let message = Message::new_with_blockhash(
+        &[instruction],
+        Some(&multisig_pubkey),
+        &hash(proposal_id.as_bytes()),
+    );
+    Ok(Transaction::new_unsigned(message))
+}
+
+/// Signer of `transaction` that produced a base58 signature over its message
+pub fn verify_owner_signature(
+    transaction: &Transaction,
+    signature: &str,
+) -> Result<String, MultisigError> {
+    let signature = Signature::from_str(signature.trim())
+        .map_err(|e| MultisigError::InvalidSignature(e.to_string()))?;
+    let message = transaction.message_data();
+    let signers = transaction.message.header.num_required_signatures as usize;
+
+    transaction.message.account_keys[..signers]
+        .iter()
+        .find(|key| signature.verify(key.as_ref(), &message))
+        .map(Pubkey::to_string)
+        .ok_or_else(|| {
+            MultisigError::InvalidSignature("not signed by a required signer".to_string())
+        })
+}
+
 /// Async version of create_multisig
 pub async fn create_multisig_async(
     rpc_url: &str,
@@ -232,3 +313,39 @@ pub async fn create_multisig_async(
     .await
     .map_err(|e| MultisigError::RpcError(e.to_string()))?
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_owner_signs_transfer_offline() {
+        let owner = Keypair::new();
+        let outsider = Keypair::new();
+        let multisig = derive_multisig_address(&[owner.pubkey()], 0);
+        let transaction = build_transfer_for_signing(
+            &multisig.to_string(),
+            &[owner.pubkey().to_string()],
+            &Pubkey::new_unique().to_string(),
+            1_000,
+            "proposal-1",
+        )
+        .unwrap();
+
+        let signature = owner.sign_message(&transaction.message_data());
+        assert_eq!(
+            verify_owner_signature(&transaction, &signature.to_string()).unwrap(),
+            owner.pubkey().to_string()
+        );
+
+        let signature = outsider.sign_message(&transaction.message_data());
+        assert!(verify_owner_signature(&transaction, &signature.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_squads_account_grows_per_member() {
+        assert_eq!(squads_multisig_len(0), 132);
+        assert_eq!(squads_multisig_len(3), 231);
+    }
+}
@@ -1,10 +1,16 @@
 //! Solana NFT operations (Metaplex)
 
+use mpl_token_metadata::{accounts::Metadata, types::UseMethod};
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
 use thiserror::Error;
 
+use crate::chains::client::{
+    NftCollectionKey, NftCreator, NftHolder, NftMetadata, NftRoyalties, NftUses,
+};
+
 #[derive(Debug, Error)]
 pub enum NftError {
     #[error("RPC error: {0}")]
@@ -29,6 +35,9 @@ pub struct SolanaNft {
     pub description: Option<String>,
     pub collection: Option<NftCollection>,
     pub attributes: Option<Vec<NftAttribute>>,
+    pub royalties: NftRoyalties,
+    /// Collection recorded on chain, as opposed to the document's name
+    pub collection_key: Option<NftCollectionKey>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +55,9 @@ pub struct NftAttribute {
 /// Metaplex metadata PDA
 const METADATA_PREFIX: &[u8] = b"metadata";
 
+/// Royalties are at most the whole sale price
+const MAX_BASIS_POINTS: u16 = 10_000;
+
 /// Get metadata PDA for a mint
 pub fn get_metadata_pda(mint: &Pubkey) -> Pubkey {
     let metadata_program_id: Pubkey = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
@@ -108,10 +120,12 @@ pub fn get_nfts_for_owner(rpc_url: &str, owner: &str) -> Result<Vec<SolanaNft>,
                                 name: metadata.name,
                                 symbol: metadata.symbol,
                                 uri: metadata.uri,
-                                image_url: metadata.image_url,
-                                description: metadata.description,
-                                collection: metadata.collection,
-                                attributes: metadata.attributes,
+                                image_url: None,
+                                description: None,
+                                collection: None,
+                                attributes: None,
+                                royalties: metadata.royalties,
+                                collection_key: metadata.collection,
                             });
                         }
                     }
@@ -133,20 +147,74 @@ pub async fn get_nfts_for_owner_async(rpc_url: &str, owner: &str) -> Result<Vec<
         .map_err(|e| NftError::RpcError(e.to_string()))?
 }
 
-/// NFT metadata response (partial)
-#[derive(Debug, Clone)]
-struct NftMetadata {
-    name: String,
-    symbol: String,
-    uri: String,
-    image_url: Option<String>,
-    description: Option<String>,
-    collection: Option<NftCollection>,
-    attributes: Option<Vec<NftAttribute>>,
+/// Current holder of an NFT mint. A closed mint or zero supply is a burn; a
+/// holder that is a program address, or a frozen account with a delegate, is
+/// a marketplace escrow.
+pub fn get_nft_holder(rpc_url: &str, mint: &str) -> Result<NftHolder, NftError> {
+    let client = RpcClient::new(rpc_url.to_string());
+    let mint_pubkey: Pubkey = mint
+        .parse()
+        .map_err(|_| NftError::InvalidAddress(mint.to_string()))?;
+
+    let mint_account = client
+        .get_account_with_commitment(&mint_pubkey, client.commitment())
+        .map_err(|e| NftError::RpcError(e.to_string()))?
+        .value;
+    let Some(mint_account) = mint_account else {
+        return Ok(NftHolder::Burned);
+    };
+    if mint_account.owner != spl_token::id() {
+        return Err(NftError::InvalidAddress(mint.to_string()));
+    }
+    let supply = Mint::unpack(&mint_account.data)
+        .map_err(|_| NftError::InvalidAddress(mint.to_string()))?
+        .supply;
+    if supply == 0 {
+        return Ok(NftHolder::Burned);
+    }
+
+    let largest = client
+        .get_token_largest_accounts(&mint_pubkey)
+        .map_err(|e| NftError::RpcError(e.to_string()))?;
+    let holding = largest
+        .into_iter()
+        .find(|a| a.amount.amount != "0")
+        .ok_or(NftError::NftNotFound)?;
+    let holding_pubkey: Pubkey = holding
+        .address
+        .parse()
+        .map_err(|_| NftError::RpcError(format!("bad token account {}", holding.address)))?;
+
+    let data = client
+        .get_account_data(&holding_pubkey)
+        .map_err(|e| NftError::RpcError(e.to_string()))?;
+    let account =
+        TokenAccount::unpack(&data).map_err(|e| NftError::MetadataError(e.to_string()))?;
+
+    // Escrowless marketplaces freeze the seller's account under their delegate
+    if account.state == AccountState::Frozen {
+        if let Some(delegate) = Option::<Pubkey>::from(account.delegate) {
+            return Ok(NftHolder::Escrow(delegate.to_string()));
+        }
+    }
+    if !account.owner.is_on_curve() {
+        return Ok(NftHolder::Escrow(account.owner.to_string()));
+    }
+    Ok(NftHolder::Address(account.owner.to_string()))
+}
+
+/// Get the holder of an NFT mint (async version)
+pub async fn get_nft_holder_async(rpc_url: &str, mint: &str) -> Result<NftHolder, NftError> {
+    let rpc_url = rpc_url.to_string();
+    let mint = mint.to_string();
+
+    tokio::task::spawn_blocking(move || get_nft_holder(&rpc_url, &mint))
+        .await
+        .map_err(|e| NftError::RpcError(e.to_string()))?
 }
 
 /// Get NFT metadata from on-chain data
-fn get_nft_metadata(rpc_url: &str, mint: &str) -> Result<NftMetadata, NftError> {
+fn get_nft_metadata(rpc_url: &str, mint: &str) -> Result<MetaplexMetadata, NftError> {
     let client = RpcClient::new(rpc_url.to_string());
 
     let mint_pubkey: Pubkey = mint
@@ -160,55 +228,97 @@ fn get_nft_metadata(rpc_url: &str, mint: &str) -> Result<NftMetadata, NftError>
         .get_account(&metadata_pda)
         .map_err(|_| NftError::NftNotFound)?;
 
-    // Parse Metaplex metadata
-    // The metadata account has a specific structure
-    let data = &account.data;
-    
-    // Basic structural check for Metaplex Data V1 (minimum size)
-    // 1 (key) + 32 (auth) + 32 (mint) + 4 (name len) + 32 (name) + 4 (sym len) + 10 (sym) + 4 (uri len) + 200 (uri)
-    // = 319 bytes
-    if data.len() < 319 {
-        return Err(NftError::MetadataError("Invalid metadata: Data too short".to_string()));
+    parse_metadata(&account.data)
+}
+
+/// Decoded Metaplex metadata account
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MetaplexMetadata {
+    name: String,
+    symbol: String,
+    uri: String,
+    royalties: NftRoyalties,
+    collection: Option<NftCollectionKey>,
+    uses: Option<NftUses>,
+}
+
+/// Decode a Metaplex metadata account. The strings are padded with NULs to
+/// their maximum lengths. Accounts written before a field was added are
+/// zero-padded there, so it reads as absent; Metaplex's decoder also drops
+/// the later fields when they don't decode.
+fn parse_metadata(data: &[u8]) -> Result<MetaplexMetadata, NftError> {
+    let metadata =
+        Metadata::safe_deserialize(data).map_err(|e| NftError::MetadataError(e.to_string()))?;
+    if metadata.seller_fee_basis_points > MAX_BASIS_POINTS {
+        return Err(NftError::MetadataError(format!(
+            "seller fee of {} basis points",
+            metadata.seller_fee_basis_points
+        )));
     }
 
-    // Skip discriminator and update authority
-    let mut offset = 1 + 32 + 32;
-
-    // Read name (32 bytes max, 4 byte length prefix)
-    // Safe slicing since we checked total length
-    let name_len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-    offset += 4;
-    let name = String::from_utf8_lossy(&data[offset..offset + name_len.min(32)])
-        .trim_end_matches('\0')
-        .to_string();
-    offset += 32;
-
-    // Read symbol (10 bytes max, 4 byte length prefix)
-    let symbol_len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-    offset += 4;
-    let symbol = String::from_utf8_lossy(&data[offset..offset + symbol_len.min(10)])
-        .trim_end_matches('\0')
-        .to_string();
-    offset += 10;
-
-    // Read URI (200 bytes max, 4 byte length prefix)
-    let uri_len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-    offset += 4;
-    let uri = String::from_utf8_lossy(&data[offset..offset + uri_len.min(200)])
-        .trim_end_matches('\0')
-        .to_string();
-
-    Ok(NftMetadata {
-        name,
-        symbol,
-        uri,
-        image_url: None,
-        description: None,
-        collection: None,
-        attributes: None,
+    let trim = |s: String| s.trim_end_matches('\0').to_string();
+    let creators = metadata
+        .creators
+        .unwrap_or_default()
+        .into_iter()
+        .map(|creator| NftCreator {
+            address: creator.address.to_string(),
+            verified: creator.verified,
+            share: creator.share,
+        })
+        .collect();
+    let collection = metadata.collection.map(|collection| NftCollectionKey {
+        address: collection.key.to_string(),
+        verified: collection.verified,
+    });
+    let uses = metadata.uses.map(|uses| NftUses {
+        method: match uses.use_method {
+            UseMethod::Burn => "burn",
+            UseMethod::Multiple => "multiple",
+            UseMethod::Single => "single",
+        }
+        .to_string(),
+        remaining: uses.remaining,
+        total: uses.total,
+    });
+
+    Ok(MetaplexMetadata {
+        name: trim(metadata.name),
+        symbol: trim(metadata.symbol),
+        uri: trim(metadata.uri),
+        royalties: NftRoyalties {
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators,
+        },
+        collection,
+        uses,
     })
 }
 
+/// Current metadata of a mint: the URI in its Metaplex account and the
+/// document it points to, falling back to the on-chain name
+pub async fn get_nft_metadata_async(rpc_url: &str, mint: &str) -> Result<NftMetadata, NftError> {
+    let rpc_url = rpc_url.to_string();
+    let mint = mint.to_string();
+
+    let on_chain = tokio::task::spawn_blocking(move || get_nft_metadata(&rpc_url, &mint))
+        .await
+        .map_err(|e| NftError::RpcError(e.to_string()))??;
+    let document = match on_chain.uri.as_str() {
+        "" => None,
+        uri => Some(fetch_off_chain_metadata(uri).await?),
+    };
+
+    let mut metadata = NftMetadata::from_document(on_chain.uri, document);
+    if metadata.name.is_none() && !on_chain.name.is_empty() {
+        metadata.name = Some(on_chain.name);
+    }
+    metadata.royalties = Some(on_chain.royalties);
+    metadata.collection = on_chain.collection;
+    metadata.uses = on_chain.uses;
+    Ok(metadata)
+}
+
 /// Fetch off-chain metadata from URI
 pub async fn fetch_off_chain_metadata(uri: &str) -> Result<serde_json::Value, NftError> {
     // Handle IPFS URIs
@@ -229,3 +339,162 @@ pub async fn fetch_off_chain_metadata(uri: &str) -> Result<serde_json::Value, Nf
 
     Ok(metadata)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::fuzz::no_panic;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// `Key::MetadataV1`, the discriminator of every metadata account version
+    const METADATA_KEY: u8 = 4;
+
+    /// Maximum Metaplex name, symbol and URI lengths
+    const MAX_NAME_LEN: usize = 32;
+    const MAX_SYMBOL_LEN: usize = 10;
+    const MAX_URI_LEN: usize = 200;
+
+    /// Size metadata accounts are allocated at
+    const METADATA_ACCOUNT_LEN: usize = 679;
+
+    /// A metadata account as first written, before any trailing fields
+    fn metadata_account(name: &str, symbol: &str, uri: &str) -> Vec<u8> {
+        let mut data = vec![METADATA_KEY];
+        data.extend_from_slice(&[1u8; 64]);
+        for (value, max) in [(name, MAX_NAME_LEN), (symbol, MAX_SYMBOL_LEN), (uri, MAX_URI_LEN)] {
+            let mut padded = value.as_bytes().to_vec();
+            padded.resize(max, 0);
+            data.extend_from_slice(&(max as u32).to_le_bytes());
+            data.extend_from_slice(&padded);
+        }
+        // 5% royalties to two creators, only the first verified
+        data.extend_from_slice(&500u16.to_le_bytes());
+        data.extend_from_slice(&[1, 2, 0, 0, 0]);
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&[1, 60]);
+        data.extend_from_slice(&[8u8; 32]);
+        data.extend_from_slice(&[0, 40]);
+        // Primary sale happened, mutable
+        data.extend_from_slice(&[1, 1]);
+        data
+    }
+
+    /// The same account with every field of the current version
+    fn latest_metadata_account() -> Vec<u8> {
+        let mut data = metadata_account("Degen Ape #1", "DAPE", "https://arweave.net/ape");
+        // Edition nonce and token standard
+        data.extend_from_slice(&[1, 255, 1, 0]);
+        // Verified collection
+        data.extend_from_slice(&[1, 1]);
+        data.extend_from_slice(&[9u8; 32]);
+        // Ten uses of a multiple-use token, three left
+        data.extend_from_slice(&[1, 1]);
+        data.extend_from_slice(&3u64.to_le_bytes());
+        data.extend_from_slice(&10u64.to_le_bytes());
+        // Collection details and programmable config
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let data = metadata_account("Degen Ape #1", "DAPE", "https://arweave.net/ape");
+        // First-version accounts are zero-padded to their full size
+        let mut padded = data.clone();
+        padded.resize(METADATA_ACCOUNT_LEN, 0);
+        let metadata = parse_metadata(&padded).unwrap();
+        assert_eq!(metadata.name, "Degen Ape #1");
+        assert_eq!(metadata.symbol, "DAPE");
+        assert_eq!(metadata.uri, "https://arweave.net/ape");
+        assert_eq!(metadata.royalties.seller_fee_basis_points, 500);
+        assert_eq!(
+            metadata.royalties.creators,
+            vec![
+                NftCreator {
+                    address: Pubkey::new_from_array([7; 32]).to_string(),
+                    verified: true,
+                    share: 60,
+                },
+                NftCreator {
+                    address: Pubkey::new_from_array([8; 32]).to_string(),
+                    verified: false,
+                    share: 40,
+                },
+            ]
+        );
+        assert_eq!(metadata.collection, None);
+        assert_eq!(metadata.uses, None);
+
+        // Cut off anywhere before the end of the first version's fields
+        for len in 0..=data.len() {
+            assert!(parse_metadata(&data[..len]).is_err(), "{}", len);
+        }
+
+        // A name length past the data is rejected rather than trusted
+        let mut long = padded.clone();
+        long[65..69].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_metadata(&long).is_err());
+
+        // So are royalties over the whole price and other account types
+        let fee = 1 + 64 + 4 + MAX_NAME_LEN + 4 + MAX_SYMBOL_LEN + 4 + MAX_URI_LEN;
+        let mut greedy = padded.clone();
+        greedy[fee..fee + 2].copy_from_slice(&10_001u16.to_le_bytes());
+        assert!(parse_metadata(&greedy).is_err());
+        let mut edition = padded;
+        edition[0] = 6;
+        assert!(parse_metadata(&edition).is_err());
+    }
+
+    #[test]
+    fn test_parse_latest_metadata() {
+        let metadata = parse_metadata(&latest_metadata_account()).unwrap();
+        assert_eq!(metadata.royalties.creators.len(), 2);
+        assert_eq!(
+            metadata.collection,
+            Some(NftCollectionKey {
+                address: Pubkey::new_from_array([9; 32]).to_string(),
+                verified: true,
+            })
+        );
+        assert_eq!(
+            metadata.uses,
+            Some(NftUses {
+                method: "multiple".to_string(),
+                remaining: 3,
+                total: 10,
+            })
+        );
+
+        // A later field cut short is dropped with the fields beside it
+        let data = latest_metadata_account();
+        let metadata = parse_metadata(&data[..data.len() - 10]).unwrap();
+        assert_eq!(metadata.name, "Degen Ape #1");
+        assert_eq!(metadata.collection, None);
+        assert_eq!(metadata.uses, None);
+    }
+
+    #[test]
+    fn test_fuzz_parse_metadata() {
+        // Fixed so a failure reproduces
+        let mut rng = StdRng::seed_from_u64(0);
+        let valid = latest_metadata_account();
+
+        for _ in 0..10_000 {
+            let len = rng.gen_range(0..512);
+            let data: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            no_panic(&data, |data| {
+                let _ = parse_metadata(data);
+            });
+
+            let mut mutated = valid.clone();
+            for _ in 0..rng.gen_range(1..8) {
+                let i = rng.gen_range(0..mutated.len());
+                mutated[i] = rng.gen();
+            }
+            mutated.truncate(rng.gen_range(0..=mutated.len()));
+            no_panic(&mutated, |mutated| {
+                let _ = parse_metadata(mutated);
+            });
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! Rent held by a Solana address's accounts
+//!
+//! Every Solana account holds a rent-exempt reserve sized to its data. For
+//! an address this report covers its token accounts under both token
+//! programs, the stake accounts it can withdraw from and the durable nonce
+//! accounts it controls. Token accounts holding no tokens can be closed to
+//! get their reserve back; they are closed in batches, as few transactions
+//! as fit.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::{UiAccountData, UiAccountEncoding};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::Instruction, program_pack::Pack,
+    pubkey::Pubkey, transaction::Transaction,
+};
+
+use super::balance::token_programs;
+use super::transaction::TransactionError;
+use super::wallet::SolanaKeypair;
+
+/// Close instructions per transaction, comfortably inside the size limit
+pub const MAX_CLOSES_PER_TRANSACTION: usize = 20;
+
+/// Stake account layout: `Meta.rent_exempt_reserve` and the withdraw authority
+const STAKE_RESERVE_OFFSET: usize = 4;
+const STAKE_WITHDRAWER_OFFSET: usize = 44;
+
+/// Nonce account layout: the authority follows the version and state tags
+const NONCE_AUTHORITY_OFFSET: usize = 8;
+const NONCE_ACCOUNT_LEN: usize = 80;
+
+/// Nonce accounts belong to the system program
+const SYSTEM_PROGRAM: Pubkey = Pubkey::new_from_array([0; 32]);
+
+/// What kind of account holds the rent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RentAccountKind {
+    Token,
+    Stake,
+    Nonce,
+}
+
+/// One account and the rent it holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RentAccount {
+    pub address: String,
+    pub kind: RentAccountKind,
+    /// Program that owns the account
+    pub program: String,
+    /// Token accounts: the mint
+    pub mint: Option<String>,
+    /// Token accounts: raw token amount
+    pub token_amount: Option<String>,
+    pub lamports: u64,
+    /// Lamports locked as the rent-exempt reserve
+    pub rent_exempt_reserve: u64,
+    /// Empty token account the address can close to reclaim the reserve
+    pub reclaimable: bool,
+}
+
+/// Rent held by an address's accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RentReport {
+    pub address: String,
+    pub accounts: Vec<RentAccount>,
+    /// Sum of the reserves, by kind
+    pub token_reserves: u64,
+    pub stake_reserves: u64,
+    pub nonce_reserves: u64,
+    pub total_reserves: u64,
+    /// Lamports closing the reclaimable accounts would return
+    pub reclaimable_lamports: u64,
+    pub reclaimable_accounts: usize,
+}
+
+impl RentReport {
+    fn new(address: String, accounts: Vec<RentAccount>) -> Self {
+        let reserves = |kind| {
+            accounts
+                .iter()
+                .filter(|a| a.kind == kind)
+                .map(|a| a.rent_exempt_reserve)
+                .sum::<u64>()
+        };
+        let reclaimable = accounts.iter().filter(|a| a.reclaimable);
+        Self {
+            token_reserves: reserves(RentAccountKind::Token),
+            stake_reserves: reserves(RentAccountKind::Stake),
+            nonce_reserves: reserves(RentAccountKind::Nonce),
+            total_reserves: accounts.iter().map(|a| a.rent_exempt_reserve).sum(),
+            reclaimable_lamports: reclaimable.clone().map(|a| a.lamports).sum(),
+            reclaimable_accounts: reclaimable.count(),
+            address,
+            accounts,
+        }
+    }
+}
+
+/// Result of closing an address's empty token accounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReclaimResult {
+    /// One signature per batch, in order
+    pub signatures: Vec<String>,
+    pub closed: Vec<String>,
+    /// Lamports returned to the owner
+    pub lamports: u64,
+}
+
+/// Report the rent held by `owner`'s token, stake and nonce accounts
+pub fn rent_report(rpc_url: &str, owner: &str) -> Result<RentReport, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let owner_pubkey: Pubkey = owner
+        .parse()
+        .map_err(|_| TransactionError::InvalidAddress(owner.to_string()))?;
+
+    let mut accounts = token_accounts(&client, &owner_pubkey)?;
+    accounts.extend(stake_accounts(&client, &owner_pubkey)?);
+    accounts.extend(nonce_accounts(&client, &owner_pubkey)?);
+
+    Ok(RentReport::new(owner.to_string(), accounts))
+}
+
+/// Close every empty token account the keypair owns, in batches of
+/// `MAX_CLOSES_PER_TRANSACTION`; `None` if there is nothing to close
+pub fn reclaim_rent(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+) -> Result<Option<ReclaimResult>, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let owner = keypair.pubkey();
+
+    let closable: Vec<RentAccount> = token_accounts(&client, &owner)?
+        .into_iter()
+        .filter(|a| a.reclaimable)
+        .collect();
+    if closable.is_empty() {
+        return Ok(None);
+    }
+
+    let mut result = ReclaimResult {
+        signatures: Vec::new(),
+        closed: Vec::new(),
+        lamports: 0,
+    };
+    for batch in closable.chunks(MAX_CLOSES_PER_TRANSACTION) {
+        let instructions = close_instructions(&owner, batch)?;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&owner),
+            &[keypair.keypair()],
+            client
+                .get_latest_blockhash()
+                .map_err(|e| TransactionError::RpcError(e.to_string()))?,
+        );
+        let signature = client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+
+        result.signatures.push(signature.to_string());
+        result.closed.extend(batch.iter().map(|a| a.address.clone()));
+        result.lamports += batch.iter().map(|a| a.lamports).sum::<u64>();
+    }
+
+    Ok(Some(result))
+}
+
+/// Instructions closing each token account back into `owner`
+pub fn close_instructions(
+    owner: &Pubkey,
+    accounts: &[RentAccount],
+) -> Result<Vec<Instruction>, TransactionError> {
+    accounts
+        .iter()
+        .map(|account| {
+            let address: Pubkey = account
+                .address
+                .parse()
+                .map_err(|_| TransactionError::InvalidAddress(account.address.clone()))?;
+            let program: Pubkey = account
+                .program
+                .parse()
+                .map_err(|_| TransactionError::InvalidAddress(account.program.clone()))?;
+            let instruction = if program == spl_token_2022::id() {
+                spl_token_2022::instruction::close_account(&program, &address, owner, owner, &[])
+            } else {
+                spl_token::instruction::close_account(&program, &address, owner, owner, &[])
+            };
+            instruction.map_err(|e| TransactionError::TransactionFailed(e.to_string()))
+        })
+        .collect()
+}
+
+/// Token accounts under both programs. Reclaimable ones hold no tokens, are
+/// not frozen, can be closed by the owner and, under Token-2022, have no
+/// withheld transfer fees.
+fn token_accounts(client: &RpcClient, owner: &Pubkey) -> Result<Vec<RentAccount>, TransactionError> {
+    let mut reserves = HashMap::new();
+    let mut accounts = Vec::new();
+
+    for program in token_programs() {
+        let keyed = client
+            .get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(program))
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+        for account in keyed {
+            let UiAccountData::Json(parsed) = &account.account.data else {
+                continue;
+            };
+            let Some(info) = parsed.parsed.get("info") else {
+                continue;
+            };
+            let space = account.account.space.unwrap_or(spl_token::state::Account::LEN as u64);
+            let reserve = match reserves.get(&space) {
+                Some(reserve) => *reserve,
+                None => {
+                    let reserve = client
+                        .get_minimum_balance_for_rent_exemption(space as usize)
+                        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+                    reserves.insert(space, reserve);
+                    reserve
+                }
+            };
+
+            let token_amount = info["tokenAmount"]["amount"].as_str().map(str::to_string);
+            accounts.push(RentAccount {
+                address: account.pubkey.clone(),
+                kind: RentAccountKind::Token,
+                program: program.to_string(),
+                mint: info["mint"].as_str().map(str::to_string),
+                reclaimable: is_closable(info, &owner.to_string()),
+                token_amount,
+                lamports: account.account.lamports,
+                rent_exempt_reserve: reserve.min(account.account.lamports),
+            });
+        }
+    }
+
+    Ok(accounts)
+}
+
+/// Whether a parsed token account can be closed by `owner` right now
+fn is_closable(info: &serde_json::Value, owner: &str) -> bool {
+    let empty = info["tokenAmount"]["amount"].as_str() == Some("0");
+    let initialized = info["state"].as_str() == Some("initialized");
+    let authority = match info["closeAuthority"].as_str() {
+        Some(authority) => authority == owner,
+        None => true,
+    };
+    let fees_withheld = info["extensions"].as_array().is_some_and(|extensions| {
+        extensions.iter().any(|e| {
+            e["extension"] == "transferFeeAmount"
+                && e["state"]["withheldAmount"].as_u64().unwrap_or(0) > 0
+        })
+    });
+    empty && initialized && authority && !fees_withheld
+}
+
+/// Stake accounts `owner` may withdraw from; their reserve is recorded in
+/// the account itself
+fn stake_accounts(client: &RpcClient, owner: &Pubkey) -> Result<Vec<RentAccount>, TransactionError> {
+    let program = solana_sdk::stake::program::id();
+    let accounts = program_accounts(client, &program, None, STAKE_WITHDRAWER_OFFSET, owner)?;
+
+    Ok(accounts
+        .into_iter()
+        .map(|(address, account)| RentAccount {
+            address: address.to_string(),
+            kind: RentAccountKind::Stake,
+            program: program.to_string(),
+            mint: None,
+            token_amount: None,
+            lamports: account.lamports,
+            rent_exempt_reserve: stake_reserve(&account.data).unwrap_or(0),
+            reclaimable: false,
+        })
+        .collect())
+}
+
+/// `Meta.rent_exempt_reserve` of an initialized or delegated stake account
+fn stake_reserve(data: &[u8]) -> Option<u64> {
+    let tag = u32::from_le_bytes(data.get(0..4)?.try_into().ok()?);
+    if !matches!(tag, 1 | 2) {
+        return None;
+    }
+    let reserve = data.get(STAKE_RESERVE_OFFSET..STAKE_RESERVE_OFFSET + 8)?;
+    Some(u64::from_le_bytes(reserve.try_into().ok()?))
+}
+
+/// Durable nonce accounts `owner` is the authority of
+fn nonce_accounts(client: &RpcClient, owner: &Pubkey) -> Result<Vec<RentAccount>, TransactionError> {
+    let accounts = program_accounts(
+        client,
+        &SYSTEM_PROGRAM,
+        Some(NONCE_ACCOUNT_LEN as u64),
+        NONCE_AUTHORITY_OFFSET,
+        owner,
+    )?;
+    let reserve = client
+        .get_minimum_balance_for_rent_exemption(NONCE_ACCOUNT_LEN)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    Ok(accounts
+        .into_iter()
+        .map(|(address, account)| RentAccount {
+            address: address.to_string(),
+            kind: RentAccountKind::Nonce,
+            program: SYSTEM_PROGRAM.to_string(),
+            mint: None,
+            token_amount: None,
+            lamports: account.lamports,
+            rent_exempt_reserve: reserve.min(account.lamports),
+            reclaimable: false,
+        })
+        .collect())
+}
+
+/// Accounts of `program` with `owner` at `offset`, optionally of one size
+fn program_accounts(
+    client: &RpcClient,
+    program: &Pubkey,
+    size: Option<u64>,
+    offset: usize,
+    owner: &Pubkey,
+) -> Result<Vec<(Pubkey, solana_sdk::account::Account)>, TransactionError> {
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+        offset,
+        owner.as_ref(),
+    ))];
+    if let Some(size) = size {
+        filters.push(RpcFilterType::DataSize(size));
+    }
+
+    client
+        .get_program_accounts_with_config(
+            program,
+            RpcProgramAccountsConfig {
+                filters: Some(filters),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .map_err(|e| TransactionError::RpcError(e.to_string()))
+}
+
+/// Rent report (async version)
+pub async fn rent_report_async(rpc_url: &str, owner: &str) -> Result<RentReport, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let owner = owner.to_string();
+
+    tokio::task::spawn_blocking(move || rent_report(&rpc_url, &owner))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Reclaim rent (async version)
+pub async fn reclaim_rent_async(
+    rpc_url: &str,
+    keypair: SolanaKeypair,
+) -> Result<Option<ReclaimResult>, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+
+    tokio::task::spawn_blocking(move || reclaim_rent(&rpc_url, &keypair))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_account(program: Pubkey, lamports: u64) -> RentAccount {
+        RentAccount {
+            address: Pubkey::new_unique().to_string(),
+            kind: RentAccountKind::Token,
+            program: program.to_string(),
+            mint: Some(Pubkey::new_unique().to_string()),
+            token_amount: Some("0".to_string()),
+            lamports,
+            rent_exempt_reserve: lamports,
+            reclaimable: true,
+        }
+    }
+
+    #[test]
+    fn test_rent_report_and_close_instructions() {
+        let owner = Pubkey::new_unique();
+        let owner_str = owner.to_string();
+        let info = serde_json::json!({
+            "tokenAmount": { "amount": "0" },
+            "state": "initialized",
+        });
+        assert!(is_closable(&info, &owner_str));
+        let frozen = serde_json::json!({ "tokenAmount": { "amount": "0" }, "state": "frozen" });
+        assert!(!is_closable(&frozen, &owner_str));
+        let withheld = serde_json::json!({
+            "tokenAmount": { "amount": "0" },
+            "state": "initialized",
+            "extensions": [{ "extension": "transferFeeAmount", "state": { "withheldAmount": 5 } }],
+        });
+        assert!(!is_closable(&withheld, &owner_str));
+        let delegated_close = serde_json::json!({
+            "tokenAmount": { "amount": "0" },
+            "state": "initialized",
+            "closeAuthority": Pubkey::new_unique().to_string(),
+        });
+        assert!(!is_closable(&delegated_close, &owner_str));
+
+        let mut stake = vec![0u8; 200];
+        stake[0] = 2;
+        stake[STAKE_RESERVE_OFFSET..STAKE_RESERVE_OFFSET + 8]
+            .copy_from_slice(&2_282_880u64.to_le_bytes());
+        assert_eq!(stake_reserve(&stake), Some(2_282_880));
+        assert_eq!(stake_reserve(&[0u8; 200]), None);
+
+        let accounts = vec![
+            token_account(spl_token::id(), 2_039_280),
+            token_account(spl_token_2022::id(), 2_074_080),
+        ];
+        let report = RentReport::new(owner_str, accounts.clone());
+        assert_eq!(report.token_reserves, 4_113_360);
+        assert_eq!(report.reclaimable_accounts, 2);
+        assert_eq!(report.reclaimable_lamports, 4_113_360);
+
+        let instructions = close_instructions(&owner, &accounts).unwrap();
+        assert_eq!(instructions[0].program_id, spl_token::id());
+        assert_eq!(instructions[1].program_id, spl_token_2022::id());
+        // Rent goes back to the owner, who signs as the authority
+        assert!(instructions.iter().all(|ix| ix.accounts[1].pubkey == owner));
+        assert!(instructions.iter().all(|ix| ix.accounts[2].is_signer));
+    }
+}
@@ -0,0 +1,306 @@
+//! Transaction effects
+//!
+//! A signed transaction is simulated before broadcast to anticipate how it
+//! moves the balances of the accounts it touches. Once it lands, the same
+//! changes are read back from its status metadata so the two can be compared.
+
+use std::collections::{BTreeMap, HashSet};
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::Transaction,
+};
+use solana_transaction_status_client_types::{
+    EncodedTransaction, UiMessage, UiTransactionEncoding, UiTransactionTokenBalance,
+};
+use spl_token_2022::extension::StateWithExtensions;
+
+use crate::chains::client::{BalanceChange, ConfirmedEffects, TxEffects};
+
+use super::transaction::TransactionError;
+
+/// Confirmations reported for a rooted slot; the node stops counting at the
+/// 32-vote lockout depth
+pub const FINALIZED_CONFIRMATIONS: u64 = 32;
+
+/// An account whose balance a transaction is expected to move
+#[derive(Debug, Clone, Copy)]
+pub struct Watched {
+    pub account: Pubkey,
+    /// Wallet the change is reported under
+    pub owner: Pubkey,
+    /// Mint of a token account; `None` for a SOL balance
+    pub mint: Option<Pubkey>,
+}
+
+impl Watched {
+    /// SOL balance of a wallet
+    pub fn native(owner: Pubkey) -> Self {
+        Self {
+            account: owner,
+            owner,
+            mint: None,
+        }
+    }
+
+    /// Token account held by `owner`
+    pub fn token(account: Pubkey, owner: Pubkey, mint: Pubkey) -> Self {
+        Self {
+            account,
+            owner,
+            mint: Some(mint),
+        }
+    }
+
+    /// Lamports, or the token amount for a token account. Accounts that
+    /// don't exist (yet) hold nothing.
+    fn amount(&self, lamports: u64, data: &[u8]) -> u64 {
+        match self.mint {
+            None => lamports,
+            Some(_) => StateWithExtensions::<spl_token_2022::state::Account>::unpack(data)
+                .map(|account| account.base.amount)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Simulate a signed transaction and report how it would move the watched
+/// balances. The fee payer's SOL change includes the fee. A transaction the
+/// simulation rejects is refused before it is broadcast.
+pub fn simulate_effects(
+    client: &RpcClient,
+    transaction: &Transaction,
+    watched: &[Watched],
+) -> Result<TxEffects, TransactionError> {
+    // The same account watched twice (a self-send) would count twice
+    let mut seen = HashSet::new();
+    let watched: Vec<Watched> = watched
+        .iter()
+        .filter(|w| seen.insert(w.account))
+        .copied()
+        .collect();
+    let accounts: Vec<Pubkey> = watched.iter().map(|w| w.account).collect();
+
+    let before = client
+        .get_multiple_accounts(&accounts)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    let simulation = client
+        .simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                commitment: Some(CommitmentConfig::confirmed()),
+                accounts: Some(RpcSimulateTransactionAccountsConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    addresses: accounts.iter().map(|a| a.to_string()).collect(),
+                }),
+                ..Default::default()
+            },
+        )
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+        .value;
+    if let Some(err) = simulation.err {
+        return Err(TransactionError::TransactionFailed(format!(
+            "simulation failed: {}",
+            err
+        )));
+    }
+    let after = simulation.accounts.unwrap_or_default();
+
+    let fee = client
+        .get_fee_for_message(transaction.message())
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    let changes = watched
+        .iter()
+        .enumerate()
+        .filter_map(|(i, w)| {
+            let pre = before
+                .get(i)
+                .and_then(Option::as_ref)
+                .map_or(0, |a| w.amount(a.lamports, &a.data));
+            let post = after.get(i).and_then(Option::as_ref).map_or(0, |a| {
+                w.amount(a.lamports, &a.data.decode().unwrap_or_default())
+            });
+            balance_change(&w.owner.to_string(), w.mint.map(|m| m.to_string()), pre, post)
+        })
+        .collect();
+
+    Ok(TxEffects {
+        changes,
+        fee: fee.to_string(),
+    })
+}
+
+fn balance_change(
+    address: &str,
+    token: Option<String>,
+    pre: u64,
+    post: u64,
+) -> Option<BalanceChange> {
+    let delta = post as i128 - pre as i128;
+    (delta != 0).then(|| BalanceChange {
+        address: address.to_string(),
+        token,
+        delta: delta.to_string(),
+    })
+}
+
+/// Balance changes a landed transaction made for `addresses`, with token
+/// changes reported under the owning wallet; `None` while it is unconfirmed
+pub fn get_transaction_effects(
+    rpc_url: &str,
+    signature: &str,
+    addresses: &[String],
+) -> Result<Option<ConfirmedEffects>, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let signature: Signature = signature
+        .parse()
+        .map_err(|_| TransactionError::TransactionFailed(format!("bad signature {}", signature)))?;
+
+    let Some(status) = client
+        .get_signature_status_with_commitment(&signature, CommitmentConfig::confirmed())
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    let confirmed = client
+        .get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::JsonParsed),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let meta = confirmed
+        .transaction
+        .meta
+        .ok_or_else(|| TransactionError::RpcError("transaction has no status metadata".to_string()))?;
+    let account_keys: Vec<String> = match confirmed.transaction.transaction {
+        EncodedTransaction::Json(tx) => match tx.message {
+            UiMessage::Parsed(message) => message.account_keys.into_iter().map(|k| k.pubkey).collect(),
+            UiMessage::Raw(message) => message.account_keys,
+        },
+        _ => {
+            return Err(TransactionError::RpcError(
+                "transaction returned in an unexpected encoding".to_string(),
+            ))
+        }
+    };
+
+    let mut changes: Vec<BalanceChange> = account_keys
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| addresses.contains(key))
+        .filter_map(|(i, key)| {
+            let pre = meta.pre_balances.get(i).copied().unwrap_or(0);
+            let post = meta.post_balances.get(i).copied().unwrap_or(0);
+            balance_change(key, None, pre, post)
+        })
+        .collect();
+
+    // Token balances are keyed by account; net them per owner and mint
+    let mut tokens: BTreeMap<(String, String), (u64, u64)> = BTreeMap::new();
+    let pre_tokens: Option<Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.into();
+    let post_tokens: Option<Vec<UiTransactionTokenBalance>> = meta.post_token_balances.into();
+    for (balances, is_post) in [(pre_tokens, false), (post_tokens, true)] {
+        for balance in balances.unwrap_or_default() {
+            let Some(owner) = Option::<String>::from(balance.owner) else {
+                continue;
+            };
+            if !addresses.contains(&owner) {
+                continue;
+            }
+            let amount: u64 = balance.ui_token_amount.amount.parse().unwrap_or(0);
+            let entry = tokens.entry((owner, balance.mint)).or_default();
+            if is_post {
+                entry.1 += amount;
+            } else {
+                entry.0 += amount;
+            }
+        }
+    }
+    changes.extend(
+        tokens
+            .into_iter()
+            .filter_map(|((owner, mint), (pre, post))| balance_change(&owner, Some(mint), pre, post)),
+    );
+
+    // The node counts votes until the slot is rooted, then reports none
+    let confirmations = client
+        .get_signature_statuses_with_history(&[signature])
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+        .value
+        .into_iter()
+        .flatten()
+        .next()
+        .map_or(0, |s| s.confirmations.map_or(FINALIZED_CONFIRMATIONS, |c| c as u64));
+
+    Ok(Some(ConfirmedEffects {
+        status: if status.is_ok() { "confirmed" } else { "failed" }.to_string(),
+        block_number: Some(confirmed.slot as i64),
+        confirmations,
+        effects: TxEffects {
+            changes,
+            fee: meta.fee.to_string(),
+        },
+        receipt: None,
+    }))
+}
+
+/// Get transaction effects (async version)
+pub async fn get_transaction_effects_async(
+    rpc_url: &str,
+    signature: &str,
+    addresses: &[String],
+) -> Result<Option<ConfirmedEffects>, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let signature = signature.to_string();
+    let addresses = addresses.to_vec();
+
+    tokio::task::spawn_blocking(move || get_transaction_effects(&rpc_url, &signature, &addresses))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_pack::Pack;
+
+    #[test]
+    fn test_watched_amounts() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        assert_eq!(Watched::native(owner).amount(5_000, &[]), 5_000);
+
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(
+            spl_token::state::Account {
+                mint,
+                owner,
+                amount: 42,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let token = Watched::token(Pubkey::new_unique(), owner, mint);
+        assert_eq!(token.amount(2_039_280, &data), 42);
+        // A token account that doesn't exist yet holds nothing
+        assert_eq!(token.amount(0, &[]), 0);
+
+        assert!(balance_change("a", None, 10, 10).is_none());
+        assert_eq!(balance_change("a", None, 10, 4).unwrap().delta, "-6");
+    }
+}
@@ -0,0 +1,43 @@
+//! Sign-In With Solana message signature checks
+
+use std::str::FromStr;
+
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SiwsError {
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+}
+
+/// Canonical base58 form of an address
+pub fn normalize_address(address: &str) -> Result<String, SiwsError> {
+    Pubkey::from_str(address.trim())
+        .map(|key| key.to_string())
+        .map_err(|_| SiwsError::InvalidAddress(address.to_string()))
+}
+
+/// Check a base58 ed25519 signature by `address` over the message bytes, as
+/// produced by wallet `signMessage`
+pub fn verify_message_signature(
+    address: &str,
+    message: &str,
+    signature: &str,
+) -> Result<(), SiwsError> {
+    let pubkey = Pubkey::from_str(address.trim())
+        .map_err(|_| SiwsError::InvalidAddress(address.to_string()))?;
+    let signature = Signature::from_str(signature.trim())
+        .map_err(|e| SiwsError::InvalidSignature(e.to_string()))?;
+
+    if signature.verify(pubkey.as_ref(), message.as_bytes()) {
+        Ok(())
+    } else {
+        Err(SiwsError::InvalidSignature(
+            "not signed by this address".to_string(),
+        ))
+    }
+}
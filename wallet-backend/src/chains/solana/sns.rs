@@ -0,0 +1,324 @@
+//! SNS (`.sol`) primary name, profile records and domain registration
+//!
+//! Lookups go through an SNS SDK proxy (Bonfida's by default), which does the
+//! name-account derivation and record deserialization server-side. So does
+//! building registrations, which pay through the SNS registrar; domain
+//! accounts are read and transferred directly over RPC.
+
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use thiserror::Error;
+
+use super::wallet::SolanaKeypair;
+
+use crate::chains::client::Identity;
+
+#[derive(Debug, Error)]
+pub enum SnsError {
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+    #[error("SNS API error: {0}")]
+    ApiError(String),
+    #[error("RPC error: {0}")]
+    RpcError(String),
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+}
+
+/// SPL Name Service program, and the `.sol` TLD domains are registered under
+const NAME_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+const SOL_TLD: Pubkey = solana_sdk::pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+const HASH_PREFIX: &str = "SPL Name Service";
+/// `Transfer` in the name service's instruction enum
+const TRANSFER_INSTRUCTION: u8 = 2;
+/// Storage bought with a new domain, in bytes
+const DOMAIN_SPACE: u32 = 1_000;
+
+/// Record holding the profile picture
+const AVATAR_RECORD: &str = "pic";
+
+/// SNS records returned with an identity
+const PROFILE_RECORDS: &[&str] = &["url", "email", "twitter", "github"];
+
+/// Proxy envelope: `s` is `"ok"` or `"error"`
+#[derive(Debug, Deserialize)]
+struct ProxyResponse<T> {
+    s: String,
+    result: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FavoriteDomain {
+    /// Name account of the domain
+    domain: String,
+    /// Domain without the `.sol` suffix
+    reverse: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordV2 {
+    deserialized: Option<String>,
+}
+
+/// Favorite (primary) `.sol` domain of an address with its picture and
+/// profile records
+pub async fn resolve_sns_identity(
+    client: &reqwest::Client,
+    api_url: &str,
+    address: &str,
+) -> Result<Option<Identity>, SnsError> {
+    address
+        .parse::<Pubkey>()
+        .map_err(|_| SnsError::InvalidAddress(address.to_string()))?;
+
+    let favorite: Option<FavoriteDomain> =
+        get(client, &format!("{}/favorite-domain/{}", api_url, address)).await?;
+    let Some(favorite) = favorite else {
+        return Ok(None);
+    };
+
+    let avatar = record(client, api_url, &favorite.domain, AVATAR_RECORD).await?;
+    let mut records = BTreeMap::new();
+    for name in PROFILE_RECORDS {
+        if let Some(value) = record(client, api_url, &favorite.domain, name).await? {
+            records.insert(name.to_string(), value);
+        }
+    }
+
+    Ok(Some(Identity {
+        name: format!("{}.sol", favorite.reverse),
+        avatar,
+        records,
+    }))
+}
+
+/// A V2 record of a domain, `None` when unset
+async fn record(
+    client: &reqwest::Client,
+    api_url: &str,
+    domain: &str,
+    name: &str,
+) -> Result<Option<String>, SnsError> {
+    let record: Option<RecordV2> = get(
+        client,
+        &format!("{}/record-v2/{}/{}", api_url, domain, name),
+    )
+    .await?;
+
+    Ok(record
+        .and_then(|r| r.deserialized)
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty()))
+}
+
+/// Fetch a proxy endpoint; an `"error"` envelope means nothing is registered
+async fn get<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Option<T>, SnsError> {
+    let response: ProxyResponse<serde_json::Value> = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| SnsError::ApiError(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| SnsError::ApiError(e.to_string()))?;
+
+    parse_result(response)
+}
+
+/// Name account of a `.sol` domain, given without the suffix
+pub fn domain_key(domain: &str) -> Pubkey {
+    let hashed = Sha256::digest(format!("{}{}", HASH_PREFIX, domain));
+    let seeds: [&[u8]; 3] = [&hashed, &Pubkey::default().to_bytes(), &SOL_TLD.to_bytes()];
+    Pubkey::find_program_address(&seeds, &NAME_PROGRAM_ID).0
+}
+
+/// Registration price in USD, which depends on the domain's length
+pub fn domain_price_usd(domain: &str) -> u32 {
+    match domain.chars().count() {
+        1 => 750,
+        2 => 700,
+        3 => 640,
+        4 => 160,
+        _ => 20,
+    }
+}
+
+/// Owner of a domain (given without the suffix), `None` if unregistered
+pub fn get_domain_owner(rpc_url: &str, domain: &str) -> Result<Option<Pubkey>, SnsError> {
+    let client = RpcClient::new(rpc_url.to_string());
+    let account = client
+        .get_account_with_commitment(&domain_key(domain), CommitmentConfig::confirmed())
+        .map_err(|e| SnsError::RpcError(e.to_string()))?
+        .value;
+
+    // Name registry header: parent, owner, class
+    Ok(account
+        .and_then(|account| account.data.get(32..64).map(<[u8]>::to_vec))
+        .and_then(|owner| Pubkey::try_from(owner.as_slice()).ok()))
+}
+
+/// Owner of a domain (async version)
+pub async fn get_domain_owner_async(
+    rpc_url: &str,
+    domain: &str,
+) -> Result<Option<Pubkey>, SnsError> {
+    let rpc_url = rpc_url.to_string();
+    let domain = domain.to_string();
+
+    tokio::task::spawn_blocking(move || get_domain_owner(&rpc_url, &domain))
+        .await
+        .map_err(|e| SnsError::RpcError(e.to_string()))?
+}
+
+/// Registration of a domain for `buyer`, built by the proxy and still to be
+/// signed
+pub async fn registration_transaction(
+    client: &reqwest::Client,
+    api_url: &str,
+    buyer: &str,
+    domain: &str,
+) -> Result<Transaction, SnsError> {
+    let url = format!(
+        "{}/register?buyer={}&domain={}&space={}&serialize=true",
+        api_url, buyer, domain, DOMAIN_SPACE
+    );
+    let encoded: String = get(client, &url)
+        .await?
+        .ok_or_else(|| SnsError::InvalidName(format!("{}.sol can't be registered", domain)))?;
+
+    let bytes = STANDARD
+        .decode(encoded)
+        .map_err(|e| SnsError::ApiError(e.to_string()))?;
+    bincode::deserialize(&bytes).map_err(|e| SnsError::ApiError(e.to_string()))
+}
+
+/// Sign a transaction built elsewhere as the fee payer and send it
+pub fn sign_and_send(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    mut transaction: Transaction,
+) -> Result<String, SnsError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let blockhash = transaction.message.recent_blockhash;
+    transaction
+        .try_partial_sign(&[keypair.keypair()], blockhash)
+        .map_err(|e| SnsError::TransactionFailed(e.to_string()))?;
+
+    client
+        .send_and_confirm_transaction(&transaction)
+        .map(|signature| signature.to_string())
+        .map_err(|e| SnsError::TransactionFailed(e.to_string()))
+}
+
+/// Name service instruction handing a domain to `new_owner`
+pub fn transfer_instruction(domain: &str, owner: &Pubkey, new_owner: &Pubkey) -> Instruction {
+    let mut data = vec![TRANSFER_INSTRUCTION];
+    data.extend_from_slice(&new_owner.to_bytes());
+    Instruction::new_with_bytes(
+        NAME_PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new(domain_key(domain), false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    )
+}
+
+/// Transfer a domain held by the keypair to `new_owner`
+pub fn transfer_domain(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    domain: &str,
+    new_owner: &str,
+) -> Result<String, SnsError> {
+    let new_owner: Pubkey = new_owner
+        .parse()
+        .map_err(|_| SnsError::InvalidAddress(new_owner.to_string()))?;
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let blockhash = client
+        .get_latest_blockhash()
+        .map_err(|e| SnsError::RpcError(e.to_string()))?;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer_instruction(domain, &keypair.pubkey(), &new_owner)],
+        Some(&keypair.keypair().pubkey()),
+        &[keypair.keypair()],
+        blockhash,
+    );
+    client
+        .send_and_confirm_transaction(&transaction)
+        .map(|signature| signature.to_string())
+        .map_err(|e| SnsError::TransactionFailed(e.to_string()))
+}
+
+fn parse_result<T: serde::de::DeserializeOwned>(
+    response: ProxyResponse<serde_json::Value>,
+) -> Result<Option<T>, SnsError> {
+    match (response.s.as_str(), response.result) {
+        ("ok", Some(result)) => serde_json::from_value(result)
+            .map(Some)
+            .map_err(|e| SnsError::ApiError(e.to_string())),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<T: serde::de::DeserializeOwned>(body: &str) -> Result<Option<T>, SnsError> {
+        parse_result(serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_parse_proxy_responses() {
+        let favorite: FavoriteDomain = parse(
+            r#"{"s":"ok","result":{"domain":"Crf8hzfthWGbGbLTVCiqRqV5MVnbpHB1L9KQMd6gsinb","reverse":"bonfida","stale":false}}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(favorite.reverse, "bonfida");
+
+        let missing: Option<FavoriteDomain> =
+            parse(r#"{"s":"error","result":"Favourite domain not found"}"#).unwrap();
+        assert!(missing.is_none());
+
+        let record: RecordV2 = parse(r#"{"s":"ok","result":{"deserialized":"https://sns.id"}}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.deserialized.as_deref(), Some("https://sns.id"));
+    }
+
+    #[test]
+    fn test_domain_accounts() {
+        // bonfida.sol, as in the favorite-domain response above
+        assert_eq!(
+            domain_key("bonfida").to_string(),
+            "Crf8hzfthWGbGbLTVCiqRqV5MVnbpHB1L9KQMd6gsinb"
+        );
+        assert_eq!(domain_price_usd("abc"), 640);
+        assert_eq!(domain_price_usd("alice"), 20);
+
+        let owner = Pubkey::new_unique();
+        let new_owner = Pubkey::new_unique();
+        let transfer = transfer_instruction("bonfida", &owner, &new_owner);
+        assert_eq!(transfer.data[0], TRANSFER_INSTRUCTION);
+        assert_eq!(&transfer.data[1..], new_owner.as_ref());
+        assert!(transfer.accounts[1].is_signer);
+    }
+}
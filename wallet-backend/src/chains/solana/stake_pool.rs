@@ -0,0 +1,508 @@
+//! SPL stake pools and the liquid staking tokens they mint
+//!
+//! Depositing SOL into a pool mints its token at the pool's exchange rate
+//! (total lamports over pool token supply); withdrawing burns the token for
+//! SOL from the pool's reserve. Pool state is read straight from the
+//! account data rather than through the stake-pool crate.
+
+use std::str::FromStr;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+
+use super::transaction::{check_sol_transfer, TransactionError};
+use super::wallet::SolanaKeypair;
+use crate::chains::client::StakePoolState;
+
+/// SPL stake pool program, shared by Jito, BlazeStake and most other pools
+pub const STAKE_POOL_PROGRAM: &str = "SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy";
+
+const SYSTEM_PROGRAM: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+
+/// A liquid staking token the wallet knows
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidStakingToken {
+    pub symbol: &'static str,
+    pub name: &'static str,
+    pub mint: &'static str,
+    /// SPL stake pool minting it; `None` for tokens from other programs,
+    /// which are tracked but can't be deposited or withdrawn here
+    pub stake_pool: Option<&'static str>,
+}
+
+/// Major liquid staking tokens on mainnet
+pub const LIQUID_STAKING_TOKENS: &[LiquidStakingToken] = &[
+    LiquidStakingToken {
+        symbol: "jitoSOL",
+        name: "Jito Staked SOL",
+        mint: "J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn",
+        stake_pool: Some("Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb"),
+    },
+    LiquidStakingToken {
+        symbol: "bSOL",
+        name: "BlazeStake Staked SOL",
+        mint: "bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1",
+        stake_pool: Some("stk9ApL5HeVAwPLr3TLhDXdZS8ptVu7zp6ov8HFDuMi"),
+    },
+    LiquidStakingToken {
+        symbol: "mSOL",
+        name: "Marinade Staked SOL",
+        mint: "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So",
+        stake_pool: None,
+    },
+];
+
+/// The liquid staking token minted as `mint`, if it is a known one
+pub fn liquid_staking_token(mint: &str) -> Option<&'static LiquidStakingToken> {
+    LIQUID_STAKING_TOKENS.iter().find(|token| token.mint == mint)
+}
+
+/// The known liquid staking token minted by the pool at `pool`
+pub fn liquid_staking_token_for_pool(pool: &str) -> Option<&'static LiquidStakingToken> {
+    LIQUID_STAKING_TOKENS
+        .iter()
+        .find(|token| token.stake_pool == Some(pool))
+}
+
+/// `StakePool` account fields the deposit and withdraw instructions need
+#[derive(Debug, Clone)]
+pub struct StakePoolAccount {
+    pub reserve_stake: Pubkey,
+    pub pool_mint: Pubkey,
+    pub manager_fee_account: Pubkey,
+    pub token_program: Pubkey,
+    /// Set when SOL deposits / withdrawals need this authority's signature
+    pub sol_deposit_authority: Option<Pubkey>,
+    pub sol_withdraw_authority: Option<Pubkey>,
+    pub state: StakePoolState,
+}
+
+/// Borsh reader over a `StakePool` account's data
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], TransactionError> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + len)
+            .ok_or_else(|| TransactionError::RpcError("stake pool account is truncated".to_string()))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, TransactionError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, TransactionError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap_or_default()))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey, TransactionError> {
+        Ok(Pubkey::new_from_array(self.bytes(32)?.try_into().unwrap_or_default()))
+    }
+
+    fn option_pubkey(&mut self) -> Result<Option<Pubkey>, TransactionError> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => self.pubkey().map(Some),
+        }
+    }
+
+    /// `Fee { denominator, numerator }` in basis points
+    fn fee_bps(&mut self) -> Result<u32, TransactionError> {
+        let denominator = self.u64()?;
+        let numerator = self.u64()?;
+        Ok(match denominator {
+            0 => 0,
+            _ => (numerator as u128 * 10_000 / denominator as u128) as u32,
+        })
+    }
+
+    /// `FutureEpoch<Fee>`: none, or a fee taking effect in one or two epochs
+    fn future_fee(&mut self) -> Result<(), TransactionError> {
+        if self.u8()? != 0 {
+            self.bytes(16)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode a `StakePool` account
+pub fn parse_stake_pool(address: &str, data: &[u8]) -> Result<StakePoolAccount, TransactionError> {
+    let mut reader = Reader { data, offset: 0 };
+    // Account type 1 is a stake pool; 2 would be its validator list
+    if reader.u8()? != 1 {
+        return Err(TransactionError::InvalidAddress(address.to_string()));
+    }
+    // Manager, staker, stake deposit authority, bump seed, validator list
+    reader.bytes(32 * 3 + 1 + 32)?;
+    let reserve_stake = reader.pubkey()?;
+    let pool_mint = reader.pubkey()?;
+    let manager_fee_account = reader.pubkey()?;
+    let token_program = reader.pubkey()?;
+    let total_lamports = reader.u64()?;
+    let pool_token_supply = reader.u64()?;
+    let last_update_epoch = reader.u64()?;
+    // Lockup
+    reader.bytes(48)?;
+    let _epoch_fee = reader.fee_bps()?;
+    reader.future_fee()?;
+    // Preferred deposit and withdraw validators
+    reader.option_pubkey()?;
+    reader.option_pubkey()?;
+    let _stake_deposit_fee = reader.fee_bps()?;
+    let _stake_withdrawal_fee = reader.fee_bps()?;
+    reader.future_fee()?;
+    let _stake_referral_fee = reader.u8()?;
+    let sol_deposit_authority = reader.option_pubkey()?;
+    let sol_deposit_fee_bps = reader.fee_bps()?;
+    let _sol_referral_fee = reader.u8()?;
+    let sol_withdraw_authority = reader.option_pubkey()?;
+    let sol_withdrawal_fee_bps = reader.fee_bps()?;
+    reader.future_fee()?;
+    let last_epoch_pool_token_supply = reader.u64()?;
+    let last_epoch_total_lamports = reader.u64()?;
+
+    Ok(StakePoolAccount {
+        reserve_stake,
+        pool_mint,
+        manager_fee_account,
+        token_program,
+        sol_deposit_authority,
+        sol_withdraw_authority,
+        state: StakePoolState {
+            address: address.to_string(),
+            pool_mint: pool_mint.to_string(),
+            total_lamports,
+            pool_token_supply,
+            last_epoch_total_lamports,
+            last_epoch_pool_token_supply,
+            last_update_epoch,
+            sol_deposit_fee_bps,
+            sol_withdrawal_fee_bps,
+        },
+    })
+}
+
+/// PDA allowed to mint and burn the pool's tokens
+pub fn withdraw_authority(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[pool.as_ref(), b"withdraw"], &stake_pool_program()).0
+}
+
+fn stake_pool_program() -> Pubkey {
+    Pubkey::from_str(STAKE_POOL_PROGRAM).unwrap_or_default()
+}
+
+/// Instructions depositing `lamports` from `owner`, creating its token
+/// account if needed. The owner's own token account takes the referral fee.
+pub fn deposit_sol_instructions(
+    pool: &Pubkey,
+    account: &StakePoolAccount,
+    owner: &Pubkey,
+    lamports: u64,
+) -> Vec<Instruction> {
+    let token_account =
+        get_associated_token_address_with_program_id(owner, &account.pool_mint, &account.token_program);
+    let mut data = vec![14];
+    data.extend_from_slice(&lamports.to_le_bytes());
+
+    vec![
+        create_associated_token_account_idempotent(
+            owner,
+            owner,
+            &account.pool_mint,
+            &account.token_program,
+        ),
+        Instruction {
+            program_id: stake_pool_program(),
+            accounts: vec![
+                AccountMeta::new(*pool, false),
+                AccountMeta::new_readonly(withdraw_authority(pool), false),
+                AccountMeta::new(account.reserve_stake, false),
+                AccountMeta::new(*owner, true),
+                AccountMeta::new(token_account, false),
+                AccountMeta::new(account.manager_fee_account, false),
+                AccountMeta::new(token_account, false),
+                AccountMeta::new(account.pool_mint, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM, false),
+                AccountMeta::new_readonly(account.token_program, false),
+            ],
+            data,
+        },
+    ]
+}
+
+/// Instruction burning `pool_tokens` of `owner` for SOL from the reserve
+pub fn withdraw_sol_instruction(
+    pool: &Pubkey,
+    account: &StakePoolAccount,
+    owner: &Pubkey,
+    pool_tokens: u64,
+) -> Instruction {
+    let token_account =
+        get_associated_token_address_with_program_id(owner, &account.pool_mint, &account.token_program);
+    let mut data = vec![16];
+    data.extend_from_slice(&pool_tokens.to_le_bytes());
+
+    Instruction {
+        program_id: stake_pool_program(),
+        accounts: vec![
+            AccountMeta::new(*pool, false),
+            AccountMeta::new_readonly(withdraw_authority(pool), false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(account.reserve_stake, false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(account.manager_fee_account, false),
+            AccountMeta::new(account.pool_mint, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+            AccountMeta::new_readonly(solana_sdk::stake::program::id(), false),
+            AccountMeta::new_readonly(account.token_program, false),
+        ],
+        data,
+    }
+}
+
+/// Read and decode the pool at `pool`
+pub fn get_stake_pool(rpc_url: &str, pool: &str) -> Result<StakePoolAccount, TransactionError> {
+    let address =
+        Pubkey::from_str(pool).map_err(|_| TransactionError::InvalidAddress(pool.to_string()))?;
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let account = client
+        .get_account(&address)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    if account.owner != stake_pool_program() {
+        return Err(TransactionError::InvalidAddress(pool.to_string()));
+    }
+    parse_stake_pool(pool, &account.data)
+}
+
+/// Deposit `lamports` of the keypair's SOL into `pool`. The same rent rules
+/// as a SOL send apply.
+pub fn deposit_sol(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    pool: &str,
+    lamports: u64,
+) -> Result<String, TransactionError> {
+    if lamports == 0 {
+        return Err(TransactionError::InvalidAmount);
+    }
+    let account = get_stake_pool(rpc_url, pool)?;
+    if account.sol_deposit_authority.is_some() {
+        return Err(TransactionError::TransactionFailed(format!(
+            "stake pool {} only accepts SOL deposits from its deposit authority",
+            pool
+        )));
+    }
+
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let owner = keypair.pubkey();
+    let pool_address =
+        Pubkey::from_str(pool).map_err(|_| TransactionError::InvalidAddress(pool.to_string()))?;
+    let instructions = deposit_sol_instructions(&pool_address, &account, &owner, lamports);
+
+    let blockhash = client
+        .get_latest_blockhash()
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let fee = client
+        .get_fee_for_message(&Message::new_with_blockhash(
+            &instructions,
+            Some(&owner),
+            &blockhash,
+        ))
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let token_account = get_associated_token_address_with_program_id(
+        &owner,
+        &account.pool_mint,
+        &account.token_program,
+    );
+    let account_rent = if client.get_account(&token_account).is_err() {
+        client
+            .get_minimum_balance_for_rent_exemption(spl_token::state::Account::LEN)
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?
+    } else {
+        0
+    };
+    let balance = client
+        .get_balance(&owner)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let rent_minimum = client
+        .get_minimum_balance_for_rent_exemption(0)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    check_sol_transfer(balance, lamports + account_rent, fee, rent_minimum, true)?;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&owner),
+        &[keypair.keypair()],
+        blockhash,
+    );
+    let signature = client
+        .send_and_confirm_transaction(&transaction)
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+    Ok(signature.to_string())
+}
+
+/// Redeem `pool_tokens` of the keypair's pool tokens for SOL
+pub fn withdraw_sol(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    pool: &str,
+    pool_tokens: u64,
+) -> Result<String, TransactionError> {
+    if pool_tokens == 0 {
+        return Err(TransactionError::InvalidAmount);
+    }
+    let account = get_stake_pool(rpc_url, pool)?;
+    if account.sol_withdraw_authority.is_some() {
+        return Err(TransactionError::TransactionFailed(format!(
+            "stake pool {} only allows SOL withdrawals by its withdraw authority",
+            pool
+        )));
+    }
+
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let owner = keypair.pubkey();
+    let token_account = get_associated_token_address_with_program_id(
+        &owner,
+        &account.pool_mint,
+        &account.token_program,
+    );
+    let held = client
+        .get_token_account_balance(&token_account)
+        .ok()
+        .and_then(|balance| balance.amount.parse::<u64>().ok())
+        .unwrap_or(0);
+    if held < pool_tokens {
+        return Err(TransactionError::InsufficientBalance {
+            required: pool_tokens,
+            available: held,
+        });
+    }
+
+    let pool_address =
+        Pubkey::from_str(pool).map_err(|_| TransactionError::InvalidAddress(pool.to_string()))?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[withdraw_sol_instruction(&pool_address, &account, &owner, pool_tokens)],
+        Some(&owner),
+        &[keypair.keypair()],
+        client
+            .get_latest_blockhash()
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?,
+    );
+    let signature = client
+        .send_and_confirm_transaction(&transaction)
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+    Ok(signature.to_string())
+}
+
+/// Read a stake pool (async version)
+pub async fn get_stake_pool_async(
+    rpc_url: &str,
+    pool: &str,
+) -> Result<StakePoolAccount, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let pool = pool.to_string();
+
+    tokio::task::spawn_blocking(move || get_stake_pool(&rpc_url, &pool))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee(out: &mut Vec<u8>, numerator: u64, denominator: u64) {
+        out.extend_from_slice(&denominator.to_le_bytes());
+        out.extend_from_slice(&numerator.to_le_bytes());
+    }
+
+    #[test]
+    fn test_parse_stake_pool_and_instructions() {
+        let reserve = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let manager_fee = Pubkey::new_unique();
+
+        let mut data = vec![1];
+        data.extend_from_slice(&[0; 32 * 3 + 1 + 32]);
+        for key in [reserve, mint, manager_fee, spl_token::id()] {
+            data.extend_from_slice(key.as_ref());
+        }
+        for value in [1_100_000u64, 1_000_000, 700] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0; 48]);
+        fee(&mut data, 4, 100);
+        // An epoch fee change scheduled for the next epoch
+        data.push(1);
+        fee(&mut data, 1, 1000);
+        data.extend_from_slice(&[0, 0]);
+        fee(&mut data, 0, 0);
+        fee(&mut data, 1, 1000);
+        data.push(0);
+        data.push(0);
+        data.push(0);
+        fee(&mut data, 0, 0);
+        data.push(0);
+        data.push(0);
+        fee(&mut data, 1, 1000);
+        data.push(0);
+        for value in [999_000u64, 1_098_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let pool = Pubkey::new_unique();
+        let account = parse_stake_pool(&pool.to_string(), &data).unwrap();
+        assert_eq!(account.reserve_stake, reserve);
+        assert_eq!(account.state.pool_mint, mint.to_string());
+        assert_eq!(account.state.total_lamports, 1_100_000);
+        assert_eq!(account.state.pool_token_supply, 1_000_000);
+        assert_eq!(account.state.last_update_epoch, 700);
+        assert_eq!(account.state.sol_deposit_fee_bps, 0);
+        assert_eq!(account.state.sol_withdrawal_fee_bps, 10);
+        assert_eq!(account.state.last_epoch_pool_token_supply, 999_000);
+        assert_eq!(account.state.last_epoch_total_lamports, 1_098_000);
+        assert!(account.sol_deposit_authority.is_none());
+
+        // Truncated data and validator lists are refused
+        assert!(parse_stake_pool("pool", &data[..300]).is_err());
+        assert!(parse_stake_pool("pool", &[2; 600]).is_err());
+
+        let owner = Pubkey::new_unique();
+        let deposit = deposit_sol_instructions(&pool, &account, &owner, 5_000);
+        assert_eq!(deposit.len(), 2);
+        assert_eq!(deposit[1].data[0], 14);
+        assert_eq!(deposit[1].data[1..], 5_000u64.to_le_bytes());
+        assert_eq!(deposit[1].accounts.len(), 10);
+        assert_eq!(deposit[1].accounts[1].pubkey, withdraw_authority(&pool));
+        assert!(deposit[1].accounts[3].is_signer);
+
+        let withdraw = withdraw_sol_instruction(&pool, &account, &owner, 2_000);
+        assert_eq!(withdraw.data[0], 16);
+        assert_eq!(withdraw.accounts.len(), 12);
+        assert_eq!(withdraw.accounts[4].pubkey, reserve);
+        assert_eq!(withdraw.accounts[5].pubkey, owner);
+
+        assert_eq!(liquid_staking_token(LIQUID_STAKING_TOKENS[0].mint).unwrap().symbol, "jitoSOL");
+        assert!(liquid_staking_token_for_pool(STAKE_POOL_PROGRAM).is_none());
+    }
+}
@@ -1,8 +1,10 @@
 //! Jupiter swap integration for Solana
 
 use serde::{Deserialize, Serialize};
+use solana_sdk::message::VersionedMessage;
 use thiserror::Error;
 
+use super::jito::{send_bundle, JitoBundle};
 use super::wallet::SolanaKeypair;
 
 #[derive(Debug, Error)]
@@ -121,12 +123,11 @@ pub async fn get_quote(request: &QuoteRequest) -> Result<QuoteResponse, SwapErro
     Ok(quote)
 }
 
-/// Execute a swap using Jupiter
-pub async fn execute_swap(
-    rpc_url: &str,
+/// Have Jupiter build the swap transaction for `quote` and sign it
+async fn build_swap_transaction(
     keypair: &SolanaKeypair,
-    quote: QuoteResponse,
-) -> Result<SwapResult, SwapError> {
+    quote: &QuoteResponse,
+) -> Result<solana_sdk::transaction::VersionedTransaction, SwapError> {
     let client = reqwest::Client::new();
 
     // Build swap request
@@ -176,13 +177,96 @@ pub async fn execute_swap(
 
     // Sign the transaction
     tx.signatures[0] = keypair.sign(tx.message.serialize().as_slice());
+    Ok(tx)
+}
+
+/// What simulating a swap transaction showed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapSimulation {
+    pub input_amount: String,
+    pub output_amount: String,
+    /// Least output accepted within the quote's slippage
+    pub minimum_output_amount: String,
+    pub price_impact_pct: String,
+    /// Compute units the transaction used in simulation
+    pub units_consumed: Option<u64>,
+    /// Network fee in lamports
+    pub fee: Option<u64>,
+}
+
+/// Build and sign the swap for `quote`, then simulate it without sending.
+/// A failing simulation is an error, as the swap would fail too.
+pub async fn simulate_swap(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    quote: QuoteResponse,
+) -> Result<SwapSimulation, SwapError> {
+    let tx = build_swap_transaction(keypair, &quote).await?;
+
+    let rpc_url = rpc_url.to_string();
+    let (simulation, fee) = tokio::task::spawn_blocking(move || {
+        let rpc_client = solana_client::rpc_client::RpcClient::new(rpc_url);
+        let simulation = rpc_client
+            .simulate_transaction(&tx)
+            .map_err(|e| SwapError::ExecutionFailed(e.to_string()))?;
+        let fee = match &tx.message {
+            VersionedMessage::Legacy(message) => rpc_client.get_fee_for_message(message),
+            VersionedMessage::V0(message) => rpc_client.get_fee_for_message(message),
+        }
+        .ok();
+        Ok::<_, SwapError>((simulation.value, fee))
+    })
+    .await
+    .map_err(|e| SwapError::ExecutionFailed(e.to_string()))??;
+    if let Some(err) = simulation.err {
+        return Err(SwapError::ExecutionFailed(format!("simulation failed: {}", err)));
+    }
+
+    Ok(SwapSimulation {
+        input_amount: quote.in_amount,
+        output_amount: quote.out_amount,
+        minimum_output_amount: quote.other_amount_threshold,
+        price_impact_pct: quote.price_impact_pct,
+        units_consumed: simulation.units_consumed,
+        fee,
+    })
+}
+
+/// Execute a swap using Jupiter, as a Jito bundle when `jito` is given
+pub async fn execute_swap(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    quote: QuoteResponse,
+    jito: Option<&JitoBundle>,
+) -> Result<SwapResult, SwapError> {
+    let tx = build_swap_transaction(keypair, &quote).await?;
 
     // Send transaction using versioned transaction support
     let rpc_client = solana_client::rpc_client::RpcClient::new(rpc_url.to_string());
 
-    // Serialize the signed transaction back to bytes
-    let signed_tx_bytes = bincode::serialize(&tx)
+    // A bundle the engine rejects or that doesn't land falls back to the RPC
+    // node; it carries the same signed transaction, so it lands at most once
+    if let Some(jito) = jito.cloned() {
+        let rpc_url = rpc_url.to_string();
+        let payer = keypair.keypair().insecure_clone();
+        let bundled = tx.clone();
+        let landed = tokio::task::spawn_blocking(move || {
+            let rpc_client = solana_client::rpc_client::RpcClient::new(rpc_url);
+            send_bundle(&rpc_client, &jito, &payer, &bundled)
+        })
+        .await
         .map_err(|e| SwapError::ExecutionFailed(e.to_string()))?;
+        match landed {
+            Ok(signature) => {
+                return Ok(SwapResult {
+                    signature,
+                    input_amount: quote.in_amount,
+                    output_amount: quote.out_amount,
+                });
+            }
+            Err(e) => tracing::warn!(error = %e, "Bundle submission failed; sending through RPC"),
+        }
+    }
 
     // Send raw transaction using send_transaction with proper config
     use solana_client::rpc_config::RpcSendTransactionConfig;
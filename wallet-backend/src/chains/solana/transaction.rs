@@ -1,19 +1,27 @@
 //! Solana transaction operations
 
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
     native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
     signature::Signature,
     system_instruction,
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token::instruction as token_instruction;
 use thiserror::Error;
 
+use crate::chains::client::TxEffects;
+
+use super::balance::{get_mint_info, BalanceError, MintInfo};
+use super::jito::{send_bundle, JitoBundle};
+use super::simulate::{simulate_effects, Watched};
 use super::wallet::SolanaKeypair;
 
 #[derive(Debug, Error)]
@@ -22,12 +30,42 @@ pub enum TransactionError {
     RpcError(String),
     #[error("Invalid address: {0}")]
     InvalidAddress(String),
-    #[error("Insufficient balance")]
-    InsufficientBalance,
+    #[error("Insufficient balance: {required} required, {available} available")]
+    InsufficientBalance { required: u64, available: u64 },
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
     #[error("Invalid amount")]
     InvalidAmount,
+    #[error(
+        "Sender would be left with {remaining} lamports, below the rent-exempt minimum of \
+         {minimum}; send less or send the full balance"
+    )]
+    BelowRentExemption { remaining: u64, minimum: u64 },
+    #[error(
+        "Recipient account does not exist and needs at least {minimum} lamports to be created \
+         (sending {amount})"
+    )]
+    RecipientBelowRentExemption { amount: u64, minimum: u64 },
+    #[error("Not enough SOL for fees and rent: {required} lamports required, {available} available")]
+    InsufficientFeeBalance { required: u64, available: u64 },
+}
+
+/// Mint lookups fail on bad addresses or the network
+impl From<BalanceError> for TransactionError {
+    fn from(e: BalanceError) -> Self {
+        match e {
+            BalanceError::InvalidAddress(addr) => TransactionError::InvalidAddress(addr),
+            _ => TransactionError::RpcError(e.to_string()),
+        }
+    }
+}
+
+/// Amount to send
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SendAmount<T> {
+    Exact(T),
+    /// Everything the account can send after fees and rent
+    All,
 }
 
 /// Transaction send request
@@ -51,14 +89,87 @@ pub struct SendTokenRequest {
 pub struct TransactionResult {
     pub signature: String,
     pub status: String,
+    /// Amount actually sent, in base units (lamports or raw token amount)
+    pub amount: u64,
+    /// Token-2022 fee withheld from `amount`; the recipient gets the rest
+    pub transfer_fee: u64,
+    /// Decimals of the mint sent; `None` for SOL
+    pub decimals: Option<u8>,
+    /// Balance changes the simulation anticipated
+    pub expected: Option<TxEffects>,
+    /// Last block height at which the transaction's blockhash is accepted
+    pub last_valid_height: u64,
+}
+
+/// What happens to a signed transfer
+#[derive(Debug, Clone, Copy)]
+pub enum Submission<'a> {
+    /// Send and confirm through the RPC node
+    Rpc,
+    /// As a tipped Jito bundle, falling back to the RPC node
+    Jito(&'a JitoBundle),
+    /// Nothing; the transfer is only checked and simulated
+    DryRun,
+}
+
+impl Submission<'_> {
+    /// Status reported for the transfer once submitted
+    fn status(&self) -> &'static str {
+        match self {
+            Submission::DryRun => "simulated",
+            _ => "confirmed",
+        }
+    }
+}
+
+/// SPL Memo program (v2)
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+/// Solana Pay memo and reference keys attached to a transfer, so the
+/// recipient can find and reconcile the payment
+#[derive(Debug, Clone, Default)]
+pub struct PaymentMarkers {
+    pub memo: Option<String>,
+    pub references: Vec<Pubkey>,
+}
+
+impl PaymentMarkers {
+    /// Add the markers to `instructions`, whose last instruction is the
+    /// transfer: the references become read-only, non-signer accounts of the
+    /// transfer and the memo is written just before it
+    pub fn apply(&self, instructions: &mut Vec<Instruction>) {
+        let Some(transfer) = instructions.last_mut() else {
+            return;
+        };
+        transfer.accounts.extend(
+            self.references
+                .iter()
+                .map(|reference| AccountMeta::new_readonly(*reference, false)),
+        );
+
+        if let Some(ref memo) = self.memo {
+            let memo = Instruction {
+                program_id: MEMO_PROGRAM_ID.parse().expect("valid memo program id"),
+                accounts: Vec::new(),
+                data: memo.as_bytes().to_vec(),
+            };
+            instructions.insert(instructions.len() - 1, memo);
+        }
+    }
 }
 
 /// Send SOL to another address
+///
+/// Refuses transfers that would leave the sender with a non-zero balance below
+/// the rent-exempt minimum, or that would create a recipient account without
+/// funding its rent. `SendAmount::All` sends the balance minus the fee.
 pub fn send_sol(
     rpc_url: &str,
     keypair: &SolanaKeypair,
     to: &str,
-    amount_sol: f64,
+    amount: SendAmount<f64>,
+    markers: &PaymentMarkers,
+    submission: Submission<'_>,
 ) -> Result<TransactionResult, TransactionError> {
     let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
 
@@ -66,44 +177,108 @@ pub fn send_sol(
         .parse()
         .map_err(|_| TransactionError::InvalidAddress(to.to_string()))?;
 
-    let lamports = (amount_sol * LAMPORTS_PER_SOL as f64) as u64;
+    // Get recent blockhash
+    let (blockhash, last_valid_height) = client
+        .get_latest_blockhash_with_commitment(client.commitment())
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    // Fee only depends on the message shape, not the amount
+    let fee = client
+        .get_fee_for_message(&Message::new_with_blockhash(
+            &[system_instruction::transfer(&keypair.pubkey(), &to_pubkey, 1)],
+            Some(&keypair.pubkey()),
+            &blockhash,
+        ))
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let balance = client
+        .get_balance(&keypair.pubkey())
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let rent_minimum = client
+        .get_minimum_balance_for_rent_exemption(0)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let recipient_exists = client
+        .get_balance(&to_pubkey)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+        > 0;
+
+    let lamports = match amount {
+        SendAmount::Exact(amount_sol) => (amount_sol * LAMPORTS_PER_SOL as f64) as u64,
+        SendAmount::All => max_sendable_sol(balance, fee),
+    };
     if lamports == 0 {
         return Err(TransactionError::InvalidAmount);
     }
 
-    // Create transfer instruction
-    let instruction = system_instruction::transfer(&keypair.pubkey(), &to_pubkey, lamports);
+    check_sol_transfer(balance, lamports, fee, rent_minimum, recipient_exists)?;
 
-    // Get recent blockhash
-    let blockhash = client
-        .get_latest_blockhash()
-        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    // Create transfer instruction
+    let mut instructions = vec![system_instruction::transfer(
+        &keypair.pubkey(),
+        &to_pubkey,
+        lamports,
+    )];
+    markers.apply(&mut instructions);
 
     // Create and sign transaction
     let transaction = Transaction::new_signed_with_payer(
-        &[instruction],
+        &instructions,
         Some(&keypair.pubkey()),
         &[keypair.keypair()],
         blockhash,
     );
+    let expected = simulate_effects(
+        &client,
+        &transaction,
+        &[Watched::native(keypair.pubkey()), Watched::native(to_pubkey)],
+    )?;
 
-    // Send and confirm
-    let signature = client
-        .send_and_confirm_transaction(&transaction)
-        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+    let signature = submit(&client, keypair, &transaction, submission)?;
 
     Ok(TransactionResult {
         signature: signature.to_string(),
-        status: "confirmed".to_string(),
+        status: submission.status().to_string(),
+        amount: lamports,
+        transfer_fee: 0,
+        decimals: None,
+        expected: Some(expected),
+        last_valid_height,
     })
 }
 
+/// Send and confirm a signed transaction as `submission` says. A bundle the
+/// engine rejects or that doesn't land falls back to the RPC node. A dry run
+/// sends nothing and returns the signature the transaction would land under.
+fn submit(
+    client: &RpcClient,
+    keypair: &SolanaKeypair,
+    transaction: &Transaction,
+    submission: Submission<'_>,
+) -> Result<Signature, TransactionError> {
+    match submission {
+        Submission::DryRun => return Ok(transaction.signatures[0]),
+        Submission::Jito(jito) => {
+            match send_bundle(client, jito, keypair.keypair(), &transaction.clone().into()) {
+                Ok(_) => return Ok(transaction.signatures[0]),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Bundle submission failed; sending through RPC")
+                }
+            }
+        }
+        Submission::Rpc => {}
+    }
+
+    client
+        .send_and_confirm_transaction(transaction)
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))
+}
+
 /// Send SOL (async version)
 pub async fn send_sol_async(
     rpc_url: &str,
     keypair: &SolanaKeypair,
     to: &str,
-    amount_sol: f64,
+    amount: SendAmount<f64>,
+    markers: PaymentMarkers,
 ) -> Result<TransactionResult, TransactionError> {
     let rpc_url = rpc_url.to_string();
     let to = to.to_string();
@@ -118,20 +293,86 @@ pub async fn send_sol_async(
             &keypair_bytes[..32].try_into().unwrap(),
         ))
         .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
-        send_sol(&rpc_url, &wrapped, &to, amount_sol)
+        send_sol(&rpc_url, &wrapped, &to, amount, &markers, Submission::Rpc)
     })
     .await
     .map_err(|e| TransactionError::RpcError(e.to_string()))?
 }
 
-/// Send SPL tokens to another address
+/// Largest SOL transfer an account can make: its whole balance minus the fee.
+/// A system account may be emptied completely, so no rent reserve is kept.
+pub fn max_sendable_sol(balance: u64, fee: u64) -> u64 {
+    balance.saturating_sub(fee)
+}
+
+/// Rent and balance rules for a SOL transfer
+pub fn check_sol_transfer(
+    balance: u64,
+    lamports: u64,
+    fee: u64,
+    rent_minimum: u64,
+    recipient_exists: bool,
+) -> Result<(), TransactionError> {
+    let remaining = balance
+        .checked_sub(lamports)
+        .and_then(|b| b.checked_sub(fee))
+        .ok_or(TransactionError::InsufficientBalance {
+            required: lamports.saturating_add(fee),
+            available: balance,
+        })?;
+
+    if remaining > 0 && remaining < rent_minimum {
+        return Err(TransactionError::BelowRentExemption {
+            remaining,
+            minimum: rent_minimum,
+        });
+    }
+
+    if !recipient_exists && lamports < rent_minimum {
+        return Err(TransactionError::RecipientBelowRentExemption {
+            amount: lamports,
+            minimum: rent_minimum,
+        });
+    }
+
+    Ok(())
+}
+
+/// Unsigned SOL transfer paid by `from`, for a signer away from the server
+pub fn unsigned_transfer(
+    from: &str,
+    to: &str,
+    lamports: u64,
+    blockhash: Hash,
+) -> Result<Transaction, TransactionError> {
+    let from_pubkey: Pubkey = from
+        .parse()
+        .map_err(|_| TransactionError::InvalidAddress(from.to_string()))?;
+    let to_pubkey: Pubkey = to
+        .parse()
+        .map_err(|_| TransactionError::InvalidAddress(to.to_string()))?;
+    if lamports == 0 {
+        return Err(TransactionError::InvalidAmount);
+    }
+
+    let instruction = system_instruction::transfer(&from_pubkey, &to_pubkey, lamports);
+    let message = Message::new_with_blockhash(&[instruction], Some(&from_pubkey), &blockhash);
+    Ok(Transaction::new_unsigned(message))
+}
+
+/// Send SPL Token or Token-2022 tokens to another address
+///
+/// The sender must hold enough SOL for the fee and, when the recipient has no
+/// token account yet, its rent. `SendAmount::All` sends the full token balance.
+/// A Token-2022 transfer fee comes out of the amount sent.
 pub fn send_token(
     rpc_url: &str,
     keypair: &SolanaKeypair,
     to: &str,
     mint: &str,
-    amount: u64,
-    decimals: u8,
+    amount: SendAmount<u64>,
+    markers: &PaymentMarkers,
+    submission: Submission<'_>,
 ) -> Result<TransactionResult, TransactionError> {
     let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
 
@@ -142,43 +383,77 @@ pub fn send_token(
         .parse()
         .map_err(|_| TransactionError::InvalidAddress(mint.to_string()))?;
 
-    // Get associated token accounts
-    let from_ata = get_associated_token_address(&keypair.pubkey(), &mint_pubkey);
-    let to_ata = get_associated_token_address(&to_pubkey, &mint_pubkey);
-
-    let mut instructions = Vec::new();
+    let mint_info = get_mint_info(&client, &mint_pubkey)?;
 
-    // Check if recipient's ATA exists, if not create it
-    if client.get_account(&to_ata).is_err() {
-        instructions.push(
-            spl_associated_token_account::instruction::create_associated_token_account(
-                &keypair.pubkey(),
-                &to_pubkey,
-                &mint_pubkey,
-                &spl_token::id(),
-            ),
-        );
+    // A missing token account holds nothing
+    let from_ata = get_associated_token_address_with_program_id(
+        &keypair.pubkey(),
+        &mint_pubkey,
+        &mint_info.program_id,
+    );
+    let token_balance: u64 = client
+        .get_token_account_balance(&from_ata)
+        .ok()
+        .and_then(|balance| balance.amount.parse().ok())
+        .unwrap_or(0);
+
+    let amount = match amount {
+        SendAmount::Exact(amount) => amount,
+        SendAmount::All => token_balance,
+    };
+    if amount == 0 {
+        return Err(TransactionError::InvalidAmount);
+    }
+    if amount > token_balance {
+        return Err(TransactionError::InsufficientBalance {
+            required: amount,
+            available: token_balance,
+        });
     }
 
-    // Add transfer instruction
-    instructions.push(
-        token_instruction::transfer_checked(
-            &spl_token::id(),
-            &from_ata,
-            &mint_pubkey,
-            &to_ata,
-            &keypair.pubkey(),
-            &[],
-            amount,
-            decimals,
-        )
-        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?,
-    );
+    // Check if recipient's ATA exists, if not create it
+    let to_ata =
+        get_associated_token_address_with_program_id(&to_pubkey, &mint_pubkey, &mint_info.program_id);
+    let create_recipient_ata = client.get_account(&to_ata).is_err();
+
+    let mut instructions = token_transfer_instructions(
+        &keypair.pubkey(),
+        &to_pubkey,
+        &mint_pubkey,
+        &mint_info,
+        amount,
+        create_recipient_ata,
+    )?;
+    markers.apply(&mut instructions);
 
     // Get recent blockhash
-    let blockhash = client
-        .get_latest_blockhash()
+    let (blockhash, last_valid_height) = client
+        .get_latest_blockhash_with_commitment(client.commitment())
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    // The sender pays the fee and the new token account's rent in SOL
+    let fee = client
+        .get_fee_for_message(&Message::new_with_blockhash(
+            &instructions,
+            Some(&keypair.pubkey()),
+            &blockhash,
+        ))
         .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let ata_rent = if create_recipient_ata {
+        client
+            .get_minimum_balance_for_rent_exemption(mint_info.account_len)
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?
+    } else {
+        0
+    };
+    let sol_balance = client
+        .get_balance(&keypair.pubkey())
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let rent_minimum = client
+        .get_minimum_balance_for_rent_exemption(0)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    check_token_transfer_costs(sol_balance, fee, ata_rent, rent_minimum)?;
 
     // Create and sign transaction
     let transaction = Transaction::new_signed_with_payer(
@@ -187,18 +462,125 @@ pub fn send_token(
         &[keypair.keypair()],
         blockhash,
     );
-
-    // Send and confirm
-    let signature = client
-        .send_and_confirm_transaction(&transaction)
-        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+    let expected = simulate_effects(
+        &client,
+        &transaction,
+        &[
+            Watched::native(keypair.pubkey()),
+            Watched::token(from_ata, keypair.pubkey(), mint_pubkey),
+            Watched::token(to_ata, to_pubkey, mint_pubkey),
+        ],
+    )?;
+
+    let signature = submit(&client, keypair, &transaction, submission)?;
 
     Ok(TransactionResult {
         signature: signature.to_string(),
-        status: "confirmed".to_string(),
+        status: submission.status().to_string(),
+        amount,
+        transfer_fee: mint_info.transfer_fee(amount),
+        decimals: Some(mint_info.decimals),
+        expected: Some(expected),
+        last_valid_height,
     })
 }
 
+/// SOL-side rules for a token transfer: fee and recipient ATA rent must be
+/// covered without leaving the sender below rent exemption
+pub fn check_token_transfer_costs(
+    sol_balance: u64,
+    fee: u64,
+    ata_rent: u64,
+    rent_minimum: u64,
+) -> Result<(), TransactionError> {
+    let required = fee + ata_rent;
+    let remaining = sol_balance
+        .checked_sub(required)
+        .ok_or(TransactionError::InsufficientFeeBalance {
+            required,
+            available: sol_balance,
+        })?;
+
+    if remaining > 0 && remaining < rent_minimum {
+        return Err(TransactionError::BelowRentExemption {
+            remaining,
+            minimum: rent_minimum,
+        });
+    }
+
+    Ok(())
+}
+
+/// Instructions for a token transfer under the mint's program, optionally
+/// creating the recipient's associated token account (paid for by the sender).
+/// Mints with a transfer fee get `TransferCheckedWithFee`, so the transfer
+/// fails rather than charging more than was quoted.
+pub fn token_transfer_instructions(
+    owner: &Pubkey,
+    to: &Pubkey,
+    mint: &Pubkey,
+    mint_info: &MintInfo,
+    amount: u64,
+    create_recipient_ata: bool,
+) -> Result<Vec<Instruction>, TransactionError> {
+    let program_id = &mint_info.program_id;
+    let from_ata = get_associated_token_address_with_program_id(owner, mint, program_id);
+    let to_ata = get_associated_token_address_with_program_id(to, mint, program_id);
+
+    let mut instructions = Vec::new();
+
+    if create_recipient_ata {
+        instructions.push(
+            spl_associated_token_account::instruction::create_associated_token_account(
+                owner, to, mint, program_id,
+            ),
+        );
+    }
+
+    let has_transfer_fee = mint_info
+        .extensions
+        .as_ref()
+        .is_some_and(|e| e.transfer_fee.is_some());
+    let transfer = if !mint_info.is_token_2022() {
+        token_instruction::transfer_checked(
+            program_id,
+            &from_ata,
+            mint,
+            &to_ata,
+            owner,
+            &[],
+            amount,
+            mint_info.decimals,
+        )
+    } else if has_transfer_fee {
+        spl_token_2022::extension::transfer_fee::instruction::transfer_checked_with_fee(
+            program_id,
+            &from_ata,
+            mint,
+            &to_ata,
+            owner,
+            &[],
+            amount,
+            mint_info.decimals,
+            mint_info.transfer_fee(amount),
+        )
+    } else {
+        spl_token_2022::instruction::transfer_checked(
+            program_id,
+            &from_ata,
+            mint,
+            &to_ata,
+            owner,
+            &[],
+            amount,
+            mint_info.decimals,
+        )
+    };
+    instructions.push(transfer.map_err(|e| TransactionError::TransactionFailed(e.to_string()))?);
+
+    Ok(instructions)
+}
+
 /// Get transaction history for an address
 pub fn get_transaction_history(
     rpc_url: &str,
@@ -236,6 +618,36 @@ pub fn get_transaction_history(
     Ok(history)
 }
 
+/// Whether the address appears in any transaction
+pub fn has_activity(rpc_url: &str, address: &str) -> Result<bool, TransactionError> {
+    let client = RpcClient::new(rpc_url.to_string());
+    let pubkey: Pubkey = address
+        .parse()
+        .map_err(|_| TransactionError::InvalidAddress(address.to_string()))?;
+
+    let signatures = client
+        .get_signatures_for_address_with_config(
+            &pubkey,
+            GetConfirmedSignaturesForAddress2Config {
+                limit: Some(1),
+                ..Default::default()
+            },
+        )
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    Ok(!signatures.is_empty())
+}
+
+/// Whether the address appears in any transaction (async version)
+pub async fn has_activity_async(rpc_url: &str, address: &str) -> Result<bool, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let address = address.to_string();
+
+    tokio::task::spawn_blocking(move || has_activity(&rpc_url, &address))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
 /// Get transaction history (async version)
 pub async fn get_transaction_history_async(
     rpc_url: &str,
@@ -250,6 +662,85 @@ pub async fn get_transaction_history_async(
         .map_err(|e| TransactionError::RpcError(e.to_string()))?
 }
 
+/// Current block height, which blockhash expiry is measured against
+pub fn get_block_height(rpc_url: &str) -> Result<u64, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    client
+        .get_block_height()
+        .map_err(|e| TransactionError::RpcError(e.to_string()))
+}
+
+/// Get current block height (async version)
+pub async fn get_block_height_async(rpc_url: &str) -> Result<u64, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+
+    tokio::task::spawn_blocking(move || get_block_height(&rpc_url))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Ask the cluster's faucet to fund `address` with `lamports`; only devnet
+/// and testnet serve airdrops. Returns the airdrop signature.
+pub fn request_airdrop(rpc_url: &str, address: &str, lamports: u64) -> Result<String, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let pubkey: Pubkey = address
+        .parse()
+        .map_err(|_| TransactionError::InvalidAddress(address.to_string()))?;
+
+    let signature = client
+        .request_airdrop(&pubkey, lamports)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    Ok(signature.to_string())
+}
+
+/// Ask the cluster's faucet to fund `address` (async version)
+pub async fn request_airdrop_async(
+    rpc_url: &str,
+    address: &str,
+    lamports: u64,
+) -> Result<String, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let address = address.to_string();
+
+    tokio::task::spawn_blocking(move || request_airdrop(&rpc_url, &address, lamports))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Sign a base64 serialized transaction built elsewhere, such as the
+/// source step of a cross-chain route, as its fee payer and send it.
+/// Returns the signature.
+pub fn sign_and_send_serialized(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    encoded: &str,
+) -> Result<String, TransactionError> {
+    use base64::Engine;
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| TransactionError::TransactionFailed(format!("bad transaction: {}", e)))?;
+    let mut tx: VersionedTransaction = bincode::deserialize(&bytes)
+        .map_err(|e| TransactionError::TransactionFailed(format!("bad transaction: {}", e)))?;
+    if tx.message.static_account_keys().first() != Some(&keypair.pubkey()) {
+        return Err(TransactionError::InvalidAddress(format!(
+            "transaction is not paid by {}",
+            keypair.address()
+        )));
+    }
+
+    let required = tx.message.header().num_required_signatures as usize;
+    tx.signatures.resize(required.max(1), Signature::default());
+    tx.signatures[0] = keypair.sign(&tx.message.serialize());
+
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    client
+        .send_transaction(&tx)
+        .map(|signature| signature.to_string())
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))
+}
+
 /// Transaction info from history
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionInfo {
@@ -260,14 +751,132 @@ pub struct TransactionInfo {
     pub memo: Option<String>,
 }
 
-/// Estimate transaction fee
-pub fn estimate_fee(rpc_url: &str) -> Result<u64, TransactionError> {
-    let client = RpcClient::new(rpc_url.to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::solana::balance::parse_mint;
+    use solana_sdk::program_pack::Pack;
+
+    const RENT_MIN: u64 = 890_880;
+    const FEE: u64 = 5_000;
+
+    #[test]
+    fn test_payment_markers() {
+        let (from, to, reference) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut instructions = vec![system_instruction::transfer(&from, &to, 1)];
+        PaymentMarkers {
+            memo: Some("order-42".to_string()),
+            references: vec![reference],
+        }
+        .apply(&mut instructions);
+
+        // The memo precedes the transfer, which carries the reference
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].program_id.to_string(), MEMO_PROGRAM_ID);
+        assert_eq!(instructions[0].data, b"order-42");
+        let reference_meta = instructions[1].accounts.last().unwrap();
+        assert_eq!(reference_meta.pubkey, reference);
+        assert!(!reference_meta.is_signer && !reference_meta.is_writable);
+
+        let mut bare = vec![system_instruction::transfer(&from, &to, 1)];
+        PaymentMarkers::default().apply(&mut bare);
+        assert_eq!(bare[0].accounts.len(), 2);
+    }
 
-    // Get fee for a simple transfer (5000 lamports is typical)
-    let fee = client
-        .get_fee_for_message(&solana_sdk::message::Message::default())
-        .unwrap_or(5000);
+    #[test]
+    fn test_sol_transfer_rent_guard() {
+        // Leaving a dust balance below rent exemption is rejected
+        assert!(matches!(
+            check_sol_transfer(1_000_000, 500_000, FEE, RENT_MIN, true),
+            Err(TransactionError::BelowRentExemption { remaining: 495_000, .. })
+        ));
+        // Emptying the account completely is allowed
+        let all = max_sendable_sol(1_000_000, FEE);
+        assert!(check_sol_transfer(1_000_000, all, FEE, RENT_MIN, true).is_ok());
+        // New recipients must receive at least the rent-exempt minimum
+        assert!(matches!(
+            check_sol_transfer(10_000_000, 1_000, FEE, RENT_MIN, false),
+            Err(TransactionError::RecipientBelowRentExemption { .. })
+        ));
+        assert!(matches!(
+            check_sol_transfer(1_000, 1_000, FEE, RENT_MIN, true),
+            Err(TransactionError::InsufficientBalance { required: 6_000, available: 1_000 })
+        ));
+    }
+
+    #[test]
+    fn test_token_transfer_costs() {
+        assert!(check_token_transfer_costs(10_000_000, FEE, 2_039_280, RENT_MIN).is_ok());
+        assert!(matches!(
+            check_token_transfer_costs(1_000_000, FEE, 2_039_280, RENT_MIN),
+            Err(TransactionError::InsufficientFeeBalance { required: 2_044_280, .. })
+        ));
+    }
+
+    /// Token-2022 mint with 6 decimals and a transfer fee
+    fn fee_mint(basis_points: u16, maximum_fee: u64) -> Vec<u8> {
+        use spl_token_2022::extension::transfer_fee::{self, TransferFeeConfig};
+        use spl_token_2022::extension::{
+            BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+        };
+
+        let len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let mut data = vec![0; len];
+        let mut mint =
+            StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data)
+                .unwrap();
+        let fee = transfer_fee::TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: basis_points.into(),
+        };
+        let config = mint.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+        mint.base = spl_token_2022::state::Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        data
+    }
 
-    Ok(fee)
+    #[test]
+    fn test_token_2022_transfer_fee() {
+        let info = parse_mint(&spl_token_2022::id(), &fee_mint(50, 1_000), 7).unwrap();
+        assert!(info.is_token_2022());
+        assert_eq!(info.decimals, 6);
+        // Fee-bearing accounts are larger than plain SPL token accounts
+        assert!(info.account_len > spl_token::state::Account::LEN);
+
+        // 0.5% rounded up, capped at the maximum
+        assert_eq!(info.transfer_fee(1_001), 6);
+        assert_eq!(info.transfer_fee(10_000_000), 1_000);
+        assert_eq!(info.transfer_fee(0), 0);
+
+        let (owner, to, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let instructions = token_transfer_instructions(&owner, &to, &mint, &info, 1_001, true).unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions.iter().skip(1).all(|ix| ix.program_id == spl_token_2022::id()));
+        // TransferFeeExtension / TransferCheckedWithFee
+        assert_eq!(&instructions[1].data[..2], &[26, 1]);
+
+        // Plain SPL mints keep the original program and instruction
+        let mut spl_mint = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            decimals: 9,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut spl_mint);
+        let info = parse_mint(&spl_token::id(), &spl_mint, 0).unwrap();
+        assert_eq!(info.transfer_fee(1_000), 0);
+        let instructions = token_transfer_instructions(&owner, &to, &mint, &info, 1_000, false).unwrap();
+        assert_eq!(instructions[0].program_id, spl_token::id());
+    }
 }
@@ -8,7 +8,7 @@ use solana_sdk::{
 };
 use thiserror::Error;
 
-use crate::core::{derive_solana_keypair, SecureSeed};
+use crate::core::{derive_solana_from_path, derive_solana_keypair, SecureSeed};
 
 #[derive(Debug, Error)]
 pub enum SolanaWalletError {
@@ -49,6 +49,14 @@ impl SolanaKeypair {
         Self::from_signing_key(&signing_key)
     }
 
+    /// Derive keypair from seed at an account's recorded path
+    pub fn derive_path(seed: &SecureSeed, path: &str) -> Result<Self, SolanaWalletError> {
+        let (signing_key, _) = derive_solana_from_path(seed, path)
+            .map_err(|e| SolanaWalletError::DerivationFailed(e.to_string()))?;
+
+        Self::from_signing_key(&signing_key)
+    }
+
     /// Get public key
     pub fn pubkey(&self) -> Pubkey {
         self.keypair.pubkey()
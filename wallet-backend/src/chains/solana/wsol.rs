@@ -0,0 +1,235 @@
+//! Wrapped SOL (wSOL)
+//!
+//! Wrapping moves lamports into the owner's wSOL associated token account and
+//! syncs its token balance; unwrapping closes that account, returning the
+//! wrapped lamports and its rent to the owner.
+
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, instruction::Instruction, message::Message,
+    program_pack::Pack, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::native_mint;
+
+use super::transaction::{check_sol_transfer, TransactionError};
+use super::wallet::SolanaKeypair;
+
+/// Result of a wrap or unwrap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapResult {
+    pub signature: String,
+    /// wSOL token account
+    pub token_account: String,
+    /// Lamports wrapped, or returned to the owner by an unwrap (including
+    /// the token account's rent)
+    pub amount: u64,
+}
+
+/// Owner's wSOL associated token account
+pub fn wsol_account(owner: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, &native_mint::id())
+}
+
+/// Instructions that wrap `lamports`, creating the wSOL account if needed
+pub fn wrap_instructions(owner: &Pubkey, lamports: u64) -> Result<Vec<Instruction>, TransactionError> {
+    let account = wsol_account(owner);
+    Ok(vec![
+        create_associated_token_account_idempotent(
+            owner,
+            owner,
+            &native_mint::id(),
+            &spl_token::id(),
+        ),
+        system_instruction::transfer(owner, &account, lamports),
+        spl_token::instruction::sync_native(&spl_token::id(), &account)
+            .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?,
+    ])
+}
+
+/// Instruction that closes the wSOL account back into the owner
+pub fn unwrap_instruction(owner: &Pubkey) -> Result<Instruction, TransactionError> {
+    spl_token::instruction::close_account(
+        &spl_token::id(),
+        &wsol_account(owner),
+        owner,
+        owner,
+        &[],
+    )
+    .map_err(|e| TransactionError::TransactionFailed(e.to_string()))
+}
+
+/// Wrap `lamports` of the keypair's SOL. The same rent rules as a SOL send
+/// apply, with the wSOL account's rent counted when it has to be created.
+pub fn wrap_sol(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    lamports: u64,
+) -> Result<WrapResult, TransactionError> {
+    if lamports == 0 {
+        return Err(TransactionError::InvalidAmount);
+    }
+
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let owner = keypair.pubkey();
+    let account = wsol_account(&owner);
+
+    let instructions = wrap_instructions(&owner, lamports)?;
+    let blockhash = client
+        .get_latest_blockhash()
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let fee = client
+        .get_fee_for_message(&Message::new_with_blockhash(
+            &instructions,
+            Some(&owner),
+            &blockhash,
+        ))
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    let account_rent = if client.get_account(&account).is_err() {
+        client
+            .get_minimum_balance_for_rent_exemption(spl_token::state::Account::LEN)
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?
+    } else {
+        0
+    };
+    let balance = client
+        .get_balance(&owner)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    let rent_minimum = client
+        .get_minimum_balance_for_rent_exemption(0)
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+    check_sol_transfer(balance, lamports + account_rent, fee, rent_minimum, true)?;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&owner),
+        &[keypair.keypair()],
+        blockhash,
+    );
+    let signature = client
+        .send_and_confirm_transaction(&transaction)
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+
+    Ok(WrapResult {
+        signature: signature.to_string(),
+        token_account: account.to_string(),
+        amount: lamports,
+    })
+}
+
+/// Unwrap all of the keypair's wSOL; `None` if it has no wSOL account
+pub fn unwrap_sol(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+) -> Result<Option<WrapResult>, TransactionError> {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let owner = keypair.pubkey();
+    let account = wsol_account(&owner);
+
+    let Ok(wsol) = client.get_account(&account) else {
+        return Ok(None);
+    };
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[unwrap_instruction(&owner)?],
+        Some(&owner),
+        &[keypair.keypair()],
+        client
+            .get_latest_blockhash()
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?,
+    );
+    let signature = client
+        .send_and_confirm_transaction(&transaction)
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+
+    Ok(Some(WrapResult {
+        signature: signature.to_string(),
+        token_account: account.to_string(),
+        amount: wsol.lamports,
+    }))
+}
+
+/// Wait for a swap to confirm, then unwrap whatever wSOL it left behind
+pub fn unwrap_sol_after(
+    rpc_url: &str,
+    keypair: &SolanaKeypair,
+    signature: &str,
+) -> Result<Option<WrapResult>, TransactionError> {
+    let signature: Signature = signature
+        .parse()
+        .map_err(|_| TransactionError::TransactionFailed(format!("bad signature {}", signature)))?;
+    RpcClient::new(rpc_url.to_string())
+        .poll_for_signature_with_commitment(&signature, CommitmentConfig::confirmed())
+        .map_err(|e| TransactionError::TransactionFailed(e.to_string()))?;
+
+    unwrap_sol(rpc_url, keypair)
+}
+
+/// Wrap SOL (async version)
+pub async fn wrap_sol_async(
+    rpc_url: &str,
+    keypair: SolanaKeypair,
+    lamports: u64,
+) -> Result<WrapResult, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+
+    tokio::task::spawn_blocking(move || wrap_sol(&rpc_url, &keypair, lamports))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Unwrap SOL (async version)
+pub async fn unwrap_sol_async(
+    rpc_url: &str,
+    keypair: SolanaKeypair,
+) -> Result<Option<WrapResult>, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+
+    tokio::task::spawn_blocking(move || unwrap_sol(&rpc_url, &keypair))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+/// Unwrap after a swap (async version)
+pub async fn unwrap_sol_after_async(
+    rpc_url: &str,
+    keypair: SolanaKeypair,
+    signature: &str,
+) -> Result<Option<WrapResult>, TransactionError> {
+    let rpc_url = rpc_url.to_string();
+    let signature = signature.to_string();
+
+    tokio::task::spawn_blocking(move || unwrap_sol_after(&rpc_url, &keypair, &signature))
+        .await
+        .map_err(|e| TransactionError::RpcError(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_unwrap_instructions() {
+        let owner = Pubkey::new_unique();
+        let account = wsol_account(&owner);
+
+        let wrap = wrap_instructions(&owner, 1_000_000).unwrap();
+        assert_eq!(wrap.len(), 3);
+        assert_eq!(wrap[0].program_id, spl_associated_token_account::id());
+        // Lamports go straight into the token account, then get synced
+        assert!(wrap[1].accounts.iter().any(|a| a.pubkey == account && a.is_writable));
+        assert_eq!(wrap[2].program_id, spl_token::id());
+        assert_eq!(wrap[2].accounts[0].pubkey, account);
+
+        let unwrap = unwrap_instruction(&owner).unwrap();
+        assert_eq!(unwrap.accounts[0].pubkey, account);
+        // Rent and wrapped lamports go back to the owner
+        assert_eq!(unwrap.accounts[1].pubkey, owner);
+    }
+}
@@ -0,0 +1,922 @@
+//! Typed application configuration loaded from the environment
+//!
+//! Every variable is read and validated once at startup. Problems are collected
+//! rather than returned one at a time, so a misconfigured deployment reports
+//! every invalid field in a single run.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::core::Chain;
+use crate::storage::models::AuditSeverity;
+
+use super::oauth::{OAuthConfig, OAuthProvider, OAuthProviderConfig};
+use super::security::Profile;
+use super::{ProvisionConfig, SecurityConfig};
+
+/// Minimum JWT secret length (256 bits of ASCII)
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+/// Chain ID of the Sepolia test network
+pub const SEPOLIA_CHAIN_ID: u64 = 11_155_111;
+
+/// Rate limiting settings
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Requests allowed per client IP per window
+    pub max_requests: u32,
+    pub window: Duration,
+}
+
+/// Request body limits, in bytes
+#[derive(Debug, Clone)]
+pub struct BodyLimitConfig {
+    /// Login, registration and other credentials-only endpoints
+    pub auth_bytes: usize,
+    pub default_bytes: usize,
+    /// Imports, batches and keyfile uploads
+    pub upload_bytes: usize,
+    /// Ceiling for every endpoint
+    pub max_bytes: usize,
+    /// Deepest JSON nesting accepted
+    pub max_json_depth: usize,
+}
+
+/// Multi-tenant deployment settings
+#[derive(Debug, Clone)]
+pub struct TenancyConfig {
+    /// Resolve a tenant for every request; off means a single implicit tenant
+    pub enabled: bool,
+    /// Bearer token for the tenant administration API, which is disabled
+    /// when unset
+    pub admin_token: Option<String>,
+}
+
+/// Test-network faucet settings
+#[derive(Debug, Clone)]
+pub struct FaucetConfig {
+    /// Serve the faucet endpoint; meant for devnet / Sepolia deployments
+    pub enabled: bool,
+    /// Sepolia faucet API ETH requests are posted to; Ethereum funding is
+    /// unavailable when unset
+    pub sepolia_url: Option<String>,
+    /// Lamports requested per Solana airdrop
+    pub sol_lamports: u64,
+    /// Wei requested from the Sepolia faucet
+    pub eth_wei: u128,
+    /// Requests allowed per address and chain over a rolling day
+    pub daily_limit: u32,
+}
+
+/// Line format of exported security events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiemFormat {
+    /// One JSON object per event
+    Json,
+    /// ArcSight Common Event Format
+    Cef,
+    /// RFC 5424 syslog with the JSON event as message
+    Syslog,
+}
+
+impl FromStr for SiemFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SiemFormat::Json),
+            "cef" => Ok(SiemFormat::Cef),
+            "syslog" => Ok(SiemFormat::Syslog),
+            _ => Err(format!("'{}' is not json, cef or syslog", s)),
+        }
+    }
+}
+
+/// Security event (SIEM) export settings
+#[derive(Debug, Clone)]
+pub struct SiemConfig {
+    /// Collector each batch of events is POSTed to
+    pub url: Option<String>,
+    /// File events are appended to, one per line
+    pub file: Option<String>,
+    pub format: SiemFormat,
+    /// Events less severe than this are not exported
+    pub min_severity: AuditSeverity,
+    /// How often queued events are delivered
+    pub interval: Duration,
+}
+
+impl SiemConfig {
+    /// Whether events are exported anywhere
+    pub fn enabled(&self) -> bool {
+        self.url.is_some() || self.file.is_some()
+    }
+}
+
+/// Database maintenance settings
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    /// How often expired rows are pruned and the database optimized
+    pub interval: Duration,
+    /// Days expired or revoked sessions are kept, as login history
+    pub session_retention_days: u32,
+    /// Days a Solana NFT cache entry may go without being refreshed
+    pub nft_cache_retention_days: u32,
+    /// Days reconciliation runs and faucet requests are kept
+    pub job_retention_days: u32,
+    /// Free space the database file must hold before it is vacuumed
+    pub vacuum_min_free_bytes: u64,
+}
+
+/// Suspicious login detection settings
+#[derive(Debug, Clone)]
+pub struct LoginRiskConfig {
+    /// Risk score (0-100) at which a password login needs step-up
+    /// verification
+    pub step_up_score: u8,
+    /// Header a trusted proxy puts the client's ISO country code in, e.g.
+    /// `cf-ipcountry`; countries aren't compared when unset
+    pub country_header: Option<String>,
+    /// Mail relay verification links are POSTed to; step-up can't be
+    /// required when unset
+    pub verification_webhook_url: Option<String>,
+    /// Frontend page verification links open, given `challenge` and `token`
+    pub verification_url: String,
+}
+
+/// Application configuration
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub database_url: String,
+    pub db_max_connections: u32,
+    pub db_acquire_timeout: Duration,
+    /// Read-only copies of the database that history and reporting reads
+    /// are spread over
+    pub database_replica_urls: Vec<String>,
+    /// How often read replicas are checked; an unhealthy one is skipped
+    pub db_replica_check_interval: Duration,
+    pub port: u16,
+    pub grpc_port: u16,
+    pub jwt_secret: String,
+    /// Operator secret contact names and notes and transaction memos are
+    /// encrypted under; stored in plaintext when unset
+    pub field_encryption_key: Option<String>,
+    /// Secret share link tokens are signed with, apart from the JWT keys so
+    /// rotating those leaves links working; share links are off when unset
+    pub share_link_secret: Option<String>,
+    pub solana_rpc_url: String,
+    pub eth_rpc_url: String,
+    /// Network Safe multi-sig payloads are signed for
+    pub eth_chain_id: u64,
+    /// Base URL of the CoinGecko-compatible price API
+    pub price_api_url: String,
+    /// Oldest price quote accepted when converting fiat amounts
+    pub price_max_age: Duration,
+    /// How often history rows are priced at their transaction date
+    pub price_backfill_interval: Duration,
+    /// Upper-case ISO 4217 code history is priced in
+    pub reporting_currency: String,
+    /// Base URL of the SNS SDK proxy used for `.sol` lookups
+    pub sns_api_url: String,
+    /// How long resolved contact identities are cached before re-resolving
+    pub identity_refresh_interval: Duration,
+    /// Jupiter token list used for token search, swap mint checks and
+    /// balance labels
+    pub token_list_url: String,
+    /// How often the token list is fetched again
+    pub token_list_refresh_interval: Duration,
+    /// How often balance alerts are evaluated
+    pub alert_check_interval: Duration,
+    /// How often sent transactions are checked against their simulated effects
+    pub tx_reconcile_interval: Duration,
+    /// How often pending sends are checked for being stuck
+    pub stuck_check_interval: Duration,
+    /// How often scheduled sends are checked for being due
+    pub scheduled_check_interval: Duration,
+    /// How often L2 bridge deposits are checked for landing and finalizing
+    pub bridge_check_interval: Duration,
+    /// Base URL of the LI.FI-compatible API cross-chain swaps are routed by
+    pub cross_chain_api_url: String,
+    /// How often cross-chain swaps are checked for progress
+    pub cross_chain_check_interval: Duration,
+    /// How often cached balances are reconciled with the chain
+    pub reconciliation_interval: Duration,
+    /// Blocks an Ethereum send may stay pending before it is flagged as stuck
+    pub eth_stuck_blocks: u64,
+    /// Private relay MEV-protected Ethereum sends are broadcast through
+    pub mev_protect_rpc_url: String,
+    /// Status API of the private relay, queried with `/<tx hash>`
+    pub mev_protect_status_url: String,
+    /// Blocks a private send may wait before it is broadcast publicly
+    pub mev_protect_timeout_blocks: u64,
+    /// Jito block-engine endpoint Solana bundles are submitted to
+    pub jito_block_engine_url: String,
+    /// Lamports tipped to Jito with each bundle
+    pub jito_tip_lamports: u64,
+    /// Confirmations a Solana send needs before it is reported confirmed
+    pub solana_confirmations: u64,
+    /// Confirmations an Ethereum send needs before it is reported confirmed
+    pub eth_confirmations: u64,
+    /// Frontend origin wallet sign-in messages are issued for; its host is
+    /// the message domain
+    pub sign_in_uri: String,
+    /// Chains accounts may be created on
+    pub enabled_chains: Vec<Chain>,
+    /// Refuse wallet unlocks that don't redeem a challenge nonce. On unless
+    /// turned off for development; production can't turn it off
+    pub unlock_challenge_required: bool,
+    /// Let account RPC overrides point at loopback, private and link-local
+    /// hosts, for a node on the operator's own network
+    pub account_rpc_allow_private: bool,
+    /// Days a confirmed account deletion waits before it is purged; logging
+    /// in within them cancels it
+    pub account_deletion_grace_days: u32,
+    /// Upper bound on handling time for a single HTTP request
+    pub request_timeout: Duration,
+    /// Accounts read at once by `GET /accounts/balances`
+    pub balance_fetch_concurrency: usize,
+    /// How long `GET /accounts/balances` waits for balances before returning
+    /// the ones it has
+    pub balance_fetch_deadline: Duration,
+    pub rate_limit: RateLimitConfig,
+    pub body_limit: BodyLimitConfig,
+    pub tenancy: TenancyConfig,
+    pub faucet: FaucetConfig,
+    pub siem: SiemConfig,
+    pub login_risk: LoginRiskConfig,
+    pub maintenance: MaintenanceConfig,
+    pub oauth: OAuthConfig,
+    pub security: SecurityConfig,
+    /// Error reporting destination (used with the `sentry` feature)
+    pub sentry_dsn: Option<String>,
+    /// Unlock (or import) the wallet at startup; `None` leaves it locked
+    pub provision: Option<ProvisionConfig>,
+    /// Seed a demo user, wallet, contacts and history at startup; debug
+    /// builds only
+    pub dev_mode: bool,
+}
+
+/// Every invalid configuration field found at startup
+#[derive(Debug)]
+pub struct ConfigReport {
+    pub errors: Vec<(&'static str, String)>,
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid configuration ({} problem(s)):", self.errors.len())?;
+        for (field, message) in &self.errors {
+            writeln!(f, "  - {}: {}", field, message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigReport {}
+
+impl Config {
+    /// Load and validate configuration from environment variables
+    pub fn from_env() -> Result<Self, ConfigReport> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Load from an arbitrary variable lookup (the environment in production)
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigReport> {
+        let mut env = EnvReader {
+            lookup: &lookup,
+            errors: Vec::new(),
+        };
+
+        let database_url = env.string("DATABASE_URL", "sqlite:./wallet.db?mode=rwc");
+        let db_max_connections = env.parse_in("DB_MAX_CONNECTIONS", 5u32, 1..=100);
+        let db_acquire_timeout_secs = env.parse_in("DB_ACQUIRE_TIMEOUT_SECS", 3u64, 1..=60);
+        let database_replica_urls = env
+            .get("DATABASE_REPLICA_URLS")
+            .map(|urls| {
+                urls.split(',')
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let db_replica_check_secs = env.parse_in("DB_REPLICA_CHECK_SECS", 10u64, 1..=3_600);
+        let port = env.parse_in("PORT", 8080u16, 1..=u16::MAX);
+        let grpc_port = env.parse_in("GRPC_PORT", 50051u16, 1..=u16::MAX);
+        let solana_rpc_url = env.url("SOLANA_RPC_URL", "https://api.devnet.solana.com");
+        let eth_rpc_url = env.url("ETH_RPC_URL", "https://ethereum-sepolia-rpc.publicnode.com");
+        let eth_chain_id = env.parse_in("ETH_CHAIN_ID", SEPOLIA_CHAIN_ID, 1..=u64::MAX);
+        let price_api_url = env.url("PRICE_API_URL", "https://api.coingecko.com/api/v3");
+        let price_max_age_secs = env.parse_in("PRICE_MAX_AGE_SECS", 120u64, 1..=3_600);
+        let price_backfill_secs = env.parse_in("PRICE_BACKFILL_SECS", 300u64, 10..=86_400);
+        let reporting_currency = env.string("REPORTING_CURRENCY", "USD").to_uppercase();
+        let sns_api_url = env.url("SNS_API_URL", "https://sns-sdk-proxy.bonfida.workers.dev");
+        let identity_refresh_secs =
+            env.parse_in("IDENTITY_REFRESH_SECS", 86_400u64, 60..=604_800);
+        let token_list_url = env.url("TOKEN_LIST_URL", "https://tokens.jup.ag/tokens?tags=verified");
+        let token_list_refresh_secs =
+            env.parse_in("TOKEN_LIST_REFRESH_SECS", 21_600u64, 300..=604_800);
+        let alert_check_secs = env.parse_in("ALERT_CHECK_SECS", 60u64, 10..=86_400);
+        let tx_reconcile_secs = env.parse_in("TX_RECONCILE_SECS", 30u64, 5..=3_600);
+        let stuck_check_secs = env.parse_in("STUCK_CHECK_SECS", 60u64, 10..=3_600);
+        let scheduled_check_secs = env.parse_in("SCHEDULED_CHECK_SECS", 15u64, 1..=3_600);
+        let bridge_check_secs = env.parse_in("BRIDGE_CHECK_SECS", 60u64, 10..=3_600);
+        let cross_chain_api_url = env.url("CROSS_CHAIN_API_URL", "https://li.quest/v1");
+        let cross_chain_check_secs = env.parse_in("CROSS_CHAIN_CHECK_SECS", 30u64, 5..=3_600);
+        let reconciliation_secs =
+            env.parse_in("RECONCILIATION_INTERVAL_SECS", 86_400u64, 3_600..=604_800);
+        let eth_stuck_blocks = env.parse_in("ETH_STUCK_BLOCKS", 25u64, 1..=10_000);
+        let mev_protect_rpc_url =
+            env.url("MEV_PROTECT_RPC_URL", "https://rpc-sepolia.flashbots.net");
+        let mev_protect_status_url =
+            env.url("MEV_PROTECT_STATUS_URL", "https://protect-sepolia.flashbots.net/tx");
+        let mev_protect_timeout_blocks =
+            env.parse_in("MEV_PROTECT_TIMEOUT_BLOCKS", 25u64, 1..=1_000);
+        let jito_block_engine_url = env.url(
+            "JITO_BLOCK_ENGINE_URL",
+            "https://mainnet.block-engine.jito.wtf/api/v1/bundles",
+        );
+        // Jito ignores bundles tipping less than 1000 lamports
+        let jito_tip_lamports =
+            env.parse_in("JITO_TIP_LAMPORTS", 10_000u64, 1_000..=100_000_000);
+        let solana_confirmations = env.parse_in("SOLANA_CONFIRMATIONS", 1u64, 1..=32);
+        let eth_confirmations = env.parse_in("ETH_CONFIRMATIONS", 12u64, 1..=1_000);
+        let sign_in_uri = env.url("SIGN_IN_URI", "http://localhost:3000");
+        let oauth_redirect_uri =
+            env.url("OAUTH_REDIRECT_URI", "http://localhost:3000/auth/callback");
+        let oauth_providers = env.oauth_providers();
+        let enabled_chains = env.chains("ENABLED_CHAINS");
+        let sentry_dsn = env.optional_url("SENTRY_DSN");
+        let unlock_challenge_required = env.flag("UNLOCK_CHALLENGE_REQUIRED", true);
+        let account_rpc_allow_private = env.flag("ACCOUNT_RPC_ALLOW_PRIVATE", false);
+        let account_deletion_grace_days =
+            env.parse_in("ACCOUNT_DELETION_GRACE_DAYS", 30u32, 0..=365);
+        let request_timeout_secs = env.parse_in("REQUEST_TIMEOUT_SECS", 30u64, 1..=600);
+        let balance_fetch_concurrency = env.parse_in("BALANCE_FETCH_CONCURRENCY", 8usize, 1..=64);
+        let balance_fetch_deadline_ms =
+            env.parse_in("BALANCE_FETCH_DEADLINE_MS", 5_000u64, 100..=60_000);
+        let rate_limit_enabled = env.flag("RATE_LIMIT_ENABLED", false);
+        let rate_limit_max = env.parse_in("RATE_LIMIT_MAX_REQUESTS", 100u32, 1..=100_000);
+        let rate_limit_window_secs = env.parse_in("RATE_LIMIT_WINDOW_SECS", 60u64, 1..=86_400);
+        let body_limit_auth = env.parse_in("BODY_LIMIT_AUTH_BYTES", 16_384usize, 1_024..=1_048_576);
+        let body_limit_default = env.parse_in("BODY_LIMIT_BYTES", 262_144usize, 1_024..=67_108_864);
+        let body_limit_upload =
+            env.parse_in("BODY_LIMIT_UPLOAD_BYTES", 5_242_880usize, 1_024..=67_108_864);
+        let body_limit_max = env.parse_in("BODY_LIMIT_MAX_BYTES", 8_388_608usize, 1_024..=67_108_864);
+        let json_max_depth = env.parse_in("JSON_MAX_DEPTH", 32usize, 2..=128);
+        let multi_tenant = env.flag("MULTI_TENANT", false);
+        let dev_mode = env.flag("DEV_MODE", false);
+        let faucet_enabled = env.flag("FAUCET_ENABLED", false);
+        let faucet_sepolia_url = env.optional_url("FAUCET_SEPOLIA_URL");
+        let faucet_sol_lamports =
+            env.parse_in("FAUCET_SOL_LAMPORTS", 1_000_000_000u64, 1..=5_000_000_000);
+        let faucet_eth_wei = env.parse_in(
+            "FAUCET_ETH_WEI",
+            50_000_000_000_000_000u128,
+            1..=10_000_000_000_000_000_000,
+        );
+        let faucet_daily_limit = env.parse_in("FAUCET_DAILY_LIMIT", 1u32, 1..=100);
+        let siem_url = env.optional_url("SIEM_EXPORT_URL");
+        let siem_file = env.get("SIEM_EXPORT_FILE");
+        let siem_format = env.parsed("SIEM_EXPORT_FORMAT", SiemFormat::Json);
+        let siem_min_severity = env.parsed("SIEM_MIN_SEVERITY", AuditSeverity::Info);
+        let siem_interval_secs = env.parse_in("SIEM_EXPORT_INTERVAL_SECS", 10u64, 1..=3_600);
+        let login_step_up_score = env.parse_in("LOGIN_STEP_UP_SCORE", 60u8, 1..=100);
+        let login_country_header = env.get("LOGIN_COUNTRY_HEADER").map(|h| h.to_lowercase());
+        let login_verification_webhook_url = env.optional_url("LOGIN_VERIFICATION_WEBHOOK_URL");
+        let login_verification_url =
+            env.url("LOGIN_VERIFICATION_URL", "http://localhost:3000/auth/verify-login");
+        let maintenance_secs = env.parse_in("MAINTENANCE_INTERVAL_SECS", 3_600u64, 60..=604_800);
+        let session_retention_days =
+            env.parse_in("MAINTENANCE_SESSION_RETENTION_DAYS", 30u32, 1..=3_650);
+        let nft_cache_retention_days =
+            env.parse_in("MAINTENANCE_NFT_CACHE_RETENTION_DAYS", 30u32, 1..=3_650);
+        let job_retention_days = env.parse_in("MAINTENANCE_JOB_RETENTION_DAYS", 90u32, 1..=3_650);
+        let vacuum_min_free_bytes =
+            env.parse_in("MAINTENANCE_VACUUM_MIN_FREE_BYTES", 16_777_216u64, 0..=u64::MAX);
+
+        let jwt_secret = match env.get("JWT_SECRET") {
+            Some(secret) if secret.len() >= MIN_JWT_SECRET_LEN => secret,
+            Some(_) => {
+                env.error(
+                    "JWT_SECRET",
+                    format!("must be at least {} characters", MIN_JWT_SECRET_LEN),
+                );
+                String::new()
+            }
+            None => {
+                env.error("JWT_SECRET", "must be set".to_string());
+                String::new()
+            }
+        };
+
+        let field_encryption_key = match env.get("FIELD_ENCRYPTION_KEY") {
+            Some(key) if key.len() < MIN_JWT_SECRET_LEN => {
+                env.error(
+                    "FIELD_ENCRYPTION_KEY",
+                    format!("must be at least {} characters", MIN_JWT_SECRET_LEN),
+                );
+                None
+            }
+            key => key,
+        };
+
+        let share_link_secret = match env.get("SHARE_LINK_SECRET") {
+            Some(secret) if secret.len() < MIN_JWT_SECRET_LEN => {
+                env.error(
+                    "SHARE_LINK_SECRET",
+                    format!("must be at least {} characters", MIN_JWT_SECRET_LEN),
+                );
+                None
+            }
+            secret => secret,
+        };
+
+        let tenant_admin_token = match env.get("TENANT_ADMIN_TOKEN") {
+            Some(token) if token.len() < MIN_JWT_SECRET_LEN => {
+                env.error(
+                    "TENANT_ADMIN_TOKEN",
+                    format!("must be at least {} characters", MIN_JWT_SECRET_LEN),
+                );
+                None
+            }
+            token => token,
+        };
+
+        if reporting_currency.len() != 3 || !reporting_currency.chars().all(|c| c.is_ascii_alphabetic()) {
+            env.error(
+                "REPORTING_CURRENCY",
+                format!("'{}' is not a currency code", reporting_currency),
+            );
+        }
+
+        if faucet_enabled && faucet_sepolia_url.is_some() && eth_chain_id != SEPOLIA_CHAIN_ID {
+            env.error(
+                "FAUCET_SEPOLIA_URL",
+                format!("needs ETH_CHAIN_ID {} (Sepolia)", SEPOLIA_CHAIN_ID),
+            );
+        }
+
+        if let Some(header) = &login_country_header {
+            if axum::http::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                env.error(
+                    "LOGIN_COUNTRY_HEADER",
+                    format!("'{}' is not a header name", header),
+                );
+            }
+        }
+
+        if port == grpc_port {
+            env.error("GRPC_PORT", "must differ from PORT".to_string());
+        }
+
+        let security = match SecurityConfig::from_lookup(&lookup) {
+            Ok(security) => Some(security),
+            Err(e) => {
+                env.error("security", e.to_string());
+                None
+            }
+        };
+
+        let provision = ProvisionConfig::from_lookup(&lookup).unwrap_or_else(|e| {
+            env.error("provision", e.to_string());
+            None
+        });
+
+        // The challenge endpoint's issuance cap is what bounds password
+        // guesses, so production can't skip it
+        if !unlock_challenge_required
+            && matches!(&security, Some(s) if s.profile == Profile::Production)
+        {
+            env.error(
+                "UNLOCK_CHALLENGE_REQUIRED",
+                "can't be turned off with APP_ENV=production".to_string(),
+            );
+        }
+
+        // The demo wallet's mnemonic and password are public, so it must
+        // never hold real funds
+        if dev_mode {
+            if !cfg!(debug_assertions) {
+                env.error("DEV_MODE", "is only available in debug builds".to_string());
+            }
+            if matches!(&security, Some(s) if s.profile == Profile::Production) {
+                env.error("DEV_MODE", "can't be used with APP_ENV=production".to_string());
+            }
+            if solana_rpc_url.contains("mainnet") || eth_chain_id == 1 {
+                env.error("DEV_MODE", "needs test-network RPC endpoints".to_string());
+            }
+            if provision.is_some() {
+                env.error("DEV_MODE", "can't be combined with wallet provisioning".to_string());
+            }
+        }
+
+        let errors = env.errors;
+        match security {
+            Some(security) if errors.is_empty() => Ok(Self {
+                database_url,
+                db_max_connections,
+                db_acquire_timeout: Duration::from_secs(db_acquire_timeout_secs),
+                database_replica_urls,
+                db_replica_check_interval: Duration::from_secs(db_replica_check_secs),
+                port,
+                grpc_port,
+                jwt_secret,
+                field_encryption_key,
+                share_link_secret,
+                solana_rpc_url,
+                eth_rpc_url,
+                eth_chain_id,
+                price_api_url,
+                price_max_age: Duration::from_secs(price_max_age_secs),
+                price_backfill_interval: Duration::from_secs(price_backfill_secs),
+                reporting_currency,
+                sns_api_url,
+                identity_refresh_interval: Duration::from_secs(identity_refresh_secs),
+                token_list_url,
+                token_list_refresh_interval: Duration::from_secs(token_list_refresh_secs),
+                alert_check_interval: Duration::from_secs(alert_check_secs),
+                tx_reconcile_interval: Duration::from_secs(tx_reconcile_secs),
+                stuck_check_interval: Duration::from_secs(stuck_check_secs),
+                scheduled_check_interval: Duration::from_secs(scheduled_check_secs),
+                bridge_check_interval: Duration::from_secs(bridge_check_secs),
+                cross_chain_api_url,
+                cross_chain_check_interval: Duration::from_secs(cross_chain_check_secs),
+                reconciliation_interval: Duration::from_secs(reconciliation_secs),
+                eth_stuck_blocks,
+                mev_protect_rpc_url,
+                mev_protect_status_url,
+                mev_protect_timeout_blocks,
+                jito_block_engine_url,
+                jito_tip_lamports,
+                solana_confirmations,
+                eth_confirmations,
+                sign_in_uri,
+                enabled_chains,
+                unlock_challenge_required,
+                account_rpc_allow_private,
+                account_deletion_grace_days,
+                request_timeout: Duration::from_secs(request_timeout_secs),
+                balance_fetch_concurrency,
+                balance_fetch_deadline: Duration::from_millis(balance_fetch_deadline_ms),
+                rate_limit: RateLimitConfig {
+                    enabled: rate_limit_enabled,
+                    max_requests: rate_limit_max,
+                    window: Duration::from_secs(rate_limit_window_secs),
+                },
+                body_limit: BodyLimitConfig {
+                    auth_bytes: body_limit_auth,
+                    default_bytes: body_limit_default,
+                    upload_bytes: body_limit_upload,
+                    max_bytes: body_limit_max,
+                    max_json_depth: json_max_depth,
+                },
+                tenancy: TenancyConfig {
+                    enabled: multi_tenant,
+                    admin_token: tenant_admin_token,
+                },
+                faucet: FaucetConfig {
+                    enabled: faucet_enabled,
+                    sepolia_url: faucet_sepolia_url,
+                    sol_lamports: faucet_sol_lamports,
+                    eth_wei: faucet_eth_wei,
+                    daily_limit: faucet_daily_limit,
+                },
+                siem: SiemConfig {
+                    url: siem_url,
+                    file: siem_file,
+                    format: siem_format,
+                    min_severity: siem_min_severity,
+                    interval: Duration::from_secs(siem_interval_secs),
+                },
+                login_risk: LoginRiskConfig {
+                    step_up_score: login_step_up_score,
+                    country_header: login_country_header,
+                    verification_webhook_url: login_verification_webhook_url,
+                    verification_url: login_verification_url,
+                },
+                maintenance: MaintenanceConfig {
+                    interval: Duration::from_secs(maintenance_secs),
+                    session_retention_days,
+                    nft_cache_retention_days,
+                    job_retention_days,
+                    vacuum_min_free_bytes,
+                },
+                oauth: OAuthConfig {
+                    redirect_uri: oauth_redirect_uri,
+                    providers: oauth_providers,
+                },
+                security,
+                sentry_dsn,
+                provision,
+                dev_mode,
+            }),
+            _ => Err(ConfigReport { errors }),
+        }
+    }
+
+    /// Whether accounts may be created on a chain
+    pub fn chain_enabled(&self, chain: Chain) -> bool {
+        self.enabled_chains.contains(&chain)
+    }
+}
+
+/// Reads variables, substituting defaults and recording every invalid value
+struct EnvReader<'a, F: Fn(&str) -> Option<String>> {
+    lookup: &'a F,
+    errors: Vec<(&'static str, String)>,
+}
+
+impl<F: Fn(&str) -> Option<String>> EnvReader<'_, F> {
+    fn get(&self, name: &str) -> Option<String> {
+        (self.lookup)(name)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+
+    fn error(&mut self, name: &'static str, message: String) {
+        self.errors.push((name, message));
+    }
+
+    fn string(&self, name: &str, default: &str) -> String {
+        self.get(name).unwrap_or_else(|| default.to_string())
+    }
+
+    fn parse_in<T>(&mut self, name: &'static str, default: T, range: std::ops::RangeInclusive<T>) -> T
+    where
+        T: FromStr + PartialOrd + Copy + fmt::Display,
+    {
+        let Some(raw) = self.get(name) else {
+            return default;
+        };
+
+        match raw.parse::<T>() {
+            Ok(value) if range.contains(&value) => value,
+            Ok(_) => {
+                self.error(
+                    name,
+                    format!("'{}' must be between {} and {}", raw, range.start(), range.end()),
+                );
+                default
+            }
+            Err(_) => {
+                self.error(name, format!("'{}' is not a valid value", raw));
+                default
+            }
+        }
+    }
+
+    fn parsed<T>(&mut self, name: &'static str, default: T) -> T
+    where
+        T: FromStr<Err = String>,
+    {
+        match self.get(name).map(|raw| raw.parse::<T>()) {
+            None => default,
+            Some(Ok(value)) => value,
+            Some(Err(e)) => {
+                self.error(name, e);
+                default
+            }
+        }
+    }
+
+    fn flag(&mut self, name: &'static str, default: bool) -> bool {
+        match self.get(name).map(|v| v.to_lowercase()).as_deref() {
+            None => default,
+            Some("1" | "true" | "yes" | "on") => true,
+            Some("0" | "false" | "no" | "off") => false,
+            Some(other) => {
+                self.error(name, format!("'{}' is not a boolean", other));
+                default
+            }
+        }
+    }
+
+    fn url(&mut self, name: &'static str, default: &str) -> String {
+        let value = self.string(name, default);
+        match reqwest::Url::parse(&value) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => value,
+            Ok(_) => {
+                self.error(name, format!("'{}' must be an http(s) URL", value));
+                value
+            }
+            Err(e) => {
+                self.error(name, format!("'{}' is not a valid URL: {}", value, e));
+                value
+            }
+        }
+    }
+
+    fn optional_url(&mut self, name: &'static str) -> Option<String> {
+        self.get(name)?;
+        Some(self.url(name, ""))
+    }
+
+    /// Providers with both a client ID and secret set
+    fn oauth_providers(&mut self) -> Vec<OAuthProviderConfig> {
+        let mut providers = Vec::new();
+        for provider in OAuthProvider::ALL {
+            let [id_var, secret_var, auth_var, token_var, userinfo_var] = provider.env_vars();
+            let [auth_url, token_url, userinfo_url] = provider.default_endpoints();
+
+            match (self.get(id_var), self.get(secret_var)) {
+                (Some(client_id), Some(client_secret)) => {
+                    providers.push(OAuthProviderConfig {
+                        provider,
+                        client_id,
+                        client_secret,
+                        auth_url: self.url(auth_var, auth_url),
+                        token_url: self.url(token_var, token_url),
+                        userinfo_url: self.url(userinfo_var, userinfo_url),
+                    })
+                }
+                (None, None) => {}
+                (Some(_), None) => self.error(secret_var, format!("must be set with {}", id_var)),
+                (None, Some(_)) => self.error(id_var, format!("must be set with {}", secret_var)),
+            }
+        }
+        providers
+    }
+
+    fn chains(&mut self, name: &'static str) -> Vec<Chain> {
+        let Some(raw) = self.get(name) else {
+            return vec![Chain::Solana, Chain::Ethereum];
+        };
+
+        let mut chains = Vec::new();
+        for entry in raw.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match entry.parse::<Chain>() {
+                Ok(chain) if !chains.contains(&chain) => chains.push(chain),
+                Ok(_) => {}
+                Err(e) => self.error(name, e),
+            }
+        }
+        if chains.is_empty() {
+            self.error(name, "must list at least one chain".to_string());
+        }
+        chains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(vars: &[(&str, &str)]) -> Result<Config, ConfigReport> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Config::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = load(&[("JWT_SECRET", "0123456789abcdef0123456789abcdef")]).unwrap();
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.enabled_chains, vec![Chain::Solana, Chain::Ethereum]);
+        assert!(!config.rate_limit.enabled);
+    }
+
+    #[test]
+    fn test_reports_every_invalid_field() {
+        let report = load(&[
+            ("JWT_SECRET", "short"),
+            ("SOLANA_RPC_URL", "not a url"),
+            ("PORT", "99999"),
+            ("ENABLED_CHAINS", "solana,dogecoin"),
+            ("RATE_LIMIT_MAX_REQUESTS", "0"),
+        ])
+        .unwrap_err();
+
+        let fields: Vec<&str> = report.errors.iter().map(|(field, _)| *field).collect();
+        assert_eq!(
+            fields,
+            vec!["PORT", "SOLANA_RPC_URL", "ENABLED_CHAINS", "RATE_LIMIT_MAX_REQUESTS", "JWT_SECRET"]
+        );
+    }
+
+    #[test]
+    fn test_oauth_providers() {
+        let secret = ("JWT_SECRET", "0123456789abcdef0123456789abcdef");
+        let config = load(&[
+            secret,
+            ("GITHUB_CLIENT_ID", "id"),
+            ("GITHUB_CLIENT_SECRET", "secret"),
+        ])
+        .unwrap();
+        let github = config.oauth.provider(OAuthProvider::GitHub).unwrap();
+        assert_eq!(github.token_url, "https://github.com/login/oauth/access_token");
+        assert!(config.oauth.provider(OAuthProvider::Google).is_none());
+
+        let report = load(&[secret, ("GOOGLE_CLIENT_ID", "id")]).unwrap_err();
+        assert_eq!(report.errors[0].0, "GOOGLE_CLIENT_SECRET");
+    }
+
+    #[test]
+    fn test_faucet_needs_sepolia() {
+        let secret = ("JWT_SECRET", "0123456789abcdef0123456789abcdef");
+        let faucet = [
+            secret,
+            ("FAUCET_ENABLED", "true"),
+            ("FAUCET_SEPOLIA_URL", "https://faucet.example.com/fund"),
+        ];
+        let config = load(&faucet).unwrap();
+        assert!(config.faucet.enabled);
+        assert_eq!(config.faucet.daily_limit, 1);
+
+        let report = load(&[faucet[0], faucet[1], faucet[2], ("ETH_CHAIN_ID", "1")]).unwrap_err();
+        assert_eq!(report.errors[0].0, "FAUCET_SEPOLIA_URL");
+    }
+
+    #[test]
+    fn test_dev_mode_guards() {
+        let secret = ("JWT_SECRET", "0123456789abcdef0123456789abcdef");
+        let dev = ("DEV_MODE", "true");
+        assert!(load(&[secret, dev]).unwrap().dev_mode);
+
+        let production = [
+            secret,
+            dev,
+            ("APP_ENV", "production"),
+            ("CORS_ORIGIN", "https://app.example.com"),
+        ];
+        let report = load(&production).unwrap_err();
+        assert_eq!(report.errors[0].0, "DEV_MODE");
+
+        let report = load(&[secret, dev, ("ETH_CHAIN_ID", "1")]).unwrap_err();
+        assert_eq!(report.errors[0].0, "DEV_MODE");
+    }
+
+    #[test]
+    fn test_unlock_challenge_required_in_production() {
+        let secret = ("JWT_SECRET", "0123456789abcdef0123456789abcdef");
+        let opt_out = ("UNLOCK_CHALLENGE_REQUIRED", "false");
+        assert!(load(&[secret]).unwrap().unlock_challenge_required);
+        assert!(!load(&[secret, opt_out]).unwrap().unlock_challenge_required);
+
+        let production = [
+            secret,
+            ("APP_ENV", "production"),
+            ("CORS_ORIGIN", "https://app.example.com"),
+        ];
+        assert!(load(&production).unwrap().unlock_challenge_required);
+        let report = load(&[production[0], production[1], production[2], opt_out]).unwrap_err();
+        assert_eq!(report.errors[0].0, "UNLOCK_CHALLENGE_REQUIRED");
+    }
+
+    #[test]
+    fn test_siem_export() {
+        let secret = ("JWT_SECRET", "0123456789abcdef0123456789abcdef");
+        assert!(!load(&[secret]).unwrap().siem.enabled());
+
+        let config = load(&[
+            secret,
+            ("SIEM_EXPORT_FILE", "/var/log/valtix/siem.log"),
+            ("SIEM_EXPORT_FORMAT", "CEF"),
+            ("SIEM_MIN_SEVERITY", "warning"),
+        ])
+        .unwrap();
+        assert!(config.siem.enabled());
+        assert_eq!(config.siem.format, SiemFormat::Cef);
+        assert_eq!(config.siem.min_severity, AuditSeverity::Warning);
+
+        let report = load(&[secret, ("SIEM_EXPORT_FORMAT", "xml")]).unwrap_err();
+        assert_eq!(report.errors[0].0, "SIEM_EXPORT_FORMAT");
+    }
+
+    #[test]
+    fn test_login_risk() {
+        let secret = ("JWT_SECRET", "0123456789abcdef0123456789abcdef");
+        let config = load(&[secret]).unwrap();
+        assert_eq!(config.login_risk.step_up_score, 60);
+        assert!(config.login_risk.verification_webhook_url.is_none());
+
+        let config = load(&[secret, ("LOGIN_COUNTRY_HEADER", "CF-IPCountry")]).unwrap();
+        assert_eq!(config.login_risk.country_header.as_deref(), Some("cf-ipcountry"));
+
+        let report = load(&[
+            secret,
+            ("LOGIN_STEP_UP_SCORE", "0"),
+            ("LOGIN_COUNTRY_HEADER", "bad header"),
+        ])
+        .unwrap_err();
+        let fields: Vec<&str> = report.errors.iter().map(|(field, _)| *field).collect();
+        assert_eq!(fields, vec!["LOGIN_STEP_UP_SCORE", "LOGIN_COUNTRY_HEADER"]);
+    }
+
+    #[test]
+    fn test_maintenance() {
+        let secret = ("JWT_SECRET", "0123456789abcdef0123456789abcdef");
+        let config = load(&[secret, ("MAINTENANCE_SESSION_RETENTION_DAYS", "7")]).unwrap();
+        assert_eq!(config.maintenance.interval, Duration::from_secs(3_600));
+        assert_eq!(config.maintenance.session_retention_days, 7);
+        assert_eq!(config.maintenance.job_retention_days, 90);
+
+        let report = load(&[secret, ("MAINTENANCE_INTERVAL_SECS", "5")]).unwrap_err();
+        assert_eq!(report.errors[0].0, "MAINTENANCE_INTERVAL_SECS");
+    }
+}
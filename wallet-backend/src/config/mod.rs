@@ -0,0 +1,11 @@
+//! Application configuration
+
+pub mod app;
+pub mod oauth;
+pub mod provision;
+pub mod security;
+
+pub use app::Config;
+pub use oauth::{OAuthConfig, OAuthProvider};
+pub use provision::ProvisionConfig;
+pub use security::SecurityConfig;
@@ -0,0 +1,123 @@
+//! OAuth2 / OpenID Connect login providers
+//!
+//! A provider is enabled by setting both `<PROVIDER>_CLIENT_ID` and
+//! `<PROVIDER>_CLIENT_SECRET`. Its endpoints default to the provider's public
+//! ones and can be overridden with `<PROVIDER>_AUTH_URL`, `<PROVIDER>_TOKEN_URL`
+//! and `<PROVIDER>_USERINFO_URL`.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Supported identity providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OAuthProvider {
+    Google,
+    GitHub,
+}
+
+impl OAuthProvider {
+    pub const ALL: [OAuthProvider; 2] = [OAuthProvider::Google, OAuthProvider::GitHub];
+
+    /// `<PROVIDER>_CLIENT_ID`, `_CLIENT_SECRET`, `_AUTH_URL`, `_TOKEN_URL` and
+    /// `_USERINFO_URL` variable names
+    pub fn env_vars(&self) -> [&'static str; 5] {
+        match self {
+            OAuthProvider::Google => [
+                "GOOGLE_CLIENT_ID",
+                "GOOGLE_CLIENT_SECRET",
+                "GOOGLE_AUTH_URL",
+                "GOOGLE_TOKEN_URL",
+                "GOOGLE_USERINFO_URL",
+            ],
+            OAuthProvider::GitHub => [
+                "GITHUB_CLIENT_ID",
+                "GITHUB_CLIENT_SECRET",
+                "GITHUB_AUTH_URL",
+                "GITHUB_TOKEN_URL",
+                "GITHUB_USERINFO_URL",
+            ],
+        }
+    }
+
+    /// Public authorization, token and userinfo endpoints
+    pub fn default_endpoints(&self) -> [&'static str; 3] {
+        match self {
+            OAuthProvider::Google => [
+                "https://accounts.google.com/o/oauth2/v2/auth",
+                "https://oauth2.googleapis.com/token",
+                "https://openidconnect.googleapis.com/v1/userinfo",
+            ],
+            OAuthProvider::GitHub => [
+                "https://github.com/login/oauth/authorize",
+                "https://github.com/login/oauth/access_token",
+                "https://api.github.com/user",
+            ],
+        }
+    }
+
+    /// Scopes requested at authorization
+    pub fn scopes(&self) -> &'static str {
+        match self {
+            OAuthProvider::Google => "openid email profile",
+            OAuthProvider::GitHub => "read:user user:email",
+        }
+    }
+}
+
+impl fmt::Display for OAuthProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthProvider::Google => write!(f, "google"),
+            OAuthProvider::GitHub => write!(f, "github"),
+        }
+    }
+}
+
+impl FromStr for OAuthProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "google" => Ok(OAuthProvider::Google),
+            "github" => Ok(OAuthProvider::GitHub),
+            _ => Err(format!("Unknown OAuth provider: {}", s)),
+        }
+    }
+}
+
+/// Client registration with one provider
+#[derive(Clone)]
+pub struct OAuthProviderConfig {
+    pub provider: OAuthProvider,
+    pub client_id: String,
+    pub client_secret: String,
+    pub auth_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+}
+
+impl fmt::Debug for OAuthProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthProviderConfig")
+            .field("provider", &self.provider)
+            .field("client_id", &self.client_id)
+            .field("auth_url", &self.auth_url)
+            .field("token_url", &self.token_url)
+            .field("userinfo_url", &self.userinfo_url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Social login settings
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    /// Frontend page providers redirect back to with the authorization code
+    pub redirect_uri: String,
+    pub providers: Vec<OAuthProviderConfig>,
+}
+
+impl OAuthConfig {
+    pub fn provider(&self, provider: OAuthProvider) -> Option<&OAuthProviderConfig> {
+        self.providers.iter().find(|p| p.provider == provider)
+    }
+}
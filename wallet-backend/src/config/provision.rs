@@ -0,0 +1,226 @@
+//! Non-interactive wallet provisioning for server-custody deployments
+//!
+//! Each secret can come from one of three places:
+//! - `<NAME>`: the value itself
+//! - `<NAME>_FILE`: a file holding the value (Docker / Kubernetes secrets)
+//! - `<NAME>_SEALED_FILE`: an encrypted blob piped through `WALLET_UNSEAL_COMMAND`,
+//!   e.g. `aws kms decrypt --ciphertext-blob fileb:///dev/stdin --query Plaintext
+//!   --output text | base64 -d`
+//!
+//! `WALLET_PASSWORD` unlocks an existing wallet at startup. With `WALLET_MNEMONIC`
+//! as well, the wallet is imported first if the database has none.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use zeroize::Zeroizing;
+
+/// Environment variables that may hold a secret in plaintext
+pub const SECRET_VARS: &[&str] = &["WALLET_PASSWORD", "WALLET_MNEMONIC"];
+
+#[derive(Debug, Error)]
+pub enum ProvisionError {
+    #[error("Only one of {0}, {0}_FILE and {0}_SEALED_FILE may be set")]
+    ConflictingSources(&'static str),
+    #[error("{0}_SEALED_FILE requires WALLET_UNSEAL_COMMAND")]
+    MissingUnsealCommand(&'static str),
+    #[error("WALLET_MNEMONIC requires WALLET_PASSWORD to encrypt the imported wallet")]
+    MissingPassword,
+    #[error("Failed to read {0}: {1}")]
+    Read(String, String),
+    #[error("Unseal command failed: {0}")]
+    Unseal(String),
+    #[error("{0} is empty")]
+    Empty(&'static str),
+}
+
+/// Where a secret is loaded from
+#[derive(Clone)]
+pub enum SecretSource {
+    Value(Zeroizing<String>),
+    File(PathBuf),
+    Sealed { path: PathBuf, unseal_command: String },
+}
+
+impl fmt::Debug for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(_) => f.write_str("Value(<redacted>)"),
+            Self::File(path) => f.debug_tuple("File").field(path).finish(),
+            Self::Sealed { path, .. } => f.debug_struct("Sealed").field("path", path).finish(),
+        }
+    }
+}
+
+impl SecretSource {
+    /// Load the secret, trimming surrounding whitespace
+    pub async fn load(&self, name: &'static str) -> Result<Zeroizing<String>, ProvisionError> {
+        let raw = match self {
+            Self::Value(value) => value.clone(),
+            Self::File(path) => Zeroizing::new(
+                tokio::fs::read_to_string(path)
+                    .await
+                    .map_err(|e| ProvisionError::Read(path.display().to_string(), e.to_string()))?,
+            ),
+            Self::Sealed {
+                path,
+                unseal_command,
+            } => unseal(path, unseal_command).await?,
+        };
+
+        let secret = Zeroizing::new(raw.trim().to_string());
+        if secret.is_empty() {
+            return Err(ProvisionError::Empty(name));
+        }
+        Ok(secret)
+    }
+}
+
+/// Secrets used to unlock (and if needed import) the wallet at startup
+#[derive(Debug, Clone)]
+pub struct ProvisionConfig {
+    pub password: SecretSource,
+    pub mnemonic: Option<SecretSource>,
+}
+
+impl ProvisionConfig {
+    /// Load from the `WALLET_PASSWORD*`, `WALLET_MNEMONIC*` and
+    /// `WALLET_UNSEAL_COMMAND` variables; `None` when provisioning is not configured
+    pub fn from_lookup(
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Option<Self>, ProvisionError> {
+        let var = |name: &str| lookup(name).filter(|v| !v.trim().is_empty());
+        let unseal_command = var("WALLET_UNSEAL_COMMAND");
+
+        let source = |name: &'static str| -> Result<Option<SecretSource>, ProvisionError> {
+            let value = var(name);
+            let file = var(&format!("{}_FILE", name));
+            let sealed = var(&format!("{}_SEALED_FILE", name));
+
+            match (value, file, sealed) {
+                (None, None, None) => Ok(None),
+                (Some(value), None, None) => Ok(Some(SecretSource::Value(Zeroizing::new(value)))),
+                (None, Some(file), None) => Ok(Some(SecretSource::File(file.into()))),
+                (None, None, Some(sealed)) => match unseal_command.clone() {
+                    Some(unseal_command) => Ok(Some(SecretSource::Sealed {
+                        path: sealed.into(),
+                        unseal_command,
+                    })),
+                    None => Err(ProvisionError::MissingUnsealCommand(name)),
+                },
+                _ => Err(ProvisionError::ConflictingSources(name)),
+            }
+        };
+
+        let password = source("WALLET_PASSWORD")?;
+        let mnemonic = source("WALLET_MNEMONIC")?;
+
+        match (password, mnemonic) {
+            (None, None) => Ok(None),
+            (None, Some(_)) => Err(ProvisionError::MissingPassword),
+            (Some(password), mnemonic) => Ok(Some(Self { password, mnemonic })),
+        }
+    }
+}
+
+/// Pipe a sealed blob through the unseal command and capture the plaintext
+async fn unseal(path: &PathBuf, command: &str) -> Result<Zeroizing<String>, ProvisionError> {
+    let sealed = tokio::fs::read(path)
+        .await
+        .map_err(|e| ProvisionError::Read(path.display().to_string(), e.to_string()))?;
+
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ProvisionError::Unseal(e.to_string()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(&sealed)
+            .await
+            .map_err(|e| ProvisionError::Unseal(e.to_string()))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| ProvisionError::Unseal(e.to_string()))?;
+    let stdout = Zeroizing::new(output.stdout);
+    if !output.status.success() {
+        return Err(ProvisionError::Unseal(format!(
+            "exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8(stdout.to_vec())
+        .map(Zeroizing::new)
+        .map_err(|_| ProvisionError::Unseal("output is not UTF-8".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(vars: &[(&str, &str)]) -> Result<Option<ProvisionConfig>, ProvisionError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ProvisionConfig::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_sources() {
+        assert!(load(&[]).unwrap().is_none());
+
+        let config = load(&[("WALLET_PASSWORD_FILE", "/run/secrets/pw")])
+            .unwrap()
+            .unwrap();
+        assert!(matches!(config.password, SecretSource::File(_)));
+        assert!(config.mnemonic.is_none());
+
+        assert!(matches!(
+            load(&[("WALLET_PASSWORD", "pw"), ("WALLET_PASSWORD_FILE", "/pw")]),
+            Err(ProvisionError::ConflictingSources("WALLET_PASSWORD"))
+        ));
+        assert!(matches!(
+            load(&[("WALLET_PASSWORD_SEALED_FILE", "/pw.enc")]),
+            Err(ProvisionError::MissingUnsealCommand(_))
+        ));
+        assert!(matches!(
+            load(&[("WALLET_MNEMONIC", "abandon ...")]),
+            Err(ProvisionError::MissingPassword)
+        ));
+    }
+
+    #[test]
+    fn test_debug_redacts_values() {
+        let source = SecretSource::Value(Zeroizing::new("hunter2".to_string()));
+        assert!(!format!("{:?}", source).contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn test_sealed_source_runs_unseal_command() {
+        let path = std::env::temp_dir().join(format!("sealed-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "drowssap\n").unwrap();
+
+        let source = SecretSource::Sealed {
+            path: path.clone(),
+            unseal_command: "rev".to_string(),
+        };
+        let secret = source.load("WALLET_PASSWORD").await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(secret.unwrap().as_str(), "password");
+    }
+}
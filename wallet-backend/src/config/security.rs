@@ -0,0 +1,314 @@
+//! CORS and security header configuration
+//!
+//! Profiles:
+//! - `development` (default): localhost frontend allowed, `CORS_ORIGIN=*` mirrors
+//!   the request origin, no HSTS
+//! - `production` (`APP_ENV=production`): origins must be listed explicitly and
+//!   use https, HSTS and a locked-down CSP are enabled
+
+use axum::http::{HeaderName, HeaderValue};
+use thiserror::Error;
+use tokio::sync::watch;
+use tower_http::cors::AllowOrigin;
+
+/// Origins allowed in development when `CORS_ORIGIN` is unset
+const DEV_DEFAULT_ORIGINS: &[&str] = &["http://localhost:3000", "https://valtix.vercel.app"];
+
+/// API responses are JSON only, so nothing may be loaded or framed
+const DEFAULT_CSP: &str = "default-src 'none'; frame-ancestors 'none'";
+/// Two years, the minimum for HSTS preload lists
+const PRODUCTION_HSTS_MAX_AGE: u64 = 63_072_000;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Unknown APP_ENV '{0}' (expected 'development' or 'production')")]
+    UnknownProfile(String),
+    #[error("Invalid CORS origin '{0}': {1}")]
+    InvalidOrigin(String, String),
+    #[error("CORS_ORIGIN must list at least one origin in production")]
+    MissingOrigins,
+    #[error("Invalid value for {0}: {1}")]
+    InvalidValue(&'static str, String),
+}
+
+/// Deployment profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Development,
+    Production,
+}
+
+impl std::str::FromStr for Profile {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "dev" | "development" => Ok(Self::Development),
+            "prod" | "production" => Ok(Self::Production),
+            other => Err(ConfigError::UnknownProfile(other.to_string())),
+        }
+    }
+}
+
+/// Allowed CORS origins
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsOrigins {
+    /// Reflect any request origin (development wildcard)
+    Mirror,
+    List(Vec<HeaderValue>),
+}
+
+/// CORS and security header settings
+#[derive(Debug, Clone)]
+pub struct SecurityConfig {
+    pub profile: Profile,
+    pub cors_origins: CorsOrigins,
+    pub content_security_policy: String,
+    /// `Strict-Transport-Security` max-age in seconds; `None` disables the header
+    pub hsts_max_age: Option<u64>,
+    pub frame_options: String,
+    pub referrer_policy: String,
+}
+
+impl SecurityConfig {
+    /// Load from `APP_ENV`, `CORS_ORIGIN`, `CONTENT_SECURITY_POLICY`,
+    /// `HSTS_MAX_AGE`, `X_FRAME_OPTIONS` and `REFERRER_POLICY`
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let var = |name: &str| lookup(name).filter(|v| !v.trim().is_empty());
+
+        let profile: Profile = var("APP_ENV").unwrap_or_default().parse()?;
+        let mut config = Self::for_profile(profile, var("CORS_ORIGIN").as_deref())?;
+
+        if let Some(csp) = var("CONTENT_SECURITY_POLICY") {
+            config.content_security_policy = csp;
+        }
+        if let Some(max_age) = var("HSTS_MAX_AGE") {
+            let max_age: u64 = max_age
+                .trim()
+                .parse()
+                .map_err(|_| ConfigError::InvalidValue("HSTS_MAX_AGE", max_age))?;
+            config.hsts_max_age = (max_age > 0).then_some(max_age);
+        }
+        if let Some(frame_options) = var("X_FRAME_OPTIONS") {
+            config.frame_options = frame_options;
+        }
+        if let Some(referrer_policy) = var("REFERRER_POLICY") {
+            config.referrer_policy = referrer_policy;
+        }
+
+        // Surface bad header values at startup rather than per response
+        config.headers()?;
+        Ok(config)
+    }
+
+    /// Profile defaults with the given comma-separated origin list
+    pub fn for_profile(profile: Profile, cors_origin: Option<&str>) -> Result<Self, ConfigError> {
+        let cors_origins = parse_origins(profile, cors_origin)?;
+
+        Ok(match profile {
+            Profile::Development => Self {
+                profile,
+                cors_origins,
+                content_security_policy: DEFAULT_CSP.to_string(),
+                hsts_max_age: None,
+                frame_options: "DENY".to_string(),
+                referrer_policy: "no-referrer".to_string(),
+            },
+            Profile::Production => Self {
+                profile,
+                cors_origins,
+                content_security_policy: DEFAULT_CSP.to_string(),
+                hsts_max_age: Some(PRODUCTION_HSTS_MAX_AGE),
+                frame_options: "DENY".to_string(),
+                referrer_policy: "no-referrer".to_string(),
+            },
+        })
+    }
+
+    /// CORS allow-origin setting for `CorsLayer`: the configured origins plus
+    /// whatever `added` holds when each request arrives
+    pub fn allow_origin(&self, added: watch::Receiver<Vec<HeaderValue>>) -> AllowOrigin {
+        match &self.cors_origins {
+            CorsOrigins::Mirror => AllowOrigin::mirror_request(),
+            CorsOrigins::List(origins) => {
+                let origins = origins.clone();
+                AllowOrigin::predicate(move |origin, _| {
+                    origins.contains(origin) || added.borrow().contains(origin)
+                })
+            }
+        }
+    }
+
+    /// Security headers to set on every response
+    pub fn headers(&self) -> Result<Vec<(HeaderName, HeaderValue)>, ConfigError> {
+        let value = |name: &'static str, v: &str| {
+            HeaderValue::from_str(v).map_err(|_| ConfigError::InvalidValue(name, v.to_string()))
+        };
+
+        let mut headers = vec![
+            (
+                HeaderName::from_static("content-security-policy"),
+                value("CONTENT_SECURITY_POLICY", &self.content_security_policy)?,
+            ),
+            (
+                HeaderName::from_static("x-frame-options"),
+                value("X_FRAME_OPTIONS", &self.frame_options)?,
+            ),
+            (
+                HeaderName::from_static("referrer-policy"),
+                value("REFERRER_POLICY", &self.referrer_policy)?,
+            ),
+            (
+                HeaderName::from_static("x-content-type-options"),
+                HeaderValue::from_static("nosniff"),
+            ),
+            (
+                HeaderName::from_static("cross-origin-opener-policy"),
+                HeaderValue::from_static("same-origin"),
+            ),
+            (
+                HeaderName::from_static("permissions-policy"),
+                HeaderValue::from_static("camera=(), microphone=(), geolocation=()"),
+            ),
+        ];
+
+        if let Some(max_age) = self.hsts_max_age {
+            headers.push((
+                HeaderName::from_static("strict-transport-security"),
+                value(
+                    "HSTS_MAX_AGE",
+                    &format!("max-age={}; includeSubDomains", max_age),
+                )?,
+            ));
+        }
+
+        Ok(headers)
+    }
+}
+
+/// Parse a comma-separated origin list, reporting the first invalid entry
+fn parse_origins(profile: Profile, cors_origin: Option<&str>) -> Result<CorsOrigins, ConfigError> {
+    let mut entries: Vec<&str> = cors_origin
+        .unwrap_or_default()
+        .split(',')
+        .map(|o| o.trim().trim_end_matches('/'))
+        .filter(|o| !o.is_empty())
+        .collect();
+
+    if entries.contains(&"*") {
+        return match profile {
+            Profile::Development => Ok(CorsOrigins::Mirror),
+            Profile::Production => Err(ConfigError::InvalidOrigin(
+                "*".to_string(),
+                "wildcard is not allowed in production".to_string(),
+            )),
+        };
+    }
+
+    if profile == Profile::Development {
+        entries.extend(DEV_DEFAULT_ORIGINS);
+    }
+
+    let mut origins: Vec<HeaderValue> = Vec::new();
+    for entry in entries {
+        let value = parse_origin(profile, entry)?;
+        if !origins.contains(&value) {
+            origins.push(value);
+        }
+    }
+
+    if origins.is_empty() {
+        return Err(ConfigError::MissingOrigins);
+    }
+
+    Ok(CorsOrigins::List(origins))
+}
+
+/// Check a single `scheme://host[:port]` origin, normalized to lower case
+/// without a trailing slash
+pub fn parse_origin(profile: Profile, entry: &str) -> Result<HeaderValue, ConfigError> {
+    // Browsers send the scheme and host in lower case
+    let entry = entry.trim().trim_end_matches('/').to_lowercase();
+    let entry = entry.as_str();
+    let (scheme, host) = entry.split_once("://").ok_or_else(|| {
+        ConfigError::InvalidOrigin(entry.to_string(), "missing scheme".to_string())
+    })?;
+    if scheme != "http" && scheme != "https" {
+        return Err(ConfigError::InvalidOrigin(
+            entry.to_string(),
+            "scheme must be http or https".to_string(),
+        ));
+    }
+    if host.is_empty() || host.contains(['/', '*', '?', '#', '@']) {
+        return Err(ConfigError::InvalidOrigin(
+            entry.to_string(),
+            "expected scheme://host[:port]".to_string(),
+        ));
+    }
+    // Skip past an IPv6 literal before looking for the port
+    let authority = host.rsplit_once(']').map_or(host, |(_, rest)| rest);
+    if let Some((_, port)) = authority.rsplit_once(':') {
+        if port.parse::<u16>().is_err() {
+            return Err(ConfigError::InvalidOrigin(
+                entry.to_string(),
+                "invalid port".to_string(),
+            ));
+        }
+    }
+    if profile == Profile::Production && scheme != "https" {
+        return Err(ConfigError::InvalidOrigin(
+            entry.to_string(),
+            "production origins must use https".to_string(),
+        ));
+    }
+
+    HeaderValue::from_str(entry)
+        .map_err(|e| ConfigError::InvalidOrigin(entry.to_string(), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_development_origins() {
+        let config =
+            SecurityConfig::for_profile(Profile::Development, Some("https://app.example.com/, ")).unwrap();
+        match config.cors_origins {
+            CorsOrigins::List(origins) => {
+                assert_eq!(origins[0], "https://app.example.com");
+                assert!(origins.iter().any(|o| o == "http://localhost:3000"));
+            }
+            CorsOrigins::Mirror => panic!("expected origin list"),
+        }
+
+        let config = SecurityConfig::for_profile(Profile::Development, Some("*")).unwrap();
+        assert_eq!(config.cors_origins, CorsOrigins::Mirror);
+    }
+
+    #[test]
+    fn test_parse_origin() {
+        let origin = parse_origin(Profile::Development, " HTTPS://App.Example.com:8443/").unwrap();
+        assert_eq!(origin, "https://app.example.com:8443");
+        assert!(parse_origin(Profile::Development, "http://[::1]:3000").is_ok());
+        for bad in ["https://app.example.com:port", "https://*.example.com", "https://a.com/x", "https://"] {
+            assert!(parse_origin(Profile::Development, bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_production_is_strict() {
+        assert!(matches!(
+            SecurityConfig::for_profile(Profile::Production, None),
+            Err(ConfigError::MissingOrigins)
+        ));
+        assert!(SecurityConfig::for_profile(Profile::Production, Some("*")).is_err());
+        assert!(SecurityConfig::for_profile(Profile::Production, Some("http://app.example.com")).is_err());
+        assert!(SecurityConfig::for_profile(Profile::Production, Some("app.example.com")).is_err());
+
+        let config =
+            SecurityConfig::for_profile(Profile::Production, Some("https://app.example.com")).unwrap();
+        let headers = config.headers().unwrap();
+        assert!(headers.iter().any(|(name, _)| name == "strict-transport-security"));
+    }
+}
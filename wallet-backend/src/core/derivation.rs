@@ -1,7 +1,8 @@
 //! HD wallet key derivation for Solana and Ethereum
 //!
 //! Implements BIP44 derivation paths:
-//! - Solana: m/44'/501'/index'/0' (Ed25519, SLIP-0010)
+//! - Solana: m/44'/501'/index'/0' (Ed25519, SLIP-0010), or the legacy
+//!   m/44'/501'/index' for wallets imported from older software
 //! - Ethereum: m/44'/60'/0'/0/index (secp256k1, BIP32)
 
 use ed25519_dalek::{SigningKey as Ed25519SigningKey, VerifyingKey as Ed25519VerifyingKey};
@@ -10,6 +11,7 @@ use k256::{
     ecdsa::SigningKey as Secp256k1SigningKey,
     elliptic_curve::sec1::ToEncodedPoint,
 };
+use serde::{Deserialize, Serialize};
 use sha2::Sha512;
 use thiserror::Error;
 use tiny_keccak::{Hasher, Keccak};
@@ -28,6 +30,39 @@ pub enum DerivationError {
 
 type HmacSha512 = Hmac<Sha512>;
 
+/// Layouts of Solana derivation paths wallets have used over the years
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolanaScheme {
+    /// m/44'/501'/index'/0', used by Phantom, Solflare and this wallet
+    Bip44Change,
+    /// m/44'/501'/index', used by earlier Phantom releases
+    Bip44,
+}
+
+impl SolanaScheme {
+    pub const ALL: [SolanaScheme; 2] = [SolanaScheme::Bip44Change, SolanaScheme::Bip44];
+
+    pub fn path(self, index: u32) -> String {
+        match self {
+            SolanaScheme::Bip44Change => format!("m/44'/501'/{}'/0'", index),
+            SolanaScheme::Bip44 => format!("m/44'/501'/{}'", index),
+        }
+    }
+
+    /// Scheme a recorded account path follows
+    pub fn of_path(path: &str) -> Option<Self> {
+        let account = path.strip_prefix("m/44'/501'/")?;
+        let (index, rest) = account.split_once('\'')?;
+        index.parse::<u32>().ok()?;
+        match rest {
+            "/0'" => Some(SolanaScheme::Bip44Change),
+            "" => Some(SolanaScheme::Bip44),
+            _ => None,
+        }
+    }
+}
+
 /// Extended private key for BIP32/SLIP-0010
 #[derive(Clone)]
 struct ExtendedKey {
@@ -104,19 +139,7 @@ pub fn derive_account(
     index: u32,
 ) -> Result<DerivedAccount, DerivationError> {
     match chain {
-        Chain::Solana => {
-            let path = format!("m/44'/501'/{}'/0'", index);
-            let (signing_key, address) = derive_solana_keypair(seed, index)?;
-            let verifying_key: Ed25519VerifyingKey = (&signing_key).into();
-
-            Ok(DerivedAccount {
-                chain,
-                derivation_path: path,
-                derivation_index: index,
-                public_key: bs58::encode(verifying_key.as_bytes()).into_string(),
-                address,
-            })
-        }
+        Chain::Solana => derive_solana_account(seed, index, SolanaScheme::Bip44Change),
         Chain::Ethereum => {
             let path = format!("m/44'/60'/0'/0/{}", index);
             let (signing_key, address) = derive_ethereum_keypair(seed, index)?;
@@ -134,6 +157,25 @@ pub fn derive_account(
     }
 }
 
+/// Derive a Solana account at `index` under a given path scheme
+pub fn derive_solana_account(
+    seed: &SecureSeed,
+    index: u32,
+    scheme: SolanaScheme,
+) -> Result<DerivedAccount, DerivationError> {
+    let path = scheme.path(index);
+    let (signing_key, address) = derive_solana_from_path(seed, &path)?;
+    let verifying_key: Ed25519VerifyingKey = (&signing_key).into();
+
+    Ok(DerivedAccount {
+        chain: Chain::Solana,
+        derivation_path: path,
+        derivation_index: index,
+        public_key: bs58::encode(verifying_key.as_bytes()).into_string(),
+        address,
+    })
+}
+
 /// SLIP-0010 Ed25519 derivation (hardened only)
 fn derive_slip0010_ed25519(seed: &[u8], path: &str) -> Result<ExtendedKey, DerivationError> {
     // Parse path
@@ -339,6 +381,25 @@ mod tests {
         assert!(eth_account.address.starts_with("0x"));
     }
 
+    #[test]
+    fn test_solana_schemes() {
+        let mnemonic = parse_mnemonic(TEST_MNEMONIC).unwrap();
+        let seed = mnemonic_to_seed(&mnemonic, "");
+
+        let standard = derive_solana_account(&seed, 1, SolanaScheme::Bip44Change).unwrap();
+        let legacy = derive_solana_account(&seed, 1, SolanaScheme::Bip44).unwrap();
+        assert_eq!(standard.derivation_path, "m/44'/501'/1'/0'");
+        assert_eq!(legacy.derivation_path, "m/44'/501'/1'");
+        assert_ne!(standard.address, legacy.address);
+        assert_eq!(standard.address, derive_account(&seed, Chain::Solana, 1).unwrap().address);
+
+        for scheme in SolanaScheme::ALL {
+            assert_eq!(SolanaScheme::of_path(&scheme.path(7)), Some(scheme));
+        }
+        assert_eq!(SolanaScheme::of_path("m/44'/60'/0'/0/0"), None);
+        assert_eq!(SolanaScheme::of_path("m/44'/501'/0'/0'/0'"), None);
+    }
+
     #[test]
     fn test_deterministic_derivation() {
         let mnemonic = parse_mnemonic(TEST_MNEMONIC).unwrap();
@@ -4,6 +4,7 @@
 //! - Argon2id for key derivation (OWASP recommended parameters)
 //! - ChaCha20-Poly1305 for authenticated encryption
 //! - Random salt and nonce for each encryption
+//! - Optional keyfile second factor, hashed into the Argon2 input
 
 use argon2::{
     password_hash::SaltString, Algorithm, Argon2, Params, Version,
@@ -13,6 +14,7 @@ use chacha20poly1305::{
     ChaCha20Poly1305, Nonce,
 };
 use rand::RngCore;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use zeroize::Zeroizing;
 
@@ -90,6 +92,67 @@ pub fn decrypt_seed(
     Ok(SecureSeed::new(seed_bytes))
 }
 
+/// Encrypt arbitrary secret bytes with a password
+/// Output layout: salt (16) || nonce (12) || ciphertext
+pub fn encrypt_secret(secret: &[u8], password: &str) -> Result<Vec<u8>, EncryptionError> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(password, &salt)?;
+
+    let cipher = ChaCha20Poly1305::new_from_slice(key.as_ref())
+        .map_err(|e| EncryptionError::EncryptionFailed(e.to_string()))?;
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), secret)
+        .map_err(|e| EncryptionError::EncryptionFailed(e.to_string()))?;
+
+    let mut blob = Vec::with_capacity(16 + 12 + ciphertext.len());
+    blob.extend_from_slice(&salt);
+    blob.extend_from_slice(&nonce);
+    blob.extend_from_slice(&ciphertext);
+
+    Ok(blob)
+}
+
+/// Decrypt a blob produced by `encrypt_secret`
+pub fn decrypt_secret(blob: &[u8], password: &str) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+    if blob.len() < 16 + 12 {
+        return Err(EncryptionError::InvalidFormat);
+    }
+
+    let salt: [u8; 16] = blob[..16]
+        .try_into()
+        .map_err(|_| EncryptionError::InvalidFormat)?;
+    let nonce = Nonce::from_slice(&blob[16..28]);
+
+    let key = derive_key(password, &salt)?;
+
+    let cipher = ChaCha20Poly1305::new_from_slice(key.as_ref())
+        .map_err(|_| EncryptionError::DecryptionFailed)?;
+
+    let plaintext = cipher
+        .decrypt(nonce, &blob[28..])
+        .map_err(|_| EncryptionError::DecryptionFailed)?;
+
+    Ok(Zeroizing::new(plaintext))
+}
+
+/// Argon2 input for a wallet password, optionally combined with a keyfile.
+/// Only the keyfile's SHA-256 goes into the derivation, so any file size works
+/// and the file itself never needs to be kept.
+pub fn wallet_key_material(password: &str, keyfile: Option<&[u8]>) -> Zeroizing<String> {
+    match keyfile {
+        None => Zeroizing::new(password.to_string()),
+        Some(keyfile) => {
+            let digest = Zeroizing::new(hex::encode(Sha256::digest(keyfile)));
+            Zeroizing::new(format!("{}\0{}", password, digest.as_str()))
+        }
+    }
+}
+
 /// Derive a 256-bit key from password using Argon2id
 fn derive_key(password: &str, salt: &[u8; 16]) -> Result<Zeroizing<[u8; 32]>, EncryptionError> {
     let params = Params::new(
@@ -189,6 +252,31 @@ mod tests {
         assert_ne!(encrypted1.ciphertext, encrypted2.ciphertext);
     }
 
+    #[test]
+    fn test_encrypt_secret_roundtrip() {
+        let secret = b"entropy bytes for backup quiz";
+
+        let blob = encrypt_secret(secret, "pw").unwrap();
+        assert_eq!(decrypt_secret(&blob, "pw").unwrap().as_slice(), secret);
+        assert!(decrypt_secret(&blob, "other").is_err());
+        assert!(decrypt_secret(&blob[..10], "pw").is_err());
+    }
+
+    #[test]
+    fn test_keyfile_is_a_second_factor() {
+        let mnemonic = generate_mnemonic().unwrap();
+        let seed = mnemonic_to_seed(&mnemonic, "");
+        let key = wallet_key_material("pw", Some(b"keyfile contents"));
+
+        let encrypted = encrypt_seed(&seed, &key).unwrap();
+        assert!(verify_password(&encrypted, &key));
+        assert!(!verify_password(&encrypted, "pw"));
+        assert!(!verify_password(
+            &encrypted,
+            &wallet_key_material("pw", Some(b"other file"))
+        ));
+    }
+
     #[test]
     fn test_generate_random_password() {
         let password = generate_random_password(32);
@@ -4,8 +4,10 @@ pub mod derivation;
 pub mod encryption;
 pub mod seed;
 pub mod types;
+pub mod units;
 
 pub use derivation::*;
 pub use encryption::*;
 pub use seed::*;
 pub use types::*;
+pub use units::{format_signed_units, format_units, parse_units};
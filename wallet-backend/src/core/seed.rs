@@ -1,7 +1,9 @@
 //! BIP39 seed phrase generation and handling
 
 use bip39::{Language, Mnemonic};
+use serde::Serialize;
 use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
 
 use super::SecureSeed;
 
@@ -23,10 +25,18 @@ pub fn generate_mnemonic() -> Result<Mnemonic, SeedError> {
         .map_err(|e| SeedError::InvalidMnemonic(e.to_string()))
 }
 
-/// Parse a mnemonic phrase from string
+/// Parse a mnemonic phrase in any BIP39 language, detecting which
 pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, SeedError> {
-    Mnemonic::parse_in_normalized(Language::English, phrase)
-        .map_err(|e| SeedError::InvalidMnemonic(e.to_string()))
+    match Mnemonic::parse(phrase) {
+        // The Chinese lists share most of their words; take whichever checksums
+        Err(bip39::Error::AmbiguousLanguages(candidates)) => candidates
+            .iter()
+            .find_map(|language| Mnemonic::parse_in(language, phrase).ok())
+            .ok_or_else(|| {
+                SeedError::InvalidMnemonic("phrase matches several wordlists".to_string())
+            }),
+        result => result.map_err(|e| SeedError::InvalidMnemonic(e.to_string())),
+    }
 }
 
 /// Convert mnemonic to 64-byte seed using optional passphrase
@@ -37,12 +47,154 @@ pub fn mnemonic_to_seed(mnemonic: &Mnemonic, passphrase: &str) -> SecureSeed {
 
 /// Validate a mnemonic phrase
 pub fn validate_mnemonic(phrase: &str) -> bool {
-    Mnemonic::parse_in_normalized(Language::English, phrase).is_ok()
+    parse_mnemonic(phrase).is_ok()
+}
+
+/// Get the BIP39 word list for a language
+pub fn get_wordlist(language: Language) -> Vec<&'static str> {
+    language.word_list().to_vec()
+}
+
+/// Name of a wordlist language as used by the API
+pub fn language_name(language: Language) -> &'static str {
+    match language {
+        Language::English => "english",
+        Language::SimplifiedChinese => "chinese-simplified",
+        Language::TraditionalChinese => "chinese-traditional",
+        Language::Czech => "czech",
+        Language::French => "french",
+        Language::Italian => "italian",
+        Language::Japanese => "japanese",
+        Language::Korean => "korean",
+        Language::Portuguese => "portuguese",
+        Language::Spanish => "spanish",
+    }
+}
+
+/// Look up a wordlist language by its API name
+pub fn parse_language(name: &str) -> Option<Language> {
+    Language::ALL
+        .iter()
+        .copied()
+        .find(|language| language_name(*language).eq_ignore_ascii_case(name))
+}
+
+/// Normalize a typed word for wordlist comparison (NFKD, lower case)
+pub fn normalize_word(word: &str) -> String {
+    word.trim().nfkd().collect::<String>().to_lowercase()
+}
+
+/// Phrase lengths allowed by BIP39
+pub const MNEMONIC_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Suggestions further than this many edits from the typed word are dropped
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A word that is not in the BIP39 wordlist
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidWord {
+    /// 1-based position in the phrase
+    pub position: usize,
+    pub word: String,
+    /// Closest wordlist entries, nearest first
+    pub suggestions: Vec<String>,
+}
+
+/// Detailed validation of a mnemonic phrase
+#[derive(Debug, Clone, Serialize)]
+pub struct MnemonicCheck {
+    pub valid: bool,
+    /// Wordlist matching the most words, if any matched
+    pub language: Option<String>,
+    pub word_count: usize,
+    pub word_count_valid: bool,
+    pub invalid_words: Vec<InvalidWord>,
+    /// Only known once the length and every word are valid
+    pub checksum_valid: Option<bool>,
+}
+
+/// Validate a phrase word by word, suggesting corrections for unknown words
+pub fn check_mnemonic(phrase: &str) -> MnemonicCheck {
+    let words: Vec<String> = phrase.split_whitespace().map(normalize_word).collect();
+    let word_count_valid = MNEMONIC_WORD_COUNTS.contains(&words.len());
+    let language = detect_language(&words);
+
+    let invalid_words: Vec<InvalidWord> = words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| language.unwrap_or(Language::English).find_word(word).is_none())
+        .map(|(i, word)| InvalidWord {
+            position: i + 1,
+            word: word.clone(),
+            suggestions: suggest_words(language.unwrap_or(Language::English), word),
+        })
+        .collect();
+
+    let checksum_valid = match language {
+        Some(language) if word_count_valid && invalid_words.is_empty() => {
+            Some(Mnemonic::parse_in_normalized(language, &words.join(" ")).is_ok())
+        }
+        _ => None,
+    };
+
+    MnemonicCheck {
+        valid: checksum_valid == Some(true),
+        language: language.map(|l| language_name(l).to_string()),
+        word_count: words.len(),
+        word_count_valid,
+        invalid_words,
+        checksum_valid,
+    }
+}
+
+/// Wordlist containing the most of the given words; English wins ties
+fn detect_language(words: &[String]) -> Option<Language> {
+    let mut best: Option<(usize, Language)> = None;
+    for language in Language::ALL {
+        let matches = words
+            .iter()
+            .filter(|word| language.find_word(word).is_some())
+            .count();
+        if matches > 0 && best.is_none_or(|(most, _)| matches > most) {
+            best = Some((matches, *language));
+        }
+    }
+    best.map(|(_, language)| language)
 }
 
-/// Get the word list for BIP39
-pub fn get_wordlist() -> Vec<&'static str> {
-    Language::English.word_list().to_vec()
+/// Closest wordlist entries by edit distance, ties broken alphabetically
+fn suggest_words(language: Language, word: &str) -> Vec<String> {
+    let mut candidates: Vec<(usize, &str)> = language
+        .word_list()
+        .iter()
+        .map(|candidate| (edit_distance(word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    candidates.sort();
+
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
 }
 
 #[cfg(test)]
@@ -79,4 +231,43 @@ mod tests {
         assert!(validate_mnemonic(valid));
         assert!(!validate_mnemonic(invalid));
     }
+
+    #[test]
+    fn test_check_mnemonic() {
+        let valid = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let check = check_mnemonic(valid);
+        assert!(check.valid);
+        assert_eq!(check.checksum_valid, Some(true));
+
+        let typo = "abandon abandn abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let check = check_mnemonic(typo);
+        assert!(!check.valid);
+        assert_eq!(check.invalid_words.len(), 1);
+        assert_eq!(check.invalid_words[0].position, 2);
+        assert_eq!(check.invalid_words[0].suggestions[0], "abandon");
+        assert_eq!(check.checksum_valid, None);
+
+        let bad_checksum = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert_eq!(check_mnemonic(bad_checksum).checksum_valid, Some(false));
+
+        let spanish = "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco abierto";
+        let check = check_mnemonic(spanish);
+        assert!(check.valid);
+        assert_eq!(check.language.as_deref(), Some("spanish"));
+
+        let short = check_mnemonic("abandon abandon abandon");
+        assert!(!short.word_count_valid);
+        assert_eq!(short.checksum_valid, None);
+    }
+
+    #[test]
+    fn test_parse_mnemonic_detects_language() {
+        let spanish = "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco abierto";
+        let mnemonic = parse_mnemonic(spanish).unwrap();
+        assert_eq!(mnemonic.language(), Language::Spanish);
+        assert_eq!(mnemonic.to_entropy(), vec![0u8; 16]);
+
+        assert_eq!(parse_language("Japanese"), Some(Language::Japanese));
+        assert_eq!(parse_language("klingon"), None);
+    }
 }
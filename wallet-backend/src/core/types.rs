@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
 /// Supported blockchain networks
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Chain {
     Solana,
@@ -65,6 +65,13 @@ impl SecureSeed {
     }
 }
 
+/// Never print seed bytes
+impl std::fmt::Debug for SecureSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecureSeed([REDACTED])")
+    }
+}
+
 impl AsRef<[u8]> for SecureSeed {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -0,0 +1,95 @@
+//! Exact conversion between base units (lamports, wei, token units) and
+//! decimal display strings
+//!
+//! Amounts are handled as integers throughout, so balances far beyond
+//! what an `f64` holds exactly keep every digit.
+
+use super::Chain;
+
+impl Chain {
+    /// Decimals of the native coin: 9 for SOL (lamports), 18 for ETH (wei)
+    pub fn native_decimals(self) -> u32 {
+        match self {
+            Chain::Solana => 9,
+            Chain::Ethereum => 18,
+        }
+    }
+}
+
+/// Base units as a decimal string, without trailing zeros
+pub fn format_units(units: u128, decimals: u32) -> String {
+    if decimals == 0 {
+        return units.to_string();
+    }
+    let digits = format!("{:0>width$}", units, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// A signed amount of base units, such as a balance change, as a decimal
+/// string
+pub fn format_signed_units(units: i128, decimals: u32) -> String {
+    let sign = if units < 0 { "-" } else { "" };
+    format!("{}{}", sign, format_units(units.unsigned_abs(), decimals))
+}
+
+/// A non-negative decimal string as base units; `None` when it isn't a
+/// plain decimal, is finer than one base unit or doesn't fit in a `u128`
+pub fn parse_units(amount: &str, decimals: u32) -> Option<u128> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // Trailing zeros past the precision don't change the value
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return None;
+    }
+
+    let scale = 10u128.checked_pow(decimals)?;
+    let whole: u128 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+    let fraction: u128 = if fraction.is_empty() {
+        0
+    } else {
+        let padded = format!("{:0<width$}", fraction, width = decimals as usize);
+        padded.parse().ok()?
+    };
+    whole.checked_mul(scale)?.checked_add(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units_are_exact() {
+        assert_eq!(format_units(1_500_000_000, 9), "1.5");
+        assert_eq!(format_units(1, 9), "0.000000001");
+        assert_eq!(format_units(0, 18), "0");
+        assert_eq!(format_units(42, 0), "42");
+        assert_eq!(format_signed_units(-1_500_000_000, 9), "-1.5");
+        assert_eq!(format_signed_units(2_000_000_000, 9), "2");
+        // Beyond the 53 bits an f64 holds exactly
+        let whale = 123_456_789_012_345_678_901_234_567u128;
+        assert_eq!(format_units(whale, 18), "123456789.012345678901234567");
+        assert_eq!(parse_units("123456789.012345678901234567", 18), Some(whale));
+
+        assert_eq!(parse_units("1.5", 9), Some(1_500_000_000));
+        assert_eq!(parse_units(".5", 9), Some(500_000_000));
+        assert_eq!(parse_units("2.000000000000", 9), Some(2_000_000_000));
+        assert_eq!(parse_units("0.0000000001", 9), None);
+        assert_eq!(parse_units("-1", 9), None);
+        assert_eq!(parse_units("1e9", 9), None);
+        assert_eq!(parse_units("", 9), None);
+        assert_eq!(Chain::Ethereum.native_decimals(), 18);
+    }
+}
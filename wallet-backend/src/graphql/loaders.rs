@@ -0,0 +1,184 @@
+//! DataLoaders batching the lookups of nested fields
+//!
+//! Each request gets its own set, so nothing is cached across requests. Loads
+//! queued while a query resolves are gathered into one database query, or
+//! one concurrent round of chain reads, per field.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use tokio::task::JoinSet;
+
+use super::types::MAX_HISTORY_LIMIT;
+use crate::api::middleware::tenant::in_current_tenant;
+use crate::services::nft_service;
+use crate::services::transaction_service::{self, BalanceResponse};
+use crate::storage::models::{MultisigOwnerRow, MultisigTransactionRow, NftResponse, TransactionRow};
+use crate::AppState;
+
+/// Chain and address of an on-chain lookup
+type AddressKey = (String, String);
+
+/// The request's loaders
+pub struct Loaders {
+    state: Arc<AppState>,
+    pub balances: DataLoader<BalanceLoader>,
+    pub history: DataLoader<HistoryLoader>,
+    pub nfts: DataLoader<NftLoader>,
+    pub multisig_owners: DataLoader<MultisigOwnerLoader>,
+    pub multisig_transactions: DataLoader<MultisigTransactionLoader>,
+}
+
+impl Loaders {
+    /// Loaders for a request of the current tenant
+    pub fn new(state: &Arc<AppState>) -> Self {
+        // Batches run on their own tasks, which must still see the tenant
+        fn loader<T: Loader<K>, K>(loader: T) -> DataLoader<T>
+        where
+            K: Send + Sync + std::hash::Hash + Eq + Clone + 'static,
+        {
+            DataLoader::new(loader, |batch| tokio::spawn(in_current_tenant(batch)))
+        }
+
+        Self {
+            state: state.clone(),
+            balances: loader(BalanceLoader(state.clone())),
+            history: loader(HistoryLoader(state.clone())),
+            nfts: loader(NftLoader(state.clone())),
+            multisig_owners: loader(MultisigOwnerLoader(state.clone())),
+            multisig_transactions: loader(MultisigTransactionLoader(state.clone())),
+        }
+    }
+
+    pub fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+}
+
+/// Outcome of one chain read; a failed read fails only the fields that need it
+pub type ReadResult<T> = Result<T, String>;
+
+/// Live balances, read concurrently
+pub struct BalanceLoader(Arc<AppState>);
+
+impl Loader<AddressKey> for BalanceLoader {
+    type Value = ReadResult<BalanceResponse>;
+    type Error = String;
+
+    async fn load(
+        &self,
+        keys: &[AddressKey],
+    ) -> Result<HashMap<AddressKey, ReadResult<BalanceResponse>>, String> {
+        let mut reads = JoinSet::new();
+        for key in keys {
+            let (state, key) = (self.0.clone(), key.clone());
+            reads.spawn(in_current_tenant(async move {
+                let balance = transaction_service::get_balance(&state, &key.0, &key.1).await;
+                (key, balance)
+            }));
+        }
+        collect(reads).await
+    }
+}
+
+/// Cached history, the latest page of every account in one query
+pub struct HistoryLoader(Arc<AppState>);
+
+impl Loader<String> for HistoryLoader {
+    type Value = Vec<TransactionRow>;
+    type Error = String;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Vec<TransactionRow>>, String> {
+        let rows = self
+            .0
+            .db
+            .get_recent_transactions(keys, MAX_HISTORY_LIMIT)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(group(rows, |tx| tx.account_id.clone()))
+    }
+}
+
+/// NFT galleries, fetched concurrently
+pub struct NftLoader(Arc<AppState>);
+
+impl Loader<AddressKey> for NftLoader {
+    type Value = ReadResult<Vec<NftResponse>>;
+    type Error = String;
+
+    async fn load(
+        &self,
+        keys: &[AddressKey],
+    ) -> Result<HashMap<AddressKey, ReadResult<Vec<NftResponse>>>, String> {
+        let mut reads = JoinSet::new();
+        for key in keys {
+            let (state, key) = (self.0.clone(), key.clone());
+            reads.spawn(in_current_tenant(async move {
+                let nfts = nft_service::get_nfts(&state, &key.0, &key.1).await;
+                (key, nfts)
+            }));
+        }
+        collect(reads).await
+    }
+}
+
+/// Owners of every multisig in one query
+pub struct MultisigOwnerLoader(Arc<AppState>);
+
+impl Loader<String> for MultisigOwnerLoader {
+    type Value = Vec<MultisigOwnerRow>;
+    type Error = String;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Vec<MultisigOwnerRow>>, String> {
+        let rows = self
+            .0
+            .db
+            .get_owners_for_multisigs(keys)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(group(rows, |owner| owner.multisig_id.clone()))
+    }
+}
+
+/// Proposals of every multisig in one query
+pub struct MultisigTransactionLoader(Arc<AppState>);
+
+impl Loader<String> for MultisigTransactionLoader {
+    type Value = Vec<MultisigTransactionRow>;
+    type Error = String;
+
+    async fn load(
+        &self,
+        keys: &[String],
+    ) -> Result<HashMap<String, Vec<MultisigTransactionRow>>, String> {
+        let rows = self
+            .0
+            .db
+            .get_transactions_for_multisigs(keys)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(group(rows, |tx| tx.multisig_id.clone()))
+    }
+}
+
+/// Rows by the key they were loaded for, in query order
+fn group<T>(rows: Vec<T>, key: impl Fn(&T) -> String) -> HashMap<String, Vec<T>> {
+    let mut groups: HashMap<String, Vec<T>> = HashMap::new();
+    for row in rows {
+        groups.entry(key(&row)).or_default().push(row);
+    }
+    groups
+}
+
+/// Results of concurrent reads, by key
+async fn collect<V: 'static, E: std::fmt::Display + 'static>(
+    mut reads: JoinSet<(AddressKey, Result<V, E>)>,
+) -> Result<HashMap<AddressKey, ReadResult<V>>, String> {
+    let mut values = HashMap::new();
+    while let Some(read) = reads.join_next().await {
+        let (key, value) = read.map_err(|e| e.to_string())?;
+        values.insert(key, value.map_err(|e| e.to_string()));
+    }
+    Ok(values)
+}
@@ -0,0 +1,102 @@
+//! GraphQL API (enabled with the `graphql` feature)
+//!
+//! A read-only schema over the services layer, served at `POST /api/v2/graphql`
+//! for authenticated users. Nested fields such as an account's balance or a
+//! multisig's owners are batched per request through DataLoaders, so listing
+//! every account with its balance costs one round of chain reads rather than
+//! one per account.
+
+mod loaders;
+mod types;
+
+use std::sync::{Arc, OnceLock};
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Error, Object, Result, Schema};
+use axum::{extract::State, Json};
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::services::{multisig_service, wallet_service};
+use crate::storage::models::ContactResponse;
+use crate::AppState;
+use loaders::Loaders;
+use types::{Account, Balance, Contact, Multisig, Nft};
+
+/// Deepest selection accepted; the schema nests at most four levels
+const MAX_DEPTH: usize = 8;
+
+pub type WalletSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Accounts of the wallet, optionally on one chain
+    async fn accounts(&self, ctx: &Context<'_>, chain: Option<String>) -> Result<Vec<Account>> {
+        let accounts = wallet_service::list_accounts(state(ctx)).await?;
+        Ok(accounts
+            .into_iter()
+            .filter(|a| chain.as_ref().is_none_or(|c| a.chain.eq_ignore_ascii_case(c)))
+            .map(Account::from)
+            .collect())
+    }
+
+    /// One account of the wallet
+    async fn account(&self, ctx: &Context<'_>, id: String) -> Result<Option<Account>> {
+        let accounts = wallet_service::list_accounts(state(ctx)).await?;
+        Ok(accounts.into_iter().find(|a| a.id == id).map(Account::from))
+    }
+
+    /// Live balance of any address
+    async fn balance(&self, ctx: &Context<'_>, chain: String, address: String) -> Result<Balance> {
+        Ok(types::load_balance(ctx, &chain, &address).await?.into())
+    }
+
+    /// NFTs held by any address
+    async fn nfts(&self, ctx: &Context<'_>, chain: String, address: String) -> Result<Vec<Nft>> {
+        types::load_nfts(ctx, &chain, &address).await
+    }
+
+    /// The address book
+    async fn contacts(&self, ctx: &Context<'_>) -> Result<Vec<Contact>> {
+        let state = state(ctx);
+        let wallet = state
+            .db
+            .get_primary_wallet(&current_tenant_id())
+            .await?
+            .ok_or_else(|| Error::new("No wallet found"))?;
+        let contacts = state.db.get_contacts(&wallet.id).await?;
+        Ok(contacts
+            .into_iter()
+            .map(|c| ContactResponse::from(c).into())
+            .collect())
+    }
+
+    /// Multisig wallets
+    async fn multisigs(&self, ctx: &Context<'_>) -> Result<Vec<Multisig>> {
+        let multisigs = multisig_service::list_multisigs(state(ctx), false).await?;
+        Ok(multisigs.into_iter().map(Multisig::from).collect())
+    }
+}
+
+fn state<'a>(ctx: &Context<'a>) -> &'a Arc<AppState> {
+    ctx.data_unchecked::<Loaders>().state()
+}
+
+/// The schema, built on first use
+pub fn schema() -> &'static WalletSchema {
+    static SCHEMA: OnceLock<WalletSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .limit_depth(MAX_DEPTH)
+            .finish()
+    })
+}
+
+/// Execute a GraphQL request for the current user and tenant
+pub async fn execute(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let request = request.data(Loaders::new(&state));
+    Json(schema().execute(request).await)
+}
@@ -0,0 +1,345 @@
+//! GraphQL object types and their nested resolvers
+
+use async_graphql::{ComplexObject, Context, Error, Result, SimpleObject};
+
+use super::loaders::Loaders;
+use crate::services::transaction_service::{BalanceResponse, TokenBalanceResponse};
+use crate::services::wallet_service;
+use crate::storage::models::{
+    AccountResponse, ContactResponse, MultisigOwnerRow, MultisigTransactionResponse, MultisigTransactionRow,
+    MultisigWalletResponse, NftResponse, TransactionResponse,
+};
+
+/// Default history page size, matching the REST API
+const DEFAULT_HISTORY_LIMIT: u32 = 50;
+/// Largest history page; the loader fetches this many per account
+pub(super) const MAX_HISTORY_LIMIT: u32 = 100;
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Account {
+    pub id: String,
+    pub name: String,
+    pub chain: String,
+    pub address: String,
+    pub derivation_path: String,
+    pub created_at: String,
+    pub last_synced_at: Option<String>,
+    pub last_known_balance: Option<String>,
+    pub sync_error: Option<String>,
+}
+
+#[ComplexObject]
+impl Account {
+    /// Live balance, read from the chain
+    async fn balance(&self, ctx: &Context<'_>) -> Result<Balance> {
+        Ok(load_balance(ctx, &self.chain, &self.address).await?.into())
+    }
+
+    /// Recorded transactions, newest first; needs the wallet unlocked
+    async fn history(&self, ctx: &Context<'_>, limit: Option<u32>) -> Result<Vec<Transaction>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+        if !wallet_service::is_unlocked(loaders.state()).await {
+            return Err(Error::new("Wallet is locked"));
+        }
+        let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT).min(MAX_HISTORY_LIMIT) as usize;
+        let history = loaders
+            .history
+            .load_one(self.id.clone())
+            .await?
+            .unwrap_or_default();
+        Ok(history
+            .into_iter()
+            .take(limit)
+            .map(|tx| TransactionResponse::from(tx).into())
+            .collect())
+    }
+
+    /// NFTs held by the account
+    async fn nfts(&self, ctx: &Context<'_>) -> Result<Vec<Nft>> {
+        load_nfts(ctx, &self.chain, &self.address).await
+    }
+}
+
+/// Balance of an address through the request's loader
+pub(super) async fn load_balance(ctx: &Context<'_>, chain: &str, address: &str) -> Result<BalanceResponse> {
+    let balance = ctx
+        .data_unchecked::<Loaders>()
+        .balances
+        .load_one((chain.to_string(), address.to_string()))
+        .await?
+        .ok_or_else(|| Error::new("Balance unavailable"))?;
+    Ok(balance?)
+}
+
+/// NFTs of an address through the request's loader
+pub(super) async fn load_nfts(ctx: &Context<'_>, chain: &str, address: &str) -> Result<Vec<Nft>> {
+    let nfts = ctx
+        .data_unchecked::<Loaders>()
+        .nfts
+        .load_one((chain.to_string(), address.to_string()))
+        .await?
+        .ok_or_else(|| Error::new("NFTs unavailable"))?;
+    Ok(nfts?.into_iter().map(Nft::from).collect())
+}
+
+impl From<AccountResponse> for Account {
+    fn from(account: AccountResponse) -> Self {
+        Self {
+            id: account.id,
+            name: account.name,
+            chain: account.chain,
+            address: account.address,
+            derivation_path: account.derivation_path,
+            created_at: account.created_at,
+            last_synced_at: account.last_synced_at,
+            last_known_balance: account.last_known_balance,
+            sync_error: account.sync_error,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Balance {
+    pub chain: String,
+    pub address: String,
+    pub native_balance: String,
+    /// Native balance in base units (lamports, wei)
+    pub native_balance_raw: String,
+    pub native_decimals: u32,
+    pub native_symbol: String,
+    pub tokens: Vec<TokenBalance>,
+}
+
+impl From<BalanceResponse> for Balance {
+    fn from(balance: BalanceResponse) -> Self {
+        Self {
+            chain: balance.chain,
+            address: balance.address,
+            native_balance: balance.native_balance,
+            native_balance_raw: balance.native_balance_raw,
+            native_decimals: balance.native_decimals,
+            native_symbol: balance.native_symbol,
+            tokens: balance.tokens.into_iter().map(TokenBalance::from).collect(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TokenBalance {
+    pub address: String,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    pub balance: String,
+    pub decimals: u8,
+    pub formatted_balance: String,
+    pub ui_amount: f64,
+    pub logo_uri: Option<String>,
+}
+
+impl From<TokenBalanceResponse> for TokenBalance {
+    fn from(token: TokenBalanceResponse) -> Self {
+        Self {
+            address: token.address,
+            symbol: token.symbol,
+            name: token.name,
+            balance: token.balance,
+            decimals: token.decimals,
+            formatted_balance: token.formatted_balance,
+            ui_amount: token.ui_amount,
+            logo_uri: token.logo_uri,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Transaction {
+    pub id: String,
+    pub chain: String,
+    pub signature: String,
+    pub tx_type: String,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub amount: Option<String>,
+    /// Native amounts in base units (lamports, wei)
+    pub amount_raw: Option<String>,
+    pub token_address: Option<String>,
+    pub status: String,
+    pub block_number: Option<i64>,
+    pub timestamp: Option<String>,
+    pub fiat_amount: Option<String>,
+    pub fiat_currency: Option<String>,
+}
+
+impl From<TransactionResponse> for Transaction {
+    fn from(tx: TransactionResponse) -> Self {
+        Self {
+            id: tx.id,
+            chain: tx.chain,
+            signature: tx.signature,
+            tx_type: tx.tx_type,
+            from_address: tx.from_address,
+            to_address: tx.to_address,
+            amount: tx.amount,
+            amount_raw: tx.amount_raw,
+            token_address: tx.token_address,
+            status: tx.status,
+            block_number: tx.block_number,
+            timestamp: tx.timestamp,
+            fiat_amount: tx.fiat_amount,
+            fiat_currency: tx.fiat_currency,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Nft {
+    pub id: String,
+    pub chain: String,
+    pub token_address: String,
+    pub token_id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub collection_name: Option<String>,
+    pub listed: bool,
+}
+
+impl From<NftResponse> for Nft {
+    fn from(nft: NftResponse) -> Self {
+        Self {
+            id: nft.id,
+            chain: nft.chain,
+            token_address: nft.token_address,
+            token_id: nft.token_id,
+            name: nft.name,
+            description: nft.description,
+            image_url: nft.image_url,
+            collection_name: nft.collection_name,
+            listed: nft.listed,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Contact {
+    pub id: String,
+    pub name: String,
+    pub chain: String,
+    pub address: String,
+    pub notes: Option<String>,
+    pub created_at: String,
+    /// ENS / SNS name the address resolves to
+    pub identity_name: Option<String>,
+    pub default_token_address: Option<String>,
+    pub default_amount: Option<String>,
+}
+
+impl From<ContactResponse> for Contact {
+    fn from(contact: ContactResponse) -> Self {
+        Self {
+            identity_name: contact.identity.map(|identity| identity.name),
+            id: contact.id,
+            name: contact.name,
+            chain: contact.chain,
+            address: contact.address,
+            notes: contact.notes,
+            created_at: contact.created_at,
+            default_token_address: contact.default_token_address,
+            default_amount: contact.default_amount,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Multisig {
+    pub id: String,
+    pub name: String,
+    pub chain: String,
+    pub address: String,
+    pub threshold: u32,
+    pub owner_count: u32,
+    pub created_at: String,
+}
+
+#[ComplexObject]
+impl Multisig {
+    async fn owners(&self, ctx: &Context<'_>) -> Result<Vec<MultisigOwner>> {
+        let owners = ctx
+            .data_unchecked::<Loaders>()
+            .multisig_owners
+            .load_one(self.id.clone())
+            .await?
+            .unwrap_or_default();
+        Ok(owners.into_iter().map(MultisigOwner::from).collect())
+    }
+
+    /// Proposals, newest first
+    async fn transactions(&self, ctx: &Context<'_>) -> Result<Vec<MultisigTransaction>> {
+        let transactions = ctx
+            .data_unchecked::<Loaders>()
+            .multisig_transactions
+            .load_one(self.id.clone())
+            .await?
+            .unwrap_or_default();
+        Ok(transactions.into_iter().map(MultisigTransaction::from).collect())
+    }
+}
+
+impl From<MultisigWalletResponse> for Multisig {
+    fn from(multisig: MultisigWalletResponse) -> Self {
+        Self {
+            id: multisig.id,
+            name: multisig.name,
+            chain: multisig.chain,
+            address: multisig.address,
+            threshold: multisig.threshold,
+            owner_count: multisig.owner_count,
+            created_at: multisig.created_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct MultisigOwner {
+    pub address: String,
+    pub name: Option<String>,
+}
+
+impl From<MultisigOwnerRow> for MultisigOwner {
+    fn from(owner: MultisigOwnerRow) -> Self {
+        Self {
+            address: owner.owner_address,
+            name: owner.owner_name,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct MultisigTransaction {
+    pub id: String,
+    pub to_address: String,
+    pub amount: Option<String>,
+    pub data: Option<String>,
+    pub approvals: Vec<String>,
+    pub status: String,
+    pub created_at: String,
+    pub executed_at: Option<String>,
+}
+
+impl From<MultisigTransactionRow> for MultisigTransaction {
+    fn from(row: MultisigTransactionRow) -> Self {
+        let tx = MultisigTransactionResponse::from(row);
+        Self {
+            id: tx.id,
+            to_address: tx.to_address,
+            amount: tx.amount,
+            data: tx.data,
+            approvals: tx.approvals,
+            status: tx.status,
+            created_at: tx.created_at,
+            executed_at: tx.executed_at,
+        }
+    }
+}
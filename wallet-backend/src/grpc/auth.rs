@@ -0,0 +1,91 @@
+//! gRPC authentication via token metadata
+
+use std::sync::Arc;
+
+use tonic::{service::Interceptor, Request, Status};
+
+use crate::services::user_service::Claims;
+use crate::services::wallet_service::is_unlocked;
+use crate::storage::models::Scope;
+use crate::AppState;
+
+/// Validates `authorization: Bearer <token>` metadata when present and attaches
+/// the claims to the request. Calls without a token pass through unauthenticated;
+/// protected methods check for claims with [`require_claims`].
+#[derive(Clone)]
+pub struct TokenInterceptor {
+    state: Arc<AppState>,
+}
+
+impl TokenInterceptor {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = match request.metadata().get("authorization") {
+            Some(value) => {
+                let value = value
+                    .to_str()
+                    .map_err(|_| Status::unauthenticated("Invalid authorization metadata"))?;
+                value
+                    .strip_prefix("Bearer ")
+                    .ok_or_else(|| Status::unauthenticated("Invalid authorization metadata"))?
+                    .to_string()
+            }
+            None => return Ok(request),
+        };
+
+        let claims = self
+            .state
+            .user_service
+            .validate_token(&token)
+            .map_err(|_| Status::unauthenticated("Invalid or expired token"))?;
+
+        request.extensions_mut().insert(claims);
+        Ok(request)
+    }
+}
+
+/// Require an authenticated caller
+#[allow(clippy::result_large_err)] // tonic::Status is large by design
+pub fn require_claims<T>(request: &Request<T>) -> Result<Claims, Status> {
+    request
+        .extensions()
+        .get::<Claims>()
+        .cloned()
+        .ok_or_else(|| Status::unauthenticated("Missing authorization metadata"))
+}
+
+/// Require an authenticated caller and an unlocked wallet
+pub async fn require_unlocked<T>(
+    state: &Arc<AppState>,
+    request: &Request<T>,
+) -> Result<(), Status> {
+    require_claims(request)?;
+    if !is_unlocked(state).await {
+        return Err(Status::failed_precondition("Wallet is locked"));
+    }
+    Ok(())
+}
+
+/// Require the `trade` scope and a signing token from the caller's session
+/// in `x-signing-token` metadata, as REST sends do
+#[allow(clippy::result_large_err)] // tonic::Status is large by design
+pub fn require_signing_token<T>(state: &Arc<AppState>, request: &Request<T>) -> Result<(), Status> {
+    let claims = require_claims(request)?;
+    if !claims.has_scope(Scope::Trade) {
+        return Err(Status::permission_denied("Token lacks the trade scope"));
+    }
+    let token = request
+        .metadata()
+        .get("x-signing-token")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| Status::unauthenticated("Missing signing token"))?;
+    state
+        .user_service
+        .validate_signing_token(token, &claims)
+        .map_err(|_| Status::unauthenticated("Invalid or expired signing token"))
+}
@@ -0,0 +1,30 @@
+//! gRPC API (enabled with the `grpc` feature)
+//!
+//! Exposes balance, send, history and NFT operations over tonic, backed by the
+//! same services layer as the REST API.
+
+mod auth;
+mod service;
+
+use std::{net::SocketAddr, sync::Arc};
+
+use crate::AppState;
+
+pub mod proto {
+    tonic::include_proto!("valtix.wallet.v1");
+}
+
+use proto::wallet_service_server::WalletServiceServer;
+
+/// Serve the gRPC API on the given address until the process exits
+pub async fn serve(state: Arc<AppState>, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    let interceptor = auth::TokenInterceptor::new(state.clone());
+    let service = WalletServiceServer::with_interceptor(service::WalletGrpc::new(state), interceptor);
+
+    tracing::info!("Starting gRPC server on {}", addr);
+
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve(addr)
+        .await
+}
@@ -0,0 +1,236 @@
+//! gRPC WalletService implementation
+
+use std::sync::Arc;
+
+use tonic::metadata::MetadataValue;
+use tonic::{Request, Response, Status};
+
+use super::auth::{require_claims, require_signing_token, require_unlocked};
+use super::proto::{self, wallet_service_server::WalletService};
+use crate::chains::diagnostics;
+use crate::services::nft_service::{self, NftServiceError};
+use crate::services::transaction_service::{self, SendOutcome, TransactionServiceError};
+use crate::storage::models::{NftResponse, TransactionResponse};
+use crate::AppState;
+
+/// Default history page size, matching the REST API
+const DEFAULT_HISTORY_LIMIT: u32 = 50;
+
+pub struct WalletGrpc {
+    state: Arc<AppState>,
+}
+
+impl WalletGrpc {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl WalletService for WalletGrpc {
+    async fn get_balance(
+        &self,
+        request: Request<proto::GetBalanceRequest>,
+    ) -> Result<Response<proto::BalanceResponse>, Status> {
+        let req = request.into_inner();
+
+        let balance = transaction_service::get_balance(&self.state, &req.chain, &req.address)
+            .await
+            .map_err(transaction_status)?;
+
+        Ok(Response::new(proto::BalanceResponse {
+            chain: balance.chain,
+            address: balance.address,
+            native_balance: balance.native_balance,
+            native_balance_raw: balance.native_balance_raw,
+            native_decimals: balance.native_decimals,
+            native_symbol: balance.native_symbol,
+            tokens: balance
+                .tokens
+                .into_iter()
+                .map(|t| proto::TokenBalance {
+                    address: t.address,
+                    symbol: t.symbol,
+                    name: t.name,
+                    balance: t.balance,
+                    decimals: t.decimals as u32,
+                    formatted_balance: t.formatted_balance,
+                    ui_amount: t.ui_amount,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn send(
+        &self,
+        request: Request<proto::SendRequest>,
+    ) -> Result<Response<proto::SendResponse>, Status> {
+        require_unlocked(&self.state, &request).await?;
+        require_signing_token(&self.state, &request)?;
+        let claims = require_claims(&request)?;
+        let req = request.into_inner();
+
+        // There is no confirmation step over gRPC, so large sends are refused
+        let outcome = transaction_service::request_send(
+            &self.state,
+            &claims.sub,
+            transaction_service::SendRequest {
+                chain: req.chain,
+                from_address: req.from_address,
+                to_address: req.to_address,
+                amount: req.amount,
+                token_address: req.token_address,
+                drain_all: req.drain_all,
+                memo: None,
+                references: Vec::new(),
+                mev_protect: None,
+                submission_mode: None,
+                bucket: None,
+            },
+        )
+        .await
+        .map_err(transaction_status)?;
+        let result = match outcome {
+            SendOutcome::Sent(result) => result,
+            SendOutcome::ConfirmationRequired(challenge) => {
+                return Err(Status::failed_precondition(format!(
+                    "Send is above the large transfer threshold of {} {}; confirm it over REST",
+                    challenge.threshold, challenge.currency
+                )));
+            }
+        };
+
+        Ok(Response::new(proto::SendResponse {
+            tx_hash: result.tx_hash,
+            status: result.status,
+            conversion: result.conversion.map(|c| proto::FiatConversion {
+                fiat_amount: c.fiat_amount,
+                currency: c.currency,
+                rate: c.rate,
+                rate_updated_at: c.rate_updated_at,
+                native_amount: c.native_amount,
+            }),
+        }))
+    }
+
+    async fn get_history(
+        &self,
+        request: Request<proto::GetHistoryRequest>,
+    ) -> Result<Response<proto::GetHistoryResponse>, Status> {
+        require_unlocked(&self.state, &request).await?;
+        let claims = require_claims(&request)?;
+        let req = request.into_inner();
+
+        let history = transaction_service::get_transaction_history(
+            &self.state,
+            &claims.sub,
+            &req.chain,
+            &req.address,
+            req.limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+            req.offset.unwrap_or(0),
+        )
+        .await
+        .map_err(transaction_status)?;
+
+        Ok(Response::new(proto::GetHistoryResponse {
+            transactions: history.into_iter().map(proto::Transaction::from).collect(),
+        }))
+    }
+
+    async fn list_nfts(
+        &self,
+        request: Request<proto::ListNftsRequest>,
+    ) -> Result<Response<proto::ListNftsResponse>, Status> {
+        let req = request.into_inner();
+
+        let nfts = nft_service::get_nfts(&self.state, &req.chain, &req.address)
+            .await
+            .map_err(|e| match e {
+                NftServiceError::InvalidChain(_) => Status::invalid_argument(e.to_string()),
+                NftServiceError::NotFound => Status::not_found(e.to_string()),
+                NftServiceError::FetchFailed(_) => Status::unavailable(e.to_string()),
+                NftServiceError::DatabaseError(_) => Status::internal(e.to_string()),
+            })?;
+
+        Ok(Response::new(proto::ListNftsResponse {
+            nfts: nfts.into_iter().map(proto::Nft::from).collect(),
+        }))
+    }
+}
+
+fn transaction_status(e: TransactionServiceError) -> Status {
+    match e {
+        TransactionServiceError::InvalidChain(_)
+        | TransactionServiceError::InvalidAddress(_)
+        | TransactionServiceError::InvalidAmount(_)
+        | TransactionServiceError::InvalidMemo(_) => Status::invalid_argument(e.to_string()),
+        TransactionServiceError::InsufficientBalance { .. }
+        | TransactionServiceError::RentExemption(_)
+        | TransactionServiceError::ConfirmationRequired(_) => {
+            Status::failed_precondition(e.to_string())
+        }
+        TransactionServiceError::InvalidThreshold(_) | TransactionServiceError::InvalidBucket(_) => {
+            Status::invalid_argument(e.to_string())
+        }
+        TransactionServiceError::BucketExceeded(_) => Status::failed_precondition(e.to_string()),
+        TransactionServiceError::InvalidPassword => Status::unauthenticated(e.to_string()),
+        TransactionServiceError::ChallengeNotFound => Status::not_found(e.to_string()),
+        TransactionServiceError::WalletError(_) => Status::failed_precondition(e.to_string()),
+        TransactionServiceError::TransactionFailed(ref message) => {
+            match diagnostics::diagnose(message) {
+                // Diagnosed failures name their code and remedy in metadata
+                Some(diagnosis) => {
+                    let mut status = Status::failed_precondition(e.to_string());
+                    let metadata = status.metadata_mut();
+                    metadata.insert("x-error-code", MetadataValue::from_static(diagnosis.code));
+                    metadata.insert(
+                        "x-remediation",
+                        MetadataValue::from_static(diagnosis.remediation),
+                    );
+                    status
+                }
+                None => Status::aborted(e.to_string()),
+            }
+        }
+        TransactionServiceError::PriceUnavailable(_)
+        | TransactionServiceError::FeatureDisabled(_) => Status::unavailable(e.to_string()),
+        TransactionServiceError::DatabaseError(_) => Status::internal(e.to_string()),
+    }
+}
+
+impl From<TransactionResponse> for proto::Transaction {
+    fn from(tx: TransactionResponse) -> Self {
+        Self {
+            id: tx.id,
+            chain: tx.chain,
+            signature: tx.signature,
+            tx_type: tx.tx_type,
+            from_address: tx.from_address,
+            to_address: tx.to_address,
+            amount: tx.amount,
+            token_address: tx.token_address,
+            status: tx.status,
+            block_number: tx.block_number,
+            timestamp: tx.timestamp,
+            fiat_amount: tx.fiat_amount,
+            fiat_currency: tx.fiat_currency,
+            fiat_rate: tx.fiat_rate,
+        }
+    }
+}
+
+impl From<NftResponse> for proto::Nft {
+    fn from(nft: NftResponse) -> Self {
+        Self {
+            id: nft.id,
+            chain: nft.chain,
+            token_address: nft.token_address,
+            token_id: nft.token_id,
+            name: nft.name,
+            description: nft.description,
+            image_url: nft.image_url,
+            collection_name: nft.collection_name,
+            metadata_json: nft.metadata.map(|m| m.to_string()),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Multi-Chain Cryptocurrency Wallet Backend
+//!
+//! A production-grade wallet supporting Solana and Ethereum with:
+//! - HD wallet derivation (BIP39/BIP44)
+//! - Secure seed encryption (Argon2id + ChaCha20-Poly1305)
+//! - Transaction history, NFT gallery, address book
+//! - Multi-signature wallet support
+//! - Multi-user authentication with JWT
+
+pub mod api;
+pub mod chains;
+pub mod config;
+pub mod core;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod reporting;
+pub mod services;
+pub mod storage;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::{body::Body, http::{HeaderValue, Request}, Router};
+use rand::RngCore;
+use sqlx::SqlitePool;
+use tokio::sync::{watch, RwLock};
+use tower_http::{
+    catch_panic::CatchPanicLayer, cors::CorsLayer, set_header::SetResponseHeaderLayer, timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
+
+use crate::api::middleware::locale::negotiate_locale;
+use crate::api::middleware::request_id::{request_id, REQUEST_ID_HEADER};
+use crate::api::middleware::tenant::{current_tenant, API_KEY_HEADER};
+use crate::chains::metered::RpcMetrics;
+use crate::chains::{ChainClient, ChainClients};
+use crate::core::Chain;
+use crate::config::Config;
+use crate::services::cross_chain_service::{LiFiAggregator, RouteAggregator};
+use crate::services::feature_flag_service::FlagCache;
+use crate::services::login_risk_service::DEVICE_FINGERPRINT_HEADER;
+use crate::services::maintenance_service::MaintenanceMetrics;
+use crate::services::price_service::PriceFeed;
+use crate::services::user_service::UserService;
+use crate::storage::database::Database;
+use crate::storage::models::{AccountRow, TokenListRow};
+use crate::storage::FieldCipher;
+
+pub struct AppState {
+    /// Validated startup configuration
+    pub config: Config,
+    /// Database connection pool
+    pub db: Database,
+    /// User authentication service
+    pub user_service: UserService,
+    /// Network access, one client per chain
+    pub chains: ChainClients,
+    /// RPC call counts and latencies across all chain clients
+    pub rpc_metrics: Arc<RpcMetrics>,
+    /// Rows pruned and space reclaimed by database maintenance
+    pub maintenance_metrics: MaintenanceMetrics,
+    /// Fiat prices for native coins
+    pub prices: Arc<dyn PriceFeed>,
+    /// Cross-chain swap routes
+    pub routes: Arc<dyn RouteAggregator>,
+    /// Clients for tenants with their own RPC endpoints, with the URLs they
+    /// were built for
+    pub tenant_chains: Mutex<HashMap<String, TenantChains>>,
+    /// Clients for accounts with their own RPC endpoint, by account ID, with
+    /// the URL each was built for
+    pub account_chains: Mutex<HashMap<String, AccountChain>>,
+    /// Token list for search, swap mint checks and balance labels
+    pub token_list: RwLock<Vec<TokenListRow>>,
+    /// Encrypted seeds in memory (encrypted with session_key), by tenant
+    pub unlocked_seed: RwLock<HashMap<String, Vec<u8>>>,
+    /// Ephemeral session key for memory encryption
+    pub session_key: [u8; 32],
+    /// Solana RPC URL
+    pub solana_rpc_url: String,
+    /// Ethereum RPC URL
+    pub eth_rpc_url: String,
+    /// The database schema is ahead of this binary, so writes are refused
+    pub safe_mode: bool,
+    /// Operator-set feature flags, as last read from the database
+    pub feature_flags: FlagCache,
+    /// CORS origins added at runtime, on top of `CORS_ORIGIN`; the CORS
+    /// layer reads the latest value on every request
+    pub cors_origins: watch::Sender<Vec<HeaderValue>>,
+}
+
+impl AppState {
+    /// Build state over a migrated pool, with a fresh session key. Chain
+    /// clients are metered under the configured RPC hosts.
+    pub fn new(
+        config: Config,
+        pool: SqlitePool,
+        chains: ChainClients,
+        prices: Arc<dyn PriceFeed>,
+    ) -> Self {
+        let mut session_key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut session_key);
+        let rpc_metrics = Arc::new(RpcMetrics::new());
+        let mut db = match &config.field_encryption_key {
+            Some(key) => Database::new(pool.clone()).with_field_encryption(FieldCipher::new(key)),
+            None => Database::new(pool.clone()),
+        };
+        if config.siem.enabled() {
+            db = db.with_siem_outbox(config.siem.min_severity);
+        }
+
+        let mut user_service =
+            UserService::new(pool, config.jwt_secret.clone(), config.oauth.clone())
+                .with_login_risk(&config.login_risk);
+        if let Some(key) = &config.field_encryption_key {
+            user_service = user_service.with_field_encryption(FieldCipher::new(key));
+        }
+
+        Self {
+            db,
+            user_service,
+            chains: chains.metered(&rpc_metrics, &config.solana_rpc_url, &config.eth_rpc_url),
+            rpc_metrics,
+            maintenance_metrics: MaintenanceMetrics::new(),
+            prices,
+            routes: Arc::new(LiFiAggregator::new(&config.cross_chain_api_url, config.eth_chain_id)),
+            tenant_chains: Mutex::new(HashMap::new()),
+            account_chains: Mutex::new(HashMap::new()),
+            token_list: RwLock::new(Vec::new()),
+            unlocked_seed: RwLock::new(HashMap::new()),
+            session_key,
+            solana_rpc_url: config.solana_rpc_url.clone(),
+            eth_rpc_url: config.eth_rpc_url.clone(),
+            safe_mode: false,
+            feature_flags: FlagCache::default(),
+            cors_origins: watch::channel(Vec::new()).0,
+            config,
+        }
+    }
+
+    /// Spread history and reporting reads over `replicas`
+    pub fn with_read_replicas(mut self, replicas: Vec<SqlitePool>) -> Self {
+        self.db = self.db.with_read_replicas(replicas);
+        self
+    }
+
+    /// Route cross-chain swaps through `routes` instead of LI.FI
+    pub fn with_route_aggregator(mut self, routes: Arc<dyn RouteAggregator>) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Refuse writes, for a database migrated by a newer release
+    pub fn with_safe_mode(mut self, safe_mode: bool) -> Self {
+        self.safe_mode = safe_mode;
+        self
+    }
+
+    /// Chain clients for the current tenant: its own RPC endpoints where it
+    /// has them, otherwise the server-wide ones
+    pub fn chain_clients(&self) -> ChainClients {
+        current_tenant()
+            .and_then(|tenant| tenant.chains.clone())
+            .unwrap_or_else(|| self.chains.clone())
+    }
+
+    /// Chain clients for work on one account: the tenant's, with the
+    /// account's own RPC endpoint for its chain where it has one
+    pub fn account_clients(&self, account: &AccountRow) -> ChainClients {
+        let clients = self.chain_clients();
+        let (Some(url), Ok(chain)) = (&account.rpc_url, account.chain.parse::<Chain>()) else {
+            return clients;
+        };
+
+        let mut cache = self.account_chains.lock().unwrap();
+        let client = match cache.get(&account.id) {
+            Some((cached_url, client)) if cached_url == url => client.clone(),
+            _ => {
+                let client = ChainClients::connect(
+                    chain,
+                    url,
+                    &self.config.sns_api_url,
+                    &self.rpc_metrics,
+                );
+                cache.insert(account.id.clone(), (url.clone(), client.clone()));
+                client
+            }
+        };
+        clients.with(chain, client)
+    }
+}
+
+/// Solana and Ethereum RPC URL overrides and the clients built for them
+pub type TenantChains = ((Option<String>, Option<String>), ChainClients);
+
+/// RPC URL override of an account and the client built for it
+pub type AccountChain = (String, Arc<dyn ChainClient>);
+
+/// Build the HTTP application with all middleware layers
+pub fn create_app(state: Arc<AppState>) -> Result<Router, config::security::ConfigError> {
+    // Configure CORS
+    let cors = CorsLayer::new()
+        .allow_origin(state.config.security.allow_origin(state.cors_origins.subscribe()))
+        .allow_methods([
+            axum::http::Method::GET,
+            axum::http::Method::POST,
+            axum::http::Method::PUT,
+            axum::http::Method::DELETE,
+            axum::http::Method::OPTIONS,
+        ])
+        .allow_headers([
+            axum::http::header::CONTENT_TYPE,
+            axum::http::header::AUTHORIZATION,
+            axum::http::header::ACCEPT,
+            axum::http::header::COOKIE,
+            axum::http::header::IF_MATCH,
+            axum::http::header::IF_NONE_MATCH,
+            axum::http::HeaderName::from_static("x-csrf-token"),
+            axum::http::HeaderName::from_static(DEVICE_FINGERPRINT_HEADER),
+            REQUEST_ID_HEADER.clone(),
+            API_KEY_HEADER.clone(),
+        ])
+        .expose_headers([REQUEST_ID_HEADER.clone(), axum::http::header::ETAG])
+        .allow_credentials(true);
+
+    let mut app = Router::new()
+        .merge(api::routes::create_routes(state.clone()))
+        .layer(CatchPanicLayer::custom(reporting::panic_response));
+    #[cfg(feature = "sentry")]
+    {
+        app = app.layer(axum::middleware::from_fn(reporting::report_errors));
+    }
+    for (name, value) in state.config.security.headers()? {
+        app = app.layer(SetResponseHeaderLayer::if_not_present(name, value));
+    }
+
+    Ok(app
+        .layer(TimeoutLayer::new(state.config.request_timeout))
+        .layer(cors)
+        .layer(TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
+            let request_id = request
+                .headers()
+                .get(&REQUEST_ID_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            tracing::info_span!(
+                "request",
+                method = %request.method(),
+                uri = %request.uri(),
+                request_id = %request_id,
+                user_id = tracing::field::Empty,
+                tenant_id = tracing::field::Empty,
+            )
+        }))
+        .layer(axum::middleware::from_fn(negotiate_locale))
+        .layer(axum::middleware::from_fn(request_id))
+        .with_state(state))
+}
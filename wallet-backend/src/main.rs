@@ -1,151 +1,289 @@
-//! Multi-Chain Cryptocurrency Wallet Backend
-//!
-//! A production-grade wallet supporting Solana and Ethereum with:
-//! - HD wallet derivation (BIP39/BIP44)
-//! - Secure seed encryption (Argon2id + ChaCha20-Poly1305)
-//! - Transaction history, NFT gallery, address book
-//! - Multi-signature wallet support
-//! - Multi-user authentication with JWT
-
-mod api;
-mod chains;
-mod core;
-mod services;
-mod storage;
-
-use std::{net::SocketAddr, sync::Arc, time::Duration};
-
-use axum::Router;
-use sqlx::sqlite::SqlitePoolOptions;
-use tokio::sync::RwLock;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+//! Multi-Chain Cryptocurrency Wallet Backend server
 
-use crate::services::user_service::UserService;
-use crate::storage::database::Database;
-
-pub struct AppState {
-    /// Database connection pool
-    pub db: Database,
-    /// User authentication service
-    pub user_service: UserService,
-    /// Encrypted seed in memory (encrypted with session_key)
-    pub unlocked_seed: RwLock<Option<Vec<u8>>>,
-    /// Ephemeral session key for memory encryption
-    pub session_key: [u8; 32],
-    /// Solana RPC URL
-    pub solana_rpc_url: String,
-    /// Ethereum RPC URL
-    pub eth_rpc_url: String,
-}
+use std::{net::SocketAddr, sync::Arc};
 
+use sqlx::sqlite::SqlitePoolOptions;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use wallet_backend::chains::ChainClients;
+use wallet_backend::config::Config;
+use wallet_backend::services::price_service::{self, CoinGeckoPriceFeed};
+use wallet_backend::services::{
+    alert_service, bridge_service, confirmation_service, cors_service, cross_chain_service,
+    identity_service, maintenance_service, reconciliation_service, scheduled_service,
+    siem_service, stuck_service, token_list_service, user_service, wallet_service,
+};
+use wallet_backend::services::user_service::UserService;
+use wallet_backend::storage::schema::{schema_status, MIGRATOR};
+use wallet_backend::storage::{Database, FieldCipher};
+use wallet_backend::{create_app, reporting, AppState};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
+    // Load environment variables
+    dotenvy::dotenv().ok();
+
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(report) => {
+            eprintln!("{}", report);
+            std::process::exit(1);
+        }
+    };
+
+    // Error reporting starts before anything else that can fail
+    let _reporting = reporting::init(&config);
+
+    // Initialize tracing; LOG_FORMAT=json emits one JSON object per event
+    let json_logs = std::env::var("LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json"));
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "wallet_backend=debug,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(json_logs.then(|| tracing_subscriber::fmt::layer().json()))
+        .with((!json_logs).then(tracing_subscriber::fmt::layer))
+        .with(reporting::tracing_layer())
         .init();
 
-    // Load environment variables
-    dotenvy::dotenv().ok();
+    tracing::info!("Using {:?} security profile", config.security.profile);
 
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "sqlite:./wallet.db?mode=rwc".to_string());
-    let solana_rpc_url = std::env::var("SOLANA_RPC_URL")
-        .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
-    let eth_rpc_url = std::env::var("ETH_RPC_URL")
-        .unwrap_or_else(|_| "https://ethereum-sepolia-rpc.publicnode.com".to_string());
-    let mut allowed_origins = vec![
-        "http://localhost:3000".parse::<axum::http::HeaderValue>().unwrap(),
-        "https://valtix.vercel.app".parse::<axum::http::HeaderValue>().unwrap(),
-    ];
-
-    if let Ok(origins_str) = std::env::var("CORS_ORIGIN") {
-        for origin in origins_str.split(',') {
-            if let Ok(val) = origin.trim().parse::<axum::http::HeaderValue>() {
-                if !allowed_origins.contains(&val) {
-                    allowed_origins.push(val);
+    // Provisioned secrets are held in the config now; keep them out of the
+    // environment inherited by child processes
+    for name in wallet_backend::config::provision::SECRET_VARS {
+        std::env::remove_var(name);
+    }
+
+    // Create database connection pool
+    let pool = SqlitePoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .acquire_timeout(config.db_acquire_timeout)
+        .connect(&config.database_url)
+        .await?;
+
+    // A schema migrated by a newer release is left alone and served
+    // read-only; one whose applied migrations changed can't be migrated
+    let schema = schema_status(&pool, &MIGRATOR).await?;
+    if schema.has_drift() {
+        eprintln!(
+            "Database migrations differ from this binary's: modified {:?}, failed {:?}",
+            schema.modified, schema.failed
+        );
+        std::process::exit(1);
+    }
+    let safe_mode = schema.is_ahead();
+    if safe_mode {
+        tracing::error!(
+            version = schema.version,
+            expected_version = schema.expected_version,
+            "Database schema is ahead of this binary; starting in safe mode with writes refused"
+        );
+    } else {
+        MIGRATOR.run(&pool).await?;
+        tracing::info!("Database migrations completed");
+    }
+
+    // Admin command: encrypt contact and memo fields stored before
+    // FIELD_ENCRYPTION_KEY was set, then exit
+    if std::env::args().nth(1).as_deref() == Some("encrypt-fields") {
+        let Some(key) = &config.field_encryption_key else {
+            eprintln!("encrypt-fields requires FIELD_ENCRYPTION_KEY");
+            std::process::exit(1);
+        };
+        if safe_mode {
+            eprintln!("encrypt-fields can't write to a database ahead of this binary");
+            std::process::exit(1);
+        }
+        let users = UserService::new(pool.clone(), config.jwt_secret.clone(), config.oauth.clone())
+            .with_field_encryption(FieldCipher::new(key));
+        let db = Database::new(pool).with_field_encryption(FieldCipher::new(key));
+        let (contacts, transactions) = db.encrypt_plaintext_fields().await?;
+        let keys = users.encrypt_signing_keys().await?;
+        println!(
+            "Encrypted {} contact(s), {} transaction memo(s) and {} JWT signing key(s)",
+            contacts, transactions, keys
+        );
+        return Ok(());
+    }
+
+    // Admin command: list, add or retire JWT signing keys, then exit.
+    // Running servers pick up the change within a minute.
+    if std::env::args().nth(1).as_deref() == Some("jwt-keys") {
+        let action = std::env::args().nth(2);
+        if safe_mode && action.as_deref() != Some("list") {
+            eprintln!("jwt-keys can't write to a database ahead of this binary");
+            std::process::exit(1);
+        }
+        let mut users = UserService::new(pool, config.jwt_secret.clone(), config.oauth.clone());
+        if let Some(key) = &config.field_encryption_key {
+            users = users.with_field_encryption(FieldCipher::new(key));
+        }
+        match (action.as_deref(), std::env::args().nth(3)) {
+            (Some("list"), _) => {
+                for key in users.list_signing_keys().await? {
+                    println!(
+                        "{}\tcreated {}\t{}",
+                        key.kid,
+                        key.created_at,
+                        match (&key.retired_at, key.current) {
+                            (Some(at), _) => format!("retired {}", at),
+                            (None, true) => "current".to_string(),
+                            (None, false) => "active".to_string(),
+                        }
+                    );
                 }
             }
+            (Some("add"), _) => {
+                let key = users.add_signing_key().await?;
+                println!("Added signing key {}", key.kid);
+            }
+            (Some("retire"), Some(kid)) => {
+                users.retire_signing_key(&kid).await?;
+                println!("Retired signing key {}", kid);
+            }
+            _ => {
+                eprintln!("usage: jwt-keys list | add | retire <kid>");
+                std::process::exit(1);
+            }
         }
+        return Ok(());
     }
-    
-    let cors_origins = allowed_origins;
 
-    let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+    // Create application state
+    let chains = ChainClients::live(
+        &config.solana_rpc_url,
+        &config.eth_rpc_url,
+        &config.sns_api_url,
+    );
+    let prices = Arc::new(CoinGeckoPriceFeed::new(&config.price_api_url));
 
-    // Create database connection pool
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .acquire_timeout(Duration::from_secs(3))
-        .connect(&database_url)
-        .await?;
+    // Replicas connect lazily, so one that is down at startup only leaves
+    // reads on the primary until it comes up
+    let replicas = config
+        .database_replica_urls
+        .iter()
+        .map(|url| {
+            SqlitePoolOptions::new()
+                .max_connections(config.db_max_connections)
+                .acquire_timeout(config.db_acquire_timeout)
+                .connect_lazy(url)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let state = Arc::new(
+        AppState::new(config, pool, chains, prices)
+            .with_read_replicas(replicas)
+            .with_safe_mode(safe_mode),
+    );
+    if !state.config.database_replica_urls.is_empty() {
+        let healthy = state.db.check_replicas().await?;
+        tracing::info!(
+            healthy,
+            configured = state.config.database_replica_urls.len(),
+            "Read replicas checked"
+        );
+    }
 
-    // Run migrations
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    // Server custody: unlock without waiting for a password over the API
+    if let Some(provision) = state.config.provision.clone() {
+        match wallet_service::provision_wallet(&state, &provision).await {
+            Ok(provisioned) => tracing::info!("Wallet provisioned at startup: {:?}", provisioned),
+            Err(e) => {
+                eprintln!("Wallet provisioning failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    tracing::info!("Database migrations completed");
+    // Demo user, wallet and data for frontend development; the module
+    // isn't compiled into release builds
+    #[cfg(debug_assertions)]
+    if state.config.dev_mode && !state.safe_mode {
+        match wallet_backend::services::dev_service::seed(&state).await {
+            Ok(seed) => tracing::warn!(
+                "DEV_MODE: demo environment {:?}; log in as {}",
+                seed,
+                wallet_backend::services::dev_service::DEMO_EMAIL
+            ),
+            Err(e) => {
+                eprintln!("Seeding the demo environment failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    // Create user service
-    let user_service = UserService::new(pool.clone(), jwt_secret);
+    // Sign with the newest key added with `jwt-keys add`, and follow later
+    // additions and retirements
+    state.user_service.load_signing_keys().await?;
+    user_service::spawn_signing_key_refresh(state.clone());
 
-    // Generate ephemeral session key
-    let mut session_key = [0u8; 32];
-    use rand::RngCore;
-    rand::thread_rng().fill_bytes(&mut session_key);
+    // Allow the origins added through the admin API, and follow later changes
+    cors_service::load_origins(&state).await?;
+    cors_service::spawn_origin_refresh(state.clone());
 
-    // Create application state
-    let state = Arc::new(AppState {
-        db: Database::new(pool),
-        user_service,
-        unlocked_seed: RwLock::new(None),
-        session_key,
-        solana_rpc_url,
-        eth_rpc_url,
-    });
-
-    // Configure CORS
-    let cors = CorsLayer::new()
-        .allow_origin(cors_origins)
-        .allow_methods([
-            axum::http::Method::GET,
-            axum::http::Method::POST,
-            axum::http::Method::PUT,
-            axum::http::Method::DELETE,
-            axum::http::Method::OPTIONS,
-        ])
-        .allow_headers([
-            axum::http::header::CONTENT_TYPE,
-            axum::http::header::AUTHORIZATION,
-            axum::http::header::ACCEPT,
-            axum::http::header::COOKIE,
-            axum::http::HeaderName::from_static("x-csrf-token"),
-        ])
-        .allow_credentials(true);
+    // Keep reads off replicas that are down or behind
+    state
+        .db
+        .spawn_replica_health_checks(state.config.db_replica_check_interval);
+
+    // Background jobs all write, so none run in safe mode
+    if !state.safe_mode {
+        // Load the cached token list and keep it fresh
+        token_list_service::spawn_token_list_refresh(state.clone());
+
+        // Keep cached contact identities (ENS / SNS) fresh
+        identity_service::spawn_identity_refresh(state.clone());
+
+        // Evaluate balance and outflow alerts
+        alert_service::spawn_alert_watcher(state.clone());
+
+        // Settle sent transactions and compare them with their simulations
+        confirmation_service::spawn_confirmation_tracker(state.clone());
+
+        // Flag sends that are stuck pending
+        stuck_service::spawn_stuck_monitor(state.clone());
+
+        // Broadcast time-locked sends once they fall due
+        scheduled_service::spawn_scheduler(state.clone());
+
+        // Follow L2 bridge deposits until they finalize
+        bridge_service::spawn_bridge_watcher(state.clone());
+
+        // Follow cross-chain swaps until their value arrives
+        cross_chain_service::spawn_cross_chain_watcher(state.clone());
+
+        // Price history at transaction time for realized values, and price alerts
+        price_service::spawn_price_backfill(state.clone());
+
+        // Check cached balances against the chain, nightly by default
+        reconciliation_service::spawn_reconciliation(state.clone());
+
+        // Prune expired rows and keep the database file compact
+        maintenance_service::spawn_maintenance(state.clone());
+
+        // Export audit and transaction events to the configured SIEM
+        if state.config.siem.enabled() {
+            siem_service::spawn_siem_exporter(state.clone());
+        }
+    }
+
+    // Start gRPC server alongside REST; its calls aren't checked for
+    // writes, so it stays down in safe mode
+    #[cfg(feature = "grpc")]
+    if !state.safe_mode {
+        let grpc_addr = SocketAddr::from(([0, 0, 0, 0], state.config.grpc_port));
+        let grpc_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = wallet_backend::grpc::serve(grpc_state, grpc_addr).await {
+                tracing::error!("gRPC server failed: {}", e);
+            }
+        });
+    }
 
     // Build router
-    let app = Router::new()
-        .nest("/api/v1", api::routes::create_routes(state.clone()))
-        .layer(cors)
-        .layer(TraceLayer::new_for_http())
-        .with_state(state);
+    let addr = SocketAddr::from(([0, 0, 0, 0], state.config.port));
+    let app = create_app(state)?;
 
     // Start server
-    let port = std::env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse::<u16>()
-        .expect("PORT must be a valid number");
-        
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
     tracing::info!("Starting server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -0,0 +1,159 @@
+//! Error reporting
+//!
+//! With the `sentry` feature and `SENTRY_DSN` set, 5xx responses and panics are
+//! sent to Sentry tagged with the route, user id and request id. Warnings
+//! logged while handling the request (RPC failures, failed sends) are attached
+//! as breadcrumbs. Without the feature only panic recovery is active.
+
+use std::any::Any;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use tracing::Subscriber;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+use crate::api::error::ApiError;
+use crate::config::Config;
+
+/// Keeps the reporting client alive; events are flushed when dropped
+pub struct ReportingGuard {
+    #[cfg(feature = "sentry")]
+    _client: Option<sentry::ClientInitGuard>,
+}
+
+/// Marks a response produced from a caught panic, which the panic hook has
+/// already reported
+#[derive(Debug, Clone, Copy)]
+struct PanicCaught;
+
+/// Start the reporting client if a DSN is configured
+pub fn init(config: &Config) -> ReportingGuard {
+    #[cfg(feature = "sentry")]
+    {
+        let client = config.sentry_dsn.as_deref().map(|dsn| {
+            sentry::init((
+                dsn,
+                sentry::ClientOptions {
+                    release: sentry::release_name!(),
+                    environment: Some(
+                        format!("{:?}", config.security.profile).to_lowercase().into(),
+                    ),
+                    ..Default::default()
+                },
+            ))
+        });
+        ReportingGuard { _client: client }
+    }
+
+    #[cfg(not(feature = "sentry"))]
+    {
+        if config.sentry_dsn.is_some() {
+            eprintln!("SENTRY_DSN is set but the server was built without the `sentry` feature");
+        }
+        ReportingGuard {}
+    }
+}
+
+/// Tracing layer turning warnings into breadcrumbs and errors into events
+#[cfg(feature = "sentry")]
+pub fn tracing_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    sentry::integrations::tracing::layer()
+}
+
+#[cfg(not(feature = "sentry"))]
+pub fn tracing_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_subscriber::layer::Identity::new()
+}
+
+/// Attach the authenticated user to reports for the current request
+pub fn set_user(user_id: &str) {
+    #[cfg(feature = "sentry")]
+    sentry::configure_scope(|scope| {
+        scope.set_user(Some(sentry::User {
+            id: Some(user_id.to_string()),
+            ..Default::default()
+        }));
+    });
+
+    #[cfg(not(feature = "sentry"))]
+    let _ = user_id;
+}
+
+/// Turn a handler panic into a 500 instead of dropping the connection
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    tracing::error!(panic = %message, "Request handler panicked");
+
+    let mut response = ApiError::from_status(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Internal server error",
+    )
+    .into_response();
+    response.extensions_mut().insert(PanicCaught);
+    response
+}
+
+/// Report 5xx responses, with a per-request scope for route and user
+#[cfg(feature = "sentry")]
+pub async fn report_errors(
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    use std::sync::Arc;
+
+    use axum::extract::MatchedPath;
+    use sentry::{Hub, SentryFutureExt};
+
+    use crate::api::middleware::request_id::current_request_id;
+
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    hub.configure_scope(|scope| {
+        scope.set_tag("route", &route);
+        scope.set_tag("method", &method);
+        if let Some(request_id) = current_request_id() {
+            scope.set_tag("request_id", request_id);
+        }
+    });
+
+    let response = next.run(request).bind_hub(hub.clone()).await;
+
+    let status = response.status();
+    if status.is_server_error() && response.extensions().get::<PanicCaught>().is_none() {
+        hub.capture_message(
+            &format!("{} {} returned {}", method, route, status),
+            sentry::Level::Error,
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_response() {
+        let response = panic_response(Box::new("boom"));
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.extensions().get::<PanicCaught>().is_some());
+    }
+}
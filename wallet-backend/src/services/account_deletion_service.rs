@@ -0,0 +1,215 @@
+//! Account deletion service - exports a user's data, then erases it
+//!
+//! Deleting an account takes two calls with the password: the first issues
+//! a confirmation token, the second (with the token) exports an archive of
+//! the user's accounts, contacts and history, revokes every session and
+//! schedules the purge `ACCOUNT_DELETION_GRACE_DAYS` later. Logging in
+//! before then cancels it. The maintenance pass purges due deletions: the
+//! user's rows are deleted, the wallet and its encrypted seed with them when
+//! nobody else in the tenant uses it, and the user record is left as a
+//! scrubbed tombstone.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::services::user_service::{random_token, UserServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{
+    AccountArchive, AccountDeletionConfirmed, AccountDeletionPending, AccountDeletionRow,
+    AuditEventRow, AuditSeverity,
+};
+use crate::AppState;
+
+/// How long a confirmation token stays valid
+pub const CONFIRMATION_TOKEN_TTL_SECS: i64 = 900;
+
+/// Transactions read per query while exporting history
+const EXPORT_PAGE: u32 = 500;
+
+#[derive(Debug, Error)]
+pub enum AccountDeletionError {
+    #[error("Password is incorrect")]
+    InvalidPassword,
+    #[error("Confirmation token is invalid or expired")]
+    InvalidToken,
+    #[error("User error: {0}")]
+    User(UserServiceError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+impl From<UserServiceError> for AccountDeletionError {
+    fn from(e: UserServiceError) -> Self {
+        match e {
+            UserServiceError::InvalidCredentials => AccountDeletionError::InvalidPassword,
+            e => AccountDeletionError::User(e),
+        }
+    }
+}
+
+/// Check the password and issue a token to confirm the deletion with
+pub async fn request_deletion(
+    state: &Arc<AppState>,
+    user_id: &str,
+    password: &str,
+) -> Result<AccountDeletionPending, AccountDeletionError> {
+    state.user_service.verify_password(user_id, password).await?;
+
+    let token = random_token();
+    let now = Utc::now();
+    let row = AccountDeletionRow {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        tenant_id: current_tenant_id(),
+        token_hash: hash_token(&token),
+        token_expires_at: (now + Duration::seconds(CONFIRMATION_TOKEN_TTL_SECS)).to_rfc3339(),
+        confirmed_at: None,
+        purge_after: None,
+        cancelled_at: None,
+        purged_at: None,
+        created_at: now.to_rfc3339(),
+    };
+    state.db.create_account_deletion(&row).await?;
+
+    Ok(AccountDeletionPending {
+        confirmation_token: token,
+        expires_in: CONFIRMATION_TOKEN_TTL_SECS,
+        grace_period_days: state.config.account_deletion_grace_days,
+    })
+}
+
+/// Confirm a deletion: export the user's data, revoke their sessions and
+/// schedule the purge
+pub async fn confirm_deletion(
+    state: &Arc<AppState>,
+    user_id: &str,
+    password: &str,
+    token: &str,
+) -> Result<AccountDeletionConfirmed, AccountDeletionError> {
+    state.user_service.verify_password(user_id, password).await?;
+
+    let deletion = state
+        .db
+        .get_pending_account_deletion(user_id, &hash_token(token))
+        .await?
+        .ok_or(AccountDeletionError::InvalidToken)?;
+    let expires_at = DateTime::parse_from_rfc3339(&deletion.token_expires_at)
+        .map_err(|_| AccountDeletionError::InvalidToken)?;
+    let now = Utc::now();
+    if now > expires_at {
+        return Err(AccountDeletionError::InvalidToken);
+    }
+
+    let archive = export_archive(state, user_id).await?;
+
+    let purge_after =
+        (now + Duration::days(state.config.account_deletion_grace_days as i64)).to_rfc3339();
+    if !state
+        .db
+        .confirm_account_deletion(&deletion.id, &now.to_rfc3339(), &purge_after)
+        .await?
+    {
+        return Err(AccountDeletionError::InvalidToken);
+    }
+    state.user_service.logout_all(user_id).await?;
+
+    let audit = AuditEventRow::new(
+        current_tenant_id(),
+        Some(user_id.to_string()),
+        "account.deletion_confirmed",
+        AuditSeverity::Warning,
+        serde_json::json!({ "deletion_id": deletion.id, "purge_after": purge_after }),
+    );
+    state.db.record_audit_event(&audit).await?;
+    tracing::warn!(user_id = %user_id, purge_after = %purge_after, "Account deletion confirmed");
+
+    Ok(AccountDeletionConfirmed {
+        deletion_id: deletion.id,
+        purge_after,
+        archive,
+    })
+}
+
+/// Everything held about a user: their profile and linked addresses, the
+/// tenant wallet's accounts, contacts and transaction history, and their
+/// send templates and watchlist
+pub async fn export_archive(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<AccountArchive, AccountDeletionError> {
+    let user = state.user_service.get_user(user_id).await?;
+    let (accounts, contacts) = match state.db.get_primary_wallet(&current_tenant_id()).await? {
+        Some(wallet) => (
+            state.db.get_accounts(&wallet.id).await?,
+            state.db.get_contacts(&wallet.id).await?,
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+
+    let mut transactions = Vec::new();
+    for account in &accounts {
+        let mut offset = 0;
+        loop {
+            let page = state
+                .db
+                .get_transactions(&account.id, EXPORT_PAGE, offset)
+                .await?;
+            let done = (page.len() as u32) < EXPORT_PAGE;
+            offset += page.len() as u32;
+            transactions.extend(page);
+            if done {
+                break;
+            }
+        }
+    }
+
+    Ok(AccountArchive {
+        exported_at: Utc::now().to_rfc3339(),
+        user,
+        linked_addresses: state.db.get_user_addresses(user_id).await?,
+        accounts,
+        contacts,
+        transactions,
+        send_templates: state.db.get_send_templates(user_id).await?,
+        watchlist: state.db.get_watches(user_id).await?,
+    })
+}
+
+/// Purge every confirmed deletion whose grace period has ended as of `now`;
+/// the number of accounts purged
+pub async fn purge_due_deletions(
+    state: &Arc<AppState>,
+    now: DateTime<Utc>,
+) -> Result<u64, AccountDeletionError> {
+    let now = now.to_rfc3339();
+    let mut purged = 0;
+    for deletion in state.db.get_due_account_deletions(&now).await? {
+        let wallets_wiped = state.db.purge_user(&deletion, &now).await?;
+        if wallets_wiped {
+            state.unlocked_seed.write().await.remove(&deletion.tenant_id);
+        }
+        purged += 1;
+
+        let audit = AuditEventRow::new(
+            deletion.tenant_id.clone(),
+            Some(deletion.user_id.clone()),
+            "account.purged",
+            AuditSeverity::Warning,
+            serde_json::json!({ "deletion_id": deletion.id, "wallets_wiped": wallets_wiped }),
+        );
+        if let Err(e) = state.db.record_audit_event(&audit).await {
+            tracing::warn!(user_id = %deletion.user_id, error = %e, "Recording account purge failed");
+        }
+        tracing::warn!(user_id = %deletion.user_id, wallets_wiped, "Account purged");
+    }
+    Ok(purged)
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
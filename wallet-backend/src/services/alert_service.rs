@@ -0,0 +1,666 @@
+//! Alert service - user-defined balance and outflow alerts
+//!
+//! A background watcher evaluates enabled rules every `ALERT_CHECK_SECS`:
+//! balance rules against a freshly fetched balance, which is kept as the
+//! account's snapshot, and outflow rules against sends added to the history
+//! since the previous pass. A fired alert is stored as a notification and,
+//! when the rule has a webhook, POSTed to it as JSON.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::{current_tenant_id, with_tenant};
+use crate::core::Chain;
+use crate::services::webhook_service::{self, Endpoint, EventType};
+use crate::services::{tenant_service, watchlist_service};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{
+    AccountRow, AlertNotificationResponse, AlertNotificationRow, AlertResponse, AlertRow,
+    TransactionRow,
+};
+use crate::AppState;
+
+/// Notifications returned by the feed
+const NOTIFICATION_LIMIT: u32 = 100;
+
+/// Upper bound on a webhook delivery
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum AlertServiceError {
+    #[error("Invalid alert kind: {0}")]
+    InvalidKind(String),
+    #[error("Invalid threshold: {0}")]
+    InvalidThreshold(String),
+    #[error("Invalid currency: {0}")]
+    InvalidCurrency(String),
+    #[error("Invalid webhook URL: {0}")]
+    InvalidWebhook(String),
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+    #[error("Invalid window: {0} hours")]
+    InvalidWindow(i64),
+    #[error("Account not found")]
+    AccountNotFound,
+    #[error("Alert not found")]
+    NotFound,
+    #[error("Chain error: {0}")]
+    Chain(String),
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for AlertServiceError {
+    fn from(e: DatabaseError) -> Self {
+        AlertServiceError::DatabaseError(e.to_string())
+    }
+}
+
+/// What an alert watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    BalanceBelow,
+    BalanceAbove,
+    OutflowOver,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertKind::BalanceBelow => write!(f, "balance_below"),
+            AlertKind::BalanceAbove => write!(f, "balance_above"),
+            AlertKind::OutflowOver => write!(f, "outflow_over"),
+        }
+    }
+}
+
+impl FromStr for AlertKind {
+    type Err = AlertServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "balance_below" => Ok(AlertKind::BalanceBelow),
+            "balance_above" => Ok(AlertKind::BalanceAbove),
+            "outflow_over" => Ok(AlertKind::OutflowOver),
+            _ => Err(AlertServiceError::InvalidKind(s.to_string())),
+        }
+    }
+}
+
+/// Create alert request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAlertRequest {
+    pub account_id: String,
+    /// `balance_below`, `balance_above` or `outflow_over`
+    pub kind: String,
+    /// Decimal amount, e.g. `"1"` SOL or `"500"` with `currency: "USD"`
+    pub threshold: String,
+    /// Fiat currency of the threshold; the native coin when omitted
+    pub currency: Option<String>,
+    /// Receives a JSON POST each time the alert fires
+    pub webhook_url: Option<String>,
+}
+
+/// Update alert request; omitted fields are left unchanged and empty strings
+/// clear `currency` and `webhook_url`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateAlertRequest {
+    pub threshold: Option<String>,
+    pub currency: Option<String>,
+    pub webhook_url: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Body POSTed to an alert's webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertWebhookPayload {
+    pub alert_id: String,
+    pub kind: String,
+    pub account_id: String,
+    pub chain: String,
+    pub address: String,
+    pub threshold: String,
+    pub currency: Option<String>,
+    pub message: String,
+    /// Native balance that fired a balance rule
+    pub balance: Option<String>,
+    /// Send that fired an outflow rule
+    pub tx_signature: Option<String>,
+    pub triggered_at: String,
+}
+
+pub async fn list_alerts(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<AlertResponse>, AlertServiceError> {
+    let alerts = state.db.get_alerts(user_id).await?;
+    Ok(alerts.into_iter().map(AlertResponse::from).collect())
+}
+
+/// Create an alert on one of the current tenant's accounts. Outflow rules
+/// only consider sends made from now on.
+pub async fn create_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: CreateAlertRequest,
+) -> Result<AlertResponse, AlertServiceError> {
+    let kind: AlertKind = request.kind.parse()?;
+    let threshold = parse_threshold(&request.threshold)?;
+    let currency = non_empty(request.currency).map(parse_currency).transpose()?;
+    let webhook_url = non_empty(request.webhook_url)
+        .map(validate_webhook)
+        .transpose()?;
+
+    let account = state.db.get_account(&request.account_id).await.map_err(|e| match e {
+        DatabaseError::NotFound => AlertServiceError::AccountNotFound,
+        e => e.into(),
+    })?;
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(AlertServiceError::AccountNotFound);
+    }
+
+    let alert = AlertRow::new(
+        user_id.to_string(),
+        account.id,
+        kind.to_string(),
+        threshold,
+        currency,
+        webhook_url,
+        state.db.get_transaction_history_seq().await?,
+    );
+    state.db.create_alert(&alert).await?;
+    tracing::info!(user_id = %user_id, alert_id = %alert.id, kind = %kind, "Alert created");
+
+    Ok(AlertResponse::from(alert))
+}
+
+/// Change an alert's threshold, currency, webhook or enabled state
+pub async fn update_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: UpdateAlertRequest,
+) -> Result<AlertResponse, AlertServiceError> {
+    let alert = get_owned_alert(state, user_id, id).await?;
+
+    let threshold = match request.threshold {
+        Some(threshold) => parse_threshold(&threshold)?,
+        None => alert.threshold,
+    };
+    let currency = match request.currency {
+        Some(currency) => non_empty(Some(currency)).map(parse_currency).transpose()?,
+        None => alert.currency,
+    };
+    let webhook_url = match request.webhook_url {
+        Some(url) => non_empty(Some(url)).map(validate_webhook).transpose()?,
+        None => alert.webhook_url,
+    };
+    let is_active = request.is_active.unwrap_or(alert.is_active);
+
+    state
+        .db
+        .update_alert(
+            id,
+            &threshold,
+            currency.as_deref(),
+            webhook_url.as_deref(),
+            is_active,
+        )
+        .await?;
+
+    let alert = get_owned_alert(state, user_id, id).await?;
+    Ok(AlertResponse::from(alert))
+}
+
+pub async fn delete_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<(), AlertServiceError> {
+    get_owned_alert(state, user_id, id).await?;
+    Ok(state.db.delete_alert(id).await?)
+}
+
+/// The user's most recent fired balance and price alerts, newest first
+pub async fn list_notifications(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<AlertNotificationResponse>, AlertServiceError> {
+    let notifications = state
+        .db
+        .get_alert_notifications(user_id, NOTIFICATION_LIMIT)
+        .await?;
+    Ok(notifications
+        .into_iter()
+        .map(AlertNotificationResponse::from)
+        .collect())
+}
+
+/// Evaluate every enabled alert once. Returns how many fired.
+pub async fn evaluate_alerts(state: &Arc<AppState>) -> Result<usize, AlertServiceError> {
+    let alerts = state.db.get_active_alerts().await?;
+    if alerts.is_empty() {
+        return Ok(0);
+    }
+
+    // Sends recorded after this point are left for the next pass
+    let history_seq = state.db.get_transaction_history_seq().await?;
+    let mut by_account: BTreeMap<String, Vec<AlertRow>> = BTreeMap::new();
+    for alert in alerts {
+        by_account
+            .entry(alert.account_id.clone())
+            .or_default()
+            .push(alert);
+    }
+
+    let http = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let mut fired = 0;
+    for (account_id, alerts) in by_account {
+        let account = match state.db.get_account(&account_id).await {
+            Ok(account) => account,
+            Err(DatabaseError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let wallet = state.db.get_wallet(&account.wallet_id).await?;
+
+        // Balances come from the tenant's own RPC endpoints where it has them
+        let tenant = match tenant_service::tenant_context(state, &wallet.tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(tenant_id = %wallet.tenant_id, error = %e, "Skipping alerts");
+                continue;
+            }
+        };
+        let watcher = AccountWatcher {
+            state,
+            http: &http,
+            account: &account,
+            history_seq,
+        };
+        match with_tenant(tenant, watcher.evaluate(alerts)).await {
+            Ok(count) => fired += count,
+            Err(e) => tracing::warn!(account_id = %account_id, error = %e, "Alert evaluation failed"),
+        }
+    }
+
+    Ok(fired)
+}
+
+/// Run `evaluate_alerts` and poll the watchlist every `ALERT_CHECK_SECS`
+/// for the life of the process
+pub fn spawn_alert_watcher(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.alert_check_interval);
+        loop {
+            ticker.tick().await;
+            match evaluate_alerts(&state).await {
+                Ok(0) => {}
+                Ok(fired) => tracing::debug!(fired, "Alerts fired"),
+                Err(e) => tracing::warn!(error = %e, "Alert evaluation failed"),
+            }
+            match watchlist_service::poll_watchlist(&state).await {
+                Ok(0) => {}
+                Ok(notified) => tracing::debug!(notified, "Watchlist movements notified"),
+                Err(e) => tracing::warn!(error = %e, "Watchlist poll failed"),
+            }
+        }
+    })
+}
+
+/// One account's alerts for a single pass
+struct AccountWatcher<'a> {
+    state: &'a Arc<AppState>,
+    http: &'a reqwest::Client,
+    account: &'a AccountRow,
+    history_seq: i64,
+}
+
+impl AccountWatcher<'_> {
+    async fn evaluate(&self, alerts: Vec<AlertRow>) -> Result<usize, AlertServiceError> {
+        let chain: Chain = self
+            .account
+            .chain
+            .parse()
+            .map_err(|_| AlertServiceError::Chain(format!("unknown chain {}", self.account.chain)))?;
+
+        let balance = if alerts.iter().any(|a| a.kind != AlertKind::OutflowOver.to_string()) {
+            Some(self.snapshot_balance(chain).await?)
+        } else {
+            None
+        };
+
+        let mut rates: HashMap<String, Option<f64>> = HashMap::new();
+        let mut fired = 0;
+        for alert in alerts {
+            let Ok(kind) = alert.kind.parse::<AlertKind>() else {
+                continue;
+            };
+            let Ok(threshold) = alert.threshold.parse::<f64>() else {
+                continue;
+            };
+            let rate = match &alert.currency {
+                Some(currency) => match rates.get(currency) {
+                    Some(rate) => *rate,
+                    None => {
+                        let rate = self.rate(chain, currency).await;
+                        rates.insert(currency.clone(), rate);
+                        rate
+                    }
+                },
+                None => Some(1.0),
+            };
+            // Without a price the rule can't be judged; retry next pass
+            let Some(rate) = rate else {
+                continue;
+            };
+
+            fired += match (kind, balance) {
+                (AlertKind::OutflowOver, _) => {
+                    self.check_outflows(chain, &alert, threshold, rate).await?
+                }
+                (_, Some(balance)) => {
+                    self.check_balance(chain, &alert, kind, threshold, balance, rate)
+                        .await?
+                }
+                (_, None) => 0,
+            };
+        }
+
+        Ok(fired)
+    }
+
+    /// Fetch the native balance and record it as the account's snapshot
+    async fn snapshot_balance(&self, chain: Chain) -> Result<f64, AlertServiceError> {
+        let balance = self
+            .state
+            .chain_clients()
+            .get(chain)
+            .balance(&self.account.address)
+            .await
+            .map_err(|e| AlertServiceError::Chain(e.to_string()))?;
+
+        self.state
+            .db
+            .upsert_balance_snapshot(
+                &self.account.id,
+                &balance.native_balance,
+                &Utc::now().to_rfc3339(),
+            )
+            .await?;
+
+        Ok(balance.native_balance.parse().unwrap_or(0.0))
+    }
+
+    /// Fiat per native coin, `None` if the feed can't quote it
+    async fn rate(&self, chain: Chain, currency: &str) -> Option<f64> {
+        match self.state.prices.native_price(chain, currency).await {
+            Ok(price) if price.rate.is_finite() && price.rate > 0.0 => Some(price.rate),
+            Ok(price) => {
+                tracing::warn!(currency = %currency, rate = price.rate, "Invalid price for alerts");
+                None
+            }
+            Err(e) => {
+                tracing::warn!(currency = %currency, error = %e, "No price for alerts");
+                None
+            }
+        }
+    }
+
+    /// Fire when the balance crosses the threshold; stay quiet while it
+    /// remains past it
+    async fn check_balance(
+        &self,
+        chain: Chain,
+        alert: &AlertRow,
+        kind: AlertKind,
+        threshold: f64,
+        balance: f64,
+        rate: f64,
+    ) -> Result<usize, AlertServiceError> {
+        let value = balance * rate;
+        let met = match kind {
+            AlertKind::BalanceBelow => value < threshold,
+            _ => value > threshold,
+        };
+        if met == alert.triggered {
+            return Ok(0);
+        }
+        if !met {
+            self.state
+                .db
+                .set_alert_state(&alert.id, false, alert.history_cursor, None)
+                .await?;
+            return Ok(0);
+        }
+
+        let direction = match kind {
+            AlertKind::BalanceBelow => "below",
+            _ => "above",
+        };
+        let message = format!(
+            "Balance of {} is {} {}{}, {} {}",
+            self.account.address,
+            format_amount(balance),
+            native_symbol(chain),
+            in_currency(value, alert.currency.as_deref()),
+            direction,
+            threshold_label(chain, alert),
+        );
+        let triggered_at = self
+            .fire(alert, message, Some(format_amount(balance)), None)
+            .await?;
+        self.state
+            .db
+            .set_alert_state(&alert.id, true, alert.history_cursor, Some(&triggered_at))
+            .await?;
+        Ok(1)
+    }
+
+    /// Fire once for every native send since the last pass that exceeds the
+    /// threshold
+    async fn check_outflows(
+        &self,
+        chain: Chain,
+        alert: &AlertRow,
+        threshold: f64,
+        rate: f64,
+    ) -> Result<usize, AlertServiceError> {
+        let sends = self
+            .state
+            .db
+            .get_outflows(&self.account.id, alert.history_cursor, self.history_seq)
+            .await?;
+
+        let mut fired = 0;
+        let mut triggered_at = None;
+        for send in sends.iter().filter(|tx| tx.token_address.is_none()) {
+            let Some(amount) = send.amount.as_deref().and_then(|a| a.parse::<f64>().ok()) else {
+                continue;
+            };
+            let value = outflow_value(send, amount, rate, alert.currency.as_deref());
+            if value <= threshold {
+                continue;
+            }
+
+            let message = format!(
+                "Sent {} {}{} from {}, over {}",
+                format_amount(amount),
+                native_symbol(chain),
+                in_currency(value, alert.currency.as_deref()),
+                self.account.address,
+                threshold_label(chain, alert),
+            );
+            triggered_at = Some(
+                self.fire(alert, message, None, Some(send.signature.clone()))
+                    .await?,
+            );
+            fired += 1;
+        }
+
+        self.state
+            .db
+            .set_alert_state(&alert.id, false, self.history_seq, triggered_at.as_deref())
+            .await?;
+        Ok(fired)
+    }
+
+    /// Record a notification and deliver it to the webhook, if any. Returns
+    /// the time it fired.
+    async fn fire(
+        &self,
+        alert: &AlertRow,
+        message: String,
+        balance: Option<String>,
+        tx_signature: Option<String>,
+    ) -> Result<String, AlertServiceError> {
+        let mut notification = AlertNotificationRow::new(
+            alert.id.clone(),
+            alert.user_id.clone(),
+            message,
+            balance,
+            tx_signature,
+        );
+
+        if let Some(url) = &alert.webhook_url {
+            let payload = AlertWebhookPayload {
+                alert_id: alert.id.clone(),
+                kind: alert.kind.clone(),
+                account_id: self.account.id.clone(),
+                chain: self.account.chain.clone(),
+                address: self.account.address.clone(),
+                threshold: alert.threshold.clone(),
+                currency: alert.currency.clone(),
+                message: notification.message.clone(),
+                balance: notification.balance.clone(),
+                tx_signature: notification.tx_signature.clone(),
+                triggered_at: notification.created_at.clone(),
+            };
+            let tenant_id = current_tenant_id();
+            let endpoint = Endpoint {
+                tenant_id: &tenant_id,
+                webhook_id: &alert.id,
+                url,
+            };
+            notification.webhook_status = Some(
+                webhook_service::deliver(self.state, self.http, endpoint, EventType::AlertFired, &payload)
+                    .await,
+            );
+        }
+
+        self.state.db.create_alert_notification(&notification).await?;
+        tracing::info!(alert_id = %alert.id, user_id = %alert.user_id, "Alert fired");
+        Ok(notification.created_at)
+    }
+}
+
+/// Value of a send in the alert's unit. Sends entered in the same fiat
+/// currency use the amount the user entered rather than today's price.
+fn outflow_value(send: &TransactionRow, amount: f64, rate: f64, currency: Option<&str>) -> f64 {
+    match (currency, &send.fiat_currency, &send.fiat_amount) {
+        (Some(currency), Some(sent_in), Some(fiat)) if sent_in.eq_ignore_ascii_case(currency) => {
+            fiat.parse().unwrap_or(amount * rate)
+        }
+        _ => amount * rate,
+    }
+}
+
+fn native_symbol(chain: Chain) -> &'static str {
+    match chain {
+        Chain::Solana => "SOL",
+        Chain::Ethereum => "ETH",
+    }
+}
+
+fn threshold_label(chain: Chain, alert: &AlertRow) -> String {
+    let unit = alert.currency.as_deref().unwrap_or(native_symbol(chain));
+    format!("{} {}", alert.threshold, unit)
+}
+
+/// ` (160.00 USD)` for fiat rules, nothing for native ones
+fn in_currency(value: f64, currency: Option<&str>) -> String {
+    match currency {
+        Some(currency) => format!(" ({:.2} {})", value, currency),
+        None => String::new(),
+    }
+}
+
+pub(crate) fn format_amount(amount: f64) -> String {
+    let formatted = format!("{:.9}", amount);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn parse_threshold(threshold: &str) -> Result<String, AlertServiceError> {
+    let trimmed = threshold.trim();
+    match trimmed.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(trimmed.to_string()),
+        _ => Err(AlertServiceError::InvalidThreshold(threshold.to_string())),
+    }
+}
+
+pub(crate) fn parse_currency(currency: String) -> Result<String, AlertServiceError> {
+    if currency.len() == 3 && currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(currency.to_uppercase())
+    } else {
+        Err(AlertServiceError::InvalidCurrency(currency))
+    }
+}
+
+pub(crate) fn validate_webhook(url: String) -> Result<String, AlertServiceError> {
+    match reqwest::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+        _ => Err(AlertServiceError::InvalidWebhook(url)),
+    }
+}
+
+pub(crate) fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+async fn get_owned_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<AlertRow, AlertServiceError> {
+    match state.db.get_alert(id).await {
+        Ok(alert) if alert.user_id == user_id => Ok(alert),
+        Ok(_) | Err(DatabaseError::NotFound) => Err(AlertServiceError::NotFound),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_inputs() {
+        assert_eq!("outflow_over".parse::<AlertKind>().unwrap(), AlertKind::OutflowOver);
+        assert!("balance".parse::<AlertKind>().is_err());
+        assert_eq!(parse_threshold(" 1.5 ").unwrap(), "1.5");
+        assert!(parse_threshold("0").is_err());
+        assert!(parse_threshold("NaN").is_err());
+        assert_eq!(parse_currency("usd".to_string()).unwrap(), "USD");
+        assert!(parse_currency("dollars".to_string()).is_err());
+        assert!(validate_webhook("ftp://example.com".to_string()).is_err());
+        assert_eq!(format_amount(0.399995), "0.399995");
+        assert_eq!(format_amount(2.0), "2");
+    }
+}
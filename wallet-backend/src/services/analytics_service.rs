@@ -0,0 +1,423 @@
+//! Analytics service - spending aggregated over history, and the tags users
+//! assign to transactions to group it by
+//!
+//! Outflows are recorded sends that haven't failed, other than internal
+//! transfers between the wallet's own accounts. Token amounts are summed
+//! exactly per token; fiat totals use the prices backfilled at each transaction's
+//! date (see `price_service::backfill_prices`), so rows not yet priced are
+//! counted as `unpriced` rather than valued. Fees are the exact amounts
+//! settled sends paid, summed in base units.
+
+use std::sync::Arc;
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::core::{format_units, parse_units, Chain};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{FeeRow, SpendingGroup, SpendingRow};
+use crate::AppState;
+
+const MAX_TAGS: usize = 10;
+const MAX_TAG_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum AnalyticsError {
+    #[error("Transaction not found")]
+    NotFound,
+    #[error("Invalid tag: {0}")]
+    InvalidTag(String),
+    #[error("At most {MAX_TAGS} tags per transaction")]
+    TooManyTags,
+    #[error("Invalid range: {0}")]
+    InvalidRange(String),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTagsRequest {
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagsResponse {
+    pub transaction_id: String,
+    pub tags: Vec<String>,
+}
+
+/// Days to aggregate, both inclusive; open-ended when unset
+#[derive(Debug, Default, Deserialize)]
+pub struct SpendingQuery {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingReport {
+    /// Currency of every `fiat_total`
+    pub currency: String,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// Oldest month first
+    pub by_month: Vec<SpendingBucket>,
+    /// Highest fiat total first; untagged sends have a null `key`
+    pub by_tag: Vec<SpendingBucket>,
+    /// Highest fiat total first, keyed by recipient address
+    pub by_counterparty: Vec<SpendingBucket>,
+    /// Network fees per chain
+    pub fees: Vec<FeeTotal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SpendingBucket {
+    pub key: Option<String>,
+    /// Contact name of a counterparty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub count: i64,
+    /// Sum of realized values; `None` if nothing in the bucket is priced
+    pub fiat_total: Option<String>,
+    /// Sends without a historical price, left out of `fiat_total`
+    pub unpriced: i64,
+    pub tokens: Vec<TokenTotal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenTotal {
+    pub chain: String,
+    /// `None` for the native coin
+    pub token_address: Option<String>,
+    pub amount: String,
+    pub count: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FeeTotal {
+    pub chain: String,
+    /// Native coin paid by settled sends
+    pub amount: String,
+    pub count: i64,
+    /// Sends not settled yet, whose fee isn't known
+    pub unsettled: i64,
+}
+
+/// Tags of one of the tenant's transactions
+pub async fn get_tags(state: &Arc<AppState>, id: &str) -> Result<TagsResponse, AnalyticsError> {
+    check_owned(state, id).await?;
+    Ok(TagsResponse {
+        transaction_id: id.to_string(),
+        tags: state.db.get_transaction_tags(id).await?,
+    })
+}
+
+/// Replace a transaction's tags. Tags are trimmed, lower-cased and
+/// de-duplicated.
+pub async fn set_tags(
+    state: &Arc<AppState>,
+    id: &str,
+    request: SetTagsRequest,
+) -> Result<TagsResponse, AnalyticsError> {
+    let tags = normalize_tags(request.tags)?;
+    check_owned(state, id).await?;
+    state.db.set_transaction_tags(id, &tags).await?;
+
+    Ok(TagsResponse {
+        transaction_id: id.to_string(),
+        tags,
+    })
+}
+
+/// The tenant's outflows by month, tag and counterparty
+pub async fn spending(
+    state: &Arc<AppState>,
+    query: SpendingQuery,
+) -> Result<SpendingReport, AnalyticsError> {
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since > until {
+            return Err(AnalyticsError::InvalidRange(
+                "since is after until".to_string(),
+            ));
+        }
+    }
+    // Timestamps are RFC 3339, so day strings bound them lexically
+    let since = query.since.map(|d| d.to_string()).unwrap_or_default();
+    let until = match query.until {
+        Some(until) => until
+            .checked_add_days(Days::new(1))
+            .ok_or_else(|| AnalyticsError::InvalidRange("until is out of range".to_string()))?
+            .to_string(),
+        None => "9999".to_string(),
+    };
+
+    let tenant_id = current_tenant_id();
+    let currency = state.config.reporting_currency.as_str();
+    let rows = |group| state.db.get_spending(&tenant_id, group, currency, &since, &until);
+    let by_month = fold_buckets(rows(SpendingGroup::Month).await?);
+
+    Ok(SpendingReport {
+        currency: currency.to_string(),
+        since: query.since,
+        until: query.until,
+        by_month: by_month.into_iter().map(|(bucket, _)| bucket).collect(),
+        by_tag: ranked(fold_buckets(rows(SpendingGroup::Tag).await?)),
+        by_counterparty: ranked(fold_buckets(rows(SpendingGroup::Counterparty).await?)),
+        fees: fee_totals(state.db.get_fees_paid(&tenant_id, &since, &until).await?),
+    })
+}
+
+async fn check_owned(state: &Arc<AppState>, id: &str) -> Result<(), AnalyticsError> {
+    let tx = match state.db.get_transaction(id).await {
+        Ok(tx) => tx,
+        Err(DatabaseError::NotFound) => return Err(AnalyticsError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let account = state.db.get_account(&tx.account_id).await?;
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(AnalyticsError::NotFound);
+    }
+    Ok(())
+}
+
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, AnalyticsError> {
+    let mut normalized = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LEN || tag.chars().any(char::is_control) {
+            return Err(AnalyticsError::InvalidTag(format!(
+                "'{}' must be 1-{} printable characters",
+                tag, MAX_TAG_LEN
+            )));
+        }
+        normalized.push(tag);
+    }
+    normalized.sort();
+    normalized.dedup();
+
+    if normalized.len() > MAX_TAGS {
+        return Err(AnalyticsError::TooManyTags);
+    }
+    Ok(normalized)
+}
+
+/// Fold sends (ordered by bucket, then token) into buckets with per-token
+/// totals, each paired with its fiat total for sorting
+fn fold_buckets(rows: Vec<SpendingRow>) -> Vec<(SpendingBucket, f64)> {
+    let mut buckets: Vec<(SpendingBucket, f64, Vec<AmountSum>)> = Vec::new();
+    for row in rows {
+        if !matches!(buckets.last(), Some((bucket, ..)) if bucket.key == row.bucket) {
+            buckets.push((
+                SpendingBucket {
+                    key: row.bucket,
+                    name: None,
+                    count: 0,
+                    fiat_total: None,
+                    unpriced: 0,
+                    tokens: Vec::new(),
+                },
+                0.0,
+                Vec::new(),
+            ));
+        }
+        let (bucket, fiat, sums) = buckets.last_mut().expect("pushed above");
+        bucket.count += 1;
+        bucket.name = bucket.name.take().or(row.name);
+        match row.fiat {
+            Some(value) => {
+                *fiat += value;
+                bucket.fiat_total = Some(format!("{:.2}", fiat));
+            }
+            None => bucket.unpriced += 1,
+        }
+
+        let same_token = |token: &TokenTotal| {
+            token.chain == row.chain && token.token_address == row.token_address
+        };
+        if !bucket.tokens.last().is_some_and(same_token) {
+            bucket.tokens.push(TokenTotal {
+                chain: row.chain,
+                token_address: row.token_address,
+                amount: String::new(),
+                count: 0,
+            });
+            sums.push(AmountSum::default());
+        }
+        bucket.tokens.last_mut().expect("pushed above").count += 1;
+        sums.last_mut().expect("pushed above").add(&row.amount);
+    }
+
+    buckets
+        .into_iter()
+        .map(|(mut bucket, fiat, sums)| {
+            for (token, sum) in bucket.tokens.iter_mut().zip(sums) {
+                token.amount = format_units(sum.units, sum.decimals);
+            }
+            (bucket, fiat)
+        })
+        .collect()
+}
+
+/// Exact sum of display-unit amounts, kept in units of the most decimals
+/// any of them has
+#[derive(Debug, Default)]
+struct AmountSum {
+    units: u128,
+    decimals: u32,
+}
+
+impl AmountSum {
+    /// Add `amount`; one that doesn't parse as a decimal is skipped
+    fn add(&mut self, amount: &str) {
+        let decimals = amount
+            .split_once('.')
+            .map_or(0, |(_, fraction)| fraction.trim_end_matches('0').len() as u32);
+        if decimals > self.decimals {
+            let Some(scale) = 10u128.checked_pow(decimals - self.decimals) else {
+                return;
+            };
+            self.units = self.units.saturating_mul(scale);
+            self.decimals = decimals;
+        }
+        if let Some(units) = parse_units(amount, self.decimals) {
+            self.units = self.units.saturating_add(units);
+        }
+    }
+}
+
+/// Sum fees (ordered by chain) exactly, in base units
+fn fee_totals(rows: Vec<FeeRow>) -> Vec<FeeTotal> {
+    let mut totals: Vec<(FeeTotal, u128)> = Vec::new();
+    for row in rows {
+        if !matches!(totals.last(), Some((total, _)) if total.chain == row.chain) {
+            totals.push((
+                FeeTotal {
+                    chain: row.chain,
+                    amount: String::new(),
+                    count: 0,
+                    unsettled: 0,
+                },
+                0,
+            ));
+        }
+        let (total, paid) = totals.last_mut().expect("pushed above");
+        match row.fee_paid.and_then(|fee| fee.parse::<u128>().ok()) {
+            Some(fee) => {
+                total.count += 1;
+                *paid = paid.saturating_add(fee);
+            }
+            None => total.unsettled += 1,
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|(mut total, paid)| {
+            let decimals = total.chain.parse().map_or(9, Chain::native_decimals);
+            total.amount = format_units(paid, decimals);
+            total
+        })
+        .collect()
+}
+
+/// Highest fiat total first
+fn ranked(mut buckets: Vec<(SpendingBucket, f64)>) -> Vec<SpendingBucket> {
+    buckets.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    buckets.into_iter().map(|(bucket, _)| bucket).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(bucket: &str, token: Option<&str>, amount: &str, fiat: Option<f64>) -> SpendingRow {
+        SpendingRow {
+            bucket: Some(bucket.to_string()),
+            name: None,
+            chain: "solana".to_string(),
+            token_address: token.map(str::to_string),
+            amount: amount.to_string(),
+            fiat,
+        }
+    }
+
+    #[test]
+    fn test_fold_buckets() {
+        let buckets = fold_buckets(vec![
+            row("2026-09", None, "0.5", Some(75.0)),
+            row("2026-09", None, "0.25", Some(37.5)),
+            row("2026-09", Some("mint"), "10", None),
+            row("2026-10", None, "1.5", Some(20.0)),
+        ]);
+
+        assert_eq!(buckets.len(), 2);
+        let (september, fiat) = &buckets[0];
+        assert_eq!(september.key.as_deref(), Some("2026-09"));
+        assert_eq!(september.count, 3);
+        assert_eq!(september.unpriced, 1);
+        assert_eq!(september.fiat_total.as_deref(), Some("112.50"));
+        assert_eq!(*fiat, 112.5);
+        assert_eq!(september.tokens.len(), 2);
+        assert_eq!(september.tokens[0].amount, "0.75");
+        assert_eq!(september.tokens[0].count, 2);
+        assert_eq!(september.tokens[1].amount, "10");
+    }
+
+    #[test]
+    fn test_amounts_summed_exactly() {
+        let buckets = fold_buckets(vec![
+            row("2026-09", None, "0.1", None),
+            row("2026-09", None, "0.2", None),
+            row("2026-10", Some("mint"), "1234567890.123456789", None),
+            row("2026-10", Some("mint"), "0.000000000000000001", None),
+            row("2026-10", Some("mint"), "9007199254740993", None),
+        ]);
+
+        assert_eq!(buckets[0].0.tokens[0].amount, "0.3");
+        assert_eq!(buckets[1].0.tokens[0].amount, "9007200489308883.123456789000000001");
+    }
+
+    #[test]
+    fn test_fee_totals() {
+        let fee = |chain: &str, paid: Option<&str>| FeeRow {
+            chain: chain.to_string(),
+            fee_paid: paid.map(str::to_string),
+        };
+        let totals = fee_totals(vec![
+            fee("ethereum", Some("21000000000000")),
+            fee("ethereum", Some("1")),
+            fee("ethereum", None),
+            fee("solana", Some("5000")),
+        ]);
+
+        assert_eq!(
+            totals,
+            vec![
+                FeeTotal {
+                    chain: "ethereum".to_string(),
+                    amount: "0.000021000000000001".to_string(),
+                    count: 2,
+                    unsettled: 1,
+                },
+                FeeTotal {
+                    chain: "solana".to_string(),
+                    amount: "0.000005".to_string(),
+                    count: 1,
+                    unsettled: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = vec![" Rent ".to_string(), "rent".to_string(), "food".to_string()];
+        assert_eq!(normalize_tags(tags).unwrap(), vec!["food", "rent"]);
+        assert!(normalize_tags(vec!["  ".to_string()]).is_err());
+        assert!(normalize_tags(vec!["x".repeat(33)]).is_err());
+        let many = (0..11).map(|i| i.to_string()).collect();
+        assert!(matches!(normalize_tags(many), Err(AnalyticsError::TooManyTags)));
+    }
+}
@@ -0,0 +1,179 @@
+//! Avatar service - deterministic identicons for accounts and contacts
+//!
+//! Follows the blockies algorithm used across Ethereum wallets: the address
+//! seeds a xorshift generator that picks three colours and a horizontally
+//! mirrored 8x8 grid. The same address always yields the same image, so
+//! every frontend renders one visual identity without its own library.
+
+use std::fmt::Write;
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::chains::{ethereum, solana};
+use crate::core::Chain;
+
+/// Cells per side of the grid
+const GRID: usize = 8;
+
+pub const MIN_SIZE: u32 = 16;
+pub const MAX_SIZE: u32 = 512;
+pub const DEFAULT_SIZE: u32 = 64;
+
+#[derive(Debug, Error)]
+pub enum AvatarError {
+    #[error("Unsupported chain: {0}")]
+    UnsupportedChain(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Size must be between {MIN_SIZE} and {MAX_SIZE} pixels")]
+    InvalidSize,
+}
+
+/// A rendered identicon
+#[derive(Debug, Clone)]
+pub struct Avatar {
+    pub svg: String,
+    /// Strong validator derived from the seed and size
+    pub etag: String,
+}
+
+/// Identicon for an address, `size` pixels square
+pub fn generate(chain: &str, address: &str, size: Option<u32>) -> Result<Avatar, AvatarError> {
+    let size = size.unwrap_or(DEFAULT_SIZE);
+    if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+        return Err(AvatarError::InvalidSize);
+    }
+    let seed = seed(chain, address)?;
+
+    let digest = Sha256::digest(format!("{}:{}", seed, size).as_bytes());
+    Ok(Avatar {
+        svg: render(&seed, size),
+        etag: format!("\"{}\"", hex::encode(&digest[..16])),
+    })
+}
+
+/// Canonical form of the address; Ethereum addresses are case-insensitive,
+/// so checksummed and lower-case spellings share an identicon
+fn seed(chain: &str, address: &str) -> Result<String, AvatarError> {
+    let chain: Chain = chain
+        .parse()
+        .map_err(|_| AvatarError::UnsupportedChain(chain.to_string()))?;
+    let address = address.trim();
+
+    match chain {
+        Chain::Solana if solana::validate_address(address) => Ok(address.to_string()),
+        Chain::Ethereum if ethereum::validate_address(address) => {
+            let hex = address.strip_prefix("0x").unwrap_or(address);
+            Ok(format!("0x{}", hex.to_lowercase()))
+        }
+        _ => Err(AvatarError::InvalidAddress(address.to_string())),
+    }
+}
+
+fn render(seed: &str, size: u32) -> String {
+    let mut rng = Xorshift::new(seed);
+    let color = rng.color();
+    let background = rng.color();
+    let spot = rng.color();
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" \
+         viewBox=\"0 0 {GRID} {GRID}\" shape-rendering=\"crispEdges\">\
+         <rect width=\"{GRID}\" height=\"{GRID}\" fill=\"{background}\"/>"
+    );
+    for (y, row) in rng.cells().iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            let fill = match cell {
+                1 => &color,
+                2 => &spot,
+                _ => continue,
+            };
+            let _ = write!(svg, "<rect x=\"{x}\" y=\"{y}\" width=\"1\" height=\"1\" fill=\"{fill}\"/>");
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// The blockies generator; arithmetic wraps like the reference's int32 maths
+struct Xorshift([i32; 4]);
+
+impl Xorshift {
+    fn new(seed: &str) -> Self {
+        let mut state = [0i32; 4];
+        for (i, byte) in seed.bytes().enumerate() {
+            let s = state[i % 4];
+            state[i % 4] = (s << 5).wrapping_sub(s).wrapping_add(byte as i32);
+        }
+        Self(state)
+    }
+
+    /// Uniform in [0, 2)
+    fn next(&mut self) -> f64 {
+        let [a, b, c, d] = self.0;
+        let t = a ^ (a << 11);
+        self.0 = [b, c, d, d ^ (d >> 19) ^ t ^ (t >> 8)];
+        self.0[3] as u32 as f64 / 2_147_483_648.0
+    }
+
+    fn color(&mut self) -> String {
+        let hue = (self.next() * 360.0).floor();
+        let saturation = self.next() * 60.0 + 40.0;
+        let lightness = (self.next() + self.next() + self.next() + self.next()) * 25.0;
+        format!("hsl({},{:.1}%,{:.1}%)", hue, saturation, lightness)
+    }
+
+    /// 0 = background, 1 = colour, 2 = spot; the left half is random and
+    /// mirrored onto the right
+    fn cells(&mut self) -> [[u8; GRID]; GRID] {
+        let mut cells = [[0u8; GRID]; GRID];
+        for row in cells.iter_mut() {
+            for x in 0..GRID / 2 {
+                let value = (self.next() * 2.3).floor() as u8;
+                row[x] = value;
+                row[GRID - 1 - x] = value;
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ETH: &str = "0x52908400098527886E0F7030069857D2E4169EE7";
+
+    #[test]
+    fn test_avatar_is_deterministic() {
+        let first = generate("ethereum", ETH, None).unwrap();
+        let lower = generate("ethereum", &ETH.to_lowercase(), None).unwrap();
+        assert_eq!(first.svg, lower.svg);
+        assert_eq!(first.etag, lower.etag);
+        assert!(first.svg.starts_with("<svg") && first.svg.ends_with("</svg>"));
+        assert!(first.svg.contains("width=\"64\""));
+
+        let other = generate("ethereum", "0x8617e340b3d01fa5f11f306f4090fd50e238070d", None).unwrap();
+        assert_ne!(first.svg, other.svg);
+
+        let larger = generate("ethereum", ETH, Some(128)).unwrap();
+        assert_ne!(first.etag, larger.etag);
+    }
+
+    #[test]
+    fn test_cells_are_mirrored() {
+        let cells = Xorshift::new("0x52908400098527886e0f7030069857d2e4169ee7").cells();
+        for row in cells {
+            assert!(row.iter().all(|&c| c <= 2));
+            assert!(row.iter().eq(row.iter().rev()));
+        }
+    }
+
+    #[test]
+    fn test_avatar_rejects_bad_input() {
+        assert!(matches!(generate("bitcoin", ETH, None), Err(AvatarError::UnsupportedChain(_))));
+        assert!(matches!(generate("solana", ETH, None), Err(AvatarError::InvalidAddress(_))));
+        assert!(matches!(generate("ethereum", ETH, Some(8)), Err(AvatarError::InvalidSize)));
+    }
+}
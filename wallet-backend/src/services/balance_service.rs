@@ -0,0 +1,203 @@
+//! Balance service - every account's balance in one call
+//!
+//! Accounts are read concurrently, at most `BALANCE_FETCH_CONCURRENCY` at a
+//! time, and the call returns once all have answered or
+//! `BALANCE_FETCH_DEADLINE_MS` has passed. An account that failed or didn't
+//! answer in time is listed with its error instead of failing the rest.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::task::{JoinError, JoinSet};
+use tokio::time::{timeout_at, Instant};
+
+use crate::api::middleware::tenant::{current_tenant_id, in_current_tenant};
+use crate::services::token_service::{self, TokenViewQuery};
+use crate::services::transaction_service::{self, BalanceResponse};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::AccountRow;
+use crate::AppState;
+
+#[derive(Debug, Error)]
+pub enum BalanceServiceError {
+    #[error("No wallet found")]
+    NoWalletFound,
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Why an account has no balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceFetchErrorCode {
+    /// The chain read failed
+    Failed,
+    /// No answer before the deadline
+    Timeout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceFetchError {
+    pub code: BalanceFetchErrorCode,
+    pub message: String,
+}
+
+/// One account's balance, or why it's missing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub account_id: String,
+    pub name: String,
+    pub chain: String,
+    pub address: String,
+    pub balance: Option<BalanceResponse>,
+    pub error: Option<BalanceFetchError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountBalancesResponse {
+    /// In account order
+    pub accounts: Vec<AccountBalance>,
+    /// Whether every account has its balance
+    pub complete: bool,
+    pub elapsed_ms: u64,
+}
+
+type FetchResult = Result<BalanceResponse, BalanceFetchError>;
+
+/// Native and token balances of every account of the wallet, with the
+/// user's token list applied when signed in and its tokens sorted and folded
+/// as `view` or the user's settings ask
+pub async fn get_account_balances(
+    state: &Arc<AppState>,
+    user_id: Option<&str>,
+    view: TokenViewQuery,
+) -> Result<AccountBalancesResponse, BalanceServiceError> {
+    let started = Instant::now();
+    let deadline = started + state.config.balance_fetch_deadline;
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await?
+        .ok_or(BalanceServiceError::NoWalletFound)?;
+    let accounts = state.db.get_accounts(&wallet.id).await?;
+
+    let mut results: Vec<Option<FetchResult>> = accounts.iter().map(|_| None).collect();
+    // Which account a task was for, for tasks that panic
+    let mut tasks = HashMap::new();
+    let mut fetches = JoinSet::new();
+    let mut timed_out = false;
+    for (index, account) in accounts.iter().enumerate() {
+        if fetches.len() >= state.config.balance_fetch_concurrency {
+            match timeout_at(deadline, fetches.join_next_with_id()).await {
+                Ok(Some(joined)) => record(&mut results, &tasks, joined),
+                Ok(None) => {}
+                Err(_) => {
+                    timed_out = true;
+                    break;
+                }
+            }
+        }
+        let (state, account) = (state.clone(), account.clone());
+        let (user_id, view) = (user_id.map(str::to_string), view.clone());
+        let task = fetches.spawn(in_current_tenant(async move {
+            (index, fetch(&state, &account, user_id.as_deref(), &view).await)
+        }));
+        tasks.insert(task.id(), index);
+    }
+    while !timed_out {
+        match timeout_at(deadline, fetches.join_next_with_id()).await {
+            Ok(Some(joined)) => record(&mut results, &tasks, joined),
+            Ok(None) => break,
+            Err(_) => timed_out = true,
+        }
+    }
+    // Reads still running are abandoned
+    fetches.abort_all();
+
+    let deadline_ms = state.config.balance_fetch_deadline.as_millis();
+    let accounts: Vec<AccountBalance> = accounts
+        .into_iter()
+        .zip(results)
+        .map(|(account, result)| {
+            let (balance, error) = match result {
+                Some(Ok(balance)) => (Some(balance), None),
+                Some(Err(error)) => (None, Some(error)),
+                None => (
+                    None,
+                    Some(BalanceFetchError {
+                        code: BalanceFetchErrorCode::Timeout,
+                        message: format!("No balance within {} ms", deadline_ms),
+                    }),
+                ),
+            };
+            AccountBalance {
+                account_id: account.id,
+                name: account.name,
+                chain: account.chain,
+                address: account.address,
+                balance,
+                error,
+            }
+        })
+        .collect();
+
+    let failed = accounts.iter().filter(|a| a.error.is_some()).count();
+    if failed > 0 {
+        tracing::warn!(failed, total = accounts.len(), "Some account balances are missing");
+    }
+    Ok(AccountBalancesResponse {
+        complete: failed == 0,
+        accounts,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+async fn fetch(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    user_id: Option<&str>,
+    view: &TokenViewQuery,
+) -> FetchResult {
+    let failed = |message: String| BalanceFetchError {
+        code: BalanceFetchErrorCode::Failed,
+        message,
+    };
+    let mut balance = transaction_service::get_balance(state, &account.chain, &account.address)
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    if let Some(user_id) = user_id {
+        balance.tokens = token_service::merge_user_tokens(
+            state,
+            user_id,
+            &account.chain,
+            &account.address,
+            balance.tokens,
+        )
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    }
+    token_service::apply_token_view(state, user_id, &mut balance, view)
+        .await
+        .map_err(|e| failed(e.to_string()))?;
+    Ok(balance)
+}
+
+fn record(
+    results: &mut [Option<FetchResult>],
+    tasks: &HashMap<tokio::task::Id, usize>,
+    joined: Result<(tokio::task::Id, (usize, FetchResult)), JoinError>,
+) {
+    match joined {
+        Ok((_, (index, result))) => results[index] = Some(result),
+        Err(e) => {
+            if let Some(&index) = tasks.get(&e.id()) {
+                results[index] = Some(Err(BalanceFetchError {
+                    code: BalanceFetchErrorCode::Failed,
+                    message: e.to_string(),
+                }));
+            }
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Bridge service - ETH deposits into the official bridges of L2s
+//!
+//! A deposit is sent from an Ethereum account to the bridge contract of
+//! Arbitrum, OP Mainnet or Base on the configured L1, and is credited to the
+//! same address on the L2. Each deposit is tracked in `bridge_transfers`:
+//! the bridge watcher marks it confirmed once it lands on L1, and finalized
+//! once the bridge's deposit window has passed, by when the L2 has credited
+//! it. Finalized and failed deposits are logged and POSTed as JSON to the
+//! deposit's webhook when it has one.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::{current_tenant_id, with_tenant};
+use crate::chains::ethereum::{l2_bridge, L2Bridge, L2_BRIDGES};
+use crate::chains::ChainClientError;
+use crate::core::{format_units, parse_units, Chain};
+use crate::services::feature_flag_service::{self, FeatureDisabled, FeatureFlag};
+use crate::services::tenant_service;
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::services::webhook_service::{self, Endpoint, EventType};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, BridgeTransferRow, TransactionRow};
+use crate::AppState;
+
+/// ETH has 18 decimals
+const DECIMALS: u32 = 18;
+/// Upper bound on a webhook delivery
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum BridgeServiceError {
+    #[error("Not found")]
+    NotFound,
+    #[error("Bridging is only available from ethereum, not {0}")]
+    InvalidChain(String),
+    #[error("Unknown L2 network: {0}")]
+    UnknownNetwork(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid webhook URL: {0}")]
+    InvalidWebhook(String),
+    #[error("{0}")]
+    FeatureDisabled(#[from] FeatureDisabled),
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// An L2 reachable through its official bridge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct L2NetworkResponse {
+    pub network: String,
+    pub name: String,
+    /// Bridge contract on the configured L1; `None` if it isn't deployed there
+    pub bridge_contract: Option<String>,
+    /// Seconds after landing on L1 until a deposit is credited on L2
+    pub deposit_window_secs: i64,
+    /// Challenge period of withdrawals back to L1, in seconds
+    pub withdrawal_challenge_secs: i64,
+}
+
+/// Bridge deposit request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeDepositRequest {
+    pub account_id: String,
+    /// `arbitrum`, `optimism` or `base`
+    pub network: String,
+    /// ETH to deposit
+    pub amount: String,
+    /// Receives a JSON POST when the deposit finalizes or fails
+    pub webhook_url: Option<String>,
+}
+
+/// Filter for listing deposits
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BridgeTransfersQuery {
+    pub account_id: Option<String>,
+}
+
+/// A deposit and where it stands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeTransferResponse {
+    pub id: String,
+    pub account_id: String,
+    pub network: String,
+    pub l1_chain_id: i64,
+    pub l1_tx_hash: String,
+    pub bridge_contract: String,
+    /// Credited at the same address on L2
+    pub address: String,
+    pub amount: String,
+    /// `submitted`, `confirmed`, `finalized` or `failed`
+    pub status: String,
+    pub l1_block_number: Option<i64>,
+    pub confirmed_at: Option<String>,
+    pub finalizes_at: Option<String>,
+    pub finalized_at: Option<String>,
+    pub webhook_url: Option<String>,
+    pub webhook_status: Option<String>,
+    /// Challenge period a withdrawal of these funds back to L1 waits out
+    pub withdrawal_challenge_secs: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<BridgeTransferRow> for BridgeTransferResponse {
+    fn from(row: BridgeTransferRow) -> Self {
+        Self {
+            withdrawal_challenge_secs: l2_bridge(&row.network)
+                .map_or(0, |bridge| bridge.withdrawal_secs),
+            id: row.id,
+            account_id: row.account_id,
+            network: row.network,
+            l1_chain_id: row.l1_chain_id,
+            l1_tx_hash: row.l1_tx_hash,
+            bridge_contract: row.bridge_contract,
+            address: row.address,
+            amount: row.amount,
+            status: row.status,
+            l1_block_number: row.l1_block_number,
+            confirmed_at: row.confirmed_at,
+            finalizes_at: row.finalizes_at,
+            finalized_at: row.finalized_at,
+            webhook_url: row.webhook_url,
+            webhook_status: row.webhook_status,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Body POSTed to a deposit's webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeWebhookPayload {
+    pub transfer_id: String,
+    pub network: String,
+    pub l1_tx_hash: String,
+    pub address: String,
+    pub amount: String,
+    /// `finalized` or `failed`
+    pub status: String,
+    pub message: String,
+    pub detected_at: String,
+}
+
+/// Every supported L2 with its bridge on the configured L1
+pub fn list_networks(state: &Arc<AppState>) -> Vec<L2NetworkResponse> {
+    L2_BRIDGES
+        .iter()
+        .map(|bridge| L2NetworkResponse {
+            network: bridge.network.to_string(),
+            name: bridge.name.to_string(),
+            bridge_contract: bridge.contract(state.config.eth_chain_id).map(str::to_string),
+            deposit_window_secs: bridge.deposit_secs,
+            withdrawal_challenge_secs: bridge.withdrawal_secs,
+        })
+        .collect()
+}
+
+/// Deposit ETH from an account into an L2's bridge
+pub async fn deposit(
+    state: &Arc<AppState>,
+    request: BridgeDepositRequest,
+) -> Result<BridgeTransferResponse, BridgeServiceError> {
+    let account = tenant_account(state, &request.account_id).await?;
+    if account.chain != Chain::Ethereum.to_string() {
+        return Err(BridgeServiceError::InvalidChain(account.chain));
+    }
+    let bridge = l2_bridge(&request.network)
+        .ok_or_else(|| BridgeServiceError::UnknownNetwork(request.network.clone()))?;
+    let wei = parse_units(&request.amount, DECIMALS)
+        .filter(|wei| *wei > 0)
+        .ok_or_else(|| BridgeServiceError::InvalidAmount(request.amount.trim().to_string()))?;
+    let webhook_url = request
+        .webhook_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .map(validate_webhook)
+        .transpose()?;
+    feature_flag_service::check(state, FeatureFlag::Sends(Chain::Ethereum)).await?;
+
+    let seed = get_seed(state).await?;
+    let sent = state
+        .account_clients(&account)
+        .ethereum
+        .bridge_deposit(&seed, &account.derivation_path, bridge.network, wei)
+        .await?;
+
+    let transfer = BridgeTransferRow::new(
+        account.id.clone(),
+        bridge.network.to_string(),
+        sent.l1_chain_id as i64,
+        sent.tx_hash,
+        sent.contract,
+        account.address.clone(),
+        format_units(wei, DECIMALS),
+        webhook_url,
+    );
+    state.db.create_bridge_transfer(&transfer).await?;
+    state
+        .db
+        .upsert_transaction(&history_row(&transfer, "pending", None))
+        .await?;
+    tracing::info!(
+        transfer_id = %transfer.id,
+        network = %transfer.network,
+        tx_hash = %transfer.l1_tx_hash,
+        "Bridge deposit sent"
+    );
+
+    Ok(transfer.into())
+}
+
+/// The current tenant's deposits, newest first, optionally of one account
+pub async fn list_transfers(
+    state: &Arc<AppState>,
+    query: BridgeTransfersQuery,
+) -> Result<Vec<BridgeTransferResponse>, BridgeServiceError> {
+    let rows = state
+        .db
+        .get_bridge_transfers(&current_tenant_id(), query.account_id.as_deref())
+        .await?;
+    Ok(rows.into_iter().map(BridgeTransferResponse::from).collect())
+}
+
+pub async fn get_transfer(
+    state: &Arc<AppState>,
+    id: &str,
+) -> Result<BridgeTransferResponse, BridgeServiceError> {
+    let transfer = match state.db.get_bridge_transfer(id).await {
+        Ok(transfer) => transfer,
+        Err(DatabaseError::NotFound) => return Err(BridgeServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    tenant_account(state, &transfer.account_id).await?;
+    Ok(transfer.into())
+}
+
+/// Advance every open deposit once. Returns how many finalized or failed.
+pub async fn poll_bridge_transfers(state: &Arc<AppState>) -> Result<usize, BridgeServiceError> {
+    let open = state.db.get_open_bridge_transfers().await?;
+    if open.is_empty() {
+        return Ok(0);
+    }
+
+    let http = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let mut finished = 0;
+    for transfer in open {
+        let account = match state.db.get_account(&transfer.account_id).await {
+            Ok(account) => account,
+            Err(DatabaseError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let wallet = state.db.get_wallet(&account.wallet_id).await?;
+
+        // Sent through the tenant's endpoints, so followed through them too
+        let tenant = match tenant_service::tenant_context(state, &wallet.tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(tenant_id = %wallet.tenant_id, error = %e, "Skipping bridge check");
+                continue;
+            }
+        };
+        match with_tenant(tenant, advance(state, &http, &account, &transfer)).await {
+            Ok(true) => finished += 1,
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(transfer_id = %transfer.id, error = %e, "Bridge check failed")
+            }
+        }
+    }
+
+    Ok(finished)
+}
+
+/// Move one deposit along; `true` once it has finalized or failed
+async fn advance(
+    state: &Arc<AppState>,
+    http: &reqwest::Client,
+    account: &AccountRow,
+    transfer: &BridgeTransferRow,
+) -> Result<bool, BridgeServiceError> {
+    let Some(bridge) = l2_bridge(&transfer.network) else {
+        return Err(BridgeServiceError::UnknownNetwork(transfer.network.clone()));
+    };
+    let now = Utc::now();
+
+    if transfer.status == "submitted" {
+        let landed = state
+            .account_clients(account)
+            .ethereum
+            .transaction_effects(&transfer.l1_tx_hash, std::slice::from_ref(&transfer.address))
+            .await?;
+        let Some(landed) = landed else {
+            return Ok(false);
+        };
+        let succeeded = landed.status != "failed"
+            && landed.receipt.as_ref().is_none_or(|receipt| receipt.succeeded);
+        state
+            .db
+            .upsert_transaction(&history_row(
+                transfer,
+                if succeeded { "confirmed" } else { "failed" },
+                landed.block_number,
+            ))
+            .await?;
+        if !succeeded {
+            finish(state, http, bridge, transfer, "failed", landed.block_number).await?;
+            return Ok(true);
+        }
+
+        let finalizes_at = now + chrono::Duration::seconds(bridge.deposit_secs);
+        state
+            .db
+            .confirm_bridge_transfer(
+                &transfer.id,
+                landed.block_number,
+                &now.to_rfc3339(),
+                &finalizes_at.to_rfc3339(),
+            )
+            .await?;
+        tracing::info!(transfer_id = %transfer.id, finalizes_at = %finalizes_at, "Bridge deposit landed on L1");
+        return Ok(false);
+    }
+
+    let due = transfer
+        .finalizes_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .is_some_and(|at| at <= now);
+    if !due {
+        return Ok(false);
+    }
+    state
+        .db
+        .upsert_transaction(&history_row(transfer, "confirmed", transfer.l1_block_number))
+        .await?;
+    finish(state, http, bridge, transfer, "finalized", None).await?;
+    Ok(true)
+}
+
+/// Close a deposit and notify its outcome
+async fn finish(
+    state: &Arc<AppState>,
+    http: &reqwest::Client,
+    bridge: &L2Bridge,
+    transfer: &BridgeTransferRow,
+    status: &str,
+    l1_block_number: Option<i64>,
+) -> Result<(), BridgeServiceError> {
+    let now = Utc::now().to_rfc3339();
+    let webhook_status = notify(state, http, bridge, transfer, status, &now).await;
+    state
+        .db
+        .finish_bridge_transfer(
+            &transfer.id,
+            status,
+            l1_block_number,
+            &now,
+            webhook_status.as_deref(),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Deliver a finished deposit to its webhook. Returns the delivery status,
+/// `None` without a webhook.
+async fn notify(
+    state: &Arc<AppState>,
+    http: &reqwest::Client,
+    bridge: &L2Bridge,
+    transfer: &BridgeTransferRow,
+    status: &str,
+    detected_at: &str,
+) -> Option<String> {
+    let message = match status {
+        "finalized" => format!(
+            "{} ETH bridged to {}, credited to {}",
+            transfer.amount, bridge.name, transfer.address
+        ),
+        _ => format!(
+            "Deposit of {} ETH to {} failed on L1",
+            transfer.amount, bridge.name
+        ),
+    };
+    tracing::info!(transfer_id = %transfer.id, account_id = %transfer.account_id, "{}", message);
+
+    let url = transfer.webhook_url.as_ref()?;
+    let payload = BridgeWebhookPayload {
+        transfer_id: transfer.id.clone(),
+        network: transfer.network.clone(),
+        l1_tx_hash: transfer.l1_tx_hash.clone(),
+        address: transfer.address.clone(),
+        amount: transfer.amount.clone(),
+        status: status.to_string(),
+        message,
+        detected_at: detected_at.to_string(),
+    };
+    let tenant_id = current_tenant_id();
+    let endpoint = Endpoint {
+        tenant_id: &tenant_id,
+        webhook_id: &transfer.id,
+        url,
+    };
+    Some(
+        webhook_service::deliver(state, http, endpoint, EventType::BridgeTransferFinished, &payload)
+            .await,
+    )
+}
+
+/// The deposit as a send to the bridge contract in the account's history
+fn history_row(
+    transfer: &BridgeTransferRow,
+    status: &str,
+    block_number: Option<i64>,
+) -> TransactionRow {
+    TransactionRow::new(
+        transfer.account_id.clone(),
+        Chain::Ethereum.to_string(),
+        transfer.l1_tx_hash.clone(),
+        "send".to_string(),
+        Some(transfer.address.clone()),
+        Some(transfer.bridge_contract.clone()),
+        Some(transfer.amount.clone()),
+        None,
+        status.to_string(),
+        block_number,
+        Some(transfer.created_at.clone()),
+    )
+}
+
+fn validate_webhook(url: String) -> Result<String, BridgeServiceError> {
+    match reqwest::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+        _ => Err(BridgeServiceError::InvalidWebhook(url)),
+    }
+}
+
+/// An account of the current tenant; others are reported as not found
+async fn tenant_account(
+    state: &Arc<AppState>,
+    account_id: &str,
+) -> Result<AccountRow, BridgeServiceError> {
+    let account = match state.db.get_account(account_id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(BridgeServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(BridgeServiceError::NotFound);
+    }
+    Ok(account)
+}
+
+/// Run `poll_bridge_transfers` every `BRIDGE_CHECK_SECS` for the life of
+/// the process
+pub fn spawn_bridge_watcher(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.bridge_check_interval);
+        loop {
+            ticker.tick().await;
+            match poll_bridge_transfers(&state).await {
+                Ok(0) => {}
+                Ok(finished) => tracing::debug!(finished, "Bridge deposits finished"),
+                Err(e) => tracing::warn!(error = %e, "Bridge check failed"),
+            }
+        }
+    })
+}
@@ -0,0 +1,407 @@
+//! Bucket service - savings buckets over an account's native balance
+//!
+//! Buckets are named partitions such as "savings" or "rent", kept off-chain.
+//! Allocating to a bucket, or moving funds between buckets, only updates
+//! the books. A send may name a bucket to be held to what that bucket holds,
+//! and is then charged to it; sends that don't name one are unconstrained.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::core::{format_units, parse_units, Chain};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, BucketRow};
+use crate::AppState;
+
+/// Most buckets an account may have
+const MAX_BUCKETS: usize = 20;
+/// Longest bucket name, in characters
+const MAX_NAME_CHARS: usize = 40;
+/// Attempts at a bucket update that keeps racing another
+const UPDATE_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Error)]
+pub enum BucketError {
+    #[error("Account not found")]
+    AccountNotFound,
+    #[error("Bucket not found")]
+    NotFound,
+    #[error("Invalid bucket: {0}")]
+    InvalidBucket(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("A bucket named {0} already exists on this account")]
+    Duplicate(String),
+    #[error("{0}")]
+    InsufficientFunds(String),
+    #[error("Buckets changed while updating; try again")]
+    Conflict,
+    #[error("Chain error: {0}")]
+    Chain(String),
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+impl From<DatabaseError> for BucketError {
+    fn from(e: DatabaseError) -> Self {
+        match e {
+            DatabaseError::NotFound => BucketError::NotFound,
+            _ => BucketError::Database(e.to_string()),
+        }
+    }
+}
+
+/// Create bucket request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBucketRequest {
+    pub name: String,
+    /// Native amount to allocate from the unallocated balance
+    #[serde(default)]
+    pub amount: Option<String>,
+}
+
+/// Move funds between buckets; an absent side is the unallocated balance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketTransferRequest {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    /// Native amount, in display units
+    pub amount: String,
+}
+
+/// A bucket for API, in display units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketResponse {
+    pub id: String,
+    pub name: String,
+    pub balance: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// How an account's native balance is split, in display units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketSummary {
+    pub account_id: String,
+    /// Live native balance; `None` if it couldn't be read
+    pub balance: Option<String>,
+    /// Sum of every bucket
+    pub allocated: String,
+    /// Balance outside any bucket; `None` without a live balance
+    pub unallocated: Option<String>,
+    /// The buckets hold more than the account does, after sends that
+    /// weren't charged to a bucket
+    pub overallocated: bool,
+    pub buckets: Vec<BucketResponse>,
+}
+
+/// An account's buckets against its live balance
+pub async fn list_buckets(state: &Arc<AppState>, account_id: &str) -> Result<BucketSummary, BucketError> {
+    let account = get_tenant_account(state, account_id).await?;
+    let balance = live_balance(state, &account).await.ok();
+    summary(state, &account, balance).await
+}
+
+/// Add a bucket, optionally funded from the unallocated balance
+pub async fn create_bucket(
+    state: &Arc<AppState>,
+    account_id: &str,
+    request: CreateBucketRequest,
+) -> Result<BucketSummary, BucketError> {
+    let account = get_tenant_account(state, account_id).await?;
+    let name = request.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(BucketError::InvalidBucket(format!(
+            "name must be 1-{} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    let buckets = state.db.get_buckets(&account.id).await?;
+    if buckets.len() >= MAX_BUCKETS {
+        return Err(BucketError::InvalidBucket(format!(
+            "an account can have at most {} buckets",
+            MAX_BUCKETS
+        )));
+    }
+
+    // Funding is checked before the bucket exists, so a refused amount
+    // leaves nothing behind
+    let decimals = account_decimals(&account)?;
+    let (funding, balance) = match &request.amount {
+        Some(amount) => {
+            let amount = positive_units(amount, decimals)?;
+            let balance = live_balance(state, &account).await?;
+            let unallocated = balance.saturating_sub(total(&buckets));
+            check_available(amount, unallocated, "the unallocated balance", decimals)?;
+            (amount, Some(balance))
+        }
+        None => (0, live_balance(state, &account).await.ok()),
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let bucket = BucketRow {
+        id: uuid::Uuid::new_v4().to_string(),
+        account_id: account.id.clone(),
+        name: name.to_string(),
+        allocated: funding.to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    state.db.create_bucket(&bucket).await.map_err(|e| match e {
+        DatabaseError::AlreadyExists => BucketError::Duplicate(name.to_string()),
+        e => e.into(),
+    })?;
+    tracing::info!(account_id = %account.id, bucket_id = %bucket.id, "Bucket created");
+
+    summary(state, &account, balance).await
+}
+
+/// Allocate, release or move funds between an account's buckets
+pub async fn transfer(
+    state: &Arc<AppState>,
+    account_id: &str,
+    request: BucketTransferRequest,
+) -> Result<BucketSummary, BucketError> {
+    let account = get_tenant_account(state, account_id).await?;
+    let decimals = account_decimals(&account)?;
+    let amount = positive_units(&request.amount, decimals)?;
+    if request.from == request.to {
+        return Err(BucketError::InvalidBucket("from and to must differ".to_string()));
+    }
+
+    let buckets = state.db.get_buckets(&account.id).await?;
+    let find = |id: &Option<String>| -> Result<Option<&BucketRow>, BucketError> {
+        match id {
+            Some(id) => buckets.iter().find(|b| &b.id == id).map(Some).ok_or(BucketError::NotFound),
+            None => Ok(None),
+        }
+    };
+    let (from, to) = (find(&request.from)?, find(&request.to)?);
+
+    // Funding from the unallocated balance needs it read from the chain
+    let balance = match from {
+        Some(_) => None,
+        None => Some(live_balance(state, &account).await?),
+    };
+    let available = match (from, balance) {
+        (Some(bucket), _) => allocated(bucket),
+        (None, Some(balance)) => balance.saturating_sub(total(&buckets)),
+        (None, None) => 0,
+    };
+    let source = from.map_or("the unallocated balance", |b| b.name.as_str());
+    check_available(amount, available, source, decimals)?;
+
+    let mut updates = Vec::new();
+    if let Some(bucket) = from {
+        updates.push((bucket, (allocated(bucket) - amount).to_string()));
+    }
+    if let Some(bucket) = to {
+        updates.push((bucket, (allocated(bucket) + amount).to_string()));
+    }
+    let changes: Vec<(&str, &str, &str)> = updates
+        .iter()
+        .map(|(bucket, new)| (bucket.id.as_str(), bucket.allocated.as_str(), new.as_str()))
+        .collect();
+    if !state
+        .db
+        .set_bucket_allocations(&changes, &chrono::Utc::now().to_rfc3339())
+        .await?
+    {
+        return Err(BucketError::Conflict);
+    }
+
+    tracing::info!(account_id = %account.id, amount = %request.amount, "Bucket funds moved");
+    let balance = match balance {
+        Some(balance) => Some(balance),
+        None => live_balance(state, &account).await.ok(),
+    };
+    summary(state, &account, balance).await
+}
+
+/// Remove a bucket; what it held returns to the unallocated balance
+pub async fn delete_bucket(
+    state: &Arc<AppState>,
+    account_id: &str,
+    bucket_id: &str,
+) -> Result<BucketSummary, BucketError> {
+    let account = get_tenant_account(state, account_id).await?;
+    let bucket = state.db.get_bucket(bucket_id).await?;
+    if bucket.account_id != account.id {
+        return Err(BucketError::NotFound);
+    }
+    state.db.delete_bucket(&bucket.id).await?;
+    list_buckets(state, &account.id).await
+}
+
+/// Refuse a send from `bucket_id` that is more than the bucket holds.
+/// Only native amounts can be charged to a bucket.
+pub async fn check_spend(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    bucket_id: &str,
+    amount: &str,
+) -> Result<(), BucketError> {
+    let bucket = match state.db.get_bucket(bucket_id).await {
+        Ok(bucket) if bucket.account_id == account.id => bucket,
+        Ok(_) | Err(DatabaseError::NotFound) => {
+            return Err(BucketError::InvalidBucket(format!(
+                "no bucket {} on {}",
+                bucket_id, account.address
+            )))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let decimals = account_decimals(account)?;
+    let amount = positive_units(amount, decimals)?;
+    check_available(amount, allocated(&bucket), &bucket.name, decimals)
+}
+
+/// Charge a sent amount to its bucket, never below zero
+pub async fn record_spend(state: &Arc<AppState>, bucket_id: &str, amount: &str, chain: Chain) -> Result<(), BucketError> {
+    let Some(amount) = parse_units(amount, chain.native_decimals()) else {
+        return Err(BucketError::InvalidAmount(amount.to_string()));
+    };
+    for _ in 0..UPDATE_ATTEMPTS {
+        let bucket = state.db.get_bucket(bucket_id).await?;
+        let remaining = allocated(&bucket).saturating_sub(amount).to_string();
+        let change = [(bucket.id.as_str(), bucket.allocated.as_str(), remaining.as_str())];
+        if state
+            .db
+            .set_bucket_allocations(&change, &chrono::Utc::now().to_rfc3339())
+            .await?
+        {
+            return Ok(());
+        }
+    }
+    Err(BucketError::Conflict)
+}
+
+/// An account's buckets against `balance`, given in display units; `None`
+/// if the account has no buckets or belongs to another tenant
+pub async fn balance_summary(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    balance: &str,
+) -> Result<Option<BucketSummary>, BucketError> {
+    let decimals = account_decimals(account)?;
+    let buckets = state.db.get_buckets(&account.id).await?;
+    if buckets.is_empty() || state.db.get_wallet(&account.wallet_id).await?.tenant_id != current_tenant_id() {
+        return Ok(None);
+    }
+    Ok(Some(summarize(account, buckets, parse_units(balance, decimals), decimals)))
+}
+
+async fn summary(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    balance: Option<u128>,
+) -> Result<BucketSummary, BucketError> {
+    let decimals = account_decimals(account)?;
+    let buckets = state.db.get_buckets(&account.id).await?;
+    Ok(summarize(account, buckets, balance, decimals))
+}
+
+fn summarize(account: &AccountRow, buckets: Vec<BucketRow>, balance: Option<u128>, decimals: u32) -> BucketSummary {
+    let allocated_total = total(&buckets);
+    BucketSummary {
+        account_id: account.id.clone(),
+        balance: balance.map(|b| format_units(b, decimals)),
+        allocated: format_units(allocated_total, decimals),
+        unallocated: balance.map(|b| format_units(b.saturating_sub(allocated_total), decimals)),
+        overallocated: balance.is_some_and(|b| allocated_total > b),
+        buckets: buckets
+            .into_iter()
+            .map(|b| BucketResponse {
+                balance: format_units(allocated(&b), decimals),
+                id: b.id,
+                name: b.name,
+                created_at: b.created_at,
+                updated_at: b.updated_at,
+            })
+            .collect(),
+    }
+}
+
+/// Live native balance in base units
+async fn live_balance(state: &Arc<AppState>, account: &AccountRow) -> Result<u128, BucketError> {
+    let chain: Chain = account
+        .chain
+        .parse()
+        .map_err(|_| BucketError::AccountNotFound)?;
+    let balance = state
+        .account_clients(account)
+        .get(chain)
+        .balance(&account.address)
+        .await
+        .map_err(|e| BucketError::Chain(e.to_string()))?;
+    parse_units(&balance.native_balance, chain.native_decimals())
+        .ok_or_else(|| BucketError::Chain(format!("unreadable balance {}", balance.native_balance)))
+}
+
+async fn get_tenant_account(state: &Arc<AppState>, account_id: &str) -> Result<AccountRow, BucketError> {
+    let account = state.db.get_account(account_id).await.map_err(|e| match e {
+        DatabaseError::NotFound => BucketError::AccountNotFound,
+        e => e.into(),
+    })?;
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(BucketError::AccountNotFound);
+    }
+    Ok(account)
+}
+
+fn check_available(amount: u128, available: u128, source: &str, decimals: u32) -> Result<(), BucketError> {
+    if amount > available {
+        return Err(BucketError::InsufficientFunds(format!(
+            "{} requested, {} available in {}",
+            format_units(amount, decimals),
+            format_units(available, decimals),
+            source
+        )));
+    }
+    Ok(())
+}
+
+fn account_decimals(account: &AccountRow) -> Result<u32, BucketError> {
+    account
+        .chain
+        .parse()
+        .map(Chain::native_decimals)
+        .map_err(|_| BucketError::AccountNotFound)
+}
+
+fn allocated(bucket: &BucketRow) -> u128 {
+    bucket.allocated.parse().unwrap_or(0)
+}
+
+fn total(buckets: &[BucketRow]) -> u128 {
+    buckets.iter().map(allocated).fold(0, u128::saturating_add)
+}
+
+/// A positive plain decimal amount in base units
+fn positive_units(amount: &str, decimals: u32) -> Result<u128, BucketError> {
+    match parse_units(amount, decimals) {
+        Some(units) if units > 0 => Ok(units),
+        _ => Err(BucketError::InvalidAmount(amount.trim().to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positive_units() {
+        assert_eq!(positive_units(" 1.5 ", 9).unwrap(), 1_500_000_000);
+        assert_eq!(positive_units("0.000000001", 9).unwrap(), 1);
+        assert!(positive_units("1.0000000001", 9).is_err());
+        assert!(positive_units("-1", 9).is_err());
+        assert!(positive_units("0", 9).is_err());
+        assert!(positive_units("abc", 9).is_err());
+    }
+}
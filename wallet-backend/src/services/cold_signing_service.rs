@@ -0,0 +1,272 @@
+//! Cold signing - transfers signed on a device that never goes online
+//!
+//! The server builds an unsigned native transfer from an address whose key
+//! it doesn't hold and exports it as base64, or as a run of QR codes for an
+//! air-gapped signer's camera. The signature that comes back is checked
+//! against the sending address over exactly the exported transaction before
+//! the signed transaction is assembled and broadcast.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::{ethereum, solana, ChainClientError};
+use crate::core::{format_units, parse_units, Chain};
+use crate::services::feature_flag_service::{self, FeatureDisabled, FeatureFlag};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::ColdTransactionRow;
+use crate::AppState;
+
+/// Base64 characters carried by each QR part, small enough to scan reliably
+/// from a screen
+pub const QR_PART_CHARS: usize = 400;
+
+/// Prefix of every QR part, followed by `<id>:<n>/<total>:<data>`
+const QR_PART_PREFIX: &str = "valtix-cold";
+
+#[derive(Debug, Error)]
+pub enum ColdSigningServiceError {
+    #[error("Cold transaction not found")]
+    NotFound,
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("Transaction was already broadcast")]
+    AlreadyBroadcast,
+    #[error(transparent)]
+    FeatureDisabled(#[from] FeatureDisabled),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Native transfer to build for a cold signer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildColdTransferRequest {
+    pub chain: String,
+    pub from_address: String,
+    pub to_address: String,
+    /// Display units of the native coin
+    pub amount: String,
+}
+
+/// Signature produced by the cold signer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColdSignatureRequest {
+    /// Base58 over the transaction's message (Solana) or 65-byte hex over
+    /// `signing_hash` (Ethereum)
+    pub signature: String,
+}
+
+/// A cold transfer with its export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColdTransactionResponse {
+    pub id: String,
+    pub chain: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: String,
+    pub fee: String,
+    /// `exported` or `broadcast`
+    pub status: String,
+    /// Base64 unsigned transaction: bincode (Solana) or RLP (Ethereum)
+    pub payload: String,
+    /// Hash the signer signs (Ethereum only)
+    pub signing_hash: Option<String>,
+    /// The payload split for QR codes, in order
+    pub qr_parts: Vec<String>,
+    pub tx_hash: Option<String>,
+    pub created_at: String,
+    pub broadcast_at: Option<String>,
+}
+
+impl From<ColdTransactionRow> for ColdTransactionResponse {
+    fn from(row: ColdTransactionRow) -> Self {
+        Self {
+            qr_parts: qr_parts(&row.id, &row.payload),
+            id: row.id,
+            chain: row.chain,
+            from_address: row.from_address,
+            to_address: row.to_address,
+            amount: row.amount,
+            fee: row.fee,
+            status: row.status,
+            payload: row.payload,
+            signing_hash: row.signing_hash,
+            tx_hash: row.tx_hash,
+            created_at: row.created_at,
+            broadcast_at: row.broadcast_at,
+        }
+    }
+}
+
+/// Build an unsigned transfer and keep it for the signature to come back
+pub async fn build_transfer(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: BuildColdTransferRequest,
+) -> Result<ColdTransactionResponse, ColdSigningServiceError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| ColdSigningServiceError::InvalidChain(request.chain.clone()))?;
+    let from = normalize_address(chain, &request.from_address)?;
+    let to = normalize_address(chain, &request.to_address)?;
+    let decimals = chain.native_decimals();
+    let amount = parse_units(&request.amount, decimals)
+        .filter(|amount| *amount > 0)
+        .ok_or_else(|| ColdSigningServiceError::InvalidAmount(request.amount.trim().to_string()))?;
+
+    let unsigned = state
+        .chain_clients()
+        .get(chain)
+        .build_unsigned_transfer(&from, &to, amount)
+        .await?;
+    let row = ColdTransactionRow::new(
+        user_id.to_string(),
+        current_tenant_id(),
+        chain.to_string(),
+        from,
+        to,
+        format_units(amount, decimals),
+        format_units(unsigned.fee, decimals),
+        unsigned.payload,
+        unsigned.signing_hash,
+    );
+    state.db.create_cold_transaction(&row).await?;
+    tracing::info!(id = %row.id, chain = %row.chain, from = %row.from_address, "Cold transfer exported");
+
+    Ok(row.into())
+}
+
+/// The user's cold transfers, newest first
+pub async fn list_transfers(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<ColdTransactionResponse>, ColdSigningServiceError> {
+    let rows = state.db.get_cold_transactions(user_id).await?;
+    Ok(rows.into_iter().map(ColdTransactionResponse::from).collect())
+}
+
+pub async fn get_transfer(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<ColdTransactionResponse, ColdSigningServiceError> {
+    Ok(owned_transfer(state, user_id, id).await?.into())
+}
+
+/// One QR part of a transfer's export, counting from 1
+pub async fn qr_part(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    part: usize,
+) -> Result<String, ColdSigningServiceError> {
+    let row = owned_transfer(state, user_id, id).await?;
+    part.checked_sub(1)
+        .and_then(|index| qr_parts(&row.id, &row.payload).into_iter().nth(index))
+        .ok_or(ColdSigningServiceError::NotFound)
+}
+
+/// Check the cold signer's signature over the exported transfer, then
+/// broadcast the signed transaction
+pub async fn submit_signature(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: ColdSignatureRequest,
+) -> Result<ColdTransactionResponse, ColdSigningServiceError> {
+    let row = owned_transfer(state, user_id, id).await?;
+    if row.status != "exported" {
+        return Err(ColdSigningServiceError::AlreadyBroadcast);
+    }
+    let chain: Chain = row
+        .chain
+        .parse()
+        .map_err(|_| ColdSigningServiceError::InvalidChain(row.chain.clone()))?;
+    feature_flag_service::check(state, FeatureFlag::Sends(chain)).await?;
+
+    let signed = match chain {
+        Chain::Solana => {
+            solana::attach_signature(&row.payload, &row.from_address, &request.signature)
+                .map_err(|e| ColdSigningServiceError::InvalidSignature(e.to_string()))?
+        }
+        Chain::Ethereum => {
+            ethereum::attach_signature(&row.payload, &row.from_address, &request.signature)
+                .map_err(|e| ColdSigningServiceError::InvalidSignature(e.to_string()))?
+        }
+    };
+    let tx_hash = state.chain_clients().get(chain).broadcast_raw(&signed).await?;
+    if !state.db.mark_cold_transaction_broadcast(&row.id, &tx_hash).await? {
+        return Err(ColdSigningServiceError::AlreadyBroadcast);
+    }
+    tracing::info!(id = %row.id, tx_hash = %tx_hash, "Cold-signed transfer broadcast");
+
+    Ok(state.db.get_cold_transaction(&row.id).await?.into())
+}
+
+async fn owned_transfer(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<ColdTransactionRow, ColdSigningServiceError> {
+    match state.db.get_cold_transaction(id).await {
+        Ok(row) if row.user_id == user_id && row.tenant_id == current_tenant_id() => Ok(row),
+        Ok(_) | Err(DatabaseError::NotFound) => Err(ColdSigningServiceError::NotFound),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `valtix-cold:<id>:<n>/<total>:<data>` for each run of the payload
+fn qr_parts(id: &str, payload: &str) -> Vec<String> {
+    let runs: Vec<&str> = payload
+        .as_bytes()
+        .chunks(QR_PART_CHARS)
+        .map(|run| std::str::from_utf8(run).expect("base64 is ASCII"))
+        .collect();
+    let total = runs.len();
+    runs.into_iter()
+        .enumerate()
+        .map(|(i, data)| format!("{}:{}:{}/{}:{}", QR_PART_PREFIX, id, i + 1, total, data))
+        .collect()
+}
+
+fn normalize_address(chain: Chain, address: &str) -> Result<String, ColdSigningServiceError> {
+    let address = address.trim();
+    match chain {
+        Chain::Solana => solana::normalize_address(address)
+            .map_err(|_| ColdSigningServiceError::InvalidAddress(address.to_string())),
+        Chain::Ethereum if ethereum::validate_address(address) => {
+            Ok(ethereum::checksum_address(address))
+        }
+        Chain::Ethereum => Err(ColdSigningServiceError::InvalidAddress(address.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_parts() {
+        let payload = "A".repeat(QR_PART_CHARS * 2 + 5);
+        let parts = qr_parts("abc", &payload);
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].starts_with("valtix-cold:abc:1/3:AAAA"));
+        assert_eq!(parts[2], "valtix-cold:abc:3/3:AAAAA");
+        let data: String = parts
+            .iter()
+            .map(|part| part.splitn(4, ':').nth(3).unwrap())
+            .collect();
+        assert_eq!(data, payload);
+    }
+}
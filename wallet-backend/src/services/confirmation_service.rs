@@ -0,0 +1,223 @@
+//! Confirmation service - settles sent transactions against their simulation
+//!
+//! Sends are recorded with the balance changes simulated before broadcast.
+//! A background tracker polls each one every `TX_RECONCILE_SECS` until it
+//! lands and reaches the chain's confirmation threshold
+//! (`SOLANA_CONFIRMATIONS` / `ETH_CONFIRMATIONS`), recording its depth along
+//! the way. It then records the final status, observed changes and fee paid
+//! (with the receipt on Ethereum), flagging the row when the changes differ
+//! from what was anticipated by more than the fee.
+//!
+//! Ethereum sends given to the private relay are invisible to public nodes
+//! until mined. While one is pending the relay's status API is checked too,
+//! and the signed transaction is broadcast publicly once the relay reports
+//! it failed or `MEV_PROTECT_TIMEOUT_BLOCKS` have passed.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::api::middleware::tenant::with_tenant;
+use crate::chains::ethereum::{private_tx_status, PrivateTxStatus};
+use crate::chains::{ChainClient, ChainClientError};
+use crate::core::Chain;
+use crate::services::tenant_service;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, TransactionRow};
+use crate::AppState;
+
+/// Sends older than this are no longer polled
+const RECONCILE_WINDOW_HOURS: i64 = 24;
+
+#[derive(Debug, Error)]
+pub enum ConfirmationError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Check every unsettled send once. Returns how many settled.
+pub async fn reconcile_pending(state: &Arc<AppState>) -> Result<usize, ConfirmationError> {
+    let since = (chrono::Utc::now() - chrono::Duration::hours(RECONCILE_WINDOW_HOURS)).to_rfc3339();
+    let pending = state.db.get_unreconciled_transactions(&since).await?;
+
+    let mut settled = 0;
+    for tx in pending {
+        let account = match state.db.get_account(&tx.account_id).await {
+            Ok(account) => account,
+            Err(DatabaseError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let wallet = state.db.get_wallet(&account.wallet_id).await?;
+
+        // Sent through the tenant's endpoints, so settled through them too
+        let tenant = match tenant_service::tenant_context(state, &wallet.tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(tenant_id = %wallet.tenant_id, error = %e, "Skipping reconciliation");
+                continue;
+            }
+        };
+        match with_tenant(tenant, reconcile(state, &account, &tx)).await {
+            Ok(true) => settled += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(tx_hash = %tx.signature, error = %e, "Reconciliation failed"),
+        }
+    }
+
+    Ok(settled)
+}
+
+/// Compare one send with what landed; `false` while it is still pending
+async fn reconcile(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    tx: &TransactionRow,
+) -> Result<bool, ConfirmationError> {
+    let chain: Chain = tx
+        .chain
+        .parse()
+        .map_err(|_| ConfirmationError::InvalidChain(tx.chain.clone()))?;
+    let Some(expected) = tx.expected_effects() else {
+        return Ok(false);
+    };
+
+    let mut addresses: Vec<String> = expected
+        .changes
+        .iter()
+        .map(|c| c.address.clone())
+        .chain(tx.from_address.clone())
+        .chain(tx.to_address.clone())
+        .collect();
+    addresses.sort();
+    addresses.dedup();
+
+    // Sends through an account's own endpoint may only be visible there
+    // until they land
+    let clients = state.account_clients(account);
+    let client = clients.get(chain);
+    let Some(confirmed) = client.transaction_effects(&tx.signature, &addresses).await? else {
+        if chain == Chain::Ethereum {
+            check_private(state, client, tx).await?;
+        }
+        return Ok(false);
+    };
+
+    let required = match chain {
+        Chain::Solana => state.config.solana_confirmations,
+        Chain::Ethereum => state.config.eth_confirmations,
+    };
+    let confirmations = confirmed.confirmations.min(i64::MAX as u64) as i64;
+    if confirmed.confirmations < required {
+        state
+            .db
+            .set_transaction_confirmations(&tx.id, confirmed.block_number, confirmations)
+            .await?;
+        return Ok(false);
+    }
+
+    let fee_payer = tx.from_address.as_deref().unwrap_or_default();
+    let mismatch = !expected.matches(&confirmed.effects, fee_payer);
+    if mismatch {
+        tracing::warn!(
+            tx_hash = %tx.signature,
+            status = %confirmed.status,
+            "Transaction effects differ from simulation"
+        );
+    }
+
+    let actual = serde_json::to_string(&confirmed.effects).unwrap_or_default();
+    state
+        .db
+        .set_transaction_effects(
+            &tx.id,
+            &confirmed.status,
+            confirmed.block_number,
+            confirmations,
+            &actual,
+            mismatch,
+            confirmed.receipt.as_ref(),
+            &confirmed.effects.fee,
+        )
+        .await?;
+    tracing::info!(
+        tx_hash = %tx.signature,
+        chain = %chain,
+        status = %confirmed.status,
+        confirmations,
+        "Transaction settled"
+    );
+    Ok(true)
+}
+
+/// Broadcast a pending private send publicly once the relay has dropped it
+/// or it has waited out `MEV_PROTECT_TIMEOUT_BLOCKS`
+async fn check_private(
+    state: &Arc<AppState>,
+    client: &dyn ChainClient,
+    tx: &TransactionRow,
+) -> Result<(), ConfirmationError> {
+    let Some(mut broadcast) = tx.broadcast_info().filter(|b| b.private) else {
+        return Ok(());
+    };
+    let Some(raw_tx) = broadcast.raw_tx.clone() else {
+        return Ok(());
+    };
+
+    // An unreachable status API only leaves the timeout to go by
+    let status = private_tx_status(&state.config.mev_protect_status_url, &tx.signature)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::debug!(
+                tx_hash = %tx.signature,
+                error = %e,
+                "Private transaction status unavailable"
+            );
+            PrivateTxStatus::Unknown
+        });
+    let height = client.block_height().await?;
+    let timed_out =
+        height >= broadcast.height.saturating_add(state.config.mev_protect_timeout_blocks);
+    let give_up = match status {
+        PrivateTxStatus::Failed => true,
+        // Mined, just not visible to our node yet
+        PrivateTxStatus::Included => false,
+        // Withdrawn by the sender through the relay, so not resent
+        PrivateTxStatus::Cancelled => false,
+        _ => timed_out,
+    };
+    if !give_up {
+        return Ok(());
+    }
+
+    client.broadcast_raw(&raw_tx).await?;
+    broadcast.private = false;
+    broadcast.height = height;
+    let broadcast = serde_json::to_string(&broadcast).unwrap_or_default();
+    state.db.set_transaction_broadcast(&tx.id, &broadcast).await?;
+    tracing::warn!(
+        tx_hash = %tx.signature,
+        status = ?status,
+        "Private transaction not included; broadcast publicly"
+    );
+    Ok(())
+}
+
+/// Run `reconcile_pending` every `TX_RECONCILE_SECS` for the life of the
+/// process
+pub fn spawn_confirmation_tracker(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.tx_reconcile_interval);
+        loop {
+            ticker.tick().await;
+            match reconcile_pending(&state).await {
+                Ok(0) => {}
+                Ok(settled) => tracing::debug!(settled, "Transactions reconciled"),
+                Err(e) => tracing::warn!(error = %e, "Transaction reconciliation failed"),
+            }
+        }
+    })
+}
@@ -0,0 +1,421 @@
+//! Contact service - address validation, send defaults and sends addressed
+//! by contact
+//!
+//! Addresses are checked against the contact's chain when written and stored
+//! in canonical form: base58 for Solana, EIP-55 checksummed hex for Ethereum.
+//! A wallet keeps one contact per address; existing duplicates, saved before
+//! validation, are reported with a suggested merge.
+//!
+//! A contact can carry the token usually sent to it and a typical amount.
+//! Sends addressed by contact always go to the stored address, on the
+//! contact's chain, with the defaults filling whatever the request leaves
+//! out.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::solana::SubmissionMode;
+use crate::chains::{ethereum, solana};
+use crate::core::Chain;
+use crate::services::transaction_service::{self, SendRequest};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{ContactResponse, ContactRow};
+use crate::AppState;
+
+#[derive(Debug, Error)]
+pub enum ContactServiceError {
+    #[error("Contact not found")]
+    NotFound,
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("{to_address} is not the contact's address ({address})")]
+    AddressMismatch { to_address: String, address: String },
+    #[error("{address} is already saved as contact '{name}' ({id}); update that contact instead")]
+    Duplicate { address: String, name: String, id: String },
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for ContactServiceError {
+    fn from(e: DatabaseError) -> Self {
+        match e {
+            DatabaseError::NotFound => ContactServiceError::NotFound,
+            _ => ContactServiceError::DatabaseError(e.to_string()),
+        }
+    }
+}
+
+/// Send to a contact; omitted fields fall back to the contact's defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactSendRequest {
+    pub from_address: String,
+    /// Checked against the contact's address when given, e.g. as read back
+    /// from the confirmation screen
+    pub to_address: Option<String>,
+    /// The contact's default amount when omitted
+    pub amount: Option<String>,
+    /// The contact's default token when omitted; an empty string sends the
+    /// native coin
+    pub token_address: Option<String>,
+    #[serde(default)]
+    pub drain_all: bool,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub references: Vec<String>,
+    #[serde(default)]
+    pub mev_protect: Option<bool>,
+    #[serde(default)]
+    pub submission_mode: Option<SubmissionMode>,
+    #[serde(default)]
+    pub bucket: Option<String>,
+}
+
+/// Contacts saved under the same address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateContacts {
+    pub chain: String,
+    /// Canonical form of the shared address
+    pub address: String,
+    /// Oldest first
+    pub contacts: Vec<ContactResponse>,
+    pub suggestion: MergeSuggestion,
+}
+
+/// How duplicates could be folded into one contact: keep the oldest, with
+/// the others' notes and defaults it lacks, and delete the rest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeSuggestion {
+    pub keep_id: String,
+    pub delete_ids: Vec<String>,
+    pub name: String,
+    pub notes: Option<String>,
+    pub default_token_address: Option<String>,
+    pub default_amount: Option<String>,
+}
+
+/// Check an address against its declared chain and return its canonical
+/// form
+pub fn canonical_address(chain: &str, address: &str) -> Result<String, ContactServiceError> {
+    let chain: Chain = chain
+        .parse()
+        .map_err(|_| ContactServiceError::InvalidRequest(format!("unsupported chain {}", chain)))?;
+    let address = address.trim();
+    let looks_like_hex = address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit());
+
+    match chain {
+        Chain::Solana => match address.parse::<solana_sdk::pubkey::Pubkey>() {
+            Ok(pubkey) => Ok(pubkey.to_string()),
+            Err(_) if looks_like_hex => Err(ContactServiceError::InvalidRequest(format!(
+                "{} is an Ethereum address, not a Solana one",
+                address
+            ))),
+            Err(_) => Err(ContactServiceError::InvalidRequest(format!(
+                "{} is not a valid Solana address",
+                address
+            ))),
+        },
+        Chain::Ethereum if looks_like_hex => {
+            if !ethereum::validate_address(address) {
+                return Err(ContactServiceError::InvalidRequest(format!(
+                    "{} fails its EIP-55 checksum",
+                    address
+                )));
+            }
+            Ok(ethereum::checksum_address(address))
+        }
+        Chain::Ethereum if solana::validate_address(address) => {
+            Err(ContactServiceError::InvalidRequest(format!(
+                "{} is a Solana address, not an Ethereum one",
+                address
+            )))
+        }
+        Chain::Ethereum => Err(ContactServiceError::InvalidRequest(format!(
+            "{} is not a valid Ethereum address",
+            address
+        ))),
+    }
+}
+
+/// Canonicalize an address for a contact in `wallet_id`, refusing one
+/// another contact there already has; `except_id` is the contact being
+/// updated
+pub async fn check_address(
+    state: &Arc<AppState>,
+    wallet_id: &str,
+    chain: &str,
+    address: &str,
+    except_id: Option<&str>,
+) -> Result<String, ContactServiceError> {
+    let address = canonical_address(chain, address)?;
+    let contacts = state.db.get_contacts(wallet_id).await?;
+    let existing = contacts.into_iter().find(|c| {
+        Some(c.id.as_str()) != except_id
+            && c.chain == chain
+            && canonical_address(&c.chain, &c.address).is_ok_and(|a| a == address)
+    });
+    match existing {
+        Some(contact) => Err(ContactServiceError::Duplicate {
+            address,
+            name: contact.name,
+            id: contact.id,
+        }),
+        None => Ok(address),
+    }
+}
+
+/// Change a contact's address on its chain. The cached identity belonged to
+/// the old address, so it is cleared.
+pub async fn set_address(
+    state: &Arc<AppState>,
+    id: &str,
+    address: &str,
+) -> Result<Option<ContactRow>, ContactServiceError> {
+    let contact = get_wallet_contact(state, id).await?;
+    let address = check_address(state, &contact.wallet_id, &contact.chain, address, Some(id)).await?;
+    if address == contact.address {
+        return Ok(None);
+    }
+    state.db.set_contact_address(id, &address).await?;
+    Ok(Some(state.db.get_contact(id).await?))
+}
+
+/// The current wallet's contacts that share an address, with a suggested
+/// merge for each group
+pub async fn find_duplicates(
+    state: &Arc<AppState>,
+) -> Result<Vec<DuplicateContacts>, ContactServiceError> {
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await?
+        .ok_or(ContactServiceError::NotFound)?;
+
+    let mut groups: BTreeMap<(String, String), Vec<ContactRow>> = BTreeMap::new();
+    for contact in state.db.get_contacts(&wallet.id).await? {
+        // Addresses saved before validation that don't parse can't match
+        if let Ok(address) = canonical_address(&contact.chain, &contact.address) {
+            groups
+                .entry((contact.chain.clone(), address))
+                .or_default()
+                .push(contact);
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .filter(|(_, contacts)| contacts.len() > 1)
+        .map(|((chain, address), mut contacts)| {
+            contacts.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+            DuplicateContacts {
+                chain,
+                address,
+                suggestion: merge_suggestion(&contacts),
+                contacts: contacts.into_iter().map(ContactResponse::from).collect(),
+            }
+        })
+        .collect())
+}
+
+/// Keep the first contact, filling what it lacks from the others
+fn merge_suggestion(contacts: &[ContactRow]) -> MergeSuggestion {
+    let keep = &contacts[0];
+    let mut notes: Vec<&str> = Vec::new();
+    for note in contacts.iter().filter_map(|c| c.notes.as_deref()) {
+        let note = note.trim();
+        if !note.is_empty() && !notes.contains(&note) {
+            notes.push(note);
+        }
+    }
+    let first = |field: fn(&ContactRow) -> &Option<String>| {
+        contacts.iter().find_map(|c| field(c).clone())
+    };
+
+    MergeSuggestion {
+        keep_id: keep.id.clone(),
+        delete_ids: contacts[1..].iter().map(|c| c.id.clone()).collect(),
+        name: keep.name.clone(),
+        notes: Some(notes.join("\n")).filter(|n| !n.is_empty()),
+        default_token_address: first(|c| &c.default_token_address),
+        default_amount: first(|c| &c.default_amount),
+    }
+}
+
+/// Check send defaults against the contact's chain; empty values are
+/// cleared
+pub fn check_defaults(
+    contact: &ContactRow,
+    token_address: Option<String>,
+    amount: Option<String>,
+) -> Result<(Option<String>, Option<String>), ContactServiceError> {
+    let token_address = token_address
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let amount = amount
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
+
+    if let Some(token) = &token_address {
+        if !valid_address(&contact.chain, token) {
+            return Err(ContactServiceError::InvalidRequest(format!(
+                "invalid token address {}",
+                token
+            )));
+        }
+    }
+    if let Some(amount) = &amount {
+        if !transaction_service::is_valid_amount_input(amount) {
+            return Err(ContactServiceError::InvalidRequest(format!(
+                "invalid amount {}",
+                amount
+            )));
+        }
+    }
+    Ok((token_address, amount))
+}
+
+/// Set a contact's send defaults; `None` leaves a default unchanged and an
+/// empty string clears it
+pub async fn set_defaults(
+    state: &Arc<AppState>,
+    id: &str,
+    token_address: Option<String>,
+    amount: Option<String>,
+) -> Result<ContactRow, ContactServiceError> {
+    let contact = get_wallet_contact(state, id).await?;
+    if token_address.is_none() && amount.is_none() {
+        return Ok(contact);
+    }
+
+    let (token_address, amount) = check_defaults(
+        &contact,
+        token_address.or_else(|| contact.default_token_address.clone()),
+        amount.or_else(|| contact.default_amount.clone()),
+    )?;
+    state
+        .db
+        .set_contact_defaults(id, token_address.as_deref(), amount.as_deref())
+        .await?;
+    Ok(state.db.get_contact(id).await?)
+}
+
+/// The send request for a send to a contact: its stored address and chain,
+/// with its defaults merged in
+pub async fn send_request(
+    state: &Arc<AppState>,
+    id: &str,
+    request: ContactSendRequest,
+) -> Result<SendRequest, ContactServiceError> {
+    let contact = get_wallet_contact(state, id).await?;
+
+    // A contact saved with a bad address must not be sent to
+    if !valid_address(&contact.chain, &contact.address) {
+        return Err(ContactServiceError::InvalidRequest(format!(
+            "the contact's address {} is not a valid {} address",
+            contact.address, contact.chain
+        )));
+    }
+    if let Some(to_address) = request.to_address.as_deref().map(str::trim) {
+        let matches = match contact.chain.parse::<Chain>() {
+            Ok(Chain::Ethereum) => to_address.eq_ignore_ascii_case(&contact.address),
+            _ => to_address == contact.address,
+        };
+        if !matches {
+            return Err(ContactServiceError::AddressMismatch {
+                to_address: to_address.to_string(),
+                address: contact.address,
+            });
+        }
+    }
+
+    let amount = match request.amount {
+        Some(amount) => amount,
+        None if request.drain_all => String::new(),
+        None => contact.default_amount.clone().ok_or_else(|| {
+            ContactServiceError::InvalidRequest(
+                "amount is required; the contact has no default amount".to_string(),
+            )
+        })?,
+    };
+    let token_address = match request.token_address {
+        Some(token) => Some(token).filter(|t| !t.trim().is_empty()),
+        None => contact.default_token_address.clone(),
+    };
+
+    Ok(SendRequest {
+        chain: contact.chain,
+        from_address: request.from_address,
+        to_address: contact.address,
+        amount,
+        token_address,
+        drain_all: request.drain_all,
+        memo: request.memo,
+        references: request.references,
+        mev_protect: request.mev_protect,
+        submission_mode: request.submission_mode,
+        bucket: request.bucket,
+    })
+}
+
+fn valid_address(chain: &str, address: &str) -> bool {
+    match chain.parse::<Chain>() {
+        Ok(Chain::Solana) => solana::validate_address(address),
+        Ok(Chain::Ethereum) => address.starts_with("0x") && ethereum::validate_address(address),
+        Err(_) => false,
+    }
+}
+
+/// A contact in the current tenant's wallet
+async fn get_wallet_contact(
+    state: &Arc<AppState>,
+    id: &str,
+) -> Result<ContactRow, ContactServiceError> {
+    let contact = state.db.get_contact(id).await?;
+    let wallet = state.db.get_wallet(&contact.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(ContactServiceError::NotFound);
+    }
+    Ok(contact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_address() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        assert_eq!(
+            canonical_address("ethereum", &checksummed.to_lowercase()).unwrap(),
+            checksummed
+        );
+        assert_eq!(canonical_address("ethereum", checksummed).unwrap(), checksummed);
+        // Mixed case must be the right checksum
+        let bad_checksum = checksummed.replace("aAe", "AAe");
+        assert!(canonical_address("ethereum", &bad_checksum)
+            .unwrap_err()
+            .to_string()
+            .contains("EIP-55"));
+
+        let solana = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+        assert_eq!(canonical_address("solana", &format!(" {} ", solana)).unwrap(), solana);
+
+        // An address of the other chain is named as such
+        assert!(canonical_address("solana", checksummed)
+            .unwrap_err()
+            .to_string()
+            .contains("Ethereum address"));
+        assert!(canonical_address("ethereum", solana)
+            .unwrap_err()
+            .to_string()
+            .contains("Solana address"));
+        assert!(canonical_address("bitcoin", solana).is_err());
+        assert!(canonical_address("ethereum", "0x1234").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! CORS origin service - origins operators allow without a restart
+//!
+//! Origins added through the admin API are stored in `cors_origins` and
+//! published to the CORS layer over a watch channel, so the instance that
+//! changed them applies the change at once. Other instances pick it up on
+//! their next reload, every `REFRESH_INTERVAL`. `CORS_ORIGIN` stays fixed
+//! at startup and can't be removed this way.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::HeaderValue;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::security::{parse_origin, CorsOrigins};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::CorsOriginRow;
+use crate::AppState;
+
+/// How often each instance reloads the stored origins
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum CorsServiceError {
+    #[error("{0}")]
+    InvalidOrigin(String),
+    #[error("Origin {0} is already allowed")]
+    AlreadyAllowed(String),
+    #[error("Origin {0} was not added through the API")]
+    NotFound(String),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Origin to allow or remove
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsOriginRequest {
+    pub origin: String,
+}
+
+/// An allowed origin and where it comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsOriginResponse {
+    pub origin: String,
+    /// `config` (`CORS_ORIGIN`, fixed until restart) or `api`
+    pub source: String,
+    /// `None` for configured origins
+    pub created_at: Option<String>,
+}
+
+/// Configured origins followed by those added through the API. In
+/// development with `CORS_ORIGIN=*` every origin is allowed regardless.
+pub async fn list_origins(
+    state: &Arc<AppState>,
+) -> Result<Vec<CorsOriginResponse>, CorsServiceError> {
+    let configured = match &state.config.security.cors_origins {
+        CorsOrigins::Mirror => Vec::new(),
+        CorsOrigins::List(origins) => origins.clone(),
+    };
+    let mut origins: Vec<CorsOriginResponse> = configured
+        .iter()
+        .map(|origin| CorsOriginResponse {
+            origin: origin.to_str().unwrap_or_default().to_string(),
+            source: "config".to_string(),
+            created_at: None,
+        })
+        .collect();
+    origins.extend(load_origins(state).await?.into_iter().map(|row| CorsOriginResponse {
+        origin: row.origin,
+        source: "api".to_string(),
+        created_at: Some(row.created_at),
+    }));
+    Ok(origins)
+}
+
+/// Allow an origin; it takes effect on this instance at once
+pub async fn add_origin(
+    state: &Arc<AppState>,
+    request: CorsOriginRequest,
+) -> Result<CorsOriginResponse, CorsServiceError> {
+    let origin = normalize(state, &request.origin)?;
+    if let CorsOrigins::List(configured) = &state.config.security.cors_origins {
+        if configured.iter().any(|o| o.as_bytes() == origin.as_bytes()) {
+            return Err(CorsServiceError::AlreadyAllowed(origin));
+        }
+    }
+
+    let row = CorsOriginRow {
+        origin: origin.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    match state.db.create_cors_origin(&row).await {
+        Ok(()) => {}
+        Err(DatabaseError::AlreadyExists) => return Err(CorsServiceError::AlreadyAllowed(origin)),
+        Err(e) => return Err(e.into()),
+    }
+    tracing::warn!(origin = %row.origin, "CORS origin added");
+    load_origins(state).await?;
+
+    Ok(CorsOriginResponse {
+        origin: row.origin,
+        source: "api".to_string(),
+        created_at: Some(row.created_at),
+    })
+}
+
+/// Stop allowing an origin added through the API
+pub async fn remove_origin(
+    state: &Arc<AppState>,
+    request: CorsOriginRequest,
+) -> Result<(), CorsServiceError> {
+    let origin = normalize(state, &request.origin)?;
+    if !state.db.delete_cors_origin(&origin).await? {
+        return Err(CorsServiceError::NotFound(origin));
+    }
+    tracing::warn!(origin = %origin, "CORS origin removed");
+    load_origins(state).await?;
+    Ok(())
+}
+
+/// Read the stored origins and publish them to the CORS layer
+pub async fn load_origins(state: &Arc<AppState>) -> Result<Vec<CorsOriginRow>, CorsServiceError> {
+    let rows = state.db.get_cors_origins().await?;
+    let origins: Vec<HeaderValue> = rows
+        .iter()
+        .filter_map(|row| HeaderValue::from_str(&row.origin).ok())
+        .collect();
+    state.cors_origins.send_if_modified(|current| {
+        let changed = *current != origins;
+        if changed {
+            *current = origins;
+        }
+        changed
+    });
+    Ok(rows)
+}
+
+/// Reload the stored origins in the background, following changes made on
+/// other instances
+pub fn spawn_origin_refresh(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match load_origins(&state).await {
+                Ok(rows) => tracing::debug!(count = rows.len(), "CORS origins loaded"),
+                Err(e) => tracing::warn!(error = %e, "Loading CORS origins failed"),
+            }
+        }
+    })
+}
+
+/// Validate under the deployment profile's rules (https only in production)
+fn normalize(state: &Arc<AppState>, origin: &str) -> Result<String, CorsServiceError> {
+    let value = parse_origin(state.config.security.profile, origin)
+        .map_err(|e| CorsServiceError::InvalidOrigin(e.to_string()))?;
+    Ok(value.to_str().unwrap_or_default().to_string())
+}
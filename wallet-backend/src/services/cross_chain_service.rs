@@ -0,0 +1,948 @@
+//! Cross-chain swap service - moving value between Solana and Ethereum
+//!
+//! Routes are quoted by an aggregator (LI.FI by default), which picks the
+//! bridge and any swaps on either side and builds the source transaction.
+//! Executing a swap quotes it again server-side, so only transactions the
+//! aggregator built for the wallet's own accounts are signed, and sends the
+//! source transaction from the source account. The cross-chain watcher then
+//! follows the route: `submitted` until the source transaction lands,
+//! `bridging` while the value is carried across, and `completed`,
+//! `refunded` or `failed` at the end. Both sides are recorded in history as
+//! `swap` entries carrying the route ID.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::api::middleware::tenant::{current_tenant_id, with_tenant};
+use crate::chains::{ChainClientError, RouteTransaction, TokenApproval};
+use crate::core::{format_units, parse_units, Chain};
+use crate::services::feature_flag_service::{self, FeatureDisabled, FeatureFlag};
+use crate::services::tenant_service;
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, CrossChainSwapRow, TransactionRow};
+use crate::AppState;
+
+/// Slippage when the request doesn't set one: 0.5%
+const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+/// Most slippage a route may be quoted with: 5%
+const MAX_SLIPPAGE_BPS: u16 = 500;
+
+/// Native coins as LI.FI names them
+const LIFI_NATIVE_ETH: &str = "0x0000000000000000000000000000000000000000";
+const LIFI_NATIVE_SOL: &str = "11111111111111111111111111111111";
+/// LI.FI's chain key for Solana
+const LIFI_SOLANA: &str = "SOL";
+
+#[derive(Debug, Error)]
+pub enum CrossChainServiceError {
+    #[error("Not found")]
+    NotFound,
+    #[error("Invalid route: {0}")]
+    InvalidRoute(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+    #[error("No route found: {0}")]
+    NoRoute(String),
+    #[error("Aggregator unavailable: {0}")]
+    Aggregator(String),
+    #[error("{0}")]
+    FeatureDisabled(#[from] FeatureDisabled),
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// What to route, as asked for by the aggregator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteRequest {
+    pub from_chain: Chain,
+    pub to_chain: Chain,
+    /// Token contract or mint; `None` for the native coin
+    pub from_token: Option<String>,
+    pub to_token: Option<String>,
+    /// Base units of the source token
+    pub from_amount: u128,
+    pub from_address: String,
+    pub to_address: String,
+    pub slippage_bps: u16,
+}
+
+/// One hop of a route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteStep {
+    /// `swap` within a chain or `cross` between chains
+    pub kind: String,
+    pub tool: String,
+}
+
+/// A route the aggregator found, with its source transaction
+#[derive(Debug, Clone)]
+pub struct RouteQuote {
+    /// Bridge carrying the value across
+    pub tool: String,
+    pub steps: Vec<RouteStep>,
+    /// Base units of the destination token
+    pub to_amount: u128,
+    pub to_amount_min: u128,
+    pub to_decimals: u8,
+    pub estimated_duration_secs: u64,
+    /// Fees and gas in USD, as estimated by the aggregator
+    pub fee_usd: Option<f64>,
+    pub transaction: RouteTransaction,
+}
+
+/// Where a route stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteState {
+    Pending,
+    Completed,
+    Refunded,
+    Failed,
+}
+
+/// Progress of a route, as reported by the aggregator
+#[derive(Debug, Clone)]
+pub struct RouteStatus {
+    pub state: RouteState,
+    pub message: Option<String>,
+    pub destination_tx_hash: Option<String>,
+    /// Base units that arrived
+    pub received_amount: Option<u128>,
+}
+
+/// Source of cross-chain routes
+#[async_trait]
+pub trait RouteAggregator: Send + Sync {
+    /// Best route for `request`, with its source transaction built
+    async fn quote(&self, request: &RouteRequest) -> Result<RouteQuote, CrossChainServiceError>;
+
+    /// Progress of a swap whose source transaction has been sent
+    async fn status(&self, swap: &CrossChainSwapRow)
+        -> Result<RouteStatus, CrossChainServiceError>;
+}
+
+/// Routes from the LI.FI API
+pub struct LiFiAggregator {
+    base_url: String,
+    eth_chain_id: u64,
+    client: reqwest::Client,
+}
+
+impl LiFiAggregator {
+    pub fn new(base_url: &str, eth_chain_id: u64) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            eth_chain_id,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn chain_key(&self, chain: Chain) -> String {
+        match chain {
+            Chain::Solana => LIFI_SOLANA.to_string(),
+            Chain::Ethereum => self.eth_chain_id.to_string(),
+        }
+    }
+
+    /// GET a JSON document; `None` on 404
+    async fn get_json(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Option<Value>, CrossChainServiceError> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| CrossChainServiceError::Aggregator(e.to_string()))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| CrossChainServiceError::Aggregator(e.to_string()))?;
+        if !status.is_success() {
+            let message = body["message"].as_str().unwrap_or("request refused").to_string();
+            return Err(if status.is_client_error() {
+                CrossChainServiceError::NoRoute(message)
+            } else {
+                CrossChainServiceError::Aggregator(message)
+            });
+        }
+        Ok(Some(body))
+    }
+}
+
+#[async_trait]
+impl RouteAggregator for LiFiAggregator {
+    async fn quote(&self, request: &RouteRequest) -> Result<RouteQuote, CrossChainServiceError> {
+        let token = |chain: Chain, token: &Option<String>| {
+            token.clone().unwrap_or_else(|| match chain {
+                Chain::Solana => LIFI_NATIVE_SOL.to_string(),
+                Chain::Ethereum => LIFI_NATIVE_ETH.to_string(),
+            })
+        };
+        let query = [
+            ("fromChain", self.chain_key(request.from_chain)),
+            ("toChain", self.chain_key(request.to_chain)),
+            ("fromToken", token(request.from_chain, &request.from_token)),
+            ("toToken", token(request.to_chain, &request.to_token)),
+            ("fromAmount", request.from_amount.to_string()),
+            ("fromAddress", request.from_address.clone()),
+            ("toAddress", request.to_address.clone()),
+            ("slippage", (request.slippage_bps as f64 / 10_000.0).to_string()),
+        ];
+        let quote = self
+            .get_json("/quote", &query)
+            .await?
+            .ok_or_else(|| CrossChainServiceError::NoRoute("no route for these tokens".to_string()))?;
+        parse_lifi_quote(&quote, request)
+    }
+
+    async fn status(
+        &self,
+        swap: &CrossChainSwapRow,
+    ) -> Result<RouteStatus, CrossChainServiceError> {
+        let chain = |name: &str| name.parse::<Chain>().map(|chain| self.chain_key(chain));
+        let (Ok(from_chain), Ok(to_chain)) = (chain(&swap.from_chain), chain(&swap.to_chain)) else {
+            return Err(CrossChainServiceError::InvalidRoute(swap.id.clone()));
+        };
+        let query = [
+            ("txHash", swap.source_tx_hash.clone()),
+            ("bridge", swap.tool.clone()),
+            ("fromChain", from_chain),
+            ("toChain", to_chain),
+        ];
+        let Some(status) = self.get_json("/status", &query).await? else {
+            return Ok(RouteStatus {
+                state: RouteState::Pending,
+                message: None,
+                destination_tx_hash: None,
+                received_amount: None,
+            });
+        };
+        Ok(parse_lifi_status(&status))
+    }
+}
+
+/// Read a LI.FI quote: a step whose estimate and transaction request
+/// describe the whole route
+fn parse_lifi_quote(
+    quote: &Value,
+    request: &RouteRequest,
+) -> Result<RouteQuote, CrossChainServiceError> {
+    let invalid = |field: &str| CrossChainServiceError::Aggregator(format!("quote without {}", field));
+    let estimate = &quote["estimate"];
+    let to_amount = quantity(&estimate["toAmount"]).ok_or_else(|| invalid("toAmount"))?;
+    let to_amount_min = quantity(&estimate["toAmountMin"]).unwrap_or(to_amount);
+    let to_decimals = quote["action"]["toToken"]["decimals"]
+        .as_u64()
+        .ok_or_else(|| invalid("toToken decimals"))? as u8;
+    let fee_usd = ["feeCosts", "gasCosts"]
+        .iter()
+        .flat_map(|costs| estimate[*costs].as_array().cloned().unwrap_or_default())
+        .filter_map(|cost| cost["amountUSD"].as_str().and_then(|usd| usd.parse::<f64>().ok()))
+        .reduce(|a, b| a + b);
+
+    let tx = &quote["transactionRequest"];
+    let data = tx["data"].as_str().ok_or_else(|| invalid("transaction"))?;
+    let transaction = match request.from_chain {
+        Chain::Solana => RouteTransaction::Solana {
+            transaction: data.to_string(),
+        },
+        Chain::Ethereum => RouteTransaction::Ethereum {
+            to: tx["to"].as_str().ok_or_else(|| invalid("transaction target"))?.to_string(),
+            data: data.to_string(),
+            value: quantity(&tx["value"]).unwrap_or(0),
+            gas_limit: quantity(&tx["gasLimit"]).map(|gas| gas as u64),
+            // Tokens are pulled by the aggregator's contract, which needs
+            // an allowance first
+            approval: match (&request.from_token, estimate["approvalAddress"].as_str()) {
+                (Some(token), Some(spender)) => Some(TokenApproval {
+                    token: token.clone(),
+                    spender: spender.to_string(),
+                    amount: request.from_amount,
+                }),
+                _ => None,
+            },
+        },
+    };
+
+    let steps = quote["includedSteps"]
+        .as_array()
+        .map(|steps| {
+            steps
+                .iter()
+                .map(|step| RouteStep {
+                    kind: step["type"].as_str().unwrap_or("unknown").to_string(),
+                    tool: step["tool"].as_str().unwrap_or_default().to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(RouteQuote {
+        tool: quote["tool"].as_str().ok_or_else(|| invalid("tool"))?.to_string(),
+        steps,
+        to_amount,
+        to_amount_min,
+        to_decimals,
+        estimated_duration_secs: estimate["executionDuration"].as_f64().unwrap_or(0.0) as u64,
+        fee_usd,
+        transaction,
+    })
+}
+
+/// Read a LI.FI transfer status
+fn parse_lifi_status(status: &Value) -> RouteStatus {
+    let state = match (status["status"].as_str(), status["substatus"].as_str()) {
+        (Some("DONE"), Some("REFUNDED")) => RouteState::Refunded,
+        (Some("DONE"), _) => RouteState::Completed,
+        (Some("FAILED" | "INVALID"), _) => RouteState::Failed,
+        _ => RouteState::Pending,
+    };
+    RouteStatus {
+        state,
+        message: status["substatusMessage"].as_str().map(str::to_string),
+        destination_tx_hash: status["receiving"]["txHash"].as_str().map(str::to_string),
+        received_amount: quantity(&status["receiving"]["amount"]),
+    }
+}
+
+/// A decimal or `0x` hex quantity, as a string or number
+fn quantity(value: &Value) -> Option<u128> {
+    match value {
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        Value::Number(n) => n.as_u64().map(u128::from),
+        _ => None,
+    }
+}
+
+/// Route between two of the wallet's accounts on different chains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossChainSwapRequest {
+    pub from_account_id: String,
+    pub to_account_id: String,
+    /// Token contract or mint on the source chain; the native coin if unset
+    pub from_token: Option<String>,
+    /// Token contract or mint on the destination chain; the native coin if
+    /// unset
+    pub to_token: Option<String>,
+    /// Display units of the source token
+    pub amount: String,
+    pub slippage_bps: Option<u16>,
+}
+
+/// A quoted route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossChainQuoteResponse {
+    pub from_chain: String,
+    pub to_chain: String,
+    pub from_token: Option<String>,
+    pub to_token: Option<String>,
+    pub from_amount: String,
+    /// Display units of the destination token
+    pub to_amount: String,
+    /// Least the route delivers within its slippage
+    pub min_amount: String,
+    pub tool: String,
+    pub steps: Vec<RouteStep>,
+    pub estimated_duration_secs: u64,
+    pub fee_usd: Option<String>,
+}
+
+/// Filter for listing swaps
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CrossChainSwapsQuery {
+    /// Swaps leaving or reaching this account
+    pub account_id: Option<String>,
+}
+
+/// A swap and where it stands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossChainSwapResponse {
+    /// Route ID; both history entries of the swap carry it
+    pub route_id: String,
+    pub from_account_id: String,
+    pub to_account_id: String,
+    pub from_chain: String,
+    pub to_chain: String,
+    pub from_token: Option<String>,
+    pub to_token: Option<String>,
+    pub from_amount: String,
+    pub quoted_amount: String,
+    pub min_amount: String,
+    pub received_amount: Option<String>,
+    pub tool: String,
+    pub estimated_duration_secs: i64,
+    /// `submitted`, `bridging`, `completed`, `refunded` or `failed`
+    pub status: String,
+    pub status_message: Option<String>,
+    pub approval_tx_hash: Option<String>,
+    pub source_tx_hash: String,
+    pub destination_tx_hash: Option<String>,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<CrossChainSwapRow> for CrossChainSwapResponse {
+    fn from(row: CrossChainSwapRow) -> Self {
+        Self {
+            route_id: row.id,
+            from_account_id: row.from_account_id,
+            to_account_id: row.to_account_id,
+            from_chain: row.from_chain,
+            to_chain: row.to_chain,
+            from_token: row.from_token,
+            to_token: row.to_token,
+            from_amount: row.from_amount,
+            quoted_amount: row.quoted_amount,
+            min_amount: row.min_amount,
+            received_amount: row.received_amount,
+            tool: row.tool,
+            estimated_duration_secs: row.estimated_duration_secs,
+            status: row.status,
+            status_message: row.status_message,
+            approval_tx_hash: row.approval_tx_hash,
+            source_tx_hash: row.source_tx_hash,
+            destination_tx_hash: row.destination_tx_hash,
+            completed_at: row.completed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// A request checked against the wallet's accounts and ready to route
+struct ResolvedSwap {
+    from: AccountRow,
+    to: AccountRow,
+    from_chain: Chain,
+    from_decimals: u32,
+    route: RouteRequest,
+}
+
+/// Quote the best route for a swap without sending anything
+pub async fn quote(
+    state: &Arc<AppState>,
+    request: CrossChainSwapRequest,
+) -> Result<CrossChainQuoteResponse, CrossChainServiceError> {
+    let resolved = resolve(state, &request).await?;
+    let quote = state.routes.quote(&resolved.route).await?;
+    let to_decimals = quote.to_decimals as u32;
+
+    Ok(CrossChainQuoteResponse {
+        from_chain: resolved.route.from_chain.to_string(),
+        to_chain: resolved.route.to_chain.to_string(),
+        from_amount: format_units(resolved.route.from_amount, resolved.from_decimals),
+        from_token: resolved.route.from_token,
+        to_token: resolved.route.to_token,
+        to_amount: format_units(quote.to_amount, to_decimals),
+        min_amount: format_units(quote.to_amount_min, to_decimals),
+        tool: quote.tool,
+        steps: quote.steps,
+        estimated_duration_secs: quote.estimated_duration_secs,
+        fee_usd: quote.fee_usd.map(|usd| format!("{:.2}", usd)),
+    })
+}
+
+/// Quote a swap afresh and send its source transaction
+pub async fn execute(
+    state: &Arc<AppState>,
+    request: CrossChainSwapRequest,
+) -> Result<CrossChainSwapResponse, CrossChainServiceError> {
+    let resolved = resolve(state, &request).await?;
+    feature_flag_service::check(state, FeatureFlag::Swaps).await?;
+    feature_flag_service::check(state, FeatureFlag::Sends(resolved.from_chain)).await?;
+
+    let quote = state.routes.quote(&resolved.route).await?;
+    let seed = get_seed(state).await?;
+    let sent = state
+        .account_clients(&resolved.from)
+        .get(resolved.from_chain)
+        .send_route_transaction(&seed, &resolved.from.derivation_path, &quote.transaction)
+        .await?;
+
+    let to_decimals = quote.to_decimals as u32;
+    let now = chrono::Utc::now().to_rfc3339();
+    let swap = CrossChainSwapRow {
+        id: uuid::Uuid::new_v4().to_string(),
+        from_account_id: resolved.from.id.clone(),
+        to_account_id: resolved.to.id.clone(),
+        from_chain: resolved.from.chain.clone(),
+        to_chain: resolved.to.chain.clone(),
+        from_token: resolved.route.from_token.clone(),
+        to_token: resolved.route.to_token.clone(),
+        from_amount: format_units(resolved.route.from_amount, resolved.from_decimals),
+        quoted_amount: format_units(quote.to_amount, to_decimals),
+        min_amount: format_units(quote.to_amount_min, to_decimals),
+        received_amount: None,
+        tool: quote.tool,
+        estimated_duration_secs: quote.estimated_duration_secs as i64,
+        status: "submitted".to_string(),
+        status_message: None,
+        approval_tx_hash: sent.approval_tx_hash,
+        source_tx_hash: sent.tx_hash,
+        destination_tx_hash: None,
+        completed_at: None,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    state.db.create_cross_chain_swap(&swap).await?;
+    state
+        .db
+        .upsert_transaction(&source_row(
+            &swap,
+            &resolved.from,
+            &resolved.to,
+            "pending",
+            None,
+            Some(resolved.from_decimals),
+        ))
+        .await?;
+    tracing::info!(
+        route_id = %swap.id,
+        tool = %swap.tool,
+        tx_hash = %swap.source_tx_hash,
+        "Cross-chain swap sent"
+    );
+
+    Ok(swap.into())
+}
+
+/// The current tenant's swaps, newest first
+pub async fn list_swaps(
+    state: &Arc<AppState>,
+    query: CrossChainSwapsQuery,
+) -> Result<Vec<CrossChainSwapResponse>, CrossChainServiceError> {
+    let rows = state
+        .db
+        .get_cross_chain_swaps(&current_tenant_id(), query.account_id.as_deref())
+        .await?;
+    Ok(rows.into_iter().map(CrossChainSwapResponse::from).collect())
+}
+
+pub async fn get_swap(
+    state: &Arc<AppState>,
+    route_id: &str,
+) -> Result<CrossChainSwapResponse, CrossChainServiceError> {
+    let swap = match state.db.get_cross_chain_swap(route_id).await {
+        Ok(swap) => swap,
+        Err(DatabaseError::NotFound) => return Err(CrossChainServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    tenant_account(state, &swap.from_account_id).await?;
+    Ok(swap.into())
+}
+
+/// Check the accounts, tokens and amount of a request
+async fn resolve(
+    state: &Arc<AppState>,
+    request: &CrossChainSwapRequest,
+) -> Result<ResolvedSwap, CrossChainServiceError> {
+    let from = tenant_account(state, &request.from_account_id).await?;
+    let to = tenant_account(state, &request.to_account_id).await?;
+    let (Ok(from_chain), Ok(to_chain)) = (from.chain.parse::<Chain>(), to.chain.parse::<Chain>())
+    else {
+        return Err(CrossChainServiceError::InvalidRoute(format!(
+            "{} to {}",
+            from.chain, to.chain
+        )));
+    };
+    if from_chain == to_chain {
+        return Err(CrossChainServiceError::InvalidRoute(format!(
+            "both accounts are on {}; use a swap within the chain",
+            from_chain
+        )));
+    }
+    let slippage_bps = request.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS);
+    if slippage_bps == 0 || slippage_bps > MAX_SLIPPAGE_BPS {
+        return Err(CrossChainServiceError::InvalidRoute(format!(
+            "slippage must be 1 to {} bps",
+            MAX_SLIPPAGE_BPS
+        )));
+    }
+
+    let from_token = token(&request.from_token);
+    let to_token = token(&request.to_token);
+    let from_clients = state.account_clients(&from);
+    let from_decimals = match &from_token {
+        Some(token) => decimals(from_clients.get(from_chain), token).await?,
+        None => from_chain.native_decimals(),
+    };
+    if let Some(token) = &to_token {
+        decimals(state.account_clients(&to).get(to_chain), token).await?;
+    }
+    let from_amount = parse_units(&request.amount, from_decimals)
+        .filter(|amount| *amount > 0)
+        .ok_or_else(|| CrossChainServiceError::InvalidAmount(request.amount.trim().to_string()))?;
+
+    Ok(ResolvedSwap {
+        route: RouteRequest {
+            from_chain,
+            to_chain,
+            from_token,
+            to_token,
+            from_amount,
+            from_address: from.address.clone(),
+            to_address: to.address.clone(),
+            slippage_bps,
+        },
+        from_chain,
+        from_decimals,
+        from,
+        to,
+    })
+}
+
+fn token(token: &Option<String>) -> Option<String> {
+    token
+        .as_deref()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+}
+
+/// Decimals of a token, which must exist on its chain
+async fn decimals(
+    client: &dyn crate::chains::ChainClient,
+    token: &str,
+) -> Result<u32, CrossChainServiceError> {
+    match client.token_metadata(token).await {
+        Ok(metadata) => Ok(metadata.decimals as u32),
+        Err(ChainClientError::InvalidAddress(_)) => {
+            Err(CrossChainServiceError::InvalidToken(token.to_string()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Advance every open swap once. Returns how many reached an outcome.
+pub async fn poll_cross_chain_swaps(
+    state: &Arc<AppState>,
+) -> Result<usize, CrossChainServiceError> {
+    let open = state.db.get_open_cross_chain_swaps().await?;
+    let mut finished = 0;
+    for swap in open {
+        let (from, to) = match (
+            state.db.get_account(&swap.from_account_id).await,
+            state.db.get_account(&swap.to_account_id).await,
+        ) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(DatabaseError::NotFound), _) | (_, Err(DatabaseError::NotFound)) => continue,
+            (Err(e), _) | (_, Err(e)) => return Err(e.into()),
+        };
+        let wallet = state.db.get_wallet(&from.wallet_id).await?;
+
+        // Sent through the tenant's endpoints, so followed through them too
+        let tenant = match tenant_service::tenant_context(state, &wallet.tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(tenant_id = %wallet.tenant_id, error = %e, "Skipping cross-chain check");
+                continue;
+            }
+        };
+        match with_tenant(tenant, advance(state, &from, &to, &swap)).await {
+            Ok(true) => finished += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(route_id = %swap.id, error = %e, "Cross-chain check failed"),
+        }
+    }
+
+    Ok(finished)
+}
+
+/// Move one swap along; `true` once it has an outcome
+async fn advance(
+    state: &Arc<AppState>,
+    from: &AccountRow,
+    to: &AccountRow,
+    swap: &CrossChainSwapRow,
+) -> Result<bool, CrossChainServiceError> {
+    let (Ok(from_chain), Ok(to_chain)) = (from.chain.parse::<Chain>(), to.chain.parse::<Chain>())
+    else {
+        return Err(CrossChainServiceError::InvalidRoute(swap.id.clone()));
+    };
+
+    if swap.status == "submitted" {
+        let landed = state
+            .account_clients(from)
+            .get(from_chain)
+            .transaction_effects(&swap.source_tx_hash, std::slice::from_ref(&from.address))
+            .await?;
+        let Some(landed) = landed else {
+            return Ok(false);
+        };
+        let succeeded = landed.status != "failed"
+            && landed.receipt.as_ref().is_none_or(|receipt| receipt.succeeded);
+        let status = if succeeded { "confirmed" } else { "failed" };
+        state
+            .db
+            .upsert_transaction(&source_row(swap, from, to, status, landed.block_number, None))
+            .await?;
+        if !succeeded {
+            let message = "Source transaction failed";
+            state
+                .db
+                .finish_cross_chain_swap(&swap.id, "failed", Some(message), None, None)
+                .await?;
+            tracing::info!(route_id = %swap.id, "{}", message);
+            return Ok(true);
+        }
+        state
+            .db
+            .update_cross_chain_swap(&swap.id, "bridging", None)
+            .await?;
+        tracing::info!(route_id = %swap.id, "Cross-chain swap left the source chain");
+        return Ok(false);
+    }
+
+    let progress = state.routes.status(swap).await?;
+    let message = progress.message.as_deref();
+    match progress.state {
+        RouteState::Pending => {
+            if message.is_some() && message != swap.status_message.as_deref() {
+                state
+                    .db
+                    .update_cross_chain_swap(&swap.id, "bridging", message)
+                    .await?;
+            }
+            return Ok(false);
+        }
+        RouteState::Completed => {
+            let to_decimals = match &swap.to_token {
+                Some(token) => decimals(state.account_clients(to).get(to_chain), token).await?,
+                None => to_chain.native_decimals(),
+            };
+            let received = progress
+                .received_amount
+                .map(|amount| format_units(amount, to_decimals))
+                .unwrap_or_else(|| swap.quoted_amount.clone());
+            // Without a destination hash the route ID stands in for it
+            let destination = progress
+                .destination_tx_hash
+                .clone()
+                .unwrap_or_else(|| swap.id.clone());
+            state
+                .db
+                .upsert_transaction(&destination_row(
+                    swap,
+                    from,
+                    to,
+                    &destination,
+                    &received,
+                    to_decimals,
+                ))
+                .await?;
+            state
+                .db
+                .finish_cross_chain_swap(
+                    &swap.id,
+                    "completed",
+                    message,
+                    Some(&destination),
+                    Some(&received),
+                )
+                .await?;
+            tracing::info!(route_id = %swap.id, received = %received, "Cross-chain swap completed");
+        }
+        RouteState::Refunded | RouteState::Failed => {
+            let status = if progress.state == RouteState::Refunded { "refunded" } else { "failed" };
+            state
+                .db
+                .finish_cross_chain_swap(
+                    &swap.id,
+                    status,
+                    message,
+                    progress.destination_tx_hash.as_deref(),
+                    None,
+                )
+                .await?;
+            tracing::info!(route_id = %swap.id, status, "Cross-chain swap did not complete");
+        }
+    }
+    Ok(true)
+}
+
+/// The source side of a swap in the source account's history. Its decimals
+/// are only needed when it's first recorded; later upserts keep them.
+fn source_row(
+    swap: &CrossChainSwapRow,
+    from: &AccountRow,
+    to: &AccountRow,
+    status: &str,
+    block_number: Option<i64>,
+    from_decimals: Option<u32>,
+) -> TransactionRow {
+    let mut row = TransactionRow::new(
+        from.id.clone(),
+        swap.from_chain.clone(),
+        swap.source_tx_hash.clone(),
+        "swap".to_string(),
+        Some(from.address.clone()),
+        Some(to.address.clone()),
+        Some(swap.from_amount.clone()),
+        swap.from_token.clone(),
+        status.to_string(),
+        block_number,
+        Some(swap.created_at.clone()),
+    );
+    row.route_id = Some(swap.id.clone());
+    row.token_decimals = from_decimals.filter(|_| swap.from_token.is_some()).map(i64::from);
+    row
+}
+
+/// The destination side of a swap in the destination account's history
+fn destination_row(
+    swap: &CrossChainSwapRow,
+    from: &AccountRow,
+    to: &AccountRow,
+    tx_hash: &str,
+    received: &str,
+    to_decimals: u32,
+) -> TransactionRow {
+    let mut row = TransactionRow::new(
+        to.id.clone(),
+        swap.to_chain.clone(),
+        tx_hash.to_string(),
+        "swap".to_string(),
+        Some(from.address.clone()),
+        Some(to.address.clone()),
+        Some(received.to_string()),
+        swap.to_token.clone(),
+        "confirmed".to_string(),
+        None,
+        Some(chrono::Utc::now().to_rfc3339()),
+    );
+    row.route_id = Some(swap.id.clone());
+    row.token_decimals = swap.to_token.as_ref().map(|_| i64::from(to_decimals));
+    row
+}
+
+/// An account of the current tenant; others are reported as not found
+async fn tenant_account(
+    state: &Arc<AppState>,
+    account_id: &str,
+) -> Result<AccountRow, CrossChainServiceError> {
+    let account = match state.db.get_account(account_id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(CrossChainServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(CrossChainServiceError::NotFound);
+    }
+    Ok(account)
+}
+
+/// Run `poll_cross_chain_swaps` every `CROSS_CHAIN_CHECK_SECS` for the life
+/// of the process
+pub fn spawn_cross_chain_watcher(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.cross_chain_check_interval);
+        loop {
+            ticker.tick().await;
+            match poll_cross_chain_swaps(&state).await {
+                Ok(0) => {}
+                Ok(finished) => tracing::debug!(finished, "Cross-chain swaps finished"),
+                Err(e) => tracing::warn!(error = %e, "Cross-chain check failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(from_chain: Chain, from_token: Option<&str>) -> RouteRequest {
+        RouteRequest {
+            from_chain,
+            to_chain: Chain::Solana,
+            from_token: from_token.map(str::to_string),
+            to_token: None,
+            from_amount: 5_000_000,
+            from_address: "0xfrom".to_string(),
+            to_address: "to".to_string(),
+            slippage_bps: 50,
+        }
+    }
+
+    #[test]
+    fn test_parse_lifi_quote_and_status() {
+        let quote = json!({
+            "tool": "mayan",
+            "action": { "toToken": { "decimals": 9 } },
+            "estimate": {
+                "toAmount": "24000000",
+                "toAmountMin": "23880000",
+                "approvalAddress": "0x1231DEB6f5749EF6cE6943a275A1D3E7486F4EaE",
+                "executionDuration": 368.5,
+                "feeCosts": [{ "amountUSD": "0.75" }],
+                "gasCosts": [{ "amountUSD": "1.50" }]
+            },
+            "includedSteps": [
+                { "type": "swap", "tool": "uniswap" },
+                { "type": "cross", "tool": "mayan" }
+            ],
+            "transactionRequest": {
+                "to": "0x1231DEB6f5749EF6cE6943a275A1D3E7486F4EaE",
+                "data": "0xabcdef",
+                "value": "0x0",
+                "gasLimit": "0x7a120"
+            }
+        });
+        let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let parsed = parse_lifi_quote(&quote, &request(Chain::Ethereum, Some(usdc))).unwrap();
+        assert_eq!((parsed.to_amount, parsed.to_amount_min, parsed.to_decimals), (24_000_000, 23_880_000, 9));
+        assert_eq!(parsed.estimated_duration_secs, 368);
+        assert_eq!(parsed.fee_usd, Some(2.25));
+        assert_eq!(parsed.steps.len(), 2);
+        let RouteTransaction::Ethereum { value, gas_limit, approval, .. } = parsed.transaction else {
+            panic!("expected an ethereum transaction");
+        };
+        assert_eq!((value, gas_limit), (0, Some(500_000)));
+        // An ERC-20 source needs an allowance for the aggregator's contract
+        assert_eq!(approval.unwrap().amount, 5_000_000);
+
+        // ETH is sent along, so there is nothing to approve
+        let parsed = parse_lifi_quote(&quote, &request(Chain::Ethereum, None)).unwrap();
+        assert!(matches!(parsed.transaction, RouteTransaction::Ethereum { approval: None, .. }));
+
+        let done = parse_lifi_status(&json!({
+            "status": "DONE",
+            "substatus": "COMPLETED",
+            "receiving": { "txHash": "dest", "amount": "23950000" }
+        }));
+        assert_eq!(done.state, RouteState::Completed);
+        assert_eq!(done.received_amount, Some(23_950_000));
+        let refunded = parse_lifi_status(&json!({ "status": "DONE", "substatus": "REFUNDED" }));
+        assert_eq!(refunded.state, RouteState::Refunded);
+        let pending = parse_lifi_status(&json!({
+            "status": "PENDING",
+            "substatusMessage": "Waiting for the bridge"
+        }));
+        assert_eq!(pending.state, RouteState::Pending);
+        assert_eq!(pending.message.as_deref(), Some("Waiting for the bridge"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Dev service - a seeded demo environment for frontend development
+//!
+//! With `DEV_MODE` set, startup registers a demo user, imports the demo
+//! wallet from a fixed mnemonic, airdrops devnet SOL to its Solana account
+//! and adds a few contacts and history entries, all with the same addresses
+//! and hashes on every run. Once the demo user exists, startup only unlocks
+//! the wallet again.
+//!
+//! The module is compiled into debug builds only, and the configuration
+//! refuses `DEV_MODE` in release builds and under the production profile.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::core::Chain;
+use crate::services::user_service::UserServiceError;
+use crate::services::wallet_service::{self, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{ContactRow, CreateUserRequest, TransactionRow};
+use crate::AppState;
+
+/// Demo login
+pub const DEMO_EMAIL: &str = "demo@valtix.dev";
+pub const DEMO_PASSWORD: &str = "valtix-demo-password";
+/// Demo wallet; its password is `DEMO_PASSWORD`
+pub const DEMO_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon \
+                                 abandon abandon abandon abandon about";
+
+#[derive(Debug, Error)]
+pub enum DevSeedError {
+    #[error("User error: {0}")]
+    User(#[from] UserServiceError),
+    #[error("Wallet error: {0}")]
+    Wallet(#[from] WalletServiceError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// What startup did with the demo environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevSeed {
+    /// Created from scratch
+    Seeded,
+    /// Already there; the wallet was unlocked
+    Unlocked,
+}
+
+/// A demo contact: a name and the byte its address is made of
+struct DemoContact {
+    name: &'static str,
+    chain: Chain,
+    fill: u8,
+}
+
+const DEMO_CONTACTS: &[DemoContact] = &[
+    DemoContact { name: "Alice (demo)", chain: Chain::Solana, fill: 0x11 },
+    DemoContact { name: "Bob (demo)", chain: Chain::Ethereum, fill: 0x22 },
+    DemoContact { name: "Carol (demo)", chain: Chain::Solana, fill: 0x33 },
+];
+
+/// A demo history entry with the contact on the other side
+struct DemoTransaction {
+    chain: Chain,
+    tx_type: &'static str,
+    counterparty: u8,
+    amount: &'static str,
+    status: &'static str,
+    timestamp: &'static str,
+}
+
+const DEMO_HISTORY: &[DemoTransaction] = &[
+    DemoTransaction {
+        chain: Chain::Solana,
+        tx_type: "receive",
+        counterparty: 0x11,
+        amount: "1.5",
+        status: "confirmed",
+        timestamp: "2024-01-02T09:30:00+00:00",
+    },
+    DemoTransaction {
+        chain: Chain::Solana,
+        tx_type: "send",
+        counterparty: 0x33,
+        amount: "0.25",
+        status: "confirmed",
+        timestamp: "2024-01-03T14:05:00+00:00",
+    },
+    DemoTransaction {
+        chain: Chain::Solana,
+        tx_type: "send",
+        counterparty: 0x11,
+        amount: "0.1",
+        status: "failed",
+        timestamp: "2024-01-04T18:45:00+00:00",
+    },
+    DemoTransaction {
+        chain: Chain::Ethereum,
+        tx_type: "receive",
+        counterparty: 0x22,
+        amount: "0.5",
+        status: "confirmed",
+        timestamp: "2024-01-02T11:00:00+00:00",
+    },
+    DemoTransaction {
+        chain: Chain::Ethereum,
+        tx_type: "send",
+        counterparty: 0x22,
+        amount: "0.05",
+        status: "confirmed",
+        timestamp: "2024-01-05T16:20:00+00:00",
+    },
+];
+
+/// Address made of one repeated byte
+fn demo_address(chain: Chain, fill: u8) -> String {
+    match chain {
+        Chain::Solana => bs58::encode([fill; 32]).into_string(),
+        Chain::Ethereum => format!("0x{}", hex::encode([fill; 20])),
+    }
+}
+
+/// Transaction hash of the `n`th demo entry
+fn demo_hash(chain: Chain, n: usize) -> String {
+    let fill = 0xd0 + n as u8;
+    match chain {
+        Chain::Solana => bs58::encode([fill; 64]).into_string(),
+        Chain::Ethereum => format!("0x{}", hex::encode([fill; 32])),
+    }
+}
+
+/// Seed the demo environment, or unlock it when an earlier run seeded it
+pub async fn seed(state: &Arc<AppState>) -> Result<DevSeed, DevSeedError> {
+    let request = CreateUserRequest {
+        email: DEMO_EMAIL.to_string(),
+        password: DEMO_PASSWORD.to_string(),
+    };
+    match state.user_service.register(request).await {
+        Ok(_) => {}
+        Err(UserServiceError::UserAlreadyExists) => {
+            if !wallet_service::is_unlocked(state).await {
+                wallet_service::unlock_wallet(state, DEMO_PASSWORD, None).await?;
+            }
+            return Ok(DevSeed::Unlocked);
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let wallet_id = wallet_service::import_wallet(state, DEMO_MNEMONIC, DEMO_PASSWORD, None).await?;
+
+    let mut accounts = Vec::new();
+    for chain in [Chain::Solana, Chain::Ethereum] {
+        if !state.config.chain_enabled(chain) {
+            continue;
+        }
+        let name = format!("Demo {}", chain);
+        let account = wallet_service::derive_new_account(state, chain, Some(name)).await?;
+        accounts.push((chain, account));
+    }
+
+    // A devnet faucet that is rate limiting or down leaves the account
+    // unfunded, not the environment unusable
+    if let Some((_, account)) = accounts.iter().find(|(chain, _)| *chain == Chain::Solana) {
+        match state
+            .chain_clients()
+            .get(Chain::Solana)
+            .request_airdrop(&account.address, state.config.faucet.sol_lamports)
+            .await
+        {
+            Ok(signature) => tracing::info!(%signature, "Demo Solana account funded"),
+            Err(e) => tracing::warn!("Demo Solana airdrop failed: {}", e),
+        }
+    }
+
+    for contact in DEMO_CONTACTS {
+        let row = ContactRow::new(
+            wallet_id.clone(),
+            contact.name.to_string(),
+            contact.chain.to_string(),
+            demo_address(contact.chain, contact.fill),
+            Some("Demo contact".to_string()),
+        );
+        state.db.create_contact(&row).await?;
+    }
+
+    for (n, entry) in DEMO_HISTORY.iter().enumerate() {
+        let Some((_, account)) = accounts.iter().find(|(chain, _)| *chain == entry.chain) else {
+            continue;
+        };
+        let counterparty = demo_address(entry.chain, entry.counterparty);
+        let (from, to) = match entry.tx_type {
+            "receive" => (counterparty, account.address.clone()),
+            _ => (account.address.clone(), counterparty),
+        };
+        let row = TransactionRow::new(
+            account.id.clone(),
+            entry.chain.to_string(),
+            demo_hash(entry.chain, n),
+            entry.tx_type.to_string(),
+            Some(from),
+            Some(to),
+            Some(entry.amount.to_string()),
+            None,
+            entry.status.to_string(),
+            None,
+            Some(entry.timestamp.to_string()),
+        );
+        state.db.upsert_transaction(&row).await?;
+    }
+
+    tracing::info!(
+        tenant = %current_tenant_id(),
+        email = DEMO_EMAIL,
+        "Demo environment seeded"
+    );
+    Ok(DevSeed::Seeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_data_is_valid() {
+        for contact in DEMO_CONTACTS {
+            let address = demo_address(contact.chain, contact.fill);
+            match contact.chain {
+                Chain::Solana => assert_eq!(bs58::decode(&address).into_vec().unwrap().len(), 32),
+                Chain::Ethereum => assert_eq!(address.len(), 42),
+            }
+        }
+        for entry in DEMO_HISTORY {
+            assert!(DEMO_CONTACTS
+                .iter()
+                .any(|c| c.fill == entry.counterparty && c.chain == entry.chain));
+        }
+        assert_ne!(demo_hash(Chain::Solana, 0), demo_hash(Chain::Solana, 1));
+    }
+}
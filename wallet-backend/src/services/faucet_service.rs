@@ -0,0 +1,189 @@
+//! Faucet service - test-network funding for developer accounts
+//!
+//! Solana accounts are funded with an RPC airdrop, which devnet and testnet
+//! serve; Ethereum accounts through the Sepolia faucet API at
+//! `FAUCET_SEPOLIA_URL`. Each address may be funded `FAUCET_DAILY_LIMIT`
+//! times per chain over a rolling day.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::chains::{ethereum, solana};
+use crate::config::app::SEPOLIA_CHAIN_ID;
+use crate::core::Chain;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::FaucetRequestRow;
+use crate::AppState;
+
+/// Window the daily limit is counted over
+const WINDOW: chrono::Duration = chrono::Duration::hours(24);
+const FAUCET_API_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum FaucetError {
+    #[error("The faucet is disabled")]
+    Disabled,
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Faucet unavailable: {0}")]
+    Unavailable(String),
+    #[error("Daily faucet limit of {limit} reached for this address; try again after {retry_at}")]
+    LimitReached { limit: u32, retry_at: String },
+    #[error("Faucet request failed: {0}")]
+    Faucet(String),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Funding sent to an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetFunding {
+    pub chain: String,
+    pub address: String,
+    /// Base units (lamports / wei)
+    pub amount: String,
+    pub tx_hash: String,
+    /// Requests the address has left in the current window
+    pub remaining: u32,
+}
+
+#[derive(Serialize)]
+struct SepoliaRequest<'a> {
+    address: &'a str,
+    amount: String,
+}
+
+#[derive(Deserialize)]
+struct SepoliaResponse {
+    #[serde(alias = "txHash", alias = "hash")]
+    tx_hash: String,
+    /// Wei actually sent, when the faucet reports it
+    #[serde(default)]
+    amount: Option<String>,
+}
+
+/// Fund `address` on `chain` from its test-network faucet
+pub async fn request_funding(
+    state: &Arc<AppState>,
+    user_id: Option<&str>,
+    chain: &str,
+    address: &str,
+) -> Result<FaucetFunding, FaucetError> {
+    let faucet = &state.config.faucet;
+    if !faucet.enabled {
+        return Err(FaucetError::Disabled);
+    }
+
+    let chain: Chain = chain
+        .parse()
+        .map_err(|_| FaucetError::InvalidChain(chain.to_string()))?;
+    let address = address.trim();
+    let valid = match chain {
+        Chain::Solana => solana::validate_address(address),
+        Chain::Ethereum => address.starts_with("0x") && ethereum::validate_address(address),
+    };
+    if !valid {
+        return Err(FaucetError::InvalidAddress(address.to_string()));
+    }
+    // Ethereum addresses are compared case-insensitively
+    let address = match chain {
+        Chain::Solana => address.to_string(),
+        Chain::Ethereum => address.to_lowercase(),
+    };
+
+    let now = chrono::Utc::now();
+    let recent = state
+        .db
+        .get_faucet_requests_since(&chain.to_string(), &address, &(now - WINDOW).to_rfc3339())
+        .await?;
+    if recent.len() >= faucet.daily_limit as usize {
+        // The window frees up when the oldest request in it ages out
+        let retry_at = chrono::DateTime::parse_from_rfc3339(&recent[0].created_at)
+            .map(|oldest| (oldest + WINDOW).to_rfc3339())
+            .unwrap_or_default();
+        return Err(FaucetError::LimitReached {
+            limit: faucet.daily_limit,
+            retry_at,
+        });
+    }
+
+    let (tx_hash, amount) = match chain {
+        Chain::Solana => {
+            let tx_hash = state
+                .chain_clients()
+                .get(chain)
+                .request_airdrop(&address, faucet.sol_lamports)
+                .await
+                .map_err(|e| FaucetError::Faucet(e.to_string()))?;
+            (tx_hash, faucet.sol_lamports.to_string())
+        }
+        Chain::Ethereum => request_sepolia_funding(state, &address).await?,
+    };
+
+    state
+        .db
+        .record_faucet_request(&FaucetRequestRow {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.map(str::to_string),
+            chain: chain.to_string(),
+            address: address.clone(),
+            amount: amount.clone(),
+            tx_hash: tx_hash.clone(),
+            created_at: now.to_rfc3339(),
+        })
+        .await?;
+
+    Ok(FaucetFunding {
+        chain: chain.to_string(),
+        address,
+        amount,
+        tx_hash,
+        remaining: faucet.daily_limit - recent.len() as u32 - 1,
+    })
+}
+
+/// Post the address to the Sepolia faucet API; returns the transaction hash
+/// and the wei sent
+async fn request_sepolia_funding(
+    state: &Arc<AppState>,
+    address: &str,
+) -> Result<(String, String), FaucetError> {
+    let faucet = &state.config.faucet;
+    let Some(url) = &faucet.sepolia_url else {
+        return Err(FaucetError::Unavailable(
+            "no Sepolia faucet is configured".to_string(),
+        ));
+    };
+    if state.config.eth_chain_id != SEPOLIA_CHAIN_ID {
+        return Err(FaucetError::Unavailable(format!(
+            "chain {} is not Sepolia",
+            state.config.eth_chain_id
+        )));
+    }
+
+    let amount = faucet.eth_wei.to_string();
+    let http = reqwest::Client::builder()
+        .timeout(FAUCET_API_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let response: SepoliaResponse = http
+        .post(url)
+        .json(&SepoliaRequest {
+            address,
+            amount: amount.clone(),
+        })
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| FaucetError::Faucet(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| FaucetError::Faucet(e.to_string()))?;
+
+    Ok((response.tx_hash, response.amount.unwrap_or(amount)))
+}
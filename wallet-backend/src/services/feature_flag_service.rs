@@ -0,0 +1,308 @@
+//! Feature flag service - runtime switches operators flip without a redeploy
+//!
+//! Flags live in the `feature_flags` table and are read through a cache that
+//! is reloaded once it is `CACHE_TTL` old, so a change made on one instance
+//! reaches the others within seconds and the instance that made it sees it
+//! at once. A flag that was never set has its default. If the table can't
+//! be read, the last loaded values (or the defaults) stay in force.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::api::error::ApiError;
+use crate::core::Chain;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::FeatureFlagRow;
+use crate::AppState;
+
+/// How long loaded flags are trusted before the table is read again
+const CACHE_TTL: Duration = Duration::from_secs(5);
+/// Longest reason shown to refused clients, in characters
+const MAX_REASON_CHARS: usize = 200;
+
+#[derive(Debug, Error)]
+pub enum FeatureFlagError {
+    #[error("Unknown feature flag: {0}")]
+    UnknownFlag(String),
+    #[error("Invalid reason: {0}")]
+    InvalidReason(String),
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+impl From<DatabaseError> for FeatureFlagError {
+    fn from(e: DatabaseError) -> Self {
+        FeatureFlagError::Database(e.to_string())
+    }
+}
+
+/// A runtime switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureFlag {
+    /// Read-only maintenance mode: every write is refused
+    Maintenance,
+    /// Sends on one chain
+    Sends(Chain),
+    /// Token swaps
+    Swaps,
+}
+
+impl FeatureFlag {
+    /// Every flag, in listing order
+    pub const ALL: [FeatureFlag; 4] = [
+        FeatureFlag::Maintenance,
+        FeatureFlag::Sends(Chain::Solana),
+        FeatureFlag::Sends(Chain::Ethereum),
+        FeatureFlag::Swaps,
+    ];
+
+    /// Value of a flag that was never set
+    pub fn default_enabled(self) -> bool {
+        !matches!(self, FeatureFlag::Maintenance)
+    }
+
+    /// Whether the flag at `enabled` refuses what it guards
+    fn refuses(self, enabled: bool) -> bool {
+        match self {
+            FeatureFlag::Maintenance => enabled,
+            _ => !enabled,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            FeatureFlag::Maintenance => "Read-only maintenance mode; every write is refused",
+            FeatureFlag::Sends(Chain::Solana) => "Sends on Solana",
+            FeatureFlag::Sends(Chain::Ethereum) => "Sends on Ethereum",
+            FeatureFlag::Swaps => "Token swaps",
+        }
+    }
+}
+
+impl fmt::Display for FeatureFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeatureFlag::Maintenance => write!(f, "maintenance"),
+            FeatureFlag::Sends(chain) => write!(f, "sends.{}", chain),
+            FeatureFlag::Swaps => write!(f, "swaps"),
+        }
+    }
+}
+
+impl FromStr for FeatureFlag {
+    type Err = FeatureFlagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FeatureFlag::ALL
+            .into_iter()
+            .find(|flag| flag.to_string() == s)
+            .ok_or_else(|| FeatureFlagError::UnknownFlag(s.to_string()))
+    }
+}
+
+/// A request refused by a flag; answered with a structured 503
+#[derive(Debug, Clone)]
+pub struct FeatureDisabled {
+    pub flag: FeatureFlag,
+    pub reason: Option<String>,
+}
+
+impl fmt::Display for FeatureDisabled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.flag {
+            FeatureFlag::Maintenance => write!(f, "The API is in read-only maintenance mode")?,
+            FeatureFlag::Sends(chain) => write!(f, "Sends on {} are temporarily disabled", chain)?,
+            FeatureFlag::Swaps => write!(f, "Swaps are temporarily disabled")?,
+        }
+        match &self.reason {
+            Some(reason) => write!(f, ": {}", reason),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for FeatureDisabled {}
+
+impl FeatureDisabled {
+    /// Stable error code of the refusal
+    pub fn code(&self) -> &'static str {
+        match self.flag {
+            FeatureFlag::Maintenance => "maintenance",
+            _ => "feature_disabled",
+        }
+    }
+}
+
+impl IntoResponse for FeatureDisabled {
+    fn into_response(self) -> Response {
+        ApiError {
+            retryable: Some(true),
+            ..ApiError::new(StatusCode::SERVICE_UNAVAILABLE, self.code(), self.to_string())
+        }
+        .into_response()
+    }
+}
+
+/// Flags as last read from the database
+#[derive(Default)]
+pub struct FlagCache {
+    loaded: RwLock<Option<(Instant, HashMap<String, FeatureFlagRow>)>>,
+}
+
+/// Set flag request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetFlagRequest {
+    pub enabled: bool,
+    /// Shown to clients refused because of the flag
+    pub reason: Option<String>,
+}
+
+/// A flag's current value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlagResponse {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    pub default_enabled: bool,
+    pub reason: Option<String>,
+    /// `None` for flags that were never set
+    pub updated_at: Option<String>,
+}
+
+impl FeatureFlagResponse {
+    fn new(flag: FeatureFlag, row: Option<&FeatureFlagRow>) -> Self {
+        Self {
+            name: flag.to_string(),
+            description: flag.description().to_string(),
+            enabled: row.map_or(flag.default_enabled(), |r| r.enabled),
+            default_enabled: flag.default_enabled(),
+            reason: row.and_then(|r| r.reason.clone()),
+            updated_at: row.map(|r| r.updated_at.clone()),
+        }
+    }
+}
+
+/// Refuse with `FeatureDisabled` when `flag` is set against what it guards
+pub async fn check(state: &Arc<AppState>, flag: FeatureFlag) -> Result<(), FeatureDisabled> {
+    let rows = current(state).await;
+    let row = rows.get(&flag.to_string());
+    let enabled = row.map_or(flag.default_enabled(), |r| r.enabled);
+    if flag.refuses(enabled) {
+        return Err(FeatureDisabled {
+            flag,
+            reason: row.and_then(|r| r.reason.clone()),
+        });
+    }
+    Ok(())
+}
+
+/// Every flag with its current value
+pub async fn list_flags(
+    state: &Arc<AppState>,
+) -> Result<Vec<FeatureFlagResponse>, FeatureFlagError> {
+    let rows = load(state).await?;
+    Ok(FeatureFlag::ALL
+        .into_iter()
+        .map(|flag| FeatureFlagResponse::new(flag, rows.get(&flag.to_string())))
+        .collect())
+}
+
+/// Set a flag; it takes effect on this instance at once
+pub async fn set_flag(
+    state: &Arc<AppState>,
+    name: &str,
+    request: SetFlagRequest,
+) -> Result<FeatureFlagResponse, FeatureFlagError> {
+    let flag: FeatureFlag = name.parse()?;
+    let reason = request
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if reason.as_ref().is_some_and(|r| r.chars().count() > MAX_REASON_CHARS) {
+        return Err(FeatureFlagError::InvalidReason(format!(
+            "longer than {} characters",
+            MAX_REASON_CHARS
+        )));
+    }
+
+    let row = FeatureFlagRow {
+        name: flag.to_string(),
+        enabled: request.enabled,
+        reason,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    state.db.upsert_feature_flag(&row).await?;
+    tracing::warn!(flag = %flag, enabled = row.enabled, "Feature flag set");
+
+    let rows = load(state).await?;
+    Ok(FeatureFlagResponse::new(flag, rows.get(&row.name)))
+}
+
+/// Cached flags, reloaded once stale. A failed reload keeps what was
+/// loaded before.
+async fn current(state: &Arc<AppState>) -> HashMap<String, FeatureFlagRow> {
+    if let Some((loaded_at, rows)) = state.feature_flags.loaded.read().await.as_ref() {
+        if loaded_at.elapsed() < CACHE_TTL {
+            return rows.clone();
+        }
+    }
+    match load(state).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!(error = %e, "Feature flags unreadable; keeping the last values");
+            let mut loaded = state.feature_flags.loaded.write().await;
+            let rows = loaded.as_ref().map(|(_, rows)| rows.clone()).unwrap_or_default();
+            // Don't retry on every request while the database is down
+            *loaded = Some((Instant::now(), rows.clone()));
+            rows
+        }
+    }
+}
+
+/// Read the flags and refresh the cache
+async fn load(state: &Arc<AppState>) -> Result<HashMap<String, FeatureFlagRow>, FeatureFlagError> {
+    let rows: HashMap<String, FeatureFlagRow> = state
+        .db
+        .get_feature_flags()
+        .await?
+        .into_iter()
+        .map(|row| (row.name.clone(), row))
+        .collect();
+    *state.feature_flags.loaded.write().await = Some((Instant::now(), rows.clone()));
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_names() {
+        for flag in FeatureFlag::ALL {
+            assert_eq!(flag.to_string().parse::<FeatureFlag>().unwrap(), flag);
+        }
+        assert_eq!(
+            "sends.ethereum".parse::<FeatureFlag>().unwrap(),
+            FeatureFlag::Sends(Chain::Ethereum)
+        );
+        assert!("sends.bitcoin".parse::<FeatureFlag>().is_err());
+        assert!(FeatureFlag::Maintenance.refuses(true));
+        assert!(FeatureFlag::Swaps.refuses(false));
+
+        let disabled = FeatureDisabled {
+            flag: FeatureFlag::Sends(Chain::Solana),
+            reason: Some("RPC upgrade".to_string()),
+        };
+        assert_eq!(disabled.to_string(), "Sends on solana are temporarily disabled: RPC upgrade");
+        assert_eq!(disabled.code(), "feature_disabled");
+    }
+}
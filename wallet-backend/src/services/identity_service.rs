@@ -0,0 +1,198 @@
+//! Identity service - ENS / SNS names and profiles for address book contacts
+//!
+//! Lookups are cached on the contact row. A background task re-resolves
+//! entries older than `IDENTITY_REFRESH_SECS`; manual refreshes are throttled
+//! so clients can't turn the endpoint into a resolver proxy.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::chains::{ChainClientError, Identity};
+use crate::core::Chain;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::ContactRow;
+use crate::AppState;
+
+/// Manual refreshes within this long of the last lookup return the cached result
+pub const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the background task looks for stale identities
+const REFRESH_TICK: Duration = Duration::from_secs(600);
+
+/// Contacts re-resolved per background pass
+const REFRESH_BATCH_SIZE: u32 = 50;
+
+#[derive(Debug, Error)]
+pub enum IdentityServiceError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Contact not found")]
+    NotFound,
+    #[error("Identity lookup failed: {0}")]
+    Resolver(String),
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+/// Refresh a contact's identity unless it was resolved within
+/// `MIN_REFRESH_INTERVAL`, and return the updated contact
+pub async fn refresh_contact_identity(
+    state: &Arc<AppState>,
+    id: &str,
+) -> Result<ContactRow, IdentityServiceError> {
+    let contact = state.db.get_contact(id).await.map_err(|e| match e {
+        DatabaseError::NotFound => IdentityServiceError::NotFound,
+        _ => IdentityServiceError::DatabaseError(e.to_string()),
+    })?;
+
+    if refreshed_within(&contact, MIN_REFRESH_INTERVAL) {
+        return Ok(contact);
+    }
+
+    let identity = resolve(state, &contact).await?;
+    store(state, contact, identity.as_ref()).await
+}
+
+/// Resolve a newly added contact; lookup failures are logged and the contact
+/// is returned unresolved for the background task to retry
+pub async fn resolve_new_contact(state: &Arc<AppState>, contact: ContactRow) -> ContactRow {
+    let identity = match resolve(state, &contact).await {
+        Ok(identity) => identity,
+        Err(e) => {
+            tracing::warn!(contact_id = %contact.id, error = %e, "Contact identity lookup failed");
+            return contact;
+        }
+    };
+
+    let id = contact.id.clone();
+    match store(state, contact.clone(), identity.as_ref()).await {
+        Ok(contact) => contact,
+        Err(e) => {
+            tracing::warn!(contact_id = %id, error = %e, "Failed to store contact identity");
+            contact
+        }
+    }
+}
+
+/// Re-resolve one batch of contacts whose identity is missing or older than
+/// the configured interval. Returns how many were updated.
+pub async fn refresh_stale_identities(
+    state: &Arc<AppState>,
+) -> Result<usize, IdentityServiceError> {
+    let interval = chrono::Duration::from_std(state.config.identity_refresh_interval)
+        .unwrap_or(chrono::Duration::days(1));
+    let before = (Utc::now() - interval).to_rfc3339();
+    let contacts = state
+        .db
+        .get_contacts_for_identity_refresh(&before, REFRESH_BATCH_SIZE)
+        .await
+        .map_err(|e| IdentityServiceError::DatabaseError(e.to_string()))?;
+
+    // The same address is often saved by several users; resolve it once
+    let mut resolved: HashMap<(String, String), Option<Identity>> = HashMap::new();
+    let mut updated = 0;
+    for contact in contacts {
+        let key = (contact.chain.clone(), contact.address.clone());
+        let identity = match resolved.get(&key) {
+            Some(identity) => identity.clone(),
+            None => match resolve(state, &contact).await {
+                Ok(identity) => {
+                    resolved.insert(key, identity.clone());
+                    identity
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        contact_id = %contact.id,
+                        chain = %contact.chain,
+                        error = %e,
+                        "Contact identity refresh failed"
+                    );
+                    continue;
+                }
+            },
+        };
+
+        store(state, contact, identity.as_ref()).await?;
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+/// Run `refresh_stale_identities` periodically for the life of the process
+pub fn spawn_identity_refresh(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(REFRESH_TICK);
+        loop {
+            ticker.tick().await;
+            match refresh_stale_identities(&state).await {
+                Ok(0) => {}
+                Ok(updated) => tracing::debug!(updated, "Refreshed contact identities"),
+                Err(e) => tracing::warn!(error = %e, "Contact identity refresh failed"),
+            }
+        }
+    })
+}
+
+async fn resolve(
+    state: &Arc<AppState>,
+    contact: &ContactRow,
+) -> Result<Option<Identity>, IdentityServiceError> {
+    let chain: Chain = contact
+        .chain
+        .parse()
+        .map_err(|_| IdentityServiceError::InvalidChain(contact.chain.clone()))?;
+
+    match state
+        .chain_clients()
+        .get(chain)
+        .resolve_identity(&contact.address)
+        .await
+    {
+        Ok(identity) => Ok(identity),
+        // A malformed saved address simply has no name
+        Err(ChainClientError::InvalidAddress(_)) => Ok(None),
+        Err(e) => Err(IdentityServiceError::Resolver(e.to_string())),
+    }
+}
+
+async fn store(
+    state: &Arc<AppState>,
+    mut contact: ContactRow,
+    identity: Option<&Identity>,
+) -> Result<ContactRow, IdentityServiceError> {
+    contact.identity_name = identity.map(|i| i.name.clone());
+    contact.identity_avatar = identity.and_then(|i| i.avatar.clone());
+    contact.identity_records = identity
+        .map(|i| serde_json::to_string(&i.records))
+        .transpose()
+        .map_err(|e| IdentityServiceError::DatabaseError(e.to_string()))?;
+    contact.identity_refreshed_at = Some(Utc::now().to_rfc3339());
+
+    state
+        .db
+        .set_contact_identity(
+            &contact.id,
+            contact.identity_name.as_deref(),
+            contact.identity_avatar.as_deref(),
+            contact.identity_records.as_deref(),
+            contact.identity_refreshed_at.as_deref().unwrap_or_default(),
+        )
+        .await
+        .map_err(|e| IdentityServiceError::DatabaseError(e.to_string()))?;
+
+    Ok(contact)
+}
+
+fn refreshed_within(contact: &ContactRow, window: Duration) -> bool {
+    contact
+        .identity_refreshed_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .and_then(|at| (Utc::now() - at.with_timezone(&Utc)).to_std().ok())
+        .is_some_and(|age| age < window)
+}
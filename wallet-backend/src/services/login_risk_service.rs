@@ -0,0 +1,341 @@
+//! Login risk service - spots password logins that don't look like the user
+//!
+//! Each login is compared with the user's recent sessions: a new IP, a new
+//! device fingerprint, a new country, and a country change sooner than
+//! anyone could travel each add to a 0-100 risk score. At or above
+//! `LOGIN_STEP_UP_SCORE` the session is held back until the user opens a
+//! verification link sent through `LOGIN_VERIFICATION_WEBHOOK_URL`. What
+//! is decided about each unusual login is written to the audit log.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::{header, HeaderMap};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::storage::models::{AuditEventRow, AuditSeverity};
+use crate::AppState;
+
+/// Header clients may send a stable device identifier in; the user agent
+/// stands in when it is absent
+pub const DEVICE_FINGERPRINT_HEADER: &str = "x-device-fingerprint";
+
+/// Sessions a login is compared against
+pub const HISTORY_LIMIT: u32 = 50;
+
+/// A country change within this long of the last login counts as
+/// impossible travel
+const IMPOSSIBLE_TRAVEL_WINDOW: chrono::Duration = chrono::Duration::hours(2);
+
+/// Upper bound on delivering a verification link
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum LoginRiskError {
+    #[error("Verification link could not be sent: {0}")]
+    Delivery(String),
+}
+
+/// Where a login comes from
+#[derive(Debug, Clone, Default)]
+pub struct LoginClient {
+    /// User agent
+    pub device_info: Option<String>,
+    pub ip_address: Option<String>,
+    /// SHA-256 of the client's fingerprint header (or user agent)
+    pub device_fingerprint: Option<String>,
+    /// Upper-case ISO country code from the trusted proxy
+    pub country: Option<String>,
+}
+
+impl LoginClient {
+    /// Read the user agent, fingerprint and, when `country_header` names
+    /// one, the country from a request
+    pub fn from_headers(
+        headers: &HeaderMap,
+        ip_address: Option<String>,
+        country_header: Option<&str>,
+    ) -> Self {
+        let value = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let device_info = value(header::USER_AGENT.as_str());
+        let device_fingerprint = value(DEVICE_FINGERPRINT_HEADER)
+            .or_else(|| device_info.clone())
+            .map(|fingerprint| hex::encode(Sha256::digest(fingerprint.as_bytes())));
+        // Proxies use XX or T1 when they can't place the address
+        let country = country_header
+            .and_then(value)
+            .map(|c| c.to_uppercase())
+            .filter(|c| c.len() == 2 && c.chars().all(|ch| ch.is_ascii_alphabetic()) && c != "XX");
+
+        Self {
+            device_info,
+            ip_address,
+            device_fingerprint,
+            country,
+        }
+    }
+}
+
+/// An earlier session of the user
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PriorLogin {
+    pub ip_address: Option<String>,
+    pub device_fingerprint: Option<String>,
+    pub country: Option<String>,
+    pub created_at: String,
+}
+
+/// Something unusual about a login
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskSignal {
+    NewIp,
+    NewDevice,
+    NewCountry,
+    ImpossibleTravel,
+}
+
+impl RiskSignal {
+    fn weight(self) -> u8 {
+        match self {
+            RiskSignal::NewIp => 15,
+            RiskSignal::NewDevice => 30,
+            RiskSignal::NewCountry => 35,
+            RiskSignal::ImpossibleTravel => 50,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RiskSignal::NewIp => "new_ip",
+            RiskSignal::NewDevice => "new_device",
+            RiskSignal::NewCountry => "new_country",
+            RiskSignal::ImpossibleTravel => "impossible_travel",
+        }
+    }
+
+    /// Parse a stored comma-separated list, skipping unknown entries
+    pub fn parse_list(signals: &str) -> Vec<RiskSignal> {
+        signals
+            .split(',')
+            .filter_map(|s| match s.trim() {
+                "new_ip" => Some(RiskSignal::NewIp),
+                "new_device" => Some(RiskSignal::NewDevice),
+                "new_country" => Some(RiskSignal::NewCountry),
+                "impossible_travel" => Some(RiskSignal::ImpossibleTravel),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn join(signals: &[RiskSignal]) -> String {
+        signals.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// Risk score of a login and what raised it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    /// 0 (nothing unusual) to 100
+    pub score: u8,
+    pub signals: Vec<RiskSignal>,
+}
+
+/// Score a login against the user's earlier sessions, newest first. A
+/// first login has nothing to compare with and scores 0, and signals that
+/// weren't seen (no country header, say) don't count.
+pub fn assess(client: &LoginClient, history: &[PriorLogin], now: DateTime<Utc>) -> RiskAssessment {
+    let mut signals = Vec::new();
+    if history.is_empty() {
+        return RiskAssessment { score: 0, signals };
+    }
+
+    let unseen = |current: &Option<String>, earlier: fn(&PriorLogin) -> &Option<String>| {
+        let known: Vec<&String> = history.iter().filter_map(|login| earlier(login).as_ref()).collect();
+        match current {
+            Some(current) => !known.is_empty() && !known.contains(&current),
+            None => false,
+        }
+    };
+    if unseen(&client.ip_address, |login| &login.ip_address) {
+        signals.push(RiskSignal::NewIp);
+    }
+    if unseen(&client.device_fingerprint, |login| &login.device_fingerprint) {
+        signals.push(RiskSignal::NewDevice);
+    }
+    if unseen(&client.country, |login| &login.country) {
+        signals.push(RiskSignal::NewCountry);
+    }
+
+    let last_placed = history.iter().find(|login| login.country.is_some());
+    if let (Some(country), Some(last)) = (&client.country, last_placed) {
+        let recent = DateTime::parse_from_rfc3339(&last.created_at)
+            .map(|at| now - at.with_timezone(&Utc) < IMPOSSIBLE_TRAVEL_WINDOW)
+            .unwrap_or(false);
+        if recent && last.country.as_ref() != Some(country) {
+            signals.push(RiskSignal::ImpossibleTravel);
+        }
+    }
+
+    let score = signals.iter().map(|s| s.weight() as u32).sum::<u32>().min(100) as u8;
+    RiskAssessment { score, signals }
+}
+
+/// A login held back for verification, and the link that completes it
+#[derive(Debug, Clone)]
+pub struct LoginVerification {
+    pub id: String,
+    /// Plain token from the link; only its hash is stored
+    pub token: String,
+    pub email: String,
+    pub expires_at: String,
+}
+
+/// Body POSTed to the mail relay
+#[derive(Debug, Clone, Serialize)]
+struct VerificationMessage<'a> {
+    email: &'a str,
+    link: String,
+    ip_address: Option<&'a str>,
+    country: Option<&'a str>,
+    signals: &'a [RiskSignal],
+    expires_at: &'a str,
+}
+
+/// Send the verification link for the mail relay to email to the user
+pub async fn send_verification_link(
+    state: &Arc<AppState>,
+    verification: &LoginVerification,
+    client: &LoginClient,
+    assessment: &RiskAssessment,
+) -> Result<(), LoginRiskError> {
+    let config = &state.config.login_risk;
+    let url = config
+        .verification_webhook_url
+        .as_deref()
+        .ok_or_else(|| LoginRiskError::Delivery("no verification webhook".to_string()))?;
+    let mut link = reqwest::Url::parse(&config.verification_url)
+        .map_err(|e| LoginRiskError::Delivery(e.to_string()))?;
+    link.query_pairs_mut()
+        .append_pair("challenge", &verification.id)
+        .append_pair("token", &verification.token);
+
+    let message = VerificationMessage {
+        email: &verification.email,
+        link: link.to_string(),
+        ip_address: client.ip_address.as_deref(),
+        country: client.country.as_deref(),
+        signals: &assessment.signals,
+        expires_at: &verification.expires_at,
+    };
+    reqwest::Client::new()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(&message)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| LoginRiskError::Delivery(e.to_string()))?;
+    Ok(())
+}
+
+/// Record what was decided about a login that looked unusual: `allowed`,
+/// `step_up_required` or `verified`. Logins with nothing unusual aren't
+/// recorded, and a failure is logged rather than failing the login.
+pub async fn record_decision(
+    state: &Arc<AppState>,
+    user_id: &str,
+    decision: &str,
+    client: &LoginClient,
+    assessment: &RiskAssessment,
+) {
+    if assessment.signals.is_empty() {
+        return;
+    }
+    let severity = match decision {
+        "verified" => AuditSeverity::Info,
+        _ => AuditSeverity::Warning,
+    };
+    let audit = AuditEventRow::new(
+        current_tenant_id(),
+        Some(user_id.to_string()),
+        "auth.login_risk",
+        severity,
+        serde_json::json!({
+            "decision": decision,
+            "risk_score": assessment.score,
+            "signals": assessment.signals,
+            "ip_address": client.ip_address,
+            "country": client.country,
+            "device_fingerprint": client.device_fingerprint,
+        }),
+    );
+    if let Err(e) = state.db.record_audit_event(&audit).await {
+        tracing::warn!(user_id = %user_id, error = %e, "Recording login decision failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(ip: &str, device: &str, country: Option<&str>, at: DateTime<Utc>) -> PriorLogin {
+        PriorLogin {
+            ip_address: Some(ip.to_string()),
+            device_fingerprint: Some(device.to_string()),
+            country: country.map(str::to_string),
+            created_at: at.to_rfc3339(),
+        }
+    }
+
+    fn client(ip: &str, device: &str, country: Option<&str>) -> LoginClient {
+        LoginClient {
+            device_info: None,
+            ip_address: Some(ip.to_string()),
+            device_fingerprint: Some(device.to_string()),
+            country: country.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_assess() {
+        let now = Utc::now();
+        let week_ago = now - chrono::Duration::days(7);
+        let history = [login("10.0.0.1", "laptop", Some("DE"), week_ago)];
+
+        assert_eq!(assess(&client("10.0.0.9", "phone", Some("US")), &[], now).score, 0);
+        assert_eq!(assess(&client("10.0.0.1", "laptop", Some("DE")), &history, now).score, 0);
+
+        let assessment = assess(&client("10.0.0.1", "phone", None), &history, now);
+        assert_eq!(assessment.signals, [RiskSignal::NewDevice]);
+        assert_eq!(assessment.score, 30);
+
+        let assessment = assess(&client("10.0.0.9", "phone", Some("US")), &history, now);
+        assert_eq!(
+            assessment.signals,
+            [RiskSignal::NewIp, RiskSignal::NewDevice, RiskSignal::NewCountry]
+        );
+        assert_eq!(assessment.score, 80);
+
+        // The same move an hour after the last login
+        let recent = [login("10.0.0.1", "laptop", Some("DE"), now - chrono::Duration::hours(1))];
+        let assessment = assess(&client("10.0.0.1", "laptop", Some("US")), &recent, now);
+        assert_eq!(
+            assessment.signals,
+            [RiskSignal::NewCountry, RiskSignal::ImpossibleTravel]
+        );
+        assert_eq!(assessment.score, 85);
+        assert_eq!(RiskSignal::parse_list(&RiskSignal::join(&assessment.signals)), assessment.signals);
+    }
+}
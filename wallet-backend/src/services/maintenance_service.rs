@@ -0,0 +1,196 @@
+//! Maintenance service - keeps the database from growing without bound
+//!
+//! Every `MAINTENANCE_INTERVAL_SECS` (hourly by default) expired single-use
+//! challenges, old sessions, stale Solana NFT cache entries and old
+//! reconciliation runs and faucet requests are deleted, then `PRAGMA
+//! optimize` refreshes the planner statistics. Accounts whose deletion grace
+//! period has ended are purged in the same pass. Once deletes have left at
+//! least `MAINTENANCE_VACUUM_MIN_FREE_BYTES` free, the file is vacuumed, but
+//! only when no request is using the database. Rows pruned, bytes reclaimed
+//! and the database size are exported on `/metrics`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use prometheus::{Encoder, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::services::account_deletion_service::{self, AccountDeletionError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::RetentionCutoffs;
+use crate::AppState;
+
+/// Expired challenges are kept this long so a late attempt still reads as
+/// expired rather than unknown
+const CHALLENGE_GRACE: Duration = Duration::days(1);
+
+#[derive(Debug, Error)]
+pub enum MaintenanceError {
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+    #[error("Account purge failed: {0}")]
+    AccountPurge(#[from] AccountDeletionError),
+}
+
+/// What a maintenance pass did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// Rows deleted per table
+    pub pruned: BTreeMap<String, u64>,
+    /// Deleted accounts purged after their grace period
+    pub accounts_purged: u64,
+    pub vacuumed: bool,
+    /// Bytes the file shrank by
+    pub reclaimed_bytes: u64,
+    /// Database size afterwards
+    pub size_bytes: u64,
+}
+
+/// Maintenance counters, exported alongside the RPC metrics
+pub struct MaintenanceMetrics {
+    registry: Registry,
+    runs: IntCounterVec,
+    pruned: IntCounterVec,
+    reclaimed: IntCounter,
+    size: IntGauge,
+}
+
+impl MaintenanceMetrics {
+    pub fn new() -> Self {
+        let runs = IntCounterVec::new(
+            Opts::new("db_maintenance_runs_total", "Database maintenance passes by outcome"),
+            &["outcome"],
+        )
+        .expect("valid counter");
+        let pruned = IntCounterVec::new(
+            Opts::new("db_maintenance_rows_pruned_total", "Rows deleted by database maintenance"),
+            &["table"],
+        )
+        .expect("valid counter");
+        let reclaimed = IntCounter::new(
+            "db_maintenance_reclaimed_bytes_total",
+            "Bytes returned to the OS by VACUUM",
+        )
+        .expect("valid counter");
+        let size = IntGauge::new("db_size_bytes", "Database file size after the last maintenance pass")
+            .expect("valid gauge");
+
+        let registry = Registry::new();
+        for metric in [
+            Box::new(runs.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(pruned.clone()),
+            Box::new(reclaimed.clone()),
+            Box::new(size.clone()),
+        ] {
+            registry.register(metric).expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            runs,
+            pruned,
+            reclaimed,
+            size,
+        }
+    }
+
+    fn record(&self, report: &MaintenanceReport) {
+        self.runs.with_label_values(&["ok"]).inc();
+        for (table, rows) in &report.pruned {
+            self.pruned.with_label_values(&[table]).inc_by(*rows);
+        }
+        self.reclaimed.inc_by(report.reclaimed_bytes);
+        self.size.set(report.size_bytes.min(i64::MAX as u64) as i64);
+    }
+
+    /// All maintenance metrics in the Prometheus text format
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+impl Default for MaintenanceMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Prune, optimize and, when worthwhile and idle, vacuum the database as of
+/// `now`
+pub async fn run_maintenance(
+    state: &Arc<AppState>,
+    now: DateTime<Utc>,
+) -> Result<MaintenanceReport, MaintenanceError> {
+    let result = maintain(state, now).await;
+    match &result {
+        Ok(report) => state.maintenance_metrics.record(report),
+        Err(_) => state.maintenance_metrics.runs.with_label_values(&["failed"]).inc(),
+    }
+    result
+}
+
+async fn maintain(
+    state: &Arc<AppState>,
+    now: DateTime<Utc>,
+) -> Result<MaintenanceReport, MaintenanceError> {
+    let config = &state.config.maintenance;
+    let days_ago = |days: u32| (now - Duration::days(days as i64)).to_rfc3339();
+    let cutoffs = RetentionCutoffs {
+        challenges_before: (now - CHALLENGE_GRACE).to_rfc3339(),
+        sessions_before: days_ago(config.session_retention_days),
+        nft_cache_before: days_ago(config.nft_cache_retention_days),
+        jobs_before: days_ago(config.job_retention_days),
+    };
+
+    let pruned: BTreeMap<String, u64> = state
+        .db
+        .prune_expired(&cutoffs)
+        .await?
+        .into_iter()
+        .map(|(table, rows)| (table.to_string(), rows))
+        .collect();
+    let accounts_purged = account_deletion_service::purge_due_deletions(state, now).await?;
+    state.db.optimize().await?;
+
+    let before = state.db.page_stats().await?;
+    let free = before.free_bytes();
+    let vacuumed = free > 0 && free >= config.vacuum_min_free_bytes && state.db.is_idle();
+    let after = if vacuumed {
+        state.db.vacuum().await?;
+        state.db.page_stats().await?
+    } else {
+        before
+    };
+
+    Ok(MaintenanceReport {
+        pruned,
+        accounts_purged,
+        vacuumed,
+        reclaimed_bytes: before.size_bytes().saturating_sub(after.size_bytes()),
+        size_bytes: after.size_bytes(),
+    })
+}
+
+/// Run maintenance every `MAINTENANCE_INTERVAL_SECS`
+pub fn spawn_maintenance(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.maintenance.interval);
+        loop {
+            ticker.tick().await;
+            match run_maintenance(&state, Utc::now()).await {
+                Ok(report) => tracing::info!(
+                    pruned = report.pruned.values().sum::<u64>(),
+                    accounts_purged = report.accounts_purged,
+                    vacuumed = report.vacuumed,
+                    reclaimed_bytes = report.reclaimed_bytes,
+                    "Database maintenance finished"
+                ),
+                Err(e) => tracing::warn!(error = %e, "Database maintenance failed"),
+            }
+        }
+    })
+}
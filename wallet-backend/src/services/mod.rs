@@ -1,10 +1,53 @@
 //! Business logic services
 
+pub mod account_deletion_service;
+pub mod alert_service;
+pub mod analytics_service;
+pub mod avatar_service;
+pub mod balance_service;
+pub mod bridge_service;
+pub mod bucket_service;
+pub mod cold_signing_service;
+pub mod confirmation_service;
+pub mod contact_service;
+pub mod cors_service;
+pub mod cross_chain_service;
+#[cfg(debug_assertions)]
+pub mod dev_service;
+pub mod faucet_service;
+pub mod feature_flag_service;
+pub mod identity_service;
+pub mod login_risk_service;
+pub mod maintenance_service;
 pub mod multisig_service;
+pub mod name_service;
 pub mod nft_service;
+pub mod note_service;
+pub mod ownership_service;
+pub mod price_alert_service;
+pub mod price_service;
+pub mod qr_service;
+pub mod reconciliation_service;
+pub mod scheduled_service;
+pub mod security_service;
+pub mod session_key_service;
+pub mod share_service;
+pub mod siem_service;
+pub mod staking_service;
+pub mod status_service;
+pub mod sign_in_service;
+pub mod statement_service;
+pub mod stuck_service;
+pub mod sync_service;
+pub mod template_service;
+pub mod tenant_service;
+pub mod token_list_service;
+pub mod token_service;
 pub mod transaction_service;
 pub mod user_service;
 pub mod wallet_service;
+pub mod watchlist_service;
+pub mod webhook_service;
 
 pub use multisig_service::*;
 pub use nft_service::*;
@@ -2,17 +2,18 @@
 
 use std::sync::Arc;
 
+use base64::{engine::general_purpose::STANDARD, Engine};
 use thiserror::Error;
 
-use crate::chains::solana::{
-    create_multisig as create_solana_multisig, MultisigConfig as SolanaMultisigConfig,
-    SolanaKeypair,
-};
-use crate::chains::ethereum::compute_safe_address;
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::{ethereum, solana, ChainClientError, DeploymentCost};
+use crate::core::{format_units, Chain};
 use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
 use crate::storage::models::{
-    MultisigOwnerResponse, MultisigOwnerRow, MultisigTransactionResponse,
-    MultisigTransactionRow, MultisigWalletResponse, MultisigWalletRow,
+    MultisigOwnerResponse, MultisigOwnerRow, MultisigSignatureResponse, MultisigSignatureRow,
+    MultisigTransactionResponse, MultisigTransactionRow, MultisigWalletResponse,
+    AccountRow, MultisigWalletRow,
 };
 use crate::AppState;
 
@@ -22,6 +23,20 @@ pub enum MultisigServiceError {
     WalletError(#[from] WalletServiceError),
     #[error("Invalid chain: {0}")]
     InvalidChain(String),
+    #[error("Invalid multi-sig: {0}")]
+    InvalidConfig(String),
+    #[error("No {0} account to sponsor the deployment")]
+    NoSponsor(Chain),
+    #[error(
+        "Primary account {address} can't cover the deployment: {required} required, {available} available"
+    )]
+    SponsorUnderfunded {
+        address: String,
+        required: String,
+        available: String,
+    },
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
     #[error("Creation failed: {0}")]
     CreationFailed(String),
     #[error("Multi-sig not found")]
@@ -32,6 +47,14 @@ pub enum MultisigServiceError {
     AlreadyApproved,
     #[error("Insufficient approvals")]
     InsufficientApprovals,
+    #[error("Transaction is already {0}")]
+    TransactionClosed(String),
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("{0} is not an owner of this multi-sig")]
+    NotAnOwner(String),
     #[error("Database error: {0}")]
     DatabaseError(String),
 }
@@ -43,58 +66,206 @@ pub struct CreateMultisigRequest {
     pub name: String,
     pub threshold: u8,
     pub owners: Vec<String>,
+    /// Have the wallet's primary account on the chain pay for deploying it
+    #[serde(default)]
+    pub sponsor: bool,
+}
+
+/// Multi-sig to estimate the deployment of
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EstimateMultisigRequest {
+    pub chain: String,
+    pub threshold: u8,
+    pub owners: Vec<String>,
+}
+
+/// What deploying a multi-sig would cost, and whether the primary account
+/// could sponsor it
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DeploymentEstimate {
+    pub chain: String,
+    /// Rent-exempt minimum of the Squads accounts, in base units (Solana)
+    pub rent: String,
+    /// Signature fees, or the Safe deployment gas at the current gas price,
+    /// in base units
+    pub network_fee: String,
+    /// Gas the Safe deployment is expected to use (Ethereum)
+    pub gas: Option<u64>,
+    /// `rent` plus `network_fee`
+    pub total: String,
+    /// `total` in SOL or ETH
+    pub formatted_total: String,
+    /// Primary account that would pay with `sponsor: true`; `None` when
+    /// the wallet has no account on the chain
+    pub sponsor: Option<SponsorCheck>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SponsorCheck {
+    pub address: String,
+    /// Native balance in base units
+    pub balance: String,
+    /// The balance covers `total`
+    pub sufficient: bool,
+    /// Base units missing when it doesn't
+    pub shortfall: String,
+}
+
+/// Threshold and owners a multi-sig can be created with
+fn validate_config(threshold: u8, owners: &[String]) -> Result<(), MultisigServiceError> {
+    if owners.is_empty() {
+        return Err(MultisigServiceError::InvalidConfig("no owners".to_string()));
+    }
+    if threshold == 0 || threshold as usize > owners.len() {
+        return Err(MultisigServiceError::InvalidConfig(format!(
+            "threshold must be between 1 and {}",
+            owners.len()
+        )));
+    }
+    Ok(())
+}
+
+/// First account of the primary wallet on `chain`, which sponsors
+/// deployments there
+async fn sponsor_account(
+    state: &Arc<AppState>,
+    wallet_id: &str,
+    chain: Chain,
+) -> Result<Option<AccountRow>, MultisigServiceError> {
+    let accounts = state
+        .db
+        .get_accounts(wallet_id)
+        .await
+        .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?;
+    Ok(accounts
+        .into_iter()
+        .filter(|a| a.chain == chain.to_string())
+        .min_by_key(|a| a.derivation_index))
 }
 
-/// Create a new multi-sig wallet
+/// Native balance of `account` in base units
+async fn sponsor_balance(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    chain: Chain,
+) -> Result<u128, MultisigServiceError> {
+    let balance = state.account_clients(account).get(chain).balance(&account.address).await?;
+    balance.native_balance_raw.parse().map_err(|_| {
+        ChainClientError::Rpc(format!("unreadable balance {}", balance.native_balance_raw)).into()
+    })
+}
+
+/// Estimate deploying a multi-sig, with the primary account's balance
+/// checked against it
+pub async fn estimate_deployment(
+    state: &Arc<AppState>,
+    request: EstimateMultisigRequest,
+) -> Result<DeploymentEstimate, MultisigServiceError> {
+    validate_config(request.threshold, &request.owners)?;
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| MultisigServiceError::InvalidChain(request.chain.clone()))?;
+    let cost = state
+        .chain_clients()
+        .get(chain)
+        .multisig_deployment_cost(&request.owners, request.threshold)
+        .await?;
+
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await
+        .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?;
+    let account = match &wallet {
+        Some(wallet) => sponsor_account(state, &wallet.id, chain).await?,
+        None => None,
+    };
+    let sponsor = match account {
+        Some(account) => {
+            let balance = sponsor_balance(state, &account, chain).await?;
+            Some(SponsorCheck {
+                address: account.address,
+                balance: balance.to_string(),
+                sufficient: balance >= cost.total(),
+                shortfall: cost.total().saturating_sub(balance).to_string(),
+            })
+        }
+        None => None,
+    };
+
+    Ok(estimate_response(chain, &cost, sponsor))
+}
+
+fn estimate_response(chain: Chain, cost: &DeploymentCost, sponsor: Option<SponsorCheck>) -> DeploymentEstimate {
+    DeploymentEstimate {
+        chain: chain.to_string(),
+        rent: cost.rent.to_string(),
+        network_fee: cost.network_fee.to_string(),
+        gas: cost.gas,
+        total: cost.total().to_string(),
+        formatted_total: format_units(cost.total(), chain.native_decimals()),
+        sponsor,
+    }
+}
+
+/// Create a new multi-sig wallet; with `sponsor`, only once the primary
+/// account is known to cover the deployment
 pub async fn create_multisig(
     state: &Arc<AppState>,
     request: CreateMultisigRequest,
 ) -> Result<MultisigWalletResponse, MultisigServiceError> {
+    validate_config(request.threshold, &request.owners)?;
     let seed = get_seed(state).await?;
 
     // Get wallet ID
     let wallet = state
         .db
-        .get_primary_wallet()
+        .get_primary_wallet(&current_tenant_id())
         .await
         .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?
         .ok_or_else(|| MultisigServiceError::WalletError(WalletServiceError::NoWalletFound))?;
 
-    let address = match request.chain.to_lowercase().as_str() {
-        "solana" => {
-            let keypair = SolanaKeypair::derive(&seed, 0)
-                .map_err(|e| MultisigServiceError::CreationFailed(e.to_string()))?;
-
-            let config = SolanaMultisigConfig {
-                threshold: request.threshold,
-                owners: request.owners.clone(),
-                name: request.name.clone(),
-            };
-
-            let result = create_solana_multisig(&state.solana_rpc_url, &keypair, &config)
-                .map_err(|e| MultisigServiceError::CreationFailed(e.to_string()))?;
-
-            result.address
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| MultisigServiceError::InvalidChain(request.chain.clone()))?;
+
+    // Refuse before anything is created rather than leave a multi-sig its
+    // sponsor can't pay for
+    let sponsorship = match request.sponsor {
+        true => {
+            let account = sponsor_account(state, &wallet.id, chain)
+                .await?
+                .ok_or(MultisigServiceError::NoSponsor(chain))?;
+            let cost = state
+                .chain_clients()
+                .get(chain)
+                .multisig_deployment_cost(&request.owners, request.threshold)
+                .await?;
+            let balance = sponsor_balance(state, &account, chain).await?;
+            if balance < cost.total() {
+                let decimals = chain.native_decimals();
+                return Err(MultisigServiceError::SponsorUnderfunded {
+                    address: account.address,
+                    required: format_units(cost.total(), decimals),
+                    available: format_units(balance, decimals),
+                });
+            }
+            Some((account.address, cost.total()))
         }
-        "ethereum" => {
-            // Compute Safe address using default Sepolia factory addresses
-            const SAFE_PROXY_FACTORY: &str = "0xa6B71E26C5e0845f74c812102Ca7114b6a896AB2";
-            const SAFE_SINGLETON: &str = "0xd9Db270c1B5E3Bd161E8c8503c55cEABeE709552";
-
-            compute_safe_address(
-                SAFE_PROXY_FACTORY,
-                SAFE_SINGLETON,
-                &request.owners,
-                request.threshold,
-                chrono::Utc::now().timestamp() as u64,
-            )
-            .map_err(|e| MultisigServiceError::CreationFailed(e.to_string()))?
-        }
-        _ => return Err(MultisigServiceError::InvalidChain(request.chain)),
+        false => None,
     };
 
+    let address = state
+        .chain_clients()
+        .get(chain)
+        .create_multisig(&seed, &request.name, request.threshold, &request.owners)
+        .await
+        .map_err(|e| MultisigServiceError::CreationFailed(e.to_string()))?;
+
     // Store in database
-    let multisig_row = MultisigWalletRow::new(
+    let mut multisig_row = MultisigWalletRow::new(
         wallet.id.clone(),
         request.name.clone(),
         request.chain.to_lowercase(),
@@ -102,6 +273,10 @@ pub async fn create_multisig(
         request.threshold as u32,
         request.owners.len() as u32,
     );
+    if let Some((sponsor, cost)) = sponsorship {
+        multisig_row.sponsor_address = Some(sponsor);
+        multisig_row.deployment_cost = Some(cost.to_string());
+    }
 
     state
         .db
@@ -124,6 +299,15 @@ pub async fn create_multisig(
             .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?;
     }
 
+    tracing::info!(
+        multisig_id = %multisig_row.id,
+        chain = %multisig_row.chain,
+        address = %multisig_row.address,
+        threshold = request.threshold,
+        sponsor = ?multisig_row.sponsor_address,
+        "Multi-sig created"
+    );
+
     // Get owners for response
     let owners = state
         .db
@@ -146,16 +330,19 @@ pub async fn create_multisig(
             })
             .collect(),
         created_at: multisig_row.created_at,
+        sponsored_by: multisig_row.sponsor_address,
+        deployment_cost: multisig_row.deployment_cost,
     })
 }
 
-/// List multi-sig wallets
+/// List multi-sig wallets; owner lists are left empty unless `with_owners`
 pub async fn list_multisigs(
     state: &Arc<AppState>,
+    with_owners: bool,
 ) -> Result<Vec<MultisigWalletResponse>, MultisigServiceError> {
     let wallet = state
         .db
-        .get_primary_wallet()
+        .get_primary_wallet(&current_tenant_id())
         .await
         .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?
         .ok_or_else(|| MultisigServiceError::WalletError(WalletServiceError::NoWalletFound))?;
@@ -168,11 +355,14 @@ pub async fn list_multisigs(
 
     let mut responses = Vec::new();
     for ms in multisigs {
-        let owners = state
-            .db
-            .get_multisig_owners(&ms.id)
-            .await
-            .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?;
+        let owners = match with_owners {
+            true => state
+                .db
+                .get_multisig_owners(&ms.id)
+                .await
+                .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?,
+            false => Vec::new(),
+        };
 
         responses.push(MultisigWalletResponse {
             id: ms.id,
@@ -189,6 +379,8 @@ pub async fn list_multisigs(
                 })
                 .collect(),
             created_at: ms.created_at,
+            sponsored_by: ms.sponsor_address,
+            deployment_cost: ms.deployment_cost,
         });
     }
 
@@ -290,12 +482,12 @@ pub async fn approve_transaction(
     Ok(MultisigTransactionResponse::from(updated_tx))
 }
 
-/// Execute a multi-sig transaction
-pub async fn execute_transaction(
+/// A multi-sig and one of its transactions, once it has enough approvals
+async fn approved_transaction(
     state: &Arc<AppState>,
     multisig_id: &str,
     tx_id: &str,
-) -> Result<String, MultisigServiceError> {
+) -> Result<(MultisigWalletRow, MultisigTransactionRow), MultisigServiceError> {
     let multisig = state
         .db
         .get_multisig(multisig_id)
@@ -313,6 +505,20 @@ pub async fn execute_transaction(
     if approvals.len() < multisig.threshold as usize {
         return Err(MultisigServiceError::InsufficientApprovals);
     }
+    if !matches!(multisig.chain.as_str(), "solana" | "ethereum") {
+        return Err(MultisigServiceError::InvalidChain(multisig.chain));
+    }
+
+    Ok((multisig, tx))
+}
+
+/// Execute a multi-sig transaction
+pub async fn execute_transaction(
+    state: &Arc<AppState>,
+    multisig_id: &str,
+    tx_id: &str,
+) -> Result<String, MultisigServiceError> {
+    let (multisig, _) = approved_transaction(state, multisig_id, tx_id).await?;
 
     // Execute based on chain
     let signature = match multisig.chain.as_str() {
@@ -337,6 +543,34 @@ pub async fn execute_transaction(
     Ok(signature)
 }
 
+/// What executing a multi-sig transaction would do, from a dry run
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExecutePreview {
+    /// Always `true`; nothing was executed or stored
+    pub dry_run: bool,
+    pub multisig_address: String,
+    pub chain: String,
+    pub threshold: i64,
+    pub transaction: MultisigTransactionResponse,
+}
+
+/// Run the checks an execution would, without executing
+pub async fn preview_execute(
+    state: &Arc<AppState>,
+    multisig_id: &str,
+    tx_id: &str,
+) -> Result<ExecutePreview, MultisigServiceError> {
+    let (multisig, tx) = approved_transaction(state, multisig_id, tx_id).await?;
+
+    Ok(ExecutePreview {
+        dry_run: true,
+        multisig_address: multisig.address,
+        chain: multisig.chain,
+        threshold: multisig.threshold,
+        transaction: tx.into(),
+    })
+}
+
 /// Get pending transactions for a multi-sig
 pub async fn get_pending_transactions(
     state: &Arc<AppState>,
@@ -353,3 +587,260 @@ pub async fn get_pending_transactions(
         .map(MultisigTransactionResponse::from)
         .collect())
 }
+
+/// Format of an exported Solana payload: base64 of an unsigned transaction
+pub const SOLANA_PAYLOAD_FORMAT: &str = "solana-transaction";
+/// Format of an exported Ethereum payload: Safe EIP-712 typed data
+pub const SAFE_PAYLOAD_FORMAT: &str = "safe-eip712";
+
+/// Portable payload for owners to sign offline
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SigningPayload {
+    pub transaction_id: String,
+    pub multisig_address: String,
+    pub chain: String,
+    /// `solana-transaction` or `safe-eip712`
+    pub format: String,
+    /// Base64 unsigned transaction (Solana) or typed data for
+    /// `eth_signTypedData_v4` (Ethereum)
+    pub payload: serde_json::Value,
+    /// Hash the owners sign (Ethereum only)
+    pub safe_tx_hash: Option<String>,
+    /// Verified signatures collected so far
+    #[serde(default)]
+    pub signatures: Vec<MultisigSignatureResponse>,
+}
+
+/// Export a proposed transaction for offline signing. The payload is fixed on
+/// first export so later exports hand out the same bytes.
+pub async fn export_signing_payload(
+    state: &Arc<AppState>,
+    multisig_id: &str,
+    tx_id: &str,
+) -> Result<SigningPayload, MultisigServiceError> {
+    let (multisig, tx) = get_multisig_tx(state, multisig_id, tx_id).await?;
+
+    let mut payload = match &tx.signing_payload {
+        Some(stored) => serde_json::from_str(stored)
+            .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?,
+        None => {
+            if tx.status == "executed" || tx.status == "cancelled" {
+                return Err(MultisigServiceError::TransactionClosed(tx.status));
+            }
+            let payload = build_signing_payload(state, &multisig, &tx).await?;
+            let stored = serde_json::to_string(&payload)
+                .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?;
+            state
+                .db
+                .set_multisig_tx_payload(tx_id, &stored)
+                .await
+                .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?;
+            payload
+        }
+    };
+
+    payload.signatures = state
+        .db
+        .get_multisig_signatures(tx_id)
+        .await
+        .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .map(MultisigSignatureResponse::from)
+        .collect();
+
+    Ok(payload)
+}
+
+/// Verify an owner's offline signature over the exported payload and count
+/// it as that owner's approval
+pub async fn submit_signature(
+    state: &Arc<AppState>,
+    multisig_id: &str,
+    tx_id: &str,
+    signature: &str,
+) -> Result<MultisigTransactionResponse, MultisigServiceError> {
+    let payload = export_signing_payload(state, multisig_id, tx_id).await?;
+    let (multisig, tx) = get_multisig_tx(state, multisig_id, tx_id).await?;
+    if tx.status == "executed" || tx.status == "cancelled" {
+        return Err(MultisigServiceError::TransactionClosed(tx.status));
+    }
+
+    let signer = verify_payload_signature(&payload, signature)?;
+    let owners = state
+        .db
+        .get_multisig_owners(&multisig.id)
+        .await
+        .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?;
+    let owner = owners
+        .into_iter()
+        .find(|o| o.owner_address.eq_ignore_ascii_case(&signer))
+        .ok_or(MultisigServiceError::NotAnOwner(signer))?;
+
+    let row = MultisigSignatureRow::new(
+        tx_id.to_string(),
+        owner.owner_address.clone(),
+        signature.trim().to_string(),
+    );
+    state
+        .db
+        .create_multisig_signature(&row)
+        .await
+        .map_err(|e| match e {
+            DatabaseError::AlreadyExists => MultisigServiceError::AlreadyApproved,
+            _ => MultisigServiceError::DatabaseError(e.to_string()),
+        })?;
+    tracing::info!(
+        multisig_id = %multisig_id,
+        tx_id = %tx_id,
+        owner = %owner.owner_address,
+        "Multi-sig signature accepted"
+    );
+
+    match approve_transaction(state, multisig_id, tx_id, &owner.owner_address).await {
+        Ok(tx) => Ok(tx),
+        // Approved earlier without a signature; the signature is still recorded
+        Err(MultisigServiceError::AlreadyApproved) => {
+            let (_, tx) = get_multisig_tx(state, multisig_id, tx_id).await?;
+            Ok(MultisigTransactionResponse::from(tx))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn get_multisig_tx(
+    state: &Arc<AppState>,
+    multisig_id: &str,
+    tx_id: &str,
+) -> Result<(MultisigWalletRow, MultisigTransactionRow), MultisigServiceError> {
+    let multisig = state
+        .db
+        .get_multisig(multisig_id)
+        .await
+        .map_err(|_| MultisigServiceError::NotFound)?;
+    let tx = state
+        .db
+        .get_multisig_tx(tx_id)
+        .await
+        .ok()
+        .filter(|tx| tx.multisig_id == multisig.id)
+        .ok_or(MultisigServiceError::TransactionNotFound)?;
+
+    Ok((multisig, tx))
+}
+
+async fn build_signing_payload(
+    state: &Arc<AppState>,
+    multisig: &MultisigWalletRow,
+    tx: &MultisigTransactionRow,
+) -> Result<SigningPayload, MultisigServiceError> {
+    let chain: Chain = multisig
+        .chain
+        .parse()
+        .map_err(|_| MultisigServiceError::InvalidChain(multisig.chain.clone()))?;
+    let amount = tx.amount.as_deref().unwrap_or("0");
+
+    let (format, payload, safe_tx_hash) = match chain {
+        Chain::Solana => {
+            let owners: Vec<String> = state
+                .db
+                .get_multisig_owners(&multisig.id)
+                .await
+                .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?
+                .into_iter()
+                .map(|o| o.owner_address)
+                .collect();
+            let lamports = solana_sdk::native_token::sol_str_to_lamports(amount)
+                .ok_or_else(|| MultisigServiceError::InvalidTransaction(format!("amount {}", amount)))?;
+
+            let transaction = solana::build_transfer_for_signing(
+                &multisig.address,
+                &owners,
+                &tx.to_address,
+                lamports,
+                &tx.id,
+            )
+            .map_err(|e| MultisigServiceError::InvalidTransaction(e.to_string()))?;
+            let bytes = bincode::serialize(&transaction)
+                .map_err(|e| MultisigServiceError::InvalidTransaction(e.to_string()))?;
+
+            (SOLANA_PAYLOAD_FORMAT, serde_json::json!(STANDARD.encode(bytes)), None)
+        }
+        Chain::Ethereum => {
+            let value = ethers::utils::parse_ether(amount)
+                .map_err(|_| MultisigServiceError::InvalidTransaction(format!("amount {}", amount)))?;
+            let data = match tx.data.as_deref() {
+                Some(data) => hex::decode(data.trim_start_matches("0x"))
+                    .map_err(|e| MultisigServiceError::InvalidTransaction(e.to_string()))?,
+                None => Vec::new(),
+            };
+            // Safe nonces advance with each executed transaction
+            let nonce = state
+                .db
+                .get_multisig_transactions(&multisig.id)
+                .await
+                .map_err(|e| MultisigServiceError::DatabaseError(e.to_string()))?
+                .iter()
+                .filter(|t| t.status == "executed")
+                .count() as u64;
+
+            let typed_data = ethereum::safe_typed_data(
+                &multisig.address,
+                &tx.to_address,
+                &value.to_string(),
+                &data,
+                nonce,
+                state.config.eth_chain_id,
+            )
+            .map_err(|e| MultisigServiceError::InvalidTransaction(e.to_string()))?;
+            let hash = ethereum::safe_tx_hash(&typed_data)
+                .map_err(|e| MultisigServiceError::InvalidTransaction(e.to_string()))?;
+            let payload = serde_json::to_value(&typed_data)
+                .map_err(|e| MultisigServiceError::InvalidTransaction(e.to_string()))?;
+
+            (SAFE_PAYLOAD_FORMAT, payload, Some(format!("0x{}", hex::encode(hash))))
+        }
+    };
+
+    Ok(SigningPayload {
+        transaction_id: tx.id.clone(),
+        multisig_address: multisig.address.clone(),
+        chain: chain.to_string(),
+        format: format.to_string(),
+        payload,
+        safe_tx_hash,
+        signatures: Vec::new(),
+    })
+}
+
+/// Address of the signer of `signature` over the exported payload
+fn verify_payload_signature(
+    payload: &SigningPayload,
+    signature: &str,
+) -> Result<String, MultisigServiceError> {
+    let invalid_payload = |e: String| MultisigServiceError::DatabaseError(format!("stored payload: {}", e));
+
+    match payload.format.as_str() {
+        SOLANA_PAYLOAD_FORMAT => {
+            let bytes = payload
+                .payload
+                .as_str()
+                .and_then(|encoded| STANDARD.decode(encoded).ok())
+                .ok_or_else(|| invalid_payload("not base64".to_string()))?;
+            let transaction: solana_sdk::transaction::Transaction = bincode::deserialize(&bytes)
+                .map_err(|e| invalid_payload(e.to_string()))?;
+
+            solana::verify_owner_signature(&transaction, signature)
+                .map_err(|e| MultisigServiceError::InvalidSignature(e.to_string()))
+        }
+        SAFE_PAYLOAD_FORMAT => {
+            let typed_data = serde_json::from_value(payload.payload.clone())
+                .map_err(|e| invalid_payload(e.to_string()))?;
+            let hash = ethereum::safe_tx_hash(&typed_data)
+                .map_err(|e| invalid_payload(e.to_string()))?;
+
+            ethereum::recover_safe_signer(hash, signature)
+                .map_err(|e| MultisigServiceError::InvalidSignature(e.to_string()))
+        }
+        other => Err(invalid_payload(format!("unknown format {}", other))),
+    }
+}
@@ -0,0 +1,292 @@
+//! Name service - registering ENS / SNS names for wallet accounts
+//!
+//! ENS registers in two steps: the first call sends a commitment and stores
+//! the secret it hides, and calling again once the registrar's minimum
+//! commitment age has passed registers the name with that secret. SNS names
+//! are bought in one transaction built by the registration proxy. Names
+//! registered here are tracked in `owned_names` so they can be listed and
+//! pointed elsewhere later.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::{ChainClientError, NameQuote, NameRegistration};
+use crate::core::Chain;
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, OwnedNameResponse, OwnedNameRow};
+use crate::AppState;
+
+/// ENS rejects shorter labels
+const ENS_MIN_LABEL_LEN: usize = 3;
+
+/// Longest registration period accepted
+const MAX_YEARS: u32 = 10;
+
+#[derive(Debug, Error)]
+pub enum NameServiceError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+    #[error("Registration period must be between 1 and {MAX_YEARS} years")]
+    InvalidYears,
+    #[error("Not found")]
+    NotFound,
+    #[error("{0} is already taken")]
+    NameTaken(String),
+    #[error("{0} is already registered")]
+    AlreadyRegistered(String),
+    #[error("Commitment can be registered from {0}")]
+    NotReady(String),
+    #[error("{0} is not registered yet")]
+    NotRegistered(String),
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Register name request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterNameRequest {
+    pub account_id: String,
+    pub name: String,
+    /// Registration period for names that expire (ENS); defaults to 1
+    pub years: Option<u32>,
+}
+
+/// Availability, price and renewal cost of a name
+pub async fn quote(
+    state: &Arc<AppState>,
+    chain: &str,
+    name: &str,
+    years: Option<u32>,
+) -> Result<NameQuote, NameServiceError> {
+    let chain: Chain = chain
+        .parse()
+        .map_err(|_| NameServiceError::InvalidChain(chain.to_string()))?;
+    let name = normalize(chain, name)?;
+    let years = check_years(years)?;
+
+    Ok(state.chain_clients().get(chain).name_quote(&name, years).await?)
+}
+
+/// Register a name to an account, or take the next step of an ENS
+/// registration already committed to
+pub async fn register(
+    state: &Arc<AppState>,
+    request: RegisterNameRequest,
+) -> Result<OwnedNameResponse, NameServiceError> {
+    let account = tenant_account(state, &request.account_id).await?;
+    let chain: Chain = account
+        .chain
+        .parse()
+        .map_err(|_| NameServiceError::InvalidChain(account.chain.clone()))?;
+    let name = normalize(chain, &request.name)?;
+
+    let existing = state.db.get_owned_name_by_name(&chain.to_string(), &name).await?;
+    let mut row = match existing {
+        Some(row) if row.account_id != account.id => {
+            return Err(NameServiceError::NameTaken(name));
+        }
+        Some(row) if row.status == "registered" => {
+            return Err(NameServiceError::AlreadyRegistered(name));
+        }
+        Some(row) => row,
+        None => OwnedNameRow::new(
+            account.id.clone(),
+            chain.to_string(),
+            name.clone(),
+            "committed".to_string(),
+        ),
+    };
+
+    // Continuing a commitment keeps the period it was made for
+    if let Some(ready_at) = row.ready_at.as_deref() {
+        let ready = DateTime::parse_from_rfc3339(ready_at)
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now());
+        if ready > Utc::now() {
+            return Err(NameServiceError::NotReady(ready_at.to_string()));
+        }
+    }
+    let years = match row.years {
+        Some(years) if row.commitment_secret.is_some() => years as u32,
+        _ => check_years(request.years)?,
+    };
+
+    let seed = get_seed(state).await?;
+    let registration = state
+        .chain_clients()
+        .get(chain)
+        .register_name(
+            &seed,
+            &account.derivation_path,
+            &name,
+            years,
+            row.commitment_secret.as_deref(),
+        )
+        .await?;
+
+    row.years = Some(years as i64);
+    row.updated_at = Utc::now().to_rfc3339();
+    match registration {
+        NameRegistration::Committed {
+            tx_hash,
+            secret,
+            ready_at,
+        } => {
+            row.status = "committed".to_string();
+            row.commitment_secret = Some(secret);
+            row.ready_at = Some(timestamp(ready_at));
+            row.last_tx_hash = Some(tx_hash);
+        }
+        NameRegistration::Registered {
+            tx_hash,
+            expires_at,
+        } => {
+            tracing::info!(name = %name, account_id = %account.id, "Name registered");
+            row.status = "registered".to_string();
+            row.target_address = Some(account.address.clone());
+            row.commitment_secret = None;
+            row.ready_at = None;
+            row.expires_at = expires_at.map(timestamp);
+            row.last_tx_hash = Some(tx_hash);
+        }
+    }
+    state.db.save_owned_name(&row).await?;
+
+    Ok(row.into())
+}
+
+/// Point a registered name at another address. On SNS this transfers the
+/// domain to it.
+pub async fn set_target(
+    state: &Arc<AppState>,
+    chain: &str,
+    name: &str,
+    target: &str,
+) -> Result<OwnedNameResponse, NameServiceError> {
+    let chain: Chain = chain
+        .parse()
+        .map_err(|_| NameServiceError::InvalidChain(chain.to_string()))?;
+    let name = normalize(chain, name)?;
+    let mut row = state
+        .db
+        .get_owned_name_by_name(&chain.to_string(), &name)
+        .await?
+        .ok_or(NameServiceError::NotFound)?;
+    let account = tenant_account(state, &row.account_id).await?;
+    if row.status != "registered" {
+        return Err(NameServiceError::NotRegistered(name));
+    }
+
+    let seed = get_seed(state).await?;
+    let tx_hash = state
+        .chain_clients()
+        .get(chain)
+        .set_name_target(&seed, &account.derivation_path, &name, target)
+        .await?;
+    tracing::info!(name = %name, target = %target, "Name target updated");
+
+    row.target_address = Some(target.to_string());
+    row.last_tx_hash = Some(tx_hash);
+    row.updated_at = Utc::now().to_rfc3339();
+    state.db.save_owned_name(&row).await?;
+
+    Ok(row.into())
+}
+
+/// The current tenant's names, registered and committed to
+pub async fn list(state: &Arc<AppState>) -> Result<Vec<OwnedNameResponse>, NameServiceError> {
+    let rows = state.db.get_owned_names(&current_tenant_id()).await?;
+    Ok(rows.into_iter().map(OwnedNameResponse::from).collect())
+}
+
+/// An account of the current tenant; others are reported as not found
+async fn tenant_account(
+    state: &Arc<AppState>,
+    account_id: &str,
+) -> Result<AccountRow, NameServiceError> {
+    let account = match state.db.get_account(account_id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(NameServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(NameServiceError::NotFound);
+    }
+    Ok(account)
+}
+
+/// Lowercase a name and check it is a single label under the chain's TLD
+fn normalize(chain: Chain, name: &str) -> Result<String, NameServiceError> {
+    let name = name.trim().to_lowercase();
+    let tld = match chain {
+        Chain::Ethereum => ".eth",
+        Chain::Solana => ".sol",
+    };
+    let label = name
+        .strip_suffix(tld)
+        .ok_or_else(|| NameServiceError::InvalidName(format!("{} names end in {}", chain, tld)))?;
+
+    let valid_chars = label
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if label.is_empty() || !valid_chars || label.starts_with('-') || label.ends_with('-') {
+        return Err(NameServiceError::InvalidName(name));
+    }
+    if chain == Chain::Ethereum && label.len() < ENS_MIN_LABEL_LEN {
+        return Err(NameServiceError::InvalidName(format!(
+            "ENS names are at least {} characters",
+            ENS_MIN_LABEL_LEN
+        )));
+    }
+    Ok(name)
+}
+
+fn check_years(years: Option<u32>) -> Result<u32, NameServiceError> {
+    match years.unwrap_or(1) {
+        years @ 1..=MAX_YEARS => Ok(years),
+        _ => Err(NameServiceError::InvalidYears),
+    }
+}
+
+fn timestamp(unix: i64) -> String {
+    DateTime::from_timestamp(unix, 0)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(Chain::Ethereum, " Alice.ETH ").unwrap(), "alice.eth");
+        assert_eq!(normalize(Chain::Solana, "bonfida.sol").unwrap(), "bonfida.sol");
+        assert_eq!(normalize(Chain::Solana, "a.sol").unwrap(), "a.sol");
+
+        for (chain, name) in [
+            (Chain::Ethereum, "alice.sol"),
+            (Chain::Ethereum, "ab.eth"),
+            (Chain::Ethereum, "sub.alice.eth"),
+            (Chain::Solana, "-alice.sol"),
+            (Chain::Solana, ".sol"),
+        ] {
+            assert!(matches!(
+                normalize(chain, name),
+                Err(NameServiceError::InvalidName(_))
+            ));
+        }
+    }
+}
@@ -2,17 +2,34 @@
 
 use std::sync::Arc;
 
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
 use thiserror::Error;
+use tokio::task::JoinSet;
 
+use crate::api::middleware::tenant::current_tenant_id;
 use crate::chains::ethereum::get_nft_details;
 use crate::chains::solana::get_nfts_for_owner_async;
-use crate::storage::models::{NftCacheRow, NftResponse};
+use crate::chains::{
+    ChainClientError, NftCollectionKey, NftHolder, NftMetadata, NftRoyalties,
+};
+use crate::core::Chain;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, NftCacheRow, NftResponse, TransactionRow};
 use crate::AppState;
 
+/// Cached NFTs older than this have their holder re-checked on chain
+const OWNERSHIP_TTL_SECS: i64 = 300;
+
+/// Metadata documents fetched at once by a refresh
+const METADATA_REFRESH_CONCURRENCY: usize = 8;
+
 #[derive(Debug, Error)]
 pub enum NftServiceError {
     #[error("Invalid chain: {0}")]
     InvalidChain(String),
+    #[error("Account not found")]
+    NotFound,
     #[error("NFT fetch failed: {0}")]
     FetchFailed(String),
     #[error("Database error: {0}")]
@@ -28,7 +45,7 @@ pub async fn get_nfts(
     // First check cache
     let account = state
         .db
-        .get_account_by_address(chain, address)
+        .get_account_by_address(&current_tenant_id(), chain, address)
         .await
         .ok();
 
@@ -38,6 +55,7 @@ pub async fn get_nfts(
             .get_nfts(&acc.id)
             .await
             .map_err(|e| NftServiceError::DatabaseError(e.to_string()))?;
+        let cached = reconcile_ownership(state, acc, cached).await?;
 
         if !cached.is_empty() {
             return Ok(cached.into_iter().map(NftResponse::from).collect());
@@ -63,13 +81,17 @@ pub async fn get_nfts(
                     image_url: nft.image_url,
                     collection_name: nft.collection.map(|c| c.name),
                     metadata: None,
+                    listed: false,
+                    escrow_address: None,
+                    royalties: Some(nft.royalties),
+                    collection: nft.collection_key,
                 })
                 .collect();
 
             // Cache NFTs if we have an account
             if let Some(acc) = account {
                 for nft in &responses {
-                    let cache_row = NftCacheRow::new(
+                    let mut cache_row = NftCacheRow::new(
                         acc.id.clone(),
                         "solana".to_string(),
                         nft.token_address.clone(),
@@ -80,6 +102,7 @@ pub async fn get_nfts(
                         None,
                         nft.collection_name.clone(),
                     );
+                    set_on_chain(&mut cache_row, nft.royalties.as_ref(), nft.collection.as_ref());
                     let _ = state.db.upsert_nft(&cache_row).await;
                 }
             }
@@ -107,6 +130,297 @@ pub async fn get_nfts(
     }
 }
 
+/// Re-check who holds each stale cache entry. NFTs that were transferred out
+/// or burned leave the cache, listed ones are marked with their escrow, and
+/// each change is recorded in the account's transaction history. Entries
+/// whose holder can't be fetched are kept as they are.
+async fn reconcile_ownership(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    cached: Vec<NftCacheRow>,
+) -> Result<Vec<NftCacheRow>, NftServiceError> {
+    let stale_before = Utc::now() - Duration::seconds(OWNERSHIP_TTL_SECS);
+    let mut kept = Vec::with_capacity(cached.len());
+
+    for mut nft in cached {
+        let stale = DateTime::parse_from_rfc3339(&nft.last_updated)
+            .map(|t| t < stale_before)
+            .unwrap_or(true);
+        let chain: Option<Chain> = nft.chain.parse().ok();
+        let (true, Some(chain)) = (stale, chain) else {
+            kept.push(nft);
+            continue;
+        };
+
+        let holder = match state
+            .chain_clients()
+            .get(chain)
+            .nft_holder(&nft.token_address, &nft.token_id)
+            .await
+        {
+            Ok(holder) => holder,
+            Err(e) => {
+                tracing::warn!(
+                    mint = %nft.token_address,
+                    token_id = %nft.token_id,
+                    "NFT holder lookup failed: {}",
+                    e
+                );
+                kept.push(nft);
+                continue;
+            }
+        };
+
+        match holder {
+            NftHolder::Address(owner) if owner.eq_ignore_ascii_case(&account.address) => {
+                set_escrow(state, &mut nft, None).await?;
+                kept.push(nft);
+            }
+            NftHolder::Escrow(escrow) => {
+                if nft.escrow_address.as_deref() != Some(escrow.as_str()) {
+                    record_nft_event(state, account, &nft, "nft_listing", Some(&escrow)).await?;
+                }
+                set_escrow(state, &mut nft, Some(escrow)).await?;
+                kept.push(nft);
+            }
+            NftHolder::Address(owner) => {
+                record_nft_event(state, account, &nft, "nft_transfer", Some(&owner)).await?;
+                delete_cached(state, &nft).await?;
+            }
+            NftHolder::Burned => {
+                record_nft_event(state, account, &nft, "nft_burn", None).await?;
+                delete_cached(state, &nft).await?;
+            }
+        }
+    }
+
+    Ok(kept)
+}
+
+/// Update the escrow of a cached NFT, which also marks it as fresh
+async fn set_escrow(
+    state: &Arc<AppState>,
+    nft: &mut NftCacheRow,
+    escrow: Option<String>,
+) -> Result<(), NftServiceError> {
+    state
+        .db
+        .set_nft_escrow(&nft.id, escrow.as_deref())
+        .await
+        .map_err(|e| NftServiceError::DatabaseError(e.to_string()))?;
+    nft.escrow_address = escrow;
+    Ok(())
+}
+
+async fn delete_cached(state: &Arc<AppState>, nft: &NftCacheRow) -> Result<(), NftServiceError> {
+    state
+        .db
+        .delete_nft(&nft.account_id, &nft.chain, &nft.token_address, &nft.token_id)
+        .await
+        .map_err(|e| NftServiceError::DatabaseError(e.to_string()))
+}
+
+/// Add a detected ownership change to history. It has no transaction of its
+/// own to point at, so the signature is a synthetic `detected:` ID.
+async fn record_nft_event(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    nft: &NftCacheRow,
+    tx_type: &str,
+    to_address: Option<&str>,
+) -> Result<(), NftServiceError> {
+    let mut row = TransactionRow::new(
+        account.id.clone(),
+        nft.chain.clone(),
+        format!("detected:{}", uuid::Uuid::new_v4()),
+        tx_type.to_string(),
+        Some(account.address.clone()),
+        to_address.map(str::to_string),
+        Some("1".to_string()),
+        Some(nft.token_address.clone()),
+        "confirmed".to_string(),
+        None,
+        Some(Utc::now().to_rfc3339()),
+    );
+    row.token_id = Some(nft.token_id.clone());
+
+    state
+        .db
+        .upsert_transaction(&row)
+        .await
+        .map_err(|e| NftServiceError::DatabaseError(e.to_string()))
+}
+
+/// Outcome of re-fetching an account's NFT metadata
+#[derive(Debug, Serialize)]
+pub struct NftRefreshSummary {
+    /// Cached NFTs whose metadata was fetched
+    pub refreshed: usize,
+    pub changed: Vec<NftMetadataChange>,
+    /// NFTs whose metadata couldn't be fetched; their cache is left as it was
+    pub failed: Vec<NftRefreshFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NftMetadataChange {
+    /// Changed fields: `uri` (a reveal), `name`, `description`,
+    /// `image_url`, `collection_name`, `metadata`, `royalties` and
+    /// `collection`
+    pub fields: Vec<&'static str>,
+    pub nft: NftResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NftRefreshFailure {
+    pub token_address: String,
+    pub token_id: String,
+    pub error: String,
+}
+
+/// Re-fetch the metadata of every cached NFT of one of the tenant's
+/// accounts, following each token's current metadata URI
+pub async fn refresh_metadata(
+    state: &Arc<AppState>,
+    chain: &str,
+    address: &str,
+) -> Result<NftRefreshSummary, NftServiceError> {
+    let chain: Chain = chain
+        .parse()
+        .map_err(|_| NftServiceError::InvalidChain(chain.to_string()))?;
+    let db_error = |e: DatabaseError| NftServiceError::DatabaseError(e.to_string());
+    let account = match state
+        .db
+        .get_account_by_address(&current_tenant_id(), &chain.to_string(), address)
+        .await
+    {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(NftServiceError::NotFound),
+        Err(e) => return Err(db_error(e)),
+    };
+
+    // Spawned fetches don't see the tenant, so they get its clients
+    let clients = state.chain_clients();
+    let mut fetches = JoinSet::new();
+    let mut fetched = Vec::new();
+    for nft in state.db.get_nfts(&account.id).await.map_err(db_error)? {
+        if fetches.len() >= METADATA_REFRESH_CONCURRENCY {
+            fetched.extend(fetches.join_next().await);
+        }
+        let clients = clients.clone();
+        fetches.spawn(async move {
+            let metadata = clients
+                .get(chain)
+                .nft_metadata(&nft.token_address, &nft.token_id)
+                .await;
+            (nft, metadata)
+        });
+    }
+    while let Some(result) = fetches.join_next().await {
+        fetched.push(result);
+    }
+
+    let mut summary = NftRefreshSummary {
+        refreshed: 0,
+        changed: Vec::new(),
+        failed: Vec::new(),
+    };
+    for result in fetched {
+        let (mut nft, metadata) =
+            result.map_err(|e| NftServiceError::FetchFailed(e.to_string()))?;
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                summary.failed.push(refresh_failure(&nft, e));
+                continue;
+            }
+        };
+
+        summary.refreshed += 1;
+        let fields = apply_metadata(&mut nft, metadata);
+        if !fields.is_empty() {
+            state.db.update_nft_metadata(&nft).await.map_err(db_error)?;
+            summary.changed.push(NftMetadataChange {
+                fields,
+                nft: NftResponse::from(nft),
+            });
+        }
+    }
+
+    Ok(summary)
+}
+
+fn refresh_failure(nft: &NftCacheRow, e: ChainClientError) -> NftRefreshFailure {
+    tracing::warn!(
+        mint = %nft.token_address,
+        token_id = %nft.token_id,
+        "NFT metadata refresh failed: {}",
+        e
+    );
+    NftRefreshFailure {
+        token_address: nft.token_address.clone(),
+        token_id: nft.token_id.clone(),
+        error: e.to_string(),
+    }
+}
+
+/// Update a cache row from freshly fetched metadata, returning the fields
+/// that changed. Collection names are kept when the document has none, as
+/// most ERC-721 documents don't, and royalties and the on-chain collection
+/// when the chain doesn't record them.
+fn apply_metadata(nft: &mut NftCacheRow, metadata: NftMetadata) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    // The URI is only known once an NFT has been refreshed
+    match nft.metadata_uri.replace(metadata.uri.clone()) {
+        Some(previous) if previous != metadata.uri => fields.push("uri"),
+        _ => {}
+    }
+    let mut set = |field: &mut Option<String>, value: Option<String>, name: &'static str| {
+        if *field != value {
+            *field = value;
+            fields.push(name);
+        }
+    };
+
+    set(&mut nft.name, metadata.name, "name");
+    set(&mut nft.description, metadata.description, "description");
+    set(&mut nft.image_url, metadata.image_url, "image_url");
+    let collection_name = metadata.collection_name.or_else(|| nft.collection_name.clone());
+    set(&mut nft.collection_name, collection_name, "collection_name");
+
+    let cached: Option<serde_json::Value> =
+        nft.metadata_json.as_deref().and_then(|json| serde_json::from_str(json).ok());
+    if cached != metadata.document {
+        nft.metadata_json = metadata.document.map(|document| document.to_string());
+        fields.push("metadata");
+    }
+
+    let (royalties, collection) = (nft.royalties(), nft.collection());
+    set_on_chain(nft, metadata.royalties.as_ref(), metadata.collection.as_ref());
+    if nft.royalties() != royalties {
+        fields.push("royalties");
+    }
+    if nft.collection() != collection {
+        fields.push("collection");
+    }
+    fields
+}
+
+/// Record on-chain royalties and collection, keeping what's cached when
+/// there are none to record
+fn set_on_chain(
+    nft: &mut NftCacheRow,
+    royalties: Option<&NftRoyalties>,
+    collection: Option<&NftCollectionKey>,
+) {
+    if let Some(royalties) = royalties {
+        nft.royalties_json = serde_json::to_string(royalties).ok();
+    }
+    if let Some(collection) = collection {
+        nft.collection_address = Some(collection.address.clone());
+        nft.collection_verified = collection.verified;
+    }
+}
+
 /// Get single NFT details
 pub async fn get_nft_detail(
     state: &Arc<AppState>,
@@ -143,8 +457,81 @@ pub async fn get_nft_detail(
                 image_url: nft.image_url,
                 collection_name: nft.collection_name,
                 metadata: None,
+                listed: false,
+                escrow_address: None,
+                royalties: None,
+                collection: None,
             })
         }
         _ => Err(NftServiceError::InvalidChain(chain.to_string())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_metadata() {
+        let mut nft = NftCacheRow::new(
+            "account".to_string(),
+            "ethereum".to_string(),
+            "0xcontract".to_string(),
+            "7".to_string(),
+            Some("Egg".to_string()),
+            None,
+            Some("egg.png".to_string()),
+            None,
+            Some("Dragons".to_string()),
+        );
+        let document = serde_json::json!({ "name": "Egg", "image": "egg.png" });
+        let hidden = NftMetadata::from_document("ipfs://hidden".to_string(), Some(document));
+
+        // A first refresh learns the URI without calling it a change
+        assert_eq!(apply_metadata(&mut nft, hidden.clone()), vec!["metadata"]);
+        assert!(apply_metadata(&mut nft, hidden).is_empty());
+
+        let document = serde_json::json!({ "name": "Dragon #7", "image": "dragon.png" });
+        let revealed = NftMetadata::from_document("ipfs://revealed/7".to_string(), Some(document));
+        assert_eq!(
+            apply_metadata(&mut nft, revealed),
+            vec!["uri", "name", "image_url", "metadata"]
+        );
+        assert_eq!(nft.collection_name.as_deref(), Some("Dragons"));
+    }
+
+    #[test]
+    fn test_apply_on_chain_metadata() {
+        let mut nft = NftCacheRow::new(
+            "account".to_string(),
+            "solana".to_string(),
+            "mint".to_string(),
+            "1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        let mut metadata = NftMetadata::from_document("ipfs://ape".to_string(), None);
+        metadata.royalties = Some(NftRoyalties {
+            seller_fee_basis_points: 500,
+            creators: Vec::new(),
+        });
+        metadata.collection = Some(NftCollectionKey {
+            address: "collection".to_string(),
+            verified: false,
+        });
+        assert_eq!(apply_metadata(&mut nft, metadata.clone()), vec!["royalties", "collection"]);
+
+        // Verification by the collection authority is a change
+        metadata.collection.as_mut().unwrap().verified = true;
+        assert_eq!(apply_metadata(&mut nft, metadata), vec!["collection"]);
+        assert!(NftResponse::from(nft.clone()).collection.unwrap().verified);
+
+        // Metadata without on-chain terms leaves the cached ones alone
+        let document = NftMetadata::from_document("ipfs://ape".to_string(), None);
+        assert!(apply_metadata(&mut nft, document).is_empty());
+        assert_eq!(nft.royalties().unwrap().seller_fee_basis_points, 500);
+    }
+}
@@ -0,0 +1,239 @@
+//! Note service - private notes on accounts, contacts and transactions
+//!
+//! Note bodies are sealed with AES-256-GCM under a key derived from the
+//! wallet's seed, which is only in memory while the wallet is unlocked with
+//! its password. Every read and write therefore needs the wallet unlocked,
+//! and search runs over the bodies opened for the request.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{NoteResponse, NoteRow, WalletRow};
+use crate::storage::FieldCipher;
+use crate::AppState;
+
+/// Binds the notes key to this use of the seed
+const NOTES_KEY_INFO: &[u8] = b"wallet-backend notes v1";
+/// Longest note body, in characters
+const MAX_BODY_CHARS: usize = 10_000;
+
+#[derive(Debug, Error)]
+pub enum NoteError {
+    #[error("Invalid target: {0}")]
+    InvalidTarget(String),
+    #[error("Invalid note: {0}")]
+    InvalidNote(String),
+    #[error("Note not found")]
+    NotFound,
+    #[error("Note could not be decrypted with this wallet")]
+    Undecryptable,
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for NoteError {
+    fn from(e: DatabaseError) -> Self {
+        match e {
+            DatabaseError::NotFound => NoteError::NotFound,
+            _ => NoteError::DatabaseError(e.to_string()),
+        }
+    }
+}
+
+/// Create note request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateNoteRequest {
+    /// `account`, `contact` or `transaction`
+    pub target_type: String,
+    pub target_id: String,
+    pub body: String,
+}
+
+/// Update note request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateNoteRequest {
+    pub body: String,
+}
+
+/// Note list query; filters combine
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteQuery {
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    /// Case-insensitive text to look for in note bodies
+    pub q: Option<String>,
+}
+
+/// The user's notes in the current wallet, most recently updated first
+pub async fn list_notes(
+    state: &Arc<AppState>,
+    user_id: &str,
+    query: NoteQuery,
+) -> Result<Vec<NoteResponse>, NoteError> {
+    let cipher = notes_cipher(state).await?;
+    let wallet = current_wallet(state).await?;
+    let target = match (&query.target_type, &query.target_id) {
+        (Some(target_type), Some(target_id)) => Some((target_type.as_str(), target_id.as_str())),
+        (None, None) => None,
+        _ => {
+            return Err(NoteError::InvalidTarget(
+                "target_type and target_id go together".to_string(),
+            ))
+        }
+    };
+    let needle = query
+        .q
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty());
+
+    let mut notes = Vec::new();
+    for row in state.db.get_notes(user_id, &wallet.id, target).await? {
+        let note = open(&cipher, row)?;
+        if needle.as_ref().is_some_and(|n| !note.body.to_lowercase().contains(n)) {
+            continue;
+        }
+        notes.push(note);
+    }
+    Ok(notes)
+}
+
+/// Attach a note to an account, contact or transaction of the current wallet
+pub async fn create_note(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: CreateNoteRequest,
+) -> Result<NoteResponse, NoteError> {
+    let cipher = notes_cipher(state).await?;
+    let wallet = current_wallet(state).await?;
+    check_body(&request.body)?;
+    check_target(state, &wallet, &request.target_type, &request.target_id).await?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let row = NoteRow {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        wallet_id: wallet.id,
+        target_type: request.target_type,
+        target_id: request.target_id,
+        body: cipher.encrypt(&request.body),
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    state.db.create_note(&row).await?;
+    open(&cipher, row)
+}
+
+/// One of the user's notes
+pub async fn get_note(state: &Arc<AppState>, user_id: &str, id: &str) -> Result<NoteResponse, NoteError> {
+    let cipher = notes_cipher(state).await?;
+    let row = get_owned_note(state, user_id, id).await?;
+    open(&cipher, row)
+}
+
+/// Replace a note's body
+pub async fn update_note(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: UpdateNoteRequest,
+) -> Result<NoteResponse, NoteError> {
+    let cipher = notes_cipher(state).await?;
+    check_body(&request.body)?;
+    let mut row = get_owned_note(state, user_id, id).await?;
+
+    row.body = cipher.encrypt(&request.body);
+    row.updated_at = chrono::Utc::now().to_rfc3339();
+    state.db.update_note(id, &row.body, &row.updated_at).await?;
+    open(&cipher, row)
+}
+
+/// Delete a note; needs the wallet unlocked like every other note operation
+pub async fn delete_note(state: &Arc<AppState>, user_id: &str, id: &str) -> Result<(), NoteError> {
+    notes_cipher(state).await?;
+    get_owned_note(state, user_id, id).await?;
+    state.db.delete_note(id).await?;
+    Ok(())
+}
+
+/// The unlocked wallet's notes cipher
+async fn notes_cipher(state: &Arc<AppState>) -> Result<FieldCipher, NoteError> {
+    let seed = get_seed(state).await?;
+    Ok(FieldCipher::derive(seed.as_bytes(), NOTES_KEY_INFO))
+}
+
+async fn current_wallet(state: &Arc<AppState>) -> Result<WalletRow, NoteError> {
+    state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await?
+        .ok_or(NoteError::WalletError(WalletServiceError::NoWalletFound))
+}
+
+fn check_body(body: &str) -> Result<(), NoteError> {
+    if body.trim().is_empty() || body.chars().count() > MAX_BODY_CHARS {
+        return Err(NoteError::InvalidNote(format!(
+            "body must be 1-{} characters",
+            MAX_BODY_CHARS
+        )));
+    }
+    Ok(())
+}
+
+/// The target must exist in the current wallet
+async fn check_target(
+    state: &Arc<AppState>,
+    wallet: &WalletRow,
+    target_type: &str,
+    target_id: &str,
+) -> Result<(), NoteError> {
+    let wallet_id = match target_type {
+        "account" => state.db.get_account(target_id).await.map(|a| a.wallet_id),
+        "contact" => state.db.get_contact(target_id).await.map(|c| c.wallet_id),
+        "transaction" => match state.db.get_transaction(target_id).await {
+            Ok(tx) => state.db.get_account(&tx.account_id).await.map(|a| a.wallet_id),
+            Err(e) => Err(e),
+        },
+        _ => {
+            return Err(NoteError::InvalidTarget(format!(
+                "target_type must be account, contact or transaction, not {}",
+                target_type
+            )))
+        }
+    };
+    match wallet_id {
+        Ok(wallet_id) if wallet_id == wallet.id => Ok(()),
+        Ok(_) | Err(DatabaseError::NotFound) => Err(NoteError::InvalidTarget(format!(
+            "no {} {} in this wallet",
+            target_type, target_id
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn get_owned_note(state: &Arc<AppState>, user_id: &str, id: &str) -> Result<NoteRow, NoteError> {
+    let note = state.db.get_note(id).await?;
+    let wallet = current_wallet(state).await?;
+    if note.user_id != user_id || note.wallet_id != wallet.id {
+        return Err(NoteError::NotFound);
+    }
+    Ok(note)
+}
+
+fn open(cipher: &FieldCipher, row: NoteRow) -> Result<NoteResponse, NoteError> {
+    let body = cipher.decrypt(&row.body).map_err(|_| NoteError::Undecryptable)?;
+    Ok(NoteResponse {
+        id: row.id,
+        target_type: row.target_type,
+        target_id: row.target_id,
+        body,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    })
+}
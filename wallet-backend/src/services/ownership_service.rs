@@ -0,0 +1,325 @@
+//! Address ownership proofs
+//!
+//! An account signs a challenge chosen by a third party (an exchange, an
+//! airdrop claim) with its own key. The signed message names the chain, the
+//! address, the challenge and when it was signed, so anyone holding the proof
+//! can check it without trusting this server.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::ethereum::{self, EthereumWallet};
+use crate::chains::solana::{self, SolanaKeypair};
+use crate::core::Chain;
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, AuditEventRow, AuditSeverity};
+use crate::AppState;
+
+/// First line of every proof message
+const HEADER: &str = "Valtix address ownership proof";
+
+/// Longest challenge accepted, in characters
+const MAX_CHALLENGE_LEN: usize = 512;
+
+#[derive(Debug, Error)]
+pub enum OwnershipServiceError {
+    #[error("Account not found")]
+    NotFound,
+    #[error("Invalid challenge: {0}")]
+    InvalidChallenge(String),
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Signing failed: {0}")]
+    Signing(String),
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Challenge to sign, as given by whoever asks for the proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipProofRequest {
+    pub challenge: String,
+}
+
+/// A signed ownership proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipProof {
+    pub chain: String,
+    pub address: String,
+    /// The exact text that was signed
+    pub message: String,
+    /// Hex (Ethereum `personal_sign`) or base58 (Solana `signMessage`)
+    pub signature: String,
+    pub timestamp: String,
+}
+
+/// A proof handed over for checking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyOwnershipRequest {
+    pub chain: String,
+    pub address: String,
+    pub message: String,
+    pub signature: String,
+}
+
+/// Whether a proof holds, with the challenge and time it names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipVerification {
+    pub valid: bool,
+    pub chain: String,
+    pub address: String,
+    pub challenge: Option<String>,
+    pub timestamp: Option<String>,
+    /// Why the proof doesn't hold
+    pub reason: Option<String>,
+}
+
+/// Sign `request.challenge` with the key of one of the wallet's accounts
+pub async fn prove_ownership(
+    state: &Arc<AppState>,
+    user_id: &str,
+    account_id: &str,
+    request: OwnershipProofRequest,
+) -> Result<OwnershipProof, OwnershipServiceError> {
+    let challenge = request.challenge.trim();
+    if challenge.is_empty() {
+        return Err(OwnershipServiceError::InvalidChallenge(
+            "challenge is empty".to_string(),
+        ));
+    }
+    if challenge.chars().count() > MAX_CHALLENGE_LEN {
+        return Err(OwnershipServiceError::InvalidChallenge(format!(
+            "longer than {} characters",
+            MAX_CHALLENGE_LEN
+        )));
+    }
+    // A line break could pass off a forged field as part of the message
+    if challenge.chars().any(char::is_control) {
+        return Err(OwnershipServiceError::InvalidChallenge(
+            "contains control characters".to_string(),
+        ));
+    }
+
+    let account = tenant_account(state, account_id).await?;
+    let chain: Chain = account
+        .chain
+        .parse()
+        .map_err(|_| OwnershipServiceError::InvalidChain(account.chain.clone()))?;
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let message = proof_message(chain, &account.address, challenge, &timestamp);
+
+    let seed = get_seed(state).await?;
+    let signature = match chain {
+        Chain::Solana => SolanaKeypair::derive_path(&seed, &account.derivation_path)
+            .map_err(|e| OwnershipServiceError::Signing(e.to_string()))?
+            .sign(message.as_bytes())
+            .to_string(),
+        Chain::Ethereum => EthereumWallet::derive_path(&seed, &account.derivation_path)
+            .and_then(|wallet| wallet.sign_message(&message))
+            .map_err(|e| OwnershipServiceError::Signing(e.to_string()))?,
+    };
+
+    let audit = AuditEventRow::new(
+        current_tenant_id(),
+        Some(user_id.to_string()),
+        "account.ownership_proved",
+        AuditSeverity::Info,
+        serde_json::json!({
+            "account_id": account.id,
+            "address": account.address,
+            "challenge": challenge,
+        }),
+    );
+    state.db.record_audit_event(&audit).await?;
+
+    Ok(OwnershipProof {
+        chain: chain.to_string(),
+        address: account.address,
+        message,
+        signature,
+        timestamp,
+    })
+}
+
+/// Check a proof from any wallet. A bad signature or a message that isn't a
+/// proof for the address is reported as invalid rather than refused.
+pub fn verify_ownership(
+    request: VerifyOwnershipRequest,
+) -> Result<OwnershipVerification, OwnershipServiceError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| OwnershipServiceError::InvalidChain(request.chain.clone()))?;
+    let address = match chain {
+        Chain::Solana => solana::normalize_address(&request.address)
+            .map_err(|_| OwnershipServiceError::InvalidAddress(request.address.clone()))?,
+        Chain::Ethereum if ethereum::validate_address(request.address.trim()) => {
+            ethereum::checksum_address(request.address.trim())
+        }
+        Chain::Ethereum => {
+            return Err(OwnershipServiceError::InvalidAddress(request.address.clone()))
+        }
+    };
+
+    let fields = parse_proof_message(&request.message);
+    let signed = match chain {
+        Chain::Solana => {
+            solana::verify_message_signature(&address, &request.message, &request.signature)
+                .map_err(|e| e.to_string())
+        }
+        Chain::Ethereum => ethereum::recover_message_signer(&request.message, &request.signature)
+            .map_err(|e| e.to_string())
+            .and_then(|signer| {
+                if signer == address {
+                    Ok(())
+                } else {
+                    Err("not signed by this address".to_string())
+                }
+            }),
+    };
+    let reason = match (&signed, &fields) {
+        (Err(e), _) => Some(e.clone()),
+        (Ok(()), None) => Some("not an ownership proof".to_string()),
+        (Ok(()), Some(fields)) if fields.chain != chain.to_string() => {
+            Some(format!("proof is for {}", fields.chain))
+        }
+        (Ok(()), Some(fields)) if !same_address(chain, &fields.address, &address) => {
+            Some(format!("proof is for {}", fields.address))
+        }
+        (Ok(()), Some(_)) => None,
+    };
+
+    Ok(OwnershipVerification {
+        valid: reason.is_none(),
+        chain: chain.to_string(),
+        address,
+        challenge: fields.as_ref().map(|f| f.challenge.clone()),
+        timestamp: fields.map(|f| f.timestamp),
+        reason,
+    })
+}
+
+/// Fields named by a proof message
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProofFields {
+    chain: String,
+    address: String,
+    challenge: String,
+    timestamp: String,
+}
+
+fn proof_message(chain: Chain, address: &str, challenge: &str, timestamp: &str) -> String {
+    format!(
+        "{}\n\nChain: {}\nAddress: {}\nChallenge: {}\nIssued At: {}",
+        HEADER, chain, address, challenge, timestamp
+    )
+}
+
+fn parse_proof_message(message: &str) -> Option<ProofFields> {
+    let body = message.strip_prefix(HEADER)?.strip_prefix("\n\n")?;
+    let mut lines = body.split('\n');
+    let mut field = |name: &str| {
+        lines
+            .next()
+            .and_then(|line| line.strip_prefix(name))
+            .and_then(|value| value.strip_prefix(": "))
+            .map(str::to_string)
+    };
+    let fields = ProofFields {
+        chain: field("Chain")?,
+        address: field("Address")?,
+        challenge: field("Challenge")?,
+        timestamp: field("Issued At")?,
+    };
+    if lines.next().is_some() || DateTime::parse_from_rfc3339(&fields.timestamp).is_err() {
+        return None;
+    }
+    Some(fields)
+}
+
+/// Ethereum addresses compare without regard to checksum casing
+fn same_address(chain: Chain, a: &str, b: &str) -> bool {
+    match chain {
+        Chain::Solana => a == b,
+        Chain::Ethereum => a.eq_ignore_ascii_case(b),
+    }
+}
+
+async fn tenant_account(
+    state: &Arc<AppState>,
+    account_id: &str,
+) -> Result<AccountRow, OwnershipServiceError> {
+    let account = match state.db.get_account(account_id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(OwnershipServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(OwnershipServiceError::NotFound);
+    }
+    Ok(account)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::seed::{mnemonic_to_seed, parse_mnemonic};
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    #[test]
+    fn test_sign_and_verify_proofs() {
+        let seed = mnemonic_to_seed(&parse_mnemonic(TEST_MNEMONIC).unwrap(), "");
+        let timestamp = "2026-01-02T03:04:05Z";
+
+        let keypair = SolanaKeypair::derive(&seed, 0).unwrap();
+        let address = keypair.address();
+        let message = proof_message(Chain::Solana, &address, "claim #42", timestamp);
+        let signature = keypair.sign(message.as_bytes()).to_string();
+        let verify = |address: &str, message: &str, signature: &str| {
+            verify_ownership(VerifyOwnershipRequest {
+                chain: "solana".to_string(),
+                address: address.to_string(),
+                message: message.to_string(),
+                signature: signature.to_string(),
+            })
+            .unwrap()
+        };
+        let checked = verify(&address, &message, &signature);
+        assert!(checked.valid, "{:?}", checked.reason);
+        assert_eq!(checked.challenge.as_deref(), Some("claim #42"));
+        assert_eq!(checked.timestamp.as_deref(), Some(timestamp));
+        assert!(!verify(&address, &message.replace("#42", "#43"), &signature).valid);
+
+        // Signed by the address, but not a proof
+        let other = keypair.sign(b"hello").to_string();
+        let checked = verify(&address, "hello", &other);
+        assert!(!checked.valid);
+        assert_eq!(checked.reason.as_deref(), Some("not an ownership proof"));
+
+        let wallet = EthereumWallet::derive(&seed, 0).unwrap();
+        let address = ethereum::checksum_address(&wallet.address_string());
+        let message = proof_message(Chain::Ethereum, &address, "claim #42", timestamp);
+        let checked = verify_ownership(VerifyOwnershipRequest {
+            chain: "ethereum".to_string(),
+            address: wallet.address_string(),
+            message: message.clone(),
+            signature: wallet.sign_message(&message).unwrap(),
+        })
+        .unwrap();
+        assert!(checked.valid, "{:?}", checked.reason);
+        assert_eq!(checked.address, address);
+
+        assert_eq!(parse_proof_message(&format!("{}\nExtra: 1", message)), None);
+    }
+}
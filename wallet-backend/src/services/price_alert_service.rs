@@ -0,0 +1,500 @@
+//! Price alert service - user-defined rules on coin and token prices
+//!
+//! Rules are evaluated by the price refresh loop: each pass fetches the
+//! current price of everything an enabled rule watches, once per chain,
+//! token and currency, and keeps it as a sample. `price_above` and
+//! `price_below` compare that price with the threshold; `rises_by` and
+//! `drops_by` compare its change, in percent, since the earliest sample
+//! within the rule's window. A rule fires when its condition starts to hold
+//! and stays quiet until it stops holding, so a price hovering past the
+//! threshold isn't reported every pass. Fired rules land in the alert
+//! notification feed and, when the rule has one, are POSTed to its webhook.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::chains::ChainClientError;
+use crate::core::Chain;
+use crate::services::alert_service::{
+    format_amount, non_empty, parse_currency, validate_webhook, AlertServiceError,
+};
+use crate::services::webhook_service::{self, Endpoint, EventType};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AlertNotificationRow, PriceAlertResponse, PriceAlertRow};
+use crate::AppState;
+
+/// Window of change rules created without one
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+/// Longest window a change rule may use; samples are kept this long
+pub const MAX_WINDOW_HOURS: i64 = 168;
+
+/// Upper bound on a webhook delivery
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a price alert compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceAlertKind {
+    PriceAbove,
+    PriceBelow,
+    RisesBy,
+    DropsBy,
+}
+
+impl PriceAlertKind {
+    /// Whether the threshold is a percentage change over a window
+    fn is_change(self) -> bool {
+        matches!(self, PriceAlertKind::RisesBy | PriceAlertKind::DropsBy)
+    }
+}
+
+impl fmt::Display for PriceAlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceAlertKind::PriceAbove => write!(f, "price_above"),
+            PriceAlertKind::PriceBelow => write!(f, "price_below"),
+            PriceAlertKind::RisesBy => write!(f, "rises_by"),
+            PriceAlertKind::DropsBy => write!(f, "drops_by"),
+        }
+    }
+}
+
+impl FromStr for PriceAlertKind {
+    type Err = AlertServiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "price_above" => Ok(PriceAlertKind::PriceAbove),
+            "price_below" => Ok(PriceAlertKind::PriceBelow),
+            "rises_by" => Ok(PriceAlertKind::RisesBy),
+            "drops_by" => Ok(PriceAlertKind::DropsBy),
+            _ => Err(AlertServiceError::InvalidKind(s.to_string())),
+        }
+    }
+}
+
+/// Create price alert request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePriceAlertRequest {
+    pub chain: String,
+    /// Token to watch; the chain's native coin when omitted
+    pub token_address: Option<String>,
+    /// `price_above`, `price_below`, `rises_by` or `drops_by`
+    pub kind: String,
+    /// Price, e.g. `"200"`, or a percentage, e.g. `"10"`, for change rules
+    pub threshold: String,
+    /// Currency of the price; `REPORTING_CURRENCY` when omitted
+    pub currency: Option<String>,
+    /// Change rules: hours to measure the change over, 24 by default
+    pub window_hours: Option<i64>,
+    /// Receives a JSON POST each time the alert fires
+    pub webhook_url: Option<String>,
+}
+
+/// Update price alert request; omitted fields are left unchanged and an
+/// empty string clears `webhook_url`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePriceAlertRequest {
+    pub threshold: Option<String>,
+    pub window_hours: Option<i64>,
+    pub webhook_url: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Body POSTed to a price alert's webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAlertWebhookPayload {
+    pub price_alert_id: String,
+    pub kind: String,
+    pub chain: String,
+    pub token_address: Option<String>,
+    pub symbol: String,
+    pub threshold: String,
+    pub currency: String,
+    pub window_hours: Option<i64>,
+    pub message: String,
+    pub price: String,
+    /// Change rules: percentage change over the window
+    pub change_percent: Option<f64>,
+    pub triggered_at: String,
+}
+
+pub async fn list_price_alerts(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<PriceAlertResponse>, AlertServiceError> {
+    let alerts = state.db.get_price_alerts(user_id).await?;
+    Ok(alerts.into_iter().map(PriceAlertResponse::from).collect())
+}
+
+/// Create a price alert on a native coin or, after checking it exists on
+/// chain, a token
+pub async fn create_price_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: CreatePriceAlertRequest,
+) -> Result<PriceAlertResponse, AlertServiceError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| AlertServiceError::InvalidChain(request.chain.clone()))?;
+    let kind: PriceAlertKind = request.kind.parse()?;
+    let threshold = parse_threshold(kind, &request.threshold)?;
+    let window_hours = parse_window(kind, request.window_hours)?;
+    let currency = match non_empty(request.currency) {
+        Some(currency) => parse_currency(currency)?,
+        None => state.config.reporting_currency.clone(),
+    };
+    let webhook_url = non_empty(request.webhook_url)
+        .map(validate_webhook)
+        .transpose()?;
+
+    let (token_address, symbol) = match non_empty(request.token_address) {
+        None => (String::new(), native_symbol(chain).to_string()),
+        Some(address) => {
+            let address = match chain {
+                Chain::Ethereum => address.to_lowercase(),
+                Chain::Solana => address,
+            };
+            let metadata = state
+                .chain_clients()
+                .get(chain)
+                .token_metadata(&address)
+                .await
+                .map_err(|e| match e {
+                    ChainClientError::InvalidAddress(addr) => AlertServiceError::InvalidToken(addr),
+                    _ => AlertServiceError::Chain(e.to_string()),
+                })?;
+            let symbol = metadata.symbol.unwrap_or_else(|| address.clone());
+            (address, symbol)
+        }
+    };
+
+    let alert = PriceAlertRow {
+        window_hours,
+        webhook_url,
+        ..PriceAlertRow::new(
+            user_id.to_string(),
+            chain.to_string(),
+            token_address,
+            symbol,
+            kind.to_string(),
+            threshold,
+            currency,
+        )
+    };
+    state.db.create_price_alert(&alert).await?;
+    tracing::info!(user_id = %user_id, price_alert_id = %alert.id, kind = %kind, "Price alert created");
+
+    Ok(PriceAlertResponse::from(alert))
+}
+
+/// Change a price alert's threshold, window, webhook or enabled state
+pub async fn update_price_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: UpdatePriceAlertRequest,
+) -> Result<PriceAlertResponse, AlertServiceError> {
+    let alert = get_owned_price_alert(state, user_id, id).await?;
+    let kind: PriceAlertKind = alert.kind.parse()?;
+
+    let threshold = match request.threshold {
+        Some(threshold) => parse_threshold(kind, &threshold)?,
+        None => alert.threshold,
+    };
+    let window_hours = match request.window_hours {
+        Some(hours) => parse_window(kind, Some(hours))?,
+        None => alert.window_hours,
+    };
+    let webhook_url = match request.webhook_url {
+        Some(url) => non_empty(Some(url)).map(validate_webhook).transpose()?,
+        None => alert.webhook_url,
+    };
+    let is_active = request.is_active.unwrap_or(alert.is_active);
+
+    state
+        .db
+        .update_price_alert(id, &threshold, window_hours, webhook_url.as_deref(), is_active)
+        .await?;
+
+    let alert = get_owned_price_alert(state, user_id, id).await?;
+    Ok(PriceAlertResponse::from(alert))
+}
+
+pub async fn delete_price_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<(), AlertServiceError> {
+    get_owned_price_alert(state, user_id, id).await?;
+    Ok(state.db.delete_price_alert(id).await?)
+}
+
+/// Evaluate every enabled price alert against current prices. Returns how
+/// many fired.
+pub async fn evaluate_price_alerts(state: &Arc<AppState>) -> Result<usize, AlertServiceError> {
+    let now = Utc::now();
+    let cutoff = (now - chrono::Duration::hours(MAX_WINDOW_HOURS)).to_rfc3339();
+    state.db.prune_price_samples(&cutoff).await?;
+
+    let alerts = state.db.get_active_price_alerts().await?;
+    if alerts.is_empty() {
+        return Ok(0);
+    }
+
+    let mut by_asset: BTreeMap<(String, String, String), Vec<PriceAlertRow>> = BTreeMap::new();
+    for alert in alerts {
+        by_asset
+            .entry((
+                alert.chain.clone(),
+                alert.token_address.clone(),
+                alert.currency.clone(),
+            ))
+            .or_default()
+            .push(alert);
+    }
+
+    let http = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let sampled_at = now.to_rfc3339();
+    let mut fired = 0;
+    for ((chain, token_address, currency), alerts) in by_asset {
+        let Ok(chain) = chain.parse::<Chain>() else {
+            continue;
+        };
+        // Without a price nothing can be judged; retry next pass
+        let Some(price) = current_price(state, chain, &token_address, &currency).await else {
+            continue;
+        };
+        let chain = chain.to_string();
+        state
+            .db
+            .insert_price_sample(&chain, &token_address, &currency, price, &sampled_at)
+            .await?;
+
+        for alert in alerts {
+            let Ok(kind) = alert.kind.parse::<PriceAlertKind>() else {
+                continue;
+            };
+            let Ok(threshold) = alert.threshold.parse::<f64>() else {
+                continue;
+            };
+
+            let change = if kind.is_change() {
+                let window = alert.window_hours.unwrap_or(DEFAULT_WINDOW_HOURS);
+                let since = (now - chrono::Duration::hours(window)).to_rfc3339();
+                match state
+                    .db
+                    .get_price_sample_since(&chain, &token_address, &currency, &since)
+                    .await?
+                {
+                    // The sample just taken says nothing about change
+                    Some((base, at)) if at != sampled_at && base > 0.0 => {
+                        Some((price - base) / base * 100.0)
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            };
+
+            let met = match (kind, change) {
+                (PriceAlertKind::PriceAbove, _) => price > threshold,
+                (PriceAlertKind::PriceBelow, _) => price < threshold,
+                (PriceAlertKind::RisesBy, Some(change)) => change >= threshold,
+                (PriceAlertKind::DropsBy, Some(change)) => -change >= threshold,
+                (_, None) => alert.triggered,
+            };
+
+            let price_label = format_amount(price);
+            if !met || alert.triggered {
+                state
+                    .db
+                    .set_price_alert_state(&alert.id, met, &price_label, None)
+                    .await?;
+                continue;
+            }
+
+            let triggered_at = fire(state, &http, &alert, kind, price, change).await?;
+            state
+                .db
+                .set_price_alert_state(&alert.id, true, &price_label, Some(&triggered_at))
+                .await?;
+            fired += 1;
+        }
+    }
+
+    Ok(fired)
+}
+
+/// Current price in `currency` of the native coin (`token_address` empty)
+/// or a token; `None` if the feed can't quote it
+async fn current_price(
+    state: &Arc<AppState>,
+    chain: Chain,
+    token_address: &str,
+    currency: &str,
+) -> Option<f64> {
+    let price = if token_address.is_empty() {
+        state.prices.native_price(chain, currency).await.map(|p| Some(p.rate))
+    } else {
+        state.prices.token_price(chain, token_address, currency).await
+    };
+    match price {
+        Ok(Some(price)) if price.is_finite() && price > 0.0 => Some(price),
+        Ok(price) => {
+            tracing::debug!(chain = %chain, token = %token_address, ?price, "No price for price alerts");
+            None
+        }
+        Err(e) => {
+            tracing::warn!(chain = %chain, token = %token_address, error = %e, "No price for price alerts");
+            None
+        }
+    }
+}
+
+/// Record a notification for a price alert and deliver it to the webhook,
+/// if any. Returns the time it fired.
+async fn fire(
+    state: &Arc<AppState>,
+    http: &reqwest::Client,
+    alert: &PriceAlertRow,
+    kind: PriceAlertKind,
+    price: f64,
+    change: Option<f64>,
+) -> Result<String, AlertServiceError> {
+    let price_label = format_amount(price);
+    let message = match (kind, change) {
+        (PriceAlertKind::PriceAbove, _) | (PriceAlertKind::PriceBelow, _) => format!(
+            "{} is {} {}, {} {} {}",
+            alert.symbol,
+            price_label,
+            alert.currency,
+            if kind == PriceAlertKind::PriceAbove { "above" } else { "below" },
+            alert.threshold,
+            alert.currency,
+        ),
+        (_, change) => format!(
+            "{} {} {:.2}% in {}h to {} {}",
+            alert.symbol,
+            if kind == PriceAlertKind::RisesBy { "rose" } else { "dropped" },
+            change.unwrap_or_default().abs(),
+            alert.window_hours.unwrap_or(DEFAULT_WINDOW_HOURS),
+            price_label,
+            alert.currency,
+        ),
+    };
+
+    let mut notification = AlertNotificationRow::for_price_alert(
+        alert.id.clone(),
+        alert.user_id.clone(),
+        message,
+        price_label.clone(),
+    );
+
+    if let Some(url) = &alert.webhook_url {
+        let payload = PriceAlertWebhookPayload {
+            price_alert_id: alert.id.clone(),
+            kind: alert.kind.clone(),
+            chain: alert.chain.clone(),
+            token_address: Some(alert.token_address.clone()).filter(|a| !a.is_empty()),
+            symbol: alert.symbol.clone(),
+            threshold: alert.threshold.clone(),
+            currency: alert.currency.clone(),
+            window_hours: alert.window_hours,
+            message: notification.message.clone(),
+            price: price_label,
+            change_percent: change,
+            triggered_at: notification.created_at.clone(),
+        };
+        // Price alerts are evaluated across tenants; the owner's signs
+        let owner = state
+            .user_service
+            .find_user(&alert.user_id)
+            .await
+            .map_err(|e| AlertServiceError::DatabaseError(e.to_string()))?;
+        let endpoint = Endpoint {
+            tenant_id: &owner.tenant_id,
+            webhook_id: &alert.id,
+            url,
+        };
+        notification.webhook_status = Some(
+            webhook_service::deliver(state, http, endpoint, EventType::PriceAlertFired, &payload).await,
+        );
+    }
+
+    state.db.create_alert_notification(&notification).await?;
+    tracing::info!(price_alert_id = %alert.id, user_id = %alert.user_id, "Price alert fired");
+    Ok(notification.created_at)
+}
+
+fn native_symbol(chain: Chain) -> &'static str {
+    match chain {
+        Chain::Solana => "SOL",
+        Chain::Ethereum => "ETH",
+    }
+}
+
+/// A positive price, or a percentage change; drops can't exceed 100%
+fn parse_threshold(kind: PriceAlertKind, threshold: &str) -> Result<String, AlertServiceError> {
+    let trimmed = threshold.trim();
+    match trimmed.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => {
+            if kind == PriceAlertKind::DropsBy && value >= 100.0 {
+                return Err(AlertServiceError::InvalidThreshold(threshold.to_string()));
+            }
+            Ok(trimmed.to_string())
+        }
+        _ => Err(AlertServiceError::InvalidThreshold(threshold.to_string())),
+    }
+}
+
+/// Change rules get a window of 1 to `MAX_WINDOW_HOURS`; price rules none
+fn parse_window(kind: PriceAlertKind, hours: Option<i64>) -> Result<Option<i64>, AlertServiceError> {
+    if !kind.is_change() {
+        return Ok(None);
+    }
+    match hours.unwrap_or(DEFAULT_WINDOW_HOURS) {
+        hours @ 1..=MAX_WINDOW_HOURS => Ok(Some(hours)),
+        hours => Err(AlertServiceError::InvalidWindow(hours)),
+    }
+}
+
+async fn get_owned_price_alert(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<PriceAlertRow, AlertServiceError> {
+    match state.db.get_price_alert(id).await {
+        Ok(alert) if alert.user_id == user_id => Ok(alert),
+        Ok(_) | Err(DatabaseError::NotFound) => Err(AlertServiceError::NotFound),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_alert_inputs() {
+        assert_eq!("drops_by".parse::<PriceAlertKind>().unwrap(), PriceAlertKind::DropsBy);
+        assert!("price".parse::<PriceAlertKind>().is_err());
+        assert_eq!(parse_threshold(PriceAlertKind::PriceAbove, "200").unwrap(), "200");
+        assert!(parse_threshold(PriceAlertKind::DropsBy, "100").is_err());
+        assert!(parse_threshold(PriceAlertKind::RisesBy, "-5").is_err());
+        assert_eq!(parse_window(PriceAlertKind::DropsBy, None).unwrap(), Some(24));
+        assert_eq!(parse_window(PriceAlertKind::PriceBelow, Some(5)).unwrap(), None);
+        assert!(parse_window(PriceAlertKind::RisesBy, Some(0)).is_err());
+        assert!(parse_window(PriceAlertKind::RisesBy, Some(MAX_WINDOW_HOURS + 1)).is_err());
+    }
+}
@@ -0,0 +1,425 @@
+//! Price service - fiat exchange rates for native coins, and prices at
+//! transaction time for history
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use thiserror::Error;
+
+use crate::api::handlers::v2::parse_timestamp;
+use crate::core::Chain;
+use crate::services::price_alert_service;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::TransactionRow;
+use crate::AppState;
+
+/// History rows priced per backfill pass
+const BACKFILL_BATCH: u32 = 100;
+
+#[derive(Debug, Error)]
+pub enum PriceError {
+    #[error("Unsupported currency: {0}")]
+    UnsupportedCurrency(String),
+    #[error("Price feed unavailable: {0}")]
+    Unavailable(String),
+    #[error("Price is {age_secs}s old (max {max_age_secs}s)")]
+    Stale { age_secs: i64, max_age_secs: u64 },
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Price of one native coin in a fiat currency
+#[derive(Debug, Clone)]
+pub struct Price {
+    pub rate: f64,
+    /// Upper-case ISO 4217 code
+    pub currency: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Source of native coin prices
+#[async_trait]
+pub trait PriceFeed: Send + Sync {
+    async fn native_price(&self, chain: Chain, currency: &str) -> Result<Price, PriceError>;
+
+    /// Current price of the token at `token_address`; `None` if the feed
+    /// doesn't quote it
+    async fn token_price(
+        &self,
+        chain: Chain,
+        token_address: &str,
+        currency: &str,
+    ) -> Result<Option<f64>, PriceError>;
+
+    /// Price of one native coin, or of the token at `token_address`, on
+    /// `date`; `None` if the feed has no price for it
+    async fn historical_price(
+        &self,
+        chain: Chain,
+        token_address: Option<&str>,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<Option<f64>, PriceError>;
+}
+
+/// Prices from the CoinGecko `simple/price` API
+pub struct CoinGeckoPriceFeed {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl CoinGeckoPriceFeed {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// GET a JSON document; `None` on 404 (unknown coin or contract)
+    async fn get_json(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Option<serde_json::Value>, PriceError> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| PriceError::Unavailable(e.to_string()))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        response
+            .error_for_status()
+            .map_err(|e| PriceError::Unavailable(e.to_string()))?
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| PriceError::Unavailable(e.to_string()))
+    }
+}
+
+/// CoinGecko id of the chain's native coin, which is also its asset platform id
+fn coin_id(chain: Chain) -> &'static str {
+    match chain {
+        Chain::Solana => "solana",
+        Chain::Ethereum => "ethereum",
+    }
+}
+
+#[async_trait]
+impl PriceFeed for CoinGeckoPriceFeed {
+    async fn native_price(&self, chain: Chain, currency: &str) -> Result<Price, PriceError> {
+        let coin_id = coin_id(chain);
+        let vs_currency = currency.to_lowercase();
+
+        let response: serde_json::Value = self
+            .client
+            .get(format!("{}/simple/price", self.base_url))
+            .query(&[
+                ("ids", coin_id),
+                ("vs_currencies", vs_currency.as_str()),
+                ("include_last_updated_at", "true"),
+            ])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| PriceError::Unavailable(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| PriceError::Unavailable(e.to_string()))?;
+
+        // Unknown currencies come back as an empty object rather than an error
+        let quote = &response[coin_id];
+        let rate = quote[&vs_currency]
+            .as_f64()
+            .ok_or_else(|| PriceError::UnsupportedCurrency(currency.to_uppercase()))?;
+        let updated_at = quote["last_updated_at"]
+            .as_i64()
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .ok_or_else(|| PriceError::Unavailable("missing quote timestamp".to_string()))?;
+
+        Ok(Price {
+            rate,
+            currency: currency.to_uppercase(),
+            updated_at,
+        })
+    }
+
+    async fn token_price(
+        &self,
+        chain: Chain,
+        token_address: &str,
+        currency: &str,
+    ) -> Result<Option<f64>, PriceError> {
+        let vs_currency = currency.to_lowercase();
+        let path = format!("/simple/token_price/{}", coin_id(chain));
+        let query = [
+            ("contract_addresses", token_address),
+            ("vs_currencies", vs_currency.as_str()),
+        ];
+        let Some(response) = self.get_json(&path, &query).await? else {
+            return Ok(None);
+        };
+        // Keyed by contract address, lower-cased for Ethereum
+        let quote = match response.get(token_address) {
+            Some(quote) => quote,
+            None => &response[token_address.to_lowercase()],
+        };
+        Ok(quote[&vs_currency].as_f64())
+    }
+
+    async fn historical_price(
+        &self,
+        chain: Chain,
+        token_address: Option<&str>,
+        currency: &str,
+        date: NaiveDate,
+    ) -> Result<Option<f64>, PriceError> {
+        let vs_currency = currency.to_lowercase();
+
+        let Some(token_address) = token_address else {
+            let path = format!("/coins/{}/history", coin_id(chain));
+            let date = date.format("%d-%m-%Y").to_string();
+            let query = [("date", date.as_str()), ("localization", "false")];
+            let Some(response) = self.get_json(&path, &query).await? else {
+                return Ok(None);
+            };
+            // Days before the coin was listed have no market data
+            let prices = &response["market_data"]["current_price"];
+            if prices.is_null() {
+                return Ok(None);
+            }
+            return prices[&vs_currency]
+                .as_f64()
+                .map(Some)
+                .ok_or_else(|| PriceError::UnsupportedCurrency(currency.to_uppercase()));
+        };
+
+        let path = format!(
+            "/coins/{}/contract/{}/market_chart/range",
+            coin_id(chain),
+            token_address
+        );
+        let from = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp();
+        let (from, to) = (from.to_string(), (from + 86_400).to_string());
+        let query = [
+            ("vs_currency", vs_currency.as_str()),
+            ("from", from.as_str()),
+            ("to", to.as_str()),
+        ];
+        let Some(response) = self.get_json(&path, &query).await? else {
+            return Ok(None);
+        };
+        // `[[timestamp_ms, price], ...]`, earliest first
+        Ok(response["prices"][0][1].as_f64())
+    }
+}
+
+/// Amount entered in fiat, e.g. `"25 USD"`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiatAmount {
+    pub value: f64,
+    pub currency: String,
+}
+
+/// Parse `"<amount> <currency code>"`; plain coin amounts return `None`
+pub fn parse_fiat_amount(amount: &str) -> Option<FiatAmount> {
+    let mut parts = amount.split_whitespace();
+    let (value, currency) = (parts.next()?, parts.next()?);
+    if parts.next().is_some()
+        || currency.len() != 3
+        || !currency.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        return None;
+    }
+
+    Some(FiatAmount {
+        value: value.parse().ok()?,
+        currency: currency.to_uppercase(),
+    })
+}
+
+/// Fiat amount converted at send time
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FiatConversion {
+    pub fiat_amount: String,
+    pub currency: String,
+    /// Fiat per native coin
+    pub rate: f64,
+    pub rate_updated_at: String,
+    /// Native amount in display units (SOL, ETH)
+    pub native_amount: String,
+}
+
+/// Convert a fiat amount into the chain's native coin at the current price,
+/// rejecting quotes older than the configured maximum age
+pub async fn convert_to_native(
+    state: &Arc<AppState>,
+    chain: Chain,
+    fiat: &FiatAmount,
+) -> Result<FiatConversion, PriceError> {
+    let price = state.prices.native_price(chain, &fiat.currency).await?;
+
+    let max_age = state.config.price_max_age.as_secs();
+    let age_secs = (Utc::now() - price.updated_at).num_seconds();
+    if age_secs > max_age as i64 {
+        return Err(PriceError::Stale {
+            age_secs,
+            max_age_secs: max_age,
+        });
+    }
+    if !(price.rate.is_finite() && price.rate > 0.0) {
+        return Err(PriceError::Unavailable(format!("invalid rate {}", price.rate)));
+    }
+
+    Ok(FiatConversion {
+        fiat_amount: fiat.value.to_string(),
+        currency: price.currency,
+        rate: price.rate,
+        rate_updated_at: price.updated_at.to_rfc3339(),
+        native_amount: native_amount(fiat.value / price.rate, chain.native_decimals()),
+    })
+}
+
+/// Look up prices for history rows that have none yet. Returns how many
+/// were priced.
+pub async fn backfill_prices(state: &Arc<AppState>) -> Result<usize, PriceError> {
+    let currency = state.config.reporting_currency.as_str();
+    let rows = state.db.get_unpriced_transactions(BACKFILL_BATCH).await?;
+
+    let mut priced = 0;
+    for tx in rows {
+        let price = match price_at_transaction(state, &tx, currency).await {
+            Ok(price) => price,
+            // Try again next pass
+            Err(PriceError::Unavailable(e)) => {
+                tracing::warn!(error = %e, "Historical price feed unavailable");
+                break;
+            }
+            Err(PriceError::Database(e)) => return Err(e.into()),
+            Err(e) => {
+                tracing::debug!(tx_hash = %tx.signature, error = %e, "No historical price");
+                None
+            }
+        };
+
+        let now = Utc::now().to_rfc3339();
+        let price = price.map(|p| p.to_string());
+        state
+            .db
+            .set_transaction_price(&tx.id, price.as_deref(), currency, &now)
+            .await?;
+        if price.is_some() {
+            priced += 1;
+        }
+    }
+
+    Ok(priced)
+}
+
+/// Daily price of what the transaction moved, from the `historical_prices`
+/// cache or the feed. NFTs and rows without an amount aren't priced.
+async fn price_at_transaction(
+    state: &Arc<AppState>,
+    tx: &TransactionRow,
+    currency: &str,
+) -> Result<Option<f64>, PriceError> {
+    if tx.token_id.is_some() || tx.amount.is_none() {
+        return Ok(None);
+    }
+    let Ok(chain) = tx.chain.parse::<Chain>() else {
+        return Ok(None);
+    };
+    let at = tx.timestamp.as_deref().unwrap_or(&tx.created_at);
+    let Some(date) = parse_timestamp(at).map(|t| t.date_naive()) else {
+        return Ok(None);
+    };
+
+    let token = tx.token_address.as_deref().unwrap_or("");
+    let day = date.format("%Y-%m-%d").to_string();
+    if let Some(price) = state
+        .db
+        .get_historical_price(&tx.chain, token, currency, &day)
+        .await?
+    {
+        return Ok(price);
+    }
+
+    let token_address = tx.token_address.as_deref();
+    let price = state
+        .prices
+        .historical_price(chain, token_address, currency, date)
+        .await?
+        .filter(|p| p.is_finite() && *p > 0.0);
+    state
+        .db
+        .upsert_historical_price(&tx.chain, token, currency, &day, price)
+        .await?;
+    Ok(price)
+}
+
+/// Run `backfill_prices` and evaluate price alerts every
+/// `PRICE_BACKFILL_SECS` for the life of the process
+pub fn spawn_price_backfill(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.price_backfill_interval);
+        loop {
+            ticker.tick().await;
+            match backfill_prices(&state).await {
+                Ok(0) => {}
+                Ok(priced) => tracing::debug!(priced, "Transaction prices backfilled"),
+                Err(e) => tracing::warn!(error = %e, "Price backfill failed"),
+            }
+            match price_alert_service::evaluate_price_alerts(&state).await {
+                Ok(0) => {}
+                Ok(fired) => tracing::debug!(fired, "Price alerts fired"),
+                Err(e) => tracing::warn!(error = %e, "Price alert evaluation failed"),
+            }
+        }
+    })
+}
+
+/// Round down to the coin's smallest unit and format without trailing zeros
+fn native_amount(amount: f64, decimals: u32) -> String {
+    let base_units = (amount * 10f64.powi(decimals as i32)).floor() as u128;
+    let scale = 10u128.pow(decimals);
+    let (whole, fraction) = (base_units / scale, base_units % scale);
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fiat_amount() {
+        assert_eq!(
+            parse_fiat_amount("25 usd"),
+            Some(FiatAmount {
+                value: 25.0,
+                currency: "USD".to_string()
+            })
+        );
+        assert_eq!(parse_fiat_amount("0.5"), None);
+        assert_eq!(parse_fiat_amount("25 dollars"), None);
+        assert_eq!(parse_fiat_amount("ten USD"), None);
+    }
+
+    #[test]
+    fn test_native_amount_rounds_down() {
+        assert_eq!(native_amount(0.25, 9), "0.25");
+        assert_eq!(native_amount(2.0, 9), "2");
+        assert_eq!(native_amount(1.0 / 3.0, 9), "0.333333333");
+    }
+}
@@ -0,0 +1,693 @@
+//! QR service - payment URIs and the QR codes that carry them
+//!
+//! Solana requests follow the Solana Pay transfer-request spec
+//! (`solana:<recipient>?amount=..&spl-token=..`); Ethereum requests follow
+//! EIP-681, with ERC-20 payments expressed as a `transfer` call on the token
+//! contract and amounts in base units.
+//!
+//! Scanned payloads in either format, WalletConnect pairing URIs and bare
+//! addresses are parsed back into what a send form needs.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use qrcode::{render::svg, QrCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::chains::{ethereum, solana, ChainClientError};
+use crate::core::Chain;
+use crate::services::transaction_service;
+use crate::AppState;
+
+pub const MIN_SIZE: u32 = 64;
+pub const MAX_SIZE: u32 = 1024;
+pub const DEFAULT_SIZE: u32 = 200;
+
+const SOLANA_DECIMALS: u8 = 9;
+const ETHER_DECIMALS: u8 = 18;
+/// Longest label or message, in bytes
+const MAX_TEXT_BYTES: usize = 128;
+/// Longest scanned payload, in bytes; more than a QR code can hold
+const MAX_PAYLOAD_BYTES: usize = 4096;
+
+#[derive(Debug, Error)]
+pub enum QrError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid payment request: {0}")]
+    InvalidRequest(String),
+    #[error("Chain error: {0}")]
+    Chain(String),
+    #[error("Encoding failed: {0}")]
+    Encoding(String),
+}
+
+/// What a payer's wallet should pre-fill
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PaymentRequest {
+    pub chain: String,
+    pub recipient: String,
+    /// Decimal amount of the native coin or `token`
+    pub amount: Option<String>,
+    /// SPL mint or ERC-20 contract; the native coin when unset
+    pub token: Option<String>,
+    /// Solana only: memo to attach to the payment
+    pub memo: Option<String>,
+    /// Solana only: who is asking, shown by the payer's wallet
+    pub label: Option<String>,
+    /// Solana only: what the payment is for
+    pub message: Option<String>,
+    /// Solana only: reference keys to find the payment by
+    #[serde(default)]
+    pub references: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QrFormat {
+    /// JSON with the URI, SVG and a data URL
+    #[default]
+    Json,
+    Svg,
+    Png,
+}
+
+/// What a scanned payload turned out to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadKind {
+    /// A bare Solana or Ethereum address
+    Address,
+    SolanaPay,
+    Eip681,
+    /// A WalletConnect pairing URI, to hand to the WalletConnect client
+    WalletConnect,
+}
+
+/// A WalletConnect pairing request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WalletConnectPairing {
+    pub version: u8,
+    pub topic: String,
+}
+
+/// A scanned payload, normalized to pre-fill the send form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScannedPayload {
+    pub kind: PayloadKind,
+    /// Unset for WalletConnect pairings
+    pub chain: Option<Chain>,
+    pub recipient: Option<String>,
+    /// Decimal amount of the native coin or `token`
+    pub amount: Option<String>,
+    /// SPL mint or ERC-20 contract; the native coin when unset
+    pub token: Option<String>,
+    pub memo: Option<String>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    pub references: Vec<String>,
+    pub wallet_connect: Option<WalletConnectPairing>,
+}
+
+impl ScannedPayload {
+    fn new(kind: PayloadKind, chain: Option<Chain>) -> Self {
+        Self {
+            kind,
+            chain,
+            recipient: None,
+            amount: None,
+            token: None,
+            memo: None,
+            label: None,
+            message: None,
+            references: Vec::new(),
+            wallet_connect: None,
+        }
+    }
+}
+
+/// An amount as written in the payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ScannedAmount {
+    /// Solana Pay: decimal
+    Decimal(String),
+    /// EIP-681: base units
+    BaseUnits(String),
+}
+
+/// Classify and validate a scanned QR payload
+pub async fn parse_payload(state: &Arc<AppState>, payload: &str) -> Result<ScannedPayload, QrError> {
+    let (mut scanned, amount, chain_id) = classify(payload)?;
+    if let Some(chain_id) = chain_id {
+        if chain_id != state.config.eth_chain_id {
+            return Err(QrError::InvalidRequest(format!(
+                "payment is for chain ID {}, not {}",
+                chain_id, state.config.eth_chain_id
+            )));
+        }
+    }
+
+    if let (Some(amount), Some(chain)) = (amount, scanned.chain) {
+        let decimals = match (scanned.token.as_deref(), chain) {
+            (Some(token), _) => token_decimals(state, chain, token).await?,
+            (None, Chain::Solana) => SOLANA_DECIMALS,
+            (None, Chain::Ethereum) => ETHER_DECIMALS,
+        };
+        scanned.amount = Some(match amount {
+            ScannedAmount::Decimal(amount) => parse_amount(&amount, decimals)?,
+            ScannedAmount::BaseUnits(units) => decimal_amount(&units, decimals)?,
+        });
+    }
+    Ok(scanned)
+}
+
+/// Parse a payload without looking anything up: the amount still needs the
+/// asset's decimals, and an EIP-681 chain ID checking against ours
+fn classify(
+    payload: &str,
+) -> Result<(ScannedPayload, Option<ScannedAmount>, Option<u64>), QrError> {
+    let payload = payload.trim();
+    if payload.is_empty() || payload.len() > MAX_PAYLOAD_BYTES {
+        return Err(QrError::InvalidRequest(format!(
+            "payload must be 1-{} bytes",
+            MAX_PAYLOAD_BYTES
+        )));
+    }
+
+    // QR codes in alphanumeric mode upper-case the scheme
+    let (scheme, rest) = payload.split_once(':').unwrap_or(("", payload));
+    match scheme.to_ascii_lowercase().as_str() {
+        "solana" => {
+            let (scanned, amount) = classify_solana_pay(rest)?;
+            Ok((scanned, amount, None))
+        }
+        "ethereum" => classify_eip681(rest),
+        "wc" => Ok((classify_wallet_connect(rest)?, None, None)),
+        _ if solana::validate_address(payload) => {
+            let mut scanned = ScannedPayload::new(PayloadKind::Address, Some(Chain::Solana));
+            scanned.recipient = Some(payload.to_string());
+            Ok((scanned, None, None))
+        }
+        _ if payload.starts_with("0x") && ethereum::validate_address(payload) => {
+            let mut scanned = ScannedPayload::new(PayloadKind::Address, Some(Chain::Ethereum));
+            scanned.recipient = Some(payload.to_string());
+            Ok((scanned, None, None))
+        }
+        _ => Err(QrError::InvalidRequest("unrecognized QR payload".to_string())),
+    }
+}
+
+fn classify_solana_pay(rest: &str) -> Result<(ScannedPayload, Option<ScannedAmount>), QrError> {
+    let (recipient, params) = split_query(rest)?;
+    if recipient.starts_with("https:") {
+        return Err(QrError::InvalidRequest(
+            "Solana Pay transaction requests are not supported".to_string(),
+        ));
+    }
+    if !solana::validate_address(&recipient) {
+        return Err(QrError::InvalidRequest(format!("invalid address {}", recipient)));
+    }
+
+    let mut scanned = ScannedPayload::new(PayloadKind::SolanaPay, Some(Chain::Solana));
+    let mut amount = None;
+    for (key, value) in params {
+        match key.as_str() {
+            "amount" => amount = Some(ScannedAmount::Decimal(value)),
+            "spl-token" if solana::validate_address(&value) => scanned.token = Some(value),
+            "spl-token" => {
+                return Err(QrError::InvalidRequest(format!("invalid address {}", value)))
+            }
+            "reference" => scanned.references.push(value),
+            "label" => scanned.label = Some(value),
+            "message" => scanned.message = Some(value),
+            "memo" => scanned.memo = Some(value),
+            _ => {}
+        }
+    }
+
+    transaction_service::check_payment_markers(Chain::Solana, scanned.memo.as_deref(), &scanned.references)
+        .map_err(|e| QrError::InvalidRequest(e.to_string()))?;
+    for text in [&scanned.label, &scanned.message].into_iter().flatten() {
+        if text.is_empty() || text.len() > MAX_TEXT_BYTES {
+            return Err(QrError::InvalidRequest(format!(
+                "labels and messages must be 1-{} bytes",
+                MAX_TEXT_BYTES
+            )));
+        }
+    }
+
+    scanned.recipient = Some(recipient);
+    Ok((scanned, amount))
+}
+
+/// `[pay-]<address>[@<chain id>][/transfer][?params]`; only native payments
+/// and ERC-20 `transfer` calls are understood
+fn classify_eip681(
+    rest: &str,
+) -> Result<(ScannedPayload, Option<ScannedAmount>, Option<u64>), QrError> {
+    let (path, params) = split_query(rest)?;
+    let path = path.strip_prefix("pay-").unwrap_or(&path);
+    let (target, function) = match path.split_once('/') {
+        Some((target, function)) => (target, Some(function)),
+        None => (path, None),
+    };
+    let (address, chain_id) = match target.split_once('@') {
+        Some((address, chain_id)) => {
+            let chain_id = chain_id
+                .parse()
+                .map_err(|_| QrError::InvalidRequest(format!("invalid chain ID {}", chain_id)))?;
+            (address, Some(chain_id))
+        }
+        None => (target, None),
+    };
+    let valid = |address: &str| address.starts_with("0x") && ethereum::validate_address(address);
+    if !valid(address) {
+        return Err(QrError::InvalidRequest(format!("invalid address {}", address)));
+    }
+
+    let mut scanned = ScannedPayload::new(PayloadKind::Eip681, Some(Chain::Ethereum));
+    let mut amount = None;
+    match function {
+        None => {
+            scanned.recipient = Some(address.to_string());
+            if let Some((_, value)) = params.into_iter().find(|(key, _)| key == "value") {
+                amount = Some(ScannedAmount::BaseUnits(integer_units(&value)?));
+            }
+        }
+        Some("transfer") => {
+            scanned.token = Some(address.to_string());
+            for (key, value) in params {
+                match key.as_str() {
+                    "address" if valid(&value) => scanned.recipient = Some(value),
+                    "address" => {
+                        return Err(QrError::InvalidRequest(format!("invalid address {}", value)))
+                    }
+                    "uint256" => amount = Some(ScannedAmount::BaseUnits(integer_units(&value)?)),
+                    _ => {}
+                }
+            }
+            if scanned.recipient.is_none() {
+                return Err(QrError::InvalidRequest("transfer has no recipient".to_string()));
+            }
+        }
+        Some(function) => {
+            return Err(QrError::InvalidRequest(format!(
+                "unsupported contract call {}",
+                function
+            )))
+        }
+    }
+    Ok((scanned, amount, chain_id))
+}
+
+/// `wc:<topic>@<version>?...`: v2 carries `symKey` and `relay-protocol`,
+/// v1 `key` and `bridge`
+fn classify_wallet_connect(rest: &str) -> Result<ScannedPayload, QrError> {
+    let (path, params) = split_query(rest)?;
+    let invalid = || QrError::InvalidRequest("invalid WalletConnect URI".to_string());
+    let (topic, version) = path.split_once('@').ok_or_else(invalid)?;
+    let has = |key: &str| params.iter().any(|(k, v)| k == key && !v.is_empty());
+    let version = match version {
+        "1" if has("key") && has("bridge") => 1,
+        "2" if has("symKey") && has("relay-protocol") => 2,
+        _ => return Err(invalid()),
+    };
+    if topic.is_empty() || !topic.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return Err(invalid());
+    }
+
+    let mut scanned = ScannedPayload::new(PayloadKind::WalletConnect, None);
+    scanned.wallet_connect = Some(WalletConnectPairing {
+        version,
+        topic: topic.to_string(),
+    });
+    Ok(scanned)
+}
+
+/// Decoded path and query parameters of a URI body
+fn split_query(rest: &str) -> Result<(String, Vec<(String, String)>), QrError> {
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((decode_component(key)?, decode_component(value)?))
+        })
+        .collect::<Result<_, QrError>>()?;
+    Ok((decode_component(path)?, params))
+}
+
+/// EIP-681 numbers may use scientific notation (`2.014e18`); they must come
+/// out whole
+fn integer_units(number: &str) -> Result<String, QrError> {
+    let invalid = || QrError::InvalidRequest(format!("invalid amount {}", number));
+    let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<usize>().map_err(|_| invalid())?),
+        None => (number, 0),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !digits(whole) || !digits(fraction) {
+        return Err(invalid());
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > exponent || exponent > 77 {
+        return Err(invalid());
+    }
+    Ok(format!("{}{}{}", whole, fraction, "0".repeat(exponent - fraction.len())))
+}
+
+/// Base units as a normalized decimal amount
+fn decimal_amount(units: &str, decimals: u8) -> Result<String, QrError> {
+    let units = ethers::types::U256::from_dec_str(units)
+        .map_err(|_| QrError::InvalidRequest(format!("invalid amount {}", units)))?;
+    let amount = ethers::utils::format_units(units, decimals as u32)
+        .map_err(|e| QrError::InvalidRequest(e.to_string()))?;
+    parse_amount(&amount, decimals)
+}
+
+/// Build the payment URI for a request
+pub async fn payment_uri(state: &Arc<AppState>, request: &PaymentRequest) -> Result<String, QrError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| QrError::InvalidChain(request.chain.clone()))?;
+    let recipient = request.recipient.trim();
+    let token = request.token.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    for address in std::iter::once(recipient).chain(token) {
+        let valid = match chain {
+            Chain::Solana => solana::validate_address(address),
+            Chain::Ethereum => ethereum::validate_address(address),
+        };
+        if !valid {
+            return Err(QrError::InvalidRequest(format!("invalid address {}", address)));
+        }
+    }
+
+    transaction_service::check_payment_markers(chain, request.memo.as_deref(), &request.references)
+        .map_err(|e| QrError::InvalidRequest(e.to_string()))?;
+    for text in [&request.label, &request.message].into_iter().flatten() {
+        if chain != Chain::Solana {
+            return Err(QrError::InvalidRequest(
+                "labels and messages are only supported on solana".to_string(),
+            ));
+        }
+        if text.is_empty() || text.len() > MAX_TEXT_BYTES {
+            return Err(QrError::InvalidRequest(format!(
+                "labels and messages must be 1-{} bytes",
+                MAX_TEXT_BYTES
+            )));
+        }
+    }
+
+    // Solana Pay amounts are decimal, EIP-681 ones in base units
+    let amount = match request.amount.as_deref() {
+        Some(amount) => {
+            let decimals = match (token, chain) {
+                (Some(token), _) => token_decimals(state, chain, token).await?,
+                (None, Chain::Solana) => SOLANA_DECIMALS,
+                (None, Chain::Ethereum) => ETHER_DECIMALS,
+            };
+            let amount = parse_amount(amount, decimals)?;
+            Some(match chain {
+                Chain::Solana => amount,
+                Chain::Ethereum => base_units(&amount, decimals)?,
+            })
+        }
+        None => None,
+    };
+
+    Ok(match chain {
+        Chain::Solana => solana_pay_uri(recipient, amount.as_deref(), token, request),
+        Chain::Ethereum => {
+            eip681_uri(recipient, amount.as_deref(), token, state.config.eth_chain_id)
+        }
+    })
+}
+
+/// SVG QR code, at least `size` pixels square
+pub fn render_svg(uri: &str, size: u32) -> Result<String, QrError> {
+    let (min, max) = dimensions(size)?;
+    Ok(encode(uri)?
+        .render::<svg::Color>()
+        .min_dimensions(min, min)
+        .max_dimensions(max, max)
+        .build())
+}
+
+/// PNG QR code, at least `size` pixels square
+pub fn render_png(uri: &str, size: u32) -> Result<Vec<u8>, QrError> {
+    let (min, max) = dimensions(size)?;
+    let image = encode(uri)?
+        .render::<image::Luma<u8>>()
+        .min_dimensions(min, min)
+        .max_dimensions(max, max)
+        .build();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| QrError::Encoding(e.to_string()))?;
+    Ok(png)
+}
+
+fn encode(uri: &str) -> Result<QrCode, QrError> {
+    QrCode::new(uri.as_bytes()).map_err(|e| QrError::Encoding(e.to_string()))
+}
+
+/// Modules are whole pixels, so the image lands between `size` and 1.5x
+fn dimensions(size: u32) -> Result<(u32, u32), QrError> {
+    if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+        return Err(QrError::InvalidRequest(format!(
+            "size must be between {} and {} pixels",
+            MIN_SIZE, MAX_SIZE
+        )));
+    }
+    Ok((size, size * 3 / 2))
+}
+
+async fn token_decimals(state: &Arc<AppState>, chain: Chain, token: &str) -> Result<u8, QrError> {
+    state
+        .chain_clients()
+        .get(chain)
+        .token_metadata(token)
+        .await
+        .map(|metadata| metadata.decimals)
+        .map_err(|e| match e {
+            ChainClientError::InvalidAddress(addr) => {
+                QrError::InvalidRequest(format!("unknown token {}", addr))
+            }
+            _ => QrError::Chain(e.to_string()),
+        })
+}
+
+/// A positive plain decimal with no more places than the asset has,
+/// normalized to have a leading digit
+fn parse_amount(amount: &str, decimals: u8) -> Result<String, QrError> {
+    let amount = amount.trim();
+    let invalid = || QrError::InvalidRequest(format!("invalid amount {}", amount));
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !digits(whole) || !digits(fraction) {
+        return Err(invalid());
+    }
+    if fraction.len() > decimals as usize {
+        return Err(QrError::InvalidRequest(format!(
+            "amount has more than {} decimal places",
+            decimals
+        )));
+    }
+    if !amount.bytes().any(|b| (b'1'..=b'9').contains(&b)) {
+        return Err(invalid());
+    }
+
+    let whole = whole.trim_start_matches('0');
+    let whole = if whole.is_empty() { "0" } else { whole };
+    let fraction = fraction.trim_end_matches('0');
+    Ok(if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    })
+}
+
+fn base_units(amount: &str, decimals: u8) -> Result<String, QrError> {
+    ethers::utils::parse_units(amount, decimals as u32)
+        .map(|units| ethers::types::U256::from(units).to_string())
+        .map_err(|e| QrError::InvalidRequest(e.to_string()))
+}
+
+fn solana_pay_uri(
+    recipient: &str,
+    amount: Option<&str>,
+    token: Option<&str>,
+    request: &PaymentRequest,
+) -> String {
+    let mut params = Vec::new();
+    params.extend(amount.map(|a| ("amount", a)));
+    params.extend(token.map(|t| ("spl-token", t)));
+    params.extend(request.references.iter().map(|r| ("reference", r.as_str())));
+    params.extend(request.label.as_deref().map(|l| ("label", l)));
+    params.extend(request.message.as_deref().map(|m| ("message", m)));
+    params.extend(request.memo.as_deref().map(|m| ("memo", m)));
+    with_query(format!("solana:{}", recipient), &params)
+}
+
+/// Native payments target the recipient; token payments call `transfer` on
+/// the token contract
+fn eip681_uri(recipient: &str, units: Option<&str>, token: Option<&str>, chain_id: u64) -> String {
+    let with_prefix = |a: &str| format!("0x{}", a.strip_prefix("0x").unwrap_or(a));
+    match token {
+        Some(token) => {
+            let mut params = vec![("address", with_prefix(recipient))];
+            params.extend(units.map(|u| ("uint256", u.to_string())));
+            let params: Vec<_> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+            with_query(format!("ethereum:{}@{}/transfer", with_prefix(token), chain_id), &params)
+        }
+        None => {
+            let params: Vec<_> = units.map(|u| ("value", u)).into_iter().collect();
+            with_query(format!("ethereum:{}@{}", with_prefix(recipient), chain_id), &params)
+        }
+    }
+}
+
+fn with_query(mut uri: String, params: &[(&str, &str)]) -> String {
+    for (i, (key, value)) in params.iter().enumerate() {
+        uri.push(if i == 0 { '?' } else { '&' });
+        uri.push_str(key);
+        uri.push('=');
+        uri.push_str(&encode_component(value));
+    }
+    uri
+}
+
+/// Percent-encode as JavaScript's `encodeURIComponent` does, which is what
+/// wallets decode query values with
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*'
+            | b'\'' | b'(' | b')' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Reverse of [`encode_component`]
+fn decode_component(value: &str) -> Result<String, QrError> {
+    let invalid = || QrError::InvalidRequest(format!("invalid percent-encoding in {}", value));
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3).ok_or_else(invalid)?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPIENT: &str = "0x52908400098527886E0F7030069857D2E4169EE7";
+    const TOKEN: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount(".50", 9).unwrap(), "0.5");
+        assert_eq!(parse_amount("007", 9).unwrap(), "7");
+        assert_eq!(parse_amount("1.000000001", 9).unwrap(), "1.000000001");
+        assert!(parse_amount("1.0000000001", 9).is_err());
+        for bad in ["", ".", "0", "0.00", "-1", "1e9", "1,5", "1.2.3"] {
+            assert!(parse_amount(bad, 9).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_eip681_uri() {
+        let units = base_units("1.5", 18).unwrap();
+        assert_eq!(
+            eip681_uri(RECIPIENT, Some(&units), None, 1),
+            format!("ethereum:{}@1?value=1500000000000000000", RECIPIENT)
+        );
+        let units = base_units("2.5", 6).unwrap();
+        assert_eq!(
+            eip681_uri(RECIPIENT, Some(&units), Some(TOKEN), 1),
+            format!("ethereum:{}@1/transfer?address={}&uint256=2500000", TOKEN, RECIPIENT)
+        );
+    }
+
+    #[test]
+    fn test_integer_units() {
+        assert_eq!(integer_units("2.014e18").unwrap(), "2014000000000000000");
+        assert_eq!(integer_units("1500").unwrap(), "1500");
+        assert_eq!(integer_units("1.50E1").unwrap(), "15");
+        for bad in ["", "1.5", "1e", "-1", "0x10", "1.23e1"] {
+            assert!(integer_units(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_classify_payloads() {
+        let (scanned, amount, chain_id) =
+            classify(&format!("ethereum:pay-{}@1/transfer?address={}&uint256=2.5e6", TOKEN, RECIPIENT))
+                .unwrap();
+        assert_eq!(scanned.kind, PayloadKind::Eip681);
+        assert_eq!(scanned.token.as_deref(), Some(TOKEN));
+        assert_eq!(scanned.recipient.as_deref(), Some(RECIPIENT));
+        assert_eq!(amount, Some(ScannedAmount::BaseUnits("2500000".to_string())));
+        assert_eq!(chain_id, Some(1));
+
+        let (scanned, amount, _) =
+            classify("SOLANA:9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM?amount=0.5&memo=order%20%2342")
+                .unwrap();
+        assert_eq!(scanned.kind, PayloadKind::SolanaPay);
+        assert_eq!(scanned.memo.as_deref(), Some("order #42"));
+        assert_eq!(amount, Some(ScannedAmount::Decimal("0.5".to_string())));
+
+        let (scanned, _, _) = classify(&format!("  {} ", RECIPIENT)).unwrap();
+        assert_eq!(scanned.kind, PayloadKind::Address);
+        assert_eq!(scanned.chain, Some(Chain::Ethereum));
+
+        let (scanned, _, _) =
+            classify("wc:7f6e504bfad60b485450578e05678ed3e8e8c4751d3c6160be17160d63ec90f9@2?relay-protocol=irn&symKey=587d5484ce2a2a6ee3ba1962fdd7e8588e06200c46823bd18fbd67def96ad303")
+                .unwrap();
+        assert_eq!(scanned.kind, PayloadKind::WalletConnect);
+        assert_eq!(scanned.wallet_connect.unwrap().version, 2);
+
+        for bad in [
+            "hello",
+            "solana:https%3A%2F%2Fexample.com%2Fpay",
+            "ethereum:0x52908400098527886e0f7030069857d2e4169ee7/approve?uint256=1",
+            // Checksum mismatch
+            "0x52908400098527886e0F7030069857D2E4169EE7",
+            "wc:abc@2",
+        ] {
+            assert!(classify(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_solana_pay_uri() {
+        let request = PaymentRequest {
+            label: Some("Coffee & Co".to_string()),
+            memo: Some("order #42".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            solana_pay_uri("Recipient", Some("0.5"), Some("Mint"), &request),
+            "solana:Recipient?amount=0.5&spl-token=Mint&label=Coffee%20%26%20Co&memo=order%20%2342"
+        );
+    }
+}
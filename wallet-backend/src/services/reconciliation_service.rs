@@ -0,0 +1,340 @@
+//! Reconciliation service - checks cached balances against the chain
+//!
+//! Every `RECONCILIATION_INTERVAL_SECS` (nightly by default) each account's
+//! live native balance is compared with its last known balance plus the
+//! history recorded since. A higher balance than expected means an incoming
+//! transfer was missed; a lower one, with every outgoing fee accounted for,
+//! means funds left the account without a record. Accounts whose balance
+//! can't be read are reported as failed syncs. Each discrepancy is logged
+//! and written to the tenant's audit log; the latest run is served at
+//! `/api/v1/admin/reconciliation`.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::with_tenant;
+use crate::core::{format_signed_units, parse_units, Chain};
+use crate::services::tenant_service;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{
+    AccountRow, AuditEventRow, AuditSeverity, BalanceDiscrepancyRow, ReconciliationRunRow,
+    TransactionRow,
+};
+use crate::AppState;
+
+#[derive(Debug, Error)]
+pub enum ReconciliationError {
+    #[error("No reconciliation has run yet")]
+    NotFound,
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// A run and what it found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    #[serde(flatten)]
+    pub run: ReconciliationRunRow,
+    pub discrepancies: Vec<BalanceDiscrepancyRow>,
+}
+
+/// How an account's live balance differs from the expected one
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mismatch {
+    kind: &'static str,
+    /// Expected balance in base units
+    expected: i128,
+}
+
+/// The latest run's report
+pub async fn latest_report(state: &Arc<AppState>) -> Result<ReconciliationReport, ReconciliationError> {
+    let run = state
+        .db
+        .get_latest_reconciliation_run()
+        .await?
+        .ok_or(ReconciliationError::NotFound)?;
+    let discrepancies = state.db.get_balance_discrepancies(&run.id).await?;
+    Ok(ReconciliationReport { run, discrepancies })
+}
+
+/// Check every account once and record the run
+pub async fn reconcile_balances(
+    state: &Arc<AppState>,
+) -> Result<ReconciliationReport, ReconciliationError> {
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let accounts = state.db.get_all_accounts().await?;
+
+    let mut checked = 0;
+    let mut discrepancies = Vec::new();
+    for account in accounts {
+        let wallet = match state.db.get_wallet(&account.wallet_id).await {
+            Ok(wallet) => wallet,
+            Err(DatabaseError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let tenant = match tenant_service::tenant_context(state, &wallet.tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(tenant_id = %wallet.tenant_id, error = %e, "Skipping reconciliation");
+                continue;
+            }
+        };
+
+        checked += 1;
+        match with_tenant(tenant, check(state, &run_id, &wallet.tenant_id, &account)).await {
+            Ok(Some(discrepancy)) => {
+                tracing::warn!(
+                    account_id = %account.id,
+                    chain = %account.chain,
+                    kind = %discrepancy.kind,
+                    cached = ?discrepancy.cached_balance,
+                    expected = ?discrepancy.expected_balance,
+                    live = ?discrepancy.live_balance,
+                    "Balance discrepancy"
+                );
+                let audit = AuditEventRow::new(
+                    wallet.tenant_id.clone(),
+                    None,
+                    "balance.discrepancy",
+                    AuditSeverity::Warning,
+                    serde_json::json!({
+                        "run_id": run_id,
+                        "account_id": account.id,
+                        "chain": account.chain,
+                        "kind": discrepancy.kind,
+                        "detail": discrepancy.detail,
+                    }),
+                );
+                if let Err(e) = state.db.record_audit_event(&audit).await {
+                    tracing::warn!(account_id = %account.id, error = %e, "Recording discrepancy alert failed");
+                }
+                discrepancies.push(discrepancy);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(account_id = %account.id, error = %e, "Reconciliation failed"),
+        }
+    }
+
+    let run = ReconciliationRunRow {
+        id: run_id,
+        started_at,
+        finished_at: chrono::Utc::now().to_rfc3339(),
+        accounts_checked: checked,
+        discrepancy_count: discrepancies.len() as i64,
+    };
+    state.db.record_reconciliation_run(&run, &discrepancies).await?;
+    Ok(ReconciliationReport { run, discrepancies })
+}
+
+/// Reconcile one account; `None` when its balance adds up
+async fn check(
+    state: &Arc<AppState>,
+    run_id: &str,
+    tenant_id: &str,
+    account: &AccountRow,
+) -> Result<Option<BalanceDiscrepancyRow>, DatabaseError> {
+    let Ok(chain) = account.chain.parse::<Chain>() else {
+        return Ok(None);
+    };
+    let discrepancy = |kind: &str, expected: Option<String>, live: Option<String>, detail: String| {
+        BalanceDiscrepancyRow {
+            id: uuid::Uuid::new_v4().to_string(),
+            run_id: run_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            account_id: account.id.clone(),
+            chain: account.chain.clone(),
+            address: account.address.clone(),
+            kind: kind.to_string(),
+            cached_balance: account.last_known_balance.clone(),
+            expected_balance: expected,
+            live_balance: live,
+            detail,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    };
+
+    let live = match state.account_clients(account).get(chain).balance(&account.address).await {
+        Ok(balance) => balance.native_balance,
+        Err(e) => {
+            state.db.record_account_sync_error(&account.id, &e.to_string()).await?;
+            return Ok(Some(discrepancy("sync_failed", None, None, e.to_string())));
+        }
+    };
+
+    let mut found = None;
+    if let (Some(cached), Some(synced_at)) = (&account.last_known_balance, &account.last_synced_at) {
+        let history = state.db.get_transactions_since(&account.id, synced_at).await?;
+        let decimals = chain.native_decimals();
+        if let (Some(cached_units), Some(live_units)) = (units(cached, decimals), units(&live, decimals)) {
+            if let Some(mismatch) = compare(&account.address, cached_units, &history, live_units) {
+                let detail = match mismatch.kind {
+                    "missed_incoming" => format!(
+                        "live balance is above the expected one; {} transaction(s) recorded since the last sync",
+                        history.len()
+                    ),
+                    _ => format!(
+                        "live balance is below the expected one; {} transaction(s) recorded since the last sync",
+                        history.len()
+                    ),
+                };
+                found = Some(discrepancy(
+                    mismatch.kind,
+                    Some(format_signed_units(mismatch.expected, decimals)),
+                    Some(live.clone()),
+                    detail,
+                ));
+            }
+        }
+    }
+
+    // The live balance is the baseline for the next run
+    state.db.record_account_sync(&account.id, &live).await?;
+    Ok(found)
+}
+
+/// Compare the live balance with the cached one adjusted by the history
+/// recorded since, all in base units. Pending transactions may or may not
+/// have landed, so an account with any is left for the next run; an
+/// outgoing transaction whose fee isn't known yet only rules out flagging
+/// a lower balance.
+fn compare(address: &str, cached: i128, history: &[TransactionRow], live: i128) -> Option<Mismatch> {
+    let is = |other: &Option<String>| other.as_deref().is_some_and(|o| o.eq_ignore_ascii_case(address));
+    let decimals_for = |tx: &TransactionRow| match tx.chain.parse() {
+        Ok(chain) => Chain::native_decimals(chain),
+        Err(_) => 9,
+    };
+
+    let mut expected = cached;
+    let mut fee_unknown = false;
+    for tx in history {
+        if tx.status == "pending" {
+            return None;
+        }
+        let (outgoing, incoming) = (is(&tx.from_address), is(&tx.to_address));
+        // Failed transactions and token transfers move no native coin
+        if tx.token_address.is_none() && tx.status != "failed" {
+            let amount = tx
+                .amount
+                .as_deref()
+                .and_then(|a| units(a, decimals_for(tx)))
+                .unwrap_or(0);
+            if incoming {
+                expected += amount;
+            }
+            if outgoing {
+                expected -= amount;
+            }
+        }
+        if outgoing {
+            match fee(tx) {
+                Some(fee) => expected -= fee,
+                None => fee_unknown = true,
+            }
+        }
+    }
+
+    if live > expected {
+        Some(Mismatch { kind: "missed_incoming", expected })
+    } else if live < expected && !fee_unknown {
+        Some(Mismatch { kind: "unexplained_outgoing", expected })
+    } else {
+        None
+    }
+}
+
+/// Fee paid by a settled transaction, in base units
+fn fee(tx: &TransactionRow) -> Option<i128> {
+    let fee = tx
+        .fee_paid
+        .clone()
+        .or_else(|| tx.actual_effects().map(|effects| effects.fee))?;
+    fee.parse().ok()
+}
+
+/// Display units to base units, signed for the running expected balance
+fn units(amount: &str, decimals: u32) -> Option<i128> {
+    parse_units(amount, decimals)?.try_into().ok()
+}
+
+/// Run `reconcile_balances` every `RECONCILIATION_INTERVAL_SECS` for the
+/// life of the process
+pub fn spawn_reconciliation(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.reconciliation_interval);
+        // The first tick fires at once; skip it so a restart doesn't re-run
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match reconcile_balances(&state).await {
+                Ok(report) => tracing::info!(
+                    accounts = report.run.accounts_checked,
+                    discrepancies = report.run.discrepancy_count,
+                    "Balance reconciliation finished"
+                ),
+                Err(e) => tracing::warn!(error = %e, "Balance reconciliation failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0x00000000000000000000000000000000000000aa";
+    const OTHER: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn row(from: &str, to: &str, amount: &str, status: &str, fee_paid: Option<&str>) -> TransactionRow {
+        let mut tx = TransactionRow::new(
+            "account".to_string(),
+            "ethereum".to_string(),
+            "0xhash".to_string(),
+            "send".to_string(),
+            Some(from.to_string()),
+            Some(to.to_string()),
+            Some(amount.to_string()),
+            None,
+            status.to_string(),
+            None,
+            None,
+        );
+        tx.fee_paid = fee_paid.map(str::to_string);
+        tx
+    }
+
+    #[test]
+    fn test_compare() {
+        let eth = |amount: &str| units(amount, 18).unwrap();
+        let sent = row(ADDRESS, OTHER, "1", "confirmed", Some("1000"));
+        let received = row(OTHER, ADDRESS, "0.5", "confirmed", None);
+
+        // Sends and their fees, and receipts, are accounted for
+        let live = eth("1.5") - 1000;
+        assert_eq!(compare(ADDRESS, eth("2"), &[sent.clone(), received], live), None);
+
+        // Funds that arrived without a record
+        let mismatch = compare(ADDRESS, eth("2"), &[sent.clone()], eth("1.5")).unwrap();
+        assert_eq!(mismatch.kind, "missed_incoming");
+        assert_eq!(format_signed_units(mismatch.expected, 18), "0.999999999999999");
+
+        // Funds that left without a record
+        let mismatch = compare(ADDRESS, eth("2"), &[], eth("1.9")).unwrap();
+        assert_eq!(mismatch.kind, "unexplained_outgoing");
+
+        // A failed send only costs its fee
+        let failed = row(ADDRESS, OTHER, "1", "failed", Some("1000"));
+        assert_eq!(compare(ADDRESS, eth("2"), &[failed], eth("2") - 1000), None);
+
+        // An unknown fee can explain a lower balance, but not a higher one
+        let unsettled = row(ADDRESS, OTHER, "1", "confirmed", None);
+        assert_eq!(compare(ADDRESS, eth("2"), &[unsettled.clone()], eth("0.99")), None);
+        assert!(compare(ADDRESS, eth("2"), &[unsettled], eth("1.5")).is_some());
+
+        // Pending transactions leave the account for the next run
+        let pending = row(ADDRESS, OTHER, "1", "pending", None);
+        assert_eq!(compare(ADDRESS, eth("2"), &[pending], eth("5")), None);
+    }
+}
@@ -0,0 +1,452 @@
+//! Scheduled send service - time-locked transactions
+//!
+//! A scheduled send stores only the intent: recipient, amount, asset and
+//! memo. Nothing is signed up front, since a Solana blockhash expires within
+//! minutes and an Ethereum nonce may be used by other sends in the meantime.
+//! The scheduler builds and signs the send once it falls due, with the
+//! tenant's unlocked seed; while the wallet is locked it waits, and sends
+//! still waiting at their `expires_at` are given up on. Fiat amounts are
+//! converted at execution time. A send can be cancelled until the scheduler
+//! claims it.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::{current_tenant_id, with_tenant};
+use crate::core::Chain;
+use crate::services::price_service;
+use crate::services::tenant_service;
+use crate::services::transaction_service::{self, SendRequest, TransactionServiceError};
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, ScheduledTransactionResponse, ScheduledTransactionRow};
+use crate::AppState;
+
+/// Furthest ahead a send may be scheduled
+const MAX_SCHEDULE_DAYS: i64 = 365;
+
+/// Recorded on sends waiting for the wallet to be unlocked
+const WAITING_FOR_UNLOCK: &str = "Wallet is locked; waiting for it to be unlocked";
+
+#[derive(Debug, Error)]
+pub enum ScheduledServiceError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(String),
+    #[error("Scheduled transaction not found")]
+    NotFound,
+    #[error("Scheduled transaction is already {0}")]
+    NotCancellable(String),
+    #[error(transparent)]
+    InvalidSend(#[from] TransactionServiceError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Schedule send request; the send fields are as for an immediate send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleSendRequest {
+    pub chain: String,
+    pub from_address: String,
+    pub to_address: String,
+    /// Native or token amount, or a fiat amount converted when it executes
+    pub amount: String,
+    pub token_address: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Not broadcast before this time
+    pub execute_at: DateTime<Utc>,
+    /// Give up if it couldn't be sent by this time
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Needed when the send is above the user's large transfer threshold,
+    /// since nobody is there to confirm it when it executes
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+}
+
+/// Schedule a send from one of the current tenant's accounts
+pub async fn schedule(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: ScheduleSendRequest,
+) -> Result<ScheduledTransactionResponse, ScheduledServiceError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| ScheduledServiceError::InvalidChain(request.chain.clone()))?;
+    transaction_service::check_payment_markers(
+        chain,
+        request.memo.as_deref(),
+        &request.references,
+    )?;
+    check_amount(&request.amount)?;
+    check_schedule(request.execute_at, request.expires_at, Utc::now())?;
+
+    let account = match state
+        .db
+        .get_account_by_address(&current_tenant_id(), &chain.to_string(), &request.from_address)
+        .await
+    {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(ScheduledServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    check_tenant(state, &account).await?;
+
+    let send = SendRequest {
+        chain: request.chain.clone(),
+        from_address: request.from_address.clone(),
+        to_address: request.to_address.clone(),
+        amount: request.amount.clone(),
+        token_address: request.token_address.clone(),
+        drain_all: false,
+        memo: request.memo.clone(),
+        references: request.references.clone(),
+        mev_protect: None,
+        submission_mode: None,
+        bucket: None,
+    };
+    let large = transaction_service::large_transfer(state, user_id, &send).await?;
+    if let Some((threshold, _)) = large {
+        let Some(password) = request.password.as_deref() else {
+            return Err(TransactionServiceError::ConfirmationRequired(format!(
+                "Sends above {} {} need the password to be scheduled",
+                threshold.threshold, threshold.currency
+            ))
+            .into());
+        };
+        transaction_service::verify_password(state, user_id, password).await?;
+    }
+
+    let scheduled = ScheduledTransactionRow::new(
+        account.id,
+        chain.to_string(),
+        request.to_address,
+        request.amount.trim().to_string(),
+        request.token_address.filter(|t| !t.trim().is_empty()),
+        request.memo,
+        &request.references,
+        request.execute_at.to_rfc3339(),
+        request.expires_at.map(|t| t.to_rfc3339()),
+    );
+    state.db.create_scheduled_transaction(&scheduled).await?;
+    tracing::info!(
+        scheduled_id = %scheduled.id,
+        execute_after = %scheduled.execute_after,
+        "Send scheduled"
+    );
+
+    Ok(scheduled.into())
+}
+
+/// The current tenant's scheduled sends, soonest first
+pub async fn list(
+    state: &Arc<AppState>,
+) -> Result<Vec<ScheduledTransactionResponse>, ScheduledServiceError> {
+    let rows = state.db.get_scheduled_transactions(&current_tenant_id()).await?;
+    Ok(rows.into_iter().map(ScheduledTransactionResponse::from).collect())
+}
+
+/// Cancel a send that hasn't been executed yet
+pub async fn cancel(
+    state: &Arc<AppState>,
+    id: &str,
+) -> Result<ScheduledTransactionResponse, ScheduledServiceError> {
+    let scheduled = get_owned(state, id).await?;
+    let now = Utc::now().to_rfc3339();
+    if !state
+        .db
+        .transition_scheduled_transaction(id, "cancelled", &now)
+        .await?
+    {
+        let current = state.db.get_scheduled_transaction(id).await?;
+        return Err(ScheduledServiceError::NotCancellable(current.status));
+    }
+    tracing::info!(scheduled_id = %id, "Scheduled send cancelled");
+
+    Ok(ScheduledTransactionRow {
+        status: "cancelled".to_string(),
+        updated_at: now,
+        ..scheduled
+    }
+    .into())
+}
+
+/// Execute every scheduled send that has fallen due. Returns how many were
+/// broadcast.
+pub async fn execute_due(state: &Arc<AppState>) -> Result<usize, ScheduledServiceError> {
+    let due = state
+        .db
+        .get_due_scheduled_transactions(&Utc::now().to_rfc3339())
+        .await?;
+
+    let mut sent = 0;
+    for scheduled in due {
+        let account = match state.db.get_account(&scheduled.account_id).await {
+            Ok(account) => account,
+            Err(DatabaseError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let wallet = state.db.get_wallet(&account.wallet_id).await?;
+        let tenant = match tenant_service::tenant_context(state, &wallet.tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(
+                    tenant_id = %wallet.tenant_id,
+                    error = %e,
+                    "Skipping scheduled send"
+                );
+                continue;
+            }
+        };
+
+        match with_tenant(tenant, execute(state, &scheduled, account)).await {
+            Ok(true) => sent += 1,
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(scheduled_id = %scheduled.id, error = %e, "Scheduled send failed")
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Claim and send one due send; `false` if it wasn't broadcast
+async fn execute(
+    state: &Arc<AppState>,
+    scheduled: &ScheduledTransactionRow,
+    account: AccountRow,
+) -> Result<bool, ScheduledServiceError> {
+    let now = Utc::now().to_rfc3339();
+    if scheduled.expires_at.as_deref().is_some_and(|expires_at| expires_at <= now.as_str()) {
+        if state
+            .db
+            .transition_scheduled_transaction(&scheduled.id, "expired", &now)
+            .await?
+        {
+            tracing::info!(scheduled_id = %scheduled.id, "Scheduled send expired unsent");
+        }
+        return Ok(false);
+    }
+    // Cancelled, or claimed by an overlapping pass
+    if !state
+        .db
+        .transition_scheduled_transaction(&scheduled.id, "executing", &now)
+        .await?
+    {
+        return Ok(false);
+    }
+
+    let seed = match get_seed(state).await {
+        Ok(seed) => seed,
+        Err(WalletServiceError::WalletLocked) => {
+            state
+                .db
+                .finish_scheduled_transaction(
+                    &scheduled.id,
+                    "scheduled",
+                    None,
+                    Some(WAITING_FOR_UNLOCK),
+                    &now,
+                )
+                .await?;
+            return Ok(false);
+        }
+        Err(e) => {
+            let error = e.to_string();
+            state
+                .db
+                .finish_scheduled_transaction(&scheduled.id, "failed", None, Some(&error), &now)
+                .await?;
+            return Ok(false);
+        }
+    };
+
+    let send = SendRequest {
+        chain: scheduled.chain.clone(),
+        from_address: account.address,
+        to_address: scheduled.to_address.clone(),
+        amount: scheduled.amount.clone(),
+        token_address: scheduled.token_address.clone(),
+        drain_all: false,
+        memo: scheduled.memo.clone(),
+        references: scheduled.references(),
+        mev_protect: None,
+        submission_mode: None,
+        bucket: None,
+    };
+    // A failed send may still have reached the network, so it is not retried
+    let result = transaction_service::send_with_seed(state, &seed, send).await;
+    let finished_at = Utc::now().to_rfc3339();
+    match result {
+        Ok(sent) => {
+            state
+                .db
+                .finish_scheduled_transaction(
+                    &scheduled.id,
+                    "sent",
+                    Some(&sent.tx_hash),
+                    None,
+                    &finished_at,
+                )
+                .await?;
+            tracing::info!(
+                scheduled_id = %scheduled.id,
+                tx_hash = %sent.tx_hash,
+                "Scheduled send broadcast"
+            );
+            Ok(true)
+        }
+        // Refused before anything was signed, so it waits for the flag
+        Err(TransactionServiceError::FeatureDisabled(disabled)) => {
+            state
+                .db
+                .finish_scheduled_transaction(
+                    &scheduled.id,
+                    "scheduled",
+                    None,
+                    Some(&disabled.to_string()),
+                    &finished_at,
+                )
+                .await?;
+            Ok(false)
+        }
+        Err(e) => {
+            let error = e.to_string();
+            state
+                .db
+                .finish_scheduled_transaction(
+                    &scheduled.id,
+                    "failed",
+                    None,
+                    Some(&error),
+                    &finished_at,
+                )
+                .await?;
+            Err(e.into())
+        }
+    }
+}
+
+/// Run `execute_due` every `SCHEDULED_CHECK_SECS` for the life of the process
+pub fn spawn_scheduler(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        match state
+            .db
+            .fail_interrupted_scheduled_transactions(&Utc::now().to_rfc3339())
+            .await
+        {
+            Ok(0) => {}
+            Ok(failed) => tracing::warn!(failed, "Scheduled sends interrupted by a restart"),
+            Err(e) => tracing::warn!(error = %e, "Failed to clear interrupted scheduled sends"),
+        }
+
+        let mut ticker = tokio::time::interval(state.config.scheduled_check_interval);
+        loop {
+            ticker.tick().await;
+            match execute_due(&state).await {
+                Ok(0) => {}
+                Ok(sent) => tracing::debug!(sent, "Scheduled sends broadcast"),
+                Err(e) => tracing::warn!(error = %e, "Scheduled send check failed"),
+            }
+        }
+    })
+}
+
+async fn get_owned(
+    state: &Arc<AppState>,
+    id: &str,
+) -> Result<ScheduledTransactionRow, ScheduledServiceError> {
+    let scheduled = match state.db.get_scheduled_transaction(id).await {
+        Ok(scheduled) => scheduled,
+        Err(DatabaseError::NotFound) => return Err(ScheduledServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let account = state.db.get_account(&scheduled.account_id).await?;
+    check_tenant(state, &account).await?;
+    Ok(scheduled)
+}
+
+/// Other tenants' accounts are reported as not found
+async fn check_tenant(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+) -> Result<(), ScheduledServiceError> {
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(ScheduledServiceError::NotFound);
+    }
+    Ok(())
+}
+
+/// Amounts are checked when scheduling so typos don't wait to fail; whether
+/// the balance covers them is only known at execution
+fn check_amount(amount: &str) -> Result<(), ScheduledServiceError> {
+    let value = match price_service::parse_fiat_amount(amount) {
+        Some(fiat) => fiat.value,
+        None => amount.trim().parse::<f64>().unwrap_or(f64::NAN),
+    };
+    if !(value.is_finite() && value > 0.0) {
+        return Err(ScheduledServiceError::InvalidAmount(amount.to_string()));
+    }
+    Ok(())
+}
+
+fn check_schedule(
+    execute_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), ScheduledServiceError> {
+    if execute_at <= now {
+        return Err(ScheduledServiceError::InvalidSchedule(
+            "execute_at must be in the future".to_string(),
+        ));
+    }
+    if execute_at > now + Duration::days(MAX_SCHEDULE_DAYS) {
+        return Err(ScheduledServiceError::InvalidSchedule(format!(
+            "execute_at may be at most {} days ahead",
+            MAX_SCHEDULE_DAYS
+        )));
+    }
+    if expires_at.is_some_and(|expires_at| expires_at <= execute_at) {
+        return Err(ScheduledServiceError::InvalidSchedule(
+            "expires_at must be after execute_at".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_schedule() {
+        let now = Utc::now();
+        let soon = now + Duration::minutes(5);
+
+        assert!(check_schedule(soon, None, now).is_ok());
+        assert!(check_schedule(soon, Some(soon + Duration::hours(1)), now).is_ok());
+        assert!(check_schedule(now, None, now).is_err());
+        assert!(check_schedule(now + Duration::days(366), None, now).is_err());
+        assert!(check_schedule(soon, Some(soon), now).is_err());
+    }
+
+    #[test]
+    fn test_check_amount() {
+        assert!(check_amount("0.5").is_ok());
+        assert!(check_amount("25 USD").is_ok());
+        assert!(check_amount("0").is_err());
+        assert!(check_amount("all").is_err());
+        assert!(check_amount("").is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! Security service - wallet health, backup verification and security scoring
+
+use std::sync::Arc;
+
+use rand::seq::index::sample;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::core::{decrypt_secret, normalize_word, parse_language, wallet_key_material};
+use crate::services::wallet_service;
+use crate::services::user_service::UserServiceError;
+use crate::storage::models::BackupChallengeRow;
+use crate::AppState;
+
+/// Number of word positions asked in a backup quiz
+const QUIZ_WORD_COUNT: usize = 3;
+/// How long a backup quiz stays valid
+const QUIZ_TTL_MINUTES: i64 = 10;
+/// Password age after which rotation is recommended
+const PASSWORD_MAX_AGE_DAYS: i64 = 90;
+
+#[derive(Debug, Error)]
+pub enum SecurityServiceError {
+    #[error("No wallet found")]
+    NoWalletFound,
+    #[error("Backup verification is not available for this wallet")]
+    BackupUnavailable,
+    #[error("Backup challenge not found")]
+    ChallengeNotFound,
+    #[error("Backup challenge expired")]
+    ChallengeExpired,
+    #[error("Invalid password")]
+    InvalidPassword,
+    #[error("Incorrect words")]
+    IncorrectWords,
+    #[error("Keyfile error: {0}")]
+    Keyfile(String),
+    #[error("User error: {0}")]
+    UserError(#[from] UserServiceError),
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+/// Backup quiz challenge response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupChallengeResponse {
+    pub challenge_id: String,
+    /// 1-based word positions the user must provide
+    pub positions: Vec<u32>,
+    pub expires_at: String,
+}
+
+/// Backup quiz answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyBackupRequest {
+    pub challenge_id: String,
+    pub password: String,
+    /// Base64 keyfile contents, for wallets created with one
+    pub keyfile: Option<String>,
+    /// Words in the same order as the challenge positions
+    pub words: Vec<String>,
+}
+
+/// Composite security status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityStatusResponse {
+    pub score: u8,
+    pub backup_confirmed: bool,
+    pub backup_confirmed_at: Option<String>,
+    pub two_factor_enabled: bool,
+    pub password_age_days: Option<i64>,
+    pub recommendations: Vec<String>,
+}
+
+/// Start a backup quiz for the primary wallet
+pub async fn create_backup_challenge(
+    state: &Arc<AppState>,
+) -> Result<BackupChallengeResponse, SecurityServiceError> {
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await
+        .map_err(|e| SecurityServiceError::DatabaseError(e.to_string()))?
+        .ok_or(SecurityServiceError::NoWalletFound)?;
+
+    let word_count = wallet
+        .mnemonic_word_count
+        .filter(|_| wallet.encrypted_entropy.is_some())
+        .ok_or(SecurityServiceError::BackupUnavailable)? as usize;
+
+    let mut positions: Vec<u32> = sample(&mut rand::thread_rng(), word_count, QUIZ_WORD_COUNT)
+        .into_iter()
+        .map(|i| i as u32 + 1)
+        .collect();
+    positions.sort_unstable();
+
+    let challenge = BackupChallengeRow::new(
+        wallet.id,
+        &positions,
+        chrono::Duration::minutes(QUIZ_TTL_MINUTES),
+    );
+
+    state
+        .db
+        .create_backup_challenge(&challenge)
+        .await
+        .map_err(|e| SecurityServiceError::DatabaseError(e.to_string()))?;
+
+    Ok(BackupChallengeResponse {
+        challenge_id: challenge.id,
+        positions,
+        expires_at: challenge.expires_at,
+    })
+}
+
+/// Check quiz answers against the stored mnemonic and mark the backup as confirmed
+pub async fn verify_backup_challenge(
+    state: &Arc<AppState>,
+    request: VerifyBackupRequest,
+) -> Result<(), SecurityServiceError> {
+    let challenge = state
+        .db
+        .get_backup_challenge(&request.challenge_id)
+        .await
+        .map_err(|_| SecurityServiceError::ChallengeNotFound)?;
+
+    // Challenges are single-use regardless of outcome
+    state
+        .db
+        .delete_backup_challenge(&challenge.id)
+        .await
+        .map_err(|e| SecurityServiceError::DatabaseError(e.to_string()))?;
+
+    let expires_at = chrono::DateTime::parse_from_rfc3339(&challenge.expires_at)
+        .map_err(|_| SecurityServiceError::ChallengeExpired)?;
+    if chrono::Utc::now() > expires_at {
+        return Err(SecurityServiceError::ChallengeExpired);
+    }
+
+    let wallet = state
+        .db
+        .get_wallet(&challenge.wallet_id)
+        .await
+        .map_err(|_| SecurityServiceError::NoWalletFound)?;
+    let encrypted_entropy = wallet
+        .encrypted_entropy
+        .as_deref()
+        .ok_or(SecurityServiceError::BackupUnavailable)?;
+
+    let keyfile = request
+        .keyfile
+        .as_deref()
+        .map(wallet_service::decode_keyfile)
+        .transpose()
+        .map_err(|e| SecurityServiceError::Keyfile(e.to_string()))?;
+    let keyfile = keyfile.as_deref().map(Vec::as_slice);
+    wallet_service::check_keyfile(&wallet, keyfile)
+        .map_err(|e| SecurityServiceError::Keyfile(e.to_string()))?;
+
+    let key = wallet_key_material(&request.password, keyfile);
+    let entropy = decrypt_secret(encrypted_entropy, &key)
+        .map_err(|_| SecurityServiceError::InvalidPassword)?;
+    let language = parse_language(&wallet.mnemonic_language)
+        .ok_or(SecurityServiceError::BackupUnavailable)?;
+    let mnemonic = bip39::Mnemonic::from_entropy_in(language, &entropy)
+        .map_err(|_| SecurityServiceError::BackupUnavailable)?;
+    let words: Vec<&str> = mnemonic.words().collect();
+
+    let positions = challenge.positions();
+    if request.words.len() != positions.len() {
+        return Err(SecurityServiceError::IncorrectWords);
+    }
+
+    let all_match = positions.iter().zip(request.words.iter()).all(|(pos, answer)| {
+        words
+            .get(*pos as usize - 1)
+            .is_some_and(|expected| normalize_word(answer) == *expected)
+    });
+    if !all_match {
+        return Err(SecurityServiceError::IncorrectWords);
+    }
+
+    state
+        .db
+        .mark_backup_confirmed(&wallet.id)
+        .await
+        .map_err(|e| SecurityServiceError::DatabaseError(e.to_string()))?;
+
+    tracing::info!(wallet_id = %wallet.id, "Backup confirmed");
+    Ok(())
+}
+
+/// Build the composite security status for a user
+pub async fn get_security_status(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<SecurityStatusResponse, SecurityServiceError> {
+    let user = state.user_service.find_user(user_id).await?;
+
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await
+        .map_err(|e| SecurityServiceError::DatabaseError(e.to_string()))?;
+    let backup_confirmed_at = wallet.and_then(|w| w.backup_confirmed_at);
+
+    let password_changed_at = user.password_changed_at.as_deref().unwrap_or(&user.created_at);
+    let password_age_days = chrono::DateTime::parse_from_rfc3339(password_changed_at)
+        .ok()
+        .map(|changed| (chrono::Utc::now() - changed.with_timezone(&chrono::Utc)).num_days());
+
+    let (score, recommendations) = compute_security_score(
+        backup_confirmed_at.is_some(),
+        user.two_factor_enabled,
+        password_age_days,
+    );
+
+    Ok(SecurityStatusResponse {
+        score,
+        backup_confirmed: backup_confirmed_at.is_some(),
+        backup_confirmed_at,
+        two_factor_enabled: user.two_factor_enabled,
+        password_age_days,
+        recommendations,
+    })
+}
+
+/// Score out of 100: backup 40, 2FA 30, fresh password 30
+fn compute_security_score(
+    backup_confirmed: bool,
+    two_factor_enabled: bool,
+    password_age_days: Option<i64>,
+) -> (u8, Vec<String>) {
+    let mut score = 0u8;
+    let mut recommendations = Vec::new();
+
+    if backup_confirmed {
+        score += 40;
+    } else {
+        recommendations.push("Verify your recovery phrase backup".to_string());
+    }
+
+    if two_factor_enabled {
+        score += 30;
+    } else {
+        recommendations.push("Enable two-factor authentication".to_string());
+    }
+
+    match password_age_days {
+        Some(age) if age <= PASSWORD_MAX_AGE_DAYS => score += 30,
+        _ => recommendations.push(format!(
+            "Change your password (older than {} days)",
+            PASSWORD_MAX_AGE_DAYS
+        )),
+    }
+
+    (score, recommendations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_score() {
+        let (score, recs) = compute_security_score(true, true, Some(10));
+        assert_eq!(score, 100);
+        assert!(recs.is_empty());
+
+        let (score, recs) = compute_security_score(false, false, Some(365));
+        assert_eq!(score, 0);
+        assert_eq!(recs.len(), 3);
+
+        let (score, _) = compute_security_score(true, false, None);
+        assert_eq!(score, 40);
+    }
+}
@@ -0,0 +1,364 @@
+//! Session key service - scoped keys that let dApps sign without prompting
+//!
+//! While the wallet is unlocked, the user authorizes a session key for one
+//! account with a policy: the asset it may send, the most it may send in a
+//! single transaction, the contracts or programs it may pay, and when it
+//! expires. The seed is stored encrypted under the key, which is returned
+//! once and never kept, so the backend can only sign for a session when the
+//! dApp presents its key. Every send is checked against the policy before
+//! the seed is decrypted.
+
+use std::sync::Arc;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::core::{self, Chain, SecureSeed};
+use crate::services::transaction_service::{
+    self, SendRequest, SendResponse, TransactionServiceError,
+};
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, SessionKeyResponse, SessionKeyRow};
+use crate::AppState;
+
+/// Shortest and longest a session may be authorized for
+const MIN_SESSION_SECS: u64 = 60;
+const MAX_SESSION_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Most contracts / programs a single session may pay
+const MAX_TARGETS: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum SessionKeyError {
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid expiry: {0}")]
+    InvalidExpiry(String),
+    #[error("At least one and at most 32 allowed targets are required")]
+    InvalidTargets,
+    #[error("Account not found")]
+    AccountNotFound,
+    #[error("Session key not found")]
+    NotFound,
+    #[error("Session key has expired")]
+    Expired,
+    #[error("Session key has been revoked")]
+    Revoked,
+    #[error("Invalid session key")]
+    InvalidKey,
+    #[error("Not allowed by session policy: {0}")]
+    PolicyViolation(String),
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error(transparent)]
+    SendFailed(#[from] TransactionServiceError),
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for SessionKeyError {
+    fn from(e: DatabaseError) -> Self {
+        SessionKeyError::DatabaseError(e.to_string())
+    }
+}
+
+/// Create session key request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSessionKeyRequest {
+    pub account_id: String,
+    /// Token contract or mint the session may send; the native coin when
+    /// omitted
+    pub token_address: Option<String>,
+    /// Decimal amount allowed per transaction, e.g. `"0.1"`
+    pub max_amount: String,
+    /// Contracts or programs the session may send to
+    pub allowed_targets: Vec<String>,
+    pub expires_in_secs: u64,
+}
+
+/// New session key; the key is only ever returned here
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionKeyWithSecret {
+    pub session: SessionKeyResponse,
+    pub session_key: String,
+}
+
+/// Send signed with a session key, from the session's account in its asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSendRequest {
+    pub session_key: String,
+    pub to_address: String,
+    pub amount: String,
+}
+
+pub async fn list_session_keys(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<SessionKeyResponse>, SessionKeyError> {
+    let sessions = state.db.get_session_keys(user_id).await?;
+    Ok(sessions.into_iter().map(SessionKeyResponse::from).collect())
+}
+
+/// Authorize a session key on one of the current tenant's accounts. Needs
+/// the wallet unlocked, since the seed is encrypted under the new key.
+pub async fn create_session_key(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: CreateSessionKeyRequest,
+) -> Result<SessionKeyWithSecret, SessionKeyError> {
+    let max_amount = parse_amount(&request.max_amount)?;
+    if !(MIN_SESSION_SECS..=MAX_SESSION_SECS).contains(&request.expires_in_secs) {
+        return Err(SessionKeyError::InvalidExpiry(format!(
+            "expires_in_secs must be between {} and {}",
+            MIN_SESSION_SECS, MAX_SESSION_SECS
+        )));
+    }
+    let targets: Vec<String> = request
+        .allowed_targets
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if targets.is_empty() || targets.len() > MAX_TARGETS {
+        return Err(SessionKeyError::InvalidTargets);
+    }
+
+    let account = get_tenant_account(state, &request.account_id).await?;
+    let seed = get_seed(state).await?;
+
+    let session_key = generate_session_key();
+    let encrypted_seed = core::encrypt_secret(seed.as_bytes(), &session_key)
+        .map_err(|e| SessionKeyError::DatabaseError(e.to_string()))?;
+    let expires_at = (chrono::Utc::now()
+        + chrono::Duration::seconds(request.expires_in_secs as i64))
+    .to_rfc3339();
+
+    let session = SessionKeyRow::new(
+        user_id.to_string(),
+        account.id,
+        request.token_address.filter(|t| !t.trim().is_empty()),
+        max_amount,
+        &targets,
+        encrypted_seed,
+        expires_at,
+    );
+    state.db.create_session_key(&session).await?;
+    tracing::info!(user_id = %user_id, session_id = %session.id, "Session key authorized");
+
+    Ok(SessionKeyWithSecret {
+        session: SessionKeyResponse::from(session),
+        session_key,
+    })
+}
+
+/// Revoke a session key; its encrypted seed is discarded
+pub async fn revoke_session_key(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<(), SessionKeyError> {
+    get_owned_session(state, user_id, id).await?;
+    state
+        .db
+        .revoke_session_key(id, &chrono::Utc::now().to_rfc3339())
+        .await?;
+    tracing::info!(user_id = %user_id, session_id = %id, "Session key revoked");
+    Ok(())
+}
+
+/// Send on behalf of a dApp, signed through its session key
+pub async fn send_with_session_key(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: SessionSendRequest,
+) -> Result<SendResponse, SessionKeyError> {
+    let session = get_owned_session(state, user_id, id).await?;
+    let account = get_tenant_account(state, &session.account_id).await?;
+    let chain: Chain = account
+        .chain
+        .parse()
+        .map_err(|_| SessionKeyError::AccountNotFound)?;
+
+    check_policy(&session, chain, &request, chrono::Utc::now())?;
+    let seed = decrypt_session_seed(&session, &request.session_key)?;
+
+    let send = SendRequest {
+        chain: account.chain,
+        from_address: account.address,
+        to_address: request.to_address,
+        amount: request.amount,
+        token_address: session.token_address.clone(),
+        drain_all: false,
+        memo: None,
+        references: Vec::new(),
+        mev_protect: None,
+        submission_mode: None,
+        bucket: None,
+    };
+    let result = transaction_service::send_with_seed(state, &seed, send).await?;
+
+    if let Err(e) = state
+        .db
+        .record_session_key_use(&session.id, &chrono::Utc::now().to_rfc3339())
+        .await
+    {
+        tracing::error!(session_id = %session.id, error = %e, "Failed to record session key use");
+    }
+    Ok(result)
+}
+
+/// Whether `request` is within what the session was authorized for
+fn check_policy(
+    session: &SessionKeyRow,
+    chain: Chain,
+    request: &SessionSendRequest,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), SessionKeyError> {
+    if session.revoked_at.is_some() {
+        return Err(SessionKeyError::Revoked);
+    }
+    let expired = chrono::DateTime::parse_from_rfc3339(&session.expires_at)
+        .map_or(true, |expires_at| expires_at <= now);
+    if expired {
+        return Err(SessionKeyError::Expired);
+    }
+
+    // Ethereum addresses may arrive with or without checksum casing
+    let target = request.to_address.trim();
+    let allowed = session.allowed_targets().iter().any(|t| match chain {
+        Chain::Ethereum => t.eq_ignore_ascii_case(target),
+        Chain::Solana => t == target,
+    });
+    if !allowed {
+        return Err(SessionKeyError::PolicyViolation(format!(
+            "{} is not an allowed target",
+            target
+        )));
+    }
+
+    let amount: f64 = parse_amount(&request.amount)?.parse().unwrap_or(f64::INFINITY);
+    let max_amount: f64 = session.max_amount.parse().unwrap_or(0.0);
+    if amount > max_amount {
+        return Err(SessionKeyError::PolicyViolation(format!(
+            "{} exceeds the per-transaction limit of {}",
+            request.amount.trim(),
+            session.max_amount
+        )));
+    }
+    Ok(())
+}
+
+fn decrypt_session_seed(session: &SessionKeyRow, key: &str) -> Result<SecureSeed, SessionKeyError> {
+    let encrypted = session
+        .encrypted_seed
+        .as_deref()
+        .ok_or(SessionKeyError::Revoked)?;
+    let bytes = core::decrypt_secret(encrypted, key).map_err(|_| SessionKeyError::InvalidKey)?;
+    let seed: [u8; 64] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| SessionKeyError::InvalidKey)?;
+    Ok(SecureSeed::new(seed))
+}
+
+async fn get_tenant_account(
+    state: &Arc<AppState>,
+    account_id: &str,
+) -> Result<AccountRow, SessionKeyError> {
+    let account = state.db.get_account(account_id).await.map_err(|e| match e {
+        DatabaseError::NotFound => SessionKeyError::AccountNotFound,
+        e => e.into(),
+    })?;
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(SessionKeyError::AccountNotFound);
+    }
+    Ok(account)
+}
+
+async fn get_owned_session(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<SessionKeyRow, SessionKeyError> {
+    match state.db.get_session_key(id).await {
+        Ok(session) if session.user_id == user_id => Ok(session),
+        Ok(_) | Err(DatabaseError::NotFound) => Err(SessionKeyError::NotFound),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Plain decimal amounts only; fiat amounts can't be held to a fixed limit
+fn parse_amount(amount: &str) -> Result<String, SessionKeyError> {
+    let trimmed = amount.trim();
+    match trimmed.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(trimmed.to_string()),
+        _ => Err(SessionKeyError::InvalidAmount(amount.to_string())),
+    }
+}
+
+fn generate_session_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("vsk_{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(max_amount: &str, targets: &[&str]) -> SessionKeyRow {
+        let targets: Vec<String> = targets.iter().map(|t| t.to_string()).collect();
+        SessionKeyRow::new(
+            "user".to_string(),
+            "account".to_string(),
+            None,
+            max_amount.to_string(),
+            &targets,
+            Vec::new(),
+            (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+        )
+    }
+
+    fn send(to: &str, amount: &str) -> SessionSendRequest {
+        SessionSendRequest {
+            session_key: String::new(),
+            to_address: to.to_string(),
+            amount: amount.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_policy() {
+        let now = chrono::Utc::now();
+        let dex = "0xAbC0000000000000000000000000000000000001";
+        let s = session("0.5", &[dex]);
+
+        assert!(check_policy(&s, Chain::Ethereum, &send(dex, "0.5"), now).is_ok());
+        assert!(check_policy(&s, Chain::Ethereum, &send(&dex.to_lowercase(), "0.1"), now).is_ok());
+        assert!(matches!(
+            check_policy(&s, Chain::Ethereum, &send(dex, "0.51"), now),
+            Err(SessionKeyError::PolicyViolation(_))
+        ));
+        assert!(matches!(
+            check_policy(&s, Chain::Ethereum, &send("0xdead", "0.1"), now),
+            Err(SessionKeyError::PolicyViolation(_))
+        ));
+        assert!(matches!(
+            check_policy(&s, Chain::Ethereum, &send(dex, "25 USD"), now),
+            Err(SessionKeyError::InvalidAmount(_))
+        ));
+        // Solana addresses are case-sensitive
+        assert!(check_policy(&s, Chain::Solana, &send(&dex.to_lowercase(), "0.1"), now).is_err());
+
+        let later = now + chrono::Duration::hours(2);
+        assert!(matches!(
+            check_policy(&s, Chain::Ethereum, &send(dex, "0.1"), later),
+            Err(SessionKeyError::Expired)
+        ));
+    }
+}
@@ -0,0 +1,383 @@
+//! Share service - expiring read-only links to one resource
+//!
+//! A link's token carries its ID, scope, resource, tenant and expiry, signed
+//! with HMAC-SHA256 under `SHARE_LINK_SECRET`. Anyone holding it
+//! can read the resource until it expires or its owner revokes it, without
+//! signing in. Tokens aren't stored; the row only records the link so it can
+//! be listed and revoked.
+
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::services::multisig_service::{self, MultisigServiceError};
+use crate::services::transaction_service::{self, BalanceResponse, TransactionServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{
+    AccountRow, MultisigTransactionResponse, MultisigWalletResponse, ShareLinkResponse,
+    ShareLinkRow, TransactionResponse,
+};
+use crate::AppState;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Lifetime of a link when none is given
+pub const DEFAULT_EXPIRY_SECS: i64 = 24 * 60 * 60;
+pub const MIN_EXPIRY_SECS: i64 = 60;
+pub const MAX_EXPIRY_SECS: i64 = 30 * 24 * 60 * 60;
+/// History entries in a shared account view
+const SHARED_HISTORY_LIMIT: u32 = 50;
+
+#[derive(Debug, Error)]
+pub enum ShareError {
+    #[error("Invalid share link: {0}")]
+    InvalidRequest(String),
+    #[error("Share link not found")]
+    NotFound,
+    #[error("Invalid share token")]
+    InvalidToken,
+    #[error("Share link doesn't grant access to this view")]
+    WrongScope,
+    #[error("Share link has expired")]
+    Expired,
+    #[error("Share link has been revoked")]
+    Revoked,
+    /// `SHARE_LINK_SECRET` is unset
+    #[error("Share links are not enabled")]
+    Disabled,
+    #[error("Balance error: {0}")]
+    Balance(#[from] TransactionServiceError),
+    #[error("Multi-sig error: {0}")]
+    Multisig(#[from] MultisigServiceError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// What a link grants read access to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareScope {
+    /// An account's balance and history
+    Account,
+    /// A multi-sig and its proposals
+    Multisig,
+}
+
+impl ShareScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShareScope::Account => "account",
+            ShareScope::Multisig => "multisig",
+        }
+    }
+
+    /// Path of the view a link opens
+    fn view_path(self) -> &'static str {
+        match self {
+            ShareScope::Account => "/api/v2/shared/account",
+            ShareScope::Multisig => "/api/v2/shared/multisig",
+        }
+    }
+}
+
+/// Create share link request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateShareRequest {
+    pub scope: ShareScope,
+    /// Account or multi-sig ID
+    pub resource_id: String,
+    /// Seconds until the link expires; a day by default
+    #[serde(default)]
+    pub expires_in_secs: Option<i64>,
+}
+
+/// Signed contents of a share token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ShareClaims {
+    /// Share link ID
+    id: String,
+    scope: ShareScope,
+    resource: String,
+    tenant: String,
+    /// Unix timestamp
+    exp: i64,
+}
+
+/// Access granted by a valid token, for the shared views
+#[derive(Debug, Clone)]
+pub struct ShareGrant {
+    pub link_id: String,
+    pub scope: ShareScope,
+    pub resource_id: String,
+    pub expires_at: i64,
+}
+
+/// A key of its own, so a share token can never pass as anything the JWT
+/// keys sign and rotating those doesn't revoke every link
+fn signing_key(state: &AppState) -> Result<&[u8], ShareError> {
+    state
+        .config
+        .share_link_secret
+        .as_deref()
+        .map(str::as_bytes)
+        .ok_or(ShareError::Disabled)
+}
+
+fn encode_token(key: &[u8], claims: &ShareClaims) -> String {
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims).expect("claims serialize"));
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes any key length");
+    mac.update(payload.as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+    format!("{}.{}", payload, signature)
+}
+
+/// Claims of a token signed under `key`; `None` when it was tampered with
+fn decode_token(key: &[u8], token: &str) -> Option<ShareClaims> {
+    let (payload, signature) = token.split_once('.')?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes any key length");
+    mac.update(payload.as_bytes());
+    mac.verify_slice(&signature).ok()?;
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+}
+
+/// Check that the resource belongs to the current tenant's wallet
+async fn check_resource(
+    state: &Arc<AppState>,
+    scope: ShareScope,
+    resource_id: &str,
+) -> Result<(), ShareError> {
+    let wallet = state.db.get_primary_wallet(&current_tenant_id()).await?;
+    let wallet_id = match scope {
+        ShareScope::Account => state.db.get_account(resource_id).await.map(|a| a.wallet_id),
+        ShareScope::Multisig => state.db.get_multisig(resource_id).await.map(|m| m.wallet_id),
+    };
+    match (wallet, wallet_id) {
+        (Some(wallet), Ok(wallet_id)) if wallet_id == wallet.id => Ok(()),
+        (_, Ok(_)) | (_, Err(DatabaseError::NotFound)) => Err(ShareError::InvalidRequest(format!(
+            "no {} {} in this wallet",
+            scope.as_str(),
+            resource_id
+        ))),
+        (_, Err(e)) => Err(e.into()),
+    }
+}
+
+/// Create a link to one of the wallet's accounts or multi-sigs; the token
+/// is only returned here
+pub async fn create_link(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: CreateShareRequest,
+) -> Result<ShareLinkResponse, ShareError> {
+    let key = signing_key(state)?;
+    let expires_in = request.expires_in_secs.unwrap_or(DEFAULT_EXPIRY_SECS);
+    if !(MIN_EXPIRY_SECS..=MAX_EXPIRY_SECS).contains(&expires_in) {
+        return Err(ShareError::InvalidRequest(format!(
+            "expires_in_secs must be between {} and {}",
+            MIN_EXPIRY_SECS, MAX_EXPIRY_SECS
+        )));
+    }
+    check_resource(state, request.scope, &request.resource_id).await?;
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(expires_in);
+    let row = ShareLinkRow::new(
+        current_tenant_id(),
+        user_id.to_string(),
+        request.scope.as_str().to_string(),
+        request.resource_id,
+        expires_at.to_rfc3339(),
+    );
+    state.db.create_share_link(&row).await?;
+
+    let token = encode_token(
+        key,
+        &ShareClaims {
+            id: row.id.clone(),
+            scope: request.scope,
+            resource: row.resource_id.clone(),
+            tenant: row.tenant_id.clone(),
+            exp: expires_at.timestamp(),
+        },
+    );
+    tracing::info!(link_id = %row.id, scope = %row.scope, resource_id = %row.resource_id, "Share link created");
+
+    Ok(ShareLinkResponse {
+        url: Some(format!("{}?token={}", request.scope.view_path(), token)),
+        token: Some(token),
+        ..ShareLinkResponse::from(row)
+    })
+}
+
+/// The user's links, newest first, without their tokens
+pub async fn list_links(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<ShareLinkResponse>, ShareError> {
+    let links = state.db.get_share_links(user_id).await?;
+    Ok(links.into_iter().map(ShareLinkResponse::from).collect())
+}
+
+/// Revoke one of the user's links; its token stops working at once
+pub async fn revoke_link(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<ShareLinkResponse, ShareError> {
+    if !state.db.revoke_share_link(id, user_id).await? {
+        return Err(ShareError::NotFound);
+    }
+    tracing::info!(link_id = %id, "Share link revoked");
+    Ok(state.db.get_share_link(id).await?.into())
+}
+
+/// Check a token for a view of `scope`: its signature, tenant, expiry and
+/// whether its link is still live
+pub async fn validate(
+    state: &Arc<AppState>,
+    token: &str,
+    scope: ShareScope,
+) -> Result<ShareGrant, ShareError> {
+    let claims = decode_token(signing_key(state)?, token).ok_or(ShareError::InvalidToken)?;
+    if claims.tenant != current_tenant_id() {
+        return Err(ShareError::InvalidToken);
+    }
+    if claims.scope != scope {
+        return Err(ShareError::WrongScope);
+    }
+    if claims.exp <= chrono::Utc::now().timestamp() {
+        return Err(ShareError::Expired);
+    }
+    let link = match state.db.get_share_link(&claims.id).await {
+        Ok(link) => link,
+        Err(DatabaseError::NotFound) => return Err(ShareError::InvalidToken),
+        Err(e) => return Err(e.into()),
+    };
+    if link.revoked_at.is_some() {
+        return Err(ShareError::Revoked);
+    }
+
+    Ok(ShareGrant {
+        link_id: claims.id,
+        scope: claims.scope,
+        resource_id: claims.resource,
+        expires_at: claims.exp,
+    })
+}
+
+/// The account a shared view shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedAccount {
+    pub name: String,
+    pub chain: String,
+    pub address: String,
+}
+
+impl From<AccountRow> for SharedAccount {
+    fn from(row: AccountRow) -> Self {
+        Self {
+            name: row.name,
+            chain: row.chain,
+            address: row.address,
+        }
+    }
+}
+
+/// Balance and latest history of a shared account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedAccountView {
+    pub account: SharedAccount,
+    pub balance: BalanceResponse,
+    /// Newest first
+    pub history: Vec<TransactionResponse>,
+    /// Unix timestamp the link expires at
+    pub expires_at: i64,
+}
+
+/// A shared multi-sig and its proposals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedMultisigView {
+    pub multisig: MultisigWalletResponse,
+    pub proposals: Vec<MultisigTransactionResponse>,
+    /// Unix timestamp the link expires at
+    pub expires_at: i64,
+}
+
+pub async fn account_view(
+    state: &Arc<AppState>,
+    grant: &ShareGrant,
+) -> Result<SharedAccountView, ShareError> {
+    let account = match state.db.get_account(&grant.resource_id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(ShareError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let balance = transaction_service::get_balance(state, &account.chain, &account.address).await?;
+    let history = state
+        .db
+        .get_transactions_page(&account.id, None, SHARED_HISTORY_LIMIT)
+        .await?
+        .into_iter()
+        .map(TransactionResponse::from)
+        .collect();
+
+    Ok(SharedAccountView {
+        account: account.into(),
+        balance,
+        history,
+        expires_at: grant.expires_at,
+    })
+}
+
+pub async fn multisig_view(
+    state: &Arc<AppState>,
+    grant: &ShareGrant,
+) -> Result<SharedMultisigView, ShareError> {
+    let multisig = multisig_service::list_multisigs(state, true)
+        .await?
+        .into_iter()
+        .find(|m| m.id == grant.resource_id)
+        .ok_or(ShareError::NotFound)?;
+    let proposals = multisig_service::get_pending_transactions(state, &multisig.id).await?;
+
+    Ok(SharedMultisigView {
+        multisig,
+        proposals,
+        expires_at: grant.expires_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_tamper_evident() {
+        let claims = ShareClaims {
+            id: "link".to_string(),
+            scope: ShareScope::Account,
+            resource: "account-1".to_string(),
+            tenant: "default".to_string(),
+            exp: 1_900_000_000,
+        };
+        let token = encode_token(b"key", &claims);
+        let decoded = decode_token(b"key", &token).unwrap();
+        assert_eq!(decoded.resource, "account-1");
+        assert_eq!(decoded.scope, ShareScope::Account);
+
+        assert!(decode_token(b"other key", &token).is_none());
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = ShareClaims {
+            resource: "account-2".to_string(),
+            ..claims
+        };
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap());
+        assert!(decode_token(b"key", &format!("{}.{}", payload, signature)).is_none());
+        assert!(decode_token(b"key", "garbage").is_none());
+    }
+}
@@ -0,0 +1,271 @@
+//! SIEM export - streams audit and transaction events to a security team's collector
+//!
+//! Events are queued in the `siem_outbox` table by the same database
+//! transaction as the change they describe, so none is lost to a crash
+//! between the two. The exporter delivers them in order to `SIEM_EXPORT_URL`
+//! and/or appends them to `SIEM_EXPORT_FILE`, and deletes them only once
+//! delivered: every event arrives at least once. A failed batch is retried
+//! with exponential backoff.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::app::SiemFormat;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AuditSeverity, SiemEventRow};
+use crate::AppState;
+
+/// Events delivered per request or file write
+const BATCH_SIZE: i64 = 100;
+/// How long the collector has to accept a batch
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest wait between retries of a failing batch
+const MAX_BACKOFF: Duration = Duration::from_secs(3_600);
+/// Private enterprise number placeholder for syslog structured data
+const SYSLOG_SD_ID: &str = "valtix@32473";
+
+#[derive(Debug, Error)]
+pub enum SiemError {
+    #[error("Delivery failed: {0}")]
+    Delivery(String),
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+impl From<DatabaseError> for SiemError {
+    fn from(e: DatabaseError) -> Self {
+        SiemError::Database(e.to_string())
+    }
+}
+
+/// Deliver queued events every `SIEM_EXPORT_INTERVAL_SECS`
+pub fn spawn_siem_exporter(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.siem.interval);
+        loop {
+            ticker.tick().await;
+            match export_pending(&state).await {
+                Ok(0) => {}
+                Ok(exported) => tracing::debug!(exported, "Security events exported"),
+                Err(e) => tracing::warn!(error = %e, "Security event export failed"),
+            }
+        }
+    })
+}
+
+/// Deliver every due event, batch by batch; returns how many were delivered.
+/// Stops at the first failed batch, which is held back for a retry.
+pub async fn export_pending(state: &Arc<AppState>) -> Result<usize, SiemError> {
+    let siem = &state.config.siem;
+    if !siem.enabled() {
+        return Ok(0);
+    }
+    let http = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+
+    let mut exported = 0;
+    loop {
+        let now = chrono::Utc::now();
+        let batch = state
+            .db
+            .get_due_siem_events(&now.to_rfc3339(), BATCH_SIZE)
+            .await?;
+        if batch.is_empty() {
+            return Ok(exported);
+        }
+        let ids: Vec<String> = batch.iter().map(|event| event.id.clone()).collect();
+
+        if let Err(e) = deliver(&http, siem.url.as_deref(), siem.file.as_deref(), siem.format, &batch).await {
+            let attempts = batch.iter().map(|event| event.attempts).max().unwrap_or(0);
+            let retry_at = now + backoff(siem.interval, attempts);
+            state
+                .db
+                .defer_siem_events(&ids, &e.to_string(), &retry_at.to_rfc3339())
+                .await?;
+            return Err(e);
+        }
+
+        state.db.delete_siem_events(&ids).await?;
+        exported += batch.len();
+        if (batch.len() as i64) < BATCH_SIZE {
+            return Ok(exported);
+        }
+    }
+}
+
+async fn deliver(
+    http: &reqwest::Client,
+    url: Option<&str>,
+    file: Option<&str>,
+    format: SiemFormat,
+    batch: &[SiemEventRow],
+) -> Result<(), SiemError> {
+    if let Some(url) = url {
+        let request = match format {
+            // A JSON array, so collectors can take the batch as one document
+            SiemFormat::Json => {
+                let events: Vec<serde_json::Value> = batch
+                    .iter()
+                    .map(|event| serde_json::from_str(&event.payload).unwrap_or_default())
+                    .collect();
+                http.post(url).json(&events)
+            }
+            _ => http
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(lines(format, batch)),
+        };
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| SiemError::Delivery(e.to_string()))?;
+    }
+
+    if let Some(path) = file {
+        let mut out = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| SiemError::Delivery(format!("{}: {}", path, e)))?;
+        out.write_all(lines(format, batch).as_bytes())
+            .await
+            .map_err(|e| SiemError::Delivery(format!("{}: {}", path, e)))?;
+        out.flush()
+            .await
+            .map_err(|e| SiemError::Delivery(format!("{}: {}", path, e)))?;
+    }
+    Ok(())
+}
+
+/// The batch as newline-terminated lines
+fn lines(format: SiemFormat, batch: &[SiemEventRow]) -> String {
+    batch
+        .iter()
+        .map(|event| format_event(format, event) + "\n")
+        .collect()
+}
+
+/// One event as a single line in `format`
+pub fn format_event(format: SiemFormat, event: &SiemEventRow) -> String {
+    match format {
+        SiemFormat::Json => event.payload.clone(),
+        SiemFormat::Cef => cef_line(event),
+        SiemFormat::Syslog => syslog_line(event),
+    }
+}
+
+fn cef_line(event: &SiemEventRow) -> String {
+    let severity = match event.severity() {
+        AuditSeverity::Info => 3,
+        AuditSeverity::Warning => 6,
+        AuditSeverity::Critical => 9,
+    };
+    let received = chrono::DateTime::parse_from_rfc3339(&event.created_at)
+        .map(|at| at.timestamp_millis())
+        .unwrap_or_default();
+    format!(
+        "CEF:0|Valtix|wallet-backend|{}|{}|{}|{}|rt={} externalId={} cs1Label=tenant cs1={} cs2Label=source cs2={} msg={}",
+        env!("CARGO_PKG_VERSION"),
+        cef_header(&event.event),
+        cef_header(&event.event),
+        severity,
+        received,
+        cef_value(&event.id),
+        cef_value(&event.tenant_id),
+        cef_value(&event.source),
+        cef_value(&event.payload),
+    )
+}
+
+fn cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn cef_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn syslog_line(event: &SiemEventRow) -> String {
+    // Facility authpriv (10)
+    let priority = 10 * 8
+        + match event.severity() {
+            AuditSeverity::Info => 6,
+            AuditSeverity::Warning => 4,
+            AuditSeverity::Critical => 2,
+        };
+    format!(
+        "<{}>1 {} - wallet-backend - {} [{} event=\"{}\" tenant=\"{}\" severity=\"{}\"] {}",
+        priority,
+        event.created_at,
+        event.source,
+        SYSLOG_SD_ID,
+        sd_value(&event.event),
+        sd_value(&event.tenant_id),
+        event.severity,
+        event.payload,
+    )
+}
+
+fn sd_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+/// Wait before the next attempt of a batch that has failed `attempts` times
+fn backoff(interval: Duration, attempts: i64) -> chrono::Duration {
+    let delay = interval
+        .saturating_mul(1 << attempts.clamp(0, 16))
+        .min(MAX_BACKOFF);
+    chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::hours(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::models::AuditEventRow;
+
+    #[test]
+    fn test_event_formats() {
+        let audit = AuditEventRow::new(
+            "acme".to_string(),
+            Some("user-1".to_string()),
+            "wallet.encryption_password_changed",
+            AuditSeverity::Warning,
+            serde_json::json!({ "wallet_id": "w=1|2" }),
+        );
+        let event = SiemEventRow::audit(&audit);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_event(SiemFormat::Json, &event)).unwrap();
+        assert_eq!(json["event"], "wallet.encryption_password_changed");
+        assert_eq!(json["severity"], "warning");
+        assert_eq!(json["tenant_id"], "acme");
+        assert_eq!(json["details"]["wallet_id"], "w=1|2");
+
+        let cef = format_event(SiemFormat::Cef, &event);
+        assert!(cef.starts_with("CEF:0|Valtix|wallet-backend|"), "{}", cef);
+        assert!(cef.contains("|wallet.encryption_password_changed|6|"), "{}", cef);
+        assert!(cef.contains("cs1=acme"), "{}", cef);
+        assert!(cef.contains(r#"w\=1|2"#), "{}", cef);
+
+        let syslog = format_event(SiemFormat::Syslog, &event);
+        assert!(syslog.starts_with("<84>1 "), "{}", syslog);
+        assert!(syslog.contains(r#"event="wallet.encryption_password_changed""#), "{}", syslog);
+
+        assert_eq!(backoff(Duration::from_secs(10), 2), chrono::Duration::seconds(40));
+        assert_eq!(backoff(Duration::from_secs(10), 40), chrono::Duration::hours(1));
+    }
+}
@@ -0,0 +1,310 @@
+//! Wallet sign-in service - EIP-4361 (SIWE) and Sign-In With Solana
+//!
+//! The server issues a message with a single-use nonce; the client signs it
+//! with the wallet and sends the signature back. A verified address can be
+//! linked to the signed-in user, after which signing alone logs them in.
+
+use std::sync::Arc;
+
+use chrono::{SecondsFormat, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::{ethereum, solana};
+use crate::core::Chain;
+use crate::services::user_service::UserServiceError;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{
+    LoginResponse, SignInChallengeRow, UserAddressResponse, UserAddressRow,
+};
+use crate::AppState;
+
+/// How long an issued message can be signed and redeemed
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+
+/// Statement shown to the user in the wallet prompt
+const STATEMENT: &str = "Sign in to Valtix.";
+
+#[derive(Debug, Error)]
+pub enum SignInServiceError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Sign-in challenge not found or expired")]
+    InvalidChallenge,
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("Address is not linked to an account")]
+    NotLinked,
+    #[error("Address is already linked to an account")]
+    AlreadyLinked,
+    #[error("Linked address not found")]
+    NotFound,
+    #[error("User error: {0}")]
+    UserError(#[from] UserServiceError),
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for SignInServiceError {
+    fn from(e: DatabaseError) -> Self {
+        SignInServiceError::DatabaseError(e.to_string())
+    }
+}
+
+/// Sign-in message request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignInChallengeRequest {
+    pub chain: String,
+    pub address: String,
+}
+
+/// Message for the wallet to sign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignInChallengeResponse {
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: String,
+}
+
+/// Signed message, used both to log in and to link an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignInRequest {
+    pub nonce: String,
+    /// Hex (Ethereum `personal_sign`) or base58 (Solana `signMessage`)
+    pub signature: String,
+}
+
+/// Issue a sign-in message for an address
+pub async fn create_challenge(
+    state: &Arc<AppState>,
+    request: SignInChallengeRequest,
+) -> Result<SignInChallengeResponse, SignInServiceError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| SignInServiceError::InvalidChain(request.chain.clone()))?;
+    let address = normalize_address(chain, &request.address)?;
+
+    let now = Utc::now();
+    let expires_at = now + chrono::Duration::minutes(CHALLENGE_TTL_MINUTES);
+    let nonce = generate_nonce();
+    let message = sign_in_message(
+        state,
+        chain,
+        &address,
+        &nonce,
+        &now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        &expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+
+    state
+        .db
+        .delete_expired_sign_in_challenges(&now.to_rfc3339())
+        .await?;
+    let challenge = SignInChallengeRow::new(
+        nonce,
+        current_tenant_id(),
+        chain.to_string(),
+        address,
+        message,
+        expires_at,
+    );
+    state.db.create_sign_in_challenge(&challenge).await?;
+
+    Ok(SignInChallengeResponse {
+        nonce: challenge.nonce,
+        message: challenge.message,
+        expires_at: challenge.expires_at,
+    })
+}
+
+/// Log in with a signed message from a linked address; returns the same
+/// session as a password login
+pub async fn login(
+    state: &Arc<AppState>,
+    request: SignInRequest,
+    device_info: Option<String>,
+    ip_address: Option<String>,
+) -> Result<(LoginResponse, String), SignInServiceError> {
+    let challenge = verify(state, request).await?;
+
+    let linked = state
+        .db
+        .find_user_address(&challenge.chain, &challenge.address)
+        .await?
+        .ok_or(SignInServiceError::NotLinked)?;
+
+    let session = state
+        .user_service
+        .login_verified(&linked.user_id, device_info, ip_address)
+        .await
+        .map_err(|e| match e {
+            // Linked to a user of another tenant, or a deactivated one
+            UserServiceError::InvalidCredentials => SignInServiceError::NotLinked,
+            e => SignInServiceError::UserError(e),
+        })?;
+
+    tracing::info!(
+        user_id = %linked.user_id,
+        chain = %challenge.chain,
+        "User signed in with wallet"
+    );
+    Ok(session)
+}
+
+/// Link the address that signed a message to the user
+pub async fn link_address(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: SignInRequest,
+) -> Result<UserAddressResponse, SignInServiceError> {
+    let challenge = verify(state, request).await?;
+
+    let linked = UserAddressRow::new(user_id.to_string(), challenge.chain, challenge.address);
+    state
+        .db
+        .create_user_address(&linked)
+        .await
+        .map_err(|e| match e {
+            DatabaseError::AlreadyExists => SignInServiceError::AlreadyLinked,
+            e => e.into(),
+        })?;
+
+    tracing::info!(user_id = %user_id, chain = %linked.chain, "Wallet address linked");
+    Ok(UserAddressResponse::from(linked))
+}
+
+pub async fn list_addresses(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<UserAddressResponse>, SignInServiceError> {
+    let addresses = state.db.get_user_addresses(user_id).await?;
+    Ok(addresses.into_iter().map(UserAddressResponse::from).collect())
+}
+
+pub async fn unlink_address(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<(), SignInServiceError> {
+    state
+        .db
+        .delete_user_address(id, user_id)
+        .await
+        .map_err(|e| match e {
+            DatabaseError::NotFound => SignInServiceError::NotFound,
+            e => e.into(),
+        })
+}
+
+/// Redeem a challenge and check its signature
+async fn verify(
+    state: &Arc<AppState>,
+    request: SignInRequest,
+) -> Result<SignInChallengeRow, SignInServiceError> {
+    // Challenges are single-use regardless of outcome
+    let challenge = state
+        .db
+        .take_sign_in_challenge(&request.nonce, &current_tenant_id())
+        .await
+        .map_err(|e| match e {
+            DatabaseError::NotFound => SignInServiceError::InvalidChallenge,
+            e => e.into(),
+        })?;
+
+    let expires_at = chrono::DateTime::parse_from_rfc3339(&challenge.expires_at)
+        .map_err(|_| SignInServiceError::InvalidChallenge)?;
+    if Utc::now() > expires_at {
+        return Err(SignInServiceError::InvalidChallenge);
+    }
+
+    match challenge.chain.parse::<Chain>() {
+        Ok(Chain::Ethereum) => {
+            let signer = ethereum::recover_message_signer(&challenge.message, &request.signature)
+                .map_err(|e| SignInServiceError::InvalidSignature(e.to_string()))?;
+            if signer != challenge.address {
+                return Err(SignInServiceError::InvalidSignature(
+                    "not signed by this address".to_string(),
+                ));
+            }
+        }
+        Ok(Chain::Solana) => {
+            solana::verify_message_signature(
+                &challenge.address,
+                &challenge.message,
+                &request.signature,
+            )
+            .map_err(|e| SignInServiceError::InvalidSignature(e.to_string()))?;
+        }
+        Err(_) => return Err(SignInServiceError::InvalidChain(challenge.chain)),
+    }
+
+    Ok(challenge)
+}
+
+/// Canonical address form: EIP-55 checksum on Ethereum, base58 on Solana
+fn normalize_address(chain: Chain, address: &str) -> Result<String, SignInServiceError> {
+    match chain {
+        Chain::Ethereum if ethereum::validate_address(address.trim()) => {
+            Ok(ethereum::checksum_address(address.trim()))
+        }
+        Chain::Ethereum => Err(SignInServiceError::InvalidAddress(address.to_string())),
+        Chain::Solana => solana::normalize_address(address)
+            .map_err(|_| SignInServiceError::InvalidAddress(address.to_string())),
+    }
+}
+
+/// EIP-4361 message; the Solana form follows the same layout without a chain ID
+fn sign_in_message(
+    state: &Arc<AppState>,
+    chain: Chain,
+    address: &str,
+    nonce: &str,
+    issued_at: &str,
+    expires_at: &str,
+) -> String {
+    let uri = &state.config.sign_in_uri;
+    let domain = reqwest::Url::parse(uri)
+        .ok()
+        .and_then(|url| {
+            let host = url.host_str()?.to_string();
+            Some(match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            })
+        })
+        .unwrap_or_else(|| uri.clone());
+
+    let (account, chain_id) = match chain {
+        Chain::Ethereum => (
+            "Ethereum",
+            format!("Chain ID: {}\n", state.config.eth_chain_id),
+        ),
+        Chain::Solana => ("Solana", String::new()),
+    };
+
+    format!(
+        "{domain} wants you to sign in with your {account} account:\n\
+         {address}\n\
+         \n\
+         {STATEMENT}\n\
+         \n\
+         URI: {uri}\n\
+         Version: 1\n\
+         {chain_id}\
+         Nonce: {nonce}\n\
+         Issued At: {issued_at}\n\
+         Expiration Time: {expires_at}"
+    )
+}
+
+/// 32 alphanumeric characters, as EIP-4361 nonces must be
+fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
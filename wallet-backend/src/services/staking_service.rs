@@ -0,0 +1,399 @@
+//! Staking service - liquid staking through SPL stake pools
+//!
+//! Depositing SOL into a pool mints its liquid staking token (LST), whose
+//! exchange rate against SOL rises as the pool earns staking rewards.
+//! Rates and APYs are read from the pools' on-chain state. Deposits and
+//! withdrawals are recorded in history as `stake` and `unstake`, and LSTs
+//! held by an account are valued in SOL alongside its balance.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::solana::{liquid_staking_token, LiquidStakingToken, LIQUID_STAKING_TOKENS};
+use crate::chains::{ChainClientError, ChainClients, StakePoolState};
+use crate::core::{format_units, parse_units, Chain};
+use crate::services::feature_flag_service::{self, FeatureDisabled, FeatureFlag};
+use crate::services::transaction_service::TokenBalanceResponse;
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, TransactionResponse, TransactionRow};
+use crate::AppState;
+
+/// SOL and every listed LST have 9 decimals
+const DECIMALS: u32 = 9;
+
+/// Solana epochs last about two days
+const EPOCHS_PER_YEAR: f64 = 182.5;
+
+#[derive(Debug, Error)]
+pub enum StakingServiceError {
+    #[error("Not found")]
+    NotFound,
+    #[error("Staking is only available on solana, not {0}")]
+    InvalidChain(String),
+    #[error("Unknown liquid staking token: {0}")]
+    UnknownToken(String),
+    #[error("{0} can't be staked or unstaked here")]
+    Unsupported(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("{0}")]
+    FeatureDisabled(#[from] FeatureDisabled),
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// A liquid staking token and the current state of its pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakePoolResponse {
+    pub symbol: String,
+    pub name: String,
+    pub mint: String,
+    /// `None` for tokens that are only tracked
+    pub stake_pool: Option<String>,
+    /// SOL one token redeems for
+    pub exchange_rate: Option<f64>,
+    /// Yearly yield, compounded from the rate's growth over the last epoch
+    pub apy: Option<f64>,
+    /// SOL under management
+    pub total_staked: Option<String>,
+    pub deposit_fee_bps: Option<u32>,
+    pub withdrawal_fee_bps: Option<u32>,
+    /// Set when the pool's state couldn't be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Stake or unstake request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeRequest {
+    pub account_id: String,
+    /// Symbol or mint of the liquid staking token
+    pub token: String,
+    /// SOL to deposit, or tokens to redeem
+    pub amount: String,
+}
+
+/// A deposit or withdrawal sent to a stake pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeResponse {
+    pub symbol: String,
+    pub mint: String,
+    pub stake_pool: String,
+    /// Tokens (stake) or SOL (unstake) expected back at the current rate,
+    /// after the pool's fee
+    pub estimated_received: String,
+    pub transaction: TransactionResponse,
+}
+
+/// LSTs held by an account, valued in SOL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidStakingSummary {
+    pub positions: Vec<LiquidStakingPosition>,
+    /// Sum of the positions that could be valued
+    pub total_sol_value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidStakingPosition {
+    pub symbol: String,
+    pub mint: String,
+    pub balance: String,
+    /// `None` for tokens without a readable pool
+    pub sol_value: Option<String>,
+    pub exchange_rate: Option<f64>,
+}
+
+/// Every known liquid staking token with its pool's rate and APY
+pub async fn list_pools(state: &Arc<AppState>) -> Vec<StakePoolResponse> {
+    let mut pools = Vec::with_capacity(LIQUID_STAKING_TOKENS.len());
+    for token in LIQUID_STAKING_TOKENS {
+        let mut response = StakePoolResponse {
+            symbol: token.symbol.to_string(),
+            name: token.name.to_string(),
+            mint: token.mint.to_string(),
+            stake_pool: token.stake_pool.map(str::to_string),
+            exchange_rate: None,
+            apy: None,
+            total_staked: None,
+            deposit_fee_bps: None,
+            withdrawal_fee_bps: None,
+            error: None,
+        };
+        if let Some(pool) = token.stake_pool {
+            match state.chain_clients().solana.stake_pool(pool).await {
+                Ok(pool) => {
+                    response.exchange_rate = exchange_rate(pool.total_lamports, pool.pool_token_supply);
+                    response.apy = apy(&pool);
+                    response.total_staked = Some(format_units(pool.total_lamports as u128, DECIMALS));
+                    response.deposit_fee_bps = Some(pool.sol_deposit_fee_bps);
+                    response.withdrawal_fee_bps = Some(pool.sol_withdrawal_fee_bps);
+                }
+                Err(e) => {
+                    tracing::warn!(pool = %pool, error = %e, "Reading stake pool failed");
+                    response.error = Some(e.to_string());
+                }
+            }
+        }
+        pools.push(response);
+    }
+    pools
+}
+
+/// Deposit SOL from an account into a pool for its token
+pub async fn stake(
+    state: &Arc<AppState>,
+    request: StakeRequest,
+) -> Result<StakeResponse, StakingServiceError> {
+    let (account, token, pool_address) = prepare(state, &request).await?;
+    let lamports = amount(&request.amount)?;
+
+    let client = state.account_clients(&account).solana;
+    let pool = client.stake_pool(pool_address).await?;
+    let received = lamports as u128 * (10_000 - pool.sol_deposit_fee_bps.min(10_000)) as u128 / 10_000;
+    let received = convert(received, pool.pool_token_supply, pool.total_lamports);
+
+    let seed = get_seed(state).await?;
+    let signature = client
+        .stake_pool_deposit(&seed, &account.derivation_path, pool_address, lamports)
+        .await?;
+
+    let row = record(
+        state,
+        &account,
+        signature,
+        "stake",
+        pool_address,
+        &request.amount,
+        None,
+    )
+    .await?;
+    Ok(StakeResponse {
+        symbol: token.symbol.to_string(),
+        mint: token.mint.to_string(),
+        stake_pool: pool_address.to_string(),
+        estimated_received: format_units(received, DECIMALS),
+        transaction: row.into(),
+    })
+}
+
+/// Redeem an account's tokens for SOL from the pool's reserve
+pub async fn unstake(
+    state: &Arc<AppState>,
+    request: StakeRequest,
+) -> Result<StakeResponse, StakingServiceError> {
+    let (account, token, pool_address) = prepare(state, &request).await?;
+    let pool_tokens = amount(&request.amount)?;
+
+    let client = state.account_clients(&account).solana;
+    let pool = client.stake_pool(pool_address).await?;
+    let received = convert(pool_tokens as u128, pool.total_lamports, pool.pool_token_supply);
+    let received = received * (10_000 - pool.sol_withdrawal_fee_bps.min(10_000)) as u128 / 10_000;
+
+    let seed = get_seed(state).await?;
+    let signature = client
+        .stake_pool_withdraw(&seed, &account.derivation_path, pool_address, pool_tokens)
+        .await?;
+
+    let row = record(
+        state,
+        &account,
+        signature,
+        "unstake",
+        pool_address,
+        &request.amount,
+        Some(token.mint),
+    )
+    .await?;
+    Ok(StakeResponse {
+        symbol: token.symbol.to_string(),
+        mint: token.mint.to_string(),
+        stake_pool: pool_address.to_string(),
+        estimated_received: format_units(received, DECIMALS),
+        transaction: row.into(),
+    })
+}
+
+/// The LSTs among an account's token balances, valued in SOL; `None` when
+/// it holds none
+pub async fn balance_summary(
+    clients: &ChainClients,
+    chain: Chain,
+    tokens: &[TokenBalanceResponse],
+) -> Option<LiquidStakingSummary> {
+    if chain != Chain::Solana {
+        return None;
+    }
+    let mut positions = Vec::new();
+    let mut total: u128 = 0;
+    for balance in tokens {
+        let Some(token) = liquid_staking_token(&balance.address) else {
+            continue;
+        };
+        let held: u128 = balance.balance.parse().unwrap_or(0);
+        if held == 0 {
+            continue;
+        }
+        let pool = match token.stake_pool {
+            Some(pool) => match clients.solana.stake_pool(pool).await {
+                Ok(pool) => Some(pool),
+                Err(e) => {
+                    tracing::warn!(pool = %pool, error = %e, "Reading stake pool failed");
+                    None
+                }
+            },
+            None => None,
+        };
+        let sol_value = pool
+            .as_ref()
+            .map(|pool| convert(held, pool.total_lamports, pool.pool_token_supply));
+        total += sol_value.unwrap_or(0);
+        positions.push(LiquidStakingPosition {
+            symbol: token.symbol.to_string(),
+            mint: token.mint.to_string(),
+            balance: format_units(held, balance.decimals as u32),
+            sol_value: sol_value.map(|value| format_units(value, DECIMALS)),
+            exchange_rate: pool
+                .as_ref()
+                .and_then(|pool| exchange_rate(pool.total_lamports, pool.pool_token_supply)),
+        });
+    }
+    if positions.is_empty() {
+        return None;
+    }
+    Some(LiquidStakingSummary {
+        positions,
+        total_sol_value: format_units(total, DECIMALS),
+    })
+}
+
+/// Check the request's account, token and the Solana send switch
+async fn prepare(
+    state: &Arc<AppState>,
+    request: &StakeRequest,
+) -> Result<(AccountRow, &'static LiquidStakingToken, &'static str), StakingServiceError> {
+    let account = tenant_account(state, &request.account_id).await?;
+    if account.chain != Chain::Solana.to_string() {
+        return Err(StakingServiceError::InvalidChain(account.chain));
+    }
+    let token = LIQUID_STAKING_TOKENS
+        .iter()
+        .find(|token| token.mint == request.token || token.symbol.eq_ignore_ascii_case(&request.token))
+        .ok_or_else(|| StakingServiceError::UnknownToken(request.token.clone()))?;
+    let pool = token
+        .stake_pool
+        .ok_or_else(|| StakingServiceError::Unsupported(token.symbol.to_string()))?;
+    feature_flag_service::check(state, FeatureFlag::Sends(Chain::Solana)).await?;
+    Ok((account, token, pool))
+}
+
+/// Add a deposit or withdrawal to the account's history
+async fn record(
+    state: &Arc<AppState>,
+    account: &AccountRow,
+    signature: String,
+    tx_type: &str,
+    pool: &str,
+    amount: &str,
+    token_address: Option<&str>,
+) -> Result<TransactionRow, StakingServiceError> {
+    let mut row = TransactionRow::new(
+        account.id.clone(),
+        account.chain.clone(),
+        signature,
+        tx_type.to_string(),
+        Some(account.address.clone()),
+        Some(pool.to_string()),
+        Some(amount.trim().to_string()),
+        token_address.map(str::to_string),
+        "confirmed".to_string(),
+        None,
+        Some(Utc::now().to_rfc3339()),
+    );
+    row.token_decimals = token_address.map(|_| i64::from(DECIMALS));
+    state.db.upsert_transaction(&row).await?;
+    Ok(row)
+}
+
+/// An account of the current tenant; others are reported as not found
+async fn tenant_account(
+    state: &Arc<AppState>,
+    account_id: &str,
+) -> Result<AccountRow, StakingServiceError> {
+    let account = match state.db.get_account(account_id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(StakingServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(StakingServiceError::NotFound);
+    }
+    Ok(account)
+}
+
+/// A positive amount with at most 9 decimals, in base units
+fn amount(amount: &str) -> Result<u64, StakingServiceError> {
+    parse_units(amount, DECIMALS)
+        .filter(|units| *units > 0)
+        .and_then(|units| u64::try_from(units).ok())
+        .ok_or_else(|| StakingServiceError::InvalidAmount(amount.trim().to_string()))
+}
+
+/// `amount * numerator / denominator`, zero for an empty pool
+fn convert(amount: u128, numerator: u64, denominator: u64) -> u128 {
+    match denominator {
+        0 => 0,
+        _ => amount * numerator as u128 / denominator as u128,
+    }
+}
+
+fn exchange_rate(total_lamports: u64, pool_token_supply: u64) -> Option<f64> {
+    (pool_token_supply > 0).then(|| total_lamports as f64 / pool_token_supply as f64)
+}
+
+/// The rate's growth over the last epoch, compounded over a year
+fn apy(pool: &StakePoolState) -> Option<f64> {
+    let now = exchange_rate(pool.total_lamports, pool.pool_token_supply)?;
+    let before = exchange_rate(pool.last_epoch_total_lamports, pool.last_epoch_pool_token_supply)?;
+    if before <= 0.0 || now < before {
+        return None;
+    }
+    Some((now / before).powf(EPOCHS_PER_YEAR) - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_and_apy() {
+        let pool = StakePoolState {
+            total_lamports: 1_150_000_000,
+            pool_token_supply: 1_000_000_000,
+            last_epoch_total_lamports: 1_149_500_000,
+            last_epoch_pool_token_supply: 1_000_000_000,
+            ..Default::default()
+        };
+        assert_eq!(exchange_rate(pool.total_lamports, pool.pool_token_supply), Some(1.15));
+        let apy = apy(&pool).unwrap();
+        assert!((0.08..0.09).contains(&apy), "{}", apy);
+
+        // A pool that hasn't seen an epoch yet has no yield to show
+        assert_eq!(super::apy(&StakePoolState::default()), None);
+
+        assert_eq!(convert(2_000_000_000, 1_150_000_000, 1_000_000_000), 2_300_000_000);
+        assert_eq!(convert(1, 1, 0), 0);
+        assert_eq!(amount("1.5").unwrap(), 1_500_000_000);
+        assert!(amount("0").is_err());
+        assert!(amount("0.0000000001").is_err());
+    }
+}
@@ -0,0 +1,428 @@
+//! Statement service - monthly account statements
+//!
+//! A statement lists one account's recorded transactions for a calendar
+//! month with their fees and fiat values. History is only what the wallet has
+//! recorded, so balances are worked back from the current on-chain native
+//! balance: the closing balance is it less the native flows recorded after
+//! the month, the opening balance the closing one less the month's own.
+//! Token transfers are listed but only their fees move the native balance.
+
+use std::sync::Arc;
+
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfLayerReference};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::ChainClientError;
+use crate::core::Chain;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AccountRow, TransactionRow};
+use crate::AppState;
+
+// A4 portrait
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const LINE_HEIGHT: f32 = 5.0;
+
+#[derive(Debug, Error)]
+pub enum StatementError {
+    #[error("Account not found")]
+    NotFound,
+    #[error("Invalid month: {0}")]
+    InvalidMonth(String),
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Rendering failed: {0}")]
+    Render(String),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Pdf,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Statement {
+    pub account_id: String,
+    pub account_name: String,
+    pub chain: String,
+    pub address: String,
+    /// `YYYY-MM`
+    pub month: String,
+    /// Unit of every balance, total and fee
+    pub symbol: String,
+    pub opening_balance: String,
+    pub closing_balance: String,
+    /// Native coin received during the month
+    pub total_in: String,
+    /// Native coin sent during the month, excluding fees
+    pub total_out: String,
+    pub total_fees: String,
+    pub entries: Vec<StatementEntry>,
+    pub generated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementEntry {
+    pub date: String,
+    pub signature: String,
+    pub tx_type: String,
+    pub status: String,
+    /// The other side of the transfer
+    pub counterparty: Option<String>,
+    /// Signed from the account's side; in `token_address` units when set
+    pub amount: Option<String>,
+    pub token_address: Option<String>,
+    /// Network fee paid by the account
+    pub fee: Option<String>,
+    /// Value of the amount at the day's price
+    pub fiat_value: Option<String>,
+    pub fiat_currency: Option<String>,
+}
+
+/// How a transaction moved the account's native balance
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Flow {
+    received: f64,
+    sent: f64,
+    fee: f64,
+}
+
+impl Flow {
+    fn net(&self) -> f64 {
+        self.received - self.sent - self.fee
+    }
+}
+
+/// Build the statement for `month` (`YYYY-MM`) of one of the tenant's accounts
+pub async fn statement(
+    state: &Arc<AppState>,
+    account_id: &str,
+    month: &str,
+) -> Result<Statement, StatementError> {
+    let (start, end) = month_bounds(month)?;
+    let account = owned_account(state, account_id).await?;
+    let chain: Chain = account
+        .chain
+        .parse()
+        .map_err(|_| StatementError::InvalidChain(account.chain.clone()))?;
+
+    let balance = state.account_clients(&account).get(chain).balance(&account.address).await?;
+    let current: f64 = balance.native_balance.parse().unwrap_or(0.0);
+    let rows = state.db.get_all_transactions(&account.id).await?;
+
+    // Timestamps are RFC 3339, so day strings bound them lexically
+    let (start, end) = (start.to_string(), end.to_string());
+    let mut after = 0.0;
+    let mut totals = Flow::default();
+    let mut entries = Vec::new();
+    for tx in &rows {
+        let flow = flow(chain, &account.address, tx);
+        let date = tx.timestamp.as_deref().unwrap_or(&tx.created_at);
+        if date >= end.as_str() {
+            after += flow.net();
+        } else if date >= start.as_str() {
+            totals.received += flow.received;
+            totals.sent += flow.sent;
+            totals.fee += flow.fee;
+            entries.push(entry(chain, &account.address, tx, flow));
+        }
+    }
+    let closing = current - after;
+
+    Ok(Statement {
+        account_id: account.id,
+        account_name: account.name,
+        chain: account.chain,
+        address: account.address,
+        month: month.to_string(),
+        symbol: balance.native_symbol,
+        opening_balance: format_amount(closing - totals.net()),
+        closing_balance: format_amount(closing),
+        total_in: format_amount(totals.received),
+        total_out: format_amount(totals.sent),
+        total_fees: format_amount(totals.fee),
+        entries,
+        generated_at: Utc::now().to_rfc3339(),
+    })
+}
+
+/// Render a statement as an A4 PDF
+pub fn render_pdf(statement: &Statement) -> Result<Vec<u8>, StatementError> {
+    let title = format!("Statement {} - {}", statement.month, statement.account_name);
+    let (doc, page, layer) =
+        PdfDocument::new(printable(&title), Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Statement");
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| StatementError::Render(e.to_string()))?;
+    let bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| StatementError::Render(e.to_string()))?;
+
+    let mut writer = PageWriter {
+        layer: doc.get_page(page).get_layer(layer),
+        y: PAGE_HEIGHT - MARGIN,
+    };
+    writer.line(&bold, 16.0, &[(0.0, "Account statement")]);
+    writer.gap();
+    let symbol = &statement.symbol;
+    let summary = [
+        ("Account", statement.account_name.clone()),
+        ("Address", format!("{} ({})", statement.address, statement.chain)),
+        ("Period", statement.month.clone()),
+        ("Opening balance", format!("{} {}", statement.opening_balance, symbol)),
+        ("Received", format!("{} {}", statement.total_in, symbol)),
+        ("Sent", format!("{} {}", statement.total_out, symbol)),
+        ("Fees", format!("{} {}", statement.total_fees, symbol)),
+        ("Closing balance", format!("{} {}", statement.closing_balance, symbol)),
+    ];
+    for (label, value) in &summary {
+        writer.line(&font, 10.0, &[(0.0, label), (40.0, value)]);
+    }
+    writer.gap();
+
+    const COLUMNS: [f32; 6] = [0.0, 22.0, 60.0, 100.0, 132.0, 158.0];
+    let header = ["Date", "Transaction", "Counterparty", "Amount", "Fee", "Value"];
+    let header: Vec<_> = COLUMNS.iter().copied().zip(header).collect();
+    writer.line(&bold, 9.0, &header);
+    if statement.entries.is_empty() {
+        writer.line(&font, 9.0, &[(0.0, "No transactions this month")]);
+    }
+    for entry in &statement.entries {
+        if writer.y < MARGIN + LINE_HEIGHT {
+            let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Statement");
+            writer = PageWriter {
+                layer: doc.get_page(page).get_layer(layer),
+                y: PAGE_HEIGHT - MARGIN,
+            };
+            writer.line(&bold, 9.0, &header);
+        }
+        let amount = match (&entry.amount, &entry.token_address) {
+            (Some(amount), Some(token)) => format!("{} {}", amount, shorten(token)),
+            (Some(amount), None) => format!("{} {}", amount, symbol),
+            (None, _) => String::new(),
+        };
+        let value = match (&entry.fiat_value, &entry.fiat_currency) {
+            (Some(value), Some(currency)) => format!("{} {}", value, currency),
+            _ => String::new(),
+        };
+        let mut kind = shorten(&entry.signature);
+        if entry.status != "confirmed" {
+            kind = format!("{} ({})", kind, entry.status);
+        }
+        let cells = [
+            entry.date.get(..10).unwrap_or(&entry.date).to_string(),
+            kind,
+            entry.counterparty.as_deref().map(shorten).unwrap_or_default(),
+            amount,
+            entry.fee.clone().unwrap_or_default(),
+            value,
+        ];
+        let cells: Vec<_> = COLUMNS
+            .iter()
+            .copied()
+            .zip(cells.iter().map(String::as_str))
+            .collect();
+        writer.line(&font, 8.0, &cells);
+    }
+
+    writer.gap();
+    let footer = format!("Generated {}", statement.generated_at);
+    writer.line(&font, 7.0, &[(0.0, &footer)]);
+    doc.save_to_bytes()
+        .map_err(|e| StatementError::Render(e.to_string()))
+}
+
+struct PageWriter {
+    layer: PdfLayerReference,
+    /// Baseline of the next line, from the bottom of the page
+    y: f32,
+}
+
+impl PageWriter {
+    /// Write one line of cells, each at an offset from the left margin
+    fn line(&mut self, font: &IndirectFontRef, size: f32, cells: &[(f32, &str)]) {
+        for (x, text) in cells {
+            self.layer
+                .use_text(printable(text), size, Mm(MARGIN + x), Mm(self.y), font);
+        }
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn gap(&mut self) {
+        self.y -= LINE_HEIGHT;
+    }
+}
+
+/// First day of the month and of the next one
+fn month_bounds(month: &str) -> Result<(NaiveDate, NaiveDate), StatementError> {
+    let invalid = || StatementError::InvalidMonth(format!("'{}' is not a YYYY-MM month", month));
+    if month.len() != 7 {
+        return Err(invalid());
+    }
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| invalid())?;
+    let today = Utc::now().date_naive();
+    if (start.year(), start.month()) > (today.year(), today.month()) {
+        return Err(StatementError::InvalidMonth(format!("{} hasn't started", month)));
+    }
+    let end = start.checked_add_months(Months::new(1)).ok_or_else(invalid)?;
+    Ok((start, end))
+}
+
+async fn owned_account(state: &Arc<AppState>, id: &str) -> Result<AccountRow, StatementError> {
+    let account = match state.db.get_account(id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(StatementError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(StatementError::NotFound);
+    }
+    Ok(account)
+}
+
+/// Failed transactions move nothing but still cost their sender the fee
+fn flow(chain: Chain, address: &str, tx: &TransactionRow) -> Flow {
+    let is = |other: &Option<String>| other.as_deref().is_some_and(|o| o.eq_ignore_ascii_case(address));
+    let (outgoing, incoming) = (is(&tx.from_address), is(&tx.to_address));
+    let amount = match (&tx.token_address, tx.status.as_str()) {
+        (None, status) if status != "failed" => {
+            tx.amount.as_deref().and_then(|a| a.parse().ok()).unwrap_or(0.0)
+        }
+        _ => 0.0,
+    };
+
+    Flow {
+        received: if incoming { amount } else { 0.0 },
+        sent: if outgoing { amount } else { 0.0 },
+        fee: if outgoing { fee(chain, tx).unwrap_or(0.0) } else { 0.0 },
+    }
+}
+
+/// Fee observed once the transaction landed, else the simulated one
+fn fee(chain: Chain, tx: &TransactionRow) -> Option<f64> {
+    let effects = tx.actual_effects().or_else(|| tx.expected_effects())?;
+    let base_units: f64 = effects.fee.parse().ok()?;
+    Some(base_units / 10f64.powi(chain.native_decimals() as i32))
+}
+
+fn entry(chain: Chain, address: &str, tx: &TransactionRow, flow: Flow) -> StatementEntry {
+    let outgoing = tx
+        .from_address
+        .as_deref()
+        .is_some_and(|from| from.eq_ignore_ascii_case(address));
+    let counterparty = if outgoing { &tx.to_address } else { &tx.from_address };
+    let amount = match &tx.token_address {
+        None => Some(format_amount(flow.received - flow.sent)),
+        Some(_) => tx
+            .amount
+            .as_ref()
+            .map(|a| if outgoing { format!("-{}", a) } else { a.clone() }),
+    };
+
+    StatementEntry {
+        date: tx.timestamp.clone().unwrap_or_else(|| tx.created_at.clone()),
+        signature: tx.signature.clone(),
+        tx_type: tx.tx_type.clone(),
+        status: tx.status.clone(),
+        counterparty: counterparty.clone(),
+        amount,
+        token_address: tx.token_address.clone(),
+        fee: outgoing.then(|| fee(chain, tx)).flatten().map(format_amount),
+        fiat_value: tx.realized_value(),
+        fiat_currency: tx.price_currency.clone(),
+    }
+}
+
+/// Up to nine decimals, without trailing zeros
+fn format_amount(amount: f64) -> String {
+    let formatted = format!("{:.9}", amount);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => "0".to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Addresses and hashes as `abcd...wxyz`
+fn shorten(value: &str) -> String {
+    match (value.get(..6), value.get(value.len().saturating_sub(4)..)) {
+        (Some(head), Some(tail)) if value.len() > 13 => format!("{}...{}", head, tail),
+        _ => value.to_string(),
+    }
+}
+
+/// The built-in fonts carry no Unicode mapping, so non-ASCII is replaced
+fn printable(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii_graphic() || c == ' ' { c } else { '?' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0x52908400098527886E0F7030069857D2E4169EE7";
+
+    fn row(from: &str, to: &str, amount: &str, status: &str) -> TransactionRow {
+        let mut tx = TransactionRow::new(
+            "account".to_string(),
+            "ethereum".to_string(),
+            "0xhash".to_string(),
+            "send".to_string(),
+            Some(from.to_string()),
+            Some(to.to_string()),
+            Some(amount.to_string()),
+            None,
+            status.to_string(),
+            None,
+            None,
+        );
+        tx.actual_changes = Some(r#"{"changes":[],"fee":"1000000000000000"}"#.to_string());
+        tx
+    }
+
+    #[test]
+    fn test_flow_direction_and_fees() {
+        let other = "0x8617e340b3d01fa5f11f306f4090fd50e238070d";
+        let sent = flow(Chain::Ethereum, ADDRESS, &row(&ADDRESS.to_lowercase(), other, "1.5", "confirmed"));
+        assert_eq!(sent, Flow { received: 0.0, sent: 1.5, fee: 0.001 });
+
+        let received = flow(Chain::Ethereum, ADDRESS, &row(other, ADDRESS, "2", "confirmed"));
+        assert_eq!(received, Flow { received: 2.0, sent: 0.0, fee: 0.0 });
+
+        let failed = flow(Chain::Ethereum, ADDRESS, &row(ADDRESS, other, "1.5", "failed"));
+        assert_eq!(failed.net(), -0.001);
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let (start, end) = month_bounds("2024-12").unwrap();
+        assert_eq!(start.to_string(), "2024-12-01");
+        assert_eq!(end.to_string(), "2025-01-01");
+        for bad in ["2024-13", "2024-1", "december", "9999-01"] {
+            assert!(matches!(month_bounds(bad), Err(StatementError::InvalidMonth(_))), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_format_helpers() {
+        assert_eq!(format_amount(1.25), "1.25");
+        assert_eq!(format_amount(-0.0000000001), "0");
+        assert_eq!(shorten(ADDRESS), "0x5290...9EE7");
+        assert_eq!(printable("Café"), "Caf?");
+    }
+}
@@ -0,0 +1,74 @@
+//! Status service - the operator's view of a running instance
+//!
+//! Gathers what the admin dashboard at `/admin` shows: whether the database
+//! answers and writes are accepted, RPC usage per provider, how much work
+//! the background jobs have waiting, and the latest audit events of every
+//! tenant.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::chains::metered::RpcUsageReport;
+use crate::services::feature_flag_service::{self, FeatureFlag};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{AuditEventRow, QueueDepths};
+use crate::storage::schema::MIGRATOR;
+use crate::AppState;
+
+/// Audit events shown on the dashboard
+const RECENT_AUDIT_EVENTS: i64 = 25;
+
+#[derive(Debug, Error)]
+pub enum StatusError {
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Whether the instance is serving normally
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Health {
+    /// `ok` when writes are accepted, `degraded` otherwise
+    pub status: String,
+    pub version: String,
+    /// Applied database schema version
+    pub schema_version: i64,
+    /// Writes are refused because the schema is ahead of this binary
+    pub safe_mode: bool,
+    /// The maintenance flag is refusing writes
+    pub maintenance: bool,
+}
+
+/// Everything the dashboard shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusReport {
+    pub generated_at: String,
+    pub health: Health,
+    pub rpc: RpcUsageReport,
+    pub queues: QueueDepths,
+    pub recent_audit_events: Vec<AuditEventRow>,
+}
+
+/// Current status of this instance
+pub async fn report(state: &Arc<AppState>) -> Result<StatusReport, StatusError> {
+    let schema = state.db.schema_status(&MIGRATOR).await?;
+    let maintenance = feature_flag_service::check(state, FeatureFlag::Maintenance)
+        .await
+        .is_err();
+    let degraded = state.safe_mode || maintenance;
+
+    Ok(StatusReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        health: Health {
+            status: if degraded { "degraded" } else { "ok" }.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: schema.version,
+            safe_mode: state.safe_mode,
+            maintenance,
+        },
+        rpc: state.rpc_metrics.usage(),
+        queues: state.db.get_queue_depths().await?,
+        recent_audit_events: state.db.get_recent_audit_events(RECENT_AUDIT_EVENTS).await?,
+    })
+}
@@ -0,0 +1,301 @@
+//! Stuck transaction service - spots sends that won't land on their own
+//!
+//! A background monitor checks pending sends every `STUCK_CHECK_SECS`. An
+//! Ethereum send is stuck once it has been pending for `ETH_STUCK_BLOCKS`
+//! blocks, usually because its gas price fell behind the market; a Solana
+//! send once the blockhash it was signed with expires, after which it can
+//! never land. A stuck send can be sped up: on Ethereum it is replaced at
+//! the same nonce with a higher gas price, on Solana it is sent again with a
+//! fresh blockhash.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::{current_tenant_id, with_tenant};
+use crate::chains::{Broadcast, ChainClientError, Transfer};
+use crate::core::Chain;
+use crate::services::feature_flag_service::{self, FeatureFlag};
+use crate::services::tenant_service;
+use crate::services::transaction_service::{self, SendResponse, TransactionServiceError};
+use crate::services::wallet_service::{get_seed, WalletServiceError};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{TransactionResponse, TransactionRow};
+use crate::AppState;
+
+/// Sends older than this are no longer watched
+const STUCK_WINDOW_HOURS: i64 = 72;
+
+#[derive(Debug, Error)]
+pub enum StuckServiceError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Transaction not found")]
+    NotFound,
+    #[error("Transaction is not stuck")]
+    NotStuck,
+    #[error("Transaction has already landed")]
+    AlreadyLanded,
+    #[error("Transaction can't be replaced: {0}")]
+    NotReplaceable(String),
+    #[error("Wallet error: {0}")]
+    WalletError(#[from] WalletServiceError),
+    #[error(transparent)]
+    SendFailed(#[from] TransactionServiceError),
+    #[error("Chain error: {0}")]
+    Chain(#[from] ChainClientError),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// A stuck send and why it was flagged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StuckTransaction {
+    #[serde(flatten)]
+    pub transaction: TransactionResponse,
+    /// `pending_too_long` (Ethereum) or `blockhash_expired` (Solana)
+    pub reason: String,
+    /// Block number or height the send was broadcast at
+    pub broadcast_height: Option<u64>,
+}
+
+impl From<TransactionRow> for StuckTransaction {
+    fn from(row: TransactionRow) -> Self {
+        let reason = match row.chain.as_str() {
+            "solana" => "blockhash_expired",
+            _ => "pending_too_long",
+        };
+        Self {
+            reason: reason.to_string(),
+            broadcast_height: row.broadcast_info().map(|b| b.height),
+            transaction: TransactionResponse::from(row),
+        }
+    }
+}
+
+/// The current tenant's stuck sends, oldest first
+pub async fn list_stuck(state: &Arc<AppState>) -> Result<Vec<StuckTransaction>, StuckServiceError> {
+    let rows = state.db.get_stuck_transactions(&current_tenant_id()).await?;
+    Ok(rows.into_iter().map(StuckTransaction::from).collect())
+}
+
+/// Replace a stuck send with one that will land, signed with the unlocked
+/// wallet
+pub async fn speed_up(state: &Arc<AppState>, id: &str) -> Result<SendResponse, StuckServiceError> {
+    let tx = match state.db.get_transaction(id).await {
+        Ok(tx) => tx,
+        Err(DatabaseError::NotFound) => return Err(StuckServiceError::NotFound),
+        Err(e) => return Err(e.into()),
+    };
+    let account = state.db.get_account(&tx.account_id).await?;
+    let wallet = state.db.get_wallet(&account.wallet_id).await?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(StuckServiceError::NotFound);
+    }
+    if tx.stuck_at.is_none() || tx.replaced_by.is_some() || tx.status != "pending" {
+        return Err(StuckServiceError::NotStuck);
+    }
+
+    let chain: Chain = tx
+        .chain
+        .parse()
+        .map_err(|_| StuckServiceError::InvalidChain(tx.chain.clone()))?;
+    feature_flag_service::check(state, FeatureFlag::Sends(chain))
+        .await
+        .map_err(TransactionServiceError::from)?;
+    let broadcast = tx.broadcast_info().ok_or(StuckServiceError::NotStuck)?;
+    let replaces = match chain {
+        Chain::Ethereum => Some(broadcast.replacement().ok_or_else(|| {
+            StuckServiceError::NotReplaceable("no nonce was recorded for it".to_string())
+        })?),
+        Chain::Solana => None,
+    };
+    let (Some(to_address), Some(amount)) = (tx.to_address.clone(), tx.amount.clone()) else {
+        return Err(StuckServiceError::NotReplaceable(
+            "its recipient or amount is unknown".to_string(),
+        ));
+    };
+
+    // It may have landed since it was flagged
+    let clients = state.account_clients(&account);
+    let client = clients.get(chain);
+    if client.transaction_effects(&tx.signature, &[]).await?.is_some() {
+        return Err(StuckServiceError::AlreadyLanded);
+    }
+
+    // The resend carries the same memo and reference keys, so the
+    // recipient still finds it
+    let references = state.db.get_transaction_references(&tx.id).await?;
+    let seed = get_seed(state).await?;
+    let transfer = Transfer {
+        to: to_address.clone(),
+        token: tx.token_address.clone(),
+        amount,
+        drain_all: false,
+        replaces,
+        memo: tx.memo.clone(),
+        references: references.clone(),
+        private_rpc: None,
+        jito: None,
+        dry_run: false,
+    };
+    let result = client
+        .send(&seed, &account.derivation_path, transfer)
+        .await
+        .map_err(TransactionServiceError::from)?;
+    tracing::info!(tx_hash = %tx.signature, replaced_by = %result.tx_hash, "Stuck transaction sped up");
+
+    let mut tx_row = transaction_service::sent_row(
+        account.id,
+        chain,
+        tx.from_address.unwrap_or(account.address),
+        to_address,
+        tx.token_address,
+        &result,
+    );
+    tx_row.memo = tx.memo;
+    transaction_service::record_sent(state, &tx_row, &references).await;
+    state.db.mark_transaction_replaced(&tx.id, &result.tx_hash).await?;
+
+    Ok(SendResponse {
+        tx_hash: result.tx_hash,
+        status: result.status,
+        conversion: None,
+    })
+}
+
+/// Check every pending send once. Returns how many were newly flagged.
+pub async fn detect_stuck(state: &Arc<AppState>) -> Result<usize, StuckServiceError> {
+    let since = (chrono::Utc::now() - chrono::Duration::hours(STUCK_WINDOW_HOURS)).to_rfc3339();
+    let pending = state.db.get_pending_broadcasts(&since).await?;
+
+    // One height lookup per tenant and chain per pass
+    let mut heights: HashMap<(String, Chain), u64> = HashMap::new();
+    let mut flagged = 0;
+    for tx in pending {
+        let account = match state.db.get_account(&tx.account_id).await {
+            Ok(account) => account,
+            Err(DatabaseError::NotFound) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let wallet = state.db.get_wallet(&account.wallet_id).await?;
+
+        let tenant = match tenant_service::tenant_context(state, &wallet.tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(tenant_id = %wallet.tenant_id, error = %e, "Skipping stuck check");
+                continue;
+            }
+        };
+        match with_tenant(tenant, check(state, &tx, &wallet.tenant_id, &mut heights)).await {
+            Ok(true) => flagged += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(tx_hash = %tx.signature, error = %e, "Stuck check failed"),
+        }
+    }
+
+    Ok(flagged)
+}
+
+/// Flag one send if it is stuck; `false` if it isn't
+async fn check(
+    state: &Arc<AppState>,
+    tx: &TransactionRow,
+    tenant_id: &str,
+    heights: &mut HashMap<(String, Chain), u64>,
+) -> Result<bool, StuckServiceError> {
+    let chain: Chain = tx
+        .chain
+        .parse()
+        .map_err(|_| StuckServiceError::InvalidChain(tx.chain.clone()))?;
+    let Some(broadcast) = tx.broadcast_info() else {
+        return Ok(false);
+    };
+    // Private sends are left to the confirmation tracker, which broadcasts
+    // them publicly once the relay gives up
+    if broadcast.private {
+        return Ok(false);
+    }
+
+    let clients = state.chain_clients();
+    let client = clients.get(chain);
+    let height = match heights.get(&(tenant_id.to_string(), chain)) {
+        Some(height) => *height,
+        None => {
+            let height = client.block_height().await?;
+            heights.insert((tenant_id.to_string(), chain), height);
+            height
+        }
+    };
+    if !is_stuck(chain, &broadcast, height, state.config.eth_stuck_blocks) {
+        return Ok(false);
+    }
+    // Landed but not yet reconciled
+    if client.transaction_effects(&tx.signature, &[]).await?.is_some() {
+        return Ok(false);
+    }
+
+    state
+        .db
+        .mark_transaction_stuck(&tx.id, &chrono::Utc::now().to_rfc3339())
+        .await?;
+    tracing::warn!(
+        tx_hash = %tx.signature,
+        chain = %chain,
+        broadcast_height = broadcast.height,
+        height,
+        "Transaction stuck"
+    );
+    Ok(true)
+}
+
+fn is_stuck(chain: Chain, broadcast: &Broadcast, height: u64, eth_stuck_blocks: u64) -> bool {
+    match chain {
+        Chain::Ethereum => height >= broadcast.height.saturating_add(eth_stuck_blocks),
+        Chain::Solana => broadcast.last_valid_height.is_some_and(|last| height > last),
+    }
+}
+
+/// Run `detect_stuck` every `STUCK_CHECK_SECS` for the life of the process
+pub fn spawn_stuck_monitor(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.stuck_check_interval);
+        loop {
+            ticker.tick().await;
+            match detect_stuck(&state).await {
+                Ok(0) => {}
+                Ok(flagged) => tracing::debug!(flagged, "Stuck transactions flagged"),
+                Err(e) => tracing::warn!(error = %e, "Stuck transaction check failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stuck() {
+        let eth = Broadcast {
+            height: 100,
+            nonce: Some(7),
+            gas_price: Some("1000000000".to_string()),
+            ..Default::default()
+        };
+        assert!(!is_stuck(Chain::Ethereum, &eth, 124, 25));
+        assert!(is_stuck(Chain::Ethereum, &eth, 125, 25));
+
+        let sol = Broadcast {
+            height: 1_000,
+            last_valid_height: Some(1_150),
+            ..Default::default()
+        };
+        assert!(!is_stuck(Chain::Solana, &sol, 1_150, 25));
+        assert!(is_stuck(Chain::Solana, &sol, 1_151, 25));
+        // Without a blockhash expiry there is nothing to go by
+        assert!(!is_stuck(Chain::Solana, &Broadcast::default(), 5_000, 25));
+    }
+}
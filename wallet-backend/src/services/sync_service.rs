@@ -0,0 +1,100 @@
+//! Sync service - end-to-end encrypted client data shared between frontends
+//!
+//! Each user has at most one blob, encrypted by the client before upload;
+//! the server stores it as opaque bytes and never reads it. Every write names
+//! the version it replaces, so two frontends can't silently overwrite each
+//! other's changes.
+
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{SyncBlobResponse, SyncBlobRow};
+use crate::AppState;
+
+#[derive(Debug, Error)]
+pub enum SyncServiceError {
+    #[error("No sync data stored")]
+    NotFound,
+    #[error("Sync data must not be empty")]
+    Empty,
+    #[error("Sync data has changed since it was read")]
+    VersionMismatch,
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for SyncServiceError {
+    fn from(e: DatabaseError) -> Self {
+        match e {
+            DatabaseError::NotFound => SyncServiceError::NotFound,
+            _ => SyncServiceError::DatabaseError(e.to_string()),
+        }
+    }
+}
+
+/// What the client believes is stored, from `If-Match` / `If-None-Match`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+    /// The blob is at this version
+    Version(i64),
+    /// Some blob exists, whatever its version
+    Exists,
+    /// The user has no blob yet
+    Absent,
+}
+
+/// The user's blob
+pub async fn get_blob(state: &Arc<AppState>, user_id: &str) -> Result<SyncBlobRow, SyncServiceError> {
+    Ok(state.db.get_sync_blob(user_id).await?)
+}
+
+/// Replace the user's blob if `precondition` still holds
+pub async fn put_blob(
+    state: &Arc<AppState>,
+    user_id: &str,
+    precondition: Precondition,
+    data: &[u8],
+) -> Result<SyncBlobResponse, SyncServiceError> {
+    if data.is_empty() {
+        return Err(SyncServiceError::Empty);
+    }
+
+    let expected = match precondition {
+        Precondition::Version(version) => Some(version),
+        Precondition::Absent => None,
+        Precondition::Exists => match state.db.get_sync_blob(user_id).await {
+            Ok(blob) => Some(blob.version),
+            Err(DatabaseError::NotFound) => return Err(SyncServiceError::VersionMismatch),
+            Err(e) => return Err(e.into()),
+        },
+    };
+
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    let version = state
+        .db
+        .put_sync_blob(user_id, expected, data, &updated_at)
+        .await?
+        .ok_or(SyncServiceError::VersionMismatch)?;
+
+    Ok(SyncBlobResponse {
+        version,
+        size: data.len(),
+        updated_at,
+    })
+}
+
+/// Delete the user's blob if it is still at `version`
+pub async fn delete_blob(
+    state: &Arc<AppState>,
+    user_id: &str,
+    version: i64,
+) -> Result<(), SyncServiceError> {
+    if state.db.delete_sync_blob(user_id, version).await? {
+        return Ok(());
+    }
+    // Distinguish "nothing to delete" from "changed underneath"
+    state.db.get_sync_blob(user_id).await?;
+    Err(SyncServiceError::VersionMismatch)
+}
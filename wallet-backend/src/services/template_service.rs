@@ -0,0 +1,288 @@
+//! Template service - saved send drafts
+//!
+//! A template holds what a send form needs (recipient, amount, token, memo)
+//! under a name. Applying one returns the draft to fill the form with and
+//! counts the use; sending still goes through `/transactions/send`.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::chains::{ethereum, solana};
+use crate::core::Chain;
+use crate::services::transaction_service;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{SendTemplateResponse, SendTemplateRow};
+use crate::AppState;
+
+/// Longest template name, in characters
+const MAX_NAME_CHARS: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("Invalid chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+    #[error("Template not found")]
+    NotFound,
+    #[error("A template with this name already exists")]
+    NameTaken,
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for TemplateError {
+    fn from(e: DatabaseError) -> Self {
+        match e {
+            DatabaseError::NotFound => TemplateError::NotFound,
+            DatabaseError::AlreadyExists => TemplateError::NameTaken,
+            _ => TemplateError::DatabaseError(e.to_string()),
+        }
+    }
+}
+
+/// Create template request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTemplateRequest {
+    pub name: String,
+    pub chain: String,
+    /// Account to send from; left to the user when omitted
+    pub from_address: Option<String>,
+    pub to_address: String,
+    /// Coin, token or fiat amount (`25 USD`); asked for each time when omitted
+    #[serde(default)]
+    pub amount: String,
+    pub token_address: Option<String>,
+    /// Solana only
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub quick_action: bool,
+}
+
+/// Update template request; omitted fields are left unchanged, and empty
+/// strings clear the optional ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateTemplateRequest {
+    pub name: Option<String>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub amount: Option<String>,
+    pub token_address: Option<String>,
+    pub memo: Option<String>,
+    pub quick_action: Option<bool>,
+}
+
+/// Apply template request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyTemplateRequest {
+    /// Send from this account instead of the template's
+    pub from_address: Option<String>,
+}
+
+/// A template's draft, with the field names of a send request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendDraft {
+    pub chain: String,
+    pub from_address: Option<String>,
+    pub to_address: String,
+    pub amount: String,
+    pub token_address: Option<String>,
+    pub memo: Option<String>,
+}
+
+/// Applied template, with its updated usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedTemplate {
+    pub template: SendTemplateResponse,
+    pub draft: SendDraft,
+}
+
+/// A user's templates: quick actions first, then the most recently used
+pub async fn list_templates(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<SendTemplateResponse>, TemplateError> {
+    let templates = state.db.get_send_templates(user_id).await?;
+    Ok(templates.into_iter().map(SendTemplateResponse::from).collect())
+}
+
+/// Save a send draft under a name
+pub async fn create_template(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: CreateTemplateRequest,
+) -> Result<SendTemplateResponse, TemplateError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| TemplateError::InvalidChain(request.chain.clone()))?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let template = SendTemplateRow {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        name: request.name.trim().to_string(),
+        chain: chain.to_string(),
+        from_address: non_empty(request.from_address),
+        to_address: request.to_address.trim().to_string(),
+        amount: request.amount.trim().to_string(),
+        token_address: non_empty(request.token_address),
+        memo: request.memo.filter(|m| !m.is_empty()),
+        quick_action: request.quick_action,
+        use_count: 0,
+        last_used_at: None,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    validate(chain, &template)?;
+
+    state.db.create_send_template(&template).await?;
+    tracing::info!(user_id = %user_id, template_id = %template.id, "Send template saved");
+
+    Ok(SendTemplateResponse::from(template))
+}
+
+/// Change a template's name, draft fields or quick action flag
+pub async fn update_template(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: UpdateTemplateRequest,
+) -> Result<SendTemplateResponse, TemplateError> {
+    let mut template = get_owned_template(state, user_id, id).await?;
+    let chain: Chain = template
+        .chain
+        .parse()
+        .map_err(|_| TemplateError::InvalidChain(template.chain.clone()))?;
+
+    if let Some(name) = request.name {
+        template.name = name.trim().to_string();
+    }
+    if let Some(from_address) = request.from_address {
+        template.from_address = non_empty(Some(from_address));
+    }
+    if let Some(to_address) = request.to_address {
+        template.to_address = to_address.trim().to_string();
+    }
+    if let Some(amount) = request.amount {
+        template.amount = amount.trim().to_string();
+    }
+    if let Some(token_address) = request.token_address {
+        template.token_address = non_empty(Some(token_address));
+    }
+    if let Some(memo) = request.memo {
+        template.memo = Some(memo).filter(|m| !m.is_empty());
+    }
+    if let Some(quick_action) = request.quick_action {
+        template.quick_action = quick_action;
+    }
+    template.updated_at = chrono::Utc::now().to_rfc3339();
+    validate(chain, &template)?;
+
+    state.db.update_send_template(&template).await?;
+    Ok(SendTemplateResponse::from(template))
+}
+
+/// Delete a template
+pub async fn delete_template(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<(), TemplateError> {
+    get_owned_template(state, user_id, id).await?;
+    state.db.delete_send_template(id).await?;
+    Ok(())
+}
+
+/// The draft to fill the send form with; counts as a use of the template
+pub async fn apply_template(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: ApplyTemplateRequest,
+) -> Result<AppliedTemplate, TemplateError> {
+    let template = get_owned_template(state, user_id, id).await?;
+    let chain: Chain = template
+        .chain
+        .parse()
+        .map_err(|_| TemplateError::InvalidChain(template.chain.clone()))?;
+    let from_address = non_empty(request.from_address);
+    if let Some(address) = &from_address {
+        check_address(chain, "from_address", address)?;
+    }
+
+    state
+        .db
+        .record_send_template_use(id, &chrono::Utc::now().to_rfc3339())
+        .await?;
+    let template = state.db.get_send_template(id).await?;
+
+    let draft = SendDraft {
+        chain: template.chain.clone(),
+        from_address: from_address.or_else(|| template.from_address.clone()),
+        to_address: template.to_address.clone(),
+        amount: template.amount.clone(),
+        token_address: template.token_address.clone(),
+        memo: template.memo.clone(),
+    };
+    Ok(AppliedTemplate {
+        template: SendTemplateResponse::from(template),
+        draft,
+    })
+}
+
+fn validate(chain: Chain, template: &SendTemplateRow) -> Result<(), TemplateError> {
+    if template.name.is_empty() || template.name.chars().count() > MAX_NAME_CHARS {
+        return Err(TemplateError::InvalidTemplate(format!(
+            "name must be 1-{} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    check_address(chain, "to_address", &template.to_address)?;
+    if let Some(address) = &template.from_address {
+        check_address(chain, "from_address", address)?;
+    }
+    if let Some(address) = &template.token_address {
+        check_address(chain, "token_address", address)?;
+    }
+
+    let amount = &template.amount;
+    if !amount.is_empty() && !transaction_service::is_valid_amount_input(amount) {
+        return Err(TemplateError::InvalidTemplate(format!("invalid amount {}", amount)));
+    }
+
+    transaction_service::check_payment_markers(chain, template.memo.as_deref(), &[])
+        .map_err(|e| TemplateError::InvalidTemplate(e.to_string()))
+}
+
+fn check_address(chain: Chain, field: &str, address: &str) -> Result<(), TemplateError> {
+    let valid = match chain {
+        Chain::Solana => solana::validate_address(address),
+        Chain::Ethereum => address.starts_with("0x") && ethereum::validate_address(address),
+    };
+    if !valid {
+        return Err(TemplateError::InvalidTemplate(format!(
+            "invalid {} {}",
+            field, address
+        )));
+    }
+    Ok(())
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+async fn get_owned_template(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<SendTemplateRow, TemplateError> {
+    match state.db.get_send_template(id).await? {
+        template if template.user_id == user_id => Ok(template),
+        _ => Err(TemplateError::NotFound),
+    }
+}
@@ -0,0 +1,339 @@
+//! Tenant service - identification and administration of tenants
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::TenantContext;
+use crate::chains::ChainClients;
+use crate::config::app::RateLimitConfig;
+use crate::core::Chain;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{TenantResponse, TenantRow};
+use crate::AppState;
+
+/// Longest tenant ID accepted
+const MAX_TENANT_ID_LEN: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum TenantServiceError {
+    #[error("Tenant not found")]
+    NotFound,
+    #[error("Tenant is disabled")]
+    Disabled,
+    #[error("A tenant with this ID or hostname already exists")]
+    AlreadyExists,
+    #[error("Invalid tenant: {0}")]
+    InvalidTenant(String),
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for TenantServiceError {
+    fn from(e: DatabaseError) -> Self {
+        match e {
+            DatabaseError::NotFound => TenantServiceError::NotFound,
+            DatabaseError::AlreadyExists => TenantServiceError::AlreadyExists,
+            _ => TenantServiceError::DatabaseError(e.to_string()),
+        }
+    }
+}
+
+/// Create tenant request
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CreateTenantRequest {
+    /// Lowercase letters, digits and dashes
+    pub id: String,
+    pub name: String,
+    pub hostname: Option<String>,
+    #[serde(flatten)]
+    pub overrides: TenantOverrides,
+}
+
+/// Update tenant request; omitted fields are left unchanged and empty strings
+/// clear optional ones
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UpdateTenantRequest {
+    pub name: Option<String>,
+    pub hostname: Option<String>,
+    pub is_active: Option<bool>,
+    #[serde(flatten)]
+    pub overrides: TenantOverrides,
+}
+
+/// Per-tenant replacements for server-wide settings
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TenantOverrides {
+    pub solana_rpc_url: Option<String>,
+    pub eth_rpc_url: Option<String>,
+    pub rate_limit_max_requests: Option<u32>,
+    pub rate_limit_window_secs: Option<u64>,
+    /// Replaces the tenant's settings object
+    pub settings: Option<serde_json::Value>,
+}
+
+/// New tenant or rotated key; the API key is only ever returned here
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TenantWithKey {
+    pub tenant: TenantResponse,
+    pub api_key: String,
+}
+
+/// Find the active tenant a request belongs to: by API key when one is sent,
+/// otherwise by hostname
+pub async fn identify(
+    state: &Arc<AppState>,
+    api_key: Option<&str>,
+    host: Option<&str>,
+) -> Result<TenantContext, TenantServiceError> {
+    let tenant = match (api_key, host) {
+        (Some(key), _) => state.db.get_tenant_by_api_key_hash(&hash_api_key(key)).await?,
+        (None, Some(host)) => state.db.get_tenant_by_hostname(&normalize_hostname(host)).await?,
+        (None, None) => None,
+    }
+    .ok_or(TenantServiceError::NotFound)?;
+
+    if !tenant.is_active {
+        return Err(TenantServiceError::Disabled);
+    }
+    Ok(context(state, &tenant))
+}
+
+/// Context for background work on an active tenant's data
+pub async fn tenant_context(
+    state: &Arc<AppState>,
+    id: &str,
+) -> Result<TenantContext, TenantServiceError> {
+    let tenant = state.db.get_tenant(id).await?;
+    if !tenant.is_active {
+        return Err(TenantServiceError::Disabled);
+    }
+    Ok(context(state, &tenant))
+}
+
+pub async fn list_tenants(state: &Arc<AppState>) -> Result<Vec<TenantResponse>, TenantServiceError> {
+    let tenants = state.db.get_tenants().await?;
+    Ok(tenants.into_iter().map(TenantResponse::from).collect())
+}
+
+pub async fn get_tenant(state: &Arc<AppState>, id: &str) -> Result<TenantRow, TenantServiceError> {
+    Ok(state.db.get_tenant(id).await?)
+}
+
+/// Register a tenant and issue its API key
+pub async fn create_tenant(
+    state: &Arc<AppState>,
+    request: CreateTenantRequest,
+) -> Result<TenantWithKey, TenantServiceError> {
+    let id = request.id.trim().to_string();
+    if id.is_empty()
+        || id.len() > MAX_TENANT_ID_LEN
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(TenantServiceError::InvalidTenant(format!(
+            "ID must be 1-{} lowercase letters, digits or dashes",
+            MAX_TENANT_ID_LEN
+        )));
+    }
+    let name = non_empty(request.name)
+        .ok_or_else(|| TenantServiceError::InvalidTenant("name is required".to_string()))?;
+
+    let api_key = generate_api_key();
+    let mut tenant = TenantRow::new(
+        id,
+        name,
+        request.hostname.as_deref().map(normalize_hostname),
+        hash_api_key(&api_key),
+    );
+    apply_overrides(&mut tenant, request.overrides)?;
+
+    state.db.create_tenant(&tenant).await?;
+    tracing::info!(tenant_id = %tenant.id, "Tenant created");
+
+    Ok(TenantWithKey {
+        tenant: TenantResponse::from(tenant),
+        api_key,
+    })
+}
+
+pub async fn update_tenant(
+    state: &Arc<AppState>,
+    id: &str,
+    request: UpdateTenantRequest,
+) -> Result<TenantResponse, TenantServiceError> {
+    let mut tenant = state.db.get_tenant(id).await?;
+
+    if let Some(name) = request.name {
+        tenant.name = non_empty(name)
+            .ok_or_else(|| TenantServiceError::InvalidTenant("name is required".to_string()))?;
+    }
+    if let Some(hostname) = request.hostname {
+        tenant.hostname = non_empty(hostname).as_deref().map(normalize_hostname);
+    }
+    if let Some(is_active) = request.is_active {
+        tenant.is_active = is_active;
+    }
+    apply_overrides(&mut tenant, request.overrides)?;
+
+    state.db.update_tenant(&tenant).await?;
+    tracing::info!(tenant_id = %tenant.id, "Tenant updated");
+
+    Ok(TenantResponse::from(tenant))
+}
+
+/// Replace a tenant's API key; the old key stops working immediately
+pub async fn rotate_api_key(
+    state: &Arc<AppState>,
+    id: &str,
+) -> Result<TenantWithKey, TenantServiceError> {
+    let mut tenant = state.db.get_tenant(id).await?;
+
+    let api_key = generate_api_key();
+    let api_key_hash = hash_api_key(&api_key);
+    state.db.set_tenant_api_key_hash(id, &api_key_hash).await?;
+    tenant.api_key_hash = Some(api_key_hash);
+    tracing::info!(tenant_id = %id, "Tenant API key rotated");
+
+    Ok(TenantWithKey {
+        tenant: TenantResponse::from(tenant),
+        api_key,
+    })
+}
+
+/// SHA-256 hex digest under which API keys are stored
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Request-scoped view of a tenant
+fn context(state: &Arc<AppState>, tenant: &TenantRow) -> TenantContext {
+    let rate_limit = tenant
+        .rate_limit_max_requests
+        .map(|max_requests| RateLimitConfig {
+            enabled: true,
+            max_requests: max_requests as u32,
+            window: tenant
+                .rate_limit_window_secs
+                .map(|secs| Duration::from_secs(secs as u64))
+                .unwrap_or(state.config.rate_limit.window),
+        });
+
+    TenantContext {
+        id: tenant.id.clone(),
+        rate_limit,
+        chains: tenant_chains(state, tenant),
+    }
+}
+
+/// Clients for a tenant's RPC overrides, cached until its URLs change
+fn tenant_chains(state: &Arc<AppState>, tenant: &TenantRow) -> Option<ChainClients> {
+    if tenant.solana_rpc_url.is_none() && tenant.eth_rpc_url.is_none() {
+        return None;
+    }
+
+    let urls = (tenant.solana_rpc_url.clone(), tenant.eth_rpc_url.clone());
+    let mut cache = state.tenant_chains.lock().unwrap();
+    if let Some((cached_urls, clients)) = cache.get(&tenant.id) {
+        if *cached_urls == urls {
+            return Some(clients.clone());
+        }
+    }
+
+    let connect = |chain, url: &Option<String>| {
+        url.as_deref().map(|url| {
+            ChainClients::connect(chain, url, &state.config.sns_api_url, &state.rpc_metrics)
+        })
+    };
+    let mut clients = state.chains.clone();
+    if let Some(client) = connect(Chain::Solana, &urls.0) {
+        clients = clients.with(Chain::Solana, client);
+    }
+    if let Some(client) = connect(Chain::Ethereum, &urls.1) {
+        clients = clients.with(Chain::Ethereum, client);
+    }
+    cache.insert(tenant.id.clone(), (urls, clients.clone()));
+    Some(clients)
+}
+
+fn apply_overrides(
+    tenant: &mut TenantRow,
+    overrides: TenantOverrides,
+) -> Result<(), TenantServiceError> {
+    if let Some(url) = overrides.solana_rpc_url {
+        tenant.solana_rpc_url = non_empty(url).map(validate_url).transpose()?;
+    }
+    if let Some(url) = overrides.eth_rpc_url {
+        tenant.eth_rpc_url = non_empty(url).map(validate_url).transpose()?;
+    }
+    if let Some(max_requests) = overrides.rate_limit_max_requests {
+        tenant.rate_limit_max_requests = (max_requests > 0).then_some(max_requests as i64);
+    }
+    if let Some(window_secs) = overrides.rate_limit_window_secs {
+        tenant.rate_limit_window_secs = (window_secs > 0).then_some(window_secs as i64);
+    }
+    if let Some(settings) = overrides.settings {
+        if !settings.is_object() {
+            return Err(TenantServiceError::InvalidTenant(
+                "settings must be an object".to_string(),
+            ));
+        }
+        tenant.settings = settings.to_string();
+    }
+    Ok(())
+}
+
+fn validate_url(url: String) -> Result<String, TenantServiceError> {
+    match reqwest::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => {
+            Ok(url)
+        }
+        _ => Err(TenantServiceError::InvalidTenant(format!(
+            "'{}' is not an http(s) URL",
+            url
+        ))),
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Lowercase host without a port
+fn normalize_hostname(host: &str) -> String {
+    let host = host.trim().to_lowercase();
+    match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name.to_string(),
+        _ => host,
+    }
+}
+
+fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("vtx_{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_hostname() {
+        assert_eq!(normalize_hostname("Wallet.Example.com:8080"), "wallet.example.com");
+        assert_eq!(normalize_hostname("wallet.example.com"), "wallet.example.com");
+    }
+
+    #[test]
+    fn test_api_keys_are_unique_and_hashed() {
+        let (a, b) = (generate_api_key(), generate_api_key());
+        assert_ne!(a, b);
+        assert_eq!(hash_api_key(&a), hash_api_key(&a));
+        assert_ne!(hash_api_key(&a), a);
+    }
+}
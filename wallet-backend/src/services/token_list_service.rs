@@ -0,0 +1,181 @@
+//! Token list service - the Jupiter token list, held in memory and cached in
+//! the database
+//!
+//! The list gives token pickers mints, symbols, names, logos and decimals,
+//! lets swaps refuse mints it doesn't know and adds logos and missing
+//! symbols to balances. It is loaded from the database at startup and
+//! fetched again every `TOKEN_LIST_REFRESH_SECS`. Until a list has been
+//! loaded, nothing is checked against it.
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::core::Chain;
+use crate::services::transaction_service::TokenBalanceResponse;
+use crate::storage::database::DatabaseError;
+use crate::storage::models::TokenListRow;
+use crate::AppState;
+
+/// Most results a search returns
+pub const MAX_SEARCH_RESULTS: usize = 50;
+
+#[derive(Debug, Error)]
+pub enum TokenListError {
+    #[error("Token list unavailable: {0}")]
+    Fetch(String),
+    #[error("Unknown token: {0}")]
+    UnknownToken(String),
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
+
+/// Entry of the Jupiter token list
+#[derive(Deserialize)]
+struct JupiterToken {
+    address: String,
+    symbol: String,
+    name: String,
+    decimals: u8,
+    #[serde(rename = "logoURI")]
+    logo_uri: Option<String>,
+}
+
+/// Load the cached list into memory. Returns how many tokens it has.
+pub async fn load(state: &Arc<AppState>) -> Result<usize, TokenListError> {
+    let tokens = state.db.get_token_list().await?;
+    let count = tokens.len();
+    *state.token_list.write().await = tokens;
+    Ok(count)
+}
+
+/// Fetch the list from `TOKEN_LIST_URL` and replace the cached one. Returns
+/// how many tokens it has.
+pub async fn refresh(state: &Arc<AppState>) -> Result<usize, TokenListError> {
+    let tokens: Vec<JupiterToken> = reqwest::get(&state.config.token_list_url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| TokenListError::Fetch(e.to_string()))?
+        .json()
+        .await
+        .map_err(|e| TokenListError::Fetch(e.to_string()))?;
+    // An empty list would turn every swap mint away
+    if tokens.is_empty() {
+        return Err(TokenListError::Fetch("list is empty".to_string()));
+    }
+
+    // Jupiter lists Solana mints only
+    let chain = Chain::Solana.to_string();
+    let updated_at = chrono::Utc::now().to_rfc3339();
+    let rows: Vec<TokenListRow> = tokens
+        .into_iter()
+        .map(|t| TokenListRow {
+            chain: chain.clone(),
+            address: t.address,
+            symbol: t.symbol,
+            name: t.name,
+            decimals: t.decimals as i64,
+            logo_uri: t.logo_uri,
+            updated_at: updated_at.clone(),
+        })
+        .collect();
+    state.db.replace_token_list(&chain, &rows).await?;
+
+    load(state).await
+}
+
+/// Tokens whose address is `query`, or whose symbol or name contains it
+/// (case-insensitively), optionally on one chain. Exact matches come first,
+/// then symbols starting with the query.
+pub async fn search(
+    state: &Arc<AppState>,
+    query: &str,
+    chain: Option<Chain>,
+    limit: usize,
+) -> Vec<TokenListRow> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let chain = chain.map(|c| c.to_string());
+
+    let list = state.token_list.read().await;
+    let mut matches: Vec<(u8, &TokenListRow)> = list
+        .iter()
+        .filter(|t| chain.as_deref().is_none_or(|c| t.chain == c))
+        .filter_map(|t| {
+            let symbol = t.symbol.to_lowercase();
+            let rank = if t.address.to_lowercase() == query || symbol == query {
+                0
+            } else if symbol.starts_with(&query) {
+                1
+            } else if symbol.contains(&query) || t.name.to_lowercase().contains(&query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, t))
+        })
+        .collect();
+    matches.sort_by(|(a_rank, a), (b_rank, b)| {
+        a_rank
+            .cmp(b_rank)
+            .then(a.symbol.len().cmp(&b.symbol.len()))
+            .then(a.symbol.cmp(&b.symbol))
+    });
+
+    matches
+        .into_iter()
+        .take(limit.min(MAX_SEARCH_RESULTS))
+        .map(|(_, t)| t.clone())
+        .collect()
+}
+
+/// Refuse a swap mint missing from the list, once one has been loaded
+pub async fn check_swap_mint(state: &Arc<AppState>, mint: &str) -> Result<(), TokenListError> {
+    let chain = Chain::Solana.to_string();
+    let list = state.token_list.read().await;
+    if list.is_empty() || list.iter().any(|t| t.chain == chain && t.address == mint) {
+        return Ok(());
+    }
+    Err(TokenListError::UnknownToken(mint.to_string()))
+}
+
+/// Add logos to listed balances, and the symbol and name where the chain
+/// reported none
+pub async fn enrich_balances(state: &Arc<AppState>, chain: Chain, tokens: &mut [TokenBalanceResponse]) {
+    let chain = chain.to_string();
+    let list = state.token_list.read().await;
+    for balance in tokens.iter_mut() {
+        let Some(listed) = list
+            .iter()
+            .find(|t| t.chain == chain && t.address.eq_ignore_ascii_case(&balance.address))
+        else {
+            continue;
+        };
+        balance.symbol.get_or_insert_with(|| listed.symbol.clone());
+        balance.name.get_or_insert_with(|| listed.name.clone());
+        balance.logo_uri = listed.logo_uri.clone();
+    }
+}
+
+/// Load the cached list, then fetch a fresh one every
+/// `TOKEN_LIST_REFRESH_SECS` for the life of the process
+pub fn spawn_token_list_refresh(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        match load(&state).await {
+            Ok(count) => tracing::debug!(count, "Token list loaded"),
+            Err(e) => tracing::warn!(error = %e, "Loading cached token list failed"),
+        }
+
+        let mut ticker = tokio::time::interval(state.config.token_list_refresh_interval);
+        loop {
+            ticker.tick().await;
+            match refresh(&state).await {
+                Ok(count) => tracing::debug!(count, "Token list refreshed"),
+                Err(e) => tracing::warn!(error = %e, "Token list refresh failed"),
+            }
+        }
+    })
+}
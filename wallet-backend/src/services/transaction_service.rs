@@ -1,15 +1,33 @@
 //! Transaction service - orchestrates transaction operations
 
+use std::collections::{hash_map::Entry, HashMap};
 use std::sync::Arc;
 
 use thiserror::Error;
 
-use crate::chains::ethereum::{get_eth_balance, send_eth, send_erc20, EthereumWallet};
 use crate::chains::solana::{
-    get_sol_balance_async, get_token_balances_async, send_sol, send_token, SolanaKeypair,
+    estimate_transfer_fee_async, FeeEstimate, JitoBundle, PriorityLevel, SubmissionMode,
+    TransferKind,
 };
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::{
+    ChainClientError, ChainClients, ReferencedTransaction, SentTransfer, TokenExtensions, Transfer,
+    TxEffects,
+};
+use crate::core::{format_units, Chain, SecureSeed};
+use crate::services::bucket_service::{self, BucketError, BucketSummary};
+use crate::services::feature_flag_service::{self, FeatureDisabled, FeatureFlag};
+use crate::services::price_service::{self, FiatConversion, PriceError};
+use crate::services::staking_service::{self, LiquidStakingSummary};
+use crate::services::token_list_service;
+use crate::services::token_service;
+use crate::services::user_service::UserServiceError;
 use crate::services::wallet_service::{get_seed, WalletServiceError};
-use crate::storage::models::{TransactionResponse, TransactionRow};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{
+    native_amount_raw, AccountRow, LargeTransferThresholdRow, SendChallengeRow, TransactionResponse,
+    TransactionRow,
+};
 use crate::AppState;
 
 #[derive(Debug, Error)]
@@ -20,10 +38,40 @@ pub enum TransactionServiceError {
     InvalidChain(String),
     #[error("Invalid address: {0}")]
     InvalidAddress(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid memo: {0}")]
+    InvalidMemo(String),
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
-    #[error("Insufficient balance")]
-    InsufficientBalance,
+    /// Amounts are in the base unit of the asset being checked
+    #[error("Insufficient balance: {required} required, {available} available")]
+    InsufficientBalance { required: u128, available: u128 },
+    #[error("{0}")]
+    RentExemption(String),
+    /// Fiat amount couldn't be converted; nothing was sent
+    #[error("{0}")]
+    PriceUnavailable(String),
+    /// Above the user's large transfer threshold; nothing was sent
+    #[error("{0}")]
+    ConfirmationRequired(String),
+    #[error("Send challenge not found or expired")]
+    ChallengeNotFound,
+    /// Not one of the tenant's accounts
+    #[error("Account not found")]
+    AccountNotFound,
+    #[error("Incorrect password")]
+    InvalidPassword,
+    #[error("Invalid threshold: {0}")]
+    InvalidThreshold(String),
+    #[error("Invalid bucket: {0}")]
+    InvalidBucket(String),
+    /// More than the named bucket holds; nothing was sent
+    #[error("{0}")]
+    BucketExceeded(String),
+    /// Sends on the chain are switched off by an operator
+    #[error(transparent)]
+    FeatureDisabled(#[from] FeatureDisabled),
     #[error("Database error: {0}")]
     DatabaseError(String),
 }
@@ -33,9 +81,33 @@ pub enum TransactionServiceError {
 pub struct BalanceResponse {
     pub chain: String,
     pub address: String,
+    /// Native balance as an exact decimal string
     pub native_balance: String,
+    /// Native balance in base units (lamports, wei)
+    pub native_balance_raw: String,
+    pub native_decimals: u32,
     pub native_symbol: String,
     pub tokens: Vec<TokenBalanceResponse>,
+    /// How the native balance is split into savings buckets, for our own
+    /// accounts that have any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buckets: Option<BucketSummary>,
+    /// Liquid staking tokens among `tokens`, valued in SOL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liquid_staking: Option<LiquidStakingSummary>,
+    /// Tokens worth less than the small balance threshold, folded out of
+    /// `tokens`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub others: Option<OtherTokens>,
+}
+
+/// Small token balances counted together instead of listed
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct OtherTokens {
+    pub count: usize,
+    /// Their combined value, rounded to cents
+    pub fiat_value: String,
+    pub fiat_currency: String,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -43,9 +115,35 @@ pub struct TokenBalanceResponse {
     pub address: String,
     pub symbol: Option<String>,
     pub name: Option<String>,
+    /// Balance in base units
     pub balance: String,
     pub decimals: u8,
+    /// `balance` as an exact decimal string
+    pub formatted_balance: String,
     pub ui_amount: f64,
+    /// Token-2022 transfer fee and interest settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<TokenExtensions>,
+    /// Logo from the token list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    /// Value at the current price, rounded to cents; set when tokens are
+    /// sorted by value or small balances are folded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiat_value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiat_currency: Option<String>,
+}
+
+impl TokenBalanceResponse {
+    /// `balance` in `decimals` as an exact decimal string, or `ui_amount`
+    /// when the node reported a balance that isn't an integer
+    pub fn format_balance(balance: &str, decimals: u8, ui_amount: f64) -> String {
+        match balance.parse::<u128>() {
+            Ok(units) => format_units(units, decimals as u32),
+            Err(_) => ui_amount.to_string(),
+        }
+    }
 }
 
 /// Get balance for an address
@@ -54,62 +152,119 @@ pub async fn get_balance(
     chain: &str,
     address: &str,
 ) -> Result<BalanceResponse, TransactionServiceError> {
-    match chain.to_lowercase().as_str() {
-        "solana" => {
-            let sol_balance = get_sol_balance_async(&state.solana_rpc_url, address)
-                .await
-                .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?;
-
-            let token_balances = get_token_balances_async(&state.solana_rpc_url, address)
-                .await
-                .unwrap_or_default();
-
-            Ok(BalanceResponse {
-                chain: "solana".to_string(),
-                address: address.to_string(),
-                native_balance: sol_balance.sol.to_string(),
-                native_symbol: "SOL".to_string(),
-                tokens: token_balances
-                    .into_iter()
-                    .map(|t| TokenBalanceResponse {
-                        address: t.mint,
-                        symbol: t.symbol,
-                        name: t.name,
-                        balance: t.amount,
-                        decimals: t.decimals,
-                        ui_amount: t.ui_amount,
-                    })
-                    .collect(),
-            })
-        }
-        "ethereum" => {
-            let eth_balance = get_eth_balance(&state.eth_rpc_url, address)
-                .await
-                .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?;
-
-            // Note: For Ethereum, token balances require knowing which tokens to check
-            // In production, use an indexer like Alchemy or Etherscan API
-
-            Ok(BalanceResponse {
-                chain: "ethereum".to_string(),
-                address: address.to_string(),
-                native_balance: eth_balance.eth.to_string(),
-                native_symbol: "ETH".to_string(),
-                tokens: vec![],
-            })
+    let chain = parse_chain(chain)?;
+    // Reads of our own accounts double as their sync heartbeat
+    let account = state
+        .db
+        .get_account_by_address(&current_tenant_id(), &chain.to_string(), address)
+        .await
+        .ok();
+    let clients = match &account {
+        Some(account) => state.account_clients(account),
+        None => state.chain_clients(),
+    };
+    let balance = clients.get(chain).balance(address).await;
+    if let Some(account) = account.as_ref().filter(|_| !state.safe_mode) {
+        let recorded = match &balance {
+            Ok(balance) => state.db.record_account_sync(&account.id, &balance.native_balance).await,
+            Err(e) => state.db.record_account_sync_error(&account.id, &e.to_string()).await,
+        };
+        if let Err(e) = recorded {
+            tracing::warn!(account_id = %account.id, error = %e, "Recording account sync failed");
         }
-        _ => Err(TransactionServiceError::InvalidChain(chain.to_string())),
+    }
+    let balance = balance?;
+
+    let mut tokens: Vec<TokenBalanceResponse> = balance
+        .tokens
+        .into_iter()
+        .map(|t| TokenBalanceResponse {
+            formatted_balance: TokenBalanceResponse::format_balance(&t.balance, t.decimals, t.ui_amount),
+            address: t.address,
+            symbol: t.symbol,
+            name: t.name,
+            balance: t.balance,
+            decimals: t.decimals,
+            ui_amount: t.ui_amount,
+            extensions: t.extensions,
+            logo_uri: None,
+            fiat_value: None,
+            fiat_currency: None,
+        })
+        .collect();
+    token_list_service::enrich_balances(state, chain, &mut tokens).await;
+    let liquid_staking = staking_service::balance_summary(&clients, chain, &tokens).await;
+
+    let buckets = match &account {
+        Some(account) => bucket_service::balance_summary(state, account, &balance.native_balance)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(account_id = %account.id, error = %e, "Reading buckets failed");
+                None
+            }),
+        None => None,
+    };
+
+    Ok(BalanceResponse {
+        chain: chain.to_string(),
+        address: address.to_string(),
+        native_balance: balance.native_balance,
+        native_balance_raw: balance.native_balance_raw,
+        native_decimals: chain.native_decimals(),
+        native_symbol: balance.native_symbol,
+        tokens,
+        buckets,
+        liquid_staking,
+        others: None,
+    })
+}
+
+/// Clients for reads about `address`: its account's own RPC endpoint when
+/// it is one of ours and has one, otherwise the tenant's
+async fn address_clients(state: &Arc<AppState>, chain: Chain, address: &str) -> ChainClients {
+    match state
+        .db
+        .get_account_by_address(&current_tenant_id(), &chain.to_string(), address)
+        .await
+    {
+        Ok(account) => state.account_clients(&account),
+        Err(_) => state.chain_clients(),
     }
 }
 
+
 /// Send request
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SendRequest {
     pub chain: String,
     pub from_address: String,
     pub to_address: String,
+    /// Native or token amount, or a fiat amount such as `"25 USD"` for native
+    /// sends; ignored when `drain_all` is set
+    #[serde(default)]
     pub amount: String,
     pub token_address: Option<String>,
+    /// Send the whole balance minus fees (Solana only)
+    #[serde(default)]
+    pub drain_all: bool,
+    /// Memo recorded on chain with the transfer (Solana only)
+    #[serde(default)]
+    pub memo: Option<String>,
+    /// Solana Pay reference keys the recipient will look the payment up by
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Broadcast through the private relay instead of the public mempool
+    /// (Ethereum); the account's default when omitted
+    #[serde(default)]
+    pub mev_protect: Option<bool>,
+    /// How a Solana send reaches the network; straight to the RPC node when
+    /// omitted
+    #[serde(default)]
+    pub submission_mode: Option<SubmissionMode>,
+    /// Savings bucket of the sending account to spend from; the send is
+    /// refused if it holds less, and charged to it once sent (native only)
+    #[serde(default)]
+    pub bucket: Option<String>,
 }
 
 /// Send response
@@ -117,138 +272,778 @@ pub struct SendRequest {
 pub struct SendResponse {
     pub tx_hash: String,
     pub status: String,
+    /// Present when the amount was entered in fiat
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversion: Option<FiatConversion>,
+}
+
+/// Fee estimate request (same shape as a send, plus priority level)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FeeEstimateRequest {
+    pub chain: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: String,
+    pub token_address: Option<String>,
+    pub priority: Option<PriorityLevel>,
+}
+
+/// Estimate the total cost of a send without signing it
+pub async fn estimate_fee(
+    state: &Arc<AppState>,
+    request: FeeEstimateRequest,
+) -> Result<FeeEstimate, TransactionServiceError> {
+    match request.chain.to_lowercase().as_str() {
+        "solana" => {
+            let transfer = match request.token_address {
+                Some(mint) => TransferKind::Token {
+                    mint,
+                    amount: request
+                        .amount
+                        .parse()
+                        .map_err(|_| TransactionServiceError::InvalidAmount(request.amount.clone()))?,
+                },
+                None => TransferKind::Sol {
+                    amount_sol: request
+                        .amount
+                        .parse()
+                        .map_err(|_| TransactionServiceError::InvalidAmount(request.amount.clone()))?,
+                },
+            };
+
+            estimate_transfer_fee_async(
+                &state.solana_rpc_url,
+                &request.from_address,
+                &request.to_address,
+                transfer,
+                request.priority.unwrap_or_default(),
+            )
+            .await
+            .map_err(|e| ChainClientError::from(e).into())
+        }
+        _ => Err(TransactionServiceError::InvalidChain(request.chain)),
+    }
+}
+
+/// Largest amount an address can send after network fees
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MaxSendResponse {
+    pub chain: String,
+    pub address: String,
+    pub token_address: Option<String>,
+    /// Base units (lamports, wei, or raw token amount)
+    pub max_amount: String,
+    pub max_ui_amount: String,
+    /// Network fee in the chain's native base unit
+    pub fee: String,
+    /// Whether the native balance covers the fee; token sends pay fees separately
+    pub fee_covered: bool,
+}
+
+/// Compute the "send all" amount for a native coin or token
+pub async fn max_send(
+    state: &Arc<AppState>,
+    chain: &str,
+    address: &str,
+    token: Option<String>,
+) -> Result<MaxSendResponse, TransactionServiceError> {
+    let chain = parse_chain(chain)?;
+    let max = address_clients(state, chain, address)
+        .await
+        .get(chain)
+        .max_send(address, token.as_deref())
+        .await?;
+
+    Ok(MaxSendResponse {
+        chain: chain.to_string(),
+        address: address.to_string(),
+        token_address: token,
+        max_amount: max.max_amount,
+        max_ui_amount: max.max_ui_amount,
+        fee: max.fee,
+        fee_covered: max.fee_covered,
+    })
 }
 
-/// Send transaction
+/// Send transaction, signed with the unlocked wallet
 pub async fn send_transaction(
     state: &Arc<AppState>,
     request: SendRequest,
 ) -> Result<SendResponse, TransactionServiceError> {
     let seed = get_seed(state).await?;
+    send_with_seed(state, &seed, request).await
+}
+
+/// A send that passed its checks, ready to sign
+struct PreparedSend {
+    chain: Chain,
+    account: AccountRow,
+    conversion: Option<FiatConversion>,
+    transfer: Transfer,
+}
+
+/// Check a send, convert a fiat amount and build the transfer
+async fn prepare_send(
+    state: &Arc<AppState>,
+    request: &SendRequest,
+) -> Result<PreparedSend, TransactionServiceError> {
+    let chain = parse_chain(&request.chain)?;
+    feature_flag_service::check(state, FeatureFlag::Sends(chain)).await?;
+    check_payment_markers(chain, request.memo.as_deref(), &request.references)?;
+    if chain != Chain::Ethereum && request.mev_protect == Some(true) {
+        return Err(TransactionServiceError::InvalidChain(format!(
+            "{} sends can't be MEV-protected",
+            chain
+        )));
+    }
+    let submission_mode = request.submission_mode.unwrap_or_default();
+    if chain != Chain::Solana && submission_mode == SubmissionMode::Jito {
+        return Err(TransactionServiceError::InvalidChain(format!(
+            "{} sends can't be submitted as Jito bundles",
+            chain
+        )));
+    }
 
     // Get account from database to find derivation index
     let account = state
         .db
-        .get_account_by_address(&request.chain, &request.from_address)
+        .get_account_by_address(&current_tenant_id(), &request.chain, &request.from_address)
         .await
+        .map_err(account_error)?;
+
+    // Fiat amounts are converted at the current price; a missing or stale
+    // price fails the send rather than guessing
+    let conversion = match price_service::parse_fiat_amount(&request.amount) {
+        Some(_) if request.drain_all => None,
+        Some(_) if request.token_address.is_some() => {
+            return Err(TransactionServiceError::InvalidAmount(
+                "fiat amounts are only supported for native coin sends".to_string(),
+            ));
+        }
+        Some(fiat) if !(fiat.value.is_finite() && fiat.value > 0.0) => {
+            return Err(TransactionServiceError::InvalidAmount(request.amount.clone()));
+        }
+        Some(fiat) => Some(price_service::convert_to_native(state, chain, &fiat).await?),
+        None => None,
+    };
+    let amount = match conversion {
+        Some(ref c) if c.native_amount == "0" => {
+            return Err(TransactionServiceError::InvalidAmount(format!(
+                "{} is below the smallest sendable unit",
+                request.amount
+            )));
+        }
+        Some(ref c) => c.native_amount.clone(),
+        None => request.amount.clone(),
+    };
+    if let Some(bucket) = &request.bucket {
+        if request.token_address.is_some() || request.drain_all {
+            return Err(TransactionServiceError::InvalidBucket(
+                "only native sends of a set amount can be charged to a bucket".to_string(),
+            ));
+        }
+        bucket_service::check_spend(state, &account, bucket, &amount).await?;
+    }
+
+    let transfer = Transfer {
+        to: request.to_address.clone(),
+        token: request.token_address.clone(),
+        amount,
+        drain_all: request.drain_all,
+        replaces: None,
+        memo: request.memo.clone(),
+        references: request.references.clone(),
+        // Only native transfers are signed here; ERC-20 sends aren't covered
+        private_rpc: (chain == Chain::Ethereum
+            && request.token_address.is_none()
+            && request.mev_protect.unwrap_or(account.mev_protect))
+        .then(|| state.config.mev_protect_rpc_url.clone()),
+        jito: (submission_mode == SubmissionMode::Jito).then(|| JitoBundle {
+            block_engine_url: state.config.jito_block_engine_url.clone(),
+            tip_lamports: state.config.jito_tip_lamports,
+        }),
+        dry_run: false,
+    };
+
+    Ok(PreparedSend {
+        chain,
+        account,
+        conversion,
+        transfer,
+    })
+}
+
+/// Send transaction, signed with `seed`
+#[tracing::instrument(skip_all, fields(chain = %request.chain, account_id, tx_hash))]
+pub async fn send_with_seed(
+    state: &Arc<AppState>,
+    seed: &SecureSeed,
+    request: SendRequest,
+) -> Result<SendResponse, TransactionServiceError> {
+    let PreparedSend {
+        chain,
+        account,
+        conversion,
+        transfer,
+    } = prepare_send(state, &request).await?;
+    tracing::Span::current().record("account_id", account.id.as_str());
+
+    let result = state
+        .account_clients(&account)
+        .get(chain)
+        .send(seed, &account.derivation_path, transfer)
+        .await
+        .inspect_err(|e| tracing::warn!(error = %e, "Send failed"))?;
+    tracing::Span::current().record("tx_hash", result.tx_hash.as_str());
+    tracing::info!(status = %result.status, amount = %result.amount, "Transaction sent");
+
+    // Store transaction in history
+    let mut tx_row = sent_row(
+        account.id,
+        chain,
+        request.from_address,
+        request.to_address,
+        request.token_address,
+        &result,
+    );
+    if let Some(ref c) = conversion {
+        tx_row.fiat_amount = Some(c.fiat_amount.clone());
+        tx_row.fiat_currency = Some(c.currency.clone());
+        tx_row.fiat_rate = Some(c.rate.to_string());
+    }
+    tx_row.memo = request.memo;
+    record_sent(state, &tx_row, &request.references).await;
+    if let Some(bucket) = &request.bucket {
+        if let Err(e) = bucket_service::record_spend(state, bucket, &result.amount, chain).await {
+            tracing::error!(bucket_id = %bucket, error = %e, "Failed to charge send to its bucket");
+        }
+    }
+
+    Ok(SendResponse {
+        tx_hash: result.tx_hash,
+        status: result.status,
+        conversion,
+    })
+}
+
+/// What a send would do, from a dry run
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SendPreview {
+    /// Always `true`; nothing was broadcast or stored
+    pub dry_run: bool,
+    pub chain: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub token_address: Option<String>,
+    /// Amount that would be sent, after fiat conversion or `drain_all`
+    pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversion: Option<FiatConversion>,
+    /// Network fee in the chain's native base unit
+    pub fee: Option<String>,
+    /// Balance changes the simulation anticipates
+    pub expected_changes: Option<TxEffects>,
+    /// How it would reach the network: `rpc`, `jito` or `private_relay`
+    pub route: String,
+    /// Set when the send would be held for the password first
+    pub confirmation_required: Option<LargeSendCheck>,
+}
+
+/// Why a previewed send would need confirming
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LargeSendCheck {
+    /// Estimated value in `currency`; `None` if it couldn't be priced
+    pub value: Option<String>,
+    pub threshold: String,
+    pub currency: String,
+}
+
+/// Run every check a send would, build and simulate it, but broadcast and
+/// store nothing. A send above the large transfer threshold is reported
+/// rather than held.
+pub async fn preview_send(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: SendRequest,
+) -> Result<SendPreview, TransactionServiceError> {
+    let confirmation_required = large_transfer(state, user_id, &request)
+        .await?
+        .map(|(threshold, value)| LargeSendCheck {
+            value: value.map(|v| format!("{:.2}", v)),
+            threshold: threshold.threshold,
+            currency: threshold.currency,
+        });
+
+    let seed = get_seed(state).await?;
+    let PreparedSend {
+        chain,
+        account,
+        conversion,
+        mut transfer,
+    } = prepare_send(state, &request).await?;
+    let route = if transfer.jito.is_some() {
+        "jito"
+    } else if transfer.private_rpc.is_some() {
+        "private_relay"
+    } else {
+        "rpc"
+    };
+    transfer.dry_run = true;
+
+    let result = state
+        .account_clients(&account)
+        .get(chain)
+        .send(&seed, &account.derivation_path, transfer)
+        .await?;
+
+    Ok(SendPreview {
+        dry_run: true,
+        chain: chain.to_string(),
+        from_address: request.from_address,
+        to_address: request.to_address,
+        token_address: request.token_address,
+        amount: result.amount,
+        conversion,
+        fee: result.expected.as_ref().map(|e| e.fee.clone()),
+        expected_changes: result.expected,
+        route: route.to_string(),
+        confirmation_required,
+    })
+}
+
+/// How long a large send waits for its confirmation
+const CHALLENGE_TTL_SECS: i64 = 300;
+/// Wrong passwords a send challenge survives
+const MAX_CHALLENGE_ATTEMPTS: i64 = 5;
+
+/// A large send held until the password is re-entered
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SendChallenge {
+    pub challenge_id: String,
+    pub expires_at: String,
+    /// Estimated value of the send in `currency`; `None` if it couldn't be
+    /// priced, which is treated as large
+    pub value: Option<String>,
+    pub threshold: String,
+    pub currency: String,
+}
+
+/// Outcome of a send request
+#[derive(Debug, Clone)]
+pub enum SendOutcome {
+    Sent(SendResponse),
+    /// Held until confirmed with `confirm_send`
+    ConfirmationRequired(SendChallenge),
+}
+
+/// Confirm a held large send
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfirmSendRequest {
+    pub challenge_id: String,
+    pub password: String,
+}
+
+/// Set the large transfer threshold; the password is asked for again so a
+/// stolen session can't lift it
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SetThresholdRequest {
+    /// Decimal amount, e.g. `"1000"`
+    pub threshold: String,
+    /// ISO 4217 code, e.g. `"USD"`
+    pub currency: String,
+    pub password: String,
+}
+
+/// Remove the large transfer threshold
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClearThresholdRequest {
+    pub password: String,
+}
+
+/// Send for a signed-in user. Sends above the user's large transfer
+/// threshold are not signed but held as a challenge, confirmed by
+/// re-entering the password.
+pub async fn request_send(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: SendRequest,
+) -> Result<SendOutcome, TransactionServiceError> {
+    let Some((threshold, value)) = large_transfer(state, user_id, &request).await? else {
+        return Ok(SendOutcome::Sent(send_transaction(state, request).await?));
+    };
+
+    let now = chrono::Utc::now();
+    state.db.delete_expired_send_challenges(&now.to_rfc3339()).await?;
+    let expires_at = (now + chrono::Duration::seconds(CHALLENGE_TTL_SECS)).to_rfc3339();
+    let held = serde_json::to_string(&request)
         .map_err(|e| TransactionServiceError::DatabaseError(e.to_string()))?;
+    let challenge = SendChallengeRow::new(user_id.to_string(), held, expires_at);
+    state.db.create_send_challenge(&challenge).await?;
+    tracing::info!(
+        user_id = %user_id,
+        challenge_id = %challenge.id,
+        "Large send held for confirmation"
+    );
 
-    match request.chain.to_lowercase().as_str() {
-        "solana" => {
-            let keypair = SolanaKeypair::derive(&seed, account.derivation_index as u32)
-                .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?;
-
-            let token_address_clone = request.token_address.clone();
-            let result = if let Some(ref token_mint) = request.token_address {
-                let amount: u64 = request
-                    .amount
-                    .parse()
-                    .map_err(|_| TransactionServiceError::TransactionFailed("Invalid amount".to_string()))?;
-
-                // Get token decimals (default to 9 for SPL tokens)
-                let decimals = 9u8;
-
-                send_token(
-                    &state.solana_rpc_url,
-                    &keypair,
-                    &request.to_address,
-                    token_mint,
-                    amount,
-                    decimals,
-                )
-                .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?
-            } else {
-                let amount: f64 = request
-                    .amount
-                    .parse()
-                    .map_err(|_| TransactionServiceError::TransactionFailed("Invalid amount".to_string()))?;
-
-                send_sol(&state.solana_rpc_url, &keypair, &request.to_address, amount)
-                    .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?
-            };
+    Ok(SendOutcome::ConfirmationRequired(SendChallenge {
+        challenge_id: challenge.id,
+        expires_at: challenge.expires_at,
+        value: value.map(|v| format!("{:.2}", v)),
+        threshold: threshold.threshold,
+        currency: threshold.currency,
+    }))
+}
 
-            // Store transaction in history
-            let tx_row = TransactionRow::new(
-                account.id,
-                "solana".to_string(),
-                result.signature.clone(),
-                "send".to_string(),
-                Some(request.from_address),
-                Some(request.to_address),
-                Some(request.amount),
-                token_address_clone,
-                result.status.clone(),
-                None,
-                Some(chrono::Utc::now().to_rfc3339()),
+/// Send a held large send once the user's password checks out. A challenge
+/// is used once, and discarded after too many wrong passwords.
+pub async fn confirm_send(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: ConfirmSendRequest,
+) -> Result<SendResponse, TransactionServiceError> {
+    let challenge = match state.db.get_send_challenge(&request.challenge_id).await {
+        Ok(challenge) if challenge.user_id == user_id => challenge,
+        Ok(_) | Err(DatabaseError::NotFound) => {
+            return Err(TransactionServiceError::ChallengeNotFound)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if challenge.expires_at <= chrono::Utc::now().to_rfc3339() {
+        state.db.take_send_challenge(&challenge.id).await?;
+        return Err(TransactionServiceError::ChallengeNotFound);
+    }
+
+    if let Err(e) = verify_password(state, user_id, &request.password).await {
+        if matches!(e, TransactionServiceError::InvalidPassword)
+            && state.db.record_send_challenge_attempt(&challenge.id).await?
+                >= MAX_CHALLENGE_ATTEMPTS
+        {
+            state.db.take_send_challenge(&challenge.id).await?;
+            tracing::warn!(
+                user_id = %user_id,
+                challenge_id = %challenge.id,
+                "Send challenge discarded after wrong passwords"
             );
+        }
+        return Err(e);
+    }
+    if !state.db.take_send_challenge(&challenge.id).await? {
+        return Err(TransactionServiceError::ChallengeNotFound);
+    }
+
+    let held: SendRequest = serde_json::from_str(&challenge.request)
+        .map_err(|e| TransactionServiceError::DatabaseError(e.to_string()))?;
+    send_transaction(state, held).await
+}
+
+/// Whether a send needs the password re-entered, and if so the threshold
+/// and the send's estimated value
+pub async fn large_transfer(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: &SendRequest,
+) -> Result<Option<(LargeTransferThresholdRow, Option<f64>)>, TransactionServiceError> {
+    let Some(threshold) = state.db.get_large_transfer_threshold(user_id).await? else {
+        return Ok(None);
+    };
+    let limit: f64 = threshold
+        .threshold
+        .parse()
+        .map_err(|_| TransactionServiceError::InvalidThreshold(threshold.threshold.clone()))?;
+
+    let chain = parse_chain(&request.chain)?;
+    let value = send_value(state, chain, request, &threshold.currency).await;
+    match value {
+        Some(value) if value <= limit => Ok(None),
+        _ => Ok(Some((threshold, value))),
+    }
+}
 
-            let _ = state.db.upsert_transaction(&tx_row).await;
+/// Value of a send in `currency`; `None` if the amount or asset couldn't be
+/// priced
+async fn send_value(
+    state: &Arc<AppState>,
+    chain: Chain,
+    request: &SendRequest,
+    currency: &str,
+) -> Option<f64> {
+    let amount: f64 = if request.drain_all {
+        let max = state
+            .chain_clients()
+            .get(chain)
+            .max_send(&request.from_address, request.token_address.as_deref())
+            .await
+            .ok()?;
+        max.max_ui_amount.parse().ok()?
+    } else if let Some(fiat) = price_service::parse_fiat_amount(&request.amount) {
+        if fiat.currency == currency {
+            return Some(fiat.value);
+        }
+        let conversion = price_service::convert_to_native(state, chain, &fiat).await.ok()?;
+        conversion.native_amount.parse().ok()?
+    } else {
+        request.amount.trim().parse().ok()?
+    };
+
+    let rate = match request.token_address.as_deref() {
+        None => state.prices.native_price(chain, currency).await.ok()?.rate,
+        Some(token) => state
+            .prices
+            .historical_price(chain, Some(token), currency, chrono::Utc::now().date_naive())
+            .await
+            .ok()??,
+    };
+    Some(amount * rate).filter(|value| value.is_finite())
+}
+
+pub async fn get_large_transfer_threshold(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Option<LargeTransferThresholdRow>, TransactionServiceError> {
+    Ok(state.db.get_large_transfer_threshold(user_id).await?)
+}
+
+pub async fn set_large_transfer_threshold(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: SetThresholdRequest,
+) -> Result<LargeTransferThresholdRow, TransactionServiceError> {
+    let threshold = request.threshold.trim();
+    if !threshold
+        .parse::<f64>()
+        .is_ok_and(|value| value.is_finite() && value >= 0.0)
+    {
+        return Err(TransactionServiceError::InvalidThreshold(request.threshold));
+    }
+    let currency = request.currency.trim().to_uppercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(TransactionServiceError::InvalidThreshold(format!(
+            "unknown currency {}",
+            request.currency
+        )));
+    }
+    verify_password(state, user_id, &request.password).await?;
+
+    let row = LargeTransferThresholdRow {
+        user_id: user_id.to_string(),
+        threshold: threshold.to_string(),
+        currency,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    state.db.set_large_transfer_threshold(&row).await?;
+    tracing::info!(
+        user_id = %user_id,
+        threshold = %row.threshold,
+        currency = %row.currency,
+        "Large transfer threshold set"
+    );
+    Ok(row)
+}
+
+pub async fn clear_large_transfer_threshold(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: ClearThresholdRequest,
+) -> Result<(), TransactionServiceError> {
+    verify_password(state, user_id, &request.password).await?;
+    state.db.delete_large_transfer_threshold(user_id).await?;
+    tracing::info!(user_id = %user_id, "Large transfer threshold cleared");
+    Ok(())
+}
+
+/// Check a password re-entered to approve a send or a threshold change
+pub async fn verify_password(
+    state: &Arc<AppState>,
+    user_id: &str,
+    password: &str,
+) -> Result<(), TransactionServiceError> {
+    state
+        .user_service
+        .verify_password(user_id, password)
+        .await
+        .map_err(|e| match e {
+            UserServiceError::InvalidCredentials => TransactionServiceError::InvalidPassword,
+            _ => TransactionServiceError::DatabaseError(e.to_string()),
+        })
+}
+
+/// Longest memo accepted, in bytes; the memo has to fit in the transaction
+const MAX_MEMO_BYTES: usize = 256;
+const MAX_REFERENCES: usize = 5;
 
-            Ok(SendResponse {
-                tx_hash: result.signature,
-                status: result.status,
-            })
+/// Whether `amount` is a positive coin or token amount, or a positive fiat
+/// amount such as `25 USD`, as stored in drafts for a later send
+pub(crate) fn is_valid_amount_input(amount: &str) -> bool {
+    match price_service::parse_fiat_amount(amount) {
+        Some(fiat) => fiat.value > 0.0,
+        None => amount.parse::<f64>().is_ok_and(|a| a > 0.0),
+    }
+}
+
+/// Memos and reference keys are Solana Pay features; they must fit in a
+/// single transaction alongside the transfer
+pub(crate) fn check_payment_markers(
+    chain: Chain,
+    memo: Option<&str>,
+    references: &[String],
+) -> Result<(), TransactionServiceError> {
+    if chain != Chain::Solana && (memo.is_some() || !references.is_empty()) {
+        return Err(TransactionServiceError::InvalidMemo(
+            "memos and reference keys are only supported on solana".to_string(),
+        ));
+    }
+    if let Some(memo) = memo {
+        if memo.is_empty() || memo.len() > MAX_MEMO_BYTES {
+            return Err(TransactionServiceError::InvalidMemo(format!(
+                "must be 1-{} bytes",
+                MAX_MEMO_BYTES
+            )));
         }
-        "ethereum" => {
-            let wallet = EthereumWallet::derive(&seed, account.derivation_index as u32)
-                .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?;
-
-            let token_address_clone = request.token_address.clone();
-            let result = if let Some(ref token_address) = request.token_address {
-                let amount: u128 = request
-                    .amount
-                    .parse()
-                    .map_err(|_| TransactionServiceError::TransactionFailed("Invalid amount".to_string()))?;
-
-                send_erc20(
-                    &state.eth_rpc_url,
-                    &wallet,
-                    token_address,
-                    &request.to_address,
-                    amount,
-                )
-                .await
-                .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?
-            } else {
-                let amount: f64 = request
-                    .amount
-                    .parse()
-                    .map_err(|_| TransactionServiceError::TransactionFailed("Invalid amount".to_string()))?;
-
-                send_eth(&state.eth_rpc_url, &wallet, &request.to_address, amount)
-                    .await
-                    .map_err(|e| TransactionServiceError::TransactionFailed(e.to_string()))?
-            };
+    }
+    if references.len() > MAX_REFERENCES {
+        return Err(TransactionServiceError::InvalidAddress(format!(
+            "at most {} reference keys",
+            MAX_REFERENCES
+        )));
+    }
+    if let Some(bad) = references
+        .iter()
+        .find(|r| !crate::chains::solana::validate_address(r))
+    {
+        return Err(TransactionServiceError::InvalidAddress(bad.clone()));
+    }
+    Ok(())
+}
 
-            // Store transaction in history
-            let tx_row = TransactionRow::new(
-                account.id,
-                "ethereum".to_string(),
-                result.tx_hash.clone(),
-                "send".to_string(),
-                Some(request.from_address),
-                Some(request.to_address),
-                Some(request.amount),
-                token_address_clone,
-                result.status.clone(),
-                None,
-                Some(chrono::Utc::now().to_rfc3339()),
-            );
+/// Store a send's history row and its reference keys. Failures are logged:
+/// the transfer has already been broadcast.
+pub async fn record_sent(state: &Arc<AppState>, tx_row: &TransactionRow, references: &[String]) {
+    if let Err(e) = state.db.upsert_transaction(tx_row).await {
+        tracing::error!(error = %e, "Failed to record sent transaction");
+        return;
+    }
+    if let Err(e) = state.db.add_transaction_references(&tx_row.id, references).await {
+        tracing::error!(error = %e, "Failed to record reference keys");
+    }
+}
+
+/// History row for a send from `account_id`, with what was recorded at
+/// broadcast. Token amounts are recorded in display units when the token's
+/// decimals are known.
+pub fn sent_row(
+    account_id: String,
+    chain: Chain,
+    from_address: String,
+    to_address: String,
+    token_address: Option<String>,
+    result: &SentTransfer,
+) -> TransactionRow {
+    let mut tx_row = TransactionRow::new(
+        account_id,
+        chain.to_string(),
+        result.tx_hash.clone(),
+        "send".to_string(),
+        Some(from_address),
+        Some(to_address),
+        Some(sent_amount(result)),
+        token_address,
+        result.status.clone(),
+        None,
+        Some(chrono::Utc::now().to_rfc3339()),
+    );
+    tx_row.token_decimals = result.token_decimals.map(i64::from);
+    tx_row.expected_changes = result
+        .expected
+        .as_ref()
+        .and_then(|e| serde_json::to_string(e).ok());
+    tx_row.broadcast = result
+        .broadcast
+        .as_ref()
+        .and_then(|b| serde_json::to_string(b).ok());
+    tx_row
+}
 
-            let _ = state.db.upsert_transaction(&tx_row).await;
+/// A token send's base units as a decimal amount; anything else as sent
+fn sent_amount(result: &SentTransfer) -> String {
+    result
+        .token_decimals
+        .zip(result.amount.parse::<u128>().ok())
+        .map(|(decimals, units)| format_units(units, decimals))
+        .unwrap_or_else(|| result.amount.clone())
+}
+
+/// Addresses that aren't the tenant's are not found
+fn account_error(e: DatabaseError) -> TransactionServiceError {
+    match e {
+        DatabaseError::NotFound => TransactionServiceError::AccountNotFound,
+        e => e.into(),
+    }
+}
+
+fn parse_chain(chain: &str) -> Result<Chain, TransactionServiceError> {
+    chain
+        .parse()
+        .map_err(|_| TransactionServiceError::InvalidChain(chain.to_string()))
+}
+
+impl From<DatabaseError> for TransactionServiceError {
+    fn from(e: DatabaseError) -> Self {
+        TransactionServiceError::DatabaseError(e.to_string())
+    }
+}
 
-            Ok(SendResponse {
-                tx_hash: result.tx_hash,
-                status: result.status,
-            })
+impl From<BucketError> for TransactionServiceError {
+    fn from(e: BucketError) -> Self {
+        match e {
+            BucketError::InvalidAmount(amount) => TransactionServiceError::InvalidAmount(amount),
+            BucketError::InsufficientFunds(message) => TransactionServiceError::BucketExceeded(message),
+            BucketError::NotFound | BucketError::InvalidBucket(_) | BucketError::AccountNotFound => {
+                TransactionServiceError::InvalidBucket(e.to_string())
+            }
+            _ => TransactionServiceError::DatabaseError(e.to_string()),
+        }
+    }
+}
+
+impl From<PriceError> for TransactionServiceError {
+    fn from(e: PriceError) -> Self {
+        match e {
+            PriceError::UnsupportedCurrency(_) => TransactionServiceError::InvalidAmount(e.to_string()),
+            _ => TransactionServiceError::PriceUnavailable(e.to_string()),
+        }
+    }
+}
+
+/// Keep user-correctable chain errors distinct from failures
+impl From<ChainClientError> for TransactionServiceError {
+    fn from(e: ChainClientError) -> Self {
+        match e {
+            ChainClientError::InvalidAddress(addr) => TransactionServiceError::InvalidAddress(addr),
+            ChainClientError::InvalidAmount(amount) => TransactionServiceError::InvalidAmount(amount),
+            ChainClientError::InsufficientBalance {
+                required,
+                available,
+            } => TransactionServiceError::InsufficientBalance {
+                required,
+                available,
+            },
+            ChainClientError::RentExemption(message) => {
+                TransactionServiceError::RentExemption(message)
+            }
+            ChainClientError::Rpc(_) | ChainClientError::TransactionFailed(_) => {
+                tracing::warn!(error = %e, "Chain request failed");
+                TransactionServiceError::TransactionFailed(e.to_string())
+            }
         }
-        _ => Err(TransactionServiceError::InvalidChain(request.chain)),
     }
 }
 
-/// Get transaction history
+
+/// Get transaction history, with tokens shown the way `user_id` sees them
+/// and transfers of tokens they hid left out
 pub async fn get_transaction_history(
     state: &Arc<AppState>,
+    user_id: &str,
     chain: &str,
     address: &str,
     limit: u32,
@@ -257,9 +1052,9 @@ pub async fn get_transaction_history(
     // Get account
     let account = state
         .db
-        .get_account_by_address(chain, address)
+        .get_account_by_address(&current_tenant_id(), chain, address)
         .await
-        .map_err(|e| TransactionServiceError::DatabaseError(e.to_string()))?;
+        .map_err(account_error)?;
 
     // Get cached transactions
     let mut transactions: Vec<TransactionResponse> = state
@@ -271,50 +1066,421 @@ pub async fn get_transaction_history(
         .map(TransactionResponse::from)
         .collect();
 
-    // Try to fetch from chain if Ethereum (best effort)
-    if chain.to_lowercase() == "ethereum" {
-        if let Ok(chain_txs) = crate::chains::ethereum::get_transaction_history(
-            &state.eth_rpc_url,
-            address,
-            limit as usize,
-        )
-        .await
+    // Add what the chain has (best effort); entries for recorded sends are
+    // merged by `canonicalize`
+    let mut fetched = false;
+    if let Ok(chain_id) = parse_chain(&account.chain) {
+        if let Ok(entries) = state
+            .account_clients(&account)
+            .get(chain_id)
+            .history(address, limit as usize)
+            .await
         {
-            for tx in chain_txs {
-                // Deduplicate by hash
-                if !transactions
-                    .iter()
-                    .any(|t| t.signature.to_lowercase() == tx.hash.to_lowercase())
-                {
-                    transactions.push(TransactionResponse {
-                        id: uuid::Uuid::new_v4().to_string(), // Ephemeral ID
-                        chain: "ethereum".to_string(),
-                        signature: tx.hash,
-                        tx_type: "external".to_string(),
-                        from_address: Some(tx.from),
-                        to_address: tx.to,
-                        amount: Some(tx.value),
-                        token_address: None,
-                        status: tx.status,
-                        block_number: tx.block_number.map(|b| b as i64),
-                        timestamp: tx.timestamp.map(|ts| {
-                            chrono::DateTime::from_timestamp(ts as i64, 0)
-                                .map(|dt| dt.to_rfc3339())
-                                .unwrap_or_default()
-                        }),
-                    });
-                }
+            fetched = !entries.is_empty();
+            for entry in entries {
+                transactions.push(TransactionResponse {
+                    id: uuid::Uuid::new_v4().to_string(), // Ephemeral ID
+                    chain: account.chain.clone(),
+                    signature: entry.hash,
+                    tx_type: "external".to_string(),
+                    from_address: entry.from,
+                    to_address: entry.to,
+                    amount_raw: entry.amount_raw.or_else(|| {
+                        let amount = entry.amount.as_deref()?;
+                        native_amount_raw(&account.chain, amount, entry.token_address.as_deref())
+                    }),
+                    amount: entry.amount,
+                    token_address: entry.token_address,
+                    status: entry.status,
+                    block_number: entry.block_number.map(|b| b as i64),
+                    timestamp: entry.block_time.map(|ts| {
+                        chrono::DateTime::from_timestamp(ts, 0)
+                            .map(|dt| dt.to_rfc3339())
+                            .unwrap_or_default()
+                    }),
+                    fiat_amount: None,
+                    fiat_currency: None,
+                    fiat_rate: None,
+                    token_id: None,
+                    expected_changes: None,
+                    actual_changes: None,
+                    effects_mismatch: None,
+                    stuck_at: None,
+                    replaced_by: None,
+                    price_at_tx: None,
+                    price_currency: None,
+                    realized_value: None,
+                    memo: entry.memo,
+                    confirmations: None,
+                    receipt: None,
+                    fee_paid: None,
+                    route_id: None,
+                    counterparty_label: None,
+                    is_own_account: None,
+                    token_symbol: None,
+                    token_logo_uri: None,
+                });
             }
-            
-            // Re-sort by timestamp descending
-            transactions.sort_by(|a, b| {
-                b.timestamp
-                    .as_deref()
-                    .unwrap_or("")
-                    .cmp(a.timestamp.as_deref().unwrap_or(""))
-            });
         }
     }
 
+    let counterparties = Counterparties::load(state, &account)
+        .await
+        .map_err(|e| TransactionServiceError::DatabaseError(e.to_string()))?;
+    for tx in &mut transactions {
+        let (label, own) =
+            counterparties.annotate(tx.from_address.as_deref(), tx.to_address.as_deref());
+        tx.counterparty_label = label;
+        tx.is_own_account = Some(own);
+    }
+    let mut transactions = canonicalize(transactions, &account.address);
+
+    if fetched {
+        // Re-sort by timestamp descending
+        transactions.sort_by(|a, b| {
+            b.timestamp
+                .as_deref()
+                .unwrap_or("")
+                .cmp(a.timestamp.as_deref().unwrap_or(""))
+        });
+    }
+
+    let displays = token_service::token_displays(
+        state,
+        user_id,
+        &account.chain,
+        transactions.iter().filter_map(|tx| tx.token_address.as_deref()),
+    )
+    .await
+    .map_err(|e| TransactionServiceError::DatabaseError(e.to_string()))?;
+    transactions.retain_mut(|tx| {
+        let Some(display) = tx
+            .token_address
+            .as_deref()
+            .and_then(|token| displays.get(&token.to_lowercase()))
+        else {
+            return true;
+        };
+        tx.token_symbol = display.symbol.clone();
+        tx.token_logo_uri = display.logo_uri.clone();
+        !display.hidden
+    });
+
     Ok(transactions)
 }
+
+/// Names a wallet knows the addresses on one chain by, for labelling the
+/// other side of an account's transactions in a single lookup
+pub struct Counterparties {
+    account_address: String,
+    /// Lower-cased address to contact (or else account) name, and whether
+    /// it is one of the wallet's accounts
+    labels: HashMap<String, (String, bool)>,
+}
+
+impl Counterparties {
+    pub async fn load(state: &Arc<AppState>, account: &AccountRow) -> Result<Self, DatabaseError> {
+        let mut labels: HashMap<String, (String, bool)> = HashMap::new();
+        for label in state.db.get_address_labels(&account.wallet_id, &account.chain).await? {
+            labels
+                .entry(label.address)
+                .and_modify(|(_, own)| *own |= label.is_own)
+                .or_insert((label.name, label.is_own));
+        }
+        Ok(Self {
+            account_address: account.address.to_lowercase(),
+            labels,
+        })
+    }
+
+    /// Label of the other side of a transaction - the recipient of what the
+    /// account sent, the sender of what it received - and whether it is
+    /// one of the wallet's own accounts
+    pub fn annotate(&self, from: Option<&str>, to: Option<&str>) -> (Option<String>, bool) {
+        let from = from.map(str::to_lowercase);
+        let other = match from {
+            Some(ref from) if *from == self.account_address => to.map(str::to_lowercase),
+            _ => from,
+        };
+        match other.and_then(|address| self.labels.get(&address)) {
+            Some((name, own)) => (Some(name.clone()), *own),
+            None => (None, false),
+        }
+    }
+}
+
+/// History types that move value from one address to another
+const TRANSFER_TYPES: &[&str] = &["send", "receive", "external"];
+
+/// Type a transfer is listed as: `internal` when its other side is another
+/// of the wallet's accounts, so a self-transfer isn't shown as spending
+pub fn canonical_type(tx_type: &str, own_counterparty: bool) -> String {
+    if own_counterparty && TRANSFER_TYPES.contains(&tx_type) {
+        "internal".to_string()
+    } else {
+        tx_type.to_string()
+    }
+}
+
+/// Which way an entry moved value for the account whose history it is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Out,
+    In,
+    Internal,
+    Other,
+}
+
+/// Merge history entries for the same transfer - recorded by the send path
+/// and returned again by chain history - keyed by (chain, signature,
+/// direction), listing transfers between the wallet's own accounts once as
+/// `internal`. Entries must be annotated with `Counterparties` first. The
+/// first entry of each transfer is kept, filled in from later ones.
+pub fn canonicalize(
+    transactions: Vec<TransactionResponse>,
+    account_address: &str,
+) -> Vec<TransactionResponse> {
+    let account_address = account_address.to_lowercase();
+    let mut merged: Vec<TransactionResponse> = Vec::with_capacity(transactions.len());
+    let mut seen: HashMap<(String, String, Direction), usize> = HashMap::new();
+
+    for mut tx in transactions {
+        tx.tx_type = canonical_type(&tx.tx_type, tx.is_own_account == Some(true));
+        let direction = if tx.tx_type == "internal" {
+            Direction::Internal
+        } else if !TRANSFER_TYPES.contains(&tx.tx_type.as_str()) {
+            Direction::Other
+        } else if tx
+            .from_address
+            .as_deref()
+            .is_some_and(|from| from.to_lowercase() == account_address)
+        {
+            Direction::Out
+        } else {
+            Direction::In
+        };
+        // Ethereum hashes are hex and compare without case; Solana
+        // signatures are base58 and don't
+        let signature = match tx.chain.as_str() {
+            "ethereum" => tx.signature.to_lowercase(),
+            _ => tx.signature.clone(),
+        };
+
+        match seen.entry((tx.chain.clone(), signature, direction)) {
+            Entry::Occupied(entry) => merge_duplicate(&mut merged[*entry.get()], tx),
+            Entry::Vacant(entry) => {
+                entry.insert(merged.len());
+                merged.push(tx);
+            }
+        }
+    }
+
+    merged
+}
+
+/// Fill what `kept` lacks from another entry for the same transfer
+fn merge_duplicate(kept: &mut TransactionResponse, other: TransactionResponse) {
+    if kept.status == "pending" && other.status != "pending" {
+        kept.status = other.status;
+    }
+    kept.block_number = kept.block_number.or(other.block_number);
+    kept.timestamp = kept.timestamp.take().or(other.timestamp);
+    kept.from_address = kept.from_address.take().or(other.from_address);
+    kept.to_address = kept.to_address.take().or(other.to_address);
+    kept.amount = kept.amount.take().or(other.amount);
+    kept.amount_raw = kept.amount_raw.take().or(other.amount_raw);
+    kept.fee_paid = kept.fee_paid.take().or(other.fee_paid);
+    kept.counterparty_label = kept.counterparty_label.take().or(other.counterparty_label);
+}
+
+/// On-chain matches returned by a reference lookup
+const REFERENCE_LOOKUP_LIMIT: usize = 20;
+
+/// Transactions carrying a Solana Pay reference key
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReferenceLookup {
+    pub reference: String,
+    /// Sends from this wallet recorded with the reference
+    pub transactions: Vec<TransactionResponse>,
+    /// What the chain has for it, newest first, including payments made
+    /// from other wallets
+    pub on_chain: Vec<ReferencedTransaction>,
+}
+
+/// Look up a payment by one of its reference keys
+pub async fn find_by_reference(
+    state: &Arc<AppState>,
+    reference: &str,
+) -> Result<ReferenceLookup, TransactionServiceError> {
+    if !crate::chains::solana::validate_address(reference) {
+        return Err(TransactionServiceError::InvalidAddress(reference.to_string()));
+    }
+
+    let transactions = state
+        .db
+        .get_transactions_by_reference(&current_tenant_id(), reference)
+        .await
+        .map_err(|e| TransactionServiceError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .map(TransactionResponse::from)
+        .collect();
+    let on_chain = state
+        .chain_clients()
+        .get(Chain::Solana)
+        .find_by_reference(reference, REFERENCE_LOOKUP_LIMIT)
+        .await?;
+
+    Ok(ReferenceLookup {
+        reference: reference.to_string(),
+        transactions,
+        on_chain,
+    })
+}
+
+/// Columns of the history CSV export
+const CSV_HEADER: &str = "date,chain,signature,type,status,from,to,amount,token,price,currency,value";
+
+/// An account's recorded history as CSV, oldest first, with each row's
+/// fiat price and value at the transaction date
+pub async fn export_history_csv(
+    state: &Arc<AppState>,
+    chain: &str,
+    address: &str,
+) -> Result<String, TransactionServiceError> {
+    let account = state
+        .db
+        .get_account_by_address(&current_tenant_id(), chain, address)
+        .await
+        .map_err(account_error)?;
+    let rows = state
+        .db
+        .get_all_transactions(&account.id)
+        .await
+        .map_err(|e| TransactionServiceError::DatabaseError(e.to_string()))?;
+
+    let mut csv = format!("{}\n", CSV_HEADER);
+    for tx in rows {
+        let value = tx.realized_value();
+        let fields = [
+            tx.timestamp.as_deref().unwrap_or(&tx.created_at),
+            &tx.chain,
+            &tx.signature,
+            &tx.tx_type,
+            &tx.status,
+            tx.from_address.as_deref().unwrap_or(""),
+            tx.to_address.as_deref().unwrap_or(""),
+            tx.amount.as_deref().unwrap_or(""),
+            tx.token_address.as_deref().unwrap_or(""),
+            tx.price_at_tx.as_deref().unwrap_or(""),
+            tx.price_currency.as_deref().unwrap_or(""),
+            value.as_deref().unwrap_or(""),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+/// Quote a field that contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("0.25"), "0.25");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    fn entry(signature: &str, tx_type: &str, from: &str, to: &str, own: bool) -> TransactionResponse {
+        let row = TransactionRow::new(
+            "account".to_string(),
+            "ethereum".to_string(),
+            signature.to_string(),
+            tx_type.to_string(),
+            Some(from.to_string()),
+            Some(to.to_string()),
+            Some("1".to_string()),
+            None,
+            "pending".to_string(),
+            None,
+            None,
+        );
+        TransactionResponse {
+            is_own_account: Some(own),
+            ..TransactionResponse::from(row)
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_merges_duplicates() {
+        let mut fetched = entry("0xABC", "external", "0xme", "0xshop", false);
+        fetched.status = "confirmed".to_string();
+        fetched.block_number = Some(7);
+        let history = vec![
+            entry("0xabc", "send", "0xMe", "0xshop", false),
+            fetched,
+            // The other side of a transfer to one of the wallet's accounts
+            entry("0xdef", "send", "0xme", "0xsavings", true),
+            entry("0xdef", "external", "0xme", "0xsavings", true),
+            // Same hash, other direction: a different transfer
+            entry("0xabc", "external", "0xshop", "0xme", false),
+        ];
+
+        let merged = canonicalize(history, "0xme");
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].tx_type, "send");
+        assert_eq!(merged[0].status, "confirmed");
+        assert_eq!(merged[0].block_number, Some(7));
+        assert_eq!(merged[1].tx_type, "internal");
+        assert_eq!(merged[2].tx_type, "external");
+        assert_eq!(canonical_type("swap", true), "swap");
+    }
+
+    #[test]
+    fn test_sent_token_amount_raw() {
+        let sent = |amount: &str, token_decimals| SentTransfer {
+            tx_hash: "0xabc".to_string(),
+            status: "pending".to_string(),
+            amount: amount.to_string(),
+            token_decimals,
+            expected: None,
+            broadcast: None,
+        };
+        let row = |token: Option<&str>, result: &SentTransfer| {
+            sent_row(
+                "account".to_string(),
+                Chain::Ethereum,
+                "0xme".to_string(),
+                "0xshop".to_string(),
+                token.map(str::to_string),
+                result,
+            )
+        };
+
+        let usdc = row(Some("0xusdc"), &sent("2500000", Some(6)));
+        assert_eq!(usdc.amount.as_deref(), Some("2.5"));
+        assert_eq!(usdc.token_decimals, Some(6));
+        assert_eq!(usdc.amount_raw().as_deref(), Some("2500000"));
+
+        // Without decimals the base units can't be told from display units
+        let unknown = row(Some("0xusdc"), &sent("2500000", None));
+        assert_eq!(unknown.amount.as_deref(), Some("2500000"));
+        assert_eq!(unknown.amount_raw(), None);
+
+        let eth = row(None, &sent("0.5", None));
+        assert_eq!(eth.amount_raw().as_deref(), Some("500000000000000000"));
+    }
+}
@@ -4,18 +4,52 @@ use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::api::middleware::tenant::{current_tenant_id, DEFAULT_TENANT};
+use crate::config::app::LoginRiskConfig;
+use crate::config::oauth::{OAuthConfig, OAuthProvider, OAuthProviderConfig};
+use crate::services::login_risk_service::{
+    self, LoginClient, LoginVerification, PriorLogin, RiskAssessment, RiskSignal,
+};
+use crate::AppState;
+use crate::storage::field_crypto::FieldCryptoError;
+use crate::storage::FieldCipher;
 use crate::storage::models::{
-    CreateUserRequest, LoginRequest, LoginResponse, RefreshTokenResponse, User, UserPublic,
-    UserSession,
+    CreateUserRequest, JwtKeyInfo, JwtKeyRow, LoginRequest, LoginResponse, OAuthAuthorizeResponse, OAuthCallbackRequest,
+    OAuthState, RefreshTokenResponse, Scope, User, UserIdentity, UserPublic, UserSession,
 };
 
+/// How long a user has to complete a provider's consent screen
+const OAUTH_STATE_TTL_MINUTES: i64 = 10;
+
+/// How long a signing token stays valid
+pub const SIGNING_TOKEN_TTL_SECS: i64 = 60;
+
+/// `purpose` of signing tokens, which access tokens lack
+const SIGNING_TOKEN_PURPOSE: &str = "signing";
+
+/// `kid` of the `JWT_SECRET` key; tokens without a `kid` were signed with it.
+/// Retiring it stores a `jwt_keys` row under this `kid` with no secret.
+pub const DEFAULT_JWT_KID: &str = "default";
+
+/// How often the running server picks up keys added or retired elsewhere
+const JWT_KEY_REFRESH_SECS: u64 = 60;
+
+/// How long a step-up verification link stays valid
+pub const LOGIN_VERIFICATION_TTL_SECS: i64 = 900;
+
 #[derive(Debug, Error)]
 pub enum UserServiceError {
     #[error("Database error: {0}")]
@@ -36,6 +70,61 @@ pub enum UserServiceError {
     PasswordHash,
     #[error("JWT error: {0}")]
     Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error("OAuth provider not configured: {0}")]
+    OAuthProviderUnavailable(String),
+    #[error("OAuth state is invalid or expired")]
+    OAuthInvalidState,
+    #[error("OAuth provider did not return a verified email")]
+    OAuthEmailUnverified,
+    #[error("OAuth provider error: {0}")]
+    OAuthProvider(String),
+    #[error("No active signing key {0}")]
+    SigningKeyNotFound(String),
+    #[error("Signing key {0} is the only active key; add another before retiring it")]
+    LastSigningKey(String),
+    #[error("Signing key could not be decrypted: {0}")]
+    SigningKeyDecryption(#[from] FieldCryptoError),
+}
+
+/// Result of a password login
+#[derive(Debug)]
+pub enum LoginOutcome {
+    /// Session opened
+    LoggedIn {
+        response: LoginResponse,
+        refresh_token: String,
+        assessment: RiskAssessment,
+    },
+    /// Too risky to open a session until the emailed link is followed
+    StepUp {
+        user_id: String,
+        verification: LoginVerification,
+        assessment: RiskAssessment,
+    },
+}
+
+/// A login held back for step-up verification
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct PendingLogin {
+    id: String,
+    user_id: String,
+    token_hash: String,
+    scopes: String,
+    device_info: Option<String>,
+    ip_address: Option<String>,
+    device_fingerprint: Option<String>,
+    country: Option<String>,
+    risk_score: i64,
+    signals: String,
+    expires_at: String,
+    verified_at: Option<String>,
+}
+
+/// Provider account details used to find or create the user
+struct OAuthProfile {
+    subject: String,
+    /// Only set when the provider has verified it
+    email: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,27 +134,86 @@ pub struct Claims {
     pub session_id: String,
     pub exp: usize,         // expiration timestamp
     pub iat: usize,         // issued at
+    /// Tenant the token was issued for; absent on tokens from before tenancy
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// What the token may be used for; tokens from before scopes have all
+    #[serde(default = "Scope::all")]
+    pub scopes: Vec<Scope>,
+}
+
+impl Claims {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// Short-lived token required alongside the access token to sign a send,
+/// swap or multisig execution. Tied to the session it was minted in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SigningClaims {
+    sub: String,
+    session_id: String,
+    purpose: String,
+    exp: usize,
+    iat: usize,
+    #[serde(default)]
+    tenant_id: Option<String>,
 }
 
 pub struct UserService {
     pool: SqlitePool,
     jwt_secret: String,
+    /// Active keys from `jwt_keys`, oldest first, with secrets opened; the
+    /// last one signs
+    jwt_keys: RwLock<Vec<JwtKeyRow>>,
+    /// Tokens signed with `JWT_SECRET` are refused
+    default_key_retired: AtomicBool,
+    /// Seals `jwt_keys` secrets when `FIELD_ENCRYPTION_KEY` is set
+    key_cipher: Option<FieldCipher>,
     access_token_expiry: Duration,
     refresh_token_expiry: Duration,
+    oauth: OAuthConfig,
+    http: reqwest::Client,
+    /// Risk score from which password logins need step-up verification;
+    /// `None` when no verification webhook is configured
+    step_up_score: Option<u8>,
 }
 
 impl UserService {
-    pub fn new(pool: SqlitePool, jwt_secret: String) -> Self {
+    pub fn new(pool: SqlitePool, jwt_secret: String, oauth: OAuthConfig) -> Self {
         Self {
             pool,
             jwt_secret,
+            jwt_keys: RwLock::new(Vec::new()),
+            default_key_retired: AtomicBool::new(false),
+            key_cipher: None,
             access_token_expiry: Duration::minutes(15),
             refresh_token_expiry: Duration::days(7),
+            oauth,
+            http: reqwest::Client::new(),
+            step_up_score: None,
         }
     }
 
+    /// Store signing key secrets encrypted under `cipher`
+    pub fn with_field_encryption(mut self, cipher: FieldCipher) -> Self {
+        self.key_cipher = Some(cipher);
+        self
+    }
+
+    /// Hold back risky password logins for verification when a webhook to
+    /// send the link through is configured
+    pub fn with_login_risk(mut self, config: &LoginRiskConfig) -> Self {
+        self.step_up_score = config
+            .verification_webhook_url
+            .is_some()
+            .then_some(config.step_up_score);
+        self
+    }
+
     pub async fn register(&self, req: CreateUserRequest) -> Result<UserPublic, UserServiceError> {
-        // Check if user already exists
+        // Check if user already exists (emails are unique across tenants)
         let existing: Option<User> = sqlx::query_as(
             "SELECT * FROM users WHERE email = ? LIMIT 1"
         )
@@ -78,20 +226,15 @@ impl UserService {
         }
 
         // Hash password with Argon2id
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        let password_hash = argon2
-            .hash_password(req.password.as_bytes(), &salt)
-            .map_err(|_| UserServiceError::PasswordHash)?
-            .to_string();
+        let password_hash = hash_password(&req.password)?;
 
         let user_id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
 
         sqlx::query(
             r#"
-            INSERT INTO users (id, email, password_hash, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO users (id, email, password_hash, created_at, updated_at, password_changed_at, tenant_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&user_id)
@@ -99,9 +242,12 @@ impl UserService {
         .bind(&password_hash)
         .bind(&now)
         .bind(&now)
+        .bind(&now)
+        .bind(current_tenant_id())
         .execute(&self.pool)
         .await?;
 
+        tracing::info!(user_id = %user_id, "User registered");
         Ok(UserPublic {
             id: user_id,
             email: req.email.to_lowercase(),
@@ -110,16 +256,20 @@ impl UserService {
         })
     }
 
+    /// Check the password and score the login against the user's recent
+    /// sessions. Risky logins wait for step-up verification when enabled.
     pub async fn login(
         &self,
         req: LoginRequest,
-        device_info: Option<String>,
-        ip_address: Option<String>,
-    ) -> Result<(LoginResponse, String), UserServiceError> {
-        // Find user by email
-        let user: User = sqlx::query_as("SELECT * FROM users WHERE email = ? AND is_active = 1")
-            .bind(&req.email.to_lowercase())
-            .fetch_optional(&self.pool)
+        client: LoginClient,
+    ) -> Result<LoginOutcome, UserServiceError> {
+        // Find user by email within the current tenant
+        let user: User = sqlx::query_as(
+            "SELECT * FROM users WHERE email = ? AND tenant_id = ? AND is_active = 1",
+        )
+        .bind(req.email.to_lowercase())
+        .bind(current_tenant_id())
+        .fetch_optional(&self.pool)
             .await?
             .ok_or(UserServiceError::InvalidCredentials)?;
 
@@ -130,6 +280,186 @@ impl UserService {
             .verify_password(req.password.as_bytes(), &parsed_hash)
             .map_err(|_| UserServiceError::InvalidCredentials)?;
 
+        let mut scopes = req.scopes.unwrap_or_else(Scope::all);
+        if !scopes.contains(&Scope::Read) {
+            scopes.push(Scope::Read);
+        }
+        scopes.sort_by_key(|s| *s as u8);
+        scopes.dedup();
+
+        let history: Vec<PriorLogin> = sqlx::query_as(
+            r#"
+            SELECT ip_address, device_fingerprint, country, created_at FROM user_sessions
+            WHERE user_id = ? ORDER BY created_at DESC LIMIT ?
+            "#,
+        )
+        .bind(&user.id)
+        .bind(login_risk_service::HISTORY_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+        let assessment = login_risk_service::assess(&client, &history, Utc::now());
+
+        match self.step_up_score {
+            Some(threshold) if assessment.score >= threshold => {
+                let verification = self
+                    .hold_login(&user, &scopes, &client, &assessment)
+                    .await?;
+                Ok(LoginOutcome::StepUp {
+                    user_id: user.id,
+                    verification,
+                    assessment,
+                })
+            }
+            _ => {
+                let (response, refresh_token) = self
+                    .start_session(user, &scopes, &client, assessment.score)
+                    .await?;
+                Ok(LoginOutcome::LoggedIn {
+                    response,
+                    refresh_token,
+                    assessment,
+                })
+            }
+        }
+    }
+
+    /// Store a login awaiting verification and mint the token for its link
+    async fn hold_login(
+        &self,
+        user: &User,
+        scopes: &[Scope],
+        client: &LoginClient,
+        assessment: &RiskAssessment,
+    ) -> Result<LoginVerification, UserServiceError> {
+        let token = random_token();
+        let now = Utc::now();
+        let verification = LoginVerification {
+            id: Uuid::new_v4().to_string(),
+            token,
+            email: user.email.clone(),
+            expires_at: (now + Duration::seconds(LOGIN_VERIFICATION_TTL_SECS)).to_rfc3339(),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO login_verifications
+                (id, user_id, tenant_id, token_hash, scopes, device_info, ip_address,
+                 device_fingerprint, country, risk_score, signals, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&verification.id)
+        .bind(&user.id)
+        .bind(&user.tenant_id)
+        .bind(self.hash_token(&verification.token))
+        .bind(Scope::join(scopes))
+        .bind(&client.device_info)
+        .bind(&client.ip_address)
+        .bind(&client.device_fingerprint)
+        .bind(&client.country)
+        .bind(assessment.score as i64)
+        .bind(RiskSignal::join(&assessment.signals))
+        .bind(&verification.expires_at)
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        tracing::warn!(user_id = %user.id, risk_score = assessment.score, "Login held for verification");
+        Ok(verification)
+    }
+
+    /// Open the session a risky login was held back for, once the token from
+    /// its verification link is presented. Links work once.
+    pub async fn verify_login(
+        &self,
+        challenge_id: &str,
+        token: &str,
+    ) -> Result<(LoginResponse, String, LoginClient, RiskAssessment), UserServiceError> {
+        let pending: PendingLogin = sqlx::query_as(
+            "SELECT * FROM login_verifications WHERE id = ? AND tenant_id = ?",
+        )
+        .bind(challenge_id)
+        .bind(current_tenant_id())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(UserServiceError::InvalidToken)?;
+
+        if pending.verified_at.is_some() || pending.token_hash != self.hash_token(token) {
+            return Err(UserServiceError::InvalidToken);
+        }
+        let expires_at = chrono::DateTime::parse_from_rfc3339(&pending.expires_at)
+            .map_err(|_| UserServiceError::InvalidToken)?;
+        if Utc::now() > expires_at {
+            return Err(UserServiceError::TokenExpired);
+        }
+
+        // Claim the link before opening the session so it can't be used twice
+        let claimed = sqlx::query(
+            "UPDATE login_verifications SET verified_at = ? WHERE id = ? AND verified_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(&pending.id)
+        .execute(&self.pool)
+        .await?;
+        if claimed.rows_affected() == 0 {
+            return Err(UserServiceError::InvalidToken);
+        }
+
+        let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ? AND is_active = 1")
+            .bind(&pending.user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(UserServiceError::InvalidCredentials)?;
+
+        let client = LoginClient {
+            device_info: pending.device_info,
+            ip_address: pending.ip_address,
+            device_fingerprint: pending.device_fingerprint,
+            country: pending.country,
+        };
+        let assessment = RiskAssessment {
+            score: pending.risk_score.clamp(0, 100) as u8,
+            signals: RiskSignal::parse_list(&pending.signals),
+        };
+        let scopes = Scope::parse_list(&pending.scopes);
+        let (response, refresh_token) = self
+            .start_session(user, &scopes, &client, assessment.score)
+            .await?;
+        Ok((response, refresh_token, client, assessment))
+    }
+
+    /// Log in a user who proved control of a linked wallet address
+    pub async fn login_verified(
+        &self,
+        user_id: &str,
+        device_info: Option<String>,
+        ip_address: Option<String>,
+    ) -> Result<(LoginResponse, String), UserServiceError> {
+        let user: User = sqlx::query_as(
+            "SELECT * FROM users WHERE id = ? AND tenant_id = ? AND is_active = 1",
+        )
+        .bind(user_id)
+        .bind(current_tenant_id())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(UserServiceError::InvalidCredentials)?;
+
+        let client = LoginClient {
+            device_info,
+            ip_address,
+            ..LoginClient::default()
+        };
+        self.start_session(user, &Scope::all(), &client, 0).await
+    }
+
+    /// Open a session for an authenticated user and issue its tokens
+    async fn start_session(
+        &self,
+        user: User,
+        scopes: &[Scope],
+        client: &LoginClient,
+        risk_score: u8,
+    ) -> Result<(LoginResponse, String), UserServiceError> {
         // Create session
         let session_id = Uuid::new_v4().to_string();
         let refresh_token = Uuid::new_v4().to_string();
@@ -139,17 +469,21 @@ impl UserService {
 
         sqlx::query(
             r#"
-            INSERT INTO user_sessions (id, user_id, refresh_token_hash, device_info, ip_address, created_at, expires_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO user_sessions (id, user_id, refresh_token_hash, device_info, ip_address, created_at, expires_at, scopes, device_fingerprint, country, risk_score)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&session_id)
         .bind(&user.id)
         .bind(&refresh_token_hash)
-        .bind(&device_info)
-        .bind(&ip_address)
+        .bind(&client.device_info)
+        .bind(&client.ip_address)
         .bind(now.to_rfc3339())
         .bind(expires_at.to_rfc3339())
+        .bind(Scope::join(scopes))
+        .bind(&client.device_fingerprint)
+        .bind(&client.country)
+        .bind(risk_score as i64)
         .execute(&self.pool)
         .await?;
 
@@ -160,14 +494,29 @@ impl UserService {
             .execute(&self.pool)
             .await?;
 
+        // Logging in during an account deletion's grace period cancels it
+        let cancelled = sqlx::query(
+            "UPDATE account_deletions SET cancelled_at = ? \
+             WHERE user_id = ? AND confirmed_at IS NOT NULL AND cancelled_at IS NULL AND purged_at IS NULL",
+        )
+        .bind(now.to_rfc3339())
+        .bind(&user.id)
+        .execute(&self.pool)
+        .await?;
+        if cancelled.rows_affected() > 0 {
+            tracing::warn!(user_id = %user.id, "Account deletion cancelled by login");
+        }
+
         // Generate access token
-        let access_token = self.generate_access_token(&user, &session_id)?;
+        let access_token = self.generate_access_token(&user, &session_id, scopes)?;
+        tracing::info!(user_id = %user.id, session_id = %session_id, "User logged in");
 
         Ok((
             LoginResponse {
                 access_token,
                 token_type: "Bearer".to_string(),
                 expires_in: self.access_token_expiry.num_seconds(),
+                scopes: scopes.to_vec(),
                 user: user.into(),
             },
             refresh_token,
@@ -196,15 +545,19 @@ impl UserService {
             return Err(UserServiceError::TokenExpired);
         }
 
-        // Get user
-        let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ? AND is_active = 1")
-            .bind(&session.user_id)
-            .fetch_optional(&self.pool)
+        // Get user; sessions can only be refreshed through their own tenant
+        let user: User = sqlx::query_as(
+            "SELECT * FROM users WHERE id = ? AND tenant_id = ? AND is_active = 1",
+        )
+        .bind(&session.user_id)
+        .bind(current_tenant_id())
+        .fetch_optional(&self.pool)
             .await?
             .ok_or(UserServiceError::UserNotFound)?;
 
         // Generate new access token
-        let access_token = self.generate_access_token(&user, &session.id)?;
+        let scopes = Scope::parse_list(&session.scopes);
+        let access_token = self.generate_access_token(&user, &session.id, &scopes)?;
 
         Ok(RefreshTokenResponse {
             access_token,
@@ -232,48 +585,54 @@ impl UserService {
     }
 
     pub async fn get_user(&self, user_id: &str) -> Result<UserPublic, UserServiceError> {
-        let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ?")
+        Ok(self.find_user(user_id).await?.into())
+    }
+
+    /// Fetch the full user record (including security settings)
+    pub async fn find_user(&self, user_id: &str) -> Result<User, UserServiceError> {
+        sqlx::query_as("SELECT * FROM users WHERE id = ?")
             .bind(user_id)
             .fetch_optional(&self.pool)
             .await?
-            .ok_or(UserServiceError::UserNotFound)?;
-        Ok(user.into())
+            .ok_or(UserServiceError::UserNotFound)
     }
 
-    pub async fn change_password(
+    /// Check a signed-in user's password, for actions that ask for it again
+    pub async fn verify_password(
         &self,
         user_id: &str,
-        current_password: &str,
-        new_password: &str,
+        password: &str,
     ) -> Result<(), UserServiceError> {
-        let user: User = sqlx::query_as("SELECT * FROM users WHERE id = ?")
-            .bind(user_id)
-            .fetch_optional(&self.pool)
-            .await?
-            .ok_or(UserServiceError::UserNotFound)?;
-
-        // Verify current password
+        let user = self.find_user(user_id).await?;
         let parsed_hash =
             PasswordHash::new(&user.password_hash).map_err(|_| UserServiceError::PasswordHash)?;
         Argon2::default()
-            .verify_password(current_password.as_bytes(), &parsed_hash)
-            .map_err(|_| UserServiceError::InvalidCredentials)?;
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .map_err(|_| UserServiceError::InvalidCredentials)
+    }
+
+    pub async fn change_password(
+        &self,
+        user_id: &str,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), UserServiceError> {
+        self.verify_password(user_id, current_password).await?;
 
         // Hash new password
-        let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-        let new_password_hash = argon2
-            .hash_password(new_password.as_bytes(), &salt)
-            .map_err(|_| UserServiceError::PasswordHash)?
-            .to_string();
+        let new_password_hash = hash_password(new_password)?;
 
         // Update password
-        sqlx::query("UPDATE users SET password_hash = ?, updated_at = ? WHERE id = ?")
-            .bind(&new_password_hash)
-            .bind(Utc::now().to_rfc3339())
-            .bind(user_id)
-            .execute(&self.pool)
-            .await?;
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "UPDATE users SET password_hash = ?, updated_at = ?, password_changed_at = ? WHERE id = ?",
+        )
+        .bind(&new_password_hash)
+        .bind(&now)
+        .bind(&now)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
 
         // Revoke all sessions
         self.logout_all(user_id).await?;
@@ -281,19 +640,358 @@ impl UserService {
         Ok(())
     }
 
+    /// Names of the configured social login providers
+    pub fn oauth_providers(&self) -> Vec<String> {
+        self.oauth
+            .providers
+            .iter()
+            .map(|p| p.provider.to_string())
+            .collect()
+    }
+
+    /// Start an authorization-code flow: remember a state and PKCE verifier
+    /// and build the provider URL to redirect the user to
+    pub async fn oauth_authorize(
+        &self,
+        provider: &str,
+    ) -> Result<OAuthAuthorizeResponse, UserServiceError> {
+        let config = self.oauth_provider(provider)?;
+
+        let state = random_token();
+        let code_verifier = random_token();
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        let now = Utc::now();
+
+        sqlx::query("DELETE FROM oauth_states WHERE expires_at < ?")
+            .bind(now.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_states (state, tenant_id, provider, code_verifier, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&state)
+        .bind(current_tenant_id())
+        .bind(config.provider.to_string())
+        .bind(&code_verifier)
+        .bind((now + Duration::minutes(OAUTH_STATE_TTL_MINUTES)).to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        let mut authorization_url = reqwest::Url::parse(&config.auth_url)
+            .map_err(|e| UserServiceError::OAuthProvider(e.to_string()))?;
+        authorization_url
+            .query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", &self.oauth.redirect_uri)
+            .append_pair("scope", config.provider.scopes())
+            .append_pair("state", &state)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(OAuthAuthorizeResponse {
+            authorization_url: authorization_url.to_string(),
+            state,
+        })
+    }
+
+    /// Finish an authorization-code flow and log the user in. The provider
+    /// account is matched to a linked user, else to a user with the same
+    /// verified email, else a new user is created.
+    pub async fn oauth_login(
+        &self,
+        provider: &str,
+        req: OAuthCallbackRequest,
+        device_info: Option<String>,
+        ip_address: Option<String>,
+    ) -> Result<(LoginResponse, String), UserServiceError> {
+        let config = self.oauth_provider(provider)?;
+        let tenant_id = current_tenant_id();
+
+        // States are single-use regardless of outcome
+        let state: OAuthState = sqlx::query_as(
+            "DELETE FROM oauth_states WHERE state = ? AND provider = ? AND tenant_id = ? RETURNING *",
+        )
+        .bind(&req.state)
+        .bind(config.provider.to_string())
+        .bind(&tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(UserServiceError::OAuthInvalidState)?;
+
+        let expires_at = chrono::DateTime::parse_from_rfc3339(&state.expires_at)
+            .map_err(|_| UserServiceError::OAuthInvalidState)?;
+        if Utc::now() > expires_at {
+            return Err(UserServiceError::OAuthInvalidState);
+        }
+
+        let profile = self
+            .oauth_profile(config, &req.code, &state.code_verifier)
+            .await?;
+
+        let identity: Option<UserIdentity> = sqlx::query_as(
+            "SELECT * FROM user_identities WHERE provider = ? AND subject = ?",
+        )
+        .bind(config.provider.to_string())
+        .bind(&profile.subject)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let user = match identity {
+            Some(identity) => self.find_user(&identity.user_id).await?,
+            None => {
+                let email = profile.email.ok_or(UserServiceError::OAuthEmailUnverified)?;
+                let user = self.find_or_create_oauth_user(&email).await?;
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO user_identities (id, user_id, provider, subject, email, created_at)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(&user.id)
+                .bind(config.provider.to_string())
+                .bind(&profile.subject)
+                .bind(&email)
+                .bind(Utc::now().to_rfc3339())
+                .execute(&self.pool)
+                .await?;
+                tracing::info!(user_id = %user.id, provider = %config.provider, "OAuth identity linked");
+                user
+            }
+        };
+
+        // Linked accounts only sign in through their own tenant
+        if user.tenant_id != tenant_id || !user.is_active {
+            return Err(UserServiceError::InvalidCredentials);
+        }
+
+        let client = LoginClient {
+            device_info,
+            ip_address,
+            ..LoginClient::default()
+        };
+        self.start_session(user, &Scope::all(), &client, 0).await
+    }
+
+    fn oauth_provider(&self, provider: &str) -> Result<&OAuthProviderConfig, UserServiceError> {
+        provider
+            .parse::<OAuthProvider>()
+            .ok()
+            .and_then(|p| self.oauth.provider(p))
+            .ok_or_else(|| UserServiceError::OAuthProviderUnavailable(provider.to_string()))
+    }
+
+    /// User with this email, marked verified, or a new passwordless one
+    async fn find_or_create_oauth_user(&self, email: &str) -> Result<User, UserServiceError> {
+        let email = email.to_lowercase();
+        let now = Utc::now().to_rfc3339();
+
+        let existing: Option<User> = sqlx::query_as("SELECT * FROM users WHERE email = ?")
+            .bind(&email)
+            .fetch_optional(&self.pool)
+            .await?;
+        if let Some(user) = existing {
+            // Emails are unique across tenants; never link into another one
+            if user.tenant_id != current_tenant_id() {
+                return Err(UserServiceError::UserAlreadyExists);
+            }
+            sqlx::query("UPDATE users SET email_verified = 1, updated_at = ? WHERE id = ?")
+                .bind(&now)
+                .bind(&user.id)
+                .execute(&self.pool)
+                .await?;
+            return self.find_user(&user.id).await;
+        }
+
+        // The password is random and never revealed, so the account signs in
+        // through its provider
+        let user_id = Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO users (id, email, password_hash, created_at, updated_at, email_verified, tenant_id)
+            VALUES (?, ?, ?, ?, ?, 1, ?)
+            "#,
+        )
+        .bind(&user_id)
+        .bind(&email)
+        .bind(hash_password(&random_token())?)
+        .bind(&now)
+        .bind(&now)
+        .bind(current_tenant_id())
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!(user_id = %user_id, "User registered via OAuth");
+        self.find_user(&user_id).await
+    }
+
+    /// Exchange the authorization code and fetch the account it grants
+    async fn oauth_profile(
+        &self,
+        config: &OAuthProviderConfig,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OAuthProfile, UserServiceError> {
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: Option<String>,
+            error: Option<String>,
+        }
+
+        let token: TokenResponse = self
+            .http
+            .post(&config.token_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.oauth.redirect_uri.as_str()),
+                ("client_id", config.client_id.as_str()),
+                ("client_secret", config.client_secret.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| UserServiceError::OAuthProvider(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| UserServiceError::OAuthProvider(e.to_string()))?;
+
+        let access_token = match token {
+            TokenResponse {
+                access_token: Some(access_token),
+                ..
+            } => access_token,
+            TokenResponse { error, .. } => {
+                return Err(UserServiceError::OAuthProvider(
+                    error.unwrap_or_else(|| "no access token".to_string()),
+                ))
+            }
+        };
+
+        match config.provider {
+            OAuthProvider::Google => {
+                #[derive(Deserialize)]
+                struct UserInfo {
+                    sub: String,
+                    email: Option<String>,
+                    #[serde(default)]
+                    email_verified: bool,
+                }
+
+                let info: UserInfo = self.oauth_get(&config.userinfo_url, &access_token).await?;
+                Ok(OAuthProfile {
+                    subject: info.sub,
+                    email: info.email.filter(|_| info.email_verified),
+                })
+            }
+            OAuthProvider::GitHub => {
+                #[derive(Deserialize)]
+                struct GitHubUser {
+                    id: u64,
+                }
+                #[derive(Deserialize)]
+                struct GitHubEmail {
+                    email: String,
+                    primary: bool,
+                    verified: bool,
+                }
+
+                // The profile email may be unverified, so use the email list
+                let user: GitHubUser = self.oauth_get(&config.userinfo_url, &access_token).await?;
+                let emails: Vec<GitHubEmail> = self
+                    .oauth_get(&format!("{}/emails", config.userinfo_url), &access_token)
+                    .await?;
+                Ok(OAuthProfile {
+                    subject: user.id.to_string(),
+                    email: emails
+                        .into_iter()
+                        .find(|e| e.primary && e.verified)
+                        .map(|e| e.email),
+                })
+            }
+        }
+    }
+
+    async fn oauth_get<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        access_token: &str,
+    ) -> Result<T, UserServiceError> {
+        self.http
+            .get(url)
+            .bearer_auth(access_token)
+            // GitHub rejects requests without one
+            .header(reqwest::header::USER_AGENT, "valtix-wallet-backend")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| UserServiceError::OAuthProvider(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| UserServiceError::OAuthProvider(e.to_string()))
+    }
+
     pub fn validate_token(&self, token: &str) -> Result<Claims, UserServiceError> {
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
-            &Validation::default(),
-        )?;
+        let token_data = decode::<Claims>(token, &self.decoding_key(token)?, &Validation::default())?;
+
+        // A token is only valid for the tenant it was issued for
+        let tenant_id = token_data.claims.tenant_id.as_deref().unwrap_or(DEFAULT_TENANT);
+        if tenant_id != current_tenant_id() {
+            return Err(UserServiceError::InvalidToken);
+        }
         Ok(token_data.claims)
     }
 
+    /// Mint a signing token for the session `claims` belong to
+    pub fn generate_signing_token(&self, claims: &Claims) -> Result<String, UserServiceError> {
+        let now = Utc::now();
+        let signing = SigningClaims {
+            sub: claims.sub.clone(),
+            session_id: claims.session_id.clone(),
+            purpose: SIGNING_TOKEN_PURPOSE.to_string(),
+            exp: (now + Duration::seconds(SIGNING_TOKEN_TTL_SECS)).timestamp() as usize,
+            iat: now.timestamp() as usize,
+            tenant_id: claims.tenant_id.clone(),
+        };
+
+        let (header, key) = self.encoding_key();
+        Ok(encode(&header, &signing, &key)?)
+    }
+
+    /// Check a signing token was minted in the same session as `claims`
+    pub fn validate_signing_token(
+        &self,
+        token: &str,
+        claims: &Claims,
+    ) -> Result<(), UserServiceError> {
+        let mut validation = Validation::default();
+        // No clock leeway: the token is only meant to live a minute
+        validation.leeway = 0;
+        let signing = decode::<SigningClaims>(token, &self.decoding_key(token)?, &validation)?.claims;
+
+        if signing.purpose != SIGNING_TOKEN_PURPOSE
+            || signing.sub != claims.sub
+            || signing.session_id != claims.session_id
+            || signing.tenant_id != claims.tenant_id
+        {
+            return Err(UserServiceError::InvalidToken);
+        }
+        Ok(())
+    }
+
     fn generate_access_token(
         &self,
         user: &User,
         session_id: &str,
+        scopes: &[Scope],
     ) -> Result<String, UserServiceError> {
         let now = Utc::now();
         let exp = now + self.access_token_expiry;
@@ -304,21 +1002,233 @@ impl UserService {
             session_id: session_id.to_string(),
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
+            tenant_id: Some(user.tenant_id.clone()),
+            scopes: scopes.to_vec(),
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.jwt_secret.as_bytes()),
-        )?;
+        let (header, key) = self.encoding_key();
+        let token = encode(&header, &claims, &key)?;
 
         Ok(token)
     }
 
+    /// Header and key for new tokens: the newest active key, or `JWT_SECRET`
+    /// while none has been added
+    fn encoding_key(&self) -> (Header, EncodingKey) {
+        let keys = self.jwt_keys.read().unwrap_or_else(|e| e.into_inner());
+        let (kid, secret) = match keys.last() {
+            Some(key) => (key.kid.as_str(), key.secret.as_str()),
+            None => (DEFAULT_JWT_KID, self.jwt_secret.as_str()),
+        };
+        let header = Header {
+            kid: Some(kid.to_string()),
+            ..Header::default()
+        };
+        (header, EncodingKey::from_secret(secret.as_bytes()))
+    }
+
+    /// Key for the `kid` in `token`'s header; retired and unknown keys are refused
+    fn decoding_key(&self, token: &str) -> Result<DecodingKey, UserServiceError> {
+        let kid = decode_header(token)?.kid;
+        match kid.as_deref() {
+            None | Some(DEFAULT_JWT_KID) if self.default_key_retired.load(Ordering::Relaxed) => {
+                Err(UserServiceError::InvalidToken)
+            }
+            None | Some(DEFAULT_JWT_KID) => Ok(DecodingKey::from_secret(self.jwt_secret.as_bytes())),
+            Some(kid) => self
+                .jwt_keys
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .find(|key| key.kid == kid)
+                .map(|key| DecodingKey::from_secret(key.secret.as_bytes()))
+                .ok_or(UserServiceError::InvalidToken),
+        }
+    }
+
+    /// Reload the active signing keys, and whether `JWT_SECRET` was retired,
+    /// from the database
+    pub async fn load_signing_keys(&self) -> Result<usize, UserServiceError> {
+        let mut keys = sqlx::query_as::<_, JwtKeyRow>(
+            "SELECT * FROM jwt_keys WHERE retired_at IS NULL ORDER BY created_at ASC, rowid ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        if let Some(cipher) = &self.key_cipher {
+            for key in &mut keys {
+                key.secret = cipher.decrypt(&key.secret)?;
+            }
+        }
+        let default_retired: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM jwt_keys WHERE kid = ?)")
+                .bind(DEFAULT_JWT_KID)
+                .fetch_one(&self.pool)
+                .await?;
+
+        let count = keys.len();
+        *self.jwt_keys.write().unwrap_or_else(|e| e.into_inner()) = keys;
+        self.default_key_retired.store(default_retired, Ordering::Relaxed);
+        Ok(count)
+    }
+
+    /// All keys added so far, newest first. The `JWT_SECRET` key is listed
+    /// once retired.
+    pub async fn list_signing_keys(&self) -> Result<Vec<JwtKeyInfo>, UserServiceError> {
+        let rows = sqlx::query_as::<_, JwtKeyRow>(
+            "SELECT * FROM jwt_keys ORDER BY created_at DESC, rowid DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let current = rows.iter().find(|row| row.retired_at.is_none()).map(|row| row.kid.clone());
+        Ok(rows
+            .into_iter()
+            .map(|row| JwtKeyInfo {
+                current: current.as_deref() == Some(row.kid.as_str()),
+                kid: row.kid,
+                created_at: row.created_at,
+                retired_at: row.retired_at,
+            })
+            .collect())
+    }
+
+    /// Generate a key and sign new tokens with it. Tokens signed with older
+    /// keys stay valid until those keys are retired.
+    pub async fn add_signing_key(&self) -> Result<JwtKeyInfo, UserServiceError> {
+        let mut kid = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut kid);
+        let row = JwtKeyRow {
+            kid: hex::encode(kid),
+            secret: random_token(),
+            created_at: Utc::now().to_rfc3339(),
+            retired_at: None,
+        };
+
+        let secret = match &self.key_cipher {
+            Some(cipher) => cipher.encrypt(&row.secret),
+            None => row.secret.clone(),
+        };
+        sqlx::query("INSERT INTO jwt_keys (kid, secret, created_at) VALUES (?, ?, ?)")
+            .bind(&row.kid)
+            .bind(&secret)
+            .bind(&row.created_at)
+            .execute(&self.pool)
+            .await?;
+        self.load_signing_keys().await?;
+
+        Ok(JwtKeyInfo {
+            kid: row.kid,
+            created_at: row.created_at,
+            retired_at: None,
+            current: true,
+        })
+    }
+
+    /// Stop accepting tokens signed with `kid`. Retiring the newest key
+    /// hands signing back to the next newest, or to `JWT_SECRET`; the last
+    /// key left can't be retired.
+    pub async fn retire_signing_key(&self, kid: &str) -> Result<(), UserServiceError> {
+        self.load_signing_keys().await?;
+        let active = self.jwt_keys.read().unwrap_or_else(|e| e.into_inner()).len();
+        let default_retired = self.default_key_retired.load(Ordering::Relaxed);
+
+        if kid == DEFAULT_JWT_KID {
+            if default_retired {
+                return Err(UserServiceError::SigningKeyNotFound(kid.to_string()));
+            }
+            if active == 0 {
+                return Err(UserServiceError::LastSigningKey(kid.to_string()));
+            }
+            let now = Utc::now().to_rfc3339();
+            sqlx::query(
+                "INSERT INTO jwt_keys (kid, secret, created_at, retired_at) VALUES (?, '', ?, ?)",
+            )
+            .bind(DEFAULT_JWT_KID)
+            .bind(&now)
+            .bind(&now)
+            .execute(&self.pool)
+            .await?;
+            self.load_signing_keys().await?;
+            return Ok(());
+        }
+        if default_retired && active == 1 {
+            let only = self.jwt_keys.read().unwrap_or_else(|e| e.into_inner())[0].kid == kid;
+            if only {
+                return Err(UserServiceError::LastSigningKey(kid.to_string()));
+            }
+        }
+
+        let retired = sqlx::query(
+            "UPDATE jwt_keys SET retired_at = ? WHERE kid = ? AND retired_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(kid)
+        .execute(&self.pool)
+        .await?;
+        if retired.rows_affected() == 0 {
+            return Err(UserServiceError::SigningKeyNotFound(kid.to_string()));
+        }
+        self.load_signing_keys().await?;
+        Ok(())
+    }
+
+    /// Encrypt signing key secrets stored before `FIELD_ENCRYPTION_KEY` was
+    /// set. Returns how many were sealed.
+    pub async fn encrypt_signing_keys(&self) -> Result<u64, UserServiceError> {
+        let Some(cipher) = &self.key_cipher else {
+            return Ok(0);
+        };
+        let rows = sqlx::query_as::<_, JwtKeyRow>("SELECT * FROM jwt_keys WHERE secret != ''")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut sealed = 0;
+        for row in rows.iter().filter(|row| !FieldCipher::is_encrypted(&row.secret)) {
+            sqlx::query("UPDATE jwt_keys SET secret = ? WHERE kid = ?")
+                .bind(cipher.encrypt(&row.secret))
+                .bind(&row.kid)
+                .execute(&self.pool)
+                .await?;
+            sealed += 1;
+        }
+        Ok(sealed)
+    }
+
     fn hash_token(&self, token: &str) -> String {
-        use sha2::{Digest, Sha256};
         let mut hasher = Sha256::new();
         hasher.update(token.as_bytes());
         hex::encode(hasher.finalize())
     }
 }
+
+/// Argon2id hash of a password
+fn hash_password(password: &str) -> Result<String, UserServiceError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|_| UserServiceError::PasswordHash)?
+        .to_string())
+}
+
+/// 256-bit URL-safe random string, for OAuth states, PKCE verifiers and
+/// confirmation tokens
+pub(crate) fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Load the signing keys, then reload them every `JWT_KEY_REFRESH_SECS` so
+/// keys added or retired from the command line or another instance take effect
+pub fn spawn_signing_key_refresh(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker =
+            tokio::time::interval(std::time::Duration::from_secs(JWT_KEY_REFRESH_SECS));
+        loop {
+            ticker.tick().await;
+            match state.user_service.load_signing_keys().await {
+                Ok(count) => tracing::debug!(count, "JWT signing keys loaded"),
+                Err(e) => tracing::warn!(error = %e, "Loading JWT signing keys failed"),
+            }
+        }
+    })
+}
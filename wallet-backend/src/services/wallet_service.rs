@@ -1,15 +1,28 @@
 //! Wallet service - orchestrates wallet operations
 
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use serde::Serialize;
 use thiserror::Error;
 use zeroize::Zeroizing;
 
+use crate::api::middleware::tenant::current_tenant_id;
+use crate::chains::ChainClients;
+use crate::config::ProvisionConfig;
+use crate::services::transaction_service;
 use crate::core::{
-    decrypt_seed, derive_account, encrypt_seed, generate_mnemonic, mnemonic_to_seed,
-    parse_mnemonic, Chain, EncryptedSeed, SecureSeed,
+    decrypt_secret, decrypt_seed, derive_account, derive_solana_account, encrypt_secret, encrypt_seed,
+    generate_mnemonic, language_name, mnemonic_to_seed, parse_mnemonic, wallet_key_material, Chain,
+    EncryptedSeed, SecureSeed, SolanaScheme,
 };
-use crate::storage::models::{AccountResponse, AccountRow, WalletRow};
+use crate::storage::models::{
+    AccountResponse, AccountRow, AuditEventRow, AuditSeverity, UnlockChallengeRow, WalletRow,
+};
+use crate::storage::database::DatabaseError;
 use crate::storage::Database;
 use crate::AppState;
 
@@ -23,23 +36,111 @@ pub enum WalletServiceError {
     WalletLocked,
     #[error("Invalid password")]
     InvalidPassword,
+    #[error("This wallet requires its keyfile")]
+    KeyfileRequired,
+    #[error("This wallet does not use a keyfile")]
+    UnexpectedKeyfile,
+    #[error("Invalid keyfile: {0}")]
+    InvalidKeyfile(String),
     #[error("Invalid mnemonic: {0}")]
     InvalidMnemonic(String),
     #[error("Database error: {0}")]
     DatabaseError(String),
     #[error("Derivation error: {0}")]
     DerivationError(String),
+    #[error("Provisioning failed: {0}")]
+    ProvisioningFailed(String),
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("Account index {0} is already in use by another derivation path")]
+    IndexInUse(u32),
+    #[error("Chain error: {0}")]
+    ChainError(String),
+    #[error("Account not found")]
+    AccountNotFound,
+    #[error("An unlock challenge nonce is required")]
+    ChallengeRequired,
+    #[error("Unlock challenge not found, already used or expired")]
+    InvalidChallenge,
+    #[error("Too many unlock challenges; try again in a minute")]
+    TooManyChallenges,
+}
+
+/// Indexes a discovery scan covers when the caller doesn't say
+pub const DEFAULT_DISCOVERY_COUNT: u32 = 5;
+/// Most indexes a discovery scan or import may cover
+pub const MAX_DISCOVERY_COUNT: u32 = 20;
+/// How long an account's RPC endpoint has to answer when it is saved
+const RPC_HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long an unlock challenge can be redeemed
+const UNLOCK_CHALLENGE_TTL_SECS: i64 = 120;
+/// Unlock challenges one client IP may be issued per minute; as each allows
+/// one password attempt, this also bounds password guessing
+const MAX_UNLOCK_CHALLENGES_PER_MINUTE: i64 = 10;
+/// Unlock challenges a whole tenant may be issued per minute, as a backstop
+/// to the per-client limit
+const MAX_TENANT_UNLOCK_CHALLENGES_PER_MINUTE: i64 = 100;
+
+/// One Solana address an imported phrase controls
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredAccount {
+    pub index: u32,
+    pub scheme: SolanaScheme,
+    pub derivation_path: String,
+    pub address: String,
+    pub has_activity: bool,
+    /// Already one of the wallet's accounts
+    pub imported: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountDiscovery {
+    pub accounts: Vec<DiscoveredAccount>,
+    /// Scheme with the most used addresses; `None` when none has been used
+    pub suggested_scheme: Option<SolanaScheme>,
+}
+
+/// What startup provisioning did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provisioned {
+    /// Existing wallet unlocked with the provisioned password
+    Unlocked,
+    /// No wallet existed; imported from the provisioned mnemonic
+    Imported,
+}
+
+/// Decode a base64 keyfile upload
+pub fn decode_keyfile(encoded: &str) -> Result<Zeroizing<Vec<u8>>, WalletServiceError> {
+    let keyfile = STANDARD
+        .decode(encoded.trim())
+        .map(Zeroizing::new)
+        .map_err(|e| WalletServiceError::InvalidKeyfile(e.to_string()))?;
+    if keyfile.is_empty() {
+        return Err(WalletServiceError::InvalidKeyfile("keyfile is empty".to_string()));
+    }
+    Ok(keyfile)
 }
 
-/// Create a new wallet with generated mnemonic
+/// Check a keyfile was supplied exactly when the wallet requires one
+pub fn check_keyfile(wallet: &WalletRow, keyfile: Option<&[u8]>) -> Result<(), WalletServiceError> {
+    match (wallet.keyfile_required, keyfile.is_some()) {
+        (true, false) => Err(WalletServiceError::KeyfileRequired),
+        (false, true) => Err(WalletServiceError::UnexpectedKeyfile),
+        _ => Ok(()),
+    }
+}
+
+/// Create a new wallet with generated mnemonic, optionally requiring a keyfile
+/// alongside the password to unlock it
 pub async fn create_wallet(
     state: &Arc<AppState>,
     password: &str,
+    keyfile: Option<&[u8]>,
 ) -> Result<(String, Vec<String>), WalletServiceError> {
     // Check if wallet already exists
     if state
         .db
-        .wallet_exists()
+        .wallet_exists(&current_tenant_id())
         .await
         .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
     {
@@ -56,17 +157,28 @@ pub async fn create_wallet(
     let seed = mnemonic_to_seed(&mnemonic, "");
 
     // Encrypt seed
-    let encrypted = encrypt_seed(&seed, password)
+    let key = wallet_key_material(password, keyfile);
+    let encrypted = encrypt_seed(&seed, &key)
         .map_err(|e| WalletServiceError::InvalidPassword)?;
 
+    // Keep an encrypted copy of the entropy so backup quizzes can be verified later
+    let entropy = Zeroizing::new(mnemonic.to_entropy());
+    let encrypted_entropy = encrypt_secret(&entropy, &key)
+        .map_err(|_| WalletServiceError::InvalidPassword)?;
+
     // Store wallet
     let wallet_id = uuid::Uuid::new_v4().to_string();
-    let wallet = WalletRow::new(
+    let mut wallet = WalletRow::new(
         wallet_id.clone(),
         encrypted.ciphertext,
         encrypted.salt.to_vec(),
         encrypted.nonce.to_vec(),
+        Some(encrypted_entropy),
+        Some(mnemonic.word_count() as u32),
+        language_name(mnemonic.language()).to_string(),
     );
+    wallet.keyfile_required = keyfile.is_some();
+    wallet.tenant_id = current_tenant_id();
 
     state
         .db
@@ -84,7 +196,7 @@ pub async fn create_wallet(
             .zip(state.session_key.iter().cycle())
             .map(|(b, k)| b ^ k)
             .collect();
-        *unlocked = Some(encrypted_mem);
+        unlocked.insert(current_tenant_id(), encrypted_mem);
     }
 
 
@@ -97,11 +209,12 @@ pub async fn import_wallet(
     state: &Arc<AppState>,
     mnemonic_phrase: &str,
     password: &str,
+    keyfile: Option<&[u8]>,
 ) -> Result<String, WalletServiceError> {
     // Check if wallet already exists
     if state
         .db
-        .wallet_exists()
+        .wallet_exists(&current_tenant_id())
         .await
         .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
     {
@@ -116,17 +229,28 @@ pub async fn import_wallet(
     let seed = mnemonic_to_seed(&mnemonic, "");
 
     // Encrypt seed
-    let encrypted = encrypt_seed(&seed, password)
+    let key = wallet_key_material(password, keyfile);
+    let encrypted = encrypt_seed(&seed, &key)
         .map_err(|e| WalletServiceError::InvalidPassword)?;
 
+    // Keep an encrypted copy of the entropy so backup quizzes can be verified later
+    let entropy = Zeroizing::new(mnemonic.to_entropy());
+    let encrypted_entropy = encrypt_secret(&entropy, &key)
+        .map_err(|_| WalletServiceError::InvalidPassword)?;
+
     // Store wallet
     let wallet_id = uuid::Uuid::new_v4().to_string();
-    let wallet = WalletRow::new(
+    let mut wallet = WalletRow::new(
         wallet_id.clone(),
         encrypted.ciphertext,
         encrypted.salt.to_vec(),
         encrypted.nonce.to_vec(),
+        Some(encrypted_entropy),
+        Some(mnemonic.word_count() as u32),
+        language_name(mnemonic.language()).to_string(),
     );
+    wallet.keyfile_required = keyfile.is_some();
+    wallet.tenant_id = current_tenant_id();
 
     state
         .db
@@ -144,7 +268,7 @@ pub async fn import_wallet(
             .zip(state.session_key.iter().cycle())
             .map(|(b, k)| b ^ k)
             .collect();
-        *unlocked = Some(encrypted_mem);
+        unlocked.insert(current_tenant_id(), encrypted_mem);
     }
 
 
@@ -152,16 +276,112 @@ pub async fn import_wallet(
     Ok(wallet_id)
 }
 
-/// Unlock wallet with password
-pub async fn unlock_wallet(state: &Arc<AppState>, password: &str) -> Result<(), WalletServiceError> {
+/// Single-use nonce for one unlock attempt
+#[derive(Debug, Serialize)]
+pub struct UnlockChallengeResponse {
+    pub nonce: String,
+    pub expires_at: String,
+}
+
+/// Issue an unlock nonce to the client at `ip_address`, at most
+/// `MAX_UNLOCK_CHALLENGES_PER_MINUTE` a minute per client and
+/// `MAX_TENANT_UNLOCK_CHALLENGES_PER_MINUTE` per tenant
+pub async fn create_unlock_challenge(
+    state: &Arc<AppState>,
+    ip_address: &str,
+) -> Result<UnlockChallengeResponse, WalletServiceError> {
+    let tenant_id = current_tenant_id();
+    let now = chrono::Utc::now();
+    state
+        .db
+        .delete_expired_unlock_challenges(&now.to_rfc3339())
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+
+    let since = (now - chrono::Duration::minutes(1)).to_rfc3339();
+    let issued = state
+        .db
+        .count_unlock_challenges_from_since(&tenant_id, ip_address, &since)
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+    if issued >= MAX_UNLOCK_CHALLENGES_PER_MINUTE {
+        return Err(WalletServiceError::TooManyChallenges);
+    }
+    let issued = state
+        .db
+        .count_unlock_challenges_since(&tenant_id, &since)
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+    if issued >= MAX_TENANT_UNLOCK_CHALLENGES_PER_MINUTE {
+        return Err(WalletServiceError::TooManyChallenges);
+    }
+
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let challenge = UnlockChallengeRow::new(
+        hex::encode(bytes),
+        tenant_id,
+        Some(ip_address.to_string()),
+        now,
+        now + chrono::Duration::seconds(UNLOCK_CHALLENGE_TTL_SECS),
+    );
+    state
+        .db
+        .create_unlock_challenge(&challenge)
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+
+    Ok(UnlockChallengeResponse {
+        nonce: challenge.nonce,
+        expires_at: challenge.expires_at,
+    })
+}
+
+/// Unlock over the API. The nonce is redeemed before the password is
+/// checked, so each one allows a single attempt whatever its outcome. It can
+/// only be left out when `UNLOCK_CHALLENGE_REQUIRED=false`.
+pub async fn unlock_with_challenge(
+    state: &Arc<AppState>,
+    nonce: Option<&str>,
+    password: &str,
+    keyfile: Option<&[u8]>,
+) -> Result<(), WalletServiceError> {
+    match nonce {
+        Some(nonce) => {
+            state
+                .db
+                .redeem_unlock_challenge(nonce, &current_tenant_id(), &chrono::Utc::now().to_rfc3339())
+                .await
+                .map_err(|e| match e {
+                    DatabaseError::NotFound => WalletServiceError::InvalidChallenge,
+                    e => WalletServiceError::DatabaseError(e.to_string()),
+                })?;
+        }
+        None if state.config.unlock_challenge_required => {
+            return Err(WalletServiceError::ChallengeRequired);
+        }
+        None => {}
+    }
+
+    unlock_wallet(state, password, keyfile).await
+}
+
+/// Unlock wallet with password, plus the keyfile if the wallet requires one
+pub async fn unlock_wallet(
+    state: &Arc<AppState>,
+    password: &str,
+    keyfile: Option<&[u8]>,
+) -> Result<(), WalletServiceError> {
     // Get wallet from database
     let wallet = state
         .db
-        .get_primary_wallet()
+        .get_primary_wallet(&current_tenant_id())
         .await
         .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
         .ok_or(WalletServiceError::NoWalletFound)?;
 
+    check_keyfile(&wallet, keyfile)?;
+
     // Reconstruct encrypted seed
     let salt: [u8; 16] = wallet.salt.try_into().map_err(|_| WalletServiceError::InvalidPassword)?;
     let nonce: [u8; 12] = wallet.nonce.try_into().map_err(|_| WalletServiceError::InvalidPassword)?;
@@ -173,7 +393,7 @@ pub async fn unlock_wallet(state: &Arc<AppState>, password: &str) -> Result<(),
     };
 
     // Decrypt seed
-    let seed = decrypt_seed(&encrypted, password)
+    let seed = decrypt_seed(&encrypted, &wallet_key_material(password, keyfile))
         .map_err(|_| WalletServiceError::InvalidPassword)?;
 
     // Store in memory (encrypted)
@@ -185,28 +405,140 @@ pub async fn unlock_wallet(state: &Arc<AppState>, password: &str) -> Result<(),
             .zip(state.session_key.iter().cycle())
             .map(|(b, k)| b ^ k)
             .collect();
-        *unlocked = Some(encrypted_mem);
+        unlocked.insert(current_tenant_id(), encrypted_mem);
     }
 
     Ok(())
 }
 
+/// Re-encrypt the seed (and the backup entropy) under a new password with a
+/// fresh salt and nonce, then lock the wallet so it must be unlocked with the
+/// new password. The keyfile, if the wallet has one, stays the same.
+pub async fn change_encryption_password(
+    state: &Arc<AppState>,
+    user_id: &str,
+    current_password: &str,
+    new_password: &str,
+    keyfile: Option<&[u8]>,
+) -> Result<(), WalletServiceError> {
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
+        .ok_or(WalletServiceError::NoWalletFound)?;
+
+    check_keyfile(&wallet, keyfile)?;
+
+    let salt: [u8; 16] = wallet.salt.try_into().map_err(|_| WalletServiceError::InvalidPassword)?;
+    let nonce: [u8; 12] = wallet.nonce.try_into().map_err(|_| WalletServiceError::InvalidPassword)?;
+    let encrypted = EncryptedSeed {
+        ciphertext: wallet.encrypted_seed,
+        salt,
+        nonce,
+    };
+
+    let old_key = wallet_key_material(current_password, keyfile);
+    let seed = decrypt_seed(&encrypted, &old_key).map_err(|_| WalletServiceError::InvalidPassword)?;
+    let entropy = wallet
+        .encrypted_entropy
+        .as_deref()
+        .map(|blob| decrypt_secret(blob, &old_key))
+        .transpose()
+        .map_err(|_| WalletServiceError::InvalidPassword)?;
+
+    let new_key = wallet_key_material(new_password, keyfile);
+    let reencrypted = encrypt_seed(&seed, &new_key).map_err(|_| WalletServiceError::InvalidPassword)?;
+    let reencrypted_entropy = entropy
+        .map(|entropy| encrypt_secret(&entropy, &new_key))
+        .transpose()
+        .map_err(|_| WalletServiceError::InvalidPassword)?;
+
+    let audit = AuditEventRow::new(
+        current_tenant_id(),
+        Some(user_id.to_string()),
+        "wallet.encryption_password_changed",
+        AuditSeverity::Warning,
+        serde_json::json!({ "wallet_id": wallet.id }),
+    );
+    state
+        .db
+        .reencrypt_wallet(
+            &wallet.id,
+            &reencrypted.ciphertext,
+            &reencrypted.salt,
+            &reencrypted.nonce,
+            reencrypted_entropy.as_deref(),
+            &audit,
+        )
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+
+    lock_wallet(state).await;
+    Ok(())
+}
+
+/// Unlock the wallet from provisioned secrets at startup, importing it from
+/// the provisioned mnemonic if the database has none
+pub async fn provision_wallet(
+    state: &Arc<AppState>,
+    provision: &ProvisionConfig,
+) -> Result<Provisioned, WalletServiceError> {
+    let password = provision
+        .password
+        .load("WALLET_PASSWORD")
+        .await
+        .map_err(|e| WalletServiceError::ProvisioningFailed(e.to_string()))?;
+
+    let wallet_exists = state
+        .db
+        .wallet_exists(&current_tenant_id())
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+
+    match (&provision.mnemonic, wallet_exists) {
+        (_, true) => {
+            unlock_wallet(state, &password, None).await?;
+            Ok(Provisioned::Unlocked)
+        }
+        (Some(mnemonic), false) => {
+            let mnemonic = mnemonic
+                .load("WALLET_MNEMONIC")
+                .await
+                .map_err(|e| WalletServiceError::ProvisioningFailed(e.to_string()))?;
+            import_wallet(state, &mnemonic, &password, None).await?;
+            Ok(Provisioned::Imported)
+        }
+        (None, false) => Err(WalletServiceError::NoWalletFound),
+    }
+}
+
+/// Whether the primary wallet needs a keyfile to unlock
+pub async fn keyfile_required(state: &Arc<AppState>) -> bool {
+    matches!(
+        state.db.get_primary_wallet(&current_tenant_id()).await,
+        Ok(Some(wallet)) if wallet.keyfile_required
+    )
+}
+
 /// Lock wallet (clear seed from memory)
 pub async fn lock_wallet(state: &Arc<AppState>) {
     let mut unlocked = state.unlocked_seed.write().await;
-    *unlocked = None;
+    unlocked.remove(&current_tenant_id());
 }
 
 /// Check if wallet is unlocked
 pub async fn is_unlocked(state: &Arc<AppState>) -> bool {
     let unlocked = state.unlocked_seed.read().await;
-    unlocked.is_some()
+    unlocked.contains_key(&current_tenant_id())
 }
 
 /// Get unlocked seed
 pub async fn get_seed(state: &Arc<AppState>) -> Result<SecureSeed, WalletServiceError> {
     let unlocked = state.unlocked_seed.read().await;
-    let encrypted_bytes = unlocked.as_ref().ok_or(WalletServiceError::WalletLocked)?;
+    let encrypted_bytes = unlocked
+        .get(&current_tenant_id())
+        .ok_or(WalletServiceError::WalletLocked)?;
     
     // Decrypt on the fly
     let decrypted_vec: Vec<u8> = encrypted_bytes
@@ -236,7 +568,7 @@ pub async fn derive_new_account(
     // Get wallet ID
     let wallet = state
         .db
-        .get_primary_wallet()
+        .get_primary_wallet(&current_tenant_id())
         .await
         .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
         .ok_or(WalletServiceError::NoWalletFound)?;
@@ -249,20 +581,28 @@ pub async fn derive_new_account(
         .await
         .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
 
-    // Derive account
-    let derived = derive_account(&seed, chain, index)
-        .map_err(|e| WalletServiceError::DerivationError(e.to_string()))?;
-
-    let account_name = name.unwrap_or_else(|| {
-        format!(
-            "{} Account {}",
-            match chain {
-                Chain::Solana => "Solana",
-                Chain::Ethereum => "Ethereum",
-            },
-            index + 1
-        )
-    });
+    // New Solana accounts follow the scheme of the latest one, which may be
+    // a legacy path chosen on import
+    let derived = match chain {
+        Chain::Solana => {
+            let accounts = state
+                .db
+                .get_accounts(&wallet.id)
+                .await
+                .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+            let scheme = accounts
+                .iter()
+                .filter(|a| a.chain == chain_str)
+                .max_by_key(|a| a.derivation_index)
+                .and_then(|a| SolanaScheme::of_path(&a.derivation_path))
+                .unwrap_or(SolanaScheme::Bip44Change);
+            derive_solana_account(&seed, index, scheme)
+        }
+        Chain::Ethereum => derive_account(&seed, chain, index),
+    }
+    .map_err(|e| WalletServiceError::DerivationError(e.to_string()))?;
+
+    let account_name = name.unwrap_or_else(|| default_account_name(chain, index));
 
     let row = AccountRow::new(
         wallet.id,
@@ -280,14 +620,155 @@ pub async fn derive_new_account(
         .await
         .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
 
+    tracing::info!(
+        account_id = %row.id,
+        chain = %row.chain,
+        derivation_index = row.derivation_index,
+        "Account derived"
+    );
     Ok(AccountResponse::from(row))
 }
 
+fn default_account_name(chain: Chain, index: u32) -> String {
+    format!(
+        "{} Account {}",
+        match chain {
+            Chain::Solana => "Solana",
+            Chain::Ethereum => "Ethereum",
+        },
+        index + 1
+    )
+}
+
+/// Derive the first `count` Solana indexes under every path scheme and check
+/// which addresses have been used, so an imported phrase can be matched to
+/// the scheme the wallet it came from used
+pub async fn discover_solana_accounts(
+    state: &Arc<AppState>,
+    count: Option<u32>,
+) -> Result<AccountDiscovery, WalletServiceError> {
+    let count = count.unwrap_or(DEFAULT_DISCOVERY_COUNT);
+    if !(1..=MAX_DISCOVERY_COUNT).contains(&count) {
+        return Err(WalletServiceError::InvalidRequest(format!(
+            "count must be between 1 and {}",
+            MAX_DISCOVERY_COUNT
+        )));
+    }
+    let seed = get_seed(state).await?;
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
+        .ok_or(WalletServiceError::NoWalletFound)?;
+    let existing = state
+        .db
+        .get_accounts(&wallet.id)
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+
+    let clients = state.chain_clients();
+    let client = clients.get(Chain::Solana);
+    let mut accounts = Vec::new();
+    for index in 0..count {
+        for scheme in SolanaScheme::ALL {
+            let derived = derive_solana_account(&seed, index, scheme)
+                .map_err(|e| WalletServiceError::DerivationError(e.to_string()))?;
+            let has_activity = client
+                .has_activity(&derived.address)
+                .await
+                .map_err(|e| WalletServiceError::ChainError(e.to_string()))?;
+            accounts.push(DiscoveredAccount {
+                index,
+                scheme,
+                imported: existing.iter().any(|a| a.address == derived.address),
+                derivation_path: derived.derivation_path,
+                address: derived.address,
+                has_activity,
+            });
+        }
+    }
+
+    let used = |scheme| accounts.iter().filter(|a| a.scheme == scheme && a.has_activity).count();
+    let suggested_scheme = SolanaScheme::ALL
+        .into_iter()
+        .filter(|&scheme| used(scheme) > 0)
+        .max_by_key(|&scheme| used(scheme));
+
+    Ok(AccountDiscovery {
+        accounts,
+        suggested_scheme,
+    })
+}
+
+/// Add the Solana accounts at `indexes` under the chosen path scheme.
+/// Indexes already imported under it are skipped; the whole request fails if
+/// one is taken by an account on another path.
+pub async fn import_solana_accounts(
+    state: &Arc<AppState>,
+    scheme: SolanaScheme,
+    indexes: &[u32],
+) -> Result<Vec<AccountResponse>, WalletServiceError> {
+    let mut indexes = indexes.to_vec();
+    indexes.sort_unstable();
+    indexes.dedup();
+    if indexes.is_empty() || indexes.iter().any(|&i| i >= MAX_DISCOVERY_COUNT) {
+        return Err(WalletServiceError::InvalidRequest(format!(
+            "indexes must be between 0 and {}",
+            MAX_DISCOVERY_COUNT - 1
+        )));
+    }
+    let seed = get_seed(state).await?;
+    let wallet = state
+        .db
+        .get_primary_wallet(&current_tenant_id())
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
+        .ok_or(WalletServiceError::NoWalletFound)?;
+    let existing = state
+        .db
+        .get_accounts(&wallet.id)
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+
+    let mut rows = Vec::new();
+    for index in indexes {
+        let derived = derive_solana_account(&seed, index, scheme)
+            .map_err(|e| WalletServiceError::DerivationError(e.to_string()))?;
+        let taken = existing
+            .iter()
+            .find(|a| a.chain == "solana" && a.derivation_index == index as i64);
+        match taken {
+            Some(account) if account.derivation_path == derived.derivation_path => continue,
+            Some(_) => return Err(WalletServiceError::IndexInUse(index)),
+            None => rows.push(AccountRow::new(
+                wallet.id.clone(),
+                default_account_name(Chain::Solana, index),
+                Chain::Solana.to_string(),
+                derived.derivation_path,
+                derived.derivation_index,
+                derived.public_key,
+                derived.address,
+            )),
+        }
+    }
+
+    for row in &rows {
+        state
+            .db
+            .create_account(row)
+            .await
+            .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+    }
+    tracing::info!(scheme = ?scheme, imported = rows.len(), "Solana accounts imported");
+    Ok(rows.into_iter().map(AccountResponse::from).collect())
+}
+
 /// List all accounts
 pub async fn list_accounts(state: &Arc<AppState>) -> Result<Vec<AccountResponse>, WalletServiceError> {
     let wallet = state
         .db
-        .get_primary_wallet()
+        .get_primary_wallet(&current_tenant_id())
         .await
         .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?
         .ok_or(WalletServiceError::NoWalletFound)?;
@@ -303,17 +784,172 @@ pub async fn list_accounts(state: &Arc<AppState>) -> Result<Vec<AccountResponse>
 
 /// Delete an account
 pub async fn delete_account(state: &Arc<AppState>, id: &str) -> Result<(), WalletServiceError> {
-    tracing::info!("Deleting account via service: {}", id);
+    tracing::info!(account_id = %id, "Deleting account");
 
     state
         .db
         .delete_account(id)
         .await
         .map_err(|e| {
-            tracing::error!("Database error while deleting account {}: {}", id, e);
+            tracing::error!(account_id = %id, error = %e, "Database error while deleting account");
             WalletServiceError::DatabaseError(e.to_string())
         })?;
 
-    tracing::info!("Account deleted via service: {}", id);
+    tracing::info!(account_id = %id, "Account deleted");
     Ok(())
 }
+
+/// Point one of the tenant's accounts at its own RPC endpoint, or back at
+/// the default one with `None`. The endpoint must be on a public host unless
+/// the operator allows private ones, and must answer a block height request
+/// before it is saved.
+pub async fn set_account_rpc_url(
+    state: &Arc<AppState>,
+    id: &str,
+    rpc_url: Option<String>,
+) -> Result<AccountResponse, WalletServiceError> {
+    let mut account = owned_account(state, id).await?;
+    let chain: Chain = account
+        .chain
+        .parse()
+        .map_err(WalletServiceError::InvalidRequest)?;
+
+    let rpc_url = rpc_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty());
+    if let Some(url) = &rpc_url {
+        let parsed = reqwest::Url::parse(url)
+            .ok()
+            .filter(|p| matches!(p.scheme(), "http" | "https") && p.host().is_some())
+            .ok_or_else(|| {
+                WalletServiceError::InvalidRequest(format!("'{}' is not an http(s) URL", url))
+            })?;
+
+        // The caller only learns whether the endpoint answered, so it can't
+        // be used to probe what else is reachable from here
+        let unreachable = || WalletServiceError::ChainError("RPC unreachable".to_string());
+        let addresses = resolve_host(&parsed).await.ok_or_else(unreachable)?;
+        if !state.config.account_rpc_allow_private && !addresses.into_iter().all(is_public_ip) {
+            return Err(WalletServiceError::InvalidRequest(format!(
+                "'{}' is not on a public host",
+                url
+            )));
+        }
+
+        let client =
+            ChainClients::connect(chain, url, &state.config.sns_api_url, &state.rpc_metrics);
+        let height = tokio::time::timeout(RPC_HEALTH_TIMEOUT, client.block_height())
+            .await
+            .map_err(|_| "timed out".to_string())
+            .and_then(|result| result.map_err(|e| e.to_string()));
+        if let Err(e) = height {
+            tracing::warn!(account_id = %id, chain = %chain, error = %e, "RPC endpoint failed health check");
+            return Err(unreachable());
+        }
+    }
+
+    state
+        .db
+        .set_account_rpc_url(id, rpc_url.as_deref())
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+    tracing::info!(account_id = %id, custom = rpc_url.is_some(), "Account RPC endpoint updated");
+
+    account.rpc_url = rpc_url;
+    Ok(AccountResponse::from(account))
+}
+
+/// Every address `url`'s host stands for; `None` if a name doesn't resolve
+async fn resolve_host(url: &reqwest::Url) -> Option<Vec<IpAddr>> {
+    // IPv6 literals keep their brackets in the URL
+    let host = url.host_str()?.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse() {
+        return Some(vec![ip]);
+    }
+    let port = url.port_or_known_default()?;
+    let addresses: Vec<IpAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .ok()?
+        .map(|address| address.ip())
+        .collect();
+    (!addresses.is_empty()).then_some(addresses)
+}
+
+/// Not loopback, private, link-local, shared (CGNAT) or unspecified
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_ip(v4.into()),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Send an Ethereum account's transfers through the private relay unless a
+/// send says otherwise
+pub async fn set_account_mev_protect(
+    state: &Arc<AppState>,
+    id: &str,
+    enabled: bool,
+) -> Result<AccountResponse, WalletServiceError> {
+    let mut account = owned_account(state, id).await?;
+    if account.chain.parse() != Ok(Chain::Ethereum) {
+        return Err(WalletServiceError::InvalidRequest(
+            "MEV protection is only available on ethereum".to_string(),
+        ));
+    }
+
+    state
+        .db
+        .set_account_mev_protect(id, enabled)
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+    tracing::info!(account_id = %id, enabled, "Account MEV protection updated");
+
+    account.mev_protect = enabled;
+    Ok(AccountResponse::from(account))
+}
+
+/// Read one of the tenant's accounts' balance from its chain now, updating
+/// its sync state either way
+pub async fn sync_account(state: &Arc<AppState>, id: &str) -> Result<AccountResponse, WalletServiceError> {
+    let account = owned_account(state, id).await?;
+    transaction_service::get_balance(state, &account.chain, &account.address)
+        .await
+        .map_err(|e| WalletServiceError::ChainError(e.to_string()))?;
+
+    owned_account(state, id).await.map(AccountResponse::from)
+}
+
+/// One of the tenant's accounts
+async fn owned_account(state: &Arc<AppState>, id: &str) -> Result<AccountRow, WalletServiceError> {
+    let account = match state.db.get_account(id).await {
+        Ok(account) => account,
+        Err(DatabaseError::NotFound) => return Err(WalletServiceError::AccountNotFound),
+        Err(e) => return Err(WalletServiceError::DatabaseError(e.to_string())),
+    };
+    let wallet = state
+        .db
+        .get_wallet(&account.wallet_id)
+        .await
+        .map_err(|e| WalletServiceError::DatabaseError(e.to_string()))?;
+    if wallet.tenant_id != current_tenant_id() {
+        return Err(WalletServiceError::AccountNotFound);
+    }
+    Ok(account)
+}
@@ -0,0 +1,489 @@
+//! Watchlist service - follow addresses the user doesn't own
+//!
+//! Exchange wallets, DAO treasuries and the like are polled on every pass of
+//! the alert watcher: the native balance on every chain and, on Solana, the
+//! address's recent transactions. What changed since the previous poll is
+//! kept as the entry's activity. A balance movement at least as large as the
+//! entry's threshold is also a notification, POSTed as JSON to the entry's
+//! webhook when it has one.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::api::middleware::tenant::{current_tenant_id, with_tenant};
+use crate::core::{format_signed_units, format_units, parse_units, Chain};
+use crate::services::contact_service::{self, ContactServiceError};
+use crate::services::tenant_service;
+use crate::services::webhook_service::{self, Endpoint, EventType};
+use crate::storage::database::DatabaseError;
+use crate::storage::models::{WatchActivityResponse, WatchActivityRow, WatchRow};
+use crate::AppState;
+
+/// Most addresses a user may watch
+const MAX_WATCHES: usize = 50;
+/// Longest label, in characters
+const MAX_LABEL_CHARS: usize = 64;
+/// Activity rows returned per address
+const ACTIVITY_LIMIT: u32 = 20;
+/// Recent transactions fetched per poll; a busier address only has its
+/// newest ones recorded
+const TX_POLL_LIMIT: usize = 25;
+/// Upper bound on a webhook delivery
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum WatchlistError {
+    #[error("Unsupported chain: {0}")]
+    InvalidChain(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid label: {0}")]
+    InvalidLabel(String),
+    #[error("Invalid threshold: {0}")]
+    InvalidThreshold(String),
+    #[error("Invalid webhook URL: {0}")]
+    InvalidWebhook(String),
+    #[error("{0} is already on the watchlist")]
+    Duplicate(String),
+    #[error("At most {MAX_WATCHES} addresses can be watched")]
+    LimitReached,
+    #[error("Watched address not found")]
+    NotFound,
+    #[error("Chain error: {0}")]
+    Chain(String),
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+impl From<DatabaseError> for WatchlistError {
+    fn from(e: DatabaseError) -> Self {
+        WatchlistError::Database(e.to_string())
+    }
+}
+
+/// Add to watchlist request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddWatchRequest {
+    pub chain: String,
+    pub address: String,
+    /// e.g. "Binance hot wallet"
+    pub label: Option<String>,
+    /// Movements of at least this much of the native coin notify
+    pub threshold: Option<String>,
+    /// Receives a JSON POST for every notifying movement
+    pub webhook_url: Option<String>,
+}
+
+/// Update watchlist entry request; omitted fields are left unchanged and
+/// empty strings clear them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateWatchRequest {
+    pub label: Option<String>,
+    pub threshold: Option<String>,
+    pub webhook_url: Option<String>,
+}
+
+/// A watched address with its latest activity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchResponse {
+    pub id: String,
+    pub chain: String,
+    pub address: String,
+    pub label: Option<String>,
+    pub threshold: Option<String>,
+    pub webhook_url: Option<String>,
+    /// Native balance at the last poll; `None` until the first one
+    pub balance: Option<String>,
+    pub last_checked_at: Option<String>,
+    pub created_at: String,
+    /// Newest first
+    pub recent_activity: Vec<WatchActivityResponse>,
+}
+
+impl WatchResponse {
+    fn new(row: WatchRow, activity: Vec<WatchActivityRow>) -> Self {
+        Self {
+            id: row.id,
+            chain: row.chain,
+            address: row.address,
+            label: row.label,
+            threshold: row.threshold,
+            webhook_url: row.webhook_url,
+            balance: row.last_balance,
+            last_checked_at: row.last_checked_at,
+            created_at: row.created_at,
+            recent_activity: activity.into_iter().map(WatchActivityResponse::from).collect(),
+        }
+    }
+}
+
+/// Body POSTed to an entry's webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchWebhookPayload {
+    pub watch_id: String,
+    pub chain: String,
+    pub address: String,
+    pub label: Option<String>,
+    pub threshold: String,
+    /// Signed change of the native balance
+    pub amount: String,
+    pub balance: String,
+    pub message: String,
+    pub detected_at: String,
+}
+
+/// The user's watched addresses, each with its most recent activity
+pub async fn list_watches(
+    state: &Arc<AppState>,
+    user_id: &str,
+) -> Result<Vec<WatchResponse>, WatchlistError> {
+    let watches = state.db.get_watches(user_id).await?;
+    let ids: Vec<String> = watches.iter().map(|w| w.id.clone()).collect();
+    let mut activity: BTreeMap<String, Vec<WatchActivityRow>> = BTreeMap::new();
+    for row in state.db.get_recent_watch_activity(&ids, ACTIVITY_LIMIT).await? {
+        activity.entry(row.watch_id.clone()).or_default().push(row);
+    }
+
+    Ok(watches
+        .into_iter()
+        .map(|watch| {
+            let rows = activity.remove(&watch.id).unwrap_or_default();
+            WatchResponse::new(watch, rows)
+        })
+        .collect())
+}
+
+/// Start watching an address. Activity is recorded from the first poll on.
+pub async fn add_watch(
+    state: &Arc<AppState>,
+    user_id: &str,
+    request: AddWatchRequest,
+) -> Result<WatchResponse, WatchlistError> {
+    let chain: Chain = request
+        .chain
+        .parse()
+        .map_err(|_| WatchlistError::InvalidChain(request.chain.clone()))?;
+    let address = contact_service::canonical_address(&chain.to_string(), &request.address)
+        .map_err(|e| match e {
+            ContactServiceError::InvalidRequest(reason) => WatchlistError::InvalidAddress(reason),
+            e => WatchlistError::InvalidAddress(e.to_string()),
+        })?;
+    let label = non_empty(request.label).map(validate_label).transpose()?;
+    let threshold = non_empty(request.threshold)
+        .map(|t| parse_threshold(chain, &t))
+        .transpose()?;
+    let webhook_url = non_empty(request.webhook_url)
+        .map(validate_webhook)
+        .transpose()?;
+
+    if state.db.get_watches(user_id).await?.len() >= MAX_WATCHES {
+        return Err(WatchlistError::LimitReached);
+    }
+
+    let watch = WatchRow::new(
+        user_id.to_string(),
+        current_tenant_id(),
+        chain.to_string(),
+        address.clone(),
+        label,
+        threshold,
+        webhook_url,
+    );
+    state.db.create_watch(&watch).await.map_err(|e| match e {
+        DatabaseError::AlreadyExists => WatchlistError::Duplicate(address),
+        e => e.into(),
+    })?;
+    tracing::info!(user_id = %user_id, watch_id = %watch.id, chain = %watch.chain, "Address watched");
+
+    Ok(WatchResponse::new(watch, Vec::new()))
+}
+
+/// Change an entry's label, threshold or webhook
+pub async fn update_watch(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+    request: UpdateWatchRequest,
+) -> Result<WatchResponse, WatchlistError> {
+    let watch = get_owned_watch(state, user_id, id).await?;
+    let chain: Chain = watch
+        .chain
+        .parse()
+        .map_err(|_| WatchlistError::InvalidChain(watch.chain.clone()))?;
+
+    let label = match request.label {
+        Some(label) => non_empty(Some(label)).map(validate_label).transpose()?,
+        None => watch.label,
+    };
+    let threshold = match request.threshold {
+        Some(threshold) => non_empty(Some(threshold))
+            .map(|t| parse_threshold(chain, &t))
+            .transpose()?,
+        None => watch.threshold,
+    };
+    let webhook_url = match request.webhook_url {
+        Some(url) => non_empty(Some(url)).map(validate_webhook).transpose()?,
+        None => watch.webhook_url,
+    };
+
+    state
+        .db
+        .update_watch(id, label.as_deref(), threshold.as_deref(), webhook_url.as_deref())
+        .await?;
+
+    let watch = get_owned_watch(state, user_id, id).await?;
+    let activity = state
+        .db
+        .get_recent_watch_activity(std::slice::from_ref(&watch.id), ACTIVITY_LIMIT)
+        .await?;
+    Ok(WatchResponse::new(watch, activity))
+}
+
+/// Stop watching an address and drop its activity
+pub async fn remove_watch(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<(), WatchlistError> {
+    get_owned_watch(state, user_id, id).await?;
+    Ok(state.db.delete_watch(id).await?)
+}
+
+/// Poll every watched address once. Returns how many movements notified.
+pub async fn poll_watchlist(state: &Arc<AppState>) -> Result<usize, WatchlistError> {
+    let watches = state.db.get_all_watches().await?;
+    if watches.is_empty() {
+        return Ok(0);
+    }
+
+    let mut by_tenant: BTreeMap<String, Vec<WatchRow>> = BTreeMap::new();
+    for watch in watches {
+        by_tenant.entry(watch.tenant_id.clone()).or_default().push(watch);
+    }
+
+    let http = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let mut notified = 0;
+    for (tenant_id, watches) in by_tenant {
+        // Polled through the tenant's own RPC endpoints where it has them
+        let tenant = match tenant_service::tenant_context(state, &tenant_id).await {
+            Ok(tenant) => tenant,
+            Err(e) => {
+                tracing::debug!(tenant_id = %tenant_id, error = %e, "Skipping watchlist");
+                continue;
+            }
+        };
+        notified += with_tenant(tenant, async {
+            let mut notified = 0;
+            for watch in &watches {
+                match poll_watch(state, &http, watch).await {
+                    Ok(count) => notified += count,
+                    Err(e) => {
+                        tracing::warn!(watch_id = %watch.id, error = %e, "Watchlist poll failed")
+                    }
+                }
+            }
+            notified
+        })
+        .await;
+    }
+
+    Ok(notified)
+}
+
+/// Compare one address against its previous poll and record what changed
+async fn poll_watch(
+    state: &Arc<AppState>,
+    http: &reqwest::Client,
+    watch: &WatchRow,
+) -> Result<usize, WatchlistError> {
+    let chain: Chain = watch
+        .chain
+        .parse()
+        .map_err(|_| WatchlistError::InvalidChain(watch.chain.clone()))?;
+    let decimals = chain.native_decimals();
+    let clients = state.chain_clients();
+    let client = clients.get(chain);
+    let first_poll = watch.last_checked_at.is_none();
+
+    let balance = client
+        .balance(&watch.address)
+        .await
+        .map_err(|e| WatchlistError::Chain(e.to_string()))?;
+    let balance = parse_units(&balance.native_balance, decimals).ok_or_else(|| {
+        WatchlistError::Chain(format!("unreadable balance {}", balance.native_balance))
+    })?;
+
+    let mut activity = Vec::new();
+    let mut last_signature = watch.last_signature.clone();
+    // Only Solana can list an arbitrary address's transactions
+    if chain == Chain::Solana {
+        let recent = client
+            .find_by_reference(&watch.address, TX_POLL_LIMIT)
+            .await
+            .map_err(|e| WatchlistError::Chain(e.to_string()))?;
+        if !first_poll {
+            let new = recent
+                .iter()
+                .take_while(|tx| watch.last_signature.as_deref() != Some(tx.signature.as_str()));
+            // Oldest first, so the newest is also the latest row
+            for tx in new.collect::<Vec<_>>().into_iter().rev() {
+                activity.push(WatchActivityRow::transaction(
+                    watch.id.clone(),
+                    tx.signature.clone(),
+                    tx.status.clone(),
+                    tx.block_time,
+                ));
+            }
+        }
+        if let Some(newest) = recent.first() {
+            last_signature = Some(newest.signature.clone());
+        }
+    }
+
+    let mut notified = 0;
+    let previous = watch.last_balance.as_deref().and_then(|b| parse_units(b, decimals));
+    if let Some(previous) = previous.filter(|previous| *previous != balance) {
+        let change = balance as i128 - previous as i128;
+        let mut row = WatchActivityRow::balance_change(
+            watch.id.clone(),
+            format_signed_units(change, decimals),
+            format_units(balance, decimals),
+        );
+        let threshold = watch.threshold.as_deref().and_then(|t| parse_units(t, decimals));
+        if threshold.is_some_and(|threshold| balance.abs_diff(previous) >= threshold) {
+            row.notified = true;
+            row.webhook_status = notify(state, http, chain, watch, &row).await;
+            notified += 1;
+        }
+        activity.push(row);
+    }
+
+    state
+        .db
+        .record_watch_poll(
+            &watch.id,
+            &format_units(balance, decimals),
+            last_signature.as_deref(),
+            &Utc::now().to_rfc3339(),
+            &activity,
+        )
+        .await?;
+    Ok(notified)
+}
+
+/// Deliver a notifying movement to the entry's webhook. Returns the
+/// delivery status, `None` without a webhook.
+async fn notify(
+    state: &Arc<AppState>,
+    http: &reqwest::Client,
+    chain: Chain,
+    watch: &WatchRow,
+    row: &WatchActivityRow,
+) -> Option<String> {
+    let amount = row.amount.clone().unwrap_or_default();
+    let balance = row.balance.clone().unwrap_or_default();
+    let direction = if amount.starts_with('-') { "out of" } else { "into" };
+    let message = format!(
+        "{} {} moved {} {}, balance now {} {}",
+        amount.trim_start_matches('-'),
+        native_symbol(chain),
+        direction,
+        watch.label.as_deref().unwrap_or(&watch.address),
+        balance,
+        native_symbol(chain),
+    );
+    tracing::info!(watch_id = %watch.id, user_id = %watch.user_id, "{}", message);
+
+    let url = watch.webhook_url.as_ref()?;
+    let payload = WatchWebhookPayload {
+        watch_id: watch.id.clone(),
+        chain: watch.chain.clone(),
+        address: watch.address.clone(),
+        label: watch.label.clone(),
+        threshold: watch.threshold.clone().unwrap_or_default(),
+        amount,
+        balance,
+        message,
+        detected_at: row.detected_at.clone(),
+    };
+    let endpoint = Endpoint {
+        tenant_id: &watch.tenant_id,
+        webhook_id: &watch.id,
+        url,
+    };
+    Some(webhook_service::deliver(state, http, endpoint, EventType::WatchlistMovement, &payload).await)
+}
+
+fn native_symbol(chain: Chain) -> &'static str {
+    match chain {
+        Chain::Solana => "SOL",
+        Chain::Ethereum => "ETH",
+    }
+}
+
+/// A positive native amount, in canonical form
+fn parse_threshold(chain: Chain, threshold: &str) -> Result<String, WatchlistError> {
+    let decimals = chain.native_decimals();
+    match parse_units(threshold, decimals) {
+        Some(units) if units > 0 => Ok(format_units(units, decimals)),
+        _ => Err(WatchlistError::InvalidThreshold(threshold.trim().to_string())),
+    }
+}
+
+fn validate_label(label: String) -> Result<String, WatchlistError> {
+    if label.chars().count() > MAX_LABEL_CHARS {
+        return Err(WatchlistError::InvalidLabel(format!(
+            "longer than {} characters",
+            MAX_LABEL_CHARS
+        )));
+    }
+    Ok(label)
+}
+
+fn validate_webhook(url: String) -> Result<String, WatchlistError> {
+    match reqwest::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+        _ => Err(WatchlistError::InvalidWebhook(url)),
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+async fn get_owned_watch(
+    state: &Arc<AppState>,
+    user_id: &str,
+    id: &str,
+) -> Result<WatchRow, WatchlistError> {
+    match state.db.get_watch(id).await {
+        Ok(watch) if watch.user_id == user_id => Ok(watch),
+        Ok(_) | Err(DatabaseError::NotFound) => Err(WatchlistError::NotFound),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_inputs() {
+        assert_eq!(parse_threshold(Chain::Solana, " 1.50 ").unwrap(), "1.5");
+        assert_eq!(parse_threshold(Chain::Ethereum, "0.000000000000000001").unwrap(), "0.000000000000000001");
+        assert!(parse_threshold(Chain::Solana, "0").is_err());
+        assert!(parse_threshold(Chain::Solana, "-2").is_err());
+        assert!(parse_threshold(Chain::Solana, "0.0000000001").is_err());
+        assert!(validate_label("x".repeat(MAX_LABEL_CHARS + 1)).is_err());
+        assert!(validate_webhook("ftp://example.com".to_string()).is_err());
+    }
+}
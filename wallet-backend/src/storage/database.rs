@@ -1,9 +1,30 @@
 //! Database operations using SQLx
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::migrate::Migrator;
 use sqlx::{Pool, Sqlite};
 use thiserror::Error;
 
+use super::field_crypto::FieldCipher;
 use super::models::*;
+use super::schema::{schema_status, SchemaStatus};
+use crate::chains::TxReceipt;
+
+/// Subqueries selecting a tenant's rows, bound to the tenant ID
+const TENANT_WALLETS: &str = "SELECT id FROM wallets WHERE tenant_id = ?";
+const TENANT_ACCOUNTS: &str =
+    "SELECT id FROM accounts WHERE wallet_id IN (SELECT id FROM wallets WHERE tenant_id = ?)";
+const TENANT_MULTISIGS: &str =
+    "SELECT id FROM multisig_wallets WHERE wallet_id IN (SELECT id FROM wallets WHERE tenant_id = ?)";
+
+/// `?, ?, ...` for binding `count` values into an `IN` list
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
 
 #[derive(Debug, Error)]
 pub enum DatabaseError {
@@ -13,17 +34,246 @@ pub enum DatabaseError {
     NotFound,
     #[error("Record already exists")]
     AlreadyExists,
+    #[error("Encrypted field error: {0}")]
+    FieldEncryption(#[from] super::field_crypto::FieldCryptoError),
 }
 
+/// How long a replica may take to answer a health check
+const REPLICA_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Database wrapper with connection pool
 #[derive(Clone)]
 pub struct Database {
     pool: Pool<Sqlite>,
+    /// Read-only copies of the primary that history and reporting queries
+    /// are spread over
+    replicas: Arc<[Replica]>,
+    next_replica: Arc<AtomicUsize>,
+    /// Seals contact names and notes and transaction memos when set
+    cipher: Option<FieldCipher>,
+    /// Least severe event queued for SIEM export; nothing is queued when unset
+    siem_min_severity: Option<AuditSeverity>,
+}
+
+struct Replica {
+    pool: Pool<Sqlite>,
+    healthy: AtomicBool,
 }
 
 impl Database {
     pub fn new(pool: Pool<Sqlite>) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            replicas: Arc::new([]),
+            next_replica: Arc::new(AtomicUsize::new(0)),
+            cipher: None,
+            siem_min_severity: None,
+        }
+    }
+
+    /// Encrypt privacy-sensitive fields on write and decrypt them on read
+    pub fn with_field_encryption(mut self, cipher: FieldCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Queue audit and transaction events of at least `min_severity` in the
+    /// SIEM outbox, in the same transaction as the change they describe
+    pub fn with_siem_outbox(mut self, min_severity: AuditSeverity) -> Self {
+        self.siem_min_severity = Some(min_severity);
+        self
+    }
+
+    /// Route history and reporting reads to `replicas`. They are not used
+    /// until a health check has found them current.
+    pub fn with_read_replicas(mut self, replicas: Vec<Pool<Sqlite>>) -> Self {
+        self.replicas = replicas
+            .into_iter()
+            .map(|pool| Replica {
+                pool,
+                healthy: AtomicBool::new(false),
+            })
+            .collect();
+        self
+    }
+
+    // ==================== Read Replicas ====================
+
+    /// Pool for reads that can tolerate replication lag: the next healthy
+    /// replica in turn, or the primary when there is none
+    fn reader(&self) -> &Pool<Sqlite> {
+        let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        (0..self.replicas.len())
+            .map(|i| &self.replicas[(start + i) % self.replicas.len()])
+            .find(|replica| replica.healthy.load(Ordering::Relaxed))
+            .map_or(&self.pool, |replica| &replica.pool)
+    }
+
+    /// Check every replica against the primary. One is healthy when it
+    /// answers within `REPLICA_CHECK_TIMEOUT` and has every migration the
+    /// primary has; a replica behind on schema would fail newer queries.
+    /// Returns how many are healthy.
+    pub async fn check_replicas(&self) -> Result<usize, DatabaseError> {
+        let expected = Self::schema_version(&self.pool).await?;
+
+        let mut healthy = 0;
+        for (index, replica) in self.replicas.iter().enumerate() {
+            let status =
+                match tokio::time::timeout(REPLICA_CHECK_TIMEOUT, Self::schema_version(&replica.pool))
+                    .await
+                {
+                    Ok(Ok(version)) if version == expected => Ok(()),
+                    Ok(Ok(version)) => Err(format!("at schema {} of {}", version, expected)),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(_) => Err("timed out".to_string()),
+                };
+
+            let was_healthy = replica.healthy.swap(status.is_ok(), Ordering::Relaxed);
+            match status {
+                Ok(()) => {
+                    healthy += 1;
+                    if !was_healthy {
+                        tracing::info!(replica = index, "Read replica in use");
+                    }
+                }
+                Err(reason) if was_healthy => {
+                    tracing::warn!(replica = index, %reason, "Read replica unhealthy; reading from primary")
+                }
+                Err(reason) => tracing::debug!(replica = index, %reason, "Read replica still unhealthy"),
+            }
+        }
+        Ok(healthy)
+    }
+
+    /// Migrations applied to the primary compared with `migrator`'s
+    pub async fn schema_status(&self, migrator: &Migrator) -> Result<SchemaStatus, DatabaseError> {
+        Ok(schema_status(&self.pool, migrator).await?)
+    }
+
+    async fn schema_version(pool: &Pool<Sqlite>) -> Result<i64, DatabaseError> {
+        let (version,): (i64,) =
+            sqlx::query_as("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success = 1")
+                .fetch_one(pool)
+                .await?;
+        Ok(version)
+    }
+
+    /// Run `check_replicas` every `interval` for the life of the process;
+    /// nothing is spawned without replicas
+    pub fn spawn_replica_health_checks(&self, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        if self.replicas.is_empty() {
+            return None;
+        }
+        let db = self.clone();
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = db.check_replicas().await {
+                    tracing::warn!(error = %e, "Read replica check failed");
+                }
+            }
+        }))
+    }
+
+    // ==================== Field Encryption ====================
+
+    fn seal(&self, value: &str) -> String {
+        match &self.cipher {
+            Some(cipher) => cipher.encrypt(value),
+            None => value.to_string(),
+        }
+    }
+
+    fn seal_opt(&self, value: Option<&str>) -> Option<String> {
+        value.map(|value| self.seal(value))
+    }
+
+    fn open(&self, value: String) -> Result<String, DatabaseError> {
+        match &self.cipher {
+            Some(cipher) => Ok(cipher.decrypt(&value)?),
+            None => Ok(value),
+        }
+    }
+
+    fn open_opt(&self, value: Option<String>) -> Result<Option<String>, DatabaseError> {
+        value.map(|value| self.open(value)).transpose()
+    }
+
+    fn open_contact(&self, mut contact: ContactRow) -> Result<ContactRow, DatabaseError> {
+        contact.name = self.open(contact.name)?;
+        contact.notes = self.open_opt(contact.notes)?;
+        Ok(contact)
+    }
+
+    fn open_contacts(&self, contacts: Vec<ContactRow>) -> Result<Vec<ContactRow>, DatabaseError> {
+        contacts.into_iter().map(|c| self.open_contact(c)).collect()
+    }
+
+    fn open_transaction(&self, mut tx: TransactionRow) -> Result<TransactionRow, DatabaseError> {
+        tx.memo = self.open_opt(tx.memo)?;
+        Ok(tx)
+    }
+
+    fn open_transactions(
+        &self,
+        txs: Vec<TransactionRow>,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        txs.into_iter().map(|tx| self.open_transaction(tx)).collect()
+    }
+
+    /// Seal contact and memo values still stored in plaintext, such as rows
+    /// written before encryption was enabled. Returns the number of contacts
+    /// and transactions rewritten; nothing is done without a cipher.
+    pub async fn encrypt_plaintext_fields(&self) -> Result<(u64, u64), DatabaseError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok((0, 0));
+        };
+        let mut tx = self.pool.begin().await?;
+
+        let contacts: Vec<(String, String, Option<String>)> =
+            sqlx::query_as("SELECT id, name, notes FROM contacts")
+                .fetch_all(&mut *tx)
+                .await?;
+        let mut contacts_sealed = 0;
+        for (id, name, notes) in contacts {
+            let notes_sealed = notes.as_deref().is_none_or(FieldCipher::is_encrypted);
+            if FieldCipher::is_encrypted(&name) && notes_sealed {
+                continue;
+            }
+            let seal = |value: &str| match FieldCipher::is_encrypted(value) {
+                true => value.to_string(),
+                false => cipher.encrypt(value),
+            };
+            sqlx::query("UPDATE contacts SET name = ?, notes = ? WHERE id = ?")
+                .bind(seal(&name))
+                .bind(notes.as_deref().map(seal))
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+            contacts_sealed += 1;
+        }
+
+        let memos: Vec<(String, String)> = sqlx::query_as(
+            "SELECT id, memo FROM transaction_history WHERE memo IS NOT NULL",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut memos_sealed = 0;
+        for (id, memo) in memos {
+            if FieldCipher::is_encrypted(&memo) {
+                continue;
+            }
+            sqlx::query("UPDATE transaction_history SET memo = ? WHERE id = ?")
+                .bind(cipher.encrypt(&memo))
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+            memos_sealed += 1;
+        }
+
+        tx.commit().await?;
+        Ok((contacts_sealed, memos_sealed))
     }
 
     // ==================== Wallet Operations ====================
@@ -31,8 +281,8 @@ impl Database {
     pub async fn create_wallet(&self, wallet: &WalletRow) -> Result<(), DatabaseError> {
         sqlx::query(
             r#"
-            INSERT INTO wallets (id, encrypted_seed, salt, nonce, created_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO wallets (id, encrypted_seed, salt, nonce, created_at, encrypted_entropy, mnemonic_word_count, mnemonic_language, keyfile_required, tenant_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&wallet.id)
@@ -40,6 +290,11 @@ impl Database {
         .bind(&wallet.salt)
         .bind(&wallet.nonce)
         .bind(&wallet.created_at)
+        .bind(&wallet.encrypted_entropy)
+        .bind(wallet.mnemonic_word_count)
+        .bind(&wallet.mnemonic_language)
+        .bind(wallet.keyfile_required)
+        .bind(&wallet.tenant_id)
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -53,21 +308,291 @@ impl Database {
             .ok_or(DatabaseError::NotFound)
     }
 
-    pub async fn get_primary_wallet(&self) -> Result<Option<WalletRow>, DatabaseError> {
-        Ok(
-            sqlx::query_as::<_, WalletRow>("SELECT * FROM wallets ORDER BY created_at ASC LIMIT 1")
-                .fetch_optional(&self.pool)
-                .await?,
+    /// The tenant's first wallet, which is the one unlocked and used for signing
+    pub async fn get_primary_wallet(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Option<WalletRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, WalletRow>(
+            "SELECT * FROM wallets WHERE tenant_id = ? ORDER BY created_at ASC LIMIT 1",
         )
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?)
     }
 
-    pub async fn wallet_exists(&self) -> Result<bool, DatabaseError> {
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM wallets")
+    pub async fn wallet_exists(&self, tenant_id: &str) -> Result<bool, DatabaseError> {
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM wallets WHERE tenant_id = ?")
+            .bind(tenant_id)
             .fetch_one(&self.pool)
             .await?;
         Ok(count.0 > 0)
     }
 
+    pub async fn mark_backup_confirmed(&self, wallet_id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE wallets SET backup_confirmed_at = ? WHERE id = ?")
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(wallet_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Swap the wallet's encrypted seed and entropy for ones sealed under a new
+    /// password, recording `audit` in the same transaction
+    pub async fn reencrypt_wallet(
+        &self,
+        wallet_id: &str,
+        encrypted_seed: &[u8],
+        salt: &[u8],
+        nonce: &[u8],
+        encrypted_entropy: Option<&[u8]>,
+        audit: &AuditEventRow,
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE wallets SET encrypted_seed = ?, salt = ?, nonce = ?, encrypted_entropy = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(encrypted_seed)
+        .bind(salt)
+        .bind(nonce)
+        .bind(encrypted_entropy)
+        .bind(wallet_id)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        insert_audit_event(&mut tx, audit).await?;
+        self.queue_siem_event(&mut tx, SiemEventRow::audit(audit)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // ==================== Backup Challenge Operations ====================
+
+    pub async fn create_backup_challenge(
+        &self,
+        challenge: &BackupChallengeRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "INSERT INTO backup_challenges (id, wallet_id, positions, expires_at, created_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&challenge.id)
+        .bind(&challenge.wallet_id)
+        .bind(&challenge.positions)
+        .bind(&challenge.expires_at)
+        .bind(&challenge.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_backup_challenge(&self, id: &str) -> Result<BackupChallengeRow, DatabaseError> {
+        sqlx::query_as::<_, BackupChallengeRow>("SELECT * FROM backup_challenges WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    pub async fn delete_backup_challenge(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM backup_challenges WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // ==================== Sign-In Operations ====================
+
+    pub async fn create_sign_in_challenge(
+        &self,
+        challenge: &SignInChallengeRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO sign_in_challenges (nonce, tenant_id, chain, address, message, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&challenge.nonce)
+        .bind(&challenge.tenant_id)
+        .bind(&challenge.chain)
+        .bind(&challenge.address)
+        .bind(&challenge.message)
+        .bind(&challenge.expires_at)
+        .bind(&challenge.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Remove and return a challenge, so a nonce can only be redeemed once
+    pub async fn take_sign_in_challenge(
+        &self,
+        nonce: &str,
+        tenant_id: &str,
+    ) -> Result<SignInChallengeRow, DatabaseError> {
+        sqlx::query_as::<_, SignInChallengeRow>(
+            "DELETE FROM sign_in_challenges WHERE nonce = ? AND tenant_id = ? RETURNING *",
+        )
+        .bind(nonce)
+        .bind(tenant_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Drop challenges that expired without being redeemed
+    pub async fn delete_expired_sign_in_challenges(&self, now: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM sign_in_challenges WHERE expires_at < ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_unlock_challenge(
+        &self,
+        challenge: &UnlockChallengeRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "INSERT INTO unlock_challenges (nonce, tenant_id, ip_address, expires_at, redeemed_at, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&challenge.nonce)
+        .bind(&challenge.tenant_id)
+        .bind(&challenge.ip_address)
+        .bind(&challenge.expires_at)
+        .bind(&challenge.redeemed_at)
+        .bind(&challenge.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Unlock challenges issued to a tenant since a time, redeemed or not
+    pub async fn count_unlock_challenges_since(
+        &self,
+        tenant_id: &str,
+        since: &str,
+    ) -> Result<i64, DatabaseError> {
+        Ok(sqlx::query_scalar(
+            "SELECT COUNT(*) FROM unlock_challenges WHERE tenant_id = ? AND created_at > ?",
+        )
+        .bind(tenant_id)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Unlock challenges issued to one client of a tenant since a time
+    pub async fn count_unlock_challenges_from_since(
+        &self,
+        tenant_id: &str,
+        ip_address: &str,
+        since: &str,
+    ) -> Result<i64, DatabaseError> {
+        Ok(sqlx::query_scalar(
+            "SELECT COUNT(*) FROM unlock_challenges WHERE tenant_id = ? AND ip_address = ? AND created_at > ?",
+        )
+        .bind(tenant_id)
+        .bind(ip_address)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    /// Mark an unexpired, unused challenge redeemed; `NotFound` otherwise
+    pub async fn redeem_unlock_challenge(
+        &self,
+        nonce: &str,
+        tenant_id: &str,
+        now: &str,
+    ) -> Result<UnlockChallengeRow, DatabaseError> {
+        sqlx::query_as::<_, UnlockChallengeRow>(
+            r#"
+            UPDATE unlock_challenges SET redeemed_at = ?
+            WHERE nonce = ? AND tenant_id = ? AND redeemed_at IS NULL AND expires_at > ?
+            RETURNING *
+            "#,
+        )
+        .bind(now)
+        .bind(nonce)
+        .bind(tenant_id)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::NotFound)
+    }
+
+    pub async fn delete_expired_unlock_challenges(&self, now: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM unlock_challenges WHERE expires_at < ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_user_address(&self, address: &UserAddressRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "INSERT INTO user_addresses (id, user_id, chain, address, created_at) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(&address.id)
+        .bind(&address.user_id)
+        .bind(&address.chain)
+        .bind(&address.address)
+        .bind(&address.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            _ => DatabaseError::SqlxError(e),
+        })?;
+        Ok(())
+    }
+
+    pub async fn get_user_addresses(&self, user_id: &str) -> Result<Vec<UserAddressRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, UserAddressRow>(
+            "SELECT * FROM user_addresses WHERE user_id = ? ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// User an address is linked to
+    pub async fn find_user_address(
+        &self,
+        chain: &str,
+        address: &str,
+    ) -> Result<Option<UserAddressRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, UserAddressRow>(
+            "SELECT * FROM user_addresses WHERE chain = ? AND address = ?",
+        )
+        .bind(chain)
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn delete_user_address(&self, id: &str, user_id: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query("DELETE FROM user_addresses WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
     // ==================== Account Operations ====================
 
     pub async fn create_account(&self, account: &AccountRow) -> Result<(), DatabaseError> {
@@ -108,14 +633,94 @@ impl Database {
             .ok_or(DatabaseError::NotFound)
     }
 
+    /// Set or clear the RPC endpoint an account uses
+    pub async fn set_account_rpc_url(
+        &self,
+        id: &str,
+        rpc_url: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query("UPDATE accounts SET rpc_url = ? WHERE id = ?")
+            .bind(rpc_url)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Set whether an account's sends go through the private relay by default
+    pub async fn set_account_mev_protect(
+        &self,
+        id: &str,
+        enabled: bool,
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query("UPDATE accounts SET mev_protect = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    /// Record a successful balance read, clearing any sync error
+    pub async fn record_account_sync(
+        &self,
+        id: &str,
+        native_balance: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE accounts
+            SET last_synced_at = ?, last_known_balance = ?, sync_error = NULL, sync_error_at = NULL
+            WHERE id = ?
+            "#,
+        )
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(native_balance)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a failed balance read; the last known balance is kept
+    pub async fn record_account_sync_error(&self, id: &str, error: &str) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE accounts SET sync_error = ?, sync_error_at = ? WHERE id = ?")
+            .bind(error)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Every account, across wallets and tenants
+    pub async fn get_all_accounts(&self) -> Result<Vec<AccountRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AccountRow>("SELECT * FROM accounts ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    /// The tenant's account at `address`; other tenants' are not found
     pub async fn get_account_by_address(
         &self,
+        tenant_id: &str,
         chain: &str,
         address: &str,
     ) -> Result<AccountRow, DatabaseError> {
         sqlx::query_as::<_, AccountRow>(
-            "SELECT * FROM accounts WHERE chain = ? AND address = ?",
+            r#"
+            SELECT a.* FROM accounts a
+            JOIN wallets w ON w.id = a.wallet_id
+            WHERE w.tenant_id = ? AND a.chain = ? AND a.address = ?
+            "#,
         )
+        .bind(tenant_id)
         .bind(chain)
         .bind(address)
         .fetch_optional(&self.pool)
@@ -176,39 +781,89 @@ impl Database {
     pub async fn create_contact(&self, contact: &ContactRow) -> Result<(), DatabaseError> {
         sqlx::query(
             r#"
-            INSERT INTO contacts (id, wallet_id, name, chain, address, notes, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO contacts (id, wallet_id, name, chain, address, notes, created_at, default_token_address, default_amount)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&contact.id)
         .bind(&contact.wallet_id)
-        .bind(&contact.name)
+        .bind(self.seal(&contact.name))
         .bind(&contact.chain)
         .bind(&contact.address)
-        .bind(&contact.notes)
+        .bind(self.seal_opt(contact.notes.as_deref()))
         .bind(&contact.created_at)
+        .bind(&contact.default_token_address)
+        .bind(&contact.default_amount)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
     pub async fn get_contacts(&self, wallet_id: &str) -> Result<Vec<ContactRow>, DatabaseError> {
-        Ok(
-            sqlx::query_as::<_, ContactRow>(
-                "SELECT * FROM contacts WHERE wallet_id = ? ORDER BY name",
-            )
-            .bind(wallet_id)
-            .fetch_all(&self.pool)
-            .await?,
+        let contacts = sqlx::query_as::<_, ContactRow>(
+            "SELECT * FROM contacts WHERE wallet_id = ? ORDER BY name",
         )
-    }
-
+        .bind(wallet_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut contacts = self.open_contacts(contacts)?;
+        // Sealed names don't sort in SQL
+        if self.cipher.is_some() {
+            contacts.sort_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+        }
+        Ok(contacts)
+    }
+
+    /// Contacts ordered by (name, id), starting after the given position
+    pub async fn get_contacts_page(
+        &self,
+        wallet_id: &str,
+        after: Option<(&str, &str)>,
+        limit: u32,
+    ) -> Result<Vec<ContactRow>, DatabaseError> {
+        if self.cipher.is_some() {
+            // Sealed names don't sort in SQL; page over the opened contacts
+            return Ok(self
+                .get_contacts(wallet_id)
+                .await?
+                .into_iter()
+                .filter(|c| after.is_none_or(|after| (c.name.as_str(), c.id.as_str()) > after))
+                .take(limit as usize)
+                .collect());
+        }
+
+        let (after_name, after_id) = after.unwrap_or(("", ""));
+        self.open_contacts(sqlx::query_as::<_, ContactRow>(
+            r#"
+            SELECT * FROM contacts
+            WHERE wallet_id = ? AND (name, id) > (?, ?)
+            ORDER BY name, id
+            LIMIT ?
+            "#,
+        )
+        .bind(wallet_id)
+        .bind(after_name)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn count_contacts(&self, wallet_id: &str) -> Result<u64, DatabaseError> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM contacts WHERE wallet_id = ?")
+            .bind(wallet_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as u64)
+    }
+
     pub async fn get_contact(&self, id: &str) -> Result<ContactRow, DatabaseError> {
         sqlx::query_as::<_, ContactRow>("SELECT * FROM contacts WHERE id = ?")
             .bind(id)
             .fetch_optional(&self.pool)
             .await?
             .ok_or(DatabaseError::NotFound)
+            .and_then(|contact| self.open_contact(contact))
     }
 
     pub async fn update_contact(
@@ -218,14 +873,93 @@ impl Database {
         notes: Option<&str>,
     ) -> Result<(), DatabaseError> {
         sqlx::query("UPDATE contacts SET name = ?, notes = ? WHERE id = ?")
-            .bind(name)
-            .bind(notes)
+            .bind(self.seal(name))
+            .bind(self.seal_opt(notes))
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Point a contact at a new address, dropping the old address's identity
+    pub async fn set_contact_address(&self, id: &str, address: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE contacts
+            SET address = ?, identity_name = NULL, identity_avatar = NULL,
+                identity_records = NULL, identity_refreshed_at = NULL
+            WHERE id = ?
+            "#,
+        )
+        .bind(address)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_contact_defaults(
+        &self,
+        id: &str,
+        token_address: Option<&str>,
+        amount: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE contacts SET default_token_address = ?, default_amount = ? WHERE id = ?")
+            .bind(token_address)
+            .bind(amount)
             .bind(id)
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
+    /// Store a resolved identity; a `None` name records that the address has none
+    pub async fn set_contact_identity(
+        &self,
+        id: &str,
+        name: Option<&str>,
+        avatar: Option<&str>,
+        records: Option<&str>,
+        refreshed_at: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE contacts
+            SET identity_name = ?, identity_avatar = ?, identity_records = ?,
+                identity_refreshed_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(name)
+        .bind(avatar)
+        .bind(records)
+        .bind(refreshed_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Contacts never resolved or last resolved before `before`, oldest first
+    pub async fn get_contacts_for_identity_refresh(
+        &self,
+        before: &str,
+        limit: u32,
+    ) -> Result<Vec<ContactRow>, DatabaseError> {
+        self.open_contacts(sqlx::query_as::<_, ContactRow>(
+            r#"
+            SELECT * FROM contacts
+            WHERE identity_refreshed_at IS NULL OR identity_refreshed_at < ?
+            ORDER BY identity_refreshed_at
+            LIMIT ?
+            "#,
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
     pub async fn delete_contact(&self, id: &str) -> Result<(), DatabaseError> {
         sqlx::query("DELETE FROM contacts WHERE id = ?")
             .bind(id)
@@ -234,51 +968,2995 @@ impl Database {
         Ok(())
     }
 
-    // ==================== Transaction History Operations ====================
+    // ==================== User Token Operations ====================
+
+    pub async fn create_user_token(&self, token: &UserTokenRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_tokens (id, user_id, chain, token_address, symbol, name, decimals, hidden, created_at, icon_url)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&token.id)
+        .bind(&token.user_id)
+        .bind(&token.chain)
+        .bind(&token.token_address)
+        .bind(&token.symbol)
+        .bind(&token.name)
+        .bind(token.decimals)
+        .bind(token.hidden)
+        .bind(&token.created_at)
+        .bind(&token.icon_url)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            _ => DatabaseError::SqlxError(e),
+        })?;
+        Ok(())
+    }
+
+    /// A user's tokens, optionally limited to one chain
+    pub async fn get_user_tokens(
+        &self,
+        user_id: &str,
+        chain: Option<&str>,
+    ) -> Result<Vec<UserTokenRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, UserTokenRow>(
+            r#"
+            SELECT * FROM user_tokens
+            WHERE user_id = ? AND (? IS NULL OR chain = ?)
+            ORDER BY chain, symbol
+            "#,
+        )
+        .bind(user_id)
+        .bind(chain)
+        .bind(chain)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_user_token(&self, id: &str) -> Result<UserTokenRow, DatabaseError> {
+        sqlx::query_as::<_, UserTokenRow>("SELECT * FROM user_tokens WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    pub async fn update_user_token(
+        &self,
+        id: &str,
+        symbol: &str,
+        name: Option<&str>,
+        hidden: bool,
+        icon_url: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE user_tokens SET symbol = ?, name = ?, hidden = ?, icon_url = ? WHERE id = ?")
+            .bind(symbol)
+            .bind(name)
+            .bind(hidden)
+            .bind(icon_url)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_user_token(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM user_tokens WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_token_view_settings(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<TokenViewSettingsRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, TokenViewSettingsRow>(
+            "SELECT * FROM token_view_settings WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn set_token_view_settings(
+        &self,
+        settings: &TokenViewSettingsRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO token_view_settings (user_id, sort, small_balance_threshold, currency, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                sort = excluded.sort,
+                small_balance_threshold = excluded.small_balance_threshold,
+                currency = excluded.currency,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&settings.user_id)
+        .bind(&settings.sort)
+        .bind(&settings.small_balance_threshold)
+        .bind(&settings.currency)
+        .bind(&settings.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_token_view_settings(&self, user_id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM token_view_settings WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// When each token an account moved was last moved, keyed by
+    /// lower-cased token address
+    pub async fn get_token_activity(
+        &self,
+        account_id: &str,
+    ) -> Result<HashMap<String, String>, DatabaseError> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT LOWER(token_address), MAX(COALESCE(timestamp, created_at)) FROM transaction_history \
+             WHERE account_id = ? AND token_address IS NOT NULL GROUP BY LOWER(token_address)",
+        )
+        .bind(account_id)
+        .fetch_all(self.reader())
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    // ==================== Send Template Operations ====================
+
+    fn open_template(&self, mut template: SendTemplateRow) -> Result<SendTemplateRow, DatabaseError> {
+        template.memo = self.open_opt(template.memo)?;
+        Ok(template)
+    }
+
+    pub async fn create_send_template(&self, template: &SendTemplateRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO send_templates (id, user_id, name, chain, from_address, to_address, amount, token_address, memo, quick_action, use_count, last_used_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&template.id)
+        .bind(&template.user_id)
+        .bind(&template.name)
+        .bind(&template.chain)
+        .bind(&template.from_address)
+        .bind(&template.to_address)
+        .bind(&template.amount)
+        .bind(&template.token_address)
+        .bind(self.seal_opt(template.memo.as_deref()))
+        .bind(template.quick_action)
+        .bind(template.use_count)
+        .bind(&template.last_used_at)
+        .bind(&template.created_at)
+        .bind(&template.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            _ => DatabaseError::SqlxError(e),
+        })?;
+        Ok(())
+    }
+
+    /// A user's templates: quick actions first, then the most recently used
+    pub async fn get_send_templates(&self, user_id: &str) -> Result<Vec<SendTemplateRow>, DatabaseError> {
+        let templates = sqlx::query_as::<_, SendTemplateRow>(
+            r#"
+            SELECT * FROM send_templates
+            WHERE user_id = ?
+            ORDER BY quick_action DESC, last_used_at IS NULL, last_used_at DESC, name
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        templates.into_iter().map(|t| self.open_template(t)).collect()
+    }
+
+    pub async fn get_send_template(&self, id: &str) -> Result<SendTemplateRow, DatabaseError> {
+        let template = sqlx::query_as::<_, SendTemplateRow>("SELECT * FROM send_templates WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)?;
+        self.open_template(template)
+    }
+
+    /// Replace a template's draft fields; usage is left as it is
+    pub async fn update_send_template(&self, template: &SendTemplateRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE send_templates
+            SET name = ?, from_address = ?, to_address = ?, amount = ?, token_address = ?,
+                memo = ?, quick_action = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&template.name)
+        .bind(&template.from_address)
+        .bind(&template.to_address)
+        .bind(&template.amount)
+        .bind(&template.token_address)
+        .bind(self.seal_opt(template.memo.as_deref()))
+        .bind(template.quick_action)
+        .bind(&template.updated_at)
+        .bind(&template.id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            _ => DatabaseError::SqlxError(e),
+        })?;
+        Ok(())
+    }
+
+    /// Count one use of a template
+    pub async fn record_send_template_use(&self, id: &str, used_at: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "UPDATE send_templates SET use_count = use_count + 1, last_used_at = ? WHERE id = ?",
+        )
+        .bind(used_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_send_template(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM send_templates WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // ==================== Audit Log Operations ====================
+
+    pub async fn record_audit_event(&self, event: &AuditEventRow) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        insert_audit_event(&mut tx, event).await?;
+        self.queue_siem_event(&mut tx, SiemEventRow::audit(event)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The tenant's audit events, newest first
+    pub async fn get_audit_events(
+        &self,
+        tenant_id: &str,
+        limit: i64,
+    ) -> Result<Vec<AuditEventRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AuditEventRow>(
+            "SELECT * FROM audit_events WHERE tenant_id = ? ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(tenant_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// The latest audit events of every tenant, newest first
+    pub async fn get_recent_audit_events(&self, limit: i64) -> Result<Vec<AuditEventRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AuditEventRow>(
+            "SELECT * FROM audit_events ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(self.reader())
+        .await?)
+    }
+
+    // ==================== Webhook Operations ====================
+
+    /// The tenant's webhook signing secret, stored as `secret` unless it
+    /// already has one
+    pub async fn get_or_create_webhook_secret(
+        &self,
+        tenant_id: &str,
+        secret: &str,
+    ) -> Result<String, DatabaseError> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO webhook_secrets (tenant_id, secret, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(tenant_id)
+        .bind(secret)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        let (secret,): (String,) =
+            sqlx::query_as("SELECT secret FROM webhook_secrets WHERE tenant_id = ?")
+                .bind(tenant_id)
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(secret)
+    }
+
+    pub async fn set_webhook_secret(&self, tenant_id: &str, secret: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_secrets (tenant_id, secret, created_at) VALUES (?, ?, ?)
+            ON CONFLICT(tenant_id) DO UPDATE SET secret = excluded.secret, created_at = excluded.created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(secret)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_webhook_delivery(&self, delivery: &WebhookDeliveryRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries
+                (id, tenant_id, webhook_id, event_id, event_type, url, test, status_code, error, duration_ms, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&delivery.id)
+        .bind(&delivery.tenant_id)
+        .bind(&delivery.webhook_id)
+        .bind(&delivery.event_id)
+        .bind(&delivery.event_type)
+        .bind(&delivery.url)
+        .bind(delivery.test)
+        .bind(delivery.status_code)
+        .bind(&delivery.error)
+        .bind(delivery.duration_ms)
+        .bind(&delivery.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// A webhook's latest deliveries, newest first
+    pub async fn get_webhook_deliveries(
+        &self,
+        webhook_id: &str,
+        limit: i64,
+    ) -> Result<Vec<WebhookDeliveryRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, WebhookDeliveryRow>(
+            "SELECT * FROM webhook_deliveries WHERE webhook_id = ? ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(self.reader())
+        .await?)
+    }
+
+    // ==================== Share Link Operations ====================
+
+    pub async fn create_share_link(&self, link: &ShareLinkRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO share_links
+                (id, tenant_id, user_id, scope, resource_id, expires_at, revoked_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&link.id)
+        .bind(&link.tenant_id)
+        .bind(&link.user_id)
+        .bind(&link.scope)
+        .bind(&link.resource_id)
+        .bind(&link.expires_at)
+        .bind(&link.revoked_at)
+        .bind(&link.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_share_link(&self, id: &str) -> Result<ShareLinkRow, DatabaseError> {
+        sqlx::query_as::<_, ShareLinkRow>("SELECT * FROM share_links WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// A user's share links, newest first
+    pub async fn get_share_links(&self, user_id: &str) -> Result<Vec<ShareLinkRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, ShareLinkRow>(
+            "SELECT * FROM share_links WHERE user_id = ? ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(self.reader())
+        .await?)
+    }
+
+    /// Revoke one of a user's links, keeping the time of an earlier
+    /// revocation; `false` when the user has no link by that ID
+    pub async fn revoke_share_link(&self, id: &str, user_id: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE share_links SET revoked_at = COALESCE(revoked_at, ?) WHERE id = ? AND user_id = ?",
+        )
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ==================== Status Operations ====================
+
+    /// How much work each background job has waiting
+    pub async fn get_queue_depths(&self) -> Result<QueueDepths, DatabaseError> {
+        Ok(sqlx::query_as::<_, QueueDepths>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM transaction_history
+                    WHERE status = 'pending' AND broadcast IS NOT NULL) AS pending_confirmations,
+                (SELECT COUNT(*) FROM transaction_history
+                    WHERE status = 'pending' AND stuck_at IS NOT NULL) AS stuck_transactions,
+                (SELECT COUNT(*) FROM scheduled_transactions
+                    WHERE status IN ('scheduled', 'executing')) AS scheduled_transactions,
+                (SELECT COUNT(*) FROM siem_outbox) AS siem_outbox,
+                (SELECT COUNT(*) FROM bridge_transfers
+                    WHERE status IN ('submitted', 'confirmed')) AS open_bridge_transfers,
+                (SELECT COUNT(*) FROM cross_chain_swaps
+                    WHERE status IN ('submitted', 'bridging')) AS open_cross_chain_swaps
+            "#,
+        )
+        .fetch_one(self.reader())
+        .await?)
+    }
+
+    // ==================== SIEM Outbox Operations ====================
+
+    /// Queue an event for export if SIEM export is on and it is severe enough
+    async fn queue_siem_event(
+        &self,
+        tx: &mut sqlx::Transaction<'_, Sqlite>,
+        event: SiemEventRow,
+    ) -> Result<(), DatabaseError> {
+        match self.siem_min_severity {
+            Some(min) if event.severity() >= min => insert_siem_event(tx, &event).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Queue a transaction event as the row now reads, under its wallet's tenant
+    async fn queue_transaction_event(
+        &self,
+        tx: &mut sqlx::Transaction<'_, Sqlite>,
+        event: &str,
+        chain: &str,
+        signature: &str,
+    ) -> Result<(), DatabaseError> {
+        if self.siem_min_severity.is_none() {
+            return Ok(());
+        }
+        let Some(row) = sqlx::query_as::<_, TransactionRow>(
+            "SELECT * FROM transaction_history WHERE chain = ? AND signature = ?",
+        )
+        .bind(chain)
+        .bind(signature)
+        .fetch_optional(&mut **tx)
+        .await?
+        else {
+            return Ok(());
+        };
+        let tenant_id: Option<String> = sqlx::query_scalar(
+            "SELECT w.tenant_id FROM accounts a JOIN wallets w ON w.id = a.wallet_id WHERE a.id = ?",
+        )
+        .bind(&row.account_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let tenant_id = tenant_id.unwrap_or_else(|| "default".to_string());
+        self.queue_siem_event(tx, SiemEventRow::transaction(tenant_id, event, &row))
+            .await
+    }
+
+    /// Events due for delivery, oldest first
+    pub async fn get_due_siem_events(
+        &self,
+        now: &str,
+        limit: i64,
+    ) -> Result<Vec<SiemEventRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, SiemEventRow>(
+            "SELECT * FROM siem_outbox WHERE next_attempt_at <= ? ORDER BY created_at, id LIMIT ?",
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Drop delivered events
+    pub async fn delete_siem_events(&self, ids: &[String]) -> Result<(), DatabaseError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let sql = format!("DELETE FROM siem_outbox WHERE id IN ({})", placeholders(ids.len()));
+        let mut query = sqlx::query(&sql);
+        for id in ids {
+            query = query.bind(id);
+        }
+        query.execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Count a failed delivery and hold the events back until `next_attempt_at`
+    pub async fn defer_siem_events(
+        &self,
+        ids: &[String],
+        error: &str,
+        next_attempt_at: &str,
+    ) -> Result<(), DatabaseError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let sql = format!(
+            "UPDATE siem_outbox SET attempts = attempts + 1, last_error = ?, next_attempt_at = ? WHERE id IN ({})",
+            placeholders(ids.len())
+        );
+        let mut query = sqlx::query(&sql).bind(error).bind(next_attempt_at);
+        for id in ids {
+            query = query.bind(id);
+        }
+        query.execute(&self.pool).await?;
+        Ok(())
+    }
+
+    // ==================== Note Operations ====================
+
+    pub async fn create_note(&self, note: &NoteRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO notes (id, user_id, wallet_id, target_type, target_id, body, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&note.id)
+        .bind(&note.user_id)
+        .bind(&note.wallet_id)
+        .bind(&note.target_type)
+        .bind(&note.target_id)
+        .bind(&note.body)
+        .bind(&note.created_at)
+        .bind(&note.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// A user's notes in a wallet, most recently updated first, optionally
+    /// only those on one target
+    pub async fn get_notes(
+        &self,
+        user_id: &str,
+        wallet_id: &str,
+        target: Option<(&str, &str)>,
+    ) -> Result<Vec<NoteRow>, DatabaseError> {
+        let notes = match target {
+            Some((target_type, target_id)) => {
+                sqlx::query_as::<_, NoteRow>(
+                    r#"
+                    SELECT * FROM notes
+                    WHERE user_id = ? AND wallet_id = ? AND target_type = ? AND target_id = ?
+                    ORDER BY updated_at DESC
+                    "#,
+                )
+                .bind(user_id)
+                .bind(wallet_id)
+                .bind(target_type)
+                .bind(target_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query_as::<_, NoteRow>(
+                    "SELECT * FROM notes WHERE user_id = ? AND wallet_id = ? ORDER BY updated_at DESC",
+                )
+                .bind(user_id)
+                .bind(wallet_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+        Ok(notes)
+    }
+
+    pub async fn get_note(&self, id: &str) -> Result<NoteRow, DatabaseError> {
+        sqlx::query_as::<_, NoteRow>("SELECT * FROM notes WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    pub async fn update_note(&self, id: &str, body: &str, updated_at: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query("UPDATE notes SET body = ?, updated_at = ? WHERE id = ?")
+            .bind(body)
+            .bind(updated_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn delete_note(&self, id: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query("DELETE FROM notes WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    // ==================== Bucket Operations ====================
+
+    pub async fn create_bucket(&self, bucket: &BucketRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO buckets (id, account_id, name, allocated, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&bucket.id)
+        .bind(&bucket.account_id)
+        .bind(&bucket.name)
+        .bind(&bucket.allocated)
+        .bind(&bucket.created_at)
+        .bind(&bucket.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            e => e.into(),
+        })?;
+        Ok(())
+    }
+
+    /// An account's buckets, oldest first
+    pub async fn get_buckets(&self, account_id: &str) -> Result<Vec<BucketRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, BucketRow>(
+            "SELECT * FROM buckets WHERE account_id = ? ORDER BY created_at, name",
+        )
+        .bind(account_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_bucket(&self, id: &str) -> Result<BucketRow, DatabaseError> {
+        sqlx::query_as::<_, BucketRow>("SELECT * FROM buckets WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Set bucket allocations together, each `(id, expected, new)` only if
+    /// it still holds `expected`. Returns false, changing nothing, if any
+    /// allocation moved in the meantime.
+    pub async fn set_bucket_allocations(
+        &self,
+        changes: &[(&str, &str, &str)],
+        updated_at: &str,
+    ) -> Result<bool, DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        for (id, expected, new) in changes {
+            let result = sqlx::query(
+                "UPDATE buckets SET allocated = ?, updated_at = ? WHERE id = ? AND allocated = ?",
+            )
+            .bind(new)
+            .bind(updated_at)
+            .bind(id)
+            .bind(expected)
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() == 0 {
+                return Ok(false);
+            }
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn delete_bucket(&self, id: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query("DELETE FROM buckets WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    // ==================== Watchlist Operations ====================
+
+    pub async fn create_watch(&self, watch: &WatchRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO watchlist (id, user_id, tenant_id, chain, address, label, threshold, webhook_url, last_balance, last_signature, last_checked_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&watch.id)
+        .bind(&watch.user_id)
+        .bind(&watch.tenant_id)
+        .bind(&watch.chain)
+        .bind(&watch.address)
+        .bind(&watch.label)
+        .bind(&watch.threshold)
+        .bind(&watch.webhook_url)
+        .bind(&watch.last_balance)
+        .bind(&watch.last_signature)
+        .bind(&watch.last_checked_at)
+        .bind(&watch.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            e => e.into(),
+        })?;
+        Ok(())
+    }
+
+    /// A user's watched addresses, oldest first
+    pub async fn get_watches(&self, user_id: &str) -> Result<Vec<WatchRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, WatchRow>(
+            "SELECT * FROM watchlist WHERE user_id = ? ORDER BY created_at, address",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Every watched address, grouped by tenant
+    pub async fn get_all_watches(&self) -> Result<Vec<WatchRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, WatchRow>(
+            "SELECT * FROM watchlist ORDER BY tenant_id, created_at",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_watch(&self, id: &str) -> Result<WatchRow, DatabaseError> {
+        sqlx::query_as::<_, WatchRow>("SELECT * FROM watchlist WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    pub async fn update_watch(
+        &self,
+        id: &str,
+        label: Option<&str>,
+        threshold: Option<&str>,
+        webhook_url: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE watchlist SET label = ?, threshold = ?, webhook_url = ? WHERE id = ?")
+            .bind(label)
+            .bind(threshold)
+            .bind(webhook_url)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Store what a poll of a watched address saw, with the activity it
+    /// detected
+    pub async fn record_watch_poll(
+        &self,
+        id: &str,
+        last_balance: &str,
+        last_signature: Option<&str>,
+        checked_at: &str,
+        activity: &[WatchActivityRow],
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE watchlist SET last_balance = ?, last_signature = ?, last_checked_at = ? WHERE id = ?",
+        )
+        .bind(last_balance)
+        .bind(last_signature)
+        .bind(checked_at)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        for row in activity {
+            sqlx::query(
+                r#"
+                INSERT INTO watchlist_activity (id, watch_id, kind, amount, balance, signature, status, block_time, notified, webhook_status, detected_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&row.id)
+            .bind(&row.watch_id)
+            .bind(&row.kind)
+            .bind(&row.amount)
+            .bind(&row.balance)
+            .bind(&row.signature)
+            .bind(&row.status)
+            .bind(row.block_time)
+            .bind(row.notified)
+            .bind(&row.webhook_status)
+            .bind(&row.detected_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The latest `limit` activity rows of each watched address, newest
+    /// first
+    pub async fn get_recent_watch_activity(
+        &self,
+        watch_ids: &[String],
+        limit: u32,
+    ) -> Result<Vec<WatchActivityRow>, DatabaseError> {
+        if watch_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sql = format!(
+            "SELECT * FROM (SELECT *, ROW_NUMBER() OVER \
+             (PARTITION BY watch_id ORDER BY detected_at DESC, rowid DESC) AS row_num \
+             FROM watchlist_activity WHERE watch_id IN ({})) \
+             WHERE row_num <= ? ORDER BY watch_id, row_num",
+            placeholders(watch_ids.len())
+        );
+        let mut query = sqlx::query_as::<_, WatchActivityRow>(&sql);
+        for id in watch_ids {
+            query = query.bind(id);
+        }
+        Ok(query.bind(limit).fetch_all(&self.pool).await?)
+    }
+
+    /// Delete a watched address and its activity
+    pub async fn delete_watch(&self, id: &str) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM watchlist_activity WHERE watch_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM watchlist WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // ==================== Feature Flag Operations ====================
+
+    pub async fn get_feature_flags(&self) -> Result<Vec<FeatureFlagRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, FeatureFlagRow>("SELECT * FROM feature_flags ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    pub async fn upsert_feature_flag(&self, flag: &FeatureFlagRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO feature_flags (name, enabled, reason, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET
+                enabled = excluded.enabled,
+                reason = excluded.reason,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&flag.name)
+        .bind(flag.enabled)
+        .bind(&flag.reason)
+        .bind(&flag.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ==================== CORS Origin Operations ====================
+
+    pub async fn get_cors_origins(&self) -> Result<Vec<CorsOriginRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, CorsOriginRow>("SELECT * FROM cors_origins ORDER BY origin")
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    pub async fn create_cors_origin(&self, origin: &CorsOriginRow) -> Result<(), DatabaseError> {
+        sqlx::query("INSERT INTO cors_origins (origin, created_at) VALUES (?, ?)")
+            .bind(&origin.origin)
+            .bind(&origin.created_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                    DatabaseError::AlreadyExists
+                }
+                _ => DatabaseError::SqlxError(e),
+            })?;
+        Ok(())
+    }
+
+    /// Returns whether the origin was there to remove
+    pub async fn delete_cors_origin(&self, origin: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM cors_origins WHERE origin = ?")
+            .bind(origin)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ==================== Reconciliation Operations ====================
+
+    /// Record a finished run with the discrepancies it found
+    pub async fn record_reconciliation_run(
+        &self,
+        run: &ReconciliationRunRow,
+        discrepancies: &[BalanceDiscrepancyRow],
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO reconciliation_runs (id, started_at, finished_at, accounts_checked, discrepancy_count)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&run.id)
+        .bind(&run.started_at)
+        .bind(&run.finished_at)
+        .bind(run.accounts_checked)
+        .bind(run.discrepancy_count)
+        .execute(&mut *tx)
+        .await?;
+
+        for d in discrepancies {
+            sqlx::query(
+                r#"
+                INSERT INTO balance_discrepancies (id, run_id, tenant_id, account_id, chain, address, kind, cached_balance, expected_balance, live_balance, detail, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&d.id)
+            .bind(&d.run_id)
+            .bind(&d.tenant_id)
+            .bind(&d.account_id)
+            .bind(&d.chain)
+            .bind(&d.address)
+            .bind(&d.kind)
+            .bind(&d.cached_balance)
+            .bind(&d.expected_balance)
+            .bind(&d.live_balance)
+            .bind(&d.detail)
+            .bind(&d.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_latest_reconciliation_run(
+        &self,
+    ) -> Result<Option<ReconciliationRunRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, ReconciliationRunRow>(
+            "SELECT * FROM reconciliation_runs ORDER BY started_at DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_balance_discrepancies(
+        &self,
+        run_id: &str,
+    ) -> Result<Vec<BalanceDiscrepancyRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, BalanceDiscrepancyRow>(
+            "SELECT * FROM balance_discrepancies WHERE run_id = ? ORDER BY created_at, id",
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    // ==================== Faucet Operations ====================
+
+    pub async fn record_faucet_request(&self, request: &FaucetRequestRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO faucet_requests (id, user_id, chain, address, amount, tx_hash, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&request.id)
+        .bind(&request.user_id)
+        .bind(&request.chain)
+        .bind(&request.address)
+        .bind(&request.amount)
+        .bind(&request.tx_hash)
+        .bind(&request.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Faucet requests for an address recorded at or after `since`, oldest
+    /// first
+    pub async fn get_faucet_requests_since(
+        &self,
+        chain: &str,
+        address: &str,
+        since: &str,
+    ) -> Result<Vec<FaucetRequestRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, FaucetRequestRow>(
+            r#"
+            SELECT * FROM faucet_requests
+            WHERE chain = ? AND address = ? AND created_at >= ?
+            ORDER BY created_at
+            "#,
+        )
+        .bind(chain)
+        .bind(address)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    // ==================== Token List Operations ====================
+
+    /// Replace the cached token list for `chain`
+    pub async fn replace_token_list(
+        &self,
+        chain: &str,
+        tokens: &[TokenListRow],
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM token_list WHERE chain = ?")
+            .bind(chain)
+            .execute(&mut *tx)
+            .await?;
+        for token in tokens {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO token_list
+                    (chain, address, symbol, name, decimals, logo_uri, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&token.chain)
+            .bind(&token.address)
+            .bind(&token.symbol)
+            .bind(&token.name)
+            .bind(token.decimals)
+            .bind(&token.logo_uri)
+            .bind(&token.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_token_list(&self) -> Result<Vec<TokenListRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, TokenListRow>("SELECT * FROM token_list ORDER BY chain, symbol")
+            .fetch_all(&self.pool)
+            .await?)
+    }
+
+    // ==================== Transaction History Operations ====================
+
+    pub async fn upsert_transaction(&self, tx: &TransactionRow) -> Result<(), DatabaseError> {
+        let mut db_tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO transaction_history
+            (id, account_id, chain, signature, tx_type, from_address, to_address, amount, token_address, status, block_number, timestamp, created_at, fiat_amount, fiat_currency, fiat_rate, token_id, expected_changes, broadcast, memo, route_id, token_decimals)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(chain, signature) DO UPDATE SET
+                status = excluded.status,
+                block_number = excluded.block_number,
+                expected_changes = COALESCE(transaction_history.expected_changes, excluded.expected_changes),
+                broadcast = COALESCE(transaction_history.broadcast, excluded.broadcast),
+                route_id = COALESCE(transaction_history.route_id, excluded.route_id),
+                token_decimals = COALESCE(transaction_history.token_decimals, excluded.token_decimals)
+            "#,
+        )
+        .bind(&tx.id)
+        .bind(&tx.account_id)
+        .bind(&tx.chain)
+        .bind(&tx.signature)
+        .bind(&tx.tx_type)
+        .bind(&tx.from_address)
+        .bind(&tx.to_address)
+        .bind(&tx.amount)
+        .bind(&tx.token_address)
+        .bind(&tx.status)
+        .bind(tx.block_number)
+        .bind(&tx.timestamp)
+        .bind(&tx.created_at)
+        .bind(&tx.fiat_amount)
+        .bind(&tx.fiat_currency)
+        .bind(&tx.fiat_rate)
+        .bind(&tx.token_id)
+        .bind(&tx.expected_changes)
+        .bind(&tx.broadcast)
+        .bind(self.seal_opt(tx.memo.as_deref()))
+        .bind(&tx.route_id)
+        .bind(tx.token_decimals)
+        .execute(&mut *db_tx)
+        .await?;
+        self.queue_transaction_event(&mut db_tx, "transaction.recorded", &tx.chain, &tx.signature)
+            .await?;
+        db_tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_transactions(
+        &self,
+        account_id: &str,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            "SELECT * FROM transaction_history WHERE account_id = ? ORDER BY timestamp DESC LIMIT ? OFFSET ?",
+        )
+        .bind(account_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(self.reader())
+        .await?)
+    }
+
+    /// The latest `limit` transactions of each of `account_ids`, newest first
+    /// within an account
+    pub async fn get_recent_transactions(
+        &self,
+        account_ids: &[String],
+        limit: u32,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        if account_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sql = format!(
+            "SELECT * FROM (SELECT *, ROW_NUMBER() OVER \
+             (PARTITION BY account_id ORDER BY timestamp DESC) AS row_num \
+             FROM transaction_history WHERE account_id IN ({})) \
+             WHERE row_num <= ? ORDER BY account_id, row_num",
+            placeholders(account_ids.len())
+        );
+        let mut query = sqlx::query_as::<_, TransactionRow>(&sql);
+        for id in account_ids {
+            query = query.bind(id);
+        }
+        self.open_transactions(query.bind(limit).fetch_all(self.reader()).await?)
+    }
+
+    /// An account's transactions recorded at or after `since`, oldest first
+    pub async fn get_transactions_since(
+        &self,
+        account_id: &str,
+        since: &str,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            "SELECT * FROM transaction_history WHERE account_id = ? AND created_at >= ? ORDER BY created_at",
+        )
+        .bind(account_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Transactions ordered newest first by (timestamp, id), starting before the given position.
+    /// Rows without an on-chain timestamp sort by their insertion time.
+    pub async fn get_transactions_page(
+        &self,
+        account_id: &str,
+        before: Option<(&str, &str)>,
+        limit: u32,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        let query = match before {
+            Some(_) => {
+                r#"
+                SELECT * FROM transaction_history
+                WHERE account_id = ? AND (COALESCE(timestamp, created_at), id) < (?, ?)
+                ORDER BY COALESCE(timestamp, created_at) DESC, id DESC
+                LIMIT ?
+                "#
+            }
+            None => {
+                r#"
+                SELECT * FROM transaction_history
+                WHERE account_id = ?
+                ORDER BY COALESCE(timestamp, created_at) DESC, id DESC
+                LIMIT ?
+                "#
+            }
+        };
+
+        let mut q = sqlx::query_as::<_, TransactionRow>(query).bind(account_id);
+        if let Some((sort_key, id)) = before {
+            q = q.bind(sort_key).bind(id);
+        }
+
+        self.open_transactions(q.bind(limit).fetch_all(self.reader()).await?)
+    }
+
+    /// An account's recorded transactions, counting no further than `cap`
+    pub async fn count_transactions(
+        &self,
+        account_id: &str,
+        cap: Option<u32>,
+    ) -> Result<u64, DatabaseError> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM (SELECT 1 FROM transaction_history WHERE account_id = ? LIMIT ?)",
+        )
+        .bind(account_id)
+        .bind(cap.map_or(-1, i64::from))
+        .fetch_one(self.reader())
+        .await?;
+        Ok(count as u64)
+    }
+
+    pub async fn add_transaction_references(
+        &self,
+        transaction_id: &str,
+        references: &[String],
+    ) -> Result<(), DatabaseError> {
+        for reference in references {
+            sqlx::query(
+                "INSERT OR IGNORE INTO transaction_references (reference, transaction_id) VALUES (?, ?)",
+            )
+            .bind(reference)
+            .bind(transaction_id)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_transaction_references(
+        &self,
+        transaction_id: &str,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT reference FROM transaction_references WHERE transaction_id = ? ORDER BY rowid",
+        )
+        .bind(transaction_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(reference,)| reference).collect())
+    }
+
+    /// A tenant's transactions carrying `reference`, newest first
+    pub async fn get_transactions_by_reference(
+        &self,
+        tenant_id: &str,
+        reference: &str,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            r#"
+            SELECT t.* FROM transaction_history t
+            JOIN transaction_references r ON r.transaction_id = t.id
+            JOIN accounts a ON a.id = t.account_id
+            JOIN wallets w ON w.id = a.wallet_id
+            WHERE w.tenant_id = ? AND r.reference = ?
+            ORDER BY COALESCE(t.timestamp, t.created_at) DESC, t.id DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(reference)
+        .fetch_all(self.reader())
+        .await?)
+    }
+
+    /// Every transaction of an account, oldest first
+    pub async fn get_all_transactions(
+        &self,
+        account_id: &str,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            r#"
+            SELECT * FROM transaction_history
+            WHERE account_id = ?
+            ORDER BY COALESCE(timestamp, created_at), id
+            "#,
+        )
+        .bind(account_id)
+        .fetch_all(self.reader())
+        .await?)
+    }
+
+    // ==================== Alert Operations ====================
+
+    pub async fn create_alert(&self, alert: &AlertRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO alerts (id, user_id, account_id, kind, threshold, currency, webhook_url, is_active, triggered, history_cursor, last_triggered_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&alert.id)
+        .bind(&alert.user_id)
+        .bind(&alert.account_id)
+        .bind(&alert.kind)
+        .bind(&alert.threshold)
+        .bind(&alert.currency)
+        .bind(&alert.webhook_url)
+        .bind(alert.is_active)
+        .bind(alert.triggered)
+        .bind(alert.history_cursor)
+        .bind(&alert.last_triggered_at)
+        .bind(&alert.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_alerts(&self, user_id: &str) -> Result<Vec<AlertRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AlertRow>(
+            "SELECT * FROM alerts WHERE user_id = ? ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_alert(&self, id: &str) -> Result<AlertRow, DatabaseError> {
+        sqlx::query_as::<_, AlertRow>("SELECT * FROM alerts WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Every enabled alert, for the watcher
+    pub async fn get_active_alerts(&self) -> Result<Vec<AlertRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AlertRow>(
+            "SELECT * FROM alerts WHERE is_active = 1 ORDER BY account_id",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Replace an alert's rule; the trigger state is reset so the new rule is
+    /// evaluated from scratch
+    pub async fn update_alert(
+        &self,
+        id: &str,
+        threshold: &str,
+        currency: Option<&str>,
+        webhook_url: Option<&str>,
+        is_active: bool,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE alerts
+            SET threshold = ?, currency = ?, webhook_url = ?, is_active = ?, triggered = 0
+            WHERE id = ?
+            "#,
+        )
+        .bind(threshold)
+        .bind(currency)
+        .bind(webhook_url)
+        .bind(is_active)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the outcome of evaluating an alert
+    pub async fn set_alert_state(
+        &self,
+        id: &str,
+        triggered: bool,
+        history_cursor: i64,
+        last_triggered_at: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE alerts
+            SET triggered = ?, history_cursor = ?, last_triggered_at = COALESCE(?, last_triggered_at)
+            WHERE id = ?
+            "#,
+        )
+        .bind(triggered)
+        .bind(history_cursor)
+        .bind(last_triggered_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_alert(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM alerts WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // ==================== Price Alert Operations ====================
+
+    pub async fn create_price_alert(&self, alert: &PriceAlertRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO price_alerts (id, user_id, chain, token_address, symbol, kind, threshold, currency, window_hours, webhook_url, is_active, triggered, last_price, last_triggered_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&alert.id)
+        .bind(&alert.user_id)
+        .bind(&alert.chain)
+        .bind(&alert.token_address)
+        .bind(&alert.symbol)
+        .bind(&alert.kind)
+        .bind(&alert.threshold)
+        .bind(&alert.currency)
+        .bind(alert.window_hours)
+        .bind(&alert.webhook_url)
+        .bind(alert.is_active)
+        .bind(alert.triggered)
+        .bind(&alert.last_price)
+        .bind(&alert.last_triggered_at)
+        .bind(&alert.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_price_alerts(&self, user_id: &str) -> Result<Vec<PriceAlertRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, PriceAlertRow>(
+            "SELECT * FROM price_alerts WHERE user_id = ? ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_price_alert(&self, id: &str) -> Result<PriceAlertRow, DatabaseError> {
+        sqlx::query_as::<_, PriceAlertRow>("SELECT * FROM price_alerts WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Every enabled price alert, grouped by what it prices
+    pub async fn get_active_price_alerts(&self) -> Result<Vec<PriceAlertRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, PriceAlertRow>(
+            "SELECT * FROM price_alerts WHERE is_active = 1 ORDER BY chain, token_address, currency",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Replace a price alert's rule; the trigger state is reset so the new
+    /// rule is evaluated from scratch
+    pub async fn update_price_alert(
+        &self,
+        id: &str,
+        threshold: &str,
+        window_hours: Option<i64>,
+        webhook_url: Option<&str>,
+        is_active: bool,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE price_alerts
+            SET threshold = ?, window_hours = ?, webhook_url = ?, is_active = ?, triggered = 0
+            WHERE id = ?
+            "#,
+        )
+        .bind(threshold)
+        .bind(window_hours)
+        .bind(webhook_url)
+        .bind(is_active)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the outcome of evaluating a price alert
+    pub async fn set_price_alert_state(
+        &self,
+        id: &str,
+        triggered: bool,
+        last_price: &str,
+        last_triggered_at: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE price_alerts
+            SET triggered = ?, last_price = ?, last_triggered_at = COALESCE(?, last_triggered_at)
+            WHERE id = ?
+            "#,
+        )
+        .bind(triggered)
+        .bind(last_price)
+        .bind(last_triggered_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_price_alert(&self, id: &str) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM price_alerts WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn insert_price_sample(
+        &self,
+        chain: &str,
+        token_address: &str,
+        currency: &str,
+        price: f64,
+        sampled_at: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO price_samples (chain, token_address, currency, price, sampled_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(chain)
+        .bind(token_address)
+        .bind(currency)
+        .bind(price)
+        .bind(sampled_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Earliest price sampled at or after `since`, with when it was sampled
+    pub async fn get_price_sample_since(
+        &self,
+        chain: &str,
+        token_address: &str,
+        currency: &str,
+        since: &str,
+    ) -> Result<Option<(f64, String)>, DatabaseError> {
+        Ok(sqlx::query_as(
+            r#"
+            SELECT price, sampled_at FROM price_samples
+            WHERE chain = ? AND token_address = ? AND currency = ? AND sampled_at >= ?
+            ORDER BY sampled_at
+            LIMIT 1
+            "#,
+        )
+        .bind(chain)
+        .bind(token_address)
+        .bind(currency)
+        .bind(since)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Delete price samples taken before `before`; how many were deleted
+    pub async fn prune_price_samples(&self, before: &str) -> Result<u64, DatabaseError> {
+        let result = sqlx::query("DELETE FROM price_samples WHERE sampled_at < ?")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Transactions with simulated effects that haven't been checked against
+    /// the chain yet, recorded at or after `since`, oldest first
+    pub async fn get_unreconciled_transactions(
+        &self,
+        since: &str,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            r#"
+            SELECT * FROM transaction_history
+            WHERE expected_changes IS NOT NULL AND actual_changes IS NULL AND created_at >= ?
+            ORDER BY created_at
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Record how a transaction settled and whether that matched its
+    /// simulation, with its receipt on Ethereum and the fee it paid
+    #[allow(clippy::too_many_arguments)]
+    pub async fn set_transaction_effects(
+        &self,
+        id: &str,
+        status: &str,
+        block_number: Option<i64>,
+        confirmations: i64,
+        actual_changes: &str,
+        mismatch: bool,
+        receipt: Option<&TxReceipt>,
+        fee_paid: &str,
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE transaction_history
+            SET status = ?, block_number = COALESCE(?, block_number), confirmations = ?,
+                actual_changes = ?, effects_mismatch = ?, gas_used = ?,
+                effective_gas_price = ?, receipt_status = ?, logs_count = ?,
+                contract_address = ?, fee_paid = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(status)
+        .bind(block_number)
+        .bind(confirmations)
+        .bind(actual_changes)
+        .bind(mismatch)
+        .bind(receipt.map(|r| r.gas_used.min(i64::MAX as u64) as i64))
+        .bind(receipt.map(|r| r.effective_gas_price.as_str()))
+        .bind(receipt.map(|r| r.succeeded as i64))
+        .bind(receipt.map(|r| r.logs_count.min(i64::MAX as u64) as i64))
+        .bind(receipt.and_then(|r| r.contract_address.as_deref()))
+        .bind(fee_paid)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        if let Some((chain, signature)) =
+            sqlx::query_as::<_, (String, String)>("SELECT chain, signature FROM transaction_history WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+        {
+            self.queue_transaction_event(&mut tx, "transaction.settled", &chain, &signature)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record the progress of a landed transaction that is still short of
+    /// its confirmation threshold
+    pub async fn set_transaction_confirmations(
+        &self,
+        id: &str,
+        block_number: Option<i64>,
+        confirmations: i64,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE transaction_history
+            SET block_number = COALESCE(?, block_number), confirmations = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(block_number)
+        .bind(confirmations)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_transaction(&self, id: &str) -> Result<TransactionRow, DatabaseError> {
+        sqlx::query_as::<_, TransactionRow>("SELECT * FROM transaction_history WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+            .and_then(|tx| self.open_transaction(tx))
+    }
+
+    /// Pending sends with broadcast details that aren't yet flagged as stuck
+    pub async fn get_pending_broadcasts(
+        &self,
+        since: &str,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            r#"
+            SELECT * FROM transaction_history
+            WHERE status = 'pending' AND broadcast IS NOT NULL AND replaced_by IS NULL
+                AND stuck_at IS NULL AND created_at >= ?
+            ORDER BY created_at
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn mark_transaction_stuck(&self, id: &str, stuck_at: &str) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE transaction_history SET stuck_at = ? WHERE id = ?")
+            .bind(stuck_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Replace where a pending send was broadcast, after it is rebroadcast
+    pub async fn set_transaction_broadcast(
+        &self,
+        id: &str,
+        broadcast: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE transaction_history SET broadcast = ? WHERE id = ?")
+            .bind(broadcast)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// A tenant's stuck sends that haven't been replaced, oldest first
+    pub async fn get_stuck_transactions(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            r#"
+            SELECT t.* FROM transaction_history t
+            JOIN accounts a ON a.id = t.account_id
+            JOIN wallets w ON w.id = a.wallet_id
+            WHERE w.tenant_id = ? AND t.status = 'pending' AND t.stuck_at IS NOT NULL
+                AND t.replaced_by IS NULL
+            ORDER BY t.created_at
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Record the speed-up sent in place of a stuck transaction. The original
+    /// is marked failed; if it lands after all, reconciliation corrects that.
+    pub async fn mark_transaction_replaced(
+        &self,
+        id: &str,
+        replaced_by: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "UPDATE transaction_history SET status = 'failed', replaced_by = ? WHERE id = ?",
+        )
+        .bind(replaced_by)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Position of the newest transaction history row
+    pub async fn get_transaction_history_seq(&self) -> Result<i64, DatabaseError> {
+        let seq: (Option<i64>,) = sqlx::query_as("SELECT MAX(rowid) FROM transaction_history")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(seq.0.unwrap_or(0))
+    }
+
+    /// Sends from an account recorded after position `after`, up to and
+    /// including `up_to`, oldest first
+    pub async fn get_outflows(
+        &self,
+        account_id: &str,
+        after: i64,
+        up_to: i64,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            r#"
+            SELECT * FROM transaction_history
+            WHERE account_id = ? AND tx_type = 'send' AND status != 'failed'
+              AND rowid > ? AND rowid <= ?
+            ORDER BY rowid
+            "#,
+        )
+        .bind(account_id)
+        .bind(after)
+        .bind(up_to)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn upsert_balance_snapshot(
+        &self,
+        account_id: &str,
+        balance: &str,
+        taken_at: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO balance_snapshots (account_id, balance, taken_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(account_id) DO UPDATE SET
+                balance = excluded.balance,
+                taken_at = excluded.taken_at
+            "#,
+        )
+        .bind(account_id)
+        .bind(balance)
+        .bind(taken_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn create_alert_notification(
+        &self,
+        notification: &AlertNotificationRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO alert_notifications (id, alert_id, price_alert_id, user_id, message, balance, price, tx_signature, webhook_status, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&notification.id)
+        .bind(&notification.alert_id)
+        .bind(&notification.price_alert_id)
+        .bind(&notification.user_id)
+        .bind(&notification.message)
+        .bind(&notification.balance)
+        .bind(&notification.price)
+        .bind(&notification.tx_signature)
+        .bind(&notification.webhook_status)
+        .bind(&notification.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// A user's most recent notifications, newest first
+    pub async fn get_alert_notifications(
+        &self,
+        user_id: &str,
+        limit: u32,
+    ) -> Result<Vec<AlertNotificationRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AlertNotificationRow>(
+            "SELECT * FROM alert_notifications WHERE user_id = ? ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// A user's notifications newest first by (created_at, id), starting
+    /// before the given position
+    pub async fn get_alert_notifications_page(
+        &self,
+        user_id: &str,
+        before: Option<(&str, &str)>,
+        limit: u32,
+    ) -> Result<Vec<AlertNotificationRow>, DatabaseError> {
+        let query = match before {
+            Some(_) => {
+                r#"
+                SELECT * FROM alert_notifications
+                WHERE user_id = ? AND (created_at, id) < (?, ?)
+                ORDER BY created_at DESC, id DESC
+                LIMIT ?
+                "#
+            }
+            None => {
+                r#"
+                SELECT * FROM alert_notifications
+                WHERE user_id = ?
+                ORDER BY created_at DESC, id DESC
+                LIMIT ?
+                "#
+            }
+        };
+
+        let mut q = sqlx::query_as::<_, AlertNotificationRow>(query).bind(user_id);
+        if let Some((created_at, id)) = before {
+            q = q.bind(created_at).bind(id);
+        }
+        Ok(q.bind(limit).fetch_all(&self.pool).await?)
+    }
+
+    /// A user's notifications, counting no further than `cap`
+    pub async fn count_alert_notifications(
+        &self,
+        user_id: &str,
+        cap: Option<u32>,
+    ) -> Result<u64, DatabaseError> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM (SELECT 1 FROM alert_notifications WHERE user_id = ? LIMIT ?)",
+        )
+        .bind(user_id)
+        .bind(cap.map_or(-1, i64::from))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as u64)
+    }
+
+    // ==================== Historical Price Operations ====================
+
+    /// Transactions whose historical price hasn't been looked up, oldest first
+    pub async fn get_unpriced_transactions(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<TransactionRow>, DatabaseError> {
+        self.open_transactions(sqlx::query_as::<_, TransactionRow>(
+            "SELECT * FROM transaction_history WHERE priced_at IS NULL ORDER BY created_at LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Record the looked-up price; `None` when the feed had none
+    pub async fn set_transaction_price(
+        &self,
+        id: &str,
+        price: Option<&str>,
+        currency: &str,
+        priced_at: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "UPDATE transaction_history SET price_at_tx = ?, price_currency = ?, priced_at = ? WHERE id = ?",
+        )
+        .bind(price)
+        .bind(currency)
+        .bind(priced_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Cached daily price: `None` if never fetched, `Some(None)` if the feed
+    /// had no price that day
+    pub async fn get_historical_price(
+        &self,
+        chain: &str,
+        token_address: &str,
+        currency: &str,
+        date: &str,
+    ) -> Result<Option<Option<f64>>, DatabaseError> {
+        let row: Option<(Option<f64>,)> = sqlx::query_as(
+            r#"
+            SELECT price FROM historical_prices
+            WHERE chain = ? AND token_address = ? AND currency = ? AND date = ?
+            "#,
+        )
+        .bind(chain)
+        .bind(token_address)
+        .bind(currency)
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|(price,)| price))
+    }
+
+    pub async fn upsert_historical_price(
+        &self,
+        chain: &str,
+        token_address: &str,
+        currency: &str,
+        date: &str,
+        price: Option<f64>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO historical_prices (chain, token_address, currency, date, price, fetched_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(chain, token_address, currency, date) DO UPDATE SET
+                price = excluded.price,
+                fetched_at = excluded.fetched_at
+            "#,
+        )
+        .bind(chain)
+        .bind(token_address)
+        .bind(currency)
+        .bind(date)
+        .bind(price)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ==================== Transaction Tag Operations ====================
+
+    pub async fn get_transaction_tags(
+        &self,
+        transaction_id: &str,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT tag FROM transaction_tags WHERE transaction_id = ? ORDER BY tag",
+        )
+        .bind(transaction_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|(tag,)| tag).collect())
+    }
+
+    /// Replace a transaction's tags
+    pub async fn set_transaction_tags(
+        &self,
+        transaction_id: &str,
+        tags: &[String],
+    ) -> Result<(), DatabaseError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM transaction_tags WHERE transaction_id = ?")
+            .bind(transaction_id)
+            .execute(&mut *tx)
+            .await?;
+        for tag in tags {
+            sqlx::query("INSERT INTO transaction_tags (transaction_id, tag) VALUES (?, ?)")
+                .bind(transaction_id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Contacts and accounts of a wallet on `chain`, contacts first
+    pub async fn get_address_labels(
+        &self,
+        wallet_id: &str,
+        chain: &str,
+    ) -> Result<Vec<AddressLabel>, DatabaseError> {
+        let labels = sqlx::query_as::<_, AddressLabel>(
+            r#"
+            SELECT LOWER(address) AS address, name, FALSE AS is_own
+            FROM contacts WHERE wallet_id = ? AND chain = ?
+            UNION ALL
+            SELECT LOWER(address), name, TRUE
+            FROM accounts WHERE wallet_id = ? AND chain = ?
+            "#,
+        )
+        .bind(wallet_id)
+        .bind(chain)
+        .bind(wallet_id)
+        .bind(chain)
+        .fetch_all(self.reader())
+        .await?;
+        labels
+            .into_iter()
+            .map(|mut label| {
+                label.name = self.open(label.name)?;
+                Ok(label)
+            })
+            .collect()
+    }
+
+    /// A tenant's outflows between `since` and `until` (exclusive), one row
+    /// per send and bucket, ordered by bucket and token. Sends to another of
+    /// the wallet's accounts are internal and left out. Realized values only
+    /// count rows priced in `currency`.
+    pub async fn get_spending(
+        &self,
+        tenant_id: &str,
+        group: SpendingGroup,
+        currency: &str,
+        since: &str,
+        until: &str,
+    ) -> Result<Vec<SpendingRow>, DatabaseError> {
+        let (bucket, name, join) = match group {
+            SpendingGroup::Month => (
+                "substr(COALESCE(t.timestamp, t.created_at), 1, 7)",
+                "NULL",
+                "",
+            ),
+            SpendingGroup::Tag => (
+                "g.tag",
+                "NULL",
+                "LEFT JOIN transaction_tags g ON g.transaction_id = t.id",
+            ),
+            SpendingGroup::Counterparty => (
+                "t.to_address",
+                "(SELECT MAX(c.name) FROM contacts c WHERE c.wallet_id = w.id AND c.chain = t.chain \
+                 AND LOWER(c.address) = LOWER(t.to_address))",
+                "",
+            ),
+        };
+
+        let query = format!(
+            r#"
+            SELECT {bucket} AS bucket, {name} AS name, t.chain, t.token_address, t.amount,
+                CASE WHEN t.price_currency = ?
+                    THEN CAST(t.amount AS REAL) * CAST(t.price_at_tx AS REAL) END AS fiat
+            FROM transaction_history t
+            JOIN accounts a ON a.id = t.account_id
+            JOIN wallets w ON w.id = a.wallet_id
+            {join}
+            WHERE w.tenant_id = ? AND t.tx_type = 'send' AND t.status != 'failed'
+                AND t.amount IS NOT NULL AND t.token_id IS NULL
+                AND COALESCE(t.timestamp, t.created_at) >= ?
+                AND COALESCE(t.timestamp, t.created_at) < ?
+                AND NOT EXISTS (
+                    SELECT 1 FROM accounts o
+                    WHERE o.wallet_id = a.wallet_id AND o.chain = t.chain
+                        AND LOWER(o.address) = LOWER(t.to_address)
+                )
+            ORDER BY bucket, t.chain, t.token_address
+            "#
+        );
+
+        let rows = sqlx::query_as::<_, SpendingRow>(&query)
+            .bind(currency)
+            .bind(tenant_id)
+            .bind(since)
+            .bind(until)
+            .fetch_all(self.reader())
+            .await?;
+        rows.into_iter()
+            .map(|mut row| {
+                row.name = self.open_opt(row.name)?;
+                Ok(row)
+            })
+            .collect()
+    }
+
+    /// Fees of a tenant's sends with timestamps in `[since, until)`,
+    /// including failed ones, which pay for their gas too
+    pub async fn get_fees_paid(
+        &self,
+        tenant_id: &str,
+        since: &str,
+        until: &str,
+    ) -> Result<Vec<FeeRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, FeeRow>(
+            r#"
+            SELECT t.chain, t.fee_paid
+            FROM transaction_history t
+            JOIN accounts a ON a.id = t.account_id
+            JOIN wallets w ON w.id = a.wallet_id
+            WHERE w.tenant_id = ? AND t.tx_type = 'send'
+                AND COALESCE(t.timestamp, t.created_at) >= ?
+                AND COALESCE(t.timestamp, t.created_at) < ?
+            ORDER BY t.chain
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(since)
+        .bind(until)
+        .fetch_all(self.reader())
+        .await?)
+    }
+
+    // ==================== Sync Blob Operations ====================
+
+    pub async fn get_sync_blob(&self, user_id: &str) -> Result<SyncBlobRow, DatabaseError> {
+        sqlx::query_as::<_, SyncBlobRow>("SELECT * FROM sync_blobs WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Store a user's blob if it is still at `expected_version` (`None`: if
+    /// the user has none yet). Returns the new version, or `None` when the
+    /// stored blob has moved on.
+    pub async fn put_sync_blob(
+        &self,
+        user_id: &str,
+        expected_version: Option<i64>,
+        data: &[u8],
+        updated_at: &str,
+    ) -> Result<Option<i64>, DatabaseError> {
+        let result = match expected_version {
+            Some(version) => {
+                sqlx::query(
+                    r#"
+                    UPDATE sync_blobs SET version = version + 1, data = ?, updated_at = ?
+                    WHERE user_id = ? AND version = ?
+                    "#,
+                )
+                .bind(data)
+                .bind(updated_at)
+                .bind(user_id)
+                .bind(version)
+                .execute(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO sync_blobs (user_id, version, data, updated_at) VALUES (?, 1, ?, ?)
+                    ON CONFLICT(user_id) DO NOTHING
+                    "#,
+                )
+                .bind(user_id)
+                .bind(data)
+                .bind(updated_at)
+                .execute(&self.pool)
+                .await?
+            }
+        };
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(expected_version.unwrap_or(0) + 1))
+    }
+
+    /// Delete a user's blob if it is at `version`; `false` if it wasn't
+    pub async fn delete_sync_blob(&self, user_id: &str, version: i64) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM sync_blobs WHERE user_id = ? AND version = ?")
+            .bind(user_id)
+            .bind(version)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ==================== Large Transfer Operations ====================
+
+    pub async fn get_large_transfer_threshold(
+        &self,
+        user_id: &str,
+    ) -> Result<Option<LargeTransferThresholdRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, LargeTransferThresholdRow>(
+            "SELECT * FROM large_transfer_thresholds WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn set_large_transfer_threshold(
+        &self,
+        threshold: &LargeTransferThresholdRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO large_transfer_thresholds (user_id, threshold, currency, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                threshold = excluded.threshold,
+                currency = excluded.currency,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&threshold.user_id)
+        .bind(&threshold.threshold)
+        .bind(&threshold.currency)
+        .bind(&threshold.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_large_transfer_threshold(
+        &self,
+        user_id: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("DELETE FROM large_transfer_thresholds WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Hold a send for confirmation; the request may carry a memo, so it is
+    /// sealed like one
+    pub async fn create_send_challenge(
+        &self,
+        challenge: &SendChallengeRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO send_challenges (id, user_id, request, attempts, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&challenge.id)
+        .bind(&challenge.user_id)
+        .bind(self.seal(&challenge.request))
+        .bind(challenge.attempts)
+        .bind(&challenge.expires_at)
+        .bind(&challenge.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_send_challenge(&self, id: &str) -> Result<SendChallengeRow, DatabaseError> {
+        let mut challenge =
+            sqlx::query_as::<_, SendChallengeRow>("SELECT * FROM send_challenges WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or(DatabaseError::NotFound)?;
+        challenge.request = self.open(challenge.request)?;
+        Ok(challenge)
+    }
+
+    /// Count a wrong password against a challenge; returns the new count
+    pub async fn record_send_challenge_attempt(&self, id: &str) -> Result<i64, DatabaseError> {
+        let attempts: Option<i64> = sqlx::query_scalar(
+            "UPDATE send_challenges SET attempts = attempts + 1 WHERE id = ? RETURNING attempts",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        attempts.ok_or(DatabaseError::NotFound)
+    }
+
+    /// Delete a challenge. Returns `false` if it was already gone, so only
+    /// one confirmation can use it.
+    pub async fn take_send_challenge(&self, id: &str) -> Result<bool, DatabaseError> {
+        let result = sqlx::query("DELETE FROM send_challenges WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_expired_send_challenges(&self, now: &str) -> Result<u64, DatabaseError> {
+        let result = sqlx::query("DELETE FROM send_challenges WHERE expires_at <= ?")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    // ==================== Session Key Operations ====================
+
+    pub async fn create_session_key(&self, session: &SessionKeyRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO session_keys (id, user_id, account_id, token_address, max_amount, allowed_targets, encrypted_seed, expires_at, revoked_at, last_used_at, use_count, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&session.id)
+        .bind(&session.user_id)
+        .bind(&session.account_id)
+        .bind(&session.token_address)
+        .bind(&session.max_amount)
+        .bind(&session.allowed_targets)
+        .bind(&session.encrypted_seed)
+        .bind(&session.expires_at)
+        .bind(&session.revoked_at)
+        .bind(&session.last_used_at)
+        .bind(session.use_count)
+        .bind(&session.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_session_keys(&self, user_id: &str) -> Result<Vec<SessionKeyRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, SessionKeyRow>(
+            "SELECT * FROM session_keys WHERE user_id = ? ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn get_session_key(&self, id: &str) -> Result<SessionKeyRow, DatabaseError> {
+        sqlx::query_as::<_, SessionKeyRow>("SELECT * FROM session_keys WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Revoke a session key and discard its encrypted seed
+    pub async fn revoke_session_key(&self, id: &str, revoked_at: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "UPDATE session_keys SET encrypted_seed = NULL, revoked_at = COALESCE(revoked_at, ?) WHERE id = ?",
+        )
+        .bind(revoked_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn record_session_key_use(&self, id: &str, used_at: &str) -> Result<(), DatabaseError> {
+        sqlx::query(
+            "UPDATE session_keys SET last_used_at = ?, use_count = use_count + 1 WHERE id = ?",
+        )
+        .bind(used_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ==================== Owned Name Operations ====================
+
+    /// Insert a name, or update the row already held for it on that chain
+    /// (an ENS commitment becoming a registration)
+    pub async fn save_owned_name(&self, name: &OwnedNameRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO owned_names (id, account_id, chain, name, status, target_address, years, commitment_secret, ready_at, expires_at, last_tx_hash, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(chain, name) DO UPDATE SET
+                account_id = excluded.account_id,
+                status = excluded.status,
+                target_address = excluded.target_address,
+                years = excluded.years,
+                commitment_secret = excluded.commitment_secret,
+                ready_at = excluded.ready_at,
+                expires_at = excluded.expires_at,
+                last_tx_hash = excluded.last_tx_hash,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&name.id)
+        .bind(&name.account_id)
+        .bind(&name.chain)
+        .bind(&name.name)
+        .bind(&name.status)
+        .bind(&name.target_address)
+        .bind(name.years)
+        .bind(&name.commitment_secret)
+        .bind(&name.ready_at)
+        .bind(&name.expires_at)
+        .bind(&name.last_tx_hash)
+        .bind(&name.created_at)
+        .bind(&name.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_owned_name(&self, id: &str) -> Result<OwnedNameRow, DatabaseError> {
+        sqlx::query_as::<_, OwnedNameRow>("SELECT * FROM owned_names WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    pub async fn get_owned_name_by_name(
+        &self,
+        chain: &str,
+        name: &str,
+    ) -> Result<Option<OwnedNameRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, OwnedNameRow>(
+            "SELECT * FROM owned_names WHERE chain = ? AND name = ?",
+        )
+        .bind(chain)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// A tenant's names, alphabetically
+    pub async fn get_owned_names(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<OwnedNameRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, OwnedNameRow>(&format!(
+            "SELECT * FROM owned_names WHERE account_id IN ({}) ORDER BY name, chain",
+            TENANT_ACCOUNTS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    // ==================== Bridge Transfer Operations ====================
+
+    pub async fn create_bridge_transfer(
+        &self,
+        transfer: &BridgeTransferRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO bridge_transfers (id, account_id, network, l1_chain_id, l1_tx_hash, bridge_contract, address, amount, status, l1_block_number, confirmed_at, finalizes_at, finalized_at, webhook_url, webhook_status, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&transfer.id)
+        .bind(&transfer.account_id)
+        .bind(&transfer.network)
+        .bind(transfer.l1_chain_id)
+        .bind(&transfer.l1_tx_hash)
+        .bind(&transfer.bridge_contract)
+        .bind(&transfer.address)
+        .bind(&transfer.amount)
+        .bind(&transfer.status)
+        .bind(transfer.l1_block_number)
+        .bind(&transfer.confirmed_at)
+        .bind(&transfer.finalizes_at)
+        .bind(&transfer.finalized_at)
+        .bind(&transfer.webhook_url)
+        .bind(&transfer.webhook_status)
+        .bind(&transfer.created_at)
+        .bind(&transfer.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_bridge_transfer(&self, id: &str) -> Result<BridgeTransferRow, DatabaseError> {
+        sqlx::query_as::<_, BridgeTransferRow>("SELECT * FROM bridge_transfers WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// A tenant's deposits, newest first, optionally of one account
+    pub async fn get_bridge_transfers(
+        &self,
+        tenant_id: &str,
+        account_id: Option<&str>,
+    ) -> Result<Vec<BridgeTransferRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, BridgeTransferRow>(&format!(
+            r#"
+            SELECT * FROM bridge_transfers
+            WHERE account_id IN ({}) AND (? IS NULL OR account_id = ?)
+            ORDER BY created_at DESC
+            "#,
+            TENANT_ACCOUNTS
+        ))
+        .bind(tenant_id)
+        .bind(account_id)
+        .bind(account_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Deposits not yet finalized or failed, oldest first
+    pub async fn get_open_bridge_transfers(&self) -> Result<Vec<BridgeTransferRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, BridgeTransferRow>(
+            "SELECT * FROM bridge_transfers WHERE status IN ('submitted', 'confirmed') ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Record a deposit landing on L1, and when it should be credited on L2
+    pub async fn confirm_bridge_transfer(
+        &self,
+        id: &str,
+        l1_block_number: Option<i64>,
+        confirmed_at: &str,
+        finalizes_at: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE bridge_transfers
+            SET status = 'confirmed', l1_block_number = ?, confirmed_at = ?, finalizes_at = ?, updated_at = ?
+            WHERE id = ? AND status = 'submitted'
+            "#,
+        )
+        .bind(l1_block_number)
+        .bind(confirmed_at)
+        .bind(finalizes_at)
+        .bind(confirmed_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Close an open deposit as `finalized` or `failed`, with how its
+    /// notification was delivered
+    pub async fn finish_bridge_transfer(
+        &self,
+        id: &str,
+        status: &str,
+        l1_block_number: Option<i64>,
+        finished_at: &str,
+        webhook_status: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE bridge_transfers
+            SET status = ?, l1_block_number = COALESCE(?, l1_block_number),
+                finalized_at = CASE WHEN ? = 'finalized' THEN ? ELSE finalized_at END,
+                webhook_status = ?, updated_at = ?
+            WHERE id = ? AND status IN ('submitted', 'confirmed')
+            "#,
+        )
+        .bind(status)
+        .bind(l1_block_number)
+        .bind(status)
+        .bind(finished_at)
+        .bind(webhook_status)
+        .bind(finished_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ==================== Cross-Chain Swap Operations ====================
+
+    pub async fn create_cross_chain_swap(
+        &self,
+        swap: &CrossChainSwapRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO cross_chain_swaps (id, from_account_id, to_account_id, from_chain, to_chain, from_token, to_token, from_amount, quoted_amount, min_amount, received_amount, tool, estimated_duration_secs, status, status_message, approval_tx_hash, source_tx_hash, destination_tx_hash, completed_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&swap.id)
+        .bind(&swap.from_account_id)
+        .bind(&swap.to_account_id)
+        .bind(&swap.from_chain)
+        .bind(&swap.to_chain)
+        .bind(&swap.from_token)
+        .bind(&swap.to_token)
+        .bind(&swap.from_amount)
+        .bind(&swap.quoted_amount)
+        .bind(&swap.min_amount)
+        .bind(&swap.received_amount)
+        .bind(&swap.tool)
+        .bind(swap.estimated_duration_secs)
+        .bind(&swap.status)
+        .bind(&swap.status_message)
+        .bind(&swap.approval_tx_hash)
+        .bind(&swap.source_tx_hash)
+        .bind(&swap.destination_tx_hash)
+        .bind(&swap.completed_at)
+        .bind(&swap.created_at)
+        .bind(&swap.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_cross_chain_swap(&self, id: &str) -> Result<CrossChainSwapRow, DatabaseError> {
+        sqlx::query_as::<_, CrossChainSwapRow>("SELECT * FROM cross_chain_swaps WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// A tenant's swaps, newest first, optionally those leaving or reaching
+    /// one account
+    pub async fn get_cross_chain_swaps(
+        &self,
+        tenant_id: &str,
+        account_id: Option<&str>,
+    ) -> Result<Vec<CrossChainSwapRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, CrossChainSwapRow>(&format!(
+            r#"
+            SELECT * FROM cross_chain_swaps
+            WHERE from_account_id IN ({})
+              AND (? IS NULL OR from_account_id = ? OR to_account_id = ?)
+            ORDER BY created_at DESC
+            "#,
+            TENANT_ACCOUNTS
+        ))
+        .bind(tenant_id)
+        .bind(account_id)
+        .bind(account_id)
+        .bind(account_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Swaps still on their way, oldest first
+    pub async fn get_open_cross_chain_swaps(&self) -> Result<Vec<CrossChainSwapRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, CrossChainSwapRow>(
+            "SELECT * FROM cross_chain_swaps WHERE status IN ('submitted', 'bridging') ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Move an open swap to `status` (still open) with the aggregator's
+    /// latest message
+    pub async fn update_cross_chain_swap(
+        &self,
+        id: &str,
+        status: &str,
+        status_message: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE cross_chain_swaps
+            SET status = ?, status_message = COALESCE(?, status_message), updated_at = ?
+            WHERE id = ? AND status IN ('submitted', 'bridging')
+            "#,
+        )
+        .bind(status)
+        .bind(status_message)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Close an open swap as `completed`, `refunded` or `failed`
+    pub async fn finish_cross_chain_swap(
+        &self,
+        id: &str,
+        status: &str,
+        status_message: Option<&str>,
+        destination_tx_hash: Option<&str>,
+        received_amount: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            UPDATE cross_chain_swaps
+            SET status = ?, status_message = COALESCE(?, status_message),
+                destination_tx_hash = COALESCE(?, destination_tx_hash),
+                received_amount = COALESCE(?, received_amount),
+                completed_at = ?, updated_at = ?
+            WHERE id = ? AND status IN ('submitted', 'bridging')
+            "#,
+        )
+        .bind(status)
+        .bind(status_message)
+        .bind(destination_tx_hash)
+        .bind(received_amount)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ==================== Cold Transaction Operations ====================
+
+    pub async fn create_cold_transaction(
+        &self,
+        cold: &ColdTransactionRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO cold_transactions (id, user_id, tenant_id, chain, from_address, to_address, amount, fee, payload, signing_hash, status, tx_hash, created_at, broadcast_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&cold.id)
+        .bind(&cold.user_id)
+        .bind(&cold.tenant_id)
+        .bind(&cold.chain)
+        .bind(&cold.from_address)
+        .bind(&cold.to_address)
+        .bind(&cold.amount)
+        .bind(&cold.fee)
+        .bind(&cold.payload)
+        .bind(&cold.signing_hash)
+        .bind(&cold.status)
+        .bind(&cold.tx_hash)
+        .bind(&cold.created_at)
+        .bind(&cold.broadcast_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_cold_transaction(&self, id: &str) -> Result<ColdTransactionRow, DatabaseError> {
+        sqlx::query_as::<_, ColdTransactionRow>("SELECT * FROM cold_transactions WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// A user's cold transfers, newest first
+    pub async fn get_cold_transactions(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<ColdTransactionRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, ColdTransactionRow>(
+            "SELECT * FROM cold_transactions WHERE user_id = ? ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Record the broadcast of an exported transfer. Returns `false` when it
+    /// had already been broadcast.
+    pub async fn mark_cold_transaction_broadcast(
+        &self,
+        id: &str,
+        tx_hash: &str,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE cold_transactions
+            SET status = 'broadcast', tx_hash = ?, broadcast_at = ?
+            WHERE id = ? AND status = 'exported'
+            "#,
+        )
+        .bind(tx_hash)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ==================== Scheduled Transaction Operations ====================
+
+    pub async fn create_scheduled_transaction(
+        &self,
+        scheduled: &ScheduledTransactionRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO scheduled_transactions (id, account_id, chain, to_address, amount, token_address, memo, reference_keys, execute_after, expires_at, status, tx_hash, last_error, executed_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&scheduled.id)
+        .bind(&scheduled.account_id)
+        .bind(&scheduled.chain)
+        .bind(&scheduled.to_address)
+        .bind(&scheduled.amount)
+        .bind(&scheduled.token_address)
+        .bind(self.seal_opt(scheduled.memo.as_deref()))
+        .bind(&scheduled.reference_keys)
+        .bind(&scheduled.execute_after)
+        .bind(&scheduled.expires_at)
+        .bind(&scheduled.status)
+        .bind(&scheduled.tx_hash)
+        .bind(&scheduled.last_error)
+        .bind(&scheduled.executed_at)
+        .bind(&scheduled.created_at)
+        .bind(&scheduled.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_scheduled_transaction(
+        &self,
+        id: &str,
+    ) -> Result<ScheduledTransactionRow, DatabaseError> {
+        let scheduled = sqlx::query_as::<_, ScheduledTransactionRow>(
+            "SELECT * FROM scheduled_transactions WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DatabaseError::NotFound)?;
+        self.open_scheduled(scheduled)
+    }
+
+    /// A tenant's scheduled sends, soonest first
+    pub async fn get_scheduled_transactions(
+        &self,
+        tenant_id: &str,
+    ) -> Result<Vec<ScheduledTransactionRow>, DatabaseError> {
+        sqlx::query_as::<_, ScheduledTransactionRow>(&format!(
+            "SELECT * FROM scheduled_transactions WHERE account_id IN ({}) \
+             ORDER BY execute_after, created_at",
+            TENANT_ACCOUNTS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|scheduled| self.open_scheduled(scheduled))
+        .collect()
+    }
+
+    /// Scheduled sends of every tenant that are due at `now`
+    pub async fn get_due_scheduled_transactions(
+        &self,
+        now: &str,
+    ) -> Result<Vec<ScheduledTransactionRow>, DatabaseError> {
+        sqlx::query_as::<_, ScheduledTransactionRow>(
+            "SELECT * FROM scheduled_transactions WHERE status = 'scheduled' \
+             AND execute_after <= ? ORDER BY execute_after",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|scheduled| self.open_scheduled(scheduled))
+        .collect()
+    }
+
+    /// Move a scheduled send on from `scheduled`. Returns `false` if it was
+    /// no longer scheduled, e.g. cancelled or claimed by another pass.
+    pub async fn transition_scheduled_transaction(
+        &self,
+        id: &str,
+        status: &str,
+        updated_at: &str,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE scheduled_transactions SET status = ?, updated_at = ? \
+             WHERE id = ? AND status = 'scheduled'",
+        )
+        .bind(status)
+        .bind(updated_at)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
 
-    pub async fn upsert_transaction(&self, tx: &TransactionRow) -> Result<(), DatabaseError> {
+    /// Record the outcome of a claimed send: `sent` with its hash, `failed`,
+    /// or back to `scheduled` to be retried
+    pub async fn finish_scheduled_transaction(
+        &self,
+        id: &str,
+        status: &str,
+        tx_hash: Option<&str>,
+        last_error: Option<&str>,
+        updated_at: &str,
+    ) -> Result<(), DatabaseError> {
+        let executed_at = tx_hash.map(|_| updated_at);
         sqlx::query(
-            r#"
-            INSERT INTO transaction_history
-            (id, account_id, chain, signature, tx_type, from_address, to_address, amount, token_address, status, block_number, timestamp, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(chain, signature) DO UPDATE SET
-                status = excluded.status,
-                block_number = excluded.block_number
-            "#,
+            "UPDATE scheduled_transactions SET status = ?, tx_hash = ?, last_error = ?, \
+             executed_at = COALESCE(?, executed_at), updated_at = ? \
+             WHERE id = ? AND status = 'executing'",
         )
-        .bind(&tx.id)
-        .bind(&tx.account_id)
-        .bind(&tx.chain)
-        .bind(&tx.signature)
-        .bind(&tx.tx_type)
-        .bind(&tx.from_address)
-        .bind(&tx.to_address)
-        .bind(&tx.amount)
-        .bind(&tx.token_address)
-        .bind(&tx.status)
-        .bind(tx.block_number)
-        .bind(&tx.timestamp)
-        .bind(&tx.created_at)
+        .bind(status)
+        .bind(tx_hash)
+        .bind(last_error)
+        .bind(executed_at)
+        .bind(updated_at)
+        .bind(id)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn get_transactions(
+    /// Fail sends left `executing` by a previous process. Whether they were
+    /// broadcast is unknown, so they are not retried.
+    pub async fn fail_interrupted_scheduled_transactions(
         &self,
-        account_id: &str,
-        limit: u32,
-        offset: u32,
-    ) -> Result<Vec<TransactionRow>, DatabaseError> {
-        Ok(sqlx::query_as::<_, TransactionRow>(
-            "SELECT * FROM transaction_history WHERE account_id = ? ORDER BY timestamp DESC LIMIT ? OFFSET ?",
+        updated_at: &str,
+    ) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE scheduled_transactions SET status = 'failed', \
+             last_error = 'Interrupted while executing; check history before rescheduling', \
+             updated_at = ? WHERE status = 'executing'",
         )
-        .bind(account_id)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await?)
+        .bind(updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    fn open_scheduled(
+        &self,
+        mut scheduled: ScheduledTransactionRow,
+    ) -> Result<ScheduledTransactionRow, DatabaseError> {
+        scheduled.memo = self.open_opt(scheduled.memo)?;
+        Ok(scheduled)
     }
 
     // ==================== Multi-sig Operations ====================
@@ -286,8 +3964,10 @@ impl Database {
     pub async fn create_multisig(&self, multisig: &MultisigWalletRow) -> Result<(), DatabaseError> {
         sqlx::query(
             r#"
-            INSERT INTO multisig_wallets (id, wallet_id, name, chain, address, threshold, owner_count, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO multisig_wallets
+                (id, wallet_id, name, chain, address, threshold, owner_count, created_at,
+                 sponsor_address, deployment_cost)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&multisig.id)
@@ -298,6 +3978,8 @@ impl Database {
         .bind(multisig.threshold)
         .bind(multisig.owner_count)
         .bind(&multisig.created_at)
+        .bind(&multisig.sponsor_address)
+        .bind(&multisig.deployment_cost)
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -383,6 +4065,44 @@ impl Database {
         .await?)
     }
 
+    /// Owners of each of `multisig_ids`
+    pub async fn get_owners_for_multisigs(
+        &self,
+        multisig_ids: &[String],
+    ) -> Result<Vec<MultisigOwnerRow>, DatabaseError> {
+        if multisig_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sql = format!(
+            "SELECT * FROM multisig_owners WHERE multisig_id IN ({})",
+            placeholders(multisig_ids.len())
+        );
+        let mut query = sqlx::query_as::<_, MultisigOwnerRow>(&sql);
+        for id in multisig_ids {
+            query = query.bind(id);
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
+    /// Proposals of each of `multisig_ids`, newest first
+    pub async fn get_transactions_for_multisigs(
+        &self,
+        multisig_ids: &[String],
+    ) -> Result<Vec<MultisigTransactionRow>, DatabaseError> {
+        if multisig_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sql = format!(
+            "SELECT * FROM multisig_transactions WHERE multisig_id IN ({}) ORDER BY created_at DESC",
+            placeholders(multisig_ids.len())
+        );
+        let mut query = sqlx::query_as::<_, MultisigTransactionRow>(&sql);
+        for id in multisig_ids {
+            query = query.bind(id);
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
     pub async fn get_multisig_tx(&self, id: &str) -> Result<MultisigTransactionRow, DatabaseError> {
         sqlx::query_as::<_, MultisigTransactionRow>(
             "SELECT * FROM multisig_transactions WHERE id = ?",
@@ -418,20 +4138,186 @@ impl Database {
         Ok(())
     }
 
+    pub async fn set_multisig_tx_payload(&self, id: &str, payload: &str) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE multisig_transactions SET signing_payload = ? WHERE id = ?")
+            .bind(payload)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn create_multisig_signature(
+        &self,
+        signature: &MultisigSignatureRow,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO multisig_signatures (id, transaction_id, owner_address, signature, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&signature.id)
+        .bind(&signature.transaction_id)
+        .bind(&signature.owner_address)
+        .bind(&signature.signature)
+        .bind(&signature.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            _ => DatabaseError::SqlxError(e),
+        })?;
+        Ok(())
+    }
+
+    pub async fn get_multisig_signatures(
+        &self,
+        transaction_id: &str,
+    ) -> Result<Vec<MultisigSignatureRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, MultisigSignatureRow>(
+            "SELECT * FROM multisig_signatures WHERE transaction_id = ? ORDER BY created_at ASC",
+        )
+        .bind(transaction_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    // ==================== Tenant Operations ====================
+
+    pub async fn create_tenant(&self, tenant: &TenantRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO tenants (id, name, hostname, api_key_hash, solana_rpc_url, eth_rpc_url,
+                rate_limit_max_requests, rate_limit_window_secs, settings, is_active, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&tenant.id)
+        .bind(&tenant.name)
+        .bind(&tenant.hostname)
+        .bind(&tenant.api_key_hash)
+        .bind(&tenant.solana_rpc_url)
+        .bind(&tenant.eth_rpc_url)
+        .bind(tenant.rate_limit_max_requests)
+        .bind(tenant.rate_limit_window_secs)
+        .bind(&tenant.settings)
+        .bind(tenant.is_active)
+        .bind(&tenant.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            _ => DatabaseError::SqlxError(e),
+        })?;
+        Ok(())
+    }
+
+    pub async fn get_tenants(&self) -> Result<Vec<TenantRow>, DatabaseError> {
+        Ok(
+            sqlx::query_as::<_, TenantRow>("SELECT * FROM tenants ORDER BY created_at, id")
+                .fetch_all(&self.pool)
+                .await?,
+        )
+    }
+
+    pub async fn get_tenant(&self, id: &str) -> Result<TenantRow, DatabaseError> {
+        sqlx::query_as::<_, TenantRow>("SELECT * FROM tenants WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    pub async fn get_tenant_by_api_key_hash(
+        &self,
+        api_key_hash: &str,
+    ) -> Result<Option<TenantRow>, DatabaseError> {
+        Ok(
+            sqlx::query_as::<_, TenantRow>("SELECT * FROM tenants WHERE api_key_hash = ?")
+                .bind(api_key_hash)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    pub async fn get_tenant_by_hostname(
+        &self,
+        hostname: &str,
+    ) -> Result<Option<TenantRow>, DatabaseError> {
+        Ok(
+            sqlx::query_as::<_, TenantRow>("SELECT * FROM tenants WHERE hostname = ?")
+                .bind(hostname)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Save every mutable field of a tenant except its API key
+    pub async fn update_tenant(&self, tenant: &TenantRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE tenants
+            SET name = ?, hostname = ?, solana_rpc_url = ?, eth_rpc_url = ?,
+                rate_limit_max_requests = ?, rate_limit_window_secs = ?, settings = ?,
+                is_active = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&tenant.name)
+        .bind(&tenant.hostname)
+        .bind(&tenant.solana_rpc_url)
+        .bind(&tenant.eth_rpc_url)
+        .bind(tenant.rate_limit_max_requests)
+        .bind(tenant.rate_limit_window_secs)
+        .bind(&tenant.settings)
+        .bind(tenant.is_active)
+        .bind(&tenant.id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                DatabaseError::AlreadyExists
+            }
+            _ => DatabaseError::SqlxError(e),
+        })?;
+        Ok(())
+    }
+
+    pub async fn set_tenant_api_key_hash(
+        &self,
+        id: &str,
+        api_key_hash: &str,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE tenants SET api_key_hash = ? WHERE id = ?")
+            .bind(api_key_hash)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     // ==================== NFT Cache Operations ====================
 
     pub async fn upsert_nft(&self, nft: &NftCacheRow) -> Result<(), DatabaseError> {
         sqlx::query(
             r#"
-            INSERT INTO nft_cache (id, account_id, chain, token_address, token_id, name, description, image_url, metadata_json, collection_name, last_updated)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO nft_cache (id, account_id, chain, token_address, token_id, name, description, image_url, metadata_json, collection_name, last_updated, royalties_json, collection_address, collection_verified)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(chain, token_address, token_id, account_id) DO UPDATE SET
                 name = excluded.name,
                 description = excluded.description,
                 image_url = excluded.image_url,
                 metadata_json = excluded.metadata_json,
                 collection_name = excluded.collection_name,
-                last_updated = excluded.last_updated
+                last_updated = excluded.last_updated,
+                royalties_json = excluded.royalties_json,
+                collection_address = excluded.collection_address,
+                collection_verified = excluded.collection_verified
             "#,
         )
         .bind(&nft.id)
@@ -445,6 +4331,9 @@ impl Database {
         .bind(&nft.metadata_json)
         .bind(&nft.collection_name)
         .bind(&nft.last_updated)
+        .bind(&nft.royalties_json)
+        .bind(&nft.collection_address)
+        .bind(nft.collection_verified)
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -454,7 +4343,7 @@ impl Database {
         Ok(
             sqlx::query_as::<_, NftCacheRow>("SELECT * FROM nft_cache WHERE account_id = ?")
                 .bind(account_id)
-                .fetch_all(&self.pool)
+                .fetch_all(self.reader())
                 .await?,
         )
     }
@@ -489,57 +4378,525 @@ impl Database {
         Ok(())
     }
 
-    pub async fn reset_database(&self) -> Result<(), DatabaseError> {
-        tracing::info!("Starting database reset...");
+    /// Record the marketplace escrow holding a cached NFT, or clear it
+    pub async fn set_nft_escrow(
+        &self,
+        id: &str,
+        escrow_address: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        sqlx::query("UPDATE nft_cache SET escrow_address = ?, last_updated = ? WHERE id = ?")
+            .bind(escrow_address)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Store re-fetched metadata of a cached NFT. Ownership isn't re-checked,
+    /// so `last_updated` is left alone.
+    pub async fn update_nft_metadata(&self, nft: &NftCacheRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            UPDATE nft_cache
+            SET name = ?, description = ?, image_url = ?, metadata_json = ?,
+                collection_name = ?, metadata_uri = ?, royalties_json = ?,
+                collection_address = ?, collection_verified = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&nft.name)
+        .bind(&nft.description)
+        .bind(&nft.image_url)
+        .bind(&nft.metadata_json)
+        .bind(&nft.collection_name)
+        .bind(&nft.metadata_uri)
+        .bind(&nft.royalties_json)
+        .bind(&nft.collection_address)
+        .bind(nft.collection_verified)
+        .bind(&nft.id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete a tenant's wallets and everything derived from them
+    pub async fn reset_database(&self, tenant_id: &str) -> Result<(), DatabaseError> {
+        tracing::info!(tenant_id = %tenant_id, "Starting database reset...");
 
         let mut tx = self.pool.begin().await?;
+        clear_tenant_wallets(&mut tx, tenant_id).await?;
 
-        // Delete in order to respect foreign keys
-        // 1. Clear cache and history data first (these reference accounts)
-        tracing::debug!("Clearing transaction history...");
-        sqlx::query("DELETE FROM transaction_history")
-            .execute(&mut *tx)
-            .await?;
+        // Commit the transaction
+        tx.commit().await?;
 
-        tracing::debug!("Clearing NFT cache...");
-        sqlx::query("DELETE FROM nft_cache")
-            .execute(&mut *tx)
-            .await?;
+        tracing::info!("Database reset complete");
+        Ok(())
+    }
 
-        // 2. Clear Application Data
-        tracing::debug!("Clearing accounts...");
-        sqlx::query("DELETE FROM accounts")
-            .execute(&mut *tx)
-            .await?;
+    // ==================== Account Deletion Operations ====================
 
-        tracing::debug!("Clearing contacts...");
-        sqlx::query("DELETE FROM contacts")
-            .execute(&mut *tx)
-            .await?;
+    pub async fn create_account_deletion(&self, row: &AccountDeletionRow) -> Result<(), DatabaseError> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_deletions (id, user_id, tenant_id, token_hash, token_expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&row.id)
+        .bind(&row.user_id)
+        .bind(&row.tenant_id)
+        .bind(&row.token_hash)
+        .bind(&row.token_expires_at)
+        .bind(&row.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
 
-        // 3. Clear Multisig Data (these tables might not exist in all schemas but should in production)
-        tracing::debug!("Clearing multisig data...");
-        // valid tables from migration 001
-        sqlx::query("DELETE FROM multisig_owners")
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM multisig_transactions")
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM multisig_wallets")
-            .execute(&mut *tx)
-            .await?;
+    /// A user's unconfirmed deletion request with the given token
+    pub async fn get_pending_account_deletion(
+        &self,
+        user_id: &str,
+        token_hash: &str,
+    ) -> Result<Option<AccountDeletionRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AccountDeletionRow>(
+            "SELECT * FROM account_deletions \
+             WHERE user_id = ? AND token_hash = ? AND confirmed_at IS NULL AND cancelled_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
 
-        // 4. Clear Core Wallet Data
-        tracing::debug!("Clearing wallets...");
-        sqlx::query("DELETE FROM wallets")
-            .execute(&mut *tx)
-            .await?;
+    /// Confirm a request, scheduling its purge; false if it was already
+    /// confirmed or cancelled
+    pub async fn confirm_account_deletion(
+        &self,
+        id: &str,
+        confirmed_at: &str,
+        purge_after: &str,
+    ) -> Result<bool, DatabaseError> {
+        let result = sqlx::query(
+            "UPDATE account_deletions SET confirmed_at = ?, purge_after = ? \
+             WHERE id = ? AND confirmed_at IS NULL AND cancelled_at IS NULL",
+        )
+        .bind(confirmed_at)
+        .bind(purge_after)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
 
-        // Commit the transaction
-        tx.commit().await?;
+    /// Confirmed deletions whose grace period ended before `now`
+    pub async fn get_due_account_deletions(
+        &self,
+        now: &str,
+    ) -> Result<Vec<AccountDeletionRow>, DatabaseError> {
+        Ok(sqlx::query_as::<_, AccountDeletionRow>(
+            "SELECT * FROM account_deletions \
+             WHERE confirmed_at IS NOT NULL AND cancelled_at IS NULL AND purged_at IS NULL \
+             AND julianday(purge_after) <= julianday(?) ORDER BY purge_after",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?)
+    }
 
-        tracing::info!("Database reset complete");
+    /// Delete everything a user owns and tombstone their record, with
+    /// `secure_delete` on so freed pages are zeroed rather than left in the
+    /// file. The tenant's wallets, seeds included, go too when no other
+    /// active user shares them; returns whether they did.
+    pub async fn purge_user(
+        &self,
+        deletion: &AccountDeletionRow,
+        now: &str,
+    ) -> Result<bool, DatabaseError> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("PRAGMA secure_delete = ON").execute(&mut *conn).await?;
+        let purged = purge_user_rows(&mut conn, deletion, now).await;
+        // The connection goes back to the pool for ordinary deletes
+        sqlx::query("PRAGMA secure_delete = OFF").execute(&mut *conn).await?;
+        purged
+    }
+
+    // ==================== Maintenance Operations ====================
+
+    /// Delete expired and aged-out rows from the primary, one table at a
+    /// time to keep each write lock short; the rows removed per table.
+    /// Times are compared with `julianday` since older rows were written
+    /// with SQLite's `datetime('now')` format.
+    pub async fn prune_expired(
+        &self,
+        cutoffs: &RetentionCutoffs,
+    ) -> Result<Vec<(&'static str, u64)>, DatabaseError> {
+        let challenges = cutoffs.challenges_before.as_str();
+        let statements: [(&'static str, &str, &str); 11] = [
+            (
+                "sign_in_challenges",
+                "DELETE FROM sign_in_challenges WHERE julianday(expires_at) < julianday(?)",
+                challenges,
+            ),
+            (
+                "oauth_states",
+                "DELETE FROM oauth_states WHERE julianday(expires_at) < julianday(?)",
+                challenges,
+            ),
+            (
+                "unlock_challenges",
+                "DELETE FROM unlock_challenges WHERE julianday(expires_at) < julianday(?)",
+                challenges,
+            ),
+            (
+                "send_challenges",
+                "DELETE FROM send_challenges WHERE julianday(expires_at) < julianday(?)",
+                challenges,
+            ),
+            (
+                "backup_challenges",
+                "DELETE FROM backup_challenges WHERE julianday(expires_at) < julianday(?)",
+                challenges,
+            ),
+            (
+                "login_verifications",
+                "DELETE FROM login_verifications WHERE julianday(expires_at) < julianday(?)",
+                challenges,
+            ),
+            (
+                "user_sessions",
+                "DELETE FROM user_sessions \
+                 WHERE MIN(julianday(expires_at), IFNULL(julianday(revoked_at), julianday(expires_at))) < julianday(?)",
+                &cutoffs.sessions_before,
+            ),
+            // Ethereum NFTs can't be listed from the chain again, so only
+            // Solana entries are dropped
+            (
+                "nft_cache",
+                "DELETE FROM nft_cache WHERE chain = 'solana' AND julianday(last_updated) < julianday(?)",
+                &cutoffs.nft_cache_before,
+            ),
+            (
+                "reconciliation_runs",
+                "DELETE FROM reconciliation_runs WHERE julianday(finished_at) < julianday(?)",
+                &cutoffs.jobs_before,
+            ),
+            (
+                "faucet_requests",
+                "DELETE FROM faucet_requests WHERE julianday(created_at) < julianday(?)",
+                &cutoffs.jobs_before,
+            ),
+            (
+                "webhook_deliveries",
+                "DELETE FROM webhook_deliveries WHERE julianday(created_at) < julianday(?)",
+                &cutoffs.jobs_before,
+            ),
+        ];
+
+        let mut pruned = Vec::with_capacity(statements.len());
+        for (table, sql, cutoff) in statements {
+            let result = sqlx::query(sql).bind(cutoff).execute(&self.pool).await?;
+            pruned.push((table, result.rows_affected()));
+        }
+        Ok(pruned)
+    }
+
+    /// Size and free space of the primary
+    pub async fn page_stats(&self) -> Result<PageStats, DatabaseError> {
+        let pragma = |name: &'static str| async move {
+            let (value,): (i64,) = sqlx::query_as(&format!("PRAGMA {}", name))
+                .fetch_one(&self.pool)
+                .await?;
+            Ok::<u64, DatabaseError>(value.max(0) as u64)
+        };
+        Ok(PageStats {
+            page_size: pragma("page_size").await?,
+            page_count: pragma("page_count").await?,
+            freelist_count: pragma("freelist_count").await?,
+        })
+    }
+
+    /// Let SQLite refresh the query planner statistics it thinks are stale
+    pub async fn optimize(&self) -> Result<(), DatabaseError> {
+        sqlx::query("PRAGMA optimize").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Whether no query currently holds a connection to the primary
+    pub fn is_idle(&self) -> bool {
+        self.pool.num_idle() == self.pool.size() as usize
+    }
+
+    /// Rebuild the primary's file, returning free pages to the OS. Blocks
+    /// writers while it runs.
+    pub async fn vacuum(&self) -> Result<(), DatabaseError> {
+        sqlx::query("VACUUM").execute(&self.pool).await?;
         Ok(())
     }
 }
+
+/// Delete a tenant's wallets and everything derived from them, within `tx`
+async fn clear_tenant_wallets(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    tenant_id: &str,
+) -> Result<(), DatabaseError> {
+    // Delete in order to respect foreign keys
+    // 1. Clear cache and history data first (these reference accounts)
+    tracing::debug!("Clearing transaction history...");
+    sqlx::query(&format!(
+        "DELETE FROM transaction_history WHERE account_id IN ({})",
+        TENANT_ACCOUNTS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    tracing::debug!("Clearing NFT cache...");
+    sqlx::query(&format!(
+        "DELETE FROM nft_cache WHERE account_id IN ({})",
+        TENANT_ACCOUNTS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    tracing::debug!("Clearing alerts...");
+    sqlx::query(&format!(
+        "DELETE FROM alert_notifications WHERE alert_id IN \
+         (SELECT id FROM alerts WHERE account_id IN ({}))",
+        TENANT_ACCOUNTS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+    for table in ["alerts", "balance_snapshots"] {
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE account_id IN ({})",
+            table, TENANT_ACCOUNTS
+        ))
+        .bind(tenant_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    tracing::debug!("Clearing scheduled sends...");
+    sqlx::query(&format!(
+        "DELETE FROM scheduled_transactions WHERE account_id IN ({})",
+        TENANT_ACCOUNTS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    tracing::debug!("Clearing owned names...");
+    sqlx::query(&format!(
+        "DELETE FROM owned_names WHERE account_id IN ({})",
+        TENANT_ACCOUNTS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    tracing::debug!("Clearing bridge transfers...");
+    sqlx::query(&format!(
+        "DELETE FROM bridge_transfers WHERE account_id IN ({})",
+        TENANT_ACCOUNTS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    tracing::debug!("Clearing cross-chain swaps...");
+    sqlx::query(&format!(
+        "DELETE FROM cross_chain_swaps WHERE from_account_id IN ({0}) OR to_account_id IN ({0})",
+        TENANT_ACCOUNTS
+    ))
+    .bind(tenant_id)
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    // 2. Clear Application Data
+    tracing::debug!("Clearing accounts...");
+    sqlx::query(&format!("DELETE FROM accounts WHERE wallet_id IN ({})", TENANT_WALLETS))
+        .bind(tenant_id)
+        .execute(&mut **tx)
+        .await?;
+
+    tracing::debug!("Clearing contacts...");
+    sqlx::query(&format!("DELETE FROM contacts WHERE wallet_id IN ({})", TENANT_WALLETS))
+        .bind(tenant_id)
+        .execute(&mut **tx)
+        .await?;
+
+    // 3. Clear Multisig Data (these tables might not exist in all schemas but should in production)
+    tracing::debug!("Clearing multisig data...");
+    // valid tables from migrations 001 and 009
+    sqlx::query(&format!(
+        "DELETE FROM multisig_signatures WHERE transaction_id IN \
+         (SELECT id FROM multisig_transactions WHERE multisig_id IN ({}))",
+        TENANT_MULTISIGS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(&format!(
+        "DELETE FROM multisig_owners WHERE multisig_id IN ({})",
+        TENANT_MULTISIGS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(&format!(
+        "DELETE FROM multisig_transactions WHERE multisig_id IN ({})",
+        TENANT_MULTISIGS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(&format!(
+        "DELETE FROM multisig_wallets WHERE wallet_id IN ({})",
+        TENANT_WALLETS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    // 4. Clear Core Wallet Data
+    tracing::debug!("Clearing backup challenges...");
+    sqlx::query(&format!(
+        "DELETE FROM backup_challenges WHERE wallet_id IN ({})",
+        TENANT_WALLETS
+    ))
+    .bind(tenant_id)
+    .execute(&mut **tx)
+    .await?;
+
+    tracing::debug!("Clearing wallets...");
+    sqlx::query("DELETE FROM wallets WHERE tenant_id = ?")
+        .bind(tenant_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Tables holding rows that belong to a single user
+const USER_TABLES: &[&str] = &[
+    "user_sessions",
+    "api_keys",
+    "connected_dapps",
+    "session_keys",
+    "user_tokens",
+    "user_addresses",
+    "user_identities",
+    "alert_notifications",
+    "alerts",
+    "price_alerts",
+    "sync_blobs",
+    "large_transfer_thresholds",
+    "send_challenges",
+    "send_templates",
+    "notes",
+    "watchlist",
+    "cold_transactions",
+    "login_verifications",
+    "faucet_requests",
+    "contacts",
+    "share_links",
+    "token_view_settings",
+];
+
+async fn purge_user_rows(
+    conn: &mut sqlx::SqliteConnection,
+    deletion: &AccountDeletionRow,
+    now: &str,
+) -> Result<bool, DatabaseError> {
+    let mut tx = sqlx::Connection::begin(conn).await?;
+
+    for table in USER_TABLES {
+        sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", table))
+            .bind(&deletion.user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let (others,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM users \
+         WHERE tenant_id = ? AND id != ? AND is_active = 1 AND deleted_at IS NULL",
+    )
+    .bind(&deletion.tenant_id)
+    .bind(&deletion.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let wipe_wallets = others == 0;
+    if wipe_wallets {
+        clear_tenant_wallets(&mut tx, &deletion.tenant_id).await?;
+    }
+
+    sqlx::query(
+        "UPDATE users SET email = ?, password_hash = '', is_active = 0, email_verified = 0, \
+         last_login_at = NULL, updated_at = ?, deleted_at = ? WHERE id = ?",
+    )
+    .bind(format!("deleted+{}@deleted.invalid", deletion.user_id))
+    .bind(now)
+    .bind(now)
+    .bind(&deletion.user_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE account_deletions SET purged_at = ? WHERE id = ?")
+        .bind(now)
+        .bind(&deletion.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(wipe_wallets)
+}
+
+async fn insert_siem_event(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    event: &SiemEventRow,
+) -> Result<(), DatabaseError> {
+    sqlx::query(
+        r#"
+        INSERT INTO siem_outbox (id, tenant_id, source, event, severity, payload, attempts, last_error, next_attempt_at, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&event.id)
+    .bind(&event.tenant_id)
+    .bind(&event.source)
+    .bind(&event.event)
+    .bind(&event.severity)
+    .bind(&event.payload)
+    .bind(event.attempts)
+    .bind(&event.last_error)
+    .bind(&event.next_attempt_at)
+    .bind(&event.created_at)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn insert_audit_event(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    event: &AuditEventRow,
+) -> Result<(), DatabaseError> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_events (id, tenant_id, user_id, event, severity, details, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&event.id)
+    .bind(&event.tenant_id)
+    .bind(&event.user_id)
+    .bind(&event.event)
+    .bind(&event.severity)
+    .bind(&event.details)
+    .bind(&event.created_at)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,138 @@
+//! Field-level encryption for privacy-sensitive columns
+//!
+//! Values are sealed with AES-256-GCM under a key derived from the operator
+//! secret with HKDF-SHA256, and stored as `enc:v1:` followed by the base64 of
+//! nonce and ciphertext. Values without that prefix are read back unchanged,
+//! so rows written before encryption was enabled stay readable until they
+//! are backfilled.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::Sha256;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Marks a stored value as sealed, and with which scheme
+const PREFIX: &str = "enc:v1:";
+/// Binds derived keys to this use of the operator secret
+const KEY_INFO: &[u8] = b"wallet-backend field encryption v1";
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Error)]
+pub enum FieldCryptoError {
+    #[error("Encrypted field is malformed")]
+    InvalidFormat,
+    #[error("Encrypted field could not be decrypted with the configured key")]
+    DecryptionFailed,
+}
+
+/// Seals and opens column values under the operator's key
+#[derive(Clone)]
+pub struct FieldCipher {
+    cipher: Aes256Gcm,
+}
+
+impl FieldCipher {
+    pub fn new(secret: &str) -> Self {
+        Self::derive(secret.as_bytes(), KEY_INFO)
+    }
+
+    /// A cipher keyed by other secret material, bound to the use named by
+    /// `info`
+    pub fn derive(secret: &[u8], info: &[u8]) -> Self {
+        let mut key = Zeroizing::new([0u8; 32]);
+        Hkdf::<Sha256>::new(None, secret)
+            .expand(info, key.as_mut())
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Self {
+            cipher: Aes256Gcm::new(key.as_ref().into()),
+        }
+    }
+
+    /// Whether a stored value is already sealed
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(PREFIX)
+    }
+
+    /// Seal a value with a fresh random nonce
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .expect("AES-GCM encryption of an in-memory buffer");
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        format!("{}{}", PREFIX, STANDARD.encode(sealed))
+    }
+
+    /// Open a stored value; values that were never sealed pass through
+    pub fn decrypt(&self, value: &str) -> Result<String, FieldCryptoError> {
+        let Some(encoded) = value.strip_prefix(PREFIX) else {
+            return Ok(value.to_string());
+        };
+
+        let sealed = STANDARD
+            .decode(encoded)
+            .map_err(|_| FieldCryptoError::InvalidFormat)?;
+        if sealed.len() < NONCE_LEN {
+            return Err(FieldCryptoError::InvalidFormat);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| FieldCryptoError::DecryptionFailed)?;
+        String::from_utf8(plaintext).map_err(|_| FieldCryptoError::InvalidFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_uses_fresh_nonces() {
+        let cipher = FieldCipher::new("operator secret");
+        let first = cipher.encrypt("Alice");
+        let second = cipher.encrypt("Alice");
+
+        assert!(FieldCipher::is_encrypted(&first));
+        assert_ne!(first, second);
+        assert_eq!(cipher.decrypt(&first).unwrap(), "Alice");
+        assert_eq!(cipher.decrypt(&second).unwrap(), "Alice");
+    }
+
+    #[test]
+    fn test_derived_keys_are_bound_to_their_use() {
+        let sealed = FieldCipher::derive(&[7u8; 64], b"notes").encrypt("Alice");
+        assert_eq!(FieldCipher::derive(&[7u8; 64], b"notes").decrypt(&sealed).unwrap(), "Alice");
+        assert!(FieldCipher::derive(&[7u8; 64], b"other").decrypt(&sealed).is_err());
+    }
+
+    #[test]
+    fn test_plaintext_passes_through() {
+        let cipher = FieldCipher::new("operator secret");
+        assert_eq!(cipher.decrypt("rent for March").unwrap(), "rent for March");
+    }
+
+    #[test]
+    fn test_wrong_key_is_rejected() {
+        let sealed = FieldCipher::new("operator secret").encrypt("Alice");
+        assert!(matches!(
+            FieldCipher::new("another secret").decrypt(&sealed),
+            Err(FieldCryptoError::DecryptionFailed)
+        ));
+        assert!(matches!(
+            FieldCipher::new("operator secret").decrypt("enc:v1:not base64!"),
+            Err(FieldCryptoError::InvalidFormat)
+        ));
+    }
+}
@@ -1,6 +1,9 @@
 //! Storage layer for the wallet backend
 
 pub mod database;
+pub mod field_crypto;
 pub mod models;
+pub mod schema;
 
 pub use database::Database;
+pub use field_crypto::FieldCipher;
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::native_amount_raw;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AccountRow {
     pub id: String,
@@ -13,6 +15,17 @@ pub struct AccountRow {
     pub public_key: String,
     pub address: String,
     pub created_at: String,
+    /// RPC endpoint used for this account instead of the default one
+    pub rpc_url: Option<String>,
+    /// Send through the private relay by default (Ethereum)
+    pub mev_protect: bool,
+    /// Last successful balance read
+    pub last_synced_at: Option<String>,
+    /// Native balance in display units as of `last_synced_at`
+    pub last_known_balance: Option<String>,
+    /// Error from the last balance read, when it failed
+    pub sync_error: Option<String>,
+    pub sync_error_at: Option<String>,
 }
 
 impl AccountRow {
@@ -35,6 +48,12 @@ impl AccountRow {
             public_key,
             address,
             created_at: chrono::Utc::now().to_rfc3339(),
+            rpc_url: None,
+            mev_protect: false,
+            last_synced_at: None,
+            last_known_balance: None,
+            sync_error: None,
+            sync_error_at: None,
         }
     }
 }
@@ -50,10 +69,22 @@ pub struct AccountResponse {
     pub public_key: String,
     pub address: String,
     pub created_at: String,
+    pub rpc_url: Option<String>,
+    pub mev_protect: bool,
+    pub last_synced_at: Option<String>,
+    pub last_known_balance: Option<String>,
+    /// `last_known_balance` in base units (lamports, wei)
+    pub last_known_balance_raw: Option<String>,
+    pub sync_error: Option<String>,
+    pub sync_error_at: Option<String>,
 }
 
 impl From<AccountRow> for AccountResponse {
     fn from(row: AccountRow) -> Self {
+        let last_known_balance_raw = row
+            .last_known_balance
+            .as_deref()
+            .and_then(|balance| native_amount_raw(&row.chain, balance, None));
         Self {
             id: row.id,
             name: row.name,
@@ -63,6 +94,13 @@ impl From<AccountRow> for AccountResponse {
             public_key: row.public_key,
             address: row.address,
             created_at: row.created_at,
+            rpc_url: row.rpc_url,
+            mev_protect: row.mev_protect,
+            last_synced_at: row.last_synced_at,
+            last_known_balance_raw,
+            last_known_balance: row.last_known_balance,
+            sync_error: row.sync_error,
+            sync_error_at: row.sync_error_at,
         }
     }
 }
@@ -0,0 +1,63 @@
+//! Account deletion models
+
+use serde::{Deserialize, Serialize};
+
+use super::{AccountRow, ContactRow, SendTemplateRow, TransactionRow, UserAddressRow, UserPublic, WatchRow};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AccountDeletionRow {
+    pub id: String,
+    pub user_id: String,
+    pub tenant_id: String,
+    /// SHA-256 of the confirmation token
+    pub token_hash: String,
+    pub token_expires_at: String,
+    pub confirmed_at: Option<String>,
+    /// When a confirmed deletion may be purged
+    pub purge_after: Option<String>,
+    pub cancelled_at: Option<String>,
+    pub purged_at: Option<String>,
+    pub created_at: String,
+}
+
+/// Body of `POST /users/delete-account`. Without a token a confirmation
+/// token is issued; sending it back with the password again confirms.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+    pub confirmation_token: Option<String>,
+}
+
+/// Returned with 202 when a deletion has been requested but not confirmed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDeletionPending {
+    pub confirmation_token: String,
+    /// Seconds until the token expires
+    pub expires_in: i64,
+    /// Days between confirming and the data being purged
+    pub grace_period_days: u32,
+}
+
+/// A confirmed deletion and the user's data as of confirming
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountDeletionConfirmed {
+    pub deletion_id: String,
+    /// Logging in before this cancels the deletion
+    pub purge_after: String,
+    pub archive: AccountArchive,
+}
+
+/// Everything the wallet holds about a user, exported before deletion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountArchive {
+    pub exported_at: String,
+    pub user: UserPublic,
+    /// Addresses linked for wallet sign-in
+    pub linked_addresses: Vec<UserAddressRow>,
+    pub accounts: Vec<AccountRow>,
+    pub contacts: Vec<ContactRow>,
+    /// Transaction history of every account
+    pub transactions: Vec<TransactionRow>,
+    pub send_templates: Vec<SendTemplateRow>,
+    pub watchlist: Vec<WatchRow>,
+}
@@ -0,0 +1,174 @@
+//! Balance alert and notification database models
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AlertRow {
+    pub id: String,
+    pub user_id: String,
+    pub account_id: String,
+    /// `balance_below`, `balance_above` or `outflow_over`
+    pub kind: String,
+    /// Decimal amount in the native coin, or in `currency`
+    pub threshold: String,
+    /// Upper-case ISO 4217 code for fiat thresholds
+    pub currency: Option<String>,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    pub triggered: bool,
+    pub history_cursor: i64,
+    pub last_triggered_at: Option<String>,
+    pub created_at: String,
+}
+
+impl AlertRow {
+    pub fn new(
+        user_id: String,
+        account_id: String,
+        kind: String,
+        threshold: String,
+        currency: Option<String>,
+        webhook_url: Option<String>,
+        history_cursor: i64,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            account_id,
+            kind,
+            threshold,
+            currency,
+            webhook_url,
+            is_active: true,
+            triggered: false,
+            history_cursor,
+            last_triggered_at: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Alert response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertResponse {
+    pub id: String,
+    pub account_id: String,
+    pub kind: String,
+    pub threshold: String,
+    pub currency: Option<String>,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    /// Balance rules: the condition currently holds
+    pub triggered: bool,
+    pub last_triggered_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<AlertRow> for AlertResponse {
+    fn from(row: AlertRow) -> Self {
+        Self {
+            id: row.id,
+            account_id: row.account_id,
+            kind: row.kind,
+            threshold: row.threshold,
+            currency: row.currency,
+            webhook_url: row.webhook_url,
+            is_active: row.is_active,
+            triggered: row.triggered,
+            last_triggered_at: row.last_triggered_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AlertNotificationRow {
+    pub id: String,
+    /// Balance alert that fired; `None` for price alerts
+    pub alert_id: Option<String>,
+    /// Price alert that fired; `None` for balance alerts
+    pub price_alert_id: Option<String>,
+    pub user_id: String,
+    pub message: String,
+    pub balance: Option<String>,
+    pub price: Option<String>,
+    pub tx_signature: Option<String>,
+    /// `delivered` or `failed`; `None` without a webhook
+    pub webhook_status: Option<String>,
+    pub created_at: String,
+}
+
+impl AlertNotificationRow {
+    pub fn new(
+        alert_id: String,
+        user_id: String,
+        message: String,
+        balance: Option<String>,
+        tx_signature: Option<String>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            alert_id: Some(alert_id),
+            price_alert_id: None,
+            user_id,
+            message,
+            balance,
+            price: None,
+            tx_signature,
+            webhook_status: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+impl AlertNotificationRow {
+    pub fn for_price_alert(
+        price_alert_id: String,
+        user_id: String,
+        message: String,
+        price: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            alert_id: None,
+            price_alert_id: Some(price_alert_id),
+            user_id,
+            message,
+            balance: None,
+            price: Some(price),
+            tx_signature: None,
+            webhook_status: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Notification response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertNotificationResponse {
+    pub id: String,
+    pub alert_id: Option<String>,
+    pub price_alert_id: Option<String>,
+    pub message: String,
+    pub balance: Option<String>,
+    pub price: Option<String>,
+    pub tx_signature: Option<String>,
+    pub webhook_status: Option<String>,
+    pub created_at: String,
+}
+
+impl From<AlertNotificationRow> for AlertNotificationResponse {
+    fn from(row: AlertNotificationRow) -> Self {
+        Self {
+            id: row.id,
+            alert_id: row.alert_id,
+            price_alert_id: row.price_alert_id,
+            message: row.message,
+            balance: row.balance,
+            price: row.price,
+            tx_signature: row.tx_signature,
+            webhook_status: row.webhook_status,
+            created_at: row.created_at,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Spending analytics database model
+
+/// Dimension outflows are aggregated by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpendingGroup {
+    /// `YYYY-MM` of the transaction
+    Month,
+    /// User-assigned tag; untagged rows fall in a `None` bucket
+    Tag,
+    /// Recipient address
+    Counterparty,
+}
+
+/// One send within a bucket
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SpendingRow {
+    pub bucket: Option<String>,
+    /// Contact name of a counterparty bucket
+    pub name: Option<String>,
+    pub chain: String,
+    pub token_address: Option<String>,
+    /// Amount in display units
+    pub amount: String,
+    /// Realized value, when the send is priced in the requested currency
+    pub fiat: Option<f64>,
+}
+
+/// Fee of one send in the analytics range
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FeeRow {
+    pub chain: String,
+    /// Base units paid; `None` until the send has settled
+    pub fee_paid: Option<String>,
+}
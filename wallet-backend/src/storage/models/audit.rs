@@ -0,0 +1,68 @@
+//! Audit log model
+
+use serde::{Deserialize, Serialize};
+
+/// How much attention an audit event deserves, least first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AuditSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditSeverity::Info => "info",
+            AuditSeverity::Warning => "warning",
+            AuditSeverity::Critical => "critical",
+        }
+    }
+}
+
+impl std::str::FromStr for AuditSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(AuditSeverity::Info),
+            "warning" => Ok(AuditSeverity::Warning),
+            "critical" => Ok(AuditSeverity::Critical),
+            _ => Err(format!("'{}' is not info, warning or critical", s)),
+        }
+    }
+}
+
+/// One recorded security-sensitive change
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditEventRow {
+    pub id: String,
+    pub tenant_id: String,
+    pub user_id: Option<String>,
+    pub event: String,
+    pub severity: String,
+    /// JSON object with event-specific fields
+    pub details: String,
+    pub created_at: String,
+}
+
+impl AuditEventRow {
+    pub fn new(
+        tenant_id: String,
+        user_id: Option<String>,
+        event: &str,
+        severity: AuditSeverity,
+        details: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tenant_id,
+            user_id,
+            event: event.to_string(),
+            severity: severity.as_str().to_string(),
+            details: details.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
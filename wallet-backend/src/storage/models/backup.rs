@@ -0,0 +1,30 @@
+//! Backup quiz challenge database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BackupChallengeRow {
+    pub id: String,
+    pub wallet_id: String,
+    pub positions: String,
+    pub expires_at: String,
+    pub created_at: String,
+}
+
+impl BackupChallengeRow {
+    pub fn new(wallet_id: String, positions: &[u32], ttl: chrono::Duration) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            wallet_id,
+            positions: serde_json::to_string(positions).unwrap_or_else(|_| "[]".to_string()),
+            expires_at: (now + ttl).to_rfc3339(),
+            created_at: now.to_rfc3339(),
+        }
+    }
+
+    /// 1-based word positions asked in this challenge
+    pub fn positions(&self) -> Vec<u32> {
+        serde_json::from_str(&self.positions).unwrap_or_default()
+    }
+}
@@ -0,0 +1,65 @@
+//! L2 bridge deposit database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BridgeTransferRow {
+    pub id: String,
+    pub account_id: String,
+    /// `arbitrum`, `optimism` or `base`
+    pub network: String,
+    pub l1_chain_id: i64,
+    pub l1_tx_hash: String,
+    pub bridge_contract: String,
+    /// Sender on L1, credited at the same address on L2
+    pub address: String,
+    /// Decimal amount in ETH
+    pub amount: String,
+    /// `submitted`, `confirmed`, `finalized` or `failed`
+    pub status: String,
+    pub l1_block_number: Option<i64>,
+    pub confirmed_at: Option<String>,
+    /// When the deposit should be credited on L2
+    pub finalizes_at: Option<String>,
+    pub finalized_at: Option<String>,
+    pub webhook_url: Option<String>,
+    /// `delivered` or `failed`; `None` until notified or without a webhook
+    pub webhook_status: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl BridgeTransferRow {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        account_id: String,
+        network: String,
+        l1_chain_id: i64,
+        l1_tx_hash: String,
+        bridge_contract: String,
+        address: String,
+        amount: String,
+        webhook_url: Option<String>,
+    ) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            account_id,
+            network,
+            l1_chain_id,
+            l1_tx_hash,
+            bridge_contract,
+            address,
+            amount,
+            status: "submitted".to_string(),
+            l1_block_number: None,
+            confirmed_at: None,
+            finalizes_at: None,
+            finalized_at: None,
+            webhook_url,
+            webhook_status: None,
+            created_at: now.clone(),
+            updated_at: now,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Savings bucket database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BucketRow {
+    pub id: String,
+    pub account_id: String,
+    pub name: String,
+    /// Native base units set aside, as a decimal string
+    pub allocated: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
@@ -0,0 +1,58 @@
+//! Cold-signed transfer database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ColdTransactionRow {
+    pub id: String,
+    pub user_id: String,
+    /// Tenant whose RPC endpoints build and broadcast the transfer
+    pub tenant_id: String,
+    pub chain: String,
+    pub from_address: String,
+    pub to_address: String,
+    /// Display units of the native coin
+    pub amount: String,
+    pub fee: String,
+    /// Base64 unsigned transaction as exported
+    pub payload: String,
+    /// Hash an Ethereum signer signs
+    pub signing_hash: Option<String>,
+    /// `exported` or `broadcast`
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub created_at: String,
+    pub broadcast_at: Option<String>,
+}
+
+impl ColdTransactionRow {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_id: String,
+        tenant_id: String,
+        chain: String,
+        from_address: String,
+        to_address: String,
+        amount: String,
+        fee: String,
+        payload: String,
+        signing_hash: Option<String>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            tenant_id,
+            chain,
+            from_address,
+            to_address,
+            amount,
+            fee,
+            payload,
+            signing_hash,
+            status: "exported".to_string(),
+            tx_hash: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            broadcast_at: None,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Contact (address book) database model
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -11,6 +13,16 @@ pub struct ContactRow {
     pub address: String,
     pub notes: Option<String>,
     pub created_at: String,
+    /// ENS / SNS name the address resolves to
+    pub identity_name: Option<String>,
+    pub identity_avatar: Option<String>,
+    /// JSON object of profile text records
+    pub identity_records: Option<String>,
+    pub identity_refreshed_at: Option<String>,
+    /// Token sends to the contact default to; native when `None`
+    pub default_token_address: Option<String>,
+    /// Amount sends to the contact default to, as entered
+    pub default_amount: Option<String>,
 }
 
 impl ContactRow {
@@ -29,8 +41,35 @@ impl ContactRow {
             address,
             notes,
             created_at: chrono::Utc::now().to_rfc3339(),
+            identity_name: None,
+            identity_avatar: None,
+            identity_records: None,
+            identity_refreshed_at: None,
+            default_token_address: None,
+            default_amount: None,
         }
     }
+
+    /// Cached identity, if the address has a name
+    pub fn identity(&self) -> Option<ContactIdentity> {
+        Some(ContactIdentity {
+            name: self.identity_name.clone()?,
+            avatar: self.identity_avatar.clone(),
+            records: self
+                .identity_records
+                .as_deref()
+                .and_then(|records| serde_json::from_str(records).ok())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Name-service identity of a contact's address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactIdentity {
+    pub name: String,
+    pub avatar: Option<String>,
+    pub records: BTreeMap<String, String>,
 }
 
 /// Contact response for API
@@ -42,17 +81,36 @@ pub struct ContactResponse {
     pub address: String,
     pub notes: Option<String>,
     pub created_at: String,
+    pub identity: Option<ContactIdentity>,
+    /// When the identity was last resolved; `None` if never
+    pub identity_refreshed_at: Option<String>,
+    pub default_token_address: Option<String>,
+    pub default_amount: Option<String>,
 }
 
 impl From<ContactRow> for ContactResponse {
     fn from(row: ContactRow) -> Self {
         Self {
+            identity: row.identity(),
             id: row.id,
             name: row.name,
             chain: row.chain,
             address: row.address,
             notes: row.notes,
             created_at: row.created_at,
+            identity_refreshed_at: row.identity_refreshed_at,
+            default_token_address: row.default_token_address,
+            default_amount: row.default_amount,
         }
     }
 }
+
+/// A name a wallet knows an address by
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AddressLabel {
+    /// Lower-cased, as transaction addresses are matched case-insensitively
+    pub address: String,
+    pub name: String,
+    /// One of the wallet's own accounts rather than a contact
+    pub is_own: bool,
+}
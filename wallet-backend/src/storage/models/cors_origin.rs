@@ -0,0 +1,9 @@
+//! Runtime CORS origin database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CorsOriginRow {
+    pub origin: String,
+    pub created_at: String,
+}
@@ -0,0 +1,37 @@
+//! Cross-chain swap database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CrossChainSwapRow {
+    /// Route ID, carried by both history entries of the swap
+    pub id: String,
+    pub from_account_id: String,
+    pub to_account_id: String,
+    pub from_chain: String,
+    pub to_chain: String,
+    /// Token contract or mint; `None` for the native coin
+    pub from_token: Option<String>,
+    pub to_token: Option<String>,
+    /// Display units of the source token
+    pub from_amount: String,
+    /// Display units of the destination token, as quoted
+    pub quoted_amount: String,
+    /// Least the route delivers within its slippage
+    pub min_amount: String,
+    /// What arrived, once completed
+    pub received_amount: Option<String>,
+    /// Bridge carrying the value across, as named by the aggregator
+    pub tool: String,
+    pub estimated_duration_secs: i64,
+    /// `submitted`, `bridging`, `completed`, `refunded` or `failed`
+    pub status: String,
+    /// Latest progress reported by the aggregator
+    pub status_message: Option<String>,
+    pub approval_tx_hash: Option<String>,
+    pub source_tx_hash: String,
+    pub destination_tx_hash: Option<String>,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
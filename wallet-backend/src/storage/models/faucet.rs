@@ -0,0 +1,16 @@
+//! Faucet request model
+
+use serde::{Deserialize, Serialize};
+
+/// Test-network funding sent to an address
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FaucetRequestRow {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub chain: String,
+    pub address: String,
+    /// Base units (lamports / wei)
+    pub amount: String,
+    pub tx_hash: String,
+    pub created_at: String,
+}
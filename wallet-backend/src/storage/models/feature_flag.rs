@@ -0,0 +1,12 @@
+//! Feature flag database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FeatureFlagRow {
+    pub name: String,
+    pub enabled: bool,
+    /// Shown to clients refused because of the flag
+    pub reason: Option<String>,
+    pub updated_at: String,
+}
@@ -0,0 +1,21 @@
+//! JWT signing key models
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct JwtKeyRow {
+    pub kid: String,
+    pub secret: String,
+    pub created_at: String,
+    pub retired_at: Option<String>,
+}
+
+/// A signing key as shown to operators; the secret never leaves the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtKeyInfo {
+    pub kid: String,
+    pub created_at: String,
+    pub retired_at: Option<String>,
+    /// New tokens are signed with this key
+    pub current: bool,
+}
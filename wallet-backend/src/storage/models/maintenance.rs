@@ -0,0 +1,36 @@
+//! Database maintenance models
+
+use serde::{Deserialize, Serialize};
+
+/// RFC 3339 times before which each kind of row is pruned
+#[derive(Debug, Clone)]
+pub struct RetentionCutoffs {
+    /// Single-use challenges and nonces that expired before this
+    pub challenges_before: String,
+    /// Sessions that expired or were revoked before this
+    pub sessions_before: String,
+    /// Solana NFT cache entries last refreshed before this
+    pub nft_cache_before: String,
+    /// Reconciliation runs, faucet requests and webhook deliveries from
+    /// before this
+    pub jobs_before: String,
+}
+
+/// Size of the database file in pages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageStats {
+    pub page_size: u64,
+    pub page_count: u64,
+    /// Pages freed by deletes, only returned to the OS by `VACUUM`
+    pub freelist_count: u64,
+}
+
+impl PageStats {
+    pub fn size_bytes(&self) -> u64 {
+        self.page_size * self.page_count
+    }
+
+    pub fn free_bytes(&self) -> u64 {
+        self.page_size * self.freelist_count
+    }
+}
@@ -7,6 +7,39 @@ mod transaction;
 mod multisig;
 mod nft;
 mod user;
+mod backup;
+mod user_token;
+mod tenant;
+mod sign_in;
+mod alert;
+mod sync_blob;
+mod session_key;
+mod analytics;
+mod owned_name;
+mod scheduled_transaction;
+mod transfer_confirmation;
+mod token_list;
+mod audit;
+mod jwt_key;
+mod faucet;
+mod send_template;
+mod reconciliation;
+mod note;
+mod bucket;
+mod watchlist;
+mod feature_flag;
+mod unlock_challenge;
+mod siem;
+mod bridge;
+mod cross_chain;
+mod cold_transaction;
+mod cors_origin;
+mod maintenance;
+mod account_deletion;
+mod price_alert;
+mod status;
+mod webhook;
+mod share_link;
 
 pub use wallet::*;
 pub use account::*;
@@ -15,3 +48,36 @@ pub use transaction::*;
 pub use multisig::*;
 pub use nft::*;
 pub use user::*;
+pub use backup::*;
+pub use user_token::*;
+pub use tenant::*;
+pub use sign_in::*;
+pub use alert::*;
+pub use sync_blob::*;
+pub use session_key::*;
+pub use analytics::*;
+pub use owned_name::*;
+pub use scheduled_transaction::*;
+pub use transfer_confirmation::*;
+pub use token_list::*;
+pub use audit::*;
+pub use jwt_key::*;
+pub use faucet::*;
+pub use send_template::*;
+pub use reconciliation::*;
+pub use note::*;
+pub use bucket::*;
+pub use watchlist::*;
+pub use feature_flag::*;
+pub use unlock_challenge::*;
+pub use siem::*;
+pub use bridge::*;
+pub use cross_chain::*;
+pub use cold_transaction::*;
+pub use cors_origin::*;
+pub use maintenance::*;
+pub use account_deletion::*;
+pub use price_alert::*;
+pub use status::*;
+pub use webhook::*;
+pub use share_link::*;
@@ -12,6 +12,10 @@ pub struct MultisigWalletRow {
     pub threshold: i64,
     pub owner_count: i64,
     pub created_at: String,
+    /// Primary account that paid for the deployment
+    pub sponsor_address: Option<String>,
+    /// Estimated deployment cost in base units, for sponsored deployments
+    pub deployment_cost: Option<String>,
 }
 
 impl MultisigWalletRow {
@@ -32,6 +36,8 @@ impl MultisigWalletRow {
             threshold: threshold as i64,
             owner_count: owner_count as i64,
             created_at: chrono::Utc::now().to_rfc3339(),
+            sponsor_address: None,
+            deployment_cost: None,
         }
     }
 }
@@ -66,6 +72,8 @@ pub struct MultisigTransactionRow {
     pub status: String,
     pub created_at: String,
     pub executed_at: Option<String>,
+    /// JSON signing payload, set on first export
+    pub signing_payload: Option<String>,
 }
 
 impl MultisigTransactionRow {
@@ -85,6 +93,28 @@ impl MultisigTransactionRow {
             status: "pending".to_string(),
             created_at: chrono::Utc::now().to_rfc3339(),
             executed_at: None,
+            signing_payload: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MultisigSignatureRow {
+    pub id: String,
+    pub transaction_id: String,
+    pub owner_address: String,
+    pub signature: String,
+    pub created_at: String,
+}
+
+impl MultisigSignatureRow {
+    pub fn new(transaction_id: String, owner_address: String, signature: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            transaction_id,
+            owner_address,
+            signature,
+            created_at: chrono::Utc::now().to_rfc3339(),
         }
     }
 }
@@ -100,6 +130,11 @@ pub struct MultisigWalletResponse {
     pub owner_count: u32,
     pub owners: Vec<MultisigOwnerResponse>,
     pub created_at: String,
+    /// Primary account that paid for the deployment; `None` when the
+    /// owners deploy it
+    pub sponsored_by: Option<String>,
+    /// Estimated deployment cost in base units, for sponsored deployments
+    pub deployment_cost: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,3 +172,20 @@ impl From<MultisigTransactionRow> for MultisigTransactionResponse {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultisigSignatureResponse {
+    pub owner_address: String,
+    pub signature: String,
+    pub created_at: String,
+}
+
+impl From<MultisigSignatureRow> for MultisigSignatureResponse {
+    fn from(row: MultisigSignatureRow) -> Self {
+        Self {
+            owner_address: row.owner_address,
+            signature: row.signature,
+            created_at: row.created_at,
+        }
+    }
+}
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::chains::{NftCollectionKey, NftRoyalties};
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct NftCacheRow {
     pub id: String,
@@ -15,6 +17,14 @@ pub struct NftCacheRow {
     pub metadata_json: Option<String>,
     pub collection_name: Option<String>,
     pub last_updated: String,
+    /// Marketplace escrow holding the NFT while it is listed
+    pub escrow_address: Option<String>,
+    /// URI the metadata was last read from
+    pub metadata_uri: Option<String>,
+    /// `NftRoyalties` as JSON, where the chain records them
+    pub royalties_json: Option<String>,
+    pub collection_address: Option<String>,
+    pub collection_verified: bool,
 }
 
 impl NftCacheRow {
@@ -41,8 +51,24 @@ impl NftCacheRow {
             metadata_json,
             collection_name,
             last_updated: chrono::Utc::now().to_rfc3339(),
+            escrow_address: None,
+            metadata_uri: None,
+            royalties_json: None,
+            collection_address: None,
+            collection_verified: false,
         }
     }
+
+    pub fn royalties(&self) -> Option<NftRoyalties> {
+        serde_json::from_str(self.royalties_json.as_deref()?).ok()
+    }
+
+    pub fn collection(&self) -> Option<NftCollectionKey> {
+        Some(NftCollectionKey {
+            address: self.collection_address.clone()?,
+            verified: self.collection_verified,
+        })
+    }
 }
 
 /// NFT response for API
@@ -57,6 +83,12 @@ pub struct NftResponse {
     pub image_url: Option<String>,
     pub collection_name: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    /// Listed on a marketplace, which holds it in escrow
+    pub listed: bool,
+    pub escrow_address: Option<String>,
+    pub royalties: Option<NftRoyalties>,
+    /// Collection recorded on chain; trust it only when `verified`
+    pub collection: Option<NftCollectionKey>,
 }
 
 impl From<NftCacheRow> for NftResponse {
@@ -65,6 +97,8 @@ impl From<NftCacheRow> for NftResponse {
             .metadata_json
             .as_ref()
             .and_then(|json| serde_json::from_str(json).ok());
+        let royalties = row.royalties();
+        let collection = row.collection();
 
         Self {
             id: row.id,
@@ -76,6 +110,10 @@ impl From<NftCacheRow> for NftResponse {
             image_url: row.image_url,
             collection_name: row.collection_name,
             metadata,
+            listed: row.escrow_address.is_some(),
+            escrow_address: row.escrow_address,
+            royalties,
+            collection,
         }
     }
 }
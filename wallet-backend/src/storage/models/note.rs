@@ -0,0 +1,28 @@
+//! Note database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NoteRow {
+    pub id: String,
+    pub user_id: String,
+    pub wallet_id: String,
+    /// `account`, `contact` or `transaction`
+    pub target_type: String,
+    pub target_id: String,
+    /// Sealed under the wallet's notes key
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Note response for API, with the body opened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteResponse {
+    pub id: String,
+    pub target_type: String,
+    pub target_id: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
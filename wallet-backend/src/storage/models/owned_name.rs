@@ -0,0 +1,83 @@
+//! Registered ENS / SNS name database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OwnedNameRow {
+    pub id: String,
+    pub account_id: String,
+    pub chain: String,
+    /// Full name, e.g. `alice.eth` or `alice.sol`
+    pub name: String,
+    /// `committed` (ENS, waiting to register) or `registered`
+    pub status: String,
+    /// Address the name points to
+    pub target_address: Option<String>,
+    /// Registration period, for names that expire
+    pub years: Option<i64>,
+    /// ENS commitment secret, kept until the name is registered
+    pub commitment_secret: Option<String>,
+    /// When the ENS commitment is old enough to register
+    pub ready_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub last_tx_hash: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl OwnedNameRow {
+    pub fn new(account_id: String, chain: String, name: String, status: String) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            account_id,
+            chain,
+            name,
+            status,
+            target_address: None,
+            years: None,
+            commitment_secret: None,
+            ready_at: None,
+            expires_at: None,
+            last_tx_hash: None,
+            created_at: now.clone(),
+            updated_at: now,
+        }
+    }
+}
+
+/// Owned name response for API; the commitment secret is never returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedNameResponse {
+    pub id: String,
+    pub account_id: String,
+    pub chain: String,
+    pub name: String,
+    pub status: String,
+    pub target_address: Option<String>,
+    pub years: Option<i64>,
+    pub ready_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub last_tx_hash: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<OwnedNameRow> for OwnedNameResponse {
+    fn from(row: OwnedNameRow) -> Self {
+        Self {
+            id: row.id,
+            account_id: row.account_id,
+            chain: row.chain,
+            name: row.name,
+            status: row.status,
+            target_address: row.target_address,
+            years: row.years,
+            ready_at: row.ready_at,
+            expires_at: row.expires_at,
+            last_tx_hash: row.last_tx_hash,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Price alert database models
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PriceAlertRow {
+    pub id: String,
+    pub user_id: String,
+    pub chain: String,
+    /// Empty for the chain's native coin
+    pub token_address: String,
+    pub symbol: String,
+    /// `price_above`, `price_below`, `rises_by` or `drops_by`
+    pub kind: String,
+    /// Price in `currency`, or a percentage for change rules
+    pub threshold: String,
+    /// Upper-case ISO 4217 code
+    pub currency: String,
+    /// Change rules: hours the change is measured over
+    pub window_hours: Option<i64>,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    pub triggered: bool,
+    pub last_price: Option<String>,
+    pub last_triggered_at: Option<String>,
+    pub created_at: String,
+}
+
+impl PriceAlertRow {
+    pub fn new(
+        user_id: String,
+        chain: String,
+        token_address: String,
+        symbol: String,
+        kind: String,
+        threshold: String,
+        currency: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            chain,
+            token_address,
+            symbol,
+            kind,
+            threshold,
+            currency,
+            window_hours: None,
+            webhook_url: None,
+            is_active: true,
+            triggered: false,
+            last_price: None,
+            last_triggered_at: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Price alert response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAlertResponse {
+    pub id: String,
+    pub chain: String,
+    /// `None` for the native coin
+    pub token_address: Option<String>,
+    pub symbol: String,
+    pub kind: String,
+    pub threshold: String,
+    pub currency: String,
+    pub window_hours: Option<i64>,
+    pub webhook_url: Option<String>,
+    pub is_active: bool,
+    /// The condition currently holds
+    pub triggered: bool,
+    /// Price at the last evaluation
+    pub last_price: Option<String>,
+    pub last_triggered_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<PriceAlertRow> for PriceAlertResponse {
+    fn from(row: PriceAlertRow) -> Self {
+        Self {
+            id: row.id,
+            chain: row.chain,
+            token_address: Some(row.token_address).filter(|a| !a.is_empty()),
+            symbol: row.symbol,
+            kind: row.kind,
+            threshold: row.threshold,
+            currency: row.currency,
+            window_hours: row.window_hours,
+            webhook_url: row.webhook_url,
+            is_active: row.is_active,
+            triggered: row.triggered,
+            last_price: row.last_price,
+            last_triggered_at: row.last_triggered_at,
+            created_at: row.created_at,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Balance reconciliation models
+
+use serde::{Deserialize, Serialize};
+
+/// One pass over every account
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ReconciliationRunRow {
+    pub id: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub accounts_checked: i64,
+    pub discrepancy_count: i64,
+}
+
+/// An account whose live balance didn't match what the wallet recorded
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BalanceDiscrepancyRow {
+    pub id: String,
+    pub run_id: String,
+    pub tenant_id: String,
+    pub account_id: String,
+    pub chain: String,
+    pub address: String,
+    /// `missed_incoming`, `unexplained_outgoing` or `sync_failed`
+    pub kind: String,
+    /// Last known balance before the run, in display units
+    pub cached_balance: Option<String>,
+    /// Last known balance adjusted by the history recorded since
+    pub expected_balance: Option<String>,
+    /// Balance read from the chain; `None` when the read failed
+    pub live_balance: Option<String>,
+    pub detail: String,
+    pub created_at: String,
+}
@@ -0,0 +1,112 @@
+//! Scheduled (time-locked) send database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ScheduledTransactionRow {
+    pub id: String,
+    pub account_id: String,
+    pub chain: String,
+    pub to_address: String,
+    /// Native or token amount, as it would be given to a send
+    pub amount: String,
+    pub token_address: Option<String>,
+    pub memo: Option<String>,
+    /// JSON array of Solana Pay reference keys
+    pub reference_keys: String,
+    /// Not broadcast before this time
+    pub execute_after: String,
+    /// Given up on if still unsent at this time
+    pub expires_at: Option<String>,
+    /// `scheduled`, `executing`, `sent`, `failed`, `cancelled` or `expired`
+    pub status: String,
+    pub tx_hash: Option<String>,
+    /// Why the last attempt failed, or is waiting
+    pub last_error: Option<String>,
+    pub executed_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl ScheduledTransactionRow {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        account_id: String,
+        chain: String,
+        to_address: String,
+        amount: String,
+        token_address: Option<String>,
+        memo: Option<String>,
+        references: &[String],
+        execute_after: String,
+        expires_at: Option<String>,
+    ) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            account_id,
+            chain,
+            to_address,
+            amount,
+            token_address,
+            memo,
+            reference_keys: serde_json::to_string(references).unwrap_or_default(),
+            execute_after,
+            expires_at,
+            status: "scheduled".to_string(),
+            tx_hash: None,
+            last_error: None,
+            executed_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        }
+    }
+
+    pub fn references(&self) -> Vec<String> {
+        serde_json::from_str(&self.reference_keys).unwrap_or_default()
+    }
+}
+
+/// Scheduled send response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTransactionResponse {
+    pub id: String,
+    pub account_id: String,
+    pub chain: String,
+    pub to_address: String,
+    pub amount: String,
+    pub token_address: Option<String>,
+    pub memo: Option<String>,
+    pub references: Vec<String>,
+    pub execute_after: String,
+    pub expires_at: Option<String>,
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub last_error: Option<String>,
+    pub executed_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<ScheduledTransactionRow> for ScheduledTransactionResponse {
+    fn from(row: ScheduledTransactionRow) -> Self {
+        Self {
+            references: row.references(),
+            id: row.id,
+            account_id: row.account_id,
+            chain: row.chain,
+            to_address: row.to_address,
+            amount: row.amount,
+            token_address: row.token_address,
+            memo: row.memo,
+            execute_after: row.execute_after,
+            expires_at: row.expires_at,
+            status: row.status,
+            tx_hash: row.tx_hash,
+            last_error: row.last_error,
+            executed_at: row.executed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Send template database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SendTemplateRow {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub chain: String,
+    pub from_address: Option<String>,
+    pub to_address: String,
+    /// As entered; empty when the draft leaves it to the user
+    pub amount: String,
+    pub token_address: Option<String>,
+    pub memo: Option<String>,
+    pub quick_action: bool,
+    pub use_count: i64,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Send template response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendTemplateResponse {
+    pub id: String,
+    pub name: String,
+    pub chain: String,
+    pub from_address: Option<String>,
+    pub to_address: String,
+    pub amount: String,
+    pub token_address: Option<String>,
+    pub memo: Option<String>,
+    pub quick_action: bool,
+    /// Times the template has been applied
+    pub use_count: u64,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<SendTemplateRow> for SendTemplateResponse {
+    fn from(row: SendTemplateRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            chain: row.chain,
+            from_address: row.from_address,
+            to_address: row.to_address,
+            amount: row.amount,
+            token_address: row.token_address,
+            memo: row.memo,
+            quick_action: row.quick_action,
+            use_count: row.use_count as u64,
+            last_used_at: row.last_used_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! dApp session key database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SessionKeyRow {
+    pub id: String,
+    pub user_id: String,
+    pub account_id: String,
+    /// Asset the session may send; `None` for the native coin
+    pub token_address: Option<String>,
+    /// Decimal amount of that asset allowed per transaction
+    pub max_amount: String,
+    /// JSON array of recipient contract / program addresses
+    pub allowed_targets: String,
+    /// Seed encrypted under the session key; `None` once revoked
+    pub encrypted_seed: Option<Vec<u8>>,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub use_count: i64,
+    pub created_at: String,
+}
+
+impl SessionKeyRow {
+    pub fn new(
+        user_id: String,
+        account_id: String,
+        token_address: Option<String>,
+        max_amount: String,
+        allowed_targets: &[String],
+        encrypted_seed: Vec<u8>,
+        expires_at: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            account_id,
+            token_address,
+            max_amount,
+            allowed_targets: serde_json::to_string(allowed_targets).unwrap_or_default(),
+            encrypted_seed: Some(encrypted_seed),
+            expires_at,
+            revoked_at: None,
+            last_used_at: None,
+            use_count: 0,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn allowed_targets(&self) -> Vec<String> {
+        serde_json::from_str(&self.allowed_targets).unwrap_or_default()
+    }
+}
+
+/// Session key response for API; never includes the key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionKeyResponse {
+    pub id: String,
+    pub account_id: String,
+    pub token_address: Option<String>,
+    pub max_amount: String,
+    pub allowed_targets: Vec<String>,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub use_count: i64,
+    pub created_at: String,
+}
+
+impl From<SessionKeyRow> for SessionKeyResponse {
+    fn from(row: SessionKeyRow) -> Self {
+        Self {
+            allowed_targets: row.allowed_targets(),
+            id: row.id,
+            account_id: row.account_id,
+            token_address: row.token_address,
+            max_amount: row.max_amount,
+            expires_at: row.expires_at,
+            revoked_at: row.revoked_at,
+            last_used_at: row.last_used_at,
+            use_count: row.use_count,
+            created_at: row.created_at,
+        }
+    }
+}
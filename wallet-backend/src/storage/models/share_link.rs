@@ -0,0 +1,72 @@
+//! Share link models
+
+use serde::{Deserialize, Serialize};
+
+/// A read-only link to one resource
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ShareLinkRow {
+    pub id: String,
+    pub tenant_id: String,
+    pub user_id: String,
+    /// `account` or `multisig`
+    pub scope: String,
+    /// Account or multi-sig ID
+    pub resource_id: String,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+impl ShareLinkRow {
+    pub fn new(
+        tenant_id: String,
+        user_id: String,
+        scope: String,
+        resource_id: String,
+        expires_at: String,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tenant_id,
+            user_id,
+            scope,
+            resource_id,
+            expires_at,
+            revoked_at: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Share link response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareLinkResponse {
+    pub id: String,
+    pub scope: String,
+    pub resource_id: String,
+    /// Only returned when the link is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Path of the shared view, with the token; only returned when the link
+    /// is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<ShareLinkRow> for ShareLinkResponse {
+    fn from(row: ShareLinkRow) -> Self {
+        Self {
+            id: row.id,
+            scope: row.scope,
+            resource_id: row.resource_id,
+            token: None,
+            url: None,
+            expires_at: row.expires_at,
+            revoked_at: row.revoked_at,
+            created_at: row.created_at,
+        }
+    }
+}
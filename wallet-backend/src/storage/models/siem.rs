@@ -0,0 +1,112 @@
+//! SIEM outbox model
+
+use serde::{Deserialize, Serialize};
+
+use super::{AuditEventRow, AuditSeverity, TransactionRow};
+
+/// An event waiting to be exported
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SiemEventRow {
+    pub id: String,
+    pub tenant_id: String,
+    /// `audit` or `transaction`
+    pub source: String,
+    pub event: String,
+    pub severity: String,
+    /// Structured JSON event as exported
+    pub payload: String,
+    /// Failed delivery attempts so far
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub next_attempt_at: String,
+    pub created_at: String,
+}
+
+impl SiemEventRow {
+    fn new(
+        tenant_id: String,
+        source: &str,
+        event: &str,
+        severity: AuditSeverity,
+        fields: serde_json::Value,
+    ) -> Self {
+        let id = uuid::Uuid::new_v4().to_string();
+        let created_at = chrono::Utc::now().to_rfc3339();
+        let mut payload = serde_json::json!({
+            "id": id,
+            "time": created_at,
+            "source": source,
+            "tenant_id": tenant_id,
+            "event": event,
+            "severity": severity.as_str(),
+        });
+        if let (Some(payload), serde_json::Value::Object(fields)) = (payload.as_object_mut(), fields) {
+            payload.extend(fields);
+        }
+
+        Self {
+            id,
+            tenant_id,
+            source: source.to_string(),
+            event: event.to_string(),
+            severity: severity.as_str().to_string(),
+            payload: payload.to_string(),
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: created_at.clone(),
+            created_at,
+        }
+    }
+
+    pub fn audit(audit: &AuditEventRow) -> Self {
+        let severity = audit.severity.parse().unwrap_or(AuditSeverity::Info);
+        let details: serde_json::Value =
+            serde_json::from_str(&audit.details).unwrap_or(serde_json::Value::Null);
+        Self::new(
+            audit.tenant_id.clone(),
+            "audit",
+            &audit.event,
+            severity,
+            serde_json::json!({
+                "audit_id": audit.id,
+                "user_id": audit.user_id,
+                "details": details,
+            }),
+        )
+    }
+
+    /// A transaction recorded or settled; failures are warnings. Memos are
+    /// left out.
+    pub fn transaction(tenant_id: String, event: &str, tx: &TransactionRow) -> Self {
+        let severity = if tx.status == "failed" {
+            AuditSeverity::Warning
+        } else {
+            AuditSeverity::Info
+        };
+        Self::new(
+            tenant_id,
+            "transaction",
+            event,
+            severity,
+            serde_json::json!({
+                "transaction": {
+                    "id": tx.id,
+                    "account_id": tx.account_id,
+                    "chain": tx.chain,
+                    "signature": tx.signature,
+                    "tx_type": tx.tx_type,
+                    "from_address": tx.from_address,
+                    "to_address": tx.to_address,
+                    "amount": tx.amount,
+                    "token_address": tx.token_address,
+                    "status": tx.status,
+                    "block_number": tx.block_number,
+                },
+            }),
+        )
+    }
+
+    pub fn severity(&self) -> AuditSeverity {
+        self.severity.parse().unwrap_or(AuditSeverity::Info)
+    }
+}
@@ -0,0 +1,77 @@
+//! Wallet sign-in challenge and linked address database models
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SignInChallengeRow {
+    pub nonce: String,
+    pub tenant_id: String,
+    pub chain: String,
+    pub address: String,
+    /// Exact text the wallet must sign
+    pub message: String,
+    pub expires_at: String,
+    pub created_at: String,
+}
+
+impl SignInChallengeRow {
+    pub fn new(
+        nonce: String,
+        tenant_id: String,
+        chain: String,
+        address: String,
+        message: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            nonce,
+            tenant_id,
+            chain,
+            address,
+            message,
+            expires_at: expires_at.to_rfc3339(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserAddressRow {
+    pub id: String,
+    pub user_id: String,
+    pub chain: String,
+    pub address: String,
+    pub created_at: String,
+}
+
+impl UserAddressRow {
+    pub fn new(user_id: String, chain: String, address: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            chain,
+            address,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Linked address response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserAddressResponse {
+    pub id: String,
+    pub chain: String,
+    pub address: String,
+    pub created_at: String,
+}
+
+impl From<UserAddressRow> for UserAddressResponse {
+    fn from(row: UserAddressRow) -> Self {
+        Self {
+            id: row.id,
+            chain: row.chain,
+            address: row.address,
+            created_at: row.created_at,
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Operator status models
+
+use serde::{Deserialize, Serialize};
+
+/// Work waiting on background jobs, across tenants
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct QueueDepths {
+    /// Sends the confirmation tracker is still polling
+    pub pending_confirmations: i64,
+    /// Pending sends flagged as stuck
+    pub stuck_transactions: i64,
+    /// Scheduled sends not yet executed
+    pub scheduled_transactions: i64,
+    /// Events awaiting SIEM export
+    pub siem_outbox: i64,
+    /// Bridge transfers not yet finalized
+    pub open_bridge_transfers: i64,
+    /// Cross-chain swaps not yet settled
+    pub open_cross_chain_swaps: i64,
+}
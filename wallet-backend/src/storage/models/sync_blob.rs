@@ -0,0 +1,31 @@
+//! Encrypted sync blob database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SyncBlobRow {
+    pub user_id: String,
+    pub version: i64,
+    /// Client-encrypted; never inspected by the server
+    pub data: Vec<u8>,
+    pub updated_at: String,
+}
+
+/// Sync blob metadata for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncBlobResponse {
+    pub version: i64,
+    /// Blob size in bytes
+    pub size: usize,
+    pub updated_at: String,
+}
+
+impl From<&SyncBlobRow> for SyncBlobResponse {
+    fn from(row: &SyncBlobRow) -> Self {
+        Self {
+            version: row.version,
+            size: row.data.len(),
+            updated_at: row.updated_at.clone(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Tenant database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TenantRow {
+    pub id: String,
+    pub name: String,
+    pub hostname: Option<String>,
+    pub api_key_hash: Option<String>,
+    pub solana_rpc_url: Option<String>,
+    pub eth_rpc_url: Option<String>,
+    pub rate_limit_max_requests: Option<i64>,
+    pub rate_limit_window_secs: Option<i64>,
+    /// JSON object
+    pub settings: String,
+    pub is_active: bool,
+    pub created_at: String,
+}
+
+impl TenantRow {
+    pub fn new(id: String, name: String, hostname: Option<String>, api_key_hash: String) -> Self {
+        Self {
+            id,
+            name,
+            hostname,
+            api_key_hash: Some(api_key_hash),
+            solana_rpc_url: None,
+            eth_rpc_url: None,
+            rate_limit_max_requests: None,
+            rate_limit_window_secs: None,
+            settings: "{}".to_string(),
+            is_active: true,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn settings(&self) -> serde_json::Value {
+        serde_json::from_str(&self.settings).unwrap_or_else(|_| serde_json::json!({}))
+    }
+}
+
+/// Tenant response for the administration API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantResponse {
+    pub id: String,
+    pub name: String,
+    pub hostname: Option<String>,
+    pub has_api_key: bool,
+    pub solana_rpc_url: Option<String>,
+    pub eth_rpc_url: Option<String>,
+    pub rate_limit_max_requests: Option<u32>,
+    pub rate_limit_window_secs: Option<u64>,
+    pub settings: serde_json::Value,
+    pub is_active: bool,
+    pub created_at: String,
+}
+
+impl From<TenantRow> for TenantResponse {
+    fn from(row: TenantRow) -> Self {
+        Self {
+            settings: row.settings(),
+            id: row.id,
+            name: row.name,
+            hostname: row.hostname,
+            has_api_key: row.api_key_hash.is_some(),
+            solana_rpc_url: row.solana_rpc_url,
+            eth_rpc_url: row.eth_rpc_url,
+            rate_limit_max_requests: row.rate_limit_max_requests.map(|n| n as u32),
+            rate_limit_window_secs: row.rate_limit_window_secs.map(|n| n as u64),
+            is_active: row.is_active,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// What a tenant's own clients may see about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantPublic {
+    pub id: String,
+    pub name: String,
+    pub settings: serde_json::Value,
+}
+
+impl From<TenantRow> for TenantPublic {
+    fn from(row: TenantRow) -> Self {
+        Self {
+            settings: row.settings(),
+            id: row.id,
+            name: row.name,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Token list model
+
+use serde::{Deserialize, Serialize};
+
+/// A token from the published token list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TokenListRow {
+    pub chain: String,
+    /// Mint or contract address
+    pub address: String,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: i64,
+    pub logo_uri: Option<String>,
+    pub updated_at: String,
+}
@@ -2,6 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::chains::{Broadcast, TxEffects, TxReceipt};
+use crate::core::{parse_units, Chain};
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct TransactionRow {
     pub id: String,
@@ -17,6 +20,47 @@ pub struct TransactionRow {
     pub block_number: Option<i64>,
     pub timestamp: Option<String>,
     pub created_at: String,
+    /// Set when the amount was entered in fiat
+    pub fiat_amount: Option<String>,
+    pub fiat_currency: Option<String>,
+    /// Fiat per native coin at send time
+    pub fiat_rate: Option<String>,
+    /// Token within the `token_address` collection, for NFT events
+    pub token_id: Option<String>,
+    /// JSON `TxEffects` simulated before broadcast
+    pub expected_changes: Option<String>,
+    /// JSON `TxEffects` observed once the transaction landed
+    pub actual_changes: Option<String>,
+    /// Whether the observed effects differed from the simulated ones
+    pub effects_mismatch: Option<bool>,
+    /// JSON `Broadcast` recorded when the transaction was sent
+    pub broadcast: Option<String>,
+    /// When the monitor flagged the transaction as stuck
+    pub stuck_at: Option<String>,
+    /// Hash of the speed-up sent in its place
+    pub replaced_by: Option<String>,
+    /// Fiat per token on the day of the transaction
+    pub price_at_tx: Option<String>,
+    pub price_currency: Option<String>,
+    /// When the historical price was looked up
+    pub priced_at: Option<String>,
+    /// Memo sent with the transfer
+    pub memo: Option<String>,
+    /// Blocks on top of the transaction's, as of the tracker's last check
+    pub confirmations: Option<i64>,
+    /// Receipt fields of a settled Ethereum send
+    pub gas_used: Option<i64>,
+    pub effective_gas_price: Option<String>,
+    /// 1 for success, 0 for a revert
+    pub receipt_status: Option<i64>,
+    pub logs_count: Option<i64>,
+    pub contract_address: Option<String>,
+    /// Fee the settled transaction paid, in base units
+    pub fee_paid: Option<String>,
+    /// Cross-chain swap this is one side of, shared by both its entries
+    pub route_id: Option<String>,
+    /// Decimals of the token `amount` is given in
+    pub token_decimals: Option<i64>,
 }
 
 impl TransactionRow {
@@ -47,8 +91,90 @@ impl TransactionRow {
             block_number,
             timestamp,
             created_at: chrono::Utc::now().to_rfc3339(),
+            fiat_amount: None,
+            fiat_currency: None,
+            fiat_rate: None,
+            token_id: None,
+            expected_changes: None,
+            actual_changes: None,
+            effects_mismatch: None,
+            broadcast: None,
+            stuck_at: None,
+            replaced_by: None,
+            price_at_tx: None,
+            price_currency: None,
+            priced_at: None,
+            memo: None,
+            confirmations: None,
+            gas_used: None,
+            effective_gas_price: None,
+            receipt_status: None,
+            logs_count: None,
+            contract_address: None,
+            fee_paid: None,
+            route_id: None,
+            token_decimals: None,
         }
     }
+
+    pub fn expected_effects(&self) -> Option<TxEffects> {
+        parse_effects(self.expected_changes.as_deref())
+    }
+
+    pub fn actual_effects(&self) -> Option<TxEffects> {
+        parse_effects(self.actual_changes.as_deref())
+    }
+
+    pub fn broadcast_info(&self) -> Option<Broadcast> {
+        serde_json::from_str(self.broadcast.as_deref()?).ok()
+    }
+
+    pub fn receipt(&self) -> Option<TxReceipt> {
+        Some(TxReceipt {
+            gas_used: self.gas_used?.try_into().ok()?,
+            effective_gas_price: self.effective_gas_price.clone()?,
+            succeeded: self.receipt_status? == 1,
+            logs_count: self.logs_count?.try_into().ok()?,
+            contract_address: self.contract_address.clone(),
+        })
+    }
+
+    /// The amount in base units: lamports or wei, or the token's when its
+    /// decimals were recorded
+    pub fn amount_raw(&self) -> Option<String> {
+        let amount = self.amount.as_deref()?;
+        match (&self.token_address, self.token_decimals) {
+            (Some(_), Some(decimals)) => {
+                parse_units(amount, decimals.try_into().ok()?).map(|units| units.to_string())
+            }
+            _ => native_amount_raw(&self.chain, amount, self.token_address.as_deref()),
+        }
+    }
+
+    /// Fiat value of the amount at the historical price
+    pub fn realized_value(&self) -> Option<String> {
+        realized_value(self.amount.as_deref()?, self.price_at_tx.as_deref()?)
+    }
+}
+
+fn parse_effects(json: Option<&str>) -> Option<TxEffects> {
+    serde_json::from_str(json?).ok()
+}
+
+/// A native `amount` on `chain` in base units; `None` for token transfers
+/// and amounts that aren't exact decimals
+pub fn native_amount_raw(chain: &str, amount: &str, token_address: Option<&str>) -> Option<String> {
+    if token_address.is_some() {
+        return None;
+    }
+    let chain: Chain = chain.parse().ok()?;
+    parse_units(amount, chain.native_decimals()).map(|units| units.to_string())
+}
+
+/// `amount * price`, rounded to cents
+pub fn realized_value(amount: &str, price: &str) -> Option<String> {
+    let value = amount.parse::<f64>().ok()? * price.parse::<f64>().ok()?;
+    value.is_finite().then(|| format!("{:.2}", value))
 }
 
 /// Transaction response for API
@@ -60,16 +186,60 @@ pub struct TransactionResponse {
     pub tx_type: String,
     pub from_address: Option<String>,
     pub to_address: Option<String>,
+    /// Amount as a decimal string
     pub amount: Option<String>,
+    /// Native amounts in base units (lamports, wei)
+    pub amount_raw: Option<String>,
     pub token_address: Option<String>,
     pub status: String,
     pub block_number: Option<i64>,
     pub timestamp: Option<String>,
+    pub fiat_amount: Option<String>,
+    pub fiat_currency: Option<String>,
+    pub fiat_rate: Option<String>,
+    pub token_id: Option<String>,
+    /// Balance changes anticipated before broadcast
+    pub expected_changes: Option<TxEffects>,
+    /// Balance changes once confirmed
+    pub actual_changes: Option<TxEffects>,
+    pub effects_mismatch: Option<bool>,
+    pub stuck_at: Option<String>,
+    pub replaced_by: Option<String>,
+    /// Fiat per token on the day of the transaction
+    pub price_at_tx: Option<String>,
+    pub price_currency: Option<String>,
+    /// Fiat value of the amount at that price
+    pub realized_value: Option<String>,
+    pub memo: Option<String>,
+    /// Confirmations seen by the tracker; sends stay pending until the
+    /// chain's threshold is reached
+    pub confirmations: Option<i64>,
+    /// Receipt of a settled Ethereum send
+    pub receipt: Option<TxReceipt>,
+    /// Fee actually paid once settled, in base units
+    pub fee_paid: Option<String>,
+    /// Cross-chain swap this is one side of; the other side has the same ID
+    pub route_id: Option<String>,
+    /// Contact or account name of the other side; set in account history
+    pub counterparty_label: Option<String>,
+    /// The other side is another of the wallet's accounts; set in account
+    /// history
+    pub is_own_account: Option<bool>,
+    /// Symbol of the token moved as the user sees it; set in account history
+    pub token_symbol: Option<String>,
+    /// Icon of the token moved; set in account history
+    pub token_logo_uri: Option<String>,
 }
 
 impl From<TransactionRow> for TransactionResponse {
     fn from(row: TransactionRow) -> Self {
         Self {
+            expected_changes: row.expected_effects(),
+            actual_changes: row.actual_effects(),
+            realized_value: row.realized_value(),
+            amount_raw: row.amount_raw(),
+            receipt: row.receipt(),
+            effects_mismatch: row.effects_mismatch,
             id: row.id,
             chain: row.chain,
             signature: row.signature,
@@ -81,6 +251,22 @@ impl From<TransactionRow> for TransactionResponse {
             status: row.status,
             block_number: row.block_number,
             timestamp: row.timestamp,
+            fiat_amount: row.fiat_amount,
+            fiat_currency: row.fiat_currency,
+            fiat_rate: row.fiat_rate,
+            token_id: row.token_id,
+            stuck_at: row.stuck_at,
+            replaced_by: row.replaced_by,
+            price_at_tx: row.price_at_tx,
+            price_currency: row.price_currency,
+            memo: row.memo,
+            confirmations: row.confirmations,
+            fee_paid: row.fee_paid,
+            route_id: row.route_id,
+            counterparty_label: None,
+            is_own_account: None,
+            token_symbol: None,
+            token_logo_uri: None,
         }
     }
 }
@@ -0,0 +1,41 @@
+//! Large transfer threshold and send challenge database models
+
+use serde::{Deserialize, Serialize};
+
+/// Sends worth more than `threshold` need the password re-entered
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LargeTransferThresholdRow {
+    #[serde(skip_serializing)]
+    pub user_id: String,
+    /// Decimal amount in `currency`
+    pub threshold: String,
+    /// Upper-case ISO 4217 code
+    pub currency: String,
+    pub updated_at: String,
+}
+
+/// A large send held until it is confirmed
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SendChallengeRow {
+    pub id: String,
+    pub user_id: String,
+    /// The held send request, as JSON
+    pub request: String,
+    /// Wrong passwords entered so far
+    pub attempts: i64,
+    pub expires_at: String,
+    pub created_at: String,
+}
+
+impl SendChallengeRow {
+    pub fn new(user_id: String, request: String, expires_at: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            request,
+            attempts: 0,
+            expires_at,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
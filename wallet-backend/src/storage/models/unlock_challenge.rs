@@ -0,0 +1,34 @@
+//! Unlock challenge database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UnlockChallengeRow {
+    pub nonce: String,
+    pub tenant_id: String,
+    /// Client the nonce was issued to
+    pub ip_address: Option<String>,
+    pub expires_at: String,
+    /// Set once an unlock request has used the nonce
+    pub redeemed_at: Option<String>,
+    pub created_at: String,
+}
+
+impl UnlockChallengeRow {
+    pub fn new(
+        nonce: String,
+        tenant_id: String,
+        ip_address: Option<String>,
+        created_at: chrono::DateTime<chrono::Utc>,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Self {
+        Self {
+            nonce,
+            tenant_id,
+            ip_address,
+            expires_at: expires_at.to_rfc3339(),
+            redeemed_at: None,
+            created_at: created_at.to_rfc3339(),
+        }
+    }
+}
@@ -14,6 +14,9 @@ pub struct User {
     pub last_login_at: Option<String>,
     pub is_active: bool,
     pub email_verified: bool,
+    pub two_factor_enabled: bool,
+    pub password_changed_at: Option<String>,
+    pub tenant_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,10 +25,59 @@ pub struct CreateUserRequest {
     pub password: String,
 }
 
+/// What an access token may be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Balances, history and the user's own settings; always granted
+    Read,
+    /// Sending, swapping and other actions that sign with the wallet
+    Trade,
+    /// Account security: password, linked addresses, logging out everywhere
+    Admin,
+}
+
+impl Scope {
+    /// Every scope, as granted when a login doesn't ask for fewer
+    pub fn all() -> Vec<Scope> {
+        vec![Scope::Read, Scope::Trade, Scope::Admin]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Trade => "trade",
+            Scope::Admin => "admin",
+        }
+    }
+
+    /// Parse a session's comma-separated scopes, skipping unknown ones
+    pub fn parse_list(scopes: &str) -> Vec<Scope> {
+        scopes
+            .split(',')
+            .filter_map(|s| match s.trim() {
+                "read" => Some(Scope::Read),
+                "trade" => Some(Scope::Trade),
+                "admin" => Some(Scope::Admin),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Comma-separated form stored on the session
+    pub fn join(scopes: &[Scope]) -> String {
+        scopes.iter().map(Scope::as_str).collect::<Vec<_>>().join(",")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
+    /// Scopes to limit the session to, e.g. `["read"]` for a watch-only
+    /// device; all of them when absent. `read` is always included.
+    #[serde(default)]
+    pub scopes: Option<Vec<Scope>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,9 +85,30 @@ pub struct LoginResponse {
     pub access_token: String,
     pub token_type: String,
     pub expires_in: i64,
+    pub scopes: Vec<Scope>,
     pub user: UserPublic,
 }
 
+/// Returned with 202 instead of tokens when a login needs step-up
+/// verification; the link to complete it has been emailed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginStepUpResponse {
+    pub step_up_required: bool,
+    pub challenge_id: String,
+    pub risk_score: u8,
+    /// What made the login look unusual, e.g. `new_device`
+    pub signals: Vec<String>,
+    /// Seconds until the link expires
+    pub expires_in: i64,
+}
+
+/// Challenge and token from a step-up verification link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyLoginRequest {
+    pub challenge_id: String,
+    pub token: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPublic {
     pub id: String,
@@ -65,6 +138,14 @@ pub struct UserSession {
     pub created_at: String,
     pub expires_at: String,
     pub revoked_at: Option<String>,
+    /// Comma-separated scopes its access tokens carry
+    pub scopes: String,
+    /// SHA-256 of the device fingerprint the session was opened from
+    pub device_fingerprint: Option<String>,
+    /// ISO country code reported by the trusted proxy
+    pub country: Option<String>,
+    /// Risk score (0-100) of the login that opened it
+    pub risk_score: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,12 +160,57 @@ pub struct RefreshTokenResponse {
     pub expires_in: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningTokenRequest {
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningTokenResponse {
+    pub signing_token: String,
+    pub expires_in: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChangePasswordRequest {
     pub current_password: String,
     pub new_password: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OAuthState {
+    pub state: String,
+    pub tenant_id: String,
+    pub provider: String,
+    pub code_verifier: String,
+    pub expires_at: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserIdentity {
+    pub id: String,
+    pub user_id: String,
+    pub provider: String,
+    /// Provider's stable account ID
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthAuthorizeResponse {
+    /// Provider page to send the user to
+    pub authorization_url: String,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthCallbackRequest {
+    pub code: String,
+    pub state: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ConnectedDapp {
     pub id: String,
@@ -0,0 +1,87 @@
+//! User-tracked token database model
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserTokenRow {
+    pub id: String,
+    pub user_id: String,
+    pub chain: String,
+    pub token_address: String,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub decimals: i64,
+    pub hidden: bool,
+    pub created_at: String,
+    /// Shown instead of the token list's logo
+    pub icon_url: Option<String>,
+}
+
+impl UserTokenRow {
+    pub fn new(
+        user_id: String,
+        chain: String,
+        token_address: String,
+        symbol: String,
+        name: Option<String>,
+        decimals: u8,
+        hidden: bool,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            chain,
+            token_address,
+            symbol,
+            name,
+            decimals: decimals as i64,
+            hidden,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            icon_url: None,
+        }
+    }
+}
+
+/// User token response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserTokenResponse {
+    pub id: String,
+    pub chain: String,
+    pub token_address: String,
+    pub symbol: String,
+    pub name: Option<String>,
+    pub decimals: u8,
+    pub hidden: bool,
+    pub icon_url: Option<String>,
+    pub created_at: String,
+}
+
+impl From<UserTokenRow> for UserTokenResponse {
+    fn from(row: UserTokenRow) -> Self {
+        Self {
+            id: row.id,
+            chain: row.chain,
+            token_address: row.token_address,
+            symbol: row.symbol,
+            name: row.name,
+            decimals: row.decimals as u8,
+            hidden: row.hidden,
+            icon_url: row.icon_url,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// How a user's token balances are listed
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TokenViewSettingsRow {
+    #[serde(skip_serializing)]
+    pub user_id: String,
+    /// `value`, `name` or `activity`; `None` keeps the chain's order
+    pub sort: Option<String>,
+    /// Tokens worth less, in `currency`, are folded into "others"
+    pub small_balance_threshold: Option<String>,
+    /// Upper-case ISO 4217 code
+    pub currency: String,
+    pub updated_at: String,
+}
@@ -9,6 +9,12 @@ pub struct WalletRow {
     pub salt: Vec<u8>,
     pub nonce: Vec<u8>,
     pub created_at: String,
+    pub encrypted_entropy: Option<Vec<u8>>,
+    pub mnemonic_word_count: Option<i64>,
+    pub backup_confirmed_at: Option<String>,
+    pub mnemonic_language: String,
+    pub keyfile_required: bool,
+    pub tenant_id: String,
 }
 
 impl WalletRow {
@@ -17,6 +23,9 @@ impl WalletRow {
         encrypted_seed: Vec<u8>,
         salt: Vec<u8>,
         nonce: Vec<u8>,
+        encrypted_entropy: Option<Vec<u8>>,
+        mnemonic_word_count: Option<u32>,
+        mnemonic_language: String,
     ) -> Self {
         Self {
             id,
@@ -24,6 +33,12 @@ impl WalletRow {
             salt,
             nonce,
             created_at: chrono::Utc::now().to_rfc3339(),
+            encrypted_entropy,
+            mnemonic_word_count: mnemonic_word_count.map(|c| c as i64),
+            backup_confirmed_at: None,
+            mnemonic_language,
+            keyfile_required: false,
+            tenant_id: "default".to_string(),
         }
     }
 }
@@ -0,0 +1,140 @@
+//! Watchlist database models
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WatchRow {
+    pub id: String,
+    pub user_id: String,
+    /// Tenant whose RPC endpoints poll the address
+    pub tenant_id: String,
+    pub chain: String,
+    pub address: String,
+    pub label: Option<String>,
+    /// Decimal amount in the native coin
+    pub threshold: Option<String>,
+    pub webhook_url: Option<String>,
+    /// Native balance at the last poll
+    pub last_balance: Option<String>,
+    /// Newest transaction seen at the last poll
+    pub last_signature: Option<String>,
+    pub last_checked_at: Option<String>,
+    pub created_at: String,
+}
+
+impl WatchRow {
+    pub fn new(
+        user_id: String,
+        tenant_id: String,
+        chain: String,
+        address: String,
+        label: Option<String>,
+        threshold: Option<String>,
+        webhook_url: Option<String>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            tenant_id,
+            chain,
+            address,
+            label,
+            threshold,
+            webhook_url,
+            last_balance: None,
+            last_signature: None,
+            last_checked_at: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WatchActivityRow {
+    pub id: String,
+    pub watch_id: String,
+    /// `balance_change` or `transaction`
+    pub kind: String,
+    /// Signed change of the native balance
+    pub amount: Option<String>,
+    pub balance: Option<String>,
+    pub signature: Option<String>,
+    pub status: Option<String>,
+    /// Unix timestamp of the block the transaction landed in
+    pub block_time: Option<i64>,
+    /// The movement met the entry's threshold
+    pub notified: bool,
+    /// `delivered` or `failed`; `None` without a webhook
+    pub webhook_status: Option<String>,
+    pub detected_at: String,
+}
+
+impl WatchActivityRow {
+    pub fn balance_change(watch_id: String, amount: String, balance: String) -> Self {
+        Self {
+            amount: Some(amount),
+            balance: Some(balance),
+            ..Self::new(watch_id, "balance_change")
+        }
+    }
+
+    pub fn transaction(
+        watch_id: String,
+        signature: String,
+        status: String,
+        block_time: Option<i64>,
+    ) -> Self {
+        Self {
+            signature: Some(signature),
+            status: Some(status),
+            block_time,
+            ..Self::new(watch_id, "transaction")
+        }
+    }
+
+    fn new(watch_id: String, kind: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            watch_id,
+            kind: kind.to_string(),
+            amount: None,
+            balance: None,
+            signature: None,
+            status: None,
+            block_time: None,
+            notified: false,
+            webhook_status: None,
+            detected_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Watchlist activity response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchActivityResponse {
+    pub kind: String,
+    pub amount: Option<String>,
+    pub balance: Option<String>,
+    pub signature: Option<String>,
+    pub status: Option<String>,
+    pub block_time: Option<i64>,
+    pub notified: bool,
+    pub webhook_status: Option<String>,
+    pub detected_at: String,
+}
+
+impl From<WatchActivityRow> for WatchActivityResponse {
+    fn from(row: WatchActivityRow) -> Self {
+        Self {
+            kind: row.kind,
+            amount: row.amount,
+            balance: row.balance,
+            signature: row.signature,
+            status: row.status,
+            block_time: row.block_time,
+            notified: row.notified,
+            webhook_status: row.webhook_status,
+            detected_at: row.detected_at,
+        }
+    }
+}
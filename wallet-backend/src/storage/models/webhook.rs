@@ -0,0 +1,86 @@
+//! Webhook delivery models
+
+use serde::{Deserialize, Serialize};
+
+/// One POST to a webhook and how it went
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookDeliveryRow {
+    /// Sent as `X-Valtix-Delivery-Id`
+    pub id: String,
+    pub tenant_id: String,
+    /// Alert, price alert, watchlist entry or bridge transfer
+    pub webhook_id: String,
+    /// Sent as `X-Valtix-Event-Id`
+    pub event_id: String,
+    pub event_type: String,
+    pub url: String,
+    pub test: bool,
+    /// `None` when no response arrived
+    pub status_code: Option<i64>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: String,
+}
+
+impl WebhookDeliveryRow {
+    pub fn new(
+        tenant_id: String,
+        webhook_id: String,
+        event_id: String,
+        event_type: String,
+        url: String,
+        test: bool,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tenant_id,
+            webhook_id,
+            event_id,
+            event_type,
+            url,
+            test,
+            status_code: None,
+            error: None,
+            duration_ms: 0,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// The receiver answered with a 2xx status
+    pub fn delivered(&self) -> bool {
+        self.status_code.is_some_and(|code| (200..300).contains(&code))
+    }
+}
+
+/// Webhook delivery response for API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDeliveryResponse {
+    pub delivery_id: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub url: String,
+    pub test: bool,
+    /// `delivered` or `failed`
+    pub status: String,
+    pub status_code: Option<i64>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub created_at: String,
+}
+
+impl From<WebhookDeliveryRow> for WebhookDeliveryResponse {
+    fn from(row: WebhookDeliveryRow) -> Self {
+        Self {
+            status: if row.delivered() { "delivered" } else { "failed" }.to_string(),
+            delivery_id: row.id,
+            event_id: row.event_id,
+            event_type: row.event_type,
+            url: row.url,
+            test: row.test,
+            status_code: row.status_code,
+            error: row.error,
+            duration_ms: row.duration_ms,
+            created_at: row.created_at,
+        }
+    }
+}
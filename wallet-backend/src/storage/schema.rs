@@ -0,0 +1,155 @@
+//! Schema version checks
+//!
+//! Compares the migrations applied to a database with the ones built into
+//! this binary. A database migrated by a newer release is ahead of the
+//! binary: its queries may not match the schema, so the server runs in safe
+//! mode and refuses writes rather than risk corrupting it.
+
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use sqlx::{Pool, Sqlite};
+
+/// Migrations built into this binary
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// A migration known to one side only
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+}
+
+/// How a database's migrations compare with the binary's
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SchemaStatus {
+    /// Latest migration applied to the database; 0 when none are
+    pub version: i64,
+    /// Latest migration this binary ships
+    pub expected_version: i64,
+    /// Built in but not yet applied
+    pub pending: Vec<MigrationInfo>,
+    /// Applied by a binary that knows migrations this one doesn't
+    pub unknown: Vec<MigrationInfo>,
+    /// Applied, but changed in this binary since
+    pub modified: Vec<i64>,
+    /// Started but never finished
+    pub failed: Vec<i64>,
+}
+
+impl SchemaStatus {
+    /// The database has migrations this binary doesn't know
+    pub fn is_ahead(&self) -> bool {
+        !self.unknown.is_empty()
+    }
+
+    /// Applied migrations disagree with the binary's, so running the rest
+    /// would fail
+    pub fn has_drift(&self) -> bool {
+        !self.modified.is_empty() || !self.failed.is_empty()
+    }
+}
+
+/// Compare the migrations applied to `pool` with `migrator`'s
+pub async fn schema_status(
+    pool: &Pool<Sqlite>,
+    migrator: &Migrator,
+) -> Result<SchemaStatus, sqlx::Error> {
+    // A fresh database has no migrations table yet
+    let (has_table,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(pool)
+    .await?;
+    let applied: Vec<(i64, String, bool, Vec<u8>)> = if has_table {
+        sqlx::query_as(
+            "SELECT version, description, success, checksum FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    let known: Vec<_> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .collect();
+
+    let mut status = SchemaStatus {
+        version: applied.iter().map(|(version, ..)| *version).max().unwrap_or(0),
+        expected_version: known.iter().map(|m| m.version).max().unwrap_or(0),
+        ..Default::default()
+    };
+    for (version, description, success, checksum) in &applied {
+        match known.iter().find(|m| m.version == *version) {
+            None => status.unknown.push(MigrationInfo {
+                version: *version,
+                description: description.clone(),
+            }),
+            Some(_) if !success => status.failed.push(*version),
+            Some(m) if *m.checksum != checksum[..] => status.modified.push(*version),
+            Some(_) => {}
+        }
+    }
+    status.pending = known
+        .iter()
+        .filter(|m| !applied.iter().any(|(version, ..)| *version == m.version))
+        .map(|m| MigrationInfo {
+            version: m.version,
+            description: m.description.to_string(),
+        })
+        .collect();
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> Pool<Sqlite> {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_schema_status() {
+        let pool = memory_pool().await;
+
+        let fresh = schema_status(&pool, &MIGRATOR).await.unwrap();
+        assert_eq!(fresh.version, 0);
+        assert_eq!(fresh.pending.len(), MIGRATOR.iter().count());
+        assert!(!fresh.is_ahead());
+
+        MIGRATOR.run(&pool).await.unwrap();
+        let current = schema_status(&pool, &MIGRATOR).await.unwrap();
+        assert_eq!(current.version, current.expected_version);
+        assert!(current.pending.is_empty());
+        assert!(!current.is_ahead() && !current.has_drift());
+
+        // Applied by a newer release
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (9999, 'from the future', 1, x'00', 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let ahead = schema_status(&pool, &MIGRATOR).await.unwrap();
+        assert!(ahead.is_ahead());
+        assert_eq!(ahead.version, 9999);
+        assert_eq!(ahead.unknown[0].description, "from the future");
+
+        sqlx::query("UPDATE _sqlx_migrations SET checksum = x'00' WHERE version = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let modified = schema_status(&pool, &MIGRATOR).await.unwrap();
+        assert_eq!(modified.modified, vec![1]);
+        assert!(modified.has_drift());
+    }
+}
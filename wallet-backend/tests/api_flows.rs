@@ -0,0 +1,7256 @@
+//! End-to-end API flows against mock chain clients
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Method, Request, StatusCode};
+use serde_json::json;
+use wallet_backend::api::middleware::tenant::DEFAULT_TENANT;
+use wallet_backend::chains::Identity;
+use wallet_backend::storage::models::ContactRow;
+
+use common::{TestApp, PASSWORD};
+
+#[tokio::test]
+async fn test_wallet_create_lock_unlock() {
+    let app = TestApp::spawn().await;
+
+    let (_, status) = app
+        .request(Method::GET, "/api/v2/auth/status", None, None)
+        .await;
+    assert_eq!(
+        status,
+        json!({ "has_wallet": false, "is_unlocked": false, "keyfile_required": false })
+    );
+
+    let (code, body) = app
+        .request(
+            Method::POST,
+            "/api/v2/wallet/create",
+            None,
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(body["mnemonic"].as_array().unwrap().len(), 24);
+
+    app.request(Method::POST, "/api/v2/auth/lock", None, None)
+        .await;
+    let (code, _) = app.unlock(json!({ "password": "wrong password" })).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, body) = app.unlock(json!({ "password": PASSWORD })).await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(body["is_unlocked"], true);
+}
+
+#[tokio::test]
+async fn test_unlock_challenge() {
+    let app = TestApp::spawn().await;
+    app.create_wallet_with_account("solana").await;
+    app.request(Method::POST, "/api/v2/auth/lock", None, None).await;
+    let challenge = || async {
+        let (code, body) = app
+            .request(Method::POST, "/api/v2/auth/unlock/challenge", None, None)
+            .await;
+        (code, body["nonce"].as_str().unwrap_or_default().to_string())
+    };
+    let unlock = |password: &'static str, nonce: Option<String>| {
+        app.request(
+            Method::POST,
+            "/api/v2/auth/unlock",
+            None,
+            Some(json!({ "password": password, "nonce": nonce })),
+        )
+    };
+
+    let (code, body) = unlock(PASSWORD, None).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    assert!(body["error"]["message"].as_str().unwrap().contains("nonce"), "{}", body);
+    let (code, _) = unlock(PASSWORD, Some("00".repeat(16))).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    // A nonce is spent by a failed attempt, so the request can't be replayed
+    let (_, nonce) = challenge().await;
+    let (code, _) = unlock("wrong password", Some(nonce.clone())).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let (code, body) = unlock(PASSWORD, Some(nonce)).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    assert!(body["error"]["message"].as_str().unwrap().contains("already used"), "{}", body);
+
+    let (_, nonce) = challenge().await;
+    let (code, body) = unlock(PASSWORD, Some(nonce.clone())).await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(body["is_unlocked"], true);
+    let (code, _) = unlock(PASSWORD, Some(nonce)).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    // Issuance is capped per client per minute, which bounds password guessing
+    let mut code = StatusCode::OK;
+    for _ in 0..10 {
+        (code, _) = challenge().await;
+        if code != StatusCode::OK {
+            break;
+        }
+    }
+    assert_eq!(code, StatusCode::TOO_MANY_REQUESTS);
+
+    // Another client still gets a nonce and can unlock
+    let (code, body) = app
+        .request_from(
+            ([10, 0, 0, 2], 0).into(),
+            Method::POST,
+            "/api/v2/auth/unlock/challenge",
+            &[],
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    app.request(Method::POST, "/api/v2/auth/lock", None, None).await;
+    let (code, _) = unlock(PASSWORD, Some(body["nonce"].as_str().unwrap().to_string())).await;
+    assert_eq!(code, StatusCode::OK);
+
+    // Development can opt out and unlock with the password alone
+    let dev = TestApp::spawn_with_env(&[("UNLOCK_CHALLENGE_REQUIRED", "false")]).await;
+    dev.create_wallet_with_account("solana").await;
+    dev.request(Method::POST, "/api/v2/auth/lock", None, None).await;
+    let (code, _) = dev
+        .request(Method::POST, "/api/v2/auth/unlock", None, Some(json!({ "password": PASSWORD })))
+        .await;
+    assert_eq!(code, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_keyfile_required_to_unlock() {
+    let app = TestApp::spawn().await;
+    // base64 of "keyfile contents"
+    let keyfile = "a2V5ZmlsZSBjb250ZW50cw==";
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/wallet/create",
+            None,
+            Some(json!({ "password": PASSWORD, "keyfile": keyfile })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    app.request(Method::POST, "/api/v2/auth/lock", None, None)
+        .await;
+    let (_, status) = app
+        .request(Method::GET, "/api/v2/auth/status", None, None)
+        .await;
+    assert_eq!(status["keyfile_required"], true);
+
+    let (code, _) = app.unlock(json!({ "password": PASSWORD })).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, _) = app.unlock(json!({ "password": PASSWORD, "keyfile": "b3RoZXIgZmlsZQ==" })).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, body) = app.unlock(json!({ "password": PASSWORD, "keyfile": keyfile })).await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(body["is_unlocked"], true);
+}
+
+#[tokio::test]
+async fn test_encryption_password_rotation() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let new_password = "a brand new passphrase";
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/wallet/change-encryption-password",
+            Some(&token),
+            Some(json!({ "current_password": "wrong password", "new_password": new_password })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, body) = app
+        .request(
+            Method::POST,
+            "/api/v1/wallet/change-encryption-password",
+            Some(&token),
+            Some(json!({ "current_password": PASSWORD, "new_password": new_password })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(body["is_unlocked"], false);
+
+    let (code, _) = app.unlock(json!({ "password": PASSWORD })).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, _) = app.unlock(json!({ "password": new_password })).await;
+    assert_eq!(code, StatusCode::OK);
+
+    // Same seed, so the same accounts
+    let (_, accounts) = app
+        .request(Method::GET, "/api/v2/accounts", Some(&token), None)
+        .await;
+    assert_eq!(accounts[0]["address"], address);
+
+    let events = app.state.db.get_audit_events("default", 10).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event, "wallet.encryption_password_changed");
+}
+
+#[tokio::test]
+async fn test_wallet_provisioned_at_startup() {
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                    abandon abandon about";
+    let app =
+        TestApp::spawn_with_env(&[("WALLET_MNEMONIC", mnemonic), ("WALLET_PASSWORD", PASSWORD)])
+            .await;
+
+    let (_, status) = app
+        .request(Method::GET, "/api/v2/auth/status", None, None)
+        .await;
+    assert_eq!(status["has_wallet"], true);
+    assert_eq!(status["is_unlocked"], true);
+
+    let (code, account) = app
+        .request(
+            Method::POST,
+            "/api/v2/accounts",
+            None,
+            Some(json!({ "chain": "ethereum" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(
+        account["address"].as_str().unwrap().to_lowercase(),
+        "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+    );
+}
+
+#[tokio::test]
+async fn test_dev_mode_seeds_demo_environment() {
+    use wallet_backend::services::dev_service::{self, DevSeed, DEMO_EMAIL, DEMO_PASSWORD};
+    use wallet_backend::services::wallet_service;
+
+    let app = TestApp::spawn_with_env(&[("DEV_MODE", "true")]).await;
+
+    let (code, login) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/login",
+            None,
+            Some(json!({ "email": DEMO_EMAIL, "password": DEMO_PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", login);
+    let token = login["access_token"].as_str().unwrap();
+
+    let (_, status) = app
+        .request(Method::GET, "/api/v2/auth/status", None, None)
+        .await;
+    assert_eq!(status["is_unlocked"], true);
+
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", Some(token), None).await;
+    let accounts = accounts.as_array().unwrap();
+    assert_eq!(accounts.len(), 2);
+    let solana = accounts.iter().find(|a| a["chain"] == "solana").unwrap();
+    let address = solana["address"].as_str().unwrap();
+    assert_eq!(address, "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+    assert_eq!(
+        *app.solana.airdrops.lock().unwrap(),
+        vec![(address.to_string(), 1_000_000_000)]
+    );
+
+    let (_, contacts) = app.request(Method::GET, "/api/v2/contacts", Some(token), None).await;
+    assert_eq!(contacts["items"].as_array().unwrap().len(), 3, "{}", contacts);
+
+    let (_, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", address),
+            Some(token),
+            None,
+        )
+        .await;
+    assert_eq!(history["items"].as_array().unwrap().len(), 3, "{}", history);
+
+    // A restart finds the seeded data and only unlocks the wallet
+    wallet_service::lock_wallet(&app.state).await;
+    assert_eq!(dev_service::seed(&app.state).await.unwrap(), DevSeed::Unlocked);
+    assert!(wallet_service::is_unlocked(&app.state).await);
+    assert_eq!(app.solana.airdrops.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_send_records_history() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "0.5",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["tx_hash"], "mock-tx-1");
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+    assert!(app.ethereum.sent.lock().unwrap().is_empty());
+
+    let (code, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(history["items"][0]["signature"], "mock-tx-1");
+    assert_eq!(history["items"][0]["amount"], "0.5");
+    assert_eq!(history["items"][0]["amount_raw"], "500000000");
+}
+
+#[tokio::test]
+async fn test_send_to_contact_with_defaults() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let recipient = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/contacts",
+            None,
+            Some(json!({
+                "name": "Bob",
+                "chain": "solana",
+                "address": recipient,
+                "default_amount": "nope"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, contact) = app
+        .request(
+            Method::POST,
+            "/api/v2/contacts",
+            None,
+            Some(json!({
+                "name": "Bob",
+                "chain": "solana",
+                "address": recipient,
+                "default_amount": "0.25"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", contact);
+    assert_eq!(contact["default_amount"], "0.25");
+    assert!(contact["default_token_address"].is_null());
+    let id = contact["id"].as_str().unwrap().to_string();
+    let uri = format!("/api/v2/contacts/{}/send", id);
+
+    // The stored address and default amount fill the send
+    let (status, sent) = app
+        .request_signed(
+            Method::POST,
+            &uri,
+            &token,
+            Some(json!({ "from_address": address, "to_address": recipient })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", sent);
+    {
+        let transfers = app.solana.sent.lock().unwrap();
+        assert_eq!(transfers[0].to, recipient);
+        assert_eq!(transfers[0].amount, "0.25");
+    }
+
+    // A recipient that isn't the contact's address is refused
+    let (status, body) = app
+        .request_signed(
+            Method::POST,
+            &uri,
+            &token,
+            Some(json!({
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "0.1"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{}", body);
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+
+    // Updating the name alone keeps the defaults; an empty string clears one
+    let (status, contact) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/contacts/{}", id),
+            None,
+            Some(json!({ "name": "Bobby" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", contact);
+    assert_eq!(contact["default_amount"], "0.25");
+    let (_, contact) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/contacts/{}", id),
+            None,
+            Some(json!({ "name": "Bobby", "default_amount": "" })),
+        )
+        .await;
+    assert!(contact["default_amount"].is_null());
+
+    let (status, body) = app
+        .request_signed(Method::POST, &uri, &token, Some(json!({ "from_address": address })))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+
+    // Sending to a contact needs a signing token like any send
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &uri,
+            Some(&token),
+            Some(json!({ "from_address": address, "amount": "0.1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_send_insufficient_balance() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "5",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        body["error"]["message"],
+        "Insufficient balance: 5000005000 required, 2000000000 available"
+    );
+    assert!(app.solana.sent.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_refused_sends_are_diagnosed() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let send = |version: &'static str| {
+        let uri = format!("/api/{}/transactions/send", version);
+        let body = json!({
+            "chain": "solana",
+            "from_address": address,
+            "to_address": "11111111111111111111111111111111",
+            "amount": "0.1",
+        });
+        let token = token.clone();
+        let app = &app;
+        async move { app.request_signed(Method::POST, &uri, &token, Some(body)).await }
+    };
+
+    *app.solana.send_error.lock().unwrap() =
+        Some("Transaction simulation failed: Blockhash not found".to_string());
+    let (code, body) = send("v2").await;
+    assert_eq!(code, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["code"], "blockhash_expired");
+    assert_eq!(body["error"]["retryable"], true);
+    assert!(body["error"]["remediation"].as_str().unwrap().contains("fresh"));
+    assert!(body["error"]["message"].as_str().unwrap().contains("Blockhash not found"));
+
+    // v1 keeps plain-text errors
+    *app.solana.send_error.lock().unwrap() = Some(
+        "Transaction results in an account (1) with insufficient funds for rent".to_string(),
+    );
+    let (code, body) = send("v1").await;
+    assert_eq!(code, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body.as_str().unwrap().contains("insufficient funds for rent"));
+
+    // Unrecognized failures are the node's problem
+    *app.solana.send_error.lock().unwrap() = Some("connection reset by peer".to_string());
+    let (code, body) = send("v2").await;
+    assert_eq!(code, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["error"]["code"], "upstream_error");
+    assert!(body["error"].get("remediation").is_none());
+    assert!(app.solana.sent.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sends_need_a_signing_token_and_the_trade_scope() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let send = json!({
+        "chain": "solana",
+        "from_address": address,
+        "to_address": "11111111111111111111111111111111",
+        "amount": "0.5",
+    });
+
+    // The session token alone isn't enough
+    let (code, _) = app
+        .request(Method::POST, "/api/v2/transactions/send", Some(&token), Some(send.clone()))
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let (code, _) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &[("X-Signing-Token", token.as_str())],
+            Some(&token),
+            Some(send.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/auth/signing-token",
+            Some(&token),
+            Some(json!({ "password": "wrong password" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    // A signing token only works in the session it was minted in
+    let credentials = json!({ "email": "alice@example.com", "password": PASSWORD });
+    let (_, other) = app
+        .request(Method::POST, "/api/v2/users/login", None, Some(credentials.clone()))
+        .await;
+    let other_signing = app.signing_token(other["access_token"].as_str().unwrap()).await;
+    let (code, _) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &[("X-Signing-Token", other_signing.as_str())],
+            Some(&token),
+            Some(send.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    assert!(app.solana.sent.lock().unwrap().is_empty());
+
+    let (code, body) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send.clone()))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+
+    // A read-only session can look but not sign or change security settings
+    let mut read_only = credentials;
+    read_only["scopes"] = json!(["read"]);
+    let (code, login) = app
+        .request(Method::POST, "/api/v2/users/login", None, Some(read_only))
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(login["scopes"], json!(["read"]));
+    let read_token = login["access_token"].as_str().unwrap();
+
+    let (code, _) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", address),
+            Some(read_token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/auth/signing-token",
+            Some(read_token),
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::FORBIDDEN);
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/swap/wrap",
+            Some(read_token),
+            Some(json!({ "address": address, "amount": "0.1" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::FORBIDDEN);
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/change-password",
+            Some(read_token),
+            Some(json!({ "current_password": PASSWORD, "new_password": "another password" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_dry_runs_change_nothing() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    *app.prices.quote.lock().unwrap() = Some((100.0, chrono::Utc::now()));
+    let (code, _) = app
+        .request(
+            Method::PUT,
+            "/api/v2/users/me/large-transfer-threshold",
+            Some(&token),
+            Some(json!({ "threshold": "10", "currency": "usd", "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    // Worth 50 USD, above the threshold: reported, not held
+    let (code, preview) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send?dry_run=true",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "0.5",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", preview);
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(preview["amount"], "0.5");
+    assert_eq!(preview["fee"], "5000");
+    assert_eq!(preview["route"], "rpc");
+    assert_eq!(preview["expected_changes"]["changes"][1]["delta"], "500000000");
+    assert_eq!(preview["confirmation_required"]["value"], "50.00");
+    assert_eq!(preview["confirmation_required"]["threshold"], "10");
+
+    assert!(app.solana.sent.lock().unwrap().is_empty());
+    let (_, balance) = app
+        .request(Method::GET, &format!("/api/v2/balances/solana/{}", address), None, None)
+        .await;
+    assert_eq!(balance["native_balance"], "2");
+    let (_, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert!(history["items"].as_array().unwrap().is_empty());
+
+    // The same checks as a real send
+    let (code, _) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send?dry_run=true",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "5",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // A multi-sig execution without enough approvals is refused, and a
+    // ready one isn't marked executed
+    let (_, multisig) = app
+        .request(
+            Method::POST,
+            "/api/v2/multisig/create",
+            None,
+            Some(json!({
+                "chain": "solana",
+                "name": "treasury",
+                "threshold": 1,
+                "owners": ["owner-a", "owner-b"],
+            })),
+        )
+        .await;
+    let multisig_id = multisig["id"].as_str().unwrap();
+    let (_, tx) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/multisig/{}/propose", multisig_id),
+            Some(&token),
+            Some(json!({ "to_address": "11111111111111111111111111111111", "amount": "0.1" })),
+        )
+        .await;
+    let tx_id = tx["id"].as_str().unwrap();
+    let execute = format!("/api/v2/multisig/{}/execute/{}?dry_run=true", multisig_id, tx_id);
+    let (code, _) = app.request_signed(Method::POST, &execute, &token, None).await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/multisig/{}/approve/{}", multisig_id, tx_id),
+            Some(&token),
+            Some(json!({ "approver_address": "owner-a" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (code, preview) = app.request_signed(Method::POST, &execute, &token, None).await;
+    assert_eq!(code, StatusCode::OK, "{}", preview);
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(preview["transaction"]["status"], "ready");
+    let (_, pending) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/multisig/{}/transactions", multisig_id),
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(pending[0]["status"], "ready");
+}
+
+#[tokio::test]
+async fn test_fiat_send_converts_at_current_price() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let send = json!({
+        "chain": "solana",
+        "from_address": address,
+        "to_address": "11111111111111111111111111111111",
+        "amount": "25 USD",
+    });
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(send.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["conversion"]["native_amount"], "0.25");
+    assert_eq!(body["conversion"]["rate"], 100.0);
+    assert_eq!(app.solana.sent.lock().unwrap()[0].amount, "0.25");
+
+    let (_, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(history["items"][0]["fiat_amount"], "25");
+    assert_eq!(history["items"][0]["fiat_currency"], "USD");
+    assert_eq!(history["items"][0]["fiat_rate"], "100");
+
+    // Stale and missing prices fail without sending
+    *app.prices.quote.lock().unwrap() =
+        Some((100.0, chrono::Utc::now() - chrono::Duration::minutes(10)));
+    let (code, _) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(send.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+
+    *app.prices.quote.lock().unwrap() = None;
+    let (code, _) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(send),
+        )
+        .await;
+    assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_send_requires_unlocked_wallet() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    app.request(Method::POST, "/api/v2/auth/lock", None, None)
+        .await;
+
+    let (code, _) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "ethereum",
+                "from_address": address,
+                "to_address": address,
+                "amount": "0.1",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    assert!(app.ethereum.sent.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_account_sync_state() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v1/accounts", None, None).await;
+    let id = accounts[0]["id"].as_str().unwrap().to_string();
+    assert!(accounts[0]["last_synced_at"].is_null());
+
+    // Balance reads are the heartbeat
+    let (status, _) = app
+        .request(Method::GET, &format!("/api/v2/balances/solana/{}", address), None, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let first_sync = accounts[0]["last_synced_at"].as_str().unwrap().to_string();
+    assert_eq!(accounts[0]["last_known_balance"], "2");
+    assert_eq!(accounts[0]["last_known_balance_raw"], "2000000000");
+    assert!(accounts[0]["sync_error"].is_null());
+
+    // A failed refresh keeps the last known balance and records the error
+    app.solana.set_balance(3_000_000_000);
+    *app.solana.balance_error.lock().unwrap() = Some("node unreachable".to_string());
+    let sync = format!("/api/v2/accounts/{}/sync", id);
+    let (status, _) = app.request(Method::POST, &sync, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let token = app.login().await;
+    let (status, _) = app.request(Method::POST, &sync, Some(&token), None).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    assert_eq!(accounts[0]["last_known_balance"], "2");
+    assert_eq!(accounts[0]["last_synced_at"], first_sync.as_str());
+    assert!(accounts[0]["sync_error"].as_str().unwrap().contains("node unreachable"));
+    assert!(accounts[0]["sync_error_at"].is_string());
+
+    *app.solana.balance_error.lock().unwrap() = None;
+    let (status, account) = app.request(Method::POST, &sync, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", account);
+    assert_eq!(account["last_known_balance"], "3");
+    assert!(account["sync_error"].is_null());
+    assert!(account["sync_error_at"].is_null());
+
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/accounts/missing/sync", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_balance_reconciliation() {
+    use wallet_backend::services::reconciliation_service::reconcile_balances;
+
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "ethereum" })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .request(Method::GET, "/api/v1/admin/reconciliation", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Cache the Solana balance, then have funds arrive without a record
+    app.request(Method::GET, &format!("/api/v2/balances/solana/{}", address), None, None)
+        .await;
+    app.solana.set_balance(2_500_000_000);
+    *app.ethereum.balance_error.lock().unwrap() = Some("node unreachable".to_string());
+
+    let report = reconcile_balances(&app.state).await.unwrap();
+    assert_eq!(report.run.accounts_checked, 2);
+    assert_eq!(report.run.discrepancy_count, 2);
+
+    let (status, body) = app
+        .request(Method::GET, "/api/v1/admin/reconciliation", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["id"], report.run.id.as_str());
+    let discrepancies = body["discrepancies"].as_array().unwrap();
+    let kind = |chain: &str| {
+        discrepancies
+            .iter()
+            .find(|d| d["chain"] == chain)
+            .unwrap_or_else(|| panic!("no {} discrepancy in {}", chain, body))
+    };
+    let solana = kind("solana");
+    assert_eq!(solana["kind"], "missed_incoming");
+    assert_eq!(solana["address"], address.as_str());
+    assert_eq!(solana["cached_balance"], "2");
+    assert_eq!(solana["expected_balance"], "2");
+    assert_eq!(solana["live_balance"], "2.5");
+    let ethereum = kind("ethereum");
+    assert_eq!(ethereum["kind"], "sync_failed");
+    assert!(ethereum["detail"].as_str().unwrap().contains("node unreachable"));
+
+    // Each discrepancy raised an alert in the audit log
+    let events = app.state.db.get_audit_events("default", 50).await.unwrap();
+    let alerts = events.iter().filter(|e| e.event == "balance.discrepancy").count();
+    assert_eq!(alerts, 2);
+
+    // The live balances became the baseline, so the next run is clean
+    *app.ethereum.balance_error.lock().unwrap() = None;
+    let report = reconcile_balances(&app.state).await.unwrap();
+    assert_eq!(report.run.discrepancy_count, 0);
+    let (_, body) = app
+        .request(Method::GET, "/api/v2/admin/reconciliation", Some(admin_token), None)
+        .await;
+    assert_eq!(body["id"], report.run.id.as_str());
+
+    let (status, _) = app
+        .request(Method::GET, "/api/v1/admin/reconciliation", None, None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+/// Local Sepolia faucet API; returns its URL and the bodies it was sent
+async fn spawn_sepolia_faucet() -> (String, std::sync::Arc<std::sync::Mutex<Vec<serde_json::Value>>>) {
+    use std::sync::{Arc, Mutex};
+
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let router = Router::new().route(
+        "/fund",
+        post(move |Json(body): Json<serde_json::Value>| async move {
+            sink.lock().unwrap().push(body);
+            Json(json!({ "txHash": "0xfunded" }))
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}/fund", addr), received)
+}
+
+#[tokio::test]
+async fn test_faucet_funding() {
+    let solana_address = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    let eth_address = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+
+    // Off unless the deployment opts in
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/faucet/solana/{}", solana_address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (faucet_url, received) = spawn_sepolia_faucet().await;
+    let app = TestApp::spawn_with_env(&[
+        ("FAUCET_ENABLED", "true"),
+        ("FAUCET_SEPOLIA_URL", &faucet_url),
+        ("FAUCET_DAILY_LIMIT", "2"),
+    ])
+    .await;
+    let token = app.login().await;
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/faucet/solana/{}", solana_address),
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, funding) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/faucet/solana/{}", solana_address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", funding);
+    assert_eq!(funding["tx_hash"], "airdrop-1");
+    assert_eq!(funding["amount"], "1000000000");
+    assert_eq!(funding["remaining"], 1);
+    assert_eq!(
+        *app.solana.airdrops.lock().unwrap(),
+        vec![(solana_address.to_string(), 1_000_000_000)]
+    );
+
+    let (status, funding) = app
+        .request(
+            Method::POST,
+            &format!("/api/v1/faucet/solana/{}", solana_address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", funding);
+    assert_eq!(funding["remaining"], 0);
+
+    // The daily limit is per address
+    let (status, body) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/faucet/solana/{}", solana_address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body["error"]["message"].as_str().unwrap().contains("try again after"));
+    assert_eq!(app.solana.airdrops.lock().unwrap().len(), 2);
+
+    // Ethereum goes through the Sepolia faucet API
+    let (status, funding) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/faucet/ethereum/{}", eth_address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", funding);
+    assert_eq!(funding["tx_hash"], "0xfunded");
+    assert_eq!(funding["amount"], "50000000000000000");
+    let sent = received.lock().unwrap().clone();
+    assert_eq!(sent[0]["address"], eth_address.to_lowercase());
+
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/faucet/ethereum/0xnope", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_encrypted_notes() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let (status, contact) = app
+        .request(
+            Method::POST,
+            "/api/v2/contacts",
+            None,
+            Some(json!({
+                "name": "Bob",
+                "chain": "solana",
+                "address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", contact);
+    let contact_id = contact["id"].as_str().unwrap().to_string();
+
+    let create = |target_type: &str, target_id: &str, body: &str| {
+        let note = json!({ "target_type": target_type, "target_id": target_id, "body": body });
+        let token = token.clone();
+        let app = &app;
+        async move { app.request(Method::POST, "/api/v2/notes", Some(&token), Some(note)).await }
+    };
+    let (status, savings) = create("account", &account_id, "Savings - don't spend").await;
+    assert_eq!(status, StatusCode::OK, "{}", savings);
+    assert_eq!(savings["body"], "Savings - don't spend");
+    let savings_id = savings["id"].as_str().unwrap().to_string();
+    let (status, _) = create("contact", &contact_id, "Owes me lunch").await;
+    assert_eq!(status, StatusCode::OK);
+
+    for (target_type, target_id, body) in [
+        ("account", "missing", "note"),
+        ("wallet", account_id.as_str(), "note"),
+        ("account", account_id.as_str(), "  "),
+    ] {
+        let (status, _) = create(target_type, target_id, body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{} {} {:?}", target_type, target_id, body);
+    }
+
+    // Stored sealed under the wallet's key
+    let row = app.state.db.get_note(&savings_id).await.unwrap();
+    assert!(!row.body.contains("Savings"));
+
+    let (status, notes) = app.request(Method::GET, "/api/v2/notes", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(notes.as_array().unwrap().len(), 2);
+    let (_, notes) = app
+        .request(Method::GET, "/api/v2/notes?q=LUNCH", Some(&token), None)
+        .await;
+    assert_eq!(notes.as_array().unwrap().len(), 1);
+    assert_eq!(notes[0]["target_id"], contact_id.as_str());
+    let (_, notes) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/notes?target_type=account&target_id={}", account_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(notes.as_array().unwrap().len(), 1);
+    assert_eq!(notes[0]["id"], savings_id.as_str());
+
+    // Nothing is readable while the wallet is locked
+    app.request(Method::POST, "/api/v2/auth/lock", None, None).await;
+    let (status, _) = app.request(Method::GET, "/api/v2/notes", Some(&token), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app
+        .request(Method::GET, &format!("/api/v2/notes/{}", savings_id), Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.unlock(json!({ "password": PASSWORD })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, note) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/notes/{}", savings_id),
+            Some(&token),
+            Some(json!({ "body": "Savings, locked until June" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", note);
+    let (_, note) = app
+        .request(Method::GET, &format!("/api/v2/notes/{}", savings_id), Some(&token), None)
+        .await;
+    assert_eq!(note["body"], "Savings, locked until June");
+
+    let (status, _) = app
+        .request(Method::DELETE, &format!("/api/v2/notes/{}", savings_id), Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .request(Method::GET, &format!("/api/v2/notes/{}", savings_id), Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_send_templates() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let recipient = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    let (status, rent) = app
+        .request(
+            Method::POST,
+            "/api/v2/templates",
+            Some(&token),
+            Some(json!({
+                "name": "Rent",
+                "chain": "solana",
+                "to_address": recipient,
+                "amount": "25 USD",
+                "memo": "monthly rent"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", rent);
+    assert_eq!(rent["use_count"], 0);
+    assert!(rent["last_used_at"].is_null());
+    let rent_id = rent["id"].as_str().unwrap().to_string();
+
+    let (status, tip) = app
+        .request(
+            Method::POST,
+            "/api/v2/templates",
+            Some(&token),
+            Some(json!({ "name": "Tip", "chain": "solana", "to_address": recipient })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", tip);
+    let tip_id = tip["id"].as_str().unwrap().to_string();
+
+    for (body, expected) in [
+        (json!({ "name": "Rent", "chain": "solana", "to_address": recipient }), StatusCode::CONFLICT),
+        (json!({ "name": "Bad", "chain": "solana", "to_address": "nope" }), StatusCode::BAD_REQUEST),
+        (
+            json!({ "name": "Bad", "chain": "solana", "to_address": recipient, "amount": "-1" }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({
+                "name": "Bad",
+                "chain": "ethereum",
+                "to_address": "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+                "memo": "memos are solana only"
+            }),
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        let (status, _) = app
+            .request(Method::POST, "/api/v2/templates", Some(&token), Some(body))
+            .await;
+        assert_eq!(status, expected);
+    }
+
+    // Applying fills the draft and counts the use
+    let (status, applied) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/templates/{}/apply", rent_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", applied);
+    assert_eq!(applied["draft"]["to_address"], recipient);
+    assert_eq!(applied["draft"]["amount"], "25 USD");
+    assert_eq!(applied["draft"]["memo"], "monthly rent");
+    assert_eq!(applied["template"]["use_count"], 1);
+    assert!(applied["template"]["last_used_at"].is_string());
+
+    let (_, templates) = app
+        .request(Method::GET, "/api/v2/templates", Some(&token), None)
+        .await;
+    assert_eq!(templates[0]["id"], rent_id.as_str());
+
+    // Quick actions come first
+    let (status, tip) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/templates/{}", tip_id),
+            Some(&token),
+            Some(json!({ "quick_action": true, "amount": "0.1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", tip);
+    assert_eq!(tip["amount"], "0.1");
+    assert_eq!(tip["name"], "Tip");
+    let (_, templates) = app
+        .request(Method::GET, "/api/v1/templates", Some(&token), None)
+        .await;
+    assert_eq!(templates[0]["id"], tip_id.as_str());
+    assert_eq!(templates[0]["quick_action"], true);
+
+    // Templates are private to their owner
+    let bob = json!({ "email": "bob@example.com", "password": "correct horse battery" });
+    app.request(Method::POST, "/api/v2/users/register", None, Some(bob.clone()))
+        .await;
+    let (_, other) = app
+        .request(Method::POST, "/api/v2/users/login", None, Some(bob))
+        .await;
+    let other_token = other["access_token"].as_str().unwrap().to_string();
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/templates/{}/apply", rent_id),
+            Some(&other_token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/api/v2/templates/{}", rent_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, templates) = app
+        .request(Method::GET, "/api/v2/templates", Some(&token), None)
+        .await;
+    assert_eq!(templates.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_balance_and_max_send() {
+    let app = TestApp::spawn().await;
+
+    let (code, body) = app
+        .request(Method::GET, "/api/v2/balances/ethereum/0xabc", None, None)
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(body["native_balance"], "1");
+    assert_eq!(body["native_balance_raw"], "1000000000000000000");
+    assert_eq!(body["native_decimals"], 18);
+    assert_eq!(body["native_symbol"], "ETH");
+
+    // Every digit of a balance past what a float holds
+    app.ethereum.set_balance(123_456_789_012_345_678_901_234_567);
+    let (_, body) = app
+        .request(Method::GET, "/api/v2/balances/ethereum/0xabc", None, None)
+        .await;
+    assert_eq!(body["native_balance"], "123456789.012345678901234567");
+    assert_eq!(body["native_balance_raw"], "123456789012345678901234567");
+
+    let (code, body) = app
+        .request(
+            Method::GET,
+            "/api/v2/transactions/max-send?chain=solana&address=abc",
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(body["max_amount"], "1999995000");
+    assert_eq!(body["fee_covered"], true);
+}
+
+#[tokio::test]
+async fn test_account_balances_aggregate_with_deadline() {
+    let app = TestApp::spawn_with_env(&[("BALANCE_FETCH_DEADLINE_MS", "500")]).await;
+    let sol_address = app.create_wallet_with_account("solana").await;
+    for chain in ["ethereum", "solana"] {
+        let (status, _) = app
+            .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": chain })))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = app.request(Method::GET, "/api/v2/accounts/balances", None, None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["complete"], true);
+    let accounts = body["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 3);
+    // By chain, then derivation index
+    assert_eq!(accounts[0]["chain"], "ethereum");
+    assert_eq!(accounts[0]["balance"]["native_balance"], "1");
+    assert_eq!(accounts[1]["address"], sol_address.as_str());
+    assert_eq!(accounts[1]["balance"]["native_balance_raw"], "2000000000");
+    assert!(accounts.iter().all(|a| a["error"].is_null()));
+
+    // A chain that doesn't answer in time doesn't hold up the others
+    *app.ethereum.balance_delay.lock().unwrap() = Some(std::time::Duration::from_secs(10));
+    let started = std::time::Instant::now();
+    let (status, body) = app.request(Method::GET, "/api/v2/accounts/balances", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(body["complete"], false);
+    let accounts = body["accounts"].as_array().unwrap();
+    assert!(accounts[0]["balance"].is_null());
+    assert_eq!(accounts[0]["error"]["code"], "timeout");
+    assert_eq!(accounts[1]["balance"]["native_balance"], "2");
+    assert_eq!(accounts[2]["balance"]["native_balance"], "2");
+
+    // Failures are reported per account
+    *app.ethereum.balance_delay.lock().unwrap() = None;
+    *app.solana.balance_error.lock().unwrap() = Some("node unreachable".to_string());
+    let (_, body) = app.request(Method::GET, "/api/v2/accounts/balances", None, None).await;
+    let accounts = body["accounts"].as_array().unwrap();
+    assert_eq!(accounts[1]["error"]["code"], "failed");
+    assert!(accounts[1]["error"]["message"].as_str().unwrap().contains("node unreachable"));
+    assert_eq!(accounts[0]["balance"]["native_balance"], "1");
+}
+
+#[tokio::test]
+async fn test_token_sorting_and_small_balances() {
+    use wallet_backend::storage::models::TransactionRow;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let tokens = [
+        ("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", "AAA", 100_000_000, Some(0.001)),
+        ("0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb", "BBB", 2_000_000, Some(10.0)),
+        ("0xcccccccccccccccccccccccccccccccccccccccc", "CCC", 100_000_000, Some(0.5)),
+        ("0xdddddddddddddddddddddddddddddddddddddddd", "DDD", 5_000_000, None),
+    ];
+    for (token_address, symbol, balance, price) in tokens {
+        app.ethereum.add_token(token_address, Some(symbol), 6, balance);
+        if let Some(price) = price {
+            app.prices.token_quotes.lock().unwrap().insert(token_address.to_string(), price);
+        }
+        let (status, body) = app
+            .request(
+                Method::POST,
+                "/api/v2/user-tokens",
+                Some(&token),
+                Some(json!({ "chain": "ethereum", "token_address": token_address })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+    let balance_uri = format!("/api/v2/balances/ethereum/{}", address);
+    let symbols = |tokens: &serde_json::Value| -> Vec<String> {
+        tokens
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["symbol"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Most valuable first, unpriced last
+    let (status, body) = app
+        .request(Method::GET, &format!("{}?sort=value", balance_uri), Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(symbols(&body["tokens"]), ["CCC", "BBB", "AAA", "DDD"]);
+    assert_eq!(body["tokens"][0]["fiat_value"], "50.00");
+    assert_eq!(body["tokens"][0]["fiat_currency"], "USD");
+    assert!(body["tokens"][3]["fiat_value"].is_null());
+    assert!(body["others"].is_null());
+
+    // Small balances are counted instead of listed; unpriced ones stay
+    let (_, body) = app
+        .request(
+            Method::GET,
+            &format!("{}?sort=name&small_balance_threshold=1", balance_uri),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(symbols(&body["tokens"]), ["BBB", "CCC", "DDD"]);
+    assert_eq!(body["others"]["count"], 1);
+    assert_eq!(body["others"]["fiat_value"], "0.10");
+    assert_eq!(body["others"]["fiat_currency"], "USD");
+
+    // Most recently moved first
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", Some(&token), None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap();
+    for (signature, token_address, timestamp) in [
+        ("0xold", tokens[1].0, "2026-01-01T00:00:00+00:00"),
+        ("0xnew", tokens[3].0, "2026-03-01T00:00:00+00:00"),
+    ] {
+        let row = TransactionRow::new(
+            account_id.to_string(),
+            "ethereum".to_string(),
+            signature.to_string(),
+            "receive".to_string(),
+            Some("0x1111111111111111111111111111111111111111".to_string()),
+            Some(address.clone()),
+            Some("1".to_string()),
+            Some(token_address.to_string()),
+            "confirmed".to_string(),
+            None,
+            Some(timestamp.to_string()),
+        );
+        app.state.db.upsert_transaction(&row).await.unwrap();
+    }
+    let (_, body) = app
+        .request(Method::GET, &format!("{}?sort=activity", balance_uri), Some(&token), None)
+        .await;
+    assert_eq!(symbols(&body["tokens"]), ["DDD", "BBB", "AAA", "CCC"]);
+
+    // Saved settings apply without query parameters, which override them
+    let (status, settings) = app
+        .request(Method::GET, "/api/v2/users/me/token-view", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(settings.is_null());
+    for bad in [
+        json!({ "small_balance_threshold": "-1" }),
+        json!({ "small_balance_threshold": "1", "currency": "dollars" }),
+        json!({ "sort": "size" }),
+    ] {
+        let (status, _) = app
+            .request(Method::PUT, "/api/v2/users/me/token-view", Some(&token), Some(bad.clone()))
+            .await;
+        assert!(status.is_client_error(), "{}", bad);
+    }
+    let (status, settings) = app
+        .request(
+            Method::PUT,
+            "/api/v2/users/me/token-view",
+            Some(&token),
+            Some(json!({ "sort": "value", "small_balance_threshold": "1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", settings);
+    assert_eq!(settings["sort"], "value");
+    assert_eq!(settings["currency"], "USD");
+
+    let (_, body) = app.request(Method::GET, &balance_uri, Some(&token), None).await;
+    assert_eq!(symbols(&body["tokens"]), ["CCC", "BBB", "DDD"]);
+    assert_eq!(body["others"]["count"], 1);
+    let (_, body) = app
+        .request(
+            Method::GET,
+            &format!("{}?sort=name&small_balance_threshold=0", balance_uri),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(symbols(&body["tokens"]), ["AAA", "BBB", "CCC", "DDD"]);
+    assert!(body["others"].is_null());
+
+    // A bare token list is sorted but keeps its small balances
+    let (_, body) = app
+        .request(Method::GET, &format!("/api/v2/tokens/ethereum/{}", address), Some(&token), None)
+        .await;
+    assert_eq!(symbols(&body), ["CCC", "BBB", "AAA", "DDD"]);
+
+    let (status, body) = app
+        .request(Method::GET, "/api/v2/accounts/balances", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(symbols(&body["accounts"][0]["balance"]["tokens"]), ["CCC", "BBB", "DDD"]);
+    assert_eq!(body["accounts"][0]["balance"]["others"]["fiat_value"], "0.10");
+
+    for uri in [
+        format!("{}?sort=size", balance_uri),
+        format!("{}?small_balance_threshold=lots", balance_uri),
+        "/api/v2/accounts/balances?currency=US".to_string(),
+    ] {
+        let (status, _) = app.request(Method::GET, &uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+
+    let (status, _) = app
+        .request(Method::DELETE, "/api/v2/users/me/token-view", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = app.request(Method::GET, &balance_uri, Some(&token), None).await;
+    assert_eq!(body["tokens"].as_array().unwrap().len(), 4);
+    assert!(body["others"].is_null());
+}
+
+#[tokio::test]
+async fn test_multisig_create_and_list() {
+    let app = TestApp::spawn().await;
+    app.create_wallet_with_account("solana").await;
+
+    let (code, body) = app
+        .request(
+            Method::POST,
+            "/api/v2/multisig/create",
+            None,
+            Some(json!({
+                "chain": "solana",
+                "name": "treasury",
+                "threshold": 2,
+                "owners": ["owner-a", "owner-b", "owner-c"],
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["address"], "mock-multisig-treasury");
+    assert_eq!(body["owner_count"], 3);
+    assert!(body["sponsored_by"].is_null());
+
+    let estimate = json!({
+        "chain": "solana",
+        "threshold": 2,
+        "owners": ["owner-a", "owner-b", "owner-c"],
+    });
+    let (code, _) = app
+        .request(Method::POST, "/api/v2/multisig/estimate", None, Some(estimate.clone()))
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let token = app.login().await;
+    let (code, body) = app
+        .request(Method::POST, "/api/v2/multisig/estimate", Some(&token), Some(estimate.clone()))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["rent"], "500000");
+    assert_eq!(body["network_fee"], "5000");
+    assert_eq!(body["total"], "505000");
+    assert_eq!(body["formatted_total"], "0.000505");
+    assert_eq!(body["sponsor"]["sufficient"], true);
+    let sponsor = body["sponsor"]["address"].as_str().unwrap().to_string();
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/multisig/estimate",
+            Some(&token),
+            Some(json!({ "chain": "solana", "threshold": 3, "owners": ["owner-a"] })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    // A sponsor that can't pay is refused before anything is created
+    let sponsored = json!({
+        "chain": "solana",
+        "name": "payroll",
+        "threshold": 1,
+        "owners": ["owner-a", "owner-b"],
+        "sponsor": true,
+    });
+    app.solana.set_balance(1_000);
+    let (_, body) = app
+        .request(Method::POST, "/api/v2/multisig/estimate", Some(&token), Some(estimate))
+        .await;
+    assert_eq!(body["sponsor"]["sufficient"], false);
+    assert_eq!(body["sponsor"]["shortfall"], "504000");
+    let (code, body) = app
+        .request(Method::POST, "/api/v2/multisig/create", None, Some(sponsored.clone()))
+        .await;
+    assert_eq!(code, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+
+    app.solana.set_balance(2_000_000_000);
+    let (code, body) = app
+        .request(Method::POST, "/api/v2/multisig/create", None, Some(sponsored))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["sponsored_by"], sponsor);
+    assert_eq!(body["deployment_cost"], "505000");
+
+    let (code, list) = app
+        .request(Method::GET, "/api/v2/multisig", None, None)
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(list["items"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_share_links() {
+    let app = TestApp::spawn_with_env(&[("SHARE_LINK_SECRET", "fedcba9876543210fedcba9876543210")]).await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let (code, multisig) = app
+        .request(
+            Method::POST,
+            "/api/v2/multisig/create",
+            None,
+            Some(json!({
+                "chain": "solana",
+                "name": "treasury",
+                "threshold": 1,
+                "owners": ["owner-a", "owner-b"],
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", multisig);
+
+    let share = |scope: &str, resource_id: &str, expires_in_secs: Option<i64>| {
+        let request = json!({
+            "scope": scope,
+            "resource_id": resource_id,
+            "expires_in_secs": expires_in_secs,
+        });
+        let token = token.clone();
+        let app = &app;
+        async move { app.request(Method::POST, "/api/v2/shares", Some(&token), Some(request)).await }
+    };
+
+    let (code, link) = share("account", &account_id, None).await;
+    assert_eq!(code, StatusCode::CREATED, "{}", link);
+    let share_token = link["token"].as_str().unwrap().to_string();
+    let url = link["url"].as_str().unwrap().to_string();
+    assert!(url.starts_with("/api/v2/shared/account?token="));
+
+    // No JWT needed, the token is enough
+    let (code, view) = app.request(Method::GET, &url, None, None).await;
+    assert_eq!(code, StatusCode::OK, "{}", view);
+    assert_eq!(view["account"]["address"], address);
+    assert_eq!(view["balance"]["native_balance_raw"], "2000000000");
+    assert!(view["history"].is_array());
+    let (code, _) = app
+        .request_with_headers(
+            Method::GET,
+            "/api/v2/shared/account",
+            &[("X-Share-Token", share_token.as_str())],
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    let (code, _) = app.request(Method::GET, "/api/v2/shared/account", None, None).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let mut tampered = share_token.clone();
+    tampered.insert(0, 'x');
+    let (code, _) = app
+        .request(Method::GET, &format!("/api/v2/shared/account?token={}", tampered), None, None)
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    // An account link doesn't open multi-sig views
+    let (code, _) = app
+        .request(Method::GET, &format!("/api/v2/shared/multisig?token={}", share_token), None, None)
+        .await;
+    assert_eq!(code, StatusCode::FORBIDDEN);
+
+    let (code, link) = share("multisig", multisig["id"].as_str().unwrap(), Some(3600)).await;
+    assert_eq!(code, StatusCode::CREATED, "{}", link);
+    let (code, view) = app
+        .request(Method::GET, link["url"].as_str().unwrap(), None, None)
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", view);
+    assert_eq!(view["multisig"]["name"], "treasury");
+    assert_eq!(view["multisig"]["owners"].as_array().unwrap().len(), 2);
+    assert!(view["proposals"].as_array().unwrap().is_empty());
+
+    let (code, _) = share("account", "no-such-account", None).await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+    let (code, _) = share("account", &account_id, Some(10)).await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    // Listing never returns tokens; revoking ends access at once
+    let (code, links) = app.request(Method::GET, "/api/v2/shares", Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK);
+    let links = links.as_array().unwrap();
+    assert_eq!(links.len(), 2);
+    assert!(links.iter().all(|l| l.get("token").is_none()));
+    let account_link = links.iter().find(|l| l["scope"] == "account").unwrap();
+    let (code, revoked) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/shares/{}/revoke", account_link["id"].as_str().unwrap()),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", revoked);
+    assert!(revoked["revoked_at"].is_string());
+    let (code, _) = app.request(Method::GET, &url, None, None).await;
+    assert_eq!(code, StatusCode::GONE);
+
+    // Without its own secret the server doesn't make links
+    let unset = TestApp::spawn().await;
+    let token = unset.login().await;
+    unset.create_wallet_with_account("solana").await;
+    let (_, accounts) = unset.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let request = json!({ "scope": "account", "resource_id": accounts[0]["id"] });
+    let (code, _) = unset
+        .request(Method::POST, "/api/v2/shares", Some(&token), Some(request))
+        .await;
+    assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_multisig_offline_signatures() {
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::H256;
+
+    let app = TestApp::spawn().await;
+    app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    *app.ethereum.multisig_address.lock().unwrap() =
+        Some("0x5afe5afe5afe5afe5afe5afe5afe5afe5afe5afe".to_string());
+
+    let owners: Vec<LocalWallet> = (0..3)
+        .map(|_| LocalWallet::new(&mut rand::thread_rng()))
+        .collect();
+    let (_, multisig) = app
+        .request(
+            Method::POST,
+            "/api/v2/multisig/create",
+            None,
+            Some(json!({
+                "chain": "ethereum",
+                "name": "treasury",
+                "threshold": 2,
+                "owners": owners[..2].iter().map(|o| format!("{:?}", o.address())).collect::<Vec<_>>(),
+            })),
+        )
+        .await;
+    let multisig_id = multisig["id"].as_str().unwrap();
+
+    let (_, tx) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/multisig/{}/propose", multisig_id),
+            Some(&token),
+            Some(json!({ "to_address": "0x000000000000000000000000000000000000dead", "amount": "0.01" })),
+        )
+        .await;
+    let base = format!(
+        "/api/v2/multisig/{}/transactions/{}",
+        multisig_id,
+        tx["id"].as_str().unwrap()
+    );
+
+    let (code, payload) = app
+        .request(
+            Method::GET,
+            &format!("{}/payload", base),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", payload);
+    assert_eq!(payload["format"], "safe-eip712");
+    assert_eq!(payload["payload"]["message"]["value"], "10000000000000000");
+    let hash: H256 = payload["safe_tx_hash"].as_str().unwrap().parse().unwrap();
+
+    let sign =
+        |owner: &LocalWallet| json!({ "signature": owner.sign_hash(hash).unwrap().to_string() });
+    let signatures = format!("{}/signatures", base);
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            &signatures,
+            Some(&token),
+            Some(sign(&owners[2])),
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    let (code, body) = app
+        .request(
+            Method::POST,
+            &signatures,
+            Some(&token),
+            Some(sign(&owners[0])),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "pending");
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            &signatures,
+            Some(&token),
+            Some(sign(&owners[0])),
+        )
+        .await;
+    assert_eq!(code, StatusCode::CONFLICT);
+
+    let (_, body) = app
+        .request(
+            Method::POST,
+            &signatures,
+            Some(&token),
+            Some(sign(&owners[1])),
+        )
+        .await;
+    assert_eq!(body["status"], "ready");
+
+    let (_, payload) = app
+        .request(
+            Method::GET,
+            &format!("{}/payload", base),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(payload["signatures"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_custom_tokens_merge_into_balances() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let usdc = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    app.ethereum.add_token(usdc, Some("USDC"), 6, 2_500_000);
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/user-tokens",
+            Some(&token),
+            Some(json!({ "chain": "ethereum", "token_address": "0xdead", "symbol": "X" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/user-tokens",
+            Some(&token),
+            Some(json!({ "chain": "ethereum", "token_address": usdc, "decimals": 18 })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    // Ethereum addresses are stored lowercased
+    let checksummed = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    let (code, added) = app
+        .request(
+            Method::POST,
+            "/api/v2/user-tokens",
+            Some(&token),
+            Some(json!({ "chain": "ethereum", "token_address": checksummed })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", added);
+    assert_eq!(added["symbol"], "USDC");
+    assert_eq!(added["decimals"], 6);
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/user-tokens",
+            Some(&token),
+            Some(json!({ "chain": "ethereum", "token_address": usdc })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::CONFLICT);
+
+    let uri = "/api/v2/tokens/ethereum/0xabc";
+    let (_, anonymous) = app.request(Method::GET, uri, None, None).await;
+    assert_eq!(anonymous, json!([]));
+
+    let (code, tokens) = app.request(Method::GET, uri, Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(tokens[0]["address"], usdc);
+    assert_eq!(tokens[0]["balance"], "2500000");
+    assert_eq!(tokens[0]["ui_amount"], 2.5);
+    assert_eq!(tokens[0]["formatted_balance"], "2.5");
+
+    let id = added["id"].as_str().unwrap();
+    let (code, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/user-tokens/{}", id),
+            Some(&token),
+            Some(json!({ "hidden": true })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (_, tokens) = app.request(Method::GET, uri, Some(&token), None).await;
+    assert_eq!(tokens, json!([]));
+
+    let (code, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/api/v2/user-tokens/{}", id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (_, list) = app
+        .request(Method::GET, "/api/v2/user-tokens", Some(&token), None)
+        .await;
+    assert_eq!(list, json!([]));
+}
+
+#[tokio::test]
+async fn test_token_display_overrides() {
+    use wallet_backend::storage::models::{TokenListRow, TransactionRow};
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let usdc = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    let listed_logo = "https://example.com/usdc.png";
+    app.ethereum.add_token(usdc, Some("USDC"), 6, 2_500_000);
+    app.state.token_list.write().await.push(TokenListRow {
+        chain: "ethereum".to_string(),
+        address: usdc.to_string(),
+        symbol: "USDC".to_string(),
+        name: "USD Coin".to_string(),
+        decimals: 6,
+        logo_uri: Some(listed_logo.to_string()),
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    });
+
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", Some(&token), None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let row = TransactionRow::new(
+        account_id,
+        "ethereum".to_string(),
+        "0xtransfer".to_string(),
+        "receive".to_string(),
+        Some("0x1111111111111111111111111111111111111111".to_string()),
+        Some(address.clone()),
+        Some("2.5".to_string()),
+        Some(usdc.to_string()),
+        "confirmed".to_string(),
+        None,
+        Some(chrono::Utc::now().to_rfc3339()),
+    );
+    app.state.db.upsert_transaction(&row).await.unwrap();
+
+    for bad in ["http://icons.example.com/usdc.png", "javascript:alert(1)"] {
+        let (status, _) = app
+            .request(
+                Method::POST,
+                "/api/v2/user-tokens",
+                Some(&token),
+                Some(json!({ "chain": "ethereum", "token_address": usdc, "icon_url": bad })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", bad);
+    }
+
+    let icon = "https://icons.example.com/usdc.png";
+    let (status, added) = app
+        .request(
+            Method::POST,
+            "/api/v2/user-tokens",
+            Some(&token),
+            Some(json!({
+                "chain": "ethereum",
+                "token_address": usdc,
+                "symbol": "MYUSD",
+                "icon_url": " https://ICONS.example.com/usdc.png ",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", added);
+    assert_eq!(added["icon_url"], icon);
+
+    // The user's symbol and icon win over the token list, in balances and
+    // history alike
+    let balances = format!("/api/v2/tokens/ethereum/{}", address);
+    let (_, tokens) = app.request(Method::GET, &balances, Some(&token), None).await;
+    assert_eq!(tokens[0]["symbol"], "MYUSD");
+    assert_eq!(tokens[0]["logo_uri"], icon);
+    let v1_history = format!("/api/v1/transactions/ethereum/{}", address);
+    let v2_history = format!("/api/v2/transactions/ethereum/{}", address);
+    let (status, history) = app.request(Method::GET, &v1_history, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", history);
+    assert_eq!(history[0]["token_symbol"], "MYUSD");
+    assert_eq!(history[0]["token_logo_uri"], icon);
+
+    // Without an icon of its own the token list's logo shows again
+    let id = added["id"].as_str().unwrap();
+    let (status, updated) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/user-tokens/{}", id),
+            Some(&token),
+            Some(json!({ "icon_url": "" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(updated["icon_url"].is_null());
+    let (_, tokens) = app.request(Method::GET, &balances, Some(&token), None).await;
+    assert_eq!(tokens[0]["logo_uri"], listed_logo);
+    let (_, history) = app.request(Method::GET, &v2_history, Some(&token), None).await;
+    assert_eq!(history["items"][0]["token_symbol"], "MYUSD");
+    assert_eq!(history["items"][0]["token_logo_uri"], listed_logo);
+
+    // Hidden tokens drop out of history as they do from balances
+    app.request(
+        Method::POST,
+        &format!("/api/v2/user-tokens/{}", id),
+        Some(&token),
+        Some(json!({ "hidden": true })),
+    )
+    .await;
+    let (_, history) = app.request(Method::GET, &v1_history, Some(&token), None).await;
+    assert_eq!(history, json!([]));
+    let (_, history) = app.request(Method::GET, &v2_history, Some(&token), None).await;
+    assert_eq!(history["items"], json!([]));
+}
+
+#[tokio::test]
+async fn test_rent_endpoints_reject_bad_addresses() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    app.create_wallet_with_account("solana").await;
+
+    let (status, _) = app
+        .request(Method::GET, "/api/v2/solana/rent/not-an-address", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = app
+        .request(Method::GET, "/api/v2/solana/rent/11111111111111111111111111111111", None, None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Only the wallet's own accounts can be closed
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/solana/rent/reclaim",
+            Some(&token),
+            Some(json!({ "address": "11111111111111111111111111111111" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_request_id_propagated_to_errors() {
+    let app = TestApp::spawn().await;
+
+    let request = Request::get("/api/v2/users/me")
+        .header("X-Request-Id", "trace-abc-123")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send(request).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["x-request-id"], "trace-abc-123");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["request_id"], "trace-abc-123");
+
+    // Unusable IDs are replaced rather than logged
+    let request = Request::get("/api/v2/auth/status")
+        .header("X-Request-Id", "bad id\twith spaces")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send(request).await;
+    let id = response.headers()["x-request-id"].to_str().unwrap();
+    assert!(uuid::Uuid::parse_str(id).is_ok());
+}
+
+#[tokio::test]
+async fn test_contact_addresses_validated() {
+    let app = TestApp::spawn().await;
+    app.create_wallet_with_account("ethereum").await;
+    let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let create = |name: &str, chain: &str, address: &str| {
+        let body = json!({ "name": name, "chain": chain, "address": address });
+        let app = &app;
+        async move { app.request(Method::POST, "/api/v2/contacts", None, Some(body)).await }
+    };
+
+    // Stored checksummed whatever the case it was entered in
+    let (status, vitalik) = create("Vitalik", "ethereum", &checksummed.to_lowercase()).await;
+    assert_eq!(status, StatusCode::OK, "{}", vitalik);
+    assert_eq!(vitalik["address"], checksummed);
+    let vitalik_id = vitalik["id"].as_str().unwrap().to_string();
+
+    for (chain, address) in [
+        ("ethereum", "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beae"),
+        ("ethereum", "0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+        ("ethereum", "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"),
+        ("solana", checksummed),
+        ("bitcoin", checksummed),
+    ] {
+        let (status, body) = create("Bad", chain, address).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{} {}: {}", chain, address, body);
+    }
+
+    // The same address again, in any case, points at the existing contact
+    let (status, body) = create("V", "ethereum", &checksummed.to_uppercase().replace("0X", "0x")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body["error"]["message"].as_str().unwrap().contains(&vitalik_id), "{}", body);
+
+    // Updates are validated the same way
+    let (status, other) = create("Other", "ethereum", "0x0000000000000000000000000000000000000001").await;
+    assert_eq!(status, StatusCode::OK);
+    let other_uri = format!("/api/v2/contacts/{}", other["id"].as_str().unwrap());
+    let (status, _) = app
+        .request(Method::POST, &other_uri, None, Some(json!({ "name": "Other", "address": checksummed })))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = app
+        .request(Method::POST, &other_uri, None, Some(json!({ "name": "Other", "address": "nope" })))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, updated) = app
+        .request(
+            Method::POST,
+            &other_uri,
+            None,
+            Some(json!({ "name": "Other", "address": "0xab5801a7d398351b8be11c439e05c5b3259aec9b" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", updated);
+    assert_eq!(updated["address"], "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B");
+
+    // Duplicates saved before validation are reported with a merge
+    let wallet_id = app.state.db.get_contact(&vitalik_id).await.unwrap().wallet_id;
+    let mut legacy = ContactRow::new(
+        wallet_id,
+        "Vitalik (old)".to_string(),
+        "ethereum".to_string(),
+        checksummed.to_lowercase(),
+        Some("met at devcon".to_string()),
+    );
+    legacy.default_amount = Some("0.1".to_string());
+    app.state.db.create_contact(&legacy).await.unwrap();
+
+    let (status, _) = app
+        .request(Method::GET, "/api/v2/contacts/duplicates", None, None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let token = app.login().await;
+    let (status, duplicates) = app
+        .request(Method::GET, "/api/v2/contacts/duplicates", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", duplicates);
+    assert_eq!(duplicates.as_array().unwrap().len(), 1);
+    let group = &duplicates[0];
+    assert_eq!(group["address"], checksummed);
+    assert_eq!(group["contacts"].as_array().unwrap().len(), 2);
+    assert_eq!(group["suggestion"]["keep_id"], vitalik_id.as_str());
+    assert_eq!(group["suggestion"]["delete_ids"], json!([legacy.id]));
+    assert_eq!(group["suggestion"]["name"], "Vitalik");
+    assert_eq!(group["suggestion"]["notes"], "met at devcon");
+    assert_eq!(group["suggestion"]["default_amount"], "0.1");
+}
+
+#[tokio::test]
+async fn test_contact_identity_resolved_and_cached() {
+    let app = TestApp::spawn().await;
+    app.create_wallet_with_account("ethereum").await;
+
+    let address = "0xd8da6bf26964af9d7eed9e10c46c6b4f5b8f4a2c";
+    app.ethereum.identities.lock().unwrap().insert(
+        address.to_string(),
+        Identity {
+            name: "vitalik.eth".to_string(),
+            avatar: Some("https://example.com/avatar.png".to_string()),
+            records: [("twitter".to_string(), "VitalikButerin".to_string())].into(),
+        },
+    );
+
+    let (code, contact) = app
+        .request(
+            Method::POST,
+            "/api/v1/contacts",
+            None,
+            Some(json!({ "name": "Vitalik", "chain": "ethereum", "address": address })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(contact["identity"]["name"], "vitalik.eth");
+    assert_eq!(contact["identity"]["records"]["twitter"], "VitalikButerin");
+    assert!(contact["identity_refreshed_at"].is_string());
+
+    // Refreshing needs a logged-in user
+    let id = contact["id"].as_str().unwrap();
+    let refresh = format!("/api/v2/contacts/{}/identity/refresh", id);
+    let (code, _) = app.request(Method::POST, &refresh, None, None).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    // A refresh right after a lookup is served from the cache
+    let token = app.login().await;
+    let (code, refreshed) = app.request(Method::POST, &refresh, Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(refreshed["identity"]["name"], "vitalik.eth");
+    assert_eq!(*app.ethereum.identity_lookups.lock().unwrap(), 1);
+
+    // Addresses without a name are cached as such
+    let (_, unnamed) = app
+        .request(
+            Method::POST,
+            "/api/v1/contacts",
+            None,
+            Some(json!({ "name": "Nobody", "chain": "ethereum", "address": "0x0000000000000000000000000000000000000001" })),
+        )
+        .await;
+    assert!(unnamed["identity"].is_null());
+    assert!(unnamed["identity_refreshed_at"].is_string());
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/contacts/missing/identity/refresh",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_tenants_are_isolated() {
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[
+        ("MULTI_TENANT", "true"),
+        ("TENANT_ADMIN_TOKEN", admin_token),
+    ])
+    .await;
+
+    // Requests that match no tenant are refused
+    let (code, _) = app
+        .request(Method::GET, "/api/v2/auth/status", None, None)
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, _) = app
+        .request(Method::GET, "/api/admin/tenants", Some("wrong"), None)
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let mut keys = Vec::new();
+    for (id, name) in [("acme", "Acme"), ("globex", "Globex")] {
+        let (code, created) = app
+            .request(
+                Method::POST,
+                "/api/admin/tenants",
+                Some(admin_token),
+                Some(json!({ "id": id, "name": name, "settings": { "theme": id } })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(created["tenant"]["has_api_key"], true);
+        keys.push(created["api_key"].as_str().unwrap().to_string());
+    }
+    let acme = [("X-Api-Key", keys[0].as_str())];
+    let globex = [("X-Api-Key", keys[1].as_str())];
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/admin/tenants",
+            Some(admin_token),
+            Some(json!({ "id": "acme", "name": "Again" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::CONFLICT);
+
+    let (_, tenant) = app
+        .request_with_headers(Method::GET, "/api/v2/tenant", &acme, None, None)
+        .await;
+    assert_eq!(tenant["name"], "Acme");
+    assert_eq!(tenant["settings"]["theme"], "acme");
+
+    // Each tenant has its own wallet
+    let (code, _) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/wallet/create",
+            &acme,
+            None,
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    let (_, status) = app
+        .request_with_headers(Method::GET, "/api/v2/auth/status", &acme, None, None)
+        .await;
+    assert_eq!(status["has_wallet"], true);
+    let (_, status) = app
+        .request_with_headers(Method::GET, "/api/v2/auth/status", &globex, None, None)
+        .await;
+    assert_eq!(status["has_wallet"], false);
+
+    // Users belong to the tenant they registered with
+    let credentials = json!({ "email": "alice@example.com", "password": PASSWORD });
+    let (code, _) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/users/register",
+            &acme,
+            None,
+            Some(credentials.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    let (code, _) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/users/login",
+            &globex,
+            None,
+            Some(credentials.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (_, login) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/users/login",
+            &acme,
+            None,
+            Some(credentials),
+        )
+        .await;
+    let token = login["access_token"].as_str().unwrap();
+
+    let (code, _) = app
+        .request_with_headers(Method::GET, "/api/v2/users/me", &acme, Some(token), None)
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (code, _) = app
+        .request_with_headers(Method::GET, "/api/v2/users/me", &globex, Some(token), None)
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    // Another tenant's addresses are unknown to its users
+    let (code, account) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/accounts",
+            &acme,
+            Some(token),
+            Some(json!({ "chain": "solana" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let history = format!("/transactions/solana/{}", account["address"].as_str().unwrap());
+    let credentials = json!({ "email": "bob@example.com", "password": PASSWORD });
+    for path in ["/api/v2/users/register", "/api/v2/users/login"] {
+        let (code, _) = app
+            .request_with_headers(Method::POST, path, &globex, None, Some(credentials.clone()))
+            .await;
+        assert_eq!(code, StatusCode::OK);
+    }
+    let (_, login) = app
+        .request_with_headers(Method::POST, "/api/v2/users/login", &globex, None, Some(credentials))
+        .await;
+    let other = login["access_token"].as_str().unwrap();
+    let (code, _) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/wallet/create",
+            &globex,
+            None,
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    for version in ["v1", "v2"] {
+        let path = format!("/api/{}{}", version, history);
+        let (code, _) = app
+            .request_with_headers(Method::GET, &path, &acme, Some(token), None)
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", path);
+        let (code, _) = app
+            .request_with_headers(Method::GET, &path, &globex, Some(other), None)
+            .await;
+        assert_eq!(code, StatusCode::NOT_FOUND, "{}", path);
+    }
+
+    // Disabled tenants and rotated keys stop working
+    let (code, rotated) = app
+        .request(
+            Method::POST,
+            "/api/admin/tenants/acme/api-key",
+            Some(admin_token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (code, _) = app
+        .request_with_headers(Method::GET, "/api/v2/auth/status", &acme, None, None)
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let (code, _) = app
+        .request_with_headers(
+            Method::GET,
+            "/api/v2/auth/status",
+            &[("X-Api-Key", rotated["api_key"].as_str().unwrap())],
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/admin/tenants/globex",
+            Some(admin_token),
+            Some(json!({ "is_active": false })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (code, _) = app
+        .request_with_headers(Method::GET, "/api/v2/auth/status", &globex, None, None)
+        .await;
+    assert_eq!(code, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_wallet_signature_login() {
+    use ethers::signers::{LocalWallet, Signer as _};
+    use solana_sdk::signature::{Keypair, Signer as _};
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+
+    let eth_wallet = LocalWallet::new(&mut rand::thread_rng());
+    let eth_address = ethers::utils::to_checksum(&eth_wallet.address(), None);
+    let sol_wallet = Keypair::new();
+    let sol_address = sol_wallet.pubkey().to_string();
+
+    // Issue a message, then return its nonce with the wallet's signature
+    let eth_sign_in = || async {
+        let (code, challenge) = app
+            .request(
+                Method::POST,
+                "/api/v2/users/wallet-login/challenge",
+                None,
+                Some(json!({ "chain": "ethereum", "address": eth_address })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK);
+        let message = challenge["message"].as_str().unwrap();
+        assert!(message.contains("wants you to sign in with your Ethereum account"));
+        assert!(message.contains("Chain ID: 11155111"));
+        let signature = eth_wallet.sign_message(message).await.unwrap();
+        json!({ "nonce": challenge["nonce"], "signature": signature.to_string() })
+    };
+
+    // Unlinked addresses cannot sign in
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/wallet-login",
+            None,
+            Some(eth_sign_in().await),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (code, linked) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/addresses",
+            Some(&token),
+            Some(eth_sign_in().await),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(linked["address"], eth_address.as_str());
+
+    // A signature from the linked wallet yields a session for its user
+    let signed = eth_sign_in().await;
+    let (code, session) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/wallet-login",
+            None,
+            Some(signed.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(session["user"]["email"], "alice@example.com");
+    let (code, _) = app
+        .request(
+            Method::GET,
+            "/api/v2/users/me",
+            session["access_token"].as_str(),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    // Nonces are single-use
+    let (code, _) = app
+        .request(Method::POST, "/api/v2/users/wallet-login", None, Some(signed))
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    // Solana: a signature by another key is rejected
+    let (_, challenge) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/wallet-login/challenge",
+            None,
+            Some(json!({ "chain": "solana", "address": sol_address })),
+        )
+        .await;
+    let message = challenge["message"].as_str().unwrap();
+    assert!(message.contains("wants you to sign in with your Solana account"));
+    let forged = Keypair::new().sign_message(message.as_bytes());
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/addresses",
+            Some(&token),
+            Some(json!({ "nonce": challenge["nonce"], "signature": forged.to_string() })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+
+    let (_, challenge) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/wallet-login/challenge",
+            None,
+            Some(json!({ "chain": "solana", "address": sol_address })),
+        )
+        .await;
+    let signature = sol_wallet.sign_message(challenge["message"].as_str().unwrap().as_bytes());
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/addresses",
+            Some(&token),
+            Some(json!({ "nonce": challenge["nonce"], "signature": signature.to_string() })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    let (_, addresses) = app
+        .request(Method::GET, "/api/v2/users/addresses", Some(&token), None)
+        .await;
+    assert_eq!(addresses.as_array().unwrap().len(), 2);
+
+    // Unlinking stops the address from signing in
+    let uri = format!("/api/v2/users/addresses/{}", linked["id"].as_str().unwrap());
+    let (code, _) = app.request(Method::DELETE, &uri, Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK);
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/wallet-login",
+            None,
+            Some(eth_sign_in().await),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+}
+
+/// Google- and GitHub-style token and userinfo endpoints on a local port. The
+/// access token is the authorization code, which picks the account returned.
+async fn spawn_oauth_provider() -> String {
+    use std::collections::HashMap;
+
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Form, Json, Router};
+
+    let router = Router::new()
+        .route(
+            "/token",
+            post(|Form(form): Form<HashMap<String, String>>| async move {
+                assert_eq!(form["grant_type"], "authorization_code");
+                assert!(form.contains_key("code_verifier"));
+                Json(json!({ "access_token": form["code"], "token_type": "Bearer" }))
+            }),
+        )
+        .route(
+            "/google/userinfo",
+            get(|headers: HeaderMap| async move {
+                let token = headers["authorization"].to_str().unwrap().to_string();
+                Json(match token.as_str() {
+                    "Bearer alice" => json!({
+                        "sub": "google-alice",
+                        "email": "alice@example.com",
+                        "email_verified": true,
+                    }),
+                    _ => json!({
+                        "sub": "google-mallory",
+                        "email": "mallory@example.com",
+                        "email_verified": false,
+                    }),
+                })
+            }),
+        )
+        .route("/github/user", get(|| async { Json(json!({ "id": 42, "email": null })) }))
+        .route(
+            "/github/user/emails",
+            get(|| async {
+                Json(json!([
+                    { "email": "bob@old.example.com", "primary": false, "verified": true },
+                    { "email": "bob@example.com", "primary": true, "verified": true },
+                ]))
+            }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_jwt_signing_key_rotation() {
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[
+        ("TENANT_ADMIN_TOKEN", admin_token),
+        ("FIELD_ENCRYPTION_KEY", "fedcba9876543210fedcba9876543210"),
+    ])
+    .await;
+    let app = &app;
+    let original = app.login().await;
+    let kid = |token: &str| jsonwebtoken::decode_header(token).unwrap().kid.unwrap();
+    assert_eq!(kid(&original), "default");
+
+    let login = || async {
+        let (status, body) = app
+            .request(
+                Method::POST,
+                "/api/v2/users/login",
+                None,
+                Some(json!({ "email": "alice@example.com", "password": PASSWORD })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        body["access_token"].as_str().unwrap().to_string()
+    };
+    let me = |token: String| async move {
+        app.request(Method::GET, "/api/v2/users/me", Some(&token), None)
+            .await
+            .0
+    };
+
+    let (status, first) = app
+        .request(Method::POST, "/api/admin/jwt-keys", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let first_kid = first["kid"].as_str().unwrap().to_string();
+    let rotated = login().await;
+    assert_eq!(kid(&rotated), first_kid);
+
+    let (_, second) = app
+        .request(Method::POST, "/api/admin/jwt-keys", Some(admin_token), None)
+        .await;
+    let second_kid = second["kid"].as_str().unwrap().to_string();
+    let newest = login().await;
+    assert_eq!(kid(&newest), second_kid);
+
+    // Every active key is accepted
+    assert_eq!(me(original.clone()).await, StatusCode::OK);
+    assert_eq!(me(rotated.clone()).await, StatusCode::OK);
+    assert_eq!(me(newest.clone()).await, StatusCode::OK);
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/admin/jwt-keys/{}/retire", first_kid),
+            Some(admin_token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(me(rotated).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(me(original.clone()).await, StatusCode::OK);
+    assert_eq!(me(newest).await, StatusCode::OK);
+
+    let (_, keys) = app
+        .request(Method::GET, "/api/admin/jwt-keys", Some(admin_token), None)
+        .await;
+    assert_eq!(keys[0]["kid"], second_kid.as_str());
+    assert_eq!(keys[0]["current"], true);
+    assert!(keys[1]["retired_at"].is_string());
+
+    // Retiring the newest key hands signing back to JWT_SECRET
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/admin/jwt-keys/{}/retire", second_kid),
+            Some(admin_token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(kid(&login().await), "default");
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &format!("/api/admin/jwt-keys/{}/retire", second_kid),
+            Some(admin_token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // JWT_SECRET can be retired too, but not while it's the only key
+    let retire = |kid: String| async move {
+        app.request(
+            Method::POST,
+            &format!("/api/admin/jwt-keys/{}/retire", kid),
+            Some(admin_token),
+            None,
+        )
+        .await
+        .0
+    };
+    assert_eq!(retire("default".to_string()).await, StatusCode::CONFLICT);
+    let (_, third) = app
+        .request(Method::POST, "/api/admin/jwt-keys", Some(admin_token), None)
+        .await;
+    let third_kid = third["kid"].as_str().unwrap().to_string();
+    assert_eq!(retire("default".to_string()).await, StatusCode::NO_CONTENT);
+    assert_eq!(me(original).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(kid(&login().await), third_kid);
+    assert_eq!(retire(third_kid).await, StatusCode::CONFLICT);
+    assert_eq!(retire("default".to_string()).await, StatusCode::NOT_FOUND);
+
+    // Secrets were sealed on write, so the backfill finds nothing to do
+    assert_eq!(app.state.user_service.encrypt_signing_keys().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_oauth_login() {
+    let provider = spawn_oauth_provider().await;
+    let urls = [
+        format!("{}/token", provider),
+        format!("{}/google/userinfo", provider),
+        format!("{}/github/user", provider),
+    ];
+    let app = TestApp::spawn_with_env(&[
+        ("GOOGLE_CLIENT_ID", "google-client"),
+        ("GOOGLE_CLIENT_SECRET", "google-secret"),
+        ("GOOGLE_TOKEN_URL", &urls[0]),
+        ("GOOGLE_USERINFO_URL", &urls[1]),
+        ("GITHUB_CLIENT_ID", "github-client"),
+        ("GITHUB_CLIENT_SECRET", "github-secret"),
+        ("GITHUB_TOKEN_URL", &urls[0]),
+        ("GITHUB_USERINFO_URL", &urls[2]),
+    ])
+    .await;
+
+    let (_, providers) = app
+        .request(Method::GET, "/api/v2/users/oauth/providers", None, None)
+        .await;
+    assert_eq!(providers["providers"], json!(["google", "github"]));
+
+    // Authorize, then return to the callback with `code`
+    let app = &app;
+    let oauth_login = |provider: &'static str, code: &'static str| async move {
+        let (status, authorize) = app
+            .request(
+                Method::POST,
+                &format!("/api/v2/users/oauth/{}/authorize", provider),
+                None,
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        let url = authorize["authorization_url"].as_str().unwrap();
+        assert!(url.contains("code_challenge_method=S256"));
+        assert!(url.contains(&format!("{}-client", provider)));
+
+        let body = json!({ "code": code, "state": authorize["state"] });
+        let response = app
+            .request(
+                Method::POST,
+                &format!("/api/v2/users/oauth/{}/callback", provider),
+                None,
+                Some(body.clone()),
+            )
+            .await;
+        (response, body)
+    };
+
+    // A verified Google email links to the existing password account
+    let token = app.login().await;
+    let (_, me) = app
+        .request(Method::GET, "/api/v2/users/me", Some(&token), None)
+        .await;
+    let ((status, session), replay) = oauth_login("google", "alice").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(session["user"]["id"], me["id"]);
+    assert!(session["access_token"].is_string());
+
+    // States are single-use
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/oauth/google/callback",
+            None,
+            Some(replay),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Unverified emails neither link nor register
+    let ((status, _), _) = oauth_login("google", "mallory").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // A new GitHub account registers with its primary verified email, and
+    // later logins find the same user
+    let ((status, first), _) = oauth_login("github", "bob").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["user"]["email"], "bob@example.com");
+    assert_eq!(first["user"]["email_verified"], true);
+    let ((_, second), _) = oauth_login("github", "bob").await;
+    assert_eq!(second["user"]["id"], first["user"]["id"]);
+
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/users/oauth/gitlab/authorize", None, None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_body_limits_and_json_guards() {
+    let app = TestApp::spawn().await;
+    let post = |uri: &str, body: String| {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    };
+    let error_code = |bytes: &[u8]| -> String {
+        let body: serde_json::Value = serde_json::from_slice(bytes).unwrap();
+        body["error"]["code"].as_str().unwrap().to_string()
+    };
+
+    // Auth endpoints take credentials only
+    let padding = "x".repeat(20_000);
+    let response = app
+        .send(post(
+            "/api/v2/users/login",
+            json!({ "email": "a@example.com", "password": padding }).to_string(),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(error_code(&bytes), "payload_too_large");
+
+    // The same body fits the default limit
+    let response = app
+        .send(post(
+            "/api/v2/contacts",
+            json!({ "name": "x", "notes": padding }).to_string(),
+        ))
+        .await;
+    assert_ne!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let response = app
+        .send(post(
+            "/api/v2/users/login",
+            r#"{"email": "a@example.com", "password": "one", "password": "two"}"#.to_string(),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&bytes).contains("duplicate JSON key 'password'"));
+
+    let nested = format!("{}{}", "[".repeat(40), "]".repeat(40));
+    let response = app.send(post("/api/v2/contacts", nested)).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_rpc_usage_metrics() {
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+    let address = app.create_wallet_with_account("solana").await;
+
+    for _ in 0..2 {
+        let (status, _) = app
+            .request(Method::GET, &format!("/api/v2/balances/solana/{}", address), None, None)
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = app.request(Method::GET, "/metrics", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let text = body.as_str().unwrap();
+    assert!(text.contains(
+        r#"rpc_calls_total{chain="solana",method="balance",outcome="ok",provider="api.devnet.solana.com"} 2"#
+    ));
+    assert!(text.contains("rpc_call_duration_seconds_bucket"));
+
+    let (status, _) = app
+        .request(Method::GET, "/api/v1/admin/rpc-usage", None, None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, usage) = app
+        .request(Method::GET, "/api/v1/admin/rpc-usage", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let providers = usage["providers"].as_array().unwrap();
+    assert_eq!(providers.len(), 1);
+    assert_eq!(providers[0]["chain"], "solana");
+    assert_eq!(providers[0]["provider"], "api.devnet.solana.com");
+    assert_eq!(providers[0]["calls"], 2);
+    assert_eq!(providers[0]["failure_rate"], 0.0);
+    assert_eq!(providers[0]["methods"][0]["method"], "balance");
+}
+
+#[tokio::test]
+async fn test_admin_dashboard() {
+    use wallet_backend::storage::models::{AuditEventRow, AuditSeverity};
+
+    // Not served at all without an admin token
+    let app = TestApp::spawn().await;
+    let response = app.send(Request::get("/admin").body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+
+    let response = app.send(Request::get("/admin").body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/html; charset=utf-8");
+    let csp = response.headers()["content-security-policy"].to_str().unwrap();
+    assert!(csp.contains("script-src 'self'"));
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("/admin/dashboard.js"));
+
+    let response = app
+        .send(Request::get("/admin/dashboard.js").body(Body::empty()).unwrap())
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/javascript; charset=utf-8");
+    let response = app
+        .send(Request::get("/admin/missing.js").body(Body::empty()).unwrap())
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    app.state
+        .db
+        .record_audit_event(&AuditEventRow::new(
+            "default".to_string(),
+            None,
+            "test.dashboard",
+            AuditSeverity::Warning,
+            json!({}),
+        ))
+        .await
+        .unwrap();
+
+    let (status, _) = app.request(Method::GET, "/api/v2/admin/status", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, report) = app
+        .request(Method::GET, "/api/v2/admin/status", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["health"]["status"], "ok");
+    assert_eq!(report["health"]["safe_mode"], false);
+    assert_eq!(report["queues"]["pending_confirmations"], 0);
+    assert_eq!(report["queues"]["siem_outbox"], 0);
+    assert!(report["rpc"]["providers"].is_array());
+    assert_eq!(report["recent_audit_events"][0]["event"], "test.dashboard");
+}
+
+/// Local Ethereum node answering every JSON-RPC call with block 0x3e8;
+/// returns its URL
+async fn spawn_eth_node() -> String {
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    let router = Router::new().route(
+        "/",
+        post(|Json(body): Json<serde_json::Value>| async move {
+            Json(json!({ "jsonrpc": "2.0", "id": body["id"], "result": "0x3e8" }))
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}/", addr)
+}
+
+#[tokio::test]
+async fn test_account_rpc_override() {
+    let node_url = spawn_eth_node().await;
+
+    // By default a node on this machine is refused before it is contacted
+    let app = TestApp::spawn().await;
+    app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let path = format!("/api/v2/accounts/{}/rpc", accounts[0]["id"].as_str().unwrap());
+    let node = json!({ "rpc_url": node_url });
+
+    let (status, _) = app.request(Method::PUT, &path, None, Some(node.clone())).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = app
+        .request(Method::PUT, &path, Some(&token), Some(node.clone()))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]["message"].as_str().unwrap().contains("public host"), "{}", body);
+
+    let app = TestApp::spawn_with_env(&[("ACCOUNT_RPC_ALLOW_PRIVATE", "true")]).await;
+    app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let path = format!("/api/v2/accounts/{}/rpc", accounts[0]["id"].as_str().unwrap());
+
+    let (status, _) = app
+        .request(Method::PUT, &path, Some(&token), Some(json!({ "rpc_url": "ftp://node" })))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Nothing listens there, so the health check fails and nothing is saved;
+    // the reason isn't passed on
+    let (status, body) = app
+        .request(
+            Method::PUT,
+            &path,
+            Some(&token),
+            Some(json!({ "rpc_url": "http://127.0.0.1:1/" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert!(body["error"]["message"].as_str().unwrap().ends_with("RPC unreachable"), "{}", body);
+
+    let (status, account) = app
+        .request(Method::PUT, &path, Some(&token), Some(node.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", account);
+    assert_eq!(account["rpc_url"], node_url.as_str());
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    assert_eq!(accounts[0]["rpc_url"], node_url.as_str());
+
+    let (status, account) = app
+        .request(Method::PUT, &path, Some(&token), Some(json!({ "rpc_url": "" })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(account["rpc_url"].is_null());
+
+    let (status, _) = app
+        .request(Method::PUT, "/api/v2/accounts/missing/rpc", Some(&token), Some(node))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// Local webhook receiver; returns its URL and the bodies it was sent
+async fn spawn_webhook_receiver() -> (String, std::sync::Arc<std::sync::Mutex<Vec<serde_json::Value>>>) {
+    use std::sync::{Arc, Mutex};
+
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let router = Router::new().route(
+        "/hook",
+        post(move |Json(body): Json<serde_json::Value>| async move {
+            sink.lock().unwrap().push(body);
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}/hook", addr), received)
+}
+
+#[tokio::test]
+async fn test_balance_alerts() {
+    use wallet_backend::services::alert_service;
+
+    let app = TestApp::spawn().await;
+    let (webhook_url, received) = spawn_webhook_receiver().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+
+    let (status, below) = app
+        .request(
+            Method::POST,
+            "/api/v2/alerts",
+            Some(&token),
+            Some(json!({
+                "account_id": account_id,
+                "kind": "balance_below",
+                "threshold": "1",
+                "webhook_url": webhook_url,
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", below);
+    let (status, outflow) = app
+        .request(
+            Method::POST,
+            "/api/v2/alerts",
+            Some(&token),
+            Some(json!({
+                "account_id": account_id,
+                "kind": "outflow_over",
+                "threshold": "150",
+                "currency": "usd",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", outflow);
+    assert_eq!(outflow["currency"], "USD");
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/alerts",
+            Some(&token),
+            Some(json!({ "account_id": account_id, "kind": "balance_below", "threshold": "-1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 2 SOL, nothing sent yet
+    assert_eq!(alert_service::evaluate_alerts(&app.state).await.unwrap(), 0);
+
+    // 1.6 SOL is $160 at the mock price, and leaves the balance under 1 SOL
+    let (status, _) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "1.6",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(alert_service::evaluate_alerts(&app.state).await.unwrap(), 2);
+
+    // Neither fires again while nothing changes
+    assert_eq!(alert_service::evaluate_alerts(&app.state).await.unwrap(), 0);
+
+    let hooks = received.lock().unwrap().clone();
+    assert_eq!(hooks.len(), 1);
+    assert_eq!(hooks[0]["alert_id"], below["id"]);
+    assert_eq!(hooks[0]["balance"], "0.399995");
+
+    let (status, notifications) = app
+        .request(Method::GET, "/api/v2/alerts/notifications", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let notifications = notifications["items"].as_array().unwrap();
+    assert_eq!(notifications.len(), 2);
+    let sent = notifications
+        .iter()
+        .find(|n| n["alert_id"] == outflow["id"])
+        .unwrap();
+    assert_eq!(sent["tx_signature"], "mock-tx-1");
+    assert!(sent["message"].as_str().unwrap().contains("160.00 USD"));
+    assert!(sent["webhook_status"].is_null());
+
+    // The balance rule re-arms once the balance recovers
+    app.solana.set_balance(3_000_000_000);
+    assert_eq!(alert_service::evaluate_alerts(&app.state).await.unwrap(), 0);
+    app.solana.set_balance(500_000_000);
+    assert_eq!(alert_service::evaluate_alerts(&app.state).await.unwrap(), 1);
+
+    // Disabled rules are skipped
+    let (status, updated) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/alerts/{}", below["id"].as_str().unwrap()),
+            Some(&token),
+            Some(json!({ "is_active": false })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["is_active"], false);
+    app.solana.set_balance(3_000_000_000);
+    assert_eq!(alert_service::evaluate_alerts(&app.state).await.unwrap(), 0);
+
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/api/v2/alerts/{}", outflow["id"].as_str().unwrap()),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, alerts) = app.request(Method::GET, "/api/v2/alerts", Some(&token), None).await;
+    assert_eq!(alerts.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_webhook_catalog_and_test_delivery() {
+    use std::sync::{Arc, Mutex};
+
+    use axum::body::Bytes;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use axum::Router;
+    use wallet_backend::services::webhook_service;
+
+    // Records each POST's headers and raw body
+    let received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let router = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: Bytes| async move {
+            sink.lock().unwrap().push((headers, body));
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    let app = TestApp::spawn().await;
+
+    // The catalog is public
+    let (status, catalog) = app.request(Method::GET, "/api/v2/webhooks/events", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(catalog["version"], 1);
+    assert_eq!(catalog["signature"]["header"], "X-Valtix-Signature");
+    let events = catalog["events"].as_array().unwrap();
+    let alert_event = events.iter().find(|e| e["type"] == "alert.fired").unwrap();
+    assert_eq!(alert_event["schema"]["type"], "object");
+    assert_eq!(alert_event["sample"]["kind"], "balance_below");
+    assert!(events.iter().any(|e| e["type"] == "webhook.test"));
+
+    let token = app.login().await;
+    app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let (status, alert) = app
+        .request(
+            Method::POST,
+            "/api/v2/alerts",
+            Some(&token),
+            Some(json!({
+                "account_id": account_id,
+                "kind": "balance_below",
+                "threshold": "1",
+                "webhook_url": format!("http://{}/hook", addr),
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let alert_id = alert["id"].as_str().unwrap();
+
+    let (status, webhooks) = app.request(Method::GET, "/api/v2/webhooks", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(webhooks[0]["id"], alert_id);
+    assert_eq!(webhooks[0]["source"], "alert");
+    assert_eq!(webhooks[0]["event_type"], "alert.fired");
+
+    let (status, secret) = app
+        .request(Method::GET, "/api/v2/webhooks/secret", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let secret = secret["secret"].as_str().unwrap().to_string();
+    assert!(secret.starts_with("whsec_"));
+
+    let (status, delivery) = app
+        .request(
+            Method::POST,
+            &format!("/api/v1/webhooks/{}/test", alert_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", delivery);
+    assert_eq!(delivery["status"], "delivered");
+    assert_eq!(delivery["status_code"], 200);
+    assert_eq!(delivery["event_type"], "webhook.test");
+    assert_eq!(delivery["test"], true);
+
+    let (headers, body) = received.lock().unwrap().pop().unwrap();
+    assert_eq!(headers["x-valtix-event"], "webhook.test");
+    assert_eq!(headers["x-valtix-delivery-id"], delivery["delivery_id"].as_str().unwrap());
+    assert_eq!(headers["x-valtix-event-id"], delivery["event_id"].as_str().unwrap());
+    let timestamp: i64 = headers["x-valtix-timestamp"].to_str().unwrap().parse().unwrap();
+    let signature = headers["x-valtix-signature"].to_str().unwrap();
+    let now = chrono::Utc::now().timestamp();
+    assert!(webhook_service::verify(&secret, timestamp, &body, signature, now));
+    let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(payload["webhook_id"], alert_id);
+    assert_eq!(payload["event_type"], "alert.fired");
+    assert_eq!(payload["sample"]["kind"], "balance_below");
+
+    let (status, deliveries) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/webhooks/{}/deliveries", alert_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(deliveries.as_array().unwrap().len(), 1);
+    assert_eq!(deliveries[0]["delivery_id"], delivery["delivery_id"]);
+
+    // After rotation deliveries are signed with the new secret
+    let (status, rotated) = app
+        .request(Method::POST, "/api/v2/webhooks/secret/rotate", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let rotated = rotated["secret"].as_str().unwrap().to_string();
+    assert_ne!(rotated, secret);
+    app.request(
+        Method::POST,
+        &format!("/api/v2/webhooks/{}/test", alert_id),
+        Some(&token),
+        None,
+    )
+    .await;
+    let (headers, body) = received.lock().unwrap().pop().unwrap();
+    let timestamp: i64 = headers["x-valtix-timestamp"].to_str().unwrap().parse().unwrap();
+    let signature = headers["x-valtix-signature"].to_str().unwrap();
+    assert!(webhook_service::verify(&rotated, timestamp, &body, signature, now));
+    assert!(!webhook_service::verify(&secret, timestamp, &body, signature, now));
+
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/webhooks/missing/test", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app
+        .request(Method::POST, &format!("/api/v2/webhooks/{}/test", alert_id), None, None)
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_price_alerts() {
+    use wallet_backend::services::price_alert_service::evaluate_price_alerts;
+
+    let app = TestApp::spawn().await;
+    let (webhook_url, received) = spawn_webhook_receiver().await;
+    let token = app.login().await;
+    let usdc = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    app.ethereum.add_token(usdc, Some("USDC"), 6, 0);
+    app.prices.token_quotes.lock().unwrap().insert(usdc.to_string(), 1.0);
+
+    let (status, above) = app
+        .request(
+            Method::POST,
+            "/api/v2/alerts/prices",
+            Some(&token),
+            Some(json!({
+                "chain": "solana",
+                "kind": "price_above",
+                "threshold": "150",
+                "webhook_url": webhook_url,
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", above);
+    assert_eq!(above["symbol"], "SOL");
+    assert_eq!(above["currency"], "USD");
+    assert!(above["window_hours"].is_null());
+    let (status, drop) = app
+        .request(
+            Method::POST,
+            "/api/v2/alerts/prices",
+            Some(&token),
+            Some(json!({ "chain": "ethereum", "kind": "drops_by", "threshold": "10" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", drop);
+    assert_eq!(drop["window_hours"], 24);
+    let (status, depeg) = app
+        .request(
+            Method::POST,
+            "/api/v2/alerts/prices",
+            Some(&token),
+            Some(json!({
+                "chain": "ethereum",
+                "token_address": usdc.to_uppercase().replace("0X", "0x"),
+                "kind": "price_below",
+                "threshold": "0.95",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", depeg);
+    assert_eq!(depeg["token_address"], usdc);
+    assert_eq!(depeg["symbol"], "USDC");
+
+    for body in [
+        json!({ "chain": "solana", "kind": "price", "threshold": "1" }),
+        json!({ "chain": "solana", "kind": "drops_by", "threshold": "100" }),
+        json!({ "chain": "solana", "kind": "rises_by", "threshold": "5", "window_hours": 0 }),
+        json!({ "chain": "bitcoin", "kind": "price_above", "threshold": "1" }),
+    ] {
+        let (status, _) = app
+            .request(Method::POST, "/api/v2/alerts/prices", Some(&token), Some(body))
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    // $100 and $1: nothing holds, and there's no earlier price to drop from
+    assert_eq!(evaluate_price_alerts(&app.state).await.unwrap(), 0);
+
+    *app.prices.quote.lock().unwrap() = Some((160.0, chrono::Utc::now()));
+    assert_eq!(evaluate_price_alerts(&app.state).await.unwrap(), 1);
+    // Still above, so it doesn't fire again
+    assert_eq!(evaluate_price_alerts(&app.state).await.unwrap(), 0);
+
+    // 15% under the first price, and USDC off its peg
+    *app.prices.quote.lock().unwrap() = Some((85.0, chrono::Utc::now()));
+    app.prices.token_quotes.lock().unwrap().insert(usdc.to_string(), 0.9);
+    assert_eq!(evaluate_price_alerts(&app.state).await.unwrap(), 2);
+
+    // The SOL rule re-armed when the price fell back
+    *app.prices.quote.lock().unwrap() = Some((160.0, chrono::Utc::now()));
+    assert_eq!(evaluate_price_alerts(&app.state).await.unwrap(), 1);
+
+    let hooks = received.lock().unwrap().clone();
+    assert_eq!(hooks.len(), 2);
+    assert_eq!(hooks[0]["price_alert_id"], above["id"]);
+    assert_eq!(hooks[0]["price"], "160");
+
+    let (status, notifications) = app
+        .request(Method::GET, "/api/v2/alerts/notifications", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let notifications = notifications["items"].as_array().unwrap();
+    assert_eq!(notifications.len(), 4);
+    assert!(notifications.iter().all(|n| n["alert_id"].is_null()));
+    let dropped = notifications
+        .iter()
+        .find(|n| n["price_alert_id"] == drop["id"])
+        .unwrap();
+    assert_eq!(dropped["price"], "85");
+    assert!(dropped["message"].as_str().unwrap().contains("ETH dropped 15.00% in 24h"));
+    assert_eq!(dropped["webhook_status"], serde_json::Value::Null);
+
+    let (status, updated) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/alerts/prices/{}", above["id"].as_str().unwrap()),
+            Some(&token),
+            Some(json!({ "threshold": "200", "is_active": false })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["threshold"], "200");
+    assert_eq!(updated["is_active"], false);
+    assert_eq!(updated["triggered"], false);
+
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/api/v2/alerts/prices/{}", depeg["id"].as_str().unwrap()),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, alerts) = app
+        .request(Method::GET, "/api/v2/alerts/prices", Some(&token), None)
+        .await;
+    assert_eq!(alerts.as_array().unwrap().len(), 2);
+    let (_, notifications) = app
+        .request(Method::GET, "/api/v2/alerts/notifications", Some(&token), None)
+        .await;
+    assert_eq!(notifications["items"].as_array().unwrap().len(), 3);
+    assert!(notifications.get("total").is_none());
+
+    // Paged newest first, counted on request
+    let uri = "/api/v2/alerts/notifications?limit=2&include_total=true";
+    let (_, first) = app.request(Method::GET, uri, Some(&token), None).await;
+    assert_eq!(first["items"].as_array().unwrap().len(), 2);
+    assert_eq!(first["has_more"], true);
+    assert_eq!(first["total"], 3);
+    let uri = format!(
+        "/api/v2/alerts/notifications?limit=2&cursor={}",
+        first["next_cursor"].as_str().unwrap()
+    );
+    let (_, second) = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(second["items"].as_array().unwrap().len(), 1);
+    assert_eq!(second["has_more"], false);
+    assert!(second["next_cursor"].is_null());
+    assert_eq!(second["items"][0]["id"], notifications["items"][2]["id"]);
+}
+
+#[tokio::test]
+async fn test_nft_ownership_changes() {
+    use wallet_backend::chains::NftHolder;
+    use wallet_backend::storage::models::NftCacheRow;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+
+    let stale = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+    for mint in ["kept", "listed", "sold", "burned", "unknown"] {
+        let mut nft = NftCacheRow::new(
+            account_id.clone(),
+            "solana".to_string(),
+            mint.to_string(),
+            "1".to_string(),
+            Some(mint.to_string()),
+            None,
+            None,
+            None,
+            None,
+        );
+        nft.last_updated = stale.clone();
+        app.state.db.upsert_nft(&nft).await.unwrap();
+    }
+    {
+        let mut holders = app.solana.nft_holders.lock().unwrap();
+        holders.insert("kept".to_string(), NftHolder::Address(address.clone()));
+        holders.insert("listed".to_string(), NftHolder::Escrow("market-escrow".to_string()));
+        holders.insert("sold".to_string(), NftHolder::Address("buyer".to_string()));
+        holders.insert("burned".to_string(), NftHolder::Burned);
+    }
+
+    let (status, nfts) = app
+        .request(Method::GET, &format!("/api/v2/nfts/solana/{}", address), None, None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", nfts);
+    let mut nfts: Vec<(String, bool)> = nfts["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| (n["token_address"].as_str().unwrap().to_string(), n["listed"] == true))
+        .collect();
+    nfts.sort();
+    // A failed lookup keeps the cached entry
+    assert_eq!(
+        nfts,
+        vec![
+            ("kept".to_string(), false),
+            ("listed".to_string(), true),
+            ("unknown".to_string(), false),
+        ]
+    );
+
+    let (status, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", address),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", history);
+    let mut events: Vec<(String, String, serde_json::Value)> = history["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tx| {
+            (
+                tx["token_address"].as_str().unwrap().to_string(),
+                tx["tx_type"].as_str().unwrap().to_string(),
+                tx["to_address"].clone(),
+            )
+        })
+        .collect();
+    events.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        events,
+        vec![
+            ("burned".to_string(), "nft_burn".to_string(), serde_json::Value::Null),
+            ("listed".to_string(), "nft_listing".to_string(), json!("market-escrow")),
+            ("sold".to_string(), "nft_transfer".to_string(), json!("buyer")),
+        ]
+    );
+
+    // Delisting returns the NFT to the wallet without another event
+    app.solana
+        .nft_holders
+        .lock()
+        .unwrap()
+        .insert("listed".to_string(), NftHolder::Address(address.clone()));
+    let listed = app.state.db.get_nft("solana", "listed", "1").await.unwrap();
+    let mut aged = listed.clone();
+    aged.last_updated = stale;
+    app.state.db.upsert_nft(&aged).await.unwrap();
+    let (_, nfts) = app
+        .request(Method::GET, &format!("/api/v2/nfts/solana/{}", address), None, None)
+        .await;
+    let listed = nfts["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["token_address"] == "listed")
+        .unwrap();
+    assert_eq!(listed["listed"], false);
+    assert!(listed["escrow_address"].is_null());
+}
+
+#[tokio::test]
+async fn test_send_effects_reconciled_on_confirmation() {
+    use wallet_backend::chains::{BalanceChange, ConfirmedEffects, TxEffects};
+    use wallet_backend::services::confirmation_service::reconcile_pending;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let recipient = "11111111111111111111111111111111";
+
+    for _ in 0..2 {
+        let (code, body) = app
+            .request_signed(
+                Method::POST,
+                "/api/v2/transactions/send",
+                &token,
+                Some(json!({
+                    "chain": "solana",
+                    "from_address": address,
+                    "to_address": recipient,
+                    "amount": "0.5",
+                })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+    }
+
+    let path = format!("/api/v2/transactions/solana/{}", address);
+    // Simulated changes are shown before anything has settled
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    let items = history["items"].as_array().unwrap();
+    let first = items.iter().find(|t| t["signature"] == "mock-tx-1").unwrap();
+    let changes = first["expected_changes"]["changes"].as_array().unwrap();
+    assert!(changes
+        .iter()
+        .any(|c| c["address"] == address.as_str() && c["delta"] == "-500005000"));
+    assert!(first["actual_changes"].is_null());
+    assert!(first["effects_mismatch"].is_null());
+    assert_eq!(reconcile_pending(&app.state).await.unwrap(), 0);
+
+    let landed = |sender_delta: &str, recipient_delta: &str| ConfirmedEffects {
+        status: "confirmed".to_string(),
+        block_number: Some(42),
+        confirmations: 1,
+        effects: TxEffects {
+            changes: vec![
+                BalanceChange {
+                    address: address.clone(),
+                    token: None,
+                    delta: sender_delta.to_string(),
+                },
+                BalanceChange {
+                    address: recipient.to_string(),
+                    token: None,
+                    delta: recipient_delta.to_string(),
+                },
+            ],
+            fee: "4000".to_string(),
+        },
+        receipt: None,
+    };
+    {
+        let mut confirmations = app.solana.confirmations.lock().unwrap();
+        // A lower fee than simulated is not a mismatch; a short delivery is
+        confirmations.insert("mock-tx-1".to_string(), landed("-500004000", "500000000"));
+        confirmations.insert("mock-tx-2".to_string(), landed("-500004000", "400000000"));
+    }
+    assert_eq!(reconcile_pending(&app.state).await.unwrap(), 2);
+    // Settled rows aren't polled again
+    assert_eq!(reconcile_pending(&app.state).await.unwrap(), 0);
+
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    let items = history["items"].as_array().unwrap();
+    let first = items.iter().find(|t| t["signature"] == "mock-tx-1").unwrap();
+    assert_eq!(first["effects_mismatch"], false);
+    assert_eq!(first["block_number"], 42);
+    assert_eq!(first["actual_changes"]["fee"], "4000");
+    let second = items.iter().find(|t| t["signature"] == "mock-tx-2").unwrap();
+    assert_eq!(second["effects_mismatch"], true);
+}
+
+#[tokio::test]
+async fn test_sends_confirm_at_chain_threshold() {
+    use wallet_backend::chains::{ConfirmedEffects, TxEffects, TxReceipt};
+    use wallet_backend::services::confirmation_service::reconcile_pending;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    *app.ethereum.send_status.lock().unwrap() = "pending";
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "ethereum",
+                "from_address": address,
+                "to_address": "0x71C7656EC7ab88b098defB751B7401B5f6d8976F",
+                "amount": "0.1",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+
+    let landed = |confirmations| ConfirmedEffects {
+        status: "confirmed".to_string(),
+        block_number: Some(1_001),
+        confirmations,
+        effects: TxEffects {
+            changes: Vec::new(),
+            fee: "630000000000000".to_string(),
+        },
+        receipt: Some(TxReceipt {
+            gas_used: 21_000,
+            effective_gas_price: "30000000000".to_string(),
+            succeeded: true,
+            logs_count: 0,
+            contract_address: None,
+        }),
+    };
+    let path = format!("/api/v2/transactions/ethereum/{}", address);
+
+    // Landed, but short of ETH_CONFIRMATIONS
+    app.ethereum
+        .confirmations
+        .lock()
+        .unwrap()
+        .insert("mock-tx-1".to_string(), landed(3));
+    assert_eq!(reconcile_pending(&app.state).await.unwrap(), 0);
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    let sent = &history["items"][0];
+    assert_eq!(sent["status"], "pending");
+    assert_eq!(sent["confirmations"], 3);
+    assert_eq!(sent["block_number"], 1_001);
+    assert!(sent["actual_changes"].is_null());
+
+    app.ethereum
+        .confirmations
+        .lock()
+        .unwrap()
+        .insert("mock-tx-1".to_string(), landed(12));
+    assert_eq!(reconcile_pending(&app.state).await.unwrap(), 1);
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    let sent = &history["items"][0];
+    assert_eq!(sent["status"], "confirmed");
+    assert_eq!(sent["confirmations"], 12);
+    assert_eq!(sent["receipt"]["gas_used"], 21_000);
+    assert_eq!(sent["receipt"]["effective_gas_price"], "30000000000");
+    assert_eq!(sent["fee_paid"], "630000000000000");
+
+    let (code, spending) = app
+        .request(Method::GET, "/api/v2/analytics/spending", Some(&token), None)
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", spending);
+    assert_eq!(spending["fees"][0]["chain"], "ethereum");
+    assert_eq!(spending["fees"][0]["amount"], "0.00063");
+    assert_eq!(spending["fees"][0]["count"], 1);
+}
+
+/// Local private relay status API; returns its URL and the status it
+/// reports per transaction hash (`PENDING` for others)
+async fn spawn_relay_status() -> (
+    String,
+    std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+) {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::Path;
+    use axum::routing::get;
+    use axum::{Json, Router};
+
+    let statuses = Arc::new(Mutex::new(HashMap::new()));
+    let known = statuses.clone();
+    let router = Router::new().route(
+        "/tx/:hash",
+        get(move |Path(hash): Path<String>| async move {
+            let status = known.lock().unwrap().get(&hash).cloned();
+            Json(json!({ "status": status.unwrap_or_else(|| "PENDING".to_string()) }))
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    (format!("http://{}/tx", addr), statuses)
+}
+
+#[tokio::test]
+async fn test_mev_protected_sends_fall_back_to_public() {
+    use wallet_backend::services::confirmation_service::reconcile_pending;
+
+    let (status_url, statuses) = spawn_relay_status().await;
+    let app = TestApp::spawn_with_env(&[
+        ("MEV_PROTECT_RPC_URL", "https://relay.example"),
+        ("MEV_PROTECT_STATUS_URL", status_url.as_str()),
+        ("MEV_PROTECT_TIMEOUT_BLOCKS", "5"),
+    ])
+    .await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    *app.ethereum.send_status.lock().unwrap() = "pending";
+
+    let (_, solana) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "solana" })))
+        .await;
+    let path = format!("/api/v2/accounts/{}/mev-protection", solana["id"].as_str().unwrap());
+    let enable = json!({ "enabled": true });
+    let (status, _) = app.request(Method::PUT, &path, Some(&token), Some(enable.clone())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Only a signed-in user can change it
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let path = format!("/api/v2/accounts/{}/mev-protection", accounts[0]["id"].as_str().unwrap());
+    let (status, _) = app.request(Method::PUT, &path, None, Some(enable.clone())).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, account) = app.request(Method::PUT, &path, Some(&token), Some(enable)).await;
+    assert_eq!(status, StatusCode::OK, "{}", account);
+    assert_eq!(account["mev_protect"], true);
+
+    // Protected by the account default, then opted out, then protected again
+    for mev_protect in [None, Some(false), None] {
+        let (code, body) = app
+            .request_signed(
+                Method::POST,
+                "/api/v2/transactions/send",
+                &token,
+                Some(json!({
+                    "chain": "ethereum",
+                    "from_address": address,
+                    "to_address": "0x71C7656EC7ab88b098defB751B7401B5f6d8976F",
+                    "amount": "0.01",
+                    "mev_protect": mev_protect,
+                })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+    }
+    let relays: Vec<_> = app
+        .ethereum
+        .sent
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|t| t.private_rpc.clone())
+        .collect();
+    let relay = Some("https://relay.example".to_string());
+    assert_eq!(relays, vec![relay.clone(), None, relay]);
+
+    // Still pending with the relay: nothing is rebroadcast
+    assert_eq!(reconcile_pending(&app.state).await.unwrap(), 0);
+    assert!(app.ethereum.rebroadcast.lock().unwrap().is_empty());
+
+    // The relay gave up on the first; the third is resent once it times out
+    statuses
+        .lock()
+        .unwrap()
+        .insert("mock-tx-1".to_string(), "FAILED".to_string());
+    reconcile_pending(&app.state).await.unwrap();
+    assert_eq!(*app.ethereum.rebroadcast.lock().unwrap(), vec!["0xsigned-1"]);
+
+    *app.ethereum.height.lock().unwrap() += 5;
+    reconcile_pending(&app.state).await.unwrap();
+    reconcile_pending(&app.state).await.unwrap();
+    assert_eq!(
+        *app.ethereum.rebroadcast.lock().unwrap(),
+        vec!["0xsigned-1", "0xsigned-3"]
+    );
+}
+
+#[tokio::test]
+async fn test_solana_sends_can_go_through_jito() {
+    use wallet_backend::chains::solana::JitoBundle;
+
+    let app = TestApp::spawn_with_env(&[
+        ("JITO_BLOCK_ENGINE_URL", "https://engine.example/api/v1/bundles"),
+        ("JITO_TIP_LAMPORTS", "5000"),
+    ])
+    .await;
+    let solana = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+
+    for submission_mode in [json!("jito"), json!(null), json!("rpc")] {
+        let (code, body) = app
+            .request_signed(
+                Method::POST,
+                "/api/v2/transactions/send",
+                &token,
+                Some(json!({
+                    "chain": "solana",
+                    "from_address": solana,
+                    "to_address": "11111111111111111111111111111111",
+                    "amount": "0.1",
+                    "submission_mode": submission_mode,
+                })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+    }
+    let bundles: Vec<_> = app
+        .solana
+        .sent
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|t| t.jito.clone())
+        .collect();
+    let bundle = JitoBundle {
+        block_engine_url: "https://engine.example/api/v1/bundles".to_string(),
+        tip_lamports: 5000,
+    };
+    assert_eq!(bundles, vec![Some(bundle), None, None]);
+
+    let (_, accounts) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "ethereum" })))
+        .await;
+    let (code, _) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "ethereum",
+                "from_address": accounts["address"],
+                "to_address": "0x71C7656EC7ab88b098defB751B7401B5f6d8976F",
+                "amount": "0.01",
+                "submission_mode": "jito",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+    assert!(app.ethereum.sent.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_history_reads_use_healthy_replicas() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "0.1",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    let account = app
+        .state
+        .db
+        .get_account_by_address(DEFAULT_TENANT, "solana", &address)
+        .await
+        .unwrap();
+
+    // An empty copy, so reads it serves are told apart from the primary's
+    let replica = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let db = app.state.db.clone().with_read_replicas(vec![replica.clone()]);
+    let history = || async { db.get_transactions(&account.id, 10, 0).await.unwrap().len() };
+
+    // Unchecked, then behind on schema: the primary serves reads
+    assert_eq!(history().await, 1);
+    assert_eq!(db.check_replicas().await.unwrap(), 0);
+    assert_eq!(history().await, 1);
+
+    sqlx::migrate!("./migrations").run(&replica).await.unwrap();
+    assert_eq!(db.check_replicas().await.unwrap(), 1);
+    assert_eq!(history().await, 0);
+    // Lookups by ID stay on the primary
+    assert!(db.get_account(&account.id).await.is_ok());
+
+    replica.close().await;
+    assert_eq!(db.check_replicas().await.unwrap(), 0);
+    assert_eq!(history().await, 1);
+}
+
+#[tokio::test]
+async fn test_schema_status_and_safe_mode() {
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+
+    let (status, _) = app.request(Method::GET, "/api/v1/admin/schema", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, schema) = app
+        .request(Method::GET, "/api/v1/admin/schema", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", schema);
+    assert_eq!(schema["version"], schema["expected_version"]);
+    assert_eq!(schema["pending"], json!([]));
+    assert_eq!(schema["unknown"], json!([]));
+    assert_eq!(schema["safe_mode"], false);
+
+    // Reads are served, writes refused
+    let app = TestApp::spawn_in_safe_mode(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+    let (status, schema) = app
+        .request(Method::GET, "/api/v2/admin/schema", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(schema["safe_mode"], true);
+    let (status, _) = app.request(Method::GET, "/api/v2/auth/status", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/wallet/create",
+            None,
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+/// Local server publishing a three-token Jupiter list; returns its URL
+async fn spawn_token_list() -> String {
+    use axum::routing::get;
+    use axum::{Json, Router};
+
+    let router = Router::new().route(
+        "/tokens",
+        get(|| async {
+            Json(json!([
+                {
+                    "address": "So11111111111111111111111111111111111111112",
+                    "symbol": "SOL",
+                    "name": "Wrapped SOL",
+                    "decimals": 9,
+                    "logoURI": "https://example.com/sol.png",
+                    "tags": ["verified"]
+                },
+                {
+                    "address": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                    "symbol": "USDC",
+                    "name": "USD Coin",
+                    "decimals": 6,
+                    "logoURI": "https://example.com/usdc.png"
+                },
+                {
+                    "address": "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+                    "symbol": "USDT",
+                    "name": "USDT",
+                    "decimals": 6,
+                    "logoURI": null
+                }
+            ]))
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{}/tokens", addr)
+}
+
+#[tokio::test]
+async fn test_token_list_search_and_swap_mints() {
+    use wallet_backend::services::token_list_service;
+
+    let url = spawn_token_list().await;
+    let app = TestApp::spawn_with_env(&[("TOKEN_LIST_URL", url.as_str())]).await;
+
+    // Nothing is loaded yet, so nothing is found and no mint is refused
+    let (status, found) = app.request(Method::GET, "/api/v1/tokens/search?q=usd", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(found, json!([]));
+
+    assert_eq!(token_list_service::refresh(&app.state).await.unwrap(), 3);
+
+    let (_, found) = app.request(Method::GET, "/api/v2/tokens/search?q=usd", None, None).await;
+    let symbols: Vec<_> = found.as_array().unwrap().iter().map(|t| t["symbol"].clone()).collect();
+    assert_eq!(symbols, vec![json!("USDC"), json!("USDT")]);
+    assert_eq!(found[0]["decimals"], 6);
+    assert_eq!(found[0]["logo_uri"], "https://example.com/usdc.png");
+
+    let (_, found) = app
+        .request(Method::GET, "/api/v1/tokens/search?q=usdt&limit=5&chain=solana", None, None)
+        .await;
+    assert_eq!(found[0]["address"], "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB");
+    let (_, found) = app
+        .request(Method::GET, "/api/v1/tokens/search?q=usdc&chain=ethereum", None, None)
+        .await;
+    assert_eq!(found, json!([]));
+    let (status, _) = app
+        .request(Method::GET, "/api/v1/tokens/search?q=usdc&chain=dogecoin", None, None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The list survives a restart through the database
+    app.state.token_list.write().await.clear();
+    assert_eq!(token_list_service::load(&app.state).await.unwrap(), 3);
+
+    let (status, body) = app
+        .request(
+            Method::GET,
+            "/api/v1/swap/quote?input_mint=So11111111111111111111111111111111111111112&output_mint=NotListed1111111111111111111111111111111111&amount=1000",
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.as_str().unwrap().contains("NotListed"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_error_messages_follow_accept_language() {
+    let app = TestApp::spawn().await;
+    app.create_wallet_with_account("solana").await;
+    let mut wrong = json!({ "password": "wrong password" });
+
+    let (code, english) = app.unlock(wrong.clone()).await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    assert!(english["error"]["detail"].is_null());
+
+    let (_, challenge) = app
+        .request(Method::POST, "/api/v2/auth/unlock/challenge", None, None)
+        .await;
+    wrong["nonce"] = challenge["nonce"].clone();
+    let (code, spanish) = app
+        .request_with_headers(
+            Method::POST,
+            "/api/v2/auth/unlock",
+            &[("Accept-Language", "ja, es-ES;q=0.9, en;q=0.5")],
+            None,
+            Some(wrong),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    // The code stays put; the English text is kept as detail
+    assert_eq!(spanish["error"]["code"], english["error"]["code"]);
+    assert_eq!(spanish["error"]["message"], "Se requiere autenticación");
+    assert_eq!(spanish["error"]["detail"], english["error"]["message"]);
+}
+
+#[tokio::test]
+async fn test_sync_blob_versions() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let request = |method: Method, preconditions: &[(&str, &str)], body: &'static [u8]| {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri("/api/v2/sync")
+            .header("authorization", format!("Bearer {}", token))
+            .header("x-csrf-token", "sync-csrf")
+            .header("cookie", "csrf_token=sync-csrf")
+            .header("content-type", "application/octet-stream");
+        for (name, value) in preconditions {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::from(body)).unwrap()
+    };
+
+    let response = app.send(request(Method::GET, &[], b"")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Writes must say what they expect to replace
+    let response = app.send(request(Method::PUT, &[], b"ciphertext-1")).await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_REQUIRED);
+
+    let response = app
+        .send(request(Method::PUT, &[("if-none-match", "*")], b"ciphertext-1"))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["etag"], "\"1\"");
+
+    // A second frontend that still thinks nothing is stored loses
+    let response = app
+        .send(request(Method::PUT, &[("if-none-match", "*")], b"other"))
+        .await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+    let response = app
+        .send(request(Method::PUT, &[("if-match", "\"1\"")], b"ciphertext-2"))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let blob: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(blob["version"], 2);
+    assert_eq!(blob["size"], 12);
+
+    let response = app
+        .send(request(Method::PUT, &[("if-match", "\"1\"")], b"stale"))
+        .await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+    // Stored bytes come back untouched
+    let response = app.send(request(Method::GET, &[], b"")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["etag"], "\"2\"");
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&bytes[..], b"ciphertext-2");
+
+    let response = app
+        .send(request(Method::GET, &[("if-none-match", "W/\"2\"")], b""))
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = app
+        .send(request(Method::DELETE, &[("if-match", "\"1\"")], b""))
+        .await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    let response = app
+        .send(request(Method::DELETE, &[("if-match", "\"2\"")], b""))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.send(request(Method::GET, &[], b"")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_session_key_sends_within_policy() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let program = "11111111111111111111111111111111";
+
+    let (status, created) = app
+        .request(
+            Method::POST,
+            "/api/v2/session-keys",
+            Some(&token),
+            Some(json!({
+                "account_id": account_id,
+                "max_amount": "0.5",
+                "allowed_targets": [program],
+                "expires_in_secs": 3600,
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    let id = created["session"]["id"].as_str().unwrap().to_string();
+    let key = created["session_key"].as_str().unwrap().to_string();
+
+    // Signing with the session doesn't need the wallet unlocked
+    app.request(Method::POST, "/api/v2/auth/lock", None, None)
+        .await;
+    let send_path = format!("/api/v2/session-keys/{}/send", id);
+    let (status, sent) = app
+        .request(
+            Method::POST,
+            &send_path,
+            Some(&token),
+            Some(json!({ "session_key": key, "to_address": program, "amount": "0.25" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", sent);
+    assert_eq!(sent["tx_hash"], "mock-tx-1");
+
+    // A read-only session can't sign through a session key either
+    let (_, login) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/login",
+            None,
+            Some(json!({ "email": "alice@example.com", "password": PASSWORD, "scopes": ["read"] })),
+        )
+        .await;
+    let read_token = login["access_token"].as_str().unwrap();
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &send_path,
+            Some(read_token),
+            Some(json!({ "session_key": key, "to_address": program, "amount": "0.1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+
+    // Over the limit, to a target outside the allowlist, or with the wrong key
+    for body in [
+        json!({ "session_key": key, "to_address": program, "amount": "0.75" }),
+        json!({ "session_key": key, "to_address": address, "amount": "0.1" }),
+        json!({ "session_key": "vsk_wrong", "to_address": program, "amount": "0.1" }),
+    ] {
+        let (status, _) = app
+            .request(Method::POST, &send_path, Some(&token), Some(body))
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+
+    let (_, sessions) = app
+        .request(Method::GET, "/api/v2/session-keys", Some(&token), None)
+        .await;
+    assert_eq!(sessions[0]["use_count"], 1);
+    assert!(sessions[0].get("session_key").is_none());
+
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/api/v2/session-keys/{}", id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &send_path,
+            Some(&token),
+            Some(json!({ "session_key": key, "to_address": program, "amount": "0.1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::GONE);
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_stuck_transaction_sped_up() {
+    use wallet_backend::chains::Replacement;
+    use wallet_backend::services::stuck_service::detect_stuck;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    let recipient = "0x71C7656EC7ab88b098defB751B7401B5f6d8976F";
+    *app.ethereum.send_status.lock().unwrap() = "pending";
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "ethereum",
+                "from_address": address,
+                "to_address": recipient,
+                "amount": "0.1",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+
+    // Not stuck until ETH_STUCK_BLOCKS have passed
+    *app.ethereum.height.lock().unwrap() += 10;
+    assert_eq!(detect_stuck(&app.state).await.unwrap(), 0);
+    *app.ethereum.height.lock().unwrap() += 15;
+    assert_eq!(detect_stuck(&app.state).await.unwrap(), 1);
+    assert_eq!(detect_stuck(&app.state).await.unwrap(), 0);
+
+    let (code, stuck) = app
+        .request(Method::GET, "/api/v2/transactions/stuck", Some(&token), None)
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    assert_eq!(stuck.as_array().unwrap().len(), 1);
+    assert_eq!(stuck[0]["signature"], "mock-tx-1");
+    assert_eq!(stuck[0]["reason"], "pending_too_long");
+    assert_eq!(stuck[0]["broadcast_height"], 1_000);
+    let id = stuck[0]["id"].as_str().unwrap().to_string();
+
+    let speed_up = format!("/api/v2/transactions/stuck/{}/speed-up", id);
+    let (code, replaced) = app
+        .request(Method::POST, &speed_up, Some(&token), None)
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", replaced);
+    assert_eq!(replaced["tx_hash"], "mock-tx-2");
+    // Same nonce as the original, outbidding its gas price
+    let sent = app.ethereum.sent.lock().unwrap().clone();
+    assert_eq!(sent[1].to, recipient);
+    assert_eq!(sent[1].amount, "0.1");
+    let fee = sent[1].replaces.map(|r| r.gas_price);
+    assert_eq!(
+        sent[1].replaces,
+        Some(Replacement {
+            nonce: 0,
+            gas_price: fee.unwrap(),
+        })
+    );
+
+    let (_, stuck) = app
+        .request(Method::GET, "/api/v2/transactions/stuck", Some(&token), None)
+        .await;
+    assert!(stuck.as_array().unwrap().is_empty());
+    let (code, _) = app
+        .request(Method::POST, &speed_up, Some(&token), None)
+        .await;
+    assert_eq!(code, StatusCode::CONFLICT);
+
+    let path = format!("/api/v2/transactions/ethereum/{}", address);
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    let items = history["items"].as_array().unwrap();
+    let original = items.iter().find(|t| t["signature"] == "mock-tx-1").unwrap();
+    assert_eq!(original["status"], "failed");
+    assert_eq!(original["replaced_by"], "mock-tx-2");
+    assert!(original["stuck_at"].is_string());
+}
+
+#[tokio::test]
+async fn test_history_priced_at_transaction_date() {
+    use wallet_backend::services::price_service::backfill_prices;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    app.prices.history.lock().unwrap().insert(None, 150.0);
+
+    for amount in ["0.5", "0.25"] {
+        let (code, body) = app
+            .request_signed(
+                Method::POST,
+                "/api/v2/transactions/send",
+                &token,
+                Some(json!({
+                    "chain": "solana",
+                    "from_address": address,
+                    "to_address": "11111111111111111111111111111111",
+                    "amount": amount,
+                })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+    }
+
+    let path = format!("/api/v2/transactions/solana/{}", address);
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    assert!(history["items"][0]["realized_value"].is_null());
+
+    assert_eq!(backfill_prices(&app.state).await.unwrap(), 2);
+    // Both sends are on the same day, so the second comes from the cache
+    assert_eq!(app.prices.history_lookups.lock().unwrap().len(), 1);
+    assert_eq!(backfill_prices(&app.state).await.unwrap(), 0);
+
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    let items = history["items"].as_array().unwrap();
+    let first = items.iter().find(|t| t["signature"] == "mock-tx-1").unwrap();
+    assert_eq!(first["price_at_tx"], "150");
+    assert_eq!(first["price_currency"], "USD");
+    assert_eq!(first["realized_value"], "75.00");
+
+    let export = format!("/api/v2/transactions/solana/{}/export", address);
+    let (code, csv) = app.request(Method::GET, &export, Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK);
+    let lines: Vec<&str> = csv.as_str().unwrap().lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("amount,token,price,currency,value"));
+    assert!(lines[1].contains(",mock-tx-1,send,"));
+    assert!(lines[1].ends_with(",0.5,,150,USD,75.00"));
+    assert!(lines[2].ends_with(",0.25,,150,USD,37.50"));
+}
+
+#[tokio::test]
+async fn test_spending_analytics_by_tag_and_counterparty() {
+    use wallet_backend::services::price_service::backfill_prices;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    app.prices.history.lock().unwrap().insert(None, 150.0);
+    let alice = "11111111111111111111111111111111";
+    let bob = "SysvarRent111111111111111111111111111111111";
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/contacts",
+            Some(&token),
+            Some(json!({ "name": "Alice", "chain": "solana", "address": alice })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    // Moving funds to the wallet's own account isn't spending
+    let (_, savings) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "solana" })))
+        .await;
+    let savings = savings["address"].as_str().unwrap();
+    for (to, amount) in [(alice, "0.5"), (alice, "0.25"), (bob, "0.1"), (savings, "0.3")] {
+        let (code, body) = app
+            .request_signed(
+                Method::POST,
+                "/api/v2/transactions/send",
+                &token,
+                Some(json!({
+                    "chain": "solana",
+                    "from_address": address,
+                    "to_address": to,
+                    "amount": amount,
+                })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+    }
+    backfill_prices(&app.state).await.unwrap();
+
+    let path = format!("/api/v2/transactions/solana/{}", address);
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    let items = history["items"].as_array().unwrap();
+    let id_of = |signature: &str| {
+        let tx = items.iter().find(|t| t["signature"] == signature).unwrap();
+        tx["id"].as_str().unwrap().to_string()
+    };
+    let tagged = [
+        ("mock-tx-1", json!([" Rent ", "rent"])),
+        ("mock-tx-3", json!(["food"])),
+    ];
+    for (signature, tags) in tagged {
+        let tags_path = format!("/api/v2/transactions/tags/{}", id_of(signature));
+        let (code, body) = app
+            .request(Method::PUT, &tags_path, Some(&token), Some(json!({ "tags": tags })))
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+    }
+    let tags_path = format!("/api/v2/transactions/tags/{}", id_of("mock-tx-1"));
+    let (_, tags) = app.request(Method::GET, &tags_path, Some(&token), None).await;
+    assert_eq!(tags["tags"], json!(["rent"]));
+    let (code, _) = app
+        .request(Method::PUT, &tags_path, Some(&token), Some(json!({ "tags": [""] })))
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    let (code, report) = app
+        .request(Method::GET, "/api/v2/analytics/spending", Some(&token), None)
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", report);
+    assert_eq!(report["currency"], "USD");
+    let months = report["by_month"].as_array().unwrap();
+    assert_eq!(months.len(), 1);
+    assert_eq!(months[0]["count"], 3);
+    assert_eq!(months[0]["fiat_total"], "127.50");
+    assert_eq!(months[0]["tokens"][0]["amount"], "0.85");
+
+    let tags = report["by_tag"].as_array().unwrap();
+    assert_eq!(tags.len(), 3);
+    assert_eq!(tags[0]["key"], "rent");
+    assert_eq!(tags[0]["fiat_total"], "75.00");
+    assert!(tags[1]["key"].is_null());
+    assert_eq!(tags[2]["key"], "food");
+
+    let counterparties = report["by_counterparty"].as_array().unwrap();
+    assert_eq!(counterparties[0]["key"], alice);
+    assert_eq!(counterparties[0]["name"], "Alice");
+    assert_eq!(counterparties[0]["count"], 2);
+    assert_eq!(counterparties[0]["fiat_total"], "112.50");
+    assert_eq!(counterparties[1]["key"], bob);
+    assert!(counterparties[1].get("name").is_none());
+
+    let (_, report) = app
+        .request(
+            Method::GET,
+            "/api/v2/analytics/spending?until=2000-01-01",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert!(report["by_month"].as_array().unwrap().is_empty());
+    let (code, _) = app
+        .request(
+            Method::GET,
+            "/api/v2/analytics/spending?since=2026-02-01&until=2026-01-01",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_send_with_memo_and_reference_keys() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let reference = "SysvarRent111111111111111111111111111111111";
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "0.5",
+                "memo": "order-42",
+                "references": [reference],
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    let sent = app.solana.sent.lock().unwrap().clone();
+    assert_eq!(sent[0].memo.as_deref(), Some("order-42"));
+    assert_eq!(sent[0].references, vec![reference.to_string()]);
+
+    let path = format!("/api/v2/transactions/references/{}", reference);
+    let (code, lookup) = app.request(Method::GET, &path, Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK, "{}", lookup);
+    assert_eq!(lookup["transactions"].as_array().unwrap().len(), 1);
+    assert_eq!(lookup["transactions"][0]["signature"], "mock-tx-1");
+    assert_eq!(lookup["transactions"][0]["memo"], "order-42");
+    assert_eq!(lookup["on_chain"][0]["signature"], "mock-tx-1");
+
+    let other = "SysvarC1ock11111111111111111111111111111111";
+    let path = format!("/api/v2/transactions/references/{}", other);
+    let (_, lookup) = app.request(Method::GET, &path, Some(&token), None).await;
+    assert!(lookup["transactions"].as_array().unwrap().is_empty());
+    assert!(lookup["on_chain"].as_array().unwrap().is_empty());
+
+    // Bad reference keys, oversized memos and memos on Ethereum are refused
+    let eth_address = "0x71C7656EC7ab88b098defB751B7401B5f6d8976F";
+    let sol_recipient = "11111111111111111111111111111111";
+    for (chain, from, to, memo, references) in [
+        ("solana", address.as_str(), sol_recipient, json!(null), json!(["not-a-key"])),
+        ("solana", address.as_str(), sol_recipient, json!("x".repeat(257)), json!([])),
+        ("ethereum", eth_address, eth_address, json!("order-42"), json!([])),
+    ] {
+        let (code, body) = app
+            .request_signed(
+                Method::POST,
+                "/api/v2/transactions/send",
+                &token,
+                Some(json!({
+                    "chain": chain,
+                    "from_address": from,
+                    "to_address": to,
+                    "amount": "0.1",
+                    "memo": memo,
+                    "references": references,
+                })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::BAD_REQUEST, "{}", body);
+    }
+    let (code, _) = app
+        .request(
+            Method::GET,
+            "/api/v2/transactions/references/not-a-key",
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_avatar_is_cacheable() {
+    let app = TestApp::spawn().await;
+    let uri = "/api/v1/avatar/ethereum/0x52908400098527886E0F7030069857D2E4169EE7";
+
+    let response = app.send(Request::get(uri).body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/svg+xml");
+    assert!(response.headers()["cache-control"].to_str().unwrap().contains("immutable"));
+    let etag = response.headers()["etag"].clone();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).starts_with("<svg"));
+
+    // Same identity under any casing, revalidated without a body
+    let lower = uri.to_lowercase();
+    let request = Request::get(lower.as_str())
+        .header("If-None-Match", etag)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send(request).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let (status, _) = app
+        .request(Method::GET, "/api/v2/avatar/solana/not-an-address", None, None)
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_payment_request_qr_codes() {
+    let app = TestApp::spawn().await;
+    let usdc = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    let recipient = "0x52908400098527886E0F7030069857D2E4169EE7";
+    app.ethereum.add_token(usdc, Some("USDC"), 6, 0);
+
+    let (status, body) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/qr/ethereum/{}?amount=2.5&token={}", recipient, usdc),
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["uri"],
+        format!("ethereum:{}@11155111/transfer?address={}&uint256=2500000", usdc, recipient)
+    );
+    assert!(body["qr_svg"].as_str().unwrap().starts_with("<?xml"));
+
+    // More decimals than the token has can't be paid
+    let (status, _) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/qr/ethereum/{}?amount=0.0000001&token={}", recipient, usdc),
+            None,
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .send(
+            Request::get(format!("/api/v2/qr/ethereum/{}?format=png&size=256", recipient))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/png");
+    let png = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+
+    let reference = "11111111111111111111111111111112";
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/api/v2/qr/payment-request",
+            None,
+            Some(json!({
+                "chain": "solana",
+                "recipient": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+                "amount": ".25",
+                "label": "Coffee Shop",
+                "memo": "order 42",
+                "references": [reference],
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["uri"],
+        format!(
+            "solana:9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM?amount=0.25&reference={}\
+             &label=Coffee%20Shop&memo=order%2042",
+            reference
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_scanned_qr_codes_prefill_sends() {
+    let app = TestApp::spawn().await;
+    let usdc = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    let recipient = "0x52908400098527886E0F7030069857D2E4169EE7";
+    app.ethereum.add_token(usdc, Some("USDC"), 6, 0);
+    let parse = |payload: String| {
+        app.request(Method::POST, "/api/v2/qr/parse", None, Some(json!({ "payload": payload })))
+    };
+
+    let (status, body) = parse(format!(
+        "ethereum:{}@11155111/transfer?address={}&uint256=2500000",
+        usdc, recipient
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["kind"], "eip681");
+    assert_eq!(body["chain"], "ethereum");
+    assert_eq!(body["recipient"], recipient);
+    assert_eq!(body["token"], usdc);
+    assert_eq!(body["amount"], "2.5");
+
+    let (status, body) = parse(format!("ethereum:{}?value=1.5e18", recipient)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["amount"], "1.5");
+
+    // Payments for another network are refused
+    let (status, _) = parse(format!("ethereum:{}@1?value=1", recipient)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // What the payment request endpoint produces reads back the same
+    let (_, generated) = app
+        .request(
+            Method::POST,
+            "/api/v2/qr/payment-request",
+            None,
+            Some(json!({
+                "chain": "solana",
+                "recipient": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+                "amount": ".25",
+                "label": "Coffee Shop",
+                "memo": "order 42",
+            })),
+        )
+        .await;
+    let (status, body) = parse(generated["uri"].as_str().unwrap().to_string()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["kind"], "solana_pay");
+    assert_eq!(body["recipient"], "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
+    assert_eq!(body["amount"], "0.25");
+    assert_eq!(body["label"], "Coffee Shop");
+    assert_eq!(body["memo"], "order 42");
+
+    // Unknown tokens can't be priced in decimals
+    let (status, _) = parse(format!(
+        "ethereum:0x6b175474e89094c44da98b954eedeac495271d0f/transfer?address={}&uint256=1",
+        recipient
+    ))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = parse("not a payment".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_nft_metadata_refresh_follows_reveal() {
+    use wallet_backend::chains::NftMetadata;
+    use wallet_backend::storage::models::NftCacheRow;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+
+    for mint in ["mystery", "plain", "missing"] {
+        let nft = NftCacheRow::new(
+            account_id.clone(),
+            "solana".to_string(),
+            mint.to_string(),
+            "1".to_string(),
+            Some(mint.to_string()),
+            None,
+            None,
+            None,
+            Some("Collection".to_string()),
+        );
+        app.state.db.upsert_nft(&nft).await.unwrap();
+    }
+    let published = |uri: &str, name: &str, image: &str| {
+        let document = json!({ "name": name, "image": image });
+        NftMetadata::from_document(uri.to_string(), Some(document))
+    };
+    {
+        let mut metadata = app.solana.nft_metadata.lock().unwrap();
+        metadata.insert("mystery".to_string(), published("ipfs://hidden", "Mystery", "egg.png"));
+        metadata.insert("plain".to_string(), published("ipfs://plain", "plain", "plain.png"));
+    }
+
+    let uri = format!("/api/v2/nfts/solana/{}/refresh", address);
+    let (status, _) = app.request(Method::POST, &uri, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, summary) = app.request(Method::POST, &uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", summary);
+    assert_eq!(summary["refreshed"], 2);
+    assert_eq!(summary["changed"].as_array().unwrap().len(), 2);
+    assert_eq!(summary["failed"][0]["token_address"], "missing");
+
+    // The collection reveals by pointing the token at new metadata
+    app.solana.nft_metadata.lock().unwrap().insert(
+        "mystery".to_string(),
+        published("ipfs://revealed/1", "Dragon #1", "dragon.png"),
+    );
+    let (_, summary) = app.request(Method::POST, &uri, Some(&token), None).await;
+    assert_eq!(summary["refreshed"], 2);
+    let changed = summary["changed"].as_array().unwrap();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0]["fields"], json!(["uri", "name", "image_url", "metadata"]));
+    assert_eq!(changed[0]["nft"]["name"], "Dragon #1");
+    assert_eq!(changed[0]["nft"]["collection_name"], "Collection");
+
+    let cached = app.state.db.get_nft("solana", "mystery", "1").await.unwrap();
+    assert_eq!(cached.image_url.as_deref(), Some("dragon.png"));
+    assert_eq!(cached.metadata_uri.as_deref(), Some("ipfs://revealed/1"));
+}
+
+#[tokio::test]
+async fn test_history_labels_counterparties() {
+    use wallet_backend::storage::models::TransactionRow;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let (_, savings) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "solana" })))
+        .await;
+    let savings = savings["address"].as_str().unwrap().to_string();
+    let alice = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/contacts",
+            None,
+            Some(json!({ "name": "Alice", "chain": "solana", "address": alice })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let transfers = [
+        ("to-alice", "send", address.as_str(), alice),
+        ("from-savings", "receive", savings.as_str(), address.as_str()),
+        ("from-stranger", "receive", "11111111111111111111111111111112", address.as_str()),
+    ];
+    for (i, (signature, tx_type, from, to)) in transfers.into_iter().enumerate() {
+        let row = TransactionRow::new(
+            account_id.clone(),
+            "solana".to_string(),
+            signature.to_string(),
+            tx_type.to_string(),
+            Some(from.to_string()),
+            Some(to.to_string()),
+            Some("1".to_string()),
+            None,
+            "confirmed".to_string(),
+            None,
+            Some(format!("2026-10-0{}T12:00:00+00:00", i + 1)),
+        );
+        app.state.db.upsert_transaction(&row).await.unwrap();
+    }
+
+    let labels = |history: &serde_json::Value| -> Vec<(String, serde_json::Value, bool)> {
+        history
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tx| {
+                let signature = tx["signature"].as_str().unwrap().to_string();
+                (signature, tx["counterparty_label"].clone(), tx["is_own_account"] == true)
+            })
+            .collect()
+    };
+    let expected = vec![
+        ("from-stranger".to_string(), serde_json::Value::Null, false),
+        ("from-savings".to_string(), json!("Solana Account 2"), true),
+        ("to-alice".to_string(), json!("Alice"), false),
+    ];
+
+    let uri = format!("/api/v2/transactions/solana/{}", address);
+    let (status, page) = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(labels(&page["items"]), expected);
+    // Transfers between the wallet's own accounts are listed as internal
+    assert_eq!(page["items"][0]["tx_type"], "receive");
+    assert_eq!(page["items"][1]["tx_type"], "internal");
+    assert_eq!(page["items"][2]["tx_type"], "send");
+
+    let uri = format!("/api/v1/transactions/solana/{}", address);
+    let (status, history) = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", history);
+    assert_eq!(labels(&history), expected);
+    assert_eq!(history[1]["tx_type"], "internal");
+}
+
+#[tokio::test]
+async fn test_history_includes_parsed_chain_transfers() {
+    use wallet_backend::chains::HistoryEntry;
+    use wallet_backend::storage::models::TransactionRow;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let other = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    let mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    // A send recorded before its amount was known
+    let row = TransactionRow::new(
+        account_id,
+        "solana".to_string(),
+        "sent-sig".to_string(),
+        "send".to_string(),
+        Some(address.clone()),
+        Some(other.to_string()),
+        None,
+        None,
+        "confirmed".to_string(),
+        None,
+        Some("2025-01-03T00:00:00+00:00".to_string()),
+    );
+    app.state.db.upsert_transaction(&row).await.unwrap();
+
+    *app.solana.history.lock().unwrap() = vec![
+        HistoryEntry {
+            hash: "token-sig".to_string(),
+            status: "confirmed".to_string(),
+            block_time: Some(1_735_948_800),
+            from: Some(other.to_string()),
+            to: Some(address.clone()),
+            amount: Some("2.5".to_string()),
+            amount_raw: Some("2500000".to_string()),
+            token_address: Some(mint.to_string()),
+            ..Default::default()
+        },
+        HistoryEntry {
+            hash: "sent-sig".to_string(),
+            status: "confirmed".to_string(),
+            block_time: Some(1_735_862_400),
+            from: Some(address.clone()),
+            to: Some(other.to_string()),
+            amount: Some("1.5".to_string()),
+            amount_raw: Some("1500000000".to_string()),
+            ..Default::default()
+        },
+    ];
+
+    let uri = format!("/api/v1/transactions/solana/{}", address);
+    let (status, history) = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", history);
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["signature"], "token-sig");
+    assert_eq!(history[0]["from_address"], other);
+    assert_eq!(history[0]["amount"], "2.5");
+    assert_eq!(history[0]["amount_raw"], "2500000");
+    assert_eq!(history[0]["token_address"], mint);
+    // The recorded send is filled in from the chain, not listed twice
+    assert_eq!(history[1]["signature"], "sent-sig");
+    assert_eq!(history[1]["tx_type"], "send");
+    assert_eq!(history[1]["amount"], "1.5");
+    assert_eq!(history[1]["amount_raw"], "1500000000");
+}
+
+#[tokio::test]
+async fn test_monthly_account_statement() {
+    use wallet_backend::storage::models::TransactionRow;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    let other = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    // The mock holds 2 SOL now
+    let transfers = [
+        ("jan-in", "receive", other, address.as_str(), "1", "2025-01-10"),
+        ("jan-out", "send", address.as_str(), other, "0.5", "2025-01-20"),
+        ("feb-in", "receive", other, address.as_str(), "0.25", "2025-02-05"),
+    ];
+    for (signature, tx_type, from, to, amount, day) in transfers {
+        let mut row = TransactionRow::new(
+            account_id.clone(),
+            "solana".to_string(),
+            signature.to_string(),
+            tx_type.to_string(),
+            Some(from.to_string()),
+            Some(to.to_string()),
+            Some(amount.to_string()),
+            None,
+            "confirmed".to_string(),
+            None,
+            Some(format!("{}T12:00:00+00:00", day)),
+        );
+        if from == address {
+            row.expected_changes = Some(r#"{"changes":[],"fee":"5000"}"#.to_string());
+        }
+        app.state.db.upsert_transaction(&row).await.unwrap();
+    }
+
+    let uri = format!("/api/v1/accounts/{}/statement?month=2025-01&format=json", account_id);
+    let (status, statement) = app.request(Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", statement);
+    assert_eq!(statement["closing_balance"], "1.75");
+    assert_eq!(statement["opening_balance"], "1.250005");
+    assert_eq!(statement["total_in"], "1");
+    assert_eq!(statement["total_out"], "0.5");
+    assert_eq!(statement["total_fees"], "0.000005");
+    let entries = statement["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1]["amount"], "-0.5");
+    assert_eq!(entries[1]["fee"], "0.000005");
+
+    let uri = format!("/api/v1/accounts/{}/statement?month=2025-01", account_id);
+    let request = Request::get(uri.as_str())
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/pdf");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(body.starts_with(b"%PDF"));
+
+    for month in ["2025-13", "9999-01"] {
+        let uri = format!("/api/v2/accounts/{}/statement?month={}", account_id, month);
+        let (status, _) = app.request(Method::GET, &uri, Some(&token), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let uri = "/api/v2/accounts/missing/statement?month=2025-01";
+    let (status, _) = app.request(Method::GET, uri, Some(&token), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.request(Method::GET, uri, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_discover_legacy_solana_accounts() {
+    use wallet_backend::core::{derive_solana_account, mnemonic_to_seed, parse_mnemonic, SolanaScheme};
+
+    let app = TestApp::spawn().await;
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                    abandon abandon about";
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/wallet/import",
+            None,
+            Some(json!({ "mnemonic": mnemonic, "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    // The phrase was last used by a wallet on the legacy path
+    let seed = mnemonic_to_seed(&parse_mnemonic(mnemonic).unwrap(), "");
+    let legacy: Vec<String> = (0..2)
+        .map(|i| derive_solana_account(&seed, i, SolanaScheme::Bip44).unwrap().address)
+        .collect();
+    app.solana.active.lock().unwrap().extend(legacy.iter().cloned());
+
+    let (code, _) = app
+        .request(Method::GET, "/api/v2/accounts/discover?count=3", None, None)
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let token = app.login().await;
+    let (code, discovery) = app
+        .request(Method::GET, "/api/v2/accounts/discover?count=3", Some(&token), None)
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", discovery);
+    assert_eq!(discovery["suggested_scheme"], "bip44");
+    let found = discovery["accounts"].as_array().unwrap();
+    assert_eq!(found.len(), 6);
+    let active: Vec<_> = found.iter().filter(|a| a["has_activity"] == true).collect();
+    assert_eq!(active.len(), 2);
+    assert_eq!(active[0]["derivation_path"], "m/44'/501'/0'");
+    assert_eq!(active[0]["address"], legacy[0].as_str());
+
+    let choice = json!({ "scheme": "bip44", "indexes": [1, 0] });
+    let (code, imported) = app
+        .request(Method::POST, "/api/v2/accounts/discover", Some(&token), Some(choice.clone()))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", imported);
+    let addresses: Vec<_> = imported
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["address"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(addresses, legacy);
+    // Importing again is a no-op; another scheme at a taken index conflicts
+    let (_, again) = app
+        .request(Method::POST, "/api/v2/accounts/discover", Some(&token), Some(choice))
+        .await;
+    assert_eq!(again, json!([]));
+    let other = json!({ "scheme": "bip44_change", "indexes": [0] });
+    let (code, _) = app
+        .request(Method::POST, "/api/v2/accounts/discover", Some(&token), Some(other))
+        .await;
+    assert_eq!(code, StatusCode::CONFLICT);
+
+    // New accounts stay on the chosen scheme and sign with it
+    let (_, account) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "solana" })))
+        .await;
+    assert_eq!(account["derivation_path"], "m/44'/501'/2'");
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": legacy[1],
+                "to_address": "11111111111111111111111111111111",
+                "amount": "0.1",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    let uri = format!("/api/v2/transactions/solana/{}", legacy[1]);
+    let (_, history) = app.request(Method::GET, &uri, Some(&token), None).await;
+    let changes = &history["items"][0]["expected_changes"]["changes"];
+    assert_eq!(changes[0]["address"], legacy[1].as_str());
+}
+
+#[tokio::test]
+async fn test_field_encryption_is_transparent() {
+    let app = TestApp::spawn_with_env(&[(
+        "FIELD_ENCRYPTION_KEY",
+        "fedcba9876543210fedcba9876543210",
+    )])
+    .await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let alice = "11111111111111111111111111111111";
+
+    for (name, address, notes) in [
+        ("Carol", "SysvarC1ock11111111111111111111111111111111", json!(null)),
+        ("Alice", alice, json!("rent")),
+        ("Bob", "SysvarRent111111111111111111111111111111111", json!(null)),
+    ] {
+        let (code, body) = app
+            .request(
+                Method::POST,
+                "/api/v2/contacts",
+                Some(&token),
+                Some(json!({
+                    "name": name,
+                    "chain": "solana",
+                    "address": address,
+                    "notes": notes,
+                })),
+            )
+            .await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+    }
+
+    // Sealed names still list in name order across pages
+    let mut names = Vec::new();
+    let mut uri = "/api/v2/contacts?limit=2&include_total=true".to_string();
+    loop {
+        let (code, page) = app.request(Method::GET, &uri, Some(&token), None).await;
+        assert_eq!(code, StatusCode::OK, "{}", page);
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["total_is_estimate"], false);
+        assert_eq!(page["has_more"], page["next_cursor"].is_string());
+        for contact in page["items"].as_array().unwrap() {
+            names.push(contact["name"].as_str().unwrap().to_string());
+            if contact["name"] == "Alice" {
+                assert_eq!(contact["notes"], "rent");
+            }
+        }
+        match page["next_cursor"].as_str() {
+            Some(cursor) => {
+                uri = format!("/api/v2/contacts?limit=2&include_total=true&cursor={}", cursor)
+            }
+            None => break,
+        }
+    }
+    assert_eq!(names, ["Alice", "Bob", "Carol"]);
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": alice,
+                "amount": "0.5",
+                "memo": "order-42",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    let path = format!("/api/v2/transactions/solana/{}", address);
+    let (_, history) = app.request(Method::GET, &path, Some(&token), None).await;
+    assert_eq!(history["items"][0]["memo"], "order-42");
+    assert_eq!(history["items"][0]["counterparty_label"], "Alice");
+
+    // Everything was sealed on write, so the backfill finds nothing to do
+    let sealed = app.state.db.encrypt_plaintext_fields().await.unwrap();
+    assert_eq!(sealed, (0, 0));
+}
+
+#[tokio::test]
+async fn test_sparse_fieldsets() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let alice = "11111111111111111111111111111111";
+
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/contacts",
+            Some(&token),
+            Some(json!({
+                "name": "Alice",
+                "chain": "solana",
+                "address": alice,
+                "notes": "rent",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": alice,
+                "amount": "0.5",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    let (code, body) = app
+        .request(
+            Method::POST,
+            "/api/v2/multisig/create",
+            None,
+            Some(json!({
+                "chain": "solana",
+                "name": "treasury",
+                "threshold": 2,
+                "owners": ["owner-a", "owner-b", "owner-c"],
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+
+    let (code, contacts) = app
+        .request(Method::GET, "/api/v2/contacts?fields=id,name", Some(&token), None)
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", contacts);
+    let contact = contacts["items"][0].as_object().unwrap();
+    assert_eq!(contact.keys().collect::<Vec<_>>(), ["id", "name"]);
+    assert!(contacts.get("next_cursor").is_some());
+    let (_, contacts) = app
+        .request(Method::GET, "/api/v1/contacts?fields=name", Some(&token), None)
+        .await;
+    assert_eq!(contacts, json!([{ "name": "Alice" }]));
+
+    for version in ["v1", "v2"] {
+        let uri = format!(
+            "/api/{}/transactions/solana/{}?limit=10&fields=signature,status",
+            version, address
+        );
+        let (code, history) = app.request(Method::GET, &uri, Some(&token), None).await;
+        assert_eq!(code, StatusCode::OK, "{}", history);
+        let items = if version == "v1" { &history } else { &history["items"] };
+        assert_eq!(items[0], json!({ "signature": "mock-tx-1", "status": "confirmed" }));
+    }
+
+    // Owners are only listed with a sparse fieldset when included
+    let (_, full) = app.request(Method::GET, "/api/v2/multisig", None, None).await;
+    assert_eq!(full["items"][0]["owners"].as_array().unwrap().len(), 3);
+    let (_, sparse) = app
+        .request(Method::GET, "/api/v2/multisig?fields=id,owner_count", None, None)
+        .await;
+    assert_eq!(sparse["items"][0].as_object().unwrap().len(), 2);
+    assert_eq!(sparse["items"][0]["owner_count"], 3);
+    let (_, expanded) = app
+        .request(Method::GET, "/api/v2/multisig?fields=id&include=owners", None, None)
+        .await;
+    assert_eq!(expanded["items"][0].as_object().unwrap().len(), 2);
+    assert_eq!(expanded["items"][0]["owners"][1]["address"], "owner-b");
+}
+
+#[tokio::test]
+async fn test_register_and_point_names() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+
+    let (code, quote) = app
+        .request(Method::GET, "/api/v2/names/ethereum/Alice.eth?years=2", None, None)
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", quote);
+    assert_eq!(quote["available"], true);
+    assert_eq!(quote["years"], 2);
+    let (code, _) = app
+        .request(Method::GET, "/api/v2/names/ethereum/ab.eth", None, None)
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    // ENS commits first, then registers with the stored secret
+    let register = json!({ "account_id": account_id, "name": "alice.eth", "years": 2 });
+    let (code, committed) = app
+        .request(Method::POST, "/api/v2/names/register", Some(&token), Some(register.clone()))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", committed);
+    assert_eq!(committed["status"], "committed");
+    assert!(committed.get("commitment_secret").is_none());
+    let (code, registered) = app
+        .request(Method::POST, "/api/v2/names/register", Some(&token), Some(register.clone()))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", registered);
+    assert_eq!(registered["status"], "registered");
+    assert_eq!(registered["target_address"], address.as_str());
+    assert_eq!(registered["years"], 2);
+    let (code, _) = app
+        .request(Method::POST, "/api/v2/names/register", Some(&token), Some(register))
+        .await;
+    assert_eq!(code, StatusCode::CONFLICT);
+
+    let (_, quote) = app
+        .request(Method::GET, "/api/v1/names/ethereum/alice.eth", None, None)
+        .await;
+    assert_eq!(quote["available"], false);
+    assert_eq!(quote["address"], address.as_str());
+
+    let target = "0x000000000000000000000000000000000000dEaD";
+    let (code, updated) = app
+        .request(
+            Method::POST,
+            "/api/v2/names/ethereum/alice.eth/target",
+            Some(&token),
+            Some(json!({ "address": target })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", updated);
+    assert_eq!(updated["target_address"], target);
+    assert_eq!(app.ethereum.names.lock().unwrap()["alice.eth"], target);
+
+    let (code, names) = app.request(Method::GET, "/api/v2/names", Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK, "{}", names);
+    assert_eq!(names.as_array().unwrap().len(), 1);
+    assert_eq!(names[0]["last_tx_hash"], "mock-target-alice.eth");
+}
+
+#[tokio::test]
+async fn test_scheduled_send() {
+    use wallet_backend::services::scheduled_service::execute_due;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let recipient = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+    let schedule = |execute_at: chrono::DateTime<chrono::Utc>, amount: &str| {
+        json!({
+            "chain": "solana",
+            "from_address": address,
+            "to_address": recipient,
+            "amount": amount,
+            "memo": "rent",
+            "execute_at": execute_at,
+        })
+    };
+
+    let now = chrono::Utc::now();
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/transactions/scheduled",
+            Some(&token),
+            Some(schedule(now - chrono::Duration::minutes(1), "0.5")),
+        )
+        .await;
+    assert_eq!(code, StatusCode::BAD_REQUEST);
+
+    let soon = now + chrono::Duration::seconds(1);
+    let (code, first) = app
+        .request(
+            Method::POST,
+            "/api/v2/transactions/scheduled",
+            Some(&token),
+            Some(schedule(soon, "0.5")),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", first);
+    assert_eq!(first["status"], "scheduled");
+    let (_, second) = app
+        .request(
+            Method::POST,
+            "/api/v2/transactions/scheduled",
+            Some(&token),
+            Some(schedule(soon, "0.25")),
+        )
+        .await;
+
+    // Nothing is sent early, and a cancelled send is never sent
+    assert_eq!(execute_due(&app.state).await.unwrap(), 0);
+    let uri = format!("/api/v2/transactions/scheduled/{}/cancel", second["id"].as_str().unwrap());
+    let (code, cancelled) = app.request(Method::POST, &uri, Some(&token), None).await;
+    assert_eq!(code, StatusCode::OK, "{}", cancelled);
+    assert_eq!(cancelled["status"], "cancelled");
+    let (code, _) = app.request(Method::POST, &uri, Some(&token), None).await;
+    assert_eq!(code, StatusCode::CONFLICT);
+
+    // Signed only once due, and only while the wallet is unlocked
+    tokio::time::sleep(std::time::Duration::from_millis(1_100)).await;
+    app.request(Method::POST, "/api/v2/auth/lock", None, None).await;
+    assert_eq!(execute_due(&app.state).await.unwrap(), 0);
+    let (_, scheduled) = app
+        .request(Method::GET, "/api/v2/transactions/scheduled", Some(&token), None)
+        .await;
+    assert_eq!(scheduled[0]["status"], "scheduled");
+    assert!(scheduled[0]["last_error"].as_str().unwrap().contains("locked"));
+    assert!(app.solana.sent.lock().unwrap().is_empty());
+
+    app.unlock(json!({ "password": PASSWORD })).await;
+    assert_eq!(execute_due(&app.state).await.unwrap(), 1);
+    assert_eq!(execute_due(&app.state).await.unwrap(), 0);
+    let sent = app.solana.sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].amount, "0.5");
+    assert_eq!(sent[0].memo.as_deref(), Some("rent"));
+
+    let (_, scheduled) = app
+        .request(Method::GET, "/api/v1/transactions/scheduled", Some(&token), None)
+        .await;
+    let first = scheduled
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["id"] == first["id"])
+        .unwrap();
+    assert_eq!(first["status"], "sent");
+    assert_eq!(first["tx_hash"], "mock-tx-1");
+    assert!(first["last_error"].is_null());
+}
+
+#[tokio::test]
+async fn test_large_send_needs_password() {
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    app.solana.set_balance(10_000_000_000);
+    *app.prices.quote.lock().unwrap() = Some((100.0, chrono::Utc::now()));
+    let send = |amount: &str| {
+        json!({
+            "chain": "solana",
+            "from_address": address,
+            "to_address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+            "amount": amount,
+        })
+    };
+    let threshold = "/api/v2/users/me/large-transfer-threshold";
+
+    let (code, _) = app
+        .request(
+            Method::PUT,
+            threshold,
+            Some(&token),
+            Some(json!({ "threshold": "100", "currency": "usd", "password": "wrong" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let (code, body) = app
+        .request(
+            Method::PUT,
+            threshold,
+            Some(&token),
+            Some(json!({ "threshold": "100", "currency": "usd", "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    let (_, body) = app.request(Method::GET, threshold, Some(&token), None).await;
+    assert_eq!(body["threshold"], "100");
+    assert_eq!(body["currency"], "USD");
+
+    // Worth 50 USD: sent straight away
+    let (code, body) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send("0.5")))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["tx_hash"], "mock-tx-1");
+
+    // Worth 200 USD: held until the password is re-entered
+    let (code, challenge) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send("2")))
+        .await;
+    assert_eq!(code, StatusCode::ACCEPTED, "{}", challenge);
+    assert_eq!(challenge["value"], "200.00");
+    assert_eq!(challenge["threshold"], "100");
+    assert_eq!(app.solana.sent.lock().unwrap().len(), 1);
+
+    let confirm = |password: &str| {
+        json!({ "challenge_id": challenge["challenge_id"], "password": password })
+    };
+    let uri = "/api/v2/transactions/send/confirm";
+    let (code, _) = app
+        .request(Method::POST, uri, Some(&token), Some(confirm("wrong")))
+        .await;
+    assert_eq!(code, StatusCode::UNAUTHORIZED);
+    let (code, body) = app
+        .request(Method::POST, uri, Some(&token), Some(confirm(PASSWORD)))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    assert_eq!(body["tx_hash"], "mock-tx-2");
+    assert_eq!(app.solana.sent.lock().unwrap()[1].amount, "2");
+    let (code, _) = app
+        .request(Method::POST, uri, Some(&token), Some(confirm(PASSWORD)))
+        .await;
+    assert_eq!(code, StatusCode::NOT_FOUND);
+
+    // Without a price the send can't be valued, so it is treated as large
+    *app.prices.quote.lock().unwrap() = None;
+    let (code, _) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send("0.5")))
+        .await;
+    assert_eq!(code, StatusCode::ACCEPTED);
+
+    // Scheduling a large send takes the password up front
+    let mut scheduled = send("2");
+    scheduled["execute_at"] = json!(chrono::Utc::now() + chrono::Duration::hours(1));
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/transactions/scheduled",
+            Some(&token),
+            Some(scheduled.clone()),
+        )
+        .await;
+    assert_eq!(code, StatusCode::PRECONDITION_REQUIRED);
+    scheduled["password"] = json!(PASSWORD);
+    let (code, body) = app
+        .request(Method::POST, "/api/v2/transactions/scheduled", Some(&token), Some(scheduled))
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+
+    let (code, _) = app
+        .request(
+            Method::DELETE,
+            threshold,
+            Some(&token),
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::NO_CONTENT);
+    let (code, _) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send("0.5")))
+        .await;
+    assert_eq!(code, StatusCode::OK);
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn test_graphql_queries() {
+    use wallet_backend::storage::models::TransactionRow;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "ethereum" })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    app.solana.set_balance(2_000_000_000);
+
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["chain"] == "solana")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    for i in 1..=3 {
+        let row = TransactionRow::new(
+            account_id.clone(),
+            "solana".to_string(),
+            format!("sig-{}", i),
+            "receive".to_string(),
+            None,
+            Some(address.clone()),
+            Some("1".to_string()),
+            None,
+            "confirmed".to_string(),
+            None,
+            Some(format!("2026-10-0{}T12:00:00+00:00", i)),
+        );
+        app.state.db.upsert_transaction(&row).await.unwrap();
+    }
+    app.request(
+        Method::POST,
+        "/api/v2/multisig/create",
+        None,
+        Some(json!({
+            "chain": "solana",
+            "name": "treasury",
+            "threshold": 1,
+            "owners": ["owner-a", "owner-b"],
+        })),
+    )
+    .await;
+
+    let query = json!({
+        "query": "query($chain: String) { \
+            accounts(chain: $chain) { address balance { nativeBalance nativeSymbol } history(limit: 2) { signature } } \
+            multisigs { name owners { address } transactions { status } } \
+            contacts { name } }",
+        "variables": { "chain": "solana" },
+    });
+    let (status, body) = app
+        .request(Method::POST, "/api/v2/graphql", Some(&token), Some(query.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["errors"].is_null(), "{}", body);
+    let accounts = body["data"]["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0]["address"], address.as_str());
+    assert_eq!(accounts[0]["balance"]["nativeBalance"], "2");
+    assert_eq!(accounts[0]["balance"]["nativeSymbol"], "SOL");
+    assert_eq!(accounts[0]["history"], json!([{ "signature": "sig-3" }, { "signature": "sig-2" }]));
+    let multisig = &body["data"]["multisigs"][0];
+    assert_eq!(multisig["name"], "treasury");
+    assert_eq!(multisig["owners"].as_array().unwrap().len(), 2);
+    assert_eq!(multisig["transactions"], json!([]));
+    assert_eq!(body["data"]["contacts"], json!([]));
+
+    // History needs the wallet unlocked, like the REST endpoint
+    app.request(Method::POST, "/api/v2/auth/lock", None, None).await;
+    let (_, body) = app
+        .request(Method::POST, "/api/v2/graphql", Some(&token), Some(query.clone()))
+        .await;
+    assert_eq!(body["errors"][0]["message"], "Wallet is locked");
+
+    let (status, _) = app.request(Method::POST, "/api/v2/graphql", None, Some(query)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_savings_buckets() {
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let address = app.create_wallet_with_account("solana").await;
+    app.solana.set_balance(5_000_000_000);
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let buckets = format!("/api/v2/accounts/{}/buckets", accounts[0]["id"].as_str().unwrap());
+
+    let (status, body) = app
+        .request(Method::POST, &buckets, Some(&token), Some(json!({ "name": "savings", "amount": "2" })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["allocated"], "2");
+    assert_eq!(body["unallocated"], "3");
+    let savings = body["buckets"][0]["id"].as_str().unwrap().to_string();
+
+    // Funding can't exceed the unallocated balance, and a refused bucket
+    // isn't created
+    let (status, _) = app
+        .request(Method::POST, &buckets, Some(&token), Some(json!({ "name": "rent", "amount": "4" })))
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, body) = app
+        .request(Method::POST, &buckets, Some(&token), Some(json!({ "name": "rent" })))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let rent = body["buckets"][1]["id"].as_str().unwrap().to_string();
+    let (status, _) = app
+        .request(Method::POST, &buckets, Some(&token), Some(json!({ "name": "rent" })))
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let transfer = format!("{}/transfer", buckets);
+    let (status, body) = app
+        .request(
+            Method::POST,
+            &transfer,
+            Some(&token),
+            Some(json!({ "from": savings, "to": rent, "amount": "0.5" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["buckets"][0]["balance"], "1.5");
+    assert_eq!(body["buckets"][1]["balance"], "0.5");
+    let (status, _) = app
+        .request(Method::POST, &transfer, Some(&token), Some(json!({ "from": rent, "amount": "1" })))
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // The balance shows the split
+    let (_, balance) = app
+        .request(Method::GET, &format!("/api/v2/balances/solana/{}", address), None, None)
+        .await;
+    assert_eq!(balance["buckets"]["unallocated"], "3", "{}", balance);
+    assert_eq!(balance["buckets"]["buckets"][1]["name"], "rent");
+
+    // A send named to a bucket is held to it and charged to it
+    let send = |amount: &str| {
+        json!({
+            "chain": "solana",
+            "from_address": address,
+            "to_address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+            "amount": amount,
+            "bucket": rent,
+        })
+    };
+    let (status, body) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send("1")))
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    let (status, body) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send("0.2")))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, body) = app.request(Method::GET, &buckets, Some(&token), None).await;
+    assert_eq!(body["buckets"][1]["balance"], "0.3");
+
+    let (status, body) = app
+        .request(Method::DELETE, &format!("{}/{}", buckets, savings), Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["allocated"], "0.3");
+    assert_eq!(body["buckets"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_watchlist() {
+    use wallet_backend::services::watchlist_service::poll_watchlist;
+
+    let app = TestApp::spawn().await;
+    let (webhook_url, received) = spawn_webhook_receiver().await;
+    let token = app.login().await;
+    let treasury = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+
+    let (status, watch) = app
+        .request(
+            Method::POST,
+            "/api/v2/watchlist",
+            Some(&token),
+            Some(json!({
+                "chain": "solana",
+                "address": format!(" {} ", treasury),
+                "label": "DAO treasury",
+                "threshold": "1.0",
+                "webhook_url": webhook_url,
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", watch);
+    assert_eq!(watch["address"], treasury);
+    assert_eq!(watch["threshold"], "1");
+    assert!(watch["balance"].is_null());
+    let id = watch["id"].as_str().unwrap().to_string();
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/watchlist",
+            Some(&token),
+            Some(json!({ "chain": "solana", "address": treasury })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/watchlist",
+            Some(&token),
+            Some(json!({ "chain": "solana", "address": "0x52908400098527886E0F7030069857D2E4169EE7" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // The first poll only takes the baseline
+    app.solana.set_balance(2_000_000_000);
+    assert_eq!(poll_watchlist(&app.state).await.unwrap(), 0);
+
+    // Small movements are recorded without notifying
+    app.solana.set_balance(2_500_000_000);
+    assert_eq!(poll_watchlist(&app.state).await.unwrap(), 0);
+    app.solana.set_balance(1_000_000_000);
+    assert_eq!(poll_watchlist(&app.state).await.unwrap(), 1);
+    assert_eq!(poll_watchlist(&app.state).await.unwrap(), 0);
+
+    let hooks = received.lock().unwrap().clone();
+    assert_eq!(hooks.len(), 1);
+    assert_eq!(hooks[0]["watch_id"], id.as_str());
+    assert_eq!(hooks[0]["amount"], "-1.5");
+    assert_eq!(hooks[0]["balance"], "1");
+
+    let (status, watches) = app
+        .request(Method::GET, "/api/v2/watchlist", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let watches = watches.as_array().unwrap();
+    assert_eq!(watches.len(), 1);
+    assert_eq!(watches[0]["balance"], "1");
+    let activity = watches[0]["recent_activity"].as_array().unwrap();
+    assert_eq!(activity.len(), 2);
+    assert_eq!(activity[0]["amount"], "-1.5");
+    assert_eq!(activity[0]["notified"], true);
+    assert_eq!(activity[0]["webhook_status"], "delivered");
+    assert_eq!(activity[1]["amount"], "0.5");
+    assert_eq!(activity[1]["notified"], false);
+
+    // Other users can't see or change the entry
+    let bob = json!({ "email": "bob@example.com", "password": "correct horse battery" });
+    app.request(Method::POST, "/api/v2/users/register", None, Some(bob.clone()))
+        .await;
+    let (_, other) = app
+        .request(Method::POST, "/api/v2/users/login", None, Some(bob))
+        .await;
+    let other_token = other["access_token"].as_str().unwrap().to_string();
+    let (_, watches) = app
+        .request(Method::GET, "/api/v2/watchlist", Some(&other_token), None)
+        .await;
+    assert!(watches.as_array().unwrap().is_empty());
+    let (status, _) = app
+        .request(
+            Method::DELETE,
+            &format!("/api/v2/watchlist/{}", id),
+            Some(&other_token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, updated) = app
+        .request(
+            Method::POST,
+            &format!("/api/v2/watchlist/{}", id),
+            Some(&token),
+            Some(json!({ "threshold": "", "webhook_url": "" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", updated);
+    assert!(updated["threshold"].is_null());
+    assert_eq!(updated["label"], "DAO treasury");
+    assert_eq!(updated["recent_activity"].as_array().unwrap().len(), 2);
+
+    let (status, _) = app
+        .request(Method::DELETE, &format!("/api/v2/watchlist/{}", id), Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, watches) = app
+        .request(Method::GET, "/api/v2/watchlist", Some(&token), None)
+        .await;
+    assert!(watches.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_feature_flags() {
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let send = json!({
+        "chain": "solana",
+        "from_address": address,
+        "to_address": "11111111111111111111111111111111",
+        "amount": "0.1",
+    });
+
+    let (status, flags) = app
+        .request(Method::GET, "/api/admin/flags", Some(admin_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", flags);
+    let names: Vec<&str> = flags
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["maintenance", "sends.solana", "sends.ethereum", "swaps"]);
+    assert_eq!(flags[0]["enabled"], false);
+    assert!(flags[1]["updated_at"].is_null());
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/admin/flags/sends.bitcoin",
+            Some(admin_token),
+            Some(json!({ "enabled": false })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Solana sends are refused with a structured 503
+    let (status, flag) = app
+        .request(
+            Method::POST,
+            "/api/admin/flags/sends.solana",
+            Some(admin_token),
+            Some(json!({ "enabled": false, "reason": "RPC upgrade" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", flag);
+    assert_eq!(flag["enabled"], false);
+    let (status, body) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send.clone()))
+        .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["code"], "feature_disabled");
+    assert_eq!(body["error"]["retryable"], true);
+    assert!(body["error"]["message"].as_str().unwrap().contains("RPC upgrade"));
+    assert!(app.solana.sent.lock().unwrap().is_empty());
+
+    app.request(
+        Method::POST,
+        "/api/admin/flags/sends.solana",
+        Some(admin_token),
+        Some(json!({ "enabled": true })),
+    )
+    .await;
+    let (status, body) = app
+        .request_signed(Method::POST, "/api/v2/transactions/send", &token, Some(send))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // Maintenance mode refuses writes but still serves reads and admin
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/admin/flags/maintenance",
+            Some(admin_token),
+            Some(json!({ "enabled": true, "reason": "Database migration" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let contact = json!({
+        "name": "Bob",
+        "chain": "solana",
+        "address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+    });
+    let (status, body) = app
+        .request(Method::POST, "/api/v2/contacts", None, Some(contact.clone()))
+        .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["code"], "maintenance");
+    assert!(body["error"]["message"].as_str().unwrap().contains("Database migration"));
+    let (status, _) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    app.request(
+        Method::POST,
+        "/api/admin/flags/maintenance",
+        Some(admin_token),
+        Some(json!({ "enabled": false })),
+    )
+    .await;
+    let (status, body) = app
+        .request(Method::POST, "/api/v2/contacts", None, Some(contact))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn test_siem_export() {
+    use wallet_backend::services::siem_service;
+
+    let (collector_url, received) = spawn_webhook_receiver().await;
+    let log_path = std::env::temp_dir().join(format!("siem-{}.log", uuid::Uuid::new_v4()));
+    let app = TestApp::spawn_with_env(&[
+        ("SIEM_EXPORT_URL", &collector_url),
+        ("SIEM_EXPORT_FILE", log_path.to_str().unwrap()),
+    ])
+    .await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+
+    let (code, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/transactions/send",
+            &token,
+            Some(json!({
+                "chain": "solana",
+                "from_address": address,
+                "to_address": "11111111111111111111111111111111",
+                "amount": "0.5",
+            })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK, "{}", body);
+    let (code, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/wallet/change-encryption-password",
+            Some(&token),
+            Some(json!({ "current_password": PASSWORD, "new_password": "a brand new passphrase" })),
+        )
+        .await;
+    assert_eq!(code, StatusCode::OK);
+
+    let exported = siem_service::export_pending(&app.state).await.unwrap();
+    assert_eq!(exported, 2);
+    assert_eq!(siem_service::export_pending(&app.state).await.unwrap(), 0);
+
+    // The collector gets the batch as one JSON array, the file one event per line
+    let batches = received.lock().unwrap().clone();
+    assert_eq!(batches.len(), 1);
+    let events = batches[0].as_array().unwrap();
+    assert_eq!(events[0]["event"], "transaction.recorded");
+    assert_eq!(events[0]["transaction"]["signature"], "mock-tx-1");
+    assert_eq!(events[1]["event"], "wallet.encryption_password_changed");
+    assert_eq!(events[1]["severity"], "warning");
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&log_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(&lines, events);
+    std::fs::remove_file(&log_path).unwrap();
+
+    // Below the minimum severity nothing is queued; an unreachable collector
+    // leaves the event queued for a retry
+    let app = TestApp::spawn_with_env(&[
+        ("SIEM_EXPORT_URL", "http://127.0.0.1:9/hook"),
+        ("SIEM_EXPORT_FORMAT", "cef"),
+        ("SIEM_MIN_SEVERITY", "warning"),
+    ])
+    .await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    app.request_signed(
+        Method::POST,
+        "/api/v2/transactions/send",
+        &token,
+        Some(json!({
+            "chain": "solana",
+            "from_address": address,
+            "to_address": "11111111111111111111111111111111",
+            "amount": "0.5",
+        })),
+    )
+    .await;
+    app.request(
+        Method::POST,
+        "/api/v1/wallet/change-encryption-password",
+        Some(&token),
+        Some(json!({ "current_password": PASSWORD, "new_password": "a brand new passphrase" })),
+    )
+    .await;
+
+    assert!(siem_service::export_pending(&app.state).await.is_err());
+    let later = (chrono::Utc::now() + chrono::Duration::hours(2)).to_rfc3339();
+    let queued = app.state.db.get_due_siem_events(&later, 10).await.unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].event, "wallet.encryption_password_changed");
+    assert_eq!(queued[0].attempts, 1);
+    assert!(queued[0].last_error.is_some());
+    // Held back until the retry is due
+    assert_eq!(siem_service::export_pending(&app.state).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_liquid_staking() {
+    use wallet_backend::chains::solana::LIQUID_STAKING_TOKENS;
+    use wallet_backend::chains::StakePoolState;
+
+    let app = TestApp::spawn().await;
+    let address = app.create_wallet_with_account("solana").await;
+    let token = app.login().await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+
+    let jito = LIQUID_STAKING_TOKENS[0];
+    let pool = jito.stake_pool.unwrap();
+    app.solana.stake_pools.lock().unwrap().insert(
+        pool.to_string(),
+        StakePoolState {
+            address: pool.to_string(),
+            pool_mint: jito.mint.to_string(),
+            total_lamports: 1_150_000_000_000,
+            pool_token_supply: 1_000_000_000_000,
+            last_epoch_total_lamports: 1_149_500_000_000,
+            last_epoch_pool_token_supply: 1_000_000_000_000,
+            ..Default::default()
+        },
+    );
+
+    let (status, pools) = app.request(Method::GET, "/api/v2/staking/pools", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(pools[0]["symbol"], "jitoSOL");
+    assert_eq!(pools[0]["exchange_rate"], 1.15);
+    assert!(pools[0]["apy"].as_f64().unwrap() > 0.08, "{}", pools);
+    // Pools that can't be read still list, with the reason
+    assert!(pools[1]["exchange_rate"].is_null());
+    assert!(pools[1]["error"].is_string());
+    assert!(pools[2]["stake_pool"].is_null());
+
+    let (status, staked) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/staking/stake",
+            &token,
+            Some(json!({ "account_id": account_id, "token": "jitosol", "amount": "1" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", staked);
+    assert_eq!(staked["estimated_received"], "0.869565217");
+    assert_eq!(staked["transaction"]["tx_type"], "stake");
+    assert_eq!(staked["transaction"]["to_address"], pool);
+
+    // The minted tokens show up valued in SOL next to the balance
+    let (_, balance) = app
+        .request(Method::GET, &format!("/api/v2/balances/solana/{}", address), None, None)
+        .await;
+    assert_eq!(balance["native_balance"], "0.999995");
+    let position = &balance["liquid_staking"]["positions"][0];
+    assert_eq!(position["symbol"], "jitoSOL");
+    assert_eq!(position["balance"], "0.869565217");
+    assert!(position["sol_value"].as_str().unwrap().starts_with("0.99999"), "{}", balance);
+
+    // Only what is held can be redeemed, and only from pools we can use
+    for (request, expected) in [
+        (json!({ "token": jito.mint, "amount": "5" }), StatusCode::UNPROCESSABLE_ENTITY),
+        (json!({ "token": "mSOL", "amount": "0.1" }), StatusCode::BAD_REQUEST),
+        (json!({ "token": "stSOL", "amount": "0.1" }), StatusCode::BAD_REQUEST),
+        (json!({ "token": "jitoSOL", "amount": "0" }), StatusCode::BAD_REQUEST),
+    ] {
+        let mut request = request;
+        request["account_id"] = json!(account_id);
+        let (status, body) = app
+            .request_signed(Method::POST, "/api/v2/staking/unstake", &token, Some(request))
+            .await;
+        assert_eq!(status, expected, "{}", body);
+    }
+
+    let (status, unstaked) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/staking/unstake",
+            &token,
+            Some(json!({ "account_id": account_id, "token": "jitoSOL", "amount": "0.5" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", unstaked);
+    assert_eq!(unstaked["transaction"]["token_address"], jito.mint);
+
+    let (_, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", address),
+            Some(&token),
+            None,
+        )
+        .await;
+    let mut types: Vec<&str> = history["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tx| tx["tx_type"].as_str().unwrap())
+        .collect();
+    types.sort();
+    assert_eq!(types, ["stake", "unstake"]);
+}
+
+#[tokio::test]
+async fn test_l2_bridge_deposits() {
+    use wallet_backend::chains::{ConfirmedEffects, TxEffects, TxReceipt};
+    use wallet_backend::services::bridge_service::poll_bridge_transfers;
+
+    let app = TestApp::spawn().await;
+    let (webhook_url, received) = spawn_webhook_receiver().await;
+    let address = app.create_wallet_with_account("ethereum").await;
+    let token = app.login().await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+
+    let (status, networks) = app.request(Method::GET, "/api/v2/bridge/networks", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(networks.as_array().unwrap().len(), 3);
+    assert_eq!(networks[0]["network"], "arbitrum");
+    assert_eq!(networks[0]["bridge_contract"], "0xaAe29B0366299461418F5324a79Afc425BE5ae21");
+    assert_eq!(networks[1]["withdrawal_challenge_secs"], 7 * 24 * 60 * 60);
+
+    for (request, expected) in [
+        (json!({ "network": "zksync", "amount": "0.1" }), StatusCode::BAD_REQUEST),
+        (json!({ "network": "base", "amount": "0" }), StatusCode::BAD_REQUEST),
+        (json!({ "network": "base", "amount": "0.1", "webhook_url": "ftp://x" }), StatusCode::BAD_REQUEST),
+        (json!({ "network": "base", "amount": "5" }), StatusCode::UNPROCESSABLE_ENTITY),
+    ] {
+        let mut request = request;
+        request["account_id"] = json!(account_id);
+        let (status, body) = app
+            .request_signed(Method::POST, "/api/v2/bridge/deposit", &token, Some(request))
+            .await;
+        assert_eq!(status, expected, "{}", body);
+    }
+
+    let mut ids = Vec::new();
+    for (network, webhook) in [("arbitrum", Some(&webhook_url)), ("optimism", None), ("base", None)] {
+        let (status, transfer) = app
+            .request_signed(
+                Method::POST,
+                "/api/v2/bridge/deposit",
+                &token,
+                Some(json!({
+                    "account_id": account_id,
+                    "network": network,
+                    "amount": "0.1",
+                    "webhook_url": webhook,
+                })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", transfer);
+        assert_eq!(transfer["status"], "submitted");
+        assert_eq!(transfer["address"], address.as_str());
+        ids.push(transfer["id"].as_str().unwrap().to_string());
+    }
+    assert_eq!(app.ethereum.bridge_deposits.lock().unwrap().len(), 3);
+
+    // Nothing has landed yet
+    assert_eq!(poll_bridge_transfers(&app.state).await.unwrap(), 0);
+
+    // The arbitrum deposit landed long enough ago to have been credited
+    let past = (chrono::Utc::now() - chrono::Duration::minutes(20)).to_rfc3339();
+    app.state
+        .db
+        .confirm_bridge_transfer(&ids[0], Some(40), &past, &past)
+        .await
+        .unwrap();
+    let landed = |succeeded| ConfirmedEffects {
+        status: if succeeded { "confirmed" } else { "failed" }.to_string(),
+        block_number: Some(42),
+        confirmations: 1,
+        effects: TxEffects::default(),
+        receipt: Some(TxReceipt {
+            succeeded,
+            ..Default::default()
+        }),
+    };
+    let mut confirmations = app.ethereum.confirmations.lock().unwrap();
+    confirmations.insert("0xbridge2".to_string(), landed(true));
+    confirmations.insert("0xbridge3".to_string(), landed(false));
+    drop(confirmations);
+
+    // Arbitrum finalizes and base fails; optimism waits out its window
+    assert_eq!(poll_bridge_transfers(&app.state).await.unwrap(), 2);
+    assert_eq!(poll_bridge_transfers(&app.state).await.unwrap(), 0);
+
+    let hooks = received.lock().unwrap().clone();
+    assert_eq!(hooks.len(), 1);
+    assert_eq!(hooks[0]["status"], "finalized");
+    assert_eq!(hooks[0]["network"], "arbitrum");
+
+    let (status, transfers) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/bridge/transfers?account_id={}", account_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let by_network = |network: &str| {
+        transfers
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|t| t["network"] == network)
+            .unwrap()
+            .clone()
+    };
+    let arbitrum = by_network("arbitrum");
+    assert_eq!(arbitrum["status"], "finalized");
+    assert_eq!(arbitrum["webhook_status"], "delivered");
+    let optimism = by_network("optimism");
+    assert_eq!(optimism["status"], "confirmed");
+    assert_eq!(optimism["l1_block_number"], 42);
+    assert!(optimism["finalizes_at"].as_str().unwrap() > optimism["confirmed_at"].as_str().unwrap());
+    assert_eq!(by_network("base")["status"], "failed");
+
+    let (status, base) = app
+        .request(Method::GET, &format!("/api/v2/bridge/transfers/{}", ids[2]), Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(base["status"], "failed");
+
+    // Deposits show in history as sends to the bridge contract
+    let (_, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/ethereum/{}", address),
+            Some(&token),
+            None,
+        )
+        .await;
+    let mut statuses: Vec<(&str, &str)> = history["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tx| (tx["to_address"].as_str().unwrap(), tx["status"].as_str().unwrap()))
+        .collect();
+    statuses.sort();
+    assert_eq!(
+        statuses,
+        [
+            ("mock-arbitrum-bridge", "confirmed"),
+            ("mock-base-bridge", "failed"),
+            ("mock-optimism-bridge", "confirmed"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_cross_chain_swaps() {
+    use wallet_backend::chains::{ConfirmedEffects, TxEffects};
+    use wallet_backend::services::cross_chain_service::{poll_cross_chain_swaps, RouteState};
+
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+    let sol_address = app.create_wallet_with_account("solana").await;
+    let (status, eth_account) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "ethereum" })))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let token = app.login().await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let sol_id = accounts
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["chain"] == "solana")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let eth_id = eth_account["id"].as_str().unwrap().to_string();
+    let swap = |from: &str, to: &str, amount: &str| {
+        json!({ "from_account_id": from, "to_account_id": to, "amount": amount })
+    };
+
+    let (status, quote) = app
+        .request(
+            Method::GET,
+            &format!(
+                "/api/v2/cross-chain/quote?from_account_id={}&to_account_id={}&amount=0.1",
+                eth_id, sol_id
+            ),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", quote);
+    assert_eq!(quote["from_chain"], "ethereum");
+    assert_eq!(quote["to_chain"], "solana");
+    assert_eq!(quote["to_amount"], "0.5");
+    assert_eq!(quote["min_amount"], "0.495");
+    assert_eq!(quote["tool"], "mock-bridge");
+    assert_eq!(quote["fee_usd"], "1.25");
+    {
+        let quotes = app.routes.quotes.lock().unwrap();
+        assert_eq!(quotes[0].from_amount, 100_000_000_000_000_000);
+        assert_eq!(quotes[0].to_address, sol_address);
+        assert_eq!(quotes[0].slippage_bps, 50);
+    }
+
+    let mut too_slippy = swap(&eth_id, &sol_id, "0.1");
+    too_slippy["slippage_bps"] = json!(1000);
+    for (request, expected) in [
+        (swap(&sol_id, &sol_id, "0.1"), StatusCode::BAD_REQUEST),
+        (swap(&eth_id, &sol_id, "0"), StatusCode::BAD_REQUEST),
+        (too_slippy, StatusCode::BAD_REQUEST),
+        (swap(&eth_id, "missing", "0.1"), StatusCode::NOT_FOUND),
+        (swap(&eth_id, &sol_id, "5"), StatusCode::UNPROCESSABLE_ENTITY),
+    ] {
+        let (status, body) = app
+            .request_signed(Method::POST, "/api/v2/cross-chain/execute", &token, Some(request))
+            .await;
+        assert_eq!(status, expected, "{}", body);
+    }
+
+    let mut swaps = Vec::new();
+    for request in [
+        swap(&eth_id, &sol_id, "0.1"),
+        swap(&sol_id, &eth_id, "0.5"),
+        swap(&sol_id, &eth_id, "0.25"),
+    ] {
+        let (status, sent) = app
+            .request_signed(Method::POST, "/api/v2/cross-chain/execute", &token, Some(request))
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", sent);
+        assert_eq!(sent["status"], "submitted");
+        swaps.push(sent);
+    }
+    assert_eq!(swaps[0]["source_tx_hash"], "eth-route-1");
+    assert_eq!(swaps[1]["source_tx_hash"], "sol-route-1");
+    assert_eq!(swaps[2]["source_tx_hash"], "sol-route-2");
+    assert_eq!(swaps[1]["quoted_amount"], "0.05");
+    assert_eq!(app.ethereum.route_transactions.lock().unwrap().len(), 1);
+    assert_eq!(app.solana.route_transactions.lock().unwrap().len(), 2);
+
+    // Nothing has landed yet
+    assert_eq!(poll_cross_chain_swaps(&app.state).await.unwrap(), 0);
+
+    let landed = |status: &str| ConfirmedEffects {
+        status: status.to_string(),
+        block_number: Some(7),
+        confirmations: 1,
+        effects: TxEffects::default(),
+        receipt: None,
+    };
+    app.ethereum
+        .confirmations
+        .lock()
+        .unwrap()
+        .insert("eth-route-1".to_string(), landed("confirmed"));
+    let mut confirmations = app.solana.confirmations.lock().unwrap();
+    confirmations.insert("sol-route-1".to_string(), landed("confirmed"));
+    confirmations.insert("sol-route-2".to_string(), landed("failed"));
+    drop(confirmations);
+
+    // The failed source transaction ends its swap; the others start bridging
+    assert_eq!(poll_cross_chain_swaps(&app.state).await.unwrap(), 1);
+    assert_eq!(poll_cross_chain_swaps(&app.state).await.unwrap(), 0);
+
+    // One bridged swap arrives while the other is still under way
+    app.routes
+        .set_status("eth-route-1", RouteState::Completed, Some("dest-tx"), Some(480_000_000));
+    app.routes.set_status("sol-route-1", RouteState::Pending, None, None);
+    assert_eq!(poll_cross_chain_swaps(&app.state).await.unwrap(), 1);
+    assert_eq!(poll_cross_chain_swaps(&app.state).await.unwrap(), 0);
+
+    let get = |id: &serde_json::Value| {
+        let path = format!("/api/v2/cross-chain/swaps/{}", id.as_str().unwrap());
+        let token = token.clone();
+        let app = &app;
+        async move { app.request(Method::GET, &path, Some(&token), None).await }
+    };
+    let (status, completed) = get(&swaps[0]["route_id"]).await;
+    assert_eq!(status, StatusCode::OK, "{}", completed);
+    assert_eq!(completed["status"], "completed");
+    assert_eq!(completed["received_amount"], "0.48");
+    assert_eq!(completed["destination_tx_hash"], "dest-tx");
+    assert!(completed["completed_at"].is_string());
+    let (_, bridging) = get(&swaps[1]["route_id"]).await;
+    assert_eq!(bridging["status"], "bridging");
+    assert_eq!(bridging["status_message"], "mock Pending");
+    let (_, failed) = get(&swaps[2]["route_id"]).await;
+    assert_eq!(failed["status"], "failed");
+    assert_eq!(failed["status_message"], "Source transaction failed");
+    let (status, _) = app
+        .request(Method::GET, "/api/v2/cross-chain/swaps/missing", Some(&token), None)
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, listed) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/cross-chain/swaps?account_id={}", eth_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 3);
+
+    // Both legs of the completed swap show in history under its route ID
+    let route_id = swaps[0]["route_id"].as_str().unwrap();
+    let (_, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/solana/{}", sol_address),
+            Some(&token),
+            None,
+        )
+        .await;
+    let legs: Vec<&serde_json::Value> = history["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|tx| tx["route_id"] == route_id)
+        .collect();
+    assert_eq!(legs.len(), 1, "{}", history);
+    assert_eq!(legs[0]["signature"], "dest-tx");
+    assert_eq!(legs[0]["amount"], "0.48");
+    assert_eq!(legs[0]["status"], "confirmed");
+    let eth_address = eth_account["address"].as_str().unwrap();
+    let (_, history) = app
+        .request(
+            Method::GET,
+            &format!("/api/v2/transactions/ethereum/{}", eth_address),
+            Some(&token),
+            None,
+        )
+        .await;
+    let source = &history["items"][0];
+    assert_eq!(source["route_id"], route_id);
+    assert_eq!(source["signature"], "eth-route-1");
+    assert_eq!(source["status"], "confirmed");
+
+    // A refunded swap
+    let (_, refunded) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/cross-chain/execute",
+            &token,
+            Some(swap(&eth_id, &sol_id, "0.1")),
+        )
+        .await;
+    app.ethereum
+        .confirmations
+        .lock()
+        .unwrap()
+        .insert("eth-route-2".to_string(), landed("confirmed"));
+    app.routes.set_status("eth-route-2", RouteState::Refunded, None, None);
+    assert_eq!(poll_cross_chain_swaps(&app.state).await.unwrap(), 0);
+    assert_eq!(poll_cross_chain_swaps(&app.state).await.unwrap(), 1);
+    let (_, refunded) = get(&refunded["route_id"]).await;
+    assert_eq!(refunded["status"], "refunded");
+
+    // Swaps switched off refuse new routes but still quote
+    app.request(
+        Method::POST,
+        "/api/admin/flags/swaps",
+        Some(admin_token),
+        Some(json!({ "enabled": false })),
+    )
+    .await;
+    let (status, body) = app
+        .request_signed(
+            Method::POST,
+            "/api/v2/cross-chain/execute",
+            &token,
+            Some(swap(&eth_id, &sol_id, "0.1")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"]["code"], "feature_disabled");
+}
+
+#[tokio::test]
+async fn test_ownership_proofs() {
+    let app = TestApp::spawn().await;
+    let sol_address = app.create_wallet_with_account("solana").await;
+    let (_, eth_account) = app
+        .request(Method::POST, "/api/v2/accounts", None, Some(json!({ "chain": "ethereum" })))
+        .await;
+    let token = app.login().await;
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", None, None).await;
+    let sol_id = accounts
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["chain"] == "solana")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let eth_id = eth_account["id"].as_str().unwrap();
+
+    for (challenge, expected) in [
+        ("", StatusCode::BAD_REQUEST),
+        ("line\nAddress: forged", StatusCode::BAD_REQUEST),
+    ] {
+        let (status, body) = app
+            .request(
+                Method::POST,
+                &format!("/api/v2/accounts/{}/prove-ownership", sol_id),
+                Some(&token),
+                Some(json!({ "challenge": challenge })),
+            )
+            .await;
+        assert_eq!(status, expected, "{}", body);
+    }
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/accounts/missing/prove-ownership",
+            Some(&token),
+            Some(json!({ "challenge": "airdrop-7" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let mut proofs = Vec::new();
+    for id in [sol_id.as_str(), eth_id] {
+        let (status, proof) = app
+            .request(
+                Method::POST,
+                &format!("/api/v2/accounts/{}/prove-ownership", id),
+                Some(&token),
+                Some(json!({ "challenge": "exchange deposit 1f2e3d" })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", proof);
+        assert!(proof["message"]
+            .as_str()
+            .unwrap()
+            .contains("Challenge: exchange deposit 1f2e3d"));
+        proofs.push(proof);
+    }
+    assert_eq!(proofs[0]["address"], sol_address.as_str());
+    assert_eq!(proofs[1]["chain"], "ethereum");
+    assert!(proofs[1]["signature"].as_str().unwrap().starts_with("0x"));
+
+    // Verification needs no login
+    for proof in &proofs {
+        let (status, checked) = app
+            .request(Method::POST, "/api/v2/ownership/verify", None, Some(proof.clone()))
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", checked);
+        assert_eq!(checked["valid"], true, "{}", checked);
+        assert_eq!(checked["challenge"], "exchange deposit 1f2e3d");
+        assert_eq!(checked["timestamp"], proof["timestamp"]);
+    }
+
+    // A tampered message, or a proof claimed for another address, doesn't hold
+    let mut tampered = proofs[0].clone();
+    tampered["message"] = json!(tampered["message"].as_str().unwrap().replace("1f2e3d", "ffffff"));
+    let (_, checked) = app
+        .request(Method::POST, "/api/v2/ownership/verify", None, Some(tampered))
+        .await;
+    assert_eq!(checked["valid"], false);
+    let mut borrowed = proofs[1].clone();
+    borrowed["address"] = json!("0x742d35cc6634c0532925a3b844bc9e7595f3fe70");
+    let (_, checked) = app
+        .request(Method::POST, "/api/v2/ownership/verify", None, Some(borrowed))
+        .await;
+    assert_eq!(checked["valid"], false);
+    assert_eq!(checked["reason"], "not signed by this address");
+
+    let mut bad = proofs[0].clone();
+    bad["address"] = json!("not-an-address");
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/ownership/verify", None, Some(bad))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_cold_signing() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use ethers::signers::{LocalWallet, Signer as _};
+    use solana_sdk::signature::{Keypair, Signer as _};
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    let cold = Keypair::new();
+    let cold_address = cold.pubkey().to_string();
+    let recipient = Keypair::new().pubkey().to_string();
+    let build = |chain: &str, from: &str, to: &str, amount: &str| {
+        json!({ "chain": chain, "from_address": from, "to_address": to, "amount": amount })
+    };
+
+    for (request, expected) in [
+        (build("solana", &cold_address, &recipient, "0"), StatusCode::BAD_REQUEST),
+        (build("solana", "not-an-address", &recipient, "1"), StatusCode::BAD_REQUEST),
+        (build("dogecoin", &cold_address, &recipient, "1"), StatusCode::BAD_REQUEST),
+        (build("solana", &cold_address, &recipient, "50"), StatusCode::UNPROCESSABLE_ENTITY),
+    ] {
+        let (status, body) = app
+            .request(Method::POST, "/api/v2/cold-signing/transactions", Some(&token), Some(request))
+            .await;
+        assert_eq!(status, expected, "{}", body);
+    }
+
+    let (status, exported) = app
+        .request(
+            Method::POST,
+            "/api/v2/cold-signing/transactions",
+            Some(&token),
+            Some(build("solana", &cold_address, &recipient, "0.5")),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", exported);
+    assert_eq!(exported["status"], "exported");
+    assert_eq!(exported["amount"], "0.5");
+    assert_eq!(exported["fee"], "0.000005");
+    let id = exported["id"].as_str().unwrap().to_string();
+    let parts = exported["qr_parts"].as_array().unwrap();
+    assert!(parts[0]
+        .as_str()
+        .unwrap()
+        .starts_with(&format!("valtix-cold:{}:1/{}:", id, parts.len())));
+
+    // The offline device signs the transaction's message
+    let payload = STANDARD.decode(exported["payload"].as_str().unwrap()).unwrap();
+    let transaction: solana_sdk::transaction::Transaction = bincode::deserialize(&payload).unwrap();
+    let message = transaction.message_data();
+    let signature_path = format!("/api/v2/cold-signing/transactions/{}/signature", id);
+    let wrong = Keypair::new().sign_message(&message).to_string();
+    let (status, _) = app
+        .request(Method::POST, &signature_path, Some(&token), Some(json!({ "signature": wrong })))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(app.solana.rebroadcast.lock().unwrap().is_empty());
+
+    let signature = cold.sign_message(&message).to_string();
+    let (status, sent) = app
+        .request(
+            Method::POST,
+            &signature_path,
+            Some(&token),
+            Some(json!({ "signature": signature })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", sent);
+    assert_eq!(sent["status"], "broadcast");
+    assert!(sent["tx_hash"].as_str().unwrap().starts_with("mock-tx-"));
+    assert!(sent["broadcast_at"].is_string());
+    let broadcast = app.solana.rebroadcast.lock().unwrap().clone();
+    let signed: solana_sdk::transaction::Transaction =
+        bincode::deserialize(&STANDARD.decode(&broadcast[0]).unwrap()).unwrap();
+    assert!(signed.verify().is_ok());
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            &signature_path,
+            Some(&token),
+            Some(json!({ "signature": signature })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Ethereum: the signer signs the exported signing hash
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let (status, exported) = app
+        .request(
+            Method::POST,
+            "/api/v2/cold-signing/transactions",
+            Some(&token),
+            Some(build(
+                "ethereum",
+                &format!("{:?}", wallet.address()),
+                "0x742d35cc6634c0532925a3b844bc9e7595f3fe70",
+                "0.01",
+            )),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", exported);
+    let hash: ethers::types::H256 = exported["signing_hash"].as_str().unwrap().parse().unwrap();
+    let signature = wallet.sign_hash(hash).unwrap().to_string();
+    let (status, sent) = app
+        .request(
+            Method::POST,
+            &format!(
+                "/api/v2/cold-signing/transactions/{}/signature",
+                exported["id"].as_str().unwrap()
+            ),
+            Some(&token),
+            Some(json!({ "signature": signature })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", sent);
+    assert!(app.ethereum.rebroadcast.lock().unwrap()[0].starts_with("0x"));
+
+    let (_, listed) = app
+        .request(Method::GET, "/api/v2/cold-signing/transactions", Some(&token), None)
+        .await;
+    assert_eq!(listed.as_array().unwrap().len(), 2);
+    assert_eq!(listed[0]["chain"], "ethereum");
+
+    // Each QR part renders; parts count from 1
+    let response = app
+        .send(
+            Request::get(format!("/api/v2/cold-signing/transactions/{}/qr/1", id))
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/svg+xml");
+    for part in [0, parts.len() + 1] {
+        let (status, _) = app
+            .request(
+                Method::GET,
+                &format!("/api/v2/cold-signing/transactions/{}/qr/{}", id, part),
+                Some(&token),
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn test_cors_origins_hot_reload() {
+    let admin_token = "tenant-admin-token-0123456789abcdef";
+    let app = TestApp::spawn_with_env(&[("TENANT_ADMIN_TOKEN", admin_token)]).await;
+    let allowed = |origin: &'static str| {
+        let request = Request::get("/metrics")
+            .header("origin", origin)
+            .body(Body::empty())
+            .unwrap();
+        let app = &app;
+        async move {
+            app.send(request)
+                .await
+                .headers()
+                .get("access-control-allow-origin")
+                .map(|v| v.to_str().unwrap().to_string())
+        }
+    };
+    assert_eq!(allowed("http://localhost:3000").await.as_deref(), Some("http://localhost:3000"));
+    assert_eq!(allowed("https://app.example.com").await, None);
+
+    for origin in ["app.example.com", "ftp://app.example.com", "https://app.example.com/path", "*"] {
+        let (status, _) = app
+            .request(
+                Method::POST,
+                "/api/admin/cors-origins",
+                Some(admin_token),
+                Some(json!({ "origin": origin })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", origin);
+    }
+    let (status, added) = app
+        .request(
+            Method::POST,
+            "/api/admin/cors-origins",
+            Some(admin_token),
+            Some(json!({ "origin": "https://App.Example.com/" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", added);
+    assert_eq!(added["origin"], "https://app.example.com");
+    assert_eq!(added["source"], "api");
+    assert_eq!(allowed("https://app.example.com").await.as_deref(), Some("https://app.example.com"));
+
+    for origin in ["https://app.example.com", "http://localhost:3000"] {
+        let (status, _) = app
+            .request(
+                Method::POST,
+                "/api/admin/cors-origins",
+                Some(admin_token),
+                Some(json!({ "origin": origin })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::CONFLICT, "{}", origin);
+    }
+    let (_, origins) = app
+        .request(Method::GET, "/api/admin/cors-origins", Some(admin_token), None)
+        .await;
+    let origins = origins.as_array().unwrap();
+    assert!(origins.iter().any(|o| o["origin"] == "http://localhost:3000" && o["source"] == "config"));
+    assert_eq!(origins.last().unwrap()["origin"], "https://app.example.com");
+
+    // Configured origins stay; added ones can be removed
+    let remove = |origin: &str| {
+        app.request(
+            Method::DELETE,
+            "/api/admin/cors-origins",
+            Some(admin_token),
+            Some(json!({ "origin": origin })),
+        )
+    };
+    assert_eq!(remove("http://localhost:3000").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(remove("https://app.example.com").await.0, StatusCode::NO_CONTENT);
+    assert_eq!(allowed("https://app.example.com").await, None);
+    assert_eq!(remove("https://app.example.com").await.0, StatusCode::NOT_FOUND);
+
+    let (status, _) = app.request(Method::GET, "/api/admin/cors-origins", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_login_step_up() {
+    let (webhook_url, received) = spawn_webhook_receiver().await;
+    let app = TestApp::spawn_with_env(&[
+        ("LOGIN_COUNTRY_HEADER", "cf-ipcountry"),
+        ("LOGIN_VERIFICATION_WEBHOOK_URL", webhook_url.as_str()),
+    ])
+    .await;
+    let credentials = json!({ "email": "alice@example.com", "password": "correct horse battery" });
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/users/register", None, Some(credentials.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let laptop = [("x-device-fingerprint", "laptop"), ("cf-ipcountry", "DE")];
+    // Nothing to compare the first login with, then the same device again
+    for _ in 0..2 {
+        let (status, body) = app
+            .request_with_headers(Method::POST, "/api/v2/users/login", &laptop, None, Some(credentials.clone()))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["access_token"].is_string());
+    }
+
+    let phone = [("x-device-fingerprint", "phone"), ("cf-ipcountry", "us")];
+    let (status, challenge) = app
+        .request_with_headers(Method::POST, "/api/v2/users/login", &phone, None, Some(credentials.clone()))
+        .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(challenge["step_up_required"], true);
+    assert!(challenge["access_token"].is_null());
+    let signals = challenge["signals"].as_array().unwrap();
+    assert!(signals.contains(&json!("new_device")));
+    assert!(signals.contains(&json!("impossible_travel")));
+    let challenge_id = challenge["challenge_id"].as_str().unwrap();
+
+    let message = received.lock().unwrap()[0].clone();
+    assert_eq!(message["email"], "alice@example.com");
+    assert_eq!(message["country"], "US");
+    let link = reqwest::Url::parse(message["link"].as_str().unwrap()).unwrap();
+    let token = link
+        .query_pairs()
+        .find(|(name, _)| name == "token")
+        .map(|(_, value)| value.to_string())
+        .unwrap();
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v2/users/login/verify",
+            None,
+            Some(json!({ "challenge_id": challenge_id, "token": "wrong" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let verify = json!({ "challenge_id": challenge_id, "token": token });
+    let (status, body) = app
+        .request(Method::POST, "/api/v2/users/login/verify", None, Some(verify.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let access_token = body["access_token"].as_str().unwrap();
+    let (status, _) = app
+        .request(Method::GET, "/api/v2/users/me", Some(access_token), None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Links work once
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/users/login/verify", None, Some(verify))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The verified device is known from then on
+    let (status, _) = app
+        .request_with_headers(Method::POST, "/api/v2/users/login", &phone, None, Some(credentials))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only the unusual login was audited
+    let events = app.state.db.get_audit_events("default", 10).await.unwrap();
+    let decisions: Vec<serde_json::Value> = events
+        .iter()
+        .filter(|e| e.event == "auth.login_risk")
+        .map(|e| serde_json::from_str::<serde_json::Value>(&e.details).unwrap()["decision"].clone())
+        .collect();
+    assert_eq!(decisions.len(), 2);
+    assert!(decisions.contains(&json!("step_up_required")) && decisions.contains(&json!("verified")));
+}
+
+#[tokio::test]
+async fn test_database_maintenance() {
+    use wallet_backend::services::maintenance_service;
+    use wallet_backend::storage::models::NftCacheRow;
+
+    let app = TestApp::spawn_with_env(&[("MAINTENANCE_VACUUM_MIN_FREE_BYTES", "0")]).await;
+    let token = app.login().await;
+    app.create_wallet_with_account("solana").await;
+    let (status, _) = app
+        .request(Method::POST, "/api/v2/auth/unlock/challenge", None, None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, accounts) = app.request(Method::GET, "/api/v2/accounts", Some(&token), None).await;
+    let account_id = accounts[0]["id"].as_str().unwrap().to_string();
+    for (chain, mint) in [("solana", "So1anaMint"), ("ethereum", "0xcontract")] {
+        let nft = NftCacheRow::new(
+            account_id.clone(),
+            chain.to_string(),
+            mint.to_string(),
+            "1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        app.state.db.upsert_nft(&nft).await.unwrap();
+    }
+
+    // Nothing has expired yet
+    let report = maintenance_service::run_maintenance(&app.state, chrono::Utc::now())
+        .await
+        .unwrap();
+    assert!(report.pruned.values().all(|rows| *rows == 0), "{:?}", report.pruned);
+    assert!(report.size_bytes > 0);
+
+    // Two months on the session, challenge and Solana cache entry are gone;
+    // the Ethereum entry can't be fetched again so it stays
+    let later = chrono::Utc::now() + chrono::Duration::days(60);
+    let report = maintenance_service::run_maintenance(&app.state, later).await.unwrap();
+    assert_eq!(report.pruned["user_sessions"], 1);
+    assert_eq!(report.pruned["unlock_challenges"], 1);
+    assert_eq!(report.pruned["nft_cache"], 1);
+    let nfts = app.state.db.get_nfts(&account_id).await.unwrap();
+    assert_eq!(nfts.len(), 1);
+    assert_eq!(nfts[0].chain, "ethereum");
+
+    let (status, body) = app.request(Method::GET, "/metrics", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let text = body.as_str().unwrap();
+    assert!(text.contains(r#"db_maintenance_rows_pruned_total{table="user_sessions"} 1"#));
+    assert!(text.contains(r#"db_maintenance_runs_total{outcome="ok"} 2"#));
+    assert!(text.contains("db_maintenance_reclaimed_bytes_total"));
+    assert!(text.contains("db_size_bytes"));
+}
+
+#[tokio::test]
+async fn test_account_deletion() {
+    use wallet_backend::services::account_deletion_service;
+
+    let app = TestApp::spawn().await;
+    let token = app.login().await;
+    app.create_wallet_with_account("solana").await;
+    let credentials = json!({ "email": "alice@example.com", "password": PASSWORD });
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/users/delete-account",
+            Some(&token),
+            Some(json!({ "password": "wrong password" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, pending) = app
+        .request(
+            Method::POST,
+            "/api/v1/users/delete-account",
+            Some(&token),
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(pending["grace_period_days"], 30);
+    let confirmation = pending["confirmation_token"].as_str().unwrap().to_string();
+
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/users/delete-account",
+            Some(&token),
+            Some(json!({ "password": PASSWORD, "confirmation_token": "not-the-token" })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, confirmed) = app
+        .request(
+            Method::POST,
+            "/api/v1/users/delete-account",
+            Some(&token),
+            Some(json!({ "password": PASSWORD, "confirmation_token": confirmation })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(confirmed["archive"]["user"]["email"], "alice@example.com");
+    assert_eq!(confirmed["archive"]["accounts"].as_array().unwrap().len(), 1);
+    assert!(confirmed["purge_after"].is_string());
+
+    // Tokens work once
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/users/delete-account",
+            Some(&token),
+            Some(json!({ "password": PASSWORD, "confirmation_token": confirmation })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Logging in during the grace period cancels the deletion
+    let (status, session) = app
+        .request(Method::POST, "/api/v1/users/login", None, Some(credentials.clone()))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let after_grace = chrono::Utc::now() + chrono::Duration::days(31);
+    let purged = account_deletion_service::purge_due_deletions(&app.state, after_grace)
+        .await
+        .unwrap();
+    assert_eq!(purged, 0);
+
+    // Confirmed again and left alone, it is purged once the grace period ends
+    let token = session["access_token"].as_str().unwrap().to_string();
+    let (_, pending) = app
+        .request(
+            Method::POST,
+            "/api/v1/users/delete-account",
+            Some(&token),
+            Some(json!({ "password": PASSWORD })),
+        )
+        .await;
+    let confirmation = pending["confirmation_token"].as_str().unwrap();
+    let (status, _) = app
+        .request(
+            Method::POST,
+            "/api/v1/users/delete-account",
+            Some(&token),
+            Some(json!({ "password": PASSWORD, "confirmation_token": confirmation })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let purged = account_deletion_service::purge_due_deletions(&app.state, chrono::Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(purged, 0);
+    let purged = account_deletion_service::purge_due_deletions(&app.state, after_grace)
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+
+    let user_id = session["user"]["id"].as_str().unwrap();
+    let user = app.state.user_service.find_user(user_id).await.unwrap();
+    assert!(!user.is_active);
+    assert!(user.password_hash.is_empty());
+    assert_ne!(user.email, "alice@example.com");
+    assert!(!app.state.db.wallet_exists("default").await.unwrap());
+    assert!(app.state.unlocked_seed.read().await.is_empty());
+    assert!(app.state.db.get_user_addresses(user_id).await.unwrap().is_empty());
+
+    let (status, _) = app
+        .request(Method::POST, "/api/v1/users/login", None, Some(credentials.clone()))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    // The email can be registered again
+    let (status, _) = app
+        .request(Method::POST, "/api/v1/users/register", None, Some(credentials))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,1104 @@
+//! Shared harness: the full axum app over in-memory SQLite and mock chain clients
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::extract::connect_info::{ConnectInfo, MockConnectInfo};
+use axum::http::{header, Method, Request, Response, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use sqlx::sqlite::SqlitePoolOptions;
+use tower::ServiceExt;
+
+use wallet_backend::chains::{
+    BalanceChange, BridgeDeposit, Broadcast, ChainBalance, ChainClient, ChainClientError,
+    ChainClients, ChainTokenBalance, ConfirmedEffects, DeploymentCost, HistoryEntry, Identity, MaxSend, NameQuote,
+    NameRegistration, NftHolder, NftMetadata, ReferencedTransaction, RouteSubmission,
+    RouteTransaction, SentTransfer, StakePoolState, TokenApproval, TokenMetadata, Transfer,
+    TxEffects, UnsignedTransfer,
+};
+use wallet_backend::chains::{ethereum, solana};
+use wallet_backend::chains::ethereum::EthereumWallet;
+use wallet_backend::chains::solana::SolanaKeypair;
+use wallet_backend::config::Config;
+use wallet_backend::core::{format_units, Chain, SecureSeed};
+use wallet_backend::services::cross_chain_service::{
+    CrossChainServiceError, RouteAggregator, RouteQuote, RouteRequest, RouteState, RouteStatus,
+    RouteStep,
+};
+use wallet_backend::services::price_service::{Price, PriceError, PriceFeed};
+use wallet_backend::storage::models::CrossChainSwapRow;
+use wallet_backend::services::wallet_service;
+use wallet_backend::{create_app, AppState};
+
+pub const PASSWORD: &str = "correct horse battery staple";
+const CSRF_TOKEN: &str = "test-csrf-token";
+
+/// In-memory chain: a single native balance, fixed fee, recorded sends
+pub struct MockChainClient {
+    symbol: &'static str,
+    decimals: u32,
+    balance: Mutex<u128>,
+    fee: u128,
+    /// Known tokens and the balance every address holds of them
+    tokens: Mutex<HashMap<String, (TokenMetadata, u128)>>,
+    /// Address returned for new multi-sigs instead of `mock-multisig-<name>`
+    pub multisig_address: Mutex<Option<String>>,
+    /// Published names by address, and how many lookups were made
+    pub identities: Mutex<HashMap<String, Identity>>,
+    pub identity_lookups: Mutex<u32>,
+    /// Taken names and the address each points at
+    pub names: Mutex<HashMap<String, String>>,
+    /// Holders by NFT mint or contract; lookups of others fail
+    pub nft_holders: Mutex<HashMap<String, NftHolder>>,
+    /// Published metadata by NFT mint or contract; lookups of others fail
+    pub nft_metadata: Mutex<HashMap<String, NftMetadata>>,
+    /// Landed transactions by hash; others are still pending
+    pub confirmations: Mutex<HashMap<String, ConfirmedEffects>>,
+    /// Current block height
+    pub height: Mutex<u64>,
+    /// Addresses with on-chain history
+    pub active: Mutex<HashSet<String>>,
+    /// Status sends are reported with
+    pub send_status: Mutex<&'static str>,
+    pub sent: Mutex<Vec<Transfer>>,
+    /// RPC error the next broadcast is refused with
+    pub send_error: Mutex<Option<String>>,
+    /// RPC error balance reads fail with while set
+    pub balance_error: Mutex<Option<String>>,
+    /// How long balance reads take while set
+    pub balance_delay: Mutex<Option<Duration>>,
+    /// Signed transactions broadcast with `broadcast_raw`
+    pub rebroadcast: Mutex<Vec<String>>,
+    /// Addresses and amounts funded with `request_airdrop`
+    pub airdrops: Mutex<Vec<(String, u64)>>,
+    /// Stake pools by address; deposits mint their token into `tokens`,
+    /// and balances list it
+    pub stake_pools: Mutex<HashMap<String, StakePoolState>>,
+    /// L2 networks and amounts deposited with `bridge_deposit`
+    pub bridge_deposits: Mutex<Vec<(String, u128)>>,
+    /// Source transactions of cross-chain routes
+    pub route_transactions: Mutex<Vec<RouteTransaction>>,
+    /// On-chain history, newest first, returned for every address
+    pub history: Mutex<Vec<HistoryEntry>>,
+}
+
+impl MockChainClient {
+    pub fn new(symbol: &'static str, decimals: u32, balance: u128, fee: u128) -> Self {
+        Self {
+            symbol,
+            decimals,
+            balance: Mutex::new(balance),
+            fee,
+            tokens: Mutex::new(HashMap::new()),
+            multisig_address: Mutex::new(None),
+            identities: Mutex::new(HashMap::new()),
+            identity_lookups: Mutex::new(0),
+            names: Mutex::new(HashMap::new()),
+            nft_holders: Mutex::new(HashMap::new()),
+            nft_metadata: Mutex::new(HashMap::new()),
+            confirmations: Mutex::new(HashMap::new()),
+            height: Mutex::new(1_000),
+            active: Mutex::new(HashSet::new()),
+            send_status: Mutex::new("confirmed"),
+            sent: Mutex::new(Vec::new()),
+            send_error: Mutex::new(None),
+            balance_error: Mutex::new(None),
+            balance_delay: Mutex::new(None),
+            rebroadcast: Mutex::new(Vec::new()),
+            airdrops: Mutex::new(Vec::new()),
+            stake_pools: Mutex::new(HashMap::new()),
+            bridge_deposits: Mutex::new(Vec::new()),
+            route_transactions: Mutex::new(Vec::new()),
+            history: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn add_token(&self, address: &str, symbol: Option<&str>, decimals: u8, balance: u128) {
+        let metadata = TokenMetadata {
+            decimals,
+            symbol: symbol.map(str::to_string),
+            name: None,
+        };
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(address.to_string(), (metadata, balance));
+    }
+
+    /// Replace the native balance, in base units
+    pub fn set_balance(&self, balance: u128) {
+        *self.balance.lock().unwrap() = balance;
+    }
+
+    fn address_of(&self, seed: &SecureSeed, derivation_path: &str) -> String {
+        match self.symbol {
+            "SOL" => SolanaKeypair::derive_path(seed, derivation_path)
+                .unwrap()
+                .pubkey()
+                .to_string(),
+            _ => EthereumWallet::derive_path(seed, derivation_path)
+                .unwrap()
+                .address_string(),
+        }
+    }
+
+    fn to_base_units(&self, amount: &str) -> Result<u128, ChainClientError> {
+        let amount: f64 = amount
+            .parse()
+            .map_err(|_| ChainClientError::InvalidAmount(amount.to_string()))?;
+        Ok((amount * 10f64.powi(self.decimals as i32)) as u128)
+    }
+
+    fn to_display(&self, base_units: u128) -> String {
+        format_units(base_units, self.decimals)
+    }
+}
+
+#[async_trait]
+impl ChainClient for MockChainClient {
+    async fn balance(&self, _address: &str) -> Result<ChainBalance, ChainClientError> {
+        let delay = *self.balance_delay.lock().unwrap();
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        if let Some(error) = self.balance_error.lock().unwrap().clone() {
+            return Err(ChainClientError::Rpc(error));
+        }
+        let balance = *self.balance.lock().unwrap();
+        Ok(ChainBalance {
+            native_balance: self.to_display(balance),
+            native_balance_raw: balance.to_string(),
+            native_symbol: self.symbol.to_string(),
+            // Only stake pool tokens are listed; others are read one at a time
+            tokens: self
+                .tokens
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(address, (_, balance))| {
+                    *balance > 0
+                        && self
+                            .stake_pools
+                            .lock()
+                            .unwrap()
+                            .values()
+                            .any(|pool| &pool.pool_mint == *address)
+                })
+                .map(|(address, (metadata, balance))| ChainTokenBalance {
+                    address: address.clone(),
+                    symbol: metadata.symbol.clone(),
+                    name: metadata.name.clone(),
+                    balance: balance.to_string(),
+                    decimals: metadata.decimals,
+                    ui_amount: *balance as f64 / 10f64.powi(metadata.decimals as i32),
+                    extensions: None,
+                })
+                .collect(),
+        })
+    }
+
+    async fn token_balance(
+        &self,
+        _address: &str,
+        token: &str,
+    ) -> Result<ChainTokenBalance, ChainClientError> {
+        let tokens = self.tokens.lock().unwrap();
+        let (metadata, balance) = tokens
+            .get(token)
+            .ok_or_else(|| ChainClientError::InvalidAddress(token.to_string()))?;
+        Ok(ChainTokenBalance {
+            address: token.to_string(),
+            symbol: metadata.symbol.clone(),
+            name: metadata.name.clone(),
+            balance: balance.to_string(),
+            decimals: metadata.decimals,
+            ui_amount: *balance as f64 / 10f64.powi(metadata.decimals as i32),
+            extensions: None,
+        })
+    }
+
+    async fn token_metadata(&self, token: &str) -> Result<TokenMetadata, ChainClientError> {
+        self.tokens
+            .lock()
+            .unwrap()
+            .get(token)
+            .map(|(metadata, _)| metadata.clone())
+            .ok_or_else(|| ChainClientError::InvalidAddress(token.to_string()))
+    }
+
+    async fn max_send(
+        &self,
+        _address: &str,
+        _token: Option<&str>,
+    ) -> Result<MaxSend, ChainClientError> {
+        let balance = *self.balance.lock().unwrap();
+        let max = balance.saturating_sub(self.fee);
+        Ok(MaxSend {
+            max_amount: max.to_string(),
+            max_ui_amount: self.to_display(max),
+            fee: self.fee.to_string(),
+            fee_covered: balance >= self.fee,
+        })
+    }
+
+    async fn send(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        transfer: Transfer,
+    ) -> Result<SentTransfer, ChainClientError> {
+        let from = self.address_of(seed, derivation_path);
+        let value = self.to_base_units(&transfer.amount)?;
+        let mut balance = self.balance.lock().unwrap();
+        let required = value + self.fee;
+        if required > *balance {
+            return Err(ChainClientError::InsufficientBalance {
+                required,
+                available: *balance,
+            });
+        }
+        let expected = TxEffects {
+            changes: vec![
+                BalanceChange {
+                    address: from,
+                    token: None,
+                    delta: format!("-{}", required),
+                },
+                BalanceChange {
+                    address: transfer.to.clone(),
+                    token: None,
+                    delta: value.to_string(),
+                },
+            ],
+            fee: self.fee.to_string(),
+        };
+        if transfer.dry_run {
+            return Ok(SentTransfer {
+                tx_hash: String::new(),
+                status: "simulated".to_string(),
+                amount: transfer.amount,
+                token_decimals: None,
+                expected: Some(expected),
+                broadcast: None,
+            });
+        }
+        if let Some(error) = self.send_error.lock().unwrap().take() {
+            return Err(ChainClientError::Rpc(error));
+        }
+        *balance -= required;
+
+        // Ethereum nonces count sends, and a replacement reuses its original's
+        let height = *self.height.lock().unwrap();
+        let mut sent = self.sent.lock().unwrap();
+        let broadcast = match (self.symbol, transfer.replaces) {
+            ("SOL", _) => Broadcast {
+                height,
+                last_valid_height: Some(height + 150),
+                ..Default::default()
+            },
+            (_, replaces) => Broadcast {
+                height,
+                nonce: Some(replaces.map_or(sent.len() as u64, |r| r.nonce)),
+                gas_price: Some(replaces.map_or(self.fee, |r| r.gas_price * 2).to_string()),
+                private: transfer.private_rpc.is_some(),
+                raw_tx: Some(format!("0xsigned-{}", sent.len() + 1)),
+                ..Default::default()
+            },
+        };
+        sent.push(transfer.clone());
+        Ok(SentTransfer {
+            tx_hash: format!("mock-tx-{}", sent.len()),
+            status: self.send_status.lock().unwrap().to_string(),
+            amount: transfer.amount.clone(),
+            token_decimals: None,
+            expected: Some(expected),
+            broadcast: Some(broadcast),
+        })
+    }
+
+    async fn broadcast_raw(&self, raw_tx: &str) -> Result<String, ChainClientError> {
+        self.rebroadcast.lock().unwrap().push(raw_tx.to_string());
+        Ok(format!("mock-tx-{}", raw_tx.trim_start_matches("0xsigned-")))
+    }
+
+    /// A real unsigned transaction on a fixed blockhash / nonce 0 at 1 gwei
+    async fn build_unsigned_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        amount: u128,
+    ) -> Result<UnsignedTransfer, ChainClientError> {
+        let available = *self.balance.lock().unwrap();
+        if available < amount + self.fee {
+            return Err(ChainClientError::InsufficientBalance {
+                required: amount + self.fee,
+                available,
+            });
+        }
+        match self.symbol {
+            "SOL" => {
+                let transaction = solana::unsigned_transfer(
+                    from,
+                    to,
+                    amount as u64,
+                    solana_sdk::hash::Hash::default(),
+                )
+                .map_err(|e| ChainClientError::InvalidAddress(e.to_string()))?;
+                Ok(solana::export_transfer(&transaction, self.fee as u64))
+            }
+            _ => {
+                let transaction =
+                    ethereum::unsigned_transfer(from, to, amount, 0, 1_000_000_000, 1)
+                        .map_err(|e| ChainClientError::InvalidAddress(e.to_string()))?;
+                Ok(ethereum::export_transfer(&transaction))
+            }
+        }
+    }
+
+    async fn block_height(&self) -> Result<u64, ChainClientError> {
+        Ok(*self.height.lock().unwrap())
+    }
+
+    async fn has_activity(&self, address: &str) -> Result<bool, ChainClientError> {
+        Ok(self.active.lock().unwrap().contains(address))
+    }
+
+    async fn request_airdrop(&self, address: &str, amount: u64) -> Result<String, ChainClientError> {
+        let mut airdrops = self.airdrops.lock().unwrap();
+        airdrops.push((address.to_string(), amount));
+        Ok(format!("airdrop-{}", airdrops.len()))
+    }
+
+    async fn transaction_effects(
+        &self,
+        tx_hash: &str,
+        _addresses: &[String],
+    ) -> Result<Option<ConfirmedEffects>, ChainClientError> {
+        Ok(self.confirmations.lock().unwrap().get(tx_hash).cloned())
+    }
+
+    /// Sent transfers carrying the reference, newest first
+    async fn find_by_reference(
+        &self,
+        reference: &str,
+        limit: usize,
+    ) -> Result<Vec<ReferencedTransaction>, ChainClientError> {
+        let sent = self.sent.lock().unwrap();
+        Ok(sent
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, t)| t.references.iter().any(|r| r == reference))
+            .take(limit)
+            .map(|(i, t)| ReferencedTransaction {
+                signature: format!("mock-tx-{}", i + 1),
+                status: "confirmed".to_string(),
+                block_time: None,
+                memo: t.memo.clone(),
+            })
+            .collect())
+    }
+
+    async fn history(&self, _address: &str, limit: usize) -> Result<Vec<HistoryEntry>, ChainClientError> {
+        Ok(self.history.lock().unwrap().iter().take(limit).cloned().collect())
+    }
+
+    async fn resolve_identity(&self, address: &str) -> Result<Option<Identity>, ChainClientError> {
+        *self.identity_lookups.lock().unwrap() += 1;
+        // Lookups don't depend on the address's case
+        let identities = self.identities.lock().unwrap();
+        Ok(identities
+            .iter()
+            .find(|(a, _)| a.eq_ignore_ascii_case(address))
+            .map(|(_, identity)| identity.clone()))
+    }
+
+    async fn name_quote(&self, name: &str, years: u32) -> Result<NameQuote, ChainClientError> {
+        let address = self.names.lock().unwrap().get(name).cloned();
+        Ok(NameQuote {
+            name: name.to_string(),
+            available: address.is_none(),
+            address,
+            price: "0.01".to_string(),
+            price_unit: self.symbol.to_string(),
+            years: Some(years),
+            renewal_price: Some("0.01".to_string()),
+            expires_at: None,
+        })
+    }
+
+    async fn register_name(
+        &self,
+        seed: &SecureSeed,
+        derivation_path: &str,
+        name: &str,
+        _years: u32,
+        commitment: Option<&str>,
+    ) -> Result<NameRegistration, ChainClientError> {
+        // Mirrors ENS commit/reveal on the Ethereum mock; the commitment is
+        // usable straight away
+        if self.symbol == "ETH" && commitment.is_none() {
+            return Ok(NameRegistration::Committed {
+                tx_hash: format!("mock-commit-{}", name),
+                secret: format!("mock-secret-{}", name),
+                ready_at: chrono::Utc::now().timestamp(),
+            });
+        }
+        if self.names.lock().unwrap().contains_key(name) {
+            return Err(ChainClientError::TransactionFailed(format!("{} is taken", name)));
+        }
+        let owner = self.address_of(seed, derivation_path);
+        self.names.lock().unwrap().insert(name.to_string(), owner);
+        Ok(NameRegistration::Registered {
+            tx_hash: format!("mock-register-{}", name),
+            expires_at: None,
+        })
+    }
+
+    async fn set_name_target(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        name: &str,
+        target: &str,
+    ) -> Result<String, ChainClientError> {
+        self.names
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), target.to_string());
+        Ok(format!("mock-target-{}", name))
+    }
+
+    async fn nft_holder(
+        &self,
+        token_address: &str,
+        _token_id: &str,
+    ) -> Result<NftHolder, ChainClientError> {
+        self.nft_holders
+            .lock()
+            .unwrap()
+            .get(token_address)
+            .cloned()
+            .ok_or_else(|| ChainClientError::Rpc("mock holder unknown".to_string()))
+    }
+
+    async fn nft_metadata(
+        &self,
+        token_address: &str,
+        _token_id: &str,
+    ) -> Result<NftMetadata, ChainClientError> {
+        self.nft_metadata
+            .lock()
+            .unwrap()
+            .get(token_address)
+            .cloned()
+            .ok_or_else(|| ChainClientError::Rpc("mock metadata unknown".to_string()))
+    }
+
+    async fn stake_pool(&self, pool: &str) -> Result<StakePoolState, ChainClientError> {
+        self.stake_pools
+            .lock()
+            .unwrap()
+            .get(pool)
+            .cloned()
+            .ok_or_else(|| ChainClientError::InvalidAddress(pool.to_string()))
+    }
+
+    async fn stake_pool_deposit(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        pool: &str,
+        lamports: u64,
+    ) -> Result<String, ChainClientError> {
+        let mut pools = self.stake_pools.lock().unwrap();
+        let pool = pools
+            .get_mut(pool)
+            .ok_or_else(|| ChainClientError::InvalidAddress(pool.to_string()))?;
+        let mut balance = self.balance.lock().unwrap();
+        let required = lamports as u128 + self.fee;
+        if required > *balance {
+            return Err(ChainClientError::InsufficientBalance {
+                required,
+                available: *balance,
+            });
+        }
+        *balance -= required;
+        let minted = lamports as u128 * pool.pool_token_supply as u128 / pool.total_lamports as u128;
+        pool.total_lamports += lamports;
+        pool.pool_token_supply += minted as u64;
+        let mut tokens = self.tokens.lock().unwrap();
+        let metadata = TokenMetadata {
+            decimals: 9,
+            symbol: None,
+            name: None,
+        };
+        tokens.entry(pool.pool_mint.clone()).or_insert((metadata, 0)).1 += minted;
+        Ok(format!("mock-stake-{}", pool.pool_token_supply))
+    }
+
+    async fn stake_pool_withdraw(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        pool: &str,
+        pool_tokens: u64,
+    ) -> Result<String, ChainClientError> {
+        let mut pools = self.stake_pools.lock().unwrap();
+        let pool = pools
+            .get_mut(pool)
+            .ok_or_else(|| ChainClientError::InvalidAddress(pool.to_string()))?;
+        let mut tokens = self.tokens.lock().unwrap();
+        let held = tokens.get_mut(&pool.pool_mint).map(|(_, balance)| balance);
+        let available = held.as_ref().map_or(0, |balance| **balance);
+        if (pool_tokens as u128) > available {
+            return Err(ChainClientError::InsufficientBalance {
+                required: pool_tokens as u128,
+                available,
+            });
+        }
+        if let Some(held) = held {
+            *held -= pool_tokens as u128;
+        }
+        let lamports = pool_tokens as u128 * pool.total_lamports as u128 / pool.pool_token_supply as u128;
+        pool.total_lamports -= lamports as u64;
+        pool.pool_token_supply -= pool_tokens;
+        *self.balance.lock().unwrap() += lamports;
+        Ok(format!("mock-unstake-{}", pool.pool_token_supply))
+    }
+
+    async fn bridge_deposit(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        network: &str,
+        amount: u128,
+    ) -> Result<BridgeDeposit, ChainClientError> {
+        let mut balance = self.balance.lock().unwrap();
+        let required = amount + self.fee;
+        if required > *balance {
+            return Err(ChainClientError::InsufficientBalance {
+                required,
+                available: *balance,
+            });
+        }
+        *balance -= required;
+        let mut deposits = self.bridge_deposits.lock().unwrap();
+        deposits.push((network.to_string(), amount));
+        Ok(BridgeDeposit {
+            tx_hash: format!("0xbridge{}", deposits.len()),
+            contract: format!("mock-{}-bridge", network),
+            l1_chain_id: 11_155_111,
+        })
+    }
+
+    async fn send_route_transaction(
+        &self,
+        _seed: &SecureSeed,
+        _derivation_path: &str,
+        transaction: &RouteTransaction,
+    ) -> Result<RouteSubmission, ChainClientError> {
+        let (value, approval) = match transaction {
+            RouteTransaction::Ethereum {
+                value, approval, ..
+            } => (*value, approval.is_some()),
+            RouteTransaction::Solana { .. } => (0, false),
+        };
+        let mut balance = self.balance.lock().unwrap();
+        let required = value + self.fee;
+        if required > *balance {
+            return Err(ChainClientError::InsufficientBalance {
+                required,
+                available: *balance,
+            });
+        }
+        *balance -= required;
+        let mut sent = self.route_transactions.lock().unwrap();
+        sent.push(transaction.clone());
+        Ok(RouteSubmission {
+            tx_hash: format!("{}-route-{}", self.symbol.to_lowercase(), sent.len()),
+            approval_tx_hash: approval
+                .then(|| format!("{}-approve-{}", self.symbol.to_lowercase(), sent.len())),
+        })
+    }
+
+    async fn multisig_deployment_cost(
+        &self,
+        _owners: &[String],
+        _threshold: u8,
+    ) -> Result<DeploymentCost, ChainClientError> {
+        Ok(DeploymentCost {
+            rent: self.fee * 100,
+            network_fee: self.fee,
+            gas: None,
+        })
+    }
+
+    async fn create_multisig(
+        &self,
+        _seed: &SecureSeed,
+        name: &str,
+        _threshold: u8,
+        _owners: &[String],
+    ) -> Result<String, ChainClientError> {
+        Ok(self
+            .multisig_address
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| format!("mock-multisig-{}", name)))
+    }
+}
+
+/// Fixed USD quote; `None` simulates the feed being down
+pub struct MockPriceFeed {
+    pub quote: Mutex<Option<(f64, chrono::DateTime<chrono::Utc>)>>,
+    /// Current USD prices by token address
+    pub token_quotes: Mutex<HashMap<String, f64>>,
+    /// Historical USD prices by token (`None` for native coins)
+    pub history: Mutex<HashMap<Option<String>, f64>>,
+    /// Historical lookups made, as (token, date)
+    pub history_lookups: Mutex<Vec<(Option<String>, chrono::NaiveDate)>>,
+}
+
+#[async_trait]
+impl PriceFeed for MockPriceFeed {
+    async fn native_price(&self, _chain: Chain, currency: &str) -> Result<Price, PriceError> {
+        if !currency.eq_ignore_ascii_case("usd") {
+            return Err(PriceError::UnsupportedCurrency(currency.to_string()));
+        }
+        let (rate, updated_at) = self
+            .quote
+            .lock()
+            .unwrap()
+            .ok_or_else(|| PriceError::Unavailable("mock feed down".to_string()))?;
+        Ok(Price {
+            rate,
+            currency: "USD".to_string(),
+            updated_at,
+        })
+    }
+
+    async fn token_price(
+        &self,
+        _chain: Chain,
+        token_address: &str,
+        currency: &str,
+    ) -> Result<Option<f64>, PriceError> {
+        if !currency.eq_ignore_ascii_case("usd") {
+            return Err(PriceError::UnsupportedCurrency(currency.to_string()));
+        }
+        Ok(self.token_quotes.lock().unwrap().get(token_address).copied())
+    }
+
+    async fn historical_price(
+        &self,
+        _chain: Chain,
+        token_address: Option<&str>,
+        currency: &str,
+        date: chrono::NaiveDate,
+    ) -> Result<Option<f64>, PriceError> {
+        if !currency.eq_ignore_ascii_case("usd") {
+            return Err(PriceError::UnsupportedCurrency(currency.to_string()));
+        }
+        let token = token_address.map(str::to_string);
+        self.history_lookups.lock().unwrap().push((token.clone(), date));
+        Ok(self.history.lock().unwrap().get(&token).copied())
+    }
+}
+
+/// Quotes 0.5 SOL or 0.05 ETH for any route, 1% slippage, and reports
+/// swaps by source transaction hash as set by the test, pending until then
+pub struct MockRouteAggregator {
+    pub quotes: Mutex<Vec<RouteRequest>>,
+    pub statuses: Mutex<HashMap<String, RouteStatus>>,
+}
+
+impl MockRouteAggregator {
+    /// Report the swap sent as `tx_hash` as having reached `state`
+    pub fn set_status(
+        &self,
+        tx_hash: &str,
+        state: RouteState,
+        destination_tx_hash: Option<&str>,
+        received_amount: Option<u128>,
+    ) {
+        self.statuses.lock().unwrap().insert(
+            tx_hash.to_string(),
+            RouteStatus {
+                state,
+                message: Some(format!("mock {:?}", state)),
+                destination_tx_hash: destination_tx_hash.map(str::to_string),
+                received_amount,
+            },
+        );
+    }
+}
+
+#[async_trait]
+impl RouteAggregator for MockRouteAggregator {
+    async fn quote(&self, request: &RouteRequest) -> Result<RouteQuote, CrossChainServiceError> {
+        self.quotes.lock().unwrap().push(request.clone());
+        let (to_amount, to_decimals) = match request.to_chain {
+            Chain::Solana => (500_000_000, 9),
+            Chain::Ethereum => (50_000_000_000_000_000, 18),
+        };
+        let transaction = match request.from_chain {
+            Chain::Solana => RouteTransaction::Solana {
+                transaction: "mock-route-transaction".to_string(),
+            },
+            Chain::Ethereum => RouteTransaction::Ethereum {
+                to: "0x1231DEB6f5749EF6cE6943a275A1D3E7486F4EaE".to_string(),
+                data: "0x".to_string(),
+                value: if request.from_token.is_none() { request.from_amount } else { 0 },
+                gas_limit: Some(300_000),
+                approval: request.from_token.as_ref().map(|token| TokenApproval {
+                    token: token.clone(),
+                    spender: "0x1231DEB6f5749EF6cE6943a275A1D3E7486F4EaE".to_string(),
+                    amount: request.from_amount,
+                }),
+            },
+        };
+        Ok(RouteQuote {
+            tool: "mock-bridge".to_string(),
+            steps: vec![RouteStep {
+                kind: "cross".to_string(),
+                tool: "mock-bridge".to_string(),
+            }],
+            to_amount,
+            to_amount_min: to_amount / 100 * 99,
+            to_decimals,
+            estimated_duration_secs: 300,
+            fee_usd: Some(1.25),
+            transaction,
+        })
+    }
+
+    async fn status(
+        &self,
+        swap: &CrossChainSwapRow,
+    ) -> Result<RouteStatus, CrossChainServiceError> {
+        Ok(self
+            .statuses
+            .lock()
+            .unwrap()
+            .get(&swap.source_tx_hash)
+            .cloned()
+            .unwrap_or(RouteStatus {
+                state: RouteState::Pending,
+                message: None,
+                destination_tx_hash: None,
+                received_amount: None,
+            }))
+    }
+}
+
+/// Every request that derives a key runs Argon2 at production cost, so apps
+/// from parallel tests starve each other on a small machine until requests
+/// hit the server timeout. Apps therefore run one test at a time; a test
+/// that spawns several apps shares its thread's turn.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    static TURN: RefCell<Option<(MutexGuard<'static, ()>, usize)>> = const { RefCell::new(None) };
+}
+
+struct SerialTurn;
+
+impl SerialTurn {
+    fn take() -> Self {
+        TURN.with(|turn| {
+            let mut turn = turn.borrow_mut();
+            match turn.as_mut() {
+                Some((_, apps)) => *apps += 1,
+                // A failed test poisons the lock without leaving anything behind
+                None => *turn = Some((SERIAL.lock().unwrap_or_else(|e| e.into_inner()), 1)),
+            }
+        });
+        SerialTurn
+    }
+}
+
+impl Drop for SerialTurn {
+    fn drop(&mut self) {
+        TURN.with(|turn| {
+            let mut turn = turn.borrow_mut();
+            if let Some((_, apps)) = turn.as_mut() {
+                *apps -= 1;
+                if *apps == 0 {
+                    *turn = None;
+                }
+            }
+        });
+    }
+}
+
+pub struct TestApp {
+    pub solana: Arc<MockChainClient>,
+    pub ethereum: Arc<MockChainClient>,
+    pub prices: Arc<MockPriceFeed>,
+    pub routes: Arc<MockRouteAggregator>,
+    /// For driving background jobs directly
+    pub state: Arc<AppState>,
+    router: Router,
+    _turn: SerialTurn,
+}
+
+impl TestApp {
+    /// 2 SOL and 1 ETH available; fees of 5000 lamports and 21000 gwei;
+    /// a fresh price of 100 USD per coin
+    pub async fn spawn() -> Self {
+        Self::spawn_with_env(&[]).await
+    }
+
+    /// Spawn with extra environment variables, provisioning the wallet as
+    /// the server binary does at startup
+    pub async fn spawn_with_env(vars: &[(&str, &str)]) -> Self {
+        Self::spawn_with(vars, |state| state).await
+    }
+
+    /// Spawn over a schema from a newer release, with writes refused
+    pub async fn spawn_in_safe_mode(vars: &[(&str, &str)]) -> Self {
+        Self::spawn_with(vars, |state| state.with_safe_mode(true)).await
+    }
+
+    async fn spawn_with(vars: &[(&str, &str)], configure: impl FnOnce(AppState) -> AppState) -> Self {
+        let turn = SerialTurn::take();
+        let config = Config::from_lookup(|name| match name {
+            "JWT_SECRET" => Some("0123456789abcdef0123456789abcdef".to_string()),
+            "DATABASE_URL" => Some("sqlite::memory:".to_string()),
+            _ => vars
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string()),
+        })
+        .expect("test config");
+
+        // A single connection keeps every query on the same in-memory database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect(&config.database_url)
+            .await
+            .expect("in-memory database");
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("migrations");
+
+        let solana = Arc::new(MockChainClient::new("SOL", 9, 2_000_000_000, 5_000));
+        let ethereum = Arc::new(MockChainClient::new(
+            "ETH",
+            18,
+            1_000_000_000_000_000_000,
+            21_000_000_000_000,
+        ));
+        let chains = ChainClients {
+            solana: solana.clone(),
+            ethereum: ethereum.clone(),
+        };
+
+        let prices = Arc::new(MockPriceFeed {
+            quote: Mutex::new(Some((100.0, chrono::Utc::now()))),
+            token_quotes: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+            history_lookups: Mutex::new(Vec::new()),
+        });
+
+        let routes = Arc::new(MockRouteAggregator {
+            quotes: Mutex::new(Vec::new()),
+            statuses: Mutex::new(HashMap::new()),
+        });
+
+        let state = Arc::new(configure(
+            AppState::new(config, pool, chains, prices.clone())
+                .with_route_aggregator(routes.clone()),
+        ));
+        if let Some(provision) = state.config.provision.clone() {
+            wallet_service::provision_wallet(&state, &provision)
+                .await
+                .expect("provisioning");
+        }
+        #[cfg(debug_assertions)]
+        if state.config.dev_mode {
+            wallet_backend::services::dev_service::seed(&state)
+                .await
+                .expect("demo seed");
+        }
+
+        let router = create_app(state.clone())
+            .expect("router")
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 0))));
+
+        Self {
+            solana,
+            ethereum,
+            prices,
+            routes,
+            state,
+            router,
+            _turn: turn,
+        }
+    }
+
+    /// Send a request; mutating requests carry a matching CSRF header and cookie
+    pub async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        self.request_with_headers(method, uri, &[], token, body).await
+    }
+
+    /// `request` with extra headers, such as a tenant's `X-Api-Key`
+    pub async fn request_with_headers(
+        &self,
+        method: Method,
+        uri: &str,
+        headers: &[(&str, &str)],
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        self.request_from(([127, 0, 0, 1], 0).into(), method, uri, headers, token, body)
+            .await
+    }
+
+    /// `request_with_headers` from a client at `addr` rather than localhost
+    pub async fn request_from(
+        &self,
+        addr: SocketAddr,
+        method: Method,
+        uri: &str,
+        headers: &[(&str, &str)],
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut builder = Request::builder().method(method.clone()).uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        if method != Method::GET {
+            builder = builder
+                .header("X-CSRF-Token", CSRF_TOKEN)
+                .header(header::COOKIE, format!("csrf_token={}", CSRF_TOKEN));
+        }
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let mut request = match body {
+            Some(body) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        }
+        .unwrap();
+        request.extensions_mut().insert(ConnectInfo(addr));
+
+        let response = self.send(request).await;
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+        (status, body)
+    }
+
+    /// Mint a signing token for the session `token` belongs to
+    pub async fn signing_token(&self, token: &str) -> String {
+        let (status, body) = self
+            .request(
+                Method::POST,
+                "/api/v2/auth/signing-token",
+                Some(token),
+                Some(json!({ "password": PASSWORD })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        body["signing_token"].as_str().unwrap().to_string()
+    }
+
+    /// `request` with a fresh signing token, for sends, swaps and multisig
+    /// executions
+    pub async fn request_signed(
+        &self,
+        method: Method,
+        uri: &str,
+        token: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let signing_token = self.signing_token(token).await;
+        self.request_with_headers(
+            method,
+            uri,
+            &[("X-Signing-Token", signing_token.as_str())],
+            Some(token),
+            body,
+        )
+        .await
+    }
+
+    /// Send a prebuilt request, for tests that need headers
+    pub async fn send(&self, request: Request<Body>) -> Response<Body> {
+        self.router.clone().oneshot(request).await.unwrap()
+    }
+
+    /// Unlock with `body`, redeeming a fresh challenge nonce
+    pub async fn unlock(&self, mut body: Value) -> (StatusCode, Value) {
+        let (status, challenge) = self
+            .request(Method::POST, "/api/v2/auth/unlock/challenge", None, None)
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        body["nonce"] = challenge["nonce"].clone();
+        self.request(Method::POST, "/api/v2/auth/unlock", None, Some(body)).await
+    }
+
+    /// Register and log in a user, returning the access token
+    pub async fn login(&self) -> String {
+        let credentials = json!({ "email": "alice@example.com", "password": PASSWORD });
+        let (status, _) = self
+            .request(
+                Method::POST,
+                "/api/v2/users/register",
+                None,
+                Some(credentials.clone()),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = self
+            .request(Method::POST, "/api/v2/users/login", None, Some(credentials))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        body["access_token"].as_str().unwrap().to_string()
+    }
+
+    /// Create the wallet (left unlocked) and an account on `chain`; returns its address
+    pub async fn create_wallet_with_account(&self, chain: &str) -> String {
+        let (status, _) = self
+            .request(
+                Method::POST,
+                "/api/v2/wallet/create",
+                None,
+                Some(json!({ "password": PASSWORD })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, account) = self
+            .request(
+                Method::POST,
+                "/api/v2/accounts",
+                None,
+                Some(json!({ "chain": chain })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        account["address"].as_str().unwrap().to_string()
+    }
+}